@@ -23,6 +23,22 @@
 //! }
 //! render();
 //! ```
+//!
+//! ## Features
+//!
+//! Optional parts of the API can be left out of size-constrained builds
+//! (for example mobile PWAs on slow networks):
+//!
+//! - `holographic` (default): Looking Glass quilt rendering
+//! - `fusion` (default): touch + voice fusion; without it `processVoice`
+//!   still feeds voice events to the canvas state but never returns intents
+//! - `charts` (default): `createChartElement` / `createChartWithData`; charts
+//!   in scenes are still drawn as labelled boxes without it
+//! - `wee_alloc`: use the smaller `wee_alloc` global allocator on wasm32
+//!
+//! ```bash
+//! wasm-pack build --target web canvas-app -- --no-default-features
+//! ```
 
 #![forbid(unsafe_code)]
 #![deny(missing_docs)]
@@ -30,15 +46,54 @@
 #![deny(clippy::pedantic)]
 #![allow(clippy::module_name_repetitions)]
 
-use std::{cell::RefCell, collections::HashMap, rc::Rc};
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    rc::Rc,
+};
 
+use canvas_core::agenda::{AgendaItem, AgendaLayout, AgendaStatus};
+use canvas_core::annotation::{
+    self, caption_tab, MeasurementLayout, ANNOTATION_FONT_SIZE, REGION_TINT,
+};
+use canvas_core::barcode::BarcodeLayout;
+use canvas_core::calendar::{events_on, CalendarLayout, WEEKDAYS};
+use canvas_core::dashboard::{
+    gauge_fraction, threshold_color, Dial, GaugeLayout, GaugeShape, KpiDelta, KpiLayout,
+    GAUGE_TRACK_COLOR,
+};
+use canvas_core::floor_plan::{
+    FloorMarker, FloorPlanLayout, MarkerShape, OccupancySummary, PlanPath,
+};
+use canvas_core::highlight::{
+    self, HighlightSpan, CODE_BACKGROUND, CODE_FONT_SIZE, CODE_LINE_HEIGHT, CODE_PADDING,
+};
+use canvas_core::ink;
+use canvas_core::log_view::{self, LOG_BACKGROUND, LOG_FOREGROUND};
+use canvas_core::poll::{PollLayout, PollResults};
+use canvas_core::randomizer::{RandomOutcome, RandomSource, RandomizerLayout, WHEEL_COLORS};
+use canvas_core::shader::SHADER_PLACEHOLDER;
+use canvas_core::shape::ShapeOutline;
+use canvas_core::sparkline::{
+    sparkline_area, sparkline_bars, sparkline_points, SparklineKind, SPARKLINE_DOT_RADIUS,
+};
+use canvas_core::tree::{TreeDirection, TreeLayout, TreeNode, DEFAULT_NODE_COLOR};
 use canvas_core::{
-    CanvasState, Element, ElementId, ElementKind, FusionConfig, FusionResult, InputEvent,
-    InputFusion, Scene, SceneDocument, TouchEvent, TouchPhase, TouchPoint, Transform, VoiceEvent,
+    match_elements, AgentFollow, CameraCommand, CameraFrame, CanvasState, Color, Element,
+    ElementDocument, ElementId, ElementKind, Fill, FitOptions, Gesture, GradientStop, IdleConfig,
+    IdleTracker, InputEvent, InteractionRouter, MeasurementKind, PatchOp, PlaybackState,
+    PresenterFollow, Scene, SceneBounds, SceneDocument, ScenePage, ScenePatch, SelectionMode,
+    Spotlight, Theme, ThemeColor, TouchEvent, TouchPhase, TouchPoint, Transform, TransitionConfig,
+    TransitionEffect, ViewState, VoiceEvent,
 };
+#[cfg(feature = "fusion")]
+use canvas_core::{FusionConfig, FusionResult, InputFusion, DISAMBIGUATION_RADIUS};
 use canvas_renderer::{
-    BackendType, Camera, HolographicConfig, HolographicRenderer, RenderBackend, RenderResult,
-    Renderer, RendererConfig, Vec3,
+    BackendType, FrameStats, MathLayout, RenderBackend, RenderResult, Renderer, RendererConfig,
+};
+#[cfg(feature = "holographic")]
+use canvas_renderer::{
+    Camera, HolographicConfig, HolographicRenderer, OrbitCamera, OrbitLimits, Vec3,
 };
 
 // Chart rendering is not available in WASM - always use placeholder
@@ -48,6 +103,10 @@ use canvas_renderer::{
 use wasm_bindgen::prelude::*;
 use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement, ImageData};
 
+#[cfg(all(feature = "wee_alloc", target_arch = "wasm32"))]
+#[global_allocator]
+static ALLOC: wee_alloc::WeeAlloc = wee_alloc::WeeAlloc::INIT;
+
 /// Initialize the WASM module.
 #[wasm_bindgen(start)]
 pub fn init_wasm() {
@@ -94,6 +153,23 @@ struct DomRendererState {
     height: u32,
     background_color: String,
     video_frames: HashMap<String, VideoFrame>,
+    /// Offscreen canvases for compositing translucent groups, one per nesting depth.
+    layers: Vec<(HtmlCanvasElement, CanvasRenderingContext2d)>,
+    /// Element counts from the most recent frame.
+    last_frame: FrameStats,
+    /// Iframe overlays for `Embed` elements, keyed by element.
+    embeds: HashMap<ElementId, web_sys::Element>,
+    /// Theme of the scene being drawn, for resolving element colors.
+    theme: Theme,
+    /// Server clock minus the local clock in milliseconds, for agenda
+    /// timers.
+    clock_offset_ms: i64,
+    /// Whether screen-anchored elements are being drawn, so the camera is
+    /// left off.
+    screen_pass: bool,
+    /// Numbered badges on the candidates of a pending voice
+    /// disambiguation, with their canvas bounds.
+    badges: Vec<(usize, [f32; 4])>,
 }
 
 impl DomRendererState {
@@ -107,9 +183,21 @@ impl DomRendererState {
             height,
             background_color: "#ffffff".to_string(),
             video_frames: HashMap::new(),
+            layers: Vec::new(),
+            last_frame: FrameStats::default(),
+            embeds: HashMap::new(),
+            theme: Theme::default(),
+            clock_offset_ms: 0,
+            screen_pass: false,
+            badges: Vec::new(),
         }
     }
 
+    /// The server's clock, estimated from the local one.
+    fn server_now_ms(&self) -> u64 {
+        now_ms().saturating_add_signed(self.clock_offset_ms)
+    }
+
     fn resize(&mut self, width: u32, height: u32) {
         self.canvas.set_width(width);
         self.canvas.set_height(height);
@@ -123,6 +211,9 @@ impl DomRendererState {
 
     fn clear_dynamic_content(&mut self) {
         self.video_frames.clear();
+        for (_, frame) in self.embeds.drain() {
+            frame.remove();
+        }
     }
 }
 
@@ -132,34 +223,486 @@ struct DomCanvasBackend {
 
 impl DomRendererState {
     fn render_scene(&mut self, scene: &Scene) {
+        self.theme.clone_from(scene.theme());
         self.ctx.set_fill_style_str(&self.background_color);
         self.ctx
             .fill_rect(0.0, 0.0, f64::from(self.width), f64::from(self.height));
 
-        let mut elements: Vec<_> = scene.elements().cloned().collect();
+        // Children of containers are drawn with their container, not at top
+        // level. Offscreen leaves are culled; containers are always visited
+        // since their children may extend beyond them.
+        let nested: HashSet<ElementId> = scene
+            .elements()
+            .flat_map(|e| e.kind.children().iter().copied())
+            .collect();
+        let visible: HashSet<ElementId> = scene.visible_elements().map(|e| e.id).collect();
+        let (mut roots, culled): (Vec<_>, Vec<_>) = scene
+            .elements()
+            .filter(|e| !nested.contains(&e.id) && e.visible)
+            .partition(|e| !e.kind.children().is_empty() || visible.contains(&e.id));
+        // Removed elements are drawn in place while they fade out
+        roots.extend(scene.exiting_elements());
+        roots.sort_by_key(|e| e.transform.z_index);
+        let (pinned, roots): (Vec<_>, Vec<_>) = roots.into_iter().partition(|e| e.screen_anchored);
+        self.last_frame = FrameStats::new(scene.element_count(), culled.len());
+        let clips = scene.clip_rects();
+
+        self.ctx.save();
+        self.apply_camera(scene);
+        for element in roots {
+            self.render_tree(scene, element, &clips, 0);
+        }
+        self.ctx.restore();
+        self.sync_embeds(scene, &visible);
+
+        if let Some(spotlight) = scene.spotlight() {
+            if !spotlight.is_expired(now_ms()) {
+                self.render_spotlight(scene, spotlight, &clips);
+            }
+        }
+
+        self.render_badges(scene);
+
+        // Screen-anchored elements sit above everything, in viewport pixels,
+        // with the branding on top
+        #[allow(clippy::cast_precision_loss)] // Canvas dimensions fit in f32
+        let branding = scene
+            .branding()
+            .map(|b| b.overlay(self.width as f32, self.height as f32))
+            .unwrap_or_default();
+        if !pinned.is_empty() || !branding.is_empty() {
+            self.screen_pass = true;
+            self.ctx.save();
+            self.apply_camera(scene);
+            for element in pinned {
+                self.render_tree(scene, element, &clips, 0);
+            }
+            for element in &branding {
+                self.render_tree(scene, element, &clips, 0);
+            }
+            self.ctx.restore();
+            self.screen_pass = false;
+        }
+    }
+
+    /// Keep one iframe per `Embed` element positioned over the canvas, so it
+    /// pans, zooms, and rotates with the placeholder drawn underneath.
+    ///
+    /// Iframes live in the canvas's parent element; offscreen embeds are
+    /// hidden rather than unloaded, and removed elements drop their iframe.
+    fn sync_embeds(&mut self, scene: &Scene, visible: &HashSet<ElementId>) {
+        self.embeds.retain(|id, frame| {
+            let keep = scene
+                .get_element(*id)
+                .and_then(|e| e.kind.embed_src())
+                .is_some();
+            if !keep {
+                frame.remove();
+            }
+            keep
+        });
+
+        let Some(parent) = self.canvas.parent_element() else {
+            return;
+        };
+        // The canvas backing store may be larger than its CSS box (high-DPI).
+        let css_scale = if self.width == 0 {
+            1.0
+        } else {
+            f64::from(self.canvas.client_width()) / f64::from(self.width)
+        };
+
+        for element in scene.elements() {
+            let Some(sandbox) = element.kind.embed_sandbox() else {
+                continue;
+            };
+            let Some(src) = element.kind.embed_src() else {
+                continue;
+            };
+            let frame = if let Some(frame) = self.embeds.get(&element.id) {
+                frame.clone()
+            } else {
+                let Some(frame) = Self::create_embed(&parent) else {
+                    continue;
+                };
+                self.embeds.insert(element.id, frame.clone());
+                frame
+            };
+
+            // Tokens that would let the page out of its frame are dropped
+            if frame.get_attribute("sandbox").as_deref() != Some(sandbox.as_str()) {
+                let _ = frame.set_attribute("sandbox", &sandbox);
+            }
+            if frame.get_attribute("src").as_deref() != Some(src) {
+                let _ = frame.set_attribute("src", src);
+            }
+
+            // Screen-anchored embeds ignore the camera
+            let (scene_zoom, pan_x, pan_y) = if element.screen_anchored {
+                (1.0, 0.0, 0.0)
+            } else {
+                (scene.zoom, scene.pan_x, scene.pan_y)
+            };
+            let zoom = f64::from(scene_zoom) * css_scale;
+            let t = &element.transform;
+            let x = f64::from(self.canvas.offset_left())
+                + (f64::from(pan_x) + f64::from(t.x) * f64::from(scene_zoom)) * css_scale;
+            let y = f64::from(self.canvas.offset_top())
+                + (f64::from(pan_y) + f64::from(t.y) * f64::from(scene_zoom)) * css_scale;
+            let (half_w, half_h) = (t.width / 2.0, t.height / 2.0);
+            let display = if visible.contains(&element.id) {
+                "block"
+            } else {
+                "none"
+            };
+            let style = format!(
+                "position:absolute;left:0;top:0;border:0;transform-origin:0 0;\
+                 width:{}px;height:{}px;opacity:{};display:{display};\
+                 transform:translate({x}px,{y}px) scale({zoom}) \
+                 translate({half_w}px,{half_h}px) rotate({}rad) \
+                 translate({}px,{}px)",
+                t.width,
+                t.height,
+                element.effective_opacity(),
+                t.rotation,
+                -half_w,
+                -half_h,
+            );
+            let _ = frame.set_attribute("style", &style);
+        }
+    }
+
+    /// Create an empty iframe inside the canvas's parent element.
+    fn create_embed(parent: &web_sys::Element) -> Option<web_sys::Element> {
+        let frame = web_sys::window()?
+            .document()?
+            .create_element("iframe")
+            .ok()?;
+        let _ = frame.set_attribute("referrerpolicy", "no-referrer");
+        parent.append_child(&frame).ok()?;
+        Some(frame)
+    }
+
+    /// Number the candidates of a pending disambiguation with a badge on
+    /// each one's top-left corner, at a fixed screen size.
+    fn render_badges(&self, scene: &Scene) {
+        const RADIUS: f64 = 12.0;
+        if self.badges.is_empty() {
+            return;
+        }
+        self.ctx.save();
+        let _ = self.ctx.set_transform(1.0, 0.0, 0.0, 1.0, 0.0, 0.0);
+        self.ctx.set_line_width(2.0);
+        self.ctx.set_stroke_style_str("#0066ff");
+        self.ctx.set_font("bold 14px sans-serif");
+        self.ctx.set_text_align("center");
+        self.ctx.set_text_baseline("middle");
+        for (number, [x, y, ..]) in &self.badges {
+            let cx = f64::from(scene.pan_x + x * scene.zoom);
+            let cy = f64::from(scene.pan_y + y * scene.zoom);
+            self.ctx.begin_path();
+            let _ = self.ctx.arc(cx, cy, RADIUS, 0.0, std::f64::consts::TAU);
+            self.ctx.set_fill_style_str("#ffffff");
+            self.ctx.fill();
+            self.ctx.stroke();
+            self.ctx.set_fill_style_str("#000000");
+            let _ = self.ctx.fill_text(&number.to_string(), cx, cy);
+        }
+        self.ctx.restore();
+    }
+
+    /// Dim the whole canvas, then redraw the spotlighted elements on top.
+    fn render_spotlight(
+        &mut self,
+        scene: &Scene,
+        spotlight: &Spotlight,
+        clips: &HashMap<ElementId, [f32; 4]>,
+    ) {
+        self.ctx.save();
+        self.ctx.set_global_alpha(f64::from(spotlight.dim_opacity));
+        self.ctx.set_fill_style_str("#000000");
+        self.ctx
+            .fill_rect(0.0, 0.0, f64::from(self.width), f64::from(self.height));
+        self.ctx.restore();
+
+        let mut elements: Vec<_> = spotlight
+            .element_ids
+            .iter()
+            .filter_map(|id| scene.get_element(*id))
+            .filter(|e| !e.screen_anchored && scene.is_shown(e))
+            .collect();
         elements.sort_by_key(|e| e.transform.z_index);
 
-        for element in &elements {
+        self.ctx.save();
+        self.apply_camera(scene);
+        for element in elements {
+            self.render_tree(scene, element, clips, 0);
+        }
+        self.ctx.restore();
+    }
+
+    /// Map canvas coordinates to screen as `canvas * zoom + pan`, or leave
+    /// them as-is while drawing screen-anchored elements.
+    fn apply_camera(&self, scene: &Scene) {
+        if self.screen_pass {
+            let _ = self.ctx.set_transform(1.0, 0.0, 0.0, 1.0, 0.0, 0.0);
+            return;
+        }
+        let _ = self.ctx.set_transform(
+            f64::from(scene.zoom),
+            0.0,
+            0.0,
+            f64::from(scene.zoom),
+            f64::from(scene.pan_x),
+            f64::from(scene.pan_y),
+        );
+    }
+
+    /// Render an element followed by its children (for containers).
+    fn render_tree(
+        &mut self,
+        scene: &Scene,
+        element: &Element,
+        clips: &HashMap<ElementId, [f32; 4]>,
+        depth: usize,
+    ) {
+        // Guard against cyclic children lists
+        const MAX_DEPTH: usize = 32;
+        if depth > MAX_DEPTH {
+            return;
+        }
+
+        if !element.visible {
+            return;
+        }
+        // Element opacity fades the content and everything nested in it, as
+        // does an enter or exit transition, which also scales it
+        let transition = scene.transition_frame(element.id);
+        let opacity = element.opacity * transition.map_or(1.0, |frame| frame.opacity);
+        let faded = opacity < 1.0 || transition.is_some();
+        if faded {
+            self.ctx.save();
+            self.ctx
+                .set_global_alpha(self.ctx.global_alpha() * f64::from(opacity.max(0.0)));
+        }
+        if let Some(frame) = transition {
+            let t = &element.transform;
+            let (cx, cy) = (
+                f64::from(t.x + t.width / 2.0),
+                f64::from(t.y + t.height / 2.0),
+            );
+            let _ = self.ctx.translate(cx, cy);
+            let _ = self
+                .ctx
+                .scale(f64::from(frame.scale), f64::from(frame.scale));
+            let _ = self.ctx.translate(-cx, -cy);
+        }
+
+        if let Some(opacity) = element.kind.group_opacity() {
+            if self.render_group_layer(scene, element, opacity, clips, depth) {
+                if faded {
+                    self.ctx.restore();
+                }
+                return;
+            }
+        }
+
+        if let Some(tail) = scene.callout_tail(element.id) {
+            self.draw_callout_tail(element, &tail);
+        }
+        self.render_clipped(element, clips);
+        self.render_children(scene, element, clips, depth);
+        if faded {
+            self.ctx.restore();
+        }
+    }
+
+    fn render_children(
+        &mut self,
+        scene: &Scene,
+        element: &Element,
+        clips: &HashMap<ElementId, [f32; 4]>,
+        depth: usize,
+    ) {
+        let mut children: Vec<_> = element
+            .kind
+            .children()
+            .iter()
+            .filter_map(|id| scene.get_element(*id))
+            .collect();
+        children.sort_by_key(|e| e.transform.z_index);
+        for child in children {
+            self.render_tree(scene, child, clips, depth + 1);
+        }
+    }
+
+    /// Render a single element, clipped to its containers' bounds if needed.
+    fn render_clipped(&mut self, element: &Element, clips: &HashMap<ElementId, [f32; 4]>) {
+        let Some(rect) = clips.get(&element.id) else {
             self.render_element(element);
+            return;
+        };
+
+        self.ctx.save();
+        self.ctx.begin_path();
+        self.ctx.rect(
+            f64::from(rect[0]),
+            f64::from(rect[1]),
+            f64::from(rect[2]),
+            f64::from(rect[3]),
+        );
+        self.ctx.clip();
+        self.render_element(element);
+        self.ctx.restore();
+    }
+
+    /// Draw a translucent group and its children to an offscreen layer, then
+    /// composite the layer at the group opacity.
+    ///
+    /// Returns `false` if no layer canvas could be created, so the caller can
+    /// fall back to drawing the group directly.
+    fn render_group_layer(
+        &mut self,
+        scene: &Scene,
+        group: &Element,
+        opacity: f32,
+        clips: &HashMap<ElementId, [f32; 4]>,
+        depth: usize,
+    ) -> bool {
+        let Some((layer_canvas, layer_ctx)) = self.layer(depth) else {
+            return false;
+        };
+
+        let _ = layer_ctx.set_transform(1.0, 0.0, 0.0, 1.0, 0.0, 0.0);
+        layer_ctx.clear_rect(0.0, 0.0, f64::from(self.width), f64::from(self.height));
+
+        let main_ctx = std::mem::replace(&mut self.ctx, layer_ctx);
+        self.apply_camera(scene);
+        self.render_clipped(group, clips);
+        self.render_children(scene, group, clips, depth);
+        self.ctx = main_ctx;
+
+        self.ctx.save();
+        let _ = self.ctx.set_transform(1.0, 0.0, 0.0, 1.0, 0.0, 0.0);
+        self.ctx
+            .set_global_alpha(self.ctx.global_alpha() * f64::from(opacity));
+        let _ = self
+            .ctx
+            .draw_image_with_html_canvas_element(&layer_canvas, 0.0, 0.0);
+        self.ctx.restore();
+        true
+    }
+
+    /// Get (creating if needed) the offscreen layer for a nesting depth,
+    /// sized to match the main canvas.
+    fn layer(&mut self, depth: usize) -> Option<(HtmlCanvasElement, CanvasRenderingContext2d)> {
+        while self.layers.len() <= depth {
+            let canvas = web_sys::window()?
+                .document()?
+                .create_element("canvas")
+                .ok()?
+                .dyn_into::<HtmlCanvasElement>()
+                .ok()?;
+            let ctx = canvas
+                .get_context("2d")
+                .ok()??
+                .dyn_into::<CanvasRenderingContext2d>()
+                .ok()?;
+            self.layers.push((canvas, ctx));
+        }
+
+        let (canvas, ctx) = self.layers.get(depth)?;
+        if canvas.width() != self.width || canvas.height() != self.height {
+            canvas.set_width(self.width);
+            canvas.set_height(self.height);
         }
+        Some((canvas.clone(), ctx.clone()))
     }
 
     fn render_element(&mut self, element: &Element) {
         let t = &element.transform;
 
-        if let ElementKind::Chart { chart_type, data } = &element.kind {
+        #[cfg(feature = "charts")]
+        if let ElementKind::Chart {
+            chart_type, data, ..
+        } = &element.kind
+        {
             self.render_chart(element, chart_type, data);
-        } else if let ElementKind::Video { stream_id, .. } = &element.kind {
+            return;
+        }
+
+        if let ElementKind::Video { stream_id, .. } = &element.kind {
             self.render_video(element, stream_id);
+        } else if let ElementKind::Audio {
+            waveform, state, ..
+        } = &element.kind
+        {
+            self.render_styled_box(element);
+            self.draw_audio(t, waveform, *state);
+        } else if let ElementKind::Calendar {
+            view,
+            date,
+            events,
+            selected,
+        } = &element.kind
+        {
+            self.render_styled_box(element);
+            if let Some(layout) = CalendarLayout::new(*view, date, t.width, t.height) {
+                self.draw_calendar(t, &layout, events, selected.as_deref());
+            }
+        } else if let Some(results) = PollResults::from_kind(&element.kind) {
+            self.render_styled_box(element);
+            self.draw_poll(t, &results);
+        } else if let ElementKind::Agenda { title, items, .. } = &element.kind {
+            self.render_styled_box(element);
+            if let Some(status) = AgendaStatus::from_kind(&element.kind, self.server_now_ms()) {
+                self.draw_agenda(t, title, items, &status);
+            }
+        } else if let ElementKind::Randomizer { source, outcome } = &element.kind {
+            self.render_styled_box(element);
+            self.draw_randomizer(t, source, outcome.as_ref());
+        } else if let ElementKind::FloorPlan {
+            plan_width,
+            plan_height,
+            paths,
+            markers,
+            ..
+        } = &element.kind
+        {
+            self.render_styled_box(element);
+            let layout = FloorPlanLayout::new(*plan_width, *plan_height, t.width, t.height);
+            self.draw_floor_plan(t, &layout, paths, markers);
+        } else if let ElementKind::Tree { root, direction } = &element.kind {
+            self.render_styled_box(element);
+            self.draw_tree(t, root, *direction);
+        } else if let ElementKind::Gauge { .. } = &element.kind {
+            self.render_styled_box(element);
+            self.draw_gauge(t, &element.kind);
+        } else if let ElementKind::Kpi { .. } = &element.kind {
+            self.render_styled_box(element);
+            self.draw_kpi(t, &element.kind);
+        } else if let ElementKind::Sparkline { values, kind, .. } = &element.kind {
+            self.render_styled_box(element);
+            let [x, y, width, height] = sparkline_area(t.width, t.height);
+            let color = element
+                .kind
+                .resolved_color(&self.theme)
+                .unwrap_or(ElementKind::SPARKLINE_COLOR)
+                .to_hex();
+            self.draw_sparkline(values, *kind, [t.x + x, t.y + y, width, height], &color);
+        } else if self.draw_mark(element) {
+            // Ink and annotations are drawn without a box
+        } else if let Some((content, author)) = element.kind.note() {
+            self.render_styled_box(element);
+            self.draw_note_text(t, content, author);
+        } else if let ElementKind::Math { tex, font_size, .. } = &element.kind {
+            self.render_styled_box(element);
+            let color = self.get_element_color(element);
+            self.draw_math(t, tex, *font_size, &color);
+        } else if self.draw_listing(element) {
+            // Code and logs draw monospace rows over their box
         } else {
-            let fill_color = Self::get_element_color(element);
-            self.ctx.set_fill_style_str(&fill_color);
-            self.ctx.fill_rect(
-                f64::from(t.x),
-                f64::from(t.y),
-                f64::from(t.width),
-                f64::from(t.height),
-            );
+            self.render_styled_box(element);
 
             self.ctx.set_fill_style_str("#333333");
             self.ctx.set_font("12px sans-serif");
@@ -181,6 +724,386 @@ impl DomRendererState {
         }
     }
 
+    /// Paint an element's bounds using its style (fill, stroke, corner radius,
+    /// shadow, and opacity), falling back to the per-kind default color.
+    fn render_styled_box(&self, element: &Element) {
+        let t = &element.transform;
+        let radius = element.style.effective_corner_radius(t.width, t.height);
+        self.paint_styled(element, || {
+            self.trace_rounded_rect(
+                f64::from(t.x),
+                f64::from(t.y),
+                f64::from(t.width),
+                f64::from(t.height),
+                f64::from(radius),
+            );
+        });
+    }
+
+    /// Fill and stroke the path traced by `trace` using an element's style,
+    /// with gradients spanning the element's bounds.
+    fn paint_styled(&self, element: &Element, trace: impl FnOnce()) {
+        let t = &element.transform;
+        let style = &element.style;
+        let (x, y, width, height) = (
+            f64::from(t.x),
+            f64::from(t.y),
+            f64::from(t.width),
+            f64::from(t.height),
+        );
+
+        self.ctx.save();
+        self.ctx
+            .set_global_alpha(self.ctx.global_alpha() * f64::from(style.effective_opacity()));
+
+        if let Some(shadow) = &style.shadow {
+            self.ctx
+                .set_shadow_color(&self.css_color(&shadow.color, Color::TRANSPARENT));
+            self.ctx.set_shadow_offset_x(f64::from(shadow.offset_x));
+            self.ctx.set_shadow_offset_y(f64::from(shadow.offset_y));
+            self.ctx.set_shadow_blur(f64::from(shadow.blur.max(0.0)));
+        }
+
+        match &style.fill {
+            Some(Fill::LinearGradient { angle, stops }) => {
+                let (cos, sin) = (f64::from(*angle).cos(), f64::from(*angle).sin());
+                let (cx, cy) = (x + width / 2.0, y + height / 2.0);
+                let (dx, dy) = (cos * width / 2.0, sin * height / 2.0);
+                let gradient = self
+                    .ctx
+                    .create_linear_gradient(cx - dx, cy - dy, cx + dx, cy + dy);
+                self.add_color_stops(&gradient, stops);
+                self.ctx.set_fill_style_canvas_gradient(&gradient);
+            }
+            Some(Fill::RadialGradient {
+                center_x,
+                center_y,
+                radius: gradient_radius,
+                stops,
+            }) => {
+                let cx = x + f64::from(*center_x) * width;
+                let cy = y + f64::from(*center_y) * height;
+                let extent = (f64::from(*gradient_radius) * width.max(height)).max(0.0);
+                match self.ctx.create_radial_gradient(cx, cy, 0.0, cx, cy, extent) {
+                    Ok(gradient) => {
+                        self.add_color_stops(&gradient, stops);
+                        self.ctx.set_fill_style_canvas_gradient(&gradient);
+                    }
+                    Err(_) => self.ctx.set_fill_style_str(&stops.first().map_or_else(
+                        || Color::BLACK.to_hex(),
+                        |s| self.css_color(&s.color, Color::BLACK),
+                    )),
+                }
+            }
+            Some(Fill::Solid { color }) => self
+                .ctx
+                .set_fill_style_str(&self.css_color(color, Color::TRANSPARENT)),
+            None => self
+                .ctx
+                .set_fill_style_str(&self.get_element_color(element)),
+        }
+
+        trace();
+        self.ctx.fill();
+
+        if let Some(stroke) = &style.stroke {
+            if stroke.width > 0.0 {
+                // Strokes don't cast a second shadow
+                self.ctx.set_shadow_color("transparent");
+                self.ctx
+                    .set_stroke_style_str(&self.css_color(&stroke.color, Color::TRANSPARENT));
+                self.ctx.set_line_width(f64::from(stroke.width));
+                self.ctx.stroke();
+            }
+        }
+
+        self.ctx.restore();
+    }
+
+    /// Draw a callout's tail triangle beneath the callout body.
+    fn draw_callout_tail(&self, element: &Element, tail: &[(f32, f32); 3]) {
+        self.ctx.save();
+        self.ctx.set_global_alpha(
+            self.ctx.global_alpha() * f64::from(element.style.effective_opacity()),
+        );
+        self.ctx
+            .set_fill_style_str(&self.get_element_color(element));
+        self.ctx.set_stroke_style_str("#999999");
+        self.ctx.set_line_width(1.0);
+        self.ctx.begin_path();
+        let [a, b, tip] = tail;
+        self.ctx.move_to(f64::from(a.0), f64::from(a.1));
+        self.ctx.line_to(f64::from(tip.0), f64::from(tip.1));
+        self.ctx.line_to(f64::from(b.0), f64::from(b.1));
+        self.ctx.close_path();
+        self.ctx.fill();
+        self.ctx.stroke();
+        self.ctx.restore();
+    }
+
+    /// Draw a note's text, wrapped to its width, with the author below.
+    fn draw_note_text(&self, t: &Transform, content: &str, author: Option<&str>) {
+        const PADDING: f64 = 8.0;
+        const LINE_HEIGHT: f64 = 18.0;
+
+        let (x, y) = (f64::from(t.x) + PADDING, f64::from(t.y) + PADDING);
+        let max_width = (f64::from(t.width) - 2.0 * PADDING).max(0.0);
+        let bottom = f64::from(t.y + t.height) - PADDING;
+
+        self.ctx.save();
+        self.ctx.set_fill_style_str("#333333");
+        self.ctx.set_font("14px sans-serif");
+        self.ctx.set_text_baseline("top");
+        let mut line_y = y;
+        let mut line = String::new();
+        for word in content.split_whitespace() {
+            let candidate = if line.is_empty() {
+                word.to_string()
+            } else {
+                format!("{line} {word}")
+            };
+            let fits = self
+                .ctx
+                .measure_text(&candidate)
+                .map_or(true, |m| m.width() <= max_width);
+            if fits || line.is_empty() {
+                line = candidate;
+            } else {
+                let _ = self.ctx.fill_text(&line, x, line_y);
+                line_y += LINE_HEIGHT;
+                line = word.to_string();
+            }
+        }
+        if !line.is_empty() {
+            let _ = self.ctx.fill_text(&line, x, line_y);
+        }
+
+        if let Some(author) = author {
+            self.ctx.set_fill_style_str("#666666");
+            self.ctx.set_font("italic 11px sans-serif");
+            self.ctx.set_text_baseline("bottom");
+            let _ = self.ctx.fill_text(&format!("— {author}"), x, bottom);
+        }
+        self.ctx.restore();
+    }
+
+    /// Draw a typeset formula, scaled down to fit and vertically centered.
+    /// Formulas that cannot be typeset are drawn as monospace TeX source.
+    fn draw_math(&self, t: &Transform, tex: &str, font_size: f32, color: &str) {
+        self.ctx.save();
+        self.ctx.set_fill_style_str(color);
+        self.ctx.set_text_baseline("alphabetic");
+
+        if let Ok(layout) = MathLayout::new(tex, font_size) {
+            let scale = f64::from(layout.fit_scale(t.width, t.height));
+            let oy =
+                f64::from(t.y) + (f64::from(t.height) - f64::from(layout.height) * scale) / 2.0;
+            let _ = self.ctx.translate(f64::from(t.x), oy);
+            let _ = self.ctx.scale(scale, scale);
+            for run in &layout.runs {
+                let style = if run.italic { "italic " } else { "" };
+                self.ctx
+                    .set_font(&format!("{style}{}px serif", run.font_size));
+                let _ = self
+                    .ctx
+                    .fill_text(&run.text, f64::from(run.x), f64::from(run.y));
+            }
+            for rule in &layout.rules {
+                self.ctx.fill_rect(
+                    f64::from(rule.x),
+                    f64::from(rule.y),
+                    f64::from(rule.width),
+                    f64::from(rule.thickness),
+                );
+            }
+        } else {
+            self.ctx.set_font(&format!("{font_size}px monospace"));
+            let _ = self
+                .ctx
+                .fill_text(tex, f64::from(t.x), f64::from(t.y + font_size));
+        }
+        self.ctx.restore();
+    }
+
+    /// Draw a code or log element; returns false for other kinds.
+    fn draw_listing(&self, element: &Element) -> bool {
+        let t = &element.transform;
+        match &element.kind {
+            ElementKind::Code { source, spans, .. } => {
+                self.render_styled_box(element);
+                self.draw_code(t, source, spans);
+            }
+            ElementKind::LogView {
+                title,
+                lines,
+                follow,
+                ..
+            } => {
+                self.render_styled_box(element);
+                self.draw_log(t, title, lines, *follow);
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    /// Draw highlighted code lines, clipped to the element.
+    #[allow(clippy::cast_precision_loss)] // Line counts are small
+    fn draw_code(&self, t: &Transform, source: &str, spans: &[HighlightSpan]) {
+        let (x, y) = (f64::from(t.x), f64::from(t.y));
+        let padding = f64::from(CODE_PADDING);
+        let line_height = f64::from(CODE_LINE_HEIGHT);
+
+        self.ctx.save();
+        self.ctx.begin_path();
+        self.ctx.rect(x, y, f64::from(t.width), f64::from(t.height));
+        self.ctx.clip();
+        self.ctx.set_font(&format!("{CODE_FONT_SIZE}px monospace"));
+        self.ctx.set_text_baseline("middle");
+
+        let bottom = f64::from(t.y + t.height);
+        for (row, line) in highlight::lines(source, spans).iter().enumerate() {
+            let top = y + padding + row as f64 * line_height;
+            if top > bottom {
+                break;
+            }
+            let mut line_x = x + padding;
+            for (kind, text) in line {
+                self.ctx.set_fill_style_str(kind.color());
+                let _ = self.ctx.fill_text(text, line_x, top + line_height / 2.0);
+                line_x += self.ctx.measure_text(text).map_or(0.0, |m| m.width());
+            }
+        }
+        self.ctx.restore();
+    }
+
+    /// Draw a log view's title and the rows that fit, in their ANSI styles.
+    #[allow(clippy::cast_precision_loss)] // Line counts are small
+    fn draw_log(&self, t: &Transform, title: &str, lines: &[String], follow: bool) {
+        let (x, y) = (f64::from(t.x), f64::from(t.y));
+        let padding = f64::from(CODE_PADDING);
+        let line_height = f64::from(CODE_LINE_HEIGHT);
+
+        self.ctx.save();
+        self.ctx.begin_path();
+        self.ctx.rect(x, y, f64::from(t.width), f64::from(t.height));
+        self.ctx.clip();
+        self.ctx.set_text_baseline("middle");
+
+        if !title.is_empty() {
+            self.ctx
+                .set_font(&format!("bold {CODE_FONT_SIZE}px monospace"));
+            self.ctx.set_fill_style_str(LOG_FOREGROUND);
+            let _ = self
+                .ctx
+                .fill_text(title, x + padding, y + padding + line_height / 2.0);
+        }
+        let body_top = y + f64::from(log_view::body_top(title));
+        let range = log_view::visible_range(title, lines.len(), t.height, follow);
+        for (row, line) in lines[range].iter().enumerate() {
+            let top = body_top + row as f64 * line_height;
+            let mut line_x = x + padding;
+            for span in log_view::spans(line) {
+                let style = span.style;
+                let weight = if style.bold { "bold " } else { "" };
+                let slant = if style.italic { "italic " } else { "" };
+                self.ctx
+                    .set_font(&format!("{slant}{weight}{CODE_FONT_SIZE}px monospace"));
+                let width = self.ctx.measure_text(&span.text).map_or(0.0, |m| m.width());
+                if let Some(background) = style.background() {
+                    self.ctx.set_fill_style_str(&background.to_hex());
+                    self.ctx.fill_rect(line_x, top, width, line_height);
+                }
+                let color = style.foreground().to_hex();
+                self.ctx.set_fill_style_str(&color);
+                let _ = self
+                    .ctx
+                    .fill_text(&span.text, line_x, top + line_height / 2.0);
+                if style.underline {
+                    self.ctx
+                        .fill_rect(line_x, top + line_height - 4.0, width, 1.0);
+                }
+                line_x += width;
+            }
+        }
+        self.ctx.restore();
+    }
+
+    /// Add gradient stops, skipping any the browser rejects.
+    fn add_color_stops(&self, gradient: &web_sys::CanvasGradient, stops: &[GradientStop]) {
+        for stop in stops {
+            let color = self.css_color(&stop.color, Color::TRANSPARENT);
+            let _ = gradient.add_color_stop(stop.offset.clamp(0.0, 1.0), &color);
+        }
+    }
+
+    /// Resolve a color against the scene theme as a CSS string, using
+    /// `fallback` for undefined theme references.
+    fn css_color(&self, color: &ThemeColor, fallback: Color) -> String {
+        self.theme.resolve_or(color, fallback).to_hex()
+    }
+
+    /// Begin a new path tracing a shape's outline.
+    fn trace_outline(&self, outline: &ShapeOutline) {
+        match outline {
+            ShapeOutline::Rect {
+                bounds: [x, y, width, height],
+                radius,
+            } => self.trace_rounded_rect(
+                f64::from(*x),
+                f64::from(*y),
+                f64::from(*width),
+                f64::from(*height),
+                f64::from(*radius),
+            ),
+            ShapeOutline::Ellipse {
+                center: [cx, cy],
+                radii: [rx, ry],
+            } => {
+                self.ctx.begin_path();
+                let _ = self.ctx.ellipse(
+                    f64::from(*cx),
+                    f64::from(*cy),
+                    f64::from(rx.max(0.0)),
+                    f64::from(ry.max(0.0)),
+                    0.0,
+                    0.0,
+                    std::f64::consts::TAU,
+                );
+            }
+            ShapeOutline::Polygon(points) => {
+                self.ctx.begin_path();
+                for (i, [px, py]) in points.iter().enumerate() {
+                    let (px, py) = (f64::from(*px), f64::from(*py));
+                    if i == 0 {
+                        self.ctx.move_to(px, py);
+                    } else {
+                        self.ctx.line_to(px, py);
+                    }
+                }
+                self.ctx.close_path();
+            }
+        }
+    }
+
+    /// Begin a new path tracing a rectangle with rounded corners.
+    fn trace_rounded_rect(&self, x: f64, y: f64, width: f64, height: f64, radius: f64) {
+        self.ctx.begin_path();
+        if radius <= 0.0 {
+            self.ctx.rect(x, y, width, height);
+            return;
+        }
+        self.ctx.move_to(x + radius, y);
+        let _ = self.ctx.arc_to(x + width, y, x + width, y + height, radius);
+        let _ = self
+            .ctx
+            .arc_to(x + width, y + height, x, y + height, radius);
+        let _ = self.ctx.arc_to(x, y + height, x, y, radius);
+        let _ = self.ctx.arc_to(x, y, x + width, y, radius);
+        self.ctx.close_path();
+    }
+
+    #[cfg(feature = "charts")]
     #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
     fn render_chart(&mut self, element: &Element, chart_type: &str, _data: &serde_json::Value) {
         // Chart rendering is not available in WASM (plotters doesn't support wasm32)
@@ -189,6 +1112,7 @@ impl DomRendererState {
         self.draw_chart_placeholder(t, chart_type);
     }
 
+    #[cfg(feature = "charts")]
     fn draw_chart_placeholder(&self, t: &Transform, chart_type: &str) {
         self.ctx.set_fill_style_str("#e3f2fd");
         self.ctx.fill_rect(
@@ -303,44 +1227,1003 @@ impl DomRendererState {
         self.ctx.set_text_baseline("alphabetic");
     }
 
-    fn get_element_color(element: &Element) -> String {
-        match &element.kind {
-            ElementKind::Chart { .. } => "#e3f2fd".to_string(),
-            ElementKind::Image { .. } => "#f5f5f5".to_string(),
-            ElementKind::Model3D { .. } => "#e8f5e9".to_string(),
-            ElementKind::Video { .. } => "#212121".to_string(),
-            ElementKind::OverlayLayer { opacity, .. } => format!("rgba(255, 255, 255, {opacity})"),
-            ElementKind::Text { color, .. } => color.clone(),
-            ElementKind::Group { .. } => "rgba(255, 253, 231, 0.5)".to_string(),
+    /// Draw a play/pause button beside the waveform bars.
+    #[allow(clippy::cast_precision_loss)]
+    fn draw_audio(&self, t: &Transform, waveform: &[f32], state: PlaybackState) {
+        let (x, y, width, height) = (
+            f64::from(t.x),
+            f64::from(t.y),
+            f64::from(t.width),
+            f64::from(t.height),
+        );
+        let pad = (height * 0.15).min(12.0);
+        let diameter = (height - 2.0 * pad).min(width / 3.0).max(0.0);
+        let r = diameter / 2.0;
+        let (cx, cy) = (x + pad + r, y + height / 2.0);
+
+        self.ctx.set_fill_style_str("#eceff1");
+        self.ctx.begin_path();
+        let _ = self.ctx.arc(cx, cy, r, 0.0, std::f64::consts::TAU);
+        self.ctx.fill();
+
+        self.ctx.set_fill_style_str("#263238");
+        if state == PlaybackState::Playing {
+            for dx in [-0.35, 0.1] {
+                self.ctx
+                    .fill_rect(cx + dx * r, cy - 0.4 * r, 0.25 * r, 0.8 * r);
+            }
+        } else {
+            self.ctx.begin_path();
+            self.ctx.move_to(cx - 0.3 * r, cy - 0.45 * r);
+            self.ctx.line_to(cx - 0.3 * r, cy + 0.45 * r);
+            self.ctx.line_to(cx + 0.5 * r, cy);
+            self.ctx.close_path();
+            self.ctx.fill();
         }
-    }
 
-    fn get_element_label(element: &Element) -> String {
-        match &element.kind {
-            ElementKind::Chart { chart_type, .. } => format!("Chart: {chart_type}"),
-            ElementKind::Image { .. } => "Image".to_string(),
-            ElementKind::Model3D { .. } => "3D Model".to_string(),
-            ElementKind::Video { stream_id, .. } => format!("Video: {stream_id}"),
-            ElementKind::OverlayLayer { children, .. } => format!("Overlay ({})", children.len()),
-            ElementKind::Text { content, .. } => {
-                if content.len() > 20 {
-                    format!("{}...", &content[..20])
-                } else {
-                    content.clone()
-                }
-            }
-            ElementKind::Group { children } => format!("Group ({})", children.len()),
+        let left = x + 2.0 * pad + diameter;
+        let area = x + width - pad - left;
+        if waveform.is_empty() || area <= 0.0 {
+            return;
+        }
+        let step = area / waveform.len() as f64;
+        let max_height = height - 2.0 * pad;
+        self.ctx.set_fill_style_str("#80cbc4");
+        for (i, amplitude) in waveform.iter().enumerate() {
+            let bar = (f64::from(amplitude.clamp(0.0, 1.0)) * max_height).max(1.0);
+            self.ctx
+                .fill_rect(left + i as f64 * step, cy - bar / 2.0, step * 0.6, bar);
         }
     }
-}
 
-impl RenderBackend for DomCanvasBackend {
-    fn backend_type(&self) -> BackendType {
-        BackendType::Canvas2D
-    }
+    /// Draw a calendar's title, weekday header and day cells with their
+    /// events, matching the SVG export.
+    #[allow(clippy::cast_precision_loss)]
+    fn draw_calendar(
+        &self,
+        t: &Transform,
+        layout: &CalendarLayout,
+        events: &[canvas_core::CalendarEvent],
+        selected: Option<&str>,
+    ) {
+        let (x, y) = (f64::from(t.x), f64::from(t.y));
+        let title_height = f64::from(layout.title_height);
+        let weekday_y = y + f64::from(layout.weekday_y);
+        let weekday_height = f64::from(layout.weekday_height);
+        let column = f64::from(layout.column_width);
+
+        self.ctx.set_fill_style_str("#37474f");
+        self.ctx.fill_rect(x, y, f64::from(t.width), title_height);
+        self.ctx.set_fill_style_str("#eceff1");
+        self.ctx
+            .fill_rect(x, weekday_y, f64::from(t.width), weekday_height);
 
-    fn render(&mut self, scene: &Scene) -> RenderResult<()> {
-        if let Ok(mut state) = self.state.try_borrow_mut() {
+        self.ctx.set_text_align("center");
+        self.ctx.set_text_baseline("middle");
+        self.ctx.set_fill_style_str("#ffffff");
+        self.ctx
+            .set_font(&format!("{}px sans-serif", title_height * 0.55));
+        let _ = self.ctx.fill_text(
+            &layout.title,
+            x + f64::from(t.width) / 2.0,
+            y + title_height / 2.0,
+        );
+        self.ctx.set_fill_style_str("#546e7a");
+        self.ctx
+            .set_font(&format!("{}px sans-serif", weekday_height * 0.6));
+        for (i, name) in WEEKDAYS.iter().enumerate() {
+            let _ = self.ctx.fill_text(
+                name,
+                x + (i as f64 + 0.5) * column,
+                weekday_y + weekday_height / 2.0,
+            );
+        }
+        self.ctx.set_text_align("start");
+        self.ctx.set_text_baseline("alphabetic");
+
+        let selected = selected.and_then(canvas_core::CalendarDate::parse);
+        self.ctx.set_stroke_style_str("#cfd8dc");
+        self.ctx.set_line_width(1.0);
+        for cell in &layout.cells {
+            let (cx, cy) = (x + f64::from(cell.x), y + f64::from(cell.y));
+            let (width, height) = (f64::from(cell.width), f64::from(cell.height));
+            if Some(cell.date) == selected {
+                self.ctx.set_fill_style_str("#bbdefb");
+                self.ctx.fill_rect(cx, cy, width, height);
+            }
+            self.ctx.stroke_rect(cx, cy, width, height);
+
+            let number_size = f64::from(cell.number_size());
+            self.ctx.set_font(&format!("{number_size}px sans-serif"));
+            self.ctx
+                .set_fill_style_str(if cell.in_range { "#263238" } else { "#b0bec5" });
+            let _ =
+                self.ctx
+                    .fill_text(&cell.date.day.to_string(), cx + 4.0, cy + number_size + 2.0);
+
+            // One chip per event, with a count of any that do not fit
+            let line = f64::from(cell.event_line());
+            let top = cy + f64::from(cell.events_top());
+            let day_events: Vec<_> = events_on(events, cell.date).collect();
+            let shown = cell.visible_events(day_events.len());
+            self.ctx
+                .set_font(&format!("{}px sans-serif", number_size * 0.85));
+            for (i, event) in day_events.iter().take(shown).enumerate() {
+                let chip_y = top + i as f64 * line;
+                self.ctx
+                    .set_fill_style_str(&event.resolved_color(&self.theme).to_hex());
+                self.ctx
+                    .fill_rect(cx + 2.0, chip_y, (width - 4.0).max(0.0), line - 2.0);
+                let label = match &event.time {
+                    Some(time) => format!("{time} {}", event.title),
+                    None => event.title.clone(),
+                };
+                self.ctx.set_fill_style_str("#ffffff");
+                let _ = self.ctx.fill_text_with_max_width(
+                    &label,
+                    cx + 4.0,
+                    chip_y + line - 4.0,
+                    (width - 8.0).max(0.0),
+                );
+            }
+            if shown < day_events.len() && top + line <= cy + height {
+                self.ctx.set_fill_style_str("#546e7a");
+                let _ = self.ctx.fill_text(
+                    &format!("+{} more", day_events.len() - shown),
+                    cx + 4.0,
+                    top + (shown + 1) as f64 * line - 4.0,
+                );
+            }
+        }
+    }
+
+    /// Draw an agenda's header clock and item rows, matching the SVG export.
+    fn draw_agenda(&self, t: &Transform, title: &str, items: &[AgendaItem], status: &AgendaStatus) {
+        let (x, y) = (f64::from(t.x), f64::from(t.y));
+        let layout = AgendaLayout::new(items.len(), t.width, t.height);
+        let header_height = f64::from(layout.header_height);
+        let pad = f64::from(AgendaLayout::PADDING) * 2.0;
+
+        self.ctx.set_fill_style_str("#37474f");
+        self.ctx.fill_rect(x, y, f64::from(t.width), header_height);
+        self.ctx.set_text_baseline("middle");
+        self.ctx.set_fill_style_str("#ffffff");
+        self.ctx
+            .set_font(&format!("{}px sans-serif", layout.header_font_size()));
+        let header_y = y + header_height / 2.0;
+        let _ = self.ctx.fill_text(title, x + pad, header_y);
+        self.ctx.set_text_align("end");
+        let _ = self.ctx.fill_text(
+            &status.clock_text(items),
+            x + f64::from(t.width) - pad,
+            header_y,
+        );
+        self.ctx.set_text_align("start");
+
+        for (row, item) in layout.rows.iter().zip(items) {
+            let (rx, ry) = (x + f64::from(row.x), y + f64::from(row.y));
+            let (width, height) = (f64::from(row.width), f64::from(row.height));
+            let running = status.current() == Some(row.index);
+            if running {
+                self.ctx.set_fill_style_str("#e0f2f1");
+                self.ctx.fill_rect(rx, ry, width, height);
+                if let Some(progress) = status.progress() {
+                    let [bx, by, bar_width, bar_height] = row.progress_bar(progress).map(f64::from);
+                    self.ctx.set_fill_style_str("#26a69a");
+                    self.ctx.fill_rect(x + bx, y + by, bar_width, bar_height);
+                }
+            }
+
+            let text_y = ry + height / 2.0;
+            let weight = if running { "bold " } else { "" };
+            self.ctx
+                .set_font(&format!("{weight}{}px sans-serif", row.font_size()));
+            self.ctx.set_fill_style_str(if status.is_done(row.index) {
+                "#90a4ae"
+            } else {
+                "#263238"
+            });
+            let _ = self.ctx.fill_text(&item.label(), rx + 6.0, text_y);
+            self.ctx.set_text_align("end");
+            let _ = self
+                .ctx
+                .fill_text(&item.duration_text(), rx + width - 6.0, text_y);
+            self.ctx.set_text_align("start");
+        }
+        self.ctx.set_text_baseline("alphabetic");
+    }
+
+    /// Draw a poll's question and a result bar per option, matching the SVG
+    /// export.
+    fn draw_poll(&self, t: &Transform, results: &PollResults) {
+        let (x, y) = (f64::from(t.x), f64::from(t.y));
+        let layout = PollLayout::new(results.options.len(), t.width, t.height);
+        let title_height = f64::from(layout.title_height);
+
+        self.ctx.set_fill_style_str("#37474f");
+        self.ctx.fill_rect(x, y, f64::from(t.width), title_height);
+        self.ctx.set_text_baseline("middle");
+        self.ctx.set_fill_style_str("#ffffff");
+        self.ctx
+            .set_font(&format!("{}px sans-serif", title_height * 0.5));
+        let _ = self.ctx.fill_text(
+            &results.question,
+            x + f64::from(PollLayout::PADDING),
+            y + title_height / 2.0,
+        );
+
+        let bar = if results.closed { "#90a4ae" } else { "#4db6ac" };
+        for (row, option) in layout.rows.iter().zip(&results.options) {
+            let (rx, ry) = (x + f64::from(row.x), y + f64::from(row.y));
+            let (width, height) = (f64::from(row.width), f64::from(row.height));
+            self.ctx.set_fill_style_str("#eceff1");
+            self.ctx.fill_rect(rx, ry, width, height);
+            self.ctx.set_fill_style_str(bar);
+            self.ctx
+                .fill_rect(rx, ry, f64::from(row.bar_width(option.share)), height);
+
+            let text_y = ry + height / 2.0;
+            self.ctx
+                .set_font(&format!("{}px sans-serif", row.font_size()));
+            self.ctx.set_fill_style_str("#263238");
+            let _ = self.ctx.fill_text(&option.label, rx + 6.0, text_y);
+            self.ctx.set_text_align("end");
+            self.ctx.set_fill_style_str("#546e7a");
+            let _ = self
+                .ctx
+                .fill_text(&option.summary(), rx + width - 6.0, text_y);
+            self.ctx.set_text_align("start");
+        }
+        self.ctx.set_text_baseline("alphabetic");
+    }
+
+    /// Draw a gauge's dial filled up to its value, threshold ticks, value and
+    /// label, matching the SVG export.
+    fn draw_gauge(&self, t: &Transform, kind: &ElementKind) {
+        let ElementKind::Gauge {
+            value,
+            min,
+            max,
+            shape,
+            label,
+            thresholds,
+            format,
+        } = kind
+        else {
+            return;
+        };
+        let (x, y) = (f64::from(t.x), f64::from(t.y));
+        let layout = GaugeLayout::new(*shape, t.width, t.height);
+        let fraction = gauge_fraction(*value, *min, *max);
+        let color = threshold_color(*value, thresholds, &self.theme).to_hex();
+
+        match layout.dial {
+            Dial::Radial {
+                cx,
+                cy,
+                radius,
+                thickness,
+            } => {
+                let (cx, cy, radius) = (x + f64::from(cx), y + f64::from(cy), f64::from(radius));
+                self.ctx.set_line_width(f64::from(thickness));
+                for (to, stroke) in [(1.0, GAUGE_TRACK_COLOR.to_hex()), (fraction, color.clone())] {
+                    if to > 0.0 {
+                        self.ctx.begin_path();
+                        let _ = self.ctx.arc(
+                            cx,
+                            cy,
+                            radius,
+                            f64::from(Dial::angle(0.0)),
+                            f64::from(Dial::angle(to)),
+                        );
+                        self.ctx.set_stroke_style_str(&stroke);
+                        self.ctx.stroke();
+                    }
+                }
+            }
+            Dial::Linear {
+                x: bx,
+                y: by,
+                width,
+                height,
+            } => {
+                let (bx, by) = (x + f64::from(bx), y + f64::from(by));
+                let (width, height) = (f64::from(width), f64::from(height));
+                self.ctx.set_fill_style_str(&GAUGE_TRACK_COLOR.to_hex());
+                self.ctx.fill_rect(bx, by, width, height);
+                self.ctx.set_fill_style_str(&color);
+                self.ctx
+                    .fill_rect(bx, by, width * f64::from(fraction), height);
+            }
+        }
+
+        self.ctx.set_line_width(2.0);
+        for threshold in thresholds {
+            let [(x1, y1), (x2, y2)] =
+                layout
+                    .dial
+                    .tick(gauge_fraction(threshold.value, *min, *max));
+            self.ctx.begin_path();
+            self.ctx.move_to(x + f64::from(x1), y + f64::from(y1));
+            self.ctx.line_to(x + f64::from(x2), y + f64::from(y2));
+            self.ctx.set_stroke_style_str(
+                &threshold_color(threshold.value, thresholds, &self.theme).to_hex(),
+            );
+            self.ctx.stroke();
+        }
+
+        self.ctx.set_text_baseline("middle");
+        self.ctx.set_text_align("center");
+        self.ctx.set_fill_style_str(&color);
+        self.ctx
+            .set_font(&format!("bold {}px sans-serif", layout.value_font_size));
+        let _ = self.ctx.fill_text(
+            &format.format(*value),
+            x + f64::from(layout.value_x),
+            y + f64::from(layout.value_y),
+        );
+        if !label.is_empty() {
+            let label_x = match shape {
+                GaugeShape::Radial => x + f64::from(t.width) / 2.0,
+                GaugeShape::Linear => {
+                    self.ctx.set_text_align("start");
+                    x + f64::from(GaugeLayout::PADDING)
+                }
+            };
+            self.ctx.set_fill_style_str("#546e7a");
+            self.ctx
+                .set_font(&format!("{}px sans-serif", layout.label_font_size));
+            let _ = self
+                .ctx
+                .fill_text(label, label_x, y + f64::from(layout.label_y));
+        }
+        self.ctx.set_text_align("start");
+        self.ctx.set_text_baseline("alphabetic");
+    }
+
+    /// Draw a KPI card's label, value, change and sparkline, matching the SVG
+    /// export.
+    fn draw_kpi(&self, t: &Transform, kind: &ElementKind) {
+        let ElementKind::Kpi {
+            label,
+            value,
+            previous,
+            sparkline,
+            sparkline_kind,
+            thresholds,
+            format,
+            higher_is_better,
+        } = kind
+        else {
+            return;
+        };
+        let (x, y) = (f64::from(t.x), f64::from(t.y));
+        let layout = KpiLayout::new(t.width, t.height, !sparkline.is_empty());
+        let color = threshold_color(*value, thresholds, &self.theme).to_hex();
+        let text_x = x + f64::from(KpiLayout::PADDING);
+
+        self.ctx.set_text_baseline("middle");
+        self.ctx.set_fill_style_str("#546e7a");
+        self.ctx
+            .set_font(&format!("{}px sans-serif", layout.label_font_size));
+        let _ = self
+            .ctx
+            .fill_text(label, text_x, y + f64::from(layout.label_y));
+        self.ctx.set_fill_style_str(&color);
+        self.ctx
+            .set_font(&format!("bold {}px sans-serif", layout.value_font_size));
+        let _ = self.ctx.fill_text(
+            &format.format(*value),
+            text_x,
+            y + f64::from(layout.value_y),
+        );
+        if let Some(previous) = previous {
+            let delta = KpiDelta::new(*value, *previous, format, *higher_is_better);
+            self.ctx.set_fill_style_str(&delta.color.to_hex());
+            self.ctx
+                .set_font(&format!("{}px sans-serif", layout.delta_font_size));
+            let _ = self
+                .ctx
+                .fill_text(&delta.text, text_x, y + f64::from(layout.delta_y));
+        }
+        self.ctx.set_text_baseline("alphabetic");
+
+        if let Some([sx, sy, width, height]) = layout.sparkline {
+            self.draw_sparkline(
+                sparkline,
+                *sparkline_kind,
+                [t.x + sx, t.y + sy, width, height],
+                &color,
+            );
+        }
+    }
+
+    /// Draw an ink stroke, annotation or shape straight onto the canvas.
+    ///
+    /// Returns `false`, drawing nothing, for other kinds.
+    fn draw_mark(&self, element: &Element) -> bool {
+        match &element.kind {
+            ElementKind::Ink { .. } => {
+                let color = element
+                    .kind
+                    .resolved_color(&self.theme)
+                    .unwrap_or(ElementKind::INK_COLOR)
+                    .to_hex();
+                if let Some((points, width)) = ink::canvas_points(element) {
+                    self.draw_ink(&points, width, &color);
+                }
+            }
+            ElementKind::Measurement { .. } => {
+                if let Some(layout) = MeasurementLayout::from_element(element) {
+                    let color = Self::annotation_color(element, &self.theme);
+                    self.draw_measurement(&layout, &color);
+                }
+            }
+            ElementKind::Region { caption, .. } => {
+                let color = Self::annotation_color(element, &self.theme);
+                self.draw_region(&element.transform, caption.as_deref(), &color);
+            }
+            ElementKind::Shape { shape } => {
+                let t = &element.transform;
+                let radius = element.style.effective_corner_radius(t.width, t.height);
+                let outline = ShapeOutline::new(shape, t, radius);
+                self.paint_styled(element, || self.trace_outline(&outline));
+            }
+            ElementKind::Barcode {
+                symbology, modules, ..
+            } => {
+                let color = element
+                    .kind
+                    .resolved_color(&self.theme)
+                    .unwrap_or(ElementKind::BARCODE_COLOR)
+                    .to_hex();
+                let layout = BarcodeLayout::new(*symbology, modules, &element.transform);
+                self.draw_barcode(&layout, &color);
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    /// Draw a barcode's white background and dark modules, matching the SVG
+    /// export.
+    fn draw_barcode(&self, layout: &BarcodeLayout, color: &str) {
+        let [x, y, width, height] = layout.background.map(f64::from);
+        self.ctx.set_fill_style_str("#ffffff");
+        self.ctx.fill_rect(x, y, width, height);
+        self.ctx.set_fill_style_str(color);
+        for bar in &layout.bars {
+            let [bx, by, bw, bh] = bar.map(f64::from);
+            self.ctx.fill_rect(bx, by, bw, bh);
+        }
+    }
+
+    /// Line, label and caption color of a `Measurement` or `Region`.
+    fn annotation_color(element: &Element, theme: &Theme) -> String {
+        element
+            .kind
+            .resolved_color(theme)
+            .unwrap_or(ElementKind::ANNOTATION_COLOR)
+            .to_hex()
+    }
+
+    /// Draw a measurement line with its caps or ticks and a haloed label,
+    /// matching the SVG export.
+    fn draw_measurement(&self, layout: &MeasurementLayout, color: &str) {
+        self.ctx.save();
+        self.ctx.set_stroke_style_str(color);
+        self.ctx.set_line_width(1.5);
+        self.ctx.begin_path();
+        for [from, to] in
+            std::iter::once([layout.start, layout.end]).chain(layout.marks.iter().copied())
+        {
+            self.ctx.move_to(f64::from(from[0]), f64::from(from[1]));
+            self.ctx.line_to(f64::from(to[0]), f64::from(to[1]));
+        }
+        self.ctx.stroke();
+
+        let [x, y] = layout.label_at;
+        self.ctx
+            .set_font(&format!("{ANNOTATION_FONT_SIZE}px sans-serif"));
+        self.ctx.set_text_align("center");
+        self.ctx.set_text_baseline("middle");
+        self.ctx.set_stroke_style_str("#ffffff");
+        self.ctx.set_line_width(3.0);
+        let _ = self
+            .ctx
+            .stroke_text(&layout.label, f64::from(x), f64::from(y));
+        self.ctx.set_fill_style_str(color);
+        let _ = self
+            .ctx
+            .fill_text(&layout.label, f64::from(x), f64::from(y));
+        self.ctx.restore();
+    }
+
+    /// Draw a tinted, bordered region with its caption in a tab at the
+    /// top-left corner, matching the SVG export.
+    fn draw_region(&self, t: &Transform, caption: Option<&str>, color: &str) {
+        let (x, y, width, height) = (
+            f64::from(t.x),
+            f64::from(t.y),
+            f64::from(t.width),
+            f64::from(t.height),
+        );
+        self.ctx.save();
+        self.ctx.set_fill_style_str(color);
+        let alpha = self.ctx.global_alpha();
+        self.ctx.set_global_alpha(alpha * f64::from(REGION_TINT));
+        self.ctx.fill_rect(x, y, width, height);
+        self.ctx.set_global_alpha(alpha);
+        self.ctx.set_stroke_style_str(color);
+        self.ctx.set_line_width(2.0);
+        self.ctx.stroke_rect(x, y, width, height);
+
+        if let Some(caption) = caption.filter(|c| !c.trim().is_empty()) {
+            let [tx, ty, tw, th] = caption_tab(t, caption);
+            self.ctx
+                .fill_rect(f64::from(tx), f64::from(ty), f64::from(tw), f64::from(th));
+            self.ctx.set_fill_style_str("#ffffff");
+            self.ctx
+                .set_font(&format!("{ANNOTATION_FONT_SIZE}px sans-serif"));
+            let _ = self
+                .ctx
+                .fill_text(caption, f64::from(tx) + 6.0, f64::from(ty) + 14.0);
+        }
+        self.ctx.restore();
+    }
+
+    /// Draw an ink stroke through canvas `points` with round caps and joins,
+    /// or a dot for a single point, matching the SVG export.
+    fn draw_ink(&self, points: &[[f32; 2]], width: f32, color: &str) {
+        if let [[x, y]] = points {
+            self.ctx.set_fill_style_str(color);
+            self.ctx.begin_path();
+            let _ = self.ctx.arc(
+                f64::from(*x),
+                f64::from(*y),
+                f64::from(width / 2.0),
+                0.0,
+                std::f64::consts::TAU,
+            );
+            self.ctx.fill();
+            return;
+        }
+        self.ctx.set_stroke_style_str(color);
+        self.ctx.set_line_width(f64::from(width));
+        self.ctx.set_line_cap("round");
+        self.ctx.set_line_join("round");
+        self.ctx.begin_path();
+        for (i, [x, y]) in points.iter().enumerate() {
+            if i == 0 {
+                self.ctx.move_to(f64::from(*x), f64::from(*y));
+            } else {
+                self.ctx.line_to(f64::from(*x), f64::from(*y));
+            }
+        }
+        self.ctx.stroke();
+        self.ctx.set_line_cap("butt");
+        self.ctx.set_line_join("miter");
+    }
+
+    /// Draw a sparkline's line and latest-value dot, or its bars, across
+    /// `area` (`[x, y, width, height]`), matching the SVG export.
+    fn draw_sparkline(&self, values: &[f32], kind: SparklineKind, area: [f32; 4], color: &str) {
+        match kind {
+            SparklineKind::Line => {
+                let points = sparkline_points(values, area);
+                self.ctx.begin_path();
+                for (i, (px, py)) in points.iter().enumerate() {
+                    if i == 0 {
+                        self.ctx.move_to(f64::from(*px), f64::from(*py));
+                    } else {
+                        self.ctx.line_to(f64::from(*px), f64::from(*py));
+                    }
+                }
+                self.ctx.set_line_width(1.5);
+                self.ctx.set_line_join("round");
+                self.ctx.set_stroke_style_str(color);
+                self.ctx.stroke();
+                self.ctx.set_line_join("miter");
+                if let Some((px, py)) = points.last() {
+                    self.ctx.begin_path();
+                    let _ = self.ctx.arc(
+                        f64::from(*px),
+                        f64::from(*py),
+                        f64::from(SPARKLINE_DOT_RADIUS),
+                        0.0,
+                        std::f64::consts::TAU,
+                    );
+                    self.ctx.set_fill_style_str(color);
+                    self.ctx.fill();
+                }
+            }
+            SparklineKind::Bar => {
+                self.ctx.set_fill_style_str(color);
+                for [bx, by, width, height] in sparkline_bars(values, area) {
+                    self.ctx.fill_rect(
+                        f64::from(bx),
+                        f64::from(by),
+                        f64::from(width),
+                        f64::from(height),
+                    );
+                }
+            }
+        }
+    }
+
+    /// Draw dice, a wheel or a shuffled list above a caption with the latest
+    /// result, matching the SVG export.
+    fn draw_randomizer(
+        &self,
+        t: &Transform,
+        source: &RandomSource,
+        outcome: Option<&RandomOutcome>,
+    ) {
+        let (x, y) = (f64::from(t.x), f64::from(t.y));
+        let layout = RandomizerLayout::new(t.width, t.height);
+        let values = outcome.map(|o| o.values.as_slice());
+
+        self.ctx.set_text_baseline("middle");
+        match source {
+            RandomSource::Dice { count, .. } => {
+                self.ctx.set_text_align("center");
+                self.ctx.set_line_width(2.0);
+                self.ctx.set_stroke_style_str("#37474f");
+                for (i, (dx, dy, size)) in layout.dice(*count).into_iter().enumerate() {
+                    let (dx, dy, size) = (x + f64::from(dx), y + f64::from(dy), f64::from(size));
+                    self.ctx.set_fill_style_str("#ffffff");
+                    self.ctx.fill_rect(dx, dy, size, size);
+                    self.ctx.stroke_rect(dx, dy, size, size);
+                    let face = values
+                        .and_then(|v| v.get(i))
+                        .map_or_else(|| "?".to_string(), ToString::to_string);
+                    self.ctx.set_fill_style_str("#263238");
+                    self.ctx.set_font(&format!("{}px sans-serif", size * 0.5));
+                    let _ = self.ctx.fill_text(&face, dx + size / 2.0, dy + size / 2.0);
+                }
+            }
+            RandomSource::Wheel { segments } => {
+                let (cx, cy, r) = layout.wheel();
+                let (cx, cy, r) = (x + f64::from(cx), y + f64::from(cy), f64::from(r));
+                let selected = values.and_then(|v| v.first().copied());
+                self.ctx.set_text_align("center");
+                self.ctx.set_line_width(1.0);
+                self.ctx.set_stroke_style_str("#ffffff");
+                self.ctx
+                    .set_font(&format!("{}px sans-serif", (r * 0.16).clamp(8.0, 16.0)));
+                for (i, label) in segments.iter().enumerate() {
+                    let (start, end) = RandomizerLayout::segment_arc(i, segments.len(), selected);
+                    let (start, end) = (f64::from(start), f64::from(end));
+                    self.ctx.begin_path();
+                    self.ctx.move_to(cx, cy);
+                    let _ = self.ctx.arc(cx, cy, r, start, end);
+                    self.ctx.close_path();
+                    self.ctx
+                        .set_fill_style_str(WHEEL_COLORS[i % WHEEL_COLORS.len()]);
+                    self.ctx.fill();
+                    self.ctx.stroke();
+
+                    let middle = f64::midpoint(start, end);
+                    self.ctx.set_fill_style_str("#263238");
+                    let _ = self.ctx.fill_text(
+                        label,
+                        cx + 0.62 * r * middle.cos(),
+                        cy + 0.62 * r * middle.sin(),
+                    );
+                }
+                // Pointer at the top
+                let tip = r * 0.12;
+                self.ctx.begin_path();
+                self.ctx.move_to(cx - tip, cy - r - tip);
+                self.ctx.line_to(cx + tip, cy - r - tip);
+                self.ctx.line_to(cx, cy - r + tip);
+                self.ctx.close_path();
+                self.ctx.set_fill_style_str("#37474f");
+                self.ctx.fill();
+            }
+            RandomSource::Shuffle { items } => {
+                self.ctx.set_text_align("start");
+                self.ctx.set_fill_style_str("#263238");
+                let order = source.list_order(outcome);
+                for (n, ((row_y, height), i)) in
+                    layout.rows(order.len()).into_iter().zip(order).enumerate()
+                {
+                    let height = f64::from(height);
+                    self.ctx
+                        .set_font(&format!("{}px sans-serif", (height * 0.6).clamp(8.0, 16.0)));
+                    let _ = self.ctx.fill_text(
+                        &format!("{}. {}", n + 1, items[i]),
+                        x + f64::from(layout.body_x),
+                        y + f64::from(row_y) + height / 2.0,
+                    );
+                }
+            }
+        }
+
+        let caption_y = y + f64::from(layout.caption_y);
+        let caption_height = f64::from(layout.caption_height);
+        self.ctx.set_fill_style_str("#37474f");
+        self.ctx
+            .fill_rect(x, caption_y, f64::from(t.width), caption_height);
+        self.ctx.set_text_align("center");
+        self.ctx.set_fill_style_str("#ffffff");
+        self.ctx
+            .set_font(&format!("{}px sans-serif", caption_height * 0.55));
+        let _ = self.ctx.fill_text(
+            &source.caption(outcome),
+            x + f64::from(t.width) / 2.0,
+            caption_y + caption_height / 2.0,
+        );
+        self.ctx.set_text_align("start");
+        self.ctx.set_text_baseline("alphabetic");
+    }
+
+    /// Draw a floor plan's outlines, markers and occupancy caption, matching
+    /// the SVG export. Background images are left to the export.
+    /// Draw a tree's connectors, node boxes and collapsed-node badges,
+    /// matching the SVG export.
+    fn draw_tree(&self, t: &Transform, root: &TreeNode, direction: TreeDirection) {
+        let (x, y) = (f64::from(t.x), f64::from(t.y));
+        let layout = TreeLayout::new(root, direction, t.width, t.height);
+        let scale = f64::from(layout.scale);
+
+        self.ctx.set_stroke_style_str("#90a4ae");
+        self.ctx.set_line_width(1.5 * scale);
+        for index in 0..layout.boxes.len() {
+            let Some(points) = layout.connector(index) else {
+                continue;
+            };
+            self.ctx.begin_path();
+            for (i, (px, py)) in points.into_iter().enumerate() {
+                let (px, py) = (x + f64::from(px), y + f64::from(py));
+                if i == 0 {
+                    self.ctx.move_to(px, py);
+                } else {
+                    self.ctx.line_to(px, py);
+                }
+            }
+            self.ctx.stroke();
+        }
+
+        self.ctx.set_text_align("center");
+        self.ctx.set_text_baseline("middle");
+        for (index, node_box) in layout.boxes.iter().enumerate() {
+            let node = node_box.node;
+            let (bx, by) = (x + f64::from(node_box.x), y + f64::from(node_box.y));
+            let (width, height) = (f64::from(node_box.width), f64::from(node_box.height));
+            let fill = node.color.as_ref().map_or_else(
+                || DEFAULT_NODE_COLOR.to_hex(),
+                |c| self.css_color(c, DEFAULT_NODE_COLOR),
+            );
+            self.trace_rounded_rect(bx, by, width, height, 6.0 * scale);
+            self.ctx.set_fill_style_str(&fill);
+            self.ctx.fill();
+            self.ctx.set_line_width(1.0);
+            self.ctx.set_stroke_style_str("#607d8b");
+            self.ctx.stroke();
+
+            let center_x = bx + width / 2.0;
+            let (label_y, detail_y) = node_box.text_y();
+            self.ctx.set_fill_style_str("#263238");
+            self.ctx
+                .set_font(&format!("bold {}px sans-serif", layout.label_font_size));
+            let _ = self
+                .ctx
+                .fill_text(&node.label, center_x, y + f64::from(label_y));
+            if let (Some(detail), Some(detail_y)) = (&node.detail, detail_y) {
+                self.ctx.set_fill_style_str("#546e7a");
+                self.ctx
+                    .set_font(&format!("{}px sans-serif", layout.detail_font_size));
+                let _ = self
+                    .ctx
+                    .fill_text(detail, center_x, y + f64::from(detail_y));
+            }
+            if let Some((badge_x, badge_y, radius)) = layout.badge(index) {
+                let (badge_x, badge_y) = (x + f64::from(badge_x), y + f64::from(badge_y));
+                self.ctx.begin_path();
+                let _ = self.ctx.arc(
+                    badge_x,
+                    badge_y,
+                    f64::from(radius),
+                    0.0,
+                    std::f64::consts::TAU,
+                );
+                self.ctx.set_fill_style_str("#546e7a");
+                self.ctx.fill();
+                self.ctx.set_fill_style_str("#ffffff");
+                self.ctx.set_font(&format!("{radius}px sans-serif"));
+                let _ = self
+                    .ctx
+                    .fill_text(&format!("+{}", node_box.hidden), badge_x, badge_y);
+            }
+        }
+        self.ctx.set_text_align("start");
+        self.ctx.set_text_baseline("alphabetic");
+    }
+
+    fn draw_floor_plan(
+        &self,
+        t: &Transform,
+        layout: &FloorPlanLayout,
+        paths: &[PlanPath],
+        markers: &[FloorMarker],
+    ) {
+        let (x, y) = (f64::from(t.x), f64::from(t.y));
+        let point = |px: f32, py: f32| {
+            let (lx, ly) = layout.to_local(px, py);
+            (x + f64::from(lx), y + f64::from(ly))
+        };
+        self.ctx.set_fill_style_str("#ffffff");
+        self.ctx.fill_rect(
+            x + f64::from(layout.offset_x),
+            y + f64::from(layout.offset_y),
+            f64::from(layout.plan_width),
+            f64::from(layout.plan_height),
+        );
+
+        self.ctx.set_stroke_style_str("#455a64");
+        self.ctx.set_line_width(2.0);
+        for path in paths {
+            self.ctx.begin_path();
+            for (i, [px, py]) in path.points.iter().enumerate() {
+                let (px, py) = point(*px, *py);
+                if i == 0 {
+                    self.ctx.move_to(px, py);
+                } else {
+                    self.ctx.line_to(px, py);
+                }
+            }
+            if path.closed {
+                self.ctx.close_path();
+            }
+            self.ctx.stroke();
+        }
+
+        self.ctx.set_text_align("center");
+        self.ctx.set_text_baseline("middle");
+        for marker in markers {
+            let rect = layout.marker_rect(marker);
+            let (mx, my) = (x + f64::from(rect.x), y + f64::from(rect.y));
+            let (width, height) = (f64::from(rect.width), f64::from(rect.height));
+            let color = marker.occupancy.color();
+            self.ctx.set_fill_style_str(color);
+            match marker.shape {
+                MarkerShape::Seat => {
+                    self.ctx.begin_path();
+                    let _ = self.ctx.ellipse(
+                        mx + width / 2.0,
+                        my + height / 2.0,
+                        width / 2.0,
+                        height / 2.0,
+                        0.0,
+                        0.0,
+                        std::f64::consts::TAU,
+                    );
+                    self.ctx.fill();
+                    self.ctx.set_line_width(1.0);
+                    self.ctx.set_stroke_style_str("#37474f");
+                    self.ctx.stroke();
+                }
+                MarkerShape::Zone => {
+                    let alpha = self.ctx.global_alpha();
+                    self.ctx.set_global_alpha(alpha * 0.35);
+                    self.ctx.fill_rect(mx, my, width, height);
+                    self.ctx.set_global_alpha(alpha);
+                    self.ctx.set_line_width(2.0);
+                    self.ctx.set_stroke_style_str(color);
+                    self.ctx.stroke_rect(mx, my, width, height);
+                }
+            }
+            self.ctx.set_fill_style_str("#263238");
+            self.ctx
+                .set_font(&format!("{}px sans-serif", rect.font_size()));
+            let _ = self
+                .ctx
+                .fill_text(marker.display_label(), mx + width / 2.0, my + height / 2.0);
+        }
+
+        let caption_height = f64::from(layout.caption_height);
+        self.ctx.set_fill_style_str("#546e7a");
+        self.ctx
+            .set_font(&format!("{}px sans-serif", caption_height * 0.6));
+        let _ = self.ctx.fill_text(
+            &OccupancySummary::from_markers(markers).caption(),
+            x + f64::from(t.width) / 2.0,
+            y + f64::from(layout.caption_y) + caption_height / 2.0,
+        );
+        self.ctx.set_text_align("start");
+        self.ctx.set_text_baseline("alphabetic");
+    }
+
+    fn get_element_color(&self, element: &Element) -> String {
+        match &element.kind {
+            ElementKind::Chart { .. } => "#e3f2fd".to_string(),
+            ElementKind::Image { .. } => "#f5f5f5".to_string(),
+            ElementKind::Model3D { .. } => "#e8f5e9".to_string(),
+            ElementKind::Video { .. } => "#212121".to_string(),
+            ElementKind::Shader { .. } => SHADER_PLACEHOLDER.to_string(),
+            ElementKind::Audio { .. } => "#263238".to_string(),
+            ElementKind::Embed { .. } => "#eef0f5".to_string(),
+            ElementKind::Barcode { .. }
+            | ElementKind::Calendar { .. }
+            | ElementKind::Poll { .. }
+            | ElementKind::Agenda { .. }
+            | ElementKind::Gauge { .. }
+            | ElementKind::Kpi { .. } => "#ffffff".to_string(),
+            ElementKind::Randomizer { .. } => "#fafafa".to_string(),
+            ElementKind::FloorPlan { .. } => "#f5f7f7".to_string(),
+            ElementKind::Shape { .. } => ElementKind::SHAPE_COLOR.to_hex(),
+            ElementKind::OverlayLayer { opacity, .. } => format!("rgba(255, 255, 255, {opacity})"),
+            ElementKind::Text { .. }
+            | ElementKind::StickyNote { .. }
+            | ElementKind::Callout { .. } => element
+                .kind
+                .resolved_color(&self.theme)
+                .map_or_else(|| ElementKind::TEXT_COLOR.to_hex(), Color::to_hex),
+            ElementKind::Math { .. }
+            | ElementKind::Sparkline { .. }
+            | ElementKind::Tree { .. }
+            | ElementKind::Ink { .. }
+            | ElementKind::Measurement { .. }
+            | ElementKind::Region { .. } => "rgba(255, 255, 255, 0)".to_string(),
+            ElementKind::Code { .. } => CODE_BACKGROUND.to_string(),
+            ElementKind::LogView { .. } => LOG_BACKGROUND.to_string(),
+            ElementKind::Group { .. } => "rgba(255, 253, 231, 0.5)".to_string(),
+        }
+    }
+
+    fn get_element_label(element: &Element) -> String {
+        match &element.kind {
+            ElementKind::Chart { chart_type, .. } => format!("Chart: {chart_type}"),
+            ElementKind::Image { .. } => "Image".to_string(),
+            ElementKind::Model3D { .. } => "3D Model".to_string(),
+            ElementKind::Shader { .. } => "Shader (needs WebGPU)".to_string(),
+            ElementKind::Barcode { payload, .. } => format!("Barcode: {payload}"),
+            ElementKind::Video { stream_id, .. } => format!("Video: {stream_id}"),
+            ElementKind::Audio { stream_id, .. } => format!("Audio: {stream_id}"),
+            ElementKind::Embed { url, .. } => format!("Embed: {url}"),
+            ElementKind::Calendar { date, .. } => format!("Calendar: {date}"),
+            ElementKind::Poll { question, .. } => format!("Poll: {question}"),
+            ElementKind::Agenda { title, .. } => format!("Agenda: {title}"),
+            ElementKind::Randomizer { source, .. } => format!("Randomizer: {}", source.mode()),
+            ElementKind::FloorPlan { markers, .. } => format!("Floor plan ({})", markers.len()),
+            ElementKind::Gauge { label, .. } => format!("Gauge: {label}"),
+            ElementKind::Kpi { label, .. } => format!("KPI: {label}"),
+            ElementKind::Sparkline { values, .. } => format!("Sparkline ({})", values.len()),
+            ElementKind::Tree { root, .. } => format!("Tree: {}", root.label),
+            ElementKind::Ink { points, .. } => format!("Ink ({} points)", points.len()),
+            ElementKind::Measurement { label, .. } => {
+                format!("Measurement: {}", label.as_deref().unwrap_or_default())
+            }
+            ElementKind::Region { caption, .. } => {
+                format!("Region: {}", caption.as_deref().unwrap_or_default())
+            }
+            ElementKind::Shape { shape } => format!("Shape: {}", shape.name()),
+            ElementKind::OverlayLayer { children, .. } => format!("Overlay ({})", children.len()),
+            ElementKind::Text { content, .. } => {
+                if content.len() > 20 {
+                    format!("{}...", &content[..20])
+                } else {
+                    content.clone()
+                }
+            }
+            ElementKind::Math { tex, .. } => tex.clone(),
+            ElementKind::Code { language, .. } => format!("Code: {language}"),
+            ElementKind::LogView { title, .. } => format!("Log: {title}"),
+            ElementKind::StickyNote { .. } => "Sticky note".to_string(),
+            ElementKind::Callout { .. } => "Callout".to_string(),
+            ElementKind::Group { children, .. } => format!("Group ({})", children.len()),
+        }
+    }
+}
+
+impl RenderBackend for DomCanvasBackend {
+    fn backend_type(&self) -> BackendType {
+        BackendType::Canvas2D
+    }
+
+    fn render(&mut self, scene: &Scene) -> RenderResult<()> {
+        if let Ok(mut state) = self.state.try_borrow_mut() {
             state.render_scene(scene);
         }
         Ok(())
@@ -352,6 +2235,13 @@ impl RenderBackend for DomCanvasBackend {
         }
         Ok(())
     }
+
+    fn frame_stats(&self) -> FrameStats {
+        self.state
+            .try_borrow()
+            .map(|state| state.last_frame)
+            .unwrap_or_default()
+    }
 }
 
 /// The main canvas application for WASM.
@@ -363,13 +2253,36 @@ pub struct CanvasApp {
     renderer_state: RendererHandle,
     renderer: Renderer,
     /// Holographic rendering configuration (None when not in holographic mode).
+    #[cfg(feature = "holographic")]
     holographic_config: Option<HolographicConfig>,
     /// Holographic renderer (lazily initialized).
+    #[cfg(feature = "holographic")]
     holographic_renderer: Option<HolographicRenderer>,
-    /// Camera for holographic rendering.
-    holographic_camera: Camera,
+    /// Orbit controller of the holographic camera.
+    #[cfg(feature = "holographic")]
+    holographic_camera: OrbitCamera,
+    /// When the holographic camera last eased towards its goal.
+    #[cfg(feature = "holographic")]
+    holographic_camera_ms: Option<f64>,
     /// Input fusion processor for touch+voice combination.
+    #[cfg(feature = "fusion")]
     input_fusion: InputFusion,
+    /// Camera controller that follows agent changes.
+    agent_follow: AgentFollow,
+    /// Spotlight seen on the previous frame, to detect changes.
+    last_spotlight: Option<Spotlight>,
+    /// Whether the user has moved the camera since the spotlight changed.
+    spotlight_camera_released: bool,
+    /// "Follow the presenter" camera sharing.
+    presenter_follow: PresenterFollow,
+    /// Declarative element interactions (tap actions, drags, context menus).
+    interactions: InteractionRouter,
+    /// Idle detection for the power-saving render loop.
+    idle: IdleTracker,
+    /// Scene revision drawn by the last frame.
+    rendered_revision: u64,
+    /// Called when the render loop should restart after going idle.
+    wake_callback: Option<js_sys::Function>,
 }
 
 #[wasm_bindgen]
@@ -403,7 +2316,12 @@ impl CanvasApp {
         let height = canvas.height();
 
         #[allow(clippy::cast_precision_loss)]
-        let scene = Scene::new(width as f32, height as f32);
+        let mut scene = Scene::new(width as f32, height as f32);
+        scene.set_transition_config(
+            TransitionConfig::default()
+                .with_enter(TransitionEffect::FadeScale)
+                .with_exit(TransitionEffect::Fade),
+        );
 
         let renderer_state = Rc::new(RefCell::new(DomRendererState::new(canvas, ctx)));
 
@@ -419,7 +2337,8 @@ impl CanvasApp {
                 preferred_backend,
                 ..RendererConfig::default()
             },
-        );
+        )
+        .with_clock(performance_now_ms);
 
         Ok(Self {
             scene,
@@ -427,193 +2346,1005 @@ impl CanvasApp {
             frame_count: 0,
             renderer_state,
             renderer,
+            #[cfg(feature = "holographic")]
             holographic_config: None,
+            #[cfg(feature = "holographic")]
             holographic_renderer: None,
-            holographic_camera: Camera::default(),
-            input_fusion: InputFusion::new(),
+            #[cfg(feature = "holographic")]
+            holographic_camera: OrbitCamera::new(&Camera::default()),
+            #[cfg(feature = "holographic")]
+            holographic_camera_ms: None,
+            #[cfg(feature = "fusion")]
+            input_fusion: InputFusion::new().with_clock(performance_now_ms),
+            agent_follow: AgentFollow::new(),
+            last_spotlight: None,
+            spotlight_camera_released: false,
+            presenter_follow: PresenterFollow::new(),
+            interactions: InteractionRouter::new(),
+            idle: IdleTracker::new(),
+            rendered_revision: 0,
+            wake_callback: None,
         })
     }
 
     /// Render the current scene to the canvas.
     pub fn render(&mut self) {
-        if let Err(err) = self.renderer.render(&self.scene) {
-            tracing::error!("Renderer error: {:?}", err);
+        let now = now_ms();
+        let camera = CameraFrame::from_scene(&self.scene);
+        // Spotlight zoom wins over the presenter, who wins over the agent
+        if !self.update_spotlight_camera(now) && !self.presenter_follow.update(&mut self.scene, now)
+        {
+            self.agent_follow.update(&mut self.scene, now);
         }
-        self.frame_count += 1;
+        let transitioning = self.scene.tick(now);
+        let animating = transitioning || !camera.approx_eq(&CameraFrame::from_scene(&self.scene));
+
+        // Wake up again when the spotlight expires or following resumes
+        if let Some(expires) = self.scene.spotlight().and_then(|s| s.expires_at_ms) {
+            self.idle.wake_at(expires);
+        }
+        if self.agent_follow.is_enabled() && self.agent_follow.is_paused(now) {
+            self.idle.wake_at(self.agent_follow.paused_until_ms());
+        }
+        // Redraw running agenda clocks on the next whole second
+        if let Some(server_now) = self.ticking_agenda_clock() {
+            self.idle.wake_at(now + 1000 - server_now % 1000);
+        }
+
+        #[cfg(feature = "fusion")]
+        if let Ok(mut state) = self.renderer_state.try_borrow_mut() {
+            state.badges = self
+                .input_fusion
+                .pending_disambiguation()
+                .map(|request| {
+                    request
+                        .candidates
+                        .iter()
+                        .map(|c| (c.number, c.bounds))
+                        .collect()
+                })
+                .unwrap_or_default();
+        }
+
+        if let Err(err) = self.renderer.render(&self.scene) {
+            tracing::error!("Renderer error: {:?}", err);
+        }
+        self.rendered_revision = self.scene.revision();
+        self.idle.frame_rendered(now, animating);
+        self.frame_count += 1;
+    }
+
+    /// The server time if an agenda on the scene is counting down or
+    /// running, so its clock needs redrawing every second.
+    fn ticking_agenda_clock(&self) -> Option<u64> {
+        let server_now = self.renderer_state.try_borrow().ok()?.server_now_ms();
+        self.scene
+            .elements()
+            .filter_map(|element| AgendaStatus::from_kind(&element.kind, server_now))
+            .any(|status| status.is_ticking())
+            .then_some(server_now)
+    }
+
+    /// Tell the app the server's current time in milliseconds since the Unix
+    /// epoch, e.g. the `timestamp` of the `welcome` message, so agenda
+    /// timers agree with other participants.
+    #[wasm_bindgen(js_name = setServerTime)]
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn set_server_time(&mut self, server_ms: f64) {
+        self.invalidate();
+        if let Ok(mut state) = self.renderer_state.try_borrow_mut() {
+            state.clock_offset_ms = server_ms as i64 - now_ms().cast_signed();
+        }
+    }
+
+    /// Check whether the next animation frame needs rendering.
+    ///
+    /// Call once per animation frame. After a few seconds without input,
+    /// sync traffic or camera animation this returns `false` until the scene
+    /// changes; the host can then stop requesting frames and restart from
+    /// the callback passed to `setWakeCallback`.
+    #[wasm_bindgen(js_name = needsRender)]
+    pub fn needs_render(&mut self) -> bool {
+        // Catch scene edits that did not go through `invalidate`
+        if self.scene.revision() != self.rendered_revision {
+            self.idle.invalidate();
+        }
+        self.idle.needs_render(now_ms())
+    }
+
+    /// Check whether the app is idle (no recent input, sync or animation).
+    #[wasm_bindgen(js_name = isIdle)]
+    #[must_use]
+    pub fn is_idle(&self) -> bool {
+        self.idle.is_idle(now_ms())
+    }
+
+    /// Set a function called when the render loop should restart after
+    /// `needsRender` returned `false`. Pass `undefined` to clear it.
+    #[wasm_bindgen(js_name = setWakeCallback)]
+    pub fn set_wake_callback(&mut self, callback: Option<js_sys::Function>) {
+        self.wake_callback = callback;
+    }
+
+    /// Check whether to capture and upload a new frame of a video stream.
+    ///
+    /// Always `true` while active; while idle, streams are throttled to one
+    /// frame per idle video interval.
+    #[wasm_bindgen(js_name = wantsVideoFrame)]
+    pub fn wants_video_frame(&mut self, stream_id: &str) -> bool {
+        self.idle.accept_video_frame(stream_id, now_ms())
+    }
+
+    /// Configure idle detection: time without activity before going idle and
+    /// the minimum interval between video frames while idle, both in
+    /// milliseconds.
+    #[wasm_bindgen(js_name = configureIdle)]
+    pub fn configure_idle(&mut self, idle_after_ms: u32, idle_video_interval_ms: u32) {
+        self.idle.set_config(IdleConfig {
+            idle_after_ms: u64::from(idle_after_ms),
+            idle_video_interval_ms: u64::from(idle_video_interval_ms),
+        });
+        self.invalidate();
+    }
+
+    /// Handle a touch event at the given coordinates.
+    #[wasm_bindgen(js_name = handleTouch)]
+    pub fn handle_touch(&mut self, x: f32, y: f32, phase: &str) -> Option<String> {
+        self.record_activity();
+        // Find element at touch location
+        let element_id = self.scene.element_at(x, y);
+
+        // Parse touch phase (default to Start for unknown phases)
+        let touch_phase = match phase {
+            "move" | "moved" => TouchPhase::Move,
+            "end" | "ended" => TouchPhase::End,
+            "cancel" | "cancelled" => TouchPhase::Cancel,
+            _ => TouchPhase::Start,
+        };
+
+        // Create touch point
+        let touch_point = TouchPoint {
+            id: 0,
+            x,
+            y,
+            pressure: None,
+            radius: None,
+        };
+
+        // Create touch event with target element for fusion
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let timestamp_ms = performance_now_ms() as u64;
+        let mut touch_event = TouchEvent::new(touch_phase, vec![touch_point], timestamp_ms);
+        touch_event.target_element = element_id;
+        let event = InputEvent::Touch(touch_event.clone());
+
+        // Process through fusion system (only Start events are stored for
+        // fusion), with the elements near the touch in case it is ambiguous
+        #[cfg(feature = "fusion")]
+        {
+            let candidates: Vec<_> = self
+                .scene
+                .elements_near(x, y, DISAMBIGUATION_RADIUS)
+                .into_iter()
+                .map(|(e, _)| {
+                    let t = &e.transform;
+                    (e.id, [t.x, t.y, t.width, t.height])
+                })
+                .collect();
+            let _ = self
+                .input_fusion
+                .process_touch_near(&touch_event, candidates);
+        }
+
+        // Process the event in state; a touch end also finishes any
+        // interaction drag
+        self.state.process_event(&event);
+        self.interactions.handle(&mut self.scene, &event);
+
+        // Direct manipulation takes the camera back from the agent for a while
+        self.agent_follow.record_user_interaction(now_ms());
+        self.spotlight_camera_released = true;
+
+        // A tap selects the touched element, keeping a multi-selection it
+        // belongs to so the group can be dragged, and clears on empty space
+        if touch_phase == TouchPhase::Start {
+            match element_id {
+                Some(id) if self.state.selection.contains(id) => {}
+                Some(id) => {
+                    self.state.selection.select(&mut self.scene, id);
+                }
+                None => {
+                    self.state.selection.clear(&mut self.scene);
+                }
+            }
+        }
+        element_id.map(|id| id.to_string())
+    }
+
+    /// Handle a recognized gesture (tap, long press, drag, lasso) serialized
+    /// as JSON.
+    ///
+    /// If the gesture hits an element with a matching declared interaction,
+    /// returns the `interaction` sync message to send on `/ws/sync` so the
+    /// agent receives it. Drags move the element locally. A lasso
+    /// (`{"gesture":"Lasso","data":{"points":[[x,y],...]}}`) is held for
+    /// fusion, so the next voice command is scoped to the elements it
+    /// circled.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the gesture JSON is invalid.
+    #[wasm_bindgen(js_name = handleGesture)]
+    pub fn handle_gesture(&mut self, json: &str) -> Result<Option<String>, JsValue> {
+        self.record_activity();
+        let gesture: Gesture =
+            serde_json::from_str(json).map_err(|e| JsValue::from_str(&e.to_string()))?;
+        #[cfg(feature = "fusion")]
+        if let Some(region) = gesture.region() {
+            let elements: Vec<_> = self
+                .scene
+                .elements_in_region(region)
+                .into_iter()
+                .map(|e| e.id)
+                .collect();
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            let timestamp_ms = performance_now_ms() as u64;
+            let _ = self
+                .input_fusion
+                .process_gesture(&gesture, timestamp_ms, elements);
+        }
+        let Some(interaction) = self
+            .interactions
+            .handle(&mut self.scene, &InputEvent::Gesture(gesture))
+        else {
+            return Ok(None);
+        };
+        self.agent_follow.record_user_interaction(now_ms());
+        serde_json::to_string(&interaction.to_client_message(None))
+            .map(Some)
+            .map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// Handle a mouse click at the given coordinates.
+    #[wasm_bindgen(js_name = handleClick)]
+    pub fn handle_click(&mut self, x: f32, y: f32) -> Option<String> {
+        self.handle_touch(x, y, "start")
+    }
+
+    /// Add an element to the scene from JSON.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if JSON parsing fails.
+    #[wasm_bindgen(js_name = addElement)]
+    pub fn add_element(&mut self, json: &str) -> Result<String, JsValue> {
+        self.invalidate();
+        let element: Element =
+            serde_json::from_str(json).map_err(|e| JsValue::from_str(&e.to_string()))?;
+        let id = element.id;
+        self.scene.add_element(element);
+        Ok(id.to_string())
+    }
+
+    /// Remove an element from the scene.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the element is not found.
+    #[wasm_bindgen(js_name = removeElement)]
+    pub fn remove_element(&mut self, id: &str) -> Result<(), JsValue> {
+        self.invalidate();
+        let uuid = uuid::Uuid::parse_str(id).map_err(|e| JsValue::from_str(&e.to_string()))?;
+        let element_id = ElementId::from_uuid(uuid);
+        self.scene
+            .remove_element(&element_id)
+            .map_err(|e| JsValue::from_str(&e.to_string()))?;
+        self.state.selection.remove(&mut self.scene, element_id);
+        Ok(())
+    }
+
+    /// Get the current scene as JSON.
+    #[wasm_bindgen(js_name = getSceneJson)]
+    #[must_use]
+    pub fn get_scene_json(&self) -> String {
+        serde_json::to_string(&self.scene).unwrap_or_default()
+    }
+
+    /// Update the entire scene from JSON.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if JSON parsing fails.
+    #[wasm_bindgen(js_name = setSceneJson)]
+    pub fn set_scene_json(&mut self, json: &str) -> Result<(), JsValue> {
+        self.record_activity();
+        let mut scene = Scene::from_json(json).map_err(|e| JsValue::from_str(&e.to_string()))?;
+        scene.supersede(&self.scene);
+        self.scene = scene;
+        self.state.selection.sync(&mut self.scene);
+        if let Ok(mut state) = self.renderer_state.try_borrow_mut() {
+            state.clear_dynamic_content();
+        }
+        Ok(())
+    }
+
+    /// Apply a canonical scene document serialized as JSON.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the JSON is invalid or the scene cannot be converted.
+    #[wasm_bindgen(js_name = applySceneDocument)]
+    pub fn apply_scene_document(&mut self, json: &str) -> Result<(), JsValue> {
+        self.record_activity();
+        let document: SceneDocument = serde_json::from_str(json)
+            .map_err(|e| JsValue::from_str(&format!("Scene parse error: {e}")))?;
+        let mut scene = document
+            .into_scene()
+            .map_err(|e| JsValue::from_str(&format!("Scene conversion error: {e}")))?;
+
+        if self.agent_follow.is_enabled() {
+            // Keep the local camera; the follow controller owns it
+            CameraFrame::from_scene(&self.scene).apply_to(&mut scene);
+            scene.set_viewport(self.scene.viewport_width, self.scene.viewport_height);
+            let changed = changed_element_ids(&self.scene, &scene);
+            self.agent_follow.record_agent_changes(changed);
+        }
+        scene.supersede(&self.scene);
+        self.scene = scene;
+        self.state.selection.sync(&mut self.scene);
+        if let Ok(mut state) = self.renderer_state.try_borrow_mut() {
+            state.clear_dynamic_content();
+        }
+        Ok(())
+    }
+
+    /// Apply an incremental scene patch serialized as JSON.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the JSON is invalid or the patch cannot be applied.
+    #[wasm_bindgen(js_name = applyScenePatch)]
+    pub fn apply_scene_patch(&mut self, json: &str) -> Result<(), JsValue> {
+        self.record_activity();
+        let mut patch: ScenePatch = serde_json::from_str(json)
+            .map_err(|e| JsValue::from_str(&format!("Patch parse error: {e}")))?;
+
+        if self.agent_follow.is_enabled() {
+            // Keep the local camera; the follow controller owns it
+            patch.viewport = None;
+            let changed = patch
+                .ops
+                .iter()
+                .filter_map(|op| match op {
+                    PatchOp::Add { element } | PatchOp::Update { element } => {
+                        ElementId::parse(&element.id).ok()
+                    }
+                    PatchOp::LogAppend { id, .. } => ElementId::parse(id).ok(),
+                    PatchOp::Remove { .. }
+                    | PatchOp::Spotlight { .. }
+                    | PatchOp::Anchors { .. }
+                    | PatchOp::Theme { .. }
+                    | PatchOp::Branding { .. }
+                    | PatchOp::Coordinates { .. }
+                    | PatchOp::Data { .. } => None,
+                })
+                .collect::<Vec<_>>();
+            self.agent_follow.record_agent_changes(changed);
+        }
+        self.scene
+            .apply_patch(&patch)
+            .map_err(|e| JsValue::from_str(&format!("Patch apply error: {e}")))?;
+        self.state.selection.sync(&mut self.scene);
+        if let Ok(mut state) = self.renderer_state.try_borrow_mut() {
+            state.clear_dynamic_content();
+        }
+        Ok(())
+    }
+
+    /// Add a page of a progressive scene load, serialized as JSON.
+    ///
+    /// Returns `true` once the final page has been applied.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the JSON is invalid or the page cannot be applied.
+    #[wasm_bindgen(js_name = applyScenePage)]
+    pub fn apply_scene_page(&mut self, json: &str) -> Result<bool, JsValue> {
+        self.record_activity();
+        let page: ScenePage = serde_json::from_str(json)
+            .map_err(|e| JsValue::from_str(&format!("Page parse error: {e}")))?;
+        self.scene
+            .apply_page(&page)
+            .map_err(|e| JsValue::from_str(&format!("Page apply error: {e}")))?;
+        self.state.selection.sync(&mut self.scene);
+        if let Ok(mut state) = self.renderer_state.try_borrow_mut() {
+            state.clear_dynamic_content();
+        }
+        Ok(page.is_last())
+    }
+
+    /// Get the number of elements in the scene.
+    #[wasm_bindgen(js_name = elementCount)]
+    #[must_use]
+    pub fn element_count(&self) -> usize {
+        self.scene.element_count()
+    }
+
+    /// Get renderer statistics for performance telemetry.
+    ///
+    /// Returns a JS object with: backend, framesRendered, framesSkipped,
+    /// elementsTotal, elementsDrawn, elementsCulled, drawCalls, triangles,
+    /// textureBytes, frameTimeMs, avgFrameTimeMs, peakFrameTimeMs, gpuTimeMs
+    /// (null where the backend cannot time the GPU). Counts are from the
+    /// most recent frame.
+    #[wasm_bindgen(js_name = getRenderStats)]
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)] // Stats counters unlikely to exceed 2^52
+    pub fn get_render_stats(&self) -> JsValue {
+        let stats = self.renderer.stats();
+        let obj = js_sys::Object::new();
+        js_set_property(&obj, "backend", &JsValue::from_str(stats.backend.name()));
+        js_set_property(
+            &obj,
+            "framesRendered",
+            &JsValue::from_f64(stats.frames_rendered as f64),
+        );
+        js_set_property(
+            &obj,
+            "framesSkipped",
+            &JsValue::from_f64(stats.frames_skipped as f64),
+        );
+        js_set_property(
+            &obj,
+            "elementsTotal",
+            &JsValue::from_f64(stats.last_frame.elements_total as f64),
+        );
+        js_set_property(
+            &obj,
+            "elementsDrawn",
+            &JsValue::from_f64(stats.last_frame.elements_drawn as f64),
+        );
+        js_set_property(
+            &obj,
+            "elementsCulled",
+            &JsValue::from_f64(stats.last_frame.elements_culled as f64),
+        );
+        js_set_property(
+            &obj,
+            "drawCalls",
+            &JsValue::from_f64(stats.last_frame.draw_calls as f64),
+        );
+        js_set_property(
+            &obj,
+            "triangles",
+            &JsValue::from_f64(stats.last_frame.triangles as f64),
+        );
+        js_set_property(
+            &obj,
+            "textureBytes",
+            &JsValue::from_f64(stats.last_frame.texture_bytes as f64),
+        );
+        js_set_property(&obj, "frameTimeMs", &JsValue::from_f64(stats.frame_time_ms));
+        js_set_property(
+            &obj,
+            "avgFrameTimeMs",
+            &JsValue::from_f64(stats.avg_frame_time_ms),
+        );
+        js_set_property(
+            &obj,
+            "peakFrameTimeMs",
+            &JsValue::from_f64(stats.peak_frame_time_ms),
+        );
+        js_set_property(
+            &obj,
+            "gpuTimeMs",
+            &stats.gpu_time_ms.map_or(JsValue::NULL, JsValue::from_f64),
+        );
+        obj.into()
+    }
+
+    /// Show or hide the render statistics overlay in the top-left corner.
+    #[wasm_bindgen(js_name = setDebugOverlay)]
+    pub fn set_debug_overlay(&mut self, enabled: bool) {
+        self.invalidate();
+        self.renderer.set_debug_overlay(enabled);
+    }
+
+    /// Reset renderer statistics.
+    #[wasm_bindgen(js_name = resetRenderStats)]
+    pub fn reset_render_stats(&mut self) {
+        self.renderer.reset_stats();
+    }
+
+    /// Get the current frame count.
+    #[wasm_bindgen(js_name = frameCount)]
+    #[must_use]
+    pub fn frame_count(&self) -> u64 {
+        self.frame_count
+    }
+
+    /// Resize the canvas.
+    #[allow(clippy::cast_precision_loss)]
+    pub fn resize(&mut self, width: u32, height: u32) {
+        self.invalidate();
+        if let Ok(mut state) = self.renderer_state.try_borrow_mut() {
+            state.resize(width, height);
+        }
+        if let Err(err) = self.renderer.resize(width, height) {
+            tracing::warn!("Renderer resize failed: {:?}", err);
+        }
+        self.scene.set_viewport(width as f32, height as f32);
+    }
+
+    /// Set the background color (CSS color string).
+    #[wasm_bindgen(js_name = setBackgroundColor)]
+    pub fn set_background_color(&mut self, color: &str) {
+        self.invalidate();
+        if let Ok(mut state) = self.renderer_state.try_borrow_mut() {
+            state.set_background_color(color);
+        }
+    }
+
+    /// Enable or disable "follow the agent" mode.
+    ///
+    /// While enabled, the camera pans and zooms to frame elements changed by
+    /// incoming scene documents. User interaction pauses following briefly.
+    #[wasm_bindgen(js_name = setFollowAgent)]
+    pub fn set_follow_agent(&mut self, enabled: bool) {
+        self.invalidate();
+        self.agent_follow.set_enabled(enabled);
+    }
+
+    /// Check whether "follow the agent" mode is enabled.
+    #[wasm_bindgen(js_name = isFollowingAgent)]
+    #[must_use]
+    pub fn is_following_agent(&self) -> bool {
+        self.agent_follow.is_enabled()
+    }
+
+    /// Configure how elements added or removed by sync ease in and out.
+    ///
+    /// `json` is a transition config, e.g.
+    /// `{"duration_ms": 300, "easing": "ease_in_out", "enter": "fade_scale", "exit": "fade"}`;
+    /// effects of `"none"` turn transitions off. Defaults to a 200ms fade
+    /// and scale in and fade out.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the JSON is invalid.
+    #[wasm_bindgen(js_name = setTransitions)]
+    pub fn set_transitions(&mut self, json: &str) -> Result<(), JsValue> {
+        let config: TransitionConfig = serde_json::from_str(json)
+            .map_err(|e| JsValue::from_str(&format!("Invalid transitions: {e}")))?;
+        self.invalidate();
+        self.scene.set_transition_config(config);
+        Ok(())
+    }
+
+    /// Notify the app that the user moved the camera (pan/zoom gesture),
+    /// pausing "follow the agent" and "follow the presenter" modes.
+    #[wasm_bindgen(js_name = notifyUserCameraInteraction)]
+    pub fn notify_user_camera_interaction(&mut self) {
+        self.record_activity();
+        let now = now_ms();
+        self.agent_follow.record_user_interaction(now);
+        self.presenter_follow.record_user_interaction(now);
+        self.spotlight_camera_released = true;
+    }
+
+    /// Get this viewer's current view as JSON, for sending as a presenter.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if serialization fails.
+    #[wasm_bindgen(js_name = getViewStateJson)]
+    pub fn get_view_state_json(&self) -> Result<String, JsValue> {
+        serde_json::to_string(&ViewState::from_scene(&self.scene))
+            .map_err(|e| JsValue::from_str(&format!("Serialization error: {e}")))
+    }
+
+    /// Apply a `view_state` received from the session presenter.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the view JSON is invalid.
+    #[wasm_bindgen(js_name = applyViewState)]
+    pub fn apply_view_state(&mut self, presenter_id: &str, view_json: &str) -> Result<(), JsValue> {
+        self.record_activity();
+        let view: ViewState = serde_json::from_str(view_json)
+            .map_err(|e| JsValue::from_str(&format!("Invalid view state: {e}")))?;
+        self.presenter_follow.receive(presenter_id, view);
+        Ok(())
+    }
+
+    /// Handle a `presenter_stopped` message. Returns `true` if the camera was
+    /// following that presenter.
+    #[wasm_bindgen(js_name = presenterStopped)]
+    pub fn presenter_stopped(&mut self, presenter_id: &str) -> bool {
+        self.invalidate();
+        self.presenter_follow.presenter_stopped(presenter_id)
+    }
+
+    /// Get the ID of the current presenter, if anyone is presenting.
+    #[wasm_bindgen(js_name = presenterId)]
+    #[must_use]
+    pub fn presenter_id(&self) -> Option<String> {
+        self.presenter_follow.presenter_id().map(str::to_string)
+    }
+
+    /// Opt in to or out of mirroring the presenter's camera.
+    #[wasm_bindgen(js_name = setFollowPresenter)]
+    pub fn set_follow_presenter(&mut self, enabled: bool) {
+        self.invalidate();
+        self.presenter_follow.set_enabled(enabled);
+    }
+
+    /// Check whether this viewer mirrors the presenter's camera.
+    #[wasm_bindgen(js_name = isFollowingPresenter)]
+    #[must_use]
+    pub fn is_following_presenter(&self) -> bool {
+        self.presenter_follow.is_enabled()
+    }
+
+    /// Spotlight elements by ID, dimming everything else.
+    ///
+    /// `element_ids_json` is a JSON array of element ID strings. A
+    /// `timeout_ms` of zero keeps the spotlight until it is cleared.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the JSON is invalid or an element is not found.
+    #[wasm_bindgen(js_name = setSpotlight)]
+    pub fn set_spotlight(
+        &mut self,
+        element_ids_json: &str,
+        zoom_to_fit: bool,
+        timeout_ms: u32,
+    ) -> Result<(), JsValue> {
+        self.invalidate();
+        let ids: Vec<String> = serde_json::from_str(element_ids_json)
+            .map_err(|e| JsValue::from_str(&format!("Invalid element IDs: {e}")))?;
+        let ids = ids
+            .iter()
+            .map(|id| ElementId::parse(id))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| JsValue::from_str(&format!("Invalid element ID: {e}")))?;
+        self.apply_spotlight(ids, zoom_to_fit, timeout_ms)
+    }
+
+    /// Spotlight the elements matching a description (e.g. "revenue chart").
+    ///
+    /// Returns the number of spotlighted elements; the current spotlight is
+    /// left unchanged when nothing matches.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the spotlight cannot be applied.
+    #[wasm_bindgen(js_name = spotlightMatching)]
+    pub fn spotlight_matching(
+        &mut self,
+        query: &str,
+        zoom_to_fit: bool,
+        timeout_ms: u32,
+    ) -> Result<usize, JsValue> {
+        self.invalidate();
+        let ids = match_elements(&self.scene, query);
+        if ids.is_empty() {
+            return Ok(0);
+        }
+        let count = ids.len();
+        self.apply_spotlight(ids, zoom_to_fit, timeout_ms)?;
+        Ok(count)
+    }
+
+    /// Clear the spotlight.
+    #[wasm_bindgen(js_name = clearSpotlight)]
+    pub fn clear_spotlight(&mut self) {
+        self.invalidate();
+        self.scene.clear_spotlight();
+    }
+
+    /// Check whether a spotlight is active.
+    #[wasm_bindgen(js_name = hasSpotlight)]
+    #[must_use]
+    pub fn has_spotlight(&self) -> bool {
+        self.scene
+            .spotlight()
+            .is_some_and(|spotlight| !spotlight.is_expired(now_ms()))
+    }
+
+    /// Zoom and pan so every element is visible.
+    #[wasm_bindgen(js_name = fitToContent)]
+    pub fn fit_to_content(&mut self) {
+        self.invalidate();
+        // Fitting content never fails; an empty scene resets the view
+        let _ = self.run_camera_command(&CameraCommand::FitToContent);
+    }
+
+    /// Zoom and pan so the given elements are visible.
+    ///
+    /// `element_ids_json` is a JSON array of element ID strings.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the JSON is invalid or none of the elements exist.
+    #[wasm_bindgen(js_name = fitToElements)]
+    pub fn fit_to_elements(&mut self, element_ids_json: &str) -> Result<(), JsValue> {
+        self.invalidate();
+        let ids: Vec<String> = serde_json::from_str(element_ids_json)
+            .map_err(|e| JsValue::from_str(&format!("Invalid element IDs: {e}")))?;
+        let element_ids = ids
+            .iter()
+            .map(|id| ElementId::parse(id))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| JsValue::from_str(&format!("Invalid element ID: {e}")))?;
+        self.run_camera_command(&CameraCommand::FitToElements { element_ids })
     }
 
-    /// Handle a touch event at the given coordinates.
-    #[wasm_bindgen(js_name = handleTouch)]
-    pub fn handle_touch(&mut self, x: f32, y: f32, phase: &str) -> Option<String> {
-        // Find element at touch location
-        let element_id = self.scene.element_at(x, y);
+    /// Return to 100% zoom with no pan.
+    #[wasm_bindgen(js_name = resetView)]
+    pub fn reset_view(&mut self) {
+        self.invalidate();
+        let _ = self.run_camera_command(&CameraCommand::ResetView);
+    }
 
-        // Parse touch phase (default to Start for unknown phases)
-        let touch_phase = match phase {
-            "move" | "moved" => TouchPhase::Move,
-            "end" | "ended" => TouchPhase::End,
-            "cancel" | "cancelled" => TouchPhase::Cancel,
-            _ => TouchPhase::Start,
-        };
+    /// Move to a named anchor saved in the scene.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no anchor has that name.
+    #[wasm_bindgen(js_name = goToAnchor)]
+    pub fn go_to_anchor(&mut self, name: &str) -> Result<(), JsValue> {
+        self.invalidate();
+        self.run_camera_command(&CameraCommand::GoToAnchor {
+            name: name.to_string(),
+        })
+    }
 
-        // Create touch point
-        let touch_point = TouchPoint {
-            id: 0,
-            x,
-            y,
-            pressure: None,
-            radius: None,
-        };
+    /// Get the scene's named anchors as a JSON array, in saved order.
+    #[wasm_bindgen(js_name = getAnchorsJson)]
+    #[must_use]
+    pub fn get_anchors_json(&self) -> String {
+        serde_json::to_string(self.scene.anchors()).unwrap_or_else(|_| "[]".to_string())
+    }
 
-        // Create touch event with target element for fusion
-        let mut touch_event = TouchEvent::new(touch_phase, vec![touch_point], 0);
-        touch_event.target_element = element_id;
-        let event = InputEvent::Touch(touch_event.clone());
+    /// Check if connected to AI backend.
+    #[wasm_bindgen(js_name = isConnected)]
+    #[must_use]
+    pub fn is_connected(&self) -> bool {
+        self.state.is_connected()
+    }
 
-        // Process through fusion system (only Start events are stored for fusion)
-        let _ = self.input_fusion.process_touch(&touch_event);
+    /// ID of the topmost interactive element at a screen point.
+    #[wasm_bindgen(js_name = elementAt)]
+    #[must_use]
+    pub fn element_at(&self, x: f32, y: f32) -> Option<String> {
+        self.scene.element_at(x, y).map(|id| id.to_string())
+    }
 
-        // Process the event in state
-        self.state.process_event(&event);
+    /// Measure between two screen points in the scene's world units, for
+    /// the ruler tool's live readout.
+    ///
+    /// Returns JSON with `length`, `dx`, `dy`, `angle` (degrees
+    /// counterclockwise from the x axis) and `unit`.
+    #[wasm_bindgen(js_name = measureBetween)]
+    #[must_use]
+    pub fn measure_between(&self, x1: f32, y1: f32, x2: f32, y2: f32) -> String {
+        let (ax, ay) = self.screen_to_canvas(x1, y1);
+        let (bx, by) = self.screen_to_canvas(x2, y2);
+        serde_json::to_string(&self.scene.measure([ax, ay], [bx, by]))
+            .unwrap_or_else(|_| "{}".to_string())
+    }
 
-        // If an element was touched, select it
-        if let Some(id) = element_id {
-            self.select_element(&id);
-            Some(id.to_string())
-        } else {
-            self.clear_selection();
-            None
-        }
+    /// A measurement line between two screen points, labeled in the
+    /// scene's world units, as element JSON to send as an `add_element`
+    /// mutation. `kind` is `dimension` or `ruler`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error for an unknown kind.
+    #[wasm_bindgen(js_name = measurementElement)]
+    pub fn measurement_element(
+        &self,
+        x1: f32,
+        y1: f32,
+        x2: f32,
+        y2: f32,
+        kind: &str,
+    ) -> Result<String, JsValue> {
+        let kind = match kind {
+            "dimension" => MeasurementKind::Dimension,
+            "ruler" => MeasurementKind::Ruler,
+            other => {
+                return Err(JsValue::from_str(&format!(
+                    "Unknown measurement kind: {other}"
+                )))
+            }
+        };
+        let (ax, ay) = self.screen_to_canvas(x1, y1);
+        let (bx, by) = self.screen_to_canvas(x2, y2);
+        let element =
+            annotation::measurement_in([ax, ay], [bx, by], kind, self.scene.coordinate_system());
+        serde_json::to_string(&element).map_err(|e| JsValue::from_str(&e.to_string()))
     }
 
-    /// Handle a mouse click at the given coordinates.
-    #[wasm_bindgen(js_name = handleClick)]
-    pub fn handle_click(&mut self, x: f32, y: f32) -> Option<String> {
-        self.handle_touch(x, y, "start")
+    /// An element as a JSON element document, including who created and
+    /// last changed it.
+    ///
+    /// Returns `None` if the ID is invalid or the element does not exist.
+    #[wasm_bindgen(js_name = getElementJson)]
+    #[must_use]
+    pub fn get_element_json(&self, id: &str) -> Option<String> {
+        let id = ElementId::parse(id).ok()?;
+        let element = self.scene.get_element(id)?;
+        serde_json::to_string(&ElementDocument::from(element)).ok()
     }
 
-    /// Add an element to the scene from JSON.
+    /// Select only the given element.
     ///
     /// # Errors
     ///
-    /// Returns an error if JSON parsing fails.
-    #[wasm_bindgen(js_name = addElement)]
-    pub fn add_element(&mut self, json: &str) -> Result<String, JsValue> {
-        let element: Element =
-            serde_json::from_str(json).map_err(|e| JsValue::from_str(&e.to_string()))?;
-        let id = element.id;
-        self.scene.add_element(element);
-        Ok(id.to_string())
+    /// Returns an error if the ID is not a valid UUID.
+    #[wasm_bindgen(js_name = selectElement)]
+    pub fn select_element(&mut self, id: &str) -> Result<bool, JsValue> {
+        self.invalidate();
+        let id = parse_element_id(id)?;
+        Ok(self.state.selection.select(&mut self.scene, id))
     }
 
-    /// Remove an element from the scene.
+    /// Add an element to the selection.
     ///
     /// # Errors
     ///
-    /// Returns an error if the element is not found.
-    #[wasm_bindgen(js_name = removeElement)]
-    pub fn remove_element(&mut self, id: &str) -> Result<(), JsValue> {
-        let uuid = uuid::Uuid::parse_str(id).map_err(|e| JsValue::from_str(&e.to_string()))?;
-        let element_id = ElementId::from_uuid(uuid);
-        self.scene
-            .remove_element(&element_id)
-            .map(|_| ())
-            .map_err(|e| JsValue::from_str(&e.to_string()))
+    /// Returns an error if the ID is not a valid UUID.
+    #[wasm_bindgen(js_name = addToSelection)]
+    pub fn add_to_selection(&mut self, id: &str) -> Result<bool, JsValue> {
+        self.invalidate();
+        let id = parse_element_id(id)?;
+        Ok(self.state.selection.add(&mut self.scene, id))
     }
 
-    /// Get the current scene as JSON.
-    #[wasm_bindgen(js_name = getSceneJson)]
-    #[must_use]
-    pub fn get_scene_json(&self) -> String {
-        serde_json::to_string(&self.scene).unwrap_or_default()
+    /// Toggle an element in the selection (shift-click).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the ID is not a valid UUID.
+    #[wasm_bindgen(js_name = toggleSelection)]
+    pub fn toggle_selection(&mut self, id: &str) -> Result<bool, JsValue> {
+        self.invalidate();
+        let id = parse_element_id(id)?;
+        Ok(self.state.selection.toggle(&mut self.scene, id))
     }
 
-    /// Update the entire scene from JSON.
+    /// Replace the selection with a JSON array of element IDs.
     ///
     /// # Errors
     ///
-    /// Returns an error if JSON parsing fails.
-    #[wasm_bindgen(js_name = setSceneJson)]
-    pub fn set_scene_json(&mut self, json: &str) -> Result<(), JsValue> {
-        self.scene = serde_json::from_str(json).map_err(|e| JsValue::from_str(&e.to_string()))?;
-        if let Ok(mut state) = self.renderer_state.try_borrow_mut() {
-            state.clear_dynamic_content();
-        }
-        Ok(())
+    /// Returns an error if the JSON is not an array of element IDs.
+    #[wasm_bindgen(js_name = setSelection)]
+    pub fn set_selection(&mut self, element_ids_json: &str) -> Result<bool, JsValue> {
+        self.invalidate();
+        let ids: Vec<String> = serde_json::from_str(element_ids_json)
+            .map_err(|e| JsValue::from_str(&format!("Invalid element IDs: {e}")))?;
+        let ids = ids
+            .iter()
+            .map(|id| parse_element_id(id))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(self.state.selection.set(&mut self.scene, ids))
     }
 
-    /// Apply a canonical scene document serialized as JSON.
+    /// Deselect everything.
+    #[wasm_bindgen(js_name = clearSelection)]
+    pub fn clear_selection(&mut self) -> bool {
+        self.invalidate();
+        self.state.selection.clear(&mut self.scene)
+    }
+
+    /// Marquee-select the elements inside a rectangle given by two screen
+    /// corners. `mode` is `replace`, `add` or `toggle`.
     ///
     /// # Errors
     ///
-    /// Returns an error if the JSON is invalid or the scene cannot be converted.
-    #[wasm_bindgen(js_name = applySceneDocument)]
-    pub fn apply_scene_document(&mut self, json: &str) -> Result<(), JsValue> {
-        let document: SceneDocument = serde_json::from_str(json)
-            .map_err(|e| JsValue::from_str(&format!("Scene parse error: {e}")))?;
-        self.scene = document
-            .into_scene()
-            .map_err(|e| JsValue::from_str(&format!("Scene conversion error: {e}")))?;
-        if let Ok(mut state) = self.renderer_state.try_borrow_mut() {
-            state.clear_dynamic_content();
-        }
-        Ok(())
+    /// Returns an error for an unknown mode.
+    #[wasm_bindgen(js_name = selectInRect)]
+    pub fn select_in_rect(
+        &mut self,
+        x1: f32,
+        y1: f32,
+        x2: f32,
+        y2: f32,
+        mode: &str,
+    ) -> Result<bool, JsValue> {
+        self.invalidate();
+        let mode = SelectionMode::from_name(mode)
+            .ok_or_else(|| JsValue::from_str(&format!("Unknown selection mode: {mode}")))?;
+        let (ax, ay) = self.screen_to_canvas(x1, y1);
+        let (bx, by) = self.screen_to_canvas(x2, y2);
+        let rect = SceneBounds {
+            min_x: ax.min(bx),
+            min_y: ay.min(by),
+            max_x: ax.max(bx),
+            max_y: ay.max(by),
+        };
+        Ok(self
+            .state
+            .selection
+            .select_rect(&mut self.scene, rect, mode))
     }
 
-    /// Get the number of elements in the scene.
-    #[wasm_bindgen(js_name = elementCount)]
-    #[must_use]
-    pub fn element_count(&self) -> usize {
-        self.scene.element_count()
+    /// Lasso-select the ink strokes inside a polygon given as a JSON array
+    /// of `[x, y]` screen points. `mode` is `replace`, `add` or `toggle`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error for invalid JSON or an unknown mode.
+    #[wasm_bindgen(js_name = selectInLasso)]
+    pub fn select_in_lasso(&mut self, points_json: &str, mode: &str) -> Result<bool, JsValue> {
+        self.invalidate();
+        let mode = SelectionMode::from_name(mode)
+            .ok_or_else(|| JsValue::from_str(&format!("Unknown selection mode: {mode}")))?;
+        let points: Vec<[f32; 2]> = serde_json::from_str(points_json)
+            .map_err(|e| JsValue::from_str(&format!("Invalid lasso points: {e}")))?;
+        let lasso: Vec<(f32, f32)> = points
+            .iter()
+            .map(|[x, y]| self.screen_to_canvas(*x, *y))
+            .collect();
+        Ok(self
+            .state
+            .selection
+            .select_lasso(&mut self.scene, &lasso, mode))
     }
 
-    /// Get the current frame count.
-    #[wasm_bindgen(js_name = frameCount)]
+    /// Selected element IDs as a JSON array, in selection order.
+    #[wasm_bindgen(js_name = getSelection)]
     #[must_use]
-    pub fn frame_count(&self) -> u64 {
-        self.frame_count
-    }
-
-    /// Resize the canvas.
-    #[allow(clippy::cast_precision_loss)]
-    pub fn resize(&mut self, width: u32, height: u32) {
-        if let Ok(mut state) = self.renderer_state.try_borrow_mut() {
-            state.resize(width, height);
-        }
-        if let Err(err) = self.renderer.resize(width, height) {
-            tracing::warn!("Renderer resize failed: {:?}", err);
-        }
-        self.scene.set_viewport(width as f32, height as f32);
+    pub fn get_selection(&self) -> String {
+        serde_json::to_string(self.state.selection.ids()).unwrap_or_else(|_| "[]".to_string())
     }
 
-    /// Set the background color (CSS color string).
-    #[wasm_bindgen(js_name = setBackgroundColor)]
-    pub fn set_background_color(&mut self, color: &str) {
-        if let Ok(mut state) = self.renderer_state.try_borrow_mut() {
-            state.set_background_color(color);
-        }
+    /// Move the selected elements by a screen-space offset.
+    ///
+    /// Returns the number of elements moved.
+    #[wasm_bindgen(js_name = moveSelection)]
+    pub fn move_selection(&mut self, dx: f32, dy: f32) -> usize {
+        self.invalidate();
+        let zoom = if self.scene.zoom > 0.0 {
+            self.scene.zoom
+        } else {
+            1.0
+        };
+        self.state
+            .selection
+            .translate(&mut self.scene, dx / zoom, dy / zoom)
     }
 
-    /// Check if connected to AI backend.
-    #[wasm_bindgen(js_name = isConnected)]
-    #[must_use]
-    pub fn is_connected(&self) -> bool {
-        self.state.is_connected()
+    /// Scale the selected elements as a group around their center.
+    ///
+    /// Returns the number of elements scaled.
+    #[wasm_bindgen(js_name = scaleSelection)]
+    pub fn scale_selection(&mut self, factor: f32) -> usize {
+        self.invalidate();
+        self.state.selection.scale(&mut self.scene, factor)
     }
 
-    /// Select an element by ID.
-    fn select_element(&mut self, id: &ElementId) {
-        // Clear previous selection
-        for element in self.scene.elements_mut() {
-            element.selected = element.id == *id;
-        }
+    /// Drain selection-changed events as a JSON array of
+    /// `{ selected, added, removed }` objects, oldest first.
+    ///
+    /// Call once per frame and dispatch the events to host listeners.
+    #[wasm_bindgen(js_name = takeSelectionEvents)]
+    pub fn take_selection_events(&mut self) -> String {
+        serde_json::to_string(&self.state.selection.take_events())
+            .unwrap_or_else(|_| "[]".to_string())
     }
 
-    /// Clear all selections.
-    fn clear_selection(&mut self) {
-        for element in self.scene.elements_mut() {
-            element.selected = false;
-        }
+    /// Convert a screen point to canvas coordinates.
+    fn screen_to_canvas(&self, x: f32, y: f32) -> (f32, f32) {
+        let zoom = if self.scene.zoom > 0.0 {
+            self.scene.zoom
+        } else {
+            1.0
+        };
+        ((x - self.scene.pan_x) / zoom, (y - self.scene.pan_y) / zoom)
     }
 
     /// The data should be RGBA bytes.
@@ -626,6 +3357,7 @@ impl CanvasApp {
         height: u32,
         timestamp: f64,
     ) {
+        self.invalidate();
         if let Ok(mut state) = self.renderer_state.try_borrow_mut() {
             state.video_frames.insert(
                 stream_id.to_string(),
@@ -642,6 +3374,8 @@ impl CanvasApp {
     /// Remove a video stream from the cache.
     #[wasm_bindgen(js_name = removeVideoStream)]
     pub fn remove_video_stream(&mut self, stream_id: &str) {
+        self.idle.remove_video_stream(stream_id);
+        self.invalidate();
         if let Ok(mut state) = self.renderer_state.try_borrow_mut() {
             state.video_frames.remove(stream_id);
         }
@@ -663,8 +3397,7 @@ impl CanvasApp {
     pub fn has_video_frame(&self, stream_id: &str) -> bool {
         self.renderer_state
             .try_borrow()
-            .map(|state| state.video_frames.contains_key(stream_id))
-            .unwrap_or(false)
+            .is_ok_and(|state| state.video_frames.contains_key(stream_id))
     }
 
     /// Get the timestamp of the last frame for a video stream.
@@ -678,43 +3411,51 @@ impl CanvasApp {
             .and_then(|state| state.video_frames.get(stream_id).map(|f| f.timestamp))
             .unwrap_or(0.0)
     }
+}
 
-    // ========================================================================
-    // Holographic Mode Methods
-    // ========================================================================
+// ============================================================================
+// Holographic Mode Methods
+// ============================================================================
 
-    /// Enable holographic mode with a preset configuration.
+#[cfg(feature = "holographic")]
+#[wasm_bindgen]
+impl CanvasApp {
+    /// Enable holographic mode with a preset or custom configuration.
     ///
-    /// Supported presets: "portrait", "4k"
+    /// Supported presets: "portrait", "4k". A JSON document sets a custom
+    /// configuration (`{"views", "columns", "rows", "viewWidth",
+    /// "viewHeight"}` with optional `"depthiness"` and `"focus"`) or fits the
+    /// layout to a device's `visual.json` calibration.
     /// Pass an empty string or "off" to disable holographic mode.
     ///
     /// # Errors
     ///
-    /// Returns an error if the preset is not recognized.
+    /// Returns an error if the preset is not recognized or the JSON is not
+    /// a valid configuration.
     #[wasm_bindgen(js_name = setHolographicConfig)]
     pub fn set_holographic_config(&mut self, preset: &str) -> Result<(), JsValue> {
-        match preset.to_lowercase().as_str() {
-            "portrait" => {
-                let config = HolographicConfig::looking_glass_portrait();
-                self.holographic_renderer = Some(HolographicRenderer::new(config.clone()));
-                self.holographic_config = Some(config);
-                Ok(())
-            }
-            "4k" => {
-                let config = HolographicConfig::looking_glass_4k();
-                self.holographic_renderer = Some(HolographicRenderer::new(config.clone()));
-                self.holographic_config = Some(config);
-                Ok(())
-            }
-            "" | "off" | "none" | "disabled" => {
+        self.invalidate();
+        let config = match preset.trim() {
+            off if matches!(
+                off.to_lowercase().as_str(),
+                "" | "off" | "none" | "disabled"
+            ) =>
+            {
                 self.holographic_config = None;
                 self.holographic_renderer = None;
-                Ok(())
+                return Ok(());
             }
-            _ => Err(JsValue::from_str(&format!(
-                "Unknown holographic preset: '{preset}'. Use 'portrait', '4k', or 'off'"
-            ))),
-        }
+            json if json.starts_with('{') => HolographicConfig::from_calibration_json(json)
+                .map_err(|e| JsValue::from_str(&e.to_string()))?,
+            name => HolographicConfig::preset(name).ok_or_else(|| {
+                JsValue::from_str(&format!(
+                    "Unknown holographic preset: '{preset}'. Use 'portrait', '4k', a JSON config, or 'off'"
+                ))
+            })?,
+        };
+        self.holographic_renderer = Some(HolographicRenderer::new(config.clone()));
+        self.holographic_config = Some(config);
+        Ok(())
     }
 
     /// Check if holographic mode is currently enabled.
@@ -789,7 +3530,16 @@ impl CanvasApp {
             JsValue::from_str("Holographic mode not enabled. Call setHolographicConfig() first.")
         })?;
 
-        let result = renderer.render_quilt(&self.scene, &self.holographic_camera);
+        // Ease the orbit by the time since the last quilt
+        let now = performance_now_ms();
+        #[allow(clippy::cast_possible_truncation)] // Frame gaps are small
+        let dt = self
+            .holographic_camera_ms
+            .map_or(0.0, |last| ((now - last) / 1000.0) as f32);
+        self.holographic_camera_ms = Some(now);
+        self.holographic_camera.update(dt);
+
+        let result = renderer.render_quilt(&self.scene, &self.holographic_camera.camera());
         Ok(result.target.pixels)
     }
 
@@ -810,11 +3560,84 @@ impl CanvasApp {
         target_y: f32,
         target_z: f32,
     ) {
-        self.holographic_camera = Camera {
+        self.invalidate();
+        let damping = self.holographic_camera.damping();
+        self.holographic_camera = OrbitCamera::new(&Camera {
             position: Vec3::new(pos_x, pos_y, pos_z),
             target: Vec3::new(target_x, target_y, target_z),
             ..Camera::default()
-        };
+        })
+        .with_damping(damping);
+    }
+
+    /// Orbit the holographic camera for a pointer drag of `dx`, `dy`
+    /// pixels: right turns the scene right, down tilts it towards the
+    /// viewer. The camera eases there over the next `renderQuilt()` calls.
+    #[wasm_bindgen(js_name = orbitHolographicCamera)]
+    pub fn orbit_holographic_camera(&mut self, dx: f32, dy: f32) {
+        self.invalidate();
+        self.holographic_camera.drag(dx, dy);
+    }
+
+    /// Pan the holographic camera for a pointer drag of `dx`, `dy` pixels
+    /// over a view `view_height` pixels tall, so the scene follows the
+    /// pointer.
+    #[wasm_bindgen(js_name = panHolographicCamera)]
+    pub fn pan_holographic_camera(&mut self, dx: f32, dy: f32, view_height: f32) {
+        self.invalidate();
+        self.holographic_camera.pan(dx, dy, view_height);
+    }
+
+    /// Zoom the holographic camera by `factor`: 2 halves its distance to
+    /// the target, 0.5 doubles it.
+    #[wasm_bindgen(js_name = zoomHolographicCamera)]
+    pub fn zoom_holographic_camera(&mut self, factor: f32) {
+        self.invalidate();
+        self.holographic_camera.zoom(factor);
+    }
+
+    /// Return the holographic camera to where `setHolographicCamera` last
+    /// put it.
+    #[wasm_bindgen(js_name = resetHolographicCamera)]
+    pub fn reset_holographic_camera(&mut self) {
+        self.invalidate();
+        self.holographic_camera.reset();
+    }
+
+    /// Limit the holographic camera's distance to the target, its pitch in
+    /// degrees either side of level, and how far it may pan.
+    #[wasm_bindgen(js_name = setHolographicCameraLimits)]
+    pub fn set_holographic_camera_limits(
+        &mut self,
+        min_distance: f32,
+        max_distance: f32,
+        max_pitch_degrees: f32,
+        max_pan: f32,
+    ) {
+        self.invalidate();
+        let pitch = max_pitch_degrees.abs().min(90.0).to_radians();
+        self.holographic_camera.set_limits(OrbitLimits {
+            min_distance: min_distance.max(f32::EPSILON),
+            max_distance,
+            min_pitch: -pitch,
+            max_pitch: pitch,
+            max_pan,
+        });
+    }
+
+    /// Set how long the holographic camera takes to catch up with input,
+    /// as a time constant in seconds; 0 moves it at once.
+    #[wasm_bindgen(js_name = setHolographicCameraDamping)]
+    pub fn set_holographic_camera_damping(&mut self, seconds: f32) {
+        self.holographic_camera.set_damping(seconds);
+    }
+
+    /// Check whether the holographic camera is still easing towards its
+    /// goal, so the host should keep rendering quilts.
+    #[wasm_bindgen(js_name = isHolographicCameraMoving)]
+    #[must_use]
+    pub fn is_holographic_camera_moving(&self) -> bool {
+        !self.holographic_camera.is_settled()
     }
 
     /// Get the current holographic configuration preset name.
@@ -921,11 +3744,14 @@ impl CanvasApp {
             renderer.reset_stats();
         }
     }
+}
 
-    // =========================================================================
-    // Voice Input Methods
-    // =========================================================================
+// ============================================================================
+// Voice Input Methods
+// ============================================================================
 
+#[wasm_bindgen]
+impl CanvasApp {
     /// Process a voice recognition result.
     ///
     /// This method handles speech recognition results from the Web Speech API.
@@ -941,7 +3767,11 @@ impl CanvasApp {
     ///
     /// # Returns
     ///
-    /// JSON-encoded fusion result if fusion occurs, or null if no fusion.
+    /// JSON-encoded fusion result if fusion occurs, or null if no fusion
+    /// (always null without the `fusion` feature). When the target is
+    /// unclear the result is a disambiguation request with `candidates`;
+    /// numbered badges are drawn on them until the user answers with
+    /// "number two" or [`resolveDisambiguationTap`](Self::resolve_disambiguation_tap).
     #[wasm_bindgen(js_name = processVoice)]
     #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
     pub fn process_voice(
@@ -951,23 +3781,26 @@ impl CanvasApp {
         is_final: bool,
         timestamp: f64,
     ) -> JsValue {
+        self.record_activity();
         let voice = VoiceEvent::new(transcript, confidence, is_final, timestamp as u64);
-        let result = self.input_fusion.process_voice(&voice);
+        #[cfg(feature = "fusion")]
+        let intent = {
+            let result = self.input_fusion.process_voice(&voice);
+            self.fusion_result_json(result)
+        };
+        #[cfg(not(feature = "fusion"))]
+        let intent: Option<String> = None;
 
         // Also process the voice event through state
         self.state.process_event(&InputEvent::Voice(voice));
 
-        match result {
-            FusionResult::Fused(intent) => serde_json::to_string(&intent)
-                .map(|s| JsValue::from_str(&s))
-                .unwrap_or(JsValue::NULL),
-            FusionResult::VoiceOnly(intent) => serde_json::to_string(&intent)
-                .map(|s| JsValue::from_str(&s))
-                .unwrap_or(JsValue::NULL),
-            FusionResult::Pending | FusionResult::None => JsValue::NULL,
-        }
+        intent.map_or(JsValue::NULL, |s| JsValue::from_str(&s))
     }
+}
 
+#[cfg(feature = "fusion")]
+#[wasm_bindgen]
+impl CanvasApp {
     /// Check if there's a pending touch waiting for voice fusion.
     ///
     /// Returns true if a touch event is stored and still within the fusion window.
@@ -1013,6 +3846,38 @@ impl CanvasApp {
         self.input_fusion.clear_pending();
     }
 
+    /// Get the disambiguation request waiting for the user's choice.
+    ///
+    /// Returns the JSON request (transcript and numbered `candidates` with
+    /// canvas `bounds`), or null if none is pending.
+    #[wasm_bindgen(js_name = pendingDisambiguation)]
+    #[must_use]
+    pub fn pending_disambiguation(&self) -> Option<String> {
+        self.input_fusion
+            .pending_disambiguation()
+            .and_then(|request| serde_json::to_string(request).ok())
+    }
+
+    /// Resolve the pending disambiguation with a tap at screen coordinates.
+    ///
+    /// Returns the JSON fused intent if the tap hit a candidate, or null if
+    /// it did not; the prompt stays open until a candidate is chosen,
+    /// [`cancelDisambiguation`](Self::cancel_disambiguation) is called, or it
+    /// times out.
+    #[wasm_bindgen(js_name = resolveDisambiguationTap)]
+    pub fn resolve_disambiguation_tap(&mut self, x: f32, y: f32) -> Option<String> {
+        let element_id = self.scene.element_at(x, y)?;
+        let result = self.input_fusion.resolve_element(element_id);
+        self.fusion_result_json(result)
+    }
+
+    /// Dismiss the pending disambiguation without running its command.
+    #[wasm_bindgen(js_name = cancelDisambiguation)]
+    pub fn cancel_disambiguation(&mut self) {
+        self.input_fusion.cancel_disambiguation();
+        self.invalidate();
+    }
+
     /// Get the current fusion window configuration in milliseconds.
     #[wasm_bindgen(js_name = getFusionWindow)]
     #[must_use]
@@ -1048,7 +3913,144 @@ impl CanvasApp {
     }
 }
 
+/// Current wall-clock time in milliseconds.
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn now_ms() -> u64 {
+    js_sys::Date::now() as u64
+}
+
+/// High-resolution milliseconds for frame timing, falling back to the wall
+/// clock where `performance` is unavailable.
+fn performance_now_ms() -> f64 {
+    web_sys::window()
+        .and_then(|window| window.performance())
+        .map_or_else(js_sys::Date::now, |performance| performance.now())
+}
+
+/// Parse an element ID passed in from JavaScript.
+fn parse_element_id(id: &str) -> Result<ElementId, JsValue> {
+    ElementId::parse(id).map_err(|e| JsValue::from_str(&format!("Invalid element ID: {e}")))
+}
+
+impl CanvasApp {
+    /// Record input or sync traffic, restarting the render loop if idle.
+    fn record_activity(&mut self) {
+        if self.idle.record_activity(now_ms()) {
+            self.wake();
+        }
+    }
+
+    /// Request a redraw, restarting the render loop if idle.
+    /// Encode an intent or disambiguation request for JavaScript. A
+    /// disambiguation may have started or ended, so its badges are redrawn.
+    #[cfg(feature = "fusion")]
+    fn fusion_result_json(&mut self, result: FusionResult) -> Option<String> {
+        let json = match result {
+            FusionResult::Fused(intent) => serde_json::to_string(&intent).ok(),
+            FusionResult::VoiceOnly(intent) => serde_json::to_string(&intent).ok(),
+            FusionResult::Disambiguate(request) => serde_json::to_string(&request).ok(),
+            FusionResult::Pending | FusionResult::None => None,
+        };
+        self.invalidate();
+        json
+    }
+
+    fn invalidate(&mut self) {
+        if self.idle.invalidate() {
+            self.wake();
+        }
+    }
+
+    fn wake(&self) {
+        if let Some(callback) = &self.wake_callback {
+            if let Err(err) = callback.call0(&JsValue::NULL) {
+                tracing::warn!("Wake callback failed: {:?}", err);
+            }
+        }
+    }
+
+    fn apply_spotlight(
+        &mut self,
+        ids: Vec<ElementId>,
+        zoom_to_fit: bool,
+        timeout_ms: u32,
+    ) -> Result<(), JsValue> {
+        let mut spotlight = Spotlight::new(ids).with_zoom_to_fit(zoom_to_fit);
+        if timeout_ms > 0 {
+            spotlight = spotlight.with_timeout(now_ms(), u64::from(timeout_ms));
+        }
+        self.scene
+            .set_spotlight(spotlight)
+            .map_err(|e| JsValue::from_str(&format!("Spotlight error: {e}")))
+    }
+
+    /// Apply a user-requested camera command.
+    ///
+    /// Counts as a camera interaction, so follow mode and spotlight zoom
+    /// don't immediately move the camera back.
+    fn run_camera_command(&mut self, command: &CameraCommand) -> Result<(), JsValue> {
+        command
+            .apply(&mut self.scene, &FitOptions::default())
+            .map_err(|e| JsValue::from_str(&format!("Camera error: {e}")))?;
+        self.notify_user_camera_interaction();
+        Ok(())
+    }
+
+    /// Expire the spotlight and ease the camera toward its elements.
+    ///
+    /// Returns `true` while the spotlight owns the camera, so agent following
+    /// does not fight it.
+    fn update_spotlight_camera(&mut self, now: u64) -> bool {
+        if self
+            .scene
+            .spotlight()
+            .is_some_and(|spotlight| spotlight.is_expired(now))
+        {
+            self.scene.clear_spotlight();
+        }
+
+        let current = self.scene.spotlight();
+        if current != self.last_spotlight.as_ref() {
+            self.last_spotlight = current.cloned();
+            self.spotlight_camera_released = false;
+        }
+
+        let Some(spotlight) = &self.last_spotlight else {
+            return false;
+        };
+        if !spotlight.zoom_to_fit || self.spotlight_camera_released {
+            return false;
+        }
+
+        let config = self.agent_follow.config();
+        if let Some(goal) = CameraFrame::fit_elements(&self.scene, &spotlight.element_ids, config) {
+            let camera = CameraFrame::from_scene(&self.scene);
+            if !camera.approx_eq(&goal) {
+                camera
+                    .lerp(&goal, config.smoothing.clamp(0.01, 1.0))
+                    .apply_to(&mut self.scene);
+            }
+        }
+        true
+    }
+}
+
+/// IDs of elements that are new or whose content, transform, or style differ.
+fn changed_element_ids(old: &Scene, new: &Scene) -> Vec<ElementId> {
+    new.elements()
+        .filter(|element| {
+            old.get_element(element.id).is_none_or(|previous| {
+                previous.kind != element.kind
+                    || previous.transform != element.transform
+                    || previous.style != element.style
+            })
+        })
+        .map(|element| element.id)
+        .collect()
+}
+
 /// Create a chart element JSON with sample data.
+#[cfg(feature = "charts")]
 #[wasm_bindgen(js_name = createChartElement)]
 #[must_use]
 pub fn create_chart_element(chart_type: &str, x: f32, y: f32, width: f32, height: f32) -> String {
@@ -1096,6 +4098,7 @@ pub fn create_chart_element(chart_type: &str, x: f32, y: f32, width: f32, height
     let element = Element::new(ElementKind::Chart {
         chart_type: chart_type.to_string(),
         data,
+        binding: None,
     })
     .with_transform(Transform {
         x,
@@ -1104,6 +4107,7 @@ pub fn create_chart_element(chart_type: &str, x: f32, y: f32, width: f32, height
         height,
         rotation: 0.0,
         z_index: 0,
+        auto_size: false,
     });
 
     serde_json::to_string(&element).unwrap_or_default()
@@ -1114,6 +4118,7 @@ pub fn create_chart_element(chart_type: &str, x: f32, y: f32, width: f32, height
 /// # Errors
 ///
 /// Returns an error if the data JSON is invalid.
+#[cfg(feature = "charts")]
 #[wasm_bindgen(js_name = createChartWithData)]
 pub fn create_chart_with_data(
     chart_type: &str,
@@ -1129,6 +4134,7 @@ pub fn create_chart_with_data(
     let element = Element::new(ElementKind::Chart {
         chart_type: chart_type.to_string(),
         data,
+        binding: None,
     })
     .with_transform(Transform {
         x,
@@ -1137,6 +4143,7 @@ pub fn create_chart_with_data(
         height,
         rotation: 0.0,
         z_index: 0,
+        auto_size: false,
     });
 
     serde_json::to_string(&element).map_err(|e| JsValue::from_str(&e.to_string()))
@@ -1149,7 +4156,7 @@ pub fn create_text_element(content: &str, x: f32, y: f32, font_size: f32, color:
     let element = Element::new(ElementKind::Text {
         content: content.to_string(),
         font_size,
-        color: color.to_string(),
+        color: ThemeColor::parse(color).unwrap_or_default(),
     })
     .with_transform(Transform {
         x,
@@ -1158,6 +4165,7 @@ pub fn create_text_element(content: &str, x: f32, y: f32, font_size: f32, color:
         height: font_size * 1.5,
         rotation: 0.0,
         z_index: 0,
+        auto_size: false,
     });
 
     serde_json::to_string(&element).unwrap_or_default()
@@ -1170,6 +4178,8 @@ pub fn create_image_element(src: &str, x: f32, y: f32, width: f32, height: f32)
     let element = Element::new(ElementKind::Image {
         src: src.to_string(),
         format: canvas_core::ImageFormat::Png,
+        annotations: Vec::new(),
+        extracted_text: None,
     })
     .with_transform(Transform {
         x,
@@ -1178,6 +4188,7 @@ pub fn create_image_element(src: &str, x: f32, y: f32, width: f32, height: f32)
         height,
         rotation: 0.0,
         z_index: 0,
+        auto_size: false,
     });
 
     serde_json::to_string(&element).unwrap_or_default()
@@ -1208,13 +4219,15 @@ pub fn create_video_element(
         width,
         height,
         rotation: 0.0,
-        z_index: 10, // Video on top by default
+        z_index: 10, // Video on top by default,
+        auto_size: false,
     });
 
     serde_json::to_string(&element).unwrap_or_default()
 }
 
-#[cfg(test)]
+// All browser tests exercise holographic mode
+#[cfg(all(test, feature = "holographic"))]
 mod tests {
     use super::*;
     use wasm_bindgen_test::*;
@@ -1310,6 +4323,21 @@ mod tests {
         assert!(app.is_holographic_mode());
     }
 
+    #[wasm_bindgen_test]
+    fn test_set_holographic_config_custom_json() {
+        let mut app = create_test_app(800, 600);
+
+        app.set_holographic_config(
+            r#"{"views": 48, "columns": 8, "rows": 6, "viewWidth": 420, "viewHeight": 560}"#,
+        )
+        .expect("config failed");
+        let dims = app.get_quilt_dimensions();
+        assert_eq!(js_sys::Reflect::get(&dims, &"width".into()).unwrap(), 3360);
+        assert_eq!(js_sys::Reflect::get(&dims, &"views".into()).unwrap(), 48);
+
+        assert!(app.set_holographic_config(r#"{"views": 0}"#).is_err());
+    }
+
     // ============================================================================
     // Quilt Dimensions Tests
     // ============================================================================
@@ -1370,6 +4398,27 @@ mod tests {
         assert!(app.is_holographic_mode());
     }
 
+    #[wasm_bindgen_test]
+    fn test_holographic_camera_orbits_and_resets() {
+        let mut app = create_test_app(800, 600);
+        app.set_holographic_config("portrait")
+            .expect("config failed");
+        app.set_holographic_camera_damping(0.0);
+        assert!(!app.is_holographic_camera_moving());
+
+        app.orbit_holographic_camera(100.0, 40.0);
+        app.zoom_holographic_camera(2.0);
+        assert!(app.is_holographic_camera_moving());
+        app.render_quilt().expect("quilt");
+        assert!(!app.is_holographic_camera_moving());
+
+        app.set_holographic_camera_limits(1.0, 4.0, 30.0, 0.5);
+        app.zoom_holographic_camera(0.01);
+        app.reset_holographic_camera();
+        app.render_quilt().expect("quilt");
+        assert!(!app.is_holographic_camera_moving());
+    }
+
     #[wasm_bindgen_test]
     fn test_set_holographic_camera_not_in_holographic_mode() {
         let mut app = create_test_app(800, 600);
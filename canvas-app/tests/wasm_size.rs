@@ -0,0 +1,64 @@
+//! WASM Bundle Size Tests
+//!
+//! Guards the size of the `wasm-pack` release build so regressions show up
+//! in CI instead of on slow mobile connections. Build the bundle with the
+//! same features as this test, then run it:
+//!
+//! ```bash
+//! cd canvas-app
+//! wasm-pack build --target web --release -- --no-default-features
+//! cargo test --no-default-features --test wasm_size
+//! ```
+//!
+//! The bundle is read from `CANVAS_APP_WASM` (default
+//! `pkg/canvas_app_bg.wasm`). The test is skipped if it has not been built.
+
+use std::path::PathBuf;
+
+/// Budget for the default build (holographic, fusion and charts).
+const FULL_BUDGET_BYTES: u64 = 1_536 * 1024;
+
+/// Budget for `--no-default-features` builds.
+const MINIMAL_BUDGET_BYTES: u64 = 1_024 * 1024;
+
+/// The budget for the feature set this test was compiled with.
+fn budget() -> u64 {
+    if cfg!(any(
+        feature = "holographic",
+        feature = "fusion",
+        feature = "charts"
+    )) {
+        FULL_BUDGET_BYTES
+    } else {
+        MINIMAL_BUDGET_BYTES
+    }
+}
+
+fn wasm_path() -> PathBuf {
+    std::env::var_os("CANVAS_APP_WASM").map_or_else(
+        || PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("pkg/canvas_app_bg.wasm"),
+        PathBuf::from,
+    )
+}
+
+#[test]
+fn wasm_bundle_within_budget() {
+    let path = wasm_path();
+    let Ok(metadata) = std::fs::metadata(&path) else {
+        eprintln!("Skipping: {} not built", path.display());
+        return;
+    };
+
+    let size = metadata.len();
+    let budget = budget();
+    println!(
+        "{}: {} KiB (budget {} KiB)",
+        path.display(),
+        size / 1024,
+        budget / 1024
+    );
+    assert!(
+        size <= budget,
+        "WASM bundle is {size} bytes, over the {budget} byte budget"
+    );
+}
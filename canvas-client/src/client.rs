@@ -0,0 +1,646 @@
+//! Connection task, reconnect loop and offline queue.
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use canvas_core::protocol::{ClientMessage, QueuedOperation, ServerMessage};
+use canvas_core::{ElementDocument, ElementId, Scene};
+use futures::{SinkExt, StreamExt};
+use tokio::net::TcpStream;
+use tokio::sync::{broadcast, mpsc};
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{connect_async, MaybeTlsStream, WebSocketStream};
+
+use crate::ClientError;
+
+type Socket = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+/// Capacity of the event broadcast channel.
+const EVENT_CHANNEL_CAPACITY: usize = 256;
+/// Default maximum number of queued offline operations.
+const DEFAULT_MAX_QUEUE_SIZE: usize = 1000;
+
+/// Backoff schedule for reconnecting after the connection drops.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReconnectPolicy {
+    /// Delay before the first reconnect attempt.
+    pub initial_delay: Duration,
+    /// Upper bound for the delay between attempts.
+    pub max_delay: Duration,
+    /// Attempts before giving up (`None` retries forever).
+    pub max_attempts: Option<u32>,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            initial_delay: Duration::from_millis(250),
+            max_delay: Duration::from_secs(10),
+            max_attempts: None,
+        }
+    }
+}
+
+impl ReconnectPolicy {
+    /// A policy that never reconnects.
+    #[must_use]
+    pub fn disabled() -> Self {
+        Self {
+            max_attempts: Some(0),
+            ..Self::default()
+        }
+    }
+
+    /// Delay before reconnect attempt `attempt` (starting at 0), doubling
+    /// each time up to `max_delay`.
+    #[must_use]
+    pub fn delay(&self, attempt: u32) -> Duration {
+        self.initial_delay
+            .saturating_mul(2_u32.saturating_pow(attempt))
+            .min(self.max_delay)
+    }
+
+    /// Check whether another attempt is allowed after `attempts` failures.
+    #[must_use]
+    pub fn allows(&self, attempts: u32) -> bool {
+        self.max_attempts.is_none_or(|max| attempts < max)
+    }
+}
+
+/// Configuration for a [`CanvasClient`].
+#[derive(Debug, Clone)]
+pub struct ClientConfig {
+    /// WebSocket URL, e.g. `ws://localhost:9473/ws/sync`.
+    ///
+    /// Add `?page_size=N` to load large scenes progressively.
+    pub url: String,
+    /// Session to subscribe to.
+    pub session_id: String,
+    /// Reconnect schedule.
+    pub reconnect: ReconnectPolicy,
+    /// Maximum queued offline operations; the oldest are dropped first.
+    pub max_queue_size: usize,
+}
+
+impl ClientConfig {
+    /// Create a configuration for `url` using the `default` session.
+    #[must_use]
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            session_id: "default".to_string(),
+            reconnect: ReconnectPolicy::default(),
+            max_queue_size: DEFAULT_MAX_QUEUE_SIZE,
+        }
+    }
+
+    /// Set the session to subscribe to.
+    #[must_use]
+    pub fn with_session(mut self, session_id: impl Into<String>) -> Self {
+        self.session_id = session_id.into();
+        self
+    }
+
+    /// Set the reconnect schedule.
+    #[must_use]
+    pub fn with_reconnect(mut self, reconnect: ReconnectPolicy) -> Self {
+        self.reconnect = reconnect;
+        self
+    }
+
+    /// Set the maximum number of queued offline operations.
+    #[must_use]
+    pub fn with_max_queue_size(mut self, max_queue_size: usize) -> Self {
+        self.max_queue_size = max_queue_size;
+        self
+    }
+}
+
+/// Something that happened on the connection.
+// Events are consumed as soon as they are received, so variant size is moot
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, Clone)]
+pub enum ClientEvent {
+    /// The connection was (re-)established and the session subscribed.
+    Connected,
+    /// The connection dropped; the client will try to reconnect.
+    Disconnected,
+    /// The client gave up reconnecting or was closed.
+    Closed,
+    /// A message from the server.
+    Message(ServerMessage),
+}
+
+/// Commands from the client handle to the connection task.
+#[allow(clippy::large_enum_variant)]
+enum Command {
+    Send(ClientMessage),
+    Subscribe(String),
+    Close,
+}
+
+/// State shared between the client handle and the connection task.
+struct Shared {
+    connected: AtomicBool,
+    closed: AtomicBool,
+    session_id: Mutex<String>,
+    peer_id: Mutex<Option<String>>,
+    scene: Mutex<Option<Scene>>,
+    queue: Mutex<VecDeque<QueuedOperation>>,
+    max_queue_size: usize,
+}
+
+impl Shared {
+    fn enqueue(&self, op: QueuedOperation) {
+        let mut queue = self.queue.lock().unwrap_or_else(PoisonError::into_inner);
+        if queue.len() >= self.max_queue_size {
+            queue.pop_front();
+        }
+        queue.push_back(op);
+    }
+
+    fn take_queue(&self) -> Vec<QueuedOperation> {
+        let mut queue = self.queue.lock().unwrap_or_else(PoisonError::into_inner);
+        queue.drain(..).collect()
+    }
+
+    fn session_id(&self) -> String {
+        self.session_id
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Keep the local scene mirror and peer ID in step with a server message.
+    fn observe(&self, message: &ServerMessage) {
+        let mut scene = self.scene.lock().unwrap_or_else(PoisonError::into_inner);
+        match message {
+            ServerMessage::PeerAssigned { peer_id } => {
+                *self.peer_id.lock().unwrap_or_else(PoisonError::into_inner) =
+                    Some(peer_id.clone());
+            }
+            ServerMessage::SceneUpdate { scene: document } => match document.clone().into_scene() {
+                Ok(next) => *scene = Some(next),
+                Err(e) => tracing::warn!("Ignoring invalid scene update: {e}"),
+            },
+            ServerMessage::ScenePatch { patch } => {
+                if let Some(current) = scene.as_mut() {
+                    if let Err(e) = current.apply_patch(patch) {
+                        tracing::warn!("Failed to apply scene patch: {e}");
+                    }
+                }
+            }
+            ServerMessage::ScenePage { page } => {
+                if let Some(current) = scene.as_mut() {
+                    if let Err(e) = current.apply_page(page) {
+                        tracing::warn!("Failed to apply scene page: {e}");
+                    }
+                }
+            }
+            ServerMessage::ElementAdded { element, .. }
+            | ServerMessage::ElementUpdated { element, .. } => {
+                if let Some(current) = scene.as_mut() {
+                    match element.clone().into_element() {
+                        Ok(element) => current.upsert_element(element),
+                        Err(e) => tracing::warn!("Ignoring invalid element: {e}"),
+                    }
+                }
+            }
+            ServerMessage::ElementRemoved { id, .. } => {
+                if let Some(current) = scene.as_mut() {
+                    if let Ok(id) = ElementId::parse(id) {
+                        let _ = current.remove_element(&id);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// A connection to a canvas server's sync WebSocket.
+///
+/// Dropping the client closes the connection.
+pub struct CanvasClient {
+    commands: mpsc::UnboundedSender<Command>,
+    events: broadcast::Sender<ClientEvent>,
+    shared: Arc<Shared>,
+}
+
+impl CanvasClient {
+    /// Connect to the server and subscribe to the configured session.
+    ///
+    /// Must be called from within a Tokio runtime.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ClientError::Connect`] if the first connection attempt
+    /// fails; later drops are retried according to the reconnect policy.
+    pub async fn connect(config: ClientConfig) -> Result<Self, ClientError> {
+        let (socket, _) = connect_async(config.url.as_str())
+            .await
+            .map_err(|e| ClientError::Connect(e.to_string()))?;
+
+        let shared = Arc::new(Shared {
+            connected: AtomicBool::new(false),
+            closed: AtomicBool::new(false),
+            session_id: Mutex::new(config.session_id.clone()),
+            peer_id: Mutex::new(None),
+            scene: Mutex::new(None),
+            queue: Mutex::new(VecDeque::new()),
+            max_queue_size: config.max_queue_size,
+        });
+        let (commands, command_rx) = mpsc::unbounded_channel();
+        let (events, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
+
+        tokio::spawn(run(
+            config,
+            socket,
+            command_rx,
+            events.clone(),
+            Arc::clone(&shared),
+        ));
+
+        Ok(Self {
+            commands,
+            events,
+            shared,
+        })
+    }
+
+    /// Receive connection events and server messages from now on.
+    #[must_use]
+    pub fn events(&self) -> broadcast::Receiver<ClientEvent> {
+        self.events.subscribe()
+    }
+
+    /// Check whether the connection is currently up.
+    #[must_use]
+    pub fn is_connected(&self) -> bool {
+        self.shared.connected.load(Ordering::Acquire)
+    }
+
+    /// The peer ID the server assigned to this connection, once known.
+    #[must_use]
+    pub fn peer_id(&self) -> Option<String> {
+        self.shared
+            .peer_id
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// The session this client is subscribed to.
+    #[must_use]
+    pub fn session_id(&self) -> String {
+        self.shared.session_id()
+    }
+
+    /// A copy of the session scene as last broadcast by the server.
+    ///
+    /// Returns `None` until the first scene update arrives.
+    #[must_use]
+    pub fn scene(&self) -> Option<Scene> {
+        self.shared
+            .scene
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Number of operations waiting to be synced.
+    #[must_use]
+    pub fn queued_len(&self) -> usize {
+        self.shared
+            .queue
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .len()
+    }
+
+    /// Switch to another session. The scene mirror is reset until the
+    /// server sends the new session's scene.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ClientError::Closed`] if the client has shut down.
+    pub fn subscribe(&self, session_id: impl Into<String>) -> Result<(), ClientError> {
+        let session_id = session_id.into();
+        self.shared
+            .session_id
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone_from(&session_id);
+        *self
+            .shared
+            .scene
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner) = None;
+        self.command(Command::Subscribe(session_id))
+    }
+
+    /// Send a message to the server.
+    ///
+    /// While disconnected, element adds, updates and removals are queued
+    /// and synced on reconnect; other messages are rejected.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ClientError::NotConnected`] for an unqueueable message
+    /// while offline, or [`ClientError::Closed`] if the client has shut down.
+    pub fn send(&self, message: ClientMessage) -> Result<(), ClientError> {
+        if self.shared.closed.load(Ordering::Acquire) {
+            return Err(ClientError::Closed);
+        }
+        if self.is_connected() {
+            return self.command(Command::Send(message));
+        }
+        match to_queued(message) {
+            Some(op) => {
+                self.shared.enqueue(op);
+                Ok(())
+            }
+            None => Err(ClientError::NotConnected),
+        }
+    }
+
+    /// Add an element to the session scene.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ClientError::Closed`] if the client has shut down.
+    pub fn add_element(
+        &self,
+        element: ElementDocument,
+        message_id: Option<String>,
+    ) -> Result<(), ClientError> {
+        self.send(ClientMessage::AddElement {
+            element,
+            message_id,
+        })
+    }
+
+    /// Apply partial changes to an element.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ClientError::Closed`] if the client has shut down.
+    pub fn update_element(
+        &self,
+        id: impl Into<String>,
+        changes: serde_json::Value,
+        message_id: Option<String>,
+    ) -> Result<(), ClientError> {
+        self.send(ClientMessage::UpdateElement {
+            id: id.into(),
+            changes,
+            message_id,
+        })
+    }
+
+    /// Remove an element from the session scene.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ClientError::Closed`] if the client has shut down.
+    pub fn remove_element(
+        &self,
+        id: impl Into<String>,
+        message_id: Option<String>,
+    ) -> Result<(), ClientError> {
+        self.send(ClientMessage::RemoveElement {
+            id: id.into(),
+            message_id,
+        })
+    }
+
+    /// Close the connection and stop reconnecting.
+    pub fn close(&self) {
+        let _ = self.command(Command::Close);
+    }
+
+    fn command(&self, command: Command) -> Result<(), ClientError> {
+        self.commands.send(command).map_err(|_| ClientError::Closed)
+    }
+}
+
+impl Drop for CanvasClient {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+impl std::fmt::Debug for CanvasClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CanvasClient")
+            .field("session_id", &self.session_id())
+            .field("connected", &self.is_connected())
+            .field("queued", &self.queued_len())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Convert an element message into an offline queue entry.
+///
+/// Returns `None` for messages that cannot be queued.
+fn to_queued(message: ClientMessage) -> Option<QueuedOperation> {
+    let timestamp = now_millis();
+    match message {
+        ClientMessage::AddElement { element, .. } => {
+            Some(QueuedOperation::Add { element, timestamp })
+        }
+        ClientMessage::UpdateElement { id, changes, .. } => Some(QueuedOperation::Update {
+            id,
+            changes,
+            timestamp,
+        }),
+        ClientMessage::RemoveElement { id, .. } => Some(QueuedOperation::Remove { id, timestamp }),
+        _ => None,
+    }
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| u64::try_from(d.as_millis()).unwrap_or(u64::MAX))
+}
+
+/// Why a connection ended.
+enum Ended {
+    /// The socket closed or failed.
+    Dropped,
+    /// The client asked to close.
+    Closed,
+}
+
+/// Connection task: serve the socket, then reconnect until told to stop.
+async fn run(
+    config: ClientConfig,
+    mut socket: Socket,
+    mut commands: mpsc::UnboundedReceiver<Command>,
+    events: broadcast::Sender<ClientEvent>,
+    shared: Arc<Shared>,
+) {
+    loop {
+        if let Ended::Closed = serve(&mut socket, &mut commands, &events, &shared).await {
+            let _ = socket.close(None).await;
+            break;
+        }
+        let _ = events.send(ClientEvent::Disconnected);
+
+        match reconnect(&config, &mut commands, &shared).await {
+            Some(next) => socket = next,
+            None => break,
+        }
+    }
+    shared.connected.store(false, Ordering::Release);
+    shared.closed.store(true, Ordering::Release);
+    let _ = events.send(ClientEvent::Closed);
+}
+
+/// Subscribe, flush the offline queue and relay messages until the
+/// connection ends.
+async fn serve(
+    socket: &mut Socket,
+    commands: &mut mpsc::UnboundedReceiver<Command>,
+    events: &broadcast::Sender<ClientEvent>,
+    shared: &Shared,
+) -> Ended {
+    let subscribe = ClientMessage::Subscribe {
+        session_id: shared.session_id(),
+    };
+    if write(socket, &subscribe).await.is_err() {
+        return Ended::Dropped;
+    }
+    let operations = shared.take_queue();
+    if !operations.is_empty() {
+        let sync = ClientMessage::SyncQueue { operations };
+        if write(socket, &sync).await.is_err() {
+            if let ClientMessage::SyncQueue { operations } = sync {
+                for op in operations {
+                    shared.enqueue(op);
+                }
+            }
+            return Ended::Dropped;
+        }
+    }
+    shared.connected.store(true, Ordering::Release);
+    let _ = events.send(ClientEvent::Connected);
+
+    let ended = loop {
+        tokio::select! {
+            command = commands.recv() => match command {
+                Some(Command::Send(message)) => {
+                    if write(socket, &message).await.is_err() {
+                        // Keep element changes for the next connection
+                        if let Some(op) = to_queued(message) {
+                            shared.enqueue(op);
+                        }
+                        break Ended::Dropped;
+                    }
+                }
+                Some(Command::Subscribe(session_id)) => {
+                    if write(socket, &ClientMessage::Subscribe { session_id }).await.is_err() {
+                        break Ended::Dropped;
+                    }
+                }
+                Some(Command::Close) | None => break Ended::Closed,
+            },
+            frame = socket.next() => match frame {
+                Some(Ok(Message::Text(text))) => match serde_json::from_str::<ServerMessage>(&text) {
+                    Ok(message) => {
+                        shared.observe(&message);
+                        let _ = events.send(ClientEvent::Message(message));
+                    }
+                    Err(e) => tracing::debug!("Ignoring unrecognized server message: {e}"),
+                },
+                Some(Ok(Message::Close(_)) | Err(_)) | None => break Ended::Dropped,
+                Some(Ok(_)) => {}
+            },
+        }
+    };
+    shared.connected.store(false, Ordering::Release);
+    ended
+}
+
+/// Retry the connection according to the reconnect policy.
+///
+/// Returns `None` if the policy gives up or the client is closed meanwhile.
+/// Element messages sent while waiting are queued.
+async fn reconnect(
+    config: &ClientConfig,
+    commands: &mut mpsc::UnboundedReceiver<Command>,
+    shared: &Shared,
+) -> Option<Socket> {
+    let mut attempt = 0;
+    while config.reconnect.allows(attempt) {
+        let delay = tokio::time::sleep(config.reconnect.delay(attempt));
+        tokio::pin!(delay);
+        loop {
+            tokio::select! {
+                () = &mut delay => break,
+                command = commands.recv() => match command {
+                    Some(Command::Send(message)) => {
+                        if let Some(op) = to_queued(message) {
+                            shared.enqueue(op);
+                        }
+                    }
+                    // Picked up from the shared session ID on reconnect
+                    Some(Command::Subscribe(_)) => {}
+                    Some(Command::Close) | None => return None,
+                },
+            }
+        }
+
+        match connect_async(config.url.as_str()).await {
+            Ok((socket, _)) => return Some(socket),
+            Err(e) => {
+                tracing::debug!("Reconnect attempt {} failed: {e}", attempt + 1);
+                attempt += 1;
+            }
+        }
+    }
+    None
+}
+
+/// Encode and send one message.
+async fn write(socket: &mut Socket, message: &ClientMessage) -> Result<(), ClientError> {
+    let text = serde_json::to_string(message)?;
+    socket
+        .send(Message::Text(text))
+        .await
+        .map_err(|e| ClientError::Connect(e.to_string()))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_doubles_up_to_max() {
+        let policy = ReconnectPolicy {
+            initial_delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(500),
+            max_attempts: Some(3),
+        };
+        assert_eq!(policy.delay(0), Duration::from_millis(100));
+        assert_eq!(policy.delay(2), Duration::from_millis(400));
+        assert_eq!(policy.delay(10), Duration::from_millis(500));
+        assert!(policy.allows(2));
+        assert!(!policy.allows(3));
+        assert!(!ReconnectPolicy::disabled().allows(0));
+    }
+
+    #[test]
+    fn test_only_element_messages_are_queued() {
+        let queued = to_queued(ClientMessage::RemoveElement {
+            id: "abc".to_string(),
+            message_id: None,
+        });
+        assert!(matches!(queued, Some(QueuedOperation::Remove { ref id, .. }) if id == "abc"));
+        assert!(to_queued(ClientMessage::Ping).is_none());
+    }
+}
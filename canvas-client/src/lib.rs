@@ -0,0 +1,64 @@
+//! # Saorsa Canvas Client
+//!
+//! Rust client for the canvas WebSocket sync protocol (`/ws/sync`).
+//!
+//! [`CanvasClient`] owns a background task that keeps the connection alive:
+//!
+//! - typed messages - [`ClientMessage`] out, [`ServerMessage`] in (shared
+//!   with the server through `canvas_core::protocol`)
+//! - reconnect - dropped connections are retried with exponential backoff
+//!   ([`ReconnectPolicy`]) and the session is re-subscribed
+//! - offline queue - element adds, updates and removals sent while
+//!   disconnected are queued and flushed as one `sync_queue` on reconnect
+//! - subscriptions - any number of [`ClientEvent`] receivers, plus a mirror
+//!   of the session scene kept up to date from broadcasts
+//!
+//! ```no_run
+//! use canvas_client::{CanvasClient, ClientConfig, ClientEvent};
+//!
+//! # async fn run() -> Result<(), canvas_client::ClientError> {
+//! let client = CanvasClient::connect(
+//!     ClientConfig::new("ws://localhost:9473/ws/sync").with_session("design-review"),
+//! )
+//! .await?;
+//! let mut events = client.events();
+//! while let Ok(event) = events.recv().await {
+//!     if let ClientEvent::Message(message) = event {
+//!         println!("{message:?}");
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+#![forbid(unsafe_code)]
+#![deny(missing_docs)]
+#![deny(clippy::all)]
+#![deny(clippy::pedantic)]
+
+mod client;
+
+pub use canvas_core::protocol::{
+    BatchOperation, ClientMessage, FailedOperationInfo, OperationType, QueuedOperation,
+    ServerMessage,
+};
+pub use client::{CanvasClient, ClientConfig, ClientEvent, ReconnectPolicy};
+
+use thiserror::Error;
+
+/// Errors returned by [`CanvasClient`].
+#[derive(Debug, Error)]
+pub enum ClientError {
+    /// The WebSocket connection could not be established.
+    #[error("Connection failed: {0}")]
+    Connect(String),
+    /// The message cannot be queued and the client is offline.
+    #[error("Not connected")]
+    NotConnected,
+    /// The client was closed or gave up reconnecting.
+    #[error("Client closed")]
+    Closed,
+    /// A message could not be encoded.
+    #[error("Encoding error: {0}")]
+    Encode(#[from] serde_json::Error),
+}
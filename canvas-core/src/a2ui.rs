@@ -13,6 +13,7 @@
 //! | Image       | Image                | Static image                    |
 //! | Button      | Text (interactive)   | Clickable button with action    |
 //! | Chart       | Chart                | Data visualization              |
+//! | `Sparkline` | Sparkline            | Word-sized trend line or bars   |
 //! | `VideoFeed` | Video                | Live video stream               |
 //!
 //! ## Example A2UI JSON
@@ -33,7 +34,7 @@
 
 use serde::{Deserialize, Serialize};
 
-use crate::{Element, ElementKind, ImageFormat, Transform};
+use crate::{Element, ElementKind, ImageFormat, SparklineKind, ThemeColor, Transform};
 
 /// A2UI component tree from AI agent output.
 ///
@@ -54,12 +55,12 @@ pub struct A2UIStyle {
     /// Font size in pixels.
     #[serde(default)]
     pub font_size: Option<f32>,
-    /// Text/foreground color as hex string.
+    /// Text/foreground color.
     #[serde(default)]
-    pub color: Option<String>,
-    /// Background color as hex string.
+    pub color: Option<ThemeColor>,
+    /// Background color.
     #[serde(default)]
-    pub background: Option<String>,
+    pub background: Option<ThemeColor>,
     /// Width in pixels.
     #[serde(default)]
     pub width: Option<f32>,
@@ -135,6 +136,19 @@ pub enum A2UINode {
         style: Option<A2UIStyle>,
     },
 
+    /// A word-sized trend line or bar series, e.g. in a grid cell next to
+    /// a metric (Saorsa Canvas extension).
+    Sparkline {
+        /// Values, oldest first.
+        values: Vec<f32>,
+        /// `line` or `bar`.
+        #[serde(default)]
+        kind: SparklineKind,
+        /// Optional styling; `color` sets the line or bar color.
+        #[serde(default)]
+        style: Option<A2UIStyle>,
+    },
+
     /// A live video feed (Saorsa Canvas extension).
     VideoFeed {
         /// Stream identifier.
@@ -287,6 +301,14 @@ impl A2UIConverter {
                 vec![self.convert_chart(chart_type, data, style.as_ref(), x, y)]
             }
 
+            A2UINode::Sparkline {
+                values,
+                kind,
+                style,
+            } => {
+                vec![self.convert_sparkline(values, *kind, style.as_ref(), x, y)]
+            }
+
             A2UINode::VideoFeed {
                 stream_id,
                 mirror,
@@ -441,7 +463,7 @@ impl A2UIConverter {
         let font_size = style.and_then(|s| s.font_size).unwrap_or(16.0);
         let color = style
             .and_then(|s| s.color.clone())
-            .unwrap_or_else(|| "#000000".to_string());
+            .unwrap_or_else(|| ElementKind::TEXT_COLOR.into());
         let width = style.and_then(|s| s.width).unwrap_or(200.0);
         let height = style.and_then(|s| s.height).unwrap_or(font_size * 1.5);
 
@@ -457,6 +479,7 @@ impl A2UIConverter {
             height,
             rotation: 0.0,
             z_index: self.next_z_index(),
+            auto_size: false,
         })
     }
 
@@ -470,6 +493,8 @@ impl A2UIConverter {
         Element::new(ElementKind::Image {
             src: src.to_string(),
             format,
+            annotations: Vec::new(),
+            extracted_text: None,
         })
         .with_transform(Transform {
             x,
@@ -478,6 +503,7 @@ impl A2UIConverter {
             height,
             rotation: 0.0,
             z_index: self.next_z_index(),
+            auto_size: false,
         })
     }
 
@@ -495,6 +521,37 @@ impl A2UIConverter {
         Element::new(ElementKind::Chart {
             chart_type: chart_type.to_string(),
             data: data.clone(),
+            binding: None,
+        })
+        .with_transform(Transform {
+            x,
+            y,
+            width,
+            height,
+            rotation: 0.0,
+            z_index: self.next_z_index(),
+            auto_size: false,
+        })
+    }
+
+    fn convert_sparkline(
+        &mut self,
+        values: &[f32],
+        kind: SparklineKind,
+        style: Option<&A2UIStyle>,
+        x: f32,
+        y: f32,
+    ) -> Element {
+        let width = style.and_then(|s| s.width).unwrap_or(120.0);
+        let height = style.and_then(|s| s.height).unwrap_or(28.0);
+        let color = style
+            .and_then(|s| s.color.clone())
+            .unwrap_or_else(ElementKind::default_sparkline_color);
+
+        Element::new(ElementKind::Sparkline {
+            values: values.to_vec(),
+            kind,
+            color,
         })
         .with_transform(Transform {
             x,
@@ -503,6 +560,7 @@ impl A2UIConverter {
             height,
             rotation: 0.0,
             z_index: self.next_z_index(),
+            auto_size: false,
         })
     }
 
@@ -531,6 +589,7 @@ impl A2UIConverter {
             height,
             rotation: 0.0,
             z_index: self.next_z_index(),
+            auto_size: false,
         })
     }
 }
@@ -583,7 +642,10 @@ mod tests {
                 assert_eq!(content, "Styled Text");
                 let s = style.as_ref().expect("should have style");
                 assert_eq!(s.font_size, Some(24.0));
-                assert_eq!(s.color.as_deref(), Some("#FF0000"));
+                assert_eq!(
+                    s.color.as_ref().map(ToString::to_string).as_deref(),
+                    Some("#ff0000")
+                );
             }
             _ => panic!("Expected Text node"),
         }
@@ -816,7 +878,7 @@ mod tests {
                 font_size, color, ..
             } => {
                 assert!((font_size - 32.0).abs() < f32::EPSILON);
-                assert_eq!(color, "#00FF00");
+                assert_eq!(color.to_string(), "#00ff00");
             }
             _ => panic!("Expected Text element"),
         }
@@ -885,7 +947,7 @@ mod tests {
         assert_eq!(result.elements.len(), 1);
 
         match &result.elements[0].kind {
-            ElementKind::Image { src, format } => {
+            ElementKind::Image { src, format, .. } => {
                 assert_eq!(src, "test.png");
                 assert_eq!(*format, ImageFormat::Png);
             }
@@ -1124,6 +1186,43 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_sparklines_in_grid_cells() {
+        let json = r##"{
+            "root": {
+                "component": "container",
+                "layout": "grid:2",
+                "children": [
+                    { "component": "text", "content": "Signups" },
+                    { "component": "sparkline", "values": [3, 5, 4, 8], "kind": "bar",
+                      "style": { "color": "#43a047" } },
+                    { "component": "text", "content": "Churn" },
+                    { "component": "sparkline", "values": [2, 1, 1] }
+                ]
+            }
+        }"##;
+
+        let tree = A2UITree::from_json(json).expect("should parse");
+        let result = tree.to_elements();
+        assert!(result.warnings.is_empty());
+        assert_eq!(result.elements.len(), 4);
+
+        let bars = &result.elements[1];
+        assert!(matches!(
+            &bars.kind,
+            ElementKind::Sparkline { values, kind: SparklineKind::Bar, color }
+                if values.len() == 4 && *color == ThemeColor::parse("#43a047").expect("color")
+        ));
+        assert!((bars.transform.y - result.elements[0].transform.y).abs() < f32::EPSILON);
+        assert!((bars.transform.height - 28.0).abs() < f32::EPSILON);
+        let line = &result.elements[3];
+        assert!(matches!(
+            &line.kind,
+            ElementKind::Sparkline { kind: SparklineKind::Line, color, .. }
+                if *color == ElementKind::default_sparkline_color()
+        ));
+    }
+
     #[test]
     fn test_convert_stack_layout() {
         let json = r#"{
@@ -1473,8 +1572,8 @@ mod tests {
             A2UINode::Text { style, .. } => {
                 let s = style.as_ref().expect("should have style");
                 assert!((s.font_size.unwrap() - 18.5).abs() < 0.001);
-                assert_eq!(s.color.as_deref(), Some("#123ABC"));
-                assert_eq!(s.background.as_deref(), Some("#FFFFFF"));
+                assert_eq!(s.color, Some(crate::Color::from_rgb(0x12_3abc).into()));
+                assert_eq!(s.background, Some(crate::Color::WHITE.into()));
                 assert!((s.padding.unwrap() - 10.0).abs() < 0.001);
                 assert!((s.margin.unwrap() - 5.0).abs() < 0.001);
                 assert!((s.width.unwrap() - 200.0).abs() < 0.001);
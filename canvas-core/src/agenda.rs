@@ -0,0 +1,555 @@
+//! # Agenda Timing and Layout
+//!
+//! Schedule, countdown and geometry for `Agenda` elements.
+//!
+//! An agenda shows a clock in its header above one row per timed item; the
+//! running item is highlighted with a progress bar and finished items are
+//! dimmed:
+//!
+//! ```text
+//! ┌───────────────────────────────────┐
+//! │ Weekly sync            04:12 left │  header: countdown or time left
+//! ├───────────────────────────────────┤
+//! │ Welcome                     5 min │  done
+//! │▌Roadmap review · Ada       15 min │  running, with progress
+//! │ Hiring                     10 min │  upcoming
+//! └───────────────────────────────────┘
+//! ```
+//!
+//! The element stores only when one item started (`started_at_ms`, on the
+//! server's clock); the items after it follow on by their durations. Every
+//! client derives [`AgendaStatus`] from the same timestamp and its estimate
+//! of the server clock, so all participants see the same item highlighted
+//! without the server sending a tick every second. Before `started_at_ms`
+//! the header counts down to the start. [`apply`] advances or rewinds the
+//! schedule by restarting it from another item.
+
+use serde::{Deserialize, Serialize};
+
+use crate::element::ElementKind;
+use crate::error::{CanvasError, CanvasResult};
+
+/// Most items an agenda may have.
+pub const MAX_AGENDA_ITEMS: usize = 30;
+
+/// Longest an agenda item may run, in seconds (one day).
+pub const MAX_AGENDA_ITEM_SECS: u32 = 86_400;
+
+/// A timed item on an agenda.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+pub struct AgendaItem {
+    /// What is discussed.
+    pub title: String,
+    /// Time allotted, in seconds.
+    pub duration_secs: u32,
+    /// Who leads the item.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+}
+
+impl AgendaItem {
+    /// Create an item without an owner.
+    #[must_use]
+    pub fn new(title: impl Into<String>, duration_secs: u32) -> Self {
+        Self {
+            title: title.into(),
+            duration_secs,
+            owner: None,
+        }
+    }
+
+    /// Set who leads the item.
+    #[must_use]
+    pub fn with_owner(mut self, owner: impl Into<String>) -> Self {
+        self.owner = Some(owner.into());
+        self
+    }
+
+    /// Time allotted, in milliseconds.
+    #[must_use]
+    pub fn duration_ms(&self) -> u64 {
+        u64::from(self.duration_secs) * 1000
+    }
+
+    /// Title and owner as shown on the row, e.g. `"Roadmap review · Ada"`.
+    #[must_use]
+    pub fn label(&self) -> String {
+        match &self.owner {
+            Some(owner) => format!("{} · {owner}", self.title),
+            None => self.title.clone(),
+        }
+    }
+
+    /// Time allotted as shown on the row, e.g. `"15 min"`, `"45 s"` or
+    /// `"02:30"`.
+    #[must_use]
+    pub fn duration_text(&self) -> String {
+        let secs = self.duration_secs;
+        if secs < 60 {
+            format!("{secs} s")
+        } else if secs.is_multiple_of(60) {
+            format!("{} min", secs / 60)
+        } else {
+            format_clock(self.duration_ms())
+        }
+    }
+}
+
+/// Write a duration as `mm:ss`, or `h:mm:ss` from an hour up.
+///
+/// Partial seconds round up, so a countdown shows `00:01` until it is over.
+#[must_use]
+pub fn format_clock(ms: u64) -> String {
+    let secs = ms.div_ceil(1000);
+    let (hours, minutes, seconds) = (secs / 3600, secs / 60 % 60, secs % 60);
+    if hours > 0 {
+        format!("{hours}:{minutes:02}:{seconds:02}")
+    } else {
+        format!("{minutes:02}:{seconds:02}")
+    }
+}
+
+/// Where an agenda's schedule stands at a moment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "phase", rename_all = "snake_case")]
+pub enum AgendaStatus {
+    /// Not started or scheduled.
+    Idle,
+    /// Scheduled to start later.
+    Countdown {
+        /// Time until the start.
+        remaining_ms: u64,
+    },
+    /// An item is running.
+    Running {
+        /// Index of the running item.
+        index: usize,
+        /// Time since the item started.
+        elapsed_ms: u64,
+        /// Time left for the item.
+        remaining_ms: u64,
+    },
+    /// Every item's time is up.
+    Finished,
+}
+
+impl AgendaStatus {
+    /// Where the schedule of `items` stands at `now_ms`, when item `current`
+    /// started at `started_at_ms` (both Unix milliseconds on the server's
+    /// clock).
+    #[must_use]
+    pub fn at(
+        items: &[AgendaItem],
+        current: usize,
+        started_at_ms: Option<u64>,
+        now_ms: u64,
+    ) -> Self {
+        let Some(started_at_ms) = started_at_ms else {
+            return Self::Idle;
+        };
+        if now_ms < started_at_ms {
+            return Self::Countdown {
+                remaining_ms: started_at_ms - now_ms,
+            };
+        }
+        let mut elapsed_ms = now_ms - started_at_ms;
+        for (index, item) in items.iter().enumerate().skip(current) {
+            let duration = item.duration_ms();
+            if elapsed_ms < duration {
+                return Self::Running {
+                    index,
+                    elapsed_ms,
+                    remaining_ms: duration - elapsed_ms,
+                };
+            }
+            elapsed_ms -= duration;
+        }
+        Self::Finished
+    }
+
+    /// Where an agenda element stands at `now_ms`, or `None` if `kind` is
+    /// not an agenda.
+    #[must_use]
+    pub fn from_kind(kind: &ElementKind, now_ms: u64) -> Option<Self> {
+        let ElementKind::Agenda {
+            items,
+            current,
+            started_at_ms,
+            ..
+        } = kind
+        else {
+            return None;
+        };
+        Some(Self::at(items, *current, *started_at_ms, now_ms))
+    }
+
+    /// Index of the running item, if any.
+    #[must_use]
+    pub const fn current(&self) -> Option<usize> {
+        match self {
+            Self::Running { index, .. } => Some(*index),
+            _ => None,
+        }
+    }
+
+    /// Whether the display changes as time passes, so clients should keep
+    /// redrawing.
+    #[must_use]
+    pub const fn is_ticking(&self) -> bool {
+        matches!(self, Self::Countdown { .. } | Self::Running { .. })
+    }
+
+    /// Fraction of the running item's time used, `0.0..1.0`.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn progress(&self) -> Option<f32> {
+        match self {
+            Self::Running {
+                elapsed_ms,
+                remaining_ms,
+                ..
+            } => Some(*elapsed_ms as f32 / (elapsed_ms + remaining_ms) as f32),
+            _ => None,
+        }
+    }
+
+    /// Whether item `index` is over: before the running item, or any item
+    /// once the agenda has finished.
+    #[must_use]
+    pub const fn is_done(&self, index: usize) -> bool {
+        match self {
+            Self::Running { index: current, .. } => index < *current,
+            Self::Finished => true,
+            Self::Idle | Self::Countdown { .. } => false,
+        }
+    }
+
+    /// Header clock text, e.g. `"Starts in 04:59"`, `"12:03 left"`, or the
+    /// total time of `items` before the agenda is started.
+    #[must_use]
+    pub fn clock_text(&self, items: &[AgendaItem]) -> String {
+        match self {
+            Self::Idle => {
+                let total: u64 = items.iter().map(AgendaItem::duration_ms).sum();
+                format!("{} total", format_clock(total))
+            }
+            Self::Countdown { remaining_ms } => {
+                format!("Starts in {}", format_clock(*remaining_ms))
+            }
+            Self::Running { remaining_ms, .. } => format!("{} left", format_clock(*remaining_ms)),
+            Self::Finished => "Done".to_string(),
+        }
+    }
+}
+
+/// A change to an agenda's schedule.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AgendaCommand {
+    /// Start the first item after `delay_ms`, counting down until then.
+    Start {
+        /// Time until the start.
+        delay_ms: u64,
+    },
+    /// End the running item and start the next one (or the first, if the
+    /// agenda has not started).
+    Advance,
+    /// Restart the previous item, or the running one if it is the first.
+    Back,
+    /// Start an item now.
+    GoTo(usize),
+    /// Clear the schedule.
+    Reset,
+}
+
+/// Apply `command` to an agenda at `now_ms` and return where it then
+/// stands.
+///
+/// # Errors
+///
+/// Returns [`CanvasError::InvalidOperation`] if `kind` is not an agenda,
+/// there is no item to move to or the item index is out of range.
+pub fn apply(
+    kind: &mut ElementKind,
+    command: AgendaCommand,
+    now_ms: u64,
+) -> CanvasResult<AgendaStatus> {
+    let ElementKind::Agenda {
+        items,
+        current,
+        started_at_ms,
+        ..
+    } = kind
+    else {
+        return Err(CanvasError::InvalidOperation(format!(
+            "{} is not an agenda",
+            kind.type_name()
+        )));
+    };
+    let status = AgendaStatus::at(items, *current, *started_at_ms, now_ms);
+    let (next, start) = match command {
+        AgendaCommand::Start { delay_ms } => (0, Some(now_ms.saturating_add(delay_ms))),
+        AgendaCommand::Advance => match status {
+            AgendaStatus::Idle | AgendaStatus::Countdown { .. } => (0, Some(now_ms)),
+            // Past the last item, the schedule reads as finished
+            AgendaStatus::Running { index, .. } => (index + 1, Some(now_ms)),
+            AgendaStatus::Finished => {
+                return Err(CanvasError::InvalidOperation(
+                    "Agenda is finished".to_string(),
+                ))
+            }
+        },
+        AgendaCommand::Back => match status {
+            AgendaStatus::Running { index, .. } => (index.saturating_sub(1), Some(now_ms)),
+            AgendaStatus::Finished => (items.len().saturating_sub(1), Some(now_ms)),
+            AgendaStatus::Idle | AgendaStatus::Countdown { .. } => {
+                return Err(CanvasError::InvalidOperation(
+                    "Agenda has not started".to_string(),
+                ))
+            }
+        },
+        AgendaCommand::GoTo(index) if index < items.len() => (index, Some(now_ms)),
+        AgendaCommand::GoTo(index) => {
+            return Err(CanvasError::InvalidOperation(format!(
+                "Agenda has no item {index}"
+            )))
+        }
+        AgendaCommand::Reset => (0, None),
+    };
+    *current = next;
+    *started_at_ms = start;
+    Ok(AgendaStatus::at(items, *current, *started_at_ms, now_ms))
+}
+
+/// Check an agenda's items and schedule.
+///
+/// # Errors
+///
+/// Returns [`CanvasError::InvalidOperation`] if there are no items or more
+/// than [`MAX_AGENDA_ITEMS`], an item has no title or a duration outside
+/// `1..=MAX_AGENDA_ITEM_SECS`, or `current` is past the end.
+pub fn validate(kind: &ElementKind) -> CanvasResult<()> {
+    let ElementKind::Agenda { items, current, .. } = kind else {
+        return Ok(());
+    };
+    let invalid = |message: String| Err(CanvasError::InvalidOperation(message));
+    if !(1..=MAX_AGENDA_ITEMS).contains(&items.len()) {
+        return invalid(format!(
+            "agendas need 1 to {MAX_AGENDA_ITEMS} items, got {}",
+            items.len()
+        ));
+    }
+    if let Some(index) = items.iter().position(|item| item.title.trim().is_empty()) {
+        return invalid(format!("agenda item {index} has no title"));
+    }
+    if let Some(item) = items
+        .iter()
+        .find(|item| !(1..=MAX_AGENDA_ITEM_SECS).contains(&item.duration_secs))
+    {
+        return invalid(format!(
+            "agenda item '{}' must last 1 to {MAX_AGENDA_ITEM_SECS} seconds",
+            item.title
+        ));
+    }
+    if *current > items.len() {
+        return invalid(format!(
+            "agenda has {} items, cannot run from item {current}",
+            items.len()
+        ));
+    }
+    Ok(())
+}
+
+/// An item row in an [`AgendaLayout`], in the element's local frame.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AgendaRow {
+    /// Item index.
+    pub index: usize,
+    /// Left edge.
+    pub x: f32,
+    /// Top edge.
+    pub y: f32,
+    /// Width.
+    pub width: f32,
+    /// Height.
+    pub height: f32,
+}
+
+impl AgendaRow {
+    /// Font size for the row's label and duration.
+    #[must_use]
+    pub fn font_size(&self) -> f32 {
+        (self.height * 0.45).clamp(8.0, 16.0)
+    }
+
+    /// The progress bar along the bottom of a running row, `[x, y, width,
+    /// height]` in the local frame.
+    #[must_use]
+    pub fn progress_bar(&self, progress: f32) -> [f32; 4] {
+        let height = (self.height * 0.1).clamp(2.0, 4.0);
+        [
+            self.x,
+            self.y + self.height - height,
+            self.width * progress.clamp(0.0, 1.0),
+            height,
+        ]
+    }
+}
+
+/// Geometry of an agenda element.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AgendaLayout {
+    /// Height of the header band with the title and clock.
+    pub header_height: f32,
+    /// Item rows, top to bottom.
+    pub rows: Vec<AgendaRow>,
+}
+
+impl AgendaLayout {
+    /// Space around and between rows.
+    pub const PADDING: f32 = 4.0;
+
+    /// Lay out `item_count` items in a `width` × `height` element.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn new(item_count: usize, width: f32, height: f32) -> Self {
+        let header_height = (height * 0.2).clamp(20.0, 40.0).min(height / 3.0);
+        let pad = Self::PADDING;
+        let slot = (height - header_height - pad).max(0.0) / item_count.max(1) as f32;
+        let rows = (0..item_count)
+            .map(|index| AgendaRow {
+                index,
+                x: pad,
+                y: header_height + pad + index as f32 * slot,
+                width: (width - 2.0 * pad).max(0.0),
+                height: (slot - pad).max(0.0),
+            })
+            .collect();
+        Self {
+            header_height,
+            rows,
+        }
+    }
+
+    /// Font size for the header's title and clock.
+    #[must_use]
+    pub fn header_font_size(&self) -> f32 {
+        self.header_height * 0.45
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn agenda() -> ElementKind {
+        ElementKind::Agenda {
+            title: "Weekly sync".to_string(),
+            items: vec![
+                AgendaItem::new("Welcome", 300),
+                AgendaItem::new("Roadmap review", 900).with_owner("Ada"),
+                AgendaItem::new("Hiring", 600),
+            ],
+            current: 0,
+            started_at_ms: None,
+        }
+    }
+
+    #[test]
+    fn test_schedule_follows_the_clock() {
+        let ElementKind::Agenda { items, .. } = agenda() else {
+            unreachable!()
+        };
+        let start = 1_000_000;
+        assert_eq!(AgendaStatus::at(&items, 0, None, start), AgendaStatus::Idle);
+        assert_eq!(
+            AgendaStatus::at(&items, 0, Some(start), start - 61_500),
+            AgendaStatus::Countdown {
+                remaining_ms: 61_500
+            }
+        );
+        // Six minutes in: one minute into the second item
+        let status = AgendaStatus::at(&items, 0, Some(start), start + 360_000);
+        assert_eq!(
+            status,
+            AgendaStatus::Running {
+                index: 1,
+                elapsed_ms: 60_000,
+                remaining_ms: 840_000
+            }
+        );
+        assert!(status.is_done(0) && !status.is_done(1));
+        assert_eq!(status.clock_text(&items), "14:00 left");
+        assert_eq!(
+            AgendaStatus::at(&items, 1, Some(start), start + 1_500_000),
+            AgendaStatus::Finished
+        );
+        assert_eq!(AgendaStatus::Idle.clock_text(&items), "30:00 total");
+        assert_eq!(
+            AgendaStatus::Countdown { remaining_ms: 1 }.clock_text(&items),
+            "Starts in 00:01"
+        );
+        assert_eq!(format_clock(3_725_000), "1:02:05");
+        assert_eq!(items[1].label(), "Roadmap review · Ada");
+        assert_eq!(items[1].duration_text(), "15 min");
+        assert_eq!(AgendaItem::new("Break", 90).duration_text(), "01:30");
+    }
+
+    #[test]
+    fn test_commands_restart_the_schedule() {
+        let mut kind = agenda();
+        let now = 5_000_000;
+        assert!(apply(&mut kind, AgendaCommand::Back, now).is_err());
+        let status = apply(&mut kind, AgendaCommand::Start { delay_ms: 60_000 }, now).unwrap();
+        assert!(matches!(status, AgendaStatus::Countdown { .. }));
+
+        // Advancing during the countdown starts the first item now
+        let status = apply(&mut kind, AgendaCommand::Advance, now).unwrap();
+        assert_eq!(status.current(), Some(0));
+        let status = apply(&mut kind, AgendaCommand::Advance, now + 10_000).unwrap();
+        assert_eq!(status.current(), Some(1));
+        assert_eq!(
+            status,
+            AgendaStatus::Running {
+                index: 1,
+                elapsed_ms: 0,
+                remaining_ms: 900_000
+            }
+        );
+        let status = apply(&mut kind, AgendaCommand::Back, now + 20_000).unwrap();
+        assert_eq!(status.current(), Some(0));
+
+        let status = apply(&mut kind, AgendaCommand::GoTo(2), now).unwrap();
+        assert_eq!(status.current(), Some(2));
+        assert_eq!(
+            apply(&mut kind, AgendaCommand::Advance, now).unwrap(),
+            AgendaStatus::Finished
+        );
+        assert!(validate(&kind).is_ok());
+        assert!(apply(&mut kind, AgendaCommand::Advance, now).is_err());
+        assert!(apply(&mut kind, AgendaCommand::GoTo(3), now).is_err());
+        assert_eq!(
+            apply(&mut kind, AgendaCommand::Reset, now).unwrap(),
+            AgendaStatus::Idle
+        );
+    }
+
+    #[test]
+    fn test_validate_and_layout() {
+        let mut kind = agenda();
+        assert!(validate(&kind).is_ok());
+        if let ElementKind::Agenda { items, .. } = &mut kind {
+            items[2].duration_secs = 0;
+        }
+        assert!(validate(&kind).is_err());
+
+        let layout = AgendaLayout::new(3, 360.0, 156.0);
+        assert_eq!(layout.rows.len(), 3);
+        let row = layout.rows[1];
+        assert!((layout.header_height - 31.2).abs() < 1e-4);
+        assert!((row.y - (31.2 + 4.0 + 40.266_666)).abs() < 1e-3);
+        let [x, _, width, _] = row.progress_bar(0.5);
+        assert!((x - 4.0).abs() < f32::EPSILON);
+        assert!((width - 176.0).abs() < 1e-4);
+    }
+}
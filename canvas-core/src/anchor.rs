@@ -0,0 +1,157 @@
+//! # Named Anchors
+//!
+//! Saved views of a scene that agents and users can jump between.
+//!
+//! An anchor records the canvas point at the center of the view and the
+//! zoom level, not a raw pan offset, so it frames the same area on screens
+//! of any size:
+//!
+//! ```text
+//! set_anchor(Anchor::new("summary", 400.0, 300.0, 1.0))
+//! set_anchor(Anchor::capture("details", &scene))   ← the current view
+//! go_to_anchor("summary")                          → zoom 1.0, (400, 300) centered
+//! ```
+//!
+//! Anchors are kept in the order they were first saved, so they can double
+//! as a sequence of waypoints (e.g. the steps of a walkthrough).
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{CanvasError, CanvasResult};
+use crate::follow::CameraFrame;
+use crate::scene::Scene;
+
+/// Longest allowed anchor name, in characters.
+pub const MAX_ANCHOR_NAME_LEN: usize = 64;
+
+/// A named view: a canvas point to center and a zoom level.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+pub struct Anchor {
+    /// Unique name within the scene, e.g. `"summary"`.
+    pub name: String,
+    /// Canvas X coordinate shown at the center of the viewport.
+    pub x: f32,
+    /// Canvas Y coordinate shown at the center of the viewport.
+    pub y: f32,
+    /// Zoom level (1.0 = 100%).
+    #[serde(default = "Anchor::default_zoom")]
+    pub zoom: f32,
+}
+
+impl Anchor {
+    const fn default_zoom() -> f32 {
+        1.0
+    }
+
+    /// Create an anchor centered on (`x`, `y`) at `zoom`.
+    #[must_use]
+    pub fn new(name: impl Into<String>, x: f32, y: f32, zoom: f32) -> Self {
+        Self {
+            name: name.into(),
+            x,
+            y,
+            zoom,
+        }
+    }
+
+    /// Create an anchor for the view a scene currently shows.
+    #[must_use]
+    pub fn capture(name: impl Into<String>, scene: &Scene) -> Self {
+        Self::from_frame(name, &CameraFrame::from_scene(scene), scene)
+    }
+
+    /// Create an anchor for the view `frame` would show in `scene`'s viewport.
+    #[must_use]
+    pub fn from_frame(name: impl Into<String>, frame: &CameraFrame, scene: &Scene) -> Self {
+        let zoom = if frame.zoom > 0.0 { frame.zoom } else { 1.0 };
+        Self::new(
+            name,
+            (scene.viewport_width / 2.0 - frame.pan_x) / zoom,
+            (scene.viewport_height / 2.0 - frame.pan_y) / zoom,
+            zoom,
+        )
+    }
+
+    /// The camera frame that centers this anchor in `scene`'s viewport.
+    #[must_use]
+    pub fn frame(&self, scene: &Scene) -> CameraFrame {
+        CameraFrame {
+            zoom: self.zoom,
+            pan_x: scene.viewport_width / 2.0 - self.x * self.zoom,
+            pan_y: scene.viewport_height / 2.0 - self.y * self.zoom,
+        }
+    }
+
+    /// Check the anchor can be stored.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CanvasError::InvalidOperation`] if the name is blank or
+    /// longer than [`MAX_ANCHOR_NAME_LEN`] characters, the position is not
+    /// finite, or the zoom is not a positive number.
+    pub fn validate(&self) -> CanvasResult<()> {
+        if self.name.trim().is_empty() {
+            return Err(CanvasError::InvalidOperation(
+                "Anchor name must not be empty".to_string(),
+            ));
+        }
+        if self.name.chars().count() > MAX_ANCHOR_NAME_LEN {
+            return Err(CanvasError::InvalidOperation(format!(
+                "Anchor name is longer than {MAX_ANCHOR_NAME_LEN} characters"
+            )));
+        }
+        if !self.x.is_finite() || !self.y.is_finite() {
+            return Err(CanvasError::InvalidOperation(format!(
+                "Anchor {} has a non-finite position",
+                self.name
+            )));
+        }
+        if !(self.zoom.is_finite() && self.zoom > 0.0) {
+            return Err(CanvasError::InvalidOperation(format!(
+                "Anchor {} needs a positive zoom, got {}",
+                self.name, self.zoom
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capture_round_trips_through_frame() {
+        let mut scene = Scene::new(800.0, 600.0);
+        scene.zoom = 2.0;
+        scene.pan_x = -200.0;
+        scene.pan_y = 100.0;
+
+        let anchor = Anchor::capture("details", &scene);
+        assert!((anchor.x - 300.0).abs() < f32::EPSILON);
+        assert!((anchor.y - 100.0).abs() < f32::EPSILON);
+        assert_eq!(anchor.frame(&scene), CameraFrame::from_scene(&scene));
+
+        // A smaller viewport keeps the same point centered
+        scene.set_viewport(400.0, 300.0);
+        let frame = anchor.frame(&scene);
+        assert!((frame.pan_x - (200.0 - 600.0)).abs() < f32::EPSILON);
+        assert!((frame.pan_y - (150.0 - 200.0)).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(Anchor::new("summary", 0.0, 0.0, 1.0).validate().is_ok());
+        assert!(Anchor::new("  ", 0.0, 0.0, 1.0).validate().is_err());
+        assert!(Anchor::new("x".repeat(65), 0.0, 0.0, 1.0)
+            .validate()
+            .is_err());
+        assert!(Anchor::new("nan", f32::NAN, 0.0, 1.0).validate().is_err());
+        assert!(Anchor::new("flat", 0.0, 0.0, 0.0).validate().is_err());
+
+        let parsed: Anchor =
+            serde_json::from_str(r#"{"name":"summary","x":10,"y":20}"#).expect("parse");
+        assert!((parsed.zoom - 1.0).abs() < f32::EPSILON);
+    }
+}
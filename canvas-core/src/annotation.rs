@@ -0,0 +1,483 @@
+//! # Annotations
+//!
+//! Marks that agents draw over charts, images and video frames while
+//! explaining them: labeled measurement lines, arrows and region
+//! highlights.
+//!
+//! ```text
+//!        120 px                   ┌ Peak ──┬──────────┐
+//!  ├────────────────┤             │░░░░░░░░░░░░░░░░░░│  region: tinted,
+//!   dimension line                │░░░░░░░░░░░░░░░░░░│  bordered, captioned
+//!                                 └──────────────────┘
+//!        3.2 m
+//!  ├────┬────┬────┬─
+//!  ╵ ╵ ╵ ╵ ╵ ╵ ╵ ╵ ╵   ruler: minor ticks, every fifth one major
+//! ```
+//!
+//! [`MeasurementLayout`] turns a `Measurement` element into canvas
+//! coordinates, and [`caption_tab`] places a `Region`'s caption, so every
+//! renderer draws the same marks.
+//!
+//! ## Image markup
+//!
+//! Marks ([`is_markup`]) can be pinned to an `Image` as its `annotations`,
+//! for marking up screenshots. [`Scene::update_element`] moves and scales
+//! them with the image ([`refit`]), and exporters can flatten an image and
+//! its marks into a single picture.
+//!
+//! [`Scene::update_element`]: crate::Scene::update_element
+
+use serde::{Deserialize, Serialize};
+
+use crate::element::{Element, ElementKind, Transform};
+use crate::error::{CanvasError, CanvasResult};
+use crate::units::CoordinateSystem;
+
+/// Space around a measurement line, for its end caps, ticks and label.
+pub const MEASUREMENT_PADDING: f32 = 20.0;
+
+/// Half the length of a dimension line's end caps.
+pub const DIMENSION_CAP: f32 = 6.0;
+
+/// Length of a ruler's minor ticks.
+pub const RULER_MINOR_TICK: f32 = 4.0;
+
+/// Length of a ruler's major ticks.
+pub const RULER_MAJOR_TICK: f32 = 8.0;
+
+/// Length of the two strokes of an arrowhead.
+pub const ARROW_HEAD: f32 = 12.0;
+
+/// Most ticks drawn on a ruler.
+pub const MAX_RULER_TICKS: usize = 200;
+
+/// Distance from a measurement line to the center of its label.
+pub const LABEL_OFFSET: f32 = 10.0;
+
+/// Font size of measurement labels and region captions.
+pub const ANNOTATION_FONT_SIZE: f32 = 12.0;
+
+/// Opacity of a region's tint.
+pub const REGION_TINT: f32 = 0.15;
+
+/// Height of a region's caption tab.
+pub const CAPTION_TAB_HEIGHT: f32 = 20.0;
+
+/// Longest label or caption, in characters.
+pub const MAX_ANNOTATION_TEXT_LEN: usize = 200;
+
+/// How a measurement line is drawn.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+#[serde(rename_all = "lowercase")]
+pub enum MeasurementKind {
+    /// A line with perpendicular caps at both ends.
+    #[default]
+    Dimension,
+    /// A line with evenly spaced ticks in round units.
+    Ruler,
+    /// A line with an arrowhead at its end, labeled only if given a label.
+    Arrow,
+}
+
+/// A `Measurement` element in canvas coordinates.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MeasurementLayout {
+    /// Start of the line.
+    pub start: [f32; 2],
+    /// End of the line.
+    pub end: [f32; 2],
+    /// End caps or ticks, as `[from, to]` segments.
+    pub marks: Vec<[[f32; 2]; 2]>,
+    /// Label text: the given label or the measured length.
+    pub label: String,
+    /// Center of the label, beside the middle of the line.
+    pub label_at: [f32; 2],
+}
+
+impl MeasurementLayout {
+    /// Lay out a `Measurement` element; `None` for other kinds.
+    ///
+    /// The label sits on the upper side of the line (the right side of a
+    /// vertical one) and ruler ticks on the other.
+    #[must_use]
+    pub fn from_element(element: &Element) -> Option<Self> {
+        let ElementKind::Measurement {
+            start,
+            end,
+            kind,
+            label,
+            unit,
+            scale,
+            ..
+        } = &element.kind
+        else {
+            return None;
+        };
+        let t = &element.transform;
+        let start = [t.x + start[0] * t.width, t.y + start[1] * t.height];
+        let end = [t.x + end[0] * t.width, t.y + end[1] * t.height];
+        let (dx, dy) = (end[0] - start[0], end[1] - start[1]);
+        let length = dx.hypot(dy);
+        // Unit normal pointing up, or right for vertical lines
+        let normal = if length > f32::EPSILON {
+            let (nx, ny) = (-dy / length, dx / length);
+            if ny > 0.0 || (ny.abs() <= f32::EPSILON && nx < 0.0) {
+                [-nx, -ny]
+            } else {
+                [nx, ny]
+            }
+        } else {
+            [0.0, -1.0]
+        };
+        let offset = |p: [f32; 2], by: f32| [p[0] + normal[0] * by, p[1] + normal[1] * by];
+
+        let marks = if length <= f32::EPSILON {
+            Vec::new()
+        } else {
+            match kind {
+                MeasurementKind::Dimension => [start, end]
+                    .into_iter()
+                    .map(|p| [offset(p, -DIMENSION_CAP), offset(p, DIMENSION_CAP)])
+                    .collect(),
+                MeasurementKind::Ruler => ruler_steps(length, *scale)
+                    .into_iter()
+                    .map(|(along, major)| {
+                        let at = [start[0] + dx * along, start[1] + dy * along];
+                        let tick = if major {
+                            RULER_MAJOR_TICK
+                        } else {
+                            RULER_MINOR_TICK
+                        };
+                        [at, offset(at, -tick)]
+                    })
+                    .collect(),
+                MeasurementKind::Arrow => {
+                    let back = [
+                        end[0] - dx / length * ARROW_HEAD,
+                        end[1] - dy / length * ARROW_HEAD,
+                    ];
+                    [ARROW_HEAD / 2.0, -ARROW_HEAD / 2.0]
+                        .into_iter()
+                        .map(|by| [end, offset(back, by)])
+                        .collect()
+                }
+            }
+        };
+        let label = match label.as_deref().map(str::trim) {
+            Some(label) if !label.is_empty() => label.to_string(),
+            _ if *kind == MeasurementKind::Arrow => String::new(),
+            _ => format_length(length * scale, unit),
+        };
+        let middle = [
+            f32::midpoint(start[0], end[0]),
+            f32::midpoint(start[1], end[1]),
+        ];
+        Some(Self {
+            start,
+            end,
+            marks,
+            label,
+            label_at: offset(middle, LABEL_OFFSET),
+        })
+    }
+}
+
+/// A `Measurement` element from `start` to `end` in canvas coordinates,
+/// with room around the line for its marks and label.
+#[must_use]
+pub fn measurement(start: [f32; 2], end: [f32; 2], kind: MeasurementKind) -> Element {
+    let pad = MEASUREMENT_PADDING;
+    let (x, y) = (start[0].min(end[0]) - pad, start[1].min(end[1]) - pad);
+    let width = (start[0] - end[0]).abs() + 2.0 * pad;
+    let height = (start[1] - end[1]).abs() + 2.0 * pad;
+    let fraction = |p: [f32; 2]| [(p[0] - x) / width, (p[1] - y) / height];
+    Element::new(ElementKind::Measurement {
+        start: fraction(start),
+        end: fraction(end),
+        kind,
+        label: None,
+        unit: ElementKind::default_measurement_unit(),
+        scale: ElementKind::default_measurement_scale(),
+        color: ElementKind::default_annotation_color(),
+    })
+    .with_transform(Transform {
+        x,
+        y,
+        width,
+        height,
+        rotation: 0.0,
+        z_index: 0,
+        auto_size: false,
+    })
+}
+
+/// A `Measurement` element like [`measurement`], labeled in the units of
+/// `coordinates` instead of pixels.
+#[must_use]
+pub fn measurement_in(
+    start: [f32; 2],
+    end: [f32; 2],
+    kind: MeasurementKind,
+    coordinates: &CoordinateSystem,
+) -> Element {
+    let mut element = measurement(start, end, kind);
+    if let ElementKind::Measurement { unit, scale, .. } = &mut element.kind {
+        *unit = coordinates.unit.symbol().to_string();
+        *scale = coordinates.length_to_world(1.0);
+    }
+    element
+}
+
+/// A measured length with its unit: whole numbers from 100 up, one
+/// decimal below, without a trailing `.0`.
+#[must_use]
+pub fn format_length(value: f32, unit: &str) -> String {
+    let number = if value.abs() >= 100.0 {
+        format!("{value:.0}")
+    } else {
+        let number = format!("{value:.1}");
+        number
+            .strip_suffix(".0")
+            .map_or_else(|| number.clone(), str::to_string)
+    };
+    if unit.is_empty() {
+        number
+    } else {
+        format!("{number} {unit}")
+    }
+}
+
+/// Caption tab of a region with box `t`, as `[x, y, width, height]` in
+/// canvas coordinates: inside the top-left corner, about as wide as the
+/// caption and never wider than the region.
+#[must_use]
+#[allow(clippy::cast_precision_loss)]
+pub fn caption_tab(t: &Transform, caption: &str) -> [f32; 4] {
+    let text_width = caption.chars().count() as f32 * ANNOTATION_FONT_SIZE * 0.6;
+    [
+        t.x,
+        t.y,
+        (text_width + 12.0).min(t.width.max(0.0)),
+        CAPTION_TAB_HEIGHT.min(t.height.max(0.0)),
+    ]
+}
+
+/// Check a `Measurement` or `Region` kind; other kinds pass.
+///
+/// # Errors
+///
+/// Returns [`CanvasError::InvalidOperation`] if a point is not finite, the
+/// scale is not positive, or a label, caption or unit is too long.
+pub fn validate(kind: &ElementKind) -> CanvasResult<()> {
+    let (text, unit) = match kind {
+        ElementKind::Measurement {
+            start,
+            end,
+            label,
+            unit,
+            scale,
+            ..
+        } => {
+            if start.iter().chain(end).any(|v| !v.is_finite()) {
+                return Err(CanvasError::InvalidOperation(
+                    "Measurement points must be finite".to_string(),
+                ));
+            }
+            if !(scale.is_finite() && *scale > 0.0) {
+                return Err(CanvasError::InvalidOperation(format!(
+                    "Measurement scale must be positive, got {scale}"
+                )));
+            }
+            (label.as_deref(), Some(unit.as_str()))
+        }
+        ElementKind::Region { caption, .. } => (caption.as_deref(), None),
+        _ => return Ok(()),
+    };
+    if text
+        .into_iter()
+        .chain(unit)
+        .any(|text| text.chars().count() > MAX_ANNOTATION_TEXT_LEN)
+    {
+        return Err(CanvasError::InvalidOperation(format!(
+            "Annotation text must be at most {MAX_ANNOTATION_TEXT_LEN} characters"
+        )));
+    }
+    Ok(())
+}
+
+/// Check whether a kind can be pinned to an image as markup: ink, text,
+/// callouts, shapes, measurement lines and arrows, and regions.
+#[must_use]
+pub const fn is_markup(kind: &ElementKind) -> bool {
+    matches!(
+        kind,
+        ElementKind::Ink { .. }
+            | ElementKind::Text { .. }
+            | ElementKind::Callout { .. }
+            | ElementKind::Shape { .. }
+            | ElementKind::Measurement { .. }
+            | ElementKind::Region { .. }
+    )
+}
+
+/// Move `transform` so it keeps its place relative to a box that moved
+/// from `from` to `to`, scaling its position and size with the box.
+///
+/// A box with no width or height only moves the transform.
+pub fn refit(transform: &mut Transform, from: &Transform, to: &Transform) {
+    let ratio = |new: f32, old: f32| {
+        if old.abs() > f32::EPSILON {
+            new / old
+        } else {
+            1.0
+        }
+    };
+    let sx = ratio(to.width, from.width);
+    let sy = ratio(to.height, from.height);
+    transform.x = to.x + (transform.x - from.x) * sx;
+    transform.y = to.y + (transform.y - from.y) * sy;
+    transform.width *= sx;
+    transform.height *= sy;
+}
+
+/// Ruler ticks along a line of `length` pixels, as fractions of the line
+/// and whether each tick is major.
+///
+/// Minor ticks fall on round numbers of units (1, 2 or 5 × 10ⁿ) with about
+/// twenty to a ruler; every fifth tick is major.
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss,
+    clippy::cast_precision_loss
+)]
+fn ruler_steps(length: f32, scale: f32) -> Vec<(f32, bool)> {
+    let units = length * scale;
+    if !(units.is_finite() && units > 0.0) {
+        return Vec::new();
+    }
+    let rough = units / 20.0;
+    let magnitude = 10f32.powf(rough.log10().floor());
+    let step = [1.0, 2.0, 5.0, 10.0]
+        .into_iter()
+        .map(|m| m * magnitude)
+        .find(|step| *step >= rough)
+        .unwrap_or(10.0 * magnitude);
+    let count = ((units / step).floor() as usize + 1).min(MAX_RULER_TICKS);
+    (0..count)
+        .map(|i| ((i as f32 * step) / units, i % 5 == 0))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: [f32; 2], b: [f32; 2]) -> bool {
+        (a[0] - b[0]).abs() < 1e-3 && (a[1] - b[1]).abs() < 1e-3
+    }
+
+    #[test]
+    fn test_dimension_layout_and_label() {
+        let element = measurement([100.0, 200.0], [220.0, 200.0], MeasurementKind::Dimension);
+        assert!((element.transform.x - 80.0).abs() < f32::EPSILON);
+        let layout = MeasurementLayout::from_element(&element).expect("measurement");
+        assert!(close(layout.start, [100.0, 200.0]) && close(layout.end, [220.0, 200.0]));
+        assert_eq!(layout.label, "120 px");
+        // Label above the middle, caps across both ends
+        assert!(close(layout.label_at, [160.0, 190.0]));
+        assert_eq!(layout.marks.len(), 2);
+        assert!(close(layout.marks[0][0], [100.0, 206.0]));
+        assert!(close(layout.marks[0][1], [100.0, 194.0]));
+
+        // Scaled units and a vertical line labeled on its right
+        let mut element = measurement([0.0, 0.0], [0.0, 64.0], MeasurementKind::Dimension);
+        if let ElementKind::Measurement { unit, scale, .. } = &mut element.kind {
+            *unit = "m".to_string();
+            *scale = 0.05;
+        }
+        let layout = MeasurementLayout::from_element(&element).expect("measurement");
+        assert_eq!(layout.label, "3.2 m");
+        assert!(close(layout.label_at, [10.0, 32.0]));
+
+        // Labeled in the scene's units
+        let inches = CoordinateSystem::pixels().with_unit(crate::WorldUnit::In);
+        let element = measurement_in([0.0, 0.0], [144.0, 0.0], MeasurementKind::Ruler, &inches);
+        let layout = MeasurementLayout::from_element(&element).expect("measurement");
+        assert_eq!(layout.label, "1.5 in");
+
+        assert_eq!(format_length(12.0, ""), "12");
+        assert_eq!(format_length(0.26, "s"), "0.3 s");
+    }
+
+    #[test]
+    fn test_arrow_head_and_refit() {
+        let element = measurement([0.0, 0.0], [100.0, 0.0], MeasurementKind::Arrow);
+        let layout = MeasurementLayout::from_element(&element).expect("measurement");
+        // No length label; two strokes back from the tip
+        assert_eq!(layout.label, "");
+        assert_eq!(layout.marks.len(), 2);
+        for [tip, tail] in &layout.marks {
+            assert!(close(*tip, [100.0, 0.0]));
+            assert!((tail[0] - (100.0 - ARROW_HEAD)).abs() < 1e-3);
+        }
+        assert!(is_markup(&element.kind));
+        assert!(!is_markup(&ElementKind::Group {
+            children: Vec::new(),
+            clip: false,
+            opacity: 1.0,
+        }));
+
+        // A mark at the image's center stays centered when it doubles in size
+        let from = Transform {
+            x: 0.0,
+            y: 0.0,
+            width: 200.0,
+            height: 100.0,
+            ..Transform::default()
+        };
+        let to = Transform {
+            x: 50.0,
+            y: 50.0,
+            width: 400.0,
+            height: 200.0,
+            ..from
+        };
+        let mut mark = Transform {
+            x: 90.0,
+            y: 40.0,
+            width: 20.0,
+            height: 20.0,
+            ..from
+        };
+        refit(&mut mark, &from, &to);
+        assert!(close([mark.x, mark.y], [230.0, 130.0]));
+        assert!(close([mark.width, mark.height], [40.0, 40.0]));
+    }
+
+    #[test]
+    fn test_ruler_ticks_fall_on_round_units() {
+        let element = measurement([0.0, 0.0], [100.0, 0.0], MeasurementKind::Ruler);
+        let layout = MeasurementLayout::from_element(&element).expect("measurement");
+        // 100 px: a tick every 5 px, major every 25 px, hanging below
+        assert_eq!(layout.marks.len(), 21);
+        assert!(close(layout.marks[1][0], [5.0, 0.0]));
+        assert!(close(layout.marks[1][1], [5.0, RULER_MINOR_TICK]));
+        assert!(close(layout.marks[5][1], [25.0, RULER_MAJOR_TICK]));
+
+        let mut region = ElementKind::Region {
+            caption: Some("x".repeat(MAX_ANNOTATION_TEXT_LEN + 1)),
+            color: ElementKind::default_annotation_color(),
+        };
+        assert!(validate(&region).is_err());
+        if let ElementKind::Region { caption, .. } = &mut region {
+            *caption = Some("Peak".to_string());
+        }
+        assert!(validate(&region).is_ok());
+        let mut element = measurement([0.0, 0.0], [1.0, 1.0], MeasurementKind::Ruler);
+        if let ElementKind::Measurement { scale, .. } = &mut element.kind {
+            *scale = 0.0;
+        }
+        assert!(validate(&element.kind).is_err());
+    }
+}
@@ -0,0 +1,265 @@
+//! # Session Archives
+//!
+//! A whole session packed into one self-contained document, so a canvas
+//! can be kept in an external store (such as the user's Communitas space)
+//! rather than only on the server's disk, and restored from it later.
+//!
+//! ```text
+//! canvas_archive { session_id }
+//!   → SessionArchive::pack(scene)   inline images and models become assets
+//!   → store.put(archive)            → "communitas:archive/3f2a…"
+//!
+//! canvas_restore { reference }
+//!   → store.get(reference)          SessionArchive
+//!   → archive.unpack()              assets checked against their hashes
+//!   → scene replaced                history continues from the archive
+//! ```
+//!
+//! Inline `data:` sources are moved into the archive's asset list, so
+//! content used by several elements is stored once. Each archive also
+//! lists the archives made of the session before it, newest last, so a
+//! restored canvas knows which earlier versions it can be rolled back to.
+
+use std::collections::BTreeSet;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::asset::{AssetId, AssetStore};
+use crate::scene::Scene;
+use crate::schema::SceneDocument;
+
+/// Archive format version written by [`SessionArchive::pack`].
+pub const ARCHIVE_VERSION: u32 = 1;
+
+/// Most earlier archives listed in an archive's history.
+pub const MAX_ARCHIVE_HISTORY: usize = 100;
+
+/// An earlier archive of a session.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchiveEntry {
+    /// Reference the archive store returned for it.
+    pub reference: String,
+    /// Scene revision that was archived.
+    pub revision: u64,
+    /// When it was archived, in milliseconds since the Unix epoch.
+    pub archived_at: u64,
+}
+
+/// Content of an image or model, keyed by its hash.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchivedAsset {
+    /// Content hash the scene's `asset:` sources refer to.
+    pub id: AssetId,
+    /// The content as a base64 `data:` URI.
+    pub data_uri: String,
+}
+
+/// A session packed for storage outside the server.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionArchive {
+    /// Format version, [`ARCHIVE_VERSION`] when written by this crate.
+    pub version: u32,
+    /// Session the scene was archived from.
+    pub session_id: String,
+    /// Scene revision that was archived.
+    pub revision: u64,
+    /// When it was archived, in milliseconds since the Unix epoch.
+    pub archived_at: u64,
+    /// The scene, with inline sources replaced by `asset:` references.
+    pub scene: SceneDocument,
+    /// Content of the referenced assets, ordered by ID.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub assets: Vec<ArchivedAsset>,
+    /// Earlier archives of the session, newest last.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub history: Vec<ArchiveEntry>,
+}
+
+/// Errors from storing or restoring archives.
+#[derive(Debug, Error)]
+pub enum ArchiveError {
+    /// No archive store is configured.
+    #[error("No archive store is configured")]
+    NoStore,
+
+    /// The store holds nothing under the reference.
+    #[error("Archive not found: {0}")]
+    NotFound(String),
+
+    /// The archive cannot be restored.
+    #[error("Invalid archive: {0}")]
+    Invalid(String),
+
+    /// The store failed.
+    #[error("Archive storage failed: {0}")]
+    Storage(String),
+}
+
+impl SessionArchive {
+    /// Pack `scene` with the archives made of the session before it.
+    ///
+    /// Only the newest [`MAX_ARCHIVE_HISTORY`] history entries are kept.
+    #[must_use]
+    pub fn pack(
+        session_id: impl Into<String>,
+        scene: &Scene,
+        mut history: Vec<ArchiveEntry>,
+        archived_at: u64,
+    ) -> Self {
+        let session_id = session_id.into();
+        let mut packed = scene.clone();
+        let mut store = AssetStore::new();
+        store.intern_scene(&mut packed);
+        let ids: BTreeSet<AssetId> = packed
+            .elements()
+            .filter_map(|e| e.kind.asset_id())
+            .collect();
+        let assets = ids
+            .into_iter()
+            .filter_map(|id| {
+                let data_uri = store.get(&id)?.to_data_uri();
+                Some(ArchivedAsset { id, data_uri })
+            })
+            .collect();
+        let excess = history.len().saturating_sub(MAX_ARCHIVE_HISTORY);
+        history.drain(..excess);
+
+        Self {
+            version: ARCHIVE_VERSION,
+            scene: SceneDocument::from_scene(&session_id, &packed, archived_at),
+            session_id,
+            revision: scene.revision(),
+            archived_at,
+            assets,
+            history,
+        }
+    }
+
+    /// Rebuild the scene, with archived assets inlined again as `data:`
+    /// sources.
+    ///
+    /// `asset:` sources without an archived asset are left as they are.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ArchiveError::Invalid`] if the archive is from a newer
+    /// format version, an asset does not match its hash or the scene
+    /// cannot be materialized.
+    pub fn unpack(&self) -> Result<Scene, ArchiveError> {
+        if self.version > ARCHIVE_VERSION {
+            return Err(ArchiveError::Invalid(format!(
+                "format version {} is newer than {ARCHIVE_VERSION}",
+                self.version
+            )));
+        }
+        let mut store = AssetStore::new();
+        for asset in &self.assets {
+            let id = store
+                .insert_data_uri(&asset.data_uri)
+                .map_err(|e| ArchiveError::Invalid(format!("asset {}: {e}", asset.id)))?;
+            if id != asset.id {
+                return Err(ArchiveError::Invalid(format!(
+                    "asset {} does not match its content",
+                    asset.id
+                )));
+            }
+        }
+
+        let mut scene = self
+            .scene
+            .clone()
+            .into_scene()
+            .map_err(ArchiveError::Invalid)?;
+        for element in scene.elements_mut() {
+            let Some(asset) = element.kind.asset_id().and_then(|id| store.get(&id)) else {
+                continue;
+            };
+            let data_uri = asset.to_data_uri();
+            if let Some(src) = element.kind.source_mut() {
+                *src = data_uri;
+            }
+        }
+        Ok(scene)
+    }
+
+    /// History for the next archive of a session restored from this one,
+    /// stored under `reference`: this archive's history followed by itself.
+    #[must_use]
+    pub fn lineage(&self, reference: impl Into<String>) -> Vec<ArchiveEntry> {
+        let mut history = self.history.clone();
+        history.push(ArchiveEntry {
+            reference: reference.into(),
+            revision: self.revision,
+            archived_at: self.archived_at,
+        });
+        history
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::element::{Element, ElementKind, ImageFormat};
+
+    const PIXEL: &str = "data:image/png;base64,iVBORw0KGgo=";
+
+    fn image(src: &str) -> Element {
+        Element::new(ElementKind::Image {
+            src: src.to_string(),
+            format: ImageFormat::Png,
+            annotations: Vec::new(),
+            extracted_text: None,
+        })
+    }
+
+    #[test]
+    fn test_pack_stores_shared_content_once_and_unpack_restores_it() {
+        let mut scene = Scene::new(800.0, 600.0);
+        let a = scene.add_element(image(PIXEL));
+        let b = scene.add_element(image(PIXEL));
+        let history = vec![ArchiveEntry {
+            reference: "earlier".to_string(),
+            revision: 1,
+            archived_at: 10,
+        }];
+
+        let archive = SessionArchive::pack("demo", &scene, history, 20);
+        assert_eq!(archive.assets.len(), 1);
+        let json = serde_json::to_string(&archive).expect("json");
+        assert_eq!(json.matches("iVBORw0KGgo=").count(), 1);
+
+        let restored: SessionArchive = serde_json::from_str(&json).expect("parse");
+        let scene = restored.unpack().expect("unpack");
+        for id in [a, b] {
+            match &scene.get_element(id).expect("image").kind {
+                ElementKind::Image { src, .. } => assert_eq!(src, PIXEL),
+                other => panic!("unexpected {other:?}"),
+            }
+        }
+        let lineage = restored.lineage("latest");
+        assert_eq!(
+            lineage
+                .iter()
+                .map(|entry| entry.reference.as_str())
+                .collect::<Vec<_>>(),
+            ["earlier", "latest"]
+        );
+        assert_eq!(lineage[1].archived_at, 20);
+    }
+
+    #[test]
+    fn test_unpack_rejects_tampered_assets_and_newer_versions() {
+        let mut scene = Scene::new(800.0, 600.0);
+        scene.add_element(image(PIXEL));
+        let archive = SessionArchive::pack("demo", &scene, Vec::new(), 0);
+
+        let mut tampered = archive.clone();
+        tampered.assets[0].data_uri = "data:image/png;base64,AAAA".to_string();
+        assert!(matches!(tampered.unpack(), Err(ArchiveError::Invalid(_))));
+
+        let mut newer = archive;
+        newer.version = ARCHIVE_VERSION + 1;
+        assert!(matches!(newer.unpack(), Err(ArchiveError::Invalid(_))));
+    }
+}
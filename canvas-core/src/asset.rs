@@ -0,0 +1,564 @@
+//! # Asset Store
+//!
+//! Content-addressed storage for binary assets (images, glTF models).
+//!
+//! Assets are interned by the SHA-256 of their bytes, so the same image added
+//! twice is stored once. `Image` and `Model3D` elements reference an interned
+//! asset with an `asset:` URI in their `src` instead of embedding megabytes of
+//! base64:
+//!
+//! ```text
+//! src: "data:image/png;base64,iVBORw0..."    (inline, duplicated per element)
+//! src: "asset:sha256-9f86d081884c7d65..."   (shared via the AssetStore)
+//! ```
+//!
+//! The store counts references to each asset. Assets with no references are
+//! kept until [`AssetStore::evict_unreferenced`] is called or the store goes
+//! over its byte budget, at which point the least recently used unreferenced
+//! assets are evicted first. Referenced assets are never evicted.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+use crate::scene::Scene;
+
+/// URI scheme used by element sources that reference an interned asset.
+pub const ASSET_URI_SCHEME: &str = "asset:";
+
+/// Prefix of every asset ID, naming the hash algorithm.
+const ID_PREFIX: &str = "sha256-";
+
+/// Errors from asset store operations.
+#[derive(Debug, Error)]
+pub enum AssetError {
+    /// The string is not a valid asset ID.
+    #[error("Invalid asset ID: {0}")]
+    InvalidId(String),
+
+    /// The string is not a base64 `data:` URI.
+    #[error("Invalid data URI: {0}")]
+    InvalidDataUri(String),
+
+    /// No asset with this ID is stored.
+    #[error("Asset not found: {0}")]
+    NotFound(AssetId),
+
+    /// The asset is larger than the store's byte budget.
+    #[error("Asset of {size} bytes exceeds the {capacity} byte budget")]
+    TooLarge {
+        /// Size of the rejected asset in bytes.
+        size: usize,
+        /// Byte budget of the store.
+        capacity: usize,
+    },
+}
+
+/// Content hash identifying an asset (`sha256-<64 hex digits>`).
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct AssetId(String);
+
+impl AssetId {
+    /// Compute the ID of some content.
+    #[must_use]
+    pub fn for_content(data: &[u8]) -> Self {
+        let digest = Sha256::digest(data);
+        let mut id = String::with_capacity(ID_PREFIX.len() + digest.len() * 2);
+        id.push_str(ID_PREFIX);
+        for byte in digest {
+            id.push(char::from_digit(u32::from(byte >> 4), 16).unwrap_or('0'));
+            id.push(char::from_digit(u32::from(byte & 0x0f), 16).unwrap_or('0'));
+        }
+        Self(id)
+    }
+
+    /// Parse an asset ID.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AssetError::InvalidId`] unless `s` is `sha256-` followed by
+    /// 64 lowercase hex digits.
+    pub fn parse(s: &str) -> Result<Self, AssetError> {
+        let valid = s.strip_prefix(ID_PREFIX).is_some_and(|hex| {
+            hex.len() == 64
+                && hex
+                    .bytes()
+                    .all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b))
+        });
+        if valid {
+            Ok(Self(s.to_string()))
+        } else {
+            Err(AssetError::InvalidId(s.to_string()))
+        }
+    }
+
+    /// Extract the asset ID from an `asset:` source URI.
+    ///
+    /// Returns `None` for any other kind of source (URLs, data URIs).
+    #[must_use]
+    pub fn from_uri(src: &str) -> Option<Self> {
+        src.strip_prefix(ASSET_URI_SCHEME)
+            .and_then(|id| Self::parse(id).ok())
+    }
+
+    /// The `asset:` URI elements use to reference this asset.
+    #[must_use]
+    pub fn to_uri(&self) -> String {
+        format!("{ASSET_URI_SCHEME}{}", self.0)
+    }
+
+    /// The ID as a string.
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for AssetId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl TryFrom<String> for AssetId {
+    type Error = AssetError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::parse(&value)
+    }
+}
+
+impl From<AssetId> for String {
+    fn from(id: AssetId) -> Self {
+        id.0
+    }
+}
+
+/// An interned binary asset.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Asset {
+    id: AssetId,
+    mime_type: String,
+    data: Arc<[u8]>,
+}
+
+impl Asset {
+    /// Content hash of the asset.
+    #[must_use]
+    pub fn id(&self) -> &AssetId {
+        &self.id
+    }
+
+    /// MIME type the asset was stored with.
+    #[must_use]
+    pub fn mime_type(&self) -> &str {
+        &self.mime_type
+    }
+
+    /// Raw asset bytes (cheap to clone).
+    #[must_use]
+    pub fn data(&self) -> Arc<[u8]> {
+        Arc::clone(&self.data)
+    }
+
+    /// Size of the asset in bytes.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Check whether the asset has no bytes.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Encode the asset as a base64 `data:` URI.
+    #[must_use]
+    pub fn to_data_uri(&self) -> String {
+        format!(
+            "data:{};base64,{}",
+            self.mime_type,
+            BASE64.encode(&self.data)
+        )
+    }
+}
+
+#[derive(Debug)]
+struct Entry {
+    asset: Asset,
+    ref_count: usize,
+    last_used: u64,
+}
+
+/// Content-addressed, reference-counted asset storage.
+///
+/// # Example
+///
+/// ```
+/// use canvas_core::AssetStore;
+///
+/// let mut store = AssetStore::new();
+/// let a = store.insert(b"png bytes".to_vec(), "image/png").unwrap();
+/// let b = store.insert(b"png bytes".to_vec(), "image/png").unwrap();
+/// assert_eq!(a, b);
+/// assert_eq!(store.len(), 1);
+/// ```
+#[derive(Debug, Default)]
+pub struct AssetStore {
+    entries: HashMap<AssetId, Entry>,
+    total_bytes: usize,
+    capacity_bytes: Option<usize>,
+    clock: u64,
+}
+
+impl AssetStore {
+    /// Create a store with no byte budget.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a store that evicts unreferenced assets above `capacity_bytes`.
+    #[must_use]
+    pub fn with_capacity_bytes(capacity_bytes: usize) -> Self {
+        Self {
+            capacity_bytes: Some(capacity_bytes),
+            ..Self::default()
+        }
+    }
+
+    /// Intern an asset and return its ID.
+    ///
+    /// Inserting content that is already stored returns the existing ID
+    /// without copying. The new asset starts with no references.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AssetError::TooLarge`] if the asset alone exceeds the
+    /// store's byte budget.
+    pub fn insert(
+        &mut self,
+        data: impl Into<Vec<u8>>,
+        mime_type: impl Into<String>,
+    ) -> Result<AssetId, AssetError> {
+        let data = data.into();
+        let id = AssetId::for_content(&data);
+        let now = self.tick();
+        if let Some(entry) = self.entries.get_mut(&id) {
+            entry.last_used = now;
+            return Ok(id);
+        }
+
+        if let Some(capacity) = self.capacity_bytes {
+            if data.len() > capacity {
+                return Err(AssetError::TooLarge {
+                    size: data.len(),
+                    capacity,
+                });
+            }
+        }
+
+        self.total_bytes += data.len();
+        self.entries.insert(
+            id.clone(),
+            Entry {
+                asset: Asset {
+                    id: id.clone(),
+                    mime_type: mime_type.into(),
+                    data: data.into(),
+                },
+                ref_count: 0,
+                last_used: now,
+            },
+        );
+        self.enforce_capacity(&id);
+        Ok(id)
+    }
+
+    /// Intern the content of a base64 `data:` URI.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AssetError::InvalidDataUri`] if `uri` is not of the form
+    /// `data:<mime>;base64,<data>`, or any error from [`AssetStore::insert`].
+    pub fn insert_data_uri(&mut self, uri: &str) -> Result<AssetId, AssetError> {
+        let (mime_type, data) = parse_data_uri(uri)?;
+        self.insert(data, mime_type)
+    }
+
+    /// Look up an asset.
+    #[must_use]
+    pub fn get(&self, id: &AssetId) -> Option<&Asset> {
+        self.entries.get(id).map(|entry| &entry.asset)
+    }
+
+    /// Check whether an asset is stored.
+    #[must_use]
+    pub fn contains(&self, id: &AssetId) -> bool {
+        self.entries.contains_key(id)
+    }
+
+    /// Number of references to an asset (0 if it is not stored).
+    #[must_use]
+    pub fn ref_count(&self, id: &AssetId) -> usize {
+        self.entries.get(id).map_or(0, |entry| entry.ref_count)
+    }
+
+    /// Add a reference to an asset and return the new count.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AssetError::NotFound`] if the asset is not stored.
+    pub fn retain(&mut self, id: &AssetId) -> Result<usize, AssetError> {
+        let now = self.tick();
+        let entry = self
+            .entries
+            .get_mut(id)
+            .ok_or_else(|| AssetError::NotFound(id.clone()))?;
+        entry.ref_count += 1;
+        entry.last_used = now;
+        Ok(entry.ref_count)
+    }
+
+    /// Drop a reference to an asset and return the remaining count.
+    ///
+    /// The asset stays stored until it is evicted.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AssetError::NotFound`] if the asset is not stored.
+    pub fn release(&mut self, id: &AssetId) -> Result<usize, AssetError> {
+        let entry = self
+            .entries
+            .get_mut(id)
+            .ok_or_else(|| AssetError::NotFound(id.clone()))?;
+        entry.ref_count = entry.ref_count.saturating_sub(1);
+        Ok(entry.ref_count)
+    }
+
+    /// Remove every asset with no references and return how many were removed.
+    pub fn evict_unreferenced(&mut self) -> usize {
+        let before = self.entries.len();
+        let mut freed = 0;
+        self.entries.retain(|_, entry| {
+            let keep = entry.ref_count > 0;
+            if !keep {
+                freed += entry.asset.len();
+            }
+            keep
+        });
+        self.total_bytes -= freed;
+        before - self.entries.len()
+    }
+
+    /// Resolve an element source for loading.
+    ///
+    /// `asset:` URIs of stored assets become `data:` URIs. Any other source
+    /// is returned unchanged, and unknown assets yield `None`.
+    #[must_use]
+    pub fn resolve_src(&self, src: &str) -> Option<String> {
+        match AssetId::from_uri(src) {
+            Some(id) => self.get(&id).map(Asset::to_data_uri),
+            None if src.starts_with(ASSET_URI_SCHEME) => None,
+            None => Some(src.to_string()),
+        }
+    }
+
+    /// Move inline `data:` sources of a scene's images and models into the
+    /// store, replacing them with `asset:` references.
+    ///
+    /// Each replaced source adds one reference. Returns the number of
+    /// elements rewritten; sources that are not base64 data URIs are left as-is.
+    pub fn intern_scene(&mut self, scene: &mut Scene) -> usize {
+        let mut rewritten = 0;
+        for element in scene.elements_mut() {
+            let Some(src) = element.kind.source_mut() else {
+                continue;
+            };
+            if !src.starts_with("data:") {
+                continue;
+            }
+            let Ok(id) = self.insert_data_uri(src) else {
+                continue;
+            };
+            if self.retain(&id).is_ok() {
+                *src = id.to_uri();
+                rewritten += 1;
+            }
+        }
+        rewritten
+    }
+
+    /// Recount references from the `asset:` sources of `scenes`.
+    ///
+    /// Use this after loading scenes or applying remote changes, when
+    /// individual `retain`/`release` calls were not made.
+    pub fn sync_references<'a>(&mut self, scenes: impl IntoIterator<Item = &'a Scene>) {
+        for entry in self.entries.values_mut() {
+            entry.ref_count = 0;
+        }
+        for scene in scenes {
+            for id in scene.elements().filter_map(|e| e.kind.asset_id()) {
+                if let Some(entry) = self.entries.get_mut(&id) {
+                    entry.ref_count += 1;
+                }
+            }
+        }
+    }
+
+    /// Number of stored assets.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Check whether the store holds no assets.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Total size of stored assets in bytes.
+    #[must_use]
+    pub fn total_bytes(&self) -> usize {
+        self.total_bytes
+    }
+
+    fn tick(&mut self) -> u64 {
+        self.clock += 1;
+        self.clock
+    }
+
+    /// Evict least recently used unreferenced assets until under budget,
+    /// never evicting `keep` (the asset just inserted).
+    fn enforce_capacity(&mut self, keep: &AssetId) {
+        let Some(capacity) = self.capacity_bytes else {
+            return;
+        };
+        if self.total_bytes <= capacity {
+            return;
+        }
+
+        let mut candidates: Vec<(u64, AssetId)> = self
+            .entries
+            .iter()
+            .filter(|(id, entry)| entry.ref_count == 0 && *id != keep)
+            .map(|(id, entry)| (entry.last_used, id.clone()))
+            .collect();
+        candidates.sort();
+
+        for (_, id) in candidates {
+            if self.total_bytes <= capacity {
+                break;
+            }
+            if let Some(entry) = self.entries.remove(&id) {
+                self.total_bytes -= entry.asset.len();
+            }
+        }
+    }
+}
+
+/// Split a `data:<mime>;base64,<data>` URI into its MIME type and bytes.
+pub(crate) fn parse_data_uri(uri: &str) -> Result<(String, Vec<u8>), AssetError> {
+    let invalid = || AssetError::InvalidDataUri(uri.chars().take(64).collect());
+    let rest = uri.strip_prefix("data:").ok_or_else(invalid)?;
+    let (header, payload) = rest.split_once(',').ok_or_else(invalid)?;
+    let mime_type = header.strip_suffix(";base64").ok_or_else(invalid)?;
+    let data = BASE64.decode(payload.trim()).map_err(|_| invalid())?;
+    let mime_type = if mime_type.is_empty() {
+        "application/octet-stream"
+    } else {
+        mime_type
+    };
+    Ok((mime_type.to_string(), data))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Element, ElementKind, ImageFormat};
+
+    fn image(src: &str) -> Element {
+        Element::new(ElementKind::Image {
+            src: src.to_string(),
+            format: ImageFormat::Png,
+            annotations: Vec::new(),
+            extracted_text: None,
+        })
+    }
+
+    #[test]
+    fn test_insert_dedupes_and_counts_references() {
+        let mut store = AssetStore::new();
+        let a = store.insert(vec![1, 2, 3], "image/png").expect("insert");
+        let b = store.insert(vec![1, 2, 3], "image/png").expect("insert");
+        assert_eq!(a, b);
+        assert_eq!(store.len(), 1);
+        assert_eq!(store.total_bytes(), 3);
+        assert_eq!(AssetId::parse(a.as_str()).expect("parse"), a);
+        assert_eq!(AssetId::from_uri(&a.to_uri()), Some(a.clone()));
+
+        assert_eq!(store.retain(&a).expect("retain"), 1);
+        assert_eq!(store.evict_unreferenced(), 0);
+        assert_eq!(store.release(&a).expect("release"), 0);
+        assert_eq!(store.evict_unreferenced(), 1);
+        assert!(store.is_empty());
+        assert_eq!(store.total_bytes(), 0);
+        assert!(matches!(store.retain(&a), Err(AssetError::NotFound(_))));
+    }
+
+    #[test]
+    fn test_capacity_evicts_least_recently_used_unreferenced() {
+        let mut store = AssetStore::with_capacity_bytes(10);
+        let pinned = store.insert(vec![0; 4], "image/png").expect("insert");
+        store.retain(&pinned).expect("retain");
+        let old = store.insert(vec![1; 4], "image/png").expect("insert");
+        let new = store.insert(vec![2; 4], "image/png").expect("insert");
+
+        assert!(store.contains(&pinned));
+        assert!(!store.contains(&old));
+        assert!(store.contains(&new));
+        assert_eq!(store.total_bytes(), 8);
+
+        assert!(matches!(
+            store.insert(vec![3; 11], "image/png"),
+            Err(AssetError::TooLarge { size: 11, .. })
+        ));
+    }
+
+    #[test]
+    fn test_intern_scene_rewrites_data_uris() {
+        let data_uri = format!("data:image/png;base64,{}", BASE64.encode([9_u8; 32]));
+        let mut scene = Scene::new(800.0, 600.0);
+        let first = scene.add_element(image(&data_uri));
+        scene.add_element(image(&data_uri));
+        scene.add_element(image("https://example.com/logo.png"));
+
+        let mut store = AssetStore::new();
+        assert_eq!(store.intern_scene(&mut scene), 2);
+        assert_eq!(store.len(), 1);
+
+        let id = scene
+            .get_element(first)
+            .and_then(|e| e.kind.asset_id())
+            .expect("asset reference");
+        assert_eq!(store.ref_count(&id), 2);
+        assert_eq!(store.resolve_src(&id.to_uri()), Some(data_uri));
+        assert_eq!(
+            store.resolve_src("https://example.com/logo.png").as_deref(),
+            Some("https://example.com/logo.png")
+        );
+
+        scene.remove_element(&first).expect("remove");
+        store.sync_references([&scene]);
+        assert_eq!(store.ref_count(&id), 1);
+    }
+}
@@ -0,0 +1,173 @@
+//! # Attachments
+//!
+//! Positions an element relative to another one, so labels, badges and
+//! callouts follow their targets when agents or users move them.
+//!
+//! An attachment names a point on the target's bounds, a point on the
+//! attached element, and an offset between the two:
+//!
+//! ```text
+//! Attachment::new(card, AttachPoint::TopRight)          ← badge centered on the corner
+//! Attachment::new(card, AttachPoint::Bottom)
+//!     .with_origin(AttachPoint::Top)
+//!     .with_offset(0.0, 8.0)                            ← label 8px below the card
+//! ```
+//!
+//! The scene resolves attachments as part of layout: moving or resizing a
+//! target moves everything attached to it, including chains of
+//! attachments. Moving an attached element directly keeps it attached and
+//! changes its offset instead.
+
+use serde::{Deserialize, Serialize};
+
+use crate::camera::SceneBounds;
+use crate::element::{ElementId, Transform};
+
+/// A point on an element's bounds.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+#[serde(rename_all = "snake_case")]
+pub enum AttachPoint {
+    /// Top-left corner.
+    TopLeft,
+    /// Middle of the top edge.
+    Top,
+    /// Top-right corner.
+    TopRight,
+    /// Middle of the left edge.
+    Left,
+    /// Center.
+    #[default]
+    Center,
+    /// Middle of the right edge.
+    Right,
+    /// Bottom-left corner.
+    BottomLeft,
+    /// Middle of the bottom edge.
+    Bottom,
+    /// Bottom-right corner.
+    BottomRight,
+}
+
+impl AttachPoint {
+    /// Position as fractions of the width and height from the top-left.
+    #[must_use]
+    pub const fn fractions(self) -> (f32, f32) {
+        match self {
+            Self::TopLeft => (0.0, 0.0),
+            Self::Top => (0.5, 0.0),
+            Self::TopRight => (1.0, 0.0),
+            Self::Left => (0.0, 0.5),
+            Self::Center => (0.5, 0.5),
+            Self::Right => (1.0, 0.5),
+            Self::BottomLeft => (0.0, 1.0),
+            Self::Bottom => (0.5, 1.0),
+            Self::BottomRight => (1.0, 1.0),
+        }
+    }
+}
+
+/// Where an element sits relative to another element.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+pub struct Attachment {
+    /// Element this one follows.
+    pub target: ElementId,
+    /// Point on the target's bounds, including rotation.
+    #[serde(default)]
+    pub at: AttachPoint,
+    /// Point on this element placed at `at`.
+    #[serde(default)]
+    pub origin: AttachPoint,
+    /// Horizontal distance from `at` to `origin`, in canvas pixels.
+    #[serde(default)]
+    pub offset_x: f32,
+    /// Vertical distance from `at` to `origin`, in canvas pixels.
+    #[serde(default)]
+    pub offset_y: f32,
+}
+
+impl Attachment {
+    /// Attach to the `at` point of `target`, centered on it.
+    #[must_use]
+    pub fn new(target: ElementId, at: AttachPoint) -> Self {
+        Self {
+            target,
+            at,
+            origin: AttachPoint::Center,
+            offset_x: 0.0,
+            offset_y: 0.0,
+        }
+    }
+
+    /// Set the point of the attached element placed on the target.
+    #[must_use]
+    pub fn with_origin(mut self, origin: AttachPoint) -> Self {
+        self.origin = origin;
+        self
+    }
+
+    /// Set the offset from the target point.
+    #[must_use]
+    pub fn with_offset(mut self, offset_x: f32, offset_y: f32) -> Self {
+        self.offset_x = offset_x;
+        self.offset_y = offset_y;
+        self
+    }
+
+    /// Top-left corner `own` should move to when `target` is where it is.
+    #[must_use]
+    pub fn resolve(&self, target: &Transform, own: &Transform) -> (f32, f32) {
+        let bounds = SceneBounds::from_transform(target);
+        let (ax, ay) = self.at.fractions();
+        let (ox, oy) = self.origin.fractions();
+        (
+            bounds.min_x + bounds.width() * ax + self.offset_x - own.width * ox,
+            bounds.min_y + bounds.height() * ay + self.offset_y - own.height * oy,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect(x: f32, y: f32, width: f32, height: f32) -> Transform {
+        Transform {
+            x,
+            y,
+            width,
+            height,
+            ..Transform::default()
+        }
+    }
+
+    #[test]
+    fn test_resolve_places_origin_on_target_point() {
+        let target = rect(100.0, 100.0, 200.0, 80.0);
+        let badge = rect(0.0, 0.0, 20.0, 20.0);
+
+        let corner = Attachment::new(ElementId::new(), AttachPoint::TopRight);
+        assert_eq!(corner.resolve(&target, &badge), (290.0, 90.0));
+
+        let below = Attachment::new(ElementId::new(), AttachPoint::Bottom)
+            .with_origin(AttachPoint::Top)
+            .with_offset(0.0, 8.0);
+        assert_eq!(below.resolve(&target, &badge), (190.0, 188.0));
+    }
+
+    #[test]
+    fn test_serde_defaults_to_centered() {
+        let target = ElementId::new();
+        let json = format!(r#"{{"target":"{target}"}}"#);
+        let attachment: Attachment = serde_json::from_str(&json).expect("parse");
+        assert_eq!(attachment, Attachment::new(target, AttachPoint::Center));
+
+        let json = serde_json::to_value(
+            Attachment::new(target, AttachPoint::BottomLeft).with_origin(AttachPoint::TopLeft),
+        )
+        .expect("serialize");
+        assert_eq!(json["at"], "bottom_left");
+        assert_eq!(json["origin"], "top_left");
+    }
+}
@@ -0,0 +1,429 @@
+//! # Barcodes
+//!
+//! Scannable QR codes and Code 128 barcodes, for surfacing links, Wi-Fi
+//! credentials or ticket numbers on a shared display.
+//!
+//! The server encodes the payload once (feature `barcode`) and stores the
+//! resulting modules on the element, so clients draw them without an
+//! encoder. [`BarcodeLayout`] turns the modules into dark rectangles inside
+//! the element's box, with the quiet zone scanners need, so every renderer
+//! draws the same crisp code at any scale:
+//!
+//! ```text
+//!  ┌─────────────────┐   ┌───────────────────────────┐
+//!  │  ▛▀▀▜ ▖▗ ▛▀▀▜   │   │    ▌▐▌▌▐▐▌▌▌▐▌▐▐▌▌▐▌▌▐    │
+//!  │  ▙▄▄▟ ▞▝ ▙▄▄▟   │   │    ▌▐▌▌▐▐▌▌▌▐▌▐▐▌▌▐▌▌▐    │
+//!  │  ▗▝▖▘▚ ▗▘▞▖▗    │   │    ▌▐▌▌▐▐▌▌▌▐▌▐▐▌▌▐▌▌▐    │
+//!  │  ▛▀▀▜ ▚▖▝▞ ▘▖   │   └───────────────────────────┘
+//!  │  ▙▄▄▟ ▗▘▚▗▞▖▝   │           Code 128
+//!  └─────────────────┘
+//!        QR code
+//! ```
+
+use serde::{Deserialize, Serialize};
+
+use crate::element::{ElementKind, Transform};
+use crate::error::{CanvasError, CanvasResult};
+use crate::measure::Size;
+
+/// Maximum payload length, in bytes.
+pub const MAX_BARCODE_PAYLOAD_LEN: usize = 1024;
+
+/// Maximum payload length of a Code 128 barcode, in characters.
+pub const MAX_CODE128_LEN: usize = 80;
+
+/// Light modules kept clear around a QR code.
+pub const QR_QUIET_ZONE: usize = 4;
+
+/// Light modules kept clear either side of a Code 128 barcode.
+pub const CODE128_QUIET_ZONE: usize = 10;
+
+/// How a barcode encodes its payload.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+#[serde(rename_all = "lowercase")]
+pub enum Symbology {
+    /// A square QR code; holds any text, e.g. a URL or Wi-Fi credentials.
+    #[default]
+    Qr,
+    /// A linear Code 128 barcode; printable ASCII, e.g. a ticket number.
+    Code128,
+}
+
+impl Symbology {
+    /// Light modules kept clear around the code.
+    #[must_use]
+    pub const fn quiet_zone(self) -> usize {
+        match self {
+            Self::Qr => QR_QUIET_ZONE,
+            Self::Code128 => CODE128_QUIET_ZONE,
+        }
+    }
+}
+
+/// How much of a QR code can be damaged and still scan.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+#[serde(rename_all = "lowercase")]
+pub enum ErrorCorrection {
+    /// About 7%.
+    Low,
+    /// About 15%.
+    #[default]
+    Medium,
+    /// About 25%.
+    Quartile,
+    /// About 30%.
+    High,
+}
+
+/// Where a barcode's modules are drawn, in canvas coordinates.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BarcodeLayout {
+    /// The light background, `[x, y, width, height]`: the element's box.
+    pub background: [f32; 4],
+    /// Width of one module.
+    pub module: f32,
+    /// Dark rectangles, `[x, y, width, height]`; horizontal runs of dark
+    /// modules are merged.
+    pub bars: Vec<[f32; 4]>,
+}
+
+impl BarcodeLayout {
+    /// Lay out `modules` in the box `t`.
+    ///
+    /// QR modules are square and the code is centered; Code 128 bars fill
+    /// the box's height.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn new(symbology: Symbology, modules: &[String], t: &Transform) -> Self {
+        let columns = modules.first().map_or(0, String::len);
+        let quiet = 2 * symbology.quiet_zone();
+        let mut layout = Self {
+            background: [t.x, t.y, t.width, t.height],
+            module: 0.0,
+            bars: Vec::new(),
+        };
+        if columns == 0 {
+            return layout;
+        }
+        let (module, row_height, x, y) = match symbology {
+            Symbology::Qr => {
+                let module = t.width.min(t.height) / (columns + quiet) as f32;
+                let side = module * columns as f32;
+                (
+                    module,
+                    module,
+                    t.x + (t.width - side) / 2.0,
+                    t.y + (t.height - side) / 2.0,
+                )
+            }
+            Symbology::Code128 => {
+                let module = t.width / (columns + quiet) as f32;
+                let row_height = t.height / modules.len() as f32;
+                (module, row_height, t.x + module * (quiet / 2) as f32, t.y)
+            }
+        };
+        layout.module = module;
+        for (row, bits) in modules.iter().enumerate() {
+            let top = y + row_height * row as f32;
+            let mut run_start = None;
+            for (column, bit) in bits.bytes().chain([b'0']).enumerate() {
+                match (bit == b'1', run_start) {
+                    (true, None) => run_start = Some(column),
+                    (false, Some(start)) => {
+                        layout.bars.push([
+                            x + module * start as f32,
+                            top,
+                            module * (column - start) as f32,
+                            row_height,
+                        ]);
+                        run_start = None;
+                    }
+                    _ => {}
+                }
+            }
+        }
+        layout
+    }
+}
+
+/// Natural size of a code: 4 pixels per QR module, or 2 per Code 128
+/// module and 80 tall, quiet zones included.
+#[must_use]
+#[allow(clippy::cast_precision_loss)]
+pub fn natural_size(symbology: Symbology, modules: &[String]) -> Size {
+    let columns = (modules.first().map_or(0, String::len) + 2 * symbology.quiet_zone()) as f32;
+    match symbology {
+        Symbology::Qr => Size::new(columns * 4.0, columns * 4.0),
+        Symbology::Code128 => Size::new(columns * 2.0, 80.0),
+    }
+}
+
+/// Payload of a QR code that joins a Wi-Fi network when scanned.
+///
+/// Networks without a password are open; others use WPA.
+#[must_use]
+pub fn wifi_payload(ssid: &str, password: Option<&str>, hidden: bool) -> String {
+    let escape = |value: &str| {
+        value.chars().fold(String::new(), |mut out, c| {
+            if matches!(c, '\\' | ';' | ',' | ':' | '"') {
+                out.push('\\');
+            }
+            out.push(c);
+            out
+        })
+    };
+    let mut payload = match password.filter(|p| !p.is_empty()) {
+        Some(password) => format!("WIFI:T:WPA;S:{};P:{};", escape(ssid), escape(password)),
+        None => format!("WIFI:T:nopass;S:{};", escape(ssid)),
+    };
+    if hidden {
+        payload.push_str("H:true;");
+    }
+    payload.push(';');
+    payload
+}
+
+/// Check a `Barcode`'s payload and modules.
+///
+/// # Errors
+///
+/// Returns [`CanvasError::InvalidOperation`] describing the first problem.
+pub fn validate(kind: &ElementKind) -> CanvasResult<()> {
+    let ElementKind::Barcode {
+        symbology,
+        payload,
+        modules,
+        ..
+    } = kind
+    else {
+        return Ok(());
+    };
+    validate_payload(*symbology, payload)?;
+    let columns = modules.first().map_or(0, String::len);
+    let well_formed = columns > 0
+        && modules
+            .iter()
+            .all(|row| row.len() == columns && row.bytes().all(|b| b == b'0' || b == b'1'));
+    let shaped = match symbology {
+        Symbology::Qr => modules.len() == columns && (21..=177).contains(&columns),
+        Symbology::Code128 => modules.len() == 1,
+    };
+    if !well_formed || !shaped {
+        return Err(CanvasError::InvalidOperation(
+            "barcode modules must be equal rows of 0s and 1s: one row for Code 128, \
+             a 21 to 177 module square for QR"
+                .to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn validate_payload(symbology: Symbology, payload: &str) -> CanvasResult<()> {
+    if payload.is_empty() || payload.len() > MAX_BARCODE_PAYLOAD_LEN {
+        return Err(CanvasError::InvalidOperation(format!(
+            "barcode payload must be 1 to {MAX_BARCODE_PAYLOAD_LEN} bytes"
+        )));
+    }
+    if symbology == Symbology::Code128
+        && (payload.len() > MAX_CODE128_LEN || !payload.bytes().all(|b| (32..=126).contains(&b)))
+    {
+        return Err(CanvasError::InvalidOperation(format!(
+            "Code 128 payloads are up to {MAX_CODE128_LEN} printable ASCII characters"
+        )));
+    }
+    Ok(())
+}
+
+/// Encode `payload` as module rows for a `Barcode` element.
+///
+/// `error_correction` applies to QR codes only. Code 128 payloads of four or
+/// more digits, evenly many, use the compact numeric code set.
+///
+/// # Errors
+///
+/// Returns [`CanvasError::InvalidOperation`] if the payload is empty, too
+/// long for the symbology or has characters it cannot encode.
+#[cfg(feature = "barcode")]
+pub fn encode(
+    symbology: Symbology,
+    payload: &str,
+    error_correction: ErrorCorrection,
+) -> CanvasResult<Vec<String>> {
+    use qrcodegen::{QrCode, QrCodeEcc};
+
+    validate_payload(symbology, payload)?;
+    match symbology {
+        Symbology::Qr => {
+            let ecc = match error_correction {
+                ErrorCorrection::Low => QrCodeEcc::Low,
+                ErrorCorrection::Medium => QrCodeEcc::Medium,
+                ErrorCorrection::Quartile => QrCodeEcc::Quartile,
+                ErrorCorrection::High => QrCodeEcc::High,
+            };
+            let qr = QrCode::encode_text(payload, ecc).map_err(|_| {
+                CanvasError::InvalidOperation(
+                    "payload is too long for a QR code at this error correction".to_string(),
+                )
+            })?;
+            Ok((0..qr.size())
+                .map(|y| {
+                    (0..qr.size())
+                        .map(|x| if qr.get_module(x, y) { '1' } else { '0' })
+                        .collect()
+                })
+                .collect())
+        }
+        Symbology::Code128 => Ok(vec![code128(payload)]),
+    }
+}
+
+/// Bar and space widths of each Code 128 symbol, by value.
+#[cfg(feature = "barcode")]
+const CODE128_PATTERNS: [&str; 107] = [
+    "212222", "222122", "222221", "121223", "121322", "131222", "122213", "122312", "132212",
+    "221213", "221312", "231212", "112232", "122132", "122231", "113222", "123122", "123221",
+    "223211", "221132", "221231", "213212", "223112", "312131", "311222", "321122", "321221",
+    "312212", "322112", "322211", "212123", "212321", "232121", "111323", "131123", "131321",
+    "112313", "132113", "132311", "211313", "231113", "231311", "112133", "112331", "132131",
+    "113123", "113321", "133121", "313121", "211331", "231131", "213113", "213311", "213131",
+    "311123", "311321", "331121", "312113", "312311", "332111", "314111", "221411", "431111",
+    "111224", "111422", "121124", "121421", "141122", "141221", "112214", "112412", "122114",
+    "122411", "142112", "142211", "241211", "221114", "413111", "241112", "134111", "111242",
+    "121142", "121241", "114212", "124112", "124211", "411212", "421112", "421211", "212141",
+    "214121", "412121", "111143", "111341", "131141", "114113", "114311", "411113", "411311",
+    "113141", "114131", "311141", "411131", "211412", "211214", "211232", "2331112",
+];
+
+/// Code 128 module row for a validated payload: start symbol, data, check
+/// symbol and stop symbol.
+#[cfg(feature = "barcode")]
+fn code128(payload: &str) -> String {
+    const START_B: usize = 104;
+    const START_C: usize = 105;
+    const STOP: usize = 106;
+
+    let bytes = payload.as_bytes();
+    let numeric =
+        bytes.len() >= 4 && bytes.len().is_multiple_of(2) && bytes.iter().all(u8::is_ascii_digit);
+    let (start, values): (usize, Vec<usize>) = if numeric {
+        let pairs = bytes
+            .chunks_exact(2)
+            .map(|pair| usize::from(pair[0] - b'0') * 10 + usize::from(pair[1] - b'0'))
+            .collect();
+        (START_C, pairs)
+    } else {
+        (
+            START_B,
+            bytes.iter().map(|&b| usize::from(b - 32)).collect(),
+        )
+    };
+    let check = values
+        .iter()
+        .enumerate()
+        .fold(start, |sum, (i, value)| sum + (i + 1) * value)
+        % 103;
+
+    let mut row = String::new();
+    for symbol in std::iter::once(start).chain(values).chain([check, STOP]) {
+        for (i, width) in CODE128_PATTERNS[symbol].bytes().enumerate() {
+            let bit = if i % 2 == 0 { '1' } else { '0' };
+            row.extend(std::iter::repeat_n(bit, usize::from(width - b'0')));
+        }
+    }
+    row
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(width: f32, height: f32) -> Transform {
+        Transform {
+            x: 10.0,
+            y: 20.0,
+            width,
+            height,
+            rotation: 0.0,
+            z_index: 0,
+            auto_size: false,
+        }
+    }
+
+    #[test]
+    fn test_layout_merges_runs_inside_quiet_zone() {
+        // 21 x 21 QR grid in a 290 x 300 box: 29 modules across, 10px each
+        let mut modules = vec!["0".repeat(21); 21];
+        modules[0] = format!("111{}", "0".repeat(18));
+        let layout = BarcodeLayout::new(Symbology::Qr, &modules, &at(290.0, 300.0));
+        assert_eq!(
+            layout,
+            BarcodeLayout {
+                background: [10.0, 20.0, 290.0, 300.0],
+                module: 10.0,
+                bars: vec![[50.0, 65.0, 30.0, 10.0]],
+            }
+        );
+
+        let bars = BarcodeLayout::new(Symbology::Code128, &["1101".to_string()], &at(48.0, 30.0));
+        assert_eq!(
+            bars,
+            BarcodeLayout {
+                background: [10.0, 20.0, 48.0, 30.0],
+                module: 2.0,
+                bars: vec![[30.0, 20.0, 4.0, 30.0], [36.0, 20.0, 2.0, 30.0]],
+            }
+        );
+    }
+
+    #[test]
+    fn test_wifi_payload_escapes_specials() {
+        assert_eq!(
+            wifi_payload("Café;Guest", Some("p:w\"d"), false),
+            "WIFI:T:WPA;S:Café\\;Guest;P:p\\:w\\\"d;;"
+        );
+        assert_eq!(
+            wifi_payload("Lobby", None, true),
+            "WIFI:T:nopass;S:Lobby;H:true;;"
+        );
+    }
+
+    #[cfg(feature = "barcode")]
+    #[test]
+    fn test_encode_qr_and_code128() {
+        let qr = encode(
+            Symbology::Qr,
+            "https://saorsalabs.com",
+            ErrorCorrection::Medium,
+        )
+        .expect("qr");
+        assert_eq!(qr.len(), 25);
+        // Finder pattern in the top-left corner
+        assert!(qr[0].starts_with("1111111"));
+        let kind = ElementKind::Barcode {
+            symbology: Symbology::Qr,
+            payload: "https://saorsalabs.com".to_string(),
+            modules: qr,
+            color: ElementKind::BARCODE_COLOR.into(),
+        };
+        assert!(validate(&kind).is_ok());
+
+        // Every symbol is 11 modules wide; stop is 13
+        assert!(CODE128_PATTERNS[..106].iter().all(|p| p
+            .bytes()
+            .map(|w| usize::from(w - b'0'))
+            .sum::<usize>()
+            == 11));
+        let row = code128("PJJ123C");
+        assert_eq!(row.len(), 11 * (7 + 2) + 13);
+        assert!(row.starts_with("11010010000"), "start B");
+        assert!(row.ends_with("1100011101011"), "stop");
+        let numeric = code128("123456");
+        assert!(numeric.starts_with("11010011100"), "start C");
+        assert_eq!(numeric.len(), 11 * (3 + 2) + 13);
+
+        assert!(encode(Symbology::Code128, "naïve", ErrorCorrection::Low).is_err());
+        assert!(encode(Symbology::Qr, "", ErrorCorrection::Low).is_err());
+    }
+}
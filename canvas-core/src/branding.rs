@@ -0,0 +1,364 @@
+//! # Branding
+//!
+//! A logo and watermark stamped over a session's canvas, for customer-facing
+//! or confidential scenes.
+//!
+//! Branding is part of the scene state, so it syncs to every viewer. It is
+//! not made of scene elements: renderers and exporters ask
+//! [`Branding::overlay`] for screen-anchored elements laid out in their
+//! output size and draw them above everything else, so the mark stays in the
+//! same corner whatever the camera does and cannot be selected or deleted.
+//!
+//! ```text
+//! ┌───────────────────────────────┐
+//! │                               │
+//! │         scene content         │
+//! │                               │
+//! │           [logo] CONFIDENTIAL │  ← BrandingPosition::BottomRight
+//! └───────────────────────────────┘
+//! ```
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::color::{Color, ThemeColor};
+use crate::element::{Element, ElementId, ElementKind, ImageFormat, Transform};
+use crate::error::{CanvasError, CanvasResult};
+use crate::measure::MeasureContext;
+
+/// Longest allowed watermark, in characters.
+pub const MAX_WATERMARK_LEN: usize = 200;
+
+/// Side of the square box the logo is drawn in, in pixels.
+pub const LOGO_SIZE: f32 = 48.0;
+
+/// Font size of the watermark text, in pixels.
+pub const WATERMARK_FONT_SIZE: f32 = 16.0;
+
+/// Distance between the branding and the edges of the output, in pixels.
+const MARGIN: f32 = 16.0;
+
+/// Space between the logo and the watermark, in pixels.
+const GAP: f32 = 8.0;
+
+/// Width at which the watermark wraps, in pixels.
+const WATERMARK_MAX_WIDTH: f32 = 320.0;
+
+/// Watermark text color.
+const WATERMARK_COLOR: Color = Color::from_rgb(0x80_8080);
+
+/// Fixed IDs of the overlay elements, so renderers that key state by ID
+/// see the same elements every frame.
+const LOGO_ID: u128 = 0x6272_616e_6469_6e67_0000_0000_0000_0001;
+const WATERMARK_ID: u128 = 0x6272_616e_6469_6e67_0000_0000_0000_0002;
+
+/// Where the branding sits in the output.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+#[serde(rename_all = "snake_case")]
+pub enum BrandingPosition {
+    /// Top-left corner.
+    TopLeft,
+    /// Top-right corner.
+    TopRight,
+    /// Bottom-left corner.
+    BottomLeft,
+    /// Bottom-right corner.
+    #[default]
+    BottomRight,
+    /// Centered.
+    Center,
+}
+
+/// A session's logo and watermark.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+pub struct Branding {
+    /// Logo image: a data URI, URL or `asset:` reference.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub logo: Option<String>,
+    /// Watermark text, e.g. `"CONFIDENTIAL"`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub watermark: Option<String>,
+    /// Where the logo and watermark sit.
+    #[serde(default)]
+    pub position: BrandingPosition,
+    /// Opacity of the logo and watermark (0.0 to 1.0).
+    #[serde(default = "Branding::default_opacity")]
+    pub opacity: f32,
+}
+
+impl Default for Branding {
+    fn default() -> Self {
+        Self {
+            logo: None,
+            watermark: None,
+            position: BrandingPosition::default(),
+            opacity: Self::DEFAULT_OPACITY,
+        }
+    }
+}
+
+impl Branding {
+    /// Default opacity.
+    pub const DEFAULT_OPACITY: f32 = 0.5;
+
+    const fn default_opacity() -> f32 {
+        Self::DEFAULT_OPACITY
+    }
+
+    /// Set the logo image source.
+    #[must_use]
+    pub fn with_logo(mut self, src: impl Into<String>) -> Self {
+        self.logo = Some(src.into());
+        self
+    }
+
+    /// Set the watermark text.
+    #[must_use]
+    pub fn with_watermark(mut self, text: impl Into<String>) -> Self {
+        self.watermark = Some(text.into());
+        self
+    }
+
+    /// Set where the branding sits.
+    #[must_use]
+    pub const fn with_position(mut self, position: BrandingPosition) -> Self {
+        self.position = position;
+        self
+    }
+
+    /// Set the opacity (clamped to 0.0..=1.0).
+    #[must_use]
+    pub fn with_opacity(mut self, opacity: f32) -> Self {
+        self.opacity = opacity.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Check the branding can be stored.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CanvasError::InvalidOperation`] if there is neither a logo
+    /// nor a watermark, the logo source is blank, the watermark is blank or
+    /// longer than [`MAX_WATERMARK_LEN`] characters, or the opacity is not
+    /// between 0.0 and 1.0.
+    pub fn validate(&self) -> CanvasResult<()> {
+        if self.logo.is_none() && self.watermark.is_none() {
+            return Err(CanvasError::InvalidOperation(
+                "Branding needs a logo or a watermark".to_string(),
+            ));
+        }
+        if self.logo.as_ref().is_some_and(|src| src.trim().is_empty()) {
+            return Err(CanvasError::InvalidOperation(
+                "Branding logo must not be empty".to_string(),
+            ));
+        }
+        if let Some(text) = &self.watermark {
+            if text.trim().is_empty() {
+                return Err(CanvasError::InvalidOperation(
+                    "Watermark must not be empty".to_string(),
+                ));
+            }
+            if text.chars().count() > MAX_WATERMARK_LEN {
+                return Err(CanvasError::InvalidOperation(format!(
+                    "Watermark is longer than {MAX_WATERMARK_LEN} characters"
+                )));
+            }
+        }
+        if !(0.0..=1.0).contains(&self.opacity) {
+            return Err(CanvasError::InvalidOperation(format!(
+                "Branding opacity must be between 0 and 1, got {}",
+                self.opacity
+            )));
+        }
+        Ok(())
+    }
+
+    /// Screen-anchored, non-interactive elements drawing the branding in an
+    /// output of `width` x `height` pixels, logo first.
+    ///
+    /// They have fixed IDs and the highest z-index, so they sort above any
+    /// other screen-anchored element.
+    #[must_use]
+    pub fn overlay(&self, width: f32, height: f32) -> Vec<Element> {
+        let logo_w = if self.logo.is_some() { LOGO_SIZE } else { 0.0 };
+        let text = self.watermark.as_deref().map(|content| {
+            let size = MeasureContext::default().text_size(
+                content,
+                WATERMARK_FONT_SIZE,
+                WATERMARK_MAX_WIDTH,
+            );
+            (content, size.width, size.height)
+        });
+        let gap = if logo_w > 0.0 && text.is_some() {
+            GAP
+        } else {
+            0.0
+        };
+        let (text_w, text_h) = text.map_or((0.0, 0.0), |(_, w, h)| (w, h));
+        let content_w = logo_w + gap + text_w;
+        let content_h = text_h.max(if logo_w > 0.0 { LOGO_SIZE } else { 0.0 });
+
+        let x = match self.position {
+            BrandingPosition::TopLeft | BrandingPosition::BottomLeft => MARGIN,
+            BrandingPosition::TopRight | BrandingPosition::BottomRight => {
+                width - MARGIN - content_w
+            }
+            BrandingPosition::Center => (width - content_w) / 2.0,
+        };
+        let y = match self.position {
+            BrandingPosition::TopLeft | BrandingPosition::TopRight => MARGIN,
+            BrandingPosition::BottomLeft | BrandingPosition::BottomRight => {
+                height - MARGIN - content_h
+            }
+            BrandingPosition::Center => (height - content_h) / 2.0,
+        };
+
+        let mut elements = Vec::with_capacity(2);
+        if let Some(src) = &self.logo {
+            let kind = ElementKind::Image {
+                src: src.clone(),
+                format: logo_format(src),
+                annotations: Vec::new(),
+                extracted_text: None,
+            };
+            elements.push(self.overlay_element(
+                LOGO_ID,
+                kind,
+                [x, y + (content_h - LOGO_SIZE) / 2.0, LOGO_SIZE, LOGO_SIZE],
+            ));
+        }
+        if let Some((content, w, h)) = text {
+            let kind = ElementKind::Text {
+                content: content.to_string(),
+                font_size: WATERMARK_FONT_SIZE,
+                color: ThemeColor::from(WATERMARK_COLOR),
+            };
+            elements.push(self.overlay_element(
+                WATERMARK_ID,
+                kind,
+                [x + logo_w + gap, y + (content_h - h) / 2.0, w, h],
+            ));
+        }
+        elements
+    }
+
+    fn overlay_element(&self, id: u128, kind: ElementKind, rect: [f32; 4]) -> Element {
+        let mut element = Element::new(kind)
+            .with_transform(Transform {
+                x: rect[0],
+                y: rect[1],
+                width: rect[2],
+                height: rect[3],
+                z_index: i32::MAX,
+                ..Transform::default()
+            })
+            .with_interactive(false)
+            .with_opacity(self.opacity)
+            .with_screen_anchored(true);
+        element.id = ElementId::from_uuid(Uuid::from_u128(id));
+        element
+    }
+}
+
+/// Image format of a logo, from a data URI's MIME type or the extension.
+fn logo_format(src: &str) -> ImageFormat {
+    let head = src.split([',', '?', '#']).next().unwrap_or_default();
+    let kind = match head.strip_prefix("data:") {
+        Some(mime) => mime.split(';').next().and_then(|m| m.rsplit('/').next()),
+        None => head.rsplit_once('.').map(|(_, ext)| ext),
+    };
+    match kind.unwrap_or_default().to_ascii_lowercase().as_str() {
+        "svg" | "svg+xml" => ImageFormat::Svg,
+        "jpg" | "jpeg" => ImageFormat::Jpeg,
+        "webp" => ImageFormat::WebP,
+        _ => ImageFormat::Png,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate() {
+        assert!(Branding::default().validate().is_err());
+        assert!(Branding::default().with_watermark("  ").validate().is_err());
+        assert!(Branding::default().with_logo("").validate().is_err());
+        assert!(Branding::default()
+            .with_watermark("x".repeat(MAX_WATERMARK_LEN + 1))
+            .validate()
+            .is_err());
+        let mut branding = Branding::default().with_watermark("CONFIDENTIAL");
+        assert!(branding.validate().is_ok());
+        branding.opacity = 1.5;
+        assert!(branding.validate().is_err());
+        assert!((Branding::default().with_opacity(2.0).opacity - 1.0).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn test_overlay_corners() {
+        let branding = Branding::default()
+            .with_logo("https://example.com/logo.svg")
+            .with_watermark("CONFIDENTIAL");
+        let overlay = branding.overlay(800.0, 600.0);
+        assert_eq!(overlay.len(), 2);
+        let (logo, text) = (&overlay[0].transform, &overlay[1].transform);
+        assert!(overlay.iter().all(|e| e.screen_anchored && !e.interactive));
+        assert!(matches!(
+            overlay[0].kind,
+            ElementKind::Image {
+                format: ImageFormat::Svg,
+                ..
+            }
+        ));
+        assert_eq!(
+            logo_format("data:image/jpeg;base64,AA=="),
+            ImageFormat::Jpeg
+        );
+        assert_eq!(logo_format("logo.WEBP?v=2"), ImageFormat::WebP);
+
+        // Bottom-right: the watermark ends at the right margin, after the logo
+        assert!((text.x + text.width - (800.0 - MARGIN)).abs() < 0.01);
+        assert!((logo.x + LOGO_SIZE + GAP - text.x).abs() < 0.01);
+        assert!((logo.y + LOGO_SIZE - (600.0 - MARGIN)).abs() < 0.01);
+
+        let top_left = branding
+            .clone()
+            .with_position(BrandingPosition::TopLeft)
+            .overlay(800.0, 600.0);
+        assert!((top_left[0].transform.x - MARGIN).abs() < 0.01);
+        assert!((top_left[0].transform.y - MARGIN).abs() < 0.01);
+
+        // The same branding yields the same element IDs
+        assert_eq!(overlay[0].id, top_left[0].id);
+        assert_ne!(overlay[0].id, overlay[1].id);
+    }
+
+    #[test]
+    fn test_overlay_centers_watermark_alone() {
+        let overlay = Branding::default()
+            .with_watermark("DRAFT")
+            .with_position(BrandingPosition::Center)
+            .with_opacity(0.25)
+            .overlay(400.0, 200.0);
+        assert_eq!(overlay.len(), 1);
+        let tf = &overlay[0].transform;
+        assert!((tf.x + tf.width / 2.0 - 200.0).abs() < 0.01);
+        assert!((tf.y + tf.height / 2.0 - 100.0).abs() < 0.01);
+        assert!((overlay[0].opacity - 0.25).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn test_serde_defaults() {
+        let branding: Branding =
+            serde_json::from_str(r#"{"watermark":"DRAFT"}"#).expect("branding");
+        assert_eq!(branding.position, BrandingPosition::BottomRight);
+        assert!((branding.opacity - Branding::DEFAULT_OPACITY).abs() < f32::EPSILON);
+        let json =
+            serde_json::to_value(branding.with_position(BrandingPosition::TopRight)).expect("json");
+        assert_eq!(json["position"], "top_right");
+        assert!(json.get("logo").is_none());
+    }
+}
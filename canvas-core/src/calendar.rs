@@ -0,0 +1,391 @@
+//! # Calendar Layout
+//!
+//! Dates and grid geometry for `Calendar` elements.
+//!
+//! A calendar shows a month (the weeks covering it) or a single week, with
+//! weeks starting on Monday:
+//!
+//! ```text
+//! ┌───────────────────────────────────┐
+//! │           October 2026            │  title
+//! ├────┬────┬────┬────┬────┬────┬─────┤
+//! │Mon │Tue │Wed │Thu │Fri │Sat │Sun  │  weekdays
+//! ├────┼────┼────┼────┼────┼────┼─────┤
+//! │ 28 │ 29 │ 30 │  1 │  2 │  3 │  4  │  day cells
+//! │ …  │    │    │    │    │    │     │
+//! └────┴────┴────┴────┴────┴────┴─────┘
+//! ```
+//!
+//! [`CalendarLayout`] computes the cells in the element's local frame
+//! (origin at its unrotated top-left corner). Every renderer and the
+//! interaction router use it, so a tap selects the day drawn under it.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::color::{Color, Theme, ThemeColor};
+
+/// Short weekday names, Monday first.
+pub const WEEKDAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+
+const MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+/// Which span of days a calendar shows.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+#[serde(rename_all = "lowercase")]
+pub enum CalendarView {
+    /// The weeks covering a month.
+    #[default]
+    Month,
+    /// One week, with room for more events per day.
+    Week,
+}
+
+/// An entry on a calendar.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+pub struct CalendarEvent {
+    /// Day of the event, `YYYY-MM-DD`.
+    pub date: String,
+    /// Event title.
+    pub title: String,
+    /// Free-form time label, e.g. `"09:30"`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time: Option<String>,
+    /// Marker color.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<ThemeColor>,
+}
+
+impl CalendarEvent {
+    /// Marker color used when an event has none.
+    pub const DEFAULT_COLOR: Color = Color::from_rgb(0x1e_88e5);
+
+    /// The event's marker color resolved against `theme`.
+    #[must_use]
+    pub fn resolved_color(&self, theme: &Theme) -> Color {
+        self.color.as_ref().map_or(Self::DEFAULT_COLOR, |color| {
+            theme.resolve_or(color, Self::DEFAULT_COLOR)
+        })
+    }
+}
+
+/// A day in the proleptic Gregorian calendar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CalendarDate {
+    /// Year, e.g. 2026.
+    pub year: i32,
+    /// Month, 1-12.
+    pub month: u8,
+    /// Day of the month, starting at 1.
+    pub day: u8,
+}
+
+impl CalendarDate {
+    /// Create a date, or `None` if it does not exist.
+    #[must_use]
+    pub fn new(year: i32, month: u8, day: u8) -> Option<Self> {
+        ((1..=12).contains(&month) && day >= 1 && day <= days_in_month(year, month))
+            .then_some(Self { year, month, day })
+    }
+
+    /// Parse a `YYYY-MM-DD` date.
+    #[must_use]
+    pub fn parse(text: &str) -> Option<Self> {
+        let mut parts = text.trim().splitn(3, '-');
+        let year = parts.next()?.parse().ok()?;
+        let month = parts.next()?.parse().ok()?;
+        let day = parts.next()?.parse().ok()?;
+        Self::new(year, month, day)
+    }
+
+    /// Day of the week, 0 = Monday through 6 = Sunday.
+    #[must_use]
+    pub fn weekday(self) -> u8 {
+        // 1970-01-01 was a Thursday
+        u8::try_from((self.to_days() + 3).rem_euclid(7)).unwrap_or(0)
+    }
+
+    /// The date `days` later (or earlier, if negative).
+    #[must_use]
+    pub fn add_days(self, days: i64) -> Self {
+        Self::from_days(self.to_days() + days)
+    }
+
+    /// Monday of this date's week.
+    #[must_use]
+    pub fn week_start(self) -> Self {
+        self.add_days(-i64::from(self.weekday()))
+    }
+
+    /// Days since 1970-01-01 (Howard Hinnant's `days_from_civil`).
+    fn to_days(self) -> i64 {
+        let year = i64::from(self.year) - i64::from(self.month <= 2);
+        let era = year.div_euclid(400);
+        let yoe = year - era * 400;
+        let month = i64::from(self.month);
+        let doy =
+            (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + i64::from(self.day) - 1;
+        let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+        era * 146_097 + doe - 719_468
+    }
+
+    /// Inverse of [`Self::to_days`].
+    fn from_days(days: i64) -> Self {
+        let z = days + 719_468;
+        let era = z.div_euclid(146_097);
+        let doe = z - era * 146_097;
+        let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = doy - (153 * mp + 2) / 5 + 1;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 };
+        let year = yoe + era * 400 + i64::from(month <= 2);
+        Self {
+            year: i32::try_from(year).unwrap_or(i32::MAX),
+            month: u8::try_from(month).unwrap_or(1),
+            day: u8::try_from(day).unwrap_or(1),
+        }
+    }
+}
+
+impl fmt::Display for CalendarDate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+/// Number of days in a month (1-12).
+#[must_use]
+pub fn days_in_month(year: i32, month: u8) -> u8 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Events falling on `date`, in list order. Events with invalid dates are
+/// never shown.
+pub fn events_on(
+    events: &[CalendarEvent],
+    date: CalendarDate,
+) -> impl Iterator<Item = &CalendarEvent> {
+    events
+        .iter()
+        .filter(move |event| CalendarDate::parse(&event.date) == Some(date))
+}
+
+/// One day cell of a calendar, in the element's local frame.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CalendarCell {
+    /// The day shown.
+    pub date: CalendarDate,
+    /// Left edge.
+    pub x: f32,
+    /// Top edge.
+    pub y: f32,
+    /// Cell width.
+    pub width: f32,
+    /// Cell height.
+    pub height: f32,
+    /// Whether the day is in the displayed month (always true for weeks);
+    /// leading and trailing days are usually drawn dimmed.
+    pub in_range: bool,
+}
+
+impl CalendarCell {
+    /// Font size of the day number.
+    #[must_use]
+    pub fn number_size(&self) -> f32 {
+        (self.height * 0.2).clamp(8.0, 14.0)
+    }
+
+    /// Height of one event line.
+    #[must_use]
+    pub fn event_line(&self) -> f32 {
+        self.number_size() + 2.0
+    }
+
+    /// Offset of the first event line below the cell's top edge.
+    #[must_use]
+    pub fn events_top(&self) -> f32 {
+        self.number_size() + 6.0
+    }
+
+    /// How many of `count` events to list. When they do not all fit, the
+    /// last line is left for a "+N more" count.
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    pub fn visible_events(&self, count: usize) -> usize {
+        let room = ((self.height - self.events_top()) / self.event_line())
+            .floor()
+            .max(0.0) as usize;
+        if count > room {
+            room.saturating_sub(1)
+        } else {
+            count
+        }
+    }
+}
+
+/// Geometry of a calendar element.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CalendarLayout {
+    /// Title, e.g. `"October 2026"` or `"Week of 12 Oct 2026"`.
+    pub title: String,
+    /// Height of the title band.
+    pub title_height: f32,
+    /// Top of the weekday header row.
+    pub weekday_y: f32,
+    /// Height of the weekday header row.
+    pub weekday_height: f32,
+    /// Width of each column.
+    pub column_width: f32,
+    /// Day cells in reading order.
+    pub cells: Vec<CalendarCell>,
+}
+
+impl CalendarLayout {
+    /// Lay out a calendar showing `date` in a `width` × `height` element.
+    ///
+    /// Returns `None` if `date` is not a valid `YYYY-MM-DD` date.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn new(view: CalendarView, date: &str, width: f32, height: f32) -> Option<Self> {
+        let date = CalendarDate::parse(date)?;
+        let (title, start, in_month, rows) = match view {
+            CalendarView::Month => {
+                let first = CalendarDate { day: 1, ..date };
+                let days = usize::from(days_in_month(date.year, date.month));
+                let rows = (usize::from(first.weekday()) + days).div_ceil(7);
+                (
+                    format!("{} {}", MONTHS[usize::from(date.month - 1)], date.year),
+                    first.week_start(),
+                    Some(date.month),
+                    rows,
+                )
+            }
+            CalendarView::Week => {
+                let start = date.week_start();
+                (
+                    format!(
+                        "Week of {} {} {}",
+                        start.day,
+                        &MONTHS[usize::from(start.month - 1)][..3],
+                        start.year
+                    ),
+                    start,
+                    None,
+                    1,
+                )
+            }
+        };
+
+        let title_height = (height * 0.12).clamp(16.0, 32.0).min(height / 3.0);
+        let weekday_height = (height * 0.07).clamp(12.0, 20.0).min(height / 4.0);
+        let top = title_height + weekday_height;
+        let column_width = width / 7.0;
+        let row_height = (height - top).max(0.0) / rows as f32;
+
+        let cells = (0..rows * 7)
+            .map(|i| {
+                let day = start.add_days(i64::try_from(i).unwrap_or_default());
+                CalendarCell {
+                    date: day,
+                    x: (i % 7) as f32 * column_width,
+                    y: top + (i / 7) as f32 * row_height,
+                    width: column_width,
+                    height: row_height,
+                    in_range: in_month.is_none_or(|month| day.month == month),
+                }
+            })
+            .collect();
+
+        Some(Self {
+            title,
+            title_height,
+            weekday_y: title_height,
+            weekday_height,
+            column_width,
+            cells,
+        })
+    }
+
+    /// The day cell containing a local point, if any.
+    #[must_use]
+    pub fn cell_at(&self, x: f32, y: f32) -> Option<&CalendarCell> {
+        self.cells.iter().find(|cell| {
+            x >= cell.x && x < cell.x + cell.width && y >= cell.y && y < cell.y + cell.height
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dates() {
+        let date = CalendarDate::parse("2026-10-17").expect("valid");
+        assert_eq!(date.weekday(), 5); // Saturday
+        assert_eq!(date.week_start().to_string(), "2026-10-12");
+        assert_eq!(date.add_days(15).to_string(), "2026-11-01");
+        assert_eq!(
+            CalendarDate::parse("2024-02-28").map(|d| d.add_days(1).to_string()),
+            Some("2024-02-29".to_string())
+        );
+        assert!(CalendarDate::parse("2026-02-29").is_none());
+        assert!(CalendarDate::parse("2026-13-01").is_none());
+        assert!(CalendarDate::parse("yesterday").is_none());
+    }
+
+    #[test]
+    fn test_month_layout() {
+        let layout =
+            CalendarLayout::new(CalendarView::Month, "2026-10-17", 700.0, 500.0).expect("layout");
+        assert_eq!(layout.title, "October 2026");
+        // October 2026 starts on a Thursday: 3 leading days + 31 = 5 weeks
+        assert_eq!(layout.cells.len(), 35);
+        assert_eq!(layout.cells[0].date.to_string(), "2026-09-28");
+        assert!(!layout.cells[0].in_range);
+        assert!(layout.cells[3].in_range);
+
+        let cell = layout.cells[19];
+        let hit = layout
+            .cell_at(cell.x + 1.0, cell.y + 1.0)
+            .expect("inside a cell");
+        assert_eq!(hit.date.to_string(), "2026-10-17");
+        assert!(layout.cell_at(10.0, 1.0).is_none()); // title band
+
+        // ~82px rows fit 4 event lines; overflow keeps one for "+N more"
+        assert_eq!(cell.visible_events(2), 2);
+        assert_eq!(cell.visible_events(9), 3);
+    }
+
+    #[test]
+    fn test_week_layout() {
+        let layout =
+            CalendarLayout::new(CalendarView::Week, "2026-10-17", 700.0, 300.0).expect("layout");
+        assert_eq!(layout.title, "Week of 12 Oct 2026");
+        assert_eq!(layout.cells.len(), 7);
+        assert!(layout.cells.iter().all(|c| c.in_range));
+        assert!(CalendarLayout::new(CalendarView::Week, "soon", 700.0, 300.0).is_none());
+    }
+}
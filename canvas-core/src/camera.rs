@@ -0,0 +1,299 @@
+//! # Camera Commands
+//!
+//! High-level camera commands computed from scene bounds, so clients don't
+//! each need their own viewport math.
+//!
+//! ```text
+//! fit_to_content        → frame every element in the scene
+//! fit_to_elements(ids)  → frame the given elements
+//! reset_view            → zoom 1.0, no pan
+//! go_to_anchor(name)    → center a named anchor at its zoom
+//! ```
+//!
+//! Fitting honours [`FitOptions`]: screen-space padding and zoom limits.
+//! Commands are serialized with a `command` tag so they can be sent as-is
+//! over the WebSocket and MCP:
+//!
+//! ```json
+//! { "command": "fit_to_elements", "element_ids": ["..."] }
+//! ```
+
+use serde::{Deserialize, Serialize};
+
+use crate::element::{ElementId, Transform};
+use crate::error::{CanvasError, CanvasResult};
+use crate::follow::CameraFrame;
+use crate::geometry::OrientedRect;
+use crate::scene::Scene;
+
+/// Axis-aligned bounding box in canvas coordinates.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SceneBounds {
+    /// Left edge.
+    pub min_x: f32,
+    /// Top edge.
+    pub min_y: f32,
+    /// Right edge.
+    pub max_x: f32,
+    /// Bottom edge.
+    pub max_y: f32,
+}
+
+impl SceneBounds {
+    /// Axis-aligned bounds of an element transform, including rotation.
+    #[must_use]
+    pub fn from_transform(transform: &Transform) -> Self {
+        // Unrotated elements map directly, without rounding through the center
+        if transform.rotation.abs() <= f32::EPSILON {
+            return Self {
+                min_x: transform.x,
+                min_y: transform.y,
+                max_x: transform.x + transform.width,
+                max_y: transform.y + transform.height,
+            };
+        }
+        OrientedRect::from_transform(transform).bounds()
+    }
+
+    /// Smallest bounds containing both `self` and `other`.
+    #[must_use]
+    pub fn union(&self, other: &Self) -> Self {
+        Self {
+            min_x: self.min_x.min(other.min_x),
+            min_y: self.min_y.min(other.min_y),
+            max_x: self.max_x.max(other.max_x),
+            max_y: self.max_y.max(other.max_y),
+        }
+    }
+
+    /// Width of the bounds.
+    #[must_use]
+    pub fn width(&self) -> f32 {
+        self.max_x - self.min_x
+    }
+
+    /// Height of the bounds.
+    #[must_use]
+    pub fn height(&self) -> f32 {
+        self.max_y - self.min_y
+    }
+
+    /// Check whether `other` lies entirely within these bounds.
+    #[must_use]
+    pub fn contains(&self, other: &Self) -> bool {
+        other.min_x >= self.min_x
+            && other.min_y >= self.min_y
+            && other.max_x <= self.max_x
+            && other.max_y <= self.max_y
+    }
+
+    /// Check whether `other` overlaps these bounds (touching edges count).
+    #[must_use]
+    pub fn intersects(&self, other: &Self) -> bool {
+        self.min_x <= other.max_x
+            && other.min_x <= self.max_x
+            && self.min_y <= other.max_y
+            && other.min_y <= self.max_y
+    }
+
+    /// Check whether a point lies within these bounds (edges included).
+    #[must_use]
+    pub fn contains_point(&self, x: f32, y: f32) -> bool {
+        x >= self.min_x && x <= self.max_x && y >= self.min_y && y <= self.max_y
+    }
+
+    /// Center point of the bounds.
+    #[must_use]
+    pub fn center(&self) -> (f32, f32) {
+        (
+            f32::midpoint(self.min_x, self.max_x),
+            f32::midpoint(self.min_y, self.max_y),
+        )
+    }
+}
+
+/// Padding and zoom limits used when fitting bounds into the viewport.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+#[serde(default)]
+pub struct FitOptions {
+    /// Screen-space padding around the fitted bounds, in pixels.
+    pub padding: f32,
+    /// Minimum zoom level.
+    pub min_zoom: f32,
+    /// Maximum zoom level.
+    pub max_zoom: f32,
+}
+
+impl Default for FitOptions {
+    fn default() -> Self {
+        Self {
+            padding: 48.0,
+            min_zoom: 0.1,
+            max_zoom: 4.0,
+        }
+    }
+}
+
+impl FitOptions {
+    /// Set the padding in pixels.
+    #[must_use]
+    pub const fn with_padding(mut self, padding: f32) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    /// Set the zoom limits.
+    #[must_use]
+    pub const fn with_zoom_range(mut self, min_zoom: f32, max_zoom: f32) -> Self {
+        self.min_zoom = min_zoom;
+        self.max_zoom = max_zoom;
+        self
+    }
+}
+
+/// A camera command, resolved against a scene into a [`CameraFrame`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum CameraCommand {
+    /// Frame every element in the scene (resets the view if it is empty).
+    FitToContent,
+    /// Frame the given elements.
+    FitToElements {
+        /// Elements to frame.
+        element_ids: Vec<ElementId>,
+    },
+    /// Return to zoom 1.0 with no pan.
+    ResetView,
+    /// Move to a named anchor saved in the scene.
+    GoToAnchor {
+        /// Anchor name.
+        name: String,
+    },
+}
+
+impl CameraCommand {
+    /// Compute the camera frame this command produces for `scene`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CanvasError::ElementNotFound`] if none of the elements of a
+    /// `FitToElements` command exist, or [`CanvasError::InvalidOperation`] if
+    /// it lists no elements or a `GoToAnchor` anchor does not exist.
+    pub fn target_frame(&self, scene: &Scene, options: &FitOptions) -> CanvasResult<CameraFrame> {
+        match self {
+            Self::FitToContent => Ok(scene
+                .content_bounds()
+                .and_then(|bounds| CameraFrame::fit_bounds(scene, &bounds, options))
+                .unwrap_or_else(CameraFrame::reset)),
+            Self::FitToElements { element_ids } => {
+                let Some(first) = element_ids.first() else {
+                    return Err(CanvasError::InvalidOperation(
+                        "fit_to_elements requires at least one element".to_string(),
+                    ));
+                };
+                let bounds = scene
+                    .bounds_of(element_ids)
+                    .ok_or_else(|| CanvasError::ElementNotFound(first.to_string()))?;
+                Ok(CameraFrame::fit_bounds(scene, &bounds, options)
+                    .unwrap_or_else(|| CameraFrame::from_scene(scene)))
+            }
+            Self::ResetView => Ok(CameraFrame::reset()),
+            Self::GoToAnchor { name } => scene
+                .anchor(name)
+                .map(|anchor| anchor.frame(scene))
+                .ok_or_else(|| CanvasError::InvalidOperation(format!("No anchor named {name}"))),
+        }
+    }
+
+    /// Apply this command to the scene's camera and return the new frame.
+    ///
+    /// # Errors
+    ///
+    /// See [`CameraCommand::target_frame`]. The scene is unchanged on error.
+    pub fn apply(&self, scene: &mut Scene, options: &FitOptions) -> CanvasResult<CameraFrame> {
+        let frame = self.target_frame(scene, options)?;
+        frame.apply_to(scene);
+        Ok(frame)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Color, Element, ElementKind};
+
+    fn element_at(x: f32, y: f32, width: f32, height: f32) -> Element {
+        Element::new(ElementKind::Text {
+            content: "Label".to_string(),
+            font_size: 16.0,
+            color: Color::BLACK.into(),
+        })
+        .with_transform(Transform {
+            x,
+            y,
+            width,
+            height,
+            rotation: 0.0,
+            z_index: 0,
+            auto_size: false,
+        })
+    }
+
+    #[test]
+    fn test_fit_to_content_frames_all_elements() {
+        let mut scene = Scene::new(800.0, 600.0);
+        scene.add_element(element_at(0.0, 0.0, 100.0, 100.0));
+        scene.add_element(element_at(1500.0, 1100.0, 100.0, 100.0));
+
+        let options = FitOptions::default().with_padding(0.0);
+        let frame = CameraCommand::FitToContent
+            .apply(&mut scene, &options)
+            .expect("fit");
+
+        // 1600x1200 of content in an 800x600 viewport
+        assert!((frame.zoom - 0.5).abs() < f32::EPSILON);
+        assert!(frame.pan_x.abs() < 0.01 && frame.pan_y.abs() < 0.01);
+        assert_eq!(CameraFrame::from_scene(&scene), frame);
+
+        let frame = CameraCommand::ResetView
+            .apply(&mut scene, &options)
+            .expect("reset");
+        assert_eq!(frame, CameraFrame::reset());
+
+        // An empty scene fits to the default view
+        let empty = Scene::new(800.0, 600.0);
+        let frame = CameraCommand::FitToContent
+            .target_frame(&empty, &options)
+            .expect("fit");
+        assert_eq!(frame, CameraFrame::reset());
+    }
+
+    #[test]
+    fn test_fit_to_elements_respects_options() {
+        let mut scene = Scene::new(800.0, 600.0);
+        let small = scene.add_element(element_at(100.0, 100.0, 10.0, 10.0));
+        scene.add_element(element_at(600.0, 400.0, 50.0, 50.0));
+
+        let command = CameraCommand::FitToElements {
+            element_ids: vec![small],
+        };
+        let frame = command
+            .target_frame(&scene, &FitOptions::default().with_zoom_range(0.5, 3.0))
+            .expect("fit");
+        assert!((frame.zoom - 3.0).abs() < f32::EPSILON);
+        assert!((frame.pan_x - (400.0 - 105.0 * 3.0)).abs() < 0.01);
+
+        let missing = CameraCommand::FitToElements {
+            element_ids: vec![ElementId::new()],
+        };
+        assert!(missing.apply(&mut scene, &FitOptions::default()).is_err());
+        assert_eq!(CameraFrame::from_scene(&scene), CameraFrame::reset());
+
+        let json = serde_json::to_value(&command).expect("serialize");
+        assert_eq!(json["command"], "fit_to_elements");
+        let parsed: CameraCommand = serde_json::from_value(json).expect("parse");
+        assert_eq!(parsed, command);
+    }
+}
@@ -0,0 +1,212 @@
+//! # Change Tracking
+//!
+//! Revision counters that let renderers and the sync layer ask "what
+//! changed since revision N" instead of re-serializing or redrawing the
+//! whole scene after every mutation.
+//!
+//! ```text
+//!  revision   1        2         3          4
+//!             add A    add B     move A     remove B
+//!
+//!  A ── rev 3          B ── removed at 4          scene settings ── rev 0
+//!
+//!  changes_since(2) → changed [A], removed [B]
+//!  changes_since(0) → changed [A], removed [B]    (B never seen: harmless)
+//! ```
+//!
+//! Every mutation bumps the scene revision and stamps what it touched: an
+//! element, a removal (kept as a tombstone), or the scene-wide settings
+//! (viewport, camera, spotlight, anchors and theme). Tombstones are pruned
+//! past [`MAX_TOMBSTONES`]; asking about revisions from before the pruned
+//! history, or from another scene instance, reports a full change so the
+//! caller resynchronizes from scratch.
+
+use std::collections::HashMap;
+
+use crate::ElementId;
+
+/// Most element removals remembered before the oldest are forgotten.
+pub const MAX_TOMBSTONES: usize = 1024;
+
+/// What changed in a scene after a given revision.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SceneChanges {
+    /// The scene's current revision; pass it to the next query.
+    pub revision: u64,
+    /// The history does not reach back far enough: treat every element and
+    /// setting as changed. `changed` then lists every element.
+    pub full: bool,
+    /// Elements added or modified, in no particular order.
+    pub changed: Vec<ElementId>,
+    /// Elements removed and not added back.
+    pub removed: Vec<ElementId>,
+    /// Whether the viewport, camera, spotlight, anchors or theme changed.
+    pub settings: bool,
+}
+
+impl SceneChanges {
+    /// Check whether nothing changed.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        !self.full && !self.settings && self.changed.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// Revision stamps for a scene's elements and settings.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ChangeLog {
+    /// Latest revision.
+    revision: u64,
+    /// History starts after this revision; earlier queries get a full change.
+    base: u64,
+    /// Revision at which each element last changed.
+    elements: HashMap<ElementId, u64>,
+    /// Revision at which each removed element was removed.
+    removed: HashMap<ElementId, u64>,
+    /// Revision at which the scene-wide settings last changed.
+    settings: u64,
+}
+
+impl ChangeLog {
+    /// A log for a scene whose history is unknown, e.g. one just
+    /// deserialized: every query reports a full change.
+    pub(crate) fn restored() -> Self {
+        Self {
+            revision: 1,
+            base: 1,
+            ..Self::default()
+        }
+    }
+
+    /// Latest revision.
+    pub(crate) fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// Revision at which an element last changed.
+    pub(crate) fn element_revision(&self, id: ElementId) -> Option<u64> {
+        self.elements.get(&id).copied()
+    }
+
+    /// Record that an element was added or modified.
+    pub(crate) fn touch(&mut self, id: ElementId) {
+        self.revision += 1;
+        self.removed.remove(&id);
+        self.elements.insert(id, self.revision);
+    }
+
+    /// Record that several elements changed in one revision.
+    pub(crate) fn touch_all(&mut self, ids: impl IntoIterator<Item = ElementId>) {
+        self.revision += 1;
+        for id in ids {
+            self.removed.remove(&id);
+            self.elements.insert(id, self.revision);
+        }
+    }
+
+    /// Record that an element was removed.
+    pub(crate) fn remove(&mut self, id: ElementId) {
+        self.revision += 1;
+        self.elements.remove(&id);
+        self.removed.insert(id, self.revision);
+        if self.removed.len() > MAX_TOMBSTONES {
+            self.prune();
+        }
+    }
+
+    /// Record that several elements were removed in one revision.
+    pub(crate) fn remove_all(&mut self, ids: impl IntoIterator<Item = ElementId>) {
+        self.revision += 1;
+        for id in ids {
+            self.elements.remove(&id);
+            self.removed.insert(id, self.revision);
+        }
+        if self.removed.len() > MAX_TOMBSTONES {
+            self.prune();
+        }
+    }
+
+    /// Record that the scene-wide settings changed.
+    pub(crate) fn touch_settings(&mut self) {
+        self.revision += 1;
+        self.settings = self.revision;
+    }
+
+    /// Continue after `previous` revisions of a scene this one replaces:
+    /// the revision moves past it and the history restarts, so any earlier
+    /// revision reports a full change.
+    pub(crate) fn supersede(&mut self, previous: u64) {
+        self.revision = self.revision.max(previous) + 1;
+        self.base = self.revision;
+        self.removed.clear();
+    }
+
+    /// Forget the older half of the tombstones.
+    fn prune(&mut self) {
+        let mut stamps: Vec<u64> = self.removed.values().copied().collect();
+        stamps.sort_unstable();
+        let cutoff = stamps[stamps.len() / 2];
+        self.removed.retain(|_, &mut revision| revision > cutoff);
+        self.base = self.base.max(cutoff);
+    }
+
+    /// What changed after `since`, given every element now in the scene.
+    pub(crate) fn since(
+        &self,
+        since: u64,
+        elements: impl Iterator<Item = ElementId>,
+    ) -> SceneChanges {
+        if since < self.base || since > self.revision {
+            return SceneChanges {
+                revision: self.revision,
+                full: true,
+                changed: elements.collect(),
+                removed: Vec::new(),
+                settings: true,
+            };
+        }
+        let newer = |stamps: &HashMap<ElementId, u64>| {
+            stamps
+                .iter()
+                .filter(|(_, &revision)| revision > since)
+                .map(|(&id, _)| id)
+                .collect()
+        };
+        SceneChanges {
+            revision: self.revision,
+            full: false,
+            changed: newer(&self.elements),
+            removed: newer(&self.removed),
+            settings: self.settings > since,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pruned_history_reports_full_change() {
+        let mut log = ChangeLog::default();
+        let kept = ElementId::new();
+        log.touch(kept);
+        let start = log.revision();
+        for _ in 0..=MAX_TOMBSTONES {
+            let id = ElementId::new();
+            log.touch(id);
+            log.remove(id);
+        }
+        assert!(log.removed.len() <= MAX_TOMBSTONES);
+
+        let recent = log.revision() - 1;
+        let changes = log.since(recent, [kept].into_iter());
+        assert!(!changes.full);
+        assert_eq!(changes.removed.len(), 1);
+
+        let changes = log.since(start, [kept].into_iter());
+        assert!(changes.full);
+        assert_eq!(changes.changed, vec![kept]);
+        assert!(log.since(log.revision() + 1, std::iter::empty()).full);
+    }
+}
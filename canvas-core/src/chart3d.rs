@@ -0,0 +1,188 @@
+//! # 3D Charts
+//!
+//! `scatter3d` and `surface` charts plot their data inside a cube seen
+//! through an orbiting camera, kept in the chart's `data.camera` so every
+//! client shows the same view:
+//!
+//! ```json
+//! { "chart_type": "surface",
+//!   "data": { "values": [[0, 1, 0], [1, 3, 1], [0, 1, 0]],
+//!             "camera": { "yaw": 0.8, "pitch": 0.5 } } }
+//! ```
+//!
+//! The data is scaled into the cube from -1 to 1 on each axis, with values
+//! along +Y. The camera is a [`ModelCamera`] around the cube's center;
+//! fields left out of `data.camera` keep [`default_camera`]'s. Renderers
+//! with a 3D pipeline draw the cube as meshes, the rest project it onto the
+//! chart, and holographic views frame it like a `Model3D`. Dragging a 3D
+//! chart orbits the camera (see [`crate::interaction`]).
+
+use std::f32::consts::{FRAC_PI_4, TAU};
+
+use crate::element::ElementKind;
+use crate::error::{CanvasError, CanvasResult};
+use crate::model3d::ModelCamera;
+
+/// Chart types plotted in 3D.
+pub const CHART_3D_TYPES: [&str; 2] = ["scatter3d", "surface"];
+
+/// Radius of the sphere around the data cube.
+pub const CUBE_RADIUS: f32 = 1.732_050_8;
+
+/// Camera turn per pixel dragged, in radians.
+pub const ORBIT_RADIANS_PER_PIXEL: f32 = 0.01;
+
+/// Highest and lowest the camera orbits, in radians, short of looking
+/// straight down or up.
+pub const MAX_ORBIT_PITCH: f32 = 1.4;
+
+/// Whether `chart_type` is plotted in 3D.
+#[must_use]
+pub fn is_3d(chart_type: &str) -> bool {
+    CHART_3D_TYPES
+        .iter()
+        .any(|name| name.eq_ignore_ascii_case(chart_type))
+}
+
+/// A three-quarter view from above that fits the whole cube.
+#[must_use]
+pub fn default_camera() -> ModelCamera {
+    ModelCamera {
+        yaw: FRAC_PI_4,
+        pitch: 0.5,
+        ..ModelCamera::fit([0.0; 3], CUBE_RADIUS)
+    }
+}
+
+/// The camera a 3D chart's `data` asks for, over [`default_camera`].
+/// Unreadable fields keep the default, and a camera that fails
+/// [`ModelCamera::validate`] is replaced by it.
+#[must_use]
+#[allow(clippy::cast_possible_truncation)] // Camera angles and distances fit in f32
+pub fn camera(data: &serde_json::Value) -> ModelCamera {
+    let mut camera = default_camera();
+    let Some(fields) = data.get("camera") else {
+        return camera;
+    };
+    let number = |key| {
+        fields
+            .get(key)
+            .and_then(serde_json::Value::as_f64)
+            .map(|v| v as f32)
+    };
+    camera.yaw = number("yaw").unwrap_or(camera.yaw);
+    camera.pitch = number("pitch").unwrap_or(camera.pitch);
+    camera.distance = number("distance").unwrap_or(camera.distance);
+    camera.fov = number("fov").unwrap_or(camera.fov);
+    if camera.validate().is_ok() {
+        camera
+    } else {
+        default_camera()
+    }
+}
+
+/// The camera of a 3D chart, or `None` for other kinds.
+#[must_use]
+pub fn chart_camera(kind: &ElementKind) -> Option<ModelCamera> {
+    match kind {
+        ElementKind::Chart {
+            chart_type, data, ..
+        } if is_3d(chart_type) => Some(camera(data)),
+        _ => None,
+    }
+}
+
+/// Turn a 3D chart's camera to `yaw` and `pitch`, keeping the pitch within
+/// [`MAX_ORBIT_PITCH`] and the yaw within one turn. Returns the new camera.
+///
+/// # Errors
+///
+/// Returns [`CanvasError::InvalidOperation`] if the element is not a 3D
+/// chart.
+pub fn orbit(kind: &mut ElementKind, yaw: f32, pitch: f32) -> CanvasResult<ModelCamera> {
+    let ElementKind::Chart {
+        chart_type, data, ..
+    } = kind
+    else {
+        return Err(CanvasError::InvalidOperation(
+            "only charts can orbit".to_string(),
+        ));
+    };
+    if !is_3d(chart_type) {
+        return Err(CanvasError::InvalidOperation(format!(
+            "{chart_type} charts are not 3D"
+        )));
+    }
+    let camera = ModelCamera {
+        yaw: yaw.rem_euclid(TAU),
+        pitch: pitch.clamp(-MAX_ORBIT_PITCH, MAX_ORBIT_PITCH),
+        ..camera(data)
+    };
+    if !data.is_object() {
+        *data = serde_json::json!({});
+    }
+    data["camera"] = serde_json::json!({
+        "yaw": camera.yaw,
+        "pitch": camera.pitch,
+        "distance": camera.distance,
+        "fov": camera.fov,
+    });
+    Ok(camera)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chart(chart_type: &str, data: serde_json::Value) -> ElementKind {
+        ElementKind::Chart {
+            chart_type: chart_type.to_string(),
+            data,
+            binding: None,
+        }
+    }
+
+    #[test]
+    fn test_camera_reads_data_over_default() {
+        let default = default_camera();
+        assert!(default.validate().is_ok());
+        assert_eq!(camera(&serde_json::json!({})), default);
+
+        let custom = camera(&serde_json::json!({ "camera": { "yaw": 1.0, "fov": "wide" } }));
+        assert!((custom.yaw - 1.0).abs() < f32::EPSILON);
+        assert!((custom.fov - default.fov).abs() < f32::EPSILON);
+
+        // Looking straight down is not a camera
+        let invalid = camera(&serde_json::json!({ "camera": { "pitch": 2.0 } }));
+        assert_eq!(invalid, default);
+    }
+
+    #[test]
+    fn test_orbit_clamps_and_stores_camera() {
+        let mut kind = chart("surface", serde_json::json!({ "values": [[1, 2]] }));
+        let moved = orbit(&mut kind, -0.5, 3.0).expect("3D chart");
+        assert!((moved.yaw - (TAU - 0.5)).abs() < 1e-5);
+        assert!((moved.pitch - MAX_ORBIT_PITCH).abs() < f32::EPSILON);
+        assert_eq!(chart_camera(&kind), Some(moved));
+
+        let ElementKind::Chart { data, .. } = &kind else {
+            unreachable!();
+        };
+        assert_eq!(data["values"], serde_json::json!([[1, 2]]));
+    }
+
+    #[test]
+    fn test_only_3d_charts_orbit() {
+        assert!(is_3d("Scatter3D"));
+        assert!(!is_3d("scatter"));
+        let mut bar = chart("bar", serde_json::json!({}));
+        assert!(orbit(&mut bar, 0.0, 0.0).is_err());
+        assert_eq!(chart_camera(&bar), None);
+        let mut text = ElementKind::Text {
+            content: String::new(),
+            font_size: 16.0,
+            color: crate::Color::BLACK.into(),
+        };
+        assert!(orbit(&mut text, 0.0, 0.0).is_err());
+    }
+}
@@ -0,0 +1,708 @@
+//! # Colors and Themes
+//!
+//! Typed colors for element content and styles, and scene-level themes that
+//! let many elements share one color definition.
+//!
+//! Colors travel as CSS strings. Anywhere an element takes a color it
+//! accepts a literal or a reference into the scene's [`Theme`]:
+//!
+//! ```text
+//! "#1976d2", "rgb(25, 118, 210)", "hsl(210, 100%, 50%)", "navy"  ← literals
+//! "var(--accent)"                                               ← theme variable
+//! "palette(brand, 2)"                                           ← palette entry
+//! ```
+//!
+//! Literals are normalized to hex (`#rrggbb`, or `#rrggbbaa` when
+//! translucent). References are resolved when the scene is drawn, so
+//! changing a variable or palette restyles every element that uses it. A
+//! reference the theme does not define draws in the element's default color.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use thiserror::Error;
+
+use crate::error::{CanvasError, CanvasResult};
+
+/// Longest allowed variable or palette name, in characters.
+pub const MAX_THEME_NAME_LEN: usize = 32;
+
+/// Most variables, and separately most palettes, one theme may define.
+pub const MAX_THEME_ENTRIES: usize = 64;
+
+/// Most colors one palette may hold.
+pub const MAX_PALETTE_COLORS: usize = 32;
+
+/// A color string that could not be parsed.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("Invalid color: {0}")]
+pub struct ColorError(String);
+
+/// An sRGB color with 8-bit channels and alpha.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(as = "String"))]
+pub struct Color {
+    /// Red channel.
+    pub r: u8,
+    /// Green channel.
+    pub g: u8,
+    /// Blue channel.
+    pub b: u8,
+    /// Alpha channel (0 = transparent, 255 = opaque).
+    pub a: u8,
+}
+
+/// CSS named colors accepted by [`Color::parse`].
+const NAMED_COLORS: &[(&str, u32)] = &[
+    ("black", 0x00_0000),
+    ("white", 0xff_ffff),
+    ("gray", 0x80_8080),
+    ("grey", 0x80_8080),
+    ("silver", 0xc0_c0c0),
+    ("red", 0xff_0000),
+    ("maroon", 0x80_0000),
+    ("orange", 0xff_a500),
+    ("yellow", 0xff_ff00),
+    ("olive", 0x80_8000),
+    ("lime", 0x00_ff00),
+    ("green", 0x00_8000),
+    ("teal", 0x00_8080),
+    ("aqua", 0x00_ffff),
+    ("cyan", 0x00_ffff),
+    ("blue", 0x00_00ff),
+    ("navy", 0x00_0080),
+    ("purple", 0x80_0080),
+    ("fuchsia", 0xff_00ff),
+    ("magenta", 0xff_00ff),
+    ("pink", 0xff_c0cb),
+    ("brown", 0xa5_2a2a),
+];
+
+impl Color {
+    /// Opaque black.
+    pub const BLACK: Self = Self::from_rgb(0x00_0000);
+    /// Opaque white.
+    pub const WHITE: Self = Self::from_rgb(0xff_ffff);
+    /// Fully transparent black.
+    pub const TRANSPARENT: Self = Self::rgba(0, 0, 0, 0);
+
+    /// Create an opaque color from its channels.
+    #[must_use]
+    pub const fn rgb(r: u8, g: u8, b: u8) -> Self {
+        Self::rgba(r, g, b, 255)
+    }
+
+    /// Create a color from its channels and alpha.
+    #[must_use]
+    pub const fn rgba(r: u8, g: u8, b: u8, a: u8) -> Self {
+        Self { r, g, b, a }
+    }
+
+    /// Create an opaque color from a `0xRRGGBB` value.
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation)]
+    pub const fn from_rgb(rgb: u32) -> Self {
+        Self::rgb((rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8)
+    }
+
+    /// Parse a CSS color: `#rgb`, `#rgba`, `#rrggbb`, `#rrggbbaa`,
+    /// `rgb()`/`rgba()`, `hsl()`/`hsla()`, or a basic named color.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ColorError`] if the string is not one of those forms.
+    pub fn parse(input: &str) -> Result<Self, ColorError> {
+        let invalid = || ColorError(input.to_string());
+        let lower = input.trim().to_ascii_lowercase();
+
+        if let Some(hex) = lower.strip_prefix('#') {
+            return parse_hex(hex).ok_or_else(invalid);
+        }
+        if let Some(args) = function_args(&lower, &["rgba", "rgb"]) {
+            return parse_rgb(&args).ok_or_else(invalid);
+        }
+        if let Some(args) = function_args(&lower, &["hsla", "hsl"]) {
+            return parse_hsl(&args).ok_or_else(invalid);
+        }
+        if lower == "transparent" {
+            return Ok(Self::TRANSPARENT);
+        }
+        NAMED_COLORS
+            .iter()
+            .find(|(name, _)| *name == lower)
+            .map(|&(_, rgb)| Self::from_rgb(rgb))
+            .ok_or_else(invalid)
+    }
+
+    /// Alpha as a fraction (0.0 = transparent, 1.0 = opaque).
+    #[must_use]
+    pub fn alpha(self) -> f32 {
+        f32::from(self.a) / 255.0
+    }
+
+    /// The same color with its alpha scaled by `opacity` (clamped to `0.0..=1.0`).
+    #[must_use]
+    pub fn with_opacity(self, opacity: f32) -> Self {
+        Self {
+            a: channel(self.alpha() * opacity.clamp(0.0, 1.0) * 255.0),
+            ..self
+        }
+    }
+
+    /// Channels as `[r, g, b, a]` fractions, as GPU backends expect.
+    #[must_use]
+    pub fn to_rgba_f32(self) -> [f32; 4] {
+        [
+            f32::from(self.r) / 255.0,
+            f32::from(self.g) / 255.0,
+            f32::from(self.b) / 255.0,
+            self.alpha(),
+        ]
+    }
+
+    /// Format as `#rrggbb`, or `#rrggbbaa` when not fully opaque.
+    #[must_use]
+    pub fn to_hex(self) -> String {
+        if self.a == 255 {
+            format!("#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+        } else {
+            format!("#{:02x}{:02x}{:02x}{:02x}", self.r, self.g, self.b, self.a)
+        }
+    }
+
+    /// This color painted over an opaque `background`, as an opaque color.
+    #[must_use]
+    pub fn over(self, background: Self) -> Self {
+        let alpha = self.alpha();
+        let mix = |top: u8, bottom: u8| {
+            channel(f32::from(top) * alpha + f32::from(bottom) * (1.0 - alpha))
+        };
+        Self::rgba(
+            mix(self.r, background.r),
+            mix(self.g, background.g),
+            mix(self.b, background.b),
+            255,
+        )
+    }
+
+    /// WCAG relative luminance (0.0 = black, 1.0 = white), ignoring alpha.
+    #[must_use]
+    pub fn relative_luminance(self) -> f32 {
+        let linear = |c: u8| {
+            let c = f32::from(c) / 255.0;
+            if c <= 0.040_45 {
+                c / 12.92
+            } else {
+                ((c + 0.055) / 1.055).powf(2.4)
+            }
+        };
+        0.2126 * linear(self.r) + 0.7152 * linear(self.g) + 0.0722 * linear(self.b)
+    }
+
+    /// WCAG contrast ratio between two opaque colors, from 1.0 (none) to
+    /// 21.0 (black on white). Body text needs 4.5 for WCAG AA.
+    #[must_use]
+    pub fn contrast_ratio(self, other: Self) -> f32 {
+        let (a, b) = (self.relative_luminance(), other.relative_luminance());
+        (a.max(b) + 0.05) / (a.min(b) + 0.05)
+    }
+}
+
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_hex())
+    }
+}
+
+impl FromStr for Color {
+    type Err = ColorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl Serialize for Color {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Color {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        Self::parse(&s).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Round and clamp a channel value to `0..=255`.
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn channel(value: f32) -> u8 {
+    value.round().clamp(0.0, 255.0) as u8
+}
+
+/// The arguments of `name(...)` for the first matching name, split on
+/// commas, slashes and whitespace.
+fn function_args<'a>(input: &'a str, names: &[&str]) -> Option<Vec<&'a str>> {
+    let body = names.iter().find_map(|name| {
+        input
+            .strip_prefix(name)?
+            .trim_start()
+            .strip_prefix('(')?
+            .strip_suffix(')')
+    })?;
+    Some(
+        body.split(|c: char| c == ',' || c == '/' || c.is_whitespace())
+            .filter(|arg| !arg.is_empty())
+            .collect(),
+    )
+}
+
+fn parse_hex(hex: &str) -> Option<Color> {
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let digit = |i: usize| u8::from_str_radix(&hex[i..=i], 16).ok();
+    let pair = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    match hex.len() {
+        3 | 4 => {
+            let a = if hex.len() == 4 { digit(3)? * 17 } else { 255 };
+            Some(Color::rgba(
+                digit(0)? * 17,
+                digit(1)? * 17,
+                digit(2)? * 17,
+                a,
+            ))
+        }
+        6 | 8 => {
+            let a = if hex.len() == 8 { pair(6)? } else { 255 };
+            Some(Color::rgba(pair(0)?, pair(2)?, pair(4)?, a))
+        }
+        _ => None,
+    }
+}
+
+/// A number, or a percentage of `full`.
+fn number_or_percent(arg: &str, full: f32) -> Option<f32> {
+    let value = match arg.strip_suffix('%') {
+        Some(percent) => percent.parse::<f32>().ok()? / 100.0 * full,
+        None => arg.parse::<f32>().ok()?,
+    };
+    value.is_finite().then_some(value)
+}
+
+/// An optional alpha argument as a 0..=255 channel.
+fn alpha_arg(arg: Option<&&str>) -> Option<u8> {
+    match arg {
+        Some(arg) => Some(channel(
+            number_or_percent(arg, 1.0)?.clamp(0.0, 1.0) * 255.0,
+        )),
+        None => Some(255),
+    }
+}
+
+fn parse_rgb(args: &[&str]) -> Option<Color> {
+    if !(3..=4).contains(&args.len()) {
+        return None;
+    }
+    let c = |i: usize| number_or_percent(args[i], 255.0).map(channel);
+    Some(Color::rgba(c(0)?, c(1)?, c(2)?, alpha_arg(args.get(3))?))
+}
+
+fn parse_hsl(args: &[&str]) -> Option<Color> {
+    if !(3..=4).contains(&args.len()) {
+        return None;
+    }
+    let hue = args[0].strip_suffix("deg").unwrap_or(args[0]);
+    let hue = hue
+        .parse::<f32>()
+        .ok()
+        .filter(|hue| hue.is_finite())?
+        .rem_euclid(360.0);
+    let saturation = number_or_percent(args[1], 100.0)?.clamp(0.0, 100.0) / 100.0;
+    let lightness = number_or_percent(args[2], 100.0)?.clamp(0.0, 100.0) / 100.0;
+
+    let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+    let second = chroma * (1.0 - ((hue / 60.0).rem_euclid(2.0) - 1.0).abs());
+    let (red, green, blue) = match hue {
+        hue if hue < 60.0 => (chroma, second, 0.0),
+        hue if hue < 120.0 => (second, chroma, 0.0),
+        hue if hue < 180.0 => (0.0, chroma, second),
+        hue if hue < 240.0 => (0.0, second, chroma),
+        hue if hue < 300.0 => (second, 0.0, chroma),
+        _ => (chroma, 0.0, second),
+    };
+    let offset = lightness - chroma / 2.0;
+    let scale = |v: f32| channel((v + offset) * 255.0);
+    Some(Color::rgba(
+        scale(red),
+        scale(green),
+        scale(blue),
+        alpha_arg(args.get(3))?,
+    ))
+}
+
+/// A color as elements store it: a literal or a reference into the scene's
+/// [`Theme`].
+///
+/// Serialized as a CSS string (see the [module docs](self)).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS), ts(as = "String"))]
+pub enum ThemeColor {
+    /// A fixed color.
+    Literal(Color),
+    /// A theme variable, written `var(--name)`.
+    Variable(String),
+    /// An entry of a theme palette, written `palette(name, index)`.
+    Palette {
+        /// Palette name.
+        name: String,
+        /// Position in the palette; wraps around past the end.
+        index: usize,
+    },
+}
+
+impl ThemeColor {
+    /// Reference the theme variable `name`.
+    #[must_use]
+    pub fn variable(name: impl Into<String>) -> Self {
+        Self::Variable(name.into())
+    }
+
+    /// Reference entry `index` of the theme palette `name`.
+    #[must_use]
+    pub fn palette(name: impl Into<String>, index: usize) -> Self {
+        Self::Palette {
+            name: name.into(),
+            index,
+        }
+    }
+
+    /// Parse a literal color, `var(--name)` or `palette(name, index)`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ColorError`] if the string is none of those, or a
+    /// reference names an invalid variable or palette.
+    pub fn parse(input: &str) -> Result<Self, ColorError> {
+        let invalid = || ColorError(input.to_string());
+        let trimmed = input.trim();
+
+        if let Some(args) = function_args(trimmed, &["var"]) {
+            return match args.as_slice() {
+                [name] => name
+                    .strip_prefix("--")
+                    .filter(|name| valid_name(name))
+                    .map(Self::variable)
+                    .ok_or_else(invalid),
+                _ => Err(invalid()),
+            };
+        }
+        if let Some(args) = function_args(trimmed, &["palette"]) {
+            return match args.as_slice() {
+                [name, index] if valid_name(name) => index
+                    .parse()
+                    .map(|index| Self::palette(*name, index))
+                    .map_err(|_| invalid()),
+                _ => Err(invalid()),
+            };
+        }
+        Color::parse(trimmed).map(Self::Literal)
+    }
+
+    /// The fixed color, if this is not a theme reference.
+    #[must_use]
+    pub fn literal(&self) -> Option<Color> {
+        match self {
+            Self::Literal(color) => Some(*color),
+            Self::Variable(_) | Self::Palette { .. } => None,
+        }
+    }
+}
+
+impl From<Color> for ThemeColor {
+    fn from(color: Color) -> Self {
+        Self::Literal(color)
+    }
+}
+
+impl Default for ThemeColor {
+    fn default() -> Self {
+        Self::Literal(Color::BLACK)
+    }
+}
+
+impl fmt::Display for ThemeColor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Literal(color) => color.fmt(f),
+            Self::Variable(name) => write!(f, "var(--{name})"),
+            Self::Palette { name, index } => write!(f, "palette({name}, {index})"),
+        }
+    }
+}
+
+impl FromStr for ThemeColor {
+    type Err = ColorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl Serialize for ThemeColor {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for ThemeColor {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        Self::parse(&s).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Whether `name` can name a theme variable or palette.
+fn valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.chars().count() <= MAX_THEME_NAME_LEN
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Named colors shared by every element in a scene.
+///
+/// Variables map a name to a color, which may itself be a palette entry so
+/// that switching palettes restyles everything derived from it:
+///
+/// ```text
+/// palettes:  brand = [#1976d2, #ff7043]
+/// variables: accent = palette(brand, 1), ink = #263238
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+pub struct Theme {
+    /// Named colors, referenced as `var(--name)`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub variables: BTreeMap<String, ThemeColor>,
+    /// Named color lists, referenced as `palette(name, index)`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub palettes: BTreeMap<String, Vec<Color>>,
+}
+
+impl Theme {
+    /// Check whether the theme defines nothing.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.variables.is_empty() && self.palettes.is_empty()
+    }
+
+    /// Set a variable.
+    #[must_use]
+    pub fn with_variable(mut self, name: impl Into<String>, color: impl Into<ThemeColor>) -> Self {
+        self.variables.insert(name.into(), color.into());
+        self
+    }
+
+    /// Set a palette.
+    #[must_use]
+    pub fn with_palette(mut self, name: impl Into<String>, colors: Vec<Color>) -> Self {
+        self.palettes.insert(name.into(), colors);
+        self
+    }
+
+    /// Resolve a color against this theme.
+    ///
+    /// Returns `None` for a reference to an undefined variable or palette.
+    #[must_use]
+    pub fn resolve(&self, color: &ThemeColor) -> Option<Color> {
+        match color {
+            ThemeColor::Literal(color) => Some(*color),
+            ThemeColor::Variable(name) => match self.variables.get(name)? {
+                // Variables cannot reference variables (see `validate`)
+                ThemeColor::Variable(_) => None,
+                other => self.resolve(other),
+            },
+            ThemeColor::Palette { name, index } => {
+                let colors = self.palettes.get(name)?;
+                colors.get(index % colors.len().max(1)).copied()
+            }
+        }
+    }
+
+    /// Resolve a color, falling back to `fallback` for undefined references.
+    #[must_use]
+    pub fn resolve_or(&self, color: &ThemeColor, fallback: Color) -> Color {
+        self.resolve(color).unwrap_or(fallback)
+    }
+
+    /// Check the theme can be stored.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CanvasError::InvalidOperation`] if a name is invalid, there
+    /// are more than [`MAX_THEME_ENTRIES`] variables or palettes, a palette
+    /// is empty or longer than [`MAX_PALETTE_COLORS`], or a variable refers
+    /// to another variable.
+    pub fn validate(&self) -> CanvasResult<()> {
+        if self.variables.len() > MAX_THEME_ENTRIES || self.palettes.len() > MAX_THEME_ENTRIES {
+            return Err(CanvasError::InvalidOperation(format!(
+                "A theme may define at most {MAX_THEME_ENTRIES} variables and {MAX_THEME_ENTRIES} palettes"
+            )));
+        }
+        for name in self.variables.keys().chain(self.palettes.keys()) {
+            if !valid_name(name) {
+                return Err(CanvasError::InvalidOperation(format!(
+                    "Invalid theme name {name:?}: use up to {MAX_THEME_NAME_LEN} letters, digits, '-' or '_'"
+                )));
+            }
+        }
+        for (name, colors) in &self.palettes {
+            if colors.is_empty() || colors.len() > MAX_PALETTE_COLORS {
+                return Err(CanvasError::InvalidOperation(format!(
+                    "Palette {name} needs 1 to {MAX_PALETTE_COLORS} colors, got {}",
+                    colors.len()
+                )));
+            }
+        }
+        for (name, color) in &self.variables {
+            if matches!(color, ThemeColor::Variable(_)) {
+                return Err(CanvasError::InvalidOperation(format!(
+                    "Variable {name} refers to another variable ({color})"
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_format_literals() {
+        let blue = Color::rgb(0x19, 0x76, 0xd2);
+        assert_eq!(Color::parse("#1976d2"), Ok(blue));
+        assert_eq!(Color::parse("#1976D2"), Ok(blue));
+        assert_eq!(Color::parse("rgb(25, 118, 210)"), Ok(blue));
+        assert_eq!(Color::parse("rgb(25 118 210)"), Ok(blue));
+        assert_eq!(
+            Color::parse("hsl(210, 100%, 50%)"),
+            Ok(Color::rgb(0, 128, 255))
+        );
+        assert_eq!(Color::parse("#fff"), Ok(Color::WHITE));
+        assert_eq!(Color::parse("navy"), Ok(Color::from_rgb(0x00_0080)));
+        assert_eq!(Color::parse("#00000040"), Ok(Color::rgba(0, 0, 0, 0x40)));
+        assert_eq!(
+            Color::parse("rgba(255, 0, 0, 0.5)"),
+            Ok(Color::rgba(255, 0, 0, 128))
+        );
+        assert_eq!(
+            Color::parse("hsla(120deg 100% 50% / 50%)"),
+            Ok(Color::rgba(0, 255, 0, 128))
+        );
+        for bad in [
+            "",
+            "#12",
+            "#ggg",
+            "rgb(1, 2)",
+            "hsl(x, 1%, 1%)",
+            "chartreuse-ish",
+        ] {
+            assert!(Color::parse(bad).is_err(), "{bad} should not parse");
+        }
+
+        assert_eq!(blue.to_hex(), "#1976d2");
+        assert_eq!(Color::rgba(0, 0, 0, 0x40).to_string(), "#00000040");
+        assert_eq!(Color::WHITE.with_opacity(0.5).a, 128);
+        let [r, g, b, a] = Color::from_rgb(0xff_0000).to_rgba_f32();
+        assert!((r - 1.0).abs() < f32::EPSILON && g == 0.0 && b == 0.0);
+        assert!((a - 1.0).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn test_contrast_ratio() {
+        assert!((Color::BLACK.contrast_ratio(Color::WHITE) - 21.0).abs() < 0.01);
+        assert!((Color::WHITE.contrast_ratio(Color::WHITE) - 1.0).abs() < f32::EPSILON);
+        let gray = Color::parse("#777777").expect("gray");
+        let ratio = gray.contrast_ratio(Color::WHITE);
+        assert!(ratio > 4.4 && ratio < 4.5, "{ratio}");
+
+        let faded = Color::BLACK.with_opacity(0.5).over(Color::WHITE);
+        assert_eq!(faded, Color::rgba(127, 127, 127, 255));
+    }
+
+    #[test]
+    fn test_theme_color_serializes_as_css_string() {
+        for (input, canonical) in [
+            ("#FF0000", "#ff0000"),
+            ("var(--accent)", "var(--accent)"),
+            ("palette(brand,2)", "palette(brand, 2)"),
+        ] {
+            let color: ThemeColor =
+                serde_json::from_value(serde_json::json!(input)).expect("parse");
+            assert_eq!(serde_json::to_value(&color).expect("json"), canonical);
+        }
+        for bad in [
+            "var(accent)",
+            "var(--a b)",
+            "palette(brand)",
+            "palette(brand, -1)",
+        ] {
+            assert!(ThemeColor::parse(bad).is_err(), "{bad} should not parse");
+        }
+    }
+
+    #[test]
+    fn test_theme_resolves_references() {
+        let brand = vec![Color::from_rgb(0x19_76d2), Color::from_rgb(0xff_7043)];
+        let theme = Theme::default()
+            .with_palette("brand", brand.clone())
+            .with_variable("accent", ThemeColor::palette("brand", 1))
+            .with_variable("ink", Color::from_rgb(0x26_3238));
+        assert!(theme.validate().is_ok());
+
+        assert_eq!(
+            theme.resolve(&ThemeColor::variable("accent")),
+            Some(brand[1])
+        );
+        // Palette indices wrap around
+        assert_eq!(
+            theme.resolve(&ThemeColor::palette("brand", 2)),
+            Some(brand[0])
+        );
+        assert_eq!(theme.resolve(&ThemeColor::variable("missing")), None);
+        assert_eq!(
+            theme.resolve_or(&ThemeColor::palette("missing", 0), Color::BLACK),
+            Color::BLACK
+        );
+
+        // Swapping the palette restyles everything that references it
+        let mut rebranded = theme.clone();
+        rebranded
+            .palettes
+            .insert("brand".to_string(), vec![Color::BLACK, Color::WHITE]);
+        assert_eq!(
+            rebranded.resolve(&ThemeColor::variable("accent")),
+            Some(Color::WHITE)
+        );
+
+        let chained = theme
+            .clone()
+            .with_variable("alias", ThemeColor::variable("accent"));
+        assert!(chained.validate().is_err());
+        assert!(Theme::default()
+            .with_palette("empty", Vec::new())
+            .validate()
+            .is_err());
+        assert!(Theme::default()
+            .with_variable("bad name", Color::BLACK)
+            .validate()
+            .is_err());
+    }
+}
@@ -0,0 +1,689 @@
+//! # Gauges and KPI Cards
+//!
+//! Thresholds, number formatting and geometry for `Gauge` and `Kpi`
+//! elements, the building blocks of dashboards.
+//!
+//! ```text
+//! ┌───────────────────────┐  ┌───────────────────────┐  ┌───────────────────────┐
+//! │      ╭───────╮        │  │ Uptime                │  │ Revenue               │  label
+//! │    ╱ ███       ╲      │  │ ██████████░░░░░░  72% │  │ $1.2M                 │  value
+//! │   │ ██  72%     │     │  └───────────────────────┘  │ ▲ +120k (+11.1%)      │  delta
+//! │        Load           │        linear gauge         │ ╱╲__╱‾‾╲_╱‾           │  sparkline
+//! └───────────────────────┘                             └───────────────────────┘
+//!       radial gauge                                            KPI card
+//! ```
+//!
+//! A gauge fills its dial from `min` up to its value; a KPI card shows a
+//! headline number, its change since a previous value and an optional
+//! sparkline. Both color the value by the highest [`Threshold`] it reaches.
+//! [`GaugeLayout`] and [`KpiLayout`] compute the geometry in the element's
+//! local frame (origin at its unrotated top-left corner) so every renderer
+//! draws the same thing.
+
+use std::f32::consts::PI;
+
+use serde::{Deserialize, Serialize};
+
+use crate::color::{Color, Theme, ThemeColor};
+use crate::element::ElementKind;
+use crate::error::{CanvasError, CanvasResult};
+use crate::sparkline;
+
+/// Most thresholds a gauge or KPI card may have.
+pub const MAX_THRESHOLDS: usize = 8;
+
+/// Most decimal places a [`NumberFormat`] may show.
+pub const MAX_DECIMALS: u8 = 6;
+
+/// Color of a gauge's unfilled track.
+pub const GAUGE_TRACK_COLOR: Color = Color::from_rgb(0xec_eff1);
+
+/// Color of a value below every threshold.
+pub const VALUE_COLOR: Color = Color::from_rgb(0x1e_88e5);
+
+/// Color of a change for the better.
+pub const GOOD_COLOR: Color = Color::from_rgb(0x2e_7d32);
+
+/// Color of a change for the worse.
+pub const BAD_COLOR: Color = Color::from_rgb(0xc6_2828);
+
+/// Color of an unchanged value.
+pub const NEUTRAL_COLOR: Color = Color::from_rgb(0x75_7575);
+
+/// Whether a gauge is drawn as a dial or a bar.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+#[serde(rename_all = "lowercase")]
+pub enum GaugeShape {
+    /// A half-circle dial filling clockwise from the left.
+    #[default]
+    Radial,
+    /// A horizontal bar filling from the left.
+    Linear,
+}
+
+/// A value from which a gauge or KPI takes a different color, e.g. amber
+/// from 70 and red from 90.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+pub struct Threshold {
+    /// Values at or above this take `color`.
+    pub value: f64,
+    /// Color of the value, fill and tick mark.
+    pub color: ThemeColor,
+}
+
+/// Color of `value`: that of the highest threshold it reaches, or
+/// [`VALUE_COLOR`] below every threshold.
+#[must_use]
+pub fn threshold_color(value: f64, thresholds: &[Threshold], theme: &Theme) -> Color {
+    thresholds
+        .iter()
+        .filter(|threshold| value >= threshold.value)
+        .max_by(|a, b| a.value.total_cmp(&b.value))
+        .map_or(VALUE_COLOR, |threshold| {
+            theme.resolve_or(&threshold.color, VALUE_COLOR)
+        })
+}
+
+/// How a gauge or KPI value is written, e.g. `$1,250.50` or `1.3M users`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+pub struct NumberFormat {
+    /// Digits after the decimal point (at most [`MAX_DECIMALS`]).
+    #[serde(default)]
+    pub decimals: u8,
+    /// Text before the number, e.g. `$`.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub prefix: String,
+    /// Text after the number, e.g. `%` or ` ms`.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub suffix: String,
+    /// Abbreviate thousands, millions and billions as `k`, `M` and `B`.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub compact: bool,
+}
+
+impl NumberFormat {
+    /// Write `value` with thousands separators, or abbreviated when
+    /// `compact`. Non-finite values are written as `–`.
+    #[must_use]
+    pub fn format(&self, value: f64) -> String {
+        if !value.is_finite() {
+            return "–".to_string();
+        }
+        let magnitude = self.magnitude(value.abs());
+        let sign = if value < 0.0 && magnitude.bytes().any(|b| (b'1'..=b'9').contains(&b)) {
+            "-"
+        } else {
+            ""
+        };
+        format!("{sign}{}{magnitude}{}", self.prefix, self.suffix)
+    }
+
+    /// Write a change in value with an explicit sign, e.g. `+1,200`.
+    #[must_use]
+    pub fn format_delta(&self, delta: f64) -> String {
+        let text = self.format(delta);
+        if delta > 0.0 && text.bytes().any(|b| (b'1'..=b'9').contains(&b)) {
+            format!("+{text}")
+        } else {
+            text
+        }
+    }
+
+    /// Digits of a non-negative value, without sign, prefix or suffix.
+    fn magnitude(&self, value: f64) -> String {
+        let decimals = usize::from(self.decimals.min(MAX_DECIMALS));
+        if self.compact {
+            // Move up a unit when rounding reaches 1000 (999.96k → 1M)
+            let mut scaled = value;
+            for unit in ["", "k", "M", "B"] {
+                let digits = if unit.is_empty() {
+                    format!("{scaled:.decimals$}")
+                } else {
+                    trim_zeros(&format!("{scaled:.*}", decimals.max(1)))
+                };
+                let rounded: f64 = digits.parse().unwrap_or(scaled);
+                if rounded < 1000.0 || unit == "B" {
+                    return format!("{}{unit}", group_thousands(&digits));
+                }
+                scaled /= 1000.0;
+            }
+        }
+        group_thousands(&format!("{value:.decimals$}"))
+    }
+}
+
+/// Drop trailing zeros after a decimal point, and the point itself.
+fn trim_zeros(digits: &str) -> String {
+    if digits.contains('.') {
+        digits
+            .trim_end_matches('0')
+            .trim_end_matches('.')
+            .to_string()
+    } else {
+        digits.to_string()
+    }
+}
+
+/// Insert `,` between groups of three integer digits.
+fn group_thousands(digits: &str) -> String {
+    let (integer, fraction) = digits.split_at(digits.find('.').unwrap_or(digits.len()));
+    let mut grouped = String::with_capacity(digits.len() + integer.len() / 3);
+    for (i, c) in integer.chars().enumerate() {
+        if i > 0 && (integer.len() - i) % 3 == 0 {
+            grouped.push(',');
+        }
+        grouped.push(c);
+    }
+    grouped.push_str(fraction);
+    grouped
+}
+
+/// How far `value` is from `min` to `max`, clamped to `0.0..=1.0`.
+#[must_use]
+#[allow(clippy::cast_possible_truncation)]
+pub fn gauge_fraction(value: f64, min: f64, max: f64) -> f32 {
+    let fraction = (value - min) / (max - min);
+    if fraction.is_finite() {
+        fraction.clamp(0.0, 1.0) as f32
+    } else {
+        0.0
+    }
+}
+
+/// Whether a value went up, down or stayed the same.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Trend {
+    /// The value increased.
+    Up,
+    /// The value decreased.
+    Down,
+    /// The value did not change.
+    Flat,
+}
+
+impl Trend {
+    /// Compare a value with its previous value.
+    #[must_use]
+    pub fn between(previous: f64, value: f64) -> Self {
+        if value > previous {
+            Self::Up
+        } else if value < previous {
+            Self::Down
+        } else {
+            Self::Flat
+        }
+    }
+
+    /// Arrow shown before the change.
+    #[must_use]
+    pub fn arrow(self) -> &'static str {
+        match self {
+            Self::Up => "▲",
+            Self::Down => "▼",
+            Self::Flat => "▶",
+        }
+    }
+
+    /// Color of the change: going up is good when `higher_is_better`.
+    #[must_use]
+    pub fn color(self, higher_is_better: bool) -> Color {
+        match (self, higher_is_better) {
+            (Self::Flat, _) => NEUTRAL_COLOR,
+            (Self::Up, true) | (Self::Down, false) => GOOD_COLOR,
+            (Self::Up, false) | (Self::Down, true) => BAD_COLOR,
+        }
+    }
+}
+
+/// The change shown on a KPI card.
+#[derive(Debug, Clone, PartialEq)]
+pub struct KpiDelta {
+    /// Direction of the change.
+    pub trend: Trend,
+    /// Arrow, change and percentage, e.g. `▲ +120k (+11.1%)`.
+    pub text: String,
+    /// Green for better, red for worse, gray for no change.
+    pub color: Color,
+}
+
+impl KpiDelta {
+    /// The change from `previous` to `value`; the percentage is omitted when
+    /// `previous` is zero.
+    #[must_use]
+    pub fn new(value: f64, previous: f64, format: &NumberFormat, higher_is_better: bool) -> Self {
+        let trend = Trend::between(previous, value);
+        let delta = value - previous;
+        let mut text = format!("{} {}", trend.arrow(), format.format_delta(delta));
+        if previous != 0.0 {
+            let percent = delta / previous.abs() * 100.0;
+            let sign = if percent > 0.0 { "+" } else { "" };
+            text = format!("{text} ({sign}{percent:.1}%)");
+        }
+        Self {
+            trend,
+            text,
+            color: trend.color(higher_is_better),
+        }
+    }
+}
+
+/// The dial of a [`GaugeLayout`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Dial {
+    /// A half-circle arc over its center.
+    Radial {
+        /// Center x (bottom middle of the arc).
+        cx: f32,
+        /// Center y.
+        cy: f32,
+        /// Radius of the arc's center line.
+        radius: f32,
+        /// Arc thickness.
+        thickness: f32,
+    },
+    /// A horizontal bar.
+    Linear {
+        /// Left edge.
+        x: f32,
+        /// Top edge.
+        y: f32,
+        /// Full width.
+        width: f32,
+        /// Bar height.
+        height: f32,
+    },
+}
+
+impl Dial {
+    /// Angle of `fraction` along a radial dial, in radians clockwise from
+    /// the positive x axis (y down): π at the left end, 2π at the right.
+    #[must_use]
+    pub fn angle(fraction: f32) -> f32 {
+        PI + fraction.clamp(0.0, 1.0) * PI
+    }
+
+    /// A tick across the dial at `fraction`, as two end points.
+    #[must_use]
+    pub fn tick(&self, fraction: f32) -> [(f32, f32); 2] {
+        match *self {
+            Self::Radial {
+                cx,
+                cy,
+                radius,
+                thickness,
+            } => {
+                let angle = Self::angle(fraction);
+                let (inner, outer) = (radius - thickness * 0.7, radius + thickness * 0.7);
+                [
+                    (cx + inner * angle.cos(), cy + inner * angle.sin()),
+                    (cx + outer * angle.cos(), cy + outer * angle.sin()),
+                ]
+            }
+            Self::Linear {
+                x,
+                y,
+                width,
+                height,
+            } => {
+                let tx = x + width * fraction.clamp(0.0, 1.0);
+                [(tx, y - height * 0.3), (tx, y + height * 1.3)]
+            }
+        }
+    }
+}
+
+/// Geometry of a gauge element.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GaugeLayout {
+    /// The dial.
+    pub dial: Dial,
+    /// Center of the value text.
+    pub value_x: f32,
+    /// Center of the value text.
+    pub value_y: f32,
+    /// Font size of the value.
+    pub value_font_size: f32,
+    /// Center of the label text.
+    pub label_y: f32,
+    /// Font size of the label.
+    pub label_font_size: f32,
+}
+
+impl GaugeLayout {
+    /// Space around the dial.
+    pub const PADDING: f32 = 8.0;
+
+    /// Lay out a gauge in a `width` × `height` element.
+    #[must_use]
+    pub fn new(shape: GaugeShape, width: f32, height: f32) -> Self {
+        let pad = Self::PADDING;
+        let label_font_size = (height * 0.12).clamp(9.0, 16.0);
+        match shape {
+            GaugeShape::Radial => {
+                let label_y = height - pad - label_font_size / 2.0;
+                let bottom = height - 2.0 * pad - label_font_size;
+                let outer = ((width - 2.0 * pad) / 2.0).min(bottom - pad).max(0.0);
+                let thickness = outer * 0.22;
+                let radius = outer - thickness / 2.0;
+                let value_font_size = (radius * 0.4).clamp(10.0, 40.0);
+                Self {
+                    dial: Dial::Radial {
+                        cx: width / 2.0,
+                        cy: bottom,
+                        radius,
+                        thickness,
+                    },
+                    value_x: width / 2.0,
+                    value_y: bottom - value_font_size / 2.0,
+                    value_font_size,
+                    label_y,
+                    label_font_size,
+                }
+            }
+            GaugeShape::Linear => {
+                let value_font_size = (height * 0.3).clamp(10.0, 28.0);
+                let value_width = value_font_size * 3.5;
+                let bar_height = (height * 0.2).clamp(6.0, 24.0);
+                let label_y = pad + label_font_size / 2.0;
+                let bar_y = (label_y + label_font_size / 2.0 + pad)
+                    .max((height - bar_height) / 2.0)
+                    .min(height - pad - bar_height);
+                Self {
+                    dial: Dial::Linear {
+                        x: pad,
+                        y: bar_y,
+                        width: (width - 3.0 * pad - value_width).max(0.0),
+                        height: bar_height,
+                    },
+                    value_x: width - pad - value_width / 2.0,
+                    value_y: bar_y + bar_height / 2.0,
+                    value_font_size,
+                    label_y,
+                    label_font_size,
+                }
+            }
+        }
+    }
+}
+
+/// Geometry of a KPI card, top to bottom: label, value, change, sparkline.
+///
+/// Text positions are the vertical centers of each line; text starts at
+/// [`KpiLayout::PADDING`] from the left.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KpiLayout {
+    /// Center of the label line.
+    pub label_y: f32,
+    /// Font size of the label.
+    pub label_font_size: f32,
+    /// Center of the value line.
+    pub value_y: f32,
+    /// Font size of the value.
+    pub value_font_size: f32,
+    /// Center of the change line.
+    pub delta_y: f32,
+    /// Font size of the change.
+    pub delta_font_size: f32,
+    /// Sparkline area as `[x, y, width, height]`, if the card has one.
+    pub sparkline: Option<[f32; 4]>,
+}
+
+impl KpiLayout {
+    /// Space around and between lines.
+    pub const PADDING: f32 = 12.0;
+
+    /// Lay out a KPI card in a `width` × `height` element.
+    #[must_use]
+    pub fn new(width: f32, height: f32, has_sparkline: bool) -> Self {
+        let pad = Self::PADDING;
+        let spark_height = if has_sparkline {
+            (height * 0.25).clamp(12.0, 64.0)
+        } else {
+            0.0
+        };
+        let text_height = (height - 2.0 * pad - spark_height).max(0.0);
+        let label_font_size = (text_height * 0.16).clamp(10.0, 16.0);
+        let value_font_size = (text_height * 0.4).clamp(14.0, 48.0);
+        let delta_font_size = (text_height * 0.14).clamp(9.0, 14.0);
+        let label_y = pad + label_font_size / 2.0;
+        let value_y = label_y + label_font_size / 2.0 + value_font_size * 0.7;
+        let delta_y = value_y + value_font_size * 0.55 + delta_font_size * 0.7;
+        Self {
+            label_y,
+            label_font_size,
+            value_y,
+            value_font_size,
+            delta_y,
+            delta_font_size,
+            sparkline: has_sparkline.then(|| {
+                [
+                    pad,
+                    height - pad - spark_height,
+                    (width - 2.0 * pad).max(0.0),
+                    spark_height,
+                ]
+            }),
+        }
+    }
+}
+
+/// Check the content of a `Gauge` or `Kpi`; other kinds pass.
+///
+/// # Errors
+///
+/// Returns [`CanvasError::InvalidOperation`] if a value is not finite, a
+/// gauge's `min` is not below its `max`, there are more than
+/// [`MAX_THRESHOLDS`] thresholds or
+/// [`MAX_SPARKLINE_POINTS`](sparkline::MAX_SPARKLINE_POINTS) sparkline
+/// points, or the format asks for more than [`MAX_DECIMALS`] decimals.
+pub fn validate(kind: &ElementKind) -> CanvasResult<()> {
+    let invalid = |message: String| Err(CanvasError::InvalidOperation(message));
+    let (values, thresholds, format) = match kind {
+        ElementKind::Gauge {
+            value,
+            min,
+            max,
+            thresholds,
+            format,
+            ..
+        } => {
+            if !(min.is_finite() && max.is_finite() && min < max) {
+                return invalid(format!(
+                    "Gauge range must be finite with min < max, got {min}..{max}"
+                ));
+            }
+            (vec![*value], thresholds, format)
+        }
+        ElementKind::Kpi {
+            value,
+            previous,
+            sparkline,
+            thresholds,
+            format,
+            ..
+        } => {
+            sparkline::validate(sparkline)?;
+            (
+                [*value].into_iter().chain(*previous).collect(),
+                thresholds,
+                format,
+            )
+        }
+        _ => return Ok(()),
+    };
+    if values
+        .iter()
+        .chain(thresholds.iter().map(|t| &t.value))
+        .any(|v| !v.is_finite())
+    {
+        return invalid(format!("{} values must be finite", kind.type_name()));
+    }
+    if thresholds.len() > MAX_THRESHOLDS {
+        return invalid(format!(
+            "{} elements take up to {MAX_THRESHOLDS} thresholds, got {}",
+            kind.type_name(),
+            thresholds.len()
+        ));
+    }
+    if format.decimals > MAX_DECIMALS {
+        return invalid(format!(
+            "Number formats show up to {MAX_DECIMALS} decimals, got {}",
+            format.decimals
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sparkline::{sparkline_points, SparklineKind, MAX_SPARKLINE_POINTS};
+
+    #[test]
+    fn test_number_format() {
+        let plain = NumberFormat::default();
+        assert_eq!(plain.format(1_234_567.0), "1,234,567");
+        assert_eq!(plain.format(-0.2), "0");
+        assert_eq!(plain.format(f64::NAN), "–");
+
+        let money = NumberFormat {
+            decimals: 2,
+            prefix: "$".to_string(),
+            ..NumberFormat::default()
+        };
+        assert_eq!(money.format(1250.5), "$1,250.50");
+        assert_eq!(money.format(-3.0), "-$3.00");
+        assert_eq!(money.format_delta(12.0), "+$12.00");
+
+        let compact = NumberFormat {
+            compact: true,
+            suffix: " users".to_string(),
+            ..NumberFormat::default()
+        };
+        assert_eq!(compact.format(1_260_000.0), "1.3M users");
+        assert_eq!(compact.format(120_000.0), "120k users");
+        assert_eq!(compact.format(999_960.0), "1M users");
+        assert_eq!(compact.format(42.0), "42 users");
+        assert_eq!(compact.format(7.2e12), "7,200B users");
+    }
+
+    #[test]
+    fn test_thresholds_and_deltas() {
+        let amber = Color::from_rgb(0xff_a000);
+        let red = Color::from_rgb(0xe5_3935);
+        let thresholds = vec![
+            Threshold {
+                value: 90.0,
+                color: red.into(),
+            },
+            Threshold {
+                value: 70.0,
+                color: ThemeColor::variable("warn"),
+            },
+        ];
+        let theme = Theme::default().with_variable("warn", amber);
+        assert_eq!(threshold_color(50.0, &thresholds, &theme), VALUE_COLOR);
+        assert_eq!(threshold_color(70.0, &thresholds, &theme), amber);
+        assert_eq!(threshold_color(95.0, &thresholds, &theme), red);
+        // Undefined references use the default value color
+        assert_eq!(
+            threshold_color(75.0, &thresholds, &Theme::default()),
+            VALUE_COLOR
+        );
+
+        let format = NumberFormat {
+            compact: true,
+            ..NumberFormat::default()
+        };
+        let up = KpiDelta::new(1_200_000.0, 1_080_000.0, &format, true);
+        assert_eq!(up.trend, Trend::Up);
+        assert_eq!(up.text, "▲ +120k (+11.1%)");
+        assert_eq!(up.color, GOOD_COLOR);
+        // Falling costs are good news
+        let down = KpiDelta::new(80.0, 100.0, &NumberFormat::default(), false);
+        assert_eq!(down.text, "▼ -20 (-20.0%)");
+        assert_eq!(down.color, GOOD_COLOR);
+        assert_eq!(KpiDelta::new(5.0, 0.0, &format, true).text, "▲ +5");
+        assert_eq!(KpiDelta::new(5.0, 5.0, &format, true).color, NEUTRAL_COLOR);
+    }
+
+    #[test]
+    fn test_gauge_layout() {
+        assert!((gauge_fraction(75.0, 50.0, 150.0) - 0.25).abs() < f32::EPSILON);
+        assert!((gauge_fraction(500.0, 0.0, 100.0) - 1.0).abs() < f32::EPSILON);
+        assert!(gauge_fraction(1.0, 5.0, 5.0).abs() < f32::EPSILON);
+
+        let layout = GaugeLayout::new(GaugeShape::Radial, 240.0, 160.0);
+        let Dial::Radial { cx, cy, radius, .. } = layout.dial else {
+            panic!("expected a radial dial");
+        };
+        assert!((cx - 120.0).abs() < f32::EPSILON);
+        assert!(cy - radius > 0.0, "arc fits inside the element");
+        assert!(layout.label_y > cy);
+        // Halfway is straight up
+        let [_, (x, y)] = layout.dial.tick(0.5);
+        assert!((x - cx).abs() < 1e-3 && y < cy - radius);
+
+        let layout = GaugeLayout::new(GaugeShape::Linear, 280.0, 80.0);
+        let Dial::Linear { x, width, .. } = layout.dial else {
+            panic!("expected a linear dial");
+        };
+        assert!(layout.value_x > x + width);
+        let [(tick_x, _), _] = layout.dial.tick(0.5);
+        assert!((tick_x - (x + width / 2.0)).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_kpi_layout_and_sparkline() {
+        let layout = KpiLayout::new(240.0, 180.0, true);
+        assert!(layout.label_y < layout.value_y && layout.value_y < layout.delta_y);
+        let [x, y, width, height] = layout.sparkline.expect("sparkline area");
+        assert!(layout.delta_y < y && y + height <= 180.0);
+        assert!(KpiLayout::new(240.0, 140.0, false).sparkline.is_none());
+
+        let points = sparkline_points(&[1.0, 3.0, f32::NAN, 2.0], [x, y, width, height]);
+        assert_eq!(points.len(), 3);
+        assert_eq!(points[0], (x, y + height));
+        assert_eq!(points[1], (x + width / 3.0, y));
+        assert!((points[2].0 - (x + width)).abs() < 1e-3);
+        let flat = sparkline_points(&[4.0, 4.0], [0.0, 0.0, 10.0, 10.0]);
+        assert_eq!(flat, [(0.0, 5.0), (10.0, 5.0)]);
+    }
+
+    #[test]
+    fn test_validate() {
+        let gauge = |min: f64, max: f64| ElementKind::Gauge {
+            value: 50.0,
+            min,
+            max,
+            shape: GaugeShape::Radial,
+            label: String::new(),
+            thresholds: Vec::new(),
+            format: NumberFormat::default(),
+        };
+        assert!(validate(&gauge(0.0, 100.0)).is_ok());
+        assert!(validate(&gauge(100.0, 100.0)).is_err());
+        assert!(validate(&gauge(0.0, f64::INFINITY)).is_err());
+
+        let kpi = |previous: Option<f64>, points: usize, decimals: u8| ElementKind::Kpi {
+            label: "Revenue".to_string(),
+            value: 10.0,
+            previous,
+            sparkline: vec![1.0; points],
+            thresholds: Vec::new(),
+            format: NumberFormat {
+                decimals,
+                ..NumberFormat::default()
+            },
+            sparkline_kind: SparklineKind::Line,
+            higher_is_better: true,
+        };
+        assert!(validate(&kpi(Some(8.0), 10, 2)).is_ok());
+        assert!(validate(&kpi(Some(f64::NAN), 10, 2)).is_err());
+        assert!(validate(&kpi(None, MAX_SPARKLINE_POINTS + 1, 2)).is_err());
+        assert!(validate(&kpi(None, 0, MAX_DECIMALS + 1)).is_err());
+    }
+}
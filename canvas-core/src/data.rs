@@ -0,0 +1,939 @@
+//! # Datasets and Chart Bindings
+//!
+//! Named tables that live beside the scene's elements, so several charts
+//! can plot the same numbers and a data change is sent once instead of as
+//! every chart's full JSON.
+//!
+//! ```text
+//!  DataStore                      Chart { binding }
+//!  ┌ "sales" ──────────────┐      dataset: "sales"
+//!  │ region  month  revenue│ ──▶  query: filter → aggregate → sort → limit
+//!  │ EU      Jan    120    │              │
+//!  │ US      Jan    340    │              ▼
+//!  └───────────────────────┘      data.x_labels / data.series
+//! ```
+//!
+//! A chart's [`DataBinding`] names a [`Dataset`] and a [`DataQuery`]. The
+//! scene runs the query whenever the chart is added or the dataset changes
+//! and writes the result into the chart's `x_labels` and `series`, leaving
+//! its other options (title, axis labels, series colors) alone, so
+//! renderers draw bound charts like any other. Datasets change through
+//! [`DatasetDelta`]s, which [`DataStore::diff`] also produces for sync.
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::element::ElementKind;
+use crate::error::{CanvasError, CanvasResult};
+
+/// Most datasets in a scene.
+pub const MAX_DATASETS: usize = 64;
+
+/// Most columns in a dataset.
+pub const MAX_DATASET_COLUMNS: usize = 64;
+
+/// Most rows in a dataset.
+pub const MAX_DATASET_ROWS: usize = 10_000;
+
+/// Longest dataset ID or column name, in characters.
+pub const MAX_DATASET_NAME_LEN: usize = 64;
+
+/// A named table of rows.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+pub struct Dataset {
+    /// Unique name within the scene, e.g. `"sales"`.
+    pub id: String,
+    /// Column names, in row order.
+    pub columns: Vec<String>,
+    /// Rows, each with one value per column.
+    #[serde(default)]
+    pub rows: Vec<Vec<serde_json::Value>>,
+}
+
+impl Dataset {
+    /// Create a dataset.
+    #[must_use]
+    pub fn new(
+        id: impl Into<String>,
+        columns: Vec<String>,
+        rows: Vec<Vec<serde_json::Value>>,
+    ) -> Self {
+        Self {
+            id: id.into(),
+            columns,
+            rows,
+        }
+    }
+
+    /// Index of a column.
+    #[must_use]
+    pub fn column(&self, name: &str) -> Option<usize> {
+        self.columns.iter().position(|column| column == name)
+    }
+
+    /// Check names, sizes and row widths.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CanvasError::InvalidOperation`] for an empty or overlong ID
+    /// or column name, duplicate columns, more than [`MAX_DATASET_COLUMNS`]
+    /// columns or [`MAX_DATASET_ROWS`] rows, or a row whose width does not
+    /// match the columns.
+    pub fn validate(&self) -> CanvasResult<()> {
+        validate_name("Dataset ID", &self.id)?;
+        if self.columns.is_empty() || self.columns.len() > MAX_DATASET_COLUMNS {
+            return Err(CanvasError::InvalidOperation(format!(
+                "Dataset '{}' needs 1 to {MAX_DATASET_COLUMNS} columns, got {}",
+                self.id,
+                self.columns.len()
+            )));
+        }
+        for (i, column) in self.columns.iter().enumerate() {
+            validate_name("Column name", column)?;
+            if self.columns[..i].contains(column) {
+                return Err(CanvasError::InvalidOperation(format!(
+                    "Dataset '{}' has duplicate column '{column}'",
+                    self.id
+                )));
+            }
+        }
+        self.check_rows(&self.rows)?;
+        if self.rows.len() > MAX_DATASET_ROWS {
+            return Err(CanvasError::InvalidOperation(format!(
+                "Dataset '{}' has {} rows; at most {MAX_DATASET_ROWS} are allowed",
+                self.id,
+                self.rows.len()
+            )));
+        }
+        Ok(())
+    }
+
+    /// Check that every row has one value per column.
+    fn check_rows(&self, rows: &[Vec<serde_json::Value>]) -> CanvasResult<()> {
+        match rows.iter().position(|row| row.len() != self.columns.len()) {
+            Some(i) => Err(CanvasError::InvalidOperation(format!(
+                "Row {i} of dataset '{}' has {} values for {} columns",
+                self.id,
+                rows[i].len(),
+                self.columns.len()
+            ))),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Check a dataset ID or column name.
+fn validate_name(what: &str, name: &str) -> CanvasResult<()> {
+    if name.trim().is_empty() || name.chars().count() > MAX_DATASET_NAME_LEN {
+        return Err(CanvasError::InvalidOperation(format!(
+            "{what} must be 1 to {MAX_DATASET_NAME_LEN} characters: '{name}'"
+        )));
+    }
+    Ok(())
+}
+
+/// A change to one dataset.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum DatasetDelta {
+    /// Create a dataset or replace it whole.
+    Put {
+        /// The new dataset.
+        dataset: Dataset,
+    },
+    /// Add rows to the end of a dataset, e.g. from a live feed.
+    Append {
+        /// Dataset ID.
+        id: String,
+        /// Rows to add.
+        rows: Vec<Vec<serde_json::Value>>,
+        /// Drop the oldest rows beyond this many, for a sliding window.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        keep_last: Option<usize>,
+    },
+    /// Delete a dataset.
+    Remove {
+        /// Dataset ID.
+        id: String,
+    },
+}
+
+impl DatasetDelta {
+    /// ID of the dataset this delta changes.
+    #[must_use]
+    pub fn dataset_id(&self) -> &str {
+        match self {
+            Self::Put { dataset } => &dataset.id,
+            Self::Append { id, .. } | Self::Remove { id } => id,
+        }
+    }
+}
+
+/// The scene's datasets, in ID order.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct DataStore {
+    datasets: Vec<Dataset>,
+}
+
+impl DataStore {
+    /// Check whether there are no datasets.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.datasets.is_empty()
+    }
+
+    /// Number of datasets.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.datasets.len()
+    }
+
+    /// Datasets in ID order.
+    pub fn iter(&self) -> impl Iterator<Item = &Dataset> {
+        self.datasets.iter()
+    }
+
+    /// Look up a dataset.
+    #[must_use]
+    pub fn get(&self, id: &str) -> Option<&Dataset> {
+        self.find(id).ok().map(|i| &self.datasets[i])
+    }
+
+    fn find(&self, id: &str) -> Result<usize, usize> {
+        self.datasets
+            .iter()
+            .position(|dataset| dataset.id == id)
+            .ok_or_else(|| {
+                self.datasets
+                    .partition_point(|dataset| dataset.id.as_str() < id)
+            })
+    }
+
+    /// Apply a change. Removing a missing dataset does nothing.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CanvasError::InvalidOperation`] if the result would be an
+    /// invalid dataset (see [`Dataset::validate`]), a new dataset would
+    /// exceed [`MAX_DATASETS`], or rows are appended to a missing dataset.
+    /// The store is unchanged on error.
+    pub fn apply(&mut self, delta: &DatasetDelta) -> CanvasResult<()> {
+        match delta {
+            DatasetDelta::Put { dataset } => {
+                dataset.validate()?;
+                match self.find(&dataset.id) {
+                    Ok(i) => self.datasets[i] = dataset.clone(),
+                    Err(_) if self.datasets.len() >= MAX_DATASETS => {
+                        return Err(CanvasError::InvalidOperation(format!(
+                            "A scene holds at most {MAX_DATASETS} datasets"
+                        )));
+                    }
+                    Err(i) => self.datasets.insert(i, dataset.clone()),
+                }
+            }
+            DatasetDelta::Append {
+                id,
+                rows,
+                keep_last,
+            } => {
+                let Ok(i) = self.find(id) else {
+                    return Err(CanvasError::InvalidOperation(format!(
+                        "Dataset not found: {id}"
+                    )));
+                };
+                let dataset = &mut self.datasets[i];
+                dataset.check_rows(rows)?;
+                let total = dataset.rows.len() + rows.len();
+                let kept = keep_last.map_or(total, |keep| total.min(keep));
+                if kept > MAX_DATASET_ROWS {
+                    return Err(CanvasError::InvalidOperation(format!(
+                        "Dataset '{id}' would have {kept} rows; at most {MAX_DATASET_ROWS} are allowed (use keep_last)"
+                    )));
+                }
+                dataset.rows.extend(rows.iter().cloned());
+                dataset.rows.drain(..total - kept);
+            }
+            DatasetDelta::Remove { id } => {
+                if let Ok(i) = self.find(id) {
+                    self.datasets.remove(i);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Deltas that turn this store into `other`.
+    ///
+    /// Rows added at the end, optionally with the oldest ones dropped,
+    /// become an `Append`; other changes to a dataset `Put` it whole.
+    #[must_use]
+    pub fn diff(&self, other: &DataStore) -> Vec<DatasetDelta> {
+        let mut deltas: Vec<DatasetDelta> = self
+            .datasets
+            .iter()
+            .filter(|dataset| other.get(&dataset.id).is_none())
+            .map(|dataset| DatasetDelta::Remove {
+                id: dataset.id.clone(),
+            })
+            .collect();
+        for dataset in &other.datasets {
+            let append = match self.get(&dataset.id) {
+                Some(previous) if previous == dataset => continue,
+                Some(previous) if previous.columns == dataset.columns => {
+                    appended_rows(&previous.rows, &dataset.rows)
+                }
+                _ => None,
+            };
+            deltas.push(match append {
+                Some((dropped, added)) => DatasetDelta::Append {
+                    id: dataset.id.clone(),
+                    rows: added.to_vec(),
+                    keep_last: (dropped > 0).then_some(dataset.rows.len()),
+                },
+                None => DatasetDelta::Put {
+                    dataset: dataset.clone(),
+                },
+            });
+        }
+        deltas
+    }
+}
+
+/// If `after` is `before` with its first `dropped` rows removed and rows
+/// added at the end, the number dropped and the rows added.
+///
+/// Returns `None` when no row of `before` survives, since sending the rows
+/// whole is then no larger.
+fn appended_rows<'a>(
+    before: &[Vec<serde_json::Value>],
+    after: &'a [Vec<serde_json::Value>],
+) -> Option<(usize, &'a [Vec<serde_json::Value>])> {
+    if before.is_empty() {
+        return Some((0, after));
+    }
+    (0..before.len())
+        .find(|&dropped| {
+            let kept = &before[dropped..];
+            kept.len() <= after.len() && after[..kept.len()] == *kept
+        })
+        .map(|dropped| (dropped, &after[before.len() - dropped..]))
+}
+
+/// A chart's link to a dataset.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+pub struct DataBinding {
+    /// Dataset ID.
+    pub dataset: String,
+    /// Which rows and columns to plot.
+    #[serde(default)]
+    pub query: DataQuery,
+}
+
+impl DataBinding {
+    /// Bind to every row of `dataset`.
+    #[must_use]
+    pub fn new(dataset: impl Into<String>) -> Self {
+        Self {
+            dataset: dataset.into(),
+            query: DataQuery::default(),
+        }
+    }
+
+    /// Set the query.
+    #[must_use]
+    pub fn with_query(mut self, query: DataQuery) -> Self {
+        self.query = query;
+        self
+    }
+
+    /// Check the dataset ID and query limits, without looking the dataset up.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CanvasError::InvalidOperation`] for an invalid dataset ID
+    /// or a zero `limit`.
+    pub fn validate(&self) -> CanvasResult<()> {
+        validate_name("Dataset ID", &self.dataset)?;
+        if self.query.limit == Some(0) {
+            return Err(CanvasError::InvalidOperation(
+                "Query limit must be positive".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Rows and columns of a dataset to plot, applied as filter, aggregate,
+/// sort, then limit.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+pub struct DataQuery {
+    /// Column of category labels, or of x values for scatter charts.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub x: Option<String>,
+    /// Columns plotted as series; empty plots every numeric column other
+    /// than `x`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub y: Vec<String>,
+    /// Conditions every plotted row meets.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub filter: Vec<DataFilter>,
+    /// Combine rows with the same `x` value.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub aggregate: Option<Aggregate>,
+    /// Row order; dataset order if unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sort: Option<DataSort>,
+    /// Most rows plotted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<usize>,
+}
+
+/// A condition on one column.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+pub struct DataFilter {
+    /// Column name.
+    pub column: String,
+    /// Comparison.
+    #[serde(default)]
+    pub op: FilterOp,
+    /// Value compared against; numbers compare numerically and strings
+    /// lexically.
+    pub value: serde_json::Value,
+}
+
+/// Comparison in a [`DataFilter`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+#[serde(rename_all = "lowercase")]
+pub enum FilterOp {
+    /// Equal.
+    #[default]
+    Eq,
+    /// Not equal.
+    Ne,
+    /// Less than.
+    Lt,
+    /// Less than or equal.
+    Le,
+    /// Greater than.
+    Gt,
+    /// Greater than or equal.
+    Ge,
+}
+
+/// How rows sharing an `x` value are combined.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+#[serde(rename_all = "lowercase")]
+pub enum Aggregate {
+    /// Sum of the values.
+    Sum,
+    /// Mean of the values.
+    Mean,
+    /// Smallest value.
+    Min,
+    /// Largest value.
+    Max,
+    /// Number of rows.
+    Count,
+}
+
+/// Row order of a query result.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+pub struct DataSort {
+    /// The `x` column or one of the `y` columns.
+    pub column: String,
+    /// Largest first.
+    #[serde(default)]
+    pub descending: bool,
+}
+
+/// The rows a [`DataQuery`] selects, ready to plot.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryResult {
+    /// Category label of each row (empty without an `x` column).
+    pub labels: Vec<String>,
+    /// X value of each row: the `x` column where numeric, else its index.
+    pub xs: Vec<f64>,
+    /// Each plotted column's name and per-row values; `None` where a row
+    /// holds no number.
+    pub series: Vec<(String, Vec<Option<f64>>)>,
+}
+
+/// One row on its way through a query: its `x` value and plotted values.
+struct QueryRow<'a> {
+    x: Option<&'a serde_json::Value>,
+    values: Vec<Option<f64>>,
+}
+
+impl DataQuery {
+    /// Run the query against `dataset`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CanvasError::InvalidOperation`] for a column the dataset
+    /// does not have, or a sort column that is neither `x` nor plotted.
+    pub fn run(&self, dataset: &Dataset) -> CanvasResult<QueryResult> {
+        let column = |name: &str| {
+            dataset.column(name).ok_or_else(|| {
+                CanvasError::InvalidOperation(format!(
+                    "Unknown column '{name}' in dataset '{}'",
+                    dataset.id
+                ))
+            })
+        };
+        let x = self.x.as_deref().map(column).transpose()?;
+        let y: Vec<usize> = if self.y.is_empty() {
+            (0..dataset.columns.len())
+                .filter(|&i| Some(i) != x && dataset.rows.iter().any(|row| row[i].is_number()))
+                .collect()
+        } else {
+            self.y
+                .iter()
+                .map(|name| column(name))
+                .collect::<CanvasResult<_>>()?
+        };
+        let filters = self
+            .filter
+            .iter()
+            .map(|filter| Ok((column(&filter.column)?, filter)))
+            .collect::<CanvasResult<Vec<_>>>()?;
+
+        let mut rows: Vec<QueryRow<'_>> = dataset
+            .rows
+            .iter()
+            .filter(|row| filters.iter().all(|(i, filter)| filter.matches(&row[*i])))
+            .map(|row| QueryRow {
+                x: x.map(|i| &row[i]),
+                values: y.iter().map(|&i| row[i].as_f64()).collect(),
+            })
+            .collect();
+        if let Some(aggregate) = self.aggregate {
+            rows = aggregate.apply(rows, y.len());
+        }
+        if let Some(sort) = &self.sort {
+            self.sort_rows(&mut rows, sort, &dataset.columns, &y)?;
+        }
+        if let Some(limit) = self.limit {
+            rows.truncate(limit);
+        }
+
+        #[allow(clippy::cast_precision_loss)]
+        let xs = rows
+            .iter()
+            .enumerate()
+            .map(|(i, row)| {
+                row.x
+                    .and_then(serde_json::Value::as_f64)
+                    .unwrap_or(i as f64)
+            })
+            .collect();
+        Ok(QueryResult {
+            labels: rows
+                .iter()
+                .map(|row| row.x.map(label).unwrap_or_default())
+                .collect(),
+            xs,
+            series: y
+                .iter()
+                .enumerate()
+                .map(|(s, &i)| {
+                    (
+                        dataset.columns[i].clone(),
+                        rows.iter().map(|row| row.values[s]).collect(),
+                    )
+                })
+                .collect(),
+        })
+    }
+
+    fn sort_rows(
+        &self,
+        rows: &mut [QueryRow<'_>],
+        sort: &DataSort,
+        columns: &[String],
+        y: &[usize],
+    ) -> CanvasResult<()> {
+        let key = if self.x.as_deref() == Some(sort.column.as_str()) {
+            None
+        } else if let Some(s) = y.iter().position(|&i| columns[i] == sort.column) {
+            Some(s)
+        } else {
+            return Err(CanvasError::InvalidOperation(format!(
+                "Sort column '{}' must be the x column or a plotted column",
+                sort.column
+            )));
+        };
+        // Stable sort; rows without a value go last either way
+        rows.sort_by(|a, b| {
+            let order = match key {
+                None => compare_values(a.x, b.x),
+                Some(s) => compare_numbers(a.values[s], b.values[s]),
+            };
+            match order {
+                Some(order) if sort.descending => order.reverse(),
+                Some(order) => order,
+                None => missing_last(a, b, key),
+            }
+        });
+        Ok(())
+    }
+}
+
+/// Order rows where at least one lacks a comparable sort value.
+fn missing_last(a: &QueryRow<'_>, b: &QueryRow<'_>, key: Option<usize>) -> Ordering {
+    let has = |row: &QueryRow<'_>| match key {
+        None => row.x.is_some_and(|x| x.is_number() || x.is_string()),
+        Some(s) => row.values[s].is_some(),
+    };
+    has(b).cmp(&has(a))
+}
+
+impl DataFilter {
+    /// Check whether a cell meets this condition.
+    #[must_use]
+    pub fn matches(&self, cell: &serde_json::Value) -> bool {
+        let order = compare_values(Some(cell), Some(&self.value));
+        match self.op {
+            FilterOp::Eq => order == Some(Ordering::Equal) || *cell == self.value,
+            FilterOp::Ne => order != Some(Ordering::Equal) && *cell != self.value,
+            FilterOp::Lt => order == Some(Ordering::Less),
+            FilterOp::Le => matches!(order, Some(Ordering::Less | Ordering::Equal)),
+            FilterOp::Gt => order == Some(Ordering::Greater),
+            FilterOp::Ge => matches!(order, Some(Ordering::Greater | Ordering::Equal)),
+        }
+    }
+}
+
+impl Aggregate {
+    /// Combine rows sharing an `x` value, in order of first appearance.
+    fn apply(self, rows: Vec<QueryRow<'_>>, width: usize) -> Vec<QueryRow<'_>> {
+        let mut xs = Vec::new();
+        let mut groups: Vec<Vec<Vec<Option<f64>>>> = Vec::new();
+        let mut index: HashMap<String, usize> = HashMap::new();
+        for row in rows {
+            let key = row.x.map(ToString::to_string).unwrap_or_default();
+            let group = *index.entry(key).or_insert_with(|| {
+                xs.push(row.x);
+                groups.push(Vec::new());
+                groups.len() - 1
+            });
+            groups[group].push(row.values);
+        }
+        xs.into_iter()
+            .zip(groups)
+            .map(|(x, members)| QueryRow {
+                x,
+                values: (0..width).map(|s| self.combine(&members, s)).collect(),
+            })
+            .collect()
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn combine(self, members: &[Vec<Option<f64>>], s: usize) -> Option<f64> {
+        let values = members.iter().filter_map(|values| values[s]);
+        match self {
+            Self::Count => Some(members.len() as f64),
+            Self::Sum => Some(values.sum()),
+            Self::Mean => {
+                let (sum, count) = values.fold((0.0, 0_usize), |(sum, n), v| (sum + v, n + 1));
+                (count > 0).then(|| sum / count as f64)
+            }
+            Self::Min => values.reduce(f64::min),
+            Self::Max => values.reduce(f64::max),
+        }
+    }
+}
+
+/// Compare two cells: numbers numerically, strings lexically, anything
+/// else not at all.
+fn compare_values(
+    a: Option<&serde_json::Value>,
+    b: Option<&serde_json::Value>,
+) -> Option<Ordering> {
+    match (a?, b?) {
+        (serde_json::Value::String(a), serde_json::Value::String(b)) => Some(a.cmp(b)),
+        (a, b) => compare_numbers(a.as_f64(), b.as_f64()),
+    }
+}
+
+fn compare_numbers(a: Option<f64>, b: Option<f64>) -> Option<Ordering> {
+    a?.partial_cmp(&b?)
+}
+
+/// Category label of an `x` cell.
+fn label(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(text) => text.clone(),
+        serde_json::Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+/// Fill in a bound chart's `x_labels` and `series` from the store.
+///
+/// Series keep the colors of same-named series already in the chart.
+/// Charts bound to a missing dataset, or whose query fails, get no series.
+/// Returns `false` for kinds other than a bound chart.
+pub fn materialize(kind: &mut ElementKind, store: &DataStore) -> bool {
+    let ElementKind::Chart {
+        data,
+        binding: Some(binding),
+        ..
+    } = kind
+    else {
+        return false;
+    };
+    let result = store
+        .get(&binding.dataset)
+        .and_then(|dataset| binding.query.run(dataset).ok());
+    if !data.is_object() {
+        *data = serde_json::json!({});
+    }
+    let colors: HashMap<String, serde_json::Value> = data
+        .get("series")
+        .and_then(serde_json::Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|series| {
+            Some((
+                series.get("name")?.as_str()?.to_string(),
+                series.get("color")?.clone(),
+            ))
+        })
+        .collect();
+
+    let (labels, series) = result.map_or_else(
+        || (Vec::new(), Vec::new()),
+        |result| {
+            let series = result
+                .series
+                .iter()
+                .map(|(name, values)| {
+                    let points: Vec<serde_json::Value> = values
+                        .iter()
+                        .enumerate()
+                        .filter_map(|(i, value)| {
+                            Some(serde_json::json!({
+                                "x": result.xs[i],
+                                "y": (*value)?,
+                                "label": result.labels[i],
+                            }))
+                        })
+                        .collect();
+                    let mut series = serde_json::json!({ "name": name, "points": points });
+                    if let Some(color) = colors.get(name) {
+                        series["color"] = color.clone();
+                    }
+                    series
+                })
+                .collect();
+            (result.labels, series)
+        },
+    );
+    data["x_labels"] = serde_json::json!(labels);
+    data["series"] = serde_json::Value::Array(series);
+    true
+}
+
+/// A copy of a chart's `data` without the values [`materialize`] writes,
+/// or `None` for kinds other than a bound chart.
+#[must_use]
+pub fn unbound_data(kind: &ElementKind) -> Option<serde_json::Value> {
+    let ElementKind::Chart {
+        data,
+        binding: Some(_),
+        ..
+    } = kind
+    else {
+        return None;
+    };
+    let mut data = data.clone();
+    if let Some(fields) = data.as_object_mut() {
+        fields.remove("x_labels");
+        fields.remove("series");
+    }
+    Some(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn sales() -> Dataset {
+        Dataset::new(
+            "sales",
+            vec![
+                "region".into(),
+                "month".into(),
+                "revenue".into(),
+                "units".into(),
+            ],
+            vec![
+                vec![json!("EU"), json!("Jan"), json!(120), json!(3)],
+                vec![json!("US"), json!("Jan"), json!(340), json!(7)],
+                vec![json!("EU"), json!("Feb"), json!(90), json!(2)],
+                vec![json!("US"), json!("Feb"), json!(null), json!(5)],
+            ],
+        )
+    }
+
+    #[test]
+    fn test_query_filter_aggregate_sort_limit() {
+        let dataset = sales();
+        let all = DataQuery {
+            x: Some("month".into()),
+            ..DataQuery::default()
+        }
+        .run(&dataset)
+        .expect("query");
+        assert_eq!(all.labels, ["Jan", "Jan", "Feb", "Feb"]);
+        // Every numeric column other than x, with gaps for missing numbers
+        assert_eq!(all.series.len(), 2);
+        assert_eq!(all.series[0].0, "revenue");
+        assert_eq!(
+            all.series[0].1,
+            [Some(120.0), Some(340.0), Some(90.0), None]
+        );
+
+        let totals = DataQuery {
+            x: Some("region".into()),
+            y: vec!["revenue".into()],
+            aggregate: Some(Aggregate::Sum),
+            sort: Some(DataSort {
+                column: "revenue".into(),
+                descending: true,
+            }),
+            limit: Some(1),
+            ..DataQuery::default()
+        }
+        .run(&dataset)
+        .expect("query");
+        assert_eq!(totals.labels, ["US"]);
+        assert_eq!(totals.series[0].1, [Some(340.0)]);
+
+        let filtered = DataQuery {
+            x: Some("month".into()),
+            y: vec!["units".into()],
+            filter: vec![
+                DataFilter {
+                    column: "region".into(),
+                    op: FilterOp::Eq,
+                    value: json!("EU"),
+                },
+                DataFilter {
+                    column: "units".into(),
+                    op: FilterOp::Ge,
+                    value: json!(3),
+                },
+            ],
+            ..DataQuery::default()
+        }
+        .run(&dataset)
+        .expect("query");
+        assert_eq!(filtered.labels, ["Jan"]);
+        assert_eq!(filtered.xs, [0.0]);
+
+        let unknown = DataQuery {
+            y: vec!["profit".into()],
+            ..DataQuery::default()
+        };
+        assert!(unknown.run(&dataset).is_err());
+    }
+
+    #[test]
+    fn test_store_apply_and_diff() {
+        let mut store = DataStore::default();
+        store
+            .apply(&DatasetDelta::Put { dataset: sales() })
+            .expect("put");
+        let before = store.clone();
+
+        // A sliding window diffs to a small append
+        let append = DatasetDelta::Append {
+            id: "sales".into(),
+            rows: vec![vec![json!("EU"), json!("Mar"), json!(150), json!(4)]],
+            keep_last: Some(4),
+        };
+        store.apply(&append).expect("append");
+        let rows = &store.get("sales").expect("dataset").rows;
+        assert_eq!(rows.len(), 4);
+        assert_eq!(rows[0][2], json!(340));
+        assert_eq!(before.diff(&store), [append]);
+
+        // Rows must match the columns, and the store is unchanged on error
+        let short = DatasetDelta::Append {
+            id: "sales".into(),
+            rows: vec![vec![json!("EU")]],
+            keep_last: None,
+        };
+        assert!(store.apply(&short).is_err());
+        assert_eq!(store.get("sales").expect("dataset").rows.len(), 4);
+        assert!(store
+            .apply(&DatasetDelta::Append {
+                id: "missing".into(),
+                rows: Vec::new(),
+                keep_last: None,
+            })
+            .is_err());
+
+        // Edits other than appends replace the dataset; deletions remove it
+        let mut edited = store.clone();
+        edited.datasets[0].rows[0][2] = json!(1);
+        assert!(matches!(
+            store.diff(&edited).as_slice(),
+            [DatasetDelta::Put { .. }]
+        ));
+        assert_eq!(
+            store.diff(&DataStore::default()),
+            [DatasetDelta::Remove { id: "sales".into() }]
+        );
+    }
+
+    #[test]
+    fn test_materialize_keeps_chart_options() {
+        let mut store = DataStore::default();
+        store
+            .apply(&DatasetDelta::Put { dataset: sales() })
+            .expect("put");
+        let mut kind = ElementKind::Chart {
+            chart_type: "bar".into(),
+            data: json!({
+                "title": "Revenue",
+                "series": [{ "name": "revenue", "color": "#ff0000", "points": [] }]
+            }),
+            binding: Some(DataBinding::new("sales").with_query(DataQuery {
+                x: Some("region".into()),
+                y: vec!["revenue".into()],
+                aggregate: Some(Aggregate::Sum),
+                ..DataQuery::default()
+            })),
+        };
+        assert!(materialize(&mut kind, &store));
+        let ElementKind::Chart { data, .. } = &kind else {
+            unreachable!()
+        };
+        assert_eq!(data["title"], "Revenue");
+        assert_eq!(data["x_labels"], json!(["EU", "US"]));
+        assert_eq!(data["series"][0]["color"], "#ff0000");
+        assert_eq!(data["series"][0]["points"][1]["y"], json!(340.0));
+        assert_eq!(unbound_data(&kind), Some(json!({ "title": "Revenue" })));
+
+        // A missing dataset plots nothing
+        assert!(materialize(&mut kind, &DataStore::default()));
+        let ElementKind::Chart { data, .. } = &kind else {
+            unreachable!()
+        };
+        assert_eq!(data["series"], json!([]));
+    }
+}
@@ -0,0 +1,492 @@
+//! # Scene Diagnostics
+//!
+//! Integrity checks and statistics for debugging agent-generated scenes.
+//!
+//! [`Scene::validate`](crate::Scene::validate) reports structural problems
+//! that renderers silently tolerate (a group listing a child that was never
+//! added, a transform full of `NaN`), and
+//! [`Scene::stats`](crate::Scene::stats) summarizes what a scene contains.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::camera::SceneBounds;
+use crate::{Element, ElementId, ElementKind, Transform};
+
+/// How serious a [`SceneIssue`] is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IssueSeverity {
+    /// The scene is inconsistent; rendering or hit testing may misbehave.
+    Error,
+    /// Probably unintended, but harmless.
+    Warning,
+}
+
+/// What is wrong with an element.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "issue", rename_all = "snake_case")]
+pub enum IssueKind {
+    /// A container lists a child that is not in the scene.
+    OrphanChild {
+        /// The missing child.
+        child: ElementId,
+    },
+    /// The element's parent is not in the scene.
+    MissingParent {
+        /// The missing parent.
+        parent: ElementId,
+    },
+    /// The element's parent does not list it as a child.
+    ParentMismatch {
+        /// The parent that does not list the element.
+        parent: ElementId,
+    },
+    /// More than one container lists the element as a child.
+    SharedChild {
+        /// Every container listing the element.
+        containers: Vec<ElementId>,
+    },
+    /// Following parent links from the element leads back to it.
+    ParentCycle,
+    /// More than one element claims this ID.
+    DuplicateId {
+        /// Number of elements with the ID.
+        count: usize,
+    },
+    /// A transform field is `NaN` or infinite.
+    InvalidTransform {
+        /// The offending field.
+        field: &'static str,
+    },
+    /// The element has zero or negative width or height.
+    ZeroSize,
+}
+
+impl IssueKind {
+    /// Severity of this kind of issue.
+    #[must_use]
+    pub fn severity(&self) -> IssueSeverity {
+        match self {
+            Self::ZeroSize => IssueSeverity::Warning,
+            _ => IssueSeverity::Error,
+        }
+    }
+}
+
+/// A problem found by [`Scene::validate`](crate::Scene::validate).
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SceneIssue {
+    /// How serious the issue is.
+    pub severity: IssueSeverity,
+    /// The element the issue concerns.
+    pub element_id: ElementId,
+    /// What is wrong.
+    #[serde(flatten)]
+    pub kind: IssueKind,
+}
+
+impl SceneIssue {
+    /// Create an issue, taking the severity from its kind.
+    #[must_use]
+    pub fn new(element_id: ElementId, kind: IssueKind) -> Self {
+        Self {
+            severity: kind.severity(),
+            element_id,
+            kind,
+        }
+    }
+}
+
+impl fmt::Display for SceneIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let id = self.element_id;
+        match &self.kind {
+            IssueKind::OrphanChild { child } => {
+                write!(f, "{id} lists child {child}, which is not in the scene")
+            }
+            IssueKind::MissingParent { parent } => {
+                write!(f, "{id} has parent {parent}, which is not in the scene")
+            }
+            IssueKind::ParentMismatch { parent } => {
+                write!(f, "{id} has parent {parent}, which does not list it")
+            }
+            IssueKind::SharedChild { containers } => {
+                write!(f, "{id} is a child of {} containers", containers.len())
+            }
+            IssueKind::ParentCycle => write!(f, "{id} is its own ancestor"),
+            IssueKind::DuplicateId { count } => write!(f, "{count} elements share ID {id}"),
+            IssueKind::InvalidTransform { field } => {
+                write!(f, "{id} has a non-finite transform {field}")
+            }
+            IssueKind::ZeroSize => write!(f, "{id} has zero width or height"),
+        }
+    }
+}
+
+/// Summary of a scene's contents from [`Scene::stats`](crate::Scene::stats).
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SceneStats {
+    /// Number of elements.
+    pub element_count: usize,
+    /// Number of top-level elements.
+    pub root_count: usize,
+    /// Number of selected elements.
+    pub selected_count: usize,
+    /// Element count per kind, keyed by the kind's `type` tag.
+    pub by_kind: BTreeMap<&'static str, usize>,
+    /// Bounding box of every element, if the scene is not empty.
+    pub bounds: Option<SceneBounds>,
+    /// Deepest container nesting (0 when no element has a parent).
+    pub max_depth: usize,
+    /// Rough heap and inline size of the elements, in bytes.
+    pub memory_estimate: usize,
+}
+
+/// Check a scene's elements for integrity issues.
+///
+/// Issues are ordered by element ID, errors first, so reports are stable.
+pub(crate) fn validate(elements: &HashMap<ElementId, Element>) -> Vec<SceneIssue> {
+    let mut issues = Vec::new();
+
+    // Which containers list each child
+    let mut listed_by: HashMap<ElementId, Vec<ElementId>> = HashMap::new();
+    let mut claims: HashMap<ElementId, usize> = HashMap::new();
+    // Elements are keyed by ID, so duplicates only come from documents
+    // whose keys disagree with the elements' own IDs
+    for element in elements.values() {
+        *claims.entry(element.id).or_default() += 1;
+        for &child in element.kind.children() {
+            if elements.contains_key(&child) {
+                listed_by.entry(child).or_default().push(element.id);
+            } else {
+                issues.push(SceneIssue::new(
+                    element.id,
+                    IssueKind::OrphanChild { child },
+                ));
+            }
+        }
+    }
+
+    for (&id, &count) in &claims {
+        if count > 1 {
+            issues.push(SceneIssue::new(id, IssueKind::DuplicateId { count }));
+        }
+    }
+
+    for (&child, containers) in &listed_by {
+        if containers.len() > 1 {
+            let mut containers = containers.clone();
+            containers.sort_by_key(ToString::to_string);
+            issues.push(SceneIssue::new(
+                child,
+                IssueKind::SharedChild { containers },
+            ));
+        }
+    }
+
+    for element in elements.values() {
+        let id = element.id;
+        match element.parent {
+            Some(parent) if !elements.contains_key(&parent) => {
+                issues.push(SceneIssue::new(id, IssueKind::MissingParent { parent }));
+            }
+            Some(parent)
+                if !listed_by
+                    .get(&id)
+                    .is_some_and(|containers| containers.contains(&parent)) =>
+            {
+                issues.push(SceneIssue::new(id, IssueKind::ParentMismatch { parent }));
+            }
+            Some(_) if in_cycle(elements, id) => {
+                issues.push(SceneIssue::new(id, IssueKind::ParentCycle));
+            }
+            _ => {}
+        }
+
+        if let Some(field) = non_finite_field(&element.transform) {
+            issues.push(SceneIssue::new(id, IssueKind::InvalidTransform { field }));
+        } else if !matches!(
+            element.kind,
+            ElementKind::Group { .. } | ElementKind::OverlayLayer { .. }
+        ) && (element.transform.width <= 0.0 || element.transform.height <= 0.0)
+        {
+            issues.push(SceneIssue::new(id, IssueKind::ZeroSize));
+        }
+    }
+
+    issues.sort_by_key(|issue| (issue.element_id.to_string(), issue.severity));
+    issues
+}
+
+/// Summarize a scene's elements.
+pub(crate) fn stats(
+    elements: &HashMap<ElementId, Element>,
+    root_count: usize,
+    selected_count: usize,
+) -> SceneStats {
+    let mut by_kind = BTreeMap::new();
+    let mut memory_estimate = 0;
+    let mut max_depth = 0;
+    for element in elements.values() {
+        *by_kind.entry(element.kind.type_name()).or_default() += 1;
+        memory_estimate += element_size(element);
+        max_depth = max_depth.max(depth(elements, element));
+    }
+
+    SceneStats {
+        element_count: elements.len(),
+        root_count,
+        selected_count,
+        by_kind,
+        bounds: elements
+            .values()
+            .map(|e| SceneBounds::from_transform(&e.transform))
+            .reduce(|a, b| a.union(&b)),
+        max_depth,
+        memory_estimate,
+    }
+}
+
+/// Name of the first non-finite field of a transform.
+fn non_finite_field(transform: &Transform) -> Option<&'static str> {
+    [
+        ("x", transform.x),
+        ("y", transform.y),
+        ("width", transform.width),
+        ("height", transform.height),
+        ("rotation", transform.rotation),
+    ]
+    .into_iter()
+    .find(|(_, value)| !value.is_finite())
+    .map(|(field, _)| field)
+}
+
+/// Check whether following parent links from `id` returns to it.
+fn in_cycle(elements: &HashMap<ElementId, Element>, id: ElementId) -> bool {
+    let mut seen = HashSet::new();
+    let mut current = elements.get(&id).and_then(|e| e.parent);
+    while let Some(parent) = current {
+        if parent == id {
+            return true;
+        }
+        if !seen.insert(parent) {
+            // A cycle further up that does not include `id`
+            return false;
+        }
+        current = elements.get(&parent).and_then(|e| e.parent);
+    }
+    false
+}
+
+/// Number of ancestors of an element, stopping at cycles.
+fn depth(elements: &HashMap<ElementId, Element>, element: &Element) -> usize {
+    let mut seen = HashSet::new();
+    let mut current = element.parent;
+    while let Some(parent) = current {
+        if !seen.insert(parent) {
+            break;
+        }
+        current = elements.get(&parent).and_then(|e| e.parent);
+    }
+    seen.len()
+}
+
+/// Approximate memory used by an element: its inline size plus its
+/// serialized content as a stand-in for heap data.
+fn element_size(element: &Element) -> usize {
+    let heap = serde_json::to_vec(&element.kind).map_or(0, |json| json.len())
+        + element.created_by.as_ref().map_or(0, String::len)
+        + element.last_modified_by.as_ref().map_or(0, String::len);
+    std::mem::size_of::<(ElementId, Element)>() + heap
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Color, Scene};
+
+    fn text(x: f32, y: f32) -> Element {
+        Element::new(ElementKind::Text {
+            content: "Label".to_string(),
+            font_size: 16.0,
+            color: Color::BLACK.into(),
+        })
+        .with_transform(Transform {
+            x,
+            y,
+            width: 100.0,
+            height: 20.0,
+            rotation: 0.0,
+            z_index: 0,
+            auto_size: false,
+        })
+    }
+
+    fn group(children: Vec<ElementId>) -> Element {
+        Element::new(ElementKind::Group {
+            children,
+            clip: false,
+            opacity: 1.0,
+        })
+    }
+
+    fn kinds(scene: &Scene, id: ElementId) -> Vec<IssueKind> {
+        scene
+            .validate()
+            .into_iter()
+            .filter(|issue| issue.element_id == id)
+            .map(|issue| issue.kind)
+            .collect()
+    }
+
+    #[test]
+    fn test_consistent_scene_is_valid() {
+        let mut scene = Scene::new(800.0, 600.0);
+        let layer = scene.add_element(Element::new(ElementKind::OverlayLayer {
+            children: Vec::new(),
+            opacity: 1.0,
+            clip: false,
+        }));
+        let label = scene.add_element(text(10.0, 10.0));
+        scene.reparent(label, layer).expect("reparent");
+        assert_eq!(scene.validate(), Vec::new());
+    }
+
+    #[test]
+    fn test_structural_issues() {
+        let mut scene = Scene::new(800.0, 600.0);
+        let missing = ElementId::new();
+        let orphaning = scene.add_element(group(vec![missing]));
+
+        let shared = text(0.0, 0.0);
+        let shared_id = shared.id;
+        scene.add_element(shared);
+        let first = scene.add_element(group(vec![shared_id]));
+        let second = scene.add_element(group(vec![shared_id]));
+
+        let mut stray = text(0.0, 0.0);
+        stray.parent = Some(missing);
+        let stray = scene.add_element(stray);
+
+        let mut unlisted = text(0.0, 0.0);
+        unlisted.parent = Some(first);
+        let unlisted = scene.add_element(unlisted);
+
+        assert_eq!(
+            kinds(&scene, orphaning),
+            vec![IssueKind::OrphanChild { child: missing }]
+        );
+        let mut containers = vec![first, second];
+        containers.sort_by_key(ToString::to_string);
+        assert_eq!(
+            kinds(&scene, shared_id),
+            vec![IssueKind::SharedChild { containers }]
+        );
+        assert_eq!(
+            kinds(&scene, stray),
+            vec![IssueKind::MissingParent { parent: missing }]
+        );
+        assert_eq!(
+            kinds(&scene, unlisted),
+            vec![IssueKind::ParentMismatch { parent: first }]
+        );
+    }
+
+    #[test]
+    fn test_transform_issues() {
+        let mut scene = Scene::new(800.0, 600.0);
+        let mut nan = text(0.0, 0.0);
+        nan.transform.rotation = f32::NAN;
+        let nan = scene.add_element(nan);
+        let mut flat = text(0.0, 0.0);
+        flat.transform.height = 0.0;
+        let flat = scene.add_element(flat);
+        // Empty containers are not zero-size warnings
+        scene.add_element(group(Vec::new()));
+
+        assert_eq!(
+            kinds(&scene, nan),
+            vec![IssueKind::InvalidTransform { field: "rotation" }]
+        );
+        let issues = scene.validate();
+        assert_eq!(issues.len(), 2);
+        let warning = issues.iter().find(|i| i.element_id == flat).expect("flat");
+        assert_eq!(warning.kind, IssueKind::ZeroSize);
+        assert_eq!(warning.severity, IssueSeverity::Warning);
+    }
+
+    #[test]
+    fn test_duplicate_ids_and_cycles() {
+        let a = text(0.0, 0.0);
+        let mut b = text(0.0, 0.0);
+        b.id = a.id;
+        let key = ElementId::new();
+        let json = serde_json::json!({
+            "elements": { a.id.to_string(): a, key.to_string(): b },
+            "root_elements": [],
+            "selected": [],
+            "viewport_width": 800.0,
+            "viewport_height": 600.0,
+            "zoom": 1.0,
+            "pan_x": 0.0,
+            "pan_y": 0.0
+        });
+        let scene = Scene::from_json(&json.to_string()).expect("scene");
+        assert_eq!(
+            kinds(&scene, a.id),
+            vec![IssueKind::DuplicateId { count: 2 }]
+        );
+
+        let mut outer = group(Vec::new());
+        let mut inner = group(vec![outer.id]);
+        outer.kind = ElementKind::Group {
+            children: vec![inner.id],
+            clip: false,
+            opacity: 1.0,
+        };
+        outer.parent = Some(inner.id);
+        inner.parent = Some(outer.id);
+        let mut scene = Scene::new(800.0, 600.0);
+        let outer = scene.add_element(outer);
+        scene.add_element(inner);
+        assert_eq!(kinds(&scene, outer), vec![IssueKind::ParentCycle]);
+        assert_eq!(scene.stats().max_depth, 2);
+    }
+
+    #[test]
+    fn test_stats() {
+        let mut scene = Scene::new(800.0, 600.0);
+        assert_eq!(scene.stats().bounds, None);
+
+        let first = scene.add_element(text(0.0, 0.0));
+        scene.add_element(text(200.0, 100.0));
+        let layer = scene.add_element(Element::new(ElementKind::OverlayLayer {
+            children: Vec::new(),
+            opacity: 1.0,
+            clip: false,
+        }));
+        scene.reparent(first, layer).expect("reparent");
+        scene.select(first).expect("select");
+
+        let stats = scene.stats();
+        assert_eq!(stats.element_count, 3);
+        assert_eq!(stats.root_count, 2);
+        assert_eq!(stats.selected_count, 1);
+        assert_eq!(stats.by_kind.get("Text"), Some(&2));
+        assert_eq!(stats.by_kind.get("OverlayLayer"), Some(&1));
+        assert_eq!(stats.max_depth, 1);
+        assert_eq!(
+            stats.bounds,
+            Some(SceneBounds {
+                min_x: 0.0,
+                min_y: 0.0,
+                max_x: 300.0,
+                max_y: 120.0,
+            })
+        );
+        assert!(stats.memory_estimate > 3 * std::mem::size_of::<Element>());
+    }
+}
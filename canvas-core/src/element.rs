@@ -1,10 +1,35 @@
 //! Canvas elements - the building blocks of scenes.
 
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::agenda::AgendaItem;
+use crate::annotation::MeasurementKind;
+use crate::asset::AssetId;
+use crate::attachment::Attachment;
+use crate::barcode::Symbology;
+use crate::calendar::{CalendarEvent, CalendarView};
+use crate::color::{Color, Theme, ThemeColor};
+use crate::dashboard::{GaugeShape, NumberFormat, Threshold};
+use crate::data::DataBinding;
+use crate::floor_plan::{FloorMarker, PlanPath};
+use crate::geometry::OrientedRect;
+use crate::highlight::HighlightSpan;
+use crate::interaction::Interactions;
+use crate::measure::{self, MeasureContext, Size};
+use crate::model3d::{Lighting, ModelCamera, Transform3D};
+use crate::ocr::OcrText;
+use crate::randomizer::{RandomOutcome, RandomSource};
+use crate::shape::ShapeKind;
+use crate::sparkline::SparklineKind;
+use crate::style::Style;
+use crate::tree::{TreeDirection, TreeNode};
+
 /// Unique identifier for an element.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
 pub struct ElementId(Uuid);
 
 impl ElementId {
@@ -44,6 +69,7 @@ impl std::fmt::Display for ElementId {
 
 /// The type of content an element contains.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
 #[serde(tag = "type", content = "data")]
 pub enum ElementKind {
     /// A 2D chart (bar, line, pie, etc.).
@@ -52,24 +78,55 @@ pub enum ElementKind {
         chart_type: String,
         /// Chart data as JSON.
         data: serde_json::Value,
+        /// Dataset the chart plots; the scene fills in `data`'s `x_labels`
+        /// and `series` from it (see [`crate::data`]).
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        binding: Option<DataBinding>,
     },
 
     /// A 2D image (PNG, JPG, SVG).
     Image {
-        /// Image source URI or base64 data.
+        /// Image source URI, base64 data, or `asset:` reference.
         src: String,
         /// Image format.
         format: ImageFormat,
+        /// Markup pinned to the image (ink, arrows, highlights, text), which
+        /// moves and scales with it; see [`crate::annotation`].
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        annotations: Vec<ElementId>,
+        /// Text read out of the image on request; see [`crate::ocr`].
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        extracted_text: Option<OcrText>,
     },
 
     /// A 3D model (glTF).
     Model3D {
-        /// glTF source URI.
+        /// glTF source URI or `asset:` reference.
         src: String,
         /// Initial rotation (euler angles in radians).
         rotation: [f32; 3],
         /// Initial scale.
         scale: f32,
+        /// Position, quaternion rotation and scale in model space; replaces
+        /// `rotation` and `scale` when set. See [`crate::model3d`].
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pose: Option<Transform3D>,
+        /// How 3D views should frame the model.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        camera: Option<ModelCamera>,
+        /// Lights the model is shaded with; [`Lighting::default`] if unset.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        lighting: Option<Lighting>,
+    },
+
+    /// Generative visuals: a WGSL `shade` function run for every pixel of
+    /// the element's rect. See [`crate::shader`] for what the source may use.
+    Shader {
+        /// WGSL source defining `fn shade(uv: vec2<f32>) -> vec4<f32>`.
+        wgsl: String,
+        /// Custom values the source reads with `param(i)`.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        uniforms: Vec<f32>,
     },
 
     /// A video stream or WebRTC feed.
@@ -86,12 +143,326 @@ pub enum ElementKind {
         media_config: Option<MediaConfig>,
     },
 
+    /// An audio stream or clip, such as a voice note or text-to-speech output.
+    ///
+    /// Drawn as a waveform with a play/pause control; tapping an interactive
+    /// audio element toggles `state`.
+    Audio {
+        /// Stream identifier (peer ID, "local", or media URL).
+        stream_id: String,
+        /// Peak amplitude per bucket in `0.0..=1.0`, oldest first (see
+        /// [`downsample_waveform`]).
+        #[serde(default)]
+        waveform: Vec<f32>,
+        /// Playback state.
+        #[serde(default)]
+        state: PlaybackState,
+    },
+
+    /// A month or week calendar listing events on their days.
+    ///
+    /// Tapping a day of an interactive calendar selects it; the layout is
+    /// shared by all renderers through [`CalendarLayout`].
+    ///
+    /// [`CalendarLayout`]: crate::calendar::CalendarLayout
+    Calendar {
+        /// Month or week view.
+        #[serde(default)]
+        view: CalendarView,
+        /// A day in the month or week to show, `YYYY-MM-DD`.
+        date: String,
+        /// Events, each shown on its day.
+        #[serde(default)]
+        events: Vec<CalendarEvent>,
+        /// Selected day, `YYYY-MM-DD`.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        selected: Option<String>,
+    },
+
+    /// A question with options that peers vote on, drawn with live result
+    /// bars.
+    ///
+    /// Tapping an option of an interactive, open poll emits a `vote`
+    /// action; the server records it per peer (see [`cast_vote`]).
+    ///
+    /// [`cast_vote`]: crate::poll::cast_vote
+    Poll {
+        /// The question asked.
+        question: String,
+        /// Option labels, in display order.
+        options: Vec<String>,
+        /// Option index chosen by each voter (peer or agent ID).
+        #[serde(default)]
+        votes: BTreeMap<String, usize>,
+        /// Whether voting has closed.
+        #[serde(default)]
+        closed: bool,
+    },
+
+    /// A meeting agenda: timed items under a countdown or time-left clock,
+    /// with the running item highlighted.
+    ///
+    /// Clients work out the running item from `started_at_ms` and the
+    /// server clock, so the highlight moves on without sync traffic; the
+    /// server restarts the schedule from another item with [`apply`].
+    ///
+    /// [`apply`]: crate::agenda::apply
+    Agenda {
+        /// Heading, e.g. the meeting name.
+        #[serde(default, skip_serializing_if = "String::is_empty")]
+        title: String,
+        /// Items, in running order.
+        items: Vec<AgendaItem>,
+        /// Item the schedule runs from; the items after it follow on by
+        /// their durations.
+        #[serde(default)]
+        current: usize,
+        /// When `current` started, or starts if in the future (ms since the
+        /// Unix epoch, server clock); `None` until the agenda is started.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        started_at_ms: Option<u64>,
+    },
+
+    /// Dice, a wheel spinner or a shuffled list.
+    ///
+    /// Tapping an interactive randomizer emits a `randomize` action; the
+    /// server draws the outcome (see [`randomize`]) so every participant sees
+    /// the same result.
+    ///
+    /// [`randomize`]: crate::randomizer::randomize
+    Randomizer {
+        /// What is drawn.
+        source: RandomSource,
+        /// The latest draw, if any.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        outcome: Option<RandomOutcome>,
+    },
+
+    /// A floor plan or seating chart: a background image or vector outline
+    /// with seat and zone markers colored by occupancy.
+    ///
+    /// Tapping a marker of an interactive plan emits `select_marker`;
+    /// dragging one emits `move_marker`, which the server applies (see
+    /// [`move_marker`]) unless the plan is locked.
+    ///
+    /// [`move_marker`]: crate::floor_plan::move_marker
+    FloorPlan {
+        /// Background image URL or data URI, stretched over the plan area.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        background: Option<String>,
+        /// Plan width in plan units (e.g. centimeters or image pixels).
+        #[serde(default = "ElementKind::default_plan_width")]
+        plan_width: f32,
+        /// Plan height in plan units.
+        #[serde(default = "ElementKind::default_plan_height")]
+        plan_height: f32,
+        /// Walls and outlines drawn over the background.
+        #[serde(default)]
+        paths: Vec<PlanPath>,
+        /// Seats and zones, drawn in order.
+        #[serde(default)]
+        markers: Vec<FloorMarker>,
+        /// Whether markers stay put when dragged.
+        #[serde(default)]
+        locked: bool,
+    },
+
+    /// A radial or linear gauge showing a value within a range, colored by
+    /// the highest threshold it reaches.
+    ///
+    /// [`GaugeLayout`] shares the geometry between renderers.
+    ///
+    /// [`GaugeLayout`]: crate::dashboard::GaugeLayout
+    Gauge {
+        /// Current value.
+        value: f64,
+        /// Value at the empty end of the dial.
+        #[serde(default)]
+        min: f64,
+        /// Value at the full end of the dial.
+        #[serde(default = "ElementKind::default_gauge_max")]
+        max: f64,
+        /// Dial or bar.
+        #[serde(default)]
+        shape: GaugeShape,
+        /// Caption, e.g. `CPU load`.
+        #[serde(default, skip_serializing_if = "String::is_empty")]
+        label: String,
+        /// Colors from given values upwards.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        thresholds: Vec<Threshold>,
+        /// How the value is written.
+        #[serde(default)]
+        format: NumberFormat,
+    },
+
+    /// A KPI card: a headline number with its change since a previous value
+    /// and an optional sparkline of recent values.
+    ///
+    /// [`KpiLayout`] shares the geometry between renderers.
+    ///
+    /// [`KpiLayout`]: crate::dashboard::KpiLayout
+    Kpi {
+        /// What is measured, e.g. `Revenue`.
+        label: String,
+        /// Current value.
+        value: f64,
+        /// Value to compare against, e.g. last month's.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        previous: Option<f64>,
+        /// Recent values, oldest first.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        sparkline: Vec<f32>,
+        /// Whether the sparkline is a line or bars.
+        #[serde(default)]
+        sparkline_kind: SparklineKind,
+        /// Value colors from given values upwards.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        thresholds: Vec<Threshold>,
+        /// How the value and change are written.
+        #[serde(default)]
+        format: NumberFormat,
+        /// Whether an increase is good news (green) rather than bad (red).
+        #[serde(default = "ElementKind::default_higher_is_better")]
+        higher_is_better: bool,
+    },
+
+    /// A word-sized trend line or bar series without axes or labels, for
+    /// table rows, text layouts and dashboards.
+    Sparkline {
+        /// Values, oldest first.
+        values: Vec<f32>,
+        /// Line or bars.
+        #[serde(default)]
+        kind: SparklineKind,
+        /// Line or bar color.
+        #[serde(default = "ElementKind::default_sparkline_color")]
+        color: ThemeColor,
+    },
+
+    /// A hierarchy such as an org chart or decision tree, laid out as a
+    /// tidy tree (see [`TreeLayout`]).
+    ///
+    /// Tapping a node with children collapses or expands it and emits
+    /// `toggle_node`; tapping a leaf emits `select_node`.
+    ///
+    /// [`TreeLayout`]: crate::tree::TreeLayout
+    Tree {
+        /// Root node.
+        root: TreeNode,
+        /// Which way the tree grows.
+        #[serde(default)]
+        direction: TreeDirection,
+    },
+
+    /// A freehand whiteboard stroke.
+    ///
+    /// Points are relative to the element's top-left corner at its natural
+    /// size, so resizing the element scales the stroke. Erasers, restyling
+    /// and lasso selection work on canvas coordinates; see [`crate::ink`].
+    Ink {
+        /// Points along the stroke, `[x, y]`.
+        points: Vec<[f32; 2]>,
+        /// Stroke color.
+        #[serde(default = "ElementKind::default_ink_color")]
+        color: ThemeColor,
+        /// Stroke width in pixels at natural size.
+        #[serde(default = "ElementKind::default_ink_width")]
+        width: f32,
+    },
+
+    /// A labeled measurement line between two points, drawn as a dimension
+    /// line with end caps or as a ruler with ticks; see
+    /// [`crate::annotation`].
+    ///
+    /// `start` and `end` are fractions of the element's box, so moving or
+    /// resizing the element moves the line with it.
+    Measurement {
+        /// Start point, `[x, y]` as fractions of the box.
+        start: [f32; 2],
+        /// End point, `[x, y]` as fractions of the box.
+        end: [f32; 2],
+        /// Dimension line, ruler or arrow.
+        #[serde(default)]
+        kind: MeasurementKind,
+        /// Label; defaults to the measured length, except on arrows.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        label: Option<String>,
+        /// Unit written after the measured length.
+        #[serde(default = "ElementKind::default_measurement_unit")]
+        unit: String,
+        /// Units per canvas pixel, e.g. `0.5` for a plan drawn at two pixels
+        /// per meter.
+        #[serde(default = "ElementKind::default_measurement_scale")]
+        scale: f32,
+        /// Line and label color.
+        #[serde(default = "ElementKind::default_annotation_color")]
+        color: ThemeColor,
+    },
+
+    /// A translucent highlight over a rectangular region, with an optional
+    /// caption, for pointing at part of a chart, image or video frame.
+    Region {
+        /// Caption shown in a tab at the top-left corner.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        caption: Option<String>,
+        /// Border, tint and caption tab color.
+        #[serde(default = "ElementKind::default_annotation_color")]
+        color: ThemeColor,
+    },
+
+    /// A rectangle, ellipse, regular polygon, star or block arrow filling the
+    /// element's box, painted with the element's style; see
+    /// [`crate::shape`].
+    ///
+    /// Shapes without a fill use [`ElementKind::SHAPE_COLOR`].
+    Shape {
+        /// Geometry.
+        shape: ShapeKind,
+    },
+
+    /// A scannable QR code or Code 128 barcode on a light background; see
+    /// [`crate::barcode`].
+    ///
+    /// The server encodes `payload` into `modules`, so clients draw the
+    /// code without an encoder.
+    Barcode {
+        /// How the payload is encoded.
+        symbology: Symbology,
+        /// Encoded text, e.g. a URL, Wi-Fi credentials or a ticket number.
+        payload: String,
+        /// Rows of modules, `1` dark and `0` light, without the quiet zone;
+        /// Code 128 has a single row.
+        modules: Vec<String>,
+        /// Color of the dark modules.
+        #[serde(default = "ElementKind::default_barcode_color")]
+        color: ThemeColor,
+    },
+
+    /// An embedded web page, such as a dashboard or document.
+    ///
+    /// DOM hosts show it as an iframe positioned over the canvas; other
+    /// renderers draw a placeholder. Only `http` and `https` URLs are loaded
+    /// (see [`ElementKind::embed_src`]).
+    Embed {
+        /// Page URL.
+        url: String,
+        /// iframe `sandbox` tokens, space-separated; empty applies every
+        /// restriction. Only [`ElementKind::EMBED_SANDBOX_TOKENS`] are
+        /// honored.
+        #[serde(default = "ElementKind::default_embed_sandbox")]
+        sandbox: String,
+    },
+
     /// A transparent overlay layer for annotations on top of video.
     OverlayLayer {
         /// Child element IDs drawn on this layer.
         children: Vec<ElementId>,
         /// Background opacity (0.0 = fully transparent).
         opacity: f32,
+        /// Whether children are clipped to the layer bounds.
+        #[serde(default)]
+        clip: bool,
     },
 
     /// A text label or annotation.
@@ -100,19 +471,444 @@ pub enum ElementKind {
         content: String,
         /// Font size in pixels.
         font_size: f32,
-        /// Text color as hex.
-        color: String,
+        /// Text color.
+        color: ThemeColor,
+    },
+
+    /// A math formula written in TeX notation, e.g. `\frac{a}{b} + x^2`.
+    ///
+    /// Renderers that cannot typeset the formula show the TeX source in a
+    /// monospace font instead.
+    Math {
+        /// TeX source (math mode, without `$` delimiters).
+        tex: String,
+        /// Font size in pixels.
+        #[serde(default = "ElementKind::default_math_font_size")]
+        font_size: f32,
+        /// Text color.
+        #[serde(default = "ElementKind::default_math_color")]
+        color: ThemeColor,
+    },
+
+    /// A code snippet, drawn in a monospace font with syntax colors.
+    ///
+    /// Create with [`ElementKind::code`] to fill in `spans`; renderers draw
+    /// uncovered source as plain text.
+    Code {
+        /// Language name, e.g. `rust` or `python`.
+        #[serde(default)]
+        language: String,
+        /// Source text.
+        source: String,
+        /// Highlight spans over `source`.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        spans: Vec<HighlightSpan>,
+    },
+
+    /// A terminal-style log pane: an append-only stream of text lines with
+    /// ANSI colors, e.g. build or test output.
+    ///
+    /// Feed it with [`log_view::append`]; the oldest lines are dropped once
+    /// `max_lines` are held.
+    ///
+    /// [`log_view::append`]: crate::log_view::append
+    LogView {
+        /// Heading, e.g. the command being run.
+        #[serde(default, skip_serializing_if = "String::is_empty")]
+        title: String,
+        /// Lines, oldest first, with any ANSI escape codes.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        lines: Vec<String>,
+        /// Most lines kept.
+        #[serde(default = "ElementKind::default_log_lines")]
+        max_lines: usize,
+        /// Keep the newest line in view; otherwise the oldest lines show.
+        #[serde(default = "ElementKind::default_log_follow")]
+        follow: bool,
+    },
+
+    /// A sticky note: text on a colored card, attributed to its author.
+    StickyNote {
+        /// Note text.
+        content: String,
+        /// Background color.
+        #[serde(default = "ElementKind::default_note_color")]
+        color: ThemeColor,
+        /// Who wrote the note (user or agent name).
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        author: Option<String>,
+    },
+
+    /// A callout: a note with an optional tail pointing at an element or
+    /// canvas position, used to annotate charts and video regions.
+    Callout {
+        /// Callout text.
+        content: String,
+        /// Background color.
+        #[serde(default = "ElementKind::default_callout_color")]
+        color: ThemeColor,
+        /// Who wrote the callout (user or agent name).
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        author: Option<String>,
+        /// What the tail points at (no tail if `None`).
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        target: Option<CalloutTarget>,
     },
 
     /// A container group for other elements.
     Group {
         /// Child element IDs.
         children: Vec<ElementId>,
+        /// Whether children are clipped to the group bounds.
+        #[serde(default)]
+        clip: bool,
+        /// Group opacity; children are composited as a unit, then faded.
+        #[serde(default = "ElementKind::default_group_opacity")]
+        opacity: f32,
+    },
+}
+
+impl ElementKind {
+    const fn default_log_lines() -> usize {
+        crate::log_view::DEFAULT_LOG_LINES
+    }
+
+    const fn default_log_follow() -> bool {
+        true
+    }
+
+    const fn default_group_opacity() -> f32 {
+        1.0
+    }
+
+    const fn default_math_font_size() -> f32 {
+        24.0
+    }
+
+    fn default_math_color() -> ThemeColor {
+        Self::TEXT_COLOR.into()
+    }
+
+    /// A code element, highlighted when the `highlight` feature is enabled.
+    #[must_use]
+    pub fn code(language: impl Into<String>, source: impl Into<String>) -> Self {
+        let language = language.into();
+        let source = source.into();
+        #[cfg(feature = "highlight")]
+        let spans = crate::highlight::highlight(&language, &source);
+        #[cfg(not(feature = "highlight"))]
+        let spans = Vec::new();
+        Self::Code {
+            language,
+            source,
+            spans,
+        }
+    }
+
+    /// Default `FloorPlan` width in plan units.
+    #[must_use]
+    pub fn default_plan_width() -> f32 {
+        1000.0
+    }
+
+    /// Default `FloorPlan` height in plan units.
+    #[must_use]
+    pub fn default_plan_height() -> f32 {
+        600.0
+    }
+
+    /// Default `Gauge` maximum.
+    #[must_use]
+    pub fn default_gauge_max() -> f64 {
+        100.0
+    }
+
+    /// Default for `Kpi` `higher_is_better`.
+    #[must_use]
+    pub fn default_higher_is_better() -> bool {
+        true
+    }
+
+    /// Default `Sparkline` color.
+    #[must_use]
+    pub fn default_sparkline_color() -> ThemeColor {
+        Self::SPARKLINE_COLOR.into()
+    }
+
+    /// Default `Ink` color.
+    #[must_use]
+    pub fn default_ink_color() -> ThemeColor {
+        Self::INK_COLOR.into()
+    }
+
+    /// Default `Ink` width in pixels.
+    #[must_use]
+    pub fn default_ink_width() -> f32 {
+        3.0
+    }
+
+    /// Default `Measurement` unit.
+    #[must_use]
+    pub fn default_measurement_unit() -> String {
+        "px".to_string()
+    }
+
+    /// Default `Measurement` scale: one unit per canvas pixel.
+    #[must_use]
+    pub fn default_measurement_scale() -> f32 {
+        1.0
+    }
+
+    /// Default `Measurement` and `Region` color.
+    #[must_use]
+    pub fn default_annotation_color() -> ThemeColor {
+        Self::ANNOTATION_COLOR.into()
+    }
+
+    /// Default `Embed` sandbox: scripts, forms and popups, but no
+    /// same-origin access.
+    #[must_use]
+    pub fn default_embed_sandbox() -> String {
+        "allow-scripts allow-forms allow-popups".to_string()
+    }
+
+    /// `Embed` sandbox tokens that may be lifted. Others, such as
+    /// `allow-same-origin` or `allow-top-navigation`, would let the page
+    /// reach beyond its frame.
+    pub const EMBED_SANDBOX_TOKENS: &'static [&'static str] =
+        &["allow-scripts", "allow-forms", "allow-popups"];
+
+    /// Sandbox of an `Embed` element, keeping only the tokens in
+    /// [`EMBED_SANDBOX_TOKENS`](Self::EMBED_SANDBOX_TOKENS).
+    #[must_use]
+    pub fn embed_sandbox(&self) -> Option<String> {
+        let Self::Embed { sandbox, .. } = self else {
+            return None;
+        };
+        let allowed: Vec<&str> = sandbox
+            .split_whitespace()
+            .filter(|token| Self::EMBED_SANDBOX_TOKENS.contains(token))
+            .collect();
+        Some(allowed.join(" "))
+    }
+
+    /// Check whether a URL may be loaded by an `Embed` element: absolute
+    /// `http` or `https` only, so `javascript:` and `data:` pages never run.
+    #[must_use]
+    pub fn is_embeddable_url(url: &str) -> bool {
+        let url = url.trim();
+        ["https://", "http://"].iter().any(|scheme| {
+            url.get(..scheme.len())
+                .is_some_and(|prefix| prefix.eq_ignore_ascii_case(scheme))
+        }) && !url.contains(char::is_whitespace)
+    }
+
+    /// URL of an `Embed` element, if it may be loaded.
+    #[must_use]
+    pub fn embed_src(&self) -> Option<&str> {
+        match self {
+            Self::Embed { url, .. } if Self::is_embeddable_url(url) => Some(url.trim()),
+            _ => None,
+        }
+    }
+
+    fn default_barcode_color() -> ThemeColor {
+        Self::BARCODE_COLOR.into()
+    }
+
+    fn default_note_color() -> ThemeColor {
+        Self::NOTE_COLOR.into()
+    }
+
+    fn default_callout_color() -> ThemeColor {
+        Self::CALLOUT_COLOR.into()
+    }
+
+    /// Color of a `Text` or `Math` element, or the fallback for an undefined
+    /// theme reference.
+    pub const TEXT_COLOR: Color = Color::BLACK;
+    /// Default `StickyNote` background.
+    pub const NOTE_COLOR: Color = Color::from_rgb(0xff_f59d);
+    /// Default `Callout` background.
+    pub const CALLOUT_COLOR: Color = Color::WHITE;
+    /// Default `Sparkline` color.
+    pub const SPARKLINE_COLOR: Color = Color::from_rgb(0x1e_88e5);
+    /// Default `Ink` color.
+    pub const INK_COLOR: Color = Color::from_rgb(0x21_2121);
+    /// Default `Measurement` and `Region` color.
+    pub const ANNOTATION_COLOR: Color = Color::from_rgb(0xe5_3935);
+    /// Default `Shape` fill.
+    pub const SHAPE_COLOR: Color = Color::from_rgb(0x90_caf9);
+    /// Default `Barcode` module color.
+    pub const BARCODE_COLOR: Color = Color::BLACK;
+
+    /// Text color of a `Text` or `Math` element, background of a
+    /// `StickyNote` or `Callout`, or color of a `Sparkline`, `Ink` stroke,
+    /// annotation or `Barcode`, resolved against `theme`.
+    ///
+    /// Theme references the theme does not define fall back to the kind's
+    /// default color.
+    #[must_use]
+    pub fn resolved_color(&self, theme: &Theme) -> Option<Color> {
+        let (color, fallback) = match self {
+            Self::Text { color, .. } | Self::Math { color, .. } => (color, Self::TEXT_COLOR),
+            Self::StickyNote { color, .. } => (color, Self::NOTE_COLOR),
+            Self::Callout { color, .. } => (color, Self::CALLOUT_COLOR),
+            Self::Sparkline { color, .. } => (color, Self::SPARKLINE_COLOR),
+            Self::Ink { color, .. } => (color, Self::INK_COLOR),
+            Self::Measurement { color, .. } | Self::Region { color, .. } => {
+                (color, Self::ANNOTATION_COLOR)
+            }
+            Self::Barcode { color, .. } => (color, Self::BARCODE_COLOR),
+            _ => return None,
+        };
+        Some(theme.resolve_or(color, fallback))
+    }
+
+    /// Text and author of a `StickyNote` or `Callout`.
+    #[must_use]
+    pub fn note(&self) -> Option<(&str, Option<&str>)> {
+        match self {
+            Self::StickyNote {
+                content, author, ..
+            }
+            | Self::Callout {
+                content, author, ..
+            } => Some((content, author.as_deref())),
+            _ => None,
+        }
+    }
+
+    /// Mutable tail target of a `Callout`.
+    pub(crate) fn callout_target_mut(&mut self) -> Option<&mut CalloutTarget> {
+        match self {
+            Self::Callout { target, .. } => target.as_mut(),
+            _ => None,
+        }
+    }
+
+    /// The kind's `type` tag, e.g. `"Text"` or `"Chart"`.
+    #[must_use]
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Self::Chart { .. } => "Chart",
+            Self::Image { .. } => "Image",
+            Self::Model3D { .. } => "Model3D",
+            Self::Shader { .. } => "Shader",
+            Self::Video { .. } => "Video",
+            Self::Audio { .. } => "Audio",
+            Self::Calendar { .. } => "Calendar",
+            Self::Poll { .. } => "Poll",
+            Self::Agenda { .. } => "Agenda",
+            Self::Randomizer { .. } => "Randomizer",
+            Self::FloorPlan { .. } => "FloorPlan",
+            Self::Gauge { .. } => "Gauge",
+            Self::Kpi { .. } => "Kpi",
+            Self::Sparkline { .. } => "Sparkline",
+            Self::Tree { .. } => "Tree",
+            Self::Ink { .. } => "Ink",
+            Self::Measurement { .. } => "Measurement",
+            Self::Region { .. } => "Region",
+            Self::Shape { .. } => "Shape",
+            Self::Barcode { .. } => "Barcode",
+            Self::Embed { .. } => "Embed",
+            Self::OverlayLayer { .. } => "OverlayLayer",
+            Self::Text { .. } => "Text",
+            Self::Math { .. } => "Math",
+            Self::Code { .. } => "Code",
+            Self::LogView { .. } => "LogView",
+            Self::StickyNote { .. } => "StickyNote",
+            Self::Callout { .. } => "Callout",
+            Self::Group { .. } => "Group",
+        }
+    }
+
+    /// Child element IDs of a container (`Group` or `OverlayLayer`), or the
+    /// annotations pinned to an `Image`.
+    ///
+    /// Returns an empty slice for other kinds.
+    #[must_use]
+    pub fn children(&self) -> &[ElementId] {
+        match self {
+            Self::Group { children, .. } | Self::OverlayLayer { children, .. } => children,
+            Self::Image { annotations, .. } => annotations,
+            _ => &[],
+        }
+    }
+
+    /// The asset referenced by an `Image` or `Model3D` source, if any.
+    #[must_use]
+    pub fn asset_id(&self) -> Option<AssetId> {
+        match self {
+            Self::Image { src, .. } | Self::Model3D { src, .. } => AssetId::from_uri(src),
+            _ => None,
+        }
+    }
+
+    /// Mutable source of an `Image` or `Model3D`.
+    pub(crate) fn source_mut(&mut self) -> Option<&mut String> {
+        match self {
+            Self::Image { src, .. } | Self::Model3D { src, .. } => Some(src),
+            _ => None,
+        }
+    }
+
+    /// Mutable child list of a container (`Group` or `OverlayLayer`) or
+    /// annotation list of an `Image`.
+    pub(crate) fn children_mut(&mut self) -> Option<&mut Vec<ElementId>> {
+        match self {
+            Self::Group { children, .. } | Self::OverlayLayer { children, .. } => Some(children),
+            Self::Image { annotations, .. } => Some(annotations),
+            _ => None,
+        }
+    }
+
+    /// Check whether this container clips its children to its bounds.
+    #[must_use]
+    pub fn clips_children(&self) -> bool {
+        match self {
+            Self::Group { clip, .. } | Self::OverlayLayer { clip, .. } => *clip,
+            _ => false,
+        }
+    }
+
+    /// Opacity at which a `Group` composites its children as a unit.
+    ///
+    /// Returns `None` for non-group kinds and for fully opaque groups, which
+    /// need no separate compositing layer.
+    #[must_use]
+    pub fn group_opacity(&self) -> Option<f32> {
+        match self {
+            Self::Group { opacity, .. } if opacity.is_finite() && *opacity < 1.0 => {
+                Some(opacity.max(0.0))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// What a callout's tail points at.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum CalloutTarget {
+    /// The center of another element; follows it as it moves.
+    Element {
+        /// Target element.
+        element_id: ElementId,
+    },
+    /// A fixed canvas position.
+    Point {
+        /// X coordinate.
+        x: f32,
+        /// Y coordinate.
+        y: f32,
     },
 }
 
 /// Supported image formats.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
 #[serde(rename_all = "lowercase")]
 pub enum ImageFormat {
     /// PNG image.
@@ -128,6 +924,7 @@ pub enum ImageFormat {
 /// A crop rectangle for video frames.
 /// Values are normalized (0.0 to 1.0) relative to the video dimensions.
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
 pub struct CropRect {
     /// Left edge (0.0 = leftmost).
     pub x: f32,
@@ -171,6 +968,7 @@ impl CropRect {
 
 /// Video resolution presets.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
 #[serde(rename_all = "lowercase")]
 pub enum Resolution {
     /// 426x240 (very low bandwidth).
@@ -214,6 +1012,7 @@ impl Resolution {
 
 /// Quality presets for automatic video configuration.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
 #[serde(rename_all = "lowercase")]
 pub enum QualityPreset {
     /// Automatic adaptation based on network conditions.
@@ -265,6 +1064,7 @@ impl QualityPreset {
 
 /// Configuration for video stream quality.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
 pub struct MediaConfig {
     /// Target bitrate in kbps (e.g., 1500 for 720p).
     pub bitrate_kbps: Option<u32>,
@@ -325,6 +1125,65 @@ impl MediaConfig {
     }
 }
 
+/// Largest number of waveform points an `Audio` element should carry.
+pub const MAX_WAVEFORM_POINTS: usize = 512;
+
+/// Playback state of an `Audio` element.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+#[serde(rename_all = "lowercase")]
+pub enum PlaybackState {
+    /// Not playing (the initial state).
+    #[default]
+    Paused,
+    /// Playing.
+    Playing,
+    /// Played to the end.
+    Ended,
+}
+
+impl PlaybackState {
+    /// The state after pressing play/pause: playing pauses, anything else
+    /// (re)starts playback.
+    #[must_use]
+    pub fn toggled(self) -> Self {
+        match self {
+            Self::Playing => Self::Paused,
+            Self::Paused | Self::Ended => Self::Playing,
+        }
+    }
+}
+
+/// Reduce audio samples to `points` peak amplitudes for an `Audio`
+/// element's waveform.
+///
+/// Each point is the largest absolute sample in its bucket, clamped to
+/// `0.0..=1.0`; non-finite samples are ignored. `points` is capped at
+/// [`MAX_WAVEFORM_POINTS`], and fewer samples than points are kept as-is.
+#[must_use]
+pub fn downsample_waveform(samples: &[f32], points: usize) -> Vec<f32> {
+    let points = points.min(MAX_WAVEFORM_POINTS);
+    if points == 0 || samples.is_empty() {
+        return Vec::new();
+    }
+    let peak = |bucket: &[f32]| {
+        bucket
+            .iter()
+            .filter(|s| s.is_finite())
+            .fold(0.0_f32, |max, s| max.max(s.abs()))
+            .min(1.0)
+    };
+    if samples.len() <= points {
+        return samples
+            .iter()
+            .map(|s| peak(std::slice::from_ref(s)))
+            .collect();
+    }
+    (0..points)
+        .map(|i| peak(&samples[i * samples.len() / points..(i + 1) * samples.len() / points]))
+        .collect()
+}
+
 /// Real-time media statistics from WebRTC.
 #[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
 pub struct MediaStats {
@@ -362,6 +1221,7 @@ impl MediaStats {
 
 /// Transform for positioning and sizing elements.
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
 pub struct Transform {
     /// X position (pixels from left).
     pub x: f32,
@@ -375,6 +1235,9 @@ pub struct Transform {
     pub rotation: f32,
     /// Z-index for layering.
     pub z_index: i32,
+    /// Whether the size follows the content (see [`Element::apply_auto_size`]).
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub auto_size: bool,
 }
 
 impl Default for Transform {
@@ -386,12 +1249,14 @@ impl Default for Transform {
             height: 100.0,
             rotation: 0.0,
             z_index: 0,
+            auto_size: false,
         }
     }
 }
 
 /// A canvas element with content and transform.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[allow(clippy::struct_excessive_bools)]
 pub struct Element {
     /// Unique identifier.
     pub id: ElementId,
@@ -403,8 +1268,48 @@ pub struct Element {
     pub selected: bool,
     /// Whether this element can be interacted with.
     pub interactive: bool,
+    /// Whether this element is drawn and hit-testable. Hiding a container
+    /// hides its descendants.
+    #[serde(default = "Element::default_visible")]
+    pub visible: bool,
+    /// Opacity of the whole element, multiplied with the style's opacity
+    /// (0.0 = transparent, 1.0 = opaque). Meant for fading elements in and
+    /// out without touching their styling.
+    #[serde(default = "Element::default_opacity")]
+    pub opacity: f32,
+    /// Whether the element is pinned to the viewport (a HUD element such
+    /// as a logo, timer or status badge). Its transform is in viewport
+    /// pixels and ignores the camera's pan and zoom. Children are not
+    /// pinned by their parent.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub screen_anchored: bool,
+    /// Whether the element holds confidential content. Redacted exports
+    /// blur it or replace it with a placeholder, along with its
+    /// descendants; see [`redact`](crate::redact).
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub sensitive: bool,
     /// Optional parent element ID (for grouped elements).
     pub parent: Option<ElementId>,
+    /// Visual style (fill, stroke, corner radius, shadow, opacity).
+    #[serde(default)]
+    pub style: Style,
+    /// Declarative tap, drag and long-press behaviors.
+    #[serde(default, skip_serializing_if = "Interactions::is_empty")]
+    pub interactions: Interactions,
+    /// Peer that created this element, if known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_by: Option<String>,
+    /// Peer that last changed this element, if known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_modified_by: Option<String>,
+    /// Another element this one is positioned relative to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attachment: Option<Attachment>,
+    /// Distance in front of the canvas plane on holographic displays, in
+    /// canvas units (negative is behind it). Without one the depth follows
+    /// `transform.z_index`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub depth: Option<f32>,
 }
 
 impl Element {
@@ -417,7 +1322,17 @@ impl Element {
             transform: Transform::default(),
             selected: false,
             interactive: true,
+            visible: true,
+            opacity: 1.0,
+            screen_anchored: false,
+            sensitive: false,
             parent: None,
+            style: Style::default(),
+            interactions: Interactions::default(),
+            created_by: None,
+            last_modified_by: None,
+            attachment: None,
+            depth: None,
         }
     }
 
@@ -428,6 +1343,20 @@ impl Element {
         self
     }
 
+    /// Set the visual style.
+    #[must_use]
+    pub fn with_style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Set the declarative interactions.
+    #[must_use]
+    pub fn with_interactions(mut self, interactions: Interactions) -> Self {
+        self.interactions = interactions;
+        self
+    }
+
     /// Set whether the element is interactive.
     #[must_use]
     pub fn with_interactive(mut self, interactive: bool) -> Self {
@@ -435,10 +1364,116 @@ impl Element {
         self
     }
 
-    /// Check if a point (in canvas coordinates) is within this element.
+    /// Set whether the element is visible.
+    #[must_use]
+    pub fn with_visible(mut self, visible: bool) -> Self {
+        self.visible = visible;
+        self
+    }
+
+    /// Set the element opacity.
+    #[must_use]
+    pub fn with_opacity(mut self, opacity: f32) -> Self {
+        self.opacity = opacity;
+        self
+    }
+
+    /// Set whether the element is pinned to the viewport.
+    #[must_use]
+    pub fn with_screen_anchored(mut self, screen_anchored: bool) -> Self {
+        self.screen_anchored = screen_anchored;
+        self
+    }
+
+    /// Set whether the element holds confidential content.
+    #[must_use]
+    pub fn with_sensitive(mut self, sensitive: bool) -> Self {
+        self.sensitive = sensitive;
+        self
+    }
+
+    /// Position the element relative to another one.
+    #[must_use]
+    pub fn with_attachment(mut self, attachment: Attachment) -> Self {
+        self.attachment = Some(attachment);
+        self
+    }
+
+    /// Set the depth in front of the canvas plane on holographic displays.
+    #[must_use]
+    pub fn with_depth(mut self, depth: f32) -> Self {
+        self.depth = Some(depth);
+        self
+    }
+
+    const fn default_visible() -> bool {
+        true
+    }
+
+    const fn default_opacity() -> f32 {
+        1.0
+    }
+
+    /// Element opacity times style opacity, each clamped to `0.0..=1.0`
+    /// with non-finite values treated as opaque. Zero when hidden.
+    #[must_use]
+    pub fn effective_opacity(&self) -> f32 {
+        if !self.visible {
+            return 0.0;
+        }
+        let opacity = if self.opacity.is_finite() {
+            self.opacity.clamp(0.0, 1.0)
+        } else {
+            1.0
+        };
+        opacity * self.style.effective_opacity()
+    }
+
+    /// Attribute the element to the peer that created it.
+    #[must_use]
+    pub fn with_author(mut self, peer_id: impl Into<String>) -> Self {
+        let peer_id = peer_id.into();
+        self.created_by = Some(peer_id.clone());
+        self.last_modified_by = Some(peer_id);
+        self
+    }
+
+    /// Record that `peer_id` changed the element.
+    pub fn touch(&mut self, peer_id: impl Into<String>) {
+        self.last_modified_by = Some(peer_id.into());
+    }
+
+    /// Preferred size of the element's content.
+    ///
+    /// Containers (groups and overlay layers) have no intrinsic size and
+    /// report their current one.
+    #[must_use]
+    pub fn preferred_size(&self, ctx: &MeasureContext) -> Size {
+        measure::preferred_size(&self.kind, ctx)
+            .unwrap_or(Size::new(self.transform.width, self.transform.height))
+    }
+
+    /// Resize an auto-sized element to its preferred size, keeping its
+    /// top-left corner.
+    ///
+    /// Call after changing the content. Returns `true` if the size changed;
+    /// elements without [`Transform::auto_size`] are left alone.
+    pub fn apply_auto_size(&mut self, ctx: &MeasureContext) -> bool {
+        if !self.transform.auto_size {
+            return false;
+        }
+        let size = self.preferred_size(ctx);
+        let changed = (size.width - self.transform.width).abs() > f32::EPSILON
+            || (size.height - self.transform.height).abs() > f32::EPSILON;
+        self.transform.width = size.width;
+        self.transform.height = size.height;
+        changed
+    }
+
+    /// Check if a point (in canvas coordinates) is within this element,
+    /// taking its rotation into account.
     #[must_use]
     pub fn contains_point(&self, x: f32, y: f32) -> bool {
-        let t = &self.transform;
-        x >= t.x && x <= t.x + t.width && y >= t.y && y <= t.y + t.height
+        OrientedRect::from_transform(&self.transform).contains_point(x, y)
     }
 }
@@ -0,0 +1,208 @@
+//! # Wire Encodings
+//!
+//! Serialization formats for scene documents and sync messages.
+//!
+//! JSON is always available and stays the default. With the `msgpack`
+//! feature, [`Encoding::MessagePack`] provides a compact binary encoding that
+//! is much smaller for large scenes (image data, chart series) and cheaper to
+//! parse. Both encodings round-trip every scene type:
+//!
+//! ```text
+//! decode(encode(document)) == document
+//! ```
+//!
+//! `MessagePack` values are written with field names (maps, not arrays) so
+//! tagged enums such as `ElementKind` and optional fields decode the same way
+//! they do from JSON.
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use thiserror::Error;
+
+/// Errors from encoding or decoding a value.
+#[derive(Debug, Error)]
+pub enum EncodingError {
+    /// JSON encoding or decoding failed.
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+
+    /// `MessagePack` encoding failed.
+    #[cfg(feature = "msgpack")]
+    #[error("MessagePack encode error: {0}")]
+    MessagePackEncode(#[from] rmp_serde::encode::Error),
+
+    /// `MessagePack` decoding failed.
+    #[cfg(feature = "msgpack")]
+    #[error("MessagePack decode error: {0}")]
+    MessagePackDecode(#[from] rmp_serde::decode::Error),
+}
+
+/// A wire encoding for canvas data.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Encoding {
+    /// UTF-8 JSON (the default).
+    #[default]
+    Json,
+    /// Binary `MessagePack`.
+    #[cfg(feature = "msgpack")]
+    MessagePack,
+}
+
+impl Encoding {
+    /// Look up an encoding by name (`json`, `msgpack` or `messagepack`).
+    ///
+    /// Returns `None` for unknown names or encodings not compiled in.
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "json" => Some(Self::Json),
+            #[cfg(feature = "msgpack")]
+            "msgpack" | "messagepack" => Some(Self::MessagePack),
+            _ => None,
+        }
+    }
+
+    /// Short name of the encoding, as accepted by [`Encoding::from_name`].
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            #[cfg(feature = "msgpack")]
+            Self::MessagePack => "msgpack",
+        }
+    }
+
+    /// MIME type of encoded data.
+    #[must_use]
+    pub const fn mime_type(self) -> &'static str {
+        match self {
+            Self::Json => "application/json",
+            #[cfg(feature = "msgpack")]
+            Self::MessagePack => "application/msgpack",
+        }
+    }
+
+    /// Whether encoded data is binary (not UTF-8 text).
+    #[must_use]
+    pub const fn is_binary(self) -> bool {
+        match self {
+            Self::Json => false,
+            #[cfg(feature = "msgpack")]
+            Self::MessagePack => true,
+        }
+    }
+
+    /// Encode a value.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the value cannot be serialized.
+    pub fn encode<T: Serialize + ?Sized>(self, value: &T) -> Result<Vec<u8>, EncodingError> {
+        match self {
+            Self::Json => Ok(serde_json::to_vec(value)?),
+            #[cfg(feature = "msgpack")]
+            Self::MessagePack => Ok(rmp_serde::to_vec_named(value)?),
+        }
+    }
+
+    /// Decode a value.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the bytes are not a valid encoding of `T`.
+    pub fn decode<T: DeserializeOwned>(self, bytes: &[u8]) -> Result<T, EncodingError> {
+        match self {
+            Self::Json => Ok(serde_json::from_slice(bytes)?),
+            #[cfg(feature = "msgpack")]
+            Self::MessagePack => Ok(rmp_serde::from_slice(bytes)?),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        Color, Element, ElementKind, Fill, GradientStop, Scene, SceneDocument, Spotlight, Style,
+        Transform,
+    };
+
+    fn sample_document() -> SceneDocument {
+        let mut scene = Scene::new(1280.0, 720.0);
+        let chart = scene.add_element(
+            Element::new(ElementKind::Chart {
+                chart_type: "bar".to_string(),
+                data: serde_json::json!({
+                    "title": "Revenue",
+                    "series": [{ "label": "Q1", "value": 1.5 }, { "label": "Q2", "value": -2 }],
+                    "stacked": false,
+                    "note": null
+                }),
+                binding: None,
+            })
+            .with_transform(Transform {
+                x: 10.5,
+                y: -4.25,
+                width: 320.0,
+                height: 200.0,
+                rotation: 0.1,
+                z_index: 3,
+                auto_size: false,
+            }),
+        );
+        let mut text = Element::new(ElementKind::Text {
+            content: "Hello ✓".to_string(),
+            font_size: 18.0,
+            color: Color::from_rgb(0x11_2233).into(),
+        });
+        text.style = Style::default()
+            .with_fill(Fill::LinearGradient {
+                angle: 45.0,
+                stops: vec![
+                    GradientStop::new(0.0, Color::WHITE),
+                    GradientStop::new(1.0, Color::BLACK),
+                ],
+            })
+            .with_corner_radius(6.0);
+        scene.add_element(text);
+        scene
+            .set_spotlight(Spotlight::new([chart]).with_timeout(1_000, 5_000))
+            .expect("spotlight");
+
+        SceneDocument::from_scene("session-1", &scene, 1_700_000_000_123)
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let document = sample_document();
+        let bytes = Encoding::Json.encode(&document).expect("encode");
+        let decoded: SceneDocument = Encoding::Json.decode(&bytes).expect("decode");
+        assert_eq!(decoded, document);
+    }
+
+    #[cfg(feature = "msgpack")]
+    #[test]
+    fn test_msgpack_round_trip_and_size() {
+        let document = sample_document();
+        let bytes = Encoding::MessagePack.encode(&document).expect("encode");
+        let decoded: SceneDocument = Encoding::MessagePack.decode(&bytes).expect("decode");
+        assert_eq!(decoded, document);
+
+        let json = Encoding::Json.encode(&document).expect("encode");
+        assert!(bytes.len() < json.len());
+    }
+
+    #[test]
+    fn test_encoding_names() {
+        assert_eq!(Encoding::from_name("JSON"), Some(Encoding::Json));
+        assert_eq!(Encoding::from_name("xml"), None);
+        assert!(!Encoding::Json.is_binary());
+        #[cfg(feature = "msgpack")]
+        {
+            let msgpack = Encoding::from_name("msgpack").expect("msgpack");
+            assert_eq!(msgpack.name(), "msgpack");
+            assert_eq!(msgpack.mime_type(), "application/msgpack");
+            assert!(msgpack.is_binary());
+        }
+    }
+}
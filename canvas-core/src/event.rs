@@ -181,6 +181,26 @@ pub enum Gesture {
         /// Rotation angle in radians.
         angle_radians: f32,
     },
+
+    /// Freehand loop drawn around an area, e.g. circling some elements.
+    Lasso {
+        /// Points along the loop in screen coordinates; the last joins the
+        /// first.
+        points: Vec<[f32; 2]>,
+    },
+}
+
+impl Gesture {
+    /// The area a region gesture encloses, as a polygon in screen
+    /// coordinates, or `None` for point and manipulation gestures and
+    /// loops with fewer than three points.
+    #[must_use]
+    pub fn region(&self) -> Option<&[[f32; 2]]> {
+        match self {
+            Self::Lasso { points } if points.len() >= 3 => Some(points),
+            _ => None,
+        }
+    }
 }
 
 /// All input events the canvas can receive.
@@ -0,0 +1,555 @@
+//! # Floor Plans
+//!
+//! Seats, zones and occupancy for `FloorPlan` elements.
+//!
+//! A floor plan draws a background image or vector outline, then a marker
+//! per seat or zone colored by its occupancy, with a summary caption:
+//!
+//! ```text
+//! ┌───────────────────────────────────┐
+//! │ ┌───────────┐        ● ● ●        │  zones (rectangles)
+//! │ │  Stage    │        ● ○ ●        │  seats (circles)
+//! │ └───────────┘        ○ ○ ◐        │
+//! ├───────────────────────────────────┤
+//! │ 4 free · 4 occupied · 1 reserved  │  caption
+//! └───────────────────────────────────┘
+//! ```
+//!
+//! Marker positions are in plan units (`0..plan_width`, `0..plan_height`),
+//! so a plan keeps its layout when the element is resized. [`FloorPlanLayout`]
+//! maps plan units to the element's local frame (origin at its unrotated
+//! top-left corner); the renderers and the interaction router share it, so a
+//! tap or drag hits the marker drawn under it.
+
+use serde::{Deserialize, Serialize};
+
+use crate::element::ElementKind;
+use crate::error::{CanvasError, CanvasResult};
+
+/// Most markers a floor plan may have.
+pub const MAX_FLOOR_MARKERS: usize = 500;
+
+/// Width and height of a seat without an explicit size, in plan units.
+pub const DEFAULT_SEAT_SIZE: f32 = 24.0;
+
+/// Width and height of a zone without an explicit size, in plan units.
+pub const DEFAULT_ZONE_SIZE: (f32, f32) = (120.0, 80.0);
+
+/// Whether a seat or zone is in use.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+#[serde(rename_all = "lowercase")]
+pub enum Occupancy {
+    /// Available.
+    #[default]
+    Free,
+    /// In use.
+    Occupied,
+    /// Held for someone.
+    Reserved,
+    /// Not available (e.g. broken or closed off).
+    Blocked,
+}
+
+impl Occupancy {
+    /// Every state, in caption order.
+    pub const ALL: [Self; 4] = [Self::Free, Self::Occupied, Self::Reserved, Self::Blocked];
+
+    /// State name as serialized, e.g. `"free"`.
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Free => "free",
+            Self::Occupied => "occupied",
+            Self::Reserved => "reserved",
+            Self::Blocked => "blocked",
+        }
+    }
+
+    /// Marker fill color.
+    #[must_use]
+    pub fn color(self) -> &'static str {
+        match self {
+            Self::Free => "#81c784",
+            Self::Occupied => "#e57373",
+            Self::Reserved => "#ffb74d",
+            Self::Blocked => "#90a4ae",
+        }
+    }
+}
+
+/// How a marker is drawn and hit-tested.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+#[serde(rename_all = "lowercase")]
+pub enum MarkerShape {
+    /// A single seat or desk, drawn as a circle.
+    #[default]
+    Seat,
+    /// An area such as a room or table, drawn as a rectangle.
+    Zone,
+}
+
+/// A seat or zone on a floor plan.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+pub struct FloorMarker {
+    /// Unique ID within the plan, e.g. `"A1"`.
+    pub id: String,
+    /// Text shown on the marker; the ID if empty.
+    #[serde(default)]
+    pub label: String,
+    /// Seat or zone.
+    #[serde(default)]
+    pub shape: MarkerShape,
+    /// Center X in plan units.
+    pub x: f32,
+    /// Center Y in plan units.
+    pub y: f32,
+    /// Width in plan units; defaults by shape.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub width: Option<f32>,
+    /// Height in plan units; defaults by shape.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub height: Option<f32>,
+    /// Whether the seat or zone is in use.
+    #[serde(default)]
+    pub occupancy: Occupancy,
+    /// Who occupies or reserved it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub occupant: Option<String>,
+}
+
+impl FloorMarker {
+    /// Create a free marker centered on (`x`, `y`) with its default size.
+    #[must_use]
+    pub fn new(id: impl Into<String>, shape: MarkerShape, x: f32, y: f32) -> Self {
+        Self {
+            id: id.into(),
+            label: String::new(),
+            shape,
+            x,
+            y,
+            width: None,
+            height: None,
+            occupancy: Occupancy::Free,
+            occupant: None,
+        }
+    }
+
+    /// Width and height in plan units.
+    #[must_use]
+    pub fn size(&self) -> (f32, f32) {
+        let (width, height) = match self.shape {
+            MarkerShape::Seat => (DEFAULT_SEAT_SIZE, DEFAULT_SEAT_SIZE),
+            MarkerShape::Zone => DEFAULT_ZONE_SIZE,
+        };
+        (self.width.unwrap_or(width), self.height.unwrap_or(height))
+    }
+
+    /// Text shown on the marker.
+    #[must_use]
+    pub fn display_label(&self) -> &str {
+        if self.label.is_empty() {
+            &self.id
+        } else {
+            &self.label
+        }
+    }
+}
+
+/// An outline drawn on the plan, such as a wall or room boundary.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+pub struct PlanPath {
+    /// Points in plan units, `[x, y]`.
+    pub points: Vec<[f32; 2]>,
+    /// Whether the last point joins the first.
+    #[serde(default)]
+    pub closed: bool,
+}
+
+/// Number of markers in each occupancy state.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OccupancySummary {
+    /// Available markers.
+    pub free: usize,
+    /// Markers in use.
+    pub occupied: usize,
+    /// Markers held for someone.
+    pub reserved: usize,
+    /// Unavailable markers.
+    pub blocked: usize,
+}
+
+impl OccupancySummary {
+    /// Count the states of `markers`.
+    #[must_use]
+    pub fn from_markers(markers: &[FloorMarker]) -> Self {
+        let mut summary = Self::default();
+        for marker in markers {
+            *summary.count_mut(marker.occupancy) += 1;
+        }
+        summary
+    }
+
+    /// Markers in `state`.
+    #[must_use]
+    pub fn count(&self, state: Occupancy) -> usize {
+        match state {
+            Occupancy::Free => self.free,
+            Occupancy::Occupied => self.occupied,
+            Occupancy::Reserved => self.reserved,
+            Occupancy::Blocked => self.blocked,
+        }
+    }
+
+    fn count_mut(&mut self, state: Occupancy) -> &mut usize {
+        match state {
+            Occupancy::Free => &mut self.free,
+            Occupancy::Occupied => &mut self.occupied,
+            Occupancy::Reserved => &mut self.reserved,
+            Occupancy::Blocked => &mut self.blocked,
+        }
+    }
+
+    /// Caption text listing the states in use, e.g.
+    /// `"4 free · 4 occupied · 1 reserved"`.
+    #[must_use]
+    pub fn caption(&self) -> String {
+        let parts: Vec<String> = Occupancy::ALL
+            .into_iter()
+            .filter(|&state| self.count(state) > 0)
+            .map(|state| format!("{} {}", self.count(state), state.as_str()))
+            .collect();
+        if parts.is_empty() {
+            "No seats".to_string()
+        } else {
+            parts.join(" · ")
+        }
+    }
+}
+
+/// Check a floor plan can be stored.
+///
+/// # Errors
+///
+/// Returns [`CanvasError::InvalidOperation`] if the plan size is not
+/// positive, there are more than [`MAX_FLOOR_MARKERS`] markers, a marker ID
+/// is blank or repeated, or a coordinate or size is not finite and positive
+/// where required.
+pub fn validate(
+    plan_width: f32,
+    plan_height: f32,
+    paths: &[PlanPath],
+    markers: &[FloorMarker],
+) -> CanvasResult<()> {
+    let invalid = |message: String| Err(CanvasError::InvalidOperation(message));
+    if !(plan_width.is_finite() && plan_width > 0.0 && plan_height.is_finite() && plan_height > 0.0)
+    {
+        return invalid(format!(
+            "Floor plan size must be positive, got {plan_width} x {plan_height}"
+        ));
+    }
+    if markers.len() > MAX_FLOOR_MARKERS {
+        return invalid(format!(
+            "Floor plans hold up to {MAX_FLOOR_MARKERS} markers, got {}",
+            markers.len()
+        ));
+    }
+    if paths
+        .iter()
+        .flat_map(|path| &path.points)
+        .any(|[x, y]| !x.is_finite() || !y.is_finite())
+    {
+        return invalid("Floor plan paths need finite points".to_string());
+    }
+    let mut ids = std::collections::HashSet::new();
+    for marker in markers {
+        if marker.id.trim().is_empty() {
+            return invalid("Floor plan markers need an ID".to_string());
+        }
+        if !ids.insert(marker.id.as_str()) {
+            return invalid(format!("Duplicate floor plan marker: {}", marker.id));
+        }
+        let (width, height) = marker.size();
+        let positive = |v: f32| v.is_finite() && v > 0.0;
+        if !(marker.x.is_finite() && marker.y.is_finite() && positive(width) && positive(height)) {
+            return invalid(format!(
+                "Marker {} needs a finite position and positive size",
+                marker.id
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Move a marker to (`x`, `y`) in plan units, clamped to the plan.
+///
+/// # Errors
+///
+/// Returns [`CanvasError::InvalidOperation`] if `kind` is not a floor plan,
+/// the plan is locked, the marker does not exist or the position is not
+/// finite.
+pub fn move_marker(kind: &mut ElementKind, marker: &str, x: f32, y: f32) -> CanvasResult<()> {
+    let ElementKind::FloorPlan {
+        plan_width,
+        plan_height,
+        markers,
+        locked,
+        ..
+    } = kind
+    else {
+        return Err(CanvasError::InvalidOperation(format!(
+            "{} is not a floor plan",
+            kind.type_name()
+        )));
+    };
+    if *locked {
+        return Err(CanvasError::InvalidOperation(
+            "Floor plan is locked".to_string(),
+        ));
+    }
+    if !x.is_finite() || !y.is_finite() {
+        return Err(CanvasError::InvalidOperation(format!(
+            "Marker {marker} needs a finite position"
+        )));
+    }
+    let Some(target) = markers.iter_mut().find(|m| m.id == marker) else {
+        return Err(CanvasError::InvalidOperation(format!(
+            "Floor plan has no marker {marker}"
+        )));
+    };
+    target.x = x.clamp(0.0, *plan_width);
+    target.y = y.clamp(0.0, *plan_height);
+    Ok(())
+}
+
+/// A marker's box in the element's local frame.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MarkerRect {
+    /// Left edge.
+    pub x: f32,
+    /// Top edge.
+    pub y: f32,
+    /// Width.
+    pub width: f32,
+    /// Height.
+    pub height: f32,
+}
+
+impl MarkerRect {
+    /// Font size for the marker's label.
+    #[must_use]
+    pub fn font_size(&self) -> f32 {
+        (self.width.min(self.height) * 0.45).clamp(6.0, 14.0)
+    }
+}
+
+/// Geometry of a floor plan element: the plan scaled to fit above the
+/// caption, centered horizontally.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FloorPlanLayout {
+    /// Element pixels per plan unit.
+    pub scale: f32,
+    /// Left edge of the plan area.
+    pub offset_x: f32,
+    /// Top edge of the plan area.
+    pub offset_y: f32,
+    /// Width of the plan area.
+    pub plan_width: f32,
+    /// Height of the plan area.
+    pub plan_height: f32,
+    /// Top edge of the caption band.
+    pub caption_y: f32,
+    /// Height of the caption band.
+    pub caption_height: f32,
+}
+
+impl FloorPlanLayout {
+    /// Space around the plan.
+    pub const PADDING: f32 = 6.0;
+
+    /// Lay out a `plan_width` × `plan_height` plan in a `width` × `height`
+    /// element.
+    #[must_use]
+    pub fn new(plan_width: f32, plan_height: f32, width: f32, height: f32) -> Self {
+        let pad = Self::PADDING;
+        let caption_height = (height * 0.1).clamp(14.0, 24.0).min(height / 4.0);
+        let area_width = (width - 2.0 * pad).max(0.0);
+        let area_height = (height - caption_height - 2.0 * pad).max(0.0);
+        let scale = if plan_width > 0.0 && plan_height > 0.0 {
+            (area_width / plan_width).min(area_height / plan_height)
+        } else {
+            0.0
+        };
+        let (scaled_width, scaled_height) = (plan_width * scale, plan_height * scale);
+        Self {
+            scale,
+            offset_x: pad + (area_width - scaled_width) / 2.0,
+            offset_y: pad + (area_height - scaled_height) / 2.0,
+            plan_width: scaled_width,
+            plan_height: scaled_height,
+            caption_y: height - caption_height,
+            caption_height,
+        }
+    }
+
+    /// Map a plan point to the element's local frame.
+    #[must_use]
+    pub fn to_local(&self, x: f32, y: f32) -> (f32, f32) {
+        (
+            self.offset_x + x * self.scale,
+            self.offset_y + y * self.scale,
+        )
+    }
+
+    /// Map a local point to plan units.
+    #[must_use]
+    pub fn to_plan(&self, x: f32, y: f32) -> (f32, f32) {
+        if self.scale <= 0.0 {
+            return (0.0, 0.0);
+        }
+        (
+            (x - self.offset_x) / self.scale,
+            (y - self.offset_y) / self.scale,
+        )
+    }
+
+    /// A marker's box in the local frame.
+    #[must_use]
+    pub fn marker_rect(&self, marker: &FloorMarker) -> MarkerRect {
+        let (width, height) = marker.size();
+        let (x, y) = self.to_local(marker.x - width / 2.0, marker.y - height / 2.0);
+        MarkerRect {
+            x,
+            y,
+            width: width * self.scale,
+            height: height * self.scale,
+        }
+    }
+
+    /// Index of the marker under a local point, if any.
+    ///
+    /// Seats sit on top of zones, and later markers on top of earlier ones.
+    #[must_use]
+    pub fn marker_at(&self, markers: &[FloorMarker], x: f32, y: f32) -> Option<usize> {
+        let hit = |shape: MarkerShape| {
+            markers.iter().enumerate().rev().find_map(|(i, marker)| {
+                if marker.shape != shape {
+                    return None;
+                }
+                let rect = self.marker_rect(marker);
+                let (dx, dy) = (
+                    x - (rect.x + rect.width / 2.0),
+                    y - (rect.y + rect.height / 2.0),
+                );
+                let inside = match shape {
+                    // Ellipse test, so seats hit where they are drawn
+                    MarkerShape::Seat => {
+                        let (rx, ry) = (rect.width / 2.0, rect.height / 2.0);
+                        rx > 0.0 && ry > 0.0 && (dx / rx).powi(2) + (dy / ry).powi(2) <= 1.0
+                    }
+                    MarkerShape::Zone => {
+                        dx.abs() <= rect.width / 2.0 && dy.abs() <= rect.height / 2.0
+                    }
+                };
+                inside.then_some(i)
+            })
+        };
+        hit(MarkerShape::Seat).or_else(|| hit(MarkerShape::Zone))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plan(markers: Vec<FloorMarker>) -> ElementKind {
+        ElementKind::FloorPlan {
+            background: None,
+            plan_width: 400.0,
+            plan_height: 200.0,
+            paths: Vec::new(),
+            markers,
+            locked: false,
+        }
+    }
+
+    #[test]
+    fn test_layout_maps_plan_units() {
+        // 400 × 200 plan in a 412 × 236 element: 400 × 200 area at scale 1
+        let layout = FloorPlanLayout::new(400.0, 200.0, 412.0, 236.0);
+        assert!((layout.scale - 1.0).abs() < f32::EPSILON);
+        assert!((layout.caption_height - 23.6).abs() < 1e-4);
+        let (x, y) = layout.to_local(100.0, 50.0);
+        assert_eq!(layout.to_plan(x, y), (100.0, 50.0));
+
+        let seat = FloorMarker::new("A1", MarkerShape::Seat, 100.0, 50.0);
+        let stage = FloorMarker {
+            width: Some(200.0),
+            height: Some(100.0),
+            ..FloorMarker::new("stage", MarkerShape::Zone, 100.0, 50.0)
+        };
+        let markers = vec![seat, stage];
+        // The seat sits on top of the zone around it
+        assert_eq!(layout.marker_at(&markers, x, y), Some(0));
+        assert_eq!(layout.marker_at(&markers, x + 40.0, y), Some(1));
+        // Outside the seat's circle but inside its box corner
+        let (cx, cy) = layout.to_local(300.0, 150.0);
+        assert_eq!(layout.marker_at(&markers, cx, cy), None);
+    }
+
+    #[test]
+    fn test_move_marker_and_summary() {
+        let mut occupied = FloorMarker::new("A2", MarkerShape::Seat, 60.0, 20.0);
+        occupied.occupancy = Occupancy::Occupied;
+        let mut kind = plan(vec![
+            FloorMarker::new("A1", MarkerShape::Seat, 20.0, 20.0),
+            occupied,
+        ]);
+
+        move_marker(&mut kind, "A1", 500.0, 80.0).expect("move");
+        let ElementKind::FloorPlan { markers, .. } = &kind else {
+            panic!("expected a floor plan");
+        };
+        // Clamped to the plan
+        assert!((markers[0].x - 400.0).abs() < f32::EPSILON);
+        assert!((markers[0].y - 80.0).abs() < f32::EPSILON);
+
+        let summary = OccupancySummary::from_markers(markers);
+        assert_eq!(summary.caption(), "1 free · 1 occupied");
+        assert_eq!(OccupancySummary::default().caption(), "No seats");
+
+        assert!(move_marker(&mut kind, "Z9", 0.0, 0.0).is_err());
+        if let ElementKind::FloorPlan { locked, .. } = &mut kind {
+            *locked = true;
+        }
+        assert!(move_marker(&mut kind, "A1", 0.0, 0.0).is_err());
+    }
+
+    #[test]
+    fn test_validate() {
+        let seat = |id: &str| FloorMarker::new(id, MarkerShape::Seat, 10.0, 10.0);
+        assert!(validate(400.0, 200.0, &[], &[seat("A1"), seat("A2")]).is_ok());
+        assert!(validate(0.0, 200.0, &[], &[]).is_err());
+        assert!(validate(400.0, 200.0, &[], &[seat("A1"), seat("A1")]).is_err());
+        assert!(validate(400.0, 200.0, &[], &[seat(" ")]).is_err());
+        let flat = FloorMarker {
+            width: Some(0.0),
+            ..seat("A1")
+        };
+        assert!(validate(400.0, 200.0, &[], &[flat]).is_err());
+        let path = PlanPath {
+            points: vec![[0.0, 0.0], [f32::NAN, 1.0]],
+            closed: false,
+        };
+        assert!(validate(400.0, 200.0, &[path], &[]).is_err());
+
+        let parsed: FloorMarker =
+            serde_json::from_str(r#"{"id":"A1","x":10,"y":20}"#).expect("parse");
+        assert_eq!(parsed.shape, MarkerShape::Seat);
+        assert_eq!(parsed.occupancy, Occupancy::Free);
+        assert_eq!(parsed.display_label(), "A1");
+    }
+}
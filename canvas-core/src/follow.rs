@@ -0,0 +1,429 @@
+//! # Agent Follow Camera
+//!
+//! Keeps the viewer's camera on whatever the agent is working on.
+//!
+//! When enabled, elements changed by the agent are recorded as focus targets
+//! and the scene's zoom/pan eases toward a frame that fits them. To avoid a
+//! jittery camera, the frame is only retargeted when the targets drift out of
+//! view or the ideal zoom differs noticeably from the current one. Any user
+//! interaction (pan, zoom, touch) pauses following for a short period.
+//!
+//! ```text
+//! agent updates chart (off-screen)
+//!   → record_agent_changes([chart])
+//!   → update(scene, now) eases pan/zoom until the chart is framed
+//! ```
+//!
+//! Time is passed in as milliseconds so the same logic runs natively and in
+//! WASM (where `std::time::Instant` is unavailable).
+
+use serde::{Deserialize, Serialize};
+
+use crate::camera::{FitOptions, SceneBounds};
+use crate::element::ElementId;
+use crate::scene::Scene;
+
+/// Configuration for the agent follow camera.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct FollowConfig {
+    /// Screen-space padding around the framed elements, in pixels.
+    pub padding: f32,
+    /// Minimum zoom level the camera will use.
+    pub min_zoom: f32,
+    /// Maximum zoom level the camera will use.
+    pub max_zoom: f32,
+    /// Relative zoom difference tolerated before retargeting (0.2 = 20%).
+    pub zoom_hysteresis: f32,
+    /// How long user interaction pauses following, in milliseconds.
+    pub user_pause_ms: u64,
+    /// Fraction of the remaining distance covered per update (0.0 to 1.0).
+    pub smoothing: f32,
+}
+
+impl Default for FollowConfig {
+    fn default() -> Self {
+        Self {
+            padding: 48.0,
+            min_zoom: 0.25,
+            max_zoom: 2.0,
+            zoom_hysteresis: 0.2,
+            user_pause_ms: 5_000,
+            smoothing: 0.2,
+        }
+    }
+}
+
+impl FollowConfig {
+    /// Padding and zoom limits used to frame the agent's targets.
+    #[must_use]
+    pub const fn fit_options(&self) -> FitOptions {
+        FitOptions {
+            padding: self.padding,
+            min_zoom: self.min_zoom,
+            max_zoom: self.max_zoom,
+        }
+    }
+}
+
+/// A camera position: zoom level plus pan offset.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+pub struct CameraFrame {
+    /// Zoom level (1.0 = 100%).
+    pub zoom: f32,
+    /// Pan offset X.
+    pub pan_x: f32,
+    /// Pan offset Y.
+    pub pan_y: f32,
+}
+
+impl CameraFrame {
+    /// Read the current camera position of a scene.
+    #[must_use]
+    pub fn from_scene(scene: &Scene) -> Self {
+        Self {
+            zoom: scene.zoom,
+            pan_x: scene.pan_x,
+            pan_y: scene.pan_y,
+        }
+    }
+
+    /// The default camera: zoom 1.0 with no pan.
+    #[must_use]
+    pub const fn reset() -> Self {
+        Self {
+            zoom: 1.0,
+            pan_x: 0.0,
+            pan_y: 0.0,
+        }
+    }
+
+    /// Write this camera position to a scene.
+    pub fn apply_to(&self, scene: &mut Scene) {
+        scene.set_camera(self.zoom, self.pan_x, self.pan_y);
+    }
+
+    /// Interpolate toward `target` by `t` (0.0 = self, 1.0 = target).
+    #[must_use]
+    pub fn lerp(&self, target: &Self, t: f32) -> Self {
+        Self {
+            zoom: self.zoom + (target.zoom - self.zoom) * t,
+            pan_x: self.pan_x + (target.pan_x - self.pan_x) * t,
+            pan_y: self.pan_y + (target.pan_y - self.pan_y) * t,
+        }
+    }
+
+    /// Compute the frame that fits the given elements in the scene's viewport.
+    ///
+    /// Uses the padding and zoom limits from `config`. Returns `None` if none
+    /// of the elements exist in the scene or the viewport has no size.
+    #[must_use]
+    pub fn fit_elements(scene: &Scene, ids: &[ElementId], config: &FollowConfig) -> Option<Self> {
+        let bounds = scene.bounds_of(ids)?;
+        Self::fit_bounds(scene, &bounds, &config.fit_options())
+    }
+
+    /// Compute the frame that fits `bounds` in the scene's viewport.
+    ///
+    /// Returns `None` if the viewport has no size.
+    #[must_use]
+    pub fn fit_bounds(scene: &Scene, bounds: &SceneBounds, options: &FitOptions) -> Option<Self> {
+        if scene.viewport_width <= 0.0 || scene.viewport_height <= 0.0 {
+            return None;
+        }
+        let pad = options.padding.max(0.0) * 2.0;
+        let avail_w = (scene.viewport_width - pad).max(1.0);
+        let avail_h = (scene.viewport_height - pad).max(1.0);
+
+        let zoom = (avail_w / bounds.width().max(1.0))
+            .min(avail_h / bounds.height().max(1.0))
+            .clamp(options.min_zoom, options.max_zoom.max(options.min_zoom));
+
+        let (center_x, center_y) = bounds.center();
+        Some(Self {
+            zoom,
+            pan_x: scene.viewport_width / 2.0 - center_x * zoom,
+            pan_y: scene.viewport_height / 2.0 - center_y * zoom,
+        })
+    }
+
+    /// Check whether two frames are close enough to be considered equal.
+    #[must_use]
+    pub fn approx_eq(&self, other: &Self) -> bool {
+        (self.zoom - other.zoom).abs() < 0.001
+            && (self.pan_x - other.pan_x).abs() < 0.5
+            && (self.pan_y - other.pan_y).abs() < 0.5
+    }
+}
+
+/// Camera controller that frames the elements most recently changed by the agent.
+#[derive(Debug, Clone)]
+pub struct AgentFollow {
+    config: FollowConfig,
+    enabled: bool,
+    /// Elements most recently changed by the agent.
+    targets: Vec<ElementId>,
+    /// Frame the camera is easing toward.
+    goal: Option<CameraFrame>,
+    /// Following is paused until this timestamp (ms).
+    paused_until_ms: u64,
+}
+
+impl Default for AgentFollow {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AgentFollow {
+    /// Create a disabled follow controller with default configuration.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::with_config(FollowConfig::default())
+    }
+
+    /// Create a disabled follow controller with custom configuration.
+    #[must_use]
+    pub fn with_config(config: FollowConfig) -> Self {
+        Self {
+            config,
+            enabled: false,
+            targets: Vec::new(),
+            goal: None,
+            paused_until_ms: 0,
+        }
+    }
+
+    /// Get the configuration.
+    #[must_use]
+    pub fn config(&self) -> &FollowConfig {
+        &self.config
+    }
+
+    /// Enable or disable following. Disabling drops any pending target.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        if !enabled {
+            self.targets.clear();
+            self.goal = None;
+        }
+    }
+
+    /// Check whether following is enabled.
+    #[must_use]
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Check whether following is temporarily paused by user interaction.
+    #[must_use]
+    pub fn is_paused(&self, now_ms: u64) -> bool {
+        now_ms < self.paused_until_ms
+    }
+
+    /// Timestamp (ms) at which following resumes after user interaction.
+    #[must_use]
+    pub fn paused_until_ms(&self) -> u64 {
+        self.paused_until_ms
+    }
+
+    /// Elements currently being followed.
+    #[must_use]
+    pub fn targets(&self) -> &[ElementId] {
+        &self.targets
+    }
+
+    /// Record the elements changed by the latest agent update.
+    ///
+    /// Replaces the previous targets. An empty list is ignored so the camera
+    /// stays on the last thing the agent touched.
+    pub fn record_agent_changes(&mut self, ids: impl IntoIterator<Item = ElementId>) {
+        if !self.enabled {
+            return;
+        }
+        let ids: Vec<_> = ids.into_iter().collect();
+        if !ids.is_empty() {
+            self.targets = ids;
+        }
+    }
+
+    /// Record a user camera interaction, pausing following.
+    pub fn record_user_interaction(&mut self, now_ms: u64) {
+        self.paused_until_ms = now_ms.saturating_add(self.config.user_pause_ms);
+        self.goal = None;
+    }
+
+    /// Compute the frame that fits the current targets in the viewport.
+    ///
+    /// Returns `None` if none of the targets exist in the scene or the
+    /// viewport has no size.
+    #[must_use]
+    pub fn target_frame(&self, scene: &Scene) -> Option<CameraFrame> {
+        CameraFrame::fit_elements(scene, &self.targets, &self.config)
+    }
+
+    /// Advance the camera one step toward the agent's focus.
+    ///
+    /// Call once per frame. Returns `true` if the scene camera moved.
+    pub fn update(&mut self, scene: &mut Scene, now_ms: u64) -> bool {
+        if !self.enabled || self.is_paused(now_ms) {
+            return false;
+        }
+
+        if let Some(ideal) = self.target_frame(scene) {
+            if self.needs_retarget(scene, &ideal) {
+                self.goal = Some(ideal);
+            }
+        }
+
+        let Some(goal) = self.goal else {
+            return false;
+        };
+
+        let current = CameraFrame::from_scene(scene);
+        let next = current.lerp(&goal, self.config.smoothing.clamp(0.01, 1.0));
+        if next.approx_eq(&goal) {
+            goal.apply_to(scene);
+            self.goal = None;
+        } else {
+            next.apply_to(scene);
+        }
+        true
+    }
+
+    /// Decide whether the camera should move toward `ideal`.
+    ///
+    /// The camera stays put while all targets are visible and the zoom is
+    /// within the hysteresis band of the ideal zoom.
+    fn needs_retarget(&self, scene: &Scene, ideal: &CameraFrame) -> bool {
+        if let Some(goal) = &self.goal {
+            if goal.approx_eq(ideal) {
+                return false;
+            }
+        }
+
+        let Some(bounds) = scene.bounds_of(&self.targets) else {
+            return false;
+        };
+        let zoom = scene.zoom;
+        let visible = bounds.min_x * zoom + scene.pan_x >= 0.0
+            && bounds.min_y * zoom + scene.pan_y >= 0.0
+            && bounds.max_x * zoom + scene.pan_x <= scene.viewport_width
+            && bounds.max_y * zoom + scene.pan_y <= scene.viewport_height;
+        let zoom_ok =
+            (zoom - ideal.zoom).abs() <= ideal.zoom * self.config.zoom_hysteresis.max(0.0);
+
+        !(visible && zoom_ok)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Color, Element, ElementKind, Transform};
+
+    fn scene_with_element(x: f32, y: f32) -> (Scene, ElementId) {
+        let mut scene = Scene::new(800.0, 600.0);
+        let element = Element::new(ElementKind::Text {
+            content: "Revenue".to_string(),
+            font_size: 16.0,
+            color: Color::BLACK.into(),
+        })
+        .with_transform(Transform {
+            x,
+            y,
+            width: 200.0,
+            height: 100.0,
+            rotation: 0.0,
+            z_index: 0,
+            auto_size: false,
+        });
+        let id = scene.add_element(element);
+        (scene, id)
+    }
+
+    fn settle(follow: &mut AgentFollow, scene: &mut Scene, now_ms: u64) {
+        for _ in 0..200 {
+            if !follow.update(scene, now_ms) {
+                break;
+            }
+        }
+    }
+
+    #[test]
+    fn test_disabled_does_not_move_camera() {
+        let (mut scene, id) = scene_with_element(2000.0, 2000.0);
+        let mut follow = AgentFollow::new();
+        follow.record_agent_changes([id]);
+
+        assert!(follow.targets().is_empty());
+        assert!(!follow.update(&mut scene, 0));
+        assert_eq!(
+            CameraFrame::from_scene(&scene),
+            CameraFrame::from_scene(&Scene::new(800.0, 600.0))
+        );
+    }
+
+    #[test]
+    fn test_frames_offscreen_element() {
+        let (mut scene, id) = scene_with_element(2000.0, 2000.0);
+        let mut follow = AgentFollow::new();
+        follow.set_enabled(true);
+        follow.record_agent_changes([id]);
+
+        settle(&mut follow, &mut scene, 0);
+
+        // Element center (2100, 2050) should now be at the viewport center
+        let screen_x = 2100.0 * scene.zoom + scene.pan_x;
+        let screen_y = 2050.0 * scene.zoom + scene.pan_y;
+        assert!((screen_x - 400.0).abs() < 1.0);
+        assert!((screen_y - 300.0).abs() < 1.0);
+        assert!(scene.zoom <= follow.config().max_zoom);
+    }
+
+    #[test]
+    fn test_hysteresis_keeps_visible_element_still() {
+        let (mut scene, id) = scene_with_element(100.0, 100.0);
+        let mut follow = AgentFollow::new();
+        follow.set_enabled(true);
+        follow.record_agent_changes([id]);
+        settle(&mut follow, &mut scene, 0);
+        let framed = CameraFrame::from_scene(&scene);
+
+        // Nudge the element slightly; it stays visible so the camera holds
+        if let Some(element) = scene.get_element_mut(id) {
+            element.transform.x += 5.0;
+        }
+        follow.record_agent_changes([id]);
+        assert!(!follow.update(&mut scene, 10));
+        assert_eq!(CameraFrame::from_scene(&scene), framed);
+    }
+
+    #[test]
+    fn test_user_interaction_pauses_following() {
+        let (mut scene, id) = scene_with_element(2000.0, 2000.0);
+        let mut follow = AgentFollow::new();
+        follow.set_enabled(true);
+        follow.record_user_interaction(1_000);
+        follow.record_agent_changes([id]);
+
+        assert!(follow.is_paused(2_000));
+        assert!(!follow.update(&mut scene, 2_000));
+
+        let resume = 1_000 + follow.config().user_pause_ms;
+        assert!(!follow.is_paused(resume));
+        assert!(follow.update(&mut scene, resume));
+    }
+
+    #[test]
+    fn test_missing_targets_are_ignored() {
+        let (mut scene, id) = scene_with_element(0.0, 0.0);
+        let mut follow = AgentFollow::new();
+        follow.set_enabled(true);
+        follow.record_agent_changes([id]);
+        scene.remove_element(&id).expect("should remove");
+
+        assert!(follow.target_frame(&scene).is_none());
+        assert!(!follow.update(&mut scene, 0));
+    }
+}
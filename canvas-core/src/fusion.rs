@@ -9,21 +9,67 @@
 //! User touches element X while saying "Make this red"
 //!   → FusedIntent { element: X, command: "Make this red" }
 //! ```
+//!
+//! When the touch lands between several elements, or the speech recognizer
+//! is unsure, fusion asks the user which element they meant instead of
+//! guessing:
+//!
+//! ```text
+//! User touches between A and B while saying "Delete this"
+//!   → DisambiguationRequest { candidates: [1: A, 2: B] }
+//! User says "number two" (or taps B)
+//!   → FusedIntent { element: B, command: "Delete this" }
+//! ```
+//!
+//! Touches and voice results are matched by when they happened, not when
+//! they arrived. Each source's timestamps are mapped onto the local clock
+//! with an estimate of that source's clock skew, and a final transcript is
+//! placed back at the start of its utterance, so a slow recognizer still
+//! fuses with the touch made while the user was speaking:
+//!
+//! ```text
+//!  touch    interim "make"        final "make this red"
+//!    │         │                          │ arrives
+//!    ▼         ▼                          ▼
+//!  ──●─────────●━━━━━━ recognition ━━━━━━━●──▶ local time
+//!    └─ within fusion_window of ─┘
+//!       the utterance start
+//! ```
+//!
+//! Without interim results the utterance start is estimated from the
+//! recognition latency observed so far.
+//!
+//! A recognized region gesture can stand in for the touch, scoping the
+//! command to an area instead of a point:
+//!
+//! ```text
+//! User circles A, B and C while saying "Summarize these"
+//!   → FusedIntent { region: [loop], element_ids: [A, B, C], command: "Summarize these" }
+//! ```
 
 use crate::element::ElementId;
-use crate::event::{InputEvent, TouchEvent, VoiceEvent};
+use crate::event::{Gesture, InputEvent, TouchEvent, VoiceEvent};
 use serde::{Deserialize, Serialize};
-use std::time::{Duration, Instant};
+use std::collections::VecDeque;
+use std::time::Duration;
 
 /// A fused intent combining touch and voice.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct FusedIntent {
     /// The voice transcript.
     pub transcript: String,
-    /// Touch location (x, y).
+    /// Touch location (x, y), or the center of the region.
     pub location: (f32, f32),
-    /// Target element if touch hit an element.
+    /// Target element if touch hit an element, or the only element in the
+    /// region.
     pub element_id: Option<ElementId>,
+    /// Area the command applies to, as a polygon in screen coordinates,
+    /// when a region gesture was fused instead of a touch.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub region: Option<Vec<[f32; 2]>>,
+    /// Elements inside the region, topmost first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub element_ids: Vec<ElementId>,
     /// Confidence of the voice recognition.
     pub confidence: f32,
     /// Timestamp of the fusion.
@@ -41,6 +87,70 @@ pub struct VoiceOnlyIntent {
     pub timestamp_ms: u64,
 }
 
+/// Distance from a touch, in screen pixels, within which elements are
+/// candidates for it.
+pub const DISAMBIGUATION_RADIUS: f32 = 24.0;
+
+/// Most candidates offered in one disambiguation prompt.
+pub const MAX_CANDIDATES: usize = 9;
+
+/// An element the user may have meant, shown with a numbered badge.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DisambiguationCandidate {
+    /// Number on the badge, starting at 1.
+    pub number: usize,
+    /// The candidate element.
+    pub element_id: ElementId,
+    /// Element bounds in canvas coordinates: x, y, width, height.
+    pub bounds: [f32; 4],
+}
+
+/// A fused command whose target is unclear, waiting for the user to pick a
+/// candidate by tapping it or saying its number.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DisambiguationRequest {
+    /// The voice transcript awaiting a target.
+    pub transcript: String,
+    /// Touch location (x, y).
+    pub location: (f32, f32),
+    /// Candidate elements, nearest first.
+    pub candidates: Vec<DisambiguationCandidate>,
+    /// Confidence of the voice recognition.
+    pub confidence: f32,
+    /// Timestamp of the voice command.
+    pub timestamp_ms: u64,
+}
+
+impl DisambiguationRequest {
+    /// The fused intent for the candidate numbered `number`, if any.
+    #[must_use]
+    pub fn choose(&self, number: usize) -> Option<FusedIntent> {
+        let candidate = self.candidates.iter().find(|c| c.number == number)?;
+        Some(self.fuse(candidate.element_id))
+    }
+
+    /// The fused intent for `element_id`, if it is a candidate.
+    #[must_use]
+    pub fn choose_element(&self, element_id: ElementId) -> Option<FusedIntent> {
+        self.candidates
+            .iter()
+            .any(|c| c.element_id == element_id)
+            .then(|| self.fuse(element_id))
+    }
+
+    fn fuse(&self, element_id: ElementId) -> FusedIntent {
+        FusedIntent {
+            transcript: self.transcript.clone(),
+            location: self.location,
+            element_id: Some(element_id),
+            region: None,
+            element_ids: Vec::new(),
+            confidence: self.confidence,
+            timestamp_ms: self.timestamp_ms,
+        }
+    }
+}
+
 /// Result of processing an input event.
 #[derive(Debug, Clone, PartialEq)]
 pub enum FusionResult {
@@ -48,26 +158,68 @@ pub enum FusionResult {
     Fused(FusedIntent),
     /// Voice-only command.
     VoiceOnly(VoiceOnlyIntent),
+    /// Touch and voice were fused, but the target is unclear; the user must
+    /// pick one of the candidates.
+    Disambiguate(DisambiguationRequest),
     /// Input was stored for potential fusion.
     Pending,
     /// No action needed.
     None,
 }
 
+/// Monotonic clock in milliseconds that fusion times event arrival with.
+pub type FusionClock = fn() -> f64;
+
+/// Default fusion clock: `std::time::Instant` on native targets. There is
+/// no portable clock on `wasm32`, so hosts there pass one via
+/// [`InputFusion::with_clock`].
+#[cfg(not(target_arch = "wasm32"))]
+fn monotonic_ms() -> f64 {
+    use std::sync::OnceLock;
+    use std::time::Instant;
+
+    static EPOCH: OnceLock<Instant> = OnceLock::new();
+    EPOCH.get_or_init(Instant::now).elapsed().as_secs_f64() * 1000.0
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+const DEFAULT_CLOCK: Option<FusionClock> = Some(monotonic_ms);
+#[cfg(target_arch = "wasm32")]
+const DEFAULT_CLOCK: Option<FusionClock> = None;
+
+/// Arrival delays kept per event source to estimate its clock skew.
+const SKEW_SAMPLES: usize = 32;
+
+/// Weight of the newest sample in the recognition latency average.
+const LATENCY_SMOOTHING: f64 = 0.25;
+
 /// Configuration for input fusion.
 #[derive(Debug, Clone)]
 pub struct FusionConfig {
-    /// Time window for fusion (how long touch waits for voice).
+    /// Time window for fusion: how long before an utterance starts a touch
+    /// still applies to it.
     pub fusion_window: Duration,
     /// Minimum confidence for voice recognition.
     pub min_confidence: f32,
+    /// Voice confidence below which a fused command asks the user to
+    /// confirm its target.
+    pub disambiguation_confidence: f32,
+    /// How long a disambiguation prompt waits for the user's choice.
+    pub disambiguation_timeout: Duration,
+    /// Longest recognition latency allowed for: caps how far back a final
+    /// transcript is placed and how long a touch waits beyond
+    /// `fusion_window` for a slow recognizer.
+    pub max_recognition_latency: Duration,
 }
 
 impl Default for FusionConfig {
     fn default() -> Self {
         Self {
-            fusion_window: Duration::from_millis(2000),
+            fusion_window: Duration::from_secs(2),
             min_confidence: 0.5,
+            disambiguation_confidence: 0.75,
+            disambiguation_timeout: Duration::from_secs(10),
+            max_recognition_latency: Duration::from_secs(5),
         }
     }
 }
@@ -77,20 +229,64 @@ impl Default for FusionConfig {
 /// Combines touch and voice inputs that occur within a configurable time window.
 #[derive(Debug)]
 pub struct InputFusion {
-    /// Pending touch event waiting for voice.
+    /// Pending touch or region gesture waiting for voice.
     pending_touch: Option<PendingTouch>,
+    /// Prompt waiting for the user to pick a target, and when it was made
+    /// (local ms).
+    pending_disambiguation: Option<(DisambiguationRequest, f64)>,
     /// Configuration.
     config: FusionConfig,
+    /// Local clock event arrival is timed with.
+    clock: Option<FusionClock>,
+    /// Skew of the touch timestamps' clock.
+    touch_clock: ClockSkew,
+    /// Skew of the voice timestamps' clock.
+    voice_clock: ClockSkew,
+    /// Local time of the first interim result of the utterance in progress.
+    utterance_start: Option<f64>,
+    /// Smoothed time from the start of an utterance to its final result, in
+    /// ms, once one has been observed.
+    recognition_latency: Option<f64>,
+}
+
+/// Estimates how an event source's clock relates to the local clock.
+///
+/// Each event's arrival delay (local arrival time minus its timestamp) is
+/// the clock offset plus transit time. The smallest recent delay is taken
+/// as the offset, so the fastest delivery counts as instant and slower ones
+/// as late.
+#[derive(Debug, Clone, Default)]
+struct ClockSkew {
+    delays: VecDeque<f64>,
+}
+
+impl ClockSkew {
+    /// Record an event stamped `event_ms` that arrived at local time
+    /// `received_ms`, and return when it happened on the local clock.
+    fn observe(&mut self, event_ms: u64, received_ms: f64) -> f64 {
+        #[allow(clippy::cast_precision_loss)] // Epoch milliseconds fit in f64
+        let event_ms = event_ms as f64;
+        if self.delays.len() == SKEW_SAMPLES {
+            self.delays.pop_front();
+        }
+        self.delays.push_back(received_ms - event_ms);
+        let offset = self.delays.iter().copied().fold(f64::INFINITY, f64::min);
+        event_ms + offset
+    }
 }
 
 #[derive(Debug, Clone)]
 struct PendingTouch {
-    /// Touch location.
+    /// Touch location, or the center of the region.
     location: (f32, f32),
     /// Target element.
     element_id: Option<ElementId>,
-    /// When the touch occurred.
-    timestamp: Instant,
+    /// Elements near the touch, nearest first.
+    candidates: Vec<DisambiguationCandidate>,
+    /// Polygon a region gesture enclosed, with the elements inside it.
+    region: Option<(Vec<[f32; 2]>, Vec<ElementId>)>,
+    /// When the touch occurred, on the local clock (ms).
+    at: f64,
 }
 
 impl InputFusion {
@@ -105,10 +301,31 @@ impl InputFusion {
     pub fn with_config(config: FusionConfig) -> Self {
         Self {
             pending_touch: None,
+            pending_disambiguation: None,
             config,
+            clock: DEFAULT_CLOCK,
+            touch_clock: ClockSkew::default(),
+            voice_clock: ClockSkew::default(),
+            utterance_start: None,
+            recognition_latency: None,
         }
     }
 
+    /// Time event arrival with `clock` instead of the platform default.
+    ///
+    /// On `wasm32` there is no default: without a clock every event is
+    /// taken to arrive at once and touches never expire.
+    #[must_use]
+    pub fn with_clock(mut self, clock: FusionClock) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+
+    /// Current local time in ms.
+    fn now(&self) -> f64 {
+        self.clock.map_or(0.0, |clock| clock())
+    }
+
     /// Get the current configuration.
     #[must_use]
     pub const fn config(&self) -> &FusionConfig {
@@ -124,6 +341,20 @@ impl InputFusion {
     ///
     /// Stores the touch for potential fusion with upcoming voice.
     pub fn process_touch(&mut self, touch: &TouchEvent) -> FusionResult {
+        self.process_touch_near(touch, [])
+    }
+
+    /// Process a touch event that landed near `candidates`: elements with
+    /// their canvas bounds, nearest first.
+    ///
+    /// If more than one element is near the touch, the voice command that
+    /// follows asks the user which one they meant. At most
+    /// [`MAX_CANDIDATES`] are kept.
+    pub fn process_touch_near(
+        &mut self,
+        touch: &TouchEvent,
+        candidates: impl IntoIterator<Item = (ElementId, [f32; 4])>,
+    ) -> FusionResult {
         // Only process touch start events
         if touch.phase != crate::event::TouchPhase::Start {
             return FusionResult::None;
@@ -133,39 +364,136 @@ impl InputFusion {
         let Some(point) = touch.primary_touch() else {
             return FusionResult::None;
         };
+        let at = self.touch_clock.observe(touch.timestamp_ms, self.now());
 
         // Store touch for potential fusion
         self.pending_touch = Some(PendingTouch {
             location: (point.x, point.y),
             element_id: touch.target_element,
-            timestamp: Instant::now(),
+            candidates: candidates
+                .into_iter()
+                .take(MAX_CANDIDATES)
+                .enumerate()
+                .map(|(i, (element_id, bounds))| DisambiguationCandidate {
+                    number: i + 1,
+                    element_id,
+                    bounds,
+                })
+                .collect(),
+            region: None,
+            at,
         });
 
         FusionResult::Pending
     }
 
+    /// Process a recognized gesture made at `timestamp_ms` (on the touch
+    /// events' clock) that enclosed `elements`, topmost first.
+    ///
+    /// Region gestures (see [`Gesture::region`]) are stored like a touch,
+    /// and the voice command that follows is scoped to the region. Other
+    /// gestures are ignored; taps reach fusion through their touch events.
+    pub fn process_gesture(
+        &mut self,
+        gesture: &Gesture,
+        timestamp_ms: u64,
+        elements: impl IntoIterator<Item = ElementId>,
+    ) -> FusionResult {
+        if gesture.region().is_none() {
+            return FusionResult::None;
+        }
+        let at = self.touch_clock.observe(timestamp_ms, self.now());
+        self.store_region(gesture, elements, at)
+    }
+
+    /// Store a region gesture that happened at local time `at`.
+    fn store_region(
+        &mut self,
+        gesture: &Gesture,
+        elements: impl IntoIterator<Item = ElementId>,
+        at: f64,
+    ) -> FusionResult {
+        let Some(polygon) = gesture.region() else {
+            return FusionResult::None;
+        };
+        let elements: Vec<_> = elements.into_iter().collect();
+        #[allow(clippy::cast_precision_loss)] // Loops have far fewer than 2^24 points
+        let count = polygon.len() as f32;
+        let (sum_x, sum_y) = polygon
+            .iter()
+            .fold((0.0, 0.0), |(sx, sy), [x, y]| (sx + x, sy + y));
+        self.pending_touch = Some(PendingTouch {
+            location: (sum_x / count, sum_y / count),
+            element_id: match elements.as_slice() {
+                [only] => Some(*only),
+                _ => None,
+            },
+            candidates: Vec::new(),
+            region: Some((polygon.to_vec(), elements)),
+            at,
+        });
+        FusionResult::Pending
+    }
+
     /// Process a voice event.
     ///
-    /// If a touch is pending within the fusion window, creates a fused intent.
+    /// Interim results mark the start of the utterance. If a touch is
+    /// pending from within the fusion window before that start, the final
+    /// result creates a fused intent,
+    /// or a disambiguation request when several elements are near the touch
+    /// or the recognition confidence is below
+    /// [`FusionConfig::disambiguation_confidence`].
+    ///
+    /// While a disambiguation is pending, a reply naming a candidate's number
+    /// ("two", "number 2", "the second one") resolves it; any other command
+    /// cancels it and is processed as usual.
     pub fn process_voice(&mut self, voice: &VoiceEvent) -> FusionResult {
+        let now = self.now();
+        let at = self.voice_clock.observe(voice.timestamp_ms, now);
+
         // Only process final transcriptions
         if !voice.is_final {
+            self.utterance_start.get_or_insert(at);
             return FusionResult::None;
         }
+        let spoken_at = self.utterance_started(at);
 
         // Check confidence threshold
         if voice.confidence < self.config.min_confidence {
             return FusionResult::None;
         }
 
+        if let Some(request) = self.take_disambiguation() {
+            if let Some(intent) = parse_choice(&voice.transcript).and_then(|n| request.choose(n)) {
+                return FusionResult::Fused(intent);
+            }
+        }
+
         // Check for pending touch
         if let Some(pending) = self.pending_touch.take() {
-            // Check if within fusion window
-            if pending.timestamp.elapsed() <= self.config.fusion_window {
+            // Check if within fusion window of the utterance
+            if pending.at >= spoken_at - self.config.fusion_window.as_secs_f64() * 1000.0 {
+                let ambiguous = pending.candidates.len() > 1;
+                let unsure = voice.confidence < self.config.disambiguation_confidence
+                    && !pending.candidates.is_empty();
+                if ambiguous || unsure {
+                    let request = DisambiguationRequest {
+                        transcript: voice.transcript.clone(),
+                        location: pending.location,
+                        candidates: pending.candidates,
+                        confidence: voice.confidence,
+                        timestamp_ms: voice.timestamp_ms,
+                    };
+                    self.pending_disambiguation = Some((request.clone(), now));
+                    return FusionResult::Disambiguate(request);
+                }
+                let (region, element_ids) = pending.region.unzip();
                 return FusionResult::Fused(FusedIntent {
                     transcript: voice.transcript.clone(),
                     location: pending.location,
                     element_id: pending.element_id,
+                    region,
+                    element_ids: element_ids.unwrap_or_default(),
                     confidence: voice.confidence,
                     timestamp_ms: voice.timestamp_ms,
                 });
@@ -181,9 +509,14 @@ impl InputFusion {
     }
 
     /// Process any input event.
+    ///
+    /// Gesture events carry no timestamp or scene, so a region gesture is
+    /// taken to happen on arrival, with no elements inside it; use
+    /// [`process_gesture`](Self::process_gesture) to supply both.
     pub fn process(&mut self, event: &InputEvent) -> FusionResult {
         match event {
             InputEvent::Touch(touch) => self.process_touch(touch),
+            InputEvent::Gesture(gesture) => self.store_region(gesture, [], self.now()),
             InputEvent::Voice(voice) => self.process_voice(voice),
             _ => FusionResult::None,
         }
@@ -195,12 +528,57 @@ impl InputFusion {
         self.pending_touch.is_some()
     }
 
-    /// Check if pending touch is still within fusion window.
+    /// Check if pending touch is still within fusion window, extended by
+    /// the observed recognition latency.
     #[must_use]
     pub fn is_touch_valid(&self) -> bool {
-        self.pending_touch
-            .as_ref()
-            .is_some_and(|p| p.timestamp.elapsed() <= self.config.fusion_window)
+        self.time_remaining().is_some()
+    }
+
+    /// How long a touch waits for voice: the fusion window plus the
+    /// recognition latency observed so far, up to
+    /// [`FusionConfig::max_recognition_latency`].
+    #[must_use]
+    pub fn effective_window(&self) -> Duration {
+        self.config.fusion_window + self.latency_allowance()
+    }
+
+    /// Smoothed time from the start of an utterance to its final result,
+    /// once an utterance with interim results has been recognized.
+    #[must_use]
+    pub fn recognition_latency(&self) -> Option<Duration> {
+        self.recognition_latency
+            .map(|ms| Duration::from_secs_f64(ms.max(0.0) / 1000.0))
+    }
+
+    fn latency_allowance(&self) -> Duration {
+        self.recognition_latency()
+            .unwrap_or_default()
+            .min(self.config.max_recognition_latency)
+    }
+
+    /// Local time the utterance ending in a final result at `at` started:
+    /// its first interim result if there was one, otherwise `at` less the
+    /// observed recognition latency. Learns the latency from the former.
+    fn utterance_started(&mut self, at: f64) -> f64 {
+        let max_latency = self.config.max_recognition_latency.as_secs_f64() * 1000.0;
+        // An interim result from longer ago than that belongs to an
+        // utterance the recognizer gave up on
+        match self
+            .utterance_start
+            .take()
+            .filter(|start| at - start <= max_latency)
+        {
+            Some(start) => {
+                let latency = (at - start).max(0.0);
+                self.recognition_latency = Some(
+                    self.recognition_latency
+                        .map_or(latency, |avg| avg + LATENCY_SMOOTHING * (latency - avg)),
+                );
+                start
+            }
+            None => at - self.latency_allowance().as_secs_f64() * 1000.0,
+        }
     }
 
     /// Clear any pending touch.
@@ -208,13 +586,71 @@ impl InputFusion {
         self.pending_touch = None;
     }
 
-    /// Get time remaining in fusion window for pending touch.
+    /// The disambiguation prompt waiting for the user's choice, unless it
+    /// has timed out.
+    #[must_use]
+    pub fn pending_disambiguation(&self) -> Option<&DisambiguationRequest> {
+        let now = self.now();
+        self.pending_disambiguation
+            .as_ref()
+            .filter(|(_, at)| self.within(*at, now, self.config.disambiguation_timeout))
+            .map(|(request, _)| request)
+    }
+
+    /// Resolve the pending disambiguation with the candidate numbered
+    /// `number`.
+    ///
+    /// Returns [`FusionResult::Fused`] and ends the prompt if `number` is a
+    /// candidate; otherwise the prompt stays open and
+    /// [`FusionResult::None`] is returned.
+    pub fn resolve(&mut self, number: usize) -> FusionResult {
+        self.resolve_with(|request| request.choose(number))
+    }
+
+    /// Resolve the pending disambiguation with a tapped element.
+    ///
+    /// Returns [`FusionResult::Fused`] and ends the prompt if the element is
+    /// a candidate; otherwise the prompt stays open and
+    /// [`FusionResult::None`] is returned.
+    pub fn resolve_element(&mut self, element_id: ElementId) -> FusionResult {
+        self.resolve_with(|request| request.choose_element(element_id))
+    }
+
+    /// Dismiss the pending disambiguation without running its command.
+    pub fn cancel_disambiguation(&mut self) {
+        self.pending_disambiguation = None;
+    }
+
+    fn resolve_with(
+        &mut self,
+        choose: impl FnOnce(&DisambiguationRequest) -> Option<FusedIntent>,
+    ) -> FusionResult {
+        let Some(intent) = self.pending_disambiguation().and_then(choose) else {
+            return FusionResult::None;
+        };
+        self.pending_disambiguation = None;
+        FusionResult::Fused(intent)
+    }
+
+    /// Take the pending disambiguation, unless it has timed out.
+    fn take_disambiguation(&mut self) -> Option<DisambiguationRequest> {
+        let (request, at) = self.pending_disambiguation.take()?;
+        self.within(at, self.now(), self.config.disambiguation_timeout)
+            .then_some(request)
+    }
+
+    /// Whether `now` is no more than `limit` after `at` (local ms).
+    fn within(&self, at: f64, now: f64, limit: Duration) -> bool {
+        self.clock.is_none() || now - at <= limit.as_secs_f64() * 1000.0
+    }
+
+    /// Get time remaining in the effective fusion window for the pending
+    /// touch.
     #[must_use]
     pub fn time_remaining(&self) -> Option<Duration> {
-        self.pending_touch.as_ref().and_then(|p| {
-            let elapsed = p.timestamp.elapsed();
-            self.config.fusion_window.checked_sub(elapsed)
-        })
+        let pending = self.pending_touch.as_ref()?;
+        let elapsed = Duration::from_secs_f64((self.now() - pending.at).max(0.0) / 1000.0);
+        self.effective_window().checked_sub(elapsed)
     }
 }
 
@@ -224,6 +660,39 @@ impl Default for InputFusion {
     }
 }
 
+/// The candidate number named in a spoken reply: "2", "two", "number two",
+/// "the second one".
+fn parse_choice(transcript: &str) -> Option<usize> {
+    const NUMBERS: [(&str, &str); MAX_CANDIDATES] = [
+        ("one", "first"),
+        ("two", "second"),
+        ("three", "third"),
+        ("four", "fourth"),
+        ("five", "fifth"),
+        ("six", "sixth"),
+        ("seven", "seventh"),
+        ("eight", "eighth"),
+        ("nine", "ninth"),
+    ];
+    let words: Vec<String> = transcript
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+    // A choice is a short reply, not a command that happens to hold a number
+    if words.len() > 4 {
+        return None;
+    }
+    words.iter().find_map(|word| {
+        word.parse().ok().or_else(|| {
+            NUMBERS
+                .iter()
+                .position(|(cardinal, ordinal)| word == cardinal || word == ordinal)
+                .map(|i| i + 1)
+        })
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -263,8 +732,9 @@ mod tests {
     #[test]
     fn test_fusion_config() {
         let config = FusionConfig {
-            fusion_window: Duration::from_millis(3000),
+            fusion_window: Duration::from_secs(3),
             min_confidence: 0.7,
+            ..FusionConfig::default()
         };
         let fusion = InputFusion::with_config(config);
         assert_eq!(fusion.config().fusion_window.as_millis(), 3000);
@@ -398,18 +868,294 @@ mod tests {
     fn test_set_config() {
         let mut fusion = InputFusion::new();
         fusion.set_config(FusionConfig {
-            fusion_window: Duration::from_millis(5000),
+            fusion_window: Duration::from_secs(5),
             min_confidence: 0.8,
+            ..FusionConfig::default()
         });
         assert_eq!(fusion.config().fusion_window.as_millis(), 5000);
     }
 
+    #[test]
+    fn test_nearby_candidates_ask_which_element() {
+        let mut fusion = InputFusion::new();
+        let (a, b) = (ElementId::new(), ElementId::new());
+        let touch = create_touch_event(100.0, 200.0, Some(a));
+        let _ = fusion.process_touch_near(
+            &touch,
+            [
+                (a, [90.0, 190.0, 20.0, 20.0]),
+                (b, [112.0, 190.0, 20.0, 20.0]),
+            ],
+        );
+
+        let FusionResult::Disambiguate(request) =
+            fusion.process_voice(&create_voice_event("Delete this", true))
+        else {
+            panic!("Expected Disambiguate result");
+        };
+        assert_eq!(request.transcript, "Delete this");
+        assert_eq!(
+            request
+                .candidates
+                .iter()
+                .map(|c| c.number)
+                .collect::<Vec<_>>(),
+            vec![1, 2]
+        );
+        assert_eq!(
+            request.candidates[1],
+            DisambiguationCandidate {
+                number: 2,
+                element_id: b,
+                bounds: [112.0, 190.0, 20.0, 20.0],
+            }
+        );
+        assert!(fusion.pending_disambiguation().is_some());
+
+        // An unknown number keeps the prompt open
+        assert_eq!(fusion.resolve(3), FusionResult::None);
+        match fusion.process_voice(&create_voice_event("number two", true)) {
+            FusionResult::Fused(intent) => {
+                assert_eq!(intent.transcript, "Delete this");
+                assert_eq!(intent.element_id, Some(b));
+            }
+            other => panic!("Expected Fused result, got {other:?}"),
+        }
+        assert!(fusion.pending_disambiguation().is_none());
+    }
+
+    #[test]
+    fn test_low_confidence_asks_to_confirm_single_candidate() {
+        let mut fusion = InputFusion::new();
+        let a = ElementId::new();
+        let touch = create_touch_event(100.0, 200.0, Some(a));
+        let _ = fusion.process_touch_near(&touch, [(a, [90.0, 190.0, 20.0, 20.0])]);
+
+        let mut voice = create_voice_event("Make this red", true);
+        voice.confidence = 0.6;
+        assert!(matches!(
+            fusion.process_voice(&voice),
+            FusionResult::Disambiguate(_)
+        ));
+
+        // Tapping something else keeps the prompt; tapping the candidate fuses
+        assert_eq!(fusion.resolve_element(ElementId::new()), FusionResult::None);
+        match fusion.resolve_element(a) {
+            FusionResult::Fused(intent) => assert_eq!(intent.element_id, Some(a)),
+            other => panic!("Expected Fused result, got {other:?}"),
+        }
+
+        // A confident command on one candidate fuses directly
+        let _ = fusion.process_touch_near(&touch, [(a, [90.0, 190.0, 20.0, 20.0])]);
+        assert!(matches!(
+            fusion.process_voice(&create_voice_event("Make this red", true)),
+            FusionResult::Fused(_)
+        ));
+    }
+
+    #[test]
+    fn test_other_command_cancels_disambiguation() {
+        let mut fusion = InputFusion::new();
+        let (a, b) = (ElementId::new(), ElementId::new());
+        let touch = create_touch_event(100.0, 200.0, None);
+        let _ = fusion.process_touch_near(&touch, [(a, [0.0; 4]), (b, [0.0; 4])]);
+        let _ = fusion.process_voice(&create_voice_event("Delete this", true));
+
+        assert!(matches!(
+            fusion.process_voice(&create_voice_event("Undo", true)),
+            FusionResult::VoiceOnly(_)
+        ));
+        assert!(fusion.pending_disambiguation().is_none());
+    }
+
+    thread_local! {
+        static NOW: std::cell::Cell<f64> = const { std::cell::Cell::new(0.0) };
+    }
+
+    fn fake_now() -> f64 {
+        NOW.with(std::cell::Cell::get)
+    }
+
+    fn set_now(ms: f64) {
+        NOW.with(|now| now.set(ms));
+    }
+
+    /// Voice timestamps are epoch ms; touch timestamps count from canvas
+    /// start, and the local clock from neither.
+    const EPOCH: u64 = 1_700_000_000_000;
+
+    fn touch_at(timestamp_ms: u64) -> TouchEvent {
+        TouchEvent {
+            timestamp_ms,
+            ..create_touch_event(10.0, 10.0, Some(ElementId::new()))
+        }
+    }
+
+    fn voice_at(transcript: &str, is_final: bool, timestamp_ms: u64) -> VoiceEvent {
+        VoiceEvent {
+            timestamp_ms,
+            ..create_voice_event(transcript, is_final)
+        }
+    }
+
+    #[test]
+    fn test_slow_recognizer_fuses_by_utterance_start() {
+        let mut fusion = InputFusion::new().with_clock(fake_now);
+
+        // Touch at local 1000, then the user starts speaking
+        set_now(1000.0);
+        let _ = fusion.process_touch(&touch_at(500));
+        set_now(1300.0);
+        let _ = fusion.process_voice(&voice_at("make", false, EPOCH + 1200));
+
+        // The final result arrives 2.6s after the touch, past the 2s window
+        set_now(3600.0);
+        assert!(!fusion.is_touch_valid());
+        let result = fusion.process_voice(&voice_at("make this red", true, EPOCH + 3500));
+        assert!(matches!(result, FusionResult::Fused(_)));
+        assert_eq!(
+            fusion.recognition_latency(),
+            Some(Duration::from_millis(2300))
+        );
+    }
+
+    #[test]
+    fn test_learned_latency_extends_window_without_interims() {
+        let mut fusion = InputFusion::new().with_clock(fake_now);
+        set_now(0.0);
+        let _ = fusion.process_voice(&voice_at("one", false, EPOCH));
+        set_now(3000.0);
+        let _ = fusion.process_voice(&voice_at("one two", true, EPOCH + 3000));
+        assert_eq!(fusion.effective_window(), Duration::from_secs(5));
+
+        // No interim this time: the utterance is placed 3s before its result
+        set_now(10_000.0);
+        let _ = fusion.process_touch(&touch_at(10_000));
+        set_now(14_500.0);
+        assert!(fusion.is_touch_valid());
+        let result = fusion.process_voice(&voice_at("delete", true, EPOCH + 14_500));
+        assert!(matches!(result, FusionResult::Fused(_)));
+    }
+
+    #[test]
+    fn test_touch_before_window_is_not_fused() {
+        let mut fusion = InputFusion::new().with_clock(fake_now);
+        set_now(0.0);
+        let _ = fusion.process_touch(&touch_at(42));
+        set_now(4000.0);
+        let _ = fusion.process_voice(&voice_at("make", false, EPOCH + 4000));
+        set_now(4500.0);
+        let result = fusion.process_voice(&voice_at("make this red", true, EPOCH + 4500));
+        assert!(matches!(result, FusionResult::VoiceOnly(_)));
+    }
+
+    #[test]
+    fn test_clock_skew_uses_fastest_delivery() {
+        let mut skew = ClockSkew::default();
+        // Delivered 300ms late, then promptly: the offset is 100
+        assert!((skew.observe(1000, 1400.0) - 1400.0).abs() < f64::EPSILON);
+        assert!((skew.observe(2000, 2100.0) - 2100.0).abs() < f64::EPSILON);
+        assert!((skew.observe(3000, 3500.0) - 3100.0).abs() < f64::EPSILON);
+    }
+
+    fn lasso() -> Gesture {
+        Gesture::Lasso {
+            points: vec![[0.0, 0.0], [100.0, 0.0], [100.0, 50.0], [0.0, 50.0]],
+        }
+    }
+
+    #[test]
+    fn test_lasso_then_voice_fuses_region() {
+        let mut fusion = InputFusion::new();
+        let (a, b) = (ElementId::new(), ElementId::new());
+
+        let result = fusion.process_gesture(&lasso(), 1000, [a, b]);
+        assert!(matches!(result, FusionResult::Pending));
+
+        // Several elements inside a region are its scope, not an ambiguity
+        match fusion.process_voice(&create_voice_event("Summarize these", true)) {
+            FusionResult::Fused(intent) => {
+                assert_eq!(intent.location, (50.0, 25.0));
+                assert_eq!(intent.element_id, None);
+                assert_eq!(intent.element_ids, vec![a, b]);
+                assert_eq!(intent.region.map(|r| r.len()), Some(4));
+            }
+            other => panic!("Expected Fused result, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_lasso_around_one_element_targets_it() {
+        let mut fusion = InputFusion::new();
+        let only = ElementId::new();
+        let _ = fusion.process(&InputEvent::Gesture(lasso()));
+        assert!(fusion.has_pending_touch());
+        let _ = fusion.process_gesture(&lasso(), 1000, [only]);
+
+        let FusionResult::Fused(intent) =
+            fusion.process_voice(&create_voice_event("Make it red", true))
+        else {
+            panic!("Expected Fused result");
+        };
+        assert_eq!(intent.element_id, Some(only));
+
+        let json = serde_json::to_value(&intent).unwrap();
+        assert_eq!(json["element_ids"].as_array().map(Vec::len), Some(1));
+        assert_eq!(json["region"][1], serde_json::json!([100.0, 0.0]));
+    }
+
+    #[test]
+    fn test_non_region_gestures_are_ignored() {
+        let mut fusion = InputFusion::new();
+        let tap = Gesture::Tap { x: 1.0, y: 2.0 };
+        assert!(matches!(
+            fusion.process_gesture(&tap, 1000, []),
+            FusionResult::None
+        ));
+        let line = Gesture::Lasso {
+            points: vec![[0.0, 0.0], [10.0, 10.0]],
+        };
+        assert!(matches!(
+            fusion.process(&InputEvent::Gesture(line)),
+            FusionResult::None
+        ));
+        assert!(!fusion.has_pending_touch());
+    }
+
+    #[test]
+    fn test_touch_after_lasso_replaces_region() {
+        let mut fusion = InputFusion::new();
+        let _ = fusion.process_gesture(&lasso(), 1000, [ElementId::new()]);
+        let _ = fusion.process_touch(&create_touch_event(5.0, 6.0, None));
+
+        let FusionResult::Fused(intent) =
+            fusion.process_voice(&create_voice_event("Put it here", true))
+        else {
+            panic!("Expected Fused result");
+        };
+        assert_eq!(intent.location, (5.0, 6.0));
+        assert!(intent.region.is_none());
+        assert!(intent.element_ids.is_empty());
+    }
+
+    #[test]
+    fn test_parse_choice() {
+        assert_eq!(parse_choice("2"), Some(2));
+        assert_eq!(parse_choice("Number two."), Some(2));
+        assert_eq!(parse_choice("the third one"), Some(3));
+        assert_eq!(parse_choice("#1"), Some(1));
+        assert_eq!(parse_choice("make it red"), None);
+        assert_eq!(parse_choice("move this one to the left please"), None);
+    }
+
     #[test]
     fn test_fused_intent_fields() {
         let intent = FusedIntent {
             transcript: "test".to_string(),
             location: (10.0, 20.0),
             element_id: None,
+            region: None,
+            element_ids: Vec::new(),
             confidence: 0.9,
             timestamp_ms: 1234,
         };
@@ -0,0 +1,283 @@
+//! # Geometry
+//!
+//! Rotation-aware shapes for hit testing, bounds and culling.
+//!
+//! An element's [`Transform`] describes a rectangle rotated by
+//! `transform.rotation` radians about its center:
+//!
+//! ```text
+//!         ╱╲            world bounds
+//!        ╱  ╲         ┌─────────────┐
+//!       ╱    ╲        │     ╱╲      │
+//!       ╲     ╲   →   │    ╱  ╲     │
+//!        ╲    ╱       │    ╲   ╲    │
+//!         ╲  ╱        │     ╲  ╱    │
+//!          ╲╱         └─────────────┘
+//! ```
+//!
+//! [`OrientedRect`] does point and rectangle tests against the rotated
+//! shape; [`SceneBounds::from_transform`] is the axis-aligned box around it.
+//! Scene hit testing, marquee selection and viewport culling all go through
+//! these helpers so they agree on what a rotated element covers.
+
+use crate::camera::SceneBounds;
+use crate::element::Transform;
+
+/// A rectangle rotated about its center, in canvas coordinates.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OrientedRect {
+    /// Center point.
+    pub center: (f32, f32),
+    /// Half the width.
+    pub half_width: f32,
+    /// Half the height.
+    pub half_height: f32,
+    /// Rotation in radians, clockwise in screen space.
+    pub rotation: f32,
+}
+
+impl OrientedRect {
+    /// The rotated rectangle an element transform covers.
+    #[must_use]
+    pub fn from_transform(transform: &Transform) -> Self {
+        let half_width = transform.width / 2.0;
+        let half_height = transform.height / 2.0;
+        Self {
+            center: (transform.x + half_width, transform.y + half_height),
+            half_width,
+            half_height,
+            rotation: transform.rotation,
+        }
+    }
+
+    /// Whether the rectangle is rotated at all.
+    fn is_rotated(&self) -> bool {
+        self.rotation.abs() > f32::EPSILON
+    }
+
+    /// Unit vectors along the rectangle's local x and y axes.
+    fn axes(&self) -> [(f32, f32); 2] {
+        let (sin, cos) = self.rotation.sin_cos();
+        [(cos, sin), (-sin, cos)]
+    }
+
+    /// Map a point into the rectangle's local frame (origin at the center).
+    fn local_point(&self, x: f32, y: f32) -> (f32, f32) {
+        let (dx, dy) = (x - self.center.0, y - self.center.1);
+        let [ax, ay] = self.axes();
+        (dx * ax.0 + dy * ax.1, dx * ay.0 + dy * ay.1)
+    }
+
+    /// Map a canvas point to the element's local frame: offsets from its
+    /// unrotated top-left corner along its own axes.
+    #[must_use]
+    pub fn local_offset(&self, x: f32, y: f32) -> (f32, f32) {
+        let (u, v) = self.local_point(x, y);
+        (u + self.half_width, v + self.half_height)
+    }
+
+    /// Corners in canvas coordinates: top-left, top-right, bottom-right,
+    /// bottom-left (before rotation).
+    #[must_use]
+    pub fn corners(&self) -> [(f32, f32); 4] {
+        let [ax, ay] = self.axes();
+        let (cx, cy) = self.center;
+        let (hw, hh) = (self.half_width, self.half_height);
+        [(-hw, -hh), (hw, -hh), (hw, hh), (-hw, hh)]
+            .map(|(u, v)| (cx + u * ax.0 + v * ay.0, cy + u * ax.1 + v * ay.1))
+    }
+
+    /// Check whether a point lies inside the rotated rectangle (edges
+    /// included).
+    #[must_use]
+    pub fn contains_point(&self, x: f32, y: f32) -> bool {
+        let (u, v) = self.local_point(x, y);
+        u.abs() <= self.half_width && v.abs() <= self.half_height
+    }
+
+    /// Distance from a point to the rotated rectangle; zero inside it.
+    #[must_use]
+    pub fn distance_to(&self, x: f32, y: f32) -> f32 {
+        let (u, v) = self.local_point(x, y);
+        let dx = (u.abs() - self.half_width).max(0.0);
+        let dy = (v.abs() - self.half_height).max(0.0);
+        dx.hypot(dy)
+    }
+
+    /// Axis-aligned bounds around the rotated rectangle.
+    #[must_use]
+    pub fn bounds(&self) -> SceneBounds {
+        let (cx, cy) = self.center;
+        if !self.is_rotated() {
+            return SceneBounds {
+                min_x: cx - self.half_width,
+                min_y: cy - self.half_height,
+                max_x: cx + self.half_width,
+                max_y: cy + self.half_height,
+            };
+        }
+        let [ax, ay] = self.axes();
+        let extent_x = self.half_width * ax.0.abs() + self.half_height * ay.0.abs();
+        let extent_y = self.half_width * ax.1.abs() + self.half_height * ay.1.abs();
+        SceneBounds {
+            min_x: cx - extent_x,
+            min_y: cy - extent_y,
+            max_x: cx + extent_x,
+            max_y: cy + extent_y,
+        }
+    }
+
+    /// Check whether the rotated rectangle overlaps `bounds` (touching
+    /// edges count).
+    ///
+    /// Uses the separating axis test, so rotated elements whose bounding box
+    /// overlaps `bounds` but whose shape does not are excluded.
+    #[must_use]
+    pub fn intersects_bounds(&self, bounds: &SceneBounds) -> bool {
+        if !self.bounds().intersects(bounds) {
+            return false;
+        }
+        if !self.is_rotated() {
+            return true;
+        }
+
+        // The canvas axes were checked above; check the rectangle's own axes
+        let (bx, by) = bounds.center();
+        let (bhw, bhh) = (bounds.width() / 2.0, bounds.height() / 2.0);
+        let (dx, dy) = (bx - self.center.0, by - self.center.1);
+        self.axes()
+            .into_iter()
+            .zip([self.half_width, self.half_height])
+            .all(|((axis_x, axis_y), half)| {
+                let distance = (dx * axis_x + dy * axis_y).abs();
+                let reach = half + bhw * axis_x.abs() + bhh * axis_y.abs();
+                distance <= reach
+            })
+    }
+}
+
+/// Check whether a point lies inside a polygon, given as its vertices in
+/// order with the last joined to the first.
+///
+/// Uses the even-odd rule, so a self-crossing loop (a lasso drawn past its
+/// start) still encloses what it circles.
+#[must_use]
+pub fn polygon_contains(polygon: &[[f32; 2]], x: f32, y: f32) -> bool {
+    let mut inside = false;
+    let Some(&last) = polygon.last() else {
+        return false;
+    };
+    let mut previous = last;
+    for &[px, py] in polygon {
+        let [qx, qy] = previous;
+        if (py > y) != (qy > y) && x < (qx - px) * (y - py) / (qy - py) + px {
+            inside = !inside;
+        }
+        previous = [px, py];
+    }
+    inside
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::FRAC_PI_4;
+
+    fn rect(x: f32, y: f32, width: f32, height: f32, rotation: f32) -> OrientedRect {
+        OrientedRect::from_transform(&Transform {
+            x,
+            y,
+            width,
+            height,
+            rotation,
+            z_index: 0,
+            auto_size: false,
+        })
+    }
+
+    #[test]
+    fn test_unrotated_bounds_match_transform() {
+        let r = rect(10.0, 20.0, 100.0, 50.0, 0.0);
+        assert_eq!(
+            r.bounds(),
+            SceneBounds {
+                min_x: 10.0,
+                min_y: 20.0,
+                max_x: 110.0,
+                max_y: 70.0,
+            }
+        );
+        assert!(r.contains_point(10.0, 20.0));
+        assert!(!r.contains_point(111.0, 20.0));
+    }
+
+    #[test]
+    fn test_rotated_contains_point() {
+        // A 100x100 square at the origin turned 45° into a diamond
+        let diamond = rect(0.0, 0.0, 100.0, 100.0, FRAC_PI_4);
+
+        // The old corner of the square is now outside the diamond
+        assert!(!diamond.contains_point(2.0, 2.0));
+        assert!(diamond.contains_point(50.0, 50.0));
+        // The diamond's tips reach beyond the original square
+        assert!(diamond.contains_point(50.0, -19.0));
+        assert!(diamond.contains_point(119.0, 50.0));
+    }
+
+    #[test]
+    fn test_rotated_bounds_enclose_corners() {
+        let diamond = rect(0.0, 0.0, 100.0, 100.0, FRAC_PI_4);
+        let bounds = diamond.bounds();
+        let reach = 50.0 * std::f32::consts::SQRT_2;
+
+        assert!((bounds.min_x - (50.0 - reach)).abs() < 1e-3);
+        assert!((bounds.max_y - (50.0 + reach)).abs() < 1e-3);
+        for (x, y) in diamond.corners() {
+            assert!(x >= bounds.min_x - 1e-3 && x <= bounds.max_x + 1e-3);
+            assert!(y >= bounds.min_y - 1e-3 && y <= bounds.max_y + 1e-3);
+        }
+    }
+
+    #[test]
+    fn test_intersects_bounds_uses_rotated_shape() {
+        let diamond = rect(0.0, 0.0, 100.0, 100.0, FRAC_PI_4);
+
+        // Inside the bounding box's corner but outside the diamond
+        let corner = SceneBounds {
+            min_x: -20.0,
+            min_y: -20.0,
+            max_x: 0.0,
+            max_y: 0.0,
+        };
+        assert!(diamond.bounds().intersects(&corner));
+        assert!(!diamond.intersects_bounds(&corner));
+
+        let center = SceneBounds {
+            min_x: 40.0,
+            min_y: 40.0,
+            max_x: 60.0,
+            max_y: 60.0,
+        };
+        assert!(diamond.intersects_bounds(&center));
+    }
+
+    #[test]
+    fn test_polygon_contains() {
+        let triangle = [[0.0, 0.0], [100.0, 0.0], [0.0, 100.0]];
+        assert!(polygon_contains(&triangle, 20.0, 20.0));
+        assert!(!polygon_contains(&triangle, 60.0, 60.0));
+        assert!(!polygon_contains(&triangle, -1.0, 10.0));
+        assert!(!polygon_contains(&[], 0.0, 0.0));
+
+        // A square loop drawn past its start still encloses its middle
+        let lasso = [
+            [0.0, 0.0],
+            [100.0, 0.0],
+            [100.0, 100.0],
+            [0.0, 100.0],
+            [0.0, -10.0],
+        ];
+        assert!(polygon_contains(&lasso, 50.0, 50.0));
+        assert!(!polygon_contains(&lasso, 150.0, 50.0));
+    }
+}
@@ -0,0 +1,636 @@
+//! # Syntax Highlighting
+//!
+//! Highlight spans for [`ElementKind::Code`](crate::ElementKind::Code)
+//! elements, computed once when the element is created so every renderer
+//! (DOM, wgpu, SVG export, the JS fallback) colors code the same way.
+//!
+//! ```text
+//! "fn main() {}"  →  highlight("rust", ..)  →  [Keyword 0..2, Function 3..7]
+//!                                                      │
+//!                     renderers  ←  lines(source, spans)  →  [[(Keyword, "fn"),
+//!                                                             (Plain, " "), ...]]
+//! ```
+//!
+//! Span offsets are UTF-8 byte offsets into the source. The tokenizer is a
+//! small keyword/string/comment/number scanner, not a full grammar, and is
+//! only compiled with the `highlight` feature; [`lines`] and the token
+//! colors are always available so renderers can draw spans they received.
+
+use serde::{Deserialize, Serialize};
+
+/// Font size for code blocks, in pixels.
+pub const CODE_FONT_SIZE: f32 = 14.0;
+
+/// Distance between code baselines, in pixels.
+pub const CODE_LINE_HEIGHT: f32 = 20.0;
+
+/// Inset of code from the element edge, in pixels.
+pub const CODE_PADDING: f32 = 8.0;
+
+/// Advance of one monospace character, as a fraction of the font size.
+pub const CODE_CHAR_WIDTH: f32 = 0.6;
+
+/// Background color of code blocks.
+pub const CODE_BACKGROUND: &str = "#1e1e1e";
+
+/// Syntax category of a span of source.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+#[serde(rename_all = "snake_case")]
+pub enum TokenKind {
+    /// Identifiers, operators and anything not otherwise classified.
+    #[default]
+    Plain,
+    /// Language keywords and literals such as `true`.
+    Keyword,
+    /// Type names.
+    Type,
+    /// Function and method names at a call or definition.
+    Function,
+    /// String and character literals.
+    String,
+    /// Numeric literals.
+    Number,
+    /// Comments.
+    Comment,
+}
+
+impl TokenKind {
+    /// Text color for this token on [`CODE_BACKGROUND`].
+    #[must_use]
+    pub const fn color(self) -> &'static str {
+        match self {
+            Self::Plain => "#d4d4d4",
+            Self::Keyword => "#569cd6",
+            Self::Type => "#4ec9b0",
+            Self::Function => "#dcdcaa",
+            Self::String => "#ce9178",
+            Self::Number => "#b5cea8",
+            Self::Comment => "#6a9955",
+        }
+    }
+}
+
+/// A highlighted range of a code element's source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+pub struct HighlightSpan {
+    /// Start byte offset (inclusive).
+    pub start: usize,
+    /// End byte offset (exclusive).
+    pub end: usize,
+    /// Token category.
+    pub kind: TokenKind,
+}
+
+/// Split `source` into lines of colored segments.
+///
+/// Text not covered by a span is [`TokenKind::Plain`]. Spans that overlap an
+/// earlier span, fall outside the source or split a UTF-8 character are
+/// ignored, so spans received over the network cannot cause a panic.
+#[must_use]
+pub fn lines<'a>(source: &'a str, spans: &[HighlightSpan]) -> Vec<Vec<(TokenKind, &'a str)>> {
+    let mut sorted: Vec<&HighlightSpan> = spans.iter().collect();
+    sorted.sort_by_key(|span| span.start);
+
+    // Cover the whole source with segments
+    let mut segments = Vec::new();
+    let mut cursor = 0;
+    for span in sorted {
+        let valid = span.start >= cursor
+            && span.start < span.end
+            && span.end <= source.len()
+            && source.is_char_boundary(span.start)
+            && source.is_char_boundary(span.end);
+        if !valid {
+            continue;
+        }
+        if span.start > cursor {
+            segments.push((TokenKind::Plain, &source[cursor..span.start]));
+        }
+        segments.push((span.kind, &source[span.start..span.end]));
+        cursor = span.end;
+    }
+    if cursor < source.len() {
+        segments.push((TokenKind::Plain, &source[cursor..]));
+    }
+
+    // Break segments at newlines
+    let mut lines = vec![Vec::new()];
+    for (kind, text) in segments {
+        let mut parts = text.split('\n');
+        if let Some(first) = parts.next() {
+            push_segment(&mut lines, kind, first);
+        }
+        for part in parts {
+            lines.push(Vec::new());
+            push_segment(&mut lines, kind, part);
+        }
+    }
+    lines
+}
+
+fn push_segment<'a>(lines: &mut [Vec<(TokenKind, &'a str)>], kind: TokenKind, text: &'a str) {
+    let text = text.strip_suffix('\r').unwrap_or(text);
+    if let (false, Some(line)) = (text.is_empty(), lines.last_mut()) {
+        line.push((kind, text));
+    }
+}
+
+/// Lexical rules for one language.
+#[cfg(feature = "highlight")]
+struct Language {
+    keywords: &'static [&'static str],
+    types: &'static [&'static str],
+    /// Identifiers starting with an uppercase letter are types.
+    capitalized_types: bool,
+    line_comments: &'static [&'static str],
+    block_comment: Option<(&'static str, &'static str)>,
+    /// Quote characters that delimit strings.
+    quotes: &'static [char],
+    /// `'x'` is a character literal but a lone `'` is not (Rust lifetimes).
+    char_literals: bool,
+    /// `name!(...)` is a macro call.
+    macros: bool,
+}
+
+#[cfg(feature = "highlight")]
+const RUST: Language = Language {
+    keywords: &[
+        "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum",
+        "extern", "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move",
+        "mut", "pub", "ref", "return", "self", "Self", "static", "struct", "super", "trait",
+        "true", "type", "unsafe", "use", "where", "while",
+    ],
+    types: &[
+        "bool", "char", "str", "u8", "u16", "u32", "u64", "u128", "usize", "i8", "i16", "i32",
+        "i64", "i128", "isize", "f32", "f64",
+    ],
+    capitalized_types: true,
+    line_comments: &["//"],
+    block_comment: Some(("/*", "*/")),
+    quotes: &['"'],
+    char_literals: true,
+    macros: true,
+};
+
+#[cfg(feature = "highlight")]
+const PYTHON: Language = Language {
+    keywords: &[
+        "and", "as", "assert", "async", "await", "break", "class", "continue", "def", "del",
+        "elif", "else", "except", "False", "finally", "for", "from", "global", "if", "import",
+        "in", "is", "lambda", "None", "nonlocal", "not", "or", "pass", "raise", "return", "True",
+        "try", "while", "with", "yield",
+    ],
+    types: &[
+        "int", "float", "str", "bool", "list", "dict", "set", "tuple", "bytes", "object",
+    ],
+    capitalized_types: true,
+    line_comments: &["#"],
+    block_comment: None,
+    quotes: &['"', '\''],
+    char_literals: false,
+    macros: false,
+};
+
+#[cfg(feature = "highlight")]
+const JAVASCRIPT: Language = Language {
+    keywords: &[
+        "async",
+        "await",
+        "break",
+        "case",
+        "catch",
+        "class",
+        "const",
+        "continue",
+        "default",
+        "delete",
+        "do",
+        "else",
+        "export",
+        "extends",
+        "false",
+        "finally",
+        "for",
+        "from",
+        "function",
+        "if",
+        "import",
+        "in",
+        "instanceof",
+        "interface",
+        "let",
+        "new",
+        "null",
+        "of",
+        "return",
+        "static",
+        "super",
+        "switch",
+        "this",
+        "throw",
+        "true",
+        "try",
+        "type",
+        "typeof",
+        "undefined",
+        "var",
+        "void",
+        "while",
+        "yield",
+    ],
+    types: &[
+        "any", "boolean", "never", "number", "object", "string", "symbol", "unknown",
+    ],
+    capitalized_types: true,
+    line_comments: &["//"],
+    block_comment: Some(("/*", "*/")),
+    quotes: &['"', '\'', '`'],
+    char_literals: false,
+    macros: false,
+};
+
+#[cfg(feature = "highlight")]
+const GO: Language = Language {
+    keywords: &[
+        "break",
+        "case",
+        "chan",
+        "const",
+        "continue",
+        "default",
+        "defer",
+        "else",
+        "false",
+        "for",
+        "func",
+        "go",
+        "goto",
+        "if",
+        "import",
+        "interface",
+        "map",
+        "nil",
+        "package",
+        "range",
+        "return",
+        "select",
+        "struct",
+        "switch",
+        "true",
+        "type",
+        "var",
+    ],
+    types: &[
+        "bool", "byte", "error", "float32", "float64", "int", "int8", "int16", "int32", "int64",
+        "rune", "string", "uint", "uint8", "uint16", "uint32", "uint64", "uintptr",
+    ],
+    capitalized_types: false,
+    line_comments: &["//"],
+    block_comment: Some(("/*", "*/")),
+    quotes: &['"', '`'],
+    char_literals: true,
+    macros: false,
+};
+
+#[cfg(feature = "highlight")]
+const C: Language = Language {
+    keywords: &[
+        "auto",
+        "break",
+        "case",
+        "class",
+        "const",
+        "constexpr",
+        "continue",
+        "default",
+        "delete",
+        "do",
+        "else",
+        "enum",
+        "extern",
+        "false",
+        "for",
+        "goto",
+        "if",
+        "include",
+        "inline",
+        "namespace",
+        "new",
+        "nullptr",
+        "private",
+        "protected",
+        "public",
+        "return",
+        "sizeof",
+        "static",
+        "struct",
+        "switch",
+        "template",
+        "this",
+        "true",
+        "typedef",
+        "union",
+        "using",
+        "virtual",
+        "volatile",
+        "while",
+    ],
+    types: &[
+        "bool", "char", "double", "float", "int", "long", "short", "signed", "size_t", "unsigned",
+        "void",
+    ],
+    capitalized_types: false,
+    line_comments: &["//"],
+    block_comment: Some(("/*", "*/")),
+    quotes: &['"'],
+    char_literals: true,
+    macros: false,
+};
+
+#[cfg(feature = "highlight")]
+const SHELL: Language = Language {
+    keywords: &[
+        "case", "do", "done", "elif", "else", "esac", "export", "fi", "for", "function", "if",
+        "in", "local", "return", "then", "until", "while",
+    ],
+    types: &[],
+    capitalized_types: false,
+    line_comments: &["#"],
+    block_comment: None,
+    quotes: &['"', '\''],
+    char_literals: false,
+    macros: false,
+};
+
+#[cfg(feature = "highlight")]
+const JSON: Language = Language {
+    keywords: &["true", "false", "null"],
+    types: &[],
+    capitalized_types: false,
+    line_comments: &[],
+    block_comment: None,
+    quotes: &['"'],
+    char_literals: false,
+    macros: false,
+};
+
+#[cfg(feature = "highlight")]
+fn language(name: &str) -> Option<&'static Language> {
+    Some(match name.to_ascii_lowercase().as_str() {
+        "rust" | "rs" => &RUST,
+        "python" | "py" => &PYTHON,
+        "javascript" | "js" | "jsx" | "typescript" | "ts" | "tsx" => &JAVASCRIPT,
+        "go" | "golang" => &GO,
+        "c" | "h" | "cpp" | "c++" | "cc" | "hpp" => &C,
+        "bash" | "sh" | "shell" | "zsh" => &SHELL,
+        "json" => &JSON,
+        _ => return None,
+    })
+}
+
+/// Keywords whose next identifier is a function being defined.
+#[cfg(feature = "highlight")]
+const DEFINITION_KEYWORDS: &[&str] = &["fn", "def", "func", "function"];
+
+/// Compute highlight spans for `source` written in `language`.
+///
+/// Returns no spans for unknown languages, so the code renders as plain
+/// text. Plain tokens are not included.
+#[cfg(feature = "highlight")]
+#[must_use]
+pub fn highlight(language_name: &str, source: &str) -> Vec<HighlightSpan> {
+    let Some(language) = language(language_name) else {
+        return Vec::new();
+    };
+
+    let mut spans = Vec::new();
+    let mut pos = 0;
+    let mut push = |start: usize, end: usize, kind: TokenKind| {
+        spans.push(HighlightSpan { start, end, kind });
+    };
+
+    // The identifier before the current token, if nothing but whitespace
+    // separates them
+    let mut previous_word = "";
+    while let Some(c) = source[pos..].chars().next() {
+        let rest = &source[pos..];
+        let start = pos;
+
+        if language.line_comments.iter().any(|p| rest.starts_with(p)) {
+            pos += rest.find('\n').unwrap_or(rest.len());
+            push(start, pos, TokenKind::Comment);
+        } else if let Some((open, close)) = language
+            .block_comment
+            .filter(|(open, _)| rest.starts_with(open))
+        {
+            pos += rest[open.len()..]
+                .find(close)
+                .map_or(rest.len(), |i| open.len() + i + close.len());
+            push(start, pos, TokenKind::Comment);
+        } else if language.quotes.contains(&c) {
+            pos += string_len(rest, c);
+            push(start, pos, TokenKind::String);
+        } else if c == '\'' && language.char_literals {
+            match char_literal_len(rest) {
+                Some(len) => {
+                    pos += len;
+                    push(start, pos, TokenKind::String);
+                }
+                None => pos += 1,
+            }
+        } else if c.is_ascii_digit() {
+            pos += rest
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '.'))
+                .unwrap_or(rest.len());
+            push(start, pos, TokenKind::Number);
+        } else if c.is_alphabetic() || c == '_' {
+            let len = rest
+                .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+                .unwrap_or(rest.len());
+            let word = &rest[..len];
+            pos += len;
+            let kind = if language.keywords.contains(&word) {
+                TokenKind::Keyword
+            } else if language.types.contains(&word)
+                || (language.capitalized_types && word.starts_with(char::is_uppercase))
+            {
+                TokenKind::Type
+            } else if DEFINITION_KEYWORDS.contains(&previous_word)
+                || source[pos..]
+                    .trim_start_matches([' ', '\t'])
+                    .starts_with('(')
+                || (language.macros && source[pos..].starts_with('!'))
+            {
+                TokenKind::Function
+            } else {
+                TokenKind::Plain
+            };
+            if kind != TokenKind::Plain {
+                push(start, pos, kind);
+            }
+            previous_word = word;
+            continue;
+        } else {
+            pos += c.len_utf8();
+        }
+        if !c.is_whitespace() {
+            previous_word = "";
+        }
+    }
+    spans
+}
+
+/// Length of the string literal at the start of `rest`, including quotes.
+/// Strings end at the closing quote or, except for backticks, the line end.
+#[cfg(feature = "highlight")]
+fn string_len(rest: &str, quote: char) -> usize {
+    let mut escaped = false;
+    for (i, c) in rest.char_indices().skip(1) {
+        if escaped {
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if c == quote {
+            return i + c.len_utf8();
+        } else if c == '\n' && quote != '`' {
+            return i;
+        }
+    }
+    rest.len()
+}
+
+/// Length of a character literal such as `'a'` or `'\n'` at the start of
+/// `rest`, or `None` if the quote starts something else (a Rust lifetime).
+#[cfg(feature = "highlight")]
+fn char_literal_len(rest: &str) -> Option<usize> {
+    let mut chars = rest.char_indices().skip(1);
+    let (_, c) = chars.next()?;
+    if c == '\\' {
+        // Escapes run to the closing quote: '\n', '\u{1F600}'
+        return rest[1..]
+            .find('\'')
+            .filter(|&i| i > 1 && !rest[1..=i].contains('\n'))
+            .map(|i| i + 2);
+    }
+    match chars.next()? {
+        (i, '\'') => Some(i + 1),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lines_fill_gaps_and_split_newlines() {
+        let source = "let x\n= 1;";
+        let spans = [
+            HighlightSpan {
+                start: 0,
+                end: 3,
+                kind: TokenKind::Keyword,
+            },
+            HighlightSpan {
+                start: 8,
+                end: 9,
+                kind: TokenKind::Number,
+            },
+        ];
+        assert_eq!(
+            lines(source, &spans),
+            vec![
+                vec![(TokenKind::Keyword, "let"), (TokenKind::Plain, " x")],
+                vec![
+                    (TokenKind::Plain, "= "),
+                    (TokenKind::Number, "1"),
+                    (TokenKind::Plain, ";"),
+                ],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_lines_ignore_invalid_spans() {
+        let source = "héllo";
+        let spans = [
+            // Splits the two-byte 'é'
+            HighlightSpan {
+                start: 0,
+                end: 2,
+                kind: TokenKind::Keyword,
+            },
+            HighlightSpan {
+                start: 4,
+                end: 100,
+                kind: TokenKind::String,
+            },
+        ];
+        assert_eq!(
+            lines(source, &spans),
+            vec![vec![(TokenKind::Plain, "héllo")]]
+        );
+        assert_eq!(lines("", &[]), vec![Vec::new()]);
+    }
+
+    #[cfg(feature = "highlight")]
+    fn tokens<'a>(language: &str, source: &'a str) -> Vec<(TokenKind, &'a str)> {
+        highlight(language, source)
+            .into_iter()
+            .map(|span| (span.kind, &source[span.start..span.end]))
+            .collect()
+    }
+
+    #[cfg(feature = "highlight")]
+    #[test]
+    fn test_highlight_rust() {
+        let source = "// add\nfn add<'a>(x: &'a str) -> String { format!(\"{x}\") + 'c' }";
+        assert_eq!(
+            tokens("rust", source),
+            vec![
+                (TokenKind::Comment, "// add"),
+                (TokenKind::Keyword, "fn"),
+                (TokenKind::Function, "add"),
+                (TokenKind::Type, "str"),
+                (TokenKind::Type, "String"),
+                (TokenKind::Function, "format"),
+                (TokenKind::String, "\"{x}\""),
+                (TokenKind::String, "'c'"),
+            ]
+        );
+    }
+
+    #[cfg(feature = "highlight")]
+    #[test]
+    fn test_highlight_python_and_unknown() {
+        let source = "def f(n):\n    return n * 2.5  # done";
+        assert_eq!(
+            tokens("Python", source),
+            vec![
+                (TokenKind::Keyword, "def"),
+                (TokenKind::Function, "f"),
+                (TokenKind::Keyword, "return"),
+                (TokenKind::Number, "2.5"),
+                (TokenKind::Comment, "# done"),
+            ]
+        );
+        assert!(highlight("brainfuck", source).is_empty());
+    }
+
+    #[cfg(feature = "highlight")]
+    #[test]
+    fn test_highlight_unterminated_literals() {
+        for (language, source) in [
+            ("rust", "let s = \"open"),
+            ("c", "/* open"),
+            ("py", "'open"),
+            ("js", "`multi\nline"),
+        ] {
+            let spans = highlight(language, source);
+            let last = spans.last().expect("has spans");
+            assert_eq!(last.end, source.len(), "{source}");
+        }
+    }
+}
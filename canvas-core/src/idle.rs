@@ -0,0 +1,250 @@
+//! # Idle Detection
+//!
+//! Decides when a host's render loop can stop requesting frames, so a
+//! tablet left showing a dashboard is not redrawing an unchanged scene
+//! sixty times a second.
+//!
+//! ```text
+//!  input / sync ──► active ──(idle_after_ms, no animation)──► idle
+//!       ▲                                                       │
+//!       └────────────── record_activity / invalidate ◄──────────┘
+//! ```
+//!
+//! While active, every frame is rendered. While idle, a frame is rendered
+//! only when something invalidated the scene or a scheduled wake-up (such
+//! as a spotlight expiring) is due, and video streams are throttled to one
+//! frame per `idle_video_interval_ms`.
+//!
+//! Hosts call [`IdleTracker::needs_render`] once per animation frame and
+//! stop requesting frames when it returns `false`. [`IdleTracker::invalidate`]
+//! and [`IdleTracker::record_activity`] report whether the loop was asleep,
+//! so the host knows to restart it.
+
+use std::collections::HashMap;
+
+/// Idle detection settings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IdleConfig {
+    /// Time without input, sync or animation before going idle (ms).
+    pub idle_after_ms: u64,
+    /// Minimum time between accepted frames of each video stream while
+    /// idle (ms).
+    pub idle_video_interval_ms: u64,
+}
+
+impl Default for IdleConfig {
+    fn default() -> Self {
+        Self {
+            idle_after_ms: 3_000,
+            idle_video_interval_ms: 1_000,
+        }
+    }
+}
+
+/// Tracks activity to decide whether the next frame needs rendering.
+#[derive(Debug, Clone, Default)]
+pub struct IdleTracker {
+    config: IdleConfig,
+    /// Last input, sync or animation (ms).
+    last_activity_ms: u64,
+    /// The scene changed since the last rendered frame.
+    dirty: bool,
+    /// The camera moved during the last rendered frame.
+    animating: bool,
+    /// Render once this time is reached, even if idle (ms).
+    wake_at_ms: Option<u64>,
+    /// The last `needs_render` returned `false`.
+    asleep: bool,
+    /// Last accepted frame time per video stream (ms).
+    video_frames: HashMap<String, u64>,
+}
+
+impl IdleTracker {
+    /// Create a tracker with the default configuration.
+    ///
+    /// The first frame always renders.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::with_config(IdleConfig::default())
+    }
+
+    /// Create a tracker with a custom configuration.
+    #[must_use]
+    pub fn with_config(config: IdleConfig) -> Self {
+        Self {
+            config,
+            dirty: true,
+            ..Self::default()
+        }
+    }
+
+    /// Current configuration.
+    #[must_use]
+    pub fn config(&self) -> IdleConfig {
+        self.config
+    }
+
+    /// Replace the configuration.
+    pub fn set_config(&mut self, config: IdleConfig) {
+        self.config = config;
+    }
+
+    /// Record user input or sync traffic: the scene needs a redraw and the
+    /// idle timer restarts.
+    ///
+    /// Returns `true` if the render loop was asleep and must be restarted.
+    pub fn record_activity(&mut self, now_ms: u64) -> bool {
+        self.last_activity_ms = self.last_activity_ms.max(now_ms);
+        self.invalidate()
+    }
+
+    /// Request a redraw without restarting the idle timer.
+    ///
+    /// Returns `true` if the render loop was asleep and must be restarted.
+    pub fn invalidate(&mut self) -> bool {
+        self.dirty = true;
+        std::mem::take(&mut self.asleep)
+    }
+
+    /// Schedule a redraw at `at_ms`, e.g. when a spotlight expires.
+    ///
+    /// Keeps the earliest pending wake-up.
+    pub fn wake_at(&mut self, at_ms: u64) {
+        self.wake_at_ms = Some(self.wake_at_ms.map_or(at_ms, |current| current.min(at_ms)));
+    }
+
+    /// Check whether nothing has happened for `idle_after_ms`.
+    #[must_use]
+    pub fn is_idle(&self, now_ms: u64) -> bool {
+        !self.animating && now_ms.saturating_sub(self.last_activity_ms) >= self.config.idle_after_ms
+    }
+
+    /// Check whether the host loop stopped requesting frames.
+    #[must_use]
+    pub fn is_asleep(&self) -> bool {
+        self.asleep
+    }
+
+    /// Decide whether to render this frame.
+    ///
+    /// Call once per animation frame. When this returns `false` the host may
+    /// stop requesting frames until [`invalidate`](Self::invalidate) or
+    /// [`record_activity`](Self::record_activity) report a wake-up.
+    pub fn needs_render(&mut self, now_ms: u64) -> bool {
+        let wake_due = self.wake_at_ms.is_some_and(|at| now_ms >= at);
+        let needed = self.dirty || wake_due || !self.is_idle(now_ms);
+        self.asleep = !needed;
+        needed
+    }
+
+    /// Record that a frame was rendered. `animating` is whether the camera
+    /// moved, which counts as activity.
+    pub fn frame_rendered(&mut self, now_ms: u64, animating: bool) {
+        self.dirty = false;
+        self.animating = animating;
+        if animating {
+            self.last_activity_ms = self.last_activity_ms.max(now_ms);
+        }
+        if self.wake_at_ms.is_some_and(|at| now_ms >= at) {
+            self.wake_at_ms = None;
+        }
+    }
+
+    /// Decide whether to process a new frame of `stream_id`.
+    ///
+    /// Always `true` while active; while idle, at most one frame per
+    /// `idle_video_interval_ms` per stream.
+    pub fn accept_video_frame(&mut self, stream_id: &str, now_ms: u64) -> bool {
+        if self.is_idle(now_ms) {
+            if let Some(&last) = self.video_frames.get(stream_id) {
+                if now_ms.saturating_sub(last) < self.config.idle_video_interval_ms {
+                    return false;
+                }
+            }
+        }
+        self.video_frames.insert(stream_id.to_string(), now_ms);
+        true
+    }
+
+    /// Forget throttling state for a removed video stream.
+    pub fn remove_video_stream(&mut self, stream_id: &str) {
+        self.video_frames.remove(stream_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settled(now_ms: u64) -> IdleTracker {
+        let mut idle = IdleTracker::new();
+        assert!(idle.needs_render(0));
+        idle.frame_rendered(0, false);
+        assert!(idle.is_idle(now_ms));
+        idle
+    }
+
+    #[test]
+    fn test_goes_idle_and_sleeps() {
+        let mut idle = IdleTracker::new();
+        idle.record_activity(1_000);
+        assert!(idle.needs_render(1_000));
+        idle.frame_rendered(1_000, false);
+
+        // Still active shortly after input
+        assert!(idle.needs_render(2_000));
+        idle.frame_rendered(2_000, false);
+
+        assert!(!idle.needs_render(4_000));
+        assert!(idle.is_asleep());
+
+        // Invalidation renders one frame and reports the wake-up once
+        assert!(idle.invalidate());
+        assert!(!idle.invalidate());
+        assert!(idle.needs_render(4_100));
+        idle.frame_rendered(4_100, false);
+        assert!(!idle.needs_render(4_116));
+    }
+
+    #[test]
+    fn test_animation_keeps_active() {
+        let mut idle = settled(10_000);
+        idle.invalidate();
+        assert!(idle.needs_render(10_000));
+        idle.frame_rendered(10_000, true);
+        assert!(!idle.is_idle(20_000));
+        assert!(idle.needs_render(10_016));
+
+        idle.frame_rendered(10_016, false);
+        assert!(idle.needs_render(12_000));
+        assert!(!idle.needs_render(13_016));
+    }
+
+    #[test]
+    fn test_wake_at_renders_once() {
+        let mut idle = settled(10_000);
+        idle.wake_at(20_000);
+        idle.wake_at(15_000);
+        assert!(!idle.needs_render(14_999));
+        assert!(idle.needs_render(15_000));
+        idle.frame_rendered(15_000, false);
+        assert!(!idle.needs_render(15_016));
+    }
+
+    #[test]
+    fn test_video_throttled_only_when_idle() {
+        let mut idle = IdleTracker::new();
+        idle.record_activity(0);
+        assert!(idle.accept_video_frame("cam", 0));
+        assert!(idle.accept_video_frame("cam", 16));
+
+        // Idle: one frame per second per stream
+        assert!(idle.accept_video_frame("cam", 5_000));
+        assert!(!idle.accept_video_frame("cam", 5_500));
+        assert!(idle.accept_video_frame("screen", 5_500));
+        assert!(idle.accept_video_frame("cam", 6_000));
+
+        idle.remove_video_stream("cam");
+        assert!(idle.accept_video_frame("cam", 6_100));
+    }
+}
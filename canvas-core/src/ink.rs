@@ -0,0 +1,396 @@
+//! # Ink
+//!
+//! Freehand whiteboard strokes and the tools that edit them after they are
+//! drawn: a pixel eraser that cuts strokes apart, a stroke eraser that
+//! removes whole strokes, restyling, and lasso selection.
+//!
+//! ```text
+//!  pixel eraser              stroke eraser          lasso
+//!  ~~~~~(  )~~~~~            ~~~~~(  )~~~~~         ╭────────╮
+//!        ↓                          ↓               │ ~~~~   │  ~~~~
+//!  ~~~~~    ~~~~~  two pieces     (gone)            ╰────────╯  └ not inside
+//! ```
+//!
+//! An `Ink` element stores its points relative to its top-left corner at
+//! natural size ([`natural_size`]); resizing the element scales the
+//! stroke. The tools work on [`canvas_points`], where strokes meet the
+//! eraser and the lasso. Every edit is described by an [`InkEdit`] holding
+//! the affected elements before and after, which
+//! [`Scene::apply_ink_edit`] applies and [`InkEdit::inverse`] undoes.
+//!
+//! [`Scene::apply_ink_edit`]: crate::Scene::apply_ink_edit
+
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+
+use crate::color::ThemeColor;
+use crate::element::{Element, ElementId, ElementKind, Transform};
+use crate::error::{CanvasError, CanvasResult};
+
+/// Most points an ink stroke may hold.
+pub const MAX_INK_POINTS: usize = 4096;
+
+/// Widest an ink stroke may be, in pixels.
+pub const MAX_INK_WIDTH: f32 = 100.0;
+
+/// How an eraser treats the strokes it touches.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+#[serde(rename_all = "snake_case")]
+pub enum EraseMode {
+    /// Remove only the parts under the eraser, splitting strokes in two
+    /// where it cuts through them.
+    #[default]
+    Pixel,
+    /// Remove every stroke the eraser touches.
+    Stroke,
+}
+
+/// Natural `(width, height)` of a stroke through local `points`: their
+/// extent plus half the stroke width, and never smaller than the width.
+#[must_use]
+pub fn natural_size(points: &[[f32; 2]], width: f32) -> (f32, f32) {
+    let half = width / 2.0;
+    points.iter().fold((width, width), |(w, h), [x, y]| {
+        (w.max(x + half), h.max(y + half))
+    })
+}
+
+/// An `Ink` element through `points` in canvas coordinates, positioned to
+/// cover them with room for the stroke width.
+#[must_use]
+pub fn stroke(points: &[[f32; 2]], color: ThemeColor, width: f32) -> Element {
+    let half = width / 2.0;
+    let (min_x, min_y) = points
+        .iter()
+        .fold((f32::INFINITY, f32::INFINITY), |(mx, my), [x, y]| {
+            (mx.min(*x), my.min(*y))
+        });
+    let (min_x, min_y) = if min_x.is_finite() {
+        (min_x, min_y)
+    } else {
+        (0.0, 0.0)
+    };
+    let local: Vec<[f32; 2]> = points
+        .iter()
+        .map(|[x, y]| [x - min_x + half, y - min_y + half])
+        .collect();
+    let (natural_width, natural_height) = natural_size(&local, width);
+    Element::new(ElementKind::Ink {
+        points: local,
+        color,
+        width,
+    })
+    .with_transform(Transform {
+        x: min_x - half,
+        y: min_y - half,
+        width: natural_width,
+        height: natural_height,
+        rotation: 0.0,
+        z_index: 0,
+        auto_size: false,
+    })
+}
+
+/// A stroke through canvas `points` that takes every other attribute
+/// (style, visibility, stacking, parent, authorship) from `source`, under a
+/// new ID.
+#[must_use]
+pub fn restroke(source: &Element, points: &[[f32; 2]], color: ThemeColor, width: f32) -> Element {
+    let mut element = stroke(points, color, width);
+    element.transform.z_index = source.transform.z_index;
+    element.interactive = source.interactive;
+    element.visible = source.visible;
+    element.opacity = source.opacity;
+    element.parent = source.parent;
+    element.style = source.style.clone();
+    element.interactions = source.interactions.clone();
+    element.created_by.clone_from(&source.created_by);
+    element
+        .last_modified_by
+        .clone_from(&source.last_modified_by);
+    element
+}
+
+/// Points of an `Ink` element in canvas coordinates, scaled and rotated
+/// with its transform, and the stroke width at that scale.
+///
+/// Returns `None` for other kinds.
+#[must_use]
+pub fn canvas_points(element: &Element) -> Option<(Vec<[f32; 2]>, f32)> {
+    let ElementKind::Ink { points, width, .. } = &element.kind else {
+        return None;
+    };
+    let t = &element.transform;
+    let (natural_width, natural_height) = natural_size(points, *width);
+    let sx = t.width / natural_width;
+    let sy = t.height / natural_height;
+    let (sin, cos) = t.rotation.sin_cos();
+    let (cx, cy) = (t.x + t.width / 2.0, t.y + t.height / 2.0);
+    let mapped = points
+        .iter()
+        .map(|[x, y]| {
+            let (dx, dy) = (t.x + x * sx - cx, t.y + y * sy - cy);
+            [cx + dx * cos - dy * sin, cy + dx * sin + dy * cos]
+        })
+        .collect();
+    Some((mapped, width * (sx * sy).abs().sqrt()))
+}
+
+/// Check whether a stroke through `points` comes within `reach` of
+/// `center`.
+#[must_use]
+pub fn touches(points: &[[f32; 2]], center: [f32; 2], reach: f32) -> bool {
+    let reach_sq = reach * reach;
+    match points {
+        [] => false,
+        [point] => distance_sq(*point, center) <= reach_sq,
+        _ => points
+            .windows(2)
+            .any(|pair| segment_distance_sq(pair[0], pair[1], center) <= reach_sq),
+    }
+}
+
+/// Cut the parts of a stroke through `points` within `reach` of `center`.
+///
+/// Returns `None` if the stroke is untouched, otherwise the pieces left
+/// over, ending exactly at the eraser's edge. Pieces shorter than two
+/// points are dropped, so a fully erased stroke leaves none.
+#[must_use]
+pub fn erase(points: &[[f32; 2]], center: [f32; 2], reach: f32) -> Option<Vec<Vec<[f32; 2]>>> {
+    if !touches(points, center, reach) {
+        return None;
+    }
+    let reach_sq = reach * reach;
+    let mut pieces = Vec::new();
+    let mut current = Vec::new();
+    let mut close = |current: &mut Vec<[f32; 2]>| {
+        if current.len() >= 2 {
+            pieces.push(std::mem::take(current));
+        } else {
+            current.clear();
+        }
+    };
+    if let Some(&first) = points.first() {
+        if distance_sq(first, center) > reach_sq {
+            current.push(first);
+        }
+    }
+    for pair in points.windows(2) {
+        let (a, b) = (pair[0], pair[1]);
+        let Some((enter, exit)) = circle_overlap(a, b, center, reach_sq) else {
+            current.push(b);
+            continue;
+        };
+        if enter > 0.0 {
+            current.push(lerp(a, b, enter));
+        }
+        close(&mut current);
+        if exit < 1.0 {
+            current.push(lerp(a, b, exit));
+            current.push(b);
+        }
+    }
+    close(&mut current);
+    Some(pieces)
+}
+
+/// Check whether `point` lies inside the polygon `lasso`.
+#[must_use]
+pub fn in_lasso(point: [f32; 2], lasso: &[(f32, f32)]) -> bool {
+    let [x, y] = point;
+    let mut inside = false;
+    let Some(&(mut previous)) = lasso.last() else {
+        return false;
+    };
+    for &(px, py) in lasso {
+        let (qx, qy) = previous;
+        if (py > y) != (qy > y) && x < (qx - px) * (y - py) / (qy - py) + px {
+            inside = !inside;
+        }
+        previous = (px, py);
+    }
+    inside
+}
+
+/// Check a stroke's points and width.
+///
+/// # Errors
+///
+/// Returns [`CanvasError::InvalidOperation`] if there are no points or more
+/// than [`MAX_INK_POINTS`], a point is not finite, or the width is not in
+/// `(0, MAX_INK_WIDTH]`.
+pub fn validate(points: &[[f32; 2]], width: f32) -> CanvasResult<()> {
+    if points.is_empty() || points.len() > MAX_INK_POINTS {
+        return Err(CanvasError::InvalidOperation(format!(
+            "Ink strokes need 1 to {MAX_INK_POINTS} points, got {}",
+            points.len()
+        )));
+    }
+    if points.iter().flatten().any(|v| !v.is_finite()) {
+        return Err(CanvasError::InvalidOperation(
+            "Ink points must be finite".to_string(),
+        ));
+    }
+    if !(width > 0.0 && width <= MAX_INK_WIDTH) {
+        return Err(CanvasError::InvalidOperation(format!(
+            "Ink width must be greater than 0 and at most {MAX_INK_WIDTH}, got {width}"
+        )));
+    }
+    Ok(())
+}
+
+/// A change to ink strokes: the affected elements before and after.
+///
+/// Elements only in `before` are removed, elements only in `after` are
+/// added, and elements in both are replaced. Apply it with
+/// [`Scene::apply_ink_edit`](crate::Scene::apply_ink_edit); applying
+/// [`InkEdit::inverse`] undoes it.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct InkEdit {
+    /// Affected elements as they were.
+    pub before: Vec<Element>,
+    /// Affected elements as they are now.
+    pub after: Vec<Element>,
+}
+
+impl InkEdit {
+    /// Check whether the edit changes nothing.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.before.is_empty() && self.after.is_empty()
+    }
+
+    /// The edit that undoes this one.
+    #[must_use]
+    pub fn inverse(&self) -> Self {
+        Self {
+            before: self.after.clone(),
+            after: self.before.clone(),
+        }
+    }
+
+    /// IDs of the elements the edit removes.
+    #[must_use]
+    pub fn removed(&self) -> Vec<ElementId> {
+        let kept: HashSet<ElementId> = self.after.iter().map(|e| e.id).collect();
+        self.before
+            .iter()
+            .map(|e| e.id)
+            .filter(|id| !kept.contains(id))
+            .collect()
+    }
+
+    /// IDs of the elements the edit adds or replaces.
+    #[must_use]
+    pub fn changed(&self) -> Vec<ElementId> {
+        self.after.iter().map(|e| e.id).collect()
+    }
+}
+
+fn distance_sq(a: [f32; 2], b: [f32; 2]) -> f32 {
+    (a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2)
+}
+
+fn lerp(a: [f32; 2], b: [f32; 2], t: f32) -> [f32; 2] {
+    [a[0] + (b[0] - a[0]) * t, a[1] + (b[1] - a[1]) * t]
+}
+
+/// Squared distance from `point` to the segment `a`–`b`.
+fn segment_distance_sq(a: [f32; 2], b: [f32; 2], point: [f32; 2]) -> f32 {
+    let length_sq = distance_sq(a, b);
+    if length_sq <= f32::EPSILON {
+        return distance_sq(a, point);
+    }
+    let t = (((point[0] - a[0]) * (b[0] - a[0]) + (point[1] - a[1]) * (b[1] - a[1])) / length_sq)
+        .clamp(0.0, 1.0);
+    distance_sq(lerp(a, b, t), point)
+}
+
+/// Where the segment `a`–`b` is inside the circle, as parameters
+/// `(enter, exit)` within `0.0..=1.0`, or `None` if it stays outside.
+fn circle_overlap(a: [f32; 2], b: [f32; 2], center: [f32; 2], reach_sq: f32) -> Option<(f32, f32)> {
+    let d = [b[0] - a[0], b[1] - a[1]];
+    let f = [a[0] - center[0], a[1] - center[1]];
+    let qa = d[0] * d[0] + d[1] * d[1];
+    if qa <= f32::EPSILON {
+        return (distance_sq(a, center) <= reach_sq).then_some((0.0, 1.0));
+    }
+    let qb = 2.0 * (f[0] * d[0] + f[1] * d[1]);
+    let qc = f[0] * f[0] + f[1] * f[1] - reach_sq;
+    let discriminant = qb * qb - 4.0 * qa * qc;
+    if discriminant <= 0.0 {
+        return None;
+    }
+    let root = discriminant.sqrt();
+    let enter = (-qb - root) / (2.0 * qa);
+    let exit = (-qb + root) / (2.0 * qa);
+    (enter < 1.0 && exit > 0.0).then(|| (enter.max(0.0), exit.min(1.0)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Color;
+
+    fn assert_points(actual: &[[f32; 2]], expected: &[[f32; 2]]) {
+        assert_eq!(actual.len(), expected.len(), "{actual:?} != {expected:?}");
+        assert!(
+            actual
+                .iter()
+                .zip(expected)
+                .all(|(a, e)| (a[0] - e[0]).abs() < 1e-3 && (a[1] - e[1]).abs() < 1e-3),
+            "{actual:?} != {expected:?}"
+        );
+    }
+
+    #[test]
+    fn test_pixel_erase_splits_at_the_eraser_edge() {
+        let line = [[0.0, 0.0], [100.0, 0.0]];
+        let pieces = erase(&line, [50.0, 0.0], 10.0).expect("touched");
+        assert_eq!(pieces.len(), 2);
+        assert_points(&pieces[0], &[[0.0, 0.0], [40.0, 0.0]]);
+        assert_points(&pieces[1], &[[60.0, 0.0], [100.0, 0.0]]);
+
+        // Erasing an end trims it; missing leaves the stroke alone
+        let pieces = erase(&line, [0.0, 0.0], 10.0).expect("touched");
+        assert_points(&pieces[0], &[[10.0, 0.0], [100.0, 0.0]]);
+        assert!(erase(&line, [50.0, 20.0], 10.0).is_none());
+        assert_eq!(erase(&line, [50.0, 0.0], 80.0), Some(Vec::new()));
+
+        // Corners inside the eraser are dropped
+        let bend = [[0.0, 0.0], [50.0, 0.0], [50.0, 50.0]];
+        let pieces = erase(&bend, [50.0, 0.0], 5.0).expect("touched");
+        assert_points(&pieces[0], &[[0.0, 0.0], [45.0, 0.0]]);
+        assert_points(&pieces[1], &[[50.0, 5.0], [50.0, 50.0]]);
+    }
+
+    #[test]
+    fn test_strokes_map_to_canvas_and_lasso() {
+        let element = stroke(&[[10.0, 20.0], [30.0, 60.0]], Color::BLACK.into(), 4.0);
+        let t = element.transform;
+        assert!((t.x - 8.0).abs() < f32::EPSILON && (t.width - 24.0).abs() < f32::EPSILON);
+        let (points, width) = canvas_points(&element).expect("ink");
+        assert_points(&points, &[[10.0, 20.0], [30.0, 60.0]]);
+        assert!((width - 4.0).abs() < f32::EPSILON);
+
+        // Doubling the element doubles the stroke
+        let mut scaled = element.clone();
+        scaled.transform.width *= 2.0;
+        scaled.transform.height *= 2.0;
+        let (points, width) = canvas_points(&scaled).expect("ink");
+        assert_points(&points, &[[12.0, 22.0], [52.0, 102.0]]);
+        assert!((width - 8.0).abs() < f32::EPSILON);
+
+        let square = [(0.0, 0.0), (50.0, 0.0), (50.0, 50.0), (0.0, 50.0)];
+        assert!(in_lasso([10.0, 20.0], &square));
+        assert!(!in_lasso([30.0, 60.0], &square));
+        assert!(!in_lasso([10.0, 20.0], &square[..2]));
+
+        assert!(validate(&[[0.0, 0.0]], 3.0).is_ok());
+        assert!(validate(&[], 3.0).is_err());
+        assert!(validate(&[[f32::NAN, 0.0]], 3.0).is_err());
+        assert!(validate(&[[0.0, 0.0]], 0.0).is_err());
+    }
+}
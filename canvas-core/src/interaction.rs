@@ -0,0 +1,1048 @@
+//! # Element Interactions
+//!
+//! Declarative behaviors attached to an element, so agents can build
+//! interactive UI without host code:
+//!
+//! ```json
+//! "interactions": {
+//!   "on_tap": { "action": "approve", "payload": { "row": 3 } },
+//!   "on_drag": { "axis": "x", "action": "scrub" },
+//!   "on_long_press": { "items": [{ "label": "Delete", "action": "delete" }] }
+//! }
+//! ```
+//!
+//! [`InteractionRouter`] matches recognized gestures against these
+//! descriptors. A tap emits the element's action, a drag moves the element
+//! (constrained to one axis if requested) and a long press asks the host to
+//! show a context menu. Elements with no tap action may have a built-in one:
+//! tapping audio toggles its playback and emits `play` or `pause`, tapping a
+//! calendar day selects it and emits `select_day`, tapping a poll option
+//! emits `vote` and tapping a randomizer emits `randomize` (the server
+//! records votes and draws outcomes, so the scene is left as is). Tapping a
+//! floor plan marker emits `select_marker`, and dragging one moves it within
+//! the plan and emits `move_marker` instead of moving the element. Tapping
+//! a tree node with children collapses or expands it and emits
+//! `toggle_node`; tapping a leaf emits `select_node`. Dragging a 3D chart
+//! with no declared drag orbits its camera and emits `orbit` with the new
+//! `yaw` and `pitch` (see [`crate::chart3d`]).
+//! Each result is an [`ElementInteraction`], which hosts forward to the
+//! server as a `ClientMessage::Interaction` so agents receive it as an AG-UI
+//! interaction event.
+
+use serde::{Deserialize, Serialize};
+
+use crate::calendar::CalendarLayout;
+use crate::chart3d::{self, ORBIT_RADIANS_PER_PIXEL};
+use crate::element::PlaybackState;
+use crate::event::{Gesture, InputEvent, TouchPhase};
+use crate::floor_plan::{self, FloorMarker, FloorPlanLayout};
+use crate::geometry::OrientedRect;
+use crate::measure::MeasureContext;
+use crate::poll::PollLayout;
+use crate::protocol::ClientMessage;
+use crate::tree::{self, TreeLayout};
+use crate::{Element, ElementId, ElementKind, Scene};
+
+/// Declarative behaviors for an element.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+pub struct Interactions {
+    /// Action emitted when the element is tapped.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_tap: Option<TapAction>,
+    /// Drag behavior; the element is only draggable if set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_drag: Option<DragBehavior>,
+    /// Context menu shown on long press.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_long_press: Option<ContextMenu>,
+}
+
+impl Interactions {
+    /// Whether no behavior is declared.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.on_tap.is_none() && self.on_drag.is_none() && self.on_long_press.is_none()
+    }
+}
+
+/// An action emitted on tap.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+pub struct TapAction {
+    /// Action identifier reported to the agent.
+    pub action: String,
+    /// Extra data reported with the action.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub payload: Option<serde_json::Value>,
+}
+
+/// Axis a drag is constrained to.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+#[serde(rename_all = "lowercase")]
+pub enum DragAxis {
+    /// Free movement.
+    #[default]
+    Both,
+    /// Horizontal only.
+    X,
+    /// Vertical only.
+    Y,
+}
+
+impl DragAxis {
+    /// Constrain a movement to this axis.
+    #[must_use]
+    pub fn constrain(self, dx: f32, dy: f32) -> (f32, f32) {
+        match self {
+            Self::Both => (dx, dy),
+            Self::X => (dx, 0.0),
+            Self::Y => (0.0, dy),
+        }
+    }
+}
+
+/// How an element responds to dragging.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+pub struct DragBehavior {
+    /// Axis the element moves along.
+    #[serde(default)]
+    pub axis: DragAxis,
+    /// Action identifier reported with each move.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub action: Option<String>,
+}
+
+/// A context menu shown on long press.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+pub struct ContextMenu {
+    /// Menu entries, in display order.
+    pub items: Vec<ContextMenuItem>,
+}
+
+/// A context menu entry.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+pub struct ContextMenuItem {
+    /// Label shown to the user.
+    pub label: String,
+    /// Action reported when the entry is chosen.
+    pub action: String,
+}
+
+/// The outcome of a gesture on an element with declared interactions.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ElementInteraction {
+    /// A tap triggered the element's action.
+    Action {
+        /// The tapped element.
+        element_id: ElementId,
+        /// Action identifier.
+        action: String,
+        /// Extra data declared with the action.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        payload: Option<serde_json::Value>,
+    },
+    /// A drag moved the element.
+    Drag {
+        /// The dragged element.
+        element_id: ElementId,
+        /// Action identifier, if declared.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        action: Option<String>,
+        /// New X position.
+        x: f32,
+        /// New Y position.
+        y: f32,
+    },
+    /// A long press asks the host to show a context menu.
+    ContextMenu {
+        /// The pressed element.
+        element_id: ElementId,
+        /// Press X coordinate.
+        x: f32,
+        /// Press Y coordinate.
+        y: f32,
+        /// Menu entries to show.
+        items: Vec<ContextMenuItem>,
+    },
+}
+
+impl ElementInteraction {
+    /// The element the interaction happened on.
+    #[must_use]
+    pub fn element_id(&self) -> ElementId {
+        match self {
+            Self::Action { element_id, .. }
+            | Self::Drag { element_id, .. }
+            | Self::ContextMenu { element_id, .. } => *element_id,
+        }
+    }
+
+    /// Wrap the interaction for the sync WebSocket.
+    ///
+    /// The interaction type is `action`, `drag` or `context_menu`; the
+    /// remaining fields become the message `data`.
+    #[must_use]
+    pub fn to_client_message(&self, message_id: Option<String>) -> ClientMessage {
+        let mut data = serde_json::to_value(self).unwrap_or_default();
+        let interaction_type = data
+            .as_object_mut()
+            .and_then(|fields| {
+                fields.remove("element_id");
+                fields.remove("type")
+            })
+            .and_then(|t| t.as_str().map(str::to_string))
+            .unwrap_or_default();
+        ClientMessage::Interaction {
+            interaction_type,
+            element_id: Some(self.element_id().to_string()),
+            data,
+            message_id,
+        }
+    }
+}
+
+/// An element being dragged: the gesture start point and the element's
+/// position when the drag began.
+///
+/// For a floor plan marker, `origin` is the marker's position in plan units;
+/// for an orbiting 3D chart, its camera's yaw and pitch.
+#[derive(Debug, Clone)]
+struct ActiveDrag {
+    element_id: ElementId,
+    start: (f32, f32),
+    origin: (f32, f32),
+    marker: Option<String>,
+    orbit: bool,
+}
+
+/// Matches gestures against element [`Interactions`].
+#[derive(Debug, Clone, Default)]
+pub struct InteractionRouter {
+    drag: Option<ActiveDrag>,
+}
+
+impl InteractionRouter {
+    /// Create a router with no drag in progress.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Resolve an input event against the scene.
+    ///
+    /// Only the topmost interactive element under the gesture is considered.
+    /// Drags move the element in `scene`; a touch end or cancel finishes the
+    /// current drag.
+    pub fn handle(&mut self, scene: &mut Scene, event: &InputEvent) -> Option<ElementInteraction> {
+        match event {
+            InputEvent::Gesture(Gesture::Tap { x, y }) => {
+                self.drag = None;
+                let id = scene.element_at(*x, *y)?;
+                let element = scene.get_element(id)?;
+                if !element.interactive {
+                    return None;
+                }
+                if let Some(tap) = &element.interactions.on_tap {
+                    return Some(ElementInteraction::Action {
+                        element_id: element.id,
+                        action: tap.action.clone(),
+                        payload: tap.payload.clone(),
+                    });
+                }
+                match element.kind {
+                    ElementKind::Audio { .. } => Self::toggle_playback(scene.get_element_mut(id)?),
+                    ElementKind::Calendar { .. } => {
+                        Self::select_day(scene.get_element_mut(id)?, *x, *y)
+                    }
+                    ElementKind::Poll { .. } => Self::vote(element, *x, *y),
+                    ElementKind::FloorPlan { .. } => Self::select_marker(element, *x, *y),
+                    ElementKind::Tree { .. } => {
+                        Self::tap_tree_node(scene.get_element_mut(id)?, *x, *y)
+                    }
+                    ElementKind::Randomizer { ref source, .. } => {
+                        Some(ElementInteraction::Action {
+                            element_id: element.id,
+                            action: "randomize".to_string(),
+                            payload: Some(serde_json::json!({ "mode": source.mode() })),
+                        })
+                    }
+                    _ => None,
+                }
+            }
+            InputEvent::Gesture(Gesture::LongPress { x, y, .. }) => {
+                self.drag = None;
+                let element = scene.get_element(scene.element_at(*x, *y)?)?;
+                let menu = element.interactions.on_long_press.as_ref()?;
+                element
+                    .interactive
+                    .then(|| ElementInteraction::ContextMenu {
+                        element_id: element.id,
+                        x: *x,
+                        y: *y,
+                        items: menu.items.clone(),
+                    })
+            }
+            InputEvent::Gesture(Gesture::Drag {
+                start_x,
+                start_y,
+                current_x,
+                current_y,
+                ..
+            }) => self.drag(scene, (*start_x, *start_y), (*current_x, *current_y)),
+            InputEvent::Touch(touch)
+                if matches!(touch.phase, TouchPhase::End | TouchPhase::Cancel) =>
+            {
+                self.drag = None;
+                None
+            }
+            _ => None,
+        }
+    }
+
+    /// Play or pause an audio element without a declared tap action,
+    /// reporting `play` or `pause` with its stream ID.
+    fn toggle_playback(element: &mut Element) -> Option<ElementInteraction> {
+        let ElementKind::Audio {
+            stream_id, state, ..
+        } = &mut element.kind
+        else {
+            return None;
+        };
+        *state = state.toggled();
+        let action = if *state == PlaybackState::Playing {
+            "play"
+        } else {
+            "pause"
+        };
+        Some(ElementInteraction::Action {
+            element_id: element.id,
+            action: action.to_string(),
+            payload: Some(serde_json::json!({ "stream_id": stream_id })),
+        })
+    }
+
+    /// Select the calendar day under a canvas point, reporting
+    /// `select_day` with its `YYYY-MM-DD` date.
+    fn select_day(element: &mut Element, x: f32, y: f32) -> Option<ElementInteraction> {
+        let (local_x, local_y) =
+            OrientedRect::from_transform(&element.transform).local_offset(x, y);
+        let ElementKind::Calendar {
+            view,
+            date,
+            selected,
+            ..
+        } = &mut element.kind
+        else {
+            return None;
+        };
+        let layout = CalendarLayout::new(
+            *view,
+            date,
+            element.transform.width,
+            element.transform.height,
+        )?;
+        let day = layout.cell_at(local_x, local_y)?.date.to_string();
+        *selected = Some(day.clone());
+        Some(ElementInteraction::Action {
+            element_id: element.id,
+            action: "select_day".to_string(),
+            payload: Some(serde_json::json!({ "date": day })),
+        })
+    }
+
+    /// Report a `vote` for the poll option under a canvas point, with its
+    /// index and label. Closed polls take no votes.
+    fn vote(element: &Element, x: f32, y: f32) -> Option<ElementInteraction> {
+        let ElementKind::Poll {
+            options, closed, ..
+        } = &element.kind
+        else {
+            return None;
+        };
+        if *closed {
+            return None;
+        }
+        let (local_x, local_y) =
+            OrientedRect::from_transform(&element.transform).local_offset(x, y);
+        let layout = PollLayout::new(
+            options.len(),
+            element.transform.width,
+            element.transform.height,
+        );
+        let option = layout.option_at(local_x, local_y)?.index;
+        Some(ElementInteraction::Action {
+            element_id: element.id,
+            action: "vote".to_string(),
+            payload: Some(serde_json::json!({ "option": option, "label": options[option] })),
+        })
+    }
+
+    /// Report `select_marker` for the floor plan marker under a canvas point,
+    /// with its ID, label and occupancy.
+    fn select_marker(element: &Element, x: f32, y: f32) -> Option<ElementInteraction> {
+        let (layout, markers) = Self::floor_plan(element)?;
+        let (local_x, local_y) =
+            OrientedRect::from_transform(&element.transform).local_offset(x, y);
+        let marker = &markers[layout.marker_at(markers, local_x, local_y)?];
+        Some(ElementInteraction::Action {
+            element_id: element.id,
+            action: "select_marker".to_string(),
+            payload: Some(serde_json::json!({
+                "marker": marker.id,
+                "label": marker.display_label(),
+                "occupancy": marker.occupancy,
+                "occupant": marker.occupant,
+            })),
+        })
+    }
+
+    /// Collapse or expand the tree node under a canvas point, reporting
+    /// `toggle_node` with its new state, or report `select_node` for a leaf.
+    fn tap_tree_node(element: &mut Element, x: f32, y: f32) -> Option<ElementInteraction> {
+        let (local_x, local_y) =
+            OrientedRect::from_transform(&element.transform).local_offset(x, y);
+        let ElementKind::Tree { root, direction } = &element.kind else {
+            return None;
+        };
+        let layout = TreeLayout::new(
+            root,
+            *direction,
+            element.transform.width,
+            element.transform.height,
+        );
+        let node = layout.boxes[layout.node_at(local_x, local_y)?].node;
+        let (id, label) = (node.id.clone(), node.label.clone());
+        if node.children.is_empty() {
+            return Some(ElementInteraction::Action {
+                element_id: element.id,
+                action: "select_node".to_string(),
+                payload: Some(serde_json::json!({ "node": id, "label": label })),
+            });
+        }
+        let collapsed = !node.collapsed;
+        tree::set_collapsed(&mut element.kind, &id, collapsed).ok()?;
+        element.apply_auto_size(&MeasureContext::default());
+        Some(ElementInteraction::Action {
+            element_id: element.id,
+            action: "toggle_node".to_string(),
+            payload: Some(serde_json::json!({
+                "node": id,
+                "label": label,
+                "collapsed": collapsed,
+            })),
+        })
+    }
+
+    /// Layout and markers of a floor plan element.
+    fn floor_plan(element: &Element) -> Option<(FloorPlanLayout, &[FloorMarker])> {
+        let ElementKind::FloorPlan {
+            plan_width,
+            plan_height,
+            markers,
+            ..
+        } = &element.kind
+        else {
+            return None;
+        };
+        let layout = FloorPlanLayout::new(
+            *plan_width,
+            *plan_height,
+            element.transform.width,
+            element.transform.height,
+        );
+        Some((layout, markers))
+    }
+
+    /// A canvas point in a floor plan element's plan units.
+    fn plan_point(element: &Element, (x, y): (f32, f32)) -> Option<(f32, f32)> {
+        let (layout, _) = Self::floor_plan(element)?;
+        let (local_x, local_y) =
+            OrientedRect::from_transform(&element.transform).local_offset(x, y);
+        Some(layout.to_plan(local_x, local_y))
+    }
+
+    /// Start dragging the marker of an unlocked floor plan under `start`.
+    fn start_marker_drag(element: &Element, start: (f32, f32)) -> Option<ActiveDrag> {
+        if matches!(element.kind, ElementKind::FloorPlan { locked: true, .. }) {
+            return None;
+        }
+        let (layout, markers) = Self::floor_plan(element)?;
+        let (local_x, local_y) =
+            OrientedRect::from_transform(&element.transform).local_offset(start.0, start.1);
+        let marker = &markers[layout.marker_at(markers, local_x, local_y)?];
+        Some(ActiveDrag {
+            element_id: element.id,
+            start,
+            origin: (marker.x, marker.y),
+            marker: Some(marker.id.clone()),
+            orbit: false,
+        })
+    }
+
+    /// Move a dragged floor plan marker, reporting `move_marker` with its
+    /// new position in plan units.
+    fn drag_marker(
+        element: &mut Element,
+        active: &ActiveDrag,
+        marker: &str,
+        current: (f32, f32),
+    ) -> Option<ElementInteraction> {
+        let (start_x, start_y) = Self::plan_point(element, active.start)?;
+        let (current_x, current_y) = Self::plan_point(element, current)?;
+        let (x, y) = (
+            active.origin.0 + current_x - start_x,
+            active.origin.1 + current_y - start_y,
+        );
+        floor_plan::move_marker(&mut element.kind, marker, x, y).ok()?;
+        let ElementKind::FloorPlan { markers, .. } = &element.kind else {
+            return None;
+        };
+        let moved = markers.iter().find(|m| m.id == marker)?;
+        Some(ElementInteraction::Action {
+            element_id: element.id,
+            action: "move_marker".to_string(),
+            payload: Some(serde_json::json!({ "marker": marker, "x": moved.x, "y": moved.y })),
+        })
+    }
+
+    /// Orbit a dragged 3D chart's camera, reporting `orbit` with its new
+    /// yaw and pitch. Dragging right turns the chart's front to the right
+    /// and dragging down looks at it from higher up.
+    fn orbit_chart(
+        scene: &mut Scene,
+        active: &ActiveDrag,
+        current: (f32, f32),
+    ) -> Option<ElementInteraction> {
+        let yaw = active.origin.0 - (current.0 - active.start.0) * ORBIT_RADIANS_PER_PIXEL;
+        let pitch = active.origin.1 + (current.1 - active.start.1) * ORBIT_RADIANS_PER_PIXEL;
+        let mut camera = None;
+        scene
+            .update_element(active.element_id, |element| {
+                camera = chart3d::orbit(&mut element.kind, yaw, pitch).ok();
+            })
+            .ok()?;
+        let camera = camera?;
+        Some(ElementInteraction::Action {
+            element_id: active.element_id,
+            action: "orbit".to_string(),
+            payload: Some(serde_json::json!({ "yaw": camera.yaw, "pitch": camera.pitch })),
+        })
+    }
+
+    fn drag(
+        &mut self,
+        scene: &mut Scene,
+        start: (f32, f32),
+        current: (f32, f32),
+    ) -> Option<ElementInteraction> {
+        // A new start point means a new drag
+        let active = match &self.drag {
+            Some(active) if active.start == start => active.clone(),
+            _ => {
+                let element = scene.get_element(scene.element_at(start.0, start.1)?)?;
+                if !element.interactive {
+                    self.drag = None;
+                    return None;
+                }
+                // Markers move within their plan and 3D charts orbit;
+                // otherwise the element moves
+                let camera = chart3d::chart_camera(&element.kind);
+                match (Self::start_marker_drag(element, start), camera) {
+                    (Some(active), _) => active,
+                    (None, _) if element.interactions.on_drag.is_some() => ActiveDrag {
+                        element_id: element.id,
+                        start,
+                        origin: (element.transform.x, element.transform.y),
+                        marker: None,
+                        orbit: false,
+                    },
+                    (None, Some(camera)) => ActiveDrag {
+                        element_id: element.id,
+                        start,
+                        origin: (camera.yaw, camera.pitch),
+                        marker: None,
+                        orbit: true,
+                    },
+                    (None, None) => {
+                        self.drag = None;
+                        return None;
+                    }
+                }
+            }
+        };
+        self.drag = Some(active.clone());
+
+        if let Some(marker) = &active.marker {
+            let element = scene.get_element_mut(active.element_id)?;
+            return Self::drag_marker(element, &active, marker, current);
+        }
+        if active.orbit {
+            return Self::orbit_chart(scene, &active, current);
+        }
+        let behavior = scene
+            .get_element(active.element_id)?
+            .interactions
+            .on_drag
+            .clone()?;
+        let (dx, dy) = behavior
+            .axis
+            .constrain(current.0 - start.0, current.1 - start.1);
+        let (x, y) = (active.origin.0 + dx, active.origin.1 + dy);
+        // Through update_element so attached elements follow
+        scene
+            .update_element(active.element_id, |element| {
+                element.transform.x = x;
+                element.transform.y = y;
+            })
+            .ok()?;
+        Some(ElementInteraction::Drag {
+            element_id: active.element_id,
+            action: behavior.action,
+            x,
+            y,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CalendarView, Color, Transform};
+
+    fn button(interactions: Interactions) -> Element {
+        let mut element = Element::new(ElementKind::Text {
+            content: "OK".to_string(),
+            font_size: 16.0,
+            color: Color::BLACK.into(),
+        })
+        .with_transform(Transform {
+            x: 10.0,
+            y: 10.0,
+            width: 100.0,
+            height: 40.0,
+            rotation: 0.0,
+            z_index: 0,
+            auto_size: false,
+        });
+        element.interactions = interactions;
+        element
+    }
+
+    fn drag(start: (f32, f32), current: (f32, f32)) -> InputEvent {
+        InputEvent::Gesture(Gesture::Drag {
+            start_x: start.0,
+            start_y: start.1,
+            current_x: current.0,
+            current_y: current.1,
+            delta_x: 0.0,
+            delta_y: 0.0,
+        })
+    }
+
+    #[test]
+    fn test_tap_emits_declared_action() {
+        let mut scene = Scene::new(800.0, 600.0);
+        let id = scene.add_element(button(Interactions {
+            on_tap: Some(TapAction {
+                action: "approve".to_string(),
+                payload: Some(serde_json::json!({ "row": 3 })),
+            }),
+            ..Interactions::default()
+        }));
+        let mut router = InteractionRouter::new();
+
+        let tap = router.handle(
+            &mut scene,
+            &InputEvent::Gesture(Gesture::Tap { x: 20.0, y: 20.0 }),
+        );
+        assert_eq!(
+            tap,
+            Some(ElementInteraction::Action {
+                element_id: id,
+                action: "approve".to_string(),
+                payload: Some(serde_json::json!({ "row": 3 })),
+            })
+        );
+
+        // Empty space and elements without the behavior emit nothing
+        let miss = InputEvent::Gesture(Gesture::Tap { x: 500.0, y: 500.0 });
+        assert!(router.handle(&mut scene, &miss).is_none());
+        let press = InputEvent::Gesture(Gesture::LongPress {
+            x: 20.0,
+            y: 20.0,
+            duration_ms: 600,
+        });
+        assert!(router.handle(&mut scene, &press).is_none());
+    }
+
+    #[test]
+    fn test_tap_toggles_audio_playback() {
+        let mut scene = Scene::new(800.0, 600.0);
+        let id = scene.add_element(
+            Element::new(ElementKind::Audio {
+                stream_id: "tts-1".to_string(),
+                waveform: vec![0.1, 0.4],
+                state: PlaybackState::Ended,
+            })
+            .with_transform(Transform {
+                x: 10.0,
+                y: 10.0,
+                width: 200.0,
+                height: 40.0,
+                rotation: 0.0,
+                z_index: 0,
+                auto_size: false,
+            }),
+        );
+        let mut router = InteractionRouter::new();
+        let tap = InputEvent::Gesture(Gesture::Tap { x: 20.0, y: 20.0 });
+
+        let played = router.handle(&mut scene, &tap).expect("play");
+        assert_eq!(
+            played,
+            ElementInteraction::Action {
+                element_id: id,
+                action: "play".to_string(),
+                payload: Some(serde_json::json!({ "stream_id": "tts-1" })),
+            }
+        );
+        let paused = router.handle(&mut scene, &tap).expect("pause");
+        assert!(
+            matches!(paused, ElementInteraction::Action { ref action, .. } if action == "pause")
+        );
+        assert!(matches!(
+            scene.get_element(id).map(|e| &e.kind),
+            Some(ElementKind::Audio {
+                state: PlaybackState::Paused,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_tap_selects_calendar_day() {
+        let mut scene = Scene::new(800.0, 600.0);
+        let id = scene.add_element(
+            Element::new(ElementKind::Calendar {
+                view: CalendarView::Week,
+                date: "2026-10-17".to_string(),
+                events: Vec::new(),
+                selected: None,
+            })
+            .with_transform(Transform {
+                x: 100.0,
+                y: 100.0,
+                width: 700.0,
+                height: 200.0,
+                rotation: 0.0,
+                z_index: 0,
+                auto_size: false,
+            }),
+        );
+        let mut router = InteractionRouter::new();
+
+        // Third column of the week starting Monday 12 October
+        let tap = InputEvent::Gesture(Gesture::Tap { x: 350.0, y: 250.0 });
+        assert_eq!(
+            router.handle(&mut scene, &tap),
+            Some(ElementInteraction::Action {
+                element_id: id,
+                action: "select_day".to_string(),
+                payload: Some(serde_json::json!({ "date": "2026-10-14" })),
+            })
+        );
+        assert!(matches!(
+            scene.get_element(id).map(|e| &e.kind),
+            Some(ElementKind::Calendar { selected: Some(day), .. }) if day == "2026-10-14"
+        ));
+
+        // The title band is not a day
+        let title = InputEvent::Gesture(Gesture::Tap { x: 350.0, y: 105.0 });
+        assert!(router.handle(&mut scene, &title).is_none());
+    }
+
+    #[test]
+    fn test_tap_votes_on_poll_option() {
+        let mut scene = Scene::new(800.0, 600.0);
+        let poll = ElementKind::Poll {
+            question: "Lunch?".to_string(),
+            options: vec!["Pizza".to_string(), "Salad".to_string()],
+            votes: std::collections::BTreeMap::new(),
+            closed: false,
+        };
+        let id = scene.add_element(Element::new(poll.clone()).with_transform(Transform {
+            x: 0.0,
+            y: 0.0,
+            width: 300.0,
+            height: 200.0,
+            rotation: 0.0,
+            z_index: 0,
+            auto_size: false,
+        }));
+        let mut router = InteractionRouter::new();
+
+        // Second row: 36px question band, then two 79px slots
+        let tap = InputEvent::Gesture(Gesture::Tap { x: 150.0, y: 150.0 });
+        assert_eq!(
+            router.handle(&mut scene, &tap),
+            Some(ElementInteraction::Action {
+                element_id: id,
+                action: "vote".to_string(),
+                payload: Some(serde_json::json!({ "option": 1, "label": "Salad" })),
+            })
+        );
+        // The vote is left to the server
+        assert_eq!(scene.get_element(id).map(|e| &e.kind), Some(&poll));
+
+        if let Some(ElementKind::Poll { closed, .. }) =
+            scene.get_element_mut(id).map(|e| &mut e.kind)
+        {
+            *closed = true;
+        }
+        assert!(router.handle(&mut scene, &tap).is_none());
+    }
+
+    #[test]
+    fn test_tap_requests_randomizer_draw() {
+        let mut scene = Scene::new(800.0, 600.0);
+        let id = scene.add_element(Element::new(ElementKind::Randomizer {
+            source: crate::RandomSource::Dice { count: 2, sides: 6 },
+            outcome: None,
+        }));
+        let mut router = InteractionRouter::new();
+
+        let tap = InputEvent::Gesture(Gesture::Tap { x: 50.0, y: 50.0 });
+        assert_eq!(
+            router.handle(&mut scene, &tap),
+            Some(ElementInteraction::Action {
+                element_id: id,
+                action: "randomize".to_string(),
+                payload: Some(serde_json::json!({ "mode": "dice" })),
+            })
+        );
+        // The draw is left to the server
+        assert!(matches!(
+            scene.get_element(id).map(|e| &e.kind),
+            Some(ElementKind::Randomizer { outcome: None, .. })
+        ));
+    }
+
+    #[test]
+    fn test_floor_plan_markers_select_and_move() {
+        use crate::floor_plan::{MarkerShape, Occupancy};
+
+        let mut scene = Scene::new(800.0, 600.0);
+        let mut seat = FloorMarker::new("A1", MarkerShape::Seat, 100.0, 50.0);
+        seat.occupancy = Occupancy::Reserved;
+        // A 400 × 200 plan at scale 1, its origin at (6, 18)
+        let id = scene.add_element(
+            Element::new(ElementKind::FloorPlan {
+                background: None,
+                plan_width: 400.0,
+                plan_height: 200.0,
+                paths: Vec::new(),
+                markers: vec![seat],
+                locked: false,
+            })
+            .with_transform(Transform {
+                x: 0.0,
+                y: 0.0,
+                width: 412.0,
+                height: 260.0,
+                rotation: 0.0,
+                z_index: 0,
+                auto_size: false,
+            }),
+        );
+        let mut router = InteractionRouter::new();
+
+        let tap = InputEvent::Gesture(Gesture::Tap { x: 106.0, y: 68.0 });
+        assert_eq!(
+            router.handle(&mut scene, &tap),
+            Some(ElementInteraction::Action {
+                element_id: id,
+                action: "select_marker".to_string(),
+                payload: Some(serde_json::json!({
+                    "marker": "A1",
+                    "label": "A1",
+                    "occupancy": "reserved",
+                    "occupant": null,
+                })),
+            })
+        );
+
+        // Dragging the seat moves it within the plan, not the element
+        router.handle(&mut scene, &drag((106.0, 68.0), (126.0, 78.0)));
+        let moved = router.handle(&mut scene, &drag((106.0, 68.0), (156.0, 98.0)));
+        assert_eq!(
+            moved,
+            Some(ElementInteraction::Action {
+                element_id: id,
+                action: "move_marker".to_string(),
+                payload: Some(serde_json::json!({ "marker": "A1", "x": 150.0, "y": 80.0 })),
+            })
+        );
+        let element = scene.get_element(id).expect("element");
+        assert!(element.transform.x.abs() < f32::EPSILON);
+
+        // Empty floor and locked plans do not drag
+        assert!(router
+            .handle(&mut scene, &drag((300.0, 150.0), (320.0, 160.0)))
+            .is_none());
+        if let Some(ElementKind::FloorPlan { locked, .. }) =
+            scene.get_element_mut(id).map(|e| &mut e.kind)
+        {
+            *locked = true;
+        }
+        assert!(router
+            .handle(&mut scene, &drag((156.0, 98.0), (200.0, 110.0)))
+            .is_none());
+    }
+
+    #[test]
+    fn test_tree_nodes_toggle_and_select() {
+        use crate::tree::{TreeDirection, TreeNode};
+
+        let mut scene = Scene::new(800.0, 600.0);
+        let root = TreeNode::new("ceo", "CEO").with_children(vec![TreeNode::new("cto", "CTO")]);
+        let mut element = Element::new(ElementKind::Tree {
+            root,
+            direction: TreeDirection::Down,
+        })
+        .with_transform(Transform {
+            auto_size: true,
+            ..Transform::default()
+        });
+        // Auto-sized at scale 1: CEO at (8, 8), CTO 84 below
+        element.apply_auto_size(&MeasureContext::default());
+        let id = scene.add_element(element);
+        let mut router = InteractionRouter::new();
+
+        let tap = |y: f32| InputEvent::Gesture(Gesture::Tap { x: 78.0, y });
+        assert_eq!(
+            router.handle(&mut scene, &tap(30.0)),
+            Some(ElementInteraction::Action {
+                element_id: id,
+                action: "toggle_node".to_string(),
+                payload: Some(serde_json::json!({
+                    "node": "ceo",
+                    "label": "CEO",
+                    "collapsed": true,
+                })),
+            })
+        );
+        // Collapsed to one node, the element shrinks to fit it
+        let element = scene.get_element(id).expect("tree");
+        assert!(matches!(&element.kind, ElementKind::Tree { root, .. } if root.collapsed));
+        assert!((element.transform.height - 64.0).abs() < f32::EPSILON);
+
+        router.handle(&mut scene, &tap(30.0));
+        assert_eq!(
+            router.handle(&mut scene, &tap(115.0)),
+            Some(ElementInteraction::Action {
+                element_id: id,
+                action: "select_node".to_string(),
+                payload: Some(serde_json::json!({ "node": "cto", "label": "CTO" })),
+            })
+        );
+    }
+
+    #[test]
+    fn test_drag_orbits_3d_charts() {
+        let mut scene = Scene::new(800.0, 600.0);
+        let id = scene.add_element(
+            Element::new(ElementKind::Chart {
+                chart_type: "scatter3d".to_string(),
+                data: serde_json::json!({ "camera": { "yaw": 1.0, "pitch": 0.2 } }),
+                binding: None,
+            })
+            .with_transform(Transform {
+                width: 300.0,
+                height: 200.0,
+                ..Transform::default()
+            }),
+        );
+        let mut router = InteractionRouter::new();
+
+        let Some(ElementInteraction::Action {
+            element_id,
+            action,
+            payload: Some(payload),
+        }) = router.handle(&mut scene, &drag((100.0, 100.0), (80.0, 130.0)))
+        else {
+            panic!("dragging a 3D chart should orbit it");
+        };
+        assert_eq!((element_id, action.as_str()), (id, "orbit"));
+        let camera =
+            chart3d::chart_camera(&scene.get_element(id).expect("chart").kind).expect("3D chart");
+        assert!((camera.yaw - 1.2).abs() < 1e-5);
+        assert!((camera.pitch - 0.5).abs() < 1e-5);
+        assert_eq!(payload["yaw"], serde_json::json!(camera.yaw));
+        // The chart stays put
+        assert!(scene.get_element(id).expect("chart").transform.x.abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn test_drag_is_constrained_to_axis() {
+        let mut scene = Scene::new(800.0, 600.0);
+        let id = scene.add_element(button(Interactions {
+            on_drag: Some(DragBehavior {
+                axis: DragAxis::X,
+                action: Some("scrub".to_string()),
+            }),
+            ..Interactions::default()
+        }));
+        let mut router = InteractionRouter::new();
+
+        router.handle(&mut scene, &drag((20.0, 20.0), (40.0, 50.0)));
+        // The element has moved away from the start point; the drag continues
+        let moved = router.handle(&mut scene, &drag((20.0, 20.0), (70.0, 90.0)));
+
+        assert_eq!(
+            moved,
+            Some(ElementInteraction::Drag {
+                element_id: id,
+                action: Some("scrub".to_string()),
+                x: 60.0,
+                y: 10.0,
+            })
+        );
+        let transform = scene.get_element(id).expect("element").transform;
+        assert!((transform.x - 60.0).abs() < f32::EPSILON);
+        assert!((transform.y - 10.0).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn test_interaction_client_message_shape() {
+        let id = ElementId::new();
+        let menu = ElementInteraction::ContextMenu {
+            element_id: id,
+            x: 5.0,
+            y: 6.0,
+            items: vec![ContextMenuItem {
+                label: "Delete".to_string(),
+                action: "delete".to_string(),
+            }],
+        };
+
+        let ClientMessage::Interaction {
+            interaction_type,
+            element_id,
+            data,
+            ..
+        } = menu.to_client_message(None)
+        else {
+            panic!("expected an interaction message");
+        };
+        assert_eq!(interaction_type, "context_menu");
+        assert_eq!(element_id, Some(id.to_string()));
+        assert_eq!(data["items"][0]["action"], "delete");
+        assert!(data.get("element_id").is_none());
+    }
+}
@@ -27,32 +27,157 @@
 #![allow(clippy::module_name_repetitions)]
 
 pub mod a2ui;
+pub mod agenda;
+pub mod anchor;
+pub mod annotation;
+pub mod archive;
+pub mod asset;
+pub mod attachment;
+pub mod barcode;
+pub mod branding;
+pub mod calendar;
+pub mod camera;
+pub mod changes;
+pub mod chart3d;
+pub mod color;
+pub mod dashboard;
+pub mod data;
+pub mod diagnostics;
 pub mod element;
+pub mod encoding;
 pub mod error;
 pub mod event;
+pub mod floor_plan;
+pub mod follow;
 pub mod fusion;
+pub mod geometry;
+pub mod highlight;
+pub mod idle;
+pub mod ink;
+pub mod interaction;
+pub mod lint;
+pub mod log_view;
+pub mod measure;
+pub mod merge;
+pub mod model3d;
+pub mod ocr;
 pub mod offline;
+pub mod poll;
+pub mod protocol;
+pub mod randomizer;
+pub mod redact;
 pub mod scene;
 pub mod schema;
+pub mod selection;
+pub mod shader;
+pub mod shape;
+pub mod sparkline;
+pub mod spatial;
+pub mod spotlight;
 pub mod state;
 pub mod store;
+pub mod style;
+pub mod template;
+pub mod transition;
+pub mod tree;
+#[cfg(feature = "ts")]
+pub mod typescript;
+pub mod units;
+pub mod view_state;
 
 #[cfg(feature = "wasm")]
 pub mod wasm;
 
 pub use a2ui::{A2UINode, A2UIStyle, A2UITree, ConversionResult, Layout};
+pub use agenda::{
+    AgendaCommand, AgendaItem, AgendaLayout, AgendaStatus, MAX_AGENDA_ITEMS, MAX_AGENDA_ITEM_SECS,
+};
+pub use anchor::{Anchor, MAX_ANCHOR_NAME_LEN};
+pub use annotation::{MeasurementKind, MeasurementLayout};
+pub use archive::{
+    ArchiveEntry, ArchiveError, ArchivedAsset, SessionArchive, ARCHIVE_VERSION, MAX_ARCHIVE_HISTORY,
+};
+pub use asset::{Asset, AssetError, AssetId, AssetStore};
+pub use attachment::{AttachPoint, Attachment};
+pub use barcode::{
+    BarcodeLayout, ErrorCorrection, Symbology, MAX_BARCODE_PAYLOAD_LEN, MAX_CODE128_LEN,
+};
+pub use branding::{Branding, BrandingPosition, MAX_WATERMARK_LEN};
+pub use calendar::{CalendarDate, CalendarEvent, CalendarLayout, CalendarView};
+pub use camera::{CameraCommand, FitOptions, SceneBounds};
+pub use changes::{SceneChanges, MAX_TOMBSTONES};
+pub use color::{
+    Color, ColorError, Theme, ThemeColor, MAX_PALETTE_COLORS, MAX_THEME_ENTRIES, MAX_THEME_NAME_LEN,
+};
+pub use dashboard::{
+    GaugeLayout, GaugeShape, KpiDelta, KpiLayout, NumberFormat, Threshold, MAX_THRESHOLDS,
+};
+pub use data::{
+    Aggregate, DataBinding, DataFilter, DataQuery, DataSort, DataStore, Dataset, DatasetDelta,
+    FilterOp, QueryResult, MAX_DATASETS, MAX_DATASET_COLUMNS, MAX_DATASET_NAME_LEN,
+    MAX_DATASET_ROWS,
+};
+pub use diagnostics::{IssueKind, IssueSeverity, SceneIssue, SceneStats};
 pub use element::{
-    CropRect, Element, ElementId, ElementKind, ImageFormat, MediaConfig, MediaStats, QualityPreset,
-    Resolution, Transform,
+    downsample_waveform, CalloutTarget, CropRect, Element, ElementId, ElementKind, ImageFormat,
+    MediaConfig, MediaStats, PlaybackState, QualityPreset, Resolution, Transform,
+    MAX_WAVEFORM_POINTS,
 };
+pub use encoding::{Encoding, EncodingError};
 pub use error::{CanvasError, CanvasResult};
-pub use event::{InputEvent, TouchEvent, TouchPhase, TouchPoint, VoiceEvent};
-pub use fusion::{FusedIntent, FusionConfig, FusionResult, InputFusion, VoiceOnlyIntent};
+pub use event::{Gesture, InputEvent, TouchEvent, TouchPhase, TouchPoint, VoiceEvent};
+pub use floor_plan::{
+    FloorMarker, FloorPlanLayout, MarkerShape, Occupancy, OccupancySummary, PlanPath,
+    MAX_FLOOR_MARKERS,
+};
+pub use follow::{AgentFollow, CameraFrame, FollowConfig};
+pub use fusion::{
+    DisambiguationCandidate, DisambiguationRequest, FusedIntent, FusionClock, FusionConfig,
+    FusionResult, InputFusion, VoiceOnlyIntent, DISAMBIGUATION_RADIUS,
+};
+pub use geometry::{polygon_contains, OrientedRect};
+pub use highlight::{HighlightSpan, TokenKind};
+pub use idle::{IdleConfig, IdleTracker};
+pub use ink::{EraseMode, InkEdit, MAX_INK_POINTS, MAX_INK_WIDTH};
+pub use interaction::{
+    ContextMenu, ContextMenuItem, DragAxis, DragBehavior, ElementInteraction, InteractionRouter,
+    Interactions, TapAction,
+};
+pub use lint::{LintConfig, LintFinding, LintFix, LintRule};
+pub use log_view::{LogAppend, DEFAULT_LOG_LINES, MAX_LOG_LINES, MAX_LOG_LINE_LEN};
+pub use measure::{MeasureContext, Size};
+pub use merge::{CollisionPolicy, MergeOptions, MergeReport};
+pub use model3d::{
+    Light, Lighting, ModelCamera, Transform3D, MAX_LIGHT_INTENSITY, MAX_MODEL_LIGHTS,
+};
+pub use ocr::{OcrBlock, OcrEngine, OcrError, OcrText, MAX_OCR_BLOCKS, MAX_OCR_TEXT_LEN};
 pub use offline::{ConflictResolution, ConflictStrategy, OfflineQueue, Operation, SyncResult};
+pub use poll::{PollLayout, PollOptionResult, PollResults, MAX_POLL_OPTIONS};
+pub use protocol::{ClientMessage, Consistency, QuotaBudget, QuotaStatus, ServerMessage};
+pub use randomizer::{RandomOutcome, RandomSource, RandomizerLayout};
+pub use redact::Redaction;
 pub use scene::Scene;
-pub use schema::{ElementDocument, SceneDocument, ViewportDocument};
+pub use schema::{
+    ElementDocument, PatchOp, SceneDocument, ScenePage, ScenePatch, ViewportDocument,
+};
+pub use selection::{Selection, SelectionChanged, SelectionMode};
+pub use shader::{MAX_SHADER_LEN, MAX_SHADER_UNIFORMS};
+pub use shape::{ArrowDirection, ShapeKind, ShapeOutline, MAX_SHAPE_VERTICES, MIN_SHAPE_VERTICES};
+pub use sparkline::{SparklineKind, MAX_SPARKLINE_POINTS};
+pub use spatial::SpatialIndex;
+pub use spotlight::{match_elements, Spotlight};
 pub use state::{CanvasState, ConnectionStatus};
 pub use store::{SceneStore, StoreError};
+pub use style::{Fill, GradientStop, Shadow, Stroke, Style};
+pub use template::{Template, TemplateLibrary};
+pub use transition::{
+    Easing, TransitionConfig, TransitionEffect, TransitionFrame, Transitions, MAX_TRANSITION_MS,
+};
+pub use tree::{TreeDirection, TreeLayout, TreeNode, MAX_TREE_NODES};
+pub use units::{
+    CoordinateSystem, LineMeasurement, WorldUnit, YAxis, MAX_UNIT_NAME_LEN, PX_PER_INCH,
+};
+pub use view_state::{PresenterFollow, ViewState};
 
 /// Canvas core version
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -0,0 +1,627 @@
+//! # Scene Linting
+//!
+//! Layout and legibility checks for agent-built scenes, with a suggested
+//! fix for each finding.
+//!
+//! Where [`Scene::validate`](crate::Scene::validate) reports structural
+//! problems, [`Scene::lint`](crate::Scene::lint) reports scenes that are
+//! consistent but hard to read:
+//!
+//! ```text
+//! overlapping_text   two text elements cover each other      → move one below
+//! tiny_font          font size under the minimum             → raise it
+//! low_contrast       text barely differs from what is behind → black or white
+//! out_of_bounds      element extends past the canvas         → move it inside
+//! orphan_connector   callout tail or attachment to nothing   → drop the link
+//! ```
+//!
+//! Fixes are plain edits ([`LintFix`]) that [`LintFix::apply`] or
+//! [`Scene::apply_lint_fixes`](crate::Scene::apply_lint_fixes) carry out,
+//! so agents can clean up a scene without reasoning about each finding.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::camera::SceneBounds;
+use crate::color::{Color, Theme, ThemeColor};
+use crate::element::{CalloutTarget, Element, ElementId, ElementKind};
+use crate::style::Fill;
+
+/// Space left between text moved apart by an overlap fix, in canvas pixels.
+pub const OVERLAP_GAP: f32 = 8.0;
+
+/// Canvas color behind elements that sit on nothing else.
+const CANVAS_BACKGROUND: Color = Color::WHITE;
+
+/// A check [`Scene::lint`](crate::Scene::lint) can run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LintRule {
+    /// Text elements (text, math, code, notes and callouts) that overlap,
+    /// other than text placed wholly on a note or callout.
+    OverlappingText,
+    /// Text or math smaller than [`LintConfig::min_font_size`].
+    TinyFont,
+    /// Text or math whose contrast with the color behind it is under
+    /// [`LintConfig::min_contrast`].
+    LowContrast,
+    /// Elements extending past the canvas (0, 0)–(viewport width, height).
+    OutOfBounds,
+    /// Callout tails and attachments whose target is not in the scene.
+    OrphanConnector,
+}
+
+impl LintRule {
+    /// Every rule, in report order.
+    pub const ALL: [Self; 5] = [
+        Self::OverlappingText,
+        Self::TinyFont,
+        Self::LowContrast,
+        Self::OutOfBounds,
+        Self::OrphanConnector,
+    ];
+}
+
+/// Which rules to run and their thresholds.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LintConfig {
+    /// Rules to run; all of them by default.
+    #[serde(default = "LintConfig::default_rules")]
+    pub rules: Vec<LintRule>,
+    /// Smallest acceptable font size, in canvas pixels.
+    #[serde(default = "LintConfig::default_min_font_size")]
+    pub min_font_size: f32,
+    /// Smallest acceptable WCAG contrast ratio (4.5 is AA for body text).
+    #[serde(default = "LintConfig::default_min_contrast")]
+    pub min_contrast: f32,
+}
+
+impl Default for LintConfig {
+    fn default() -> Self {
+        Self {
+            rules: Self::default_rules(),
+            min_font_size: Self::default_min_font_size(),
+            min_contrast: Self::default_min_contrast(),
+        }
+    }
+}
+
+impl LintConfig {
+    fn default_rules() -> Vec<LintRule> {
+        LintRule::ALL.to_vec()
+    }
+
+    const fn default_min_font_size() -> f32 {
+        10.0
+    }
+
+    const fn default_min_contrast() -> f32 {
+        4.5
+    }
+
+    /// Run only `rules`.
+    #[must_use]
+    pub fn with_rules(mut self, rules: Vec<LintRule>) -> Self {
+        self.rules = rules;
+        self
+    }
+
+    /// Set the smallest acceptable font size.
+    #[must_use]
+    pub fn with_min_font_size(mut self, min_font_size: f32) -> Self {
+        self.min_font_size = min_font_size;
+        self
+    }
+
+    /// Set the smallest acceptable contrast ratio.
+    #[must_use]
+    pub fn with_min_contrast(mut self, min_contrast: f32) -> Self {
+        self.min_contrast = min_contrast;
+        self
+    }
+
+    fn runs(&self, rule: LintRule) -> bool {
+        self.rules.contains(&rule)
+    }
+}
+
+/// An edit that resolves a [`LintFinding`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum LintFix {
+    /// Move the element's top-left corner.
+    Move {
+        /// New X coordinate.
+        x: f32,
+        /// New Y coordinate.
+        y: f32,
+    },
+    /// Set the font size of a `Text` or `Math` element.
+    SetFontSize {
+        /// New font size.
+        font_size: f32,
+    },
+    /// Set the color of a `Text` or `Math` element.
+    SetColor {
+        /// New color.
+        color: Color,
+    },
+    /// Remove a callout's tail.
+    ClearCalloutTarget,
+    /// Stop following the attachment target.
+    Detach,
+}
+
+impl LintFix {
+    /// Apply the fix to `element`. Fixes that do not fit the element's kind
+    /// do nothing.
+    pub fn apply(&self, element: &mut Element) {
+        match self {
+            Self::Move { x, y } => {
+                element.transform.x = *x;
+                element.transform.y = *y;
+            }
+            Self::SetFontSize { font_size: size } => {
+                if let ElementKind::Text { font_size, .. } | ElementKind::Math { font_size, .. } =
+                    &mut element.kind
+                {
+                    *font_size = *size;
+                }
+            }
+            Self::SetColor { color: new } => {
+                if let ElementKind::Text { color, .. } | ElementKind::Math { color, .. } =
+                    &mut element.kind
+                {
+                    *color = ThemeColor::from(*new);
+                }
+            }
+            Self::ClearCalloutTarget => {
+                if let ElementKind::Callout { target, .. } = &mut element.kind {
+                    *target = None;
+                }
+            }
+            Self::Detach => element.attachment = None,
+        }
+    }
+}
+
+/// A problem found by [`Scene::lint`](crate::Scene::lint).
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LintFinding {
+    /// The rule that found it.
+    pub rule: LintRule,
+    /// The element to fix.
+    pub element_id: ElementId,
+    /// The other element involved: the text it overlaps, or the missing
+    /// target.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub related: Option<ElementId>,
+    /// What is wrong, for people.
+    pub message: String,
+    /// Suggested edit to `element_id`, if one is known.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fix: Option<LintFix>,
+}
+
+/// Run the rules in `config` over `visible`, the shown subset of
+/// `elements`.
+///
+/// Findings are ordered by rule, then element ID, so reports are stable.
+pub(crate) fn lint(
+    elements: &HashMap<ElementId, Element>,
+    mut visible: Vec<&Element>,
+    canvas: (f32, f32),
+    theme: &Theme,
+    config: &LintConfig,
+) -> Vec<LintFinding> {
+    visible.sort_by_key(|e| e.id.to_string());
+
+    let mut findings = Vec::new();
+    if config.runs(LintRule::OverlappingText) {
+        overlapping_text(&visible, &mut findings);
+    }
+    for element in &visible {
+        if config.runs(LintRule::TinyFont) {
+            tiny_font(element, config.min_font_size, &mut findings);
+        }
+        if config.runs(LintRule::LowContrast) {
+            low_contrast(element, &visible, theme, config.min_contrast, &mut findings);
+        }
+        if config.runs(LintRule::OutOfBounds) {
+            out_of_bounds(element, canvas, &mut findings);
+        }
+    }
+    if config.runs(LintRule::OrphanConnector) {
+        for element in elements.values() {
+            orphan_connectors(element, elements, &mut findings);
+        }
+    }
+
+    findings.sort_by(|a, b| {
+        a.rule
+            .cmp(&b.rule)
+            .then_with(|| a.element_id.to_string().cmp(&b.element_id.to_string()))
+    });
+    findings
+}
+
+/// Whether the element draws text over its box.
+fn has_text(kind: &ElementKind) -> bool {
+    matches!(
+        kind,
+        ElementKind::Text { .. }
+            | ElementKind::Math { .. }
+            | ElementKind::Code { .. }
+            | ElementKind::LogView { .. }
+            | ElementKind::StickyNote { .. }
+            | ElementKind::Callout { .. }
+    )
+}
+
+/// Whether `card` is a note or callout wholly containing `inner`, i.e.
+/// text deliberately placed on a card.
+fn is_card_around(card: &Element, bounds: &SceneBounds, inner: &SceneBounds) -> bool {
+    matches!(
+        card.kind,
+        ElementKind::StickyNote { .. } | ElementKind::Callout { .. }
+    ) && bounds.min_x <= inner.min_x
+        && bounds.min_y <= inner.min_y
+        && bounds.max_x >= inner.max_x
+        && bounds.max_y >= inner.max_y
+}
+
+fn overlapping_text(elements: &[&Element], findings: &mut Vec<LintFinding>) {
+    let texts: Vec<(&Element, SceneBounds)> = elements
+        .iter()
+        .filter(|e| has_text(&e.kind))
+        .map(|e| (*e, SceneBounds::from_transform(&e.transform)))
+        .collect();
+    for (i, (a, a_bounds)) in texts.iter().enumerate() {
+        for (b, b_bounds) in &texts[i + 1..] {
+            let overlap_x = a_bounds.max_x.min(b_bounds.max_x) - a_bounds.min_x.max(b_bounds.min_x);
+            let overlap_y = a_bounds.max_y.min(b_bounds.max_y) - a_bounds.min_y.max(b_bounds.min_y);
+            if overlap_x <= 0.0
+                || overlap_y <= 0.0
+                || is_card_around(a, a_bounds, b_bounds)
+                || is_card_around(b, b_bounds, a_bounds)
+            {
+                continue;
+            }
+            // Move whichever is drawn on top below the other
+            let (top, under) =
+                if (b.transform.z_index, b_bounds.min_y) >= (a.transform.z_index, a_bounds.min_y) {
+                    ((b, b_bounds), (a, a_bounds))
+                } else {
+                    ((a, a_bounds), (b, b_bounds))
+                };
+            let (top, top_bounds) = top;
+            let (under, under_bounds) = under;
+            let shift = under_bounds.max_y + OVERLAP_GAP - top_bounds.min_y;
+            findings.push(LintFinding {
+                rule: LintRule::OverlappingText,
+                element_id: top.id,
+                related: Some(under.id),
+                message: format!("{} overlaps text {}", top.id, under.id),
+                fix: Some(LintFix::Move {
+                    x: top.transform.x,
+                    y: top.transform.y + shift,
+                }),
+            });
+        }
+    }
+}
+
+fn tiny_font(element: &Element, min_font_size: f32, findings: &mut Vec<LintFinding>) {
+    let (ElementKind::Text { font_size, .. } | ElementKind::Math { font_size, .. }) = &element.kind
+    else {
+        return;
+    };
+    if *font_size < min_font_size {
+        findings.push(LintFinding {
+            rule: LintRule::TinyFont,
+            element_id: element.id,
+            related: None,
+            message: format!(
+                "{} has a {font_size}px font, under the {min_font_size}px minimum",
+                element.id
+            ),
+            fix: Some(LintFix::SetFontSize {
+                font_size: min_font_size,
+            }),
+        });
+    }
+}
+
+/// Solid color an element paints over its box, if any.
+fn surface_color(element: &Element, theme: &Theme) -> Option<Color> {
+    match &element.style.fill {
+        Some(Fill::Solid { color }) => theme.resolve(color),
+        Some(_) => None,
+        None => match &element.kind {
+            ElementKind::StickyNote { .. } | ElementKind::Callout { .. } => {
+                element.kind.resolved_color(theme)
+            }
+            _ => None,
+        },
+    }
+}
+
+/// The opaque color behind the center of `element`: its own fill, or the
+/// fill of the topmost element under it, or the canvas.
+fn backdrop(element: &Element, elements: &[&Element], theme: &Theme) -> Color {
+    if let Some(color) = surface_color(element, theme) {
+        return color.over(CANVAS_BACKGROUND);
+    }
+    let (cx, cy) = SceneBounds::from_transform(&element.transform).center();
+    elements
+        .iter()
+        .filter(|other| other.id != element.id)
+        .filter(|other| other.transform.z_index <= element.transform.z_index)
+        .filter_map(|other| {
+            let bounds = SceneBounds::from_transform(&other.transform);
+            if !bounds.contains_point(cx, cy) {
+                return None;
+            }
+            let color = surface_color(other, theme)?;
+            Some((
+                other.transform.z_index,
+                bounds.width() * bounds.height(),
+                color,
+            ))
+        })
+        // Topmost first, then the tightest box among equals
+        .max_by(|a, b| a.0.cmp(&b.0).then_with(|| b.1.total_cmp(&a.1)))
+        .map_or(CANVAS_BACKGROUND, |(_, _, color)| {
+            color.over(CANVAS_BACKGROUND)
+        })
+}
+
+fn low_contrast(
+    element: &Element,
+    elements: &[&Element],
+    theme: &Theme,
+    min_contrast: f32,
+    findings: &mut Vec<LintFinding>,
+) {
+    if !matches!(
+        element.kind,
+        ElementKind::Text { .. } | ElementKind::Math { .. }
+    ) {
+        return;
+    }
+    let Some(color) = element.kind.resolved_color(theme) else {
+        return;
+    };
+    let backdrop = backdrop(element, elements, theme);
+    let ratio = color.over(backdrop).contrast_ratio(backdrop);
+    if ratio >= min_contrast {
+        return;
+    }
+    let better = if Color::BLACK.contrast_ratio(backdrop) >= Color::WHITE.contrast_ratio(backdrop) {
+        Color::BLACK
+    } else {
+        Color::WHITE
+    };
+    findings.push(LintFinding {
+        rule: LintRule::LowContrast,
+        element_id: element.id,
+        related: None,
+        message: format!(
+            "{} has contrast {ratio:.2}:1 against {}, under {min_contrast}:1",
+            element.id, backdrop
+        ),
+        fix: Some(LintFix::SetColor { color: better }),
+    });
+}
+
+fn out_of_bounds(element: &Element, (width, height): (f32, f32), findings: &mut Vec<LintFinding>) {
+    if width <= 0.0 || height <= 0.0 {
+        return;
+    }
+    let bounds = SceneBounds::from_transform(&element.transform);
+    // Shift along an axis to bring the box inside, or flush with the
+    // start edge if it is larger than the canvas
+    let shift = |min: f32, max: f32, limit: f32| {
+        if min < 0.0 || max - min > limit {
+            -min
+        } else if max > limit {
+            limit - max
+        } else {
+            0.0
+        }
+    };
+    let dx = shift(bounds.min_x, bounds.max_x, width);
+    let dy = shift(bounds.min_y, bounds.max_y, height);
+    if dx == 0.0 && dy == 0.0 {
+        return;
+    }
+    findings.push(LintFinding {
+        rule: LintRule::OutOfBounds,
+        element_id: element.id,
+        related: None,
+        message: format!("{} extends past the {width}x{height} canvas", element.id),
+        fix: Some(LintFix::Move {
+            x: element.transform.x + dx,
+            y: element.transform.y + dy,
+        }),
+    });
+}
+
+fn orphan_connectors(
+    element: &Element,
+    elements: &HashMap<ElementId, Element>,
+    findings: &mut Vec<LintFinding>,
+) {
+    if let ElementKind::Callout {
+        target: Some(CalloutTarget::Element { element_id }),
+        ..
+    } = &element.kind
+    {
+        if !elements.contains_key(element_id) {
+            findings.push(LintFinding {
+                rule: LintRule::OrphanConnector,
+                element_id: element.id,
+                related: Some(*element_id),
+                message: format!(
+                    "{} points at {element_id}, which is not in the scene",
+                    element.id
+                ),
+                fix: Some(LintFix::ClearCalloutTarget),
+            });
+        }
+    }
+    if let Some(attachment) = &element.attachment {
+        if !elements.contains_key(&attachment.target) {
+            findings.push(LintFinding {
+                rule: LintRule::OrphanConnector,
+                element_id: element.id,
+                related: Some(attachment.target),
+                message: format!(
+                    "{} is attached to {}, which is not in the scene",
+                    element.id, attachment.target
+                ),
+                fix: Some(LintFix::Detach),
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::attachment::{AttachPoint, Attachment};
+    use crate::element::Transform;
+    use crate::scene::Scene;
+
+    fn text(x: f32, y: f32, font_size: f32, color: &str) -> Element {
+        Element::new(ElementKind::Text {
+            content: "Label".to_string(),
+            font_size,
+            color: ThemeColor::parse(color).expect("color"),
+        })
+        .with_transform(Transform {
+            x,
+            y,
+            width: 100.0,
+            height: 20.0,
+            ..Transform::default()
+        })
+    }
+
+    fn rules(scene: &Scene) -> Vec<(LintRule, ElementId)> {
+        scene
+            .lint(&LintConfig::default())
+            .into_iter()
+            .map(|f| (f.rule, f.element_id))
+            .collect()
+    }
+
+    #[test]
+    fn test_clean_scene_has_no_findings() {
+        let mut scene = Scene::new(800.0, 600.0);
+        scene.add_element(text(10.0, 10.0, 16.0, "#000000"));
+        scene.add_element(text(10.0, 40.0, 16.0, "#1976d2"));
+        assert!(scene.lint(&LintConfig::default()).is_empty());
+    }
+
+    #[test]
+    fn test_overlap_and_tiny_font_fixes() {
+        let mut scene = Scene::new(800.0, 600.0);
+        let under = scene.add_element(text(10.0, 10.0, 16.0, "#000000"));
+        let top = scene.add_element(text(20.0, 15.0, 6.0, "#000000"));
+        assert_eq!(
+            rules(&scene),
+            vec![(LintRule::OverlappingText, top), (LintRule::TinyFont, top)]
+        );
+
+        let findings = scene.lint(&LintConfig::default());
+        assert_eq!(findings[0].related, Some(under));
+        assert_eq!(
+            findings[0].fix,
+            Some(LintFix::Move {
+                x: 20.0,
+                y: 30.0 + OVERLAP_GAP
+            })
+        );
+        scene.apply_lint_fixes(&findings);
+        assert!(scene.lint(&LintConfig::default()).is_empty());
+
+        // Only the requested rules run
+        let tiny = scene.add_element(text(10.0, 200.0, 4.0, "#000000"));
+        let config = LintConfig::default().with_rules(vec![LintRule::OutOfBounds]);
+        assert!(scene.lint(&config).is_empty());
+        let config = LintConfig::default().with_min_font_size(3.0);
+        assert!(scene.lint(&config).is_empty());
+        assert_eq!(rules(&scene), vec![(LintRule::TinyFont, tiny)]);
+    }
+
+    #[test]
+    fn test_low_contrast_checks_what_is_behind() {
+        let mut scene = Scene::new(800.0, 600.0);
+        let pale = scene.add_element(text(10.0, 10.0, 16.0, "#dddddd"));
+        let mut card = Element::new(ElementKind::StickyNote {
+            content: String::new(),
+            color: ThemeColor::parse("#202020").expect("color"),
+            author: None,
+        })
+        .with_transform(Transform {
+            x: 0.0,
+            y: 100.0,
+            width: 300.0,
+            height: 200.0,
+            ..Transform::default()
+        });
+        card.transform.z_index = -1;
+        scene.add_element(card);
+        // Light text on the dark card reads fine, dark text does not
+        scene.add_element(text(10.0, 150.0, 16.0, "#eeeeee"));
+        let dark = scene.add_element(text(10.0, 250.0, 16.0, "#333333"));
+
+        let findings = scene.lint(&LintConfig::default());
+        let flagged: Vec<_> = findings.iter().map(|f| f.element_id).collect();
+        assert_eq!(flagged.len(), 2);
+        assert!(flagged.contains(&pale) && flagged.contains(&dark));
+        let fix = |id| findings.iter().find(|f| f.element_id == id)?.fix.clone();
+        assert_eq!(
+            fix(pale),
+            Some(LintFix::SetColor {
+                color: Color::BLACK
+            })
+        );
+        assert_eq!(
+            fix(dark),
+            Some(LintFix::SetColor {
+                color: Color::WHITE
+            })
+        );
+    }
+
+    #[test]
+    fn test_out_of_bounds_and_orphan_connectors() {
+        let mut scene = Scene::new(800.0, 600.0);
+        let off = scene.add_element(text(750.0, -5.0, 16.0, "#000000"));
+        let mut badge = text(300.0, 300.0, 16.0, "#000000");
+        badge.attachment = Some(Attachment::new(ElementId::new(), AttachPoint::TopRight));
+        let badge = scene.add_element(badge);
+
+        let findings = scene.lint(&LintConfig::default());
+        assert_eq!(
+            rules(&scene),
+            vec![
+                (LintRule::OutOfBounds, off),
+                (LintRule::OrphanConnector, badge)
+            ]
+        );
+        assert_eq!(findings[0].fix, Some(LintFix::Move { x: 700.0, y: 0.0 }));
+        assert_eq!(findings[1].fix, Some(LintFix::Detach));
+
+        scene.apply_lint_fixes(&findings);
+        assert!(scene.lint(&LintConfig::default()).is_empty());
+        assert!(scene
+            .get_element(badge)
+            .expect("badge")
+            .attachment
+            .is_none());
+    }
+}
@@ -0,0 +1,612 @@
+//! # Log Views
+//!
+//! Buffer, ANSI color and layout rules for `LogView` elements: terminal-style
+//! panes that agents stream build, test or tool output into.
+//!
+//! ```text
+//! ┌─────────────────────────────────────────┐
+//! │ cargo test                              │  header: the title, if any
+//! │    Compiling canvas-core v0.2.0         │
+//! │ test scene::tests::add ... ok           │  oldest visible line
+//! │ test scene::tests::remove ... FAILED    │  (ANSI red)
+//! │ test result: FAILED. 41 passed; 1 failed│  newest line, kept in view
+//! └─────────────────────────────────────────┘
+//! ```
+//!
+//! Lines are stored raw, escape sequences included, and [`append`] only ever
+//! adds to the end; once a view holds `max_lines` lines the oldest are
+//! dropped. Because dropping is deterministic, sync sends just the new lines
+//! (a `log_append` patch op) and every receiver trims its copy the same way.
+//!
+//! Renderers split each line into colored [`LogSpan`]s with [`spans`].
+//! Select Graphic Rendition codes (`ESC[...m`: the 16 standard colors,
+//! 256-color and 24-bit colors, bold, dim, italic, underline and inverse)
+//! are honored; other escape sequences, such as cursor movement, are
+//! dropped. A following view shows its newest lines; otherwise it shows its
+//! oldest.
+
+use std::ops::Range;
+
+use crate::color::Color;
+use crate::element::ElementKind;
+use crate::error::{CanvasError, CanvasResult};
+use crate::highlight::{CODE_LINE_HEIGHT, CODE_PADDING};
+
+/// Lines a log view keeps unless told otherwise.
+pub const DEFAULT_LOG_LINES: usize = 500;
+
+/// Most lines a log view may keep.
+pub const MAX_LOG_LINES: usize = 10_000;
+
+/// Longest a stored line may be, in bytes; longer lines are cut.
+pub const MAX_LOG_LINE_LEN: usize = 2_000;
+
+/// Background color of log views.
+pub const LOG_BACKGROUND: &str = "#0c0c0c";
+
+/// Text color of lines without an ANSI foreground color.
+pub const LOG_FOREGROUND: &str = "#cccccc";
+
+/// [`LOG_FOREGROUND`] as a color.
+const DEFAULT_FG: Color = Color::rgb(0xcc, 0xcc, 0xcc);
+
+/// [`LOG_BACKGROUND`] as a color.
+const DEFAULT_BG: Color = Color::rgb(0x0c, 0x0c, 0x0c);
+
+/// Height of the title row, in pixels.
+pub const LOG_HEADER_HEIGHT: f32 = CODE_LINE_HEIGHT + 4.0;
+
+/// The 16 standard terminal colors: black, red, green, yellow, blue,
+/// magenta, cyan and white, then their bright variants.
+const ANSI_COLORS: [Color; 16] = [
+    Color::rgb(0x00, 0x00, 0x00),
+    Color::rgb(0xcd, 0x31, 0x31),
+    Color::rgb(0x0d, 0xbc, 0x79),
+    Color::rgb(0xe5, 0xe5, 0x10),
+    Color::rgb(0x24, 0x72, 0xc8),
+    Color::rgb(0xbc, 0x3f, 0xbc),
+    Color::rgb(0x11, 0xa8, 0xcd),
+    Color::rgb(0xe5, 0xe5, 0xe5),
+    Color::rgb(0x66, 0x66, 0x66),
+    Color::rgb(0xf1, 0x4c, 0x4c),
+    Color::rgb(0x23, 0xd1, 0x8b),
+    Color::rgb(0xf5, 0xf5, 0x43),
+    Color::rgb(0x3b, 0x8e, 0xea),
+    Color::rgb(0xd6, 0x70, 0xd6),
+    Color::rgb(0x29, 0xb8, 0xdb),
+    Color::rgb(0xff, 0xff, 0xff),
+];
+
+/// Text attributes set by ANSI escape codes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct AnsiStyle {
+    /// Foreground color, or `None` for [`LOG_FOREGROUND`].
+    pub fg: Option<Color>,
+    /// Background color, or `None` for the view's background.
+    pub bg: Option<Color>,
+    /// Bold text.
+    pub bold: bool,
+    /// Faint text.
+    pub dim: bool,
+    /// Italic text.
+    pub italic: bool,
+    /// Underlined text.
+    pub underline: bool,
+    /// Foreground and background swapped.
+    pub inverse: bool,
+}
+
+impl AnsiStyle {
+    /// Color to draw the text in.
+    #[must_use]
+    pub fn foreground(&self) -> Color {
+        let color = if self.inverse {
+            self.bg.unwrap_or(DEFAULT_BG)
+        } else {
+            self.fg.unwrap_or(DEFAULT_FG)
+        };
+        if self.dim {
+            Color::rgba(color.r, color.g, color.b, color.a / 5 * 3)
+        } else {
+            color
+        }
+    }
+
+    /// Color to fill behind the text, if any.
+    #[must_use]
+    pub fn background(&self) -> Option<Color> {
+        if self.inverse {
+            Some(self.fg.unwrap_or(DEFAULT_FG))
+        } else {
+            self.bg
+        }
+    }
+
+    /// Apply the parameters of one SGR sequence (`ESC[<params>m`).
+    fn apply_sgr(&mut self, params: &[u16]) {
+        if params.is_empty() {
+            *self = Self::default();
+            return;
+        }
+        let mut i = 0;
+        while i < params.len() {
+            match params[i] {
+                0 => *self = Self::default(),
+                1 => self.bold = true,
+                2 => self.dim = true,
+                3 => self.italic = true,
+                4 => self.underline = true,
+                7 => self.inverse = true,
+                22 => (self.bold, self.dim) = (false, false),
+                23 => self.italic = false,
+                24 => self.underline = false,
+                27 => self.inverse = false,
+                code @ 30..=37 => self.fg = Some(ANSI_COLORS[usize::from(code - 30)]),
+                code @ 90..=97 => self.fg = Some(ANSI_COLORS[usize::from(code - 90 + 8)]),
+                39 => self.fg = None,
+                code @ 40..=47 => self.bg = Some(ANSI_COLORS[usize::from(code - 40)]),
+                code @ 100..=107 => self.bg = Some(ANSI_COLORS[usize::from(code - 100 + 8)]),
+                49 => self.bg = None,
+                code @ (38 | 48) => {
+                    let (color, used) = extended_color(&params[i + 1..]);
+                    if let Some(color) = color {
+                        if code == 38 {
+                            self.fg = Some(color);
+                        } else {
+                            self.bg = Some(color);
+                        }
+                    }
+                    i += used;
+                }
+                _ => {}
+            }
+            i += 1;
+        }
+    }
+}
+
+/// Read a `5;n` (256-color) or `2;r;g;b` (24-bit) color after a 38 or 48
+/// code; returns the color and how many parameters it used.
+fn extended_color(params: &[u16]) -> (Option<Color>, usize) {
+    let channel = |i: usize| params.get(i).map(|&v| u8::try_from(v).unwrap_or(u8::MAX));
+    match params.first() {
+        Some(5) => (channel(1).map(palette_color), 2),
+        Some(2) => match (channel(1), channel(2), channel(3)) {
+            (Some(r), Some(g), Some(b)) => (Some(Color::rgb(r, g, b)), 4),
+            _ => (None, params.len()),
+        },
+        _ => (None, 0),
+    }
+}
+
+/// One of the xterm 256 colors: the 16 standard colors, a 6×6×6 color cube
+/// and 24 grays.
+fn palette_color(index: u8) -> Color {
+    const LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+    match index {
+        0..=15 => ANSI_COLORS[usize::from(index)],
+        16..=231 => {
+            let i = index - 16;
+            Color::rgb(
+                LEVELS[usize::from(i / 36)],
+                LEVELS[usize::from(i / 6 % 6)],
+                LEVELS[usize::from(i % 6)],
+            )
+        }
+        232..=255 => {
+            let level = 8 + (index - 232) * 10;
+            Color::rgb(level, level, level)
+        }
+    }
+}
+
+/// A run of a log line drawn in one style.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogSpan {
+    /// Text, with escape sequences and control characters removed.
+    pub text: String,
+    /// How the text is drawn.
+    pub style: AnsiStyle,
+}
+
+/// Split a raw log line into styled runs.
+///
+/// Tabs are kept; other control characters and escape sequences other than
+/// SGR are dropped.
+#[must_use]
+pub fn spans(line: &str) -> Vec<LogSpan> {
+    let mut spans: Vec<LogSpan> = Vec::new();
+    let mut style = AnsiStyle::default();
+    let mut text = String::new();
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c != '\u{1b}' {
+            if c == '\t' || !c.is_control() {
+                text.push(c);
+            }
+            continue;
+        }
+        match chars.next() {
+            // CSI: parameters, then a final byte in @..~
+            Some('[') => {
+                let mut raw = String::new();
+                let mut last = None;
+                for c in chars.by_ref() {
+                    if ('@'..='~').contains(&c) {
+                        last = Some(c);
+                        break;
+                    }
+                    raw.push(c);
+                }
+                if last == Some('m') {
+                    if !text.is_empty() {
+                        push_span(&mut spans, std::mem::take(&mut text), style);
+                    }
+                    let params: Vec<u16> = raw
+                        .split([';', ':'])
+                        .map(|p| p.parse().unwrap_or(0))
+                        .collect();
+                    style.apply_sgr(if raw.is_empty() { &[] } else { &params });
+                }
+            }
+            // OSC (e.g. window titles, links): up to BEL or ESC \
+            Some(']') => {
+                while let Some(c) = chars.next() {
+                    if c == '\u{7}' || (c == '\u{1b}' && chars.next_if_eq(&'\\').is_some()) {
+                        break;
+                    }
+                }
+            }
+            // Any other escape is two characters long
+            _ => {}
+        }
+    }
+    if !text.is_empty() {
+        push_span(&mut spans, text, style);
+    }
+    spans
+}
+
+/// Append a run, merging it into the previous one if the style matches.
+fn push_span(spans: &mut Vec<LogSpan>, text: String, style: AnsiStyle) {
+    match spans.last_mut() {
+        Some(last) if last.style == style => last.text.push_str(&text),
+        _ => spans.push(LogSpan { text, style }),
+    }
+}
+
+/// A raw log line without escape sequences or control characters.
+#[must_use]
+pub fn strip_ansi(line: &str) -> String {
+    spans(line).into_iter().map(|span| span.text).collect()
+}
+
+/// Split streamed text into stored lines.
+///
+/// A trailing newline ends the last line rather than starting an empty one.
+/// A carriage return rewinds to the start of the line, as terminals do for
+/// progress bars, so only the text after the last one is kept. Lines longer
+/// than [`MAX_LOG_LINE_LEN`] bytes are cut.
+#[must_use]
+pub fn split_lines(text: &str) -> Vec<String> {
+    if text.is_empty() {
+        return Vec::new();
+    }
+    let text = text.strip_suffix('\n').unwrap_or(text);
+    text.split('\n')
+        .map(|line| {
+            let line = line.strip_suffix('\r').unwrap_or(line);
+            let line = line.rsplit('\r').next().unwrap_or(line);
+            let mut end = line.len().min(MAX_LOG_LINE_LEN);
+            while !line.is_char_boundary(end) {
+                end -= 1;
+            }
+            line[..end].to_string()
+        })
+        .collect()
+}
+
+/// Add `new` lines after `lines`, dropping the oldest beyond `max_lines`.
+///
+/// Returns how many lines were dropped.
+pub fn push_lines(lines: &mut Vec<String>, new: &[String], max_lines: usize) -> usize {
+    lines.extend_from_slice(new);
+    let dropped = lines.len().saturating_sub(max_lines);
+    lines.drain(..dropped);
+    dropped
+}
+
+/// The lines appended to `before` to give `after`, if `after` is `before`
+/// with lines added and the oldest dropped to fit `max_lines`.
+#[must_use]
+pub fn appended_lines<'a>(
+    before: &[String],
+    after: &'a [String],
+    max_lines: usize,
+) -> Option<&'a [String]> {
+    // How many of the old lines were dropped; without trimming, none were
+    let drops = if after.len() < max_lines {
+        0..=0
+    } else {
+        0..=before.len()
+    };
+    for dropped in drops {
+        let kept = &before[dropped..];
+        if kept.len() > after.len() {
+            continue;
+        }
+        // Compare the last kept line first: it rules most candidates out
+        if kept
+            .last()
+            .is_some_and(|last| *last != after[kept.len() - 1])
+        {
+            continue;
+        }
+        if after.starts_with(kept) {
+            let new = &after[kept.len()..];
+            let trimmed = (before.len() + new.len()).saturating_sub(max_lines);
+            return (trimmed == dropped).then_some(new);
+        }
+    }
+    None
+}
+
+/// What an [`append`] did.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+pub struct LogAppend {
+    /// Lines added.
+    pub appended: usize,
+    /// Oldest lines dropped to stay within `max_lines`.
+    pub dropped: usize,
+    /// Lines now held.
+    pub total: usize,
+}
+
+/// Append streamed `text` to a `LogView`.
+///
+/// # Errors
+///
+/// Returns [`CanvasError::InvalidOperation`] if `kind` is not a log view.
+pub fn append(kind: &mut ElementKind, text: &str) -> CanvasResult<LogAppend> {
+    let ElementKind::LogView {
+        lines, max_lines, ..
+    } = kind
+    else {
+        return Err(CanvasError::InvalidOperation(format!(
+            "cannot append log lines to a {}",
+            kind.type_name()
+        )));
+    };
+    let new = split_lines(text);
+    let dropped = push_lines(lines, &new, *max_lines);
+    Ok(LogAppend {
+        appended: new.len(),
+        dropped,
+        total: lines.len(),
+    })
+}
+
+/// Remove every line from a `LogView`.
+///
+/// # Errors
+///
+/// Returns [`CanvasError::InvalidOperation`] if `kind` is not a log view.
+pub fn clear(kind: &mut ElementKind) -> CanvasResult<()> {
+    match kind {
+        ElementKind::LogView { lines, .. } => {
+            lines.clear();
+            Ok(())
+        }
+        _ => Err(CanvasError::InvalidOperation(format!(
+            "cannot clear log lines of a {}",
+            kind.type_name()
+        ))),
+    }
+}
+
+/// Check a `LogView`'s limits; other kinds pass.
+///
+/// # Errors
+///
+/// Returns [`CanvasError::InvalidOperation`] if `max_lines` is outside
+/// `1..=MAX_LOG_LINES`, more lines are held than that, or a line is longer
+/// than [`MAX_LOG_LINE_LEN`].
+pub fn validate(kind: &ElementKind) -> CanvasResult<()> {
+    let ElementKind::LogView {
+        lines, max_lines, ..
+    } = kind
+    else {
+        return Ok(());
+    };
+    let invalid = |message: String| Err(CanvasError::InvalidOperation(message));
+    if !(1..=MAX_LOG_LINES).contains(max_lines) {
+        return invalid(format!(
+            "log views keep 1 to {MAX_LOG_LINES} lines, got {max_lines}"
+        ));
+    }
+    if lines.len() > *max_lines {
+        return invalid(format!(
+            "log view holds {} lines but keeps at most {max_lines}",
+            lines.len()
+        ));
+    }
+    if let Some(index) = lines.iter().position(|line| line.len() > MAX_LOG_LINE_LEN) {
+        return invalid(format!(
+            "log line {index} is longer than {MAX_LOG_LINE_LEN} bytes"
+        ));
+    }
+    Ok(())
+}
+
+/// Offset of the first log row from the element's top edge.
+#[must_use]
+pub fn body_top(title: &str) -> f32 {
+    if title.is_empty() {
+        CODE_PADDING
+    } else {
+        CODE_PADDING + LOG_HEADER_HEIGHT
+    }
+}
+
+/// Lines of a log view that fit an element `height` pixels tall.
+#[must_use]
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+pub fn visible_range(title: &str, line_count: usize, height: f32, follow: bool) -> Range<usize> {
+    let room = (height - body_top(title) - CODE_PADDING).max(0.0);
+    let rows = ((room / CODE_LINE_HEIGHT).floor() as usize).min(line_count);
+    if follow {
+        line_count - rows..line_count
+    } else {
+        0..rows
+    }
+}
+
+/// The newest `count` lines without escape codes, joined by spaces.
+#[must_use]
+pub fn tail_text(lines: &[String], count: usize) -> String {
+    let start = lines.len().saturating_sub(count);
+    lines[start..]
+        .iter()
+        .map(|line| strip_ansi(line))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn log(lines: &[&str], max_lines: usize) -> ElementKind {
+        ElementKind::LogView {
+            title: String::new(),
+            lines: lines.iter().map(ToString::to_string).collect(),
+            max_lines,
+            follow: true,
+        }
+    }
+
+    fn strings(lines: &[&str]) -> Vec<String> {
+        lines.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn test_spans_follow_sgr_codes() {
+        let spans = spans("\u{1b}[1;31merror\u{1b}[0m: \u{1b}[38;5;46mok\u{1b}[m done");
+        let texts: Vec<&str> = spans.iter().map(|s| s.text.as_str()).collect();
+        assert_eq!(texts, ["error", ": ", "ok", " done"]);
+        assert!(spans[0].style.bold);
+        assert_eq!(spans[0].style.fg, Some(ANSI_COLORS[1]));
+        assert_eq!(spans[1].style, AnsiStyle::default());
+        assert_eq!(spans[2].style.fg, Some(Color::rgb(0, 255, 0)));
+        assert_eq!(spans[3].style, AnsiStyle::default());
+    }
+
+    #[test]
+    fn test_spans_handle_truecolor_background_and_inverse() {
+        let spans = spans("\u{1b}[48;2;10;20;30mbg\u{1b}[7mswap");
+        assert_eq!(spans[0].style.background(), Some(Color::rgb(10, 20, 30)));
+        assert_eq!(spans[1].style.foreground(), Color::rgb(10, 20, 30));
+    }
+
+    #[test]
+    fn test_strip_ansi_drops_other_escapes_and_controls() {
+        assert_eq!(
+            strip_ansi("\u{1b}[2K\u{1b}]0;title\u{7}\u{1b}[32mPASS\u{1b}[0m\ttest\u{8}"),
+            "PASS\ttest"
+        );
+        assert_eq!(strip_ansi("plain"), "plain");
+    }
+
+    #[test]
+    fn test_split_lines_handles_newlines_and_carriage_returns() {
+        assert_eq!(split_lines("a\nb\n"), ["a", "b"]);
+        assert_eq!(split_lines("a\r\n\nb"), ["a", "", "b"]);
+        assert_eq!(split_lines("10%\r50%\r100%"), ["100%"]);
+        assert!(split_lines("").is_empty());
+        assert_eq!(split_lines("\n"), [""]);
+
+        let long = "é".repeat(MAX_LOG_LINE_LEN);
+        let cut = &split_lines(&long)[0];
+        assert!(cut.len() <= MAX_LOG_LINE_LEN);
+        assert!(cut.chars().all(|c| c == 'é'));
+    }
+
+    #[test]
+    fn test_append_keeps_newest_lines() {
+        let mut kind = log(&["one", "two"], 3);
+        let result = append(&mut kind, "three\nfour\n").expect("append");
+        assert_eq!(
+            result,
+            LogAppend {
+                appended: 2,
+                dropped: 1,
+                total: 3
+            }
+        );
+        let ElementKind::LogView { lines, .. } = &kind else {
+            unreachable!()
+        };
+        assert_eq!(lines, &strings(&["two", "three", "four"]));
+
+        clear(&mut kind).expect("clear");
+        assert!(matches!(kind, ElementKind::LogView { ref lines, .. } if lines.is_empty()));
+
+        let mut text = ElementKind::Text {
+            content: String::new(),
+            font_size: 16.0,
+            color: Color::BLACK.into(),
+        };
+        assert!(append(&mut text, "x").is_err());
+    }
+
+    #[test]
+    fn test_appended_lines_recovers_the_delta() {
+        let before = strings(&["a", "b", "c"]);
+        let grown = strings(&["a", "b", "c", "d"]);
+        assert_eq!(appended_lines(&before, &grown, 10), Some(&grown[3..]));
+
+        // Full buffer: the oldest lines were dropped
+        let trimmed = strings(&["c", "d", "e"]);
+        assert_eq!(appended_lines(&before, &trimmed, 3), Some(&trimmed[1..]));
+
+        // Repeated lines still give a delta that replays exactly
+        let before = strings(&["x", "x", "x"]);
+        let after = strings(&["x", "x", "x"]);
+        let new = appended_lines(&before, &after, 3).expect("delta");
+        let mut replay = before.clone();
+        push_lines(&mut replay, new, 3);
+        assert_eq!(replay, after);
+
+        // Edits in the middle are not appends
+        let edited = strings(&["a", "B", "c"]);
+        assert_eq!(
+            appended_lines(&strings(&["a", "b", "c"]), &edited, 10),
+            None
+        );
+        assert_eq!(
+            appended_lines(&strings(&["a", "b"]), &strings(&["b"]), 10),
+            None
+        );
+    }
+
+    #[test]
+    fn test_validate_limits() {
+        assert!(validate(&log(&["a"], 10)).is_ok());
+        assert!(validate(&log(&[], 0)).is_err());
+        assert!(validate(&log(&[], MAX_LOG_LINES + 1)).is_err());
+        assert!(validate(&log(&["a", "b"], 1)).is_err());
+    }
+
+    #[test]
+    fn test_visible_range_follows_tail() {
+        // 8 px padding top and bottom leaves room for 5 rows of 20 px
+        assert_eq!(visible_range("", 50, 116.0, true), 45..50);
+        assert_eq!(visible_range("", 50, 116.0, false), 0..5);
+        assert_eq!(visible_range("", 3, 116.0, true), 0..3);
+        // A title takes a 24 px header row
+        assert_eq!(visible_range("build", 50, 116.0, true), 47..50);
+        assert_eq!(tail_text(&strings(&["a", "\u{1b}[31mb", "c"]), 2), "b c");
+    }
+}
@@ -0,0 +1,370 @@
+//! # Content Measurement
+//!
+//! Preferred sizes for elements, so content created without explicit bounds
+//! (e.g. by an MCP tool) gets a box that fits it.
+//!
+//! ```text
+//! Text "Quarterly revenue" @ 24px   → 225 × 32    one line
+//! Code, 12 lines, longest 40 chars  → 352 × 256   monospace grid
+//! Log view, 300 lines               → 640 × 416   newest 20 rows
+//! Chart bar, 12 labels              → 560 × 240   room for each bar
+//! Poll, 3 options                   → 320 × 168   one row per option
+//! Agenda, 3 items                   → 360 × 156   one row per item
+//! Floor plan, 1000 × 600 plan units → 480 × 317   plan aspect ratio
+//! ```
+//!
+//! Text is estimated from average glyph advances rather than real font
+//! metrics, which core does not have, and prose wraps at
+//! [`MeasureContext::max_width`]. Elements opt in to sizing through
+//! [`Transform::auto_size`](crate::Transform::auto_size); see
+//! [`Element::apply_auto_size`](crate::Element::apply_auto_size).
+
+use serde::{Deserialize, Serialize};
+
+use crate::barcode;
+use crate::calendar::CalendarView;
+use crate::dashboard::GaugeShape;
+use crate::element::ElementKind;
+use crate::highlight::{CODE_CHAR_WIDTH, CODE_FONT_SIZE, CODE_LINE_HEIGHT, CODE_PADDING};
+use crate::log_view;
+use crate::randomizer::RandomSource;
+use crate::tree::TreeLayout;
+
+/// Width of sticky notes and callouts, in pixels.
+pub const NOTE_WIDTH: f32 = 200.0;
+
+/// Font size of sticky note and callout text, in pixels.
+pub const NOTE_FONT_SIZE: f32 = 14.0;
+
+/// A width and height in pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Size {
+    /// Width in pixels.
+    pub width: f32,
+    /// Height in pixels.
+    pub height: f32,
+}
+
+impl Size {
+    /// Create a size.
+    #[must_use]
+    pub const fn new(width: f32, height: f32) -> Self {
+        Self { width, height }
+    }
+}
+
+/// Text metrics and limits used to measure content.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MeasureContext {
+    /// Average advance of a proportional glyph, as a fraction of the font
+    /// size.
+    pub char_width: f32,
+    /// Distance between baselines, as a multiple of the font size.
+    pub line_height: f32,
+    /// Width at which prose wraps, in pixels.
+    pub max_width: f32,
+}
+
+impl Default for MeasureContext {
+    fn default() -> Self {
+        Self {
+            char_width: 0.55,
+            line_height: 1.3,
+            max_width: 480.0,
+        }
+    }
+}
+
+impl MeasureContext {
+    /// Set the wrap width in pixels.
+    #[must_use]
+    pub const fn with_max_width(mut self, max_width: f32) -> Self {
+        self.max_width = max_width;
+        self
+    }
+
+    /// Split `text` into lines no wider than `max_width` at `font_size`.
+    ///
+    /// Breaks at spaces, keeps explicit newlines, and splits words that are
+    /// too long to fit on a line of their own.
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    pub fn wrap(&self, text: &str, font_size: f32, max_width: f32) -> Vec<String> {
+        let advance = (font_size * self.char_width).max(f32::EPSILON);
+        let columns = ((max_width / advance).floor() as usize).max(1);
+        let mut lines = Vec::new();
+        for paragraph in text.split('\n') {
+            let mut line = String::new();
+            for word in paragraph.split(' ').filter(|w| !w.is_empty()) {
+                let mut word: Vec<char> = word.chars().collect();
+                let used = line.chars().count();
+                if used > 0 && used + 1 + word.len() <= columns {
+                    line.push(' ');
+                    line.extend(word);
+                    continue;
+                }
+                if used > 0 {
+                    lines.push(std::mem::take(&mut line));
+                }
+                while word.len() > columns {
+                    lines.push(word.drain(..columns).collect());
+                }
+                line.extend(word);
+            }
+            lines.push(line);
+        }
+        lines
+    }
+
+    /// Size of a block of wrapped text at `font_size`.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn text_size(&self, text: &str, font_size: f32, max_width: f32) -> Size {
+        let lines = self.wrap(text, font_size, max_width);
+        let longest = lines.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+        Size::new(
+            (longest as f32 * font_size * self.char_width).ceil(),
+            (lines.len() as f32 * font_size * self.line_height).ceil(),
+        )
+    }
+}
+
+/// Number of data points in chart data, across the formats the chart
+/// renderer accepts (`labels`, `x_labels`, `points` or `series`).
+fn chart_points(data: &serde_json::Value) -> usize {
+    let len = |key: &str| {
+        data.get(key)
+            .and_then(serde_json::Value::as_array)
+            .map_or(0, Vec::len)
+    };
+    let series = data
+        .get("series")
+        .and_then(serde_json::Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|s| s.get("points").and_then(serde_json::Value::as_array))
+        .map(Vec::len)
+        .max()
+        .unwrap_or(0);
+    len("labels")
+        .max(len("x_labels"))
+        .max(len("points"))
+        .max(series)
+}
+
+/// Estimated rendered width of a TeX formula, in glyphs.
+///
+/// Control words (`\alpha`) count as one glyph and grouping or script
+/// markers (`{`, `}`, `^`, `_`) as none.
+fn tex_glyphs(tex: &str) -> usize {
+    let mut glyphs = 0;
+    let mut chars = tex.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                while chars.next_if(char::is_ascii_alphabetic).is_some() {}
+                glyphs += 1;
+            }
+            '{' | '}' | '^' | '_' | ' ' => {}
+            _ => glyphs += 1,
+        }
+    }
+    glyphs
+}
+
+/// Preferred size of an element's content, or `None` for containers
+/// (groups and overlay layers), whose size comes from their children.
+#[must_use]
+#[allow(clippy::cast_precision_loss, clippy::too_many_lines)]
+pub fn preferred_size(kind: &ElementKind, ctx: &MeasureContext) -> Option<Size> {
+    let size = match kind {
+        ElementKind::Text {
+            content, font_size, ..
+        } => {
+            let text = ctx.text_size(content, *font_size, ctx.max_width);
+            Size::new(text.width.max(*font_size), text.height.max(*font_size))
+        }
+        ElementKind::Math { tex, font_size, .. } => {
+            // Fractions stack two rows
+            let rows = if tex.contains("\\frac") { 2.5 } else { 1.5 };
+            Size::new(
+                (tex_glyphs(tex).max(1) as f32 * font_size * 0.6 + 2.0 * font_size).ceil(),
+                (font_size * rows).ceil(),
+            )
+        }
+        ElementKind::Code { source, .. } => {
+            let lines = source.lines().count().max(1);
+            let longest = source.lines().map(|l| l.chars().count()).max().unwrap_or(0);
+            Size::new(
+                (longest as f32 * CODE_FONT_SIZE * CODE_CHAR_WIDTH + 2.0 * CODE_PADDING)
+                    .ceil()
+                    .max(160.0),
+                lines as f32 * CODE_LINE_HEIGHT + 2.0 * CODE_PADDING,
+            )
+        }
+        ElementKind::LogView { title, lines, .. } => {
+            // Between 10 and 20 rows; longer logs scroll
+            let rows = lines.len().clamp(10, 20);
+            Size::new(
+                640.0,
+                log_view::body_top(title) + rows as f32 * CODE_LINE_HEIGHT + CODE_PADDING,
+            )
+        }
+        ElementKind::StickyNote {
+            content, author, ..
+        }
+        | ElementKind::Callout {
+            content, author, ..
+        } => {
+            let text = ctx.text_size(content, NOTE_FONT_SIZE, NOTE_WIDTH - 16.0);
+            let byline = if author.is_some() { 20.0 } else { 0.0 };
+            Size::new(NOTE_WIDTH, (text.height + 24.0 + byline).max(120.0))
+        }
+        ElementKind::Chart {
+            chart_type, data, ..
+        } => {
+            let points = chart_points(data) as f32;
+            match chart_type.as_str() {
+                "pie" | "donut" => Size::new(280.0, 280.0),
+                _ => Size::new((80.0 + 40.0 * points).max(320.0), 240.0),
+            }
+        }
+        ElementKind::Image { .. } | ElementKind::Shader { .. } => Size::new(320.0, 240.0),
+        ElementKind::Model3D { .. } => Size::new(400.0, 400.0),
+        ElementKind::Video { media_config, .. } => {
+            let (w, h) = media_config
+                .as_ref()
+                .and_then(|config| config.max_resolution)
+                .map_or((16, 9), |resolution| resolution.dimensions());
+            Size::new(640.0, (640.0 * h as f32 / w as f32).round())
+        }
+        ElementKind::Audio { .. } => Size::new(320.0, 72.0),
+        ElementKind::Calendar { view, .. } => match view {
+            CalendarView::Month => Size::new(420.0, 360.0),
+            CalendarView::Week => Size::new(560.0, 200.0),
+        },
+        ElementKind::Poll { options, .. } => Size::new(320.0, 48.0 + 40.0 * options.len() as f32),
+        ElementKind::Agenda { items, .. } => Size::new(360.0, 48.0 + 36.0 * items.len() as f32),
+        ElementKind::Randomizer { source, .. } => match source {
+            RandomSource::Dice { count, .. } => {
+                Size::new((*count as f32 * 64.0 + 32.0).max(160.0), 140.0)
+            }
+            RandomSource::Wheel { .. } => Size::new(240.0, 280.0),
+            RandomSource::Shuffle { items } => Size::new(240.0, 56.0 + 28.0 * items.len() as f32),
+        },
+        ElementKind::Gauge { shape, .. } => match shape {
+            GaugeShape::Radial => Size::new(240.0, 160.0),
+            GaugeShape::Linear => Size::new(280.0, 72.0),
+        },
+        ElementKind::Kpi { sparkline, .. } => {
+            Size::new(240.0, if sparkline.is_empty() { 130.0 } else { 170.0 })
+        }
+        // Word-sized: about the height of a line of body text
+        ElementKind::Sparkline { .. } => Size::new(120.0, 28.0),
+        ElementKind::Tree { root, direction } => {
+            let (width, height) = TreeLayout::natural_size(root, *direction);
+            Size::new(width, height)
+        }
+        ElementKind::Ink { points, width, .. } => {
+            let (width, height) = crate::ink::natural_size(points, *width);
+            Size::new(width, height)
+        }
+        // Drawn from two points or over a region, so the size is the caller's
+        ElementKind::Measurement { .. } => Size::new(160.0, 40.0),
+        ElementKind::Region { .. } => Size::new(200.0, 120.0),
+        ElementKind::Shape { shape } => shape.natural_size(),
+        ElementKind::Barcode {
+            symbology, modules, ..
+        } => barcode::natural_size(*symbology, modules),
+        ElementKind::Embed { .. } => Size::new(480.0, 320.0),
+        ElementKind::FloorPlan {
+            plan_width,
+            plan_height,
+            ..
+        } => floor_plan_size(*plan_width, *plan_height),
+        ElementKind::Group { .. } | ElementKind::OverlayLayer { .. } => return None,
+    };
+    Some(size)
+}
+
+/// 480px wide at the plan's aspect ratio, plus padding and caption.
+fn floor_plan_size(plan_width: f32, plan_height: f32) -> Size {
+    let aspect = if plan_width > 0.0 {
+        plan_height / plan_width
+    } else {
+        0.6
+    };
+    Size::new(480.0, (468.0 * aspect + 36.0).round().clamp(120.0, 960.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Color;
+
+    #[test]
+    fn test_wrap_breaks_at_spaces_and_long_words() {
+        let ctx = MeasureContext::default();
+        // 10px glyphs at 20px font: 10 columns in 100px
+        let ctx = MeasureContext {
+            char_width: 0.5,
+            ..ctx
+        };
+        assert_eq!(
+            ctx.wrap("the quick brown fox", 20.0, 100.0),
+            ["the quick", "brown fox"]
+        );
+        assert_eq!(
+            ctx.wrap("abcdefghijklmno\nend", 20.0, 100.0),
+            ["abcdefghij", "klmno", "end"]
+        );
+        assert_eq!(ctx.wrap("", 20.0, 100.0), [""]);
+
+        let size = ctx.text_size("the quick brown fox", 20.0, 100.0);
+        assert!((size.width - 90.0).abs() < f32::EPSILON);
+        assert!((size.height - 52.0).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn test_per_kind_sizes() {
+        let ctx = MeasureContext::default();
+
+        let code = ElementKind::Code {
+            language: "rust".to_string(),
+            source: "fn main() {\n    println!(\"hi\");\n}".to_string(),
+            spans: Vec::new(),
+        };
+        let size = preferred_size(&code, &ctx).expect("code");
+        assert!((size.height - (3.0 * CODE_LINE_HEIGHT + 2.0 * CODE_PADDING)).abs() < 0.01);
+
+        let chart = |labels: usize| ElementKind::Chart {
+            chart_type: "bar".to_string(),
+            data: serde_json::json!({ "labels": vec!["Q"; labels], "values": vec![1; labels] }),
+            binding: None,
+        };
+        let few = preferred_size(&chart(3), &ctx).expect("chart");
+        let many = preferred_size(&chart(12), &ctx).expect("chart");
+        assert!((few.width - 320.0).abs() < f32::EPSILON);
+        assert!((many.width - 560.0).abs() < f32::EPSILON);
+
+        // Long notes grow downwards at a fixed width
+        let note = |content: &str| ElementKind::StickyNote {
+            content: content.to_string(),
+            color: Color::from_rgb(0xff_f59d).into(),
+            author: None,
+        };
+        let short = preferred_size(&note("Hi"), &ctx).expect("note");
+        let long = preferred_size(&note(&"word ".repeat(80)), &ctx).expect("note");
+        assert!((short.width - NOTE_WIDTH).abs() < f32::EPSILON);
+        assert!(long.height > short.height);
+
+        assert_eq!(tex_glyphs("\\frac{a}{b} + x^2"), 6);
+        let group = ElementKind::Group {
+            children: Vec::new(),
+            clip: false,
+            opacity: 1.0,
+        };
+        assert!(preferred_size(&group, &ctx).is_none());
+    }
+}
@@ -0,0 +1,111 @@
+//! # Scene Merging
+//!
+//! Options and results for [`Scene::merge`], which imports another scene's
+//! elements, e.g. to insert a saved scene as a template or to import a file
+//! into a live session.
+//!
+//! ```text
+//!  target             source                 target after merge
+//!  ┌──────────┐       ┌──────────┐           ┌──────────┐
+//!  │ A  B     │   +   │ B' C     │  offset → │ A  B     │
+//!  └──────────┘       └──────────┘           │   B'' C  │  B' collided:
+//!                                            └──────────┘  remapped to B''
+//! ```
+//!
+//! Elements keep their IDs unless they collide with an element already in
+//! the target (or [`MergeOptions::fresh_ids`] is set); the
+//! [`CollisionPolicy`] decides what happens to colliding elements. Parent,
+//! child and callout links inside the source follow the remapping.
+//!
+//! [`Scene::merge`]: crate::Scene::merge
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::ElementId;
+
+/// What to do with a source element whose ID is already in the target.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CollisionPolicy {
+    /// Give the incoming element a fresh ID; both are kept.
+    #[default]
+    Remap,
+    /// Keep the existing element and drop the incoming one with its
+    /// descendants.
+    Skip,
+    /// Remove the existing element with its descendants and add the
+    /// incoming one under the same ID.
+    Replace,
+    /// Fail the merge without changing the target.
+    Fail,
+}
+
+/// How [`Scene::merge`](crate::Scene::merge) imports elements.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MergeOptions {
+    /// Added to the X coordinate of every imported element.
+    pub offset_x: f32,
+    /// Added to the Y coordinate of every imported element.
+    pub offset_y: f32,
+    /// Give every imported element a fresh ID, colliding or not, so the
+    /// same source can be merged repeatedly.
+    pub fresh_ids: bool,
+    /// What to do with elements whose ID is already taken.
+    pub on_collision: CollisionPolicy,
+}
+
+impl MergeOptions {
+    /// Offset every imported element by (`dx`, `dy`).
+    #[must_use]
+    pub const fn with_offset(mut self, dx: f32, dy: f32) -> Self {
+        self.offset_x = dx;
+        self.offset_y = dy;
+        self
+    }
+
+    /// Give every imported element a fresh ID.
+    #[must_use]
+    pub const fn with_fresh_ids(mut self) -> Self {
+        self.fresh_ids = true;
+        self
+    }
+
+    /// Set the collision policy.
+    #[must_use]
+    pub const fn with_collision_policy(mut self, policy: CollisionPolicy) -> Self {
+        self.on_collision = policy;
+        self
+    }
+}
+
+/// Outcome of a [`Scene::merge`](crate::Scene::merge).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MergeReport {
+    /// Source ID → ID in the target, for every imported element.
+    pub ids: HashMap<ElementId, ElementId>,
+    /// IDs of the imported elements in the target, parents before children.
+    pub added: Vec<ElementId>,
+    /// Target elements removed by [`CollisionPolicy::Replace`], including
+    /// descendants that were not themselves replaced.
+    pub removed: Vec<ElementId>,
+    /// Source elements dropped by [`CollisionPolicy::Skip`], including their
+    /// descendants.
+    pub skipped: Vec<ElementId>,
+}
+
+impl MergeReport {
+    /// Target ID of an imported source element.
+    #[must_use]
+    pub fn mapped(&self, source: ElementId) -> Option<ElementId> {
+        self.ids.get(&source).copied()
+    }
+
+    /// Number of source elements that were given a different ID.
+    #[must_use]
+    pub fn remapped(&self) -> usize {
+        self.ids.iter().filter(|(from, to)| from != to).count()
+    }
+}
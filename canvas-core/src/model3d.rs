@@ -0,0 +1,619 @@
+//! # 3D Models
+//!
+//! Placement, framing and lighting for `Model3D` elements, shared by the
+//! holographic and GPU renderers so a model looks the same in both.
+//!
+//! The element's 2D [`Transform`](crate::Transform) places the model's box on
+//! the canvas; everything here happens inside that box, in model space:
+//!
+//! ```text
+//!            +Y                       camera (yaw, pitch, distance)
+//!             │                          ●
+//!             │    pose: position,        ╲
+//!             │    rotation (quaternion),  ╲  looks at target
+//!             └──── +X   scale              ╲
+//!            ╱                               ◆ target
+//!          +Z  (towards the viewer)
+//! ```
+//!
+//! [`Transform3D`] uses glTF's conventions: translation, a unit quaternion
+//! `[x, y, z, w]` and per-axis scale, applied scale first. Lights follow the
+//! same right-handed, Y-up axes.
+
+use std::f32::consts::{FRAC_PI_2, FRAC_PI_4, PI};
+
+use serde::{Deserialize, Serialize};
+
+use crate::color::Color;
+use crate::element::ElementKind;
+use crate::error::{CanvasError, CanvasResult};
+
+/// Most lights a model can be lit by.
+pub const MAX_MODEL_LIGHTS: usize = 8;
+
+/// Largest intensity of a single light.
+pub const MAX_LIGHT_INTENSITY: f32 = 10.0;
+
+/// Position, orientation and scale of a model in its own space.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+pub struct Transform3D {
+    /// Translation.
+    #[serde(default)]
+    pub position: [f32; 3],
+    /// Orientation as a unit quaternion `[x, y, z, w]`.
+    #[serde(default = "identity_rotation")]
+    pub rotation: [f32; 4],
+    /// Scale along each axis.
+    #[serde(default = "unit_scale")]
+    pub scale: [f32; 3],
+}
+
+const fn identity_rotation() -> [f32; 4] {
+    [0.0, 0.0, 0.0, 1.0]
+}
+
+const fn unit_scale() -> [f32; 3] {
+    [1.0, 1.0, 1.0]
+}
+
+impl Default for Transform3D {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl Transform3D {
+    /// No translation, rotation or scaling.
+    pub const IDENTITY: Self = Self {
+        position: [0.0; 3],
+        rotation: identity_rotation(),
+        scale: unit_scale(),
+    };
+
+    /// A pose from Euler angles in radians, applied about X, then Y, then Z,
+    /// and a uniform scale, as `Model3D`'s `rotation` and `scale` give it.
+    #[must_use]
+    pub fn from_euler(rotation: [f32; 3], scale: f32) -> Self {
+        let axis = |index: usize, angle: f32| {
+            let (sin, cos) = (angle / 2.0).sin_cos();
+            let mut q = [0.0, 0.0, 0.0, cos];
+            q[index] = sin;
+            q
+        };
+        let [x, y, z] = rotation;
+        Self {
+            position: [0.0; 3],
+            rotation: quat_mul(quat_mul(axis(2, z), axis(1, y)), axis(0, x)),
+            scale: [scale; 3],
+        }
+    }
+
+    /// Set the translation.
+    #[must_use]
+    pub const fn with_position(mut self, position: [f32; 3]) -> Self {
+        self.position = position;
+        self
+    }
+
+    /// Rotate a direction by the pose's orientation, ignoring scale and
+    /// translation.
+    #[must_use]
+    pub fn rotate(&self, v: [f32; 3]) -> [f32; 3] {
+        let [qx, qy, qz, w] = normalize4(self.rotation);
+        let q = [qx, qy, qz];
+        let t = scale3(cross(q, v), 2.0);
+        add3(add3(v, scale3(t, w)), cross(q, t))
+    }
+
+    /// Map a point from the model's local space into model space.
+    #[must_use]
+    pub fn transform_point(&self, p: [f32; 3]) -> [f32; 3] {
+        let scaled = [
+            p[0] * self.scale[0],
+            p[1] * self.scale[1],
+            p[2] * self.scale[2],
+        ];
+        add3(self.rotate(scaled), self.position)
+    }
+
+    /// The pose as a column-major 4x4 matrix.
+    #[must_use]
+    pub fn matrix(&self) -> [f32; 16] {
+        let mut m = [0.0; 16];
+        for (axis, column) in m.chunks_exact_mut(4).take(3).enumerate() {
+            let mut basis = [0.0; 3];
+            basis[axis] = self.scale[axis];
+            let [x, y, z] = self.rotate(basis);
+            column.copy_from_slice(&[x, y, z, 0.0]);
+        }
+        let [x, y, z] = self.position;
+        m[12..].copy_from_slice(&[x, y, z, 1.0]);
+        m
+    }
+
+    /// Check the pose is finite, the rotation is a quaternion and every
+    /// scale is positive.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CanvasError::InvalidOperation`] describing the problem.
+    pub fn validate(&self) -> CanvasResult<()> {
+        let finite = self
+            .position
+            .iter()
+            .chain(&self.rotation)
+            .chain(&self.scale)
+            .all(|v| v.is_finite());
+        if !finite {
+            return Err(CanvasError::InvalidOperation(
+                "3D pose values must be finite".to_string(),
+            ));
+        }
+        if length(&self.rotation) < f32::EPSILON {
+            return Err(CanvasError::InvalidOperation(
+                "3D rotation must be a non-zero quaternion".to_string(),
+            ));
+        }
+        if self.scale.iter().any(|&s| s <= 0.0) {
+            return Err(CanvasError::InvalidOperation(
+                "3D scale must be positive".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Where a 3D view should look at a model from.
+///
+/// The camera orbits `target`: `yaw` turns it around the Y axis from +Z
+/// towards +X and `pitch` raises it towards +Y.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+#[serde(default)]
+pub struct ModelCamera {
+    /// Point the camera looks at, in model space.
+    pub target: [f32; 3],
+    /// Distance from the target.
+    pub distance: f32,
+    /// Turn around the target, in radians.
+    pub yaw: f32,
+    /// Elevation above the target, in radians (under a quarter turn).
+    pub pitch: f32,
+    /// Vertical field of view, in radians.
+    pub fov: f32,
+}
+
+impl Default for ModelCamera {
+    fn default() -> Self {
+        Self {
+            target: [0.0; 3],
+            distance: 3.0,
+            yaw: 0.0,
+            pitch: 0.0,
+            fov: FRAC_PI_4,
+        }
+    }
+}
+
+impl ModelCamera {
+    /// A camera looking straight at a sphere around `center`, close enough
+    /// for it to fill the view.
+    #[must_use]
+    pub fn fit(center: [f32; 3], radius: f32) -> Self {
+        let camera = Self {
+            target: center,
+            ..Self::default()
+        };
+        Self {
+            distance: radius / (camera.fov / 2.0).sin(),
+            ..camera
+        }
+    }
+
+    /// The camera's position in model space.
+    #[must_use]
+    pub fn position(&self) -> [f32; 3] {
+        let (sin_yaw, cos_yaw) = self.yaw.sin_cos();
+        let (sin_pitch, cos_pitch) = self.pitch.sin_cos();
+        add3(
+            self.target,
+            scale3(
+                [cos_pitch * sin_yaw, sin_pitch, cos_pitch * cos_yaw],
+                self.distance,
+            ),
+        )
+    }
+
+    /// Check the camera can be built: a positive distance, pitch short of
+    /// straight up or down and a field of view under half a turn.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CanvasError::InvalidOperation`] describing the problem.
+    pub fn validate(&self) -> CanvasResult<()> {
+        let finite = self
+            .target
+            .iter()
+            .chain([&self.distance, &self.yaw, &self.pitch, &self.fov])
+            .all(|v| v.is_finite());
+        if !finite || self.distance <= 0.0 {
+            return Err(CanvasError::InvalidOperation(
+                "camera distance must be positive and values finite".to_string(),
+            ));
+        }
+        if self.pitch.abs() >= FRAC_PI_2 {
+            return Err(CanvasError::InvalidOperation(
+                "camera pitch must be less than a quarter turn".to_string(),
+            ));
+        }
+        if self.fov <= 0.0 || self.fov >= PI {
+            return Err(CanvasError::InvalidOperation(
+                "camera field of view must be between 0 and pi radians".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Lights a model is shaded with.
+///
+/// The default is soft ambient light plus a white key light from the upper
+/// left, in front of the model.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+#[serde(default)]
+pub struct Lighting {
+    /// Light reaching every surface, from 0.0 to 1.0.
+    pub ambient: f32,
+    /// Directional and point lights.
+    pub lights: Vec<Light>,
+}
+
+impl Default for Lighting {
+    fn default() -> Self {
+        Self {
+            ambient: 0.35,
+            lights: vec![Light::Directional {
+                direction: [0.5, -1.0, -0.75],
+                color: white(),
+                intensity: 0.8,
+            }],
+        }
+    }
+}
+
+/// A light source.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum Light {
+    /// Parallel light, like the sun.
+    Directional {
+        /// Direction the light travels in.
+        direction: [f32; 3],
+        /// Light color.
+        #[serde(default = "white")]
+        color: Color,
+        /// Brightness multiplier.
+        #[serde(default = "unit_intensity")]
+        intensity: f32,
+    },
+    /// Light shining out from a point, without falloff.
+    Point {
+        /// Position of the light, in model space.
+        position: [f32; 3],
+        /// Light color.
+        #[serde(default = "white")]
+        color: Color,
+        /// Brightness multiplier.
+        #[serde(default = "unit_intensity")]
+        intensity: f32,
+    },
+}
+
+const fn white() -> Color {
+    Color::rgb(255, 255, 255)
+}
+
+const fn unit_intensity() -> f32 {
+    1.0
+}
+
+impl Lighting {
+    /// Light a surface of color `base` (linear RGB, 0.0 to 1.0) at `point`
+    /// facing along `normal`, with Lambertian diffuse shading.
+    #[must_use]
+    pub fn shade(&self, base: [f32; 3], point: [f32; 3], normal: [f32; 3]) -> [f32; 3] {
+        let normal = normalize3(normal);
+        let mut light = [self.ambient; 3];
+        for source in &self.lights {
+            let (towards, color, intensity) = match source {
+                Light::Directional {
+                    direction,
+                    color,
+                    intensity,
+                } => (scale3(*direction, -1.0), color, intensity),
+                Light::Point {
+                    position,
+                    color,
+                    intensity,
+                } => (sub3(*position, point), color, intensity),
+            };
+            let diffuse = dot(normal, normalize3(towards)).max(0.0) * intensity;
+            let [r, g, b, _] = color.to_rgba_f32();
+            light = add3(light, scale3([r, g, b], diffuse));
+        }
+        [
+            (base[0] * light[0]).clamp(0.0, 1.0),
+            (base[1] * light[1]).clamp(0.0, 1.0),
+            (base[2] * light[2]).clamp(0.0, 1.0),
+        ]
+    }
+
+    /// Check the ambient level and every light.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CanvasError::InvalidOperation`] describing the first problem.
+    pub fn validate(&self) -> CanvasResult<()> {
+        if !(0.0..=1.0).contains(&self.ambient) {
+            return Err(CanvasError::InvalidOperation(
+                "ambient light must be between 0 and 1".to_string(),
+            ));
+        }
+        if self.lights.len() > MAX_MODEL_LIGHTS {
+            return Err(CanvasError::InvalidOperation(format!(
+                "a model can have at most {MAX_MODEL_LIGHTS} lights"
+            )));
+        }
+        for light in &self.lights {
+            let (vector, intensity) = match light {
+                Light::Directional {
+                    direction,
+                    intensity,
+                    ..
+                } => {
+                    if length(direction) < f32::EPSILON {
+                        return Err(CanvasError::InvalidOperation(
+                            "directional light needs a non-zero direction".to_string(),
+                        ));
+                    }
+                    (direction, intensity)
+                }
+                Light::Point {
+                    position,
+                    intensity,
+                    ..
+                } => (position, intensity),
+            };
+            if !vector.iter().all(|v| v.is_finite())
+                || !(0.0..=MAX_LIGHT_INTENSITY).contains(intensity)
+            {
+                return Err(CanvasError::InvalidOperation(format!(
+                    "light values must be finite and intensity 0 to {MAX_LIGHT_INTENSITY}"
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The pose of a `Model3D`: its `pose` if set, otherwise its Euler
+/// `rotation` and uniform `scale`. `None` for other kinds.
+#[must_use]
+pub fn pose(kind: &ElementKind) -> Option<Transform3D> {
+    match kind {
+        ElementKind::Model3D {
+            rotation,
+            scale,
+            pose,
+            ..
+        } => Some(pose.unwrap_or_else(|| Transform3D::from_euler(*rotation, *scale))),
+        _ => None,
+    }
+}
+
+/// Color of a `Model3D`'s front face (its local +Z, facing the viewer)
+/// under its lighting, for renderers that draw the model as a flat
+/// placeholder. `None` for other kinds.
+#[must_use]
+pub fn shade_front(kind: &ElementKind, base: [f32; 3]) -> Option<[f32; 3]> {
+    let ElementKind::Model3D { lighting, .. } = kind else {
+        return None;
+    };
+    let pose = pose(kind)?;
+    let normal = pose.rotate([0.0, 0.0, 1.0]);
+    Some(match lighting {
+        Some(lighting) => lighting.shade(base, pose.position, normal),
+        None => Lighting::default().shade(base, pose.position, normal),
+    })
+}
+
+/// Check a `Model3D`'s pose, camera hint and lighting.
+///
+/// # Errors
+///
+/// Returns [`CanvasError::InvalidOperation`] describing the first problem.
+pub fn validate(kind: &ElementKind) -> CanvasResult<()> {
+    let ElementKind::Model3D {
+        rotation,
+        scale,
+        pose,
+        camera,
+        lighting,
+        ..
+    } = kind
+    else {
+        return Ok(());
+    };
+    Transform3D::from_euler(*rotation, *scale).validate()?;
+    pose.as_ref().map_or(Ok(()), Transform3D::validate)?;
+    camera.as_ref().map_or(Ok(()), ModelCamera::validate)?;
+    lighting.as_ref().map_or(Ok(()), Lighting::validate)
+}
+
+fn quat_mul(a: [f32; 4], b: [f32; 4]) -> [f32; 4] {
+    let [ax, ay, az, aw] = a;
+    let [bx, by, bz, bw] = b;
+    [
+        aw * bx + ax * bw + ay * bz - az * by,
+        aw * by - ax * bz + ay * bw + az * bx,
+        aw * bz + ax * by - ay * bx + az * bw,
+        aw * bw - ax * bx - ay * by - az * bz,
+    ]
+}
+
+fn length(v: &[f32]) -> f32 {
+    v.iter().map(|c| c * c).sum::<f32>().sqrt()
+}
+
+fn normalize4(q: [f32; 4]) -> [f32; 4] {
+    let len = length(&q);
+    if len < f32::EPSILON {
+        identity_rotation()
+    } else {
+        q.map(|c| c / len)
+    }
+}
+
+fn normalize3(v: [f32; 3]) -> [f32; 3] {
+    let len = length(&v);
+    if len < f32::EPSILON {
+        v
+    } else {
+        v.map(|c| c / len)
+    }
+}
+
+const fn add3(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [a[0] + b[0], a[1] + b[1], a[2] + b[2]]
+}
+
+const fn sub3(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+const fn scale3(v: [f32; 3], s: f32) -> [f32; 3] {
+    [v[0] * s, v[1] * s, v[2] * s]
+}
+
+const fn dot(a: [f32; 3], b: [f32; 3]) -> f32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+const fn cross(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: [f32; 3], b: [f32; 3]) {
+        assert!(
+            a.iter().zip(&b).all(|(x, y)| (x - y).abs() < 1e-5),
+            "{a:?} != {b:?}"
+        );
+    }
+
+    #[test]
+    fn test_euler_pose_matches_matrix() {
+        // A quarter turn about Y takes +X to -Z
+        let pose =
+            Transform3D::from_euler([0.0, FRAC_PI_2, 0.0], 2.0).with_position([1.0, 0.0, 0.0]);
+        assert_close(pose.rotate([1.0, 0.0, 0.0]), [0.0, 0.0, -1.0]);
+        assert_close(pose.transform_point([1.0, 0.0, 0.0]), [1.0, 0.0, -2.0]);
+
+        let m = pose.matrix();
+        let p = [1.0, 0.0, 0.0];
+        let by_matrix = [
+            m[0] * p[0] + m[4] * p[1] + m[8] * p[2] + m[12],
+            m[1] * p[0] + m[5] * p[1] + m[9] * p[2] + m[13],
+            m[2] * p[0] + m[6] * p[1] + m[10] * p[2] + m[14],
+        ];
+        assert_close(by_matrix, pose.transform_point(p));
+
+        // X is applied before Z: +Y goes to +Z, then stays there
+        let pose = Transform3D::from_euler([FRAC_PI_2, 0.0, FRAC_PI_2], 1.0);
+        assert_close(pose.rotate([0.0, 1.0, 0.0]), [0.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn test_camera_orbits_target() {
+        let camera = ModelCamera {
+            yaw: FRAC_PI_2,
+            ..ModelCamera::default()
+        };
+        assert_close(camera.position(), [3.0, 0.0, 0.0]);
+
+        let fitted = ModelCamera::fit([0.0, 1.0, 0.0], 1.0);
+        assert!(fitted.distance > 2.0);
+        assert_close(fitted.position(), [0.0, 1.0, fitted.distance]);
+
+        let straight_down = ModelCamera {
+            pitch: -FRAC_PI_2,
+            ..ModelCamera::default()
+        };
+        assert!(straight_down.validate().is_err());
+    }
+
+    #[test]
+    fn test_lighting_shades_faces_towards_the_light() {
+        let lighting = Lighting::default();
+        let base = [1.0, 1.0, 1.0];
+        let lit = lighting.shade(base, [0.0; 3], [-0.5, 1.0, 0.75]);
+        let unlit = lighting.shade(base, [0.0; 3], [0.5, -1.0, -0.75]);
+        assert!(lit[0] > 0.9);
+        assert_close(unlit, [0.35; 3]);
+
+        let point = Lighting {
+            ambient: 0.0,
+            lights: vec![Light::Point {
+                position: [0.0, 0.0, 2.0],
+                color: Color::rgb(255, 0, 0),
+                intensity: 1.0,
+            }],
+        };
+        assert_close(
+            point.shade(base, [0.0; 3], [0.0, 0.0, 1.0]),
+            [1.0, 0.0, 0.0],
+        );
+    }
+
+    #[test]
+    fn test_validate_model() {
+        let mut kind = ElementKind::Model3D {
+            src: "asset:abc".to_string(),
+            rotation: [0.0; 3],
+            scale: 1.0,
+            pose: Some(Transform3D::IDENTITY),
+            camera: Some(ModelCamera::default()),
+            lighting: Some(Lighting::default()),
+        };
+        assert!(validate(&kind).is_ok());
+        assert_eq!(pose(&kind), Some(Transform3D::IDENTITY));
+        // Facing the viewer, the default key light reaches the front face
+        let front = shade_front(&kind, [1.0; 3]).expect("model");
+        assert!(front[0] > 0.35 && front[0] < 1.0);
+
+        if let ElementKind::Model3D { pose, lighting, .. } = &mut kind {
+            *pose = Some(Transform3D {
+                scale: [1.0, 0.0, 1.0],
+                ..Transform3D::IDENTITY
+            });
+            *lighting = None;
+        }
+        assert!(validate(&kind).is_err());
+
+        let lighting: Lighting =
+            serde_json::from_str(r#"{"lights": [{"type": "point", "position": [0, 2, 0]}]}"#)
+                .expect("lighting");
+        assert!((lighting.ambient - 0.35).abs() < f32::EPSILON);
+        assert!(lighting.validate().is_ok());
+    }
+}
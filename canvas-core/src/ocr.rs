@@ -0,0 +1,211 @@
+//! # Image Text (OCR)
+//!
+//! Text read out of `Image` elements, so a screenshot's words can be
+//! searched, spotlighted and quoted like any other element's text.
+//!
+//! Recognition runs on the server through a pluggable [`OcrEngine`]; core
+//! only defines the result and where it is kept. The result is stored on the
+//! image as [`crate::ElementKind::Image`]'s `extracted_text` and syncs with it.
+//!
+//! ```text
+//! agent: canvas_ocr { element_id: screenshot }
+//!   → image_bytes(src)                      decode the data: / asset: source
+//!   → engine.recognize(bytes, "image/png")  OcrText { text, blocks }
+//!   → scene.set_extracted_text(id, text)    stored on the image
+//!   → match_elements(scene, "error 404")    now finds the screenshot
+//! ```
+//!
+//! Block positions are fractions of the image's box, like measurement
+//! endpoints, so they stay right when the image is moved or resized.
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::asset::{parse_data_uri, AssetId, AssetStore};
+use crate::error::{CanvasError, CanvasResult};
+
+/// Maximum length of the extracted text, in characters.
+pub const MAX_OCR_TEXT_LEN: usize = 20_000;
+
+/// Maximum number of text blocks kept per image.
+pub const MAX_OCR_BLOCKS: usize = 2_000;
+
+/// Text recognized in an image.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+pub struct OcrText {
+    /// All recognized text, in reading order.
+    pub text: String,
+    /// Name of the engine that read it.
+    pub engine: String,
+    /// Where each line or word was found, if the engine reports it.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub blocks: Vec<OcrBlock>,
+}
+
+/// A run of text and its position in the image.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+pub struct OcrBlock {
+    /// Recognized text.
+    pub text: String,
+    /// Left edge, as a fraction of the image width.
+    pub x: f32,
+    /// Top edge, as a fraction of the image height.
+    pub y: f32,
+    /// Width, as a fraction of the image width.
+    pub width: f32,
+    /// Height, as a fraction of the image height.
+    pub height: f32,
+    /// Engine confidence from 0.0 to 1.0, if reported.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<f32>,
+}
+
+impl OcrText {
+    /// Text read by `engine`, without block positions.
+    #[must_use]
+    pub fn new(text: impl Into<String>, engine: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            engine: engine.into(),
+            blocks: Vec::new(),
+        }
+    }
+
+    /// Check the text and blocks are within limits and the blocks lie in
+    /// the image.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CanvasError::InvalidOperation`] describing the first problem.
+    pub fn validate(&self) -> CanvasResult<()> {
+        if self.text.chars().count() > MAX_OCR_TEXT_LEN {
+            return Err(CanvasError::InvalidOperation(format!(
+                "extracted text is longer than {MAX_OCR_TEXT_LEN} characters"
+            )));
+        }
+        if self.blocks.len() > MAX_OCR_BLOCKS {
+            return Err(CanvasError::InvalidOperation(format!(
+                "extracted text has more than {MAX_OCR_BLOCKS} blocks"
+            )));
+        }
+        let fraction = |v: f32| v.is_finite() && (0.0..=1.0).contains(&v);
+        for block in &self.blocks {
+            if ![block.x, block.y, block.width, block.height]
+                .into_iter()
+                .all(fraction)
+                || block.x + block.width > 1.0 + f32::EPSILON
+                || block.y + block.height > 1.0 + f32::EPSILON
+                || block.confidence.is_some_and(|c| !fraction(c))
+            {
+                return Err(CanvasError::InvalidOperation(format!(
+                    "text block {:?} is not inside the image",
+                    block.text
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Errors from reading text out of an image.
+#[derive(Debug, Error)]
+pub enum OcrError {
+    /// No engine is configured.
+    #[error("No OCR engine is configured")]
+    NoEngine,
+
+    /// The image source cannot be read on the server.
+    #[error("Cannot read image source: {0}")]
+    UnreadableSource(String),
+
+    /// The engine failed.
+    #[error("OCR failed: {0}")]
+    Engine(String),
+}
+
+/// A text recognizer the server can run over image bytes.
+///
+/// Engines are synchronous; callers on an async runtime should run them on
+/// a blocking thread.
+pub trait OcrEngine: Send + Sync {
+    /// Short name recorded with each result, e.g. `"tesseract"`.
+    fn name(&self) -> &str;
+
+    /// Read the text in an encoded image.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`OcrError::Engine`] if recognition fails.
+    fn recognize(&self, image: &[u8], mime_type: &str) -> Result<OcrText, OcrError>;
+}
+
+/// Decode an image source to its MIME type and bytes.
+///
+/// Inline `data:` URIs are decoded; `asset:` references are looked up in
+/// `assets` when given. Remote URLs are not fetched.
+///
+/// # Errors
+///
+/// Returns [`OcrError::UnreadableSource`] for any other source, a malformed
+/// data URI or an asset that is not stored.
+pub fn image_bytes(src: &str, assets: Option<&AssetStore>) -> Result<(String, Vec<u8>), OcrError> {
+    let preview: String = src.chars().take(64).collect();
+    if src.starts_with("data:") {
+        return parse_data_uri(src).map_err(|e| OcrError::UnreadableSource(e.to_string()));
+    }
+    if let Some(id) = AssetId::from_uri(src) {
+        return assets
+            .and_then(|store| store.get(&id))
+            .map(|asset| (asset.mime_type().to_string(), asset.data().to_vec()))
+            .ok_or(OcrError::UnreadableSource(preview));
+    }
+    Err(OcrError::UnreadableSource(format!(
+        "{preview} (only data: and asset: images can be read)"
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block(x: f32, width: f32) -> OcrBlock {
+        OcrBlock {
+            text: "Error 404".to_string(),
+            x,
+            y: 0.1,
+            width,
+            height: 0.1,
+            confidence: Some(0.9),
+        }
+    }
+
+    #[test]
+    fn test_validate_blocks_inside_image() {
+        let mut text = OcrText::new("Error 404", "test");
+        text.blocks.push(block(0.2, 0.5));
+        assert!(text.validate().is_ok());
+        text.blocks.push(block(0.8, 0.5));
+        assert!(text.validate().is_err());
+        text.blocks.pop();
+        text.text = "x".repeat(MAX_OCR_TEXT_LEN + 1);
+        assert!(text.validate().is_err());
+    }
+
+    #[test]
+    fn test_image_bytes_reads_data_and_asset_sources() {
+        let (mime, bytes) = image_bytes("data:image/png;base64,aGVsbG8=", None).expect("data uri");
+        assert_eq!(
+            (mime.as_str(), bytes.as_slice()),
+            ("image/png", &b"hello"[..])
+        );
+
+        let mut assets = AssetStore::new();
+        let id = assets.insert(b"png".to_vec(), "image/png").expect("insert");
+        let (_, bytes) = image_bytes(&id.to_uri(), Some(&assets)).expect("asset");
+        assert_eq!(bytes, b"png");
+        assert!(image_bytes(&id.to_uri(), None).is_err());
+        assert!(image_bytes("https://example.com/a.png", None).is_err());
+    }
+}
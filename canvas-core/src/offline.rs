@@ -17,6 +17,8 @@ use std::collections::VecDeque;
 use std::time::{SystemTime, UNIX_EPOCH};
 
 /// A queued operation for offline sync.
+// Queued operations are short-lived; boxing `Element` is not worth the churn
+#[allow(clippy::large_enum_variant)]
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum Operation {
     /// Add a new element to the scene.
@@ -69,8 +71,7 @@ impl Operation {
     pub fn now() -> u64 {
         SystemTime::now()
             .duration_since(UNIX_EPOCH)
-            .map(|d| d.as_millis() as u64)
-            .unwrap_or(0)
+            .map_or(0, |d| d.as_millis() as u64)
     }
 }
 
@@ -251,12 +252,13 @@ pub enum ConflictResolution {
 mod tests {
     use super::*;
     use crate::element::ElementKind;
+    use crate::Color;
 
     fn create_test_element() -> Element {
         Element::new(ElementKind::Text {
             content: "Test".to_string(),
             font_size: 16.0,
-            color: "#000000".to_string(),
+            color: Color::BLACK.into(),
         })
     }
 
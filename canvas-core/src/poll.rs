@@ -0,0 +1,280 @@
+//! # Poll Layout and Tallies
+//!
+//! Votes, results and geometry for `Poll` elements.
+//!
+//! A poll shows its question above one row per option; each row is a
+//! result bar sized by the option's share of the votes:
+//!
+//! ```text
+//! ┌───────────────────────────────────┐
+//! │ Which topic next?                 │  question
+//! ├───────────────────────────────────┤
+//! │ ████████████ Pricing      3 · 60% │  option rows
+//! │ ████         Roadmap      1 · 20% │
+//! │ ████         Hiring       1 · 20% │
+//! └───────────────────────────────────┘
+//! ```
+//!
+//! Votes are kept per voter (a peer or agent ID), so voting again moves the
+//! voter's vote instead of adding one. [`PollLayout`] computes the rows in
+//! the element's local frame (origin at its unrotated top-left corner); the
+//! renderers and the interaction router share it, so a tap votes for the
+//! row drawn under it.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::element::ElementKind;
+use crate::error::{CanvasError, CanvasResult};
+
+/// Most options a poll may have.
+pub const MAX_POLL_OPTIONS: usize = 10;
+
+/// Count the votes for each of `option_count` options.
+///
+/// `votes` maps voter IDs to option indices; votes for options that no
+/// longer exist are ignored.
+#[must_use]
+pub fn tally(option_count: usize, votes: &BTreeMap<String, usize>) -> Vec<usize> {
+    let mut counts = vec![0; option_count];
+    for &option in votes.values() {
+        if let Some(count) = counts.get_mut(option) {
+            *count += 1;
+        }
+    }
+    counts
+}
+
+/// Record `voter`'s vote for `option`, replacing any earlier vote.
+///
+/// # Errors
+///
+/// Returns [`CanvasError::InvalidOperation`] if `kind` is not a poll, the
+/// poll is closed or `option` is out of range.
+pub fn cast_vote(kind: &mut ElementKind, voter: &str, option: usize) -> CanvasResult<()> {
+    let ElementKind::Poll {
+        options,
+        votes,
+        closed,
+        ..
+    } = kind
+    else {
+        return Err(CanvasError::InvalidOperation(format!(
+            "{} is not a poll",
+            kind.type_name()
+        )));
+    };
+    if *closed {
+        return Err(CanvasError::InvalidOperation("Poll is closed".to_string()));
+    }
+    if option >= options.len() {
+        return Err(CanvasError::InvalidOperation(format!(
+            "Poll has no option {option}"
+        )));
+    }
+    votes.insert(voter.to_string(), option);
+    Ok(())
+}
+
+/// One option's result.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PollOptionResult {
+    /// Option label.
+    pub label: String,
+    /// Number of votes.
+    pub votes: usize,
+    /// Fraction of all votes, `0.0..=1.0` (0 when nobody has voted).
+    pub share: f32,
+}
+
+impl PollOptionResult {
+    /// Vote count and share as shown beside the bar, e.g. `"3 · 60%"`.
+    #[must_use]
+    pub fn summary(&self) -> String {
+        format!("{} · {:.0}%", self.votes, self.share * 100.0)
+    }
+}
+
+/// Aggregated results of a poll.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PollResults {
+    /// The question asked.
+    pub question: String,
+    /// Results per option, in display order.
+    pub options: Vec<PollOptionResult>,
+    /// Number of votes counted.
+    pub total: usize,
+    /// Whether voting has closed.
+    pub closed: bool,
+}
+
+impl PollResults {
+    /// Aggregate the votes of a poll, or `None` if `kind` is not a poll.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn from_kind(kind: &ElementKind) -> Option<Self> {
+        let ElementKind::Poll {
+            question,
+            options,
+            votes,
+            closed,
+        } = kind
+        else {
+            return None;
+        };
+        let counts = tally(options.len(), votes);
+        let total: usize = counts.iter().sum();
+        Some(Self {
+            question: question.clone(),
+            options: options
+                .iter()
+                .zip(counts)
+                .map(|(label, votes)| PollOptionResult {
+                    label: label.clone(),
+                    votes,
+                    share: if total == 0 {
+                        0.0
+                    } else {
+                        votes as f32 / total as f32
+                    },
+                })
+                .collect(),
+            total,
+            closed: *closed,
+        })
+    }
+}
+
+/// An option row in a [`PollLayout`], in the element's local frame.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PollRow {
+    /// Option index.
+    pub index: usize,
+    /// Left edge of the bar track.
+    pub x: f32,
+    /// Top edge of the bar track.
+    pub y: f32,
+    /// Width of the bar track.
+    pub width: f32,
+    /// Height of the bar track.
+    pub height: f32,
+}
+
+impl PollRow {
+    /// Width of the result bar for an option with `share` of the votes.
+    #[must_use]
+    pub fn bar_width(&self, share: f32) -> f32 {
+        self.width * share.clamp(0.0, 1.0)
+    }
+
+    /// Font size for the row's label and count.
+    #[must_use]
+    pub fn font_size(&self) -> f32 {
+        (self.height * 0.55).clamp(8.0, 16.0)
+    }
+}
+
+/// Geometry of a poll element.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PollLayout {
+    /// Height of the question band.
+    pub title_height: f32,
+    /// Option rows, top to bottom.
+    pub rows: Vec<PollRow>,
+}
+
+impl PollLayout {
+    /// Space around and between rows.
+    pub const PADDING: f32 = 6.0;
+
+    /// Lay out `option_count` options in a `width` × `height` element.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn new(option_count: usize, width: f32, height: f32) -> Self {
+        let title_height = (height * 0.2).clamp(18.0, 36.0).min(height / 3.0);
+        let pad = Self::PADDING;
+        let slot = (height - title_height - pad).max(0.0) / option_count.max(1) as f32;
+        let rows = (0..option_count)
+            .map(|index| PollRow {
+                index,
+                x: pad,
+                y: title_height + pad + index as f32 * slot,
+                width: (width - 2.0 * pad).max(0.0),
+                height: (slot - pad).max(0.0),
+            })
+            .collect();
+        Self { title_height, rows }
+    }
+
+    /// The option row containing a local point, if any.
+    #[must_use]
+    pub fn option_at(&self, x: f32, y: f32) -> Option<&PollRow> {
+        self.rows
+            .iter()
+            .find(|row| x >= row.x && x < row.x + row.width && y >= row.y && y < row.y + row.height)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Color;
+
+    fn poll() -> ElementKind {
+        ElementKind::Poll {
+            question: "Which topic next?".to_string(),
+            options: vec!["Pricing".to_string(), "Roadmap".to_string()],
+            votes: BTreeMap::new(),
+            closed: false,
+        }
+    }
+
+    #[test]
+    fn test_votes_are_per_voter() {
+        let mut kind = poll();
+        cast_vote(&mut kind, "peer-a", 0).expect("vote");
+        cast_vote(&mut kind, "peer-b", 0).expect("vote");
+        cast_vote(&mut kind, "peer-c", 1).expect("vote");
+        // Voting again moves the vote
+        cast_vote(&mut kind, "peer-b", 1).expect("vote");
+
+        let results = PollResults::from_kind(&kind).expect("poll");
+        assert_eq!(results.total, 3);
+        assert_eq!(results.options[0].votes, 1);
+        assert_eq!(results.options[1].votes, 2);
+        assert!((results.options[1].share - 2.0 / 3.0).abs() < 1e-6);
+        assert_eq!(results.options[1].summary(), "2 · 67%");
+
+        assert!(cast_vote(&mut kind, "peer-a", 2).is_err());
+        if let ElementKind::Poll { closed, .. } = &mut kind {
+            *closed = true;
+        }
+        assert!(cast_vote(&mut kind, "peer-d", 0).is_err());
+        let mut text = ElementKind::Text {
+            content: "Vote here".to_string(),
+            font_size: 16.0,
+            color: Color::BLACK.into(),
+        };
+        assert!(cast_vote(&mut text, "peer-a", 0).is_err());
+    }
+
+    #[test]
+    fn test_tally_ignores_removed_options() {
+        let votes = BTreeMap::from([("a".to_string(), 0), ("b".to_string(), 4)]);
+        assert_eq!(tally(2, &votes), vec![1, 0]);
+    }
+
+    #[test]
+    fn test_layout_rows() {
+        let layout = PollLayout::new(3, 300.0, 200.0);
+        assert_eq!(layout.rows.len(), 3);
+        assert!((layout.title_height - 36.0).abs() < f32::EPSILON);
+
+        let row = layout.rows[1];
+        let hit = layout.option_at(row.x + 1.0, row.y + 1.0).expect("row");
+        assert_eq!(hit.index, 1);
+        assert!(layout.option_at(10.0, 10.0).is_none()); // question band
+        assert!((row.bar_width(0.5) - row.width / 2.0).abs() < f32::EPSILON);
+    }
+}
@@ -0,0 +1,781 @@
+//! # Sync Protocol
+//!
+//! Message types exchanged over the canvas WebSocket (`/ws/sync`).
+//!
+//! Every message is a JSON object tagged by its `type` field. The server
+//! decodes [`ClientMessage`] and sends [`ServerMessage`]; clients do the
+//! reverse, so both sides share these definitions.
+//!
+//! ```text
+//! client → {"type": "subscribe", "session_id": "default"}
+//! server → {"type": "welcome", ...}, {"type": "scene_update", ...}
+//! client → {"type": "add_element", "element": {...}, "message_id": "m1"}
+//! server → {"type": "ack", "message_id": "m1", "success": true}
+//! ```
+
+use serde::{Deserialize, Serialize};
+
+use crate::anchor::Anchor;
+use crate::branding::Branding;
+use crate::camera::{CameraCommand, FitOptions};
+use crate::color::{Theme, ThemeColor};
+use crate::data::DatasetDelta;
+use crate::ink::EraseMode;
+use crate::schema::{ElementDocument, SceneDocument, ScenePage, ScenePatch};
+use crate::view_state::ViewState;
+
+/// Client-to-server WebSocket message types.
+// Messages are handled as soon as they are decoded, so variant size is moot
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClientMessage {
+    /// Subscribe to scene updates for a session.
+    Subscribe {
+        /// Session ID to subscribe to.
+        #[serde(default = "default_session")]
+        session_id: String,
+    },
+    /// Add a new element to the scene.
+    AddElement {
+        /// The element to add.
+        element: ElementDocument,
+        /// Optional message ID for acknowledgment.
+        #[serde(default)]
+        message_id: Option<String>,
+    },
+    /// Update an existing element.
+    UpdateElement {
+        /// Element ID to update.
+        id: String,
+        /// Changes to apply (partial element data).
+        changes: serde_json::Value,
+        /// Optional message ID for acknowledgment.
+        #[serde(default)]
+        message_id: Option<String>,
+    },
+    /// Remove an element from the scene.
+    RemoveElement {
+        /// Element ID to remove.
+        id: String,
+        /// Optional message ID for acknowledgment.
+        #[serde(default)]
+        message_id: Option<String>,
+    },
+    /// Ping to keep connection alive.
+    Ping,
+    /// Sync queued offline operations.
+    SyncQueue {
+        /// Queued operations to sync.
+        operations: Vec<QueuedOperation>,
+    },
+    /// Request current scene state.
+    GetScene,
+    /// Apply several element operations atomically.
+    ///
+    /// Either every operation is applied, followed by a single scene
+    /// broadcast, or none is.
+    ///
+    /// ```json
+    /// { "type": "batch", "operations": [{ "op": "add", "element": { ... } }, { "op": "remove", "id": "..." }] }
+    /// ```
+    Batch {
+        /// Operations to apply, in order.
+        operations: Vec<BatchOperation>,
+        /// Optional message ID for acknowledgment.
+        #[serde(default)]
+        message_id: Option<String>,
+    },
+    /// Wrap sibling elements in a new group without moving them.
+    ///
+    /// The ack result is `{"id": "<group id>"}`.
+    ///
+    /// ```json
+    /// { "type": "group", "element_ids": ["...", "..."], "message_id": "m1" }
+    /// ```
+    Group {
+        /// Elements to group; they must share a parent.
+        element_ids: Vec<String>,
+        /// Optional message ID for acknowledgment.
+        #[serde(default)]
+        message_id: Option<String>,
+    },
+    /// Dissolve a group, keeping its children where they are.
+    ///
+    /// The ack result is `{"children": ["...", ...]}`.
+    Ungroup {
+        /// Group element ID.
+        id: String,
+        /// Optional message ID for acknowledgment.
+        #[serde(default)]
+        message_id: Option<String>,
+    },
+    /// Erase ink strokes under a round eraser, in canvas coordinates.
+    ///
+    /// The pixel eraser splits strokes where it cuts them; the stroke
+    /// eraser removes every stroke it touches. The ack result is
+    /// `{"removed": [...], "changed": [...]}`, listing the removed strokes
+    /// and the new pieces.
+    ///
+    /// ```json
+    /// { "type": "erase_ink", "x": 120, "y": 80, "radius": 12, "mode": "pixel" }
+    /// ```
+    EraseInk {
+        /// Eraser center X.
+        x: f32,
+        /// Eraser center Y.
+        y: f32,
+        /// Eraser radius.
+        radius: f32,
+        /// Pixel (default) or stroke eraser.
+        #[serde(default)]
+        mode: EraseMode,
+        /// Optional message ID for acknowledgment.
+        #[serde(default)]
+        message_id: Option<String>,
+    },
+    /// Change the color and/or width of ink strokes.
+    ///
+    /// ```json
+    /// { "type": "restyle_ink", "element_ids": ["..."], "color": "#e53935", "width": 6 }
+    /// ```
+    RestyleInk {
+        /// Strokes to restyle.
+        element_ids: Vec<String>,
+        /// New stroke color.
+        #[serde(default)]
+        color: Option<ThemeColor>,
+        /// New stroke width in canvas pixels.
+        #[serde(default)]
+        width: Option<f32>,
+        /// Optional message ID for acknowledgment.
+        #[serde(default)]
+        message_id: Option<String>,
+    },
+    /// Undo this connection's most recent ink edit (erase or restyle).
+    ///
+    /// Fails with `undo_failed` if there is nothing to undo or the strokes
+    /// it touched have since been removed.
+    UndoInk {
+        /// Optional message ID for acknowledgment.
+        #[serde(default)]
+        message_id: Option<String>,
+    },
+    /// Redo this connection's most recently undone ink edit.
+    RedoInk {
+        /// Optional message ID for acknowledgment.
+        #[serde(default)]
+        message_id: Option<String>,
+    },
+    /// Move the session camera (fit to content, fit to elements, reset).
+    ///
+    /// ```json
+    /// { "type": "camera", "command": "fit_to_elements", "element_ids": ["..."], "padding": 24 }
+    /// ```
+    Camera {
+        /// The camera command.
+        #[serde(flatten)]
+        command: CameraCommand,
+        /// Padding and zoom limits for fitting.
+        #[serde(flatten)]
+        options: FitOptions,
+        /// Optional message ID for acknowledgment.
+        #[serde(default)]
+        message_id: Option<String>,
+    },
+    /// Save a named view, replacing any anchor with the same name.
+    ///
+    /// Move to it with a `go_to_anchor` camera command:
+    ///
+    /// ```json
+    /// { "type": "set_anchor", "anchor": { "name": "summary", "x": 400, "y": 300, "zoom": 1.5 } }
+    /// { "type": "camera", "command": "go_to_anchor", "name": "summary" }
+    /// ```
+    SetAnchor {
+        /// The anchor to save.
+        anchor: Anchor,
+        /// Optional message ID for acknowledgment.
+        #[serde(default)]
+        message_id: Option<String>,
+    },
+    /// Delete a named view.
+    RemoveAnchor {
+        /// Anchor name.
+        name: String,
+        /// Optional message ID for acknowledgment.
+        #[serde(default)]
+        message_id: Option<String>,
+    },
+    /// Replace the scene theme, restyling every element that references it.
+    ///
+    /// ```json
+    /// { "type": "set_theme", "theme": {
+    ///     "palettes": { "brand": ["#1976d2", "#ff7043"] },
+    ///     "variables": { "accent": "palette(brand, 1)", "ink": "#263238" } } }
+    /// ```
+    SetTheme {
+        /// The new theme; an empty theme removes every definition.
+        theme: Theme,
+        /// Optional message ID for acknowledgment.
+        #[serde(default)]
+        message_id: Option<String>,
+    },
+    /// Set or clear the session's logo and watermark.
+    ///
+    /// ```json
+    /// { "type": "set_branding", "branding": {
+    ///     "watermark": "CONFIDENTIAL", "position": "bottom_right", "opacity": 0.4 } }
+    /// ```
+    SetBranding {
+        /// The new branding, or `None` to remove it.
+        #[serde(default)]
+        branding: Option<Branding>,
+        /// Optional message ID for acknowledgment.
+        #[serde(default)]
+        message_id: Option<String>,
+    },
+    /// Change a dataset, refilling every chart bound to it.
+    ///
+    /// ```json
+    /// { "type": "update_dataset", "delta": {
+    ///     "op": "append", "id": "sales", "rows": [["May", 140]], "keep_last": 12 } }
+    /// ```
+    UpdateDataset {
+        /// The change to apply.
+        delta: DatasetDelta,
+        /// Optional message ID for acknowledgment.
+        #[serde(default)]
+        message_id: Option<String>,
+    },
+    /// Share this client's camera; the sender becomes the session presenter.
+    ViewState {
+        /// The sender's current view.
+        view: ViewState,
+    },
+    /// Stop presenting this client's camera.
+    StopPresenting,
+    /// Ask for this client's remaining quota; answered with
+    /// [`ServerMessage::QuotaStatus`].
+    GetQuota,
+
+    // === WebRTC Signaling Messages ===
+    /// Start a call to a peer.
+    StartCall {
+        /// Target peer ID to call.
+        target_peer_id: String,
+        /// Session ID for the call.
+        session_id: String,
+    },
+    /// SDP offer from caller.
+    Offer {
+        /// Target peer ID.
+        target_peer_id: String,
+        /// SDP offer string.
+        sdp: String,
+    },
+    /// SDP answer from callee.
+    Answer {
+        /// Target peer ID.
+        target_peer_id: String,
+        /// SDP answer string.
+        sdp: String,
+    },
+    /// ICE candidate exchange.
+    IceCandidate {
+        /// Target peer ID.
+        target_peer_id: String,
+        /// ICE candidate string.
+        candidate: String,
+        /// SDP media ID.
+        #[serde(default)]
+        sdp_mid: Option<String>,
+        /// SDP media line index.
+        #[serde(default)]
+        sdp_m_line_index: Option<u16>,
+    },
+    /// End a call with a peer.
+    EndCall {
+        /// Target peer ID.
+        target_peer_id: String,
+    },
+
+    // === Communitas Call Control Messages ===
+    /// Start a new Communitas-backed call for the current session.
+    StartCommunitasCall {
+        /// Whether video should be enabled for this call.
+        #[serde(default)]
+        video_enabled: bool,
+        /// Optional message ID for acknowledgment.
+        #[serde(default)]
+        message_id: Option<String>,
+    },
+    /// Join an existing Communitas call by call ID.
+    JoinCommunitasCall {
+        /// The call ID to join.
+        call_id: String,
+        /// Optional message ID for acknowledgment.
+        #[serde(default)]
+        message_id: Option<String>,
+    },
+    /// Leave the current Communitas call.
+    LeaveCommunitasCall {
+        /// Optional message ID for acknowledgment.
+        #[serde(default)]
+        message_id: Option<String>,
+    },
+
+    // === Interaction Events (AG-UI) ===
+    /// Report a user interaction on the canvas.
+    Interaction {
+        /// Interaction type: `touch`, `button_click`, `form_input`, `selection`, `gesture`.
+        interaction_type: String,
+        /// Element ID involved in the interaction (if any).
+        #[serde(default)]
+        element_id: Option<String>,
+        /// Interaction-specific data.
+        data: serde_json::Value,
+        /// Optional message ID for acknowledgment.
+        #[serde(default)]
+        message_id: Option<String>,
+    },
+}
+
+/// Server-to-client WebSocket message types.
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerMessage {
+    /// Welcome message on connection.
+    Welcome {
+        /// Server version.
+        version: String,
+        /// Assigned session ID.
+        session_id: String,
+        /// Connection timestamp.
+        timestamp: u64,
+        /// Whether legacy (browser-native) signaling is enabled.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        legacy_signaling: Option<bool>,
+        /// Whether this server is the primary or a read replica.
+        #[serde(default)]
+        consistency: Consistency,
+    },
+    /// Full scene state update.
+    SceneUpdate {
+        /// Canonical scene document.
+        scene: SceneDocument,
+    },
+    /// Incremental scene update relative to the previously broadcast scene.
+    ScenePatch {
+        /// Element operations to apply.
+        patch: ScenePatch,
+    },
+    /// Follow-up elements of a paginated initial scene load.
+    ScenePage {
+        /// The next page of elements.
+        page: ScenePage,
+    },
+    /// Single element added to scene.
+    ElementAdded {
+        /// The added element.
+        element: ElementDocument,
+        /// Event timestamp.
+        timestamp: u64,
+    },
+    /// Single element updated.
+    ElementUpdated {
+        /// The updated element data.
+        element: ElementDocument,
+        /// Event timestamp.
+        timestamp: u64,
+    },
+    /// Single element removed from scene.
+    ElementRemoved {
+        /// ID of removed element.
+        id: String,
+        /// Event timestamp.
+        timestamp: u64,
+    },
+    /// Acknowledgment of a client message.
+    Ack {
+        /// The message ID being acknowledged.
+        message_id: String,
+        /// Whether the operation succeeded.
+        success: bool,
+        /// Optional result data.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        result: Option<serde_json::Value>,
+    },
+    /// Error response.
+    Error {
+        /// Error code.
+        code: String,
+        /// Human-readable error message.
+        message: String,
+        /// Related message ID if applicable.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        message_id: Option<String>,
+    },
+    /// Pong response to ping.
+    Pong {
+        /// Response timestamp.
+        timestamp: u64,
+    },
+    /// Sync result after processing queued operations.
+    SyncResult {
+        /// Number of operations synced.
+        synced_count: usize,
+        /// Number of conflicts encountered.
+        conflict_count: usize,
+        /// Event timestamp.
+        timestamp: u64,
+        /// Details of failed operations (up to 10).
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        failed_operations: Vec<FailedOperationInfo>,
+    },
+    /// Communitas call state update for this session.
+    CallState {
+        /// Session identifier for this call state.
+        session_id: String,
+        /// Active Communitas call ID (if established).
+        #[serde(skip_serializing_if = "Option::is_none")]
+        call_id: Option<String>,
+        /// Active peer IDs participating in the call.
+        participants: Vec<String>,
+    },
+    /// Result of a Communitas call operation.
+    CommunitasCallResult {
+        /// The operation that was performed.
+        operation: String,
+        /// Whether the operation succeeded.
+        success: bool,
+        /// Call ID if available.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        call_id: Option<String>,
+        /// Error message if operation failed.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        error: Option<String>,
+        /// Related message ID for acknowledgment.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        message_id: Option<String>,
+    },
+
+    // === WebRTC Signaling Messages ===
+    /// Incoming call notification.
+    IncomingCall {
+        /// Peer ID initiating the call.
+        from_peer_id: String,
+        /// Session ID for the call.
+        session_id: String,
+    },
+    /// Relay SDP offer to target peer.
+    RelayOffer {
+        /// Peer ID sending the offer.
+        from_peer_id: String,
+        /// SDP offer string.
+        sdp: String,
+    },
+    /// Relay SDP answer to target peer.
+    RelayAnswer {
+        /// Peer ID sending the answer.
+        from_peer_id: String,
+        /// SDP answer string.
+        sdp: String,
+    },
+    /// Relay ICE candidate to target peer.
+    RelayIceCandidate {
+        /// Peer ID sending the candidate.
+        from_peer_id: String,
+        /// ICE candidate string.
+        candidate: String,
+        /// SDP media ID.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        sdp_mid: Option<String>,
+        /// SDP media line index.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        sdp_m_line_index: Option<u16>,
+    },
+    /// Call ended notification.
+    CallEnded {
+        /// Peer ID that ended the call.
+        from_peer_id: String,
+        /// Reason for call ending.
+        reason: String,
+    },
+    /// Your assigned peer ID (sent on connection).
+    PeerAssigned {
+        /// Assigned unique peer ID.
+        peer_id: String,
+    },
+    /// The session presenter's current view.
+    ViewState {
+        /// Peer ID of the presenter.
+        presenter_id: String,
+        /// The presenter's view.
+        view: ViewState,
+    },
+    /// The session presenter stopped presenting.
+    PresenterStopped {
+        /// Peer ID of the former presenter.
+        presenter_id: String,
+    },
+    /// This client's remaining quota, sent on request and when a budget
+    /// runs out.
+    QuotaStatus {
+        /// Budgets of the client's identity.
+        quota: QuotaStatus,
+    },
+}
+
+/// A queued operation from offline queue.
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum QueuedOperation {
+    /// Add element operation.
+    Add {
+        /// Element to add.
+        element: ElementDocument,
+        /// Operation timestamp.
+        timestamp: u64,
+    },
+    /// Update element operation.
+    Update {
+        /// Element ID.
+        id: String,
+        /// Changes to apply.
+        changes: serde_json::Value,
+        /// Operation timestamp.
+        timestamp: u64,
+    },
+    /// Remove element operation.
+    Remove {
+        /// Element ID.
+        id: String,
+        /// Operation timestamp.
+        timestamp: u64,
+    },
+}
+
+impl QueuedOperation {
+    /// Get the type of this operation.
+    #[must_use]
+    pub fn operation_type(&self) -> OperationType {
+        match self {
+            Self::Add { .. } => OperationType::Add,
+            Self::Update { .. } => OperationType::Update,
+            Self::Remove { .. } => OperationType::Remove,
+        }
+    }
+
+    /// Get the element ID affected by this operation (if any).
+    #[must_use]
+    pub fn element_id(&self) -> Option<&str> {
+        match self {
+            Self::Add { element, .. } => Some(&element.id),
+            Self::Update { id, .. } | Self::Remove { id, .. } => Some(id),
+        }
+    }
+}
+
+/// One element operation inside a [`ClientMessage::Batch`].
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum BatchOperation {
+    /// Add an element.
+    Add {
+        /// Element to add.
+        element: ElementDocument,
+    },
+    /// Update an element.
+    Update {
+        /// Element ID.
+        id: String,
+        /// Changes to apply (partial element data).
+        changes: serde_json::Value,
+    },
+    /// Remove an element.
+    Remove {
+        /// Element ID.
+        id: String,
+    },
+}
+
+/// How current a server's scenes are, advertised in
+/// [`ServerMessage::Welcome`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+#[serde(rename_all = "snake_case")]
+pub enum Consistency {
+    /// The primary: reads see every acknowledged write.
+    #[default]
+    Strong,
+    /// A read replica: scenes trail the primary and writes are rejected.
+    Eventual,
+}
+
+/// Budgets of a client identity (signed-in user, agent or connection)
+/// on a shared server.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+pub struct QuotaStatus {
+    /// Identity the budgets belong to.
+    pub identity: String,
+    /// Messages (and MCP requests).
+    pub messages: QuotaBudget,
+    /// Elements added, if limited.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub elements: Option<QuotaBudget>,
+    /// Export jobs, if limited.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exports: Option<QuotaBudget>,
+}
+
+/// One budget of a [`QuotaStatus`]: `limit` per `window_secs`, refilled
+/// continuously.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+pub struct QuotaBudget {
+    /// Units allowed per window.
+    pub limit: u32,
+    /// Window length in seconds.
+    pub window_secs: u32,
+    /// Units that may be used right now.
+    pub remaining: u32,
+    /// Milliseconds until the budget is full again.
+    pub reset_ms: u64,
+}
+
+/// Type of sync operation.
+///
+/// Provides type-safe representation of operation types rather than using strings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+#[serde(rename_all = "snake_case")]
+pub enum OperationType {
+    /// Add a new element.
+    Add,
+    /// Update an existing element.
+    Update,
+    /// Remove an element.
+    Remove,
+}
+
+impl std::fmt::Display for OperationType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Add => write!(f, "add"),
+            Self::Update => write!(f, "update"),
+            Self::Remove => write!(f, "remove"),
+        }
+    }
+}
+
+/// Information about a failed sync operation sent to clients.
+///
+/// This struct provides minimal but useful details about operations
+/// that failed during queue processing, enabling client-side reconciliation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+pub struct FailedOperationInfo {
+    /// Type of operation that failed.
+    pub operation: OperationType,
+    /// Element ID involved in the failed operation, if available.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub element_id: Option<String>,
+    /// Human-readable error message.
+    pub error: String,
+}
+
+impl FailedOperationInfo {
+    /// Maximum number of failed operations to include in a sync result.
+    pub const MAX_FAILURES_IN_RESPONSE: usize = 10;
+
+    /// Create from a failed operation and its error message.
+    #[must_use]
+    pub fn from_failed_op(op: &QueuedOperation, error: &str) -> Self {
+        Self {
+            operation: op.operation_type(),
+            element_id: op.element_id().map(String::from),
+            error: error.to_string(),
+        }
+    }
+}
+
+fn default_session() -> String {
+    "default".to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_messages_round_trip_through_json() {
+        let camera = ClientMessage::Camera {
+            command: CameraCommand::FitToContent,
+            options: FitOptions::default(),
+            message_id: Some("m1".to_string()),
+        };
+        let json = serde_json::to_value(&camera).expect("serialize");
+        assert_eq!(json["type"], "camera");
+        assert_eq!(json["command"], "fit_to_content");
+        let back: ClientMessage = serde_json::from_value(json).expect("deserialize");
+        assert!(
+            matches!(back, ClientMessage::Camera { message_id: Some(ref id), .. } if id == "m1")
+        );
+
+        let subscribe: ClientMessage =
+            serde_json::from_str(r#"{"type":"subscribe"}"#).expect("subscribe");
+        assert!(
+            matches!(subscribe, ClientMessage::Subscribe { session_id } if session_id == "default")
+        );
+
+        let result = ServerMessage::SyncResult {
+            synced_count: 2,
+            conflict_count: 0,
+            timestamp: 1,
+            failed_operations: Vec::new(),
+        };
+        let text = serde_json::to_string(&result).expect("serialize");
+        assert!(!text.contains("failed_operations"));
+        let back: ServerMessage = serde_json::from_str(&text).expect("deserialize");
+        assert!(matches!(
+            back,
+            ServerMessage::SyncResult {
+                synced_count: 2,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_welcome_consistency_defaults_to_strong() {
+        let welcome: ServerMessage = serde_json::from_str(
+            r#"{"type":"welcome","version":"0.1.0","session_id":"default","timestamp":1}"#,
+        )
+        .expect("welcome from an older server");
+        assert!(matches!(
+            welcome,
+            ServerMessage::Welcome {
+                consistency: Consistency::Strong,
+                ..
+            }
+        ));
+
+        let replica = ServerMessage::Welcome {
+            version: "0.2.0".to_string(),
+            session_id: "default".to_string(),
+            timestamp: 1,
+            legacy_signaling: None,
+            consistency: Consistency::Eventual,
+        };
+        let json = serde_json::to_value(&replica).expect("serialize");
+        assert_eq!(json["consistency"], "eventual");
+    }
+}
@@ -0,0 +1,399 @@
+//! # Randomizers
+//!
+//! Dice, wheel spinners and shuffled lists for `Randomizer` elements.
+//!
+//! Outcomes are drawn by the server, never by a client, so every participant
+//! sees the same roll. [`randomize`] takes the random index source as a
+//! closure; the server passes its RNG and tests pass a fixed sequence.
+//!
+//! ```text
+//! ┌─────────────────────┐  ┌─────────────────────┐  ┌─────────────────────┐
+//! │   ┌───┐   ┌───┐     │  │        ▼            │  │ 1. Bea              │
+//! │   │ 4 │   │ 2 │     │  │      ╱───╲          │  │ 2. Ada              │
+//! │   └───┘   └───┘     │  │     │ ╲ ╱ │         │  │ 3. Cy               │
+//! ├─────────────────────┤  │      ╲───╱          │  ├─────────────────────┤
+//! │      4 + 2 = 6      │  ├─────────────────────┤  │    Bea, Ada, Cy     │
+//! └─────────────────────┘  │        Pizza        │  └─────────────────────┘
+//!          dice            └─────────────────────┘         shuffle
+//!                                   wheel
+//! ```
+//!
+//! [`RandomizerLayout`] computes the body and caption in the element's local
+//! frame (origin at its unrotated top-left corner) for every renderer.
+
+use std::f32::consts::{FRAC_PI_2, TAU};
+
+use serde::{Deserialize, Serialize};
+
+use crate::element::ElementKind;
+use crate::error::{CanvasError, CanvasResult};
+
+/// Most dice in one roll.
+pub const MAX_DICE: u32 = 10;
+
+/// Most faces on a die.
+pub const MAX_DIE_SIDES: u32 = 100;
+
+/// Most wheel segments or list items.
+pub const MAX_RANDOM_ITEMS: usize = 24;
+
+/// Wheel segment colors, repeated around the wheel.
+pub const WHEEL_COLORS: [&str; 6] = [
+    "#4db6ac", "#7986cb", "#ffb74d", "#e57373", "#81c784", "#ba68c8",
+];
+
+/// What a randomizer draws from.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+#[serde(tag = "mode", rename_all = "lowercase")]
+pub enum RandomSource {
+    /// Roll `count` dice with `sides` faces each.
+    Dice {
+        /// Number of dice.
+        #[serde(default = "RandomSource::default_dice_count")]
+        count: u32,
+        /// Faces per die.
+        #[serde(default = "RandomSource::default_die_sides")]
+        sides: u32,
+    },
+    /// Spin a wheel and land on one segment.
+    Wheel {
+        /// Segment labels, clockwise from the top.
+        segments: Vec<String>,
+    },
+    /// Shuffle a list into a random order.
+    Shuffle {
+        /// Items in their original order.
+        items: Vec<String>,
+    },
+}
+
+impl RandomSource {
+    /// Default number of dice.
+    #[must_use]
+    pub fn default_dice_count() -> u32 {
+        1
+    }
+
+    /// Default faces per die.
+    #[must_use]
+    pub fn default_die_sides() -> u32 {
+        6
+    }
+
+    /// Mode name as serialized, e.g. `"dice"`.
+    #[must_use]
+    pub fn mode(&self) -> &'static str {
+        match self {
+            Self::Dice { .. } => "dice",
+            Self::Wheel { .. } => "wheel",
+            Self::Shuffle { .. } => "shuffle",
+        }
+    }
+
+    /// Check the source can be drawn from.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CanvasError::InvalidOperation`] for dice outside
+    /// 1..=[`MAX_DICE`] dice with 2..=[`MAX_DIE_SIDES`] sides, or a wheel or
+    /// list without 2..=[`MAX_RANDOM_ITEMS`] entries.
+    pub fn validate(&self) -> CanvasResult<()> {
+        match self {
+            Self::Dice { count, sides }
+                if !(1..=MAX_DICE).contains(count) || !(2..=MAX_DIE_SIDES).contains(sides) =>
+            {
+                Err(CanvasError::InvalidOperation(format!(
+                    "Dice need 1 to {MAX_DICE} dice with 2 to {MAX_DIE_SIDES} sides, got {count}d{sides}"
+                )))
+            }
+            Self::Wheel { segments: entries } | Self::Shuffle { items: entries }
+                if !(2..=MAX_RANDOM_ITEMS).contains(&entries.len()) =>
+            {
+                Err(CanvasError::InvalidOperation(format!(
+                    "A {} needs 2 to {MAX_RANDOM_ITEMS} entries, got {}",
+                    self.mode(),
+                    entries.len()
+                )))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Draw an outcome. `pick(n)` must return a uniformly random index in
+    /// `0..n`.
+    ///
+    /// Dice give one face (1-based) per die, a wheel gives its segment index
+    /// and a shuffle gives the item indices in their new order.
+    pub fn draw(&self, pick: &mut dyn FnMut(usize) -> usize) -> Vec<usize> {
+        match self {
+            Self::Dice { count, sides } => (0..*count).map(|_| pick(*sides as usize) + 1).collect(),
+            Self::Wheel { segments } => vec![pick(segments.len())],
+            Self::Shuffle { items } => {
+                // Fisher-Yates
+                let mut order: Vec<usize> = (0..items.len()).collect();
+                for i in (1..order.len()).rev() {
+                    order.swap(i, pick(i + 1));
+                }
+                order
+            }
+        }
+    }
+
+    /// Human-readable result, e.g. `"4 + 2 = 6"`, `"Pizza"` or
+    /// `"Bea, Ada, Cy"`.
+    #[must_use]
+    pub fn describe(&self, values: &[usize]) -> String {
+        let label = |entries: &[String], i: &usize| entries.get(*i).cloned().unwrap_or_default();
+        match self {
+            Self::Dice { .. } if values.len() > 1 => {
+                let faces: Vec<String> = values.iter().map(ToString::to_string).collect();
+                format!("{} = {}", faces.join(" + "), values.iter().sum::<usize>())
+            }
+            Self::Dice { .. } => values.first().map(ToString::to_string).unwrap_or_default(),
+            Self::Wheel { segments } => values
+                .first()
+                .map(|i| label(segments, i))
+                .unwrap_or_default(),
+            Self::Shuffle { items } => values
+                .iter()
+                .map(|i| label(items, i))
+                .collect::<Vec<_>>()
+                .join(", "),
+        }
+    }
+
+    /// Caption shown under the randomizer: the result of `outcome`, or a
+    /// prompt before the first draw.
+    #[must_use]
+    pub fn caption(&self, outcome: Option<&RandomOutcome>) -> String {
+        match (outcome, self) {
+            (Some(outcome), _) => self.describe(&outcome.values),
+            (None, Self::Dice { .. }) => "Tap to roll".to_string(),
+            (None, Self::Wheel { .. }) => "Tap to spin".to_string(),
+            (None, Self::Shuffle { .. }) => "Tap to shuffle".to_string(),
+        }
+    }
+
+    /// Order to list a shuffle's items in: the drawn order, or the original
+    /// order before the first draw.
+    #[must_use]
+    pub fn list_order(&self, outcome: Option<&RandomOutcome>) -> Vec<usize> {
+        let Self::Shuffle { items } = self else {
+            return Vec::new();
+        };
+        match outcome {
+            Some(outcome) if outcome.values.iter().all(|&i| i < items.len()) => {
+                outcome.values.clone()
+            }
+            _ => (0..items.len()).collect(),
+        }
+    }
+}
+
+/// The latest draw of a randomizer.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+pub struct RandomOutcome {
+    /// Dice faces, the wheel segment index, or the shuffled item order (see
+    /// [`RandomSource::draw`]).
+    pub values: Vec<usize>,
+    /// Counts draws, so repeated identical results can be told apart.
+    pub draw: u64,
+    /// Peer or agent that triggered the draw.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub by: Option<String>,
+}
+
+/// Draw a new outcome for a randomizer element and store it.
+///
+/// # Errors
+///
+/// Returns [`CanvasError::InvalidOperation`] if `kind` is not a randomizer
+/// or its source is invalid (see [`RandomSource::validate`]).
+pub fn randomize(
+    kind: &mut ElementKind,
+    by: &str,
+    pick: &mut dyn FnMut(usize) -> usize,
+) -> CanvasResult<RandomOutcome> {
+    let ElementKind::Randomizer { source, outcome } = kind else {
+        return Err(CanvasError::InvalidOperation(format!(
+            "{} is not a randomizer",
+            kind.type_name()
+        )));
+    };
+    source.validate()?;
+    let next = RandomOutcome {
+        values: source.draw(pick),
+        draw: outcome.as_ref().map_or(0, |o| o.draw) + 1,
+        by: Some(by.to_string()),
+    };
+    *outcome = Some(next.clone());
+    Ok(next)
+}
+
+/// Geometry of a randomizer element: a body showing the dice, wheel or list
+/// above a caption with the result.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RandomizerLayout {
+    /// Body left edge.
+    pub body_x: f32,
+    /// Body top edge.
+    pub body_y: f32,
+    /// Body width.
+    pub body_width: f32,
+    /// Body height.
+    pub body_height: f32,
+    /// Top of the caption band.
+    pub caption_y: f32,
+    /// Height of the caption band.
+    pub caption_height: f32,
+}
+
+impl RandomizerLayout {
+    /// Space around the body.
+    pub const PADDING: f32 = 8.0;
+
+    /// Lay out a `width` × `height` randomizer.
+    #[must_use]
+    pub fn new(width: f32, height: f32) -> Self {
+        let caption_height = (height * 0.18).clamp(16.0, 32.0).min(height / 3.0);
+        let pad = Self::PADDING;
+        Self {
+            body_x: pad,
+            body_y: pad,
+            body_width: (width - 2.0 * pad).max(0.0),
+            body_height: (height - caption_height - 2.0 * pad).max(0.0),
+            caption_y: height - caption_height,
+            caption_height,
+        }
+    }
+
+    /// Squares for `count` dice as `(x, y, size)`, centered in the body.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn dice(&self, count: u32) -> Vec<(f32, f32, f32)> {
+        let n = count.max(1) as f32;
+        // Gaps are a fifth of a die
+        let size = self
+            .body_height
+            .min(self.body_width / (n + 0.2 * (n - 1.0)));
+        let gap = size * 0.2;
+        let left = self.body_x + (self.body_width - (n * size + (n - 1.0) * gap)) / 2.0;
+        let top = self.body_y + (self.body_height - size) / 2.0;
+        (0..count)
+            .map(|i| (left + i as f32 * (size + gap), top, size))
+            .collect()
+    }
+
+    /// Wheel center and radius.
+    #[must_use]
+    pub fn wheel(&self) -> (f32, f32, f32) {
+        (
+            self.body_x + self.body_width / 2.0,
+            self.body_y + self.body_height / 2.0,
+            self.body_width.min(self.body_height) / 2.0,
+        )
+    }
+
+    /// Start and end angle (radians, clockwise from the positive x axis) of
+    /// segment `index` of `count`. The wheel turns so the `selected`
+    /// segment sits under the pointer at the top.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn segment_arc(index: usize, count: usize, selected: Option<usize>) -> (f32, f32) {
+        let step = TAU / count.max(1) as f32;
+        let offset = selected.map_or(0.0, |s| (s as f32 + 0.5) * step);
+        let start = -FRAC_PI_2 - offset + index as f32 * step;
+        (start, start + step)
+    }
+
+    /// Rows for `count` list items as `(y, height)`.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn rows(&self, count: usize) -> Vec<(f32, f32)> {
+        let height = self.body_height / count.max(1) as f32;
+        (0..count)
+            .map(|i| (self.body_y + i as f32 * height, height))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A pick function replaying `values`.
+    fn replay(values: &[usize]) -> impl FnMut(usize) -> usize + '_ {
+        let mut next = values.iter();
+        move |n| next.next().copied().unwrap_or(0) % n
+    }
+
+    #[test]
+    fn test_draws() {
+        let dice = RandomSource::Dice { count: 2, sides: 6 };
+        let values = dice.draw(&mut replay(&[3, 1]));
+        assert_eq!(values, vec![4, 2]);
+        assert_eq!(dice.describe(&values), "4 + 2 = 6");
+
+        let wheel = RandomSource::Wheel {
+            segments: vec!["Pizza".to_string(), "Tacos".to_string()],
+        };
+        assert_eq!(wheel.describe(&wheel.draw(&mut replay(&[1]))), "Tacos");
+
+        let list = RandomSource::Shuffle {
+            items: vec!["Ada".to_string(), "Bea".to_string(), "Cy".to_string()],
+        };
+        let order = list.draw(&mut replay(&[0, 0]));
+        let mut sorted = order.clone();
+        sorted.sort_unstable();
+        assert_eq!(sorted, vec![0, 1, 2]);
+        assert_eq!(list.describe(&order), "Bea, Cy, Ada");
+    }
+
+    #[test]
+    fn test_randomize_counts_draws() {
+        let mut kind = ElementKind::Randomizer {
+            source: RandomSource::Dice {
+                count: 1,
+                sides: 20,
+            },
+            outcome: None,
+        };
+        let first = randomize(&mut kind, "peer-a", &mut replay(&[19])).expect("roll");
+        assert_eq!(first.values, vec![20]);
+        let second = randomize(&mut kind, "peer-b", &mut replay(&[19])).expect("roll");
+        assert_eq!(second.draw, 2);
+        assert_eq!(second.by.as_deref(), Some("peer-b"));
+
+        let mut bad = ElementKind::Randomizer {
+            source: RandomSource::Wheel {
+                segments: vec!["Only".to_string()],
+            },
+            outcome: None,
+        };
+        assert!(randomize(&mut bad, "peer-a", &mut replay(&[0])).is_err());
+    }
+
+    #[test]
+    fn test_source_json() {
+        let source: RandomSource =
+            serde_json::from_value(serde_json::json!({ "mode": "dice" })).expect("dice");
+        assert_eq!(source, RandomSource::Dice { count: 1, sides: 6 });
+    }
+
+    #[test]
+    fn test_layout() {
+        let layout = RandomizerLayout::new(220.0, 120.0);
+        let dice = layout.dice(2);
+        assert_eq!(dice.len(), 2);
+        // Both dice fit inside the body
+        let (x, _, size) = dice[1];
+        assert!(x + size <= layout.body_x + layout.body_width + 1e-3);
+        assert!((layout.caption_y + layout.caption_height - 120.0).abs() < f32::EPSILON);
+
+        // The selected segment is centered on the pointer
+        let (start, end) = RandomizerLayout::segment_arc(2, 4, Some(2));
+        assert!((f32::midpoint(start, end) + FRAC_PI_2).abs() < 1e-5);
+    }
+}
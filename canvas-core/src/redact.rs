@@ -0,0 +1,160 @@
+//! # Redaction
+//!
+//! Lets internal canvases be shared outside the team: elements marked
+//! [`sensitive`](crate::Element::sensitive), and everything inside them,
+//! are hidden from exports and scene documents that ask for it.
+//!
+//! ```text
+//! ┌ card (sensitive) ┐        ┌──────────────────┐
+//! │ Q3 revenue $4.2M │  ───▶  │░░░░ blurred ░░░░░│  Redaction::Blur
+//! │ [chart]          │        │░░░░░░░░░░░░░░░░░░│
+//! └──────────────────┘        └──────────────────┘
+//!                             ┌──────────────────┐
+//!                       ───▶  │     Redacted     │  Redaction::Placeholder
+//!                             └──────────────────┘
+//! ```
+//!
+//! [`Scene::redacted`](crate::Scene::redacted) replaces each sensitive
+//! subtree with a [`placeholder`], dropping its content, interactions,
+//! extracted text and the datasets only it was bound to, so nothing can be
+//! read back out of the copy. Exporters blur instead by rasterizing the
+//! subtree and embedding only the blurred pixels.
+
+use serde::{Deserialize, Serialize};
+
+use crate::color::{Color, ThemeColor};
+use crate::element::{Element, ElementKind};
+use crate::shape::ShapeKind;
+use crate::style::{Fill, Style};
+
+/// Fill of a redaction placeholder.
+pub const PLACEHOLDER_COLOR: Color = Color::rgba(0x9e, 0x9e, 0x9e, 0xff);
+
+/// How sensitive elements appear in redacted output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+#[serde(rename_all = "snake_case")]
+pub enum Redaction {
+    /// Drawn heavily blurred: layout and color survive, content does not.
+    Blur,
+    /// Replaced with a plain gray box.
+    Placeholder,
+}
+
+/// The placeholder standing in for a sensitive `element`: a gray box with
+/// the same ID, position and parent, and nothing of its content.
+#[must_use]
+pub fn placeholder(element: &Element) -> Element {
+    let mut placeholder = Element::new(ElementKind::Shape {
+        shape: ShapeKind::Rectangle,
+    })
+    .with_transform(element.transform)
+    .with_visible(element.visible)
+    .with_opacity(element.opacity)
+    .with_screen_anchored(element.screen_anchored)
+    .with_sensitive(true)
+    .with_interactive(false)
+    .with_style(Style {
+        fill: Some(Fill::Solid {
+            color: ThemeColor::from(PLACEHOLDER_COLOR),
+        }),
+        corner_radius: element.style.corner_radius,
+        ..Style::default()
+    });
+    placeholder.id = element.id;
+    placeholder.parent = element.parent;
+    placeholder.attachment = element.attachment;
+    placeholder
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::{DataBinding, Dataset, DatasetDelta};
+    use crate::element::Transform;
+    use crate::ocr::OcrText;
+    use crate::scene::Scene;
+    use crate::schema::SceneDocument;
+
+    fn text(content: &str) -> Element {
+        Element::new(ElementKind::Text {
+            content: content.to_string(),
+            font_size: 16.0,
+            color: Color::BLACK.into(),
+        })
+        .with_transform(Transform {
+            x: 10.0,
+            y: 10.0,
+            width: 200.0,
+            height: 20.0,
+            ..Transform::default()
+        })
+    }
+
+    #[test]
+    fn test_redacted_scene_keeps_nothing_of_sensitive_subtrees() {
+        let mut scene = Scene::new(800.0, 600.0);
+        scene
+            .apply_data_delta(&DatasetDelta::Put {
+                dataset: Dataset::new("salaries", vec!["name".into(), "pay".into()], Vec::new()),
+            })
+            .expect("dataset");
+        let public = scene.add_element(text("Roadmap"));
+        let secret = scene.add_element(text("Acquiring Initech"));
+        let chart = scene.add_element(Element::new(ElementKind::Chart {
+            chart_type: "bar".to_string(),
+            data: serde_json::json!({}),
+            binding: Some(DataBinding::new("salaries")),
+        }));
+        let group = scene.add_element(
+            Element::new(ElementKind::Group {
+                children: vec![secret, chart],
+                clip: false,
+                opacity: 1.0,
+            })
+            .with_sensitive(true),
+        );
+        for id in [secret, chart] {
+            scene.get_element_mut(id).expect("child").parent = Some(group);
+        }
+        let mut photo = Element::new(ElementKind::Image {
+            src: "data:image/png;base64,AAAA".to_string(),
+            format: crate::element::ImageFormat::Png,
+            annotations: Vec::new(),
+            extracted_text: None,
+        })
+        .with_sensitive(true);
+        photo.transform.width = 100.0;
+        let photo = scene.add_element(photo);
+        scene
+            .set_extracted_text(
+                photo,
+                OcrText {
+                    text: "PASSWORD hunter2".to_string(),
+                    engine: "test".to_string(),
+                    blocks: Vec::new(),
+                },
+            )
+            .expect("ocr");
+
+        assert!(scene.is_sensitive(scene.get_element(secret).expect("secret")));
+        assert!(!scene.is_sensitive(scene.get_element(public).expect("public")));
+
+        let redacted = scene.redacted();
+        let json =
+            serde_json::to_string(&SceneDocument::from_scene("s", &redacted, 1)).expect("json");
+        for leak in ["Initech", "hunter2", "base64", "salaries"] {
+            assert!(!json.contains(leak), "{leak} leaked");
+        }
+        assert!(json.contains("Roadmap"));
+        assert!(redacted.get_element(secret).is_none());
+        assert!(redacted.get_element(chart).is_none());
+        for id in [group, photo] {
+            let element = redacted.get_element(id).expect("placeholder");
+            assert!(matches!(element.kind, ElementKind::Shape { .. }));
+            assert!(element.sensitive);
+        }
+        // The original is untouched
+        assert_eq!(scene.element_count(), 5);
+    }
+}
@@ -1,12 +1,47 @@
 //! Scene graph for managing canvas elements.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use serde::{Deserialize, Serialize};
 
-use crate::{CanvasError, CanvasResult, Element, ElementId};
+use crate::anchor::Anchor;
+use crate::annotation::{self, MeasurementKind, MeasurementLayout};
+use crate::attachment::Attachment;
+use crate::branding::Branding;
+use crate::camera::{CameraCommand, FitOptions, SceneBounds};
+use crate::changes::{ChangeLog, SceneChanges};
+use crate::color::{Theme, ThemeColor};
+use crate::data::{self, DataStore, DatasetDelta};
+use crate::diagnostics::{self, SceneIssue, SceneStats};
+use crate::element::{CalloutTarget, ElementKind};
+use crate::follow::CameraFrame;
+use crate::geometry::{self, OrientedRect};
+use crate::ink::{self, EraseMode, InkEdit};
+use crate::lint::{self, LintConfig, LintFinding};
+use crate::log_view;
+use crate::merge::{CollisionPolicy, MergeOptions, MergeReport};
+use crate::ocr::OcrText;
+use crate::redact;
+use crate::schema::{PatchOp, ScenePage, ScenePatch, ViewportDocument};
+use crate::spatial::SpatialIndex;
+use crate::spotlight::Spotlight;
+use crate::template::Template;
+use crate::transition::{TransitionConfig, TransitionFrame, Transitions};
+use crate::units::{CoordinateSystem, LineMeasurement};
+use crate::{CanvasError, CanvasResult, Element, ElementId, Transform};
 
 /// A scene containing all canvas elements.
+///
+/// Element bounds are kept in a [`SpatialIndex`] for hit testing and
+/// culling. Elements handed out through [`Scene::get_element_mut`] are
+/// treated as stale until the next mutation re-indexes them; queries check
+/// them directly in the meantime.
+///
+/// Every mutation bumps the scene's [`revision`](Scene::revision), and
+/// [`Scene::changes_since`] reports which elements and settings changed
+/// after a given revision. Handing out an element mutably counts as
+/// changing it. Direct writes to the public viewport fields are not
+/// tracked; use [`Scene::set_viewport`] and [`Scene::set_camera`].
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Scene {
     /// All elements in the scene, indexed by ID.
@@ -25,6 +60,37 @@ pub struct Scene {
     pub pan_x: f32,
     /// Pan offset Y.
     pub pan_y: f32,
+    /// Active spotlight, if any.
+    #[serde(default)]
+    spotlight: Option<Spotlight>,
+    /// Named views, in the order they were first saved.
+    #[serde(default)]
+    anchors: Vec<Anchor>,
+    /// Named colors and palettes that elements can reference.
+    #[serde(default)]
+    theme: Theme,
+    /// Logo and watermark drawn over the canvas, if any.
+    #[serde(default)]
+    branding: Option<Branding>,
+    /// World units and origin that canvas pixels stand for.
+    #[serde(default)]
+    coordinates: CoordinateSystem,
+    /// Datasets that charts can be bound to.
+    #[serde(default)]
+    data: DataStore,
+    /// Spatial index over element bounds.
+    #[serde(skip)]
+    index: SpatialIndex,
+    /// Elements whose indexed bounds may be out of date; `None` when the
+    /// whole index needs rebuilding (e.g. after deserializing).
+    #[serde(skip)]
+    stale: Option<HashSet<ElementId>>,
+    /// Revision stamps for change tracking.
+    #[serde(skip, default = "ChangeLog::restored")]
+    changes: ChangeLog,
+    /// Enter and exit transitions of synced elements.
+    #[serde(skip)]
+    transitions: Transitions,
 }
 
 impl Scene {
@@ -40,16 +106,79 @@ impl Scene {
             zoom: 1.0,
             pan_x: 0.0,
             pan_y: 0.0,
+            spotlight: None,
+            anchors: Vec::new(),
+            theme: Theme::default(),
+            branding: None,
+            coordinates: CoordinateSystem::default(),
+            data: DataStore::default(),
+            index: SpatialIndex::new(),
+            stale: Some(HashSet::new()),
+            changes: ChangeLog::default(),
+            transitions: Transitions::default(),
         }
     }
 
+    /// Bring the spatial index up to date with every element's transform.
+    ///
+    /// Mutations do this incrementally; call it after editing elements
+    /// through [`Scene::get_element_mut`] or [`Scene::elements_mut`] to make
+    /// the next queries fully indexed.
+    pub fn refresh_index(&mut self) {
+        if let Some(stale) = self.stale.take() {
+            for id in stale {
+                if let Some(element) = self.elements.get(&id) {
+                    self.index
+                        .insert(id, SceneBounds::from_transform(&element.transform));
+                }
+            }
+        } else {
+            self.index.clear();
+            for (&id, element) in &self.elements {
+                self.index
+                    .insert(id, SceneBounds::from_transform(&element.transform));
+            }
+        }
+        self.stale = Some(HashSet::new());
+    }
+
+    /// Elements that may overlap `bounds`: indexed hits plus stale elements.
+    ///
+    /// Screen-anchored elements are indexed by their viewport-pixel
+    /// transforms like any other, so callers split the result by
+    /// [`Element::screen_anchored`] according to the space `bounds` is in.
+    fn candidates(&self, bounds: &SceneBounds) -> Vec<&Element> {
+        let Some(stale) = &self.stale else {
+            return self.elements.values().collect();
+        };
+        self.index
+            .query(bounds)
+            .into_iter()
+            .filter(|id| !stale.contains(id))
+            .chain(stale.iter().copied())
+            .filter_map(|id| self.elements.get(&id))
+            .collect()
+    }
+
     /// Add an element to the scene.
-    pub fn add_element(&mut self, element: Element) -> ElementId {
+    ///
+    /// Charts bound to a dataset are filled in from it, and an attached
+    /// element moves to where its target puts it.
+    pub fn add_element(&mut self, mut element: Element) -> ElementId {
+        data::materialize(&mut element.kind, &self.data);
         let id = element.id;
         if element.parent.is_none() {
             self.root_elements.push(id);
         }
+        self.refresh_index();
+        self.index
+            .insert(id, SceneBounds::from_transform(&element.transform));
+        let attached = element.attachment.is_some();
         self.elements.insert(id, element);
+        self.changes.touch(id);
+        if attached {
+            self.settle(&[id]);
+        }
         id
     }
 
@@ -61,9 +190,23 @@ impl Scene {
     pub fn remove_element(&mut self, id: &ElementId) -> CanvasResult<Element> {
         self.root_elements.retain(|&eid| eid != *id);
         self.selected.retain(|&eid| eid != *id);
-        self.elements
+        if let Some(spotlight) = &mut self.spotlight {
+            if spotlight.element_ids.contains(id) {
+                spotlight.element_ids.retain(|&eid| eid != *id);
+                if spotlight.element_ids.is_empty() {
+                    self.spotlight = None;
+                }
+                self.changes.touch_settings();
+            }
+        }
+        self.refresh_index();
+        self.index.remove(*id);
+        let element = self
+            .elements
             .remove(id)
-            .ok_or_else(|| CanvasError::ElementNotFound(id.to_string()))
+            .ok_or_else(|| CanvasError::ElementNotFound(id.to_string()))?;
+        self.changes.remove(*id);
+        Ok(element)
     }
 
     /// Get an element by ID.
@@ -74,9 +217,273 @@ impl Scene {
 
     /// Get a mutable reference to an element by ID.
     pub fn get_element_mut(&mut self, id: ElementId) -> Option<&mut Element> {
+        self.refresh_index();
+        if !self.elements.contains_key(&id) {
+            return None;
+        }
+        if let Some(stale) = &mut self.stale {
+            stale.insert(id);
+        }
+        self.changes.touch(id);
         self.elements.get_mut(&id)
     }
 
+    /// Change an element through `f`.
+    ///
+    /// If `f` moves or resizes an `Image`, the markup pinned to it moves and
+    /// scales with it. Elements attached to the element follow it, and if
+    /// `f` moves an attached element its offset changes so it stays where
+    /// `f` put it. Returns the IDs of the other elements that moved.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the element is not found.
+    pub fn update_element(
+        &mut self,
+        id: ElementId,
+        f: impl FnOnce(&mut Element),
+    ) -> CanvasResult<Vec<ElementId>> {
+        let element = self
+            .get_element_mut(id)
+            .ok_or_else(|| CanvasError::ElementNotFound(id.to_string()))?;
+        let before = element.transform;
+        let attachment = element.attachment;
+        f(element);
+        let after = element.transform;
+        if let Some(attached) = element
+            .attachment
+            .as_mut()
+            .filter(|attached| Some(**attached) == attachment)
+        {
+            attached.offset_x += after.x - before.x;
+            attached.offset_y += after.y - before.y;
+        }
+        let annotations = match &element.kind {
+            ElementKind::Image { annotations, .. } if before != after => annotations.clone(),
+            _ => Vec::new(),
+        };
+        let mut moved = Vec::new();
+        for annotation_id in annotations {
+            if let Some(mark) = self.elements.get_mut(&annotation_id) {
+                annotation::refit(&mut mark.transform, &before, &after);
+                moved.push(annotation_id);
+            }
+        }
+        if let Some(stale) = &mut self.stale {
+            stale.extend(moved.iter().copied());
+        }
+        self.changes.touch_all(moved.iter().copied());
+        let mut starts = moved.clone();
+        starts.push(id);
+        moved.extend(self.settle(&starts).into_iter().filter(|&m| m != id));
+        Ok(moved)
+    }
+
+    /// Position an element relative to another one, or detach it with
+    /// `None`.
+    ///
+    /// The element moves to its attached position straight away. Returns
+    /// the IDs of every element that moved, including elements attached to
+    /// this one.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if either element is not in the scene or the
+    /// element would end up following itself.
+    pub fn attach(
+        &mut self,
+        id: ElementId,
+        attachment: Option<Attachment>,
+    ) -> CanvasResult<Vec<ElementId>> {
+        if !self.elements.contains_key(&id) {
+            return Err(CanvasError::ElementNotFound(id.to_string()));
+        }
+        if let Some(attachment) = attachment {
+            if !self.elements.contains_key(&attachment.target) {
+                return Err(CanvasError::ElementNotFound(attachment.target.to_string()));
+            }
+            if self.attachment_chain(attachment.target).contains(&id) {
+                return Err(CanvasError::InvalidOperation(format!(
+                    "element {id} cannot be attached to {}, which follows it",
+                    attachment.target
+                )));
+            }
+        }
+        if let Some(element) = self.get_element_mut(id) {
+            element.attachment = attachment;
+        }
+        Ok(self.settle(&[id]))
+    }
+
+    /// Move every attached element to where its target puts it.
+    ///
+    /// Mutations do this for the elements they touch; call it after moving
+    /// elements through [`Scene::get_element_mut`] or
+    /// [`Scene::elements_mut`]. Returns the IDs of the elements that moved.
+    pub fn resolve_attachments(&mut self) -> Vec<ElementId> {
+        // Start from targets that do not follow anything themselves, so
+        // every element is placed after its target
+        let roots: Vec<ElementId> = self
+            .elements
+            .values()
+            .filter_map(|e| e.attachment)
+            .map(|attachment| attachment.target)
+            .filter(|target| {
+                self.elements.get(target).is_some_and(|e| {
+                    e.attachment
+                        .is_none_or(|a| !self.elements.contains_key(&a.target))
+                })
+            })
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+        self.settle(&roots)
+    }
+
+    /// `start` followed by the targets it is attached to, transitively.
+    fn attachment_chain(&self, start: ElementId) -> Vec<ElementId> {
+        let mut chain = vec![start];
+        let mut current = start;
+        while let Some(target) = self
+            .elements
+            .get(&current)
+            .and_then(|e| e.attachment)
+            .map(|attachment| attachment.target)
+        {
+            if chain.contains(&target) {
+                break;
+            }
+            chain.push(target);
+            current = target;
+        }
+        chain
+    }
+
+    /// Place `starts` and everything attached to them, directly or through
+    /// other attachments. Returns the IDs of the elements that moved.
+    fn settle(&mut self, starts: &[ElementId]) -> Vec<ElementId> {
+        let mut dependents: HashMap<ElementId, Vec<ElementId>> = HashMap::new();
+        for element in self.elements.values() {
+            if let Some(attachment) = element.attachment {
+                dependents
+                    .entry(attachment.target)
+                    .or_default()
+                    .push(element.id);
+            }
+        }
+
+        let mut moved: Vec<ElementId> = starts
+            .iter()
+            .copied()
+            .filter(|&id| self.place(id))
+            .collect();
+        let mut seen: HashSet<ElementId> = starts.iter().copied().collect();
+        let mut queue = starts.to_vec();
+        while let Some(target) = queue.pop() {
+            for &id in dependents.get(&target).into_iter().flatten() {
+                if seen.insert(id) {
+                    if self.place(id) {
+                        moved.push(id);
+                    }
+                    queue.push(id);
+                }
+            }
+        }
+
+        if let Some(stale) = &mut self.stale {
+            stale.extend(moved.iter().copied());
+        }
+        self.changes.touch_all(moved.iter().copied());
+        moved
+    }
+
+    /// Move an attached element to where its target puts it. Returns `true`
+    /// if it moved; elements whose target is not in the scene stay put.
+    fn place(&mut self, id: ElementId) -> bool {
+        let Some(element) = self.elements.get(&id) else {
+            return false;
+        };
+        let Some(attachment) = element.attachment else {
+            return false;
+        };
+        let Some(target) = self.elements.get(&attachment.target) else {
+            return false;
+        };
+        let (x, y) = attachment.resolve(&target.transform, &element.transform);
+        let unchanged = (x - element.transform.x).abs() <= f32::EPSILON
+            && (y - element.transform.y).abs() <= f32::EPSILON;
+        if unchanged || !(x.is_finite() && y.is_finite()) {
+            return false;
+        }
+        if let Some(element) = self.elements.get_mut(&id) {
+            element.transform.x = x;
+            element.transform.y = y;
+        }
+        true
+    }
+
+    /// Add `element` as markup pinned to `image`, stacked above it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `image` is not an image in the scene or
+    /// `element` is not markup; the scene is then unchanged.
+    pub fn pin_markup(
+        &mut self,
+        image: ElementId,
+        mut element: Element,
+    ) -> CanvasResult<ElementId> {
+        let image_z = match self.elements.get(&image) {
+            Some(target) if matches!(target.kind, ElementKind::Image { .. }) => {
+                target.transform.z_index
+            }
+            Some(target) => {
+                return Err(CanvasError::InvalidOperation(format!(
+                    "markup can only be pinned to an image, not a {}",
+                    target.kind.type_name()
+                )))
+            }
+            None => return Err(CanvasError::ElementNotFound(image.to_string())),
+        };
+        if !annotation::is_markup(&element.kind) {
+            return Err(CanvasError::InvalidOperation(format!(
+                "a {} cannot be pinned to an image",
+                element.kind.type_name()
+            )));
+        }
+        element.transform.z_index = element.transform.z_index.max(image_z.saturating_add(1));
+        element.parent = None;
+        let id = self.add_element(element);
+        self.reparent(id, image)?;
+        Ok(id)
+    }
+
+    /// Store text read out of the image `id`, replacing any earlier result.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `id` is not an image in the scene or the text is
+    /// invalid (see [`OcrText::validate`]).
+    pub fn set_extracted_text(&mut self, id: ElementId, text: OcrText) -> CanvasResult<()> {
+        text.validate()?;
+        let element = self
+            .elements
+            .get(&id)
+            .ok_or_else(|| CanvasError::ElementNotFound(id.to_string()))?;
+        if !matches!(element.kind, ElementKind::Image { .. }) {
+            return Err(CanvasError::InvalidOperation(format!(
+                "text can only be extracted from an image, not a {}",
+                element.kind.type_name()
+            )));
+        }
+        if let Some(ElementKind::Image { extracted_text, .. }) =
+            self.get_element_mut(id).map(|element| &mut element.kind)
+        {
+            *extracted_text = Some(text);
+        }
+        Ok(())
+    }
+
     /// Get all elements in the scene.
     pub fn elements(&self) -> impl Iterator<Item = &Element> {
         self.elements.values()
@@ -84,6 +491,8 @@ impl Scene {
 
     /// Get mutable references to all elements in the scene.
     pub fn elements_mut(&mut self) -> impl Iterator<Item = &mut Element> {
+        self.stale = None;
+        self.changes.touch_all(self.elements.keys().copied());
         self.elements.values_mut()
     }
 
@@ -94,144 +503,3168 @@ impl Scene {
             .filter_map(|id| self.elements.get(id))
     }
 
-    /// Set the viewport dimensions.
-    pub fn set_viewport(&mut self, width: f32, height: f32) {
-        self.viewport_width = width;
-        self.viewport_height = height;
-    }
-
-    /// Find the element at the given canvas coordinates.
-    /// Returns the ID of the topmost (highest z-index) interactive element.
-    #[must_use]
-    pub fn element_at(&self, x: f32, y: f32) -> Option<ElementId> {
-        let canvas_x = (x - self.pan_x) / self.zoom;
-        let canvas_y = (y - self.pan_y) / self.zoom;
-
+    /// Move an element into a container (`Group` or `OverlayLayer`), or pin
+    /// markup to an `Image` (see [`annotation::is_markup`]).
+    ///
+    /// The element is detached from its previous parent (or the root list)
+    /// and appended to the container's children.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if either element is missing, the target is not a
+    /// container, the element is not markup but the target is an image, or
+    /// the move would make the container its own descendant.
+    pub fn reparent(&mut self, id: ElementId, parent: ElementId) -> CanvasResult<()> {
+        let element = self
+            .elements
+            .get(&id)
+            .ok_or_else(|| CanvasError::ElementNotFound(id.to_string()))?;
+        let old_parent = element.parent;
+        if old_parent == Some(parent) {
+            return Ok(());
+        }
+        let markup = annotation::is_markup(&element.kind);
+        if !markup
+            && matches!(
+                self.elements.get(&parent).map(|p| &p.kind),
+                Some(ElementKind::Image { .. })
+            )
+        {
+            return Err(CanvasError::InvalidOperation(format!(
+                "only markup can be pinned to image {parent}, not a {}",
+                element.kind.type_name()
+            )));
+        }
+        let mut ancestor = Some(parent);
+        while let Some(current) = ancestor {
+            if current == id {
+                return Err(CanvasError::InvalidOperation(format!(
+                    "cannot move {id} into its own descendant"
+                )));
+            }
+            ancestor = self.elements.get(&current).and_then(|e| e.parent);
+        }
         self.elements
-            .values()
-            .filter(|e| e.interactive && e.contains_point(canvas_x, canvas_y))
-            .max_by_key(|e| e.transform.z_index)
-            .map(|e| e.id)
+            .get_mut(&parent)
+            .ok_or_else(|| CanvasError::ElementNotFound(parent.to_string()))?
+            .kind
+            .children_mut()
+            .ok_or_else(|| {
+                CanvasError::InvalidOperation(format!("{parent} is not a group, layer or image"))
+            })?
+            .push(id);
+
+        match old_parent.and_then(|old| self.elements.get_mut(&old)) {
+            Some(old) => {
+                if let Some(children) = old.kind.children_mut() {
+                    children.retain(|&child| child != id);
+                }
+            }
+            None => self.root_elements.retain(|&eid| eid != id),
+        }
+        if let Some(element) = self.elements.get_mut(&id) {
+            element.parent = Some(parent);
+        }
+        self.changes
+            .touch_all([id, parent].into_iter().chain(old_parent));
+        Ok(())
     }
 
-    /// Select an element.
+    /// Wrap elements in a new `Group`, returning the group's ID.
+    ///
+    /// Element transforms are in canvas coordinates whether or not the
+    /// element is nested, so members keep their transforms and nothing moves
+    /// on screen. The group covers the members' combined bounds, sits at the
+    /// lowest member's z-index, and takes the members' place under their
+    /// shared parent (or at the root). Duplicate IDs are ignored.
     ///
     /// # Errors
     ///
-    /// Returns an error if the element is not found.
-    pub fn select(&mut self, id: ElementId) -> CanvasResult<()> {
-        if let Some(element) = self.elements.get_mut(&id) {
-            element.selected = true;
-            if !self.selected.contains(&id) {
-                self.selected.push(id);
+    /// Returns an error if `ids` is empty, an element is missing, or the
+    /// elements do not share a parent.
+    pub fn group(&mut self, ids: &[ElementId]) -> CanvasResult<ElementId> {
+        let mut members: Vec<ElementId> = Vec::with_capacity(ids.len());
+        for &id in ids {
+            if !members.contains(&id) {
+                members.push(id);
             }
-            Ok(())
-        } else {
-            Err(CanvasError::ElementNotFound(id.to_string()))
         }
-    }
-
-    /// Deselect all elements.
-    pub fn deselect_all(&mut self) {
-        for id in &self.selected {
-            if let Some(element) = self.elements.get_mut(id) {
-                element.selected = false;
+        let mut parent = None;
+        let mut z_index = i32::MAX;
+        for (i, id) in members.iter().enumerate() {
+            let element = self
+                .elements
+                .get(id)
+                .ok_or_else(|| CanvasError::ElementNotFound(id.to_string()))?;
+            if i == 0 {
+                parent = element.parent;
+            } else if element.parent != parent {
+                return Err(CanvasError::InvalidOperation(
+                    "grouped elements must share a parent".to_string(),
+                ));
             }
+            z_index = z_index.min(element.transform.z_index);
         }
-        self.selected.clear();
-    }
+        let bounds = self
+            .bounds_of(&members)
+            .ok_or_else(|| CanvasError::InvalidOperation("no elements to group".to_string()))?;
 
-    /// Get currently selected elements.
-    pub fn selected_elements(&self) -> impl Iterator<Item = &Element> {
-        self.selected.iter().filter_map(|id| self.elements.get(id))
+        let group = Element::new(ElementKind::Group {
+            children: Vec::new(),
+            clip: false,
+            opacity: 1.0,
+        })
+        .with_transform(Transform {
+            x: bounds.min_x,
+            y: bounds.min_y,
+            width: bounds.width(),
+            height: bounds.height(),
+            rotation: 0.0,
+            z_index,
+            auto_size: false,
+        });
+        let group_id = self.add_element(group);
+        if let Some(parent) = parent {
+            self.reparent(group_id, parent)?;
+        }
+        for id in members {
+            self.reparent(id, group_id)?;
+        }
+        Ok(group_id)
     }
 
-    /// Get the number of elements in the scene.
-    #[must_use]
-    pub fn element_count(&self) -> usize {
-        self.elements.len()
-    }
+    /// Dissolve a `Group`, moving its children to the group's parent (or the
+    /// root) and removing it. Returns the former children.
+    ///
+    /// Children keep their canvas-coordinate transforms, so nothing moves;
+    /// the group's clipping and opacity no longer apply to them.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the element is missing or is not a group.
+    pub fn ungroup(&mut self, id: ElementId) -> CanvasResult<Vec<ElementId>> {
+        let group = self
+            .elements
+            .get_mut(&id)
+            .ok_or_else(|| CanvasError::ElementNotFound(id.to_string()))?;
+        let ElementKind::Group { children, .. } = &mut group.kind else {
+            return Err(CanvasError::InvalidOperation(format!(
+                "{id} is not a group"
+            )));
+        };
+        let mut children = std::mem::take(children);
+        let parent = group.parent;
+        children.retain(|child| self.elements.contains_key(child));
 
-    /// Check if the scene is empty.
-    #[must_use]
-    pub fn is_empty(&self) -> bool {
-        self.elements.is_empty()
+        let siblings = match parent.and_then(|p| self.elements.get_mut(&p)) {
+            Some(container) => container.kind.children_mut(),
+            None => Some(&mut self.root_elements),
+        };
+        if let Some(siblings) = siblings {
+            siblings.retain(|&sibling| sibling != id);
+            siblings.extend(&children);
+        }
+        for child in &children {
+            if let Some(element) = self.elements.get_mut(child) {
+                element.parent = parent;
+            }
+        }
+        self.changes
+            .touch_all(children.iter().copied().chain(parent));
+        self.remove_element(&id)?;
+        Ok(children)
     }
 
-    /// Clear all elements from the scene.
-    pub fn clear(&mut self) {
-        self.elements.clear();
-        self.root_elements.clear();
-        self.selected.clear();
+    /// Add a copy of `template` with its origin at (`x`, `y`).
+    ///
+    /// Every element gets a new ID and no author attribution; parent and
+    /// child links are rewritten to match, and attachments to elements
+    /// outside the template are dropped. Returns the new IDs in template order (parents before
+    /// children).
+    pub fn instantiate_template(&mut self, template: &Template, x: f32, y: f32) -> Vec<ElementId> {
+        let ids: HashMap<ElementId, ElementId> = template
+            .elements
+            .iter()
+            .map(|e| (e.id, ElementId::new()))
+            .collect();
+
+        let mut created = Vec::with_capacity(template.elements.len());
+        for source in &template.elements {
+            let mut element = source.clone();
+            element.id = ids[&source.id];
+            element.parent = source.parent.and_then(|p| ids.get(&p).copied());
+            element.selected = false;
+            element.created_by = None;
+            element.last_modified_by = None;
+            element.transform.x += x;
+            element.transform.y += y;
+            match element.kind.callout_target_mut() {
+                Some(CalloutTarget::Element { element_id }) => {
+                    if let Some(&copy) = ids.get(element_id) {
+                        *element_id = copy;
+                    }
+                }
+                Some(CalloutTarget::Point { x: px, y: py }) => {
+                    *px += x;
+                    *py += y;
+                }
+                None => {}
+            }
+            element.attachment = element.attachment.and_then(|attachment| {
+                ids.get(&attachment.target).map(|&target| Attachment {
+                    target,
+                    ..attachment
+                })
+            });
+            if let Some(children) = element.kind.children_mut() {
+                *children = children
+                    .iter()
+                    .filter_map(|child| ids.get(child).copied())
+                    .collect();
+            }
+            created.push(self.add_element(element));
+        }
+        created
     }
 
-    /// Serialize the scene to JSON.
+    /// Duplicate elements (with their descendants), offsetting the copies
+    /// by (`dx`, `dy`).
+    ///
+    /// Copies are added at the root level. Returns the new IDs in the same
+    /// order as [`Scene::instantiate_template`].
     ///
     /// # Errors
     ///
-    /// Returns an error if serialization fails.
-    pub fn to_json(&self) -> CanvasResult<String> {
-        serde_json::to_string(self).map_err(CanvasError::Serialization)
+    /// Returns an error if `ids` is empty or an element is not in the scene.
+    pub fn duplicate_elements(
+        &mut self,
+        ids: &[ElementId],
+        dx: f32,
+        dy: f32,
+    ) -> CanvasResult<Vec<ElementId>> {
+        let template = Template::capture("duplicate", self, ids)?;
+        // Captured elements keep their IDs, so this is the original subtree
+        let source_ids: Vec<ElementId> = template.elements.iter().map(|e| e.id).collect();
+        let origin = self
+            .bounds_of(&source_ids)
+            .map_or((0.0, 0.0), |b| (b.min_x, b.min_y));
+        Ok(self.instantiate_template(&template, origin.0 + dx, origin.1 + dy))
     }
 
-    /// Deserialize a scene from JSON.
+    /// Import every element of `other`, offset by the options' offset.
+    ///
+    /// Elements keep their IDs unless `options` asks for fresh ones or the
+    /// ID is already taken, in which case its
+    /// [`CollisionPolicy`] applies. Parent,
+    /// child, callout and attachment links within `other` follow the new
+    /// IDs, and attachments to elements outside it are dropped; imported
+    /// elements are deselected but keep their author attribution. Only
+    /// elements are imported, not `other`'s viewport, anchors, theme or
+    /// branding.
     ///
     /// # Errors
     ///
-    /// Returns an error if deserialization fails.
-    pub fn from_json(json: &str) -> CanvasResult<Self> {
-        serde_json::from_str(json).map_err(CanvasError::Serialization)
+    /// Returns an error, leaving the scene unchanged, if an ID collides
+    /// under [`CollisionPolicy::Fail`].
+    pub fn merge(&mut self, other: &Scene, options: MergeOptions) -> CanvasResult<MergeReport> {
+        // Parents before children, starting from every element without a
+        // parent in `other`
+        let mut roots: Vec<ElementId> = other.root_elements.clone();
+        roots.extend(
+            other
+                .elements
+                .values()
+                .filter(|e| e.parent.is_some_and(|p| !other.elements.contains_key(&p)))
+                .map(|e| e.id),
+        );
+        roots.reverse();
+        let mut visited = HashSet::new();
+        let mut order = Vec::with_capacity(other.elements.len());
+        while let Some(id) = roots.pop() {
+            let Some(element) = other.elements.get(&id) else {
+                continue;
+            };
+            if visited.insert(id) {
+                order.push(element);
+                roots.extend(element.kind.children().iter().rev());
+            }
+        }
+
+        let collides = |id: &ElementId| !options.fresh_ids && self.elements.contains_key(id);
+        if options.on_collision == CollisionPolicy::Fail {
+            if let Some(element) = order.iter().find(|e| collides(&e.id)) {
+                return Err(CanvasError::InvalidOperation(format!(
+                    "element {} is already in the scene",
+                    element.id
+                )));
+            }
+        }
+
+        let mut report = MergeReport::default();
+        let mut replaced = Vec::new();
+        for element in &order {
+            let id = element.id;
+            if element.parent.is_some_and(|p| report.skipped.contains(&p)) {
+                report.skipped.push(id);
+                continue;
+            }
+            let target = match (collides(&id), options.on_collision) {
+                (true, CollisionPolicy::Skip) => {
+                    report.skipped.push(id);
+                    continue;
+                }
+                (true, CollisionPolicy::Replace) => {
+                    replaced.push(id);
+                    id
+                }
+                _ if options.fresh_ids || collides(&id) => ElementId::new(),
+                _ => id,
+            };
+            report.ids.insert(id, target);
+        }
+
+        for id in replaced {
+            report.removed.extend(self.remove_subtree(id));
+        }
+
+        let (dx, dy) = (options.offset_x, options.offset_y);
+        for source in order {
+            let Some(&id) = report.ids.get(&source.id) else {
+                continue;
+            };
+            let mut element = source.clone();
+            element.id = id;
+            element.parent = source.parent.and_then(|p| report.mapped(p));
+            element.selected = false;
+            element.transform.x += dx;
+            element.transform.y += dy;
+            match element.kind.callout_target_mut() {
+                Some(CalloutTarget::Element { element_id }) => {
+                    if let Some(mapped) = report.mapped(*element_id) {
+                        *element_id = mapped;
+                    }
+                }
+                Some(CalloutTarget::Point { x, y }) => {
+                    *x += dx;
+                    *y += dy;
+                }
+                None => {}
+            }
+            element.attachment = element.attachment.and_then(|attachment| {
+                report.mapped(attachment.target).map(|target| Attachment {
+                    target,
+                    ..attachment
+                })
+            });
+            if let Some(children) = element.kind.children_mut() {
+                *children = children
+                    .iter()
+                    .filter_map(|&child| report.mapped(child))
+                    .collect();
+            }
+            report.added.push(self.add_element(element));
+        }
+        report.removed.retain(|id| !self.elements.contains_key(id));
+        Ok(report)
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::{ElementKind, Transform};
+    /// Remove an element and its descendants, detaching it from its parent.
+    /// Returns the removed IDs.
+    fn remove_subtree(&mut self, id: ElementId) -> Vec<ElementId> {
+        let Some(parent) = self.elements.get(&id).map(|e| e.parent) else {
+            return Vec::new();
+        };
+        if let Some(children) = parent
+            .and_then(|p| self.elements.get_mut(&p))
+            .and_then(|p| p.kind.children_mut())
+        {
+            children.retain(|&child| child != id);
+        }
+        let mut removed = Vec::new();
+        let mut pending = vec![id];
+        while let Some(id) = pending.pop() {
+            if let Ok(element) = self.remove_element(&id) {
+                pending.extend(element.kind.children());
+                removed.push(id);
+            }
+        }
+        removed
+    }
 
-    #[test]
-    fn test_scene_add_remove() {
-        let mut scene = Scene::new(800.0, 600.0);
-        assert!(scene.is_empty());
+    /// Set the viewport dimensions.
+    pub fn set_viewport(&mut self, width: f32, height: f32) {
+        self.viewport_width = width;
+        self.viewport_height = height;
+        self.changes.touch_settings();
+    }
 
-        let element = Element::new(ElementKind::Text {
-            content: "Hello".to_string(),
-            font_size: 16.0,
-            color: "#000000".to_string(),
-        });
-        let id = scene.add_element(element);
+    /// Move the camera.
+    pub fn set_camera(&mut self, zoom: f32, pan_x: f32, pan_y: f32) {
+        self.zoom = zoom;
+        self.pan_x = pan_x;
+        self.pan_y = pan_y;
+        self.changes.touch_settings();
+    }
 
-        assert_eq!(scene.element_count(), 1);
-        assert!(scene.get_element(id).is_some());
+    /// Bounding box of every element in the scene, leaving out
+    /// screen-anchored ones.
+    ///
+    /// Returns `None` if the scene has no such elements.
+    #[must_use]
+    pub fn content_bounds(&self) -> Option<SceneBounds> {
+        self.elements
+            .values()
+            .filter(|e| !e.screen_anchored)
+            .map(|e| SceneBounds::from_transform(&e.transform))
+            .reduce(|a, b| a.union(&b))
+    }
 
-        scene.remove_element(&id).expect("should remove");
-        assert!(scene.is_empty());
+    /// Bounding box of the given elements, skipping IDs not in the scene.
+    ///
+    /// Returns `None` if none of the elements exist.
+    #[must_use]
+    pub fn bounds_of(&self, ids: &[ElementId]) -> Option<SceneBounds> {
+        ids.iter()
+            .filter_map(|id| self.elements.get(id))
+            .map(|e| SceneBounds::from_transform(&e.transform))
+            .reduce(|a, b| a.union(&b))
     }
 
-    #[test]
-    fn test_element_at() {
-        let mut scene = Scene::new(800.0, 600.0);
+    /// The part of the canvas visible in the viewport, in canvas coordinates.
+    #[must_use]
+    pub fn visible_bounds(&self) -> SceneBounds {
+        ViewportDocument::from(self).visible_bounds()
+    }
 
-        let element = Element::new(ElementKind::Text {
-            content: "Test".to_string(),
-            font_size: 16.0,
-            color: "#000000".to_string(),
-        })
-        .with_transform(Transform {
-            x: 100.0,
-            y: 100.0,
-            width: 200.0,
-            height: 50.0,
-            rotation: 0.0,
-            z_index: 0,
+    /// The viewport in its own pixels, where screen-anchored elements live.
+    fn screen_bounds(&self) -> SceneBounds {
+        SceneBounds {
+            min_x: 0.0,
+            min_y: 0.0,
+            max_x: self.viewport_width,
+            max_y: self.viewport_height,
+        }
+    }
+
+    /// Shown, interactive leaf elements within `radius` screen pixels of the
+    /// screen point (`x`, `y`), nearest first, with their distance in canvas
+    /// units.
+    ///
+    /// Used to find what an imprecise touch may have meant. Containers and
+    /// screen-anchored elements are not included.
+    #[must_use]
+    pub fn elements_near(&self, x: f32, y: f32, radius: f32) -> Vec<(&Element, f32)> {
+        let canvas_x = (x - self.pan_x) / self.zoom;
+        let canvas_y = (y - self.pan_y) / self.zoom;
+        let reach = radius / self.zoom;
+        let area = SceneBounds {
+            min_x: canvas_x - reach,
+            min_y: canvas_y - reach,
+            max_x: canvas_x + reach,
+            max_y: canvas_y + reach,
+        };
+        let mut near: Vec<_> = self
+            .candidates(&area)
+            .into_iter()
+            .filter(|e| {
+                !e.screen_anchored
+                    && e.interactive
+                    && e.kind.children().is_empty()
+                    && self.is_shown(e)
+            })
+            .map(|e| {
+                let rect = OrientedRect::from_transform(&e.transform);
+                (e, rect.distance_to(canvas_x, canvas_y))
+            })
+            .filter(|(_, distance)| *distance <= reach)
+            .collect();
+        // Nearest first; of overlapping elements, the topmost first
+        near.sort_by(|(a, da), (b, db)| {
+            da.total_cmp(db)
+                .then(b.transform.z_index.cmp(&a.transform.z_index))
         });
+        near
+    }
 
-        scene.add_element(element);
+    /// Shown, interactive leaf elements whose center lies inside `polygon`,
+    /// given in screen coordinates, topmost first.
+    ///
+    /// Used to find what a lasso gesture circled. Containers and
+    /// screen-anchored elements are not included.
+    #[must_use]
+    pub fn elements_in_region(&self, polygon: &[[f32; 2]]) -> Vec<&Element> {
+        let canvas: Vec<[f32; 2]> = polygon
+            .iter()
+            .map(|&[x, y]| [(x - self.pan_x) / self.zoom, (y - self.pan_y) / self.zoom])
+            .collect();
+        let Some(area) = canvas
+            .iter()
+            .fold(None, |area: Option<SceneBounds>, &[x, y]| {
+                let point = SceneBounds {
+                    min_x: x,
+                    min_y: y,
+                    max_x: x,
+                    max_y: y,
+                };
+                Some(area.map_or(point, |a| a.union(&point)))
+            })
+        else {
+            return Vec::new();
+        };
+        let mut inside: Vec<_> = self
+            .candidates(&area)
+            .into_iter()
+            .filter(|e| {
+                let (x, y) = OrientedRect::from_transform(&e.transform).center;
+                !e.screen_anchored
+                    && e.interactive
+                    && e.kind.children().is_empty()
+                    && self.is_shown(e)
+                    && geometry::polygon_contains(&canvas, x, y)
+            })
+            .collect();
+        inside.sort_by_key(|e| std::cmp::Reverse(e.transform.z_index));
+        inside
+    }
 
-        // Point inside element
-        assert!(scene.element_at(150.0, 125.0).is_some());
+    /// Elements overlapping `bounds`, taking rotation into account.
+    ///
+    /// Screen-anchored elements are not on the canvas and never match.
+    pub fn elements_in<'a>(
+        &'a self,
+        bounds: &'a SceneBounds,
+    ) -> impl Iterator<Item = &'a Element> + 'a {
+        self.candidates(bounds).into_iter().filter(|e| {
+            !e.screen_anchored
+                && OrientedRect::from_transform(&e.transform).intersects_bounds(bounds)
+        })
+    }
 
-        // Point outside element
-        assert!(scene.element_at(50.0, 50.0).is_none());
+    /// Shown elements with any part inside the viewport, screen-anchored
+    /// ones included.
+    ///
+    /// Equivalent to filtering [`Scene::elements`] with [`Scene::is_visible`],
+    /// but only visits elements near the viewport.
+    pub fn visible_elements(&self) -> impl Iterator<Item = &Element> + '_ {
+        let screen = self.screen_bounds();
+        let pinned = self
+            .candidates(&screen)
+            .into_iter()
+            .filter(|e| e.screen_anchored);
+        let bounds = self.visible_bounds();
+        self.candidates(&bounds)
+            .into_iter()
+            .filter(|e| !e.screen_anchored)
+            .chain(pinned)
+            .filter(move |e| self.is_visible(e))
+    }
+
+    /// Check whether an element is shown and any part of it is inside the
+    /// viewport. Screen-anchored elements are tested in viewport pixels.
+    ///
+    /// Used by renderers to cull hidden and offscreen elements.
+    #[must_use]
+    pub fn is_visible(&self, element: &Element) -> bool {
+        let bounds = if element.screen_anchored {
+            self.screen_bounds()
+        } else {
+            self.visible_bounds()
+        };
+        self.is_shown(element)
+            && OrientedRect::from_transform(&element.transform).intersects_bounds(&bounds)
+    }
+
+    /// Check whether an element or any of its ancestors is
+    /// [`sensitive`](Element::sensitive), i.e. whether redaction hides it.
+    #[must_use]
+    pub fn is_sensitive(&self, element: &Element) -> bool {
+        let mut current = Some(element);
+        // Bound the walk so a corrupt parent cycle cannot loop forever
+        for _ in 0..=self.elements.len() {
+            match current {
+                Some(element) if element.sensitive => return true,
+                Some(element) => current = element.parent.and_then(|id| self.elements.get(&id)),
+                None => return false,
+            }
+        }
+        false
+    }
+
+    /// A copy of the scene that is safe to share outside: each sensitive
+    /// element becomes a [`placeholder`](redact::placeholder), its
+    /// descendants are removed, and datasets bound only to removed charts
+    /// are dropped.
+    #[must_use]
+    pub fn redacted(&self) -> Self {
+        let roots: Vec<ElementId> = self
+            .elements
+            .values()
+            .filter(|e| {
+                e.sensitive
+                    && !e
+                        .parent
+                        .and_then(|id| self.elements.get(&id))
+                        .is_some_and(|parent| self.is_sensitive(parent))
+            })
+            .map(|e| e.id)
+            .collect();
+
+        let mut scene = self.clone();
+        let mut datasets = HashSet::new();
+        for root in roots {
+            let mut stack = vec![root];
+            let mut seen = HashSet::new();
+            while let Some(id) = stack.pop() {
+                let Some(element) = scene.elements.get(&id) else {
+                    continue;
+                };
+                if !seen.insert(id) {
+                    continue;
+                }
+                if let ElementKind::Chart {
+                    binding: Some(binding),
+                    ..
+                } = &element.kind
+                {
+                    datasets.insert(binding.dataset.clone());
+                }
+                stack.extend(element.kind.children().iter().copied());
+                if id != root {
+                    let _ = scene.remove_element(&id);
+                }
+            }
+            if let Some(element) = scene.get_element_mut(root) {
+                *element = redact::placeholder(element);
+            }
+        }
+        datasets.retain(|dataset| {
+            !scene.elements.values().any(|e| {
+                matches!(
+                    &e.kind,
+                    ElementKind::Chart { binding: Some(binding), .. } if binding.dataset == *dataset
+                )
+            })
+        });
+        for id in datasets {
+            let _ = scene.data.apply(&DatasetDelta::Remove { id });
+        }
+        scene
+    }
+
+    /// Check whether an element and all of its ancestors are
+    /// [`visible`](Element::visible), wherever it is on the canvas.
+    #[must_use]
+    pub fn is_shown(&self, element: &Element) -> bool {
+        if !element.visible {
+            return false;
+        }
+        let mut current = element.parent;
+        // Bound the walk so a corrupt parent cycle cannot loop forever
+        for _ in 0..self.elements.len() {
+            match current.and_then(|id| self.elements.get(&id)) {
+                Some(parent) if !parent.visible => return false,
+                Some(parent) => current = parent.parent,
+                None => return true,
+            }
+        }
+        true
+    }
+
+    /// Show or hide every element of one kind, named as by
+    /// [`ElementKind::type_name`] (e.g. `"Video"` or `"Chart"`).
+    ///
+    /// Returns the IDs of the elements whose visibility changed.
+    pub fn set_kind_visibility(&mut self, type_name: &str, visible: bool) -> Vec<ElementId> {
+        let changed: Vec<ElementId> = self
+            .elements
+            .values_mut()
+            .filter(|e| e.kind.type_name() == type_name && e.visible != visible)
+            .map(|e| {
+                e.visible = visible;
+                e.id
+            })
+            .collect();
+        if !changed.is_empty() {
+            self.changes.touch_all(changed.iter().copied());
+        }
+        changed
+    }
+
+    /// Set the element opacity of every element of one kind, named as by
+    /// [`ElementKind::type_name`]. The opacity is clamped to `0.0..=1.0`.
+    ///
+    /// Returns the IDs of the elements whose opacity changed.
+    pub fn set_kind_opacity(&mut self, type_name: &str, opacity: f32) -> Vec<ElementId> {
+        let opacity = if opacity.is_finite() {
+            opacity.clamp(0.0, 1.0)
+        } else {
+            1.0
+        };
+        let changed: Vec<ElementId> = self
+            .elements
+            .values_mut()
+            .filter(|e| {
+                e.kind.type_name() == type_name && (e.opacity - opacity).abs() > f32::EPSILON
+            })
+            .map(|e| {
+                e.opacity = opacity;
+                e.id
+            })
+            .collect();
+        if !changed.is_empty() {
+            self.changes.touch_all(changed.iter().copied());
+        }
+        changed
+    }
+
+    /// Erase ink under a round eraser of `radius` centered at (`x`, `y`).
+    ///
+    /// In [`EraseMode::Pixel`] strokes are cut at the eraser's edge and the
+    /// remaining pieces become new strokes with the original's attributes;
+    /// in [`EraseMode::Stroke`] touched strokes are removed whole. Hidden
+    /// strokes are left alone. Returns the edit made, empty if nothing was
+    /// touched.
+    pub fn erase_ink(&mut self, x: f32, y: f32, radius: f32, mode: EraseMode) -> InkEdit {
+        if !(radius.is_finite() && radius > 0.0 && x.is_finite() && y.is_finite()) {
+            return InkEdit::default();
+        }
+        // Stroke bounds already include half the stroke width
+        let bounds = SceneBounds {
+            min_x: x - radius,
+            min_y: y - radius,
+            max_x: x + radius,
+            max_y: y + radius,
+        };
+        let mut edit = InkEdit::default();
+        for element in self.elements_in(&bounds) {
+            let ElementKind::Ink { color, .. } = &element.kind else {
+                continue;
+            };
+            let Some((points, width)) = ink::canvas_points(element) else {
+                continue;
+            };
+            if !self.is_shown(element) {
+                continue;
+            }
+            let reach = radius + width / 2.0;
+            let pieces = match mode {
+                EraseMode::Pixel => match ink::erase(&points, [x, y], reach) {
+                    Some(pieces) => pieces,
+                    None => continue,
+                },
+                EraseMode::Stroke if ink::touches(&points, [x, y], reach) => Vec::new(),
+                EraseMode::Stroke => continue,
+            };
+            edit.after.extend(
+                pieces
+                    .iter()
+                    .map(|piece| ink::restroke(element, piece, color.clone(), width)),
+            );
+            edit.before.push(element.clone());
+        }
+        if let Err(error) = self.apply_ink_edit(&edit) {
+            // Every stroke in the edit was just read from the scene
+            debug_assert!(false, "erase produced an inapplicable edit: {error}");
+        }
+        edit
+    }
+
+    /// Change the color and/or width of ink strokes, keeping their IDs.
+    ///
+    /// The width is in canvas pixels at the stroke's current scale.
+    /// Returns the edit made.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an element is missing or not an `Ink` stroke, or
+    /// the width is invalid; nothing is changed then.
+    pub fn restyle_ink(
+        &mut self,
+        ids: &[ElementId],
+        color: Option<&ThemeColor>,
+        width: Option<f32>,
+    ) -> CanvasResult<InkEdit> {
+        let mut edit = InkEdit::default();
+        for id in ids {
+            let element = self
+                .elements
+                .get(id)
+                .ok_or_else(|| CanvasError::ElementNotFound(id.to_string()))?;
+            let ElementKind::Ink { color: current, .. } = &element.kind else {
+                return Err(CanvasError::InvalidOperation(format!(
+                    "{id} is not an ink stroke"
+                )));
+            };
+            let Some((points, current_width)) = ink::canvas_points(element) else {
+                continue;
+            };
+            let width = width.unwrap_or(current_width);
+            ink::validate(&points, width)?;
+            let mut restyled =
+                ink::restroke(element, &points, color.unwrap_or(current).clone(), width);
+            restyled.id = element.id;
+            restyled.selected = element.selected;
+            edit.before.push(element.clone());
+            edit.after.push(restyled);
+        }
+        self.apply_ink_edit(&edit)?;
+        Ok(edit)
+    }
+
+    /// IDs of the shown ink strokes lying entirely inside the polygon
+    /// `lasso`, in canvas coordinates.
+    #[must_use]
+    pub fn lasso_ink(&self, lasso: &[(f32, f32)]) -> Vec<ElementId> {
+        if lasso.len() < 3 {
+            return Vec::new();
+        }
+        let mut ids: Vec<ElementId> = self
+            .elements
+            .values()
+            .filter(|e| self.is_shown(e))
+            .filter(|e| {
+                ink::canvas_points(e)
+                    .is_some_and(|(points, _)| points.iter().all(|&p| ink::in_lasso(p, lasso)))
+            })
+            .map(|e| e.id)
+            .collect();
+        ids.sort_by_key(|id| {
+            self.elements
+                .get(id)
+                .map(|e| e.transform.z_index)
+                .unwrap_or_default()
+        });
+        ids
+    }
+
+    /// Apply an [`InkEdit`], e.g. the [`inverse`](InkEdit::inverse) of an
+    /// earlier one to undo it.
+    ///
+    /// New elements are placed under their recorded parent if it still
+    /// exists, otherwise at the root.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an element the edit expects is missing, or one
+    /// it would add already exists; nothing is changed then.
+    pub fn apply_ink_edit(&mut self, edit: &InkEdit) -> CanvasResult<()> {
+        let before: HashSet<ElementId> = edit.before.iter().map(|e| e.id).collect();
+        if let Some(missing) = before.iter().find(|id| !self.elements.contains_key(id)) {
+            return Err(CanvasError::ElementNotFound(missing.to_string()));
+        }
+        if let Some(taken) = edit
+            .after
+            .iter()
+            .find(|e| !before.contains(&e.id) && self.elements.contains_key(&e.id))
+        {
+            return Err(CanvasError::InvalidOperation(format!(
+                "{} already exists",
+                taken.id
+            )));
+        }
+        for id in edit.removed() {
+            self.remove_subtree(id);
+        }
+        for element in &edit.after {
+            if self.elements.contains_key(&element.id) {
+                self.upsert_element(element.clone());
+                continue;
+            }
+            let mut element = element.clone();
+            let parent = element.parent.take();
+            let id = element.id;
+            self.upsert_element(element);
+            if let Some(parent) = parent.filter(|p| self.elements.contains_key(p)) {
+                // Containers accept any child; fall back to the root otherwise
+                let _ = self.reparent(id, parent);
+            }
+        }
+        Ok(())
+    }
+
+    /// Where a callout's tail points, in canvas coordinates.
+    ///
+    /// Element targets resolve to the target's center. Returns `None` if the
+    /// element is not a callout, has no target, or its target element is
+    /// not in the scene.
+    #[must_use]
+    pub fn callout_tip(&self, id: ElementId) -> Option<(f32, f32)> {
+        let ElementKind::Callout {
+            target: Some(target),
+            ..
+        } = &self.elements.get(&id)?.kind
+        else {
+            return None;
+        };
+        match *target {
+            CalloutTarget::Point { x, y } => Some((x, y)),
+            CalloutTarget::Element { element_id } => self
+                .elements
+                .get(&element_id)
+                .map(|e| SceneBounds::from_transform(&e.transform).center()),
+        }
+    }
+
+    /// Triangle of a callout's tail: two base corners on the callout edge
+    /// facing the tip, then the tip.
+    ///
+    /// Returns `None` if the callout has no resolvable tip or the tip lies
+    /// inside the callout.
+    #[must_use]
+    pub fn callout_tail(&self, id: ElementId) -> Option<[(f32, f32); 3]> {
+        let tip = self.callout_tip(id)?;
+        let element = self.elements.get(&id)?;
+        if element.contains_point(tip.0, tip.1) {
+            return None;
+        }
+        let bounds = SceneBounds::from_transform(&element.transform);
+        let (cx, cy) = bounds.center();
+        let (dx, dy) = (tip.0 - cx, tip.1 - cy);
+        let half = (bounds.width().min(bounds.height()) * 0.2).max(1.0);
+
+        // Leave through the side the tip lies furthest beyond
+        if dx.abs() * bounds.height() > dy.abs() * bounds.width() {
+            let x = if dx > 0.0 { bounds.max_x } else { bounds.min_x };
+            let y = tip.1.max(bounds.min_y + half).min(bounds.max_y - half);
+            Some([(x, y - half), (x, y + half), tip])
+        } else {
+            let y = if dy > 0.0 { bounds.max_y } else { bounds.min_y };
+            let x = tip.0.max(bounds.min_x + half).min(bounds.max_x - half);
+            Some([(x - half, y), (x + half, y), tip])
+        }
+    }
+
+    /// Find the element at the given screen coordinates.
+    /// Returns the ID of the topmost (highest z-index) interactive element
+    /// that is shown. Screen-anchored elements are drawn above the canvas,
+    /// so they are hit first.
+    #[must_use]
+    pub fn element_at(&self, x: f32, y: f32) -> Option<ElementId> {
+        let screen = SceneBounds {
+            min_x: x,
+            min_y: y,
+            max_x: x,
+            max_y: y,
+        };
+        let pinned = self
+            .candidates(&screen)
+            .into_iter()
+            .filter(|e| {
+                e.screen_anchored && e.interactive && e.contains_point(x, y) && self.is_shown(e)
+            })
+            .max_by_key(|e| e.transform.z_index);
+        if let Some(element) = pinned {
+            return Some(element.id);
+        }
+
+        let canvas_x = (x - self.pan_x) / self.zoom;
+        let canvas_y = (y - self.pan_y) / self.zoom;
+
+        let point = SceneBounds {
+            min_x: canvas_x,
+            min_y: canvas_y,
+            max_x: canvas_x,
+            max_y: canvas_y,
+        };
+        self.candidates(&point)
+            .into_iter()
+            .filter(|e| {
+                !e.screen_anchored
+                    && e.interactive
+                    && e.contains_point(canvas_x, canvas_y)
+                    && self.is_shown(e)
+            })
+            .max_by_key(|e| e.transform.z_index)
+            .map(|e| e.id)
+    }
+
+    /// Select an element.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the element is not found.
+    pub fn select(&mut self, id: ElementId) -> CanvasResult<()> {
+        if let Some(element) = self.elements.get_mut(&id) {
+            element.selected = true;
+            if !self.selected.contains(&id) {
+                self.selected.push(id);
+                self.changes.touch(id);
+            }
+            Ok(())
+        } else {
+            Err(CanvasError::ElementNotFound(id.to_string()))
+        }
+    }
+
+    /// Deselect all elements.
+    pub fn deselect_all(&mut self) {
+        for id in &self.selected {
+            if let Some(element) = self.elements.get_mut(id) {
+                element.selected = false;
+            }
+        }
+        if !self.selected.is_empty() {
+            self.changes.touch_all(self.selected.drain(..));
+        }
+    }
+
+    /// Get currently selected elements.
+    pub fn selected_elements(&self) -> impl Iterator<Item = &Element> {
+        self.selected.iter().filter_map(|id| self.elements.get(id))
+    }
+
+    /// Compute the clip rectangle of every element inside a clipping container.
+    ///
+    /// Each entry is `[x, y, width, height]` in canvas coordinates: the
+    /// intersection of the bounds of all ancestors with `clip` enabled.
+    /// Elements that are not clipped have no entry.
+    #[must_use]
+    pub fn clip_rects(&self) -> HashMap<ElementId, [f32; 4]> {
+        let nested: HashSet<ElementId> = self
+            .elements
+            .values()
+            .flat_map(|e| e.kind.children().iter().copied())
+            .collect();
+
+        let mut clips = HashMap::new();
+        for container in self.elements.values() {
+            if !container.kind.children().is_empty() && !nested.contains(&container.id) {
+                self.collect_clips(container, None, &mut clips, 0);
+            }
+        }
+        clips
+    }
+
+    fn collect_clips(
+        &self,
+        container: &Element,
+        inherited: Option<[f32; 4]>,
+        clips: &mut HashMap<ElementId, [f32; 4]>,
+        depth: usize,
+    ) {
+        // Guard against cyclic children lists
+        const MAX_DEPTH: usize = 32;
+        if depth > MAX_DEPTH {
+            return;
+        }
+
+        let clip = if container.kind.clips_children() {
+            let t = &container.transform;
+            let bounds = [t.x, t.y, t.width, t.height];
+            Some(inherited.map_or(bounds, |outer| intersect_rects(outer, bounds)))
+        } else {
+            inherited
+        };
+        let Some(clip) = clip else {
+            return;
+        };
+
+        for child_id in container.kind.children() {
+            if let Some(child) = self.elements.get(child_id) {
+                clips.insert(*child_id, clip);
+                self.collect_clips(child, Some(clip), clips, depth + 1);
+            }
+        }
+    }
+
+    /// Check the scene for integrity issues: orphaned or shared children,
+    /// broken parent links, duplicate IDs, non-finite transforms and
+    /// zero-size elements.
+    ///
+    /// Returns an empty list for a consistent scene.
+    #[must_use]
+    pub fn validate(&self) -> Vec<SceneIssue> {
+        diagnostics::validate(&self.elements)
+    }
+
+    /// Check shown elements for layout and legibility problems: overlapping
+    /// text, tiny fonts, low contrast, elements off the canvas and links to
+    /// missing elements.
+    ///
+    /// Each finding carries a suggested fix when one is known; see
+    /// [`Scene::apply_lint_fixes`].
+    #[must_use]
+    pub fn lint(&self, config: &LintConfig) -> Vec<LintFinding> {
+        let shown = self
+            .elements
+            .values()
+            .filter(|e| self.is_shown(e))
+            .collect();
+        lint::lint(
+            &self.elements,
+            shown,
+            (self.viewport_width, self.viewport_height),
+            &self.theme,
+            config,
+        )
+    }
+
+    /// Apply the suggested fixes of `findings`, in order, through
+    /// [`Scene::update_element`].
+    ///
+    /// Findings for elements no longer in the scene are skipped. Returns
+    /// the IDs of the fixed elements.
+    pub fn apply_lint_fixes(&mut self, findings: &[LintFinding]) -> Vec<ElementId> {
+        let mut fixed = Vec::new();
+        for finding in findings {
+            let Some(fix) = &finding.fix else {
+                continue;
+            };
+            if self
+                .update_element(finding.element_id, |e| fix.apply(e))
+                .is_ok()
+            {
+                fixed.push(finding.element_id);
+            }
+        }
+        fixed
+    }
+
+    /// Summarize the scene: element counts per kind, bounds, nesting depth
+    /// and an approximate memory footprint.
+    #[must_use]
+    pub fn stats(&self) -> SceneStats {
+        diagnostics::stats(
+            &self.elements,
+            self.root_elements().count(),
+            self.selected.len(),
+        )
+    }
+
+    /// Get the number of elements in the scene.
+    #[must_use]
+    pub fn element_count(&self) -> usize {
+        self.elements.len()
+    }
+
+    /// Check if the scene is empty.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.elements.is_empty()
+    }
+
+    /// Clear all elements from the scene.
+    ///
+    /// Named anchors are views rather than content, so they are kept, as are
+    /// the theme, branding, coordinate system and datasets.
+    pub fn clear(&mut self) {
+        self.changes.remove_all(self.elements.keys().copied());
+        if self.spotlight.is_some() {
+            self.changes.touch_settings();
+        }
+        self.elements.clear();
+        self.root_elements.clear();
+        self.selected.clear();
+        self.spotlight = None;
+        self.index.clear();
+        self.stale = Some(HashSet::new());
+    }
+
+    /// Get the active spotlight, if any.
+    ///
+    /// Expiry is not checked here; see [`Spotlight::is_expired`].
+    #[must_use]
+    pub fn spotlight(&self) -> Option<&Spotlight> {
+        self.spotlight.as_ref()
+    }
+
+    /// Spotlight a set of elements, replacing any existing spotlight.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the spotlight is empty or references an element
+    /// that is not in the scene.
+    pub fn set_spotlight(&mut self, spotlight: Spotlight) -> CanvasResult<()> {
+        if spotlight.element_ids.is_empty() {
+            return Err(CanvasError::InvalidOperation(
+                "Spotlight requires at least one element".to_string(),
+            ));
+        }
+        if let Some(missing) = spotlight
+            .element_ids
+            .iter()
+            .find(|id| !self.elements.contains_key(id))
+        {
+            return Err(CanvasError::ElementNotFound(missing.to_string()));
+        }
+        self.spotlight = Some(spotlight);
+        self.changes.touch_settings();
+        Ok(())
+    }
+
+    /// Remove the active spotlight.
+    ///
+    /// Returns the spotlight that was active, if any.
+    pub fn clear_spotlight(&mut self) -> Option<Spotlight> {
+        let spotlight = self.spotlight.take();
+        if spotlight.is_some() {
+            self.changes.touch_settings();
+        }
+        spotlight
+    }
+
+    /// Named views, in the order they were first saved.
+    #[must_use]
+    pub fn anchors(&self) -> &[Anchor] {
+        &self.anchors
+    }
+
+    /// Get an anchor by name.
+    #[must_use]
+    pub fn anchor(&self, name: &str) -> Option<&Anchor> {
+        self.anchors.iter().find(|anchor| anchor.name == name)
+    }
+
+    /// Save a named view, replacing any anchor with the same name in place.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the anchor is invalid (see [`Anchor::validate`]).
+    pub fn set_anchor(&mut self, anchor: Anchor) -> CanvasResult<()> {
+        anchor.validate()?;
+        match self.anchors.iter_mut().find(|a| a.name == anchor.name) {
+            Some(existing) => *existing = anchor,
+            None => self.anchors.push(anchor),
+        }
+        self.changes.touch_settings();
+        Ok(())
+    }
+
+    /// Remove a named view.
+    ///
+    /// Returns the removed anchor, if it existed.
+    pub fn remove_anchor(&mut self, name: &str) -> Option<Anchor> {
+        let index = self.anchors.iter().position(|a| a.name == name)?;
+        self.changes.touch_settings();
+        Some(self.anchors.remove(index))
+    }
+
+    /// Move the camera to a named view.
+    ///
+    /// The anchor's point is centered in the current viewport at its zoom.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CanvasError::InvalidOperation`] if no anchor has that name.
+    pub fn go_to_anchor(&mut self, name: &str) -> CanvasResult<CameraFrame> {
+        CameraCommand::GoToAnchor {
+            name: name.to_string(),
+        }
+        .apply(self, &FitOptions::default())
+    }
+
+    /// Named colors and palettes that elements can reference.
+    #[must_use]
+    pub fn theme(&self) -> &Theme {
+        &self.theme
+    }
+
+    /// Replace the theme, restyling every element that references it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the theme is invalid (see [`Theme::validate`]).
+    pub fn set_theme(&mut self, theme: Theme) -> CanvasResult<()> {
+        theme.validate()?;
+        self.theme = theme;
+        self.changes.touch_settings();
+        Ok(())
+    }
+
+    /// Logo and watermark drawn over the canvas, if any.
+    #[must_use]
+    pub fn branding(&self) -> Option<&Branding> {
+        self.branding.as_ref()
+    }
+
+    /// Set the branding, replacing any existing branding.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the branding is invalid (see [`Branding::validate`]).
+    pub fn set_branding(&mut self, branding: Branding) -> CanvasResult<()> {
+        branding.validate()?;
+        self.branding = Some(branding);
+        self.changes.touch_settings();
+        Ok(())
+    }
+
+    /// Remove the branding.
+    ///
+    /// Returns the branding that was set, if any.
+    pub fn clear_branding(&mut self) -> Option<Branding> {
+        let branding = self.branding.take();
+        if branding.is_some() {
+            self.changes.touch_settings();
+        }
+        branding
+    }
+
+    /// World units and origin that canvas pixels stand for.
+    #[must_use]
+    pub fn coordinate_system(&self) -> &CoordinateSystem {
+        &self.coordinates
+    }
+
+    /// Set the world units and origin. Element transforms stay in canvas
+    /// pixels; this only changes how they are read and written in world
+    /// coordinates.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the coordinate system is invalid (see
+    /// [`CoordinateSystem::validate`]).
+    pub fn set_coordinate_system(&mut self, coordinates: CoordinateSystem) -> CanvasResult<()> {
+        coordinates.validate()?;
+        self.coordinates = coordinates;
+        self.changes.touch_settings();
+        Ok(())
+    }
+
+    /// Distance and angle from canvas position `start` to `end` in the
+    /// scene's world units.
+    #[must_use]
+    pub fn measure(&self, start: [f32; 2], end: [f32; 2]) -> LineMeasurement {
+        self.coordinates.measure(start, end)
+    }
+
+    /// Every `Measurement` element other than arrows, with what it measures
+    /// in the scene's world units.
+    #[must_use]
+    pub fn measurements(&self) -> Vec<(ElementId, LineMeasurement)> {
+        self.elements
+            .values()
+            .filter(|e| {
+                !matches!(
+                    e.kind,
+                    ElementKind::Measurement {
+                        kind: MeasurementKind::Arrow,
+                        ..
+                    }
+                )
+            })
+            .filter_map(|e| {
+                MeasurementLayout::from_element(e)
+                    .map(|layout| (e.id, self.measure(layout.start, layout.end)))
+            })
+            .collect()
+    }
+
+    /// Datasets that charts can be bound to.
+    #[must_use]
+    pub fn data(&self) -> &DataStore {
+        &self.data
+    }
+
+    /// Change a dataset and refill every chart bound to it.
+    ///
+    /// Returns the IDs of the refilled charts.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the change is invalid (see [`DataStore::apply`]);
+    /// the scene is then unchanged.
+    pub fn apply_data_delta(&mut self, delta: &DatasetDelta) -> CanvasResult<Vec<ElementId>> {
+        self.data.apply(delta)?;
+        self.changes.touch_settings();
+        Ok(self.refresh_bound_charts(delta.dataset_id()))
+    }
+
+    /// Refill the charts bound to `dataset`, returning their IDs.
+    fn refresh_bound_charts(&mut self, dataset: &str) -> Vec<ElementId> {
+        let mut refreshed = Vec::new();
+        for (id, element) in &mut self.elements {
+            let bound = matches!(
+                &element.kind,
+                ElementKind::Chart { binding: Some(binding), .. } if binding.dataset == dataset
+            );
+            if bound && data::materialize(&mut element.kind, &self.data) {
+                refreshed.push(*id);
+            }
+        }
+        self.changes.touch_all(refreshed.iter().copied());
+        refreshed
+    }
+
+    /// Current revision, bumped by every mutation.
+    ///
+    /// A new scene starts at revision 0; a deserialized one at 1 with no
+    /// history before it.
+    #[must_use]
+    pub fn revision(&self) -> u64 {
+        self.changes.revision()
+    }
+
+    /// Revision at which an element was last added or changed.
+    #[must_use]
+    pub fn element_revision(&self, id: ElementId) -> Option<u64> {
+        self.changes.element_revision(id)
+    }
+
+    /// What changed after `revision`.
+    ///
+    /// Reports a [`full`](SceneChanges::full) change, listing every element,
+    /// when the history does not reach back to `revision` (see
+    /// [`MAX_TOMBSTONES`](crate::changes::MAX_TOMBSTONES)) or `revision` is
+    /// newer than this scene's.
+    #[must_use]
+    pub fn changes_since(&self, revision: u64) -> SceneChanges {
+        self.changes.since(revision, self.elements.keys().copied())
+    }
+
+    /// Continue the revisions of `previous`, a scene this one replaces.
+    ///
+    /// The revision moves past `previous`'s and the history restarts, so
+    /// anyone tracking `previous` sees a full change rather than mistaking
+    /// this scene for the one they last saw.
+    pub fn supersede(&mut self, previous: &Scene) {
+        self.changes.supersede(previous.revision());
+        self.transitions.clone_from(&previous.transitions);
+        for (id, element) in &previous.elements {
+            if !self.elements.contains_key(id) {
+                self.transitions.exit(element.clone());
+            }
+        }
+        for id in self.elements.keys() {
+            if !previous.elements.contains_key(id) {
+                self.transitions.enter(*id);
+            }
+        }
+    }
+
+    /// Settings for the transitions of elements that sync adds or removes.
+    #[must_use]
+    pub const fn transition_config(&self) -> &TransitionConfig {
+        self.transitions.config()
+    }
+
+    /// Ease elements that patches and superseding scenes add or remove.
+    ///
+    /// Transitions are local presentation state: they are not synced or
+    /// saved and do not count as scene changes.
+    pub fn set_transition_config(&mut self, config: TransitionConfig) {
+        self.transitions.set_config(config);
+    }
+
+    /// Advance element transitions to `now_ms`.
+    ///
+    /// Renderers call this once per frame before drawing; it returns whether
+    /// any transition is still running, so the host keeps requesting frames.
+    pub fn tick(&mut self, now_ms: u64) -> bool {
+        self.transitions.tick(now_ms)
+    }
+
+    /// Opacity and scale to draw `id` with at the last [`tick`](Self::tick),
+    /// if it is entering or is one of the [`exiting_elements`](Self::exiting_elements).
+    #[must_use]
+    pub fn transition_frame(&self, id: ElementId) -> Option<TransitionFrame> {
+        self.transitions.frame(id)
+    }
+
+    /// Removed elements still fading out, for renderers to draw in place.
+    ///
+    /// They are no longer part of the scene.
+    pub fn exiting_elements(&self) -> impl Iterator<Item = &Element> {
+        self.transitions.exiting()
+    }
+
+    /// Apply a patch produced by [`SceneDocument::diff`](crate::SceneDocument::diff).
+    ///
+    /// Operations are applied in order. `Add` of an existing ID replaces the
+    /// element, `Update` of a missing ID inserts it, and `Remove` of a missing
+    /// ID is ignored, so applying the same patch twice is harmless.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an element ID in the patch is not a valid UUID.
+    /// Operations before the invalid one remain applied.
+    pub fn apply_patch(&mut self, patch: &ScenePatch) -> CanvasResult<()> {
+        if let Some(viewport) = patch.viewport {
+            self.set_viewport(viewport.width, viewport.height);
+            self.set_camera(viewport.zoom, viewport.pan_x, viewport.pan_y);
+        }
+
+        for op in &patch.ops {
+            match op {
+                PatchOp::Add { element } | PatchOp::Update { element } => {
+                    let element = element
+                        .clone()
+                        .into_element()
+                        .map_err(CanvasError::InvalidOperation)?;
+                    let id = element.id;
+                    let added = !self.elements.contains_key(&id);
+                    self.upsert_element(element);
+                    if added {
+                        self.transitions.enter(id);
+                    }
+                }
+                PatchOp::Remove { id } => {
+                    let id = ElementId::parse(id)
+                        .map_err(|e| CanvasError::InvalidOperation(e.to_string()))?;
+                    if let Ok(element) = self.remove_element(&id) {
+                        self.transitions.exit(element);
+                    }
+                }
+                PatchOp::Spotlight { spotlight } => match spotlight {
+                    Some(spotlight) => self.set_spotlight(spotlight.clone())?,
+                    None => {
+                        self.clear_spotlight();
+                    }
+                },
+                PatchOp::Anchors { anchors } => {
+                    for anchor in anchors {
+                        anchor.validate()?;
+                    }
+                    self.anchors.clone_from(anchors);
+                    self.changes.touch_settings();
+                }
+                PatchOp::Theme { theme } => self.set_theme(theme.clone())?,
+                PatchOp::Branding { branding } => match branding {
+                    Some(branding) => self.set_branding(branding.clone())?,
+                    None => {
+                        self.clear_branding();
+                    }
+                },
+                PatchOp::Coordinates { coordinates } => {
+                    self.set_coordinate_system(coordinates.clone())?;
+                }
+                PatchOp::Data { delta } => {
+                    self.apply_data_delta(delta)?;
+                }
+                PatchOp::LogAppend { id, lines } => {
+                    let id = ElementId::parse(id)
+                        .map_err(|e| CanvasError::InvalidOperation(e.to_string()))?;
+                    let element = self
+                        .get_element_mut(id)
+                        .ok_or_else(|| CanvasError::ElementNotFound(id.to_string()))?;
+                    let ElementKind::LogView {
+                        lines: log,
+                        max_lines,
+                        ..
+                    } = &mut element.kind
+                    else {
+                        return Err(CanvasError::InvalidOperation(format!(
+                            "Element {id} is not a LogView"
+                        )));
+                    };
+                    log_view::push_lines(log, lines, *max_lines);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Add the elements of a progressive-load page.
+    ///
+    /// Elements already in the scene are replaced, so pages can be applied
+    /// again after a reconnect.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an element cannot be materialized. Elements
+    /// before the invalid one remain applied.
+    pub fn apply_page(&mut self, page: &ScenePage) -> CanvasResult<()> {
+        for element in &page.elements {
+            let element = element
+                .clone()
+                .into_element()
+                .map_err(CanvasError::InvalidOperation)?;
+            self.upsert_element(element);
+        }
+        Ok(())
+    }
+
+    /// Insert an element, replacing any existing element with the same ID.
+    ///
+    /// The existing element's parent link is kept, and charts bound to a
+    /// dataset are filled in from it.
+    pub fn upsert_element(&mut self, mut element: Element) {
+        if let Some(existing) = self.elements.get_mut(&element.id) {
+            data::materialize(&mut element.kind, &self.data);
+            element.parent = existing.parent;
+            if element.selected && !self.selected.contains(&element.id) {
+                self.selected.push(element.id);
+            } else if !element.selected {
+                self.selected.retain(|&eid| eid != element.id);
+            }
+            let id = element.id;
+            *existing = element;
+            if let Some(stale) = &mut self.stale {
+                stale.insert(id);
+            }
+            self.changes.touch(id);
+            self.settle(&[id]);
+            self.refresh_index();
+        } else {
+            if element.selected {
+                self.selected.push(element.id);
+            }
+            self.add_element(element);
+        }
+    }
+
+    /// Serialize the scene to JSON.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if serialization fails.
+    pub fn to_json(&self) -> CanvasResult<String> {
+        serde_json::to_string(self).map_err(CanvasError::Serialization)
+    }
+
+    /// Deserialize a scene from JSON.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if deserialization fails.
+    pub fn from_json(json: &str) -> CanvasResult<Self> {
+        let mut scene: Self = serde_json::from_str(json).map_err(CanvasError::Serialization)?;
+        scene.refresh_index();
+        Ok(scene)
+    }
+}
+
+/// Intersect two `[x, y, width, height]` rectangles (empty results have zero size).
+fn intersect_rects(a: [f32; 4], b: [f32; 4]) -> [f32; 4] {
+    let x = a[0].max(b[0]);
+    let y = a[1].max(b[1]);
+    let right = (a[0] + a[2]).min(b[0] + b[2]);
+    let bottom = (a[1] + a[3]).min(b[1] + b[3]);
+    [x, y, (right - x).max(0.0), (bottom - y).max(0.0)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::annotation::{MeasurementKind, MeasurementLayout};
+    use crate::{Color, ElementKind, Transform};
+
+    #[test]
+    fn test_scene_add_remove() {
+        let mut scene = Scene::new(800.0, 600.0);
+        assert!(scene.is_empty());
+
+        let element = Element::new(ElementKind::Text {
+            content: "Hello".to_string(),
+            font_size: 16.0,
+            color: Color::BLACK.into(),
+        });
+        let id = scene.add_element(element);
+
+        assert_eq!(scene.element_count(), 1);
+        assert!(scene.get_element(id).is_some());
+
+        scene.remove_element(&id).expect("should remove");
+        assert!(scene.is_empty());
+    }
+
+    #[test]
+    fn test_spatial_queries_on_large_scene() {
+        let mut scene = Scene::new(800.0, 600.0);
+        let mut ids = Vec::new();
+        for row in 0..100_u8 {
+            for col in 0..100_u8 {
+                let mut element = Element::new(ElementKind::Text {
+                    content: String::new(),
+                    font_size: 12.0,
+                    color: Color::BLACK.into(),
+                })
+                .with_transform(Transform {
+                    x: f32::from(col) * 100.0,
+                    y: f32::from(row) * 100.0,
+                    width: 50.0,
+                    height: 50.0,
+                    ..Default::default()
+                });
+                element.interactive = true;
+                ids.push(scene.add_element(element));
+            }
+        }
+
+        let expected = scene.elements().filter(|e| scene.is_visible(e)).count();
+        assert_eq!(scene.visible_elements().count(), expected);
+        assert_eq!(expected, 63);
+        assert_eq!(scene.element_at(9_925.0, 9_925.0), Some(ids[9_999]));
+        assert_eq!(scene.element_at(75.0, 75.0), None);
+
+        // Moving through get_element_mut is seen before the index catches up
+        let moved = ids[0];
+        if let Some(element) = scene.get_element_mut(moved) {
+            element.transform.x = 5_060.0;
+            element.transform.y = 5_060.0;
+        }
+        assert_eq!(scene.element_at(25.0, 25.0), None);
+        assert_eq!(scene.element_at(5_080.0, 5_080.0), Some(moved));
+        scene.refresh_index();
+        assert_eq!(scene.element_at(5_080.0, 5_080.0), Some(moved));
+
+        // Round-tripping through JSON rebuilds the index
+        let restored = Scene::from_json(&scene.to_json().expect("serialize")).expect("parse");
+        assert_eq!(restored.element_at(5_080.0, 5_080.0), Some(moved));
+        let region = SceneBounds {
+            min_x: 0.0,
+            min_y: 0.0,
+            max_x: 250.0,
+            max_y: 150.0,
+        };
+        assert_eq!(restored.elements_in(&region).count(), 5);
+    }
+
+    #[test]
+    fn test_reparent_into_layer() {
+        let mut scene = Scene::new(800.0, 600.0);
+        let layer = scene.add_element(Element::new(ElementKind::OverlayLayer {
+            children: Vec::new(),
+            opacity: 0.0,
+            clip: false,
+        }));
+        let label = scene.add_element(Element::new(ElementKind::Text {
+            content: "Note".to_string(),
+            font_size: 16.0,
+            color: Color::BLACK.into(),
+        }));
+
+        scene.reparent(label, layer).expect("reparent");
+        assert_eq!(scene.root_elements().count(), 1);
+        assert_eq!(
+            scene.get_element(layer).map(|e| e.kind.children().to_vec()),
+            Some(vec![label])
+        );
+        assert_eq!(scene.get_element(label).and_then(|e| e.parent), Some(layer));
+
+        // Text is not a container, and a layer cannot contain itself
+        assert!(scene.reparent(layer, label).is_err());
+        assert!(scene.reparent(layer, layer).is_err());
+    }
+
+    #[test]
+    fn test_markup_pinned_to_image_follows_it() {
+        let mut scene = Scene::new(800.0, 600.0);
+        let image = scene.add_element(
+            Element::new(ElementKind::Image {
+                src: "shot.png".to_string(),
+                format: crate::ImageFormat::Png,
+                annotations: Vec::new(),
+                extracted_text: None,
+            })
+            .with_transform(Transform {
+                x: 100.0,
+                y: 100.0,
+                width: 200.0,
+                height: 100.0,
+                rotation: 0.0,
+                z_index: 2,
+                auto_size: false,
+            }),
+        );
+        let arrow = scene
+            .pin_markup(
+                image,
+                annotation::measurement([150.0, 150.0], [200.0, 150.0], MeasurementKind::Arrow),
+            )
+            .expect("pin arrow");
+        assert_eq!(
+            scene.get_element(arrow).map(|e| e.transform.z_index),
+            Some(3)
+        );
+        assert_eq!(scene.get_element(arrow).and_then(|e| e.parent), Some(image));
+
+        // Moving and doubling the image carries the arrow along
+        let moved = scene
+            .update_element(image, |e| {
+                e.transform.x = 0.0;
+                e.transform.width = 400.0;
+            })
+            .expect("update");
+        assert_eq!(moved, vec![arrow]);
+        let layout = scene
+            .get_element(arrow)
+            .and_then(MeasurementLayout::from_element)
+            .expect("layout");
+        assert!((layout.start[0] - 100.0).abs() < 1e-3);
+        assert!((layout.end[0] - 200.0).abs() < 1e-3);
+
+        // Only markup can be pinned, and only to images
+        let video = Element::new(ElementKind::Video {
+            stream_id: "cam".to_string(),
+            is_live: true,
+            mirror: false,
+            crop: None,
+            media_config: None,
+        });
+        assert!(scene.pin_markup(image, video).is_err());
+        let note = scene.add_element(Element::new(ElementKind::Text {
+            content: "Note".to_string(),
+            font_size: 16.0,
+            color: Color::BLACK.into(),
+        }));
+        let mark = annotation::measurement([0.0, 0.0], [10.0, 0.0], MeasurementKind::Arrow);
+        assert!(scene.pin_markup(note, mark).is_err());
+        assert!(scene.reparent(note, image).is_ok());
+    }
+
+    #[test]
+    fn test_extracted_text_makes_images_searchable() {
+        use crate::ocr::OcrText;
+
+        let mut scene = Scene::new(800.0, 600.0);
+        let image = scene.add_element(Element::new(ElementKind::Image {
+            src: "asset:sha256-00".to_string(),
+            format: crate::ImageFormat::Png,
+            annotations: Vec::new(),
+            extracted_text: None,
+        }));
+        let note = scene.add_element(Element::new(ElementKind::Text {
+            content: "Note".to_string(),
+            font_size: 16.0,
+            color: Color::BLACK.into(),
+        }));
+        assert!(crate::match_elements(&scene, "gateway timeout").is_empty());
+
+        let revision = scene.revision();
+        scene
+            .set_extracted_text(image, OcrText::new("502 Gateway Timeout", "test"))
+            .expect("store text");
+        assert_eq!(scene.changes_since(revision).changed, vec![image]);
+        assert_eq!(
+            crate::match_elements(&scene, "gateway timeout"),
+            vec![image]
+        );
+        assert!(scene
+            .set_extracted_text(note, OcrText::new("Note", "test"))
+            .is_err());
+        assert!(scene
+            .set_extracted_text(ElementId::new(), OcrText::new("", "test"))
+            .is_err());
+    }
+
+    #[test]
+    fn test_group_and_ungroup_keep_transforms() {
+        let mut scene = Scene::new(800.0, 600.0);
+        let frame = scene.add_element(Element::new(ElementKind::OverlayLayer {
+            children: Vec::new(),
+            opacity: 1.0,
+            clip: false,
+        }));
+        let note = |x: f32, y: f32, z_index: i32| {
+            Element::new(ElementKind::StickyNote {
+                content: "Idea".to_string(),
+                color: Color::from_rgb(0xff_f59d).into(),
+                author: None,
+            })
+            .with_transform(Transform {
+                x,
+                y,
+                width: 100.0,
+                height: 80.0,
+                rotation: 0.0,
+                z_index,
+                auto_size: false,
+            })
+        };
+        let a = scene.add_element(note(10.0, 20.0, 3));
+        let b = scene.add_element(note(200.0, 150.0, 1));
+        let loose = scene.add_element(note(400.0, 400.0, 0));
+        scene.reparent(a, frame).expect("reparent");
+        scene.reparent(b, frame).expect("reparent");
+        let before: Vec<Transform> = [a, b]
+            .iter()
+            .filter_map(|id| scene.get_element(*id).map(|e| e.transform))
+            .collect();
+
+        // Members must share a parent
+        assert!(scene.group(&[a, loose]).is_err());
+        assert!(scene.group(&[]).is_err());
+
+        let group = scene.group(&[a, b, a]).expect("group");
+        let element = scene.get_element(group).expect("group element");
+        assert_eq!(element.parent, Some(frame));
+        assert_eq!(element.kind.children(), &[a, b]);
+        assert_eq!((element.transform.x, element.transform.y), (10.0, 20.0));
+        assert_eq!(
+            (element.transform.width, element.transform.height),
+            (290.0, 210.0)
+        );
+        assert_eq!(element.transform.z_index, 1);
+        assert_eq!(
+            scene.get_element(frame).map(|e| e.kind.children().to_vec()),
+            Some(vec![group])
+        );
+
+        // Nested groups dissolve into the enclosing container
+        assert_eq!(scene.ungroup(group).expect("ungroup"), vec![a, b]);
+        assert!(scene.get_element(group).is_none());
+        assert_eq!(
+            scene.get_element(frame).map(|e| e.kind.children().to_vec()),
+            Some(vec![a, b])
+        );
+        let after: Vec<Transform> = [a, b]
+            .iter()
+            .filter_map(|id| scene.get_element(*id).map(|e| e.transform))
+            .collect();
+        assert_eq!(before, after);
+        assert!(scene.validate().is_empty());
+        assert!(scene.ungroup(a).is_err());
+
+        // Root-level groups dissolve back to the root
+        let root_group = scene.group(&[loose]).expect("group");
+        assert_eq!(scene.ungroup(root_group).expect("ungroup"), vec![loose]);
+        assert!(scene.root_elements().any(|e| e.id == loose));
+        assert!(scene.validate().is_empty());
+    }
+
+    #[test]
+    fn test_element_at() {
+        let mut scene = Scene::new(800.0, 600.0);
+
+        let element = Element::new(ElementKind::Text {
+            content: "Test".to_string(),
+            font_size: 16.0,
+            color: Color::BLACK.into(),
+        })
+        .with_transform(Transform {
+            x: 100.0,
+            y: 100.0,
+            width: 200.0,
+            height: 50.0,
+            rotation: 0.0,
+            z_index: 0,
+            auto_size: false,
+        });
+
+        scene.add_element(element);
+
+        // Point inside element
+        assert!(scene.element_at(150.0, 125.0).is_some());
+
+        // Point outside element
+        assert!(scene.element_at(50.0, 50.0).is_none());
+    }
+
+    #[test]
+    fn test_elements_near_orders_by_distance() {
+        let mut scene = Scene::new(800.0, 600.0);
+        let square = |x: f32, z_index: i32| {
+            Element::new(ElementKind::Text {
+                content: "Test".to_string(),
+                font_size: 16.0,
+                color: Color::BLACK.into(),
+            })
+            .with_transform(Transform {
+                x,
+                y: 100.0,
+                width: 40.0,
+                height: 40.0,
+                z_index,
+                ..Transform::default()
+            })
+        };
+        let under = scene.add_element(square(100.0, 0));
+        let over = scene.add_element(square(110.0, 1));
+        let beside = scene.add_element(square(160.0, 0));
+        scene.add_element(square(400.0, 0));
+
+        let near = |scene: &Scene, x, y| {
+            scene
+                .elements_near(x, y, 24.0)
+                .into_iter()
+                .map(|(e, _)| e.id)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(near(&scene, 145.0, 120.0), vec![over, under, beside]);
+
+        // The radius is in screen pixels, so zooming in narrows it on the canvas
+        scene.zoom = 4.0;
+        assert_eq!(near(&scene, 580.0, 480.0), vec![over, under]);
+    }
+
+    #[test]
+    fn test_elements_in_region_uses_centers() {
+        let mut scene = Scene::new(800.0, 600.0);
+        let square = |x: f32, z_index: i32| {
+            Element::new(ElementKind::Text {
+                content: "Test".to_string(),
+                font_size: 16.0,
+                color: Color::BLACK.into(),
+            })
+            .with_transform(Transform {
+                x,
+                y: 100.0,
+                width: 40.0,
+                height: 40.0,
+                z_index,
+                ..Transform::default()
+            })
+        };
+        let under = scene.add_element(square(100.0, 0));
+        let over = scene.add_element(square(150.0, 1));
+        // Overlapped by the loop, but its center is outside
+        scene.add_element(square(190.0, 0));
+
+        let lasso = [[90.0, 90.0], [200.0, 90.0], [200.0, 150.0], [90.0, 150.0]];
+        let ids = |scene: &Scene| {
+            scene
+                .elements_in_region(&lasso)
+                .into_iter()
+                .map(|e| e.id)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(ids(&scene), vec![over, under]);
+        assert!(scene.elements_in_region(&[]).is_empty());
+
+        // The loop is in screen pixels, so panning moves what it circles
+        scene.pan_x = 50.0;
+        assert_eq!(ids(&scene), vec![under]);
+    }
+
+    #[test]
+    fn test_screen_anchored_elements_ignore_camera() {
+        let mut scene = Scene::new(800.0, 600.0);
+        let rect = |x: f32| Transform {
+            x,
+            y: 10.0,
+            width: 100.0,
+            height: 40.0,
+            rotation: 0.0,
+            z_index: 0,
+            auto_size: false,
+        };
+        let badge = scene.add_element(
+            Element::new(ElementKind::Text {
+                content: "LIVE".to_string(),
+                font_size: 16.0,
+                color: Color::BLACK.into(),
+            })
+            .with_transform(rect(10.0))
+            .with_screen_anchored(true),
+        );
+        let card = scene.add_element(
+            Element::new(ElementKind::Text {
+                content: "Card".to_string(),
+                font_size: 16.0,
+                color: Color::BLACK.into(),
+            })
+            .with_transform(rect(10.0)),
+        );
+        scene.set_camera(2.0, -1000.0, 0.0);
+
+        // The badge stays at its viewport pixels; the card has scrolled off
+        assert_eq!(scene.element_at(50.0, 30.0), Some(badge));
+        let visible: Vec<ElementId> = scene.visible_elements().map(|e| e.id).collect();
+        assert_eq!(visible, vec![badge]);
+        assert!(scene.is_visible(scene.get_element(badge).unwrap()));
+        assert!(!scene.is_visible(scene.get_element(card).unwrap()));
+
+        // Canvas queries only see the card
+        scene.set_camera(1.0, 0.0, 0.0);
+        let ids: Vec<ElementId> = scene
+            .elements_in(&SceneBounds::from_transform(&rect(10.0)))
+            .map(|e| e.id)
+            .collect();
+        assert_eq!(ids, vec![card]);
+        assert_eq!(
+            scene.content_bounds(),
+            Some(SceneBounds::from_transform(&rect(10.0)))
+        );
+
+        // The badge is above the canvas even with a lower z-index
+        assert_eq!(scene.element_at(50.0, 30.0), Some(badge));
+    }
+
+    #[test]
+    fn test_element_at_respects_rotation() {
+        let mut scene = Scene::new(800.0, 600.0);
+        let id = scene.add_element(
+            Element::new(ElementKind::Text {
+                content: "Tilted".to_string(),
+                font_size: 16.0,
+                color: Color::BLACK.into(),
+            })
+            .with_transform(Transform {
+                x: 100.0,
+                y: 100.0,
+                width: 200.0,
+                height: 20.0,
+                rotation: std::f32::consts::FRAC_PI_2,
+                z_index: 0,
+                auto_size: false,
+            }),
+        );
+
+        // Turned upright about its center (200, 110)
+        assert!(scene.element_at(110.0, 110.0).is_none());
+        assert_eq!(scene.element_at(200.0, 20.0), Some(id));
+
+        // Culling and region queries see the same shape
+        assert!(scene.is_visible(scene.get_element(id).unwrap()));
+        let above = SceneBounds {
+            min_x: 190.0,
+            min_y: 0.0,
+            max_x: 210.0,
+            max_y: 15.0,
+        };
+        assert_eq!(scene.elements_in(&above).count(), 1);
+        scene.pan_y = -500.0;
+        scene.zoom = 2.0;
+        assert!(!scene.is_visible(scene.get_element(id).unwrap()));
+    }
+
+    #[test]
+    fn test_clip_rects() {
+        let mut scene = Scene::new(800.0, 600.0);
+        let text = |x: f32| {
+            Element::new(ElementKind::Text {
+                content: "Clipped".to_string(),
+                font_size: 16.0,
+                color: Color::BLACK.into(),
+            })
+            .with_transform(Transform {
+                x,
+                y: 0.0,
+                width: 50.0,
+                height: 50.0,
+                rotation: 0.0,
+                z_index: 1,
+                auto_size: false,
+            })
+        };
+        let inner_child = scene.add_element(text(20.0));
+        let outer_child = scene.add_element(text(300.0));
+
+        let inner = scene.add_element(
+            Element::new(ElementKind::Group {
+                children: vec![inner_child],
+                clip: true,
+                opacity: 1.0,
+            })
+            .with_transform(Transform {
+                x: 10.0,
+                y: 10.0,
+                width: 200.0,
+                height: 200.0,
+                rotation: 0.0,
+                z_index: 0,
+                auto_size: false,
+            }),
+        );
+        scene.add_element(
+            Element::new(ElementKind::OverlayLayer {
+                children: vec![inner, outer_child],
+                opacity: 1.0,
+                clip: true,
+            })
+            .with_transform(Transform {
+                x: 0.0,
+                y: 0.0,
+                width: 100.0,
+                height: 100.0,
+                rotation: 0.0,
+                z_index: 0,
+                auto_size: false,
+            }),
+        );
+
+        let clips = scene.clip_rects();
+        assert_eq!(clips.get(&outer_child), Some(&[0.0, 0.0, 100.0, 100.0]));
+        assert_eq!(clips.get(&inner), Some(&[0.0, 0.0, 100.0, 100.0]));
+        // Nested clip is the intersection of both containers
+        assert_eq!(clips.get(&inner_child), Some(&[10.0, 10.0, 90.0, 90.0]));
+    }
+
+    #[test]
+    fn test_group_fields_default_when_missing() {
+        let kind: ElementKind =
+            serde_json::from_str(r#"{"type": "Group", "data": {"children": []}}"#)
+                .expect("legacy group");
+        assert!(!kind.clips_children());
+        assert_eq!(kind.group_opacity(), None);
+
+        let kind: ElementKind = serde_json::from_str(
+            r#"{"type": "Group", "data": {"children": [], "clip": true, "opacity": 0.5}}"#,
+        )
+        .expect("group with clip");
+        assert!(kind.clips_children());
+        assert_eq!(kind.group_opacity(), Some(0.5));
+    }
+
+    #[test]
+    fn test_diff_and_apply_patch() {
+        use crate::SceneDocument;
+
+        let text = |content: &str| {
+            Element::new(ElementKind::Text {
+                content: content.to_string(),
+                font_size: 16.0,
+                color: Color::BLACK.into(),
+            })
+        };
+
+        let mut before = Scene::new(800.0, 600.0);
+        let kept = before.add_element(text("kept"));
+        let changed = before.add_element(text("old"));
+        let removed = before.add_element(text("removed"));
+
+        let mut after = before.clone();
+        after.remove_element(&removed).expect("should remove");
+        if let Some(element) = after.get_element_mut(changed) {
+            element.transform.x = 42.0;
+        }
+        let added = after.add_element(text("added"));
+        after.zoom = 2.0;
+
+        let old_doc = SceneDocument::from_scene("s", &before, 1);
+        let new_doc = SceneDocument::from_scene("s", &after, 2);
+        let patch = old_doc.diff(&new_doc);
+
+        assert_eq!(patch.ops.len(), 3);
+        assert!(patch.viewport.is_some());
+        assert!(old_doc.diff(&old_doc).is_empty());
+
+        let mut patched = before.clone();
+        patched.apply_patch(&patch).expect("should apply");
+        assert_eq!(patched.element_count(), 3);
+        assert!(patched.get_element(kept).is_some());
+        assert!(patched.get_element(removed).is_none());
+        assert!(patched.get_element(added).is_some());
+        assert!(
+            (patched.get_element(changed).map_or(0.0, |e| e.transform.x) - 42.0).abs()
+                < f32::EPSILON
+        );
+        assert!((patched.zoom - 2.0).abs() < f32::EPSILON);
+
+        // Patches are idempotent
+        patched.apply_patch(&patch).expect("should reapply");
+        assert_eq!(patched.element_count(), 3);
+        let reapplied = SceneDocument::from_scene("s", &patched, 2);
+        assert!(new_doc.diff(&reapplied).is_empty());
+    }
+
+    #[test]
+    fn test_apply_patch_rejects_invalid_id() {
+        use crate::{PatchOp, ScenePatch};
+
+        let mut scene = Scene::new(800.0, 600.0);
+        let patch = ScenePatch {
+            session_id: "s".to_string(),
+            viewport: None,
+            ops: vec![PatchOp::Remove {
+                id: "not-a-uuid".to_string(),
+            }],
+            timestamp: 0,
+        };
+        assert!(scene.apply_patch(&patch).is_err());
+    }
+
+    #[test]
+    fn test_synced_adds_and_removes_transition() {
+        use crate::transition::{TransitionConfig, TransitionEffect};
+        use crate::{PatchOp, SceneDocument, ScenePatch};
+
+        let text = |content: &str| {
+            Element::new(ElementKind::Text {
+                content: content.to_string(),
+                font_size: 16.0,
+                color: Color::BLACK.into(),
+            })
+        };
+        let mut scene = Scene::new(800.0, 600.0);
+        let old = scene.add_element(text("Old"));
+        // Local edits and the default config never animate
+        assert!(scene.transition_frame(old).is_none());
+        scene.set_transition_config(
+            TransitionConfig::default()
+                .with_duration_ms(100)
+                .with_enter(TransitionEffect::FadeScale)
+                .with_exit(TransitionEffect::Fade),
+        );
+
+        let new = text("New");
+        let new_id = new.id;
+        let revision = scene.revision();
+        let patch = ScenePatch {
+            session_id: "s".to_string(),
+            viewport: None,
+            ops: vec![
+                PatchOp::Add {
+                    element: (&new).into(),
+                },
+                PatchOp::Remove {
+                    id: old.to_string(),
+                },
+            ],
+            timestamp: 0,
+        };
+        scene.apply_patch(&patch).expect("patch");
+        assert!(scene.get_element(old).is_none());
+        assert_eq!(
+            scene.exiting_elements().map(|e| e.id).collect::<Vec<_>>(),
+            vec![old]
+        );
+
+        assert!(scene.tick(500));
+        assert!(scene.tick(550));
+        let entering = scene.transition_frame(new_id).expect("entering");
+        assert!(entering.opacity > 0.5 && entering.opacity < 1.0);
+        assert!(scene.transition_frame(old).expect("exiting").opacity < 0.5);
+        assert!(!scene.tick(600));
+        assert!(scene.transition_frame(new_id).is_none());
+        assert_eq!(scene.exiting_elements().count(), 0);
+        // Ticking is presentation only
+        let changes = scene.changes_since(revision);
+        assert_eq!(
+            (changes.changed, changes.removed),
+            (vec![new_id], vec![old])
+        );
+
+        // A replacement document eases in what it adds
+        let mut next = scene.clone();
+        let added = next.add_element(text("Later"));
+        let mut replacement = SceneDocument::from_scene("s", &next, 0)
+            .into_scene()
+            .expect("scene");
+        replacement.supersede(&scene);
+        assert!(replacement.transition_frame(added).is_some());
+        assert!(replacement.transition_frame(new_id).is_none());
+    }
+
+    #[test]
+    fn test_anchors_sync_and_move_the_camera() {
+        use crate::{Anchor, SceneDocument};
+
+        let mut before = Scene::new(800.0, 600.0);
+        before
+            .set_anchor(Anchor::new("summary", 400.0, 300.0, 1.0))
+            .expect("anchor");
+
+        let mut after = before.clone();
+        after
+            .set_anchor(Anchor::new("details", 1200.0, 300.0, 2.0))
+            .expect("anchor");
+        // Saving an existing name replaces it in place
+        after
+            .set_anchor(Anchor::new("summary", 400.0, 350.0, 0.5))
+            .expect("anchor");
+        assert!(after.set_anchor(Anchor::new("", 0.0, 0.0, 1.0)).is_err());
+        let names: Vec<_> = after.anchors().iter().map(|a| a.name.as_str()).collect();
+        assert_eq!(names, ["summary", "details"]);
+
+        let patch = SceneDocument::from_scene("s", &before, 1)
+            .diff(&SceneDocument::from_scene("s", &after, 2));
+        assert_eq!(patch.ops.len(), 1);
+        let mut patched = before.clone();
+        patched.apply_patch(&patch).expect("should apply");
+        assert_eq!(patched.anchors(), after.anchors());
+
+        let frame = patched.go_to_anchor("details").expect("go");
+        assert!((patched.zoom - 2.0).abs() < f32::EPSILON);
+        assert!((frame.pan_x - (400.0 - 2400.0)).abs() < f32::EPSILON);
+        assert!(patched.go_to_anchor("missing").is_err());
+
+        patched.clear();
+        assert_eq!(patched.anchors().len(), 2);
+        assert_eq!(
+            patched.remove_anchor("summary").map(|a| a.name),
+            Some("summary".to_string())
+        );
+        assert!(patched.remove_anchor("summary").is_none());
+    }
+
+    #[test]
+    fn test_theme_changes_restyle_referencing_elements() {
+        use crate::{SceneDocument, Theme, ThemeColor};
+
+        let mut before = Scene::new(800.0, 600.0);
+        let note = before.add_element(Element::new(ElementKind::StickyNote {
+            content: "Idea".to_string(),
+            color: ThemeColor::variable("accent"),
+            author: None,
+        }));
+        // Undefined references fall back to the kind's default
+        let color = |scene: &Scene| {
+            scene
+                .get_element(note)
+                .and_then(|e| e.kind.resolved_color(scene.theme()))
+        };
+        assert_eq!(color(&before), Some(ElementKind::NOTE_COLOR));
+
+        let mut after = before.clone();
+        after
+            .set_theme(
+                Theme::default()
+                    .with_palette("brand", vec![Color::from_rgb(0x19_76d2)])
+                    .with_variable("accent", ThemeColor::palette("brand", 0)),
+            )
+            .expect("theme");
+        assert_eq!(color(&after), Some(Color::from_rgb(0x19_76d2)));
+        assert!(after
+            .set_theme(Theme::default().with_palette("brand", Vec::new()))
+            .is_err());
+
+        let patch = SceneDocument::from_scene("s", &before, 1)
+            .diff(&SceneDocument::from_scene("s", &after, 2));
+        assert!(matches!(patch.ops.as_slice(), [PatchOp::Theme { .. }]));
+        let mut patched = before.clone();
+        patched.apply_patch(&patch).expect("should apply");
+        assert_eq!(patched.theme(), after.theme());
+
+        patched.clear();
+        assert!(!patched.theme().is_empty());
+    }
+
+    #[test]
+    fn test_log_appends_sync_as_log_append_ops() {
+        use crate::{log_view, SceneDocument};
+
+        let mut before = Scene::new(800.0, 600.0);
+        let log = before.add_element(Element::new(ElementKind::LogView {
+            title: "cargo test".to_string(),
+            lines: vec!["one".to_string(), "two".to_string()],
+            max_lines: 3,
+            follow: true,
+        }));
+        let mut after = before.clone();
+        after
+            .update_element(log, |e| {
+                log_view::append(&mut e.kind, "three\nfour\n").expect("append");
+            })
+            .expect("update");
+
+        let patch = SceneDocument::from_scene("s", &before, 1)
+            .diff(&SceneDocument::from_scene("s", &after, 2));
+        let [PatchOp::LogAppend { lines, .. }] = patch.ops.as_slice() else {
+            panic!("expected a log append, got {:?}", patch.ops);
+        };
+        assert_eq!(lines, &["three", "four"]);
+        let mut patched = before.clone();
+        patched.apply_patch(&patch).expect("should apply");
+        assert_eq!(
+            patched.get_element(log).map(|e| &e.kind),
+            after.get_element(log).map(|e| &e.kind)
+        );
+
+        // Anything beyond appending falls back to a full update
+        let mut retitled = after.clone();
+        retitled
+            .update_element(log, |e| {
+                if let ElementKind::LogView { title, .. } = &mut e.kind {
+                    *title = "cargo build".to_string();
+                }
+            })
+            .expect("update");
+        let patch = SceneDocument::from_scene("s", &after, 2)
+            .diff(&SceneDocument::from_scene("s", &retitled, 3));
+        assert!(matches!(patch.ops.as_slice(), [PatchOp::Update { .. }]));
+
+        // Appends target log views only
+        let note = patched.add_element(Element::new(ElementKind::Text {
+            content: "Note".to_string(),
+            font_size: 16.0,
+            color: Color::BLACK.into(),
+        }));
+        let bad = ScenePatch {
+            session_id: "s".to_string(),
+            viewport: None,
+            ops: vec![PatchOp::LogAppend {
+                id: note.to_string(),
+                lines: vec!["x".to_string()],
+            }],
+            timestamp: 3,
+        };
+        assert!(patched.apply_patch(&bad).is_err());
+    }
+
+    #[test]
+    fn test_coordinate_system_syncs_through_patches() {
+        use crate::{CoordinateSystem, SceneDocument, WorldUnit, YAxis};
+
+        let before = Scene::new(800.0, 600.0);
+        let mut after = before.clone();
+        let cad = CoordinateSystem::pixels()
+            .with_unit(WorldUnit::Mm)
+            .with_origin(0.0, 600.0)
+            .with_y_axis(YAxis::Up);
+        after.set_coordinate_system(cad.clone()).expect("units");
+
+        let document = SceneDocument::from_scene("s", &after, 2);
+        assert_eq!(document.coordinates, cad);
+        let patch = SceneDocument::from_scene("s", &before, 1).diff(&document);
+        assert!(matches!(
+            patch.ops.as_slice(),
+            [PatchOp::Coordinates { .. }]
+        ));
+        let mut patched = before.clone();
+        patched.apply_patch(&patch).expect("should apply");
+        assert_eq!(patched.coordinate_system(), &cad);
+        assert_eq!(
+            document.into_scene().expect("scene").coordinate_system(),
+            &cad
+        );
+
+        // Plain pixels are left out of documents
+        let json = serde_json::to_value(SceneDocument::from_scene("s", &before, 1)).unwrap();
+        assert!(json.get("coordinates").is_none());
+        assert!(patched
+            .set_coordinate_system(CoordinateSystem::pixels().with_origin(f32::INFINITY, 0.0))
+            .is_err());
+    }
+
+    #[test]
+    fn test_measurements_read_in_scene_units() {
+        use crate::{CoordinateSystem, WorldUnit};
+
+        let mut scene = Scene::new(800.0, 600.0);
+        scene
+            .set_coordinate_system(CoordinateSystem::pixels().with_unit(WorldUnit::Cm))
+            .expect("units");
+        let ruler = scene.add_element(annotation::measurement_in(
+            [10.0, 10.0],
+            [10.0, 10.0 + 96.0 / 2.54 * 4.0],
+            MeasurementKind::Ruler,
+            scene.coordinate_system(),
+        ));
+        scene.add_element(annotation::measurement(
+            [0.0, 0.0],
+            [50.0, 0.0],
+            MeasurementKind::Arrow,
+        ));
+
+        let measurements = scene.measurements();
+        assert_eq!(measurements.len(), 1);
+        let (id, reading) = &measurements[0];
+        assert_eq!(*id, ruler);
+        assert!((reading.length - 4.0).abs() < 1e-3);
+        assert!((reading.angle + 90.0).abs() < 1e-3);
+        assert_eq!(reading.unit, "cm");
+        assert!((scene.measure([0.0, 0.0], [96.0, 0.0]).length - 2.54).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_branding_syncs_through_patches() {
+        use crate::{Branding, SceneDocument};
+
+        let before = Scene::new(800.0, 600.0);
+        let mut after = before.clone();
+        assert!(after.set_branding(Branding::default()).is_err());
+        after
+            .set_branding(Branding::default().with_watermark("CONFIDENTIAL"))
+            .expect("branding");
+
+        let diff = |a: &Scene, b: &Scene| {
+            SceneDocument::from_scene("s", a, 1).diff(&SceneDocument::from_scene("s", b, 2))
+        };
+        let patch = diff(&before, &after);
+        assert!(matches!(patch.ops.as_slice(), [PatchOp::Branding { .. }]));
+        let mut patched = before.clone();
+        patched.apply_patch(&patch).expect("should apply");
+        assert_eq!(patched.branding(), after.branding());
+
+        // Branding is not content, so clearing the scene keeps it
+        patched.clear();
+        assert!(patched.branding().is_some());
+
+        patched.apply_patch(&diff(&after, &before)).expect("clear");
+        assert!(patched.branding().is_none());
+    }
+
+    #[test]
+    fn test_dataset_changes_refill_bound_charts() {
+        use crate::{DataBinding, DataQuery, Dataset, SceneDocument};
+
+        let mut before = Scene::new(800.0, 600.0);
+        before
+            .apply_data_delta(&DatasetDelta::Put {
+                dataset: Dataset::new(
+                    "sales",
+                    vec!["month".to_string(), "revenue".to_string()],
+                    vec![
+                        vec!["Jan".into(), 100.into()],
+                        vec!["Feb".into(), 120.into()],
+                    ],
+                ),
+            })
+            .expect("put");
+        let chart = before.add_element(Element::new(ElementKind::Chart {
+            chart_type: "bar".to_string(),
+            data: serde_json::json!({ "title": "Revenue" }),
+            binding: Some(DataBinding::new("sales").with_query(DataQuery {
+                x: Some("month".to_string()),
+                ..DataQuery::default()
+            })),
+        }));
+        let labels = |scene: &Scene| match scene.get_element(chart).map(|e| &e.kind) {
+            Some(ElementKind::Chart { data, .. }) => data["x_labels"].clone(),
+            _ => serde_json::Value::Null,
+        };
+        assert_eq!(labels(&before), serde_json::json!(["Jan", "Feb"]));
+
+        let mut after = before.clone();
+        let refreshed = after
+            .apply_data_delta(&DatasetDelta::Append {
+                id: "sales".to_string(),
+                rows: vec![vec!["Mar".into(), 90.into()]],
+                keep_last: None,
+            })
+            .expect("append");
+        assert_eq!(refreshed, vec![chart]);
+        assert_eq!(labels(&after), serde_json::json!(["Jan", "Feb", "Mar"]));
+
+        // Only the new row travels; the receiver refills the chart itself
+        let patch = SceneDocument::from_scene("s", &before, 1)
+            .diff(&SceneDocument::from_scene("s", &after, 2));
+        assert!(matches!(
+            patch.ops.as_slice(),
+            [PatchOp::Data {
+                delta: DatasetDelta::Append { .. }
+            }]
+        ));
+        let mut patched = before.clone();
+        patched.apply_patch(&patch).expect("should apply");
+        assert_eq!(labels(&patched), labels(&after));
+
+        // Documents carry datasets, so a reload fills charts the same way
+        let reloaded = SceneDocument::from_scene("s", &after, 3)
+            .into_scene()
+            .expect("should load");
+        assert_eq!(labels(&reloaded), labels(&after));
+
+        patched.clear();
+        assert_eq!(patched.data().len(), 1);
+        assert!(patched
+            .apply_data_delta(&DatasetDelta::Append {
+                id: "missing".to_string(),
+                rows: Vec::new(),
+                keep_last: None,
+            })
+            .is_err());
+    }
+
+    #[test]
+    fn test_paginate_sends_visible_elements_first() {
+        use crate::SceneDocument;
+
+        let mut scene = Scene::new(800.0, 600.0);
+        let at = |x: f32| {
+            Element::new(ElementKind::Text {
+                content: String::new(),
+                font_size: 16.0,
+                color: Color::BLACK.into(),
+            })
+            .with_transform(Transform {
+                x,
+                y: 100.0,
+                width: 50.0,
+                height: 50.0,
+                rotation: 0.0,
+                z_index: 0,
+                auto_size: false,
+            })
+        };
+        let far = scene.add_element(at(5000.0));
+        let near = scene.add_element(at(1000.0));
+        let visible = scene.add_element(at(100.0));
+
+        let document = SceneDocument::from_scene("s", &scene, 1);
+        let (first, pages) = document.clone().paginate(1);
+        assert_eq!(first.elements[0].id, visible.to_string());
+        assert_eq!(pages.len(), 2);
+        assert_eq!(pages[0].elements[0].id, near.to_string());
+        assert_eq!(pages[1].elements[0].id, far.to_string());
+        assert!(!pages[0].is_last());
+        assert!(pages[1].is_last());
+        assert_eq!(pages[1].total, 3);
+
+        let mut loaded = first.into_scene().expect("should load first page");
+        assert_eq!(loaded.element_count(), 1);
+        for page in &pages {
+            loaded.apply_page(page).expect("should apply page");
+        }
+        assert_eq!(loaded.element_count(), 3);
+
+        // Small scenes are sent whole
+        let (whole, pages) = document.paginate(10);
+        assert_eq!(whole.elements.len(), 3);
+        assert!(pages.is_empty());
+    }
+
+    #[test]
+    fn test_callout_tail_follows_target() {
+        let mut scene = Scene::new(800.0, 600.0);
+        let chart = scene.add_element(
+            Element::new(ElementKind::Chart {
+                chart_type: "bar".to_string(),
+                data: serde_json::json!({}),
+                binding: None,
+            })
+            .with_transform(Transform {
+                x: 300.0,
+                y: 100.0,
+                width: 100.0,
+                height: 100.0,
+                rotation: 0.0,
+                z_index: 0,
+                auto_size: false,
+            }),
+        );
+        let callout = scene.add_element(
+            Element::new(ElementKind::Callout {
+                content: "Peak here".to_string(),
+                color: Color::WHITE.into(),
+                author: Some("agent".to_string()),
+                target: Some(CalloutTarget::Element { element_id: chart }),
+            })
+            .with_transform(Transform {
+                x: 0.0,
+                y: 100.0,
+                width: 100.0,
+                height: 50.0,
+                rotation: 0.0,
+                z_index: 0,
+                auto_size: false,
+            }),
+        );
+
+        assert_eq!(scene.callout_tip(callout), Some((350.0, 150.0)));
+        let [a, b, tip] = scene.callout_tail(callout).expect("tail");
+        assert_eq!(tip, (350.0, 150.0));
+        // The tail leaves through the right edge, toward the chart
+        assert_eq!((a.0, b.0), (100.0, 100.0));
+        assert!(a.1 < b.1);
+
+        scene.remove_element(&chart).expect("remove");
+        assert!(scene.callout_tail(callout).is_none());
+
+        let kind: ElementKind =
+            serde_json::from_str(r#"{"type":"StickyNote","data":{"content":"Todo"}}"#)
+                .expect("defaults");
+        assert_eq!(kind.note(), Some(("Todo", None)));
+    }
+
+    #[test]
+    fn test_attached_elements_follow_their_target() {
+        use crate::{AttachPoint, Attachment};
+
+        let text = |content: &str, x: f32, y: f32| {
+            Element::new(ElementKind::Text {
+                content: content.to_string(),
+                font_size: 14.0,
+                color: Color::BLACK.into(),
+            })
+            .with_transform(Transform {
+                x,
+                y,
+                width: 20.0,
+                height: 20.0,
+                ..Transform::default()
+            })
+        };
+        let mut scene = Scene::new(800.0, 600.0);
+        let card = scene.add_element(text("Card", 100.0, 100.0).with_transform(Transform {
+            x: 100.0,
+            y: 100.0,
+            width: 200.0,
+            height: 80.0,
+            ..Transform::default()
+        }));
+        let badge = scene.add_element(
+            text("3", 0.0, 0.0).with_attachment(Attachment::new(card, AttachPoint::TopRight)),
+        );
+        assert_eq!(
+            scene
+                .get_element(badge)
+                .map(|e| (e.transform.x, e.transform.y)),
+            Some((290.0, 90.0))
+        );
+
+        // A label under the badge follows through the chain
+        let label = scene.add_element(text("new", 0.0, 0.0));
+        let moved = scene
+            .attach(
+                label,
+                Some(
+                    Attachment::new(badge, AttachPoint::Bottom)
+                        .with_origin(AttachPoint::Top)
+                        .with_offset(0.0, 4.0),
+                ),
+            )
+            .expect("attach");
+        assert_eq!(moved, vec![label]);
+        assert!(scene
+            .attach(card, Some(Attachment::new(label, AttachPoint::Center)))
+            .is_err());
+
+        let revision = scene.revision();
+        let moved = scene
+            .update_element(card, |e| e.transform.x += 50.0)
+            .expect("move");
+        assert_eq!(moved.len(), 2);
+        let position = |scene: &Scene, id| {
+            scene
+                .get_element(id)
+                .map(|e| (e.transform.x, e.transform.y))
+        };
+        assert_eq!(position(&scene, badge), Some((340.0, 90.0)));
+        assert_eq!(position(&scene, label), Some((340.0, 114.0)));
+        assert!(scene.changes_since(revision).changed.contains(&label));
+
+        // Dragging the badge itself keeps it attached at the new offset
+        scene
+            .update_element(badge, |e| e.transform.y -= 10.0)
+            .expect("nudge");
+        assert_eq!(
+            scene
+                .get_element(badge)
+                .and_then(|e| e.attachment)
+                .map(|a| a.offset_y),
+            Some(-10.0)
+        );
+        assert_eq!(position(&scene, label), Some((340.0, 104.0)));
+        scene
+            .update_element(card, |e| e.transform.y = 0.0)
+            .expect("move");
+        assert_eq!(position(&scene, badge), Some((340.0, -20.0)));
+
+        // Copies only keep attachments to elements copied with them
+        let copies = scene
+            .duplicate_elements(&[badge], 0.0, 100.0)
+            .expect("copy");
+        assert!(scene
+            .get_element(copies[0])
+            .is_some_and(|e| e.attachment.is_none()));
+        assert_eq!(position(&scene, copies[0]), Some((340.0, 80.0)));
+
+        // Edits made behind the scene's back are caught up on demand
+        if let Some(element) = scene.get_element_mut(card) {
+            element.transform.x = 0.0;
+        }
+        assert_eq!(scene.resolve_attachments().len(), 2);
+        assert_eq!(position(&scene, label), Some((190.0, 4.0)));
+    }
+
+    #[test]
+    fn test_embed_url_filtering() {
+        let kind: ElementKind =
+            serde_json::from_str(r#"{"type":"Embed","data":{"url":" https://docs.example.com "}}"#)
+                .expect("defaults");
+        assert_eq!(kind.embed_src(), Some("https://docs.example.com"));
+        let ElementKind::Embed { sandbox, .. } = &kind else {
+            panic!("expected an embed");
+        };
+        assert_eq!(sandbox, &ElementKind::default_embed_sandbox());
+        assert_eq!(
+            kind.embed_sandbox(),
+            Some(ElementKind::default_embed_sandbox())
+        );
+        let escaping = ElementKind::Embed {
+            url: "https://docs.example.com".to_string(),
+            sandbox: "allow-scripts  allow-same-origin allow-top-navigation".to_string(),
+        };
+        assert_eq!(escaping.embed_sandbox().as_deref(), Some("allow-scripts"));
+
+        assert!(ElementKind::is_embeddable_url("HTTP://example.com/a"));
+        for url in [
+            "javascript:alert(1)",
+            "data:text/html,hi",
+            "//example.com",
+            "https://example.com/a b",
+        ] {
+            assert!(!ElementKind::is_embeddable_url(url), "{url}");
+        }
+    }
+
+    #[test]
+    fn test_downsample_waveform() {
+        let samples: Vec<f32> = (0..1000)
+            .map(|i| if i == 10 { -2.0 } else { 0.25 })
+            .collect();
+        let waveform = crate::element::downsample_waveform(&samples, 10);
+        assert_eq!(waveform.len(), 10);
+        assert!((waveform[0] - 1.0).abs() < f32::EPSILON);
+        assert!(waveform[1..]
+            .iter()
+            .all(|v| (v - 0.25).abs() < f32::EPSILON));
+
+        let short = crate::element::downsample_waveform(&[0.5, f32::NAN, -0.1], 8);
+        assert_eq!(short, vec![0.5, 0.0, 0.1]);
+        let capped = crate::element::downsample_waveform(&samples, usize::MAX);
+        assert_eq!(capped.len(), crate::element::MAX_WAVEFORM_POINTS);
+    }
+
+    #[test]
+    fn test_changes_since_revision() {
+        let text = || {
+            Element::new(ElementKind::Text {
+                content: "Hi".to_string(),
+                font_size: 16.0,
+                color: Color::BLACK.into(),
+            })
+        };
+        let mut scene = Scene::new(800.0, 600.0);
+        assert_eq!(scene.revision(), 0);
+        let a = scene.add_element(text());
+        let b = scene.add_element(text());
+        let checkpoint = scene.revision();
+        assert!(scene.changes_since(checkpoint).is_empty());
+
+        if let Some(element) = scene.get_element_mut(a) {
+            element.transform.x = 40.0;
+        }
+        scene.remove_element(&b).expect("remove");
+        scene.set_camera(2.0, 10.0, 0.0);
+        let changes = scene.changes_since(checkpoint);
+        assert!(!changes.full);
+        assert_eq!(changes.changed, vec![a]);
+        assert_eq!(changes.removed, vec![b]);
+        assert!(changes.settings);
+        assert_eq!(scene.element_revision(a), Some(checkpoint + 1));
+        assert!(scene.changes_since(changes.revision).is_empty());
+
+        // Elements added back are changed, not removed
+        let mut readded = text();
+        readded.id = b;
+        scene.add_element(readded);
+        let changes = scene.changes_since(checkpoint);
+        assert_eq!(changes.changed.len(), 2);
+        assert!(changes.removed.is_empty());
+
+        // Deserialized and replacing scenes have no history to diff against
+        let mut restored = Scene::from_json(&scene.to_json().expect("serialize")).expect("parse");
+        assert!(restored.changes_since(0).full);
+        restored.supersede(&scene);
+        assert!(restored.revision() > scene.revision());
+        let changes = restored.changes_since(scene.revision());
+        assert!(changes.full);
+        assert_eq!(changes.changed.len(), 2);
+    }
+
+    #[test]
+    fn test_merge_remaps_colliding_ids() {
+        let text = |x: f32| {
+            Element::new(ElementKind::Text {
+                content: "Hi".to_string(),
+                font_size: 16.0,
+                color: Color::BLACK.into(),
+            })
+            .with_transform(Transform {
+                x,
+                y: 10.0,
+                width: 40.0,
+                height: 20.0,
+                rotation: 0.0,
+                z_index: 0,
+                auto_size: false,
+            })
+        };
+        let group = || {
+            Element::new(ElementKind::Group {
+                children: Vec::new(),
+                clip: false,
+                opacity: 1.0,
+            })
+        };
+        let mut source = Scene::new(800.0, 600.0);
+        let card = source.add_element(group());
+        let label = source.add_element(text(10.0));
+        source.reparent(label, card).expect("reparent");
+        let note = source.add_element(Element::new(ElementKind::Callout {
+            content: "Look".to_string(),
+            color: Color::WHITE.into(),
+            author: None,
+            target: Some(CalloutTarget::Element { element_id: label }),
+        }));
+        let target_with = |elements: Vec<Element>| {
+            let mut scene = Scene::new(800.0, 600.0);
+            for element in elements {
+                scene.add_element(element);
+            }
+            scene
+        };
+        let copy_of = |id: ElementId| source.get_element(id).cloned().expect("source element");
+
+        // Only the colliding label is remapped; links follow it
+        let mut scene = target_with(vec![copy_of(label)]);
+        let report = scene
+            .merge(&source, MergeOptions::default().with_offset(100.0, 50.0))
+            .expect("merge");
+        assert_eq!(report.added.len(), 3);
+        assert_eq!(report.added[0], card);
+        assert_eq!(report.remapped(), 1);
+        let moved = report.mapped(label).expect("mapped");
+        assert_ne!(moved, label);
+        assert_eq!(scene.element_count(), 4);
+        assert_eq!(
+            scene.get_element(card).map(|e| e.kind.children().to_vec()),
+            Some(vec![moved])
+        );
+        let copy = scene.get_element(moved).expect("copy");
+        assert_eq!(copy.parent, Some(card));
+        assert!((copy.transform.x - 110.0).abs() < f32::EPSILON);
+        assert!(matches!(
+            scene.get_element(note).map(|e| &e.kind),
+            Some(ElementKind::Callout {
+                target: Some(CalloutTarget::Element { element_id }),
+                ..
+            }) if *element_id == moved
+        ));
+
+        // Skipping a container skips its contents
+        let mut scene = target_with(vec![copy_of(card)]);
+        let options = MergeOptions::default().with_collision_policy(CollisionPolicy::Skip);
+        let report = scene.merge(&source, options).expect("merge");
+        assert_eq!(report.skipped, vec![card, label]);
+        assert_eq!(report.added, vec![note]);
+
+        // Replacing removes the old contents that are not re-imported
+        let mut scene = target_with(vec![copy_of(card)]);
+        let stale = scene.add_element(text(0.0));
+        scene.reparent(stale, card).expect("reparent");
+        let options = MergeOptions::default().with_collision_policy(CollisionPolicy::Replace);
+        let report = scene.merge(&source, options).expect("merge");
+        assert_eq!(report.removed, vec![stale]);
+        assert_eq!(report.remapped(), 0);
+        assert_eq!(scene.element_count(), 3);
+        assert_eq!(
+            scene.get_element(card).map(|e| e.kind.children().to_vec()),
+            Some(vec![label])
+        );
+        assert_eq!(scene.root_elements().count(), 2);
+
+        // Failing leaves the scene untouched
+        let revision = scene.revision();
+        let options = MergeOptions::default().with_collision_policy(CollisionPolicy::Fail);
+        assert!(scene.merge(&source, options).is_err());
+        assert_eq!(scene.revision(), revision);
+
+        // Fresh IDs never collide, so the source can be stamped repeatedly
+        let options = options.with_fresh_ids();
+        let report = scene.merge(&source, options).expect("merge");
+        assert_eq!(report.remapped(), 3);
+        assert!(scene.merge(&source, options).is_ok());
+        assert_eq!(scene.element_count(), 9);
+    }
+
+    #[test]
+    fn test_hidden_elements_are_culled_and_not_hit() {
+        let mut scene = Scene::new(800.0, 600.0);
+        let frame = scene.add_element(
+            Element::new(ElementKind::Group {
+                children: Vec::new(),
+                clip: false,
+                opacity: 1.0,
+            })
+            .with_transform(Transform {
+                x: 0.0,
+                y: 0.0,
+                width: 200.0,
+                height: 200.0,
+                ..Transform::default()
+            }),
+        );
+        let video = scene.add_element(
+            Element::new(ElementKind::Video {
+                stream_id: "cam".to_string(),
+                is_live: true,
+                mirror: false,
+                crop: None,
+                media_config: None,
+            })
+            .with_transform(Transform {
+                x: 10.0,
+                y: 10.0,
+                width: 100.0,
+                height: 100.0,
+                z_index: 1,
+                ..Transform::default()
+            }),
+        );
+        scene.reparent(video, frame).expect("reparent");
+        assert_eq!(scene.element_at(50.0, 50.0), Some(video));
+
+        // Hiding a container hides what it holds
+        if let Some(element) = scene.get_element_mut(frame) {
+            element.visible = false;
+        }
+        assert_eq!(scene.element_at(50.0, 50.0), None);
+        assert_eq!(scene.visible_elements().count(), 0);
+        let shown = scene.get_element(video).expect("video");
+        assert!(shown.visible && !scene.is_shown(shown));
+
+        if let Some(element) = scene.get_element_mut(frame) {
+            element.visible = true;
+        }
+        let checkpoint = scene.revision();
+        assert_eq!(scene.set_kind_visibility("Video", false), vec![video]);
+        assert!(scene.set_kind_visibility("Video", false).is_empty());
+        assert_eq!(scene.element_at(50.0, 50.0), Some(frame));
+        assert_eq!(scene.changes_since(checkpoint).changed, vec![video]);
+
+        assert_eq!(scene.set_kind_opacity("Video", 7.0).len(), 0);
+        assert_eq!(scene.set_kind_opacity("Video", 0.4), vec![video]);
+        let faded = scene.get_element(video).expect("video").clone();
+        assert!(faded.effective_opacity().abs() < f32::EPSILON);
+        assert!((faded.with_visible(true).effective_opacity() - 0.4).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn test_erase_ink_splits_strokes_and_undoes() {
+        let mut scene = Scene::new(800.0, 600.0);
+        let line = scene.add_element(
+            ink::stroke(&[[0.0, 50.0], [200.0, 50.0]], Color::BLACK.into(), 4.0)
+                .with_author("alice"),
+        );
+        let dot = scene.add_element(ink::stroke(&[[300.0, 300.0]], Color::BLACK.into(), 4.0));
+
+        // Cutting through the middle leaves two strokes with the original's
+        // attributes; a miss changes nothing
+        assert!(scene
+            .erase_ink(100.0, 400.0, 10.0, EraseMode::Pixel)
+            .is_empty());
+        let edit = scene.erase_ink(100.0, 50.0, 10.0, EraseMode::Pixel);
+        assert_eq!(edit.removed(), vec![line]);
+        assert_eq!(edit.after.len(), 2);
+        assert!(scene.get_element(line).is_none());
+        let pieces: Vec<&Element> = edit
+            .changed()
+            .into_iter()
+            .map(|id| scene.get_element(id).expect("piece"))
+            .collect();
+        assert!(pieces
+            .iter()
+            .all(|e| e.created_by.as_deref() == Some("alice")));
+        let right_edge = pieces
+            .iter()
+            .map(|e| e.transform.x + e.transform.width)
+            .fold(f32::MAX, f32::min);
+        assert!((right_edge - 90.0).abs() < 1e-3, "{right_edge}");
+
+        // Undo restores the original stroke; redo cuts it again
+        scene.apply_ink_edit(&edit.inverse()).expect("undo");
+        assert!(scene.get_element(line).is_some());
+        assert!(edit
+            .changed()
+            .iter()
+            .all(|id| scene.get_element(*id).is_none()));
+        scene.apply_ink_edit(&edit).expect("redo");
+        assert!(scene.apply_ink_edit(&edit).is_err());
+
+        // The stroke eraser takes the whole stroke
+        let edit = scene.erase_ink(302.0, 300.0, 1.0, EraseMode::Stroke);
+        assert_eq!(edit.removed(), vec![dot]);
+        assert!(edit.after.is_empty());
+
+        // Restyling keeps IDs; lasso picks strokes entirely inside
+        let pieces = scene.lasso_ink(&[(-10.0, 0.0), (95.0, 0.0), (95.0, 100.0), (-10.0, 100.0)]);
+        assert_eq!(pieces.len(), 1);
+        let edit = scene
+            .restyle_ink(&pieces, Some(&Color::rgb(255, 0, 0).into()), Some(8.0))
+            .expect("restyle");
+        assert_eq!(edit.changed(), pieces);
+        let ElementKind::Ink { color, width, .. } =
+            &scene.get_element(pieces[0]).expect("piece").kind
+        else {
+            panic!("not ink");
+        };
+        assert_eq!(*color, Color::rgb(255, 0, 0).into());
+        assert!((width - 8.0).abs() < f32::EPSILON);
+        assert!(scene.restyle_ink(&pieces, None, Some(0.0)).is_err());
     }
 }
@@ -1,11 +1,24 @@
 //! Canonical serialized representation for scenes shared across MCP, WebSocket, and web client.
 
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
 
-use crate::{Element, ElementId, ElementKind, Scene, Transform};
+use crate::anchor::Anchor;
+use crate::attachment::Attachment;
+use crate::branding::Branding;
+use crate::camera::SceneBounds;
+use crate::color::Theme;
+use crate::data::{self, DataStore, Dataset, DatasetDelta};
+use crate::geometry::OrientedRect;
+use crate::log_view;
+use crate::units::CoordinateSystem;
+use crate::{Element, ElementId, ElementKind, Interactions, Scene, Spotlight, Style, Transform};
 
 /// Document-friendly element description.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+#[allow(clippy::struct_excessive_bools)]
 pub struct ElementDocument {
     /// Element identifier.
     pub id: String,
@@ -20,6 +33,36 @@ pub struct ElementDocument {
     /// Selection flag.
     #[serde(default)]
     pub selected: bool,
+    /// Visibility flag.
+    #[serde(default = "ElementDocument::default_visible")]
+    pub visible: bool,
+    /// Element opacity, multiplied with the style's.
+    #[serde(default = "ElementDocument::default_opacity")]
+    pub opacity: f32,
+    /// Pinned to the viewport; the transform is in viewport pixels.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub screen_anchored: bool,
+    /// Confidential; redacted in exports that ask for it.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub sensitive: bool,
+    /// Visual style.
+    #[serde(default, skip_serializing_if = "Style::is_default")]
+    pub style: Style,
+    /// Declarative tap, drag and long-press behaviors.
+    #[serde(default, skip_serializing_if = "Interactions::is_empty")]
+    pub interactions: Interactions,
+    /// Peer that created the element.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_by: Option<String>,
+    /// Peer that last changed the element.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_modified_by: Option<String>,
+    /// Element this one is positioned relative to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attachment: Option<Attachment>,
+    /// Depth in front of the canvas plane on holographic displays.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub depth: Option<f32>,
 }
 
 impl From<&Element> for ElementDocument {
@@ -30,6 +73,16 @@ impl From<&Element> for ElementDocument {
             transform: element.transform,
             interactive: element.interactive,
             selected: element.selected,
+            visible: element.visible,
+            opacity: element.opacity,
+            screen_anchored: element.screen_anchored,
+            sensitive: element.sensitive,
+            style: element.style.clone(),
+            interactions: element.interactions.clone(),
+            created_by: element.created_by.clone(),
+            last_modified_by: element.last_modified_by.clone(),
+            attachment: element.attachment,
+            depth: element.depth,
         }
     }
 }
@@ -43,6 +96,14 @@ impl ElementDocument {
         true
     }
 
+    const fn default_visible() -> bool {
+        true
+    }
+
+    const fn default_opacity() -> f32 {
+        1.0
+    }
+
     /// Convert document to runtime element.
     ///
     /// # Errors
@@ -52,6 +113,16 @@ impl ElementDocument {
         let mut element = Element::new(self.kind).with_transform(self.transform);
         element.interactive = self.interactive;
         element.selected = self.selected;
+        element.visible = self.visible;
+        element.opacity = self.opacity;
+        element.screen_anchored = self.screen_anchored;
+        element.sensitive = self.sensitive;
+        element.style = self.style;
+        element.interactions = self.interactions;
+        element.created_by = self.created_by;
+        element.last_modified_by = self.last_modified_by;
+        element.attachment = self.attachment;
+        element.depth = self.depth;
         let id = ElementId::parse(&self.id).map_err(|e| e.to_string())?;
         element.id = id;
         Ok(element)
@@ -59,7 +130,8 @@ impl ElementDocument {
 }
 
 /// Viewport information.
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
 pub struct ViewportDocument {
     /// Width in pixels.
     pub width: f32,
@@ -80,6 +152,18 @@ impl ViewportDocument {
     const fn default_zoom() -> f32 {
         1.0
     }
+
+    /// The region of the canvas this viewport shows, in canvas coordinates.
+    #[must_use]
+    pub fn visible_bounds(&self) -> SceneBounds {
+        let zoom = if self.zoom > 0.0 { self.zoom } else { 1.0 };
+        SceneBounds {
+            min_x: -self.pan_x / zoom,
+            min_y: -self.pan_y / zoom,
+            max_x: (self.width - self.pan_x) / zoom,
+            max_y: (self.height - self.pan_y) / zoom,
+        }
+    }
 }
 
 impl From<&Scene> for ViewportDocument {
@@ -95,7 +179,8 @@ impl From<&Scene> for ViewportDocument {
 }
 
 /// Canonical scene document.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
 pub struct SceneDocument {
     /// Scene identifier/session.
     pub session_id: String,
@@ -103,6 +188,24 @@ pub struct SceneDocument {
     pub viewport: ViewportDocument,
     /// Elements in z-order order.
     pub elements: Vec<ElementDocument>,
+    /// Active spotlight, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spotlight: Option<Spotlight>,
+    /// Named views, in the order they were first saved.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub anchors: Vec<Anchor>,
+    /// Named colors and palettes that elements can reference.
+    #[serde(default, skip_serializing_if = "Theme::is_empty")]
+    pub theme: Theme,
+    /// Logo and watermark drawn over the canvas, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub branding: Option<Branding>,
+    /// Datasets that charts can be bound to.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub datasets: Vec<Dataset>,
+    /// World units and origin; left out for plain canvas pixels.
+    #[serde(default, skip_serializing_if = "CoordinateSystem::is_default")]
+    pub coordinates: CoordinateSystem,
     /// Timestamp in milliseconds.
     pub timestamp: u64,
 }
@@ -116,6 +219,12 @@ impl SceneDocument {
             session_id: session_id.into(),
             viewport: ViewportDocument::from(scene),
             elements,
+            spotlight: scene.spotlight().cloned(),
+            anchors: scene.anchors().to_vec(),
+            theme: scene.theme().clone(),
+            branding: scene.branding().cloned(),
+            datasets: scene.data().iter().cloned().collect(),
+            coordinates: scene.coordinate_system().clone(),
             timestamp,
         }
     }
@@ -131,11 +240,336 @@ impl SceneDocument {
         scene.pan_x = self.viewport.pan_x;
         scene.pan_y = self.viewport.pan_y;
 
+        // Datasets first, so bound charts are filled in as they are added
+        for dataset in self.datasets {
+            scene
+                .apply_data_delta(&DatasetDelta::Put { dataset })
+                .map_err(|e| e.to_string())?;
+        }
         for element_doc in self.elements {
             let element = element_doc.into_element()?;
             scene.add_element(element);
         }
+        if let Some(spotlight) = self.spotlight {
+            scene.set_spotlight(spotlight).map_err(|e| e.to_string())?;
+        }
+        for anchor in self.anchors {
+            scene.set_anchor(anchor).map_err(|e| e.to_string())?;
+        }
+        scene.set_theme(self.theme).map_err(|e| e.to_string())?;
+        if let Some(branding) = self.branding {
+            scene.set_branding(branding).map_err(|e| e.to_string())?;
+        }
+        scene
+            .set_coordinate_system(self.coordinates)
+            .map_err(|e| e.to_string())?;
 
         Ok(scene)
     }
+
+    /// Compute the minimal patch that turns this document into `other`.
+    ///
+    /// Elements are matched by ID: new IDs become `Add`, changed elements
+    /// become `Update`, and missing IDs become `Remove`. The viewport,
+    /// spotlight, anchors, theme, branding and coordinate system are only
+    /// included when they changed.
+    ///
+    /// Dataset changes come first as `Data` ops. Bound charts whose only
+    /// change is the data filled in from their dataset are left out, since
+    /// the receiver refills them when it applies those ops.
+    #[must_use]
+    pub fn diff(&self, other: &SceneDocument) -> ScenePatch {
+        let before: HashMap<&str, &ElementDocument> =
+            self.elements.iter().map(|e| (e.id.as_str(), e)).collect();
+        let after: HashMap<&str, &ElementDocument> =
+            other.elements.iter().map(|e| (e.id.as_str(), e)).collect();
+
+        let mut ops: Vec<PatchOp> = datasets_diff(&self.datasets, &other.datasets)
+            .into_iter()
+            .map(|delta| PatchOp::Data { delta })
+            .collect();
+        for element in &self.elements {
+            if !after.contains_key(element.id.as_str()) {
+                ops.push(PatchOp::Remove {
+                    id: element.id.clone(),
+                });
+            }
+        }
+        // Follow the target document's (z-)order so adds replay in order
+        for element in &other.elements {
+            match before.get(element.id.as_str()) {
+                None => ops.push(PatchOp::Add {
+                    element: element.clone(),
+                }),
+                Some(previous) if !same_content(previous, element) => {
+                    ops.push(match log_append(previous, element) {
+                        Some(lines) => PatchOp::LogAppend {
+                            id: element.id.clone(),
+                            lines,
+                        },
+                        None => PatchOp::Update {
+                            element: element.clone(),
+                        },
+                    });
+                }
+                Some(_) => {}
+            }
+        }
+        if self.spotlight != other.spotlight {
+            ops.push(PatchOp::Spotlight {
+                spotlight: other.spotlight.clone(),
+            });
+        }
+        if self.anchors != other.anchors {
+            ops.push(PatchOp::Anchors {
+                anchors: other.anchors.clone(),
+            });
+        }
+        if self.theme != other.theme {
+            ops.push(PatchOp::Theme {
+                theme: other.theme.clone(),
+            });
+        }
+        if self.branding != other.branding {
+            ops.push(PatchOp::Branding {
+                branding: other.branding.clone(),
+            });
+        }
+        if self.coordinates != other.coordinates {
+            ops.push(PatchOp::Coordinates {
+                coordinates: other.coordinates.clone(),
+            });
+        }
+
+        ScenePatch {
+            session_id: other.session_id.clone(),
+            viewport: (self.viewport != other.viewport).then_some(other.viewport),
+            ops,
+            timestamp: other.timestamp,
+        }
+    }
+
+    /// Split this document for progressive loading.
+    ///
+    /// The returned document keeps the viewport, spotlight, anchors, theme,
+    /// branding and coordinate system but only the first `page_size` elements; the rest follow as
+    /// [`ScenePage`]s. Elements inside the viewport come first (in z-order),
+    /// then the others by distance from the viewport center, so clients can
+    /// render what the user sees before the whole scene has arrived.
+    ///
+    /// Returns no pages when the scene fits in one page. A `page_size` of 0
+    /// is treated as 1.
+    #[must_use]
+    pub fn paginate(mut self, page_size: usize) -> (SceneDocument, Vec<ScenePage>) {
+        let page_size = page_size.max(1);
+        if self.elements.len() <= page_size {
+            return (self, Vec::new());
+        }
+
+        let viewport = self.viewport.visible_bounds();
+        let (cx, cy) = viewport.center();
+        let (mut visible, mut offscreen): (Vec<_>, Vec<_>) = std::mem::take(&mut self.elements)
+            .into_iter()
+            .partition(|element| {
+                OrientedRect::from_transform(&element.transform).intersects_bounds(&viewport)
+            });
+        // Stable sort: equally distant elements keep their z-order
+        offscreen.sort_by(|a, b| {
+            let distance = |element: &ElementDocument| {
+                let (x, y) = OrientedRect::from_transform(&element.transform).center;
+                (x - cx).powi(2) + (y - cy).powi(2)
+            };
+            distance(a).total_cmp(&distance(b))
+        });
+        visible.append(&mut offscreen);
+
+        let rest = visible.split_off(page_size);
+        self.elements = visible;
+        let chunks: Vec<Vec<ElementDocument>> = rest.chunks(page_size).map(<[_]>::to_vec).collect();
+        let total = chunks.len() + 1;
+        let pages = chunks
+            .into_iter()
+            .enumerate()
+            .map(|(i, elements)| ScenePage {
+                session_id: self.session_id.clone(),
+                index: i + 1,
+                total,
+                elements,
+                timestamp: self.timestamp,
+            })
+            .collect();
+        (self, pages)
+    }
+}
+
+/// A follow-up page of elements from a paginated scene load.
+///
+/// The initial [`SceneDocument`] is page 0; pages `1..total` add the
+/// remaining elements. See [`SceneDocument::paginate`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+pub struct ScenePage {
+    /// Scene identifier/session.
+    pub session_id: String,
+    /// Page number, starting at 1 for the first page after the document.
+    pub index: usize,
+    /// Total number of pages, including the initial document.
+    pub total: usize,
+    /// Elements in this page, in load priority order.
+    pub elements: Vec<ElementDocument>,
+    /// Timestamp of the scene this page was cut from, in milliseconds.
+    pub timestamp: u64,
+}
+
+impl ScenePage {
+    /// Check whether this is the final page of the load.
+    #[must_use]
+    pub fn is_last(&self) -> bool {
+        self.index + 1 >= self.total
+    }
+}
+
+/// Incremental change set between two scene documents.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+pub struct ScenePatch {
+    /// Scene identifier/session.
+    pub session_id: String,
+    /// New viewport, if it changed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub viewport: Option<ViewportDocument>,
+    /// Operations, applied in order.
+    pub ops: Vec<PatchOp>,
+    /// Timestamp in milliseconds.
+    pub timestamp: u64,
+}
+
+impl ScenePatch {
+    /// Check whether this patch changes nothing.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.viewport.is_none() && self.ops.is_empty()
+    }
+}
+
+/// The deltas that turn the datasets `before` into `after`.
+fn datasets_diff(before: &[Dataset], after: &[Dataset]) -> Vec<DatasetDelta> {
+    let collect = |datasets: &[Dataset]| {
+        let mut store = DataStore::default();
+        for dataset in datasets {
+            // Documents only hold datasets that were valid when stored
+            let _ = store.apply(&DatasetDelta::Put {
+                dataset: dataset.clone(),
+            });
+        }
+        store
+    };
+    collect(before).diff(&collect(after))
+}
+
+/// Compare two elements, ignoring data that bound charts get from their
+/// dataset.
+fn same_content(a: &ElementDocument, b: &ElementDocument) -> bool {
+    if a == b {
+        return true;
+    }
+    match (data::unbound_data(&a.kind), data::unbound_data(&b.kind)) {
+        (Some(a_data), Some(b_data)) if a_data == b_data => {
+            let strip = |doc: &ElementDocument| {
+                let mut doc = doc.clone();
+                if let ElementKind::Chart { data, .. } = &mut doc.kind {
+                    *data = serde_json::Value::Null;
+                }
+                doc
+            };
+            strip(a) == strip(b)
+        }
+        _ => false,
+    }
+}
+
+/// The lines appended to a log view, if that is all that changed.
+fn log_append(before: &ElementDocument, after: &ElementDocument) -> Option<Vec<String>> {
+    let (
+        ElementKind::LogView {
+            lines: old_lines, ..
+        },
+        ElementKind::LogView {
+            lines: new_lines,
+            max_lines,
+            ..
+        },
+    ) = (&before.kind, &after.kind)
+    else {
+        return None;
+    };
+    let mut unchanged = before.clone();
+    if let ElementKind::LogView { lines, .. } = &mut unchanged.kind {
+        lines.clone_from(new_lines);
+    }
+    if unchanged != *after {
+        return None;
+    }
+    log_view::appended_lines(old_lines, new_lines, *max_lines)
+        .filter(|new| !new.is_empty())
+        .map(<[String]>::to_vec)
+}
+
+/// A single operation within a [`ScenePatch`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum PatchOp {
+    /// Add a new element.
+    Add {
+        /// The added element.
+        element: ElementDocument,
+    },
+    /// Replace an existing element.
+    Update {
+        /// The full updated element.
+        element: ElementDocument,
+    },
+    /// Remove an element.
+    Remove {
+        /// ID of the removed element.
+        id: String,
+    },
+    /// Set or clear the scene spotlight.
+    Spotlight {
+        /// The new spotlight, or `None` to clear it.
+        spotlight: Option<Spotlight>,
+    },
+    /// Replace the scene's named anchors.
+    Anchors {
+        /// Every anchor, in order.
+        anchors: Vec<Anchor>,
+    },
+    /// Replace the scene's theme.
+    Theme {
+        /// The new theme.
+        theme: Theme,
+    },
+    /// Set or clear the scene's branding.
+    Branding {
+        /// The new branding, or `None` to clear it.
+        branding: Option<Branding>,
+    },
+    /// Replace the scene's world units and origin.
+    Coordinates {
+        /// The new coordinate system.
+        coordinates: CoordinateSystem,
+    },
+    /// Change a dataset; charts bound to it are refilled.
+    Data {
+        /// The change to apply.
+        delta: DatasetDelta,
+    },
+    /// Append lines to a log view, dropping the oldest beyond its limit.
+    LogAppend {
+        /// ID of the log view.
+        id: String,
+        /// The appended lines.
+        lines: Vec<String>,
+    },
 }
@@ -0,0 +1,434 @@
+//! # Selection
+//!
+//! The set of elements the local user has selected.
+//!
+//! [`Selection`] is the source of truth; it mirrors itself onto the scene
+//! (`Element::selected` and [`Scene::selected_elements`]) so renderers can
+//! draw highlights. Every change that alters the set queues a
+//! [`SelectionChanged`] event for the host to drain with
+//! [`Selection::take_events`].
+//!
+//! ```text
+//! click            → select(id)
+//! shift+click      → toggle(id)
+//! marquee drag     → select_rect(bounds, mode)
+//! drag selection   → translate(dx, dy)
+//! ```
+
+use serde::{Deserialize, Serialize};
+
+use crate::camera::SceneBounds;
+use crate::element::{ElementId, ElementKind};
+use crate::scene::Scene;
+
+/// How a marquee selection combines with the existing selection.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SelectionMode {
+    /// Replace the selection with the enclosed elements.
+    #[default]
+    Replace,
+    /// Add the enclosed elements to the selection.
+    Add,
+    /// Toggle each enclosed element.
+    Toggle,
+}
+
+impl SelectionMode {
+    /// Look up a mode by name (`replace`, `add` or `toggle`).
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "replace" => Some(Self::Replace),
+            "add" => Some(Self::Add),
+            "toggle" => Some(Self::Toggle),
+            _ => None,
+        }
+    }
+}
+
+/// Emitted whenever the selected set changes.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SelectionChanged {
+    /// The full selection after the change, in selection order.
+    pub selected: Vec<ElementId>,
+    /// Elements that became selected.
+    pub added: Vec<ElementId>,
+    /// Elements that stopped being selected.
+    pub removed: Vec<ElementId>,
+}
+
+/// An ordered set of selected elements.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Selection {
+    ids: Vec<ElementId>,
+    #[serde(skip)]
+    events: Vec<SelectionChanged>,
+}
+
+impl Selection {
+    /// Create an empty selection.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Selected element IDs, in the order they were selected.
+    #[must_use]
+    pub fn ids(&self) -> &[ElementId] {
+        &self.ids
+    }
+
+    /// The most recently selected element.
+    #[must_use]
+    pub fn primary(&self) -> Option<ElementId> {
+        self.ids.last().copied()
+    }
+
+    /// Number of selected elements.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    /// Check whether nothing is selected.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+
+    /// Check whether an element is selected.
+    #[must_use]
+    pub fn contains(&self, id: ElementId) -> bool {
+        self.ids.contains(&id)
+    }
+
+    /// Replace the selection with `ids`, ignoring elements not in the scene.
+    ///
+    /// Returns `true` if the selection changed.
+    pub fn set(&mut self, scene: &mut Scene, ids: impl IntoIterator<Item = ElementId>) -> bool {
+        let mut next = Vec::new();
+        for id in ids {
+            if scene.get_element(id).is_some() && !next.contains(&id) {
+                next.push(id);
+            }
+        }
+        self.replace(scene, next)
+    }
+
+    /// Select only `id`.
+    ///
+    /// Returns `true` if the selection changed.
+    pub fn select(&mut self, scene: &mut Scene, id: ElementId) -> bool {
+        self.set(scene, [id])
+    }
+
+    /// Add `id` to the selection.
+    ///
+    /// Returns `true` if the selection changed.
+    pub fn add(&mut self, scene: &mut Scene, id: ElementId) -> bool {
+        let mut next = self.ids.clone();
+        next.push(id);
+        self.set(scene, next)
+    }
+
+    /// Remove `id` from the selection.
+    ///
+    /// Returns `true` if the selection changed.
+    pub fn remove(&mut self, scene: &mut Scene, id: ElementId) -> bool {
+        let next = self.ids.iter().copied().filter(|&e| e != id).collect();
+        self.replace(scene, next)
+    }
+
+    /// Select `id` if it is unselected, otherwise deselect it.
+    ///
+    /// Returns `true` if the selection changed.
+    pub fn toggle(&mut self, scene: &mut Scene, id: ElementId) -> bool {
+        if self.contains(id) {
+            self.remove(scene, id)
+        } else {
+            self.add(scene, id)
+        }
+    }
+
+    /// Deselect everything.
+    ///
+    /// Returns `true` if the selection changed.
+    pub fn clear(&mut self, scene: &mut Scene) -> bool {
+        self.replace(scene, Vec::new())
+    }
+
+    /// Select the interactive elements lying entirely inside `rect`
+    /// (canvas coordinates).
+    ///
+    /// Returns `true` if the selection changed.
+    pub fn select_rect(
+        &mut self,
+        scene: &mut Scene,
+        rect: SceneBounds,
+        mode: SelectionMode,
+    ) -> bool {
+        let mut enclosed: Vec<_> = scene
+            .elements()
+            .filter(|e| e.interactive && rect.contains(&SceneBounds::from_transform(&e.transform)))
+            .map(|e| (e.transform.z_index, e.id))
+            .collect();
+        enclosed.sort_by_key(|&(z_index, _)| z_index);
+        self.apply(scene, enclosed.into_iter().map(|(_, id)| id), mode)
+    }
+
+    /// Lasso-select the interactive ink strokes lying entirely inside the
+    /// polygon `lasso`, in canvas coordinates.
+    ///
+    /// Returns `true` if the selection changed.
+    pub fn select_lasso(
+        &mut self,
+        scene: &mut Scene,
+        lasso: &[(f32, f32)],
+        mode: SelectionMode,
+    ) -> bool {
+        let enclosed: Vec<_> = scene
+            .lasso_ink(lasso)
+            .into_iter()
+            .filter(|id| scene.get_element(*id).is_some_and(|e| e.interactive))
+            .collect();
+        self.apply(scene, enclosed, mode)
+    }
+
+    /// Combine `enclosed` with the selection according to `mode`.
+    fn apply(
+        &mut self,
+        scene: &mut Scene,
+        enclosed: impl IntoIterator<Item = ElementId>,
+        mode: SelectionMode,
+    ) -> bool {
+        match mode {
+            SelectionMode::Replace => self.set(scene, enclosed),
+            SelectionMode::Add => {
+                let next: Vec<_> = self.ids.iter().copied().chain(enclosed).collect();
+                self.set(scene, next)
+            }
+            SelectionMode::Toggle => {
+                let mut next = self.ids.clone();
+                for id in enclosed {
+                    if let Some(index) = next.iter().position(|&e| e == id) {
+                        next.remove(index);
+                    } else {
+                        next.push(id);
+                    }
+                }
+                self.set(scene, next)
+            }
+        }
+    }
+
+    /// Bounding box of the selected elements.
+    #[must_use]
+    pub fn bounds(&self, scene: &Scene) -> Option<SceneBounds> {
+        scene.bounds_of(&self.ids)
+    }
+
+    /// Move every selected element by (`dx`, `dy`) canvas units.
+    ///
+    /// Markup pinned to a selected image moves with the image, and elements
+    /// attached to a selected element follow it.
+    ///
+    /// Returns the number of elements moved.
+    pub fn translate(&self, scene: &mut Scene, dx: f32, dy: f32) -> usize {
+        let mut moved = 0;
+        for id in self.movable(scene) {
+            let result = scene.update_element(id, |element| {
+                element.transform.x += dx;
+                element.transform.y += dy;
+            });
+            if result.is_ok() {
+                moved += 1;
+            }
+        }
+        moved
+    }
+
+    /// Scale the selection as a group by `factor` around the center of its
+    /// bounds, keeping the elements' relative layout.
+    ///
+    /// Returns the number of elements scaled (0 for a non-positive factor).
+    pub fn scale(&self, scene: &mut Scene, factor: f32) -> usize {
+        let Some(bounds) = self.bounds(scene) else {
+            return 0;
+        };
+        if factor <= 0.0 {
+            return 0;
+        }
+        let (cx, cy) = bounds.center();
+        let mut scaled = 0;
+        for id in self.movable(scene) {
+            let result = scene.update_element(id, |element| {
+                let t = &mut element.transform;
+                t.x = cx + (t.x - cx) * factor;
+                t.y = cy + (t.y - cy) * factor;
+                t.width *= factor;
+                t.height *= factor;
+            });
+            if result.is_ok() {
+                scaled += 1;
+            }
+        }
+        scaled
+    }
+
+    /// Selected elements that move on their own: everything except markup
+    /// whose image is also selected, which follows the image, and elements
+    /// attached to another selected element, which follow their target.
+    fn movable(&self, scene: &Scene) -> Vec<ElementId> {
+        self.ids
+            .iter()
+            .copied()
+            .filter(|&id| {
+                let Some(element) = scene.get_element(id) else {
+                    return true;
+                };
+                let pinned = element
+                    .parent
+                    .and_then(|parent| scene.get_element(parent))
+                    .is_some_and(|parent| {
+                        matches!(parent.kind, ElementKind::Image { .. })
+                            && self.ids.contains(&parent.id)
+                    });
+                let attached = element
+                    .attachment
+                    .is_some_and(|attachment| self.ids.contains(&attachment.target));
+                !pinned && !attached
+            })
+            .collect()
+    }
+
+    /// Reconcile with a scene that was replaced or patched: drop elements
+    /// that no longer exist and restore selection flags.
+    ///
+    /// Returns `true` if the selection changed.
+    pub fn sync(&mut self, scene: &mut Scene) -> bool {
+        let next = self.ids.clone();
+        self.set(scene, next)
+    }
+
+    /// Drain queued selection-changed events, oldest first.
+    pub fn take_events(&mut self) -> Vec<SelectionChanged> {
+        std::mem::take(&mut self.events)
+    }
+
+    /// Install `next` as the selection, mirror it onto the scene and queue
+    /// an event if it differs from the current one.
+    fn replace(&mut self, scene: &mut Scene, next: Vec<ElementId>) -> bool {
+        scene.deselect_all();
+        for &id in &next {
+            let _ = scene.select(id);
+        }
+        if next == self.ids {
+            return false;
+        }
+
+        let added = next
+            .iter()
+            .copied()
+            .filter(|id| !self.ids.contains(id))
+            .collect();
+        let removed = self
+            .ids
+            .iter()
+            .copied()
+            .filter(|id| !next.contains(id))
+            .collect();
+        self.ids = next;
+        self.events.push(SelectionChanged {
+            selected: self.ids.clone(),
+            added,
+            removed,
+        });
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Color, Element, ElementKind, Transform};
+
+    fn box_at(scene: &mut Scene, x: f32, y: f32) -> ElementId {
+        scene.add_element(
+            Element::new(ElementKind::Text {
+                content: "Box".to_string(),
+                font_size: 16.0,
+                color: Color::BLACK.into(),
+            })
+            .with_transform(Transform {
+                x,
+                y,
+                width: 10.0,
+                height: 10.0,
+                rotation: 0.0,
+                z_index: 0,
+                auto_size: false,
+            }),
+        )
+    }
+
+    #[test]
+    fn test_select_add_toggle_and_events() {
+        let mut scene = Scene::new(800.0, 600.0);
+        let a = box_at(&mut scene, 0.0, 0.0);
+        let b = box_at(&mut scene, 20.0, 0.0);
+        let mut selection = Selection::new();
+
+        assert!(selection.select(&mut scene, a));
+        assert!(selection.add(&mut scene, b));
+        assert!(!selection.add(&mut scene, b));
+        assert_eq!(selection.ids(), &[a, b]);
+        assert_eq!(scene.selected_elements().count(), 2);
+
+        assert!(selection.toggle(&mut scene, a));
+        assert_eq!(selection.ids(), &[b]);
+        assert!(!scene.get_element(a).unwrap().selected);
+
+        // Unknown elements are ignored
+        assert!(!selection.add(&mut scene, ElementId::new()));
+
+        let events = selection.take_events();
+        assert_eq!(events.len(), 3);
+        assert_eq!(events[2].removed, vec![a]);
+        assert!(selection.take_events().is_empty());
+
+        scene.remove_element(&b).unwrap();
+        assert!(selection.sync(&mut scene));
+        assert!(selection.is_empty());
+    }
+
+    #[test]
+    fn test_marquee_and_group_transform() {
+        let mut scene = Scene::new(800.0, 600.0);
+        let a = box_at(&mut scene, 0.0, 0.0);
+        let b = box_at(&mut scene, 30.0, 0.0);
+        let far = box_at(&mut scene, 500.0, 500.0);
+        let mut selection = Selection::new();
+
+        let rect = SceneBounds {
+            min_x: -5.0,
+            min_y: -5.0,
+            max_x: 45.0,
+            max_y: 15.0,
+        };
+        assert!(selection.select_rect(&mut scene, rect, SelectionMode::Replace));
+        assert_eq!(selection.len(), 2);
+        let far_bounds = SceneBounds::from_transform(&scene.get_element(far).unwrap().transform);
+        selection.select_rect(&mut scene, far_bounds, SelectionMode::Add);
+        assert_eq!(selection.len(), 3);
+        selection.select_rect(&mut scene, rect, SelectionMode::Toggle);
+        assert_eq!(selection.ids(), &[far]);
+
+        selection.set(&mut scene, [a, b]);
+        assert_eq!(selection.translate(&mut scene, 5.0, 5.0), 2);
+        assert_eq!(selection.scale(&mut scene, 2.0), 2);
+        let bounds = selection.bounds(&scene).unwrap();
+        assert!((bounds.width() - 80.0).abs() < 0.01);
+        assert_eq!(bounds.center(), (25.0, 10.0));
+    }
+}
@@ -0,0 +1,556 @@
+//! # Shader Elements
+//!
+//! Sandbox rules for `Shader` elements: generative visuals an agent writes
+//! as a WGSL function, which the wgpu backend runs over the element's rect.
+//!
+//! ```wgsl
+//! fn shade(uv: vec2<f32>) -> vec4<f32> {
+//!     // uv runs from (0, 0) at the top left to (1, 1) at the bottom right
+//!     let wave = 0.5 + 0.5 * sin(uv.x * 12.0 + canvas.time * param(0u));
+//!     return vec4<f32>(uv.x, wave, 1.0 - uv.y, 1.0);
+//! }
+//! ```
+//!
+//! The source defines `shade` and any helpers it calls; the renderer adds
+//! the entry points and these declarations:
+//!
+//! | Name                | Value                                              |
+//! |---------------------|----------------------------------------------------|
+//! | `canvas.time`       | Seconds since the renderer started                 |
+//! | `canvas.resolution` | The element's size on screen, in pixels            |
+//! | `param(i)`          | The element's `uniforms[i]`, 0.0 past the last one |
+//!
+//! To keep agent code from reaching beyond its rect or stalling the GPU,
+//! sources may not declare bindings, entry points or other attributes,
+//! storage or workgroup variables, or extensions, and may not use `loop` or
+//! `while`. A `for` loop must count a variable up from a constant to a
+//! constant bound, as in `for (var i = 0; i < 8; i++)`, without assigning
+//! it in its body, and nested loops, counting those in helpers called
+//! inside them, run at most [`MAX_SHADER_LOOP_ITERATIONS`] times together. The renderer compiles
+//! every source before drawing it; backends that cannot, and sources that
+//! fail, draw a placeholder.
+
+use crate::element::ElementKind;
+use crate::error::{CanvasError, CanvasResult};
+
+/// Longest shader source, in bytes.
+pub const MAX_SHADER_LEN: usize = 16 * 1024;
+
+/// Most custom uniforms a shader takes.
+pub const MAX_SHADER_UNIFORMS: usize = 16;
+
+/// Most iterations of a `for` loop, counting the loops it is nested in.
+pub const MAX_SHADER_LOOP_ITERATIONS: i64 = 256;
+
+/// Function every shader source defines, called once per pixel.
+pub const SHADER_ENTRY: &str = "shade";
+
+/// Color of the placeholder drawn where a shader cannot run.
+pub const SHADER_PLACEHOLDER: &str = "#2b2d42";
+
+/// Attributes sources may not use: entry points and resource bindings
+/// belong to the renderer.
+const FORBIDDEN_ATTRIBUTES: &[&str] = &[
+    "group",
+    "binding",
+    "vertex",
+    "fragment",
+    "compute",
+    "workgroup_size",
+    "builtin",
+    "location",
+    "id",
+];
+
+/// Keywords sources may not use: unbounded loops, extensions, and address
+/// spaces shared beyond one pixel.
+const FORBIDDEN_WORDS: &[&str] = &[
+    "loop",
+    "while",
+    "enable",
+    "requires",
+    "storage",
+    "workgroup",
+    "texture_storage_2d",
+];
+
+/// Check a `Shader`'s source against the sandbox rules and its uniforms'
+/// limits; other kinds pass.
+///
+/// This is a textual check; the renderer compiles the source too.
+///
+/// # Errors
+///
+/// Returns [`CanvasError::InvalidOperation`] if the source is empty, longer
+/// than [`MAX_SHADER_LEN`], does not define [`SHADER_ENTRY`] or uses a
+/// forbidden attribute or keyword, has a `for` loop without a small
+/// constant bound (see [`MAX_SHADER_LOOP_ITERATIONS`]), or if there are
+/// more than [`MAX_SHADER_UNIFORMS`] uniforms or one is not finite.
+pub fn validate(kind: &ElementKind) -> CanvasResult<()> {
+    let ElementKind::Shader { wgsl, uniforms } = kind else {
+        return Ok(());
+    };
+    let invalid = |message: String| Err(CanvasError::InvalidOperation(message));
+    if uniforms.len() > MAX_SHADER_UNIFORMS {
+        return invalid(format!(
+            "shaders take at most {MAX_SHADER_UNIFORMS} uniforms, got {}",
+            uniforms.len()
+        ));
+    }
+    if let Some(index) = uniforms.iter().position(|value| !value.is_finite()) {
+        return invalid(format!("shader uniform {index} is not a finite number"));
+    }
+    if wgsl.trim().is_empty() {
+        return invalid("shader source is empty".to_string());
+    }
+    if wgsl.len() > MAX_SHADER_LEN {
+        return invalid(format!(
+            "shader source is longer than {MAX_SHADER_LEN} bytes"
+        ));
+    }
+
+    let code = strip_comments(wgsl);
+    let mut defines_entry = false;
+    let mut previous = "";
+    for (attribute, word) in words(&code) {
+        if attribute && FORBIDDEN_ATTRIBUTES.contains(&word) {
+            return invalid(format!("shaders may not use @{word}"));
+        }
+        if !attribute && FORBIDDEN_WORDS.contains(&word) {
+            return invalid(format!("shaders may not use `{word}`"));
+        }
+        defines_entry |= previous == "fn" && word == SHADER_ENTRY;
+        previous = word;
+    }
+    if !defines_entry {
+        return invalid(format!(
+            "shader source must define `fn {SHADER_ENTRY}(uv: vec2<f32>) -> vec4<f32>`"
+        ));
+    }
+    check_loops(&code).map_err(CanvasError::InvalidOperation)
+}
+
+/// Check that every `for` loop in `code` has a constant bound, and that
+/// nested loops, counting those in the functions they call, stay within
+/// [`MAX_SHADER_LOOP_ITERATIONS`].
+fn check_loops(code: &str) -> Result<(), String> {
+    let unbounded = || {
+        "shader `for` loops must count a variable up to a constant, \
+         e.g. `for (var i = 0; i < 8; i++)`"
+            .to_string()
+    };
+    let too_many = || {
+        format!(
+            "shader loops may run at most {MAX_SHADER_LOOP_ITERATIONS} times, nested \
+             loops and the functions called in them together"
+        )
+    };
+    // Body spans and iteration counts of the loops seen so far
+    let mut loops: Vec<(usize, usize, i64)> = Vec::new();
+    // Where each loop starts and how often its body runs in all
+    let mut totals: Vec<(usize, i64)> = Vec::new();
+    // Iterations of the loops around `at`, multiplied together
+    let enclosing = |loops: &[(usize, usize, i64)], at: usize| {
+        loops
+            .iter()
+            .filter(|(from, to, _)| (*from..*to).contains(&at))
+            .try_fold(1i64, |total, (_, _, outer)| total.checked_mul(*outer))
+            .unwrap_or(i64::MAX)
+    };
+    for (attribute, word) in words(code) {
+        if attribute || word != "for" {
+            continue;
+        }
+        let start = offset(code, word) + word.len();
+        let (header, after) = delimited(code, start, '(', ')').ok_or_else(unbounded)?;
+        let (body_start, body_end) = delimited(code, after, '{', '}')
+            .map(|(_, end)| (after, end))
+            .ok_or_else(unbounded)?;
+        let (counter, iterations) = counted_loop(header).ok_or_else(unbounded)?;
+        if assigns(&code[body_start..body_end], &counter) {
+            return Err(format!(
+                "shader loops may not change their counter `{counter}`"
+            ));
+        }
+        let total = iterations.saturating_mul(enclosing(&loops, start));
+        if total > MAX_SHADER_LOOP_ITERATIONS {
+            return Err(too_many());
+        }
+        loops.push((body_start, body_end, iterations));
+        totals.push((start, total));
+    }
+
+    // A helper called in a loop runs its own loops once per iteration
+    let functions = functions(code);
+    let calls: Vec<Vec<(usize, i64)>> = functions
+        .iter()
+        .map(|(_, from, to)| {
+            let body = &code[*from..*to];
+            words(body)
+                .filter(|(attribute, _)| !attribute)
+                .filter_map(|(_, word)| {
+                    let at = offset(code, word);
+                    let callee = functions.iter().position(|(name, _, _)| *name == word)?;
+                    code[at + word.len()..]
+                        .trim_start()
+                        .starts_with('(')
+                        .then(|| (callee, enclosing(&loops, at)))
+                })
+                .collect()
+        })
+        .collect();
+    let own: Vec<i64> = functions
+        .iter()
+        .map(|(_, from, to)| {
+            totals
+                .iter()
+                .filter(|(start, _)| (*from..*to).contains(start))
+                .map(|(_, total)| *total)
+                .max()
+                .unwrap_or(1)
+        })
+        .collect();
+    let mut costs = vec![None; functions.len()];
+    let mut visiting = vec![false; functions.len()];
+    for index in 0..functions.len() {
+        if cost(index, &own, &calls, &mut costs, &mut visiting)? > MAX_SHADER_LOOP_ITERATIONS {
+            return Err(too_many());
+        }
+    }
+    Ok(())
+}
+
+/// Worst-case iterations of function `index`: its `own` loops, or a call
+/// times the loops around it, whichever runs longest. WGSL forbids
+/// recursion, so the calls form a DAG; `costs` remembers each total.
+fn cost(
+    index: usize,
+    own: &[i64],
+    calls: &[Vec<(usize, i64)>],
+    costs: &mut [Option<i64>],
+    visiting: &mut [bool],
+) -> Result<i64, String> {
+    if let Some(total) = costs[index] {
+        return Ok(total);
+    }
+    if visiting[index] {
+        return Err("shader functions may not call themselves".to_string());
+    }
+    visiting[index] = true;
+    let mut total = own[index];
+    for &(callee, around) in &calls[index] {
+        total = total.max(cost(callee, own, calls, costs, visiting)?.saturating_mul(around));
+    }
+    visiting[index] = false;
+    costs[index] = Some(total);
+    Ok(total)
+}
+
+/// Names and body spans of the functions `code` defines.
+fn functions(code: &str) -> Vec<(&str, usize, usize)> {
+    let mut functions = Vec::new();
+    let mut previous = "";
+    for (_, word) in words(code) {
+        if previous == "fn" {
+            let start = offset(code, word) + word.len();
+            let body = delimited(code, start, '(', ')').and_then(|(_, after)| {
+                let from = after + code[after..].find('{')?;
+                delimited(code, from, '{', '}').map(|(_, to)| (from, to))
+            });
+            if let Some((from, to)) = body {
+                functions.push((word, from, to));
+            }
+        }
+        previous = word;
+    }
+    functions
+}
+
+/// Offset of `word`, a slice of `code`, within it.
+fn offset(code: &str, word: &str) -> usize {
+    word.as_ptr() as usize - code.as_ptr() as usize
+}
+
+/// The text between `open` at or after `from` in `code` (skipping
+/// whitespace) and its matching `close`, with the offset after `close`.
+fn delimited(code: &str, from: usize, open: char, close: char) -> Option<(&str, usize)> {
+    let rest = &code[from..];
+    let inner = rest.trim_start().strip_prefix(open)?;
+    let offset = code.len() - inner.len();
+    let mut depth = 0usize;
+    for (index, c) in inner.char_indices() {
+        if c == open {
+            depth += 1;
+        } else if c == close {
+            if depth == 0 {
+                return Some((&inner[..index], offset + index + c.len_utf8()));
+            }
+            depth -= 1;
+        }
+    }
+    None
+}
+
+/// The counter of a loop with `header` (`init; condition; update`) and how
+/// many times it runs, if it counts up from a constant to a constant.
+fn counted_loop(header: &str) -> Option<(String, i64)> {
+    let compact: String = header.chars().filter(|c| !c.is_whitespace()).collect();
+    let mut parts = compact.split(';');
+    let (init, condition, update) = (parts.next()?, parts.next()?, parts.next()?);
+    if parts.next().is_some() {
+        return None;
+    }
+
+    // var i = 0, var i: i32 = 0
+    let (declaration, first) = init.strip_prefix("var")?.split_once('=')?;
+    let counter = declaration.split(':').next()?;
+    if counter.is_empty() || !counter.chars().all(|c| c.is_alphanumeric() || c == '_') {
+        return None;
+    }
+    let first = integer(first)?;
+
+    // i < 8, i <= 8
+    let bound = condition.strip_prefix(counter)?;
+    let last = if let Some(bound) = bound.strip_prefix("<=") {
+        integer(bound)?
+    } else {
+        integer(bound.strip_prefix('<')?)? - 1
+    };
+
+    // i++, i += 2
+    let step = update.strip_prefix(counter)?;
+    let step = if step == "++" {
+        1
+    } else {
+        integer(step.strip_prefix("+=")?)?
+    };
+    // Counters must not wrap past the bound
+    if step < 1 || last + step > i64::from(i32::MAX) {
+        return None;
+    }
+    let iterations = if last < first {
+        0
+    } else {
+        (last - first) / step + 1
+    };
+    Some((counter.to_string(), iterations))
+}
+
+/// A WGSL integer literal such as `8`, `8u`, `-8i` or `0x10`, within the
+/// range of `i32`.
+fn integer(literal: &str) -> Option<i64> {
+    let digits = literal.trim_end_matches(['i', 'u']);
+    let (negative, digits) = digits
+        .strip_prefix('-')
+        .map_or((false, digits), |digits| (true, digits));
+    let value = match digits
+        .strip_prefix("0x")
+        .or_else(|| digits.strip_prefix("0X"))
+    {
+        Some(hex) => i64::from_str_radix(hex, 16).ok()?,
+        None => digits.parse().ok()?,
+    };
+    let value = if negative { -value } else { value };
+    (value.abs() <= i64::from(i32::MAX)).then_some(value)
+}
+
+/// Whether `body` assigns `counter`, increments it or takes its address.
+fn assigns(body: &str, counter: &str) -> bool {
+    words(body).any(|(_, word)| {
+        if word != counter {
+            return false;
+        }
+        let start = word.as_ptr() as usize - body.as_ptr() as usize;
+        let before = body[..start].trim_end();
+        let after = body[start + word.len()..].trim_start();
+        let address = before.ends_with('&') && !before.ends_with("&&");
+        let assigned = [
+            "++", "--", "+=", "-=", "*=", "/=", "%=", "&=", "|=", "^=", "<<=", ">>=",
+        ]
+        .iter()
+        .any(|op| after.starts_with(op))
+            || (after.starts_with('=') && !after.starts_with("=="));
+        address || assigned
+    })
+}
+
+/// `source` with line and block comments blanked out, so words inside them
+/// are not checked.
+fn strip_comments(source: &str) -> String {
+    let mut code = String::with_capacity(source.len());
+    let mut chars = source.chars().peekable();
+    // Block comments nest in WGSL
+    let mut depth = 0usize;
+    while let Some(c) = chars.next() {
+        match (c, chars.peek()) {
+            ('/', Some('*')) => {
+                chars.next();
+                depth += 1;
+                code.push(' ');
+            }
+            ('*', Some('/')) if depth > 0 => {
+                chars.next();
+                depth -= 1;
+                code.push(' ');
+            }
+            ('/', Some('/')) if depth == 0 => {
+                while chars.next_if(|&c| c != '\n').is_some() {}
+                code.push(' ');
+            }
+            _ if depth > 0 => code.push(if c == '\n' { '\n' } else { ' ' }),
+            _ => code.push(c),
+        }
+    }
+    code
+}
+
+/// Identifiers in `code`, each with whether it follows `@`.
+fn words(code: &str) -> impl Iterator<Item = (bool, &str)> {
+    let mut rest = code;
+    std::iter::from_fn(move || {
+        let start = rest.find(|c: char| c.is_alphanumeric() || c == '_')?;
+        let attribute = rest[..start].trim_end().ends_with('@');
+        let after = &rest[start..];
+        let end = after
+            .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .unwrap_or(after.len());
+        rest = &after[end..];
+        Some((attribute, &after[..end]))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shader(wgsl: &str) -> ElementKind {
+        ElementKind::Shader {
+            wgsl: wgsl.to_string(),
+            uniforms: vec![1.0, 2.0],
+        }
+    }
+
+    #[test]
+    fn test_validate_accepts_a_shade_function() {
+        let wgsl = "// A gradient; no `loop` here\n\
+                    fn shade(uv: vec2<f32>) -> vec4<f32> {\n\
+                        var sum = 0.0;\n\
+                        for (var i = 0; i < 4; i++) { sum += param(u32(i)); }\n\
+                        /* while /* nested */ comments are fine */\n\
+                        return vec4<f32>(uv, sin(canvas.time) * sum, 1.0);\n\
+                    }";
+        assert!(validate(&shader(wgsl)).is_ok());
+        assert!(validate(&ElementKind::Shape {
+            shape: crate::ShapeKind::Rectangle
+        })
+        .is_ok());
+    }
+
+    #[test]
+    fn test_validate_rejects_escapes_from_the_sandbox() {
+        for wgsl in [
+            "",
+            "fn shader(uv: vec2<f32>) -> vec4<f32> { return vec4<f32>(1.0); }",
+            "@group(0) @binding(5) var<storage, read_write> out: array<f32>;\n\
+             fn shade(uv: vec2<f32>) -> vec4<f32> { return vec4<f32>(1.0); }",
+            "fn shade(uv: vec2<f32>) -> vec4<f32> { loop { } }",
+            "fn shade(uv: vec2<f32>) -> vec4<f32> { while true { } return vec4<f32>(1.0); }",
+            "enable f16;\nfn shade(uv: vec2<f32>) -> vec4<f32> { return vec4<f32>(1.0); }",
+            "@ fragment fn shade(uv: vec2<f32>) -> vec4<f32> { return vec4<f32>(1.0); }",
+        ] {
+            assert!(validate(&shader(wgsl)).is_err(), "{wgsl}");
+        }
+
+        let ok = "fn shade(uv: vec2<f32>) -> vec4<f32> { return vec4<f32>(1.0); }";
+        let many = ElementKind::Shader {
+            wgsl: ok.to_string(),
+            uniforms: vec![0.0; MAX_SHADER_UNIFORMS + 1],
+        };
+        assert!(validate(&many).is_err());
+        let nan = ElementKind::Shader {
+            wgsl: ok.to_string(),
+            uniforms: vec![f32::NAN],
+        };
+        assert!(validate(&nan).is_err());
+        let long = format!("{ok}{}", " ".repeat(MAX_SHADER_LEN));
+        assert!(validate(&shader(&long)).is_err());
+    }
+
+    #[test]
+    fn test_validate_requires_constant_loop_bounds() {
+        let body = |header: &str, body: &str| {
+            format!(
+                "fn shade(uv: vec2<f32>) -> vec4<f32> {{\n\
+                     var sum = 0.0;\n\
+                     for ({header}) {{ {body} }}\n\
+                     return vec4<f32>(uv, sum, 1.0);\n\
+                 }}"
+            )
+        };
+        for (header, inner) in [
+            ("var i = 0; i < 16; i++", "sum += f32(i);"),
+            ("var i: u32 = 0u; i <= 255u; i += 1u", "sum += param(i);"),
+            ("var i = 8; i < 4; i++", "sum += 1.0;"),
+            (
+                "var i = 0; i < 16; i++",
+                "for (var j = 0; j < 16; j += 1) { sum += f32(i == j); }",
+            ),
+        ] {
+            let wgsl = body(header, inner);
+            assert!(validate(&shader(&wgsl)).is_ok(), "{wgsl}");
+        }
+
+        for (header, inner) in [
+            (";;", "sum += 1.0;"),
+            ("var i = 0u; i < 0xffffffffu; i++", "sum += 1.0;"),
+            ("var i = 0; i < n; i++", "sum += 1.0;"),
+            ("var i = 0; i > -1; i++", "sum += 1.0;"),
+            ("var i = 0; i < 8; i--", "sum += 1.0;"),
+            ("var i = 0; i < 8; i += 0", "sum += 1.0;"),
+            (
+                "var i = -9223372036854775807; i < 9223372036854775807; i++",
+                "sum += 1.0;",
+            ),
+            ("var i = 2147483640; i <= 2147483647; i++", "sum += 1.0;"),
+            ("var i = 0; i < 2147483000; i += 1073741824", "sum += 1.0;"),
+            ("var i = 0; i < 8; i++", "i = 0;"),
+            ("var i = 0; i < 8; i++", "i -= 1;"),
+            ("var i = 0; i < 8; i++", "reset(&i);"),
+            (
+                "var i = 0; i < 32; i++",
+                "for (var j = 0; j < 32; j++) { sum += 1.0; }",
+            ),
+        ] {
+            let wgsl = body(header, inner);
+            assert!(validate(&shader(&wgsl)).is_err(), "{wgsl}");
+        }
+
+        // Loops in helpers count once per iteration of the loops calling them
+        let helper = |name: &str, count: u32, call: &str| {
+            format!(
+                "fn {name}() -> f32 {{\n\
+                     var sum = 0.0;\n\
+                     for (var i = 0; i < {count}; i++) {{ sum += {call}; }}\n\
+                     return sum;\n\
+                 }}\n"
+            )
+        };
+        let shade = "fn shade(uv: vec2<f32>) -> vec4<f32> { return vec4<f32>(uv, a(), 1.0); }";
+        let chained = format!(
+            "{}{}{}{shade}",
+            helper("c", 256, "1.0"),
+            helper("b", 256, "c()"),
+            helper("a", 256, "b()"),
+        );
+        assert!(validate(&shader(&chained)).is_err());
+        let within = format!(
+            "{}{}{}{shade}",
+            helper("c", 4, "1.0"),
+            helper("b", 8, "c()"),
+            helper("a", 8, "b() + c()"),
+        );
+        assert!(validate(&shader(&within)).is_ok(), "{within}");
+        let recursive = format!("{}{shade}", helper("a", 2, "a()"));
+        assert!(validate(&shader(&recursive)).is_err());
+    }
+}
@@ -0,0 +1,431 @@
+//! # Shapes
+//!
+//! Common diagram shapes drawn as first-class elements, filled and stroked
+//! by the element's [`Style`](crate::Style):
+//!
+//! ```text
+//!  ┌──────┐    ╭──────╮       /\          /\         ┌──────┐\
+//!  │      │   (        )     /  \     ___/  \___     │       >
+//!  └──────┘    ╰──────╯      \__/     \        /     └──────┘/
+//!  rectangle    ellipse    polygon      star           arrow
+//! ```
+//!
+//! [`ShapeOutline::new`] turns a shape into canvas coordinates inside the
+//! element's box, so every renderer draws the same outline.
+
+use std::f32::consts::{FRAC_PI_2, TAU};
+
+use serde::{Deserialize, Serialize};
+
+use crate::element::Transform;
+use crate::error::{CanvasError, CanvasResult};
+use crate::measure::Size;
+
+/// Fewest sides of a polygon or points of a star.
+pub const MIN_SHAPE_VERTICES: u32 = 3;
+
+/// Most sides of a polygon or points of a star.
+pub const MAX_SHAPE_VERTICES: u32 = 64;
+
+/// Geometry of a `Shape` element.
+///
+/// Every shape fills the element's box: polygons and stars are inscribed in
+/// the ellipse touching its edges, and arrows span its full length.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum ShapeKind {
+    /// A rectangle, rounded by the style's corner radius.
+    Rectangle,
+    /// An ellipse touching the box's edges.
+    Ellipse,
+    /// A regular polygon with a vertex at the top.
+    Polygon {
+        /// Number of sides.
+        #[serde(default = "ShapeKind::default_sides")]
+        sides: u32,
+    },
+    /// A star with a point at the top.
+    Star {
+        /// Number of points.
+        #[serde(default = "ShapeKind::default_star_points")]
+        points: u32,
+        /// Radius of the inner vertices as a fraction of the outer radius.
+        #[serde(default = "ShapeKind::default_inner_ratio")]
+        inner_ratio: f32,
+    },
+    /// A block arrow: a shaft ending in a triangular head.
+    Arrow {
+        /// Which way the head points.
+        #[serde(default)]
+        direction: ArrowDirection,
+        /// Length of the head as a fraction of the arrow's length.
+        #[serde(default = "ShapeKind::default_head_length")]
+        head_length: f32,
+        /// Thickness of the shaft as a fraction of the arrow's thickness.
+        #[serde(default = "ShapeKind::default_shaft_width")]
+        shaft_width: f32,
+    },
+}
+
+impl ShapeKind {
+    /// Default polygon: a hexagon.
+    #[must_use]
+    pub const fn default_sides() -> u32 {
+        6
+    }
+
+    /// Default star: five points.
+    #[must_use]
+    pub const fn default_star_points() -> u32 {
+        5
+    }
+
+    /// Default star inner radius.
+    #[must_use]
+    pub const fn default_inner_ratio() -> f32 {
+        0.5
+    }
+
+    /// Default arrow head length.
+    #[must_use]
+    pub const fn default_head_length() -> f32 {
+        0.35
+    }
+
+    /// Default arrow shaft thickness.
+    #[must_use]
+    pub const fn default_shaft_width() -> f32 {
+        0.5
+    }
+
+    /// Lowercase name, e.g. `"star"`.
+    #[must_use]
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::Rectangle => "rectangle",
+            Self::Ellipse => "ellipse",
+            Self::Polygon { .. } => "polygon",
+            Self::Star { .. } => "star",
+            Self::Arrow { .. } => "arrow",
+        }
+    }
+
+    /// Default size of a new shape: arrows are twice as long as they are
+    /// thick, rectangles landscape and other shapes square.
+    #[must_use]
+    pub const fn natural_size(&self) -> Size {
+        match self {
+            Self::Arrow {
+                direction: ArrowDirection::Up | ArrowDirection::Down,
+                ..
+            } => Size::new(80.0, 160.0),
+            Self::Arrow { .. } => Size::new(160.0, 80.0),
+            Self::Rectangle => Size::new(160.0, 100.0),
+            Self::Ellipse | Self::Polygon { .. } | Self::Star { .. } => Size::new(120.0, 120.0),
+        }
+    }
+
+    /// Check that a shape's parameters are in range.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CanvasError::InvalidOperation`] for a polygon or star with
+    /// fewer than [`MIN_SHAPE_VERTICES`] or more than [`MAX_SHAPE_VERTICES`]
+    /// vertices, a star inner ratio outside `(0, 1)`, or an arrow head or
+    /// shaft outside `(0, 1]`.
+    pub fn validate(&self) -> CanvasResult<()> {
+        let in_range = |value: f32, max_inclusive: bool| {
+            value.is_finite() && value > 0.0 && (value < 1.0 || (max_inclusive && value <= 1.0))
+        };
+        match *self {
+            Self::Polygon { sides: count, .. } | Self::Star { points: count, .. }
+                if !(MIN_SHAPE_VERTICES..=MAX_SHAPE_VERTICES).contains(&count) =>
+            {
+                Err(CanvasError::InvalidOperation(format!(
+                    "A {} needs {MIN_SHAPE_VERTICES} to {MAX_SHAPE_VERTICES} vertices, got {count}",
+                    self.name()
+                )))
+            }
+            Self::Star { inner_ratio, .. } if !in_range(inner_ratio, false) => {
+                Err(CanvasError::InvalidOperation(format!(
+                    "Star inner ratio must be between 0 and 1, got {inner_ratio}"
+                )))
+            }
+            Self::Arrow {
+                head_length,
+                shaft_width,
+                ..
+            } if !(in_range(head_length, true) && in_range(shaft_width, true)) => {
+                Err(CanvasError::InvalidOperation(format!(
+                    "Arrow head length and shaft width must be in (0, 1], got {head_length} and {shaft_width}"
+                )))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Which way an arrow shape points.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+#[serde(rename_all = "lowercase")]
+pub enum ArrowDirection {
+    /// Head on the right edge.
+    #[default]
+    Right,
+    /// Head on the left edge.
+    Left,
+    /// Head on the top edge.
+    Up,
+    /// Head on the bottom edge.
+    Down,
+}
+
+/// A shape's outline in canvas coordinates.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ShapeOutline {
+    /// A rectangle, `[x, y, width, height]`, with rounded corners.
+    Rect {
+        /// Bounds.
+        bounds: [f32; 4],
+        /// Corner radius.
+        radius: f32,
+    },
+    /// An ellipse.
+    Ellipse {
+        /// Center, `[x, y]`.
+        center: [f32; 2],
+        /// Horizontal and vertical radii.
+        radii: [f32; 2],
+    },
+    /// A closed polygon through the given vertices.
+    Polygon(Vec<[f32; 2]>),
+}
+
+impl ShapeOutline {
+    /// Outline of `shape` filling the box `t`, with rectangles rounded by
+    /// `corner_radius` (usually [`Style::effective_corner_radius`]).
+    ///
+    /// [`Style::effective_corner_radius`]: crate::Style::effective_corner_radius
+    #[must_use]
+    pub fn new(shape: &ShapeKind, t: &Transform, corner_radius: f32) -> Self {
+        let center = [t.x + t.width / 2.0, t.y + t.height / 2.0];
+        let radii = [t.width / 2.0, t.height / 2.0];
+        match *shape {
+            ShapeKind::Rectangle => Self::Rect {
+                bounds: [t.x, t.y, t.width, t.height],
+                radius: corner_radius,
+            },
+            ShapeKind::Ellipse => Self::Ellipse { center, radii },
+            ShapeKind::Polygon { sides } => Self::Polygon(radial_vertices(
+                center,
+                radii,
+                sides.clamp(MIN_SHAPE_VERTICES, MAX_SHAPE_VERTICES),
+                1.0,
+            )),
+            ShapeKind::Star {
+                points,
+                inner_ratio,
+            } => Self::Polygon(radial_vertices(
+                center,
+                radii,
+                points.clamp(MIN_SHAPE_VERTICES, MAX_SHAPE_VERTICES) * 2,
+                inner_ratio,
+            )),
+            ShapeKind::Arrow {
+                direction,
+                head_length,
+                shaft_width,
+            } => Self::Polygon(arrow_vertices(t, direction, head_length, shaft_width)),
+        }
+    }
+}
+
+/// `count` vertices around an ellipse starting at the top, with every odd
+/// vertex pulled in to `inner_ratio` of the radius.
+#[allow(clippy::cast_precision_loss)]
+fn radial_vertices(
+    center: [f32; 2],
+    radii: [f32; 2],
+    count: u32,
+    inner_ratio: f32,
+) -> Vec<[f32; 2]> {
+    (0..count)
+        .map(|i| {
+            let angle = (i as f32).mul_add(TAU / count as f32, -FRAC_PI_2);
+            let scale = if i % 2 == 1 { inner_ratio } else { 1.0 };
+            [
+                (radii[0] * scale).mul_add(angle.cos(), center[0]),
+                (radii[1] * scale).mul_add(angle.sin(), center[1]),
+            ]
+        })
+        .collect()
+}
+
+/// The seven vertices of a block arrow filling `t`, starting at the tail.
+fn arrow_vertices(
+    t: &Transform,
+    direction: ArrowDirection,
+    head_length: f32,
+    shaft_width: f32,
+) -> Vec<[f32; 2]> {
+    let horizontal = matches!(direction, ArrowDirection::Right | ArrowDirection::Left);
+    let (length, thickness) = if horizontal {
+        (t.width, t.height)
+    } else {
+        (t.height, t.width)
+    };
+    let neck = length * (1.0 - head_length.clamp(0.0, 1.0));
+    let half = thickness / 2.0;
+    let shaft = half * shaft_width.clamp(0.0, 1.0);
+
+    // (along, across) from the tail, across measured from the center line
+    let local = [
+        (0.0, -shaft),
+        (neck, -shaft),
+        (neck, -half),
+        (length, 0.0),
+        (neck, half),
+        (neck, shaft),
+        (0.0, shaft),
+    ];
+    local
+        .into_iter()
+        .map(|(along, across)| match direction {
+            ArrowDirection::Right => [t.x + along, t.y + half + across],
+            ArrowDirection::Left => [t.x + t.width - along, t.y + half + across],
+            ArrowDirection::Down => [t.x + half + across, t.y + along],
+            ArrowDirection::Up => [t.x + half + across, t.y + t.height - along],
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: [f32; 2], b: [f32; 2]) -> bool {
+        (a[0] - b[0]).abs() < 1e-3 && (a[1] - b[1]).abs() < 1e-3
+    }
+
+    fn vertices(shape: ShapeKind, t: &Transform) -> Vec<[f32; 2]> {
+        match ShapeOutline::new(&shape, t, 0.0) {
+            ShapeOutline::Polygon(points) => points,
+            other => panic!("expected a polygon, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_outlines_fill_the_box() {
+        let t = Transform {
+            x: 10.0,
+            y: 20.0,
+            width: 100.0,
+            height: 60.0,
+            ..Transform::default()
+        };
+        assert_eq!(
+            ShapeOutline::new(&ShapeKind::Rectangle, &t, 8.0),
+            ShapeOutline::Rect {
+                bounds: [10.0, 20.0, 100.0, 60.0],
+                radius: 8.0
+            }
+        );
+        assert_eq!(
+            ShapeOutline::new(&ShapeKind::Ellipse, &t, 8.0),
+            ShapeOutline::Ellipse {
+                center: [60.0, 50.0],
+                radii: [50.0, 30.0]
+            }
+        );
+
+        // Square: vertex at the top, then clockwise
+        let square = vertices(ShapeKind::Polygon { sides: 4 }, &t);
+        assert_eq!(square.len(), 4);
+        assert!(close(square[0], [60.0, 20.0]));
+        assert!(close(square[1], [110.0, 50.0]));
+
+        // Star alternates outer and inner radii
+        let star = vertices(
+            ShapeKind::Star {
+                points: 5,
+                inner_ratio: 0.5,
+            },
+            &t,
+        );
+        assert_eq!(star.len(), 10);
+        assert!(close(star[0], [60.0, 20.0]));
+        assert!(close(star[5], [60.0, 65.0]));
+    }
+
+    #[test]
+    fn test_arrow_directions() {
+        let t = Transform {
+            width: 100.0,
+            height: 40.0,
+            ..Transform::default()
+        };
+        let arrow = |direction| ShapeKind::Arrow {
+            direction,
+            head_length: 0.25,
+            shaft_width: 0.5,
+        };
+        let right = vertices(arrow(ArrowDirection::Right), &t);
+        assert_eq!(right.len(), 7);
+        assert!(close(right[0], [0.0, 10.0]));
+        assert!(close(right[1], [75.0, 10.0]));
+        assert!(close(right[2], [75.0, 0.0]));
+        assert!(close(right[3], [100.0, 20.0]));
+
+        assert!(close(
+            vertices(arrow(ArrowDirection::Left), &t)[3],
+            [0.0, 20.0]
+        ));
+        let tall = Transform {
+            width: 40.0,
+            height: 100.0,
+            ..Transform::default()
+        };
+        assert!(close(
+            vertices(arrow(ArrowDirection::Down), &tall)[3],
+            [20.0, 100.0]
+        ));
+        let up = vertices(arrow(ArrowDirection::Up), &tall);
+        assert!(close(up[3], [20.0, 0.0]));
+        assert!(close(up[1], [10.0, 25.0]));
+    }
+
+    #[test]
+    fn test_validate_and_serde() {
+        assert!(ShapeKind::Rectangle.validate().is_ok());
+        assert!(ShapeKind::Polygon { sides: 2 }.validate().is_err());
+        assert!(ShapeKind::Polygon { sides: 65 }.validate().is_err());
+        let star = |inner_ratio| ShapeKind::Star {
+            points: 5,
+            inner_ratio,
+        };
+        assert!(star(0.4).validate().is_ok());
+        assert!(star(1.0).validate().is_err());
+        assert!(star(f32::NAN).validate().is_err());
+        let arrow = |head_length| ShapeKind::Arrow {
+            direction: ArrowDirection::Up,
+            head_length,
+            shaft_width: 1.0,
+        };
+        assert!(arrow(1.0).validate().is_ok());
+        assert!(arrow(0.0).validate().is_err());
+
+        let star: ShapeKind = serde_json::from_str(r#"{"kind":"star"}"#).expect("star");
+        assert_eq!(
+            star,
+            ShapeKind::Star {
+                points: 5,
+                inner_ratio: 0.5
+            }
+        );
+        let json = serde_json::to_value(arrow(0.35)).expect("serialize");
+        assert_eq!(json["kind"], "arrow");
+        assert_eq!(json["direction"], "up");
+    }
+}
@@ -0,0 +1,180 @@
+//! # Sparklines
+//!
+//! Word-sized trend lines and bar series without axes or labels, drawn
+//! inline next to text in A2UI rows and grids, as `Sparkline` elements, and
+//! on KPI cards.
+//!
+//! ```text
+//!  Revenue   $1.2M   ╱╲__╱‾‾╲_╱‾•      line: values scaled min → max
+//!  Signups     840   ▂▃▅▂▇▆▃▅         bars: drawn from zero
+//! ```
+//!
+//! The helpers here compute geometry in whatever frame the caller passes
+//! as the drawing area, so every renderer draws the same marks without the
+//! overhead of a full chart.
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{CanvasError, CanvasResult};
+
+/// Most values a sparkline may hold.
+pub const MAX_SPARKLINE_POINTS: usize = 256;
+
+/// Whether a sparkline is drawn as a line or as bars.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+#[serde(rename_all = "lowercase")]
+pub enum SparklineKind {
+    /// A line through the values, scaled between their minimum and maximum,
+    /// with a dot on the latest value.
+    #[default]
+    Line,
+    /// One bar per value, drawn up (or down) from zero.
+    Bar,
+}
+
+/// Space around a `Sparkline` element's marks, so strokes are not clipped.
+pub const SPARKLINE_PADDING: f32 = 2.0;
+
+/// Radius of the dot on a line sparkline's latest value.
+pub const SPARKLINE_DOT_RADIUS: f32 = 2.0;
+
+/// Drawing area of a `width` × `height` `Sparkline` element as
+/// `[x, y, width, height]` in its local frame.
+#[must_use]
+pub fn sparkline_area(width: f32, height: f32) -> [f32; 4] {
+    let pad = SPARKLINE_PADDING;
+    [
+        pad,
+        pad,
+        (width - 2.0 * pad).max(0.0),
+        (height - 2.0 * pad).max(0.0),
+    ]
+}
+
+/// Points of a line through `values`, spread evenly across `area`
+/// (`[x, y, width, height]`) and scaled between their minimum and maximum.
+///
+/// A flat series is drawn across the middle; non-finite values are skipped.
+#[must_use]
+#[allow(clippy::cast_precision_loss)]
+pub fn sparkline_points(values: &[f32], area: [f32; 4]) -> Vec<(f32, f32)> {
+    let [x, y, width, height] = area;
+    let (min, max) = range(values);
+    let step = width / (values.len().max(2) - 1) as f32;
+    values
+        .iter()
+        .enumerate()
+        .filter(|(_, v)| v.is_finite())
+        .map(|(i, &v)| {
+            let level = if max > min {
+                (v - min) / (max - min)
+            } else {
+                0.5
+            };
+            (x + i as f32 * step, y + height * (1.0 - level))
+        })
+        .collect()
+}
+
+/// Bars for `values` across `area` (`[x, y, width, height]`), each as
+/// `[x, y, width, height]`.
+///
+/// Bars rise from zero for positive values and hang from it for negative
+/// ones; the scale always includes zero. Non-finite values leave a gap.
+#[must_use]
+#[allow(clippy::cast_precision_loss)]
+pub fn sparkline_bars(values: &[f32], area: [f32; 4]) -> Vec<[f32; 4]> {
+    let [x, y, width, height] = area;
+    let (min, max) = range(values);
+    let (low, high) = (min.min(0.0), max.max(0.0));
+    let span = if high > low { high - low } else { 1.0 };
+    let level_y = |v: f32| y + height * (high - v) / span;
+    let zero_y = level_y(0.0);
+    let slot = width / values.len().max(1) as f32;
+    values
+        .iter()
+        .enumerate()
+        .filter(|(_, v)| v.is_finite())
+        .map(|(i, &v)| {
+            let top = level_y(v);
+            [
+                x + i as f32 * slot + slot * 0.15,
+                top.min(zero_y),
+                slot * 0.7,
+                (top - zero_y).abs(),
+            ]
+        })
+        .collect()
+}
+
+/// Smallest and largest finite values (`(∞, -∞)` when there are none).
+fn range(values: &[f32]) -> (f32, f32) {
+    values
+        .iter()
+        .copied()
+        .filter(|v| v.is_finite())
+        .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), v| {
+            (min.min(v), max.max(v))
+        })
+}
+
+/// Check a sparkline's values.
+///
+/// # Errors
+///
+/// Returns [`CanvasError::InvalidOperation`] if there are more than
+/// [`MAX_SPARKLINE_POINTS`] values or one is not finite.
+pub fn validate(values: &[f32]) -> CanvasResult<()> {
+    if values.len() > MAX_SPARKLINE_POINTS {
+        return Err(CanvasError::InvalidOperation(format!(
+            "Sparklines hold at most {MAX_SPARKLINE_POINTS} values, got {}",
+            values.len()
+        )));
+    }
+    if values.iter().any(|v| !v.is_finite()) {
+        return Err(CanvasError::InvalidOperation(
+            "Sparkline values must be finite".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_rect(actual: [f32; 4], expected: [f32; 4]) {
+        assert!(
+            actual
+                .iter()
+                .zip(expected)
+                .all(|(a, e)| (a - e).abs() < 1e-4),
+            "{actual:?} != {expected:?}"
+        );
+    }
+
+    #[test]
+    fn test_line_and_bar_geometry() {
+        let area = [0.0, 0.0, 100.0, 20.0];
+        let points = sparkline_points(&[1.0, 3.0, 2.0], area);
+        assert_eq!(points, vec![(0.0, 20.0), (50.0, 0.0), (100.0, 10.0)]);
+        assert_eq!(sparkline_points(&[4.0, 4.0], area)[0], (0.0, 10.0));
+
+        // Mixed signs share one zero line
+        let bars = sparkline_bars(&[2.0, -2.0, f32::NAN, 0.0], area);
+        assert_eq!(bars.len(), 3);
+        assert_rect(bars[0], [3.75, 0.0, 17.5, 10.0]);
+        assert_rect(bars[1], [28.75, 10.0, 17.5, 10.0]);
+        assert!(bars[2][3].abs() < f32::EPSILON);
+        // All-positive bars rise from the bottom edge
+        let bars = sparkline_bars(&[1.0, 2.0], area);
+        assert!((bars[0][1] + bars[0][3] - 20.0).abs() < f32::EPSILON);
+        assert!(bars[1][1].abs() < f32::EPSILON);
+
+        assert!(validate(&[1.0; MAX_SPARKLINE_POINTS]).is_ok());
+        assert!(validate(&[1.0; MAX_SPARKLINE_POINTS + 1]).is_err());
+        assert!(validate(&[f32::INFINITY]).is_err());
+        assert_rect(sparkline_area(10.0, 3.0), [2.0, 2.0, 6.0, 0.0]);
+    }
+}
@@ -0,0 +1,426 @@
+//! # Spatial Index
+//!
+//! A quadtree over element bounds, so hit testing, viewport culling and
+//! region queries visit the elements near the area asked about instead of
+//! every element in the scene.
+//!
+//! ```text
+//!  ┌───────────┬───────────┐
+//!  │ ▢    ▢    │           │   each item lives in the smallest node
+//!  ├─────┬─────┤     ▢     │   that fully contains it; items that
+//!  │ ▢   │  ▢  │           │   straddle a split stay in the parent
+//!  ├─────┴─────┼───────────┤
+//!  │       ▢───┼──▢        │
+//!  │           │           │
+//!  └───────────┴───────────┘
+//! ```
+//!
+//! A node splits into quadrants once it holds more than `NODE_CAPACITY`
+//! items. The tree grows outward when an item lands outside it, so scenes
+//! need no fixed extent.
+//!
+//! Queries return candidates whose axis-aligned bounds overlap the query
+//! area; callers do the exact, rotation-aware test (see
+//! [`crate::geometry`]) on those.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::camera::SceneBounds;
+use crate::ElementId;
+
+/// Items a node holds before splitting into quadrants.
+const NODE_CAPACITY: usize = 8;
+/// Deepest level a node may split to.
+const MAX_DEPTH: u32 = 16;
+/// Times the root may double to reach an item before it is kept aside.
+const MAX_GROWTH: u32 = 64;
+/// Side length of the first root node.
+const MIN_ROOT_SIZE: f32 = 256.0;
+
+/// Quadtree of element bounds.
+#[derive(Debug, Clone, Default)]
+pub struct SpatialIndex {
+    /// Indexed bounds per element.
+    bounds: HashMap<ElementId, SceneBounds>,
+    /// Root of the tree, created by the first insert.
+    root: Option<Node>,
+    /// Elements with non-finite or far-off bounds, returned by every query.
+    unbounded: HashSet<ElementId>,
+}
+
+#[derive(Debug, Clone)]
+struct Node {
+    /// Square area covered by this node.
+    bounds: SceneBounds,
+    /// Elements that fit this node but none of its quadrants.
+    items: Vec<ElementId>,
+    /// Quadrants, in NW, NE, SW, SE order.
+    children: Option<Box<[Node; 4]>>,
+}
+
+impl SpatialIndex {
+    /// Create an empty index.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of indexed elements.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.bounds.len()
+    }
+
+    /// Check whether the index is empty.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.bounds.is_empty()
+    }
+
+    /// Indexed bounds of an element.
+    #[must_use]
+    pub fn bounds(&self, id: ElementId) -> Option<SceneBounds> {
+        self.bounds.get(&id).copied()
+    }
+
+    /// Index an element, replacing any bounds it was indexed with before.
+    pub fn insert(&mut self, id: ElementId, bounds: SceneBounds) {
+        self.remove(id);
+        let bounds = normalized(bounds);
+        self.bounds.insert(id, bounds);
+
+        let finite = [bounds.min_x, bounds.min_y, bounds.max_x, bounds.max_y]
+            .iter()
+            .all(|v| v.is_finite());
+        if !finite {
+            self.unbounded.insert(id);
+            return;
+        }
+
+        let root = match self.root.take() {
+            Some(root) if !root.is_empty() => root,
+            _ => Node::new(square(
+                bounds.min_x,
+                bounds.min_y,
+                bounds.width().max(bounds.height()).max(MIN_ROOT_SIZE),
+            )),
+        };
+        let mut root = root;
+        let mut growth = 0;
+        while !root.bounds.contains(&bounds) && growth < MAX_GROWTH {
+            root = root.grow_toward(&bounds);
+            growth += 1;
+        }
+        if root.bounds.contains(&bounds) {
+            root.insert(id, bounds, &self.bounds, 0);
+        } else {
+            self.unbounded.insert(id);
+        }
+        self.root = Some(root);
+    }
+
+    /// Remove an element, returning the bounds it was indexed with.
+    pub fn remove(&mut self, id: ElementId) -> Option<SceneBounds> {
+        let bounds = self.bounds.remove(&id)?;
+        if !self.unbounded.remove(&id) {
+            if let Some(root) = &mut self.root {
+                root.remove(id, &bounds);
+            }
+        }
+        Some(bounds)
+    }
+
+    /// Remove every element.
+    pub fn clear(&mut self) {
+        self.bounds.clear();
+        self.root = None;
+        self.unbounded.clear();
+    }
+
+    /// Elements whose indexed bounds overlap `area` (touching edges count).
+    ///
+    /// The order is unspecified.
+    #[must_use]
+    pub fn query(&self, area: &SceneBounds) -> Vec<ElementId> {
+        let mut found: Vec<ElementId> = self.unbounded.iter().copied().collect();
+        if let Some(root) = &self.root {
+            root.query(area, &self.bounds, &mut found);
+        }
+        found
+    }
+
+    /// Elements whose indexed bounds contain the point.
+    #[must_use]
+    pub fn query_point(&self, x: f32, y: f32) -> Vec<ElementId> {
+        self.query(&SceneBounds {
+            min_x: x,
+            min_y: y,
+            max_x: x,
+            max_y: y,
+        })
+    }
+}
+
+impl Node {
+    fn new(bounds: SceneBounds) -> Self {
+        Self {
+            bounds,
+            items: Vec::new(),
+            children: None,
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.items.is_empty()
+            && self
+                .children
+                .as_ref()
+                .is_none_or(|children| children.iter().all(Node::is_empty))
+    }
+
+    fn insert(
+        &mut self,
+        id: ElementId,
+        bounds: SceneBounds,
+        all: &HashMap<ElementId, SceneBounds>,
+        depth: u32,
+    ) {
+        if let Some(children) = &mut self.children {
+            if let Some(child) = children.iter_mut().find(|c| c.bounds.contains(&bounds)) {
+                child.insert(id, bounds, all, depth + 1);
+                return;
+            }
+        }
+        self.items.push(id);
+        if self.children.is_none() && self.items.len() > NODE_CAPACITY && depth < MAX_DEPTH {
+            self.split(all, depth);
+        }
+    }
+
+    /// Create quadrants and push down the items that fit one.
+    fn split(&mut self, all: &HashMap<ElementId, SceneBounds>, depth: u32) {
+        self.children = Some(Box::new(quadrants(&self.bounds).map(Node::new)));
+        for id in std::mem::take(&mut self.items) {
+            if let Some(&bounds) = all.get(&id) {
+                self.insert(id, bounds, all, depth);
+            }
+        }
+    }
+
+    fn remove(&mut self, id: ElementId, bounds: &SceneBounds) -> bool {
+        if let Some(position) = self.items.iter().position(|&item| item == id) {
+            self.items.swap_remove(position);
+            return true;
+        }
+        // Items on a quadrant edge fit more than one child; try each
+        self.children.as_mut().is_some_and(|children| {
+            children
+                .iter_mut()
+                .filter(|child| child.bounds.contains(bounds))
+                .any(|child| child.remove(id, bounds))
+        })
+    }
+
+    fn query(
+        &self,
+        area: &SceneBounds,
+        all: &HashMap<ElementId, SceneBounds>,
+        found: &mut Vec<ElementId>,
+    ) {
+        if !self.bounds.intersects(area) {
+            return;
+        }
+        found.extend(
+            self.items
+                .iter()
+                .filter(|id| all.get(id).is_some_and(|b| b.intersects(area))),
+        );
+        if let Some(children) = &self.children {
+            for child in children.iter() {
+                child.query(area, all, found);
+            }
+        }
+    }
+
+    /// Double the node toward `target`, keeping `self` as one quadrant.
+    fn grow_toward(self, target: &SceneBounds) -> Self {
+        let size = self.bounds.width();
+        let left = target.min_x < self.bounds.min_x;
+        let up = target.min_y < self.bounds.min_y;
+        let min_x = if left {
+            self.bounds.min_x - size
+        } else {
+            self.bounds.min_x
+        };
+        let min_y = if up {
+            self.bounds.min_y - size
+        } else {
+            self.bounds.min_y
+        };
+        let bounds = square(min_x, min_y, size * 2.0);
+
+        let mut children = quadrants(&bounds).map(Node::new);
+        // Growing left or up leaves the old root on the right or bottom
+        children[usize::from(left) + 2 * usize::from(up)] = self;
+        Self {
+            bounds,
+            items: Vec::new(),
+            children: Some(Box::new(children)),
+        }
+    }
+}
+
+/// Square bounds with the given top-left corner and side.
+fn square(min_x: f32, min_y: f32, size: f32) -> SceneBounds {
+    SceneBounds {
+        min_x,
+        min_y,
+        max_x: min_x + size,
+        max_y: min_y + size,
+    }
+}
+
+/// The four quadrants of `bounds`, in NW, NE, SW, SE order.
+fn quadrants(bounds: &SceneBounds) -> [SceneBounds; 4] {
+    let (mid_x, mid_y) = bounds.center();
+    [
+        SceneBounds {
+            max_x: mid_x,
+            max_y: mid_y,
+            ..*bounds
+        },
+        SceneBounds {
+            min_x: mid_x,
+            max_y: mid_y,
+            ..*bounds
+        },
+        SceneBounds {
+            max_x: mid_x,
+            min_y: mid_y,
+            ..*bounds
+        },
+        SceneBounds {
+            min_x: mid_x,
+            min_y: mid_y,
+            ..*bounds
+        },
+    ]
+}
+
+/// Bounds with min and max in order, for elements with negative sizes.
+fn normalized(bounds: SceneBounds) -> SceneBounds {
+    SceneBounds {
+        min_x: bounds.min_x.min(bounds.max_x),
+        min_y: bounds.min_y.min(bounds.max_y),
+        max_x: bounds.min_x.max(bounds.max_x),
+        max_y: bounds.min_y.max(bounds.max_y),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect(x: f32, y: f32, width: f32, height: f32) -> SceneBounds {
+        SceneBounds {
+            min_x: x,
+            min_y: y,
+            max_x: x + width,
+            max_y: y + height,
+        }
+    }
+
+    fn sorted(mut ids: Vec<ElementId>) -> Vec<ElementId> {
+        ids.sort_by_key(ToString::to_string);
+        ids
+    }
+
+    #[test]
+    fn test_query_matches_linear_scan() {
+        let mut index = SpatialIndex::new();
+        let mut all = Vec::new();
+        // A 40x40 grid spread far enough to force splits and growth
+        for row in 0..40_u8 {
+            for col in 0..40_u8 {
+                let id = ElementId::new();
+                let bounds = rect(
+                    f32::from(col) * 75.0 - 1_000.0,
+                    f32::from(row) * 60.0 - 500.0,
+                    50.0,
+                    40.0,
+                );
+                index.insert(id, bounds);
+                all.push((id, bounds));
+            }
+        }
+        assert_eq!(index.len(), 1_600);
+
+        for area in [
+            rect(0.0, 0.0, 300.0, 200.0),
+            rect(-2_000.0, -2_000.0, 10.0, 10.0),
+            rect(-1_000.0, -500.0, 3_000.0, 2_400.0),
+            rect(1_012.0, 1_000.0, 0.0, 0.0),
+        ] {
+            let expected: Vec<_> = all
+                .iter()
+                .filter(|(_, b)| b.intersects(&area))
+                .map(|(id, _)| *id)
+                .collect();
+            assert_eq!(sorted(index.query(&area)), sorted(expected));
+        }
+    }
+
+    #[test]
+    fn test_move_and_remove() {
+        let mut index = SpatialIndex::new();
+        let ids: Vec<_> = (0..20_u8)
+            .map(|i| {
+                let id = ElementId::new();
+                index.insert(id, rect(f32::from(i) * 10.0, 0.0, 5.0, 5.0));
+                id
+            })
+            .collect();
+
+        // Re-inserting moves an element
+        index.insert(ids[0], rect(5_000.0, 5_000.0, 5.0, 5.0));
+        assert!(!index.query_point(2.0, 2.0).contains(&ids[0]));
+        assert_eq!(index.query_point(5_002.0, 5_002.0), vec![ids[0]]);
+        assert_eq!(index.len(), 20);
+
+        assert!(index.remove(ids[1]).is_some());
+        assert!(index.remove(ids[1]).is_none());
+        assert!(index.query_point(12.0, 2.0).is_empty());
+        assert_eq!(index.len(), 19);
+
+        index.clear();
+        assert!(index.is_empty());
+        assert!(index.query(&rect(-1e6, -1e6, 2e6, 2e6)).is_empty());
+    }
+
+    #[test]
+    fn test_edges_and_degenerate_bounds() {
+        let mut index = SpatialIndex::new();
+        // Zero-size items exactly on quadrant edges
+        let points: Vec<_> = (0..=16_u8)
+            .map(|i| {
+                let id = ElementId::new();
+                index.insert(id, rect(128.0, f32::from(i) * 16.0, 0.0, 0.0));
+                id
+            })
+            .collect();
+        for &id in &points {
+            assert!(index.remove(id).is_some());
+        }
+        assert!(index.query(&rect(0.0, 0.0, 512.0, 512.0)).is_empty());
+
+        // Negative sizes are normalized; non-finite bounds match everything
+        let flipped = ElementId::new();
+        index.insert(flipped, rect(100.0, 100.0, -50.0, -50.0));
+        assert_eq!(index.query_point(75.0, 75.0), vec![flipped]);
+        let infinite = ElementId::new();
+        index.insert(infinite, rect(f32::INFINITY, 0.0, 1.0, 1.0));
+        assert!(index.query_point(-9_999.0, 0.0).contains(&infinite));
+        assert!(index.remove(infinite).is_some());
+        assert!(!index.query_point(-9_999.0, 0.0).contains(&infinite));
+    }
+}
@@ -0,0 +1,300 @@
+//! # Spotlight
+//!
+//! Draws attention to a set of elements by dimming everything else.
+//!
+//! A spotlight is part of the scene state, so it syncs to every viewer like
+//! any other change. Renderers draw a translucent overlay above the scene and
+//! then redraw the spotlighted elements on top of it. A spotlight can ask
+//! viewers to zoom the camera to fit its elements and can carry an expiry
+//! time, after which renderers ignore it.
+//!
+//! ```text
+//! agent: "focus on the revenue chart"
+//!   → match_elements(scene, "revenue chart") = [chart]
+//!   → scene.set_spotlight(Spotlight::new([chart]).with_zoom_to_fit(true))
+//! ```
+//!
+//! Time is passed in as milliseconds since the Unix epoch so the same logic
+//! runs natively and in WASM.
+
+use serde::{Deserialize, Serialize};
+
+use crate::element::{Element, ElementId, ElementKind};
+use crate::log_view;
+use crate::randomizer::RandomSource;
+use crate::scene::Scene;
+
+/// Words ignored when matching a spoken description against elements.
+const STOP_WORDS: &[&str] = &[
+    "a", "an", "and", "at", "me", "of", "on", "please", "show", "that", "the", "this", "to",
+];
+
+/// Elements highlighted above a dimmed scene.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+pub struct Spotlight {
+    /// Elements that stay fully visible.
+    pub element_ids: Vec<ElementId>,
+    /// Opacity of the dimming overlay (0.0 = no dimming, 1.0 = black).
+    #[serde(default = "Spotlight::default_dim_opacity")]
+    pub dim_opacity: f32,
+    /// Whether viewers should zoom the camera to fit the elements.
+    #[serde(default)]
+    pub zoom_to_fit: bool,
+    /// When the spotlight stops applying (ms since the Unix epoch).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at_ms: Option<u64>,
+}
+
+impl Spotlight {
+    /// Default overlay opacity.
+    pub const DEFAULT_DIM_OPACITY: f32 = 0.6;
+
+    const fn default_dim_opacity() -> f32 {
+        Self::DEFAULT_DIM_OPACITY
+    }
+
+    /// Create a spotlight on the given elements with default dimming.
+    #[must_use]
+    pub fn new(element_ids: impl IntoIterator<Item = ElementId>) -> Self {
+        Self {
+            element_ids: element_ids.into_iter().collect(),
+            dim_opacity: Self::DEFAULT_DIM_OPACITY,
+            zoom_to_fit: false,
+            expires_at_ms: None,
+        }
+    }
+
+    /// Set the overlay opacity (clamped to 0.0..=1.0).
+    #[must_use]
+    pub fn with_dim_opacity(mut self, opacity: f32) -> Self {
+        self.dim_opacity = opacity.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Set whether viewers should zoom to fit the elements.
+    #[must_use]
+    pub const fn with_zoom_to_fit(mut self, zoom_to_fit: bool) -> Self {
+        self.zoom_to_fit = zoom_to_fit;
+        self
+    }
+
+    /// Expire the spotlight `timeout_ms` after `now_ms`.
+    #[must_use]
+    pub const fn with_timeout(mut self, now_ms: u64, timeout_ms: u64) -> Self {
+        self.expires_at_ms = Some(now_ms.saturating_add(timeout_ms));
+        self
+    }
+
+    /// Check whether the spotlight has expired at `now_ms`.
+    #[must_use]
+    pub fn is_expired(&self, now_ms: u64) -> bool {
+        self.expires_at_ms.is_some_and(|expires| now_ms >= expires)
+    }
+
+    /// Check whether an element is spotlighted.
+    #[must_use]
+    pub fn contains(&self, id: ElementId) -> bool {
+        self.element_ids.contains(&id)
+    }
+}
+
+/// Find elements matching a spoken or written description.
+///
+/// The description is split into words, filler words ("the", "on", ...) are
+/// dropped, and an element matches when every remaining word appears in its
+/// kind name or text content. For example "revenue chart" matches a chart
+/// whose data has the title "Quarterly Revenue". Results are in z-order.
+#[must_use]
+pub fn match_elements(scene: &Scene, query: &str) -> Vec<ElementId> {
+    let query = query.to_lowercase();
+    let words: Vec<&str> = query
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty() && !STOP_WORDS.contains(word))
+        .collect();
+    if words.is_empty() {
+        return Vec::new();
+    }
+
+    let mut matches: Vec<&Element> = scene
+        .elements()
+        .filter(|element| {
+            let label = element_label(element);
+            words.iter().all(|word| label.contains(word))
+        })
+        .collect();
+    matches.sort_by_key(|element| element.transform.z_index);
+    matches.into_iter().map(|element| element.id).collect()
+}
+
+/// Lowercase searchable text for an element.
+#[allow(clippy::too_many_lines)]
+fn element_label(element: &Element) -> String {
+    let mut label = match &element.kind {
+        ElementKind::Chart {
+            chart_type, data, ..
+        } => {
+            let mut text = format!("chart {chart_type}");
+            if let Some(fields) = data.as_object() {
+                for value in fields.values().filter_map(serde_json::Value::as_str) {
+                    text.push(' ');
+                    text.push_str(value);
+                }
+            }
+            text
+        }
+        ElementKind::Image {
+            src,
+            extracted_text,
+            ..
+        } => match extracted_text {
+            Some(extracted) => format!("image picture {src} {}", extracted.text),
+            None => format!("image picture {src}"),
+        },
+        ElementKind::Model3D { src, .. } => format!("model 3d {src}"),
+        ElementKind::Shader { .. } => "shader generative visual animation".to_string(),
+        ElementKind::Video { stream_id, .. } => format!("video camera {stream_id}"),
+        ElementKind::Audio { stream_id, .. } => format!("audio voice note sound {stream_id}"),
+        ElementKind::Embed { url, .. } => format!("embed web page dashboard {url}"),
+        ElementKind::Calendar { date, events, .. } => {
+            let mut text = format!("calendar schedule {date}");
+            for event in events {
+                text.push(' ');
+                text.push_str(&event.title);
+            }
+            text
+        }
+        ElementKind::Poll {
+            question, options, ..
+        } => format!("poll vote survey {question} {}", options.join(" ")),
+        ElementKind::Agenda { title, items, .. } => {
+            let mut text = format!("agenda schedule timer countdown {title}");
+            for item in items {
+                text.push(' ');
+                text.push_str(&item.label());
+            }
+            text
+        }
+        ElementKind::Randomizer { source, .. } => {
+            let entries = match source {
+                RandomSource::Dice { .. } => String::new(),
+                RandomSource::Wheel { segments: entries }
+                | RandomSource::Shuffle { items: entries } => entries.join(" "),
+            };
+            format!("randomizer random {} {entries}", source.mode())
+        }
+        ElementKind::FloorPlan { markers, .. } => {
+            let mut text = "floor plan seating chart map".to_string();
+            for marker in markers {
+                text.push(' ');
+                text.push_str(marker.display_label());
+            }
+            text
+        }
+        ElementKind::Gauge { label, .. } => format!("gauge meter dial {label}"),
+        ElementKind::Kpi { label, .. } => format!("kpi metric number card {label}"),
+        ElementKind::Sparkline { .. } => "sparkline trend".to_string(),
+        ElementKind::Ink { .. } => "ink stroke drawing".to_string(),
+        ElementKind::Measurement { label, .. } => format!(
+            "measurement dimension ruler {}",
+            label.as_deref().unwrap_or_default()
+        ),
+        ElementKind::Region { caption, .. } => format!(
+            "region highlight annotation {}",
+            caption.as_deref().unwrap_or_default()
+        ),
+        ElementKind::Shape { shape } => format!("shape {}", shape.name()),
+        ElementKind::Barcode { payload, .. } => format!("qr code barcode {payload}"),
+        ElementKind::Tree { root, .. } => {
+            let mut text = "tree org chart hierarchy".to_string();
+            let mut stack = vec![root];
+            while let Some(node) = stack.pop() {
+                text.push(' ');
+                text.push_str(&node.label);
+                stack.extend(&node.children);
+            }
+            text
+        }
+        ElementKind::OverlayLayer { .. } => "overlay layer".to_string(),
+        ElementKind::Text { content, .. } => format!("text label {content}"),
+        ElementKind::Math { tex, .. } => format!("math formula equation {tex}"),
+        ElementKind::Code {
+            language, source, ..
+        } => format!("code {language} {source}"),
+        ElementKind::LogView { title, lines, .. } => format!(
+            "log terminal console output {title} {}",
+            log_view::tail_text(lines, 20)
+        ),
+        ElementKind::StickyNote {
+            content, author, ..
+        } => format!(
+            "sticky note {content} {}",
+            author.as_deref().unwrap_or_default()
+        ),
+        ElementKind::Callout {
+            content, author, ..
+        } => format!(
+            "callout note {content} {}",
+            author.as_deref().unwrap_or_default()
+        ),
+        ElementKind::Group { .. } => "group".to_string(),
+    };
+    label.make_ascii_lowercase();
+    label
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Color, Transform};
+
+    fn chart(title: &str) -> Element {
+        Element::new(ElementKind::Chart {
+            chart_type: "bar".to_string(),
+            data: serde_json::json!({ "title": title, "values": [1, 2, 3] }),
+            binding: None,
+        })
+    }
+
+    #[test]
+    fn test_match_elements_by_description() {
+        let mut scene = Scene::new(800.0, 600.0);
+        let revenue = scene.add_element(chart("Quarterly Revenue"));
+        scene.add_element(chart("Headcount"));
+        scene.add_element(Element::new(ElementKind::Text {
+            content: "Revenue is up".to_string(),
+            font_size: 16.0,
+            color: Color::BLACK.into(),
+        }));
+
+        assert_eq!(match_elements(&scene, "the revenue chart"), vec![revenue]);
+        assert_eq!(match_elements(&scene, "revenue").len(), 2);
+        assert!(match_elements(&scene, "the").is_empty());
+        assert!(match_elements(&scene, "pie").is_empty());
+    }
+
+    #[test]
+    fn test_spotlight_expiry_and_pruning() {
+        let mut scene = Scene::new(800.0, 600.0);
+        let a = scene.add_element(chart("A").with_transform(Transform::default()));
+        let b = scene.add_element(chart("B"));
+
+        let spotlight = Spotlight::new([a, b]).with_timeout(1_000, 500);
+        assert!(!spotlight.is_expired(1_499));
+        assert!(spotlight.is_expired(1_500));
+
+        scene.set_spotlight(spotlight).expect("valid spotlight");
+        scene.remove_element(&a).expect("remove");
+        assert_eq!(
+            scene.spotlight().map(|s| s.element_ids.clone()),
+            Some(vec![b])
+        );
+
+        // Removing the last spotlighted element clears the spotlight
+        scene.remove_element(&b).expect("remove");
+        assert!(scene.spotlight().is_none());
+
+        let missing = Spotlight::new([ElementId::new()]);
+        assert!(scene.set_spotlight(missing).is_err());
+    }
+}
@@ -2,7 +2,7 @@
 
 use serde::{Deserialize, Serialize};
 
-use crate::{InputEvent, Scene};
+use crate::{ElementId, ElementInteraction, InputEvent, InteractionRouter, Scene, Selection};
 
 /// Connection status to the AI/MCP.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -41,8 +41,14 @@ pub struct CanvasState {
     pub connection: ConnectionStatus,
     /// Current interaction mode.
     pub mode: InteractionMode,
+    /// Elements selected by the local user.
+    #[serde(default)]
+    pub selection: Selection,
     /// Pending events to sync when reconnected.
     pending_sync: Vec<InputEvent>,
+    /// Gesture matching for element interactions (tracks the active drag).
+    #[serde(skip)]
+    interactions: InteractionRouter,
     /// Whether there are unsaved local changes.
     pub has_local_changes: bool,
 }
@@ -55,13 +61,19 @@ impl CanvasState {
             scene: Scene::new(width, height),
             connection: ConnectionStatus::Connecting,
             mode: InteractionMode::Select,
+            selection: Selection::new(),
             pending_sync: Vec::new(),
             has_local_changes: false,
+            interactions: InteractionRouter::new(),
         }
     }
 
     /// Process an input event.
-    pub fn process_event(&mut self, event: &InputEvent) {
+    ///
+    /// Returns the interaction triggered by the event, if it hit an element
+    /// with a matching declared behavior (see [`crate::interaction`]). Drags
+    /// move the element in the scene before returning.
+    pub fn process_event(&mut self, event: &InputEvent) -> Option<ElementInteraction> {
         // If offline, queue for later sync
         if self.connection == ConnectionStatus::Offline {
             self.pending_sync.push(event.clone());
@@ -86,6 +98,29 @@ impl CanvasState {
         }
 
         self.has_local_changes = true;
+        self.interactions.handle(&mut self.scene, event)
+    }
+
+    /// Select the element at a screen point, or clear the selection if
+    /// there is none.
+    ///
+    /// With `extend` (shift-click), the element is toggled instead and an
+    /// empty point leaves the selection unchanged. Returns the element hit.
+    pub fn select_at(&mut self, x: f32, y: f32, extend: bool) -> Option<ElementId> {
+        let hit = self.scene.element_at(x, y);
+        match (hit, extend) {
+            (Some(id), true) => {
+                self.selection.toggle(&mut self.scene, id);
+            }
+            (Some(id), false) => {
+                self.selection.select(&mut self.scene, id);
+            }
+            (None, true) => {}
+            (None, false) => {
+                self.selection.clear(&mut self.scene);
+            }
+        }
+        hit
     }
 
     /// Set connection status.
@@ -48,7 +48,7 @@ pub enum StoreError {
 ///
 /// ```
 /// use canvas_core::store::SceneStore;
-/// use canvas_core::{Element, ElementKind};
+/// use canvas_core::{Color, Element, ElementKind};
 ///
 /// let store = SceneStore::new();
 ///
@@ -56,7 +56,7 @@ pub enum StoreError {
 /// let element = Element::new(ElementKind::Text {
 ///     content: "Hello".to_string(),
 ///     font_size: 16.0,
-///     color: "#000000".to_string(),
+///     color: Color::BLACK.into(),
 /// });
 ///
 /// let id = store.add_element("default", element).unwrap();
@@ -134,18 +134,23 @@ impl SceneStore {
 
     /// Replace the entire scene for a session.
     ///
-    /// Creates the session if it does not exist.
+    /// Creates the session if it does not exist. The new scene
+    /// [supersedes](Scene::supersede) the old one, so revisions keep
+    /// increasing.
     ///
     /// # Errors
     ///
     /// Returns [`StoreError::LockPoisoned`] if the lock is poisoned (currently
     /// recovered from, so this variant is reserved for future stricter modes).
-    pub fn replace(&self, session_id: &str, scene: Scene) -> Result<(), StoreError> {
+    pub fn replace(&self, session_id: &str, mut scene: Scene) -> Result<(), StoreError> {
         {
             let mut scenes = self
                 .scenes
                 .write()
                 .unwrap_or_else(std::sync::PoisonError::into_inner);
+            if let Some(previous) = scenes.get(session_id) {
+                scene.supersede(previous);
+            }
             scenes.insert(session_id.to_string(), scene);
         }
         self.persist_session(session_id);
@@ -224,6 +229,9 @@ impl SceneStore {
 
     /// Update an element using a closure.
     ///
+    /// Markup pinned to an image moves and scales with it (see
+    /// [`Scene::update_element`]).
+    ///
     /// # Errors
     ///
     /// Returns [`StoreError::SessionNotFound`] if the session does not exist.
@@ -240,10 +248,9 @@ impl SceneStore {
             let scene = scenes
                 .get_mut(session_id)
                 .ok_or_else(|| StoreError::SessionNotFound(session_id.to_string()))?;
-            let element = scene
-                .get_element_mut(id)
-                .ok_or_else(|| StoreError::ElementNotFound(id.to_string()))?;
-            f(element);
+            scene
+                .update_element(id, f)
+                .map_err(|_| StoreError::ElementNotFound(id.to_string()))?;
         }
         self.persist_session(session_id);
         Ok(())
@@ -267,6 +274,30 @@ impl SceneStore {
         }
     }
 
+    /// Get the current revision of a session's scene without copying it.
+    ///
+    /// Returns `None` if the session does not exist.
+    #[must_use]
+    pub fn revision(&self, session_id: &str) -> Option<u64> {
+        let scenes = self
+            .scenes
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        scenes.get(session_id).map(Scene::revision)
+    }
+
+    /// Get the number of elements in a session's scene without copying it.
+    ///
+    /// Returns 0 if the session does not exist.
+    #[must_use]
+    pub fn element_count(&self, session_id: &str) -> usize {
+        let scenes = self
+            .scenes
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        scenes.get(session_id).map_or(0, Scene::element_count)
+    }
+
     /// Get a list of all session IDs.
     #[must_use]
     pub fn session_ids(&self) -> Vec<String> {
@@ -322,9 +353,7 @@ impl SceneStore {
 
         // Rebuild Scene from SceneDocument.
         let mut scene = Scene::new(doc.viewport.width, doc.viewport.height);
-        scene.zoom = doc.viewport.zoom;
-        scene.pan_x = doc.viewport.pan_x;
-        scene.pan_y = doc.viewport.pan_y;
+        scene.set_camera(doc.viewport.zoom, doc.viewport.pan_x, doc.viewport.pan_y);
         for elem_doc in &doc.elements {
             let element = crate::Element::new(elem_doc.kind.clone())
                 .with_transform(elem_doc.transform)
@@ -336,6 +365,9 @@ impl SceneStore {
             .scenes
             .write()
             .unwrap_or_else(std::sync::PoisonError::into_inner);
+        if let Some(previous) = scenes.get(session_id) {
+            scene.supersede(previous);
+        }
         scenes.insert(session_id.to_string(), scene);
         Ok(())
     }
@@ -431,7 +463,7 @@ fn current_timestamp_ms() -> u64 {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::ElementKind;
+    use crate::{Color, ElementKind};
 
     #[test]
     fn test_new_creates_default_session() {
@@ -468,7 +500,7 @@ mod tests {
         let element = Element::new(ElementKind::Text {
             content: "Hello".to_string(),
             font_size: 16.0,
-            color: "#000000".to_string(),
+            color: Color::BLACK.into(),
         });
 
         let id = store
@@ -485,7 +517,7 @@ mod tests {
         let element = Element::new(ElementKind::Text {
             content: "Remove me".to_string(),
             font_size: 14.0,
-            color: "#FF0000".to_string(),
+            color: Color::from_rgb(0xff_0000).into(),
         });
 
         let id = store
@@ -514,7 +546,7 @@ mod tests {
         let element = Element::new(ElementKind::Text {
             content: "Original".to_string(),
             font_size: 12.0,
-            color: "#000000".to_string(),
+            color: Color::BLACK.into(),
         });
 
         let id = store
@@ -541,7 +573,7 @@ mod tests {
         new_scene.add_element(Element::new(ElementKind::Text {
             content: "New scene".to_string(),
             font_size: 20.0,
-            color: "#00FF00".to_string(),
+            color: Color::from_rgb(0x00_ff00).into(),
         }));
 
         store.replace(DEFAULT_SESSION, new_scene).expect("replace");
@@ -560,7 +592,7 @@ mod tests {
                 Element::new(ElementKind::Text {
                     content: "Test".to_string(),
                     font_size: 16.0,
-                    color: "#000".to_string(),
+                    color: Color::BLACK.into(),
                 }),
             )
             .expect("add");
@@ -587,7 +619,7 @@ mod tests {
                 Element::new(ElementKind::Text {
                     content: "Doc test".to_string(),
                     font_size: 14.0,
-                    color: "#123456".to_string(),
+                    color: Color::from_rgb(0x12_3456).into(),
                 }),
             )
             .expect("add");
@@ -640,7 +672,7 @@ mod tests {
         let element = Element::new(ElementKind::Text {
             content: "Persisted".to_string(),
             font_size: 20.0,
-            color: "#ABCDEF".to_string(),
+            color: Color::from_rgb(0xab_cdef).into(),
         });
         store.add_element(DEFAULT_SESSION, element).expect("add");
 
@@ -671,7 +703,7 @@ mod tests {
         let element = Element::new(ElementKind::Text {
             content: "Auto-saved".to_string(),
             font_size: 14.0,
-            color: "#000000".to_string(),
+            color: Color::BLACK.into(),
         });
         let id = store.add_element(DEFAULT_SESSION, element).expect("add");
 
@@ -710,7 +742,7 @@ mod tests {
                     Element::new(ElementKind::Text {
                         content: format!("In {name}"),
                         font_size: 12.0,
-                        color: "#000".to_string(),
+                        color: Color::BLACK.into(),
                     }),
                 )
                 .expect("add");
@@ -733,7 +765,7 @@ mod tests {
                 Element::new(ElementKind::Text {
                     content: "Clearable".to_string(),
                     font_size: 12.0,
-                    color: "#000".to_string(),
+                    color: Color::BLACK.into(),
                 }),
             )
             .expect("add");
@@ -760,7 +792,7 @@ mod tests {
                 Element::new(ElementKind::Text {
                     content: "Delete me".to_string(),
                     font_size: 12.0,
-                    color: "#000".to_string(),
+                    color: Color::BLACK.into(),
                 }),
             )
             .expect("add");
@@ -0,0 +1,360 @@
+//! Visual styling shared by all element kinds.
+//!
+//! A [`Style`] describes how an element's bounding box is painted: its fill
+//! (solid color or gradient), stroke, corner radius, drop shadow, and overall
+//! opacity. Colors are [`ThemeColor`]s, so a style can use a literal
+//! (`"#1976d2"`) or a reference into the scene theme (`"var(--accent)"`).
+
+use serde::{Deserialize, Serialize};
+
+use crate::color::{Color, ThemeColor};
+
+/// Visual style applied to an element's bounds.
+///
+/// The default style has no fill, stroke, or shadow, square corners, and full
+/// opacity. Backends fall back to their per-kind default colors when no fill
+/// is set.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+pub struct Style {
+    /// Background fill.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fill: Option<Fill>,
+    /// Outline stroke.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stroke: Option<Stroke>,
+    /// Corner radius in pixels (0.0 = square corners).
+    #[serde(default)]
+    pub corner_radius: f32,
+    /// Drop shadow.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shadow: Option<Shadow>,
+    /// Element opacity (0.0 = transparent, 1.0 = opaque).
+    #[serde(default = "Style::default_opacity")]
+    pub opacity: f32,
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Self {
+            fill: None,
+            stroke: None,
+            corner_radius: 0.0,
+            shadow: None,
+            opacity: 1.0,
+        }
+    }
+}
+
+impl Style {
+    const fn default_opacity() -> f32 {
+        1.0
+    }
+
+    /// Create a style with a solid fill color.
+    #[must_use]
+    pub fn solid(color: impl Into<ThemeColor>) -> Self {
+        Self {
+            fill: Some(Fill::Solid {
+                color: color.into(),
+            }),
+            ..Self::default()
+        }
+    }
+
+    /// Set the fill.
+    #[must_use]
+    pub fn with_fill(mut self, fill: Fill) -> Self {
+        self.fill = Some(fill);
+        self
+    }
+
+    /// Set the stroke.
+    #[must_use]
+    pub fn with_stroke(mut self, color: impl Into<ThemeColor>, width: f32) -> Self {
+        self.stroke = Some(Stroke {
+            color: color.into(),
+            width,
+        });
+        self
+    }
+
+    /// Set the corner radius.
+    #[must_use]
+    pub fn with_corner_radius(mut self, radius: f32) -> Self {
+        self.corner_radius = radius;
+        self
+    }
+
+    /// Set the drop shadow.
+    #[must_use]
+    pub fn with_shadow(mut self, shadow: Shadow) -> Self {
+        self.shadow = Some(shadow);
+        self
+    }
+
+    /// Set the opacity.
+    #[must_use]
+    pub fn with_opacity(mut self, opacity: f32) -> Self {
+        self.opacity = opacity;
+        self
+    }
+
+    /// Check whether this is the default (unstyled) style.
+    #[must_use]
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Opacity clamped to `0.0..=1.0`, treating non-finite values as opaque.
+    #[must_use]
+    pub fn effective_opacity(&self) -> f32 {
+        if self.opacity.is_finite() {
+            self.opacity.clamp(0.0, 1.0)
+        } else {
+            1.0
+        }
+    }
+
+    /// Corner radius clamped so it never exceeds half the smaller side.
+    #[must_use]
+    pub fn effective_corner_radius(&self, width: f32, height: f32) -> f32 {
+        let max = (width.min(height) / 2.0).max(0.0);
+        if self.corner_radius.is_finite() {
+            self.corner_radius.clamp(0.0, max)
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Background fill of an element.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Fill {
+    /// A single solid color.
+    Solid {
+        /// Fill color.
+        color: ThemeColor,
+    },
+    /// A linear gradient across the element bounds.
+    LinearGradient {
+        /// Gradient direction in radians (0.0 = left to right, clockwise).
+        #[serde(default)]
+        angle: f32,
+        /// Color stops, ordered by offset.
+        stops: Vec<GradientStop>,
+    },
+    /// A radial gradient from a center point outward.
+    RadialGradient {
+        /// Center X, normalized to the element width (0.5 = middle).
+        #[serde(default = "Fill::default_center")]
+        center_x: f32,
+        /// Center Y, normalized to the element height (0.5 = middle).
+        #[serde(default = "Fill::default_center")]
+        center_y: f32,
+        /// Radius, normalized to the larger element side (0.5 = edge).
+        #[serde(default = "Fill::default_center")]
+        radius: f32,
+        /// Color stops, ordered by offset.
+        stops: Vec<GradientStop>,
+    },
+}
+
+impl Fill {
+    const fn default_center() -> f32 {
+        0.5
+    }
+
+    /// Create a left-to-right linear gradient between two colors.
+    #[must_use]
+    pub fn linear(from: impl Into<ThemeColor>, to: impl Into<ThemeColor>) -> Self {
+        Self::LinearGradient {
+            angle: 0.0,
+            stops: vec![GradientStop::new(0.0, from), GradientStop::new(1.0, to)],
+        }
+    }
+
+    /// Create a centered radial gradient between two colors.
+    #[must_use]
+    pub fn radial(inner: impl Into<ThemeColor>, outer: impl Into<ThemeColor>) -> Self {
+        Self::RadialGradient {
+            center_x: 0.5,
+            center_y: 0.5,
+            radius: 0.5,
+            stops: vec![GradientStop::new(0.0, inner), GradientStop::new(1.0, outer)],
+        }
+    }
+
+    /// The first color of this fill.
+    ///
+    /// Backends that cannot draw gradients use this as a flat approximation.
+    #[must_use]
+    pub fn primary_color(&self) -> Option<&ThemeColor> {
+        match self {
+            Self::Solid { color } => Some(color),
+            Self::LinearGradient { stops, .. } | Self::RadialGradient { stops, .. } => {
+                stops.first().map(|s| &s.color)
+            }
+        }
+    }
+
+    /// The last color of this fill (equal to the primary color for solid fills).
+    #[must_use]
+    pub fn secondary_color(&self) -> Option<&ThemeColor> {
+        match self {
+            Self::Solid { color } => Some(color),
+            Self::LinearGradient { stops, .. } | Self::RadialGradient { stops, .. } => {
+                stops.last().map(|s| &s.color)
+            }
+        }
+    }
+}
+
+/// A color stop within a gradient.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+pub struct GradientStop {
+    /// Position along the gradient (0.0 to 1.0).
+    pub offset: f32,
+    /// Stop color.
+    pub color: ThemeColor,
+}
+
+impl GradientStop {
+    /// Create a new gradient stop.
+    #[must_use]
+    pub fn new(offset: f32, color: impl Into<ThemeColor>) -> Self {
+        Self {
+            offset,
+            color: color.into(),
+        }
+    }
+}
+
+/// Outline stroke around an element.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+pub struct Stroke {
+    /// Stroke color.
+    pub color: ThemeColor,
+    /// Stroke width in pixels.
+    pub width: f32,
+}
+
+/// Drop shadow drawn behind an element.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+pub struct Shadow {
+    /// Shadow color (usually semi-transparent).
+    pub color: ThemeColor,
+    /// Horizontal offset in pixels.
+    #[serde(default)]
+    pub offset_x: f32,
+    /// Vertical offset in pixels.
+    #[serde(default)]
+    pub offset_y: f32,
+    /// Blur radius in pixels.
+    #[serde(default)]
+    pub blur: f32,
+}
+
+impl Default for Shadow {
+    fn default() -> Self {
+        Self {
+            color: Color::rgba(0, 0, 0, 0x40).into(),
+            offset_x: 0.0,
+            offset_y: 2.0,
+            blur: 4.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_style_is_default() {
+        let style = Style::default();
+        assert!(style.is_default());
+        assert!(style.fill.is_none());
+        assert!((style.opacity - 1.0).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn test_builder() {
+        let red = Color::from_rgb(0xff_0000);
+        let style = Style::solid(red)
+            .with_stroke(Color::BLACK, 2.0)
+            .with_corner_radius(8.0)
+            .with_shadow(Shadow::default())
+            .with_opacity(0.5);
+
+        assert!(!style.is_default());
+        assert_eq!(
+            style.fill.as_ref().and_then(Fill::primary_color),
+            Some(&red.into())
+        );
+        assert_eq!(style.stroke.as_ref().map(|s| s.width), Some(2.0));
+        assert!((style.corner_radius - 8.0).abs() < f32::EPSILON);
+        assert!(style.shadow.is_some());
+    }
+
+    #[test]
+    fn test_deserialize_missing_fields_uses_defaults() {
+        let style: Style = serde_json::from_str("{}").expect("empty style");
+        assert_eq!(style, Style::default());
+
+        let style: Style =
+            serde_json::from_str(r##"{"fill": {"type": "solid", "color": "#00ff00"}}"##)
+                .expect("solid fill");
+        assert!((style.opacity - 1.0).abs() < f32::EPSILON);
+        assert_eq!(
+            style.fill,
+            Some(Fill::Solid {
+                color: Color::from_rgb(0x00_ff00).into()
+            })
+        );
+    }
+
+    #[test]
+    fn test_gradient_roundtrip() {
+        let style =
+            Style::default().with_fill(Fill::radial(Color::WHITE, ThemeColor::variable("ink")));
+        let json = serde_json::to_string(&style).expect("serialize");
+        assert!(json.contains("radial_gradient"));
+        let back: Style = serde_json::from_str(&json).expect("deserialize");
+        assert_eq!(back, style);
+    }
+
+    #[test]
+    fn test_gradient_colors() {
+        let fill = Fill::linear(Color::from_rgb(0x11_1111), Color::from_rgb(0x22_2222));
+        assert_eq!(
+            fill.primary_color().map(ToString::to_string).as_deref(),
+            Some("#111111")
+        );
+        assert_eq!(
+            fill.secondary_color().map(ToString::to_string).as_deref(),
+            Some("#222222")
+        );
+
+        let empty = Fill::LinearGradient {
+            angle: 0.0,
+            stops: Vec::new(),
+        };
+        assert_eq!(empty.primary_color(), None);
+    }
+
+    #[test]
+    fn test_effective_values_are_clamped() {
+        let style = Style::default().with_opacity(2.0).with_corner_radius(500.0);
+        assert!((style.effective_opacity() - 1.0).abs() < f32::EPSILON);
+        assert!((style.effective_corner_radius(100.0, 40.0) - 20.0).abs() < f32::EPSILON);
+
+        let style = Style::default().with_opacity(f32::NAN);
+        assert!((style.effective_opacity() - 1.0).abs() < f32::EPSILON);
+    }
+}
@@ -0,0 +1,293 @@
+//! # Element Templates
+//!
+//! Reusable element subtrees that can be stamped onto a scene.
+//!
+//! A [`Template`] is captured from one or more root elements together with
+//! all of their descendants. Positions are stored relative to the top-left
+//! corner of the captured content, so [`Scene::instantiate_template`] can
+//! place a copy anywhere. Every instantiation gets fresh element IDs, and
+//! callouts pointing into the template point at the copies.
+//!
+//! ```text
+//! capture(name, scene, roots)   → Template (origin at 0, 0)
+//! library.insert(template)      → stored under its name
+//! scene.instantiate_template()  → new IDs, offset to (x, y)
+//! ```
+
+use std::collections::{BTreeMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::camera::SceneBounds;
+use crate::element::{CalloutTarget, Element, ElementId};
+use crate::scene::Scene;
+use crate::{CanvasError, CanvasResult};
+
+/// A named, reusable element subtree.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Template {
+    /// Template name (unique within a [`TemplateLibrary`]).
+    pub name: String,
+    /// Optional human-readable description.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Elements in the template, parents before children, positioned
+    /// relative to the template origin. IDs are those of the captured
+    /// elements and are replaced on instantiation.
+    pub elements: Vec<Element>,
+}
+
+impl Template {
+    /// Capture the subtrees rooted at `roots` as a template.
+    ///
+    /// Roots nested inside another root are captured once, as part of the
+    /// outer subtree.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the name is empty or contains `/` or `?`, no
+    /// roots are given, or a root is not in the scene.
+    pub fn capture(
+        name: impl Into<String>,
+        scene: &Scene,
+        roots: &[ElementId],
+    ) -> CanvasResult<Self> {
+        let name = name.into();
+        if name.is_empty() || name.contains(['/', '?']) {
+            return Err(CanvasError::InvalidOperation(format!(
+                "invalid template name: {name:?}"
+            )));
+        }
+        if roots.is_empty() {
+            return Err(CanvasError::InvalidOperation(
+                "a template needs at least one element".to_string(),
+            ));
+        }
+
+        let mut visited = HashSet::new();
+        let mut elements = Vec::new();
+        for &root in roots {
+            if scene.get_element(root).is_none() {
+                return Err(CanvasError::ElementNotFound(root.to_string()));
+            }
+            // Skip roots that an earlier root already covers
+            if has_ancestor_in(scene, root, roots) {
+                continue;
+            }
+            collect_subtree(scene, root, &mut visited, &mut elements);
+        }
+
+        let origin = elements
+            .iter()
+            .map(|e| SceneBounds::from_transform(&e.transform))
+            .reduce(|a, b| a.union(&b))
+            .map_or((0.0, 0.0), |b| (b.min_x, b.min_y));
+        let captured: HashSet<ElementId> = elements.iter().map(|e| e.id).collect();
+        for element in &mut elements {
+            element.transform.x -= origin.0;
+            element.transform.y -= origin.1;
+            if let Some(CalloutTarget::Point { x, y }) = element.kind.callout_target_mut() {
+                *x -= origin.0;
+                *y -= origin.1;
+            }
+            element.selected = false;
+            if element.parent.is_some_and(|p| !captured.contains(&p)) {
+                element.parent = None;
+            }
+        }
+
+        Ok(Self {
+            name,
+            description: None,
+            elements,
+        })
+    }
+
+    /// Set the description.
+    #[must_use]
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Bounding box of the template content, relative to its origin.
+    ///
+    /// Returns `None` for an empty template.
+    #[must_use]
+    pub fn bounds(&self) -> Option<SceneBounds> {
+        self.elements
+            .iter()
+            .map(|e| SceneBounds::from_transform(&e.transform))
+            .reduce(|a, b| a.union(&b))
+    }
+
+    /// Top-level elements of the template.
+    pub fn roots(&self) -> impl Iterator<Item = &Element> {
+        self.elements.iter().filter(|e| e.parent.is_none())
+    }
+}
+
+/// A collection of templates indexed by name.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TemplateLibrary {
+    templates: BTreeMap<String, Template>,
+}
+
+impl TemplateLibrary {
+    /// Create an empty library.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Store a template, returning any template it replaced.
+    pub fn insert(&mut self, template: Template) -> Option<Template> {
+        self.templates.insert(template.name.clone(), template)
+    }
+
+    /// Look up a template by name.
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&Template> {
+        self.templates.get(name)
+    }
+
+    /// Remove a template by name.
+    pub fn remove(&mut self, name: &str) -> Option<Template> {
+        self.templates.remove(name)
+    }
+
+    /// Template names in alphabetical order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.templates.keys().map(String::as_str)
+    }
+
+    /// All templates in name order.
+    pub fn iter(&self) -> impl Iterator<Item = &Template> {
+        self.templates.values()
+    }
+
+    /// Number of templates.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.templates.len()
+    }
+
+    /// Check whether the library is empty.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.templates.is_empty()
+    }
+}
+
+/// Check whether any ancestor of `id` is in `roots`.
+fn has_ancestor_in(scene: &Scene, id: ElementId, roots: &[ElementId]) -> bool {
+    let mut current = scene.get_element(id).and_then(|e| e.parent);
+    // Bound the walk so a corrupt parent cycle cannot loop forever
+    for _ in 0..scene.element_count() {
+        match current {
+            Some(parent) if roots.contains(&parent) => return true,
+            Some(parent) => current = scene.get_element(parent).and_then(|e| e.parent),
+            None => return false,
+        }
+    }
+    false
+}
+
+/// Append `id` and its descendants to `out`, parents first.
+fn collect_subtree(
+    scene: &Scene,
+    id: ElementId,
+    visited: &mut HashSet<ElementId>,
+    out: &mut Vec<Element>,
+) {
+    if !visited.insert(id) {
+        return;
+    }
+    let Some(element) = scene.get_element(id) else {
+        return;
+    };
+    out.push(element.clone());
+    for &child in element.kind.children() {
+        collect_subtree(scene, child, visited, out);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Color, ElementKind, Transform};
+
+    fn text_at(x: f32, y: f32) -> Element {
+        Element::new(ElementKind::Text {
+            content: "Label".to_string(),
+            font_size: 16.0,
+            color: Color::BLACK.into(),
+        })
+        .with_transform(Transform {
+            x,
+            y,
+            width: 40.0,
+            height: 20.0,
+            rotation: 0.0,
+            z_index: 0,
+            auto_size: false,
+        })
+    }
+
+    #[test]
+    fn test_capture_and_instantiate_regenerates_ids() {
+        let mut scene = Scene::new(800.0, 600.0);
+        let group = scene.add_element(
+            Element::new(ElementKind::Group {
+                children: Vec::new(),
+                clip: false,
+                opacity: 1.0,
+            })
+            .with_transform(text_at(100.0, 50.0).transform),
+        );
+        let label = scene.add_element(text_at(110.0, 60.0));
+        scene.reparent(label, group).expect("reparent");
+
+        // The nested label is captured once, through the group
+        let template = Template::capture("card", &scene, &[group, label]).expect("capture");
+        assert_eq!(template.elements.len(), 2);
+        assert_eq!(template.roots().count(), 1);
+        let bounds = template.bounds().expect("bounds");
+        assert_eq!((bounds.min_x, bounds.min_y), (0.0, 0.0));
+
+        let mut library = TemplateLibrary::new();
+        assert!(library.insert(template).is_none());
+        let template = library.get("card").expect("stored");
+
+        let created = scene.instantiate_template(template, 300.0, 200.0);
+        assert_eq!(created.len(), 2);
+        assert!(!created.contains(&group) && !created.contains(&label));
+        let copy = scene.get_element(created[0]).expect("group copy");
+        assert_eq!(copy.kind.children(), &[created[1]]);
+        assert_eq!((copy.transform.x, copy.transform.y), (300.0, 200.0));
+        let child = scene.get_element(created[1]).expect("label copy");
+        assert_eq!(child.parent, Some(created[0]));
+        assert_eq!((child.transform.x, child.transform.y), (310.0, 210.0));
+        assert_eq!(scene.root_elements().count(), 2);
+
+        assert!(Template::capture("a/b", &scene, &[group]).is_err());
+        assert!(Template::capture("empty", &scene, &[]).is_err());
+    }
+
+    #[test]
+    fn test_duplicate_offsets_copies() {
+        let mut scene = Scene::new(800.0, 600.0);
+        let a = scene.add_element(text_at(10.0, 10.0));
+        let b = scene.add_element(text_at(60.0, 30.0));
+
+        let copies = scene.duplicate_elements(&[a, b], 20.0, 20.0).expect("dup");
+        assert_eq!(copies.len(), 2);
+        let first = scene.get_element(copies[0]).expect("copy");
+        assert_eq!((first.transform.x, first.transform.y), (30.0, 30.0));
+        let second = scene.get_element(copies[1]).expect("copy");
+        assert_eq!((second.transform.x, second.transform.y), (80.0, 50.0));
+        assert!(scene
+            .duplicate_elements(&[ElementId::new()], 0.0, 0.0)
+            .is_err());
+    }
+}
@@ -0,0 +1,391 @@
+//! # Element Transitions
+//!
+//! Eases elements in and out when sync adds or removes them, so a remote
+//! agent's edits don't pop onto the canvas.
+//!
+//! The scene records an enter transition when a patch or a new scene
+//! document brings in an element, and an exit transition when one takes an
+//! element away. Removal itself is immediate: the removed element is kept
+//! only as a snapshot for renderers to draw while it fades out.
+//!
+//! ```text
+//! scene.apply_patch(&patch)         // adds chart, removes note
+//! scene.tick(now)                   // true while transitions run
+//!   → scene.transition_frame(chart) // opacity/scale to draw it with
+//!   → scene.exiting_elements()      // note, until its exit finishes
+//! ```
+//!
+//! Transitions start on the first [`Scene::tick`](crate::Scene::tick) after
+//! they are recorded, so applying a patch needs no clock. Time is passed in
+//! as milliseconds so the same logic runs natively and in WASM.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::element::{Element, ElementId};
+
+/// Scale an element grows from when entering, or shrinks to when exiting.
+pub const TRANSITION_START_SCALE: f32 = 0.8;
+
+/// Longest transition the scene will run (ms).
+pub const MAX_TRANSITION_MS: u64 = 5_000;
+
+/// How an element appears or disappears.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransitionEffect {
+    /// Appear or disappear at once.
+    #[default]
+    None,
+    /// Fade the opacity.
+    Fade,
+    /// Grow from, or shrink to, [`TRANSITION_START_SCALE`] about the center.
+    Scale,
+    /// Fade and scale together.
+    FadeScale,
+}
+
+impl TransitionEffect {
+    /// The frame at `visibility`, from 0.0 (gone) to 1.0 (fully shown).
+    #[must_use]
+    pub fn frame(self, visibility: f32) -> TransitionFrame {
+        let visibility = visibility.clamp(0.0, 1.0);
+        let scale = TRANSITION_START_SCALE + (1.0 - TRANSITION_START_SCALE) * visibility;
+        match self {
+            Self::None => TransitionFrame::IDENTITY,
+            Self::Fade => TransitionFrame {
+                opacity: visibility,
+                scale: 1.0,
+            },
+            Self::Scale => TransitionFrame {
+                opacity: 1.0,
+                scale,
+            },
+            Self::FadeScale => TransitionFrame {
+                opacity: visibility,
+                scale,
+            },
+        }
+    }
+}
+
+/// Timing curve of a transition.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Easing {
+    /// Constant speed.
+    Linear,
+    /// Start slow (quadratic).
+    EaseIn,
+    /// End slow (quadratic).
+    #[default]
+    EaseOut,
+    /// Start and end slow (cubic).
+    EaseInOut,
+}
+
+impl Easing {
+    /// Eased progress for linear progress `t` in 0.0..=1.0.
+    #[must_use]
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Self::Linear => t,
+            Self::EaseIn => t * t,
+            Self::EaseOut => t * (2.0 - t),
+            Self::EaseInOut if t < 0.5 => 4.0 * t * t * t,
+            Self::EaseInOut => 1.0 - (-2.0 * t + 2.0).powi(3) / 2.0,
+        }
+    }
+}
+
+/// Enter and exit transitions for elements added or removed by sync.
+///
+/// The default runs no effects; hosts that render remote edits opt in with
+/// [`TransitionConfig::with_enter`] and [`TransitionConfig::with_exit`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransitionConfig {
+    /// Length of each transition (ms, at most [`MAX_TRANSITION_MS`]).
+    #[serde(default = "TransitionConfig::default_duration_ms")]
+    pub duration_ms: u64,
+    /// Timing curve.
+    #[serde(default)]
+    pub easing: Easing,
+    /// Effect for elements being added.
+    #[serde(default)]
+    pub enter: TransitionEffect,
+    /// Effect for elements being removed.
+    #[serde(default)]
+    pub exit: TransitionEffect,
+}
+
+impl Default for TransitionConfig {
+    fn default() -> Self {
+        Self {
+            duration_ms: Self::DEFAULT_DURATION_MS,
+            easing: Easing::default(),
+            enter: TransitionEffect::None,
+            exit: TransitionEffect::None,
+        }
+    }
+}
+
+impl TransitionConfig {
+    /// Default transition length (ms).
+    pub const DEFAULT_DURATION_MS: u64 = 200;
+
+    const fn default_duration_ms() -> u64 {
+        Self::DEFAULT_DURATION_MS
+    }
+
+    /// Set the transition length (ms, capped at [`MAX_TRANSITION_MS`]).
+    #[must_use]
+    pub const fn with_duration_ms(mut self, duration_ms: u64) -> Self {
+        self.duration_ms = if duration_ms > MAX_TRANSITION_MS {
+            MAX_TRANSITION_MS
+        } else {
+            duration_ms
+        };
+        self
+    }
+
+    /// Set the timing curve.
+    #[must_use]
+    pub const fn with_easing(mut self, easing: Easing) -> Self {
+        self.easing = easing;
+        self
+    }
+
+    /// Set the effect for elements being added.
+    #[must_use]
+    pub const fn with_enter(mut self, effect: TransitionEffect) -> Self {
+        self.enter = effect;
+        self
+    }
+
+    /// Set the effect for elements being removed.
+    #[must_use]
+    pub const fn with_exit(mut self, effect: TransitionEffect) -> Self {
+        self.exit = effect;
+        self
+    }
+
+    /// Check whether elements animate when they are added.
+    #[must_use]
+    pub fn animates_enter(&self) -> bool {
+        self.duration_ms > 0 && self.enter != TransitionEffect::None
+    }
+
+    /// Check whether elements animate when they are removed.
+    #[must_use]
+    pub fn animates_exit(&self) -> bool {
+        self.duration_ms > 0 && self.exit != TransitionEffect::None
+    }
+}
+
+/// Opacity and scale multipliers to draw an element with mid-transition.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TransitionFrame {
+    /// Multiplies the element's opacity.
+    pub opacity: f32,
+    /// Scales the element about its center.
+    pub scale: f32,
+}
+
+impl TransitionFrame {
+    /// No change: full opacity at natural size.
+    pub const IDENTITY: Self = Self {
+        opacity: 1.0,
+        scale: 1.0,
+    };
+}
+
+/// A removed element being faded out.
+#[derive(Debug, Clone)]
+struct Exit {
+    element: Element,
+    started_ms: Option<u64>,
+}
+
+/// Running enter and exit transitions of a scene.
+#[derive(Debug, Clone, Default)]
+pub struct Transitions {
+    config: TransitionConfig,
+    /// Time of the last tick (ms).
+    now_ms: u64,
+    /// Entering elements and when they started; `None` until the next tick.
+    entering: HashMap<ElementId, Option<u64>>,
+    /// Removed elements still fading out, in removal order.
+    exiting: Vec<Exit>,
+}
+
+impl Transitions {
+    /// The transition settings.
+    #[must_use]
+    pub const fn config(&self) -> &TransitionConfig {
+        &self.config
+    }
+
+    /// Change the transition settings; running transitions finish at once
+    /// if their effect was turned off.
+    pub fn set_config(&mut self, config: TransitionConfig) {
+        self.config = config.with_duration_ms(config.duration_ms);
+        if !self.config.animates_enter() {
+            self.entering.clear();
+        }
+        if !self.config.animates_exit() {
+            self.exiting.clear();
+        }
+    }
+
+    /// Start easing `id` in, unless enter transitions are off.
+    pub fn enter(&mut self, id: ElementId) {
+        self.exiting.retain(|exit| exit.element.id != id);
+        if self.config.animates_enter() {
+            self.entering.insert(id, None);
+        }
+    }
+
+    /// Start easing a removed `element` out, unless exit transitions are off.
+    pub fn exit(&mut self, element: Element) {
+        self.entering.remove(&element.id);
+        if self.config.animates_exit() && element.visible {
+            self.exiting.retain(|exit| exit.element.id != element.id);
+            self.exiting.push(Exit {
+                element,
+                started_ms: None,
+            });
+        }
+    }
+
+    /// Advance to `now_ms`, starting newly recorded transitions and
+    /// dropping finished ones. Returns whether any are still running.
+    pub fn tick(&mut self, now_ms: u64) -> bool {
+        self.now_ms = now_ms;
+        let duration = self.config.duration_ms;
+        let running = |started: &mut Option<u64>| {
+            now_ms.saturating_sub(*started.get_or_insert(now_ms)) < duration
+        };
+        self.entering.retain(|_, started| running(started));
+        self.exiting
+            .retain_mut(|exit| running(&mut exit.started_ms));
+        self.is_active()
+    }
+
+    /// Check whether any transition is running or waiting to start.
+    #[must_use]
+    pub fn is_active(&self) -> bool {
+        !self.entering.is_empty() || !self.exiting.is_empty()
+    }
+
+    /// How to draw `id` at the last tick: an entering element or an
+    /// [exiting](Self::exiting) snapshot. `None` when `id` isn't
+    /// transitioning.
+    #[must_use]
+    pub fn frame(&self, id: ElementId) -> Option<TransitionFrame> {
+        if let Some(started) = self.entering.get(&id) {
+            let progress = self.progress(*started);
+            return Some(self.config.enter.frame(progress));
+        }
+        self.exiting
+            .iter()
+            .find(|exit| exit.element.id == id)
+            .map(|exit| self.config.exit.frame(1.0 - self.progress(exit.started_ms)))
+    }
+
+    /// Snapshots of removed elements that are still fading out.
+    pub fn exiting(&self) -> impl Iterator<Item = &Element> {
+        self.exiting.iter().map(|exit| &exit.element)
+    }
+
+    /// Eased progress of a transition started at `started_ms`.
+    #[allow(clippy::cast_precision_loss)]
+    fn progress(&self, started_ms: Option<u64>) -> f32 {
+        let Some(started) = started_ms else {
+            return 0.0;
+        };
+        if self.config.duration_ms == 0 {
+            return 1.0;
+        }
+        let elapsed = self.now_ms.saturating_sub(started) as f32;
+        self.config
+            .easing
+            .apply(elapsed / self.config.duration_ms as f32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_easing_endpoints_and_shape() {
+        for easing in [
+            Easing::Linear,
+            Easing::EaseIn,
+            Easing::EaseOut,
+            Easing::EaseInOut,
+        ] {
+            assert!(easing.apply(0.0).abs() < f32::EPSILON);
+            assert!((easing.apply(1.0) - 1.0).abs() < f32::EPSILON);
+            assert!((easing.apply(2.0) - 1.0).abs() < f32::EPSILON);
+        }
+        assert!(Easing::EaseIn.apply(0.5) < 0.5);
+        assert!(Easing::EaseOut.apply(0.5) > 0.5);
+        assert!((Easing::EaseInOut.apply(0.5) - 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_transitions_start_on_tick_and_finish() {
+        let mut transitions = Transitions::default();
+        transitions.set_config(
+            TransitionConfig::default()
+                .with_duration_ms(100)
+                .with_easing(Easing::Linear)
+                .with_enter(TransitionEffect::FadeScale)
+                .with_exit(TransitionEffect::Fade),
+        );
+        let entering = ElementId::new();
+        let removed = Element::new(crate::ElementKind::Text {
+            content: "Bye".to_string(),
+            font_size: 16.0,
+            color: crate::Color::BLACK.into(),
+        });
+        let removed_id = removed.id;
+        transitions.enter(entering);
+        transitions.exit(removed);
+
+        // Nothing has moved before the first tick
+        let frame = transitions.frame(entering).expect("entering");
+        assert!(frame.opacity.abs() < f32::EPSILON);
+        assert!((frame.scale - TRANSITION_START_SCALE).abs() < f32::EPSILON);
+
+        assert!(transitions.tick(1_000));
+        assert!(transitions.tick(1_050));
+        let frame = transitions.frame(entering).expect("entering");
+        assert!((frame.opacity - 0.5).abs() < 1e-6);
+        assert!((frame.scale - 0.9).abs() < 1e-6);
+        let frame = transitions.frame(removed_id).expect("exiting");
+        assert!((frame.opacity - 0.5).abs() < 1e-6);
+        assert!((frame.scale - 1.0).abs() < f32::EPSILON);
+        assert_eq!(transitions.exiting().count(), 1);
+
+        assert!(!transitions.tick(1_100));
+        assert!(transitions.frame(entering).is_none());
+        assert_eq!(transitions.exiting().count(), 0);
+    }
+
+    #[test]
+    fn test_transitions_off_by_default() {
+        let mut transitions = Transitions::default();
+        transitions.enter(ElementId::new());
+        assert!(!transitions.is_active());
+        assert_eq!(
+            TransitionConfig::default()
+                .with_duration_ms(60_000)
+                .duration_ms,
+            MAX_TRANSITION_MS
+        );
+    }
+}
@@ -0,0 +1,592 @@
+//! # Trees
+//!
+//! Hierarchies such as org charts and decision trees for `Tree` elements,
+//! laid out automatically as a tidy tree.
+//!
+//! ```text
+//!                 ┌──────────┐
+//!                 │   CEO    │
+//!                 └────┬─────┘
+//!          ┌───────────┴───────────┐
+//!     ┌────┴─────┐            ┌────┴─────┐
+//!     │   CTO    │            │   CFO    │
+//!     └────┬─────┘            └────(+3)──┘   collapsed: 3 hidden
+//!     ┌────┴─────┐
+//!   ┌─┴──┐    ┌──┴─┐
+//!   │ QA │    │ Ops│
+//!   └────┘    └────┘
+//! ```
+//!
+//! Every node is a fixed-size box. Subtrees are packed as close as their
+//! contours allow, and each parent is centered over its first and last
+//! child, so identical subtrees are drawn identically wherever they appear.
+//! Collapsed nodes hide their descendants and show how many are hidden.
+//!
+//! [`TreeLayout`] works in the element's local frame (origin at its
+//! unrotated top-left corner), scaling the tree to fit; the renderers and
+//! the interaction router share it, so a tap hits the node drawn under it.
+
+use serde::{Deserialize, Serialize};
+
+use crate::color::{Color, ThemeColor};
+use crate::element::ElementKind;
+use crate::error::{CanvasError, CanvasResult};
+
+/// Most nodes a tree may have.
+pub const MAX_TREE_NODES: usize = 500;
+
+/// Most levels below the root a tree may have.
+pub const MAX_TREE_DEPTH: usize = 32;
+
+/// Fill of nodes without a color of their own.
+pub const DEFAULT_NODE_COLOR: Color = Color::from_rgb(0xe3_f2fd);
+
+/// Which way a tree grows from its root.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+#[serde(rename_all = "lowercase")]
+pub enum TreeDirection {
+    /// Root at the top, children below (org charts).
+    #[default]
+    Down,
+    /// Root on the left, children to the right (decision trees).
+    Right,
+}
+
+/// A node of a tree and its subtree.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+pub struct TreeNode {
+    /// Unique ID within the tree; blank IDs are filled in by
+    /// [`assign_missing_ids`].
+    #[serde(default)]
+    pub id: String,
+    /// Main text, e.g. a name or question.
+    pub label: String,
+    /// Second line, e.g. a job title or answer.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    /// Box fill; [`DEFAULT_NODE_COLOR`] if unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<ThemeColor>,
+    /// Whether the children are hidden.
+    #[serde(default)]
+    pub collapsed: bool,
+    /// Child nodes, drawn in order.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<TreeNode>,
+}
+
+impl TreeNode {
+    /// Create a leaf node.
+    #[must_use]
+    pub fn new(id: impl Into<String>, label: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            label: label.into(),
+            detail: None,
+            color: None,
+            collapsed: false,
+            children: Vec::new(),
+        }
+    }
+
+    /// Set the second line of text.
+    #[must_use]
+    pub fn with_detail(mut self, detail: impl Into<String>) -> Self {
+        self.detail = Some(detail.into());
+        self
+    }
+
+    /// Set the child nodes.
+    #[must_use]
+    pub fn with_children(mut self, children: Vec<TreeNode>) -> Self {
+        self.children = children;
+        self
+    }
+
+    /// Number of nodes in this subtree, including this one.
+    #[must_use]
+    pub fn count(&self) -> usize {
+        1 + self.descendants()
+    }
+
+    /// Number of nodes below this one.
+    #[must_use]
+    pub fn descendants(&self) -> usize {
+        self.children.iter().map(TreeNode::count).sum()
+    }
+
+    /// The node with `id` in this subtree.
+    #[must_use]
+    pub fn find(&self, id: &str) -> Option<&TreeNode> {
+        if self.id == id {
+            return Some(self);
+        }
+        self.children.iter().find_map(|child| child.find(id))
+    }
+
+    /// The node with `id` in this subtree, mutably.
+    pub fn find_mut(&mut self, id: &str) -> Option<&mut TreeNode> {
+        if self.id == id {
+            return Some(self);
+        }
+        self.children
+            .iter_mut()
+            .find_map(|child| child.find_mut(id))
+    }
+}
+
+/// Give nodes without an ID one from their position: `"1"` for the root,
+/// `"1.2"` for its second child, and so on.
+pub fn assign_missing_ids(root: &mut TreeNode) {
+    fn assign(node: &mut TreeNode, path: &str) {
+        if node.id.trim().is_empty() {
+            node.id = path.to_string();
+        }
+        for (i, child) in node.children.iter_mut().enumerate() {
+            assign(child, &format!("{path}.{}", i + 1));
+        }
+    }
+    assign(root, "1");
+}
+
+/// Check a tree can be stored.
+///
+/// # Errors
+///
+/// Returns [`CanvasError::InvalidOperation`] if the tree has more than
+/// [`MAX_TREE_NODES`] nodes or [`MAX_TREE_DEPTH`] levels, or a node ID is
+/// blank or repeated.
+pub fn validate(root: &TreeNode) -> CanvasResult<()> {
+    let invalid = |message: String| Err(CanvasError::InvalidOperation(message));
+    let mut ids = std::collections::HashSet::new();
+    let mut stack = vec![(root, 0)];
+    while let Some((node, depth)) = stack.pop() {
+        if depth > MAX_TREE_DEPTH {
+            return invalid(format!("Trees are at most {MAX_TREE_DEPTH} levels deep"));
+        }
+        if ids.len() == MAX_TREE_NODES {
+            return invalid(format!("Trees hold up to {MAX_TREE_NODES} nodes"));
+        }
+        if node.id.trim().is_empty() {
+            return invalid("Tree nodes need an ID".to_string());
+        }
+        if !ids.insert(node.id.as_str()) {
+            return invalid(format!("Duplicate tree node: {}", node.id));
+        }
+        stack.extend(node.children.iter().map(|child| (child, depth + 1)));
+    }
+    Ok(())
+}
+
+/// Collapse or expand a node of a `Tree` element.
+///
+/// # Errors
+///
+/// Returns [`CanvasError::InvalidOperation`] if `kind` is not a tree or the
+/// node does not exist or has no children.
+pub fn set_collapsed(kind: &mut ElementKind, node: &str, collapsed: bool) -> CanvasResult<()> {
+    let ElementKind::Tree { root, .. } = kind else {
+        return Err(CanvasError::InvalidOperation(format!(
+            "{} is not a tree",
+            kind.type_name()
+        )));
+    };
+    let Some(target) = root.find_mut(node) else {
+        return Err(CanvasError::InvalidOperation(format!(
+            "Tree has no node {node}"
+        )));
+    };
+    if target.children.is_empty() {
+        return Err(CanvasError::InvalidOperation(format!(
+            "Tree node {node} has no children"
+        )));
+    }
+    target.collapsed = collapsed;
+    Ok(())
+}
+
+/// A visible node's box in the element's local frame.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TreeBox<'a> {
+    /// The node drawn in this box.
+    pub node: &'a TreeNode,
+    /// Index of the parent's box.
+    pub parent: Option<usize>,
+    /// Left edge.
+    pub x: f32,
+    /// Top edge.
+    pub y: f32,
+    /// Width.
+    pub width: f32,
+    /// Height.
+    pub height: f32,
+    /// Descendants hidden because this node is collapsed.
+    pub hidden: usize,
+}
+
+impl TreeBox<'_> {
+    /// Vertical centers of the label and, if the node has one, the detail
+    /// line.
+    #[must_use]
+    pub fn text_y(&self) -> (f32, Option<f32>) {
+        if self.node.detail.is_some() {
+            (
+                self.y + self.height * 0.36,
+                Some(self.y + self.height * 0.7),
+            )
+        } else {
+            (self.y + self.height / 2.0, None)
+        }
+    }
+}
+
+/// Geometry of a tree element: the visible nodes laid out as a tidy tree,
+/// scaled to fit the element.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TreeLayout<'a> {
+    /// Which way the tree grows.
+    pub direction: TreeDirection,
+    /// Element pixels per tree unit.
+    pub scale: f32,
+    /// Visible nodes, parents before their children.
+    pub boxes: Vec<TreeBox<'a>>,
+    /// Font size of node labels.
+    pub label_font_size: f32,
+    /// Font size of node details.
+    pub detail_font_size: f32,
+}
+
+/// A visible node while laying out.
+struct Slot<'a> {
+    node: &'a TreeNode,
+    parent: Option<usize>,
+    depth: usize,
+    children: Vec<usize>,
+}
+
+impl<'a> TreeLayout<'a> {
+    /// Width of a node box in tree units.
+    pub const NODE_WIDTH: f32 = 140.0;
+    /// Height of a node box in tree units.
+    pub const NODE_HEIGHT: f32 = 48.0;
+    /// Space between neighbouring subtrees.
+    pub const SIBLING_GAP: f32 = 16.0;
+    /// Space between levels.
+    pub const LEVEL_GAP: f32 = 36.0;
+    /// Space around the tree.
+    pub const PADDING: f32 = 8.0;
+
+    /// Size at which the tree is drawn at scale 1, padding included.
+    #[must_use]
+    pub fn natural_size(root: &TreeNode, direction: TreeDirection) -> (f32, f32) {
+        let (slots, breadth) = Self::tidy(root, direction);
+        let depth = slots.iter().map(|(slot, _)| slot.depth).max().unwrap_or(0);
+        let (width, height) = Self::extent(direction, breadth, depth);
+        (width + 2.0 * Self::PADDING, height + 2.0 * Self::PADDING)
+    }
+
+    /// Lay out `root` in a `width` × `height` element.
+    #[must_use]
+    pub fn new(root: &'a TreeNode, direction: TreeDirection, width: f32, height: f32) -> Self {
+        let (slots, breadth) = Self::tidy(root, direction);
+        let depth = slots.iter().map(|(slot, _)| slot.depth).max().unwrap_or(0);
+        let (tree_width, tree_height) = Self::extent(direction, breadth, depth);
+        let pad = Self::PADDING;
+        let (area_width, area_height) =
+            ((width - 2.0 * pad).max(0.0), (height - 2.0 * pad).max(0.0));
+        let scale = (area_width / tree_width).min(area_height / tree_height);
+        // Centered across the levels; the root keeps to the top or left
+        // edge, so it stays put as nodes collapse and expand
+        let (offset_x, offset_y) = match direction {
+            TreeDirection::Down => (pad + (area_width - tree_width * scale) / 2.0, pad),
+            TreeDirection::Right => (pad, pad + (area_height - tree_height * scale) / 2.0),
+        };
+
+        let (node_width, node_height) = (Self::NODE_WIDTH * scale, Self::NODE_HEIGHT * scale);
+        #[allow(clippy::cast_precision_loss)]
+        let boxes = slots
+            .into_iter()
+            .map(|(slot, along)| {
+                let level = slot.depth as f32;
+                let (x, y) = match direction {
+                    TreeDirection::Down => (
+                        along - Self::NODE_WIDTH / 2.0,
+                        level * (Self::NODE_HEIGHT + Self::LEVEL_GAP),
+                    ),
+                    TreeDirection::Right => (
+                        level * (Self::NODE_WIDTH + Self::LEVEL_GAP),
+                        along - Self::NODE_HEIGHT / 2.0,
+                    ),
+                };
+                TreeBox {
+                    node: slot.node,
+                    parent: slot.parent,
+                    x: offset_x + x * scale,
+                    y: offset_y + y * scale,
+                    width: node_width,
+                    height: node_height,
+                    hidden: if slot.node.collapsed {
+                        slot.node.descendants()
+                    } else {
+                        0
+                    },
+                }
+            })
+            .collect();
+        Self {
+            direction,
+            scale,
+            boxes,
+            label_font_size: 13.0 * scale,
+            detail_font_size: 11.0 * scale,
+        }
+    }
+
+    /// Width and height of a tree `breadth` wide with `depth` levels below
+    /// the root, in tree units.
+    #[allow(clippy::cast_precision_loss)]
+    fn extent(direction: TreeDirection, breadth: f32, depth: usize) -> (f32, f32) {
+        let levels = depth as f32;
+        match direction {
+            TreeDirection::Down => (
+                breadth,
+                (levels + 1.0) * Self::NODE_HEIGHT + levels * Self::LEVEL_GAP,
+            ),
+            TreeDirection::Right => (
+                (levels + 1.0) * Self::NODE_WIDTH + levels * Self::LEVEL_GAP,
+                breadth,
+            ),
+        }
+    }
+
+    /// Visible nodes in depth-first order with the position of each node's
+    /// center across the levels, starting from zero, and the tree's breadth.
+    fn tidy(root: &TreeNode, direction: TreeDirection) -> (Vec<(Slot<'_>, f32)>, f32) {
+        let size = match direction {
+            TreeDirection::Down => Self::NODE_WIDTH,
+            TreeDirection::Right => Self::NODE_HEIGHT,
+        };
+        let half = size / 2.0;
+
+        // Flatten depth-first; children follow their parent
+        let mut slots: Vec<Slot<'_>> = Vec::new();
+        let mut stack: Vec<(&TreeNode, Option<usize>, usize)> = vec![(root, None, 0)];
+        while let Some((node, parent, depth)) = stack.pop() {
+            let index = slots.len();
+            if let Some(parent) = parent {
+                slots[parent].children.push(index);
+            }
+            slots.push(Slot {
+                node,
+                parent,
+                depth,
+                children: Vec::new(),
+            });
+            if !node.collapsed && depth < MAX_TREE_DEPTH {
+                stack.extend(
+                    node.children
+                        .iter()
+                        .rev()
+                        .map(|child| (child, Some(index), depth + 1)),
+                );
+            }
+        }
+
+        // Bottom-up: pack each node's subtrees against each other's
+        // contours (the extent of every level relative to the subtree's
+        // root) and center the node over its children
+        let mut offsets = vec![0.0_f32; slots.len()];
+        let mut contours: Vec<Vec<(f32, f32)>> = vec![Vec::new(); slots.len()];
+        for index in (0..slots.len()).rev() {
+            let mut merged: Vec<(f32, f32)> = Vec::new();
+            let mut positions = Vec::with_capacity(slots[index].children.len());
+            for &child in &slots[index].children {
+                let contour = std::mem::take(&mut contours[child]);
+                let position = if merged.is_empty() {
+                    0.0
+                } else {
+                    merged
+                        .iter()
+                        .zip(&contour)
+                        .map(|(&(_, right), &(left, _))| right - left + Self::SIBLING_GAP)
+                        .fold(f32::NEG_INFINITY, f32::max)
+                };
+                for (level, &(left, right)) in contour.iter().enumerate() {
+                    let (left, right) = (left + position, right + position);
+                    match merged.get_mut(level) {
+                        Some(extent) => *extent = (extent.0.min(left), extent.1.max(right)),
+                        None => merged.push((left, right)),
+                    }
+                }
+                positions.push(position);
+            }
+            let center = match (positions.first(), positions.last()) {
+                (Some(first), Some(last)) => (first + last) / 2.0,
+                _ => 0.0,
+            };
+            for (&child, position) in slots[index].children.iter().zip(positions) {
+                offsets[child] = position - center;
+            }
+            contours[index] = std::iter::once((-half, half))
+                .chain(merged.into_iter().map(|(l, r)| (l - center, r - center)))
+                .collect();
+        }
+
+        // Top-down: absolute positions, then shift so the left edge is zero
+        let mut along = vec![0.0_f32; slots.len()];
+        for index in 1..slots.len() {
+            if let Some(parent) = slots[index].parent {
+                along[index] = along[parent] + offsets[index];
+            }
+        }
+        let (min, max) = along
+            .iter()
+            .fold((f32::INFINITY, f32::NEG_INFINITY), |(lo, hi), &v| {
+                (lo.min(v - half), hi.max(v + half))
+            });
+        let breadth = max - min;
+        let placed = slots
+            .into_iter()
+            .zip(along)
+            .map(|(slot, v)| (slot, v - min))
+            .collect();
+        (placed, breadth)
+    }
+
+    /// Elbow connector from a box's parent to it, as four points: out of
+    /// the parent, to the middle of the gap, across, and into the child.
+    #[must_use]
+    pub fn connector(&self, index: usize) -> Option<[(f32, f32); 4]> {
+        let child = self.boxes.get(index)?;
+        let parent = self.boxes.get(child.parent?)?;
+        Some(match self.direction {
+            TreeDirection::Down => {
+                let (from_x, from_y) = (parent.x + parent.width / 2.0, parent.y + parent.height);
+                let (to_x, to_y) = (child.x + child.width / 2.0, child.y);
+                let mid = f32::midpoint(from_y, to_y);
+                [(from_x, from_y), (from_x, mid), (to_x, mid), (to_x, to_y)]
+            }
+            TreeDirection::Right => {
+                let (from_x, from_y) = (parent.x + parent.width, parent.y + parent.height / 2.0);
+                let (to_x, to_y) = (child.x, child.y + child.height / 2.0);
+                let mid = f32::midpoint(from_x, to_x);
+                [(from_x, from_y), (mid, from_y), (mid, to_y), (to_x, to_y)]
+            }
+        })
+    }
+
+    /// Center and radius of the badge on a collapsed box showing how many
+    /// nodes it hides, on the edge its children would hang from.
+    #[must_use]
+    pub fn badge(&self, index: usize) -> Option<(f32, f32, f32)> {
+        let node = self.boxes.get(index).filter(|b| b.hidden > 0)?;
+        let (x, y) = match self.direction {
+            TreeDirection::Down => (node.x + node.width / 2.0, node.y + node.height),
+            TreeDirection::Right => (node.x + node.width, node.y + node.height / 2.0),
+        };
+        Some((x, y, 9.0 * self.scale))
+    }
+
+    /// Index of the box under a local point, if any.
+    #[must_use]
+    pub fn node_at(&self, x: f32, y: f32) -> Option<usize> {
+        self.boxes
+            .iter()
+            .position(|b| x >= b.x && x <= b.x + b.width && y >= b.y && y <= b.y + b.height)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn leaf(id: &str) -> TreeNode {
+        TreeNode::new(id, id.to_uppercase())
+    }
+
+    #[test]
+    fn test_tidy_layout_centers_parents() {
+        let root = leaf("ceo").with_children(vec![
+            leaf("cto").with_children(vec![leaf("qa"), leaf("ops")]),
+            leaf("cfo"),
+        ]);
+        // CFO tucks in beside CTO, above Ops: QA, Ops and CFO span
+        // 1.5 node steps either side of CTO's center, plus half a box each
+        let (width, height) = TreeLayout::natural_size(&root, TreeDirection::Down);
+        assert!((width - (156.0 + 78.0 + 140.0 + 16.0)).abs() < 1e-3);
+        assert!((height - (3.0 * 48.0 + 2.0 * 36.0 + 16.0)).abs() < 1e-3);
+
+        let layout = TreeLayout::new(&root, TreeDirection::Down, width, height);
+        assert!((layout.scale - 1.0).abs() < 1e-4);
+        let ids: Vec<&str> = layout.boxes.iter().map(|b| b.node.id.as_str()).collect();
+        assert_eq!(ids, ["ceo", "cto", "qa", "ops", "cfo"]);
+        let center = |i: usize| layout.boxes[i].x + layout.boxes[i].width / 2.0;
+        // CTO sits over QA and Ops; CEO over CTO and CFO
+        assert!((center(1) - f32::midpoint(center(2), center(3))).abs() < 1e-3);
+        assert!((center(0) - f32::midpoint(center(1), center(4))).abs() < 1e-3);
+        // Neighbours on a level keep the sibling gap
+        assert!((layout.boxes[3].x - (layout.boxes[2].x + 156.0)).abs() < 1e-3);
+        assert!((layout.boxes[4].x - (layout.boxes[1].x + 156.0)).abs() < 1e-3);
+        assert_eq!(layout.node_at(center(4), layout.boxes[4].y + 1.0), Some(4));
+
+        let [from, .., to] = layout.connector(2).expect("connector");
+        assert!((from.0 - center(1)).abs() < 1e-3);
+        assert!((to.1 - layout.boxes[2].y).abs() < 1e-3);
+        assert!(layout.connector(0).is_none());
+    }
+
+    #[test]
+    fn test_collapse_hides_descendants() {
+        let mut kind = ElementKind::Tree {
+            root: leaf("root").with_children(vec![
+                leaf("a").with_children(vec![leaf("a1"), leaf("a2")]),
+                leaf("b"),
+            ]),
+            direction: TreeDirection::Right,
+        };
+        set_collapsed(&mut kind, "a", true).expect("collapse");
+        assert!(set_collapsed(&mut kind, "b", true).is_err());
+        assert!(set_collapsed(&mut kind, "zz", true).is_err());
+
+        let ElementKind::Tree { root, direction } = &kind else {
+            panic!("expected a tree");
+        };
+        let layout = TreeLayout::new(root, *direction, 400.0, 200.0);
+        assert_eq!(layout.boxes.len(), 3);
+        assert_eq!(layout.boxes[1].hidden, 2);
+        assert!(layout.badge(1).is_some());
+        assert!(layout.badge(2).is_none());
+        // Growing right: children share a column
+        assert!((layout.boxes[1].x - layout.boxes[2].x).abs() < 1e-3);
+        assert!(layout.boxes[1].x > layout.boxes[0].x);
+    }
+
+    #[test]
+    fn test_ids_and_validation() {
+        let mut root: TreeNode = serde_json::from_str(
+            r#"{"label":"Start","children":[{"label":"Yes"},{"id":"no","label":"No","children":[{"label":"Retry"}]}]}"#,
+        )
+        .expect("parse");
+        assert!(validate(&root).is_err());
+        assign_missing_ids(&mut root);
+        assert_eq!(root.id, "1");
+        assert_eq!(root.children[0].id, "1.1");
+        assert_eq!(root.children[1].children[0].id, "1.2.1");
+        assert!(validate(&root).is_ok());
+        assert_eq!(root.count(), 4);
+
+        root.children.push(leaf("no"));
+        assert!(validate(&root).is_err());
+
+        let wide =
+            leaf("root").with_children((0..MAX_TREE_NODES).map(|i| leaf(&i.to_string())).collect());
+        assert!(validate(&wide).is_err());
+        let mut deep = leaf("0");
+        for depth in 1..=MAX_TREE_DEPTH + 1 {
+            deep = leaf(&depth.to_string()).with_children(vec![deep]);
+        }
+        assert!(validate(&deep).is_err());
+    }
+}
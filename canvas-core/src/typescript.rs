@@ -0,0 +1,214 @@
+//! # TypeScript Definitions
+//!
+//! Generates a `.d.ts` module for the sync protocol and scene schema from
+//! the Rust types so JS clients (the PWA, `web/`) stay in lockstep with
+//! the server. Requires the `ts` feature.
+//!
+//! The output covers [`ClientMessage`], [`ServerMessage`], [`SceneDocument`],
+//! [`ElementDocument`] and every type they reference, in the same JSON shape
+//! serde produces. The server serves it at `/api/types.d.ts`.
+
+use ts_rs::TS;
+
+use crate::agenda::AgendaItem;
+use crate::anchor::Anchor;
+use crate::annotation::MeasurementKind;
+use crate::attachment::{AttachPoint, Attachment};
+use crate::barcode::{ErrorCorrection, Symbology};
+use crate::branding::{Branding, BrandingPosition};
+use crate::calendar::{CalendarEvent, CalendarView};
+use crate::camera::{CameraCommand, FitOptions};
+use crate::color::{Color, Theme, ThemeColor};
+use crate::dashboard::{GaugeShape, NumberFormat, Threshold};
+use crate::data::{
+    Aggregate, DataBinding, DataFilter, DataQuery, DataSort, Dataset, DatasetDelta, FilterOp,
+};
+use crate::element::{
+    CalloutTarget, CropRect, ElementId, ElementKind, ImageFormat, MediaConfig, PlaybackState,
+    QualityPreset, Resolution, Transform,
+};
+use crate::floor_plan::{FloorMarker, MarkerShape, Occupancy, PlanPath};
+use crate::follow::CameraFrame;
+use crate::highlight::{HighlightSpan, TokenKind};
+use crate::ink::EraseMode;
+use crate::interaction::{
+    ContextMenu, ContextMenuItem, DragAxis, DragBehavior, Interactions, TapAction,
+};
+use crate::model3d::{Light, Lighting, ModelCamera, Transform3D};
+use crate::ocr::{OcrBlock, OcrText};
+use crate::protocol::{
+    BatchOperation, ClientMessage, Consistency, FailedOperationInfo, OperationType,
+    QueuedOperation, QuotaBudget, QuotaStatus, ServerMessage,
+};
+use crate::randomizer::{RandomOutcome, RandomSource};
+use crate::schema::{
+    ElementDocument, PatchOp, SceneDocument, ScenePage, ScenePatch, ViewportDocument,
+};
+use crate::shape::{ArrowDirection, ShapeKind};
+use crate::sparkline::SparklineKind;
+use crate::spotlight::Spotlight;
+use crate::style::{Fill, GradientStop, Shadow, Stroke, Style};
+use crate::tree::{TreeDirection, TreeNode};
+use crate::units::{CoordinateSystem, LineMeasurement, WorldUnit, YAxis};
+use crate::view_state::ViewState;
+
+/// Render every exported declaration as one TypeScript module.
+#[must_use]
+#[allow(clippy::too_many_lines)]
+pub fn definitions() -> String {
+    let declarations = [
+        // Protocol
+        ClientMessage::decl(),
+        ServerMessage::decl(),
+        QueuedOperation::decl(),
+        BatchOperation::decl(),
+        OperationType::decl(),
+        FailedOperationInfo::decl(),
+        Consistency::decl(),
+        QuotaStatus::decl(),
+        QuotaBudget::decl(),
+        // Schema
+        SceneDocument::decl(),
+        ScenePatch::decl(),
+        ScenePage::decl(),
+        PatchOp::decl(),
+        ViewportDocument::decl(),
+        ElementDocument::decl(),
+        // Elements
+        ElementId::decl(),
+        ElementKind::decl(),
+        CalloutTarget::decl(),
+        Attachment::decl(),
+        AttachPoint::decl(),
+        HighlightSpan::decl(),
+        TokenKind::decl(),
+        ImageFormat::decl(),
+        OcrText::decl(),
+        OcrBlock::decl(),
+        CropRect::decl(),
+        MediaConfig::decl(),
+        Resolution::decl(),
+        QualityPreset::decl(),
+        PlaybackState::decl(),
+        CalendarView::decl(),
+        CalendarEvent::decl(),
+        AgendaItem::decl(),
+        RandomSource::decl(),
+        RandomOutcome::decl(),
+        Occupancy::decl(),
+        MarkerShape::decl(),
+        FloorMarker::decl(),
+        PlanPath::decl(),
+        GaugeShape::decl(),
+        Threshold::decl(),
+        NumberFormat::decl(),
+        SparklineKind::decl(),
+        TreeNode::decl(),
+        TreeDirection::decl(),
+        EraseMode::decl(),
+        MeasurementKind::decl(),
+        ShapeKind::decl(),
+        ArrowDirection::decl(),
+        Symbology::decl(),
+        ErrorCorrection::decl(),
+        Transform3D::decl(),
+        ModelCamera::decl(),
+        Lighting::decl(),
+        Light::decl(),
+        Transform::decl(),
+        // Data
+        Dataset::decl(),
+        DatasetDelta::decl(),
+        DataBinding::decl(),
+        DataQuery::decl(),
+        DataFilter::decl(),
+        FilterOp::decl(),
+        Aggregate::decl(),
+        DataSort::decl(),
+        // Interactions
+        Interactions::decl(),
+        TapAction::decl(),
+        DragBehavior::decl(),
+        DragAxis::decl(),
+        ContextMenu::decl(),
+        ContextMenuItem::decl(),
+        // Styling
+        Style::decl(),
+        Fill::decl(),
+        GradientStop::decl(),
+        Stroke::decl(),
+        Shadow::decl(),
+        Color::decl(),
+        ThemeColor::decl(),
+        Theme::decl(),
+        Spotlight::decl(),
+        Branding::decl(),
+        BrandingPosition::decl(),
+        // Units
+        CoordinateSystem::decl(),
+        LineMeasurement::decl(),
+        WorldUnit::decl(),
+        YAxis::decl(),
+        // Camera
+        CameraCommand::decl(),
+        FitOptions::decl(),
+        CameraFrame::decl(),
+        Anchor::decl(),
+        ViewState::decl(),
+        // Free-form JSON (chart data, update changes)
+        serde_json::Value::decl(),
+    ];
+
+    let mut out = format!(
+        "// Saorsa Canvas protocol types (canvas-core {}).\n\
+         // Generated from the Rust definitions; do not edit by hand.\n",
+        env!("CARGO_PKG_VERSION")
+    );
+    for declaration in declarations {
+        out.push('\n');
+        out.push_str("export ");
+        // Timestamps are `u64`, but `JSON.parse` yields plain numbers
+        out.push_str(&declaration.replace("bigint", "number"));
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_definitions_cover_protocol_and_schema() {
+        let ts = definitions();
+
+        for name in [
+            "type ClientMessage",
+            "type ServerMessage",
+            "type SceneDocument",
+            "type ElementDocument",
+            "type ElementKind",
+            "type CalloutTarget",
+        ] {
+            assert!(ts.contains(&format!("export {name}")), "missing {name}");
+        }
+        // Tagged the way serde serializes them
+        assert!(ts.contains("\"type\": \"add_element\""));
+        assert!(ts.contains("\"type\": \"StickyNote\""));
+        // Every referenced type is declared
+        assert!(!ts.contains("bigint"));
+        for referenced in [
+            "Transform",
+            "Style",
+            "ViewState",
+            "FitOptions",
+            "JsonValue",
+            "Theme",
+            "CoordinateSystem",
+        ] {
+            assert!(ts.contains(&format!("export type {referenced} ")));
+        }
+        // Colors travel as CSS strings
+        assert!(ts.contains("export type ThemeColor = string"));
+    }
+}
@@ -0,0 +1,345 @@
+//! # Units and Coordinates
+//!
+//! Element transforms are always stored in canvas pixels, with y growing
+//! down from the top-left corner. A scene's [`CoordinateSystem`] describes
+//! the world those pixels stand for, so CAD-like and print-targeted canvases
+//! can work in millimetres (or feet, or grid squares) with y growing up from
+//! a chosen origin, and convert at the edges:
+//!
+//! ```text
+//!  canvas px                          world (mm, y up, origin at (0, 600))
+//!  (0,0) ──────────► x                 ▲ y
+//!    │                                 │
+//!    │      • (96, 504)      ⇄         │    • (25.4, 25.4)
+//!    ▼ y                               └──────────► x
+//! ```
+//!
+//! Physical units assume the canvas's natural 96 pixels per inch, the same
+//! scale print exports use. The coordinate system is part of the scene
+//! state, so it syncs to every viewer.
+//!
+//! [`CoordinateSystem::measure`] reads the distance and angle between two
+//! canvas points in world units, for rulers and agents alike.
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{CanvasError, CanvasResult};
+
+/// Canvas pixels per inch at natural size.
+pub const PX_PER_INCH: f32 = 96.0;
+
+/// Longest allowed name of a custom unit, in characters.
+pub const MAX_UNIT_NAME_LEN: usize = 16;
+
+/// The unit world coordinates are measured in.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+#[serde(rename_all = "snake_case")]
+pub enum WorldUnit {
+    /// Canvas pixels.
+    #[default]
+    Px,
+    /// Millimetres.
+    Mm,
+    /// Centimetres.
+    Cm,
+    /// Inches.
+    In,
+    /// Typographic points (1/72 in).
+    Pt,
+    /// A named unit with its own scale, e.g. feet on a floor plan.
+    Custom {
+        /// Symbol shown after lengths, e.g. `"ft"`.
+        name: String,
+        /// Canvas pixels per unit.
+        px_per_unit: f32,
+    },
+}
+
+impl WorldUnit {
+    /// Canvas pixels per unit.
+    #[must_use]
+    pub fn px_per_unit(&self) -> f32 {
+        match self {
+            Self::Px => 1.0,
+            Self::Mm => PX_PER_INCH / 25.4,
+            Self::Cm => PX_PER_INCH / 2.54,
+            Self::In => PX_PER_INCH,
+            Self::Pt => PX_PER_INCH / 72.0,
+            Self::Custom { px_per_unit, .. } => *px_per_unit,
+        }
+    }
+
+    /// Symbol shown after lengths, e.g. `"mm"`.
+    #[must_use]
+    pub fn symbol(&self) -> &str {
+        match self {
+            Self::Px => "px",
+            Self::Mm => "mm",
+            Self::Cm => "cm",
+            Self::In => "in",
+            Self::Pt => "pt",
+            Self::Custom { name, .. } => name,
+        }
+    }
+}
+
+/// Which way world y grows.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+#[serde(rename_all = "snake_case")]
+pub enum YAxis {
+    /// Down the screen, like canvas pixels.
+    #[default]
+    Down,
+    /// Up the screen, like CAD drawings and charts.
+    Up,
+}
+
+/// Distance and direction between two points, in world units.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+pub struct LineMeasurement {
+    /// Straight-line distance.
+    pub length: f32,
+    /// Change in world x from start to end.
+    pub dx: f32,
+    /// Change in world y from start to end.
+    pub dy: f32,
+    /// Direction from start to end in degrees, counterclockwise from the
+    /// world x axis as seen with y up, in `(-180, 180]`.
+    pub angle: f32,
+    /// Symbol of the unit the lengths are in.
+    pub unit: String,
+}
+
+/// How world coordinates map onto canvas pixels.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+#[serde(default)]
+pub struct CoordinateSystem {
+    /// Unit of world coordinates and lengths.
+    pub unit: WorldUnit,
+    /// Canvas position of the world origin, in pixels.
+    pub origin: [f32; 2],
+    /// Which way world y grows.
+    pub y_axis: YAxis,
+}
+
+impl CoordinateSystem {
+    /// Plain canvas pixels: the default.
+    #[must_use]
+    pub fn pixels() -> Self {
+        Self::default()
+    }
+
+    /// Measure in `unit`, keeping the pixel origin and axes.
+    #[must_use]
+    pub fn with_unit(mut self, unit: WorldUnit) -> Self {
+        self.unit = unit;
+        self
+    }
+
+    /// Put the world origin at canvas position (`x`, `y`) in pixels.
+    #[must_use]
+    pub fn with_origin(mut self, x: f32, y: f32) -> Self {
+        self.origin = [x, y];
+        self
+    }
+
+    /// Set which way world y grows.
+    #[must_use]
+    pub fn with_y_axis(mut self, y_axis: YAxis) -> Self {
+        self.y_axis = y_axis;
+        self
+    }
+
+    /// Whether this is plain canvas pixels, which documents leave out.
+    #[must_use]
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Check the unit scale and origin are usable.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a custom unit has no name, a name longer than
+    /// [`MAX_UNIT_NAME_LEN`] or a non-positive scale, or the origin is not
+    /// finite.
+    pub fn validate(&self) -> CanvasResult<()> {
+        if let WorldUnit::Custom { name, px_per_unit } = &self.unit {
+            if name.trim().is_empty() || name.chars().count() > MAX_UNIT_NAME_LEN {
+                return Err(CanvasError::InvalidOperation(format!(
+                    "Unit name must be 1-{MAX_UNIT_NAME_LEN} characters"
+                )));
+            }
+            if !(px_per_unit.is_finite() && *px_per_unit > 0.0) {
+                return Err(CanvasError::InvalidOperation(format!(
+                    "Pixels per unit must be positive, got {px_per_unit}"
+                )));
+            }
+        }
+        if !self.origin.iter().all(|v| v.is_finite()) {
+            return Err(CanvasError::InvalidOperation(
+                "Origin must be finite".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Measure from canvas position `start` to `end`, both in pixels.
+    #[must_use]
+    pub fn measure(&self, start: [f32; 2], end: [f32; 2]) -> LineMeasurement {
+        let [x0, y0] = self.to_world(start);
+        let [x1, y1] = self.to_world(end);
+        let (dx, dy) = (x1 - x0, y1 - y0);
+        // Angles read as on paper: counterclockwise is positive
+        let up = match self.y_axis {
+            YAxis::Down => -dy,
+            YAxis::Up => dy,
+        };
+        let mut angle = up.atan2(dx).to_degrees();
+        if angle <= -180.0 {
+            angle += 360.0;
+        }
+        LineMeasurement {
+            length: dx.hypot(dy),
+            dx,
+            dy,
+            angle,
+            unit: self.unit.symbol().to_string(),
+        }
+    }
+
+    /// Convert a length in canvas pixels to world units.
+    #[must_use]
+    pub fn length_to_world(&self, px: f32) -> f32 {
+        px / self.unit.px_per_unit()
+    }
+
+    /// Convert a length in world units to canvas pixels.
+    #[must_use]
+    pub fn length_to_canvas(&self, length: f32) -> f32 {
+        length * self.unit.px_per_unit()
+    }
+
+    /// Convert a canvas position in pixels to world coordinates.
+    #[must_use]
+    pub fn to_world(&self, [x, y]: [f32; 2]) -> [f32; 2] {
+        let dy = self.length_to_world(y - self.origin[1]);
+        [
+            self.length_to_world(x - self.origin[0]),
+            match self.y_axis {
+                YAxis::Down => dy,
+                YAxis::Up => -dy,
+            },
+        ]
+    }
+
+    /// Convert world coordinates to a canvas position in pixels.
+    #[must_use]
+    pub fn to_canvas(&self, [x, y]: [f32; 2]) -> [f32; 2] {
+        let dy = self.length_to_canvas(y);
+        [
+            self.origin[0] + self.length_to_canvas(x),
+            self.origin[1]
+                + match self.y_axis {
+                    YAxis::Down => dy,
+                    YAxis::Up => -dy,
+                },
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: [f32; 2], b: [f32; 2]) -> bool {
+        (a[0] - b[0]).abs() < 1e-4 && (a[1] - b[1]).abs() < 1e-4
+    }
+
+    #[test]
+    fn test_default_is_canvas_pixels() {
+        let system = CoordinateSystem::default();
+        assert!(system.is_default());
+        assert!(close(system.to_world([12.0, 34.0]), [12.0, 34.0]));
+        assert!(close(system.to_canvas([12.0, 34.0]), [12.0, 34.0]));
+        assert_eq!(system.unit.symbol(), "px");
+    }
+
+    #[test]
+    fn test_cad_style_round_trip() {
+        let system = CoordinateSystem::pixels()
+            .with_unit(WorldUnit::Mm)
+            .with_origin(0.0, 600.0)
+            .with_y_axis(YAxis::Up);
+        assert!(!system.is_default());
+        // One inch right of and above the origin
+        assert!(close(system.to_world([96.0, 504.0]), [25.4, 25.4]));
+        assert!(close(system.to_canvas([25.4, 25.4]), [96.0, 504.0]));
+        assert!((system.length_to_canvas(10.0) - 37.795_277).abs() < 1e-3);
+        assert!((WorldUnit::Pt.px_per_unit() * 72.0 - PX_PER_INCH).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_measure_in_world_units() {
+        let system = CoordinateSystem::pixels()
+            .with_unit(WorldUnit::In)
+            .with_origin(0.0, 600.0)
+            .with_y_axis(YAxis::Up);
+        // Up and to the right on screen
+        let m = system.measure([0.0, 600.0], [288.0, 216.0]);
+        assert!((m.length - 5.0).abs() < 1e-4);
+        assert!(close([m.dx, m.dy], [3.0, 4.0]));
+        assert!((m.angle - 53.130_1).abs() < 1e-3);
+        assert_eq!(m.unit, "in");
+
+        // Screen-up reads as a positive angle whichever way y grows
+        let down = CoordinateSystem::pixels().measure([0.0, 100.0], [0.0, 0.0]);
+        assert!((down.angle - 90.0).abs() < 1e-4);
+        assert!((down.dy + 100.0).abs() < 1e-4);
+        let left = CoordinateSystem::pixels().measure([100.0, 0.0], [0.0, 0.0]);
+        assert!((left.angle - 180.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_custom_units_and_validation() {
+        let feet = WorldUnit::Custom {
+            name: "ft".to_string(),
+            px_per_unit: 20.0,
+        };
+        let system = CoordinateSystem::pixels().with_unit(feet);
+        assert!(system.validate().is_ok());
+        assert_eq!(system.unit.symbol(), "ft");
+        assert!((system.length_to_world(50.0) - 2.5).abs() < f32::EPSILON);
+
+        let json = serde_json::to_value(&system).unwrap();
+        assert_eq!(json["unit"]["custom"]["name"], "ft");
+        let parsed: CoordinateSystem = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed, system);
+        let parsed: CoordinateSystem = serde_json::from_str(r#"{"unit": "mm"}"#).unwrap();
+        assert_eq!(parsed.unit, WorldUnit::Mm);
+
+        for unit in [
+            WorldUnit::Custom {
+                name: " ".to_string(),
+                px_per_unit: 1.0,
+            },
+            WorldUnit::Custom {
+                name: "ft".to_string(),
+                px_per_unit: 0.0,
+            },
+        ] {
+            assert!(CoordinateSystem::pixels()
+                .with_unit(unit)
+                .validate()
+                .is_err());
+        }
+        assert!(CoordinateSystem::pixels()
+            .with_origin(f32::NAN, 0.0)
+            .validate()
+            .is_err());
+    }
+}
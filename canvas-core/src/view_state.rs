@@ -0,0 +1,251 @@
+//! # Presenter View Sharing
+//!
+//! Lets one viewer present their camera to everyone else in a session.
+//!
+//! The presenter periodically sends a [`ViewState`] (zoom, pan and viewport
+//! size). Followers mirror the *region* the presenter is looking at rather
+//! than copying zoom/pan verbatim, so a phone following a desktop presenter
+//! still sees the same content. [`PresenterFollow`] eases the follower's
+//! camera toward that region and can be switched off per viewer.
+//!
+//! ```text
+//! presenter pans      → view_state { presenter_id, view }
+//! follower receives   → PresenterFollow::receive(presenter_id, view)
+//! every frame         → PresenterFollow::update(scene, now) eases pan/zoom
+//! ```
+//!
+//! Time is passed in as milliseconds so the same logic runs natively and in
+//! WASM.
+
+use serde::{Deserialize, Serialize};
+
+use crate::camera::{FitOptions, SceneBounds};
+use crate::follow::CameraFrame;
+use crate::scene::Scene;
+
+/// A viewer's camera together with the viewport it applies to.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts", derive(ts_rs::TS))]
+pub struct ViewState {
+    /// Zoom level and pan offset.
+    pub camera: CameraFrame,
+    /// Viewport width in pixels.
+    pub viewport_width: f32,
+    /// Viewport height in pixels.
+    pub viewport_height: f32,
+}
+
+impl ViewState {
+    /// Capture the current view of a scene.
+    #[must_use]
+    pub fn from_scene(scene: &Scene) -> Self {
+        Self {
+            camera: CameraFrame::from_scene(scene),
+            viewport_width: scene.viewport_width,
+            viewport_height: scene.viewport_height,
+        }
+    }
+
+    /// The canvas-space region visible in this view.
+    #[must_use]
+    pub fn visible_bounds(&self) -> SceneBounds {
+        let zoom = if self.camera.zoom > 0.0 {
+            self.camera.zoom
+        } else {
+            1.0
+        };
+        let min_x = -self.camera.pan_x / zoom;
+        let min_y = -self.camera.pan_y / zoom;
+        SceneBounds {
+            min_x,
+            min_y,
+            max_x: min_x + self.viewport_width / zoom,
+            max_y: min_y + self.viewport_height / zoom,
+        }
+    }
+
+    /// The camera that shows the same region in `scene`'s viewport.
+    ///
+    /// Falls back to the presenter's camera as-is if the viewport has no size.
+    #[must_use]
+    pub fn frame_for(&self, scene: &Scene) -> CameraFrame {
+        let options = FitOptions::default()
+            .with_padding(0.0)
+            .with_zoom_range(0.01, 100.0);
+        CameraFrame::fit_bounds(scene, &self.visible_bounds(), &options).unwrap_or(self.camera)
+    }
+}
+
+/// Mirrors a presenter's camera onto the local scene.
+#[derive(Debug, Clone)]
+pub struct PresenterFollow {
+    enabled: bool,
+    presenter_id: Option<String>,
+    view: Option<ViewState>,
+    /// Fraction of the remaining distance covered per update (0.0 to 1.0).
+    smoothing: f32,
+    /// How long user interaction pauses following, in milliseconds.
+    user_pause_ms: u64,
+    paused_until_ms: u64,
+}
+
+impl Default for PresenterFollow {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PresenterFollow {
+    /// Create a follower that mirrors presenters unless opted out.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            enabled: true,
+            presenter_id: None,
+            view: None,
+            smoothing: 0.25,
+            user_pause_ms: 5_000,
+            paused_until_ms: 0,
+        }
+    }
+
+    /// Opt in to or out of following the presenter.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        self.paused_until_ms = 0;
+    }
+
+    /// Check whether following is enabled.
+    #[must_use]
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// ID of the current presenter, if anyone is presenting.
+    #[must_use]
+    pub fn presenter_id(&self) -> Option<&str> {
+        self.presenter_id.as_deref()
+    }
+
+    /// Latest view received from the presenter.
+    #[must_use]
+    pub fn view(&self) -> Option<&ViewState> {
+        self.view.as_ref()
+    }
+
+    /// Check whether the camera is currently being driven by a presenter.
+    #[must_use]
+    pub fn is_following(&self, now_ms: u64) -> bool {
+        self.enabled && self.view.is_some() && now_ms >= self.paused_until_ms
+    }
+
+    /// Record a view from the presenter (a new presenter replaces the old one).
+    pub fn receive(&mut self, presenter_id: impl Into<String>, view: ViewState) {
+        self.presenter_id = Some(presenter_id.into());
+        self.view = Some(view);
+    }
+
+    /// Stop following `presenter_id` if it is the current presenter.
+    ///
+    /// Returns `true` if the presenter was cleared.
+    pub fn presenter_stopped(&mut self, presenter_id: &str) -> bool {
+        if self.presenter_id.as_deref() == Some(presenter_id) {
+            self.presenter_id = None;
+            self.view = None;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Record a local pan/zoom, pausing following for a short period.
+    pub fn record_user_interaction(&mut self, now_ms: u64) {
+        self.paused_until_ms = now_ms.saturating_add(self.user_pause_ms);
+    }
+
+    /// Advance the camera one step toward the presenter's view.
+    ///
+    /// Call once per frame. Returns `true` if the scene camera moved.
+    pub fn update(&mut self, scene: &mut Scene, now_ms: u64) -> bool {
+        if !self.is_following(now_ms) {
+            return false;
+        }
+        let Some(view) = &self.view else {
+            return false;
+        };
+
+        let goal = view.frame_for(scene);
+        let current = CameraFrame::from_scene(scene);
+        if current.approx_eq(&goal) {
+            return false;
+        }
+        let next = current.lerp(&goal, self.smoothing);
+        if next.approx_eq(&goal) {
+            goal.apply_to(scene);
+        } else {
+            next.apply_to(scene);
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn presenter_view() -> ViewState {
+        // A 1000x500 presenter zoomed 2x on the region (100, 50)-(600, 300)
+        ViewState {
+            camera: CameraFrame {
+                zoom: 2.0,
+                pan_x: -200.0,
+                pan_y: -100.0,
+            },
+            viewport_width: 1000.0,
+            viewport_height: 500.0,
+        }
+    }
+
+    #[test]
+    fn test_follower_mirrors_visible_region() {
+        let bounds = presenter_view().visible_bounds();
+        assert!((bounds.min_x - 100.0).abs() < 0.01);
+        assert!((bounds.max_y - 300.0).abs() < 0.01);
+
+        // A follower with half the viewport width sees the same region at 1x
+        let mut scene = Scene::new(500.0, 250.0);
+        let frame = presenter_view().frame_for(&scene);
+        assert!((frame.zoom - 1.0).abs() < 0.001);
+        assert!((frame.pan_x + 100.0).abs() < 0.01);
+
+        let mut follow = PresenterFollow::new();
+        follow.receive("peer-a", presenter_view());
+        for _ in 0..100 {
+            if !follow.update(&mut scene, 0) {
+                break;
+            }
+        }
+        assert!(CameraFrame::from_scene(&scene).approx_eq(&frame));
+    }
+
+    #[test]
+    fn test_opt_out_pause_and_stop() {
+        let mut scene = Scene::new(1000.0, 500.0);
+        let mut follow = PresenterFollow::new();
+        follow.receive("peer-a", presenter_view());
+
+        follow.set_enabled(false);
+        assert!(!follow.update(&mut scene, 0));
+
+        follow.set_enabled(true);
+        follow.record_user_interaction(1_000);
+        assert!(!follow.update(&mut scene, 2_000));
+        assert!(follow.update(&mut scene, 6_000));
+
+        // Only the current presenter can stop the presentation
+        assert!(!follow.presenter_stopped("peer-b"));
+        assert!(follow.presenter_stopped("peer-a"));
+        assert!(follow.presenter_id().is_none());
+        assert!(!follow.update(&mut scene, 6_000));
+    }
+}
@@ -227,6 +227,7 @@ fn test_low_confidence_rejected() {
     let config = FusionConfig {
         fusion_window: Duration::from_millis(2000),
         min_confidence: 0.7,
+        ..FusionConfig::default()
     };
     let mut fusion = InputFusion::with_config(config);
 
@@ -247,6 +248,7 @@ fn test_confidence_at_threshold() {
     let config = FusionConfig {
         fusion_window: Duration::from_millis(2000),
         min_confidence: 0.7,
+        ..FusionConfig::default()
     };
     let mut fusion = InputFusion::with_config(config);
 
@@ -266,6 +268,7 @@ fn test_adjustable_confidence_threshold() {
     fusion.set_config(FusionConfig {
         fusion_window: Duration::from_millis(2000),
         min_confidence: 0.9,
+        ..FusionConfig::default()
     });
 
     let _ = fusion.process_touch(&touch_start(100.0, 100.0, None));
@@ -407,6 +410,7 @@ fn test_time_remaining() {
     let config = FusionConfig {
         fusion_window: Duration::from_millis(2000),
         min_confidence: 0.5,
+        ..FusionConfig::default()
     };
     let mut fusion = InputFusion::with_config(config);
 
@@ -429,6 +433,7 @@ fn test_is_touch_valid() {
     let config = FusionConfig {
         fusion_window: Duration::from_millis(100), // Short window for test
         min_confidence: 0.5,
+        ..FusionConfig::default()
     };
     let mut fusion = InputFusion::with_config(config);
 
@@ -453,6 +458,7 @@ fn test_config_accessors() {
     let config = FusionConfig {
         fusion_window: Duration::from_millis(3000),
         min_confidence: 0.8,
+        ..FusionConfig::default()
     };
     let fusion = InputFusion::with_config(config);
 
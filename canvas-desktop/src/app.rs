@@ -1,32 +1,66 @@
 //! Desktop application using winit 0.30 `ApplicationHandler`.
 
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use anyhow::Result;
-use canvas_core::{Element, ElementKind, Scene, Transform};
+use canvas_core::{CameraCommand, Color, Element, ElementKind, FitOptions, Scene, Transform};
 use canvas_renderer::backend::wgpu::WgpuBackend;
-use canvas_renderer::RenderBackend;
+use canvas_renderer::{
+    Camera, ColorSpace, HolographicRenderer, OrbitCamera, PostEffects, RenderBackend, RenderResult,
+    RenderStats,
+};
 use winit::{
     application::ApplicationHandler,
-    dpi::PhysicalSize,
-    event::WindowEvent,
+    dpi::{PhysicalPosition, PhysicalSize},
+    event::{ElementState, KeyEvent, MouseButton, MouseScrollDelta, WindowEvent},
     event_loop::ActiveEventLoop,
+    keyboard::{Key, ModifiersState, NamedKey},
     window::{Window, WindowAttributes, WindowId},
 };
 
-use crate::DesktopConfig;
+use crate::{CrashReporter, DesktopConfig, GpuInfo, SceneSummary};
 
 /// Desktop canvas application.
 ///
 /// Manages the winit window and wgpu renderer lifecycle using the
 /// `ApplicationHandler` trait introduced in winit 0.30.
+///
+/// Keyboard shortcuts:
+/// - `F` fits the view to all content
+/// - `Shift+F` fits the view to the selected elements
+/// - `0` or `Home` resets the view
+/// - `H` toggles the render stats HUD
+///
+/// In holographic mode the mouse and trackpad move the camera: drag to
+/// orbit, right-, middle- or `Shift`-drag to pan, and scroll or pinch to
+/// zoom.
 pub struct CanvasDesktopApp {
     config: DesktopConfig,
     window: Option<Arc<Window>>,
     renderer: Option<WgpuBackend>,
+    holographic: Option<HolographicRenderer>,
+    /// Holographic camera, framing the scene's model on first render.
+    orbit: Option<OrbitCamera>,
+    /// Last cursor position in the window.
+    cursor: Option<PhysicalPosition<f64>>,
+    /// Mouse button held down, if any.
+    dragging: Option<MouseButton>,
+    modifiers: ModifiersState,
+    last_frame: Instant,
     scene: Scene,
+    stats: Option<RenderStats>,
+    hud: bool,
+    last_stats_report: Instant,
+    crash_reporter: Option<CrashReporter>,
 }
 
+/// How often the HUD and stats file are refreshed.
+const STATS_REPORT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Trackpad scroll pixels counted as one wheel line when zooming.
+const PIXELS_PER_SCROLL_LINE: f64 = 40.0;
+
 impl CanvasDesktopApp {
     /// Create a new desktop application with the given configuration.
     ///
@@ -37,14 +71,39 @@ impl CanvasDesktopApp {
         let scene = initial_scene.unwrap_or_else(|| Self::create_test_scene(&config));
         tracing::debug!("Scene created with {} elements", scene.element_count());
 
+        let hud = config.hud;
+        let holographic = config
+            .holographic
+            .as_ref()
+            .map(|display| HolographicRenderer::new(display.config.clone()));
         Self {
             config,
             window: None,
             renderer: None,
+            holographic,
+            orbit: None,
+            cursor: None,
+            dragging: None,
+            modifiers: ModifiersState::empty(),
+            last_frame: Instant::now(),
             scene,
+            stats: None,
+            hud,
+            last_stats_report: Instant::now(),
+            crash_reporter: None,
         }
     }
 
+    /// Keep `reporter`'s crash bundles up to date with the GPU, window and
+    /// scene.
+    #[must_use]
+    pub fn with_crash_reporter(mut self, reporter: CrashReporter) -> Self {
+        let summary = SceneSummary::of(&self.scene);
+        reporter.update(|diagnostics| diagnostics.scene = Some(summary));
+        self.crash_reporter = Some(reporter);
+        self
+    }
+
     /// Create a test scene with sample elements for development/demo purposes.
     #[allow(clippy::cast_precision_loss)] // Window dimensions fit in f32
     fn create_test_scene(config: &DesktopConfig) -> Scene {
@@ -59,6 +118,7 @@ impl CanvasDesktopApp {
                 "values": [120, 200, 150, 180],
                 "background": "#ffffff"
             }),
+            binding: None,
         })
         .with_transform(Transform {
             x: 100.0,
@@ -67,12 +127,13 @@ impl CanvasDesktopApp {
             height: 300.0,
             rotation: 0.0,
             z_index: 0,
+            auto_size: false,
         });
 
         let text_element = Element::new(ElementKind::Text {
             content: "Saorsa Canvas".to_string(),
             font_size: 24.0,
-            color: "#FFFFFF".to_string(),
+            color: Color::WHITE.into(),
         })
         .with_transform(Transform {
             x: 100.0,
@@ -81,6 +142,7 @@ impl CanvasDesktopApp {
             height: 40.0,
             rotation: 0.0,
             z_index: 1,
+            auto_size: false,
         });
 
         scene.add_element(chart_element);
@@ -97,6 +159,22 @@ impl CanvasDesktopApp {
 
         // Set a visible background color (dark blue-gray) to confirm pipeline works
         backend.set_background_color(0.1, 0.12, 0.18, 1.0);
+        if !self.config.effects.is_empty() {
+            backend.set_post_effects(&PostEffects::frame(self.config.effects.clone()));
+        }
+        if self.config.color_space != ColorSpace::Srgb {
+            backend.set_color_space(self.config.color_space);
+            tracing::info!("Presenting {}", backend.color_space().name());
+        }
+
+        if let Some(reporter) = &self.crash_reporter {
+            let gpu = GpuInfo::from_backend(&backend);
+            let size = window.inner_size();
+            reporter.update(|diagnostics| {
+                diagnostics.gpu = Ok(gpu);
+                diagnostics.window = Some((size.width, size.height));
+            });
+        }
 
         self.renderer = Some(backend);
         self.window = Some(window);
@@ -107,26 +185,255 @@ impl CanvasDesktopApp {
     }
 
     /// Handle window resize.
+    #[allow(clippy::cast_precision_loss)] // Window dimensions fit in f32
     fn handle_resize(&mut self, size: PhysicalSize<u32>) {
         if size.width == 0 || size.height == 0 {
             return;
         }
 
+        self.scene
+            .set_viewport(size.width as f32, size.height as f32);
+
         if let Some(renderer) = &mut self.renderer {
             if let Err(e) = renderer.resize(size.width, size.height) {
                 tracing::error!("Failed to resize renderer: {e}");
             }
         }
+        if let Some(reporter) = &self.crash_reporter {
+            reporter.update(|diagnostics| diagnostics.window = Some((size.width, size.height)));
+        }
+    }
+
+    /// Map a key press to a camera command.
+    fn camera_command_for_key(&self, event: &KeyEvent) -> Option<CameraCommand> {
+        if event.state != ElementState::Pressed || event.repeat {
+            return None;
+        }
+        match &event.logical_key {
+            Key::Character(c) if c.as_str() == "f" => Some(CameraCommand::FitToContent),
+            Key::Character(c) if c.as_str() == "F" => Some(CameraCommand::FitToElements {
+                element_ids: self.scene.selected_elements().map(|e| e.id).collect(),
+            }),
+            Key::Character(c) if c.as_str() == "0" => Some(CameraCommand::ResetView),
+            Key::Named(NamedKey::Home) => Some(CameraCommand::ResetView),
+            _ => None,
+        }
+    }
+
+    /// Run a camera command against the scene and redraw.
+    fn run_camera_command(&mut self, command: &CameraCommand) {
+        if matches!(command, CameraCommand::ResetView) {
+            if let Some(orbit) = &mut self.orbit {
+                orbit.reset();
+            }
+        }
+        match command.apply(&mut self.scene, &FitOptions::default()) {
+            Ok(frame) => {
+                tracing::debug!("Camera moved to {frame:?}");
+                if let Some(window) = &self.window {
+                    window.request_redraw();
+                }
+            }
+            Err(e) => tracing::debug!("Camera command ignored: {e}"),
+        }
     }
 
     /// Render the current scene.
     fn render(&mut self) {
-        if let Some(renderer) = &mut self.renderer {
-            if let Err(e) = renderer.render(&self.scene) {
-                tracing::error!("Render error: {e}");
+        let Some(renderer) = &mut self.renderer else {
+            return;
+        };
+        let start = Instant::now();
+        let dt = start.duration_since(self.last_frame).as_secs_f32();
+        self.last_frame = start;
+        let mut orbiting = false;
+        let result = match &mut self.holographic {
+            Some(holographic) => {
+                let scene = &self.scene;
+                let orbit = self.orbit.get_or_insert_with(|| {
+                    OrbitCamera::new(&HolographicRenderer::model_camera(scene).unwrap_or_default())
+                });
+                orbiting = orbit.update(dt);
+                Self::render_holographic(
+                    holographic,
+                    renderer,
+                    &self.config,
+                    scene,
+                    &orbit.camera(),
+                )
+            }
+            None => renderer.render(&self.scene),
+        };
+        if let Err(e) = result {
+            tracing::error!("Render error: {e}");
+            return;
+        }
+        let frame_time_ms = start.elapsed().as_secs_f64() * 1000.0;
+        let stats = self
+            .stats
+            .get_or_insert_with(|| RenderStats::new(renderer.backend_type()));
+        stats.record(renderer.frame_stats(), frame_time_ms);
+        stats.record_gpu_time(renderer.gpu_time_ms());
+
+        if self.last_stats_report.elapsed() >= STATS_REPORT_INTERVAL {
+            self.last_stats_report = Instant::now();
+            self.report_stats();
+        }
+
+        // Shader elements change with time, so keep drawing while any show,
+        // as well as while the holographic camera eases into place
+        let animating = orbiting
+            || self
+                .scene
+                .elements()
+                .any(|e| matches!(e.kind, ElementKind::Shader { .. }));
+        if animating {
+            if let Some(window) = &self.window {
+                window.request_redraw();
             }
         }
     }
+
+    /// Render the scene for a Looking Glass: the lightfield image when the
+    /// display's lens is known, otherwise the quilt's center view.
+    fn render_holographic(
+        holographic: &mut HolographicRenderer,
+        renderer: &mut WgpuBackend,
+        config: &DesktopConfig,
+        scene: &Scene,
+        camera: &Camera,
+    ) -> RenderResult<()> {
+        let Some(display) = &config.holographic else {
+            return renderer.render(scene);
+        };
+        if let Some(lens) = &display.lens {
+            return holographic.render_lightfield(renderer, scene, camera, lens);
+        }
+        let center = display
+            .config
+            .camera_for_view(camera, display.config.num_views / 2);
+        renderer.render_with_camera(scene, Some(&center), None)
+    }
+
+    /// Move the holographic camera for mouse and trackpad input. Returns
+    /// `true` if the event moved it.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_precision_loss)] // Small values
+    fn handle_orbit_input(&mut self, event: &WindowEvent) -> bool {
+        match event {
+            WindowEvent::ModifiersChanged(modifiers) => {
+                self.modifiers = modifiers.state();
+                return false;
+            }
+            WindowEvent::MouseInput { state, button, .. } => {
+                self.dragging = match state {
+                    ElementState::Pressed => Some(*button),
+                    ElementState::Released => None,
+                };
+                return false;
+            }
+            WindowEvent::CursorLeft { .. } => {
+                self.cursor = None;
+                return false;
+            }
+            _ => {}
+        }
+        let view_height = self
+            .window
+            .as_ref()
+            .map_or(self.config.height, |w| w.inner_size().height) as f32;
+        let Some(orbit) = &mut self.orbit else {
+            if let WindowEvent::CursorMoved { position, .. } = event {
+                self.cursor = Some(*position);
+            }
+            return false;
+        };
+        match event {
+            WindowEvent::CursorMoved { position, .. } => {
+                let previous = self.cursor.replace(*position);
+                let (Some(previous), Some(button)) = (previous, self.dragging) else {
+                    return false;
+                };
+                let dx = (position.x - previous.x) as f32;
+                let dy = (position.y - previous.y) as f32;
+                match button {
+                    MouseButton::Left if !self.modifiers.shift_key() => orbit.drag(dx, dy),
+                    MouseButton::Left | MouseButton::Right | MouseButton::Middle => {
+                        orbit.pan(dx, dy, view_height);
+                    }
+                    _ => return false,
+                }
+            }
+            WindowEvent::MouseWheel { delta, .. } => {
+                let lines = match delta {
+                    MouseScrollDelta::LineDelta(_, y) => *y,
+                    MouseScrollDelta::PixelDelta(position) => {
+                        (position.y / PIXELS_PER_SCROLL_LINE) as f32
+                    }
+                };
+                orbit.scroll(lines);
+            }
+            WindowEvent::PinchGesture { delta, .. } => orbit.zoom(1.0 + *delta as f32),
+            _ => return false,
+        }
+        true
+    }
+
+    /// Refresh the HUD, stats file and crash report, and log the current
+    /// stats.
+    fn report_stats(&self) {
+        if let Some(reporter) = &self.crash_reporter {
+            let summary = SceneSummary::of(&self.scene);
+            reporter.update(|diagnostics| diagnostics.scene = Some(summary));
+        }
+        let Some(stats) = &self.stats else {
+            return;
+        };
+        tracing::debug!(
+            "Render stats: {}/{} drawn, {:.2} ms avg ({})",
+            stats.last_frame.elements_drawn,
+            stats.last_frame.elements_total,
+            stats.avg_frame_time_ms,
+            stats.backend.name()
+        );
+
+        if self.hud {
+            if let Some(window) = &self.window {
+                let gpu = stats
+                    .gpu_time_ms
+                    .map_or_else(String::new, |ms| format!(" (gpu {ms:.2} ms)"));
+                window.set_title(&format!(
+                    "{} — {:.2} ms{gpu} · {}/{} drawn · {} culled · {} draws · {} tris · {}",
+                    self.config.title,
+                    stats.avg_frame_time_ms,
+                    stats.last_frame.elements_drawn,
+                    stats.last_frame.elements_total,
+                    stats.last_frame.elements_culled,
+                    stats.last_frame.draw_calls,
+                    stats.last_frame.triangles,
+                    stats.backend.name()
+                ));
+            }
+        }
+
+        if let Some(path) = &self.config.stats_file {
+            let result = serde_json::to_vec_pretty(stats)
+                .map_err(std::io::Error::from)
+                .and_then(|json| std::fs::write(path, json));
+            if let Err(e) = result {
+                tracing::warn!("Failed to write render stats to {}: {e}", path.display());
+            }
+        }
+    }
+
+    /// Show or hide the render stats HUD.
+    fn toggle_hud(&mut self) {
+        self.hud = !self.hud;
+        if self.hud {
+            self.report_stats();
+        } else if let Some(window) = &self.window {
+            window.set_title(&self.config.title);
+        }
+    }
 }
 
 impl ApplicationHandler for CanvasDesktopApp {
@@ -173,9 +480,19 @@ impl ApplicationHandler for CanvasDesktopApp {
             self.config.height
         );
 
+        // A Looking Glass window matches the display's screen
+        let size = self
+            .config
+            .holographic
+            .as_ref()
+            .and_then(|display| display.lens)
+            .map_or(
+                PhysicalSize::new(self.config.width, self.config.height),
+                |lens| PhysicalSize::new(lens.screen_width, lens.screen_height),
+            );
         let attrs = WindowAttributes::default()
             .with_title(&self.config.title)
-            .with_inner_size(PhysicalSize::new(self.config.width, self.config.height));
+            .with_inner_size(size);
 
         match event_loop.create_window(attrs) {
             Ok(window) => {
@@ -221,6 +538,28 @@ impl ApplicationHandler for CanvasDesktopApp {
             WindowEvent::RedrawRequested => {
                 self.render();
             }
+            WindowEvent::KeyboardInput { event, .. } => {
+                if let Some(command) = self.camera_command_for_key(&event) {
+                    self.run_camera_command(&command);
+                } else if event.state == ElementState::Pressed
+                    && !event.repeat
+                    && matches!(&event.logical_key, Key::Character(c) if c.eq_ignore_ascii_case("h"))
+                {
+                    self.toggle_hud();
+                }
+            }
+            WindowEvent::ModifiersChanged(_)
+            | WindowEvent::MouseInput { .. }
+            | WindowEvent::CursorMoved { .. }
+            | WindowEvent::CursorLeft { .. }
+            | WindowEvent::MouseWheel { .. }
+            | WindowEvent::PinchGesture { .. }
+                if self.handle_orbit_input(&event) =>
+            {
+                if let Some(window) = &self.window {
+                    window.request_redraw();
+                }
+            }
             WindowEvent::ScaleFactorChanged { scale_factor, .. } => {
                 tracing::info!("Scale factor changed to {scale_factor}");
                 if let Some(renderer) = &mut self.renderer {
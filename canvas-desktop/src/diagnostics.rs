@@ -0,0 +1,454 @@
+//! Diagnostics for bug reports: an environment and GPU capability report,
+//! and an opt-in panic handler that saves it with the crash.
+//!
+//! `--diagnostics` prints a [`Diagnostics`] report and exits. With
+//! `--crash-dir <dir>`, a [`CrashReporter`] is installed as the panic hook;
+//! when the app panics it writes a bundle to that directory with the panic
+//! message and backtrace, the report as it stood (GPU adapter, element
+//! counts of the scene) and the last log lines, then offers to copy it to
+//! the clipboard when run from a terminal.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt;
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::panic::PanicHookInfo;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use canvas_core::Scene;
+use canvas_renderer::backend::wgpu::WgpuBackend;
+use canvas_renderer::RenderBackend;
+use tracing_subscriber::fmt::MakeWriter;
+
+/// Log lines kept for crash bundles.
+pub const LOG_LINES: usize = 200;
+
+/// The most recent log lines, shared with the panic hook.
+///
+/// Cloning shares the buffer; it is a [`MakeWriter`] for
+/// `tracing_subscriber::fmt` layers.
+#[derive(Debug, Clone)]
+pub struct LogBuffer {
+    lines: Arc<Mutex<VecDeque<String>>>,
+    capacity: usize,
+}
+
+impl Default for LogBuffer {
+    fn default() -> Self {
+        Self::new(LOG_LINES)
+    }
+}
+
+impl LogBuffer {
+    /// A buffer keeping the last `capacity` lines.
+    #[must_use]
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            lines: Arc::new(Mutex::new(VecDeque::with_capacity(capacity))),
+            capacity,
+        }
+    }
+
+    /// The kept lines, oldest first.
+    #[must_use]
+    pub fn lines(&self) -> Vec<String> {
+        self.lines
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .cloned()
+            .collect()
+    }
+
+    /// Keep the non-empty lines of `text`, dropping the oldest past the
+    /// capacity.
+    fn push(&self, text: &str) {
+        let mut lines = self.lines.lock().unwrap_or_else(PoisonError::into_inner);
+        for line in text.lines().filter(|line| !line.trim().is_empty()) {
+            if lines.len() == self.capacity {
+                lines.pop_front();
+            }
+            lines.push_back(line.to_string());
+        }
+    }
+}
+
+impl<'a> MakeWriter<'a> for LogBuffer {
+    type Writer = LogWriter;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        LogWriter {
+            buffer: self.clone(),
+            pending: Vec::new(),
+        }
+    }
+}
+
+/// Collects one log event's output, added to its [`LogBuffer`] whole when
+/// dropped.
+#[derive(Debug)]
+pub struct LogWriter {
+    buffer: LogBuffer,
+    pending: Vec<u8>,
+}
+
+impl Write for LogWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.pending.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Drop for LogWriter {
+    fn drop(&mut self) {
+        self.buffer.push(&String::from_utf8_lossy(&self.pending));
+    }
+}
+
+/// The GPU adapter and what the renderer can do with it.
+#[derive(Debug, Clone)]
+pub struct GpuInfo {
+    /// Adapter name, e.g. `Apple M2`.
+    pub name: String,
+    /// Graphics API in use, e.g. `Metal`.
+    pub backend: String,
+    /// Integrated, discrete, virtual or CPU.
+    pub device_type: String,
+    /// Driver name and version, where the platform reports them.
+    pub driver: String,
+    /// Largest 2D texture side, in pixels.
+    pub max_texture_size: u32,
+    /// MSAA sample counts above 1 the device supports.
+    pub msaa_samples: Vec<u32>,
+    /// Whether frames can be timed on the GPU.
+    pub gpu_timing: bool,
+    /// Color space frames are presented in.
+    pub color_space: &'static str,
+}
+
+impl GpuInfo {
+    /// Describe the adapter behind `backend`.
+    #[must_use]
+    pub fn from_backend(backend: &WgpuBackend) -> Self {
+        let adapter = backend.adapter_info();
+        let driver = [adapter.driver.as_str(), adapter.driver_info.as_str()]
+            .into_iter()
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ");
+        Self {
+            name: adapter.name.clone(),
+            backend: format!("{:?}", adapter.backend),
+            device_type: format!("{:?}", adapter.device_type),
+            driver,
+            max_texture_size: backend.device().limits().max_texture_dimension_2d,
+            msaa_samples: backend.msaa_samples().to_vec(),
+            gpu_timing: backend
+                .device()
+                .features()
+                .contains(wgpu::Features::TIMESTAMP_QUERY),
+            color_space: backend.color_space().name(),
+        }
+    }
+}
+
+/// Element counts of a scene, by kind.
+#[derive(Debug, Clone, Default)]
+pub struct SceneSummary {
+    /// Every element, nested ones included.
+    pub elements: usize,
+    /// Elements by their kind's `type` tag.
+    pub by_kind: BTreeMap<&'static str, usize>,
+}
+
+impl SceneSummary {
+    /// Count the elements of `scene`.
+    #[must_use]
+    pub fn of(scene: &Scene) -> Self {
+        let mut summary = Self::default();
+        for element in scene.elements() {
+            summary.elements += 1;
+            *summary.by_kind.entry(element.kind.type_name()).or_default() += 1;
+        }
+        summary
+    }
+}
+
+/// Environment and render capability report for bug reports.
+#[derive(Debug, Clone)]
+pub struct Diagnostics {
+    /// canvas-desktop version.
+    pub version: &'static str,
+    /// Operating system and architecture, e.g. `macos aarch64`.
+    pub platform: String,
+    /// Logical CPUs.
+    pub cpus: usize,
+    /// Environment variables that change how the app renders or logs.
+    pub environment: Vec<(&'static str, String)>,
+    /// The GPU, or why none could be used.
+    pub gpu: Result<GpuInfo, String>,
+    /// Window size in physical pixels, once open.
+    pub window: Option<(u32, u32)>,
+    /// The scene on screen, once there is one.
+    pub scene: Option<SceneSummary>,
+}
+
+/// Environment variables worth reporting when set.
+const REPORTED_VARS: &[&str] = &[
+    "WGPU_BACKEND",
+    "WGPU_ADAPTER_NAME",
+    "RUST_LOG",
+    "XDG_SESSION_TYPE",
+    "WAYLAND_DISPLAY",
+    "DISPLAY",
+];
+
+impl Diagnostics {
+    /// A report of this environment, with no GPU looked up yet.
+    #[must_use]
+    pub fn environment() -> Self {
+        Self {
+            version: env!("CARGO_PKG_VERSION"),
+            platform: format!("{} {}", std::env::consts::OS, std::env::consts::ARCH),
+            cpus: std::thread::available_parallelism().map_or(1, std::num::NonZero::get),
+            environment: REPORTED_VARS
+                .iter()
+                .filter_map(|&name| std::env::var(name).ok().map(|value| (name, value)))
+                .collect(),
+            gpu: Err("not initialized yet".to_string()),
+            window: None,
+            scene: None,
+        }
+    }
+
+    /// A report of this environment and the GPU a headless renderer gets,
+    /// as `--diagnostics` prints.
+    #[must_use]
+    pub fn collect() -> Self {
+        let mut diagnostics = Self::environment();
+        diagnostics.gpu = WgpuBackend::new()
+            .map(|backend| GpuInfo::from_backend(&backend))
+            .map_err(|e| e.to_string());
+        diagnostics
+    }
+}
+
+impl fmt::Display for Diagnostics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "canvas-desktop {}", self.version)?;
+        writeln!(f, "platform:      {} ({} cpus)", self.platform, self.cpus)?;
+        for (name, value) in &self.environment {
+            writeln!(f, "{name}={value}")?;
+        }
+        match &self.gpu {
+            Ok(gpu) => {
+                writeln!(f, "gpu:           {} ({})", gpu.name, gpu.device_type)?;
+                writeln!(f, "backend:       {}", gpu.backend)?;
+                if !gpu.driver.is_empty() {
+                    writeln!(f, "driver:        {}", gpu.driver)?;
+                }
+                writeln!(f, "max texture:   {}px", gpu.max_texture_size)?;
+                let msaa = gpu
+                    .msaa_samples
+                    .iter()
+                    .map(|samples| format!("{samples}x"))
+                    .collect::<Vec<_>>();
+                writeln!(f, "msaa:          {}", msaa.join(", "))?;
+                let timing = if gpu.gpu_timing { "yes" } else { "no" };
+                writeln!(f, "gpu timing:    {timing}")?;
+                writeln!(f, "color space:   {}", gpu.color_space)?;
+            }
+            Err(reason) => writeln!(f, "gpu:           unavailable: {reason}")?,
+        }
+        if let Some((width, height)) = self.window {
+            writeln!(f, "window:        {width}x{height}")?;
+        }
+        if let Some(scene) = &self.scene {
+            let kinds = scene
+                .by_kind
+                .iter()
+                .map(|(kind, count)| format!("{kind} {count}"))
+                .collect::<Vec<_>>();
+            writeln!(f, "scene:         {} elements", scene.elements)?;
+            if !kinds.is_empty() {
+                writeln!(f, "               {}", kinds.join(", "))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Panic handler writing crash bundles to a directory.
+///
+/// Cloning shares the report, which the app keeps current through
+/// [`update`](Self::update).
+#[derive(Debug, Clone)]
+pub struct CrashReporter {
+    dir: PathBuf,
+    log: LogBuffer,
+    diagnostics: Arc<Mutex<Diagnostics>>,
+}
+
+impl CrashReporter {
+    /// A reporter writing to `dir`, with the log lines kept in `log`.
+    #[must_use]
+    pub fn new(dir: impl Into<PathBuf>, log: LogBuffer) -> Self {
+        Self {
+            dir: dir.into(),
+            log,
+            diagnostics: Arc::new(Mutex::new(Diagnostics::environment())),
+        }
+    }
+
+    /// Directory crash bundles are written to.
+    #[must_use]
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Install this reporter as the panic hook, ahead of the current one,
+    /// which still prints the panic.
+    pub fn install(&self) {
+        let reporter = self.clone();
+        let previous = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            previous(info);
+            reporter.report_panic(info);
+        }));
+    }
+
+    /// Change the report crash bundles include.
+    pub fn update(&self, change: impl FnOnce(&mut Diagnostics)) {
+        change(
+            &mut self
+                .diagnostics
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner),
+        );
+    }
+
+    /// Write a bundle for a panic, tell the user where and offer to copy it.
+    fn report_panic(&self, info: &PanicHookInfo<'_>) {
+        let message = info
+            .payload()
+            .downcast_ref::<&str>()
+            .copied()
+            .or_else(|| info.payload().downcast_ref::<String>().map(String::as_str))
+            .unwrap_or("(no message)");
+        let location = info
+            .location()
+            .map_or_else(|| "unknown".to_string(), ToString::to_string);
+        let backtrace = std::backtrace::Backtrace::force_capture().to_string();
+        let bundle = self.bundle(message, &location, &backtrace);
+
+        match self.write_bundle(&bundle) {
+            Ok(path) => {
+                eprintln!(
+                    "\ncanvas-desktop crashed. A diagnostics bundle was written to\n  {}\n\
+                     Please attach it to your bug report.",
+                    path.display()
+                );
+                offer_to_copy(&bundle);
+            }
+            Err(e) => {
+                eprintln!("\nFailed to write a diagnostics bundle: {e}\n\n{bundle}");
+            }
+        }
+    }
+
+    /// The text of a crash bundle.
+    fn bundle(&self, message: &str, location: &str, backtrace: &str) -> String {
+        // The panic may have come from a thread holding the lock
+        let diagnostics = match self.diagnostics.try_lock() {
+            Ok(diagnostics) => diagnostics.to_string(),
+            Err(_) => "(unavailable)\n".to_string(),
+        };
+        let thread = std::thread::current();
+        let mut bundle = format!(
+            "== Panic ==\n{message}\nat {location} on thread '{}'\n\n\
+             == Diagnostics ==\n{diagnostics}\n== Backtrace ==\n{backtrace}\n\n\
+             == Last {} log lines ==\n",
+            thread.name().unwrap_or("unnamed"),
+            self.log.capacity,
+        );
+        for line in self.log.lines() {
+            bundle.push_str(&line);
+            bundle.push('\n');
+        }
+        bundle
+    }
+
+    /// Write `bundle` to a new file in the crash directory.
+    fn write_bundle(&self, bundle: &str) -> io::Result<PathBuf> {
+        std::fs::create_dir_all(&self.dir)?;
+        let stamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs());
+        let path = self.dir.join(format!(
+            "canvas-desktop-crash-{stamp}-{}.txt",
+            std::process::id()
+        ));
+        std::fs::write(&path, bundle)?;
+        Ok(path)
+    }
+}
+
+/// Ask on the terminal whether to copy `text` to the clipboard, and do.
+fn offer_to_copy(text: &str) {
+    if !io::stdin().is_terminal() || !io::stderr().is_terminal() {
+        return;
+    }
+    eprint!("Copy it to the clipboard? [y/N] ");
+    let mut answer = String::new();
+    if io::stdin().lock().read_line(&mut answer).is_err()
+        || !answer.trim().eq_ignore_ascii_case("y")
+    {
+        return;
+    }
+    match copy_to_clipboard(text) {
+        Ok(()) => eprintln!("Copied."),
+        Err(e) => eprintln!("Could not copy it: {e}"),
+    }
+}
+
+/// Copy `text` with the platform's clipboard command.
+fn copy_to_clipboard(text: &str) -> io::Result<()> {
+    let commands: &[&[&str]] = if cfg!(target_os = "macos") {
+        &[&["pbcopy"]]
+    } else if cfg!(windows) {
+        &[&["clip"]]
+    } else {
+        &[
+            &["wl-copy"],
+            &["xclip", "-selection", "clipboard"],
+            &["xsel", "--clipboard", "--input"],
+        ]
+    };
+    for command in commands {
+        let Ok(mut child) = Command::new(command[0])
+            .args(&command[1..])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+        else {
+            continue;
+        };
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(text.as_bytes())?;
+        }
+        if child.wait()?.success() {
+            return Ok(());
+        }
+    }
+    Err(io::Error::new(
+        io::ErrorKind::NotFound,
+        "no clipboard command found",
+    ))
+}
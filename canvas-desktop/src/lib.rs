@@ -18,11 +18,32 @@
 //! cargo run -p canvas-desktop -- --mcp-url http://localhost:3040/mcp --session default
 //! ```
 //!
+//! ## Render stats
+//!
+//! Press `H` (or pass `--hud`) to show frame time and drawn/culled element
+//! counts in the window title. Pass `--stats-file <path>` to write the same
+//! statistics as JSON once a second for external telemetry.
+//!
+//! ## Looking Glass displays
+//!
+//! `--holographic <portrait|4k|FILE>` renders multi-view quilts for a
+//! Looking Glass. Given the device's `visual.json`, the window shows the
+//! lightfield image for its lens, sized to its screen; move the window onto
+//! the display. A preset or a custom layout JSON file, which carries no
+//! lens, previews the center view instead.
+//!
+//! ## Bug reports
+//!
+//! `--diagnostics` prints the environment and the GPU's render capabilities
+//! and exits. Pass `--crash-dir <dir>` to write a diagnostics bundle there
+//! if the app panics.
+//!
 //! ## Architecture
 //!
 //! - `CliArgs` - Command-line arguments parsed with clap
 //! - `DesktopConfig` - Configuration for window size, title, and Communitas connection
 //! - `CanvasDesktopApp` - Main application implementing `ApplicationHandler`
+//! - `CrashReporter` - Opt-in panic hook writing diagnostics bundles
 //! - Uses `canvas-renderer::WgpuBackend` for GPU rendering
 
 #![forbid(unsafe_code)]
@@ -32,10 +53,18 @@
 
 mod app;
 mod communitas;
+mod diagnostics;
 
 pub use app::CanvasDesktopApp;
 pub use communitas::{DesktopCommunitasError, DesktopMcpClient};
+pub use diagnostics::{
+    CrashReporter, Diagnostics, GpuInfo, LogBuffer, LogWriter, SceneSummary, LOG_LINES,
+};
 
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use canvas_renderer::{ColorSpace, HolographicConfig, LensCalibration, PostEffect};
 use clap::Parser;
 
 /// Command-line arguments for canvas-desktop.
@@ -63,6 +92,64 @@ pub struct CliArgs {
     /// Window height in pixels
     #[arg(long, default_value = "720")]
     pub height: u32,
+
+    /// Show render stats in the window title (toggle with `H`)
+    #[arg(long)]
+    pub hud: bool,
+
+    /// Write render stats as JSON to this file once a second
+    #[arg(long, env = "CANVAS_STATS_FILE")]
+    pub stats_file: Option<PathBuf>,
+
+    /// Post-processing effects over every frame, in order (e.g.
+    /// `bloom,vignette`): blur, vignette, bloom, grade
+    #[arg(long, value_delimiter = ',')]
+    pub effects: Vec<PostEffect>,
+
+    /// Color space to present in: `srgb`, `extended_srgb` or `hdr10`,
+    /// falling back to what the display supports
+    #[arg(long, default_value = "srgb", env = "CANVAS_COLOR_SPACE")]
+    pub color_space: ColorSpace,
+
+    /// Write a diagnostics bundle (backtrace, GPU, scene, recent logs) to
+    /// this directory if the app crashes
+    #[arg(long, env = "CANVAS_CRASH_DIR")]
+    pub crash_dir: Option<PathBuf>,
+
+    /// Print environment and GPU capability info for bug reports, then exit
+    #[arg(long)]
+    pub diagnostics: bool,
+
+    /// Render for a Looking Glass display: a preset (`portrait`, `4k`), or
+    /// a JSON file with a custom layout or the device's `visual.json`
+    #[arg(long, env = "CANVAS_HOLOGRAPHIC")]
+    pub holographic: Option<HolographicDisplay>,
+}
+
+/// A Looking Glass display to render quilts for.
+#[derive(Debug, Clone)]
+pub struct HolographicDisplay {
+    /// Quilt layout and camera sweep.
+    pub config: HolographicConfig,
+    /// The display's lens, when loaded from its `visual.json`.
+    pub lens: Option<LensCalibration>,
+}
+
+impl FromStr for HolographicDisplay {
+    type Err = anyhow::Error;
+
+    /// Parse a preset name, or load a layout or calibration file.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(config) = HolographicConfig::preset(s) {
+            return Ok(Self { config, lens: None });
+        }
+        let json = std::fs::read_to_string(s)
+            .map_err(|e| anyhow::anyhow!("'{s}' is not a preset (portrait, 4k) or a file: {e}"))?;
+        Ok(Self {
+            config: HolographicConfig::from_calibration_json(&json)?,
+            lens: LensCalibration::from_visual_json(&json).ok(),
+        })
+    }
 }
 
 /// Desktop application configuration.
@@ -80,6 +167,16 @@ pub struct DesktopConfig {
     pub session: Option<String>,
     /// Authentication token for Communitas.
     pub token: Option<String>,
+    /// Show render stats in the window title.
+    pub hud: bool,
+    /// File to export render stats to as JSON.
+    pub stats_file: Option<PathBuf>,
+    /// Post-processing effects over every frame, in order.
+    pub effects: Vec<PostEffect>,
+    /// Color space to present in, if the display supports it.
+    pub color_space: ColorSpace,
+    /// Looking Glass display to render for, if any.
+    pub holographic: Option<HolographicDisplay>,
 }
 
 impl Default for DesktopConfig {
@@ -99,6 +196,11 @@ impl DesktopConfig {
             mcp_url: None,
             session: None,
             token: None,
+            hud: false,
+            stats_file: None,
+            effects: Vec::new(),
+            color_space: ColorSpace::Srgb,
+            holographic: None,
         }
     }
 }
@@ -112,6 +214,11 @@ impl From<CliArgs> for DesktopConfig {
             mcp_url: args.mcp_url,
             session: args.session,
             token: args.token,
+            hud: args.hud,
+            stats_file: args.stats_file,
+            effects: args.effects,
+            color_space: args.color_space,
+            holographic: args.holographic,
         }
     }
 }
@@ -3,25 +3,48 @@
 //! Native desktop application for Saorsa Canvas.
 
 use canvas_core::Scene;
-use canvas_desktop::{CanvasDesktopApp, CliArgs, DesktopConfig, DesktopMcpClient};
+use canvas_desktop::{
+    CanvasDesktopApp, CliArgs, CrashReporter, DesktopConfig, DesktopMcpClient, Diagnostics,
+    LogBuffer,
+};
 use clap::Parser;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 use winit::event_loop::EventLoop;
 
 fn main() -> anyhow::Result<()> {
-    // Initialize tracing
+    // Parse CLI arguments
+    let args = CliArgs::parse();
+    if args.diagnostics {
+        print!("{}", Diagnostics::collect());
+        return Ok(());
+    }
+
+    // Initialize tracing, keeping recent lines for crash bundles if asked
+    let log = LogBuffer::default();
     tracing_subscriber::registry()
         .with(
             tracing_subscriber::EnvFilter::try_from_default_env()
                 .unwrap_or_else(|_| "canvas_desktop=debug,canvas_renderer=debug,wgpu=warn".into()),
         )
         .with(tracing_subscriber::fmt::layer())
+        .with(args.crash_dir.is_some().then(|| {
+            tracing_subscriber::fmt::layer()
+                .with_ansi(false)
+                .with_writer(log.clone())
+        }))
         .init();
 
     tracing::info!("Starting Saorsa Canvas Desktop");
 
-    // Parse CLI arguments
-    let args = CliArgs::parse();
+    let crash_reporter = args.crash_dir.clone().map(|dir| {
+        let reporter = CrashReporter::new(dir, log);
+        reporter.install();
+        tracing::info!(
+            "Crash reports will be written to {}",
+            reporter.dir().display()
+        );
+        reporter
+    });
     let config = DesktopConfig::from(args);
 
     tracing::info!(
@@ -57,6 +80,9 @@ fn main() -> anyhow::Result<()> {
     // Create application
     tracing::debug!("Creating CanvasDesktopApp");
     let mut app = CanvasDesktopApp::new(config, initial_scene);
+    if let Some(reporter) = crash_reporter {
+        app = app.with_crash_reporter(reporter);
+    }
 
     // Create and run event loop
     tracing::debug!("Creating event loop");
@@ -0,0 +1,74 @@
+//! Stores for `canvas_archive` and `canvas_restore`.
+//!
+//! The server packs a session into a [`SessionArchive`] and hands it to an
+//! [`ArchiveStore`], which keeps it somewhere other than the server's disk
+//! and returns a reference to restore it by. The canvas server provides a
+//! store backed by the Communitas client; [`MemoryArchiveStore`] keeps
+//! archives in memory.
+
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use canvas_core::{ArchiveError, SessionArchive};
+use tokio::sync::RwLock;
+
+/// Where session archives are kept.
+#[async_trait]
+pub trait ArchiveStore: Send + Sync {
+    /// Short name reported with each archive, e.g. `"communitas"`.
+    fn name(&self) -> &str;
+
+    /// Store an archive, returning the reference to restore it by.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ArchiveError::Storage`] if the archive cannot be stored.
+    async fn put(&self, archive: &SessionArchive) -> Result<String, ArchiveError>;
+
+    /// Fetch the archive stored under `reference`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ArchiveError::NotFound`] if nothing is stored under
+    /// `reference`, or [`ArchiveError::Storage`] if the store fails.
+    async fn get(&self, reference: &str) -> Result<SessionArchive, ArchiveError>;
+}
+
+/// Keeps archives in memory, for tests and single-process setups.
+#[derive(Debug, Default)]
+pub struct MemoryArchiveStore {
+    archives: RwLock<HashMap<String, SessionArchive>>,
+}
+
+impl MemoryArchiveStore {
+    /// Create an empty store.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl ArchiveStore for MemoryArchiveStore {
+    fn name(&self) -> &'static str {
+        "memory"
+    }
+
+    async fn put(&self, archive: &SessionArchive) -> Result<String, ArchiveError> {
+        let reference = format!("memory:{}", uuid::Uuid::new_v4());
+        self.archives
+            .write()
+            .await
+            .insert(reference.clone(), archive.clone());
+        Ok(reference)
+    }
+
+    async fn get(&self, reference: &str) -> Result<SessionArchive, ArchiveError> {
+        self.archives
+            .read()
+            .await
+            .get(reference)
+            .cloned()
+            .ok_or_else(|| ArchiveError::NotFound(reference.to_string()))
+    }
+}
@@ -21,11 +21,15 @@
 #![deny(clippy::pedantic)]
 #![allow(clippy::module_name_repetitions)]
 
+pub mod archive;
 pub mod resources;
+pub mod scope;
 pub mod server;
 pub mod tools;
 
 // Re-export key types for convenience
+pub use archive::{ArchiveStore, MemoryArchiveStore};
+pub use scope::{AgentScope, ScopeViolation};
 pub use server::{CanvasMcpServer, JsonRpcRequest, JsonRpcResponse};
 
 use serde::{Deserialize, Serialize};
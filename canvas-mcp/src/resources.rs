@@ -1,5 +1,7 @@
 //! MCP resources for canvas sessions and content.
 
+use base64::Engine;
+use canvas_core::Encoding;
 use serde::{Deserialize, Serialize};
 
 use crate::ResourceContent;
@@ -29,8 +31,12 @@ pub mod uri {
     ///
     /// Supported formats:
     /// - `canvas://session/{id}` - A canvas session
+    /// - `canvas://session/{id}/thumbnail.png` - A PNG preview of a session
+    ///   (add `?redact=blur` or `?redact=placeholder` to hide sensitive
+    ///   elements)
     /// - `canvas://chart/{type}` - Chart template
     /// - `canvas://model/{id}` - 3D model
+    /// - `canvas://template/{name}` - Saved element template
     #[must_use]
     pub fn parse(uri: &str) -> Option<CanvasUri> {
         if !uri.starts_with("canvas://") {
@@ -42,21 +48,50 @@ pub mod uri {
 
         match parts.as_slice() {
             ["session", id] => Some(CanvasUri::Session((*id).to_string())),
+            ["session", id, "thumbnail.png"] => Some(CanvasUri::Thumbnail((*id).to_string())),
             ["chart", chart_type] => Some(CanvasUri::ChartTemplate((*chart_type).to_string())),
             ["model", id] => Some(CanvasUri::Model((*id).to_string())),
+            ["template", name] => Some(CanvasUri::Template((*name).to_string())),
             _ => None,
         }
     }
 
+    /// Split an optional `?format=` query off a canvas URI.
+    ///
+    /// `canvas://session/abc?format=msgpack` becomes
+    /// `("canvas://session/abc", Some("msgpack"))`. Other query parameters
+    /// are ignored.
+    #[must_use]
+    pub fn split_format(uri: &str) -> (&str, Option<&str>) {
+        let path = uri.split_once('?').map_or(uri, |(path, _)| path);
+        (path, query_param(uri, "format"))
+    }
+
+    /// The value of the query parameter `key` of a canvas URI, e.g. `blur`
+    /// for `redact` in `canvas://session/abc/thumbnail.png?redact=blur`.
+    #[must_use]
+    pub fn query_param<'a>(uri: &'a str, key: &str) -> Option<&'a str> {
+        let (_, query) = uri.split_once('?')?;
+        query.split('&').find_map(|pair| {
+            pair.split_once('=')
+                .filter(|(name, _)| *name == key)
+                .map(|(_, value)| value)
+        })
+    }
+
     /// Parsed canvas URI.
     #[derive(Debug, Clone)]
     pub enum CanvasUri {
         /// A canvas session.
         Session(String),
+        /// A PNG preview of a canvas session.
+        Thumbnail(String),
         /// A chart template.
         ChartTemplate(String),
         /// A 3D model.
         Model(String),
+        /// A saved element template.
+        Template(String),
     }
 }
 
@@ -86,10 +121,37 @@ pub fn get_resource(uri: &str) -> Result<ResourceContent, String> {
                 "data": []
             }
         }))),
+        uri::CanvasUri::Thumbnail(id) => Err(format!("Session {id} not found")),
         uri::CanvasUri::Model(id) => {
             // TODO: Return actual model data
             Err(format!("Model {id} not found"))
         }
+        uri::CanvasUri::Template(name) => Err(format!("Template {name} not found")),
+    }
+}
+
+/// Encode a value as resource content in the given encoding.
+///
+/// JSON becomes [`ResourceContent::Json`]; binary encodings become base64
+/// [`ResourceContent::Binary`].
+///
+/// # Errors
+///
+/// Returns an error if the value cannot be encoded.
+pub fn encode_resource<T: Serialize>(
+    value: &T,
+    encoding: Encoding,
+) -> Result<ResourceContent, String> {
+    if encoding.is_binary() {
+        let bytes = encoding.encode(value).map_err(|e| e.to_string())?;
+        Ok(ResourceContent::Binary {
+            data: base64::engine::general_purpose::STANDARD.encode(bytes),
+            mime_type: encoding.mime_type().to_string(),
+        })
+    } else {
+        serde_json::to_value(value)
+            .map(ResourceContent::Json)
+            .map_err(|e| e.to_string())
     }
 }
 
@@ -0,0 +1,320 @@
+//! Per-agent tool scopes.
+//!
+//! An [`AgentScope`] limits what a single agent may do through the MCP tools,
+//! so a misbehaving agent cannot wipe or rewrite the whole canvas:
+//!
+//! - `tools` - only the listed tools may be called
+//! - `own_elements_only` - only elements the agent created may be changed
+//! - `layer` - elements must live inside one layer or group; new elements
+//!   are placed into it
+//! - `region` - elements must stay inside a canvas-space rectangle
+//!
+//! Agents identify themselves with an `agent_id` tool argument. Scopes are
+//! checked before a tool runs; a violation is reported as a JSON-RPC error
+//! with code [`SCOPE_VIOLATION_CODE`] and the [`ScopeViolation`] as data.
+
+use std::collections::BTreeSet;
+
+use canvas_core::{ElementId, Scene, SceneBounds};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// JSON-RPC error code for calls rejected by an agent scope.
+pub const SCOPE_VIOLATION_CODE: i32 = -32003;
+
+/// Restrictions applied to one agent's tool calls.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AgentScope {
+    /// Tools the agent may call (`None` allows every tool).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tools: Option<BTreeSet<String>>,
+    /// Whether the agent may only modify elements it created.
+    pub own_elements_only: bool,
+    /// Layer or group the agent's elements must live in.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub layer: Option<ElementId>,
+    /// Canvas region the agent's elements must stay inside.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub region: Option<SceneBounds>,
+}
+
+/// Why a tool call was rejected by an agent scope.
+#[derive(Debug, Clone, PartialEq, Serialize, Error)]
+#[serde(tag = "reason", rename_all = "snake_case")]
+pub enum ScopeViolation {
+    /// The tool is not in the agent's allowed list.
+    #[error("Tool {tool} is not permitted for this agent")]
+    ToolNotAllowed {
+        /// Rejected tool name.
+        tool: String,
+    },
+    /// The call would replace or clear the whole scene.
+    #[error("Tool {tool} affects the whole scene, which this agent may not change")]
+    SceneWide {
+        /// Rejected tool name.
+        tool: String,
+    },
+    /// The element was not created by this agent.
+    #[error("Element {element_id} was not created by this agent")]
+    NotOwner {
+        /// Element the agent tried to modify.
+        element_id: String,
+    },
+    /// The element is not inside the agent's layer.
+    #[error("Element is outside layer {layer}")]
+    OutsideLayer {
+        /// Element the agent tried to modify (`None` for new elements).
+        #[serde(skip_serializing_if = "Option::is_none")]
+        element_id: Option<String>,
+        /// The agent's layer.
+        layer: String,
+    },
+    /// The element would lie outside the agent's region.
+    #[error("Element is outside the agent's permitted region")]
+    OutsideRegion {
+        /// Element the agent tried to modify (`None` for new elements).
+        #[serde(skip_serializing_if = "Option::is_none")]
+        element_id: Option<String>,
+        /// The agent's region.
+        region: SceneBounds,
+    },
+}
+
+impl AgentScope {
+    /// Create a scope with no restrictions.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allow only the given tools.
+    #[must_use]
+    pub fn with_tools<I, S>(mut self, tools: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.tools = Some(tools.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Allow modifying only elements the agent created.
+    #[must_use]
+    pub const fn own_elements_only(mut self) -> Self {
+        self.own_elements_only = true;
+        self
+    }
+
+    /// Confine the agent's elements to a layer or group.
+    #[must_use]
+    pub const fn within_layer(mut self, layer: ElementId) -> Self {
+        self.layer = Some(layer);
+        self
+    }
+
+    /// Confine the agent's elements to a canvas region.
+    #[must_use]
+    pub const fn within_region(mut self, region: SceneBounds) -> Self {
+        self.region = Some(region);
+        self
+    }
+
+    /// Check whether the scope limits which elements the agent may touch.
+    #[must_use]
+    pub fn restricts_elements(&self) -> bool {
+        self.own_elements_only || self.layer.is_some() || self.region.is_some()
+    }
+
+    /// Check that the agent may call `tool`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ScopeViolation::ToolNotAllowed`] if the tool is not listed.
+    pub fn check_tool(&self, tool: &str) -> Result<(), ScopeViolation> {
+        match &self.tools {
+            Some(tools) if !tools.contains(tool) => Err(ScopeViolation::ToolNotAllowed {
+                tool: tool.to_string(),
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Check that the agent may make a change affecting the whole scene.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ScopeViolation::SceneWide`] if the scope restricts elements.
+    pub fn check_scene_wide(&self, tool: &str) -> Result<(), ScopeViolation> {
+        if self.restricts_elements() {
+            Err(ScopeViolation::SceneWide {
+                tool: tool.to_string(),
+            })
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Check that the agent may modify an existing element.
+    ///
+    /// `owner` is the agent that created the element, if known.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first rule the element breaks.
+    pub fn check_element(
+        &self,
+        scene: &Scene,
+        element_id: ElementId,
+        owner: Option<&str>,
+        agent_id: Option<&str>,
+    ) -> Result<(), ScopeViolation> {
+        if self.own_elements_only && (owner.is_none() || owner != agent_id) {
+            return Err(ScopeViolation::NotOwner {
+                element_id: element_id.to_string(),
+            });
+        }
+        if let Some(layer) = self.layer {
+            if !is_descendant(scene, element_id, layer) {
+                return Err(ScopeViolation::OutsideLayer {
+                    element_id: Some(element_id.to_string()),
+                    layer: layer.to_string(),
+                });
+            }
+        }
+        if let Some(element) = scene.get_element(element_id) {
+            self.check_bounds(
+                Some(element_id),
+                &SceneBounds::from_transform(&element.transform),
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Check that the agent may create elements in `scene`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ScopeViolation::OutsideLayer`] if the agent's layer does not
+    /// exist in the scene.
+    pub fn check_create(&self, scene: Option<&Scene>) -> Result<(), ScopeViolation> {
+        match self.layer {
+            Some(layer) if scene.and_then(|s| s.get_element(layer)).is_none() => {
+                Err(ScopeViolation::OutsideLayer {
+                    element_id: None,
+                    layer: layer.to_string(),
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Check that element bounds lie inside the agent's region.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ScopeViolation::OutsideRegion`] if they do not.
+    pub fn check_bounds(
+        &self,
+        element_id: Option<ElementId>,
+        bounds: &SceneBounds,
+    ) -> Result<(), ScopeViolation> {
+        match self.region {
+            Some(region) if !region.contains(bounds) => Err(ScopeViolation::OutsideRegion {
+                element_id: element_id.map(|id| id.to_string()),
+                region,
+            }),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Check whether `id` is `ancestor` or nested (at any depth) inside it.
+fn is_descendant(scene: &Scene, id: ElementId, ancestor: ElementId) -> bool {
+    let mut current = Some(id);
+    // Bound the walk so a corrupt parent cycle cannot loop forever
+    for _ in 0..=scene.element_count() {
+        match current {
+            Some(id) if id == ancestor => return true,
+            Some(id) => current = scene.get_element(id).and_then(|e| e.parent),
+            None => return false,
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use canvas_core::{Color, Element, ElementKind, Transform};
+
+    fn text_at(x: f32, y: f32) -> Element {
+        Element::new(ElementKind::Text {
+            content: "Note".to_string(),
+            font_size: 16.0,
+            color: Color::BLACK.into(),
+        })
+        .with_transform(Transform {
+            x,
+            y,
+            width: 50.0,
+            height: 20.0,
+            rotation: 0.0,
+            z_index: 0,
+            auto_size: false,
+        })
+    }
+
+    #[test]
+    fn test_scope_rules() {
+        let mut scene = Scene::new(800.0, 600.0);
+        let layer = scene.add_element(Element::new(ElementKind::OverlayLayer {
+            children: Vec::new(),
+            opacity: 0.0,
+            clip: false,
+        }));
+        let inside = scene.add_element(text_at(10.0, 10.0));
+        scene.reparent(inside, layer).expect("reparent");
+        let outside = scene.add_element(text_at(500.0, 500.0));
+
+        let scope = AgentScope::new()
+            .with_tools(["canvas_update_element"])
+            .within_layer(layer)
+            .within_region(SceneBounds {
+                min_x: 0.0,
+                min_y: 0.0,
+                max_x: 400.0,
+                max_y: 300.0,
+            });
+        assert!(scope.check_tool("canvas_update_element").is_ok());
+        assert!(matches!(
+            scope.check_tool("canvas_clear"),
+            Err(ScopeViolation::ToolNotAllowed { .. })
+        ));
+        assert!(scope.check_scene_wide("canvas_clear").is_err());
+        assert!(scope.check_element(&scene, inside, None, None).is_ok());
+        assert!(matches!(
+            scope.check_element(&scene, outside, None, None),
+            Err(ScopeViolation::OutsideLayer { .. })
+        ));
+        assert!(scope.check_create(Some(&scene)).is_ok());
+        assert!(scope.check_create(None).is_err());
+
+        let owned = AgentScope::new().own_elements_only();
+        assert!(owned
+            .check_element(&scene, outside, Some("agent-a"), Some("agent-a"))
+            .is_ok());
+        assert!(owned
+            .check_element(&scene, outside, Some("agent-b"), Some("agent-a"))
+            .is_err());
+
+        let violation = scope
+            .check_bounds(
+                None,
+                &SceneBounds::from_transform(&text_at(390.0, 0.0).transform),
+            )
+            .expect_err("outside region");
+        let json = serde_json::to_value(&violation).expect("serialize");
+        assert_eq!(json["reason"], "outside_region");
+    }
+}
@@ -4,17 +4,35 @@
 
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use canvas_core::{
-    A2UITree, Element, ElementId, ElementKind, ImageFormat, SceneDocument, SceneStore, Transform,
+    agenda, annotation, barcode, dashboard, floor_plan, ink, log_view, model3d, ocr, sparkline,
+    tree,
+};
+use canvas_core::{
+    downsample_waveform, match_elements, A2UITree, AgendaCommand, AgendaStatus, Anchor,
+    ArchiveEntry, ArchiveError, Attachment, Branding, CalendarDate, CalloutTarget, CameraCommand,
+    Dataset, DatasetDelta, Element, ElementId, ElementKind, Encoding, ErrorCorrection, FitOptions,
+    ImageFormat, Interactions, IssueSeverity, LintConfig, MeasureContext, MeasurementKind,
+    MeasurementLayout, OcrEngine, OcrError, PlaybackState, PollResults, Scene, SceneBounds,
+    SceneDocument, SceneStore, SessionArchive, Spotlight, Style, Symbology, Template,
+    TemplateLibrary, Theme, ThemeColor, Transform, TreeDirection, TreeNode, DEFAULT_LOG_LINES,
+    MAX_AGENDA_ITEMS, MAX_AGENDA_ITEM_SECS, MAX_ANCHOR_NAME_LEN, MAX_BARCODE_PAYLOAD_LEN,
+    MAX_DATASET_COLUMNS, MAX_DATASET_NAME_LEN, MAX_DATASET_ROWS, MAX_LOG_LINES, MAX_MODEL_LIGHTS,
+    MAX_PALETTE_COLORS, MAX_POLL_OPTIONS, MAX_SHADER_LEN, MAX_SHADER_UNIFORMS, MAX_THEME_ENTRIES,
+    MAX_WATERMARK_LEN, MAX_WAVEFORM_POINTS,
 };
 use serde::{Deserialize, Serialize};
 use tokio::sync::RwLock;
 use tracing::warn;
 
+use crate::archive::ArchiveStore;
 use crate::resources::{self, CanvasSession};
+use crate::scope::{AgentScope, ScopeViolation, SCOPE_VIOLATION_CODE};
 use crate::tools::{
-    canvas_export, canvas_interact, ExportParams, InteractParams, RenderContent, RenderParams,
+    canvas_interact, ExportFormat, ExportParams, InteractParams, Position, PrintOptions,
+    RenderContent, RenderParams,
 };
 use crate::ToolResponse;
 
@@ -22,6 +40,68 @@ use crate::ToolResponse;
 // Helper Functions
 // ============================================================================
 
+/// Tools that change the scene and therefore accept `dry_run`.
+const MUTATING_TOOLS: &[&str] = &[
+    "canvas_render",
+    "canvas_render_a2ui",
+    "canvas_clear",
+    "canvas_add_element",
+    "canvas_remove_element",
+    "canvas_update_element",
+    "canvas_group",
+    "canvas_ungroup",
+    "canvas_poll",
+    "canvas_agenda",
+    "canvas_log_append",
+    "canvas_tree",
+    "canvas_barcode",
+    "canvas_annotate",
+    "canvas_spotlight",
+    "canvas_camera",
+    "canvas_anchor",
+    "canvas_set_theme",
+    "canvas_branding",
+    "canvas_data",
+    "canvas_ocr",
+    "canvas_restore",
+    "canvas_instantiate_template",
+];
+
+/// Tools that manage transactions.
+const TRANSACTION_TOOLS: &[&str] = &[
+    "canvas_begin_transaction",
+    "canvas_commit_transaction",
+    "canvas_rollback_transaction",
+];
+
+/// Age after which open transactions are dropped when another begins.
+const TRANSACTION_TTL: Duration = Duration::from_mins(10);
+
+/// Transactions one agent may have open at once.
+const MAX_TRANSACTIONS_PER_AGENT: usize = 8;
+
+/// Width of dry-run preview thumbnails in pixels.
+#[cfg(feature = "preview")]
+const PREVIEW_WIDTH: f32 = 320.0;
+
+/// Longest side of session thumbnail resources in pixels.
+#[cfg(feature = "preview")]
+const THUMBNAIL_SIZE: u32 = 256;
+
+/// Check whether a tool call would change scenes or templates.
+fn writes(name: &str, arguments: &serde_json::Value) -> bool {
+    let flag = |key: &str| {
+        arguments
+            .get(key)
+            .and_then(serde_json::Value::as_bool)
+            .unwrap_or(false)
+    };
+    (MUTATING_TOOLS.contains(&name) && !flag("dry_run"))
+        || TRANSACTION_TOOLS.contains(&name)
+        || name == "canvas_save_template"
+        || (name == "canvas_lint" && flag("apply"))
+}
+
 /// Extract session ID from JSON arguments with a default fallback.
 fn extract_session_id(arguments: &serde_json::Value) -> String {
     arguments
@@ -41,12 +121,35 @@ fn extract_element_id(arguments: &serde_json::Value) -> Result<ElementId, ToolRe
     ElementId::parse(id_str).map_err(|e| ToolResponse::error(format!("Invalid element_id: {e}")))
 }
 
+/// Extract and parse a non-empty `element_ids` array from JSON arguments.
+fn extract_element_ids(arguments: &serde_json::Value) -> Result<Vec<ElementId>, ToolResponse> {
+    let ids = arguments
+        .get("element_ids")
+        .and_then(serde_json::Value::as_array)
+        .filter(|ids| !ids.is_empty())
+        .ok_or_else(|| ToolResponse::error("element_ids must be a non-empty array of strings"))?;
+    ids.iter()
+        .map(|id| {
+            id.as_str()
+                .and_then(|id| ElementId::parse(id).ok())
+                .ok_or_else(|| ToolResponse::error(format!("Invalid element_id: {id}")))
+        })
+        .collect()
+}
+
 /// Parse a transform from JSON, using defaults for missing fields.
+///
+/// Without a `width` or `height` the transform is auto-sized, unless
+/// `auto_size` says otherwise.
 #[allow(clippy::cast_possible_truncation)]
 fn parse_transform(json: Option<&serde_json::Value>) -> Transform {
     let Some(t) = json else {
-        return Transform::default();
+        return Transform {
+            auto_size: true,
+            ..Transform::default()
+        };
     };
+    let sized = t.get("width").is_some() || t.get("height").is_some();
 
     Transform {
         x: t.get("x")
@@ -71,7 +174,245 @@ fn parse_transform(json: Option<&serde_json::Value>) -> Transform {
             .get("z_index")
             .and_then(serde_json::Value::as_i64)
             .unwrap_or(0) as i32,
+        auto_size: t
+            .get("auto_size")
+            .and_then(serde_json::Value::as_bool)
+            .unwrap_or(!sized),
+    }
+}
+
+/// Extract the `x`/`y` placement of a template from JSON arguments.
+#[allow(clippy::cast_possible_truncation)]
+fn template_offset(arguments: &serde_json::Value) -> (f32, f32) {
+    let coord = |key: &str| {
+        arguments
+            .get(key)
+            .and_then(serde_json::Value::as_f64)
+            .unwrap_or(0.0) as f32
+    };
+    (coord("x"), coord("y"))
+}
+
+/// Parse an optional style object from JSON arguments.
+fn parse_style(json: Option<&serde_json::Value>) -> Result<Option<Style>, ToolResponse> {
+    json.map(|v| {
+        serde_json::from_value(v.clone())
+            .map_err(|e| ToolResponse::error(format!("Invalid style: {e}")))
+    })
+    .transpose()
+}
+
+/// Parse an optional `opacity` argument, which must be a number in `0..=1`.
+#[allow(clippy::cast_possible_truncation)]
+fn parse_opacity(json: Option<&serde_json::Value>) -> Result<Option<f32>, ToolResponse> {
+    json.map(|v| match v.as_f64() {
+        Some(opacity) if (0.0..=1.0).contains(&opacity) => Ok(opacity as f32),
+        _ => Err(ToolResponse::error(format!(
+            "Invalid opacity: expected a number from 0 to 1, got {v}"
+        ))),
+    })
+    .transpose()
+}
+
+/// Parse an optional `attach_to` argument: `None` when absent, `Some(None)`
+/// for `null`, which detaches the element.
+#[allow(clippy::option_option)]
+fn parse_attachment(
+    json: Option<&serde_json::Value>,
+) -> Result<Option<Option<Attachment>>, ToolResponse> {
+    json.map(|v| {
+        serde_json::from_value(v.clone())
+            .map_err(|e| ToolResponse::error(format!("Invalid attach_to: {e}")))
+    })
+    .transpose()
+}
+
+/// Parse an optional `interactions` argument.
+fn parse_interactions(
+    json: Option<&serde_json::Value>,
+) -> Result<Option<Interactions>, ToolResponse> {
+    json.map(|v| {
+        serde_json::from_value(v.clone())
+            .map_err(|e| ToolResponse::error(format!("Invalid interactions: {e}")))
+    })
+    .transpose()
+}
+
+/// Check content rules that deserialization cannot express.
+fn validate_kind(kind: &ElementKind) -> Result<(), String> {
+    match kind {
+        ElementKind::Embed { url, .. } if !ElementKind::is_embeddable_url(url) => {
+            Err(format!("Embed URL must be an absolute http(s) URL: {url}"))
+        }
+        ElementKind::Embed { sandbox, .. } => sandbox
+            .split_whitespace()
+            .find(|token| !ElementKind::EMBED_SANDBOX_TOKENS.contains(token))
+            .map_or(Ok(()), |token| {
+                Err(format!(
+                    "Embed sandbox may only use {}, not {token}",
+                    ElementKind::EMBED_SANDBOX_TOKENS.join(", ")
+                ))
+            }),
+        ElementKind::Calendar {
+            date,
+            events,
+            selected,
+            ..
+        } => events
+            .iter()
+            .map(|event| &event.date)
+            .chain([date])
+            .chain(selected)
+            .find(|day| CalendarDate::parse(day).is_none())
+            .map_or(Ok(()), |day| {
+                Err(format!("Calendar dates must be YYYY-MM-DD: {day}"))
+            }),
+        ElementKind::Randomizer { source, .. } => source.validate().map_err(|e| e.to_string()),
+        ElementKind::Gauge { .. } | ElementKind::Kpi { .. } => {
+            dashboard::validate(kind).map_err(|e| e.to_string())
+        }
+        ElementKind::Sparkline { values, .. } => {
+            sparkline::validate(values).map_err(|e| e.to_string())
+        }
+        ElementKind::Tree { root, .. } => tree::validate(root).map_err(|e| e.to_string()),
+        ElementKind::Ink { points, width, .. } => {
+            ink::validate(points, *width).map_err(|e| e.to_string())
+        }
+        ElementKind::Measurement { .. } | ElementKind::Region { .. } => {
+            annotation::validate(kind).map_err(|e| e.to_string())
+        }
+        ElementKind::Shape { shape } => shape.validate().map_err(|e| e.to_string()),
+        ElementKind::Barcode { .. } => barcode::validate(kind).map_err(|e| e.to_string()),
+        ElementKind::Agenda { .. } => agenda::validate(kind).map_err(|e| e.to_string()),
+        ElementKind::LogView { .. } => log_view::validate(kind).map_err(|e| e.to_string()),
+        ElementKind::Shader { .. } => {
+            canvas_renderer::shader::check(kind).map_err(|e| e.to_string())
+        }
+        ElementKind::Model3D { .. } => model3d::validate(kind).map_err(|e| e.to_string()),
+        ElementKind::Chart {
+            binding: Some(binding),
+            ..
+        } => binding.validate().map_err(|e| e.to_string()),
+        ElementKind::FloorPlan {
+            plan_width,
+            plan_height,
+            paths,
+            markers,
+            ..
+        } => floor_plan::validate(*plan_width, *plan_height, paths, markers)
+            .map_err(|e| e.to_string()),
+        ElementKind::Poll { options, .. } if !(2..=MAX_POLL_OPTIONS).contains(&options.len()) => {
+            Err(format!(
+                "Polls need 2 to {MAX_POLL_OPTIONS} options, got {}",
+                options.len()
+            ))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Merge floor plan marker updates into the current markers by `id`.
+///
+/// Fields of an update replace those of the marker with the same ID, so
+/// `{"id": "A1", "occupancy": "occupied"}` changes one seat; unknown IDs
+/// add markers and `{"id": "A1", "remove": true}` removes one.
+fn merge_markers(
+    current: &mut serde_json::Value,
+    updates: &serde_json::Value,
+) -> Result<(), ToolResponse> {
+    let Some(updates) = updates.as_array() else {
+        return Err(ToolResponse::error("markers must be an array"));
+    };
+    if !current.is_array() {
+        *current = serde_json::json!([]);
+    }
+    let Some(markers) = current.as_array_mut() else {
+        return Ok(());
+    };
+    for update in updates {
+        let Some(id) = update.get("id").and_then(serde_json::Value::as_str) else {
+            return Err(ToolResponse::error("Each marker update needs an id"));
+        };
+        let index = markers.iter().position(|m| m["id"] == id);
+        let remove = update
+            .get("remove")
+            .and_then(serde_json::Value::as_bool)
+            .unwrap_or(false);
+        match (index, update.as_object()) {
+            (Some(index), _) if remove => {
+                markers.remove(index);
+            }
+            (None, _) if remove => {
+                return Err(ToolResponse::error(format!(
+                    "Floor plan has no marker {id}"
+                )));
+            }
+            (Some(index), Some(fields)) => {
+                if let Some(marker) = markers[index].as_object_mut() {
+                    marker.extend(fields.clone());
+                }
+            }
+            _ => markers.push(update.clone()),
+        }
+    }
+    Ok(())
+}
+
+/// Apply an optional `data` argument to an element kind: its fields are
+/// merged into the kind's current data and the result is re-validated.
+///
+/// The kind's type never changes. Containers are rejected because their
+/// children are managed by grouping. Floor plan `markers` are merged by ID
+/// (see [`merge_markers`]).
+fn merge_kind_data(
+    kind: &ElementKind,
+    data: Option<&serde_json::Value>,
+) -> Result<Option<ElementKind>, ToolResponse> {
+    let Some(data) = data else {
+        return Ok(None);
+    };
+    let Some(fields) = data.as_object() else {
+        return Err(ToolResponse::error("data must be an object"));
+    };
+    if matches!(
+        kind,
+        ElementKind::Group { .. } | ElementKind::OverlayLayer { .. }
+    ) {
+        return Err(ToolResponse::error(format!(
+            "data cannot be updated on {} elements",
+            kind.type_name()
+        )));
+    }
+
+    let mut json = serde_json::to_value(kind)
+        .map_err(|e| ToolResponse::error(format!("Failed to serialize element: {e}")))?;
+    match json
+        .get_mut("data")
+        .and_then(serde_json::Value::as_object_mut)
+    {
+        Some(current) => {
+            for (key, value) in fields {
+                if key == "markers" && matches!(kind, ElementKind::FloorPlan { .. }) {
+                    merge_markers(
+                        current
+                            .entry(key.clone())
+                            .or_insert(serde_json::Value::Null),
+                        value,
+                    )?;
+                } else {
+                    current.insert(key.clone(), value.clone());
+                }
+            }
+        }
+        None => json["data"] = data.clone(),
+    }
+    let mut merged: ElementKind = serde_json::from_value(json)
+        .map_err(|e| ToolResponse::error(format!("Invalid data for {}: {e}", kind.type_name())))?;
+    if let ElementKind::Tree { root, .. } = &mut merged {
+        tree::assign_missing_ids(root);
     }
+    validate_kind(&merged).map_err(ToolResponse::error)?;
+    Ok(Some(merged))
 }
 
 /// Create a new default session metadata.
@@ -88,29 +429,851 @@ fn create_session_metadata(session_id: &str, width: f32, height: f32) -> CanvasS
 }
 
 /// Create an element from render content.
-fn create_element_from_content(content: &RenderContent) -> Element {
+///
+/// Notes and callouts without an explicit author are attributed to `agent_id`.
+#[allow(clippy::too_many_lines)]
+fn create_element_from_content(content: &RenderContent, agent_id: Option<String>) -> Element {
     match content {
         RenderContent::Chart {
-            chart_type, data, ..
+            chart_type,
+            data,
+            binding,
+            ..
         } => Element::new(ElementKind::Chart {
             chart_type: chart_type.clone(),
             data: data.clone(),
+            binding: binding.clone(),
         }),
         RenderContent::Image { src, .. } => Element::new(ElementKind::Image {
             src: src.clone(),
             format: detect_image_format(src),
+            annotations: Vec::new(),
+            extracted_text: None,
         }),
         RenderContent::Text { content, font_size } => Element::new(ElementKind::Text {
             content: content.clone(),
             font_size: font_size.unwrap_or(16.0),
-            color: "#000000".to_string(),
+            color: ElementKind::TEXT_COLOR.into(),
+        }),
+        RenderContent::Math {
+            tex,
+            font_size,
+            color,
+        } => Element::new(ElementKind::Math {
+            tex: tex.clone(),
+            font_size: font_size.unwrap_or(24.0),
+            color: color
+                .clone()
+                .unwrap_or_else(|| ElementKind::TEXT_COLOR.into()),
+        }),
+        RenderContent::Code { source, language } => Element::new(ElementKind::code(
+            language.clone().unwrap_or_default(),
+            source.clone(),
+        )),
+        RenderContent::LogView {
+            title,
+            text,
+            max_lines,
+            follow,
+        } => {
+            let max_lines = max_lines.unwrap_or(DEFAULT_LOG_LINES);
+            let mut lines = Vec::new();
+            log_view::push_lines(
+                &mut lines,
+                &log_view::split_lines(text.as_deref().unwrap_or_default()),
+                max_lines,
+            );
+            Element::new(ElementKind::LogView {
+                title: title.clone().unwrap_or_default(),
+                lines,
+                max_lines,
+                follow: follow.unwrap_or(true),
+            })
+        }
+        RenderContent::Shader { wgsl, uniforms } => Element::new(ElementKind::Shader {
+            wgsl: wgsl.clone(),
+            uniforms: uniforms.clone().unwrap_or_default(),
         }),
         RenderContent::Model3D { src, rotation } => Element::new(ElementKind::Model3D {
             src: src.clone(),
             rotation: rotation.unwrap_or([0.0, 0.0, 0.0]),
             scale: 1.0,
+            pose: None,
+            camera: None,
+            lighting: None,
+        }),
+        RenderContent::StickyNote {
+            content,
+            color,
+            author,
+        } => Element::new(ElementKind::StickyNote {
+            content: content.clone(),
+            color: color
+                .clone()
+                .unwrap_or_else(|| ElementKind::NOTE_COLOR.into()),
+            author: author.clone().or(agent_id),
+        }),
+        RenderContent::Callout {
+            content,
+            color,
+            author,
+            target,
+        } => Element::new(ElementKind::Callout {
+            content: content.clone(),
+            color: color
+                .clone()
+                .unwrap_or_else(|| ElementKind::CALLOUT_COLOR.into()),
+            author: author.clone().or(agent_id),
+            target: *target,
         }),
+        RenderContent::Audio {
+            stream_id,
+            waveform,
+        } => Element::new(ElementKind::Audio {
+            stream_id: stream_id.clone(),
+            waveform: waveform
+                .as_deref()
+                .map(|samples| downsample_waveform(samples, MAX_WAVEFORM_POINTS))
+                .unwrap_or_default(),
+            state: PlaybackState::Paused,
+        }),
+        RenderContent::Calendar {
+            view,
+            date,
+            events,
+            selected,
+        } => Element::new(ElementKind::Calendar {
+            view: *view,
+            date: date.trim().to_string(),
+            events: events.clone(),
+            selected: selected.clone(),
+        }),
+        RenderContent::Agenda {
+            title,
+            items,
+            starts_in_secs,
+        } => Element::new(ElementKind::Agenda {
+            title: title.clone(),
+            items: items.clone(),
+            current: 0,
+            started_at_ms: starts_in_secs
+                .map(|secs| now_millis().saturating_add(secs.saturating_mul(1000))),
+        }),
+        RenderContent::Randomizer { source } => Element::new(ElementKind::Randomizer {
+            source: source.clone(),
+            outcome: None,
+        }),
+        RenderContent::FloorPlan {
+            background,
+            plan_width,
+            plan_height,
+            paths,
+            markers,
+            locked,
+        } => Element::new(ElementKind::FloorPlan {
+            background: background.clone(),
+            plan_width: *plan_width,
+            plan_height: *plan_height,
+            paths: paths.clone(),
+            markers: markers.clone(),
+            locked: *locked,
+        }),
+        RenderContent::Gauge {
+            value,
+            min,
+            max,
+            shape,
+            label,
+            thresholds,
+            format,
+        } => Element::new(ElementKind::Gauge {
+            value: *value,
+            min: *min,
+            max: *max,
+            shape: *shape,
+            label: label.clone(),
+            thresholds: thresholds.clone(),
+            format: format.clone(),
+        }),
+        RenderContent::Kpi {
+            label,
+            value,
+            previous,
+            sparkline,
+            sparkline_kind,
+            thresholds,
+            format,
+            higher_is_better,
+        } => Element::new(ElementKind::Kpi {
+            label: label.clone(),
+            value: *value,
+            previous: *previous,
+            sparkline: sparkline.clone(),
+            sparkline_kind: *sparkline_kind,
+            thresholds: thresholds.clone(),
+            format: format.clone(),
+            higher_is_better: *higher_is_better,
+        }),
+        RenderContent::Sparkline {
+            values,
+            kind,
+            color,
+        } => Element::new(ElementKind::Sparkline {
+            values: values.clone(),
+            kind: *kind,
+            color: color.clone(),
+        }),
+        RenderContent::Tree { root, direction } => {
+            let mut root = root.clone();
+            tree::assign_missing_ids(&mut root);
+            Element::new(ElementKind::Tree {
+                root,
+                direction: *direction,
+            })
+        }
+        RenderContent::Embed { url, sandbox } => Element::new(ElementKind::Embed {
+            url: url.trim().to_string(),
+            sandbox: sandbox
+                .clone()
+                .unwrap_or_else(ElementKind::default_embed_sandbox),
+        }),
+    }
+}
+
+/// Transform of a `canvas_render` element placed at `pos`.
+fn render_transform(pos: &Position) -> Transform {
+    Transform {
+        x: pos.x,
+        y: pos.y,
+        width: pos.width.unwrap_or(200.0),
+        height: pos.height.unwrap_or(150.0),
+        rotation: 0.0,
+        z_index: 0,
+        auto_size: pos.width.is_none() && pos.height.is_none(),
+    }
+}
+
+/// Transform a `canvas_add_element` or `canvas_render` call will create,
+/// measured from its content when auto-sized.
+fn measured_transform(arguments: &serde_json::Value) -> Transform {
+    if arguments.get("content").is_some() {
+        return serde_json::from_value::<RenderParams>(arguments.clone()).map_or_else(
+            |_| Transform::default(),
+            |params| render_element(&params, None).transform,
+        );
+    }
+    let transform = parse_transform(arguments.get("transform"));
+    let Some(kind) = arguments
+        .get("kind")
+        .and_then(|k| serde_json::from_value::<ElementKind>(k.clone()).ok())
+    else {
+        return transform;
+    };
+    let mut element = Element::new(kind).with_transform(transform);
+    element.apply_auto_size(&MeasureContext::default());
+    element.transform
+}
+
+/// Element for a `canvas_render` call: its content at `position`, or at the
+/// origin when none is given, auto-sized unless the position has a size.
+fn render_element(params: &RenderParams, agent_id: Option<String>) -> Element {
+    let transform = params.position.as_ref().map_or_else(
+        || Transform {
+            auto_size: true,
+            ..Transform::default()
+        },
+        render_transform,
+    );
+    let mut element =
+        create_element_from_content(&params.content, agent_id).with_transform(transform);
+    element.apply_auto_size(&MeasureContext::default());
+    element
+}
+
+/// The schedule change a `canvas_agenda` call asks for, or `None` to only
+/// read the status.
+fn agenda_command(arguments: &serde_json::Value) -> Result<Option<AgendaCommand>, ToolResponse> {
+    let Some(action) = arguments.get("action").and_then(|v| v.as_str()) else {
+        return Err(ToolResponse::error("Missing required field: action"));
+    };
+    let command = match action {
+        "start" => {
+            let secs = arguments
+                .get("in_secs")
+                .and_then(serde_json::Value::as_f64)
+                .unwrap_or(0.0);
+            if !secs.is_finite() || secs < 0.0 {
+                return Err(ToolResponse::error("in_secs must be 0 or more"));
+            }
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            AgendaCommand::Start {
+                delay_ms: (secs * 1000.0).round() as u64,
+            }
+        }
+        "advance" => AgendaCommand::Advance,
+        "back" => AgendaCommand::Back,
+        "goto" => {
+            let Some(item) = arguments
+                .get("item")
+                .and_then(serde_json::Value::as_u64)
+                .and_then(|item| usize::try_from(item).ok())
+            else {
+                return Err(ToolResponse::error("goto needs an item index"));
+            };
+            AgendaCommand::GoTo(item)
+        }
+        "reset" => AgendaCommand::Reset,
+        "status" => return Ok(None),
+        other => {
+            return Err(ToolResponse::error(format!(
+                "Unknown agenda action: {other}"
+            )))
+        }
+    };
+    Ok(Some(command))
+}
+
+/// Transform of a `canvas_poll` element: its `position`, or a card tall
+/// enough for its options at the origin.
+#[allow(clippy::cast_precision_loss)]
+fn poll_transform(arguments: &serde_json::Value) -> Transform {
+    let option_count = arguments
+        .get("options")
+        .and_then(serde_json::Value::as_array)
+        .map_or(0, Vec::len);
+    let position = arguments
+        .get("position")
+        .and_then(|p| serde_json::from_value::<Position>(p.clone()).ok())
+        .unwrap_or(Position {
+            x: 0.0,
+            y: 0.0,
+            width: None,
+            height: None,
+        });
+    Transform {
+        width: position.width.unwrap_or(320.0),
+        height: position.height.unwrap_or(48.0 + 40.0 * option_count as f32),
+        ..render_transform(&position)
+    }
+}
+
+/// Element for a `canvas_tree` call: the tree from its nested `root`, sized
+/// to fit at its `position` unless a size is given.
+fn tree_element(arguments: &serde_json::Value) -> Result<Element, ToolResponse> {
+    let Some(root) = arguments.get("root") else {
+        return Err(ToolResponse::error("Missing required field: root"));
+    };
+    let mut root: TreeNode = serde_json::from_value(root.clone())
+        .map_err(|e| ToolResponse::error(format!("Invalid root: {e}")))?;
+    let direction = arguments
+        .get("direction")
+        .map(|v| serde_json::from_value::<TreeDirection>(v.clone()))
+        .transpose()
+        .map_err(|e| ToolResponse::error(format!("Invalid direction: {e}")))?
+        .unwrap_or_default();
+    let position = arguments
+        .get("position")
+        .and_then(|p| serde_json::from_value::<Position>(p.clone()).ok())
+        .unwrap_or(Position {
+            x: 0.0,
+            y: 0.0,
+            width: None,
+            height: None,
+        });
+
+    tree::assign_missing_ids(&mut root);
+    let mut element = Element::new(ElementKind::Tree { root, direction })
+        .with_transform(render_transform(&position));
+    element.apply_auto_size(&MeasureContext::default());
+    Ok(element)
+}
+
+/// Element for a `canvas_annotate` call: a measurement line, arrow, region
+/// highlight, ink stroke or text note in canvas coordinates, or relative to
+/// the top-left corner of `element_id` if given, stacked above the scene's
+/// other elements.
+#[allow(clippy::too_many_lines)]
+fn annotation_element(
+    arguments: &serde_json::Value,
+    scene: Option<&Scene>,
+) -> Result<Element, ToolResponse> {
+    let point = |field: &str| -> Result<[f32; 2], ToolResponse> {
+        arguments
+            .get(field)
+            .and_then(|v| serde_json::from_value::<[f32; 2]>(v.clone()).ok())
+            .ok_or_else(|| ToolResponse::error(format!("{field} must be an [x, y] pair")))
+    };
+    let number = |field: &str| arguments.get(field).and_then(serde_json::Value::as_f64);
+    let text = |field: &str| {
+        arguments
+            .get(field)
+            .and_then(|v| v.as_str())
+            .map(str::to_string)
+    };
+    let target = match arguments.get("element_id") {
+        Some(_) => {
+            let id = extract_element_id(arguments)?;
+            Some(
+                scene
+                    .and_then(|scene| scene.get_element(id))
+                    .ok_or_else(|| ToolResponse::error(format!("Element not found: {id}")))?,
+            )
+        }
+        None => None,
+    };
+    let (dx, dy) = target.map_or((0.0, 0.0), |e| (e.transform.x, e.transform.y));
+    let color = arguments
+        .get("color")
+        .map(|v| serde_json::from_value::<ThemeColor>(v.clone()))
+        .transpose()
+        .map_err(|e| ToolResponse::error(format!("Invalid color: {e}")))?
+        .unwrap_or_else(ElementKind::default_annotation_color);
+
+    #[allow(clippy::cast_possible_truncation)]
+    let mut element = match arguments.get("type").and_then(|v| v.as_str()) {
+        Some(mark @ ("measurement" | "arrow")) => {
+            let [sx, sy] = point("start")?;
+            let [ex, ey] = point("end")?;
+            let kind = if mark == "arrow" {
+                MeasurementKind::Arrow
+            } else {
+                arguments
+                    .get("kind")
+                    .map(|v| serde_json::from_value::<MeasurementKind>(v.clone()))
+                    .transpose()
+                    .map_err(|e| ToolResponse::error(format!("Invalid kind: {e}")))?
+                    .unwrap_or_default()
+            };
+            let coordinates = scene
+                .map(Scene::coordinate_system)
+                .cloned()
+                .unwrap_or_default();
+            let mut element = annotation::measurement_in(
+                [sx + dx, sy + dy],
+                [ex + dx, ey + dy],
+                kind,
+                &coordinates,
+            );
+            if let ElementKind::Measurement {
+                label,
+                unit,
+                scale,
+                color: line_color,
+                ..
+            } = &mut element.kind
+            {
+                *label = text("label");
+                if let Some(value) = text("unit") {
+                    *unit = value;
+                }
+                if let Some(value) = number("scale") {
+                    *scale = value as f32;
+                }
+                *line_color = color;
+            }
+            element
+        }
+        Some("region") => {
+            let (Some(x), Some(y), Some(width), Some(height)) =
+                (number("x"), number("y"), number("width"), number("height"))
+            else {
+                return Err(ToolResponse::error("Regions need x, y, width and height"));
+            };
+            Element::new(ElementKind::Region {
+                caption: text("caption"),
+                color,
+            })
+            .with_transform(Transform {
+                x: x as f32 + dx,
+                y: y as f32 + dy,
+                width: width as f32,
+                height: height as f32,
+                rotation: 0.0,
+                z_index: 0,
+                auto_size: false,
+            })
+        }
+        Some("ink") => {
+            let points: Vec<[f32; 2]> = arguments
+                .get("points")
+                .and_then(|v| serde_json::from_value(v.clone()).ok())
+                .ok_or_else(|| ToolResponse::error("points must be a list of [x, y] pairs"))?;
+            let points: Vec<[f32; 2]> = points.iter().map(|[x, y]| [x + dx, y + dy]).collect();
+            let width = number("width").map_or(4.0, |w| w as f32);
+            ink::stroke(&points, color, width)
+        }
+        Some("text") => {
+            let (Some(x), Some(y), Some(content)) = (number("x"), number("y"), text("content"))
+            else {
+                return Err(ToolResponse::error("Text needs x, y and content"));
+            };
+            let font_size = number("font_size").map_or(16.0, |size| size as f32);
+            Element::new(ElementKind::Text {
+                content,
+                font_size,
+                color,
+            })
+            .with_transform(Transform {
+                x: x as f32 + dx,
+                y: y as f32 + dy,
+                width: number("width").map_or(200.0, |w| w as f32),
+                height: font_size * 1.5,
+                rotation: 0.0,
+                z_index: 0,
+                auto_size: false,
+            })
+        }
+        _ => {
+            return Err(ToolResponse::error(
+                "type must be \"measurement\", \"arrow\", \"region\", \"ink\" or \"text\"",
+            ))
+        }
+    };
+    element.transform.z_index = scene
+        .and_then(|scene| scene.elements().map(|e| e.transform.z_index).max())
+        .map_or(0, |z| z.saturating_add(1));
+    Ok(element)
+}
+
+/// Element for a `canvas_barcode` call: a QR code or Code 128 barcode encoded
+/// from `payload`, or from `wifi` network credentials.
+fn barcode_element(arguments: &serde_json::Value) -> Result<Element, ToolResponse> {
+    fn parse<T: serde::de::DeserializeOwned>(
+        arguments: &serde_json::Value,
+        field: &str,
+    ) -> Result<Option<T>, ToolResponse> {
+        arguments
+            .get(field)
+            .map(|v| serde_json::from_value(v.clone()))
+            .transpose()
+            .map_err(|e| ToolResponse::error(format!("Invalid {field}: {e}")))
+    }
+    let symbology: Symbology = parse(arguments, "symbology")?.unwrap_or_default();
+    let error_correction: ErrorCorrection =
+        parse(arguments, "error_correction")?.unwrap_or_default();
+    let color: Option<ThemeColor> = parse(arguments, "color")?;
+    let payload = match (
+        arguments.get("payload").and_then(|v| v.as_str()),
+        arguments.get("wifi"),
+    ) {
+        (Some(payload), None) => payload.to_string(),
+        (None, Some(wifi)) => {
+            let ssid = wifi
+                .get("ssid")
+                .and_then(|v| v.as_str())
+                .ok_or_else(|| ToolResponse::error("wifi.ssid is required"))?;
+            let password = wifi.get("password").and_then(|v| v.as_str());
+            let hidden = wifi
+                .get("hidden")
+                .and_then(serde_json::Value::as_bool)
+                .unwrap_or(false);
+            barcode::wifi_payload(ssid, password, hidden)
+        }
+        _ => return Err(ToolResponse::error("Give exactly one of payload or wifi")),
+    };
+    let modules = barcode::encode(symbology, &payload, error_correction)
+        .map_err(|e| ToolResponse::error(e.to_string()))?;
+    let position = arguments
+        .get("position")
+        .and_then(|p| serde_json::from_value::<Position>(p.clone()).ok())
+        .unwrap_or(Position {
+            x: 0.0,
+            y: 0.0,
+            width: None,
+            height: None,
+        });
+
+    let mut element = Element::new(ElementKind::Barcode {
+        symbology,
+        payload,
+        modules,
+        color: color.unwrap_or_else(|| ElementKind::BARCODE_COLOR.into()),
+    })
+    .with_transform(render_transform(&position));
+    element.apply_auto_size(&MeasureContext::default());
+    Ok(element)
+}
+
+/// The element a `canvas_tree`, `canvas_barcode` or `canvas_annotate` call
+/// would add, if its arguments are valid.
+fn built_element(
+    name: &str,
+    arguments: &serde_json::Value,
+    scene: Option<&Scene>,
+) -> Option<Element> {
+    match name {
+        "canvas_tree" => tree_element(arguments).ok(),
+        "canvas_barcode" => barcode_element(arguments).ok(),
+        "canvas_annotate" => annotation_element(arguments, scene).ok(),
+        _ => None,
+    }
+}
+
+/// Wrap a tool result as an MCP `tools/call` response.
+fn tool_response_to_rpc(id: serde_json::Value, result: ToolResponse) -> JsonRpcResponse {
+    if result.success {
+        JsonRpcResponse::success(
+            id,
+            serde_json::json!({
+                "content": [{
+                    "type": "text",
+                    "text": serde_json::to_string_pretty(&result.data).unwrap_or_default()
+                }]
+            }),
+        )
+    } else {
+        JsonRpcResponse::error(id, -32000, result.error.unwrap_or_default())
+    }
+}
+
+/// Render a PNG thumbnail of a scene for a dry-run preview.
+#[cfg(feature = "preview")]
+fn render_preview(scene: &canvas_core::Scene) -> Result<serde_json::Value, String> {
+    use base64::Engine;
+    use canvas_renderer::export::{ExportConfig, SceneExporter};
+
+    let exporter = SceneExporter::new(ExportConfig {
+        scale: PREVIEW_WIDTH / scene.viewport_width.max(1.0),
+        ..ExportConfig::default()
+    });
+    let png = exporter
+        .render_to_png(scene)
+        .map_err(|e| format!("Preview failed: {e}"))?;
+    Ok(serde_json::json!({
+        "mime_type": "image/png",
+        "data": base64::engine::general_purpose::STANDARD.encode(png),
+    }))
+}
+
+/// Previews need the renderer's export pipeline (the `preview` feature).
+#[cfg(not(feature = "preview"))]
+fn render_preview(_scene: &canvas_core::Scene) -> Result<serde_json::Value, String> {
+    Err("Preview rendering is not enabled in this build".to_string())
+}
+
+/// Render the PNG thumbnail resource of a session.
+#[cfg(feature = "preview")]
+fn render_thumbnail(
+    scene: &canvas_core::Scene,
+    redact: Option<canvas_core::Redaction>,
+) -> Result<crate::ResourceContent, String> {
+    use base64::Engine;
+    use canvas_renderer::{BackendType, Renderer, RendererConfig};
+
+    let png = Renderer::new(RendererConfig {
+        preferred_backend: BackendType::Software,
+        ..RendererConfig::default()
+    })
+    .and_then(|renderer| renderer.render_thumbnail(scene, THUMBNAIL_SIZE, redact))
+    .and_then(|image| image.encode_png())
+    .map_err(|e| format!("Thumbnail failed: {e}"))?;
+    Ok(crate::ResourceContent::Binary {
+        data: base64::engine::general_purpose::STANDARD.encode(png),
+        mime_type: "image/png".to_string(),
+    })
+}
+
+/// Thumbnails need the renderer's export pipeline (the `preview` feature).
+#[cfg(not(feature = "preview"))]
+fn render_thumbnail(
+    _scene: &canvas_core::Scene,
+    _redact: Option<canvas_core::Redaction>,
+) -> Result<crate::ResourceContent, String> {
+    Err("Thumbnail rendering is not enabled in this build".to_string())
+}
+
+/// Render one element for `canvas_export`, returning base64 (or the SVG
+/// markup for SVG exports).
+#[cfg(feature = "preview")]
+fn render_element_export(
+    scene: &canvas_core::Scene,
+    id: ElementId,
+    params: &ExportParams,
+) -> Result<String, String> {
+    use base64::Engine;
+    use canvas_renderer::export::{self, ExportConfig, SceneExporter};
+
+    let format = match params.format {
+        crate::tools::ExportFormat::Png => export::ExportFormat::Png,
+        crate::tools::ExportFormat::Jpeg => export::ExportFormat::Jpeg,
+        crate::tools::ExportFormat::Svg => export::ExportFormat::Svg,
+        crate::tools::ExportFormat::Pdf => export::ExportFormat::Pdf,
+        crate::tools::ExportFormat::WebP => {
+            return Err("WebP is not supported for element export".to_string())
+        }
+    };
+    let exporter = SceneExporter::new(ExportConfig {
+        width: params.width,
+        height: params.height,
+        jpeg_quality: params.quality.clamp(1, 100),
+        scale: params.scale.unwrap_or(1.0),
+        redaction: params.redact,
+        ..ExportConfig::default()
+    });
+    let data = if params.flatten {
+        exporter.flatten_image(scene, id, format)
+    } else {
+        exporter.export_element(scene, id, format)
+    }
+    .map_err(|e| e.to_string())?;
+    match format {
+        export::ExportFormat::Svg => String::from_utf8(data).map_err(|e| e.to_string()),
+        _ => Ok(base64::engine::general_purpose::STANDARD.encode(data)),
+    }
+}
+
+/// Render a whole session for `canvas_export`, returning base64 (or the SVG
+/// markup for SVG exports). SVG goes through the vector backend, so charts
+/// stay vectors and local images are embedded.
+#[cfg(feature = "preview")]
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn render_session_export(
+    scene: &canvas_core::Scene,
+    params: &ExportParams,
+) -> Result<String, String> {
+    use base64::Engine;
+    use canvas_renderer::export::{self, ExportConfig, SceneExporter};
+    use canvas_renderer::SvgBackend;
+
+    let scale = params.scale.unwrap_or(1.0);
+    let format = match params.format {
+        crate::tools::ExportFormat::Png => export::ExportFormat::Png,
+        crate::tools::ExportFormat::Jpeg => export::ExportFormat::Jpeg,
+        crate::tools::ExportFormat::Pdf => export::ExportFormat::Pdf,
+        crate::tools::ExportFormat::Svg => {
+            let width = params
+                .width
+                .unwrap_or_else(|| scene.viewport_width.max(1.0) as u32);
+            let height = params
+                .height
+                .unwrap_or_else(|| scene.viewport_height.max(1.0) as u32);
+            return Ok(SvgBackend::new(width, height)
+                .with_scale(scale)
+                .with_redaction(params.redact)
+                .render_to_string(scene));
+        }
+        crate::tools::ExportFormat::WebP => {
+            return Err("WebP is not supported for export".to_string())
+        }
+    };
+    let data = SceneExporter::new(ExportConfig {
+        width: params.width,
+        height: params.height,
+        jpeg_quality: params.quality.clamp(1, 100),
+        scale,
+        redaction: params.redact,
+        ..ExportConfig::default()
+    })
+    .export(scene, format)
+    .map_err(|e| e.to_string())?;
+    Ok(base64::engine::general_purpose::STANDARD.encode(data))
+}
+
+/// Render a session as a paginated PDF for `canvas_export`, returning the
+/// base64 data and page count.
+#[cfg(feature = "preview")]
+fn render_print_export(
+    scene: &canvas_core::Scene,
+    session_id: &str,
+    print: &PrintOptions,
+    redact: Option<canvas_core::Redaction>,
+) -> Result<(String, usize), String> {
+    use base64::Engine;
+    use canvas_renderer::export::ExportConfig;
+    use canvas_renderer::print::{Orientation, PaperSize, PrintConfig, PrintLayout};
+    use canvas_renderer::SceneExporter;
+
+    let defaults = PrintConfig::default();
+    let config = PrintConfig {
+        paper: match print.paper {
+            crate::tools::PaperSize::A4 => PaperSize::A4,
+            crate::tools::PaperSize::Letter => PaperSize::Letter,
+            crate::tools::PaperSize::A3 => PaperSize::A3,
+            crate::tools::PaperSize::A5 => PaperSize::A5,
+            crate::tools::PaperSize::Legal => PaperSize::Legal,
+            crate::tools::PaperSize::Tabloid => PaperSize::Tabloid,
+        },
+        orientation: if print.landscape {
+            Orientation::Landscape
+        } else {
+            Orientation::Portrait
+        },
+        margin_mm: print.margin_mm.unwrap_or(defaults.margin_mm),
+        scale: print.scale,
+        title: Some(
+            print
+                .title
+                .clone()
+                .unwrap_or_else(|| session_id.to_string()),
+        ),
+        dpi: print.dpi.unwrap_or(defaults.dpi),
+        ..defaults
+    };
+    let pages = PrintLayout::compute(scene, &config)
+        .map_err(|e| e.to_string())?
+        .pages
+        .len();
+    let data = SceneExporter::new(ExportConfig {
+        redaction: redact,
+        ..ExportConfig::default()
+    })
+    .export_print(scene, &config)
+    .map_err(|e| e.to_string())?;
+    Ok((
+        base64::engine::general_purpose::STANDARD.encode(data),
+        pages,
+    ))
+}
+
+/// Session export needs the renderer's export pipeline (the `preview` feature).
+#[cfg(not(feature = "preview"))]
+fn render_session_export(
+    _scene: &canvas_core::Scene,
+    _params: &ExportParams,
+) -> Result<String, String> {
+    Err("Export is not enabled in this build".to_string())
+}
+
+/// Print export needs the renderer's export pipeline (the `preview` feature).
+#[cfg(not(feature = "preview"))]
+fn render_print_export(
+    _scene: &canvas_core::Scene,
+    _session_id: &str,
+    _print: &PrintOptions,
+    _redact: Option<canvas_core::Redaction>,
+) -> Result<(String, usize), String> {
+    Err("Print export is not enabled in this build".to_string())
+}
+
+/// Element export needs the renderer's export pipeline (the `preview` feature).
+#[cfg(not(feature = "preview"))]
+fn render_element_export(
+    _scene: &canvas_core::Scene,
+    _id: ElementId,
+    _params: &ExportParams,
+) -> Result<String, String> {
+    Err("Element export is not enabled in this build".to_string())
+}
+
+/// JSON-RPC error for a tool call rejected by the agent's scope.
+fn scope_violation_response(
+    id: serde_json::Value,
+    agent_id: Option<&str>,
+    violation: &ScopeViolation,
+) -> JsonRpcResponse {
+    let mut response = JsonRpcResponse::error(
+        id,
+        SCOPE_VIOLATION_CODE,
+        format!("Scope violation: {violation}"),
+    );
+    if let Some(error) = response.error.as_mut() {
+        let mut data = serde_json::to_value(violation).unwrap_or_default();
+        if let (Some(fields), Some(agent_id)) = (data.as_object_mut(), agent_id) {
+            fields.insert("agent_id".to_string(), serde_json::json!(agent_id));
+        }
+        error.data = Some(data);
     }
+    response
 }
 
 /// Update session metadata after a scene change.
@@ -140,6 +1303,13 @@ fn apply_transform_updates(transform: &mut Transform, json: &serde_json::Value)
     if let Some(z_index) = json.get("z_index").and_then(serde_json::Value::as_i64) {
         transform.z_index = z_index as i32;
     }
+    // An explicit size stops the element following its content
+    if json.get("width").is_some() || json.get("height").is_some() {
+        transform.auto_size = false;
+    }
+    if let Some(auto_size) = json.get("auto_size").and_then(serde_json::Value::as_bool) {
+        transform.auto_size = auto_size;
+    }
 }
 
 // ============================================================================
@@ -250,7 +1420,23 @@ pub struct Resource {
 // ============================================================================
 
 /// Callback type for scene change notifications.
-pub type OnChangeCallback = Box<dyn Fn(&str, &canvas_core::Scene) + Send + Sync>;
+pub type OnChangeCallback = Arc<dyn Fn(&str, &canvas_core::Scene) + Send + Sync>;
+
+/// Tool calls staged against a copy of a session, awaiting commit.
+struct Transaction {
+    /// Session the transaction applies to.
+    session_id: String,
+    /// Agent that began the transaction, if it identified itself.
+    agent_id: Option<String>,
+    /// Scratch server holding the staged scene.
+    scratch: CanvasMcpServer,
+    /// The session when the transaction began.
+    base: SceneDocument,
+    /// Names of the staged tool calls, in order.
+    staged: RwLock<Vec<String>>,
+    /// When the transaction began.
+    started: Instant,
+}
 
 /// MCP server for Saorsa Canvas.
 ///
@@ -263,6 +1449,24 @@ pub struct CanvasMcpServer {
     session_metadata: Arc<RwLock<HashMap<String, CanvasSession>>>,
     /// Change notification callback.
     on_change: Option<OnChangeCallback>,
+    /// Tool scopes by agent ID.
+    agent_scopes: Arc<RwLock<HashMap<String, AgentScope>>>,
+    /// Scope for agents without an entry in `agent_scopes`.
+    default_scope: Arc<RwLock<Option<AgentScope>>>,
+    /// Creating agent of each element, by session.
+    element_owners: Arc<RwLock<HashMap<String, HashMap<ElementId, String>>>>,
+    /// Saved element templates, shared by every session.
+    templates: Arc<RwLock<TemplateLibrary>>,
+    /// Open transactions by ID.
+    transactions: Arc<RwLock<HashMap<String, Arc<Transaction>>>>,
+    /// Reject tools that change scenes or templates.
+    read_only: bool,
+    /// Reads text out of images for `canvas_ocr`.
+    ocr_engine: Option<Arc<dyn OcrEngine>>,
+    /// Keeps archives for `canvas_archive` and `canvas_restore`.
+    archive_store: Option<Arc<dyn ArchiveStore>>,
+    /// Archives made of each session, newest last.
+    archive_history: Arc<RwLock<HashMap<String, Vec<ArchiveEntry>>>>,
 }
 
 impl CanvasMcpServer {
@@ -273,15 +1477,92 @@ impl CanvasMcpServer {
             store,
             session_metadata: Arc::new(RwLock::new(HashMap::new())),
             on_change: None,
+            agent_scopes: Arc::new(RwLock::new(HashMap::new())),
+            default_scope: Arc::new(RwLock::new(None)),
+            element_owners: Arc::new(RwLock::new(HashMap::new())),
+            templates: Arc::new(RwLock::new(TemplateLibrary::new())),
+            transactions: Arc::new(RwLock::new(HashMap::new())),
+            read_only: false,
+            ocr_engine: None,
+            archive_store: None,
+            archive_history: Arc::new(RwLock::new(HashMap::new())),
         }
     }
 
+    /// Restrict the tool calls made with the given `agent_id`.
+    pub async fn set_agent_scope(&self, agent_id: impl Into<String>, scope: AgentScope) {
+        self.agent_scopes
+            .write()
+            .await
+            .insert(agent_id.into(), scope);
+    }
+
+    /// Remove an agent's scope, returning it to the default scope.
+    pub async fn remove_agent_scope(&self, agent_id: &str) -> Option<AgentScope> {
+        self.agent_scopes.write().await.remove(agent_id)
+    }
+
+    /// Set the scope for calls from agents without their own scope,
+    /// including calls with no `agent_id`.
+    pub async fn set_default_scope(&self, scope: Option<AgentScope>) {
+        *self.default_scope.write().await = scope;
+    }
+
+    /// The agent that created an element through this server, if known.
+    pub async fn element_owner(&self, session_id: &str, element_id: ElementId) -> Option<String> {
+        self.element_owners
+            .read()
+            .await
+            .get(session_id)
+            .and_then(|owners| owners.get(&element_id))
+            .cloned()
+    }
+
+    /// Save a template, replacing any template with the same name.
+    pub async fn save_template(&self, template: Template) {
+        self.templates.write().await.insert(template);
+    }
+
+    /// Look up a saved template by name.
+    pub async fn template(&self, name: &str) -> Option<Template> {
+        self.templates.read().await.get(name).cloned()
+    }
+
+    /// Reject tools that change scenes or templates, e.g. on a read replica.
+    ///
+    /// Dry runs are still allowed.
+    pub fn set_read_only(&mut self, read_only: bool) {
+        self.read_only = read_only;
+    }
+
+    /// Check whether a request calls a tool that changes scenes or
+    /// templates, for callers that only let some users write.
+    #[must_use]
+    pub fn is_write(request: &JsonRpcRequest) -> bool {
+        let params = &request.params;
+        let name = params.get("name").and_then(|v| v.as_str()).unwrap_or("");
+        let arguments = params.get("arguments").cloned().unwrap_or_default();
+        request.method == "tools/call" && writes(name, &arguments)
+    }
+
+    /// Set the engine `canvas_ocr` reads image text with; without one the
+    /// tool reports an error.
+    pub fn set_ocr_engine(&mut self, engine: Arc<dyn OcrEngine>) {
+        self.ocr_engine = Some(engine);
+    }
+
+    /// Set the store `canvas_archive` and `canvas_restore` use; without one
+    /// both tools report an error.
+    pub fn set_archive_store(&mut self, store: Arc<dyn ArchiveStore>) {
+        self.archive_store = Some(store);
+    }
+
     /// Set the change notification callback.
     pub fn set_on_change<F>(&mut self, callback: F)
     where
         F: Fn(&str, &canvas_core::Scene) + Send + Sync + 'static,
     {
-        self.on_change = Some(Box::new(callback));
+        self.on_change = Some(Arc::new(callback));
     }
 
     /// Import a canonical scene document without triggering callbacks.
@@ -322,8 +1603,8 @@ impl CanvasMcpServer {
             "initialize" => self.handle_initialize(request.id).await,
             "tools/list" => self.handle_tools_list(request.id),
             "tools/call" => self.handle_tools_call(request.id, request.params).await,
-            "resources/list" => self.handle_resources_list(request.id),
-            "resources/read" => self.handle_resources_read(request.id, request.params),
+            "resources/list" => self.handle_resources_list(request.id).await,
+            "resources/read" => self.handle_resources_read(request.id, request.params).await,
 
             // Unknown method
             _ => JsonRpcResponse::error(
@@ -388,58 +1669,527 @@ impl CanvasMcpServer {
             .get("name")
             .and_then(|v| v.as_str())
             .unwrap_or_default();
-        let arguments = params.get("arguments").cloned().unwrap_or_default();
-
-        let result = match name {
-            "canvas_render" => self.call_canvas_render(arguments).await,
-            "canvas_render_a2ui" => self.call_canvas_render_a2ui(arguments).await,
-            "canvas_interact" => self.call_canvas_interact(arguments),
-            "canvas_export" => self.call_canvas_export(arguments),
-            "canvas_clear" => self.call_canvas_clear(arguments).await,
-            "canvas_add_element" => self.call_canvas_add_element(arguments).await,
-            "canvas_remove_element" => self.call_canvas_remove_element(arguments).await,
-            "canvas_update_element" => self.call_canvas_update_element(arguments).await,
-            "canvas_get_scene" => self.call_canvas_get_scene(arguments),
-            _ => ToolResponse::error(format!("Unknown tool: {name}")),
-        };
+        let mut arguments = params.get("arguments").cloned().unwrap_or_default();
+        let agent_id = arguments
+            .get("agent_id")
+            .and_then(|v| v.as_str())
+            .map(str::to_string);
 
-        if result.success {
-            JsonRpcResponse::success(
+        let scope = self.scope_for(agent_id.as_deref()).await;
+        if let Err(violation) = scope.as_ref().map_or(Ok(()), |s| s.check_tool(name)) {
+            return scope_violation_response(id, agent_id.as_deref(), &violation);
+        }
+        if self.read_only && writes(name, &arguments) {
+            return tool_response_to_rpc(
                 id,
-                serde_json::json!({
-                    "content": [{
-                        "type": "text",
-                        "text": serde_json::to_string_pretty(&result.data).unwrap_or_default()
-                    }]
-                }),
-            )
-        } else {
-            JsonRpcResponse::error(id, -32000, result.error.unwrap_or_default())
+                ToolResponse::error("Read replica: send writes to the primary"),
+            );
+        }
+        if TRANSACTION_TOOLS.contains(&name) {
+            let result = self
+                .call_transaction_tool(name, &arguments, agent_id.as_deref())
+                .await;
+            return tool_response_to_rpc(id, result);
         }
-    }
 
-    /// Call `canvas_render` tool with scene mutation.
-    async fn call_canvas_render(&self, arguments: serde_json::Value) -> ToolResponse {
-        let params: RenderParams = match serde_json::from_value(arguments) {
-            Ok(p) => p,
+        // Calls inside a transaction run against its staged scene
+        let transaction = match self
+            .transaction_for(name, &mut arguments, agent_id.as_deref())
+            .await
+        {
+            Ok(transaction) => transaction,
+            Err(response) => return tool_response_to_rpc(id, response),
+        };
+        let target = transaction.as_ref().map_or(self, |tx| &tx.scratch);
+
+        if let Some(scope) = &scope {
+            if let Err(violation) = target
+                .authorize(name, &arguments, agent_id.as_deref(), scope)
+                .await
+            {
+                return scope_violation_response(id, agent_id.as_deref(), &violation);
+            }
+        }
+
+        let dry_run = arguments
+            .get("dry_run")
+            .and_then(serde_json::Value::as_bool)
+            .unwrap_or(false);
+        if dry_run && MUTATING_TOOLS.contains(&name) {
+            let result = target
+                .dry_run_tool(name, arguments, agent_id.as_deref(), scope.as_ref())
+                .await;
+            return tool_response_to_rpc(id, result);
+        }
+
+        // Tools consume their arguments; keep a copy for ownership tracking
+        let call_arguments = arguments.clone();
+        let mut result = target.dispatch_tool(name, arguments).await;
+
+        if result.success {
+            target
+                .track_ownership(
+                    name,
+                    &call_arguments,
+                    &result,
+                    agent_id.as_deref(),
+                    scope.as_ref(),
+                )
+                .await;
+            if let Some(tx) = &transaction {
+                tx.staged.write().await.push(name.to_string());
+                if let Some(data) = result.data.as_mut().and_then(|d| d.as_object_mut()) {
+                    data.insert(
+                        "transaction_id".to_string(),
+                        call_arguments["transaction_id"].clone(),
+                    );
+                    data.insert("staged".to_string(), serde_json::json!(true));
+                }
+            }
+        }
+
+        tool_response_to_rpc(id, result)
+    }
+
+    /// Look up the transaction a mutating tool call is staged in.
+    ///
+    /// The call's `session_id` defaults to the transaction's session.
+    async fn transaction_for(
+        &self,
+        name: &str,
+        arguments: &mut serde_json::Value,
+        agent_id: Option<&str>,
+    ) -> Result<Option<Arc<Transaction>>, ToolResponse> {
+        if !MUTATING_TOOLS.contains(&name) {
+            return Ok(None);
+        }
+        let Some(transaction_id) = arguments.get("transaction_id").and_then(|v| v.as_str()) else {
+            return Ok(None);
+        };
+        let tx = self.open_transaction(transaction_id, agent_id).await?;
+        match arguments.get("session_id").and_then(|v| v.as_str()) {
+            Some(session_id) if session_id != tx.session_id => {
+                return Err(ToolResponse::error(format!(
+                    "Transaction {transaction_id} belongs to session {}",
+                    tx.session_id
+                )));
+            }
+            Some(_) => {}
+            None => arguments["session_id"] = serde_json::json!(tx.session_id),
+        }
+        Ok(Some(tx))
+    }
+
+    /// Find an open transaction, checking that `agent_id` began it.
+    async fn open_transaction(
+        &self,
+        transaction_id: &str,
+        agent_id: Option<&str>,
+    ) -> Result<Arc<Transaction>, ToolResponse> {
+        let tx = self
+            .transactions
+            .read()
+            .await
+            .get(transaction_id)
+            .cloned()
+            .ok_or_else(|| {
+                ToolResponse::error(format!("Transaction not found: {transaction_id}"))
+            })?;
+        if tx.agent_id.as_deref() != agent_id {
+            return Err(ToolResponse::error(format!(
+                "Transaction {transaction_id} was begun by another agent"
+            )));
+        }
+        Ok(tx)
+    }
+
+    /// Run a tool by name.
+    async fn dispatch_tool(&self, name: &str, arguments: serde_json::Value) -> ToolResponse {
+        match name {
+            "canvas_render" => self.call_canvas_render(arguments).await,
+            "canvas_render_a2ui" => self.call_canvas_render_a2ui(arguments).await,
+            "canvas_interact" => self.call_canvas_interact(arguments),
+            "canvas_export" => self.call_canvas_export(arguments),
+            "canvas_clear" => self.call_canvas_clear(arguments).await,
+            "canvas_add_element" => self.call_canvas_add_element(arguments).await,
+            "canvas_remove_element" => self.call_canvas_remove_element(arguments).await,
+            "canvas_update_element" => self.call_canvas_update_element(arguments).await,
+            "canvas_group" => self.call_canvas_group(arguments).await,
+            "canvas_ungroup" => self.call_canvas_ungroup(arguments).await,
+            "canvas_poll" => self.call_canvas_poll(arguments).await,
+            "canvas_tree" => self.call_canvas_tree(arguments).await,
+            "canvas_barcode" => self.call_canvas_barcode(arguments).await,
+            "canvas_agenda" => self.call_canvas_agenda(arguments).await,
+            "canvas_log_append" => self.call_canvas_log_append(arguments).await,
+            "canvas_annotate" => self.call_canvas_annotate(arguments).await,
+            "canvas_poll_results" => self.call_canvas_poll_results(arguments),
+            "canvas_get_scene" => self.call_canvas_get_scene(arguments),
+            "canvas_validate" => self.call_canvas_validate(arguments),
+            "canvas_measure" => self.call_canvas_measure(arguments),
+            "canvas_lint" => self.call_canvas_lint(arguments).await,
+            "canvas_spotlight" => self.call_canvas_spotlight(arguments).await,
+            "canvas_camera" => self.call_canvas_camera(arguments).await,
+            "canvas_anchor" => self.call_canvas_anchor(arguments).await,
+            "canvas_set_theme" => self.call_canvas_set_theme(arguments).await,
+            "canvas_branding" => self.call_canvas_branding(arguments).await,
+            "canvas_data" => self.call_canvas_data(arguments).await,
+            "canvas_ocr" => self.call_canvas_ocr(arguments).await,
+            "canvas_archive" => self.call_canvas_archive(arguments).await,
+            "canvas_restore" => self.call_canvas_restore(arguments).await,
+            "canvas_save_template" => self.call_canvas_save_template(arguments).await,
+            "canvas_instantiate_template" => self.call_canvas_instantiate_template(arguments).await,
+            _ => ToolResponse::error(format!("Unknown tool: {name}")),
+        }
+    }
+
+    /// Run a mutating tool against a scratch copy of the session and report
+    /// the changes it would make, without applying them.
+    async fn dry_run_tool(
+        &self,
+        name: &str,
+        arguments: serde_json::Value,
+        agent_id: Option<&str>,
+        scope: Option<&AgentScope>,
+    ) -> ToolResponse {
+        let session_id = extract_session_id(&arguments);
+        let preview = arguments
+            .get("preview")
+            .and_then(serde_json::Value::as_bool)
+            .unwrap_or(false);
+
+        let mut scratch = Self::new(SceneStore::new());
+        scratch.templates = Arc::clone(&self.templates);
+        scratch.ocr_engine.clone_from(&self.ocr_engine);
+        scratch.archive_store.clone_from(&self.archive_store);
+        if let Some(scene) = self.store.get(&session_id) {
+            if let Err(e) = scratch.store.replace(&session_id, scene) {
+                return ToolResponse::error(format!("Failed to prepare dry run: {e}"));
+            }
+        }
+        let before = scratch.store.scene_document(&session_id);
+
+        let call_arguments = arguments.clone();
+        let result = scratch.dispatch_tool(name, arguments).await;
+        if !result.success {
+            return result;
+        }
+        scratch
+            .track_ownership(name, &call_arguments, &result, agent_id, scope)
+            .await;
+
+        let after = scratch.store.scene_document(&session_id);
+        let mut response = serde_json::json!({
+            "session_id": session_id,
+            "dry_run": true,
+            "result": result.data,
+            "patch": before.diff(&after),
+        });
+        if preview {
+            let scene = scratch.store.get_or_create(&session_id);
+            response["preview"] = match render_preview(&scene) {
+                Ok(preview) => preview,
+                Err(e) => serde_json::json!({ "error": e }),
+            };
+        }
+        ToolResponse::success(response)
+    }
+
+    /// The scope that applies to `agent_id`, if any.
+    async fn scope_for(&self, agent_id: Option<&str>) -> Option<AgentScope> {
+        if let Some(agent_id) = agent_id {
+            if let Some(scope) = self.agent_scopes.read().await.get(agent_id) {
+                return Some(scope.clone());
+            }
+        }
+        self.default_scope.read().await.clone()
+    }
+
+    /// Check a tool call against the caller's scope before running it.
+    ///
+    /// Malformed arguments are left for the tool itself to report.
+    #[allow(clippy::too_many_lines)]
+    async fn authorize(
+        &self,
+        name: &str,
+        arguments: &serde_json::Value,
+        agent_id: Option<&str>,
+        scope: &AgentScope,
+    ) -> Result<(), ScopeViolation> {
+        scope.check_tool(name)?;
+        if !scope.restricts_elements() {
+            return Ok(());
+        }
+
+        let session_id = extract_session_id(arguments);
+        let scene = self.store.get(&session_id);
+        match name {
+            // The camera and anchors are views shared by the whole scene
+            "canvas_clear" | "canvas_branding" | "canvas_set_theme" | "canvas_camera"
+            | "canvas_anchor" | "canvas_restore" => scope.check_scene_wide(name),
+            // Fixes may touch any element in the scene
+            "canvas_lint" if writes(name, arguments) => scope.check_scene_wide(name),
+            "canvas_render_a2ui" => {
+                let merge = arguments
+                    .get("merge")
+                    .and_then(serde_json::Value::as_bool)
+                    .unwrap_or(false);
+                if !merge {
+                    return scope.check_scene_wide(name);
+                }
+                scope.check_create(scene.as_ref())?;
+                let Some(tree) = arguments
+                    .get("tree")
+                    .and_then(|t| serde_json::from_value::<A2UITree>(t.clone()).ok())
+                else {
+                    return Ok(());
+                };
+                #[allow(clippy::cast_possible_truncation)]
+                let (offset_x, offset_y) = (
+                    arguments
+                        .get("offset_x")
+                        .and_then(serde_json::Value::as_f64)
+                        .unwrap_or(0.0) as f32,
+                    arguments
+                        .get("offset_y")
+                        .and_then(serde_json::Value::as_f64)
+                        .unwrap_or(0.0) as f32,
+                );
+                for element in tree.to_elements().elements {
+                    let mut transform = element.transform;
+                    transform.x += offset_x;
+                    transform.y += offset_y;
+                    scope.check_bounds(None, &SceneBounds::from_transform(&transform))?;
+                }
+                Ok(())
+            }
+            "canvas_add_element" | "canvas_render" => {
+                scope.check_create(scene.as_ref())?;
+                let transform = measured_transform(arguments);
+                scope.check_bounds(None, &SceneBounds::from_transform(&transform))
+            }
+            "canvas_poll" => {
+                scope.check_create(scene.as_ref())?;
+                scope.check_bounds(
+                    None,
+                    &SceneBounds::from_transform(&poll_transform(arguments)),
+                )
+            }
+            "canvas_tree" | "canvas_barcode" | "canvas_annotate" => {
+                scope.check_create(scene.as_ref())?;
+                built_element(name, arguments, scene.as_ref()).map_or(Ok(()), |element| {
+                    scope.check_bounds(None, &SceneBounds::from_transform(&element.transform))
+                })
+            }
+            "canvas_instantiate_template" => {
+                scope.check_create(scene.as_ref())?;
+                let Some(bounds) = self.template_placement(arguments).await else {
+                    return Ok(());
+                };
+                scope.check_bounds(None, &bounds)
+            }
+            "canvas_group" => {
+                scope.check_create(scene.as_ref())?;
+                let (Some(scene), Ok(element_ids)) = (scene, extract_element_ids(arguments)) else {
+                    return Ok(());
+                };
+                for element_id in element_ids {
+                    let owner = self.element_owner(&session_id, element_id).await;
+                    scope.check_element(&scene, element_id, owner.as_deref(), agent_id)?;
+                }
+                Ok(())
+            }
+            "canvas_spotlight" => {
+                let Some(scene) = scene else {
+                    return Ok(());
+                };
+                // Agents spotlight, and lift the spotlight from, only
+                // elements they may change
+                let clear = arguments
+                    .get("clear")
+                    .and_then(serde_json::Value::as_bool)
+                    .unwrap_or(false);
+                let element_ids = if clear {
+                    scene
+                        .spotlight()
+                        .map(|spotlight| spotlight.element_ids.clone())
+                        .unwrap_or_default()
+                } else if arguments.get("element_ids").is_some() {
+                    extract_element_ids(arguments).unwrap_or_default()
+                } else if let Some(query) = arguments.get("query").and_then(|v| v.as_str()) {
+                    match_elements(&scene, query)
+                } else {
+                    Vec::new()
+                };
+                for element_id in element_ids {
+                    let owner = self.element_owner(&session_id, element_id).await;
+                    scope.check_element(&scene, element_id, owner.as_deref(), agent_id)?;
+                }
+                Ok(())
+            }
+            "canvas_data" => {
+                let (Some(scene), Some(dataset)) =
+                    (scene, arguments.get("dataset").and_then(|v| v.as_str()))
+                else {
+                    return Ok(());
+                };
+                // Every chart bound to the dataset is refilled from it
+                for element in scene.elements().filter(|element| {
+                    matches!(
+                        &element.kind,
+                        ElementKind::Chart { binding: Some(binding), .. } if binding.dataset == dataset
+                    )
+                }) {
+                    let owner = self.element_owner(&session_id, element.id).await;
+                    scope.check_element(&scene, element.id, owner.as_deref(), agent_id)?;
+                }
+                Ok(())
+            }
+            "canvas_remove_element"
+            | "canvas_update_element"
+            | "canvas_ungroup"
+            | "canvas_agenda"
+            | "canvas_log_append"
+            | "canvas_ocr" => {
+                let (Some(scene), Ok(element_id)) = (scene, extract_element_id(arguments)) else {
+                    return Ok(());
+                };
+                let owner = self.element_owner(&session_id, element_id).await;
+                scope.check_element(&scene, element_id, owner.as_deref(), agent_id)?;
+                if let (Some(element), Some(updates)) =
+                    (scene.get_element(element_id), arguments.get("transform"))
+                {
+                    let mut transform = element.transform;
+                    apply_transform_updates(&mut transform, updates);
+                    scope
+                        .check_bounds(Some(element_id), &SceneBounds::from_transform(&transform))?;
+                }
+                Ok(())
+            }
+            // Scene changes without a narrower check need the whole scene
+            _ if MUTATING_TOOLS.contains(&name) => scope.check_scene_wide(name),
+            _ => Ok(()),
+        }
+    }
+
+    /// Record which agent created the elements of a successful tool call,
+    /// and move a layer-scoped agent's new elements into its layer.
+    async fn track_ownership(
+        &self,
+        name: &str,
+        arguments: &serde_json::Value,
+        result: &ToolResponse,
+        agent_id: Option<&str>,
+        scope: Option<&AgentScope>,
+    ) {
+        let session_id = extract_session_id(arguments);
+        let data = result.data.as_ref();
+        let mut owners = self.element_owners.write().await;
+
+        let replaces_scene = name == "canvas_clear"
+            || (name == "canvas_render_a2ui"
+                && !arguments
+                    .get("merge")
+                    .and_then(serde_json::Value::as_bool)
+                    .unwrap_or(false));
+        if replaces_scene {
+            owners.remove(&session_id);
+        }
+        if name == "canvas_remove_element" || name == "canvas_ungroup" {
+            if let (Some(session), Ok(element_id)) =
+                (owners.get_mut(&session_id), extract_element_id(arguments))
+            {
+                session.remove(&element_id);
+            }
+            return;
+        }
+
+        let created: Vec<ElementId> = match name {
+            "canvas_render" | "canvas_add_element" | "canvas_group" | "canvas_poll"
+            | "canvas_tree" | "canvas_barcode" | "canvas_annotate" => data
+                .and_then(|d| d.get("element_id"))
+                .and_then(|v| v.as_str())
+                .and_then(|id| ElementId::parse(id).ok())
+                .into_iter()
+                .collect(),
+            "canvas_render_a2ui" | "canvas_instantiate_template" => data
+                .and_then(|d| d.get("element_ids"))
+                .and_then(|v| v.as_array())
+                .map(|ids| {
+                    ids.iter()
+                        .filter_map(|id| id.as_str().and_then(|id| ElementId::parse(id).ok()))
+                        .collect()
+                })
+                .unwrap_or_default(),
+            _ => return,
+        };
+        if created.is_empty() {
+            return;
+        }
+        if let Some(agent_id) = agent_id {
+            let session = owners.entry(session_id.clone()).or_default();
+            for id in &created {
+                session.insert(*id, agent_id.to_string());
+            }
+        }
+        drop(owners);
+
+        let Some(layer) = scope.and_then(|s| s.layer) else {
+            return;
+        };
+        let update = self.store.update(&session_id, |scene| {
+            for &id in &created {
+                let is_root = scene.get_element(id).is_some_and(|e| e.parent.is_none());
+                if is_root {
+                    if let Err(e) = scene.reparent(id, layer) {
+                        warn!("Failed to move {} into layer {}: {}", id, layer, e);
+                    }
+                }
+            }
+        });
+        if let Err(e) = update {
+            warn!("Failed to update layer for {}: {}", session_id, e);
+            return;
+        }
+        if let Some(ref callback) = self.on_change {
+            if let Some(scene) = self.store.get(&session_id) {
+                callback(&session_id, &scene);
+            }
+        }
+    }
+
+    /// Call `canvas_render` tool with scene mutation.
+    async fn call_canvas_render(&self, arguments: serde_json::Value) -> ToolResponse {
+        let agent_id = arguments
+            .get("agent_id")
+            .and_then(serde_json::Value::as_str)
+            .map(str::to_string);
+        let params: RenderParams = match serde_json::from_value(arguments) {
+            Ok(p) => p,
             Err(e) => return ToolResponse::error(format!("Invalid parameters: {e}")),
         };
 
         let session_id = params.session_id.clone();
 
-        // Create element from content
-        let mut element = create_element_from_content(&params.content);
+        if let RenderContent::Callout {
+            target: Some(CalloutTarget::Element { element_id }),
+            ..
+        } = &params.content
+        {
+            let exists = self
+                .store
+                .get(&session_id)
+                .is_some_and(|scene| scene.get_element(*element_id).is_some());
+            if !exists {
+                return ToolResponse::error(format!(
+                    "Callout target element not found: {element_id}"
+                ));
+            }
+        }
 
-        // Apply position if specified
-        if let Some(pos) = &params.position {
-            element = element.with_transform(Transform {
-                x: pos.x,
-                y: pos.y,
-                width: pos.width.unwrap_or(200.0),
-                height: pos.height.unwrap_or(150.0),
-                rotation: 0.0,
-                z_index: 0,
-            });
+        // Create element from content, sized to fit unless a size is given
+        let element = render_element(&params, agent_id);
+        if let Err(message) = validate_kind(&element.kind) {
+            return ToolResponse::error(message);
         }
 
         let element_id = element.id;
@@ -590,7 +2340,87 @@ impl CanvasMcpServer {
             Err(e) => return ToolResponse::error(format!("Invalid parameters: {e}")),
         };
 
-        canvas_export(&params)
+        if let Some(print) = &params.print {
+            return self.export_print(&params, print);
+        }
+        match params.element_id.as_deref() {
+            Some(element_id) => self.export_element(&params, element_id),
+            None if params.flatten => {
+                ToolResponse::error("flatten needs the element_id of an image")
+            }
+            None => self.export_session(&params),
+        }
+    }
+
+    /// Export a whole session at its viewport size, or `width` x `height`.
+    fn export_session(&self, params: &ExportParams) -> ToolResponse {
+        let Some(scene) = self.store.get(&params.session_id) else {
+            return ToolResponse::error(format!("Session not found: {}", params.session_id));
+        };
+        tracing::info!(
+            "Exporting session {} as {:?}",
+            params.session_id,
+            params.format
+        );
+
+        match render_session_export(&scene, params) {
+            Ok(data) => ToolResponse::success(serde_json::json!({
+                "session_id": &params.session_id,
+                "format": params.format,
+                "mime_type": params.format.mime_type(),
+                "data": data,
+            })),
+            Err(e) => ToolResponse::error(e),
+        }
+    }
+
+    /// Export a session as a paginated PDF handout.
+    fn export_print(&self, params: &ExportParams, print: &PrintOptions) -> ToolResponse {
+        if !matches!(params.format, ExportFormat::Pdf) {
+            return ToolResponse::error("Print layout requires the pdf format");
+        }
+        if params.element_id.is_some() {
+            return ToolResponse::error("Print layout exports the whole session, not an element");
+        }
+        let Some(scene) = self.store.get(&params.session_id) else {
+            return ToolResponse::error(format!("Session not found: {}", params.session_id));
+        };
+
+        match render_print_export(&scene, &params.session_id, print, params.redact) {
+            Ok((data, pages)) => ToolResponse::success(serde_json::json!({
+                "session_id": &params.session_id,
+                "format": params.format,
+                "mime_type": params.format.mime_type(),
+                "pages": pages,
+                "data": data,
+            })),
+            Err(e) => ToolResponse::error(e),
+        }
+    }
+
+    /// Export one element of a session as an image, for attaching a single
+    /// chart or table to a chat message.
+    fn export_element(&self, params: &ExportParams, element_id: &str) -> ToolResponse {
+        let Ok(id) = ElementId::parse(element_id) else {
+            return ToolResponse::error(format!("Invalid element ID: {element_id}"));
+        };
+        let Some(scene) = self.store.get(&params.session_id) else {
+            return ToolResponse::error(format!("Session not found: {}", params.session_id));
+        };
+        if scene.get_element(id).is_none() {
+            return ToolResponse::error(format!("Element not found: {element_id}"));
+        }
+
+        match render_element_export(&scene, id, params) {
+            Ok(data) => ToolResponse::success(serde_json::json!({
+                "session_id": &params.session_id,
+                "element_id": element_id,
+                "format": params.format,
+                "mime_type": params.format.mime_type(),
+                "data": data,
+            })),
+            Err(e) => ToolResponse::error(e),
+        }
     }
 
     /// Call `canvas_clear` tool.
@@ -639,16 +2469,67 @@ impl CanvasMcpServer {
             Ok(k) => k,
             Err(e) => return ToolResponse::error(format!("Invalid element kind: {e}")),
         };
+        if let Err(message) = validate_kind(&kind) {
+            return ToolResponse::error(message);
+        }
 
         let transform = parse_transform(arguments.get("transform"));
         let interactive = arguments
             .get("interactive")
             .and_then(serde_json::Value::as_bool)
             .unwrap_or(true);
+        let visible = arguments
+            .get("visible")
+            .and_then(serde_json::Value::as_bool)
+            .unwrap_or(true);
+        let screen_anchored = arguments
+            .get("screen_anchored")
+            .and_then(serde_json::Value::as_bool)
+            .unwrap_or(false);
+        let sensitive = arguments
+            .get("sensitive")
+            .and_then(serde_json::Value::as_bool)
+            .unwrap_or(false);
+        let opacity = match parse_opacity(arguments.get("opacity")) {
+            Ok(opacity) => opacity.unwrap_or(1.0),
+            Err(response) => return response,
+        };
+        let style = match parse_style(arguments.get("style")) {
+            Ok(style) => style.unwrap_or_default(),
+            Err(response) => return response,
+        };
+        let interactions = match parse_interactions(arguments.get("interactions")) {
+            Ok(interactions) => interactions.unwrap_or_default(),
+            Err(response) => return response,
+        };
+        let attachment = match parse_attachment(arguments.get("attach_to")) {
+            Ok(attachment) => attachment.flatten(),
+            Err(response) => return response,
+        };
+        if let Some(attachment) = attachment {
+            let target_exists = self
+                .store
+                .get(&session_id)
+                .is_some_and(|scene| scene.get_element(attachment.target).is_some());
+            if !target_exists {
+                return ToolResponse::error(format!(
+                    "Attachment target not found: {}",
+                    attachment.target
+                ));
+            }
+        }
 
-        let element = Element::new(kind)
+        let mut element = Element::new(kind)
             .with_transform(transform)
-            .with_interactive(interactive);
+            .with_interactive(interactive)
+            .with_visible(visible)
+            .with_opacity(opacity)
+            .with_screen_anchored(screen_anchored)
+            .with_sensitive(sensitive)
+            .with_style(style)
+            .with_interactions(interactions);
+        element.attachment = attachment;
+        element.apply_auto_size(&MeasureContext::default());
         let element_id = element.id;
 
         // Add element to store (creates session if needed)
@@ -734,16 +2615,46 @@ impl CanvasMcpServer {
         };
         let element_id_str = element_id.to_string();
 
-        // Check if session exists
-        if self.store.get(&session_id).is_none() {
+        let Some(mut scene) = self.store.get(&session_id) else {
             return ToolResponse::error(format!("Session not found: {session_id}"));
+        };
+        let Some(current) = scene.get_element(element_id) else {
+            return ToolResponse::error(format!("Element not found: {element_id_str}"));
+        };
+        let kind = match merge_kind_data(&current.kind, arguments.get("data")) {
+            Ok(kind) => kind,
+            Err(response) => return response,
+        };
+        let attachment = match parse_attachment(arguments.get("attach_to")) {
+            Ok(attachment) => attachment,
+            Err(response) => return response,
+        };
+        // Check the target exists and does not follow this element
+        if let Some(attachment) = attachment {
+            if let Err(e) = scene.attach(element_id, attachment) {
+                return ToolResponse::error(format!("Invalid attach_to: {e}"));
+            }
         }
 
         // Clone arguments for the closure
         let transform_json = arguments.get("transform").cloned();
-        let interactive = arguments
-            .get("interactive")
-            .and_then(serde_json::Value::as_bool);
+        let flag = |name: &str| arguments.get(name).and_then(serde_json::Value::as_bool);
+        let interactive = flag("interactive");
+        let visible = flag("visible");
+        let screen_anchored = flag("screen_anchored");
+        let sensitive = flag("sensitive");
+        let opacity = match parse_opacity(arguments.get("opacity")) {
+            Ok(opacity) => opacity,
+            Err(response) => return response,
+        };
+        let style = match parse_style(arguments.get("style")) {
+            Ok(style) => style,
+            Err(response) => return response,
+        };
+        let interactions = match parse_interactions(arguments.get("interactions")) {
+            Ok(interactions) => interactions,
+            Err(response) => return response,
+        };
 
         // Update element in store
         let result = self
@@ -755,6 +2666,31 @@ impl CanvasMcpServer {
                 if let Some(inter) = interactive {
                     element.interactive = inter;
                 }
+                if let Some(visible) = visible {
+                    element.visible = visible;
+                }
+                if let Some(opacity) = opacity {
+                    element.opacity = opacity;
+                }
+                if let Some(screen_anchored) = screen_anchored {
+                    element.screen_anchored = screen_anchored;
+                }
+                if let Some(sensitive) = sensitive {
+                    element.sensitive = sensitive;
+                }
+                if let Some(style) = style {
+                    element.style = style;
+                }
+                if let Some(interactions) = interactions {
+                    element.interactions = interactions;
+                }
+                if let Some(kind) = kind {
+                    element.kind = kind;
+                }
+                if let Some(attachment) = attachment {
+                    element.attachment = attachment;
+                }
+                element.apply_auto_size(&MeasureContext::default());
             });
 
         if let Err(e) = result {
@@ -782,544 +2718,6100 @@ impl CanvasMcpServer {
         }))
     }
 
-    /// Call `canvas_get_scene` tool - get current scene state.
-    #[allow(clippy::needless_pass_by_value)]
-    fn call_canvas_get_scene(&self, arguments: serde_json::Value) -> ToolResponse {
+    /// Call `canvas_group` tool - wrap sibling elements in a new group.
+    async fn call_canvas_group(&self, arguments: serde_json::Value) -> ToolResponse {
         let session_id = extract_session_id(&arguments);
+        let element_ids = match extract_element_ids(&arguments) {
+            Ok(ids) => ids,
+            Err(response) => return response,
+        };
 
-        // Check if session exists
-        if self.store.get(&session_id).is_none() {
-            return ToolResponse::error(format!("Session not found: {session_id}"));
+        let mut result = Err(format!("Session not found: {session_id}"));
+        let update = self.store.update(&session_id, |scene| {
+            result = scene.group(&element_ids).map_err(|e| e.to_string());
+        });
+        if let Err(e) = update {
+            return ToolResponse::error(format!("Failed to group elements: {e}"));
         }
+        let group_id = match result {
+            Ok(value) => value,
+            Err(e) => return ToolResponse::error(format!("Failed to group elements: {e}")),
+        };
 
-        let document = self.store.scene_document(&session_id);
-
+        self.finish_structure_change(&session_id).await;
         ToolResponse::success(serde_json::json!({
             "session_id": session_id,
-            "scene": document,
+            "element_id": group_id.to_string()
         }))
     }
 
-    /// Handle resources/list request.
-    fn handle_resources_list(&self, id: serde_json::Value) -> JsonRpcResponse {
-        let session_ids = self.store.session_ids();
-
-        let mut resource_list: Vec<Resource> = session_ids
-            .iter()
-            .map(|session_id| Resource {
-                uri: format!("canvas://session/{session_id}"),
-                name: format!("Canvas Session: {session_id}"),
-                description: "A canvas session with visual elements".to_string(),
-                mime_type: "application/json".to_string(),
-            })
-            .collect();
+    /// Call `canvas_ungroup` tool - dissolve a group, keeping its children.
+    async fn call_canvas_ungroup(&self, arguments: serde_json::Value) -> ToolResponse {
+        let session_id = extract_session_id(&arguments);
+        let element_id = match extract_element_id(&arguments) {
+            Ok(id) => id,
+            Err(response) => return response,
+        };
 
-        // Add chart templates
-        for chart_type in &["bar", "line", "pie", "area", "scatter"] {
-            let capitalized = capitalize(chart_type);
-            resource_list.push(Resource {
-                uri: format!("canvas://chart/{chart_type}"),
-                name: format!("{capitalized} Chart Template"),
-                description: format!("Template for creating {chart_type} charts"),
-                mime_type: "application/json".to_string(),
-            });
+        let mut result = Err(format!("Session not found: {session_id}"));
+        let update = self.store.update(&session_id, |scene| {
+            result = scene.ungroup(element_id).map_err(|e| e.to_string());
+        });
+        if let Err(e) = update {
+            return ToolResponse::error(format!("Failed to ungroup element: {e}"));
         }
+        let children = match result {
+            Ok(value) => value,
+            Err(e) => return ToolResponse::error(format!("Failed to ungroup element: {e}")),
+        };
 
-        JsonRpcResponse::success(id, serde_json::json!({ "resources": resource_list }))
+        self.finish_structure_change(&session_id).await;
+        ToolResponse::success(serde_json::json!({
+            "session_id": session_id,
+            "ungrouped": true,
+            "element_id": element_id.to_string(),
+            "children": children.iter().map(ToString::to_string).collect::<Vec<_>>()
+        }))
     }
 
-    /// Handle resources/read request.
-    #[allow(clippy::unused_self, clippy::needless_pass_by_value)]
-    fn handle_resources_read(
-        &self,
-        id: serde_json::Value,
-        params: serde_json::Value,
-    ) -> JsonRpcResponse {
-        let uri = params
-            .get("uri")
-            .and_then(|v| v.as_str())
-            .unwrap_or_default();
+    /// Call `canvas_poll` tool - add a poll that peers vote on.
+    async fn call_canvas_poll(&self, arguments: serde_json::Value) -> ToolResponse {
+        let session_id = extract_session_id(&arguments);
+        let Some(question) = arguments.get("question").and_then(|v| v.as_str()) else {
+            return ToolResponse::error("Missing required field: question");
+        };
+        let Some(options) = arguments
+            .get("options")
+            .and_then(|v| serde_json::from_value::<Vec<String>>(v.clone()).ok())
+        else {
+            return ToolResponse::error("Missing required field: options");
+        };
 
-        match resources::get_resource(uri) {
-            Ok(content) => JsonRpcResponse::success(
-                id,
-                serde_json::json!({
-                    "contents": [{
-                        "uri": uri,
-                        "mimeType": "application/json",
-                        "text": match content {
-                            crate::ResourceContent::Json(v) => serde_json::to_string_pretty(&v).unwrap_or_default(),
-                            crate::ResourceContent::Text(s) => s,
-                            crate::ResourceContent::Binary { data, .. } => data,
-                        }
-                    }]
-                }),
-            ),
-            Err(e) => JsonRpcResponse::error(id, -32002, e),
+        let kind = ElementKind::Poll {
+            question: question.to_string(),
+            options,
+            votes: std::collections::BTreeMap::new(),
+            closed: false,
+        };
+        if let Err(message) = validate_kind(&kind) {
+            return ToolResponse::error(message);
+        }
+        let element = Element::new(kind).with_transform(poll_transform(&arguments));
+        let element_id = element.id;
+        if let Err(e) = self.store.add_element(&session_id, element) {
+            return ToolResponse::error(format!("Failed to add element: {e}"));
         }
-    }
-}
 
-// ============================================================================
-// Utility Functions
-// ============================================================================
+        self.session_metadata
+            .write()
+            .await
+            .entry(session_id.clone())
+            .or_insert_with(|| create_session_metadata(&session_id, 800.0, 600.0));
+        self.finish_structure_change(&session_id).await;
+        ToolResponse::success(serde_json::json!({
+            "session_id": session_id,
+            "element_id": element_id.to_string()
+        }))
+    }
 
-/// Get current timestamp in ISO 8601 format.
-fn chrono_now() -> String {
-    use std::time::{SystemTime, UNIX_EPOCH};
-    let duration = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap_or_default();
-    let secs = duration.as_secs();
-    format!("{secs}")
-}
+    /// Call `canvas_tree` tool - add an org chart or decision tree from
+    /// nested JSON.
+    async fn call_canvas_tree(&self, arguments: serde_json::Value) -> ToolResponse {
+        let session_id = extract_session_id(&arguments);
+        let element = match tree_element(&arguments) {
+            Ok(element) => element,
+            Err(response) => return response,
+        };
+        if let Err(message) = validate_kind(&element.kind) {
+            return ToolResponse::error(message);
+        }
+        let nodes = match &element.kind {
+            ElementKind::Tree { root, .. } => root.count(),
+            _ => 0,
+        };
+        let element_id = element.id;
+        if let Err(e) = self.store.add_element(&session_id, element) {
+            return ToolResponse::error(format!("Failed to add element: {e}"));
+        }
 
-/// Detect image format from source string (URL or data URI).
-fn detect_image_format(src: &str) -> ImageFormat {
-    let src_lower = src.to_lowercase();
-    if src_lower.contains("png") || src_lower.starts_with("data:image/png") {
-        ImageFormat::Png
-    } else if src_lower.contains("jpg")
-        || src_lower.contains("jpeg")
-        || src_lower.starts_with("data:image/jpeg")
-    {
-        ImageFormat::Jpeg
-    } else if src_lower.contains("svg") || src_lower.starts_with("data:image/svg") {
-        ImageFormat::Svg
-    } else if src_lower.contains("webp") || src_lower.starts_with("data:image/webp") {
-        ImageFormat::WebP
-    } else {
-        // Default to PNG
-        ImageFormat::Png
+        self.session_metadata
+            .write()
+            .await
+            .entry(session_id.clone())
+            .or_insert_with(|| create_session_metadata(&session_id, 800.0, 600.0));
+        self.finish_structure_change(&session_id).await;
+        ToolResponse::success(serde_json::json!({
+            "session_id": session_id,
+            "element_id": element_id.to_string(),
+            "nodes": nodes
+        }))
     }
-}
 
-/// Capitalize the first letter of a string.
-fn capitalize(s: &str) -> String {
-    let mut chars = s.chars();
-    match chars.next() {
-        None => String::new(),
-        Some(c) => c.to_uppercase().chain(chars).collect(),
+    /// Call `canvas_barcode` tool - add a scannable QR code or barcode.
+    async fn call_canvas_barcode(&self, arguments: serde_json::Value) -> ToolResponse {
+        let session_id = extract_session_id(&arguments);
+        let element = match barcode_element(&arguments) {
+            Ok(element) => element,
+            Err(response) => return response,
+        };
+        let ElementKind::Barcode {
+            symbology,
+            payload,
+            modules,
+            ..
+        } = &element.kind
+        else {
+            return ToolResponse::error("Failed to build barcode");
+        };
+        let data = serde_json::json!({
+            "session_id": &session_id,
+            "element_id": element.id.to_string(),
+            "symbology": symbology,
+            "payload": payload,
+            "modules": modules.first().map_or(0, String::len),
+        });
+        if let Err(e) = self.store.add_element(&session_id, element) {
+            return ToolResponse::error(format!("Failed to add element: {e}"));
+        }
+
+        self.session_metadata
+            .write()
+            .await
+            .entry(session_id.clone())
+            .or_insert_with(|| create_session_metadata(&session_id, 800.0, 600.0));
+        self.finish_structure_change(&session_id).await;
+        ToolResponse::success(data)
     }
-}
 
-// ============================================================================
-// Tool Schemas
-// ============================================================================
+    /// Call `canvas_agenda` tool - start, advance or rewind an agenda, or
+    /// read where it stands.
+    async fn call_canvas_agenda(&self, arguments: serde_json::Value) -> ToolResponse {
+        let session_id = extract_session_id(&arguments);
+        let element_id = match extract_element_id(&arguments) {
+            Ok(id) => id,
+            Err(response) => return response,
+        };
+        let command = match agenda_command(&arguments) {
+            Ok(command) => command,
+            Err(response) => return response,
+        };
+        let Some(kind) = self
+            .store
+            .get(&session_id)
+            .and_then(|scene| scene.get_element(element_id).map(|e| e.kind.clone()))
+        else {
+            return ToolResponse::error(format!("Element not found: {element_id}"));
+        };
 
-/// Get the list of available MCP tools.
-fn get_available_tools() -> Vec<Tool> {
-    vec![
-        Tool {
-            name: "canvas_render".to_string(),
-            description: "Render content (chart, image, text, 3D model) to the canvas".to_string(),
-            input_schema: render_tool_schema(),
-        },
-        Tool {
-            name: "canvas_render_a2ui".to_string(),
-            description: "Render an A2UI component tree to the canvas. Supports Container, Text, Image, Button, Chart, and VideoFeed components with automatic layout.".to_string(),
-            input_schema: render_a2ui_tool_schema(),
-        },
-        Tool {
-            name: "canvas_interact".to_string(),
-            description: "Report user interaction (touch, voice, selection) on the canvas"
-                .to_string(),
-            input_schema: interact_tool_schema(),
-        },
-        Tool {
-            name: "canvas_export".to_string(),
-            description: "Export the canvas to an image or PDF".to_string(),
-            input_schema: export_tool_schema(),
-        },
-        Tool {
-            name: "canvas_clear".to_string(),
-            description: "Clear all elements from the canvas".to_string(),
-            input_schema: clear_tool_schema(),
-        },
-        Tool {
-            name: "canvas_add_element".to_string(),
-            description: "Add an element to the canvas with full control over type, transform, and properties".to_string(),
-            input_schema: add_element_tool_schema(),
-        },
-        Tool {
-            name: "canvas_remove_element".to_string(),
-            description: "Remove an element from the canvas by its ID".to_string(),
-            input_schema: remove_element_tool_schema(),
-        },
-        Tool {
-            name: "canvas_update_element".to_string(),
-            description: "Update an existing element's transform or properties".to_string(),
-            input_schema: update_element_tool_schema(),
-        },
-        Tool {
-            name: "canvas_get_scene".to_string(),
-            description: "Get the current scene state as a JSON document".to_string(),
-            input_schema: get_scene_tool_schema(),
-        },
-    ]
-}
+        let now = now_millis();
+        let status = match command {
+            Some(command) => {
+                let mut result = Ok(AgendaStatus::Idle);
+                let update = self
+                    .store
+                    .update_element(&session_id, element_id, |element| {
+                        result = agenda::apply(&mut element.kind, command, now);
+                    });
+                match update
+                    .map_err(|e| e.to_string())
+                    .and_then(|()| result.map_err(|e| e.to_string()))
+                {
+                    Ok(status) => {
+                        self.finish_structure_change(&session_id).await;
+                        status
+                    }
+                    Err(e) => return ToolResponse::error(format!("Failed to update agenda: {e}")),
+                }
+            }
+            None => match AgendaStatus::from_kind(&kind, now) {
+                Some(status) => status,
+                None => {
+                    return ToolResponse::error(format!(
+                        "Element {element_id} is a {}, not an agenda",
+                        kind.type_name()
+                    ))
+                }
+            },
+        };
+        let item = match (&kind, status.current()) {
+            (ElementKind::Agenda { items, .. }, Some(index)) => {
+                items.get(index).map(|item| item.title.clone())
+            }
+            _ => None,
+        };
 
-/// Common `session_id` property schema.
-fn session_id_property() -> serde_json::Value {
-    serde_json::json!({
-        "type": "string",
-        "description": "Canvas session ID (defaults to 'default')",
-        "default": "default"
-    })
-}
+        ToolResponse::success(serde_json::json!({
+            "session_id": session_id,
+            "element_id": element_id.to_string(),
+            "status": status,
+            "item": item,
+            "server_time_ms": now,
+        }))
+    }
 
-/// Common `element_id` property schema.
-fn element_id_property() -> serde_json::Value {
-    serde_json::json!({
-        "type": "string",
-        "description": "Unique element identifier (UUID format)"
-    })
-}
+    /// Call `canvas_log_append` tool - stream output into a log view.
+    async fn call_canvas_log_append(&self, arguments: serde_json::Value) -> ToolResponse {
+        let session_id = extract_session_id(&arguments);
+        let element_id = match extract_element_id(&arguments) {
+            Ok(id) => id,
+            Err(response) => return response,
+        };
+        let text = arguments
+            .get("text")
+            .and_then(|v| v.as_str())
+            .unwrap_or_default();
+        let clear = arguments
+            .get("clear")
+            .and_then(serde_json::Value::as_bool)
+            .unwrap_or(false);
 
-/// Common transform property schema.
-fn transform_property() -> serde_json::Value {
-    serde_json::json!({
-        "type": "object",
-        "description": "Element transform (position, size, rotation)",
-        "properties": {
-            "x": { "type": "number", "description": "X position in pixels" },
-            "y": { "type": "number", "description": "Y position in pixels" },
-            "width": { "type": "number", "description": "Width in pixels" },
-            "height": { "type": "number", "description": "Height in pixels" },
-            "rotation": { "type": "number", "description": "Rotation in degrees" },
-            "z_index": { "type": "integer", "description": "Stack order (higher = front)" }
+        let mut result = Err(canvas_core::CanvasError::ElementNotFound(
+            element_id.to_string(),
+        ));
+        let update = self
+            .store
+            .update_element(&session_id, element_id, |element| {
+                result = if clear {
+                    log_view::clear(&mut element.kind)
+                } else {
+                    Ok(())
+                }
+                .and_then(|()| log_view::append(&mut element.kind, text));
+            });
+        match update
+            .map_err(|e| e.to_string())
+            .and_then(|()| result.map_err(|e| e.to_string()))
+        {
+            Ok(appended) => {
+                self.finish_structure_change(&session_id).await;
+                ToolResponse::success(serde_json::json!({
+                    "session_id": session_id,
+                    "element_id": element_id.to_string(),
+                    "appended": appended.appended,
+                    "dropped": appended.dropped,
+                    "total": appended.total,
+                }))
+            }
+            Err(e) => ToolResponse::error(format!("Failed to append to log: {e}")),
         }
-    })
-}
+    }
 
-/// Schema for `canvas_render` tool.
-fn render_tool_schema() -> serde_json::Value {
-    serde_json::json!({
-        "type": "object",
-        "properties": {
-            "session_id": session_id_property(),
-            "content": {
-                "type": "object",
-                "description": "Content to render",
-                "oneOf": [
-                    {
-                        "type": "object",
-                        "properties": {
-                            "type": { "const": "Chart" },
-                            "data": {
-                                "type": "object",
-                                "properties": {
-                                    "chart_type": { "type": "string", "enum": ["bar", "line", "pie", "area", "scatter"] },
-                                    "data": { "type": "object" }
-                                },
-                                "required": ["chart_type", "data"]
-                            }
-                        },
-                        "required": ["type", "data"]
-                    },
-                    {
-                        "type": "object",
-                        "properties": {
-                            "type": { "const": "Image" },
-                            "data": {
-                                "type": "object",
-                                "properties": {
-                                    "src": { "type": "string" }
-                                },
-                                "required": ["src"]
-                            }
-                        },
-                        "required": ["type", "data"]
-                    },
-                    {
-                        "type": "object",
-                        "properties": {
-                            "type": { "const": "Text" },
-                            "data": {
-                                "type": "object",
-                                "properties": {
-                                    "content": { "type": "string" },
-                                    "font_size": { "type": "number" }
-                                },
-                                "required": ["content"]
-                            }
-                        },
-                        "required": ["type", "data"]
-                    }
-                ]
-            },
-            "position": {
-                "type": "object",
-                "properties": {
-                    "x": { "type": "number" },
-                    "y": { "type": "number" },
-                    "width": { "type": "number" },
-                    "height": { "type": "number" }
-                },
-                "required": ["x", "y"]
+    /// Call `canvas_annotate` tool - add a measurement line or region
+    /// highlight, e.g. to point at part of a chart or video frame.
+    async fn call_canvas_annotate(&self, arguments: serde_json::Value) -> ToolResponse {
+        let session_id = extract_session_id(&arguments);
+        let scene = self.store.get(&session_id);
+        let element = match annotation_element(&arguments, scene.as_ref()) {
+            Ok(element) => element,
+            Err(response) => return response,
+        };
+        if let Err(message) = validate_kind(&element.kind) {
+            return ToolResponse::error(message);
+        }
+        let label = MeasurementLayout::from_element(&element)
+            .map(|layout| layout.label)
+            .filter(|label| !label.is_empty());
+        let element_id = element.id;
+        // Marks made over an image are pinned to it so they follow it
+        let image = scene
+            .as_ref()
+            .zip(arguments.get("element_id").and_then(|v| v.as_str()))
+            .and_then(|(scene, id)| scene.get_element(ElementId::parse(id).ok()?))
+            .filter(|target| matches!(target.kind, ElementKind::Image { .. }))
+            .map(|target| target.id);
+        if let Some(image) = image {
+            let mut pinned = Ok(element_id);
+            if let Err(e) = self.store.update(&session_id, |scene| {
+                pinned = scene.pin_markup(image, element);
+            }) {
+                return ToolResponse::error(format!("Failed to add element: {e}"));
             }
-        },
-        "required": ["content"]
-    })
-}
+            if let Err(e) = pinned {
+                return ToolResponse::error(format!("Failed to pin markup: {e}"));
+            }
+        } else if let Err(e) = self.store.add_element(&session_id, element) {
+            return ToolResponse::error(format!("Failed to add element: {e}"));
+        }
 
-/// Schema for `canvas_render_a2ui` tool.
-fn render_a2ui_tool_schema() -> serde_json::Value {
-    serde_json::json!({
-        "type": "object",
-        "properties": {
-            "session_id": session_id_property(),
-            "tree": {
-                "type": "object",
-                "description": "A2UI component tree to render",
-                "properties": {
-                    "root": {
-                        "type": "object",
-                        "description": "Root A2UI node (Container, Text, Image, Button, Chart, or VideoFeed)"
-                    },
-                    "data_model": {
-                        "type": "object",
-                        "description": "Optional data bindings for dynamic content"
-                    }
-                },
-                "required": ["root"]
-            },
-            "merge": {
-                "type": "boolean",
-                "description": "If true, merge with existing scene. If false (default), replace scene.",
-                "default": false
-            },
-            "offset_x": {
-                "type": "number",
-                "description": "X offset to apply to all elements (default: 0)",
-                "default": 0
-            },
-            "offset_y": {
-                "type": "number",
-                "description": "Y offset to apply to all elements (default: 0)",
-                "default": 0
+        self.session_metadata
+            .write()
+            .await
+            .entry(session_id.clone())
+            .or_insert_with(|| create_session_metadata(&session_id, 800.0, 600.0));
+        self.finish_structure_change(&session_id).await;
+        let mut data = serde_json::json!({
+            "session_id": session_id,
+            "element_id": element_id.to_string()
+        });
+        if let Some(label) = label {
+            data["label"] = serde_json::json!(label);
+        }
+        if let Some(image) = image {
+            data["pinned_to"] = serde_json::json!(image.to_string());
+        }
+        ToolResponse::success(data)
+    }
+
+    /// Call `canvas_poll_results` tool - tally a poll's votes.
+    #[allow(clippy::needless_pass_by_value)]
+    fn call_canvas_poll_results(&self, arguments: serde_json::Value) -> ToolResponse {
+        let session_id = extract_session_id(&arguments);
+        let element_id = match extract_element_id(&arguments) {
+            Ok(id) => id,
+            Err(response) => return response,
+        };
+        let Some(scene) = self.store.get(&session_id) else {
+            return ToolResponse::error(format!("Session not found: {session_id}"));
+        };
+        let Some(element) = scene.get_element(element_id) else {
+            return ToolResponse::error(format!("Element not found: {element_id}"));
+        };
+        let Some(results) = PollResults::from_kind(&element.kind) else {
+            return ToolResponse::error(format!(
+                "Element {element_id} is a {}, not a poll",
+                element.kind.type_name()
+            ));
+        };
+
+        ToolResponse::success(serde_json::json!({
+            "session_id": session_id,
+            "element_id": element_id.to_string(),
+            "results": results
+        }))
+    }
+
+    /// Refresh session metadata and notify listeners after elements were
+    /// added or removed.
+    async fn finish_structure_change(&self, session_id: &str) {
+        let element_count = self.store.get(session_id).map_or(0, |s| s.element_count());
+        let mut metadata = self.session_metadata.write().await;
+        if let Some(session) = metadata.get_mut(session_id) {
+            update_session_metadata(session, element_count);
+        }
+        drop(metadata);
+
+        if let Some(ref callback) = self.on_change {
+            if let Some(scene) = self.store.get(session_id) {
+                callback(session_id, &scene);
             }
-        },
-        "required": ["tree"]
-    })
-}
+        }
+    }
 
-/// Schema for `canvas_interact` tool.
-fn interact_tool_schema() -> serde_json::Value {
-    serde_json::json!({
-        "type": "object",
-        "properties": {
-            "session_id": session_id_property(),
-            "interaction_type": {
-                "type": "string",
-                "enum": ["touch", "voice", "selection"],
-                "description": "Type of interaction"
-            },
-            "data": {
-                "type": "object",
-                "description": "Interaction-specific data"
+    /// Call `canvas_get_scene` tool - get current scene state.
+    #[allow(clippy::needless_pass_by_value)]
+    fn call_canvas_get_scene(&self, arguments: serde_json::Value) -> ToolResponse {
+        let session_id = extract_session_id(&arguments);
+
+        // Check if session exists
+        if self.store.get(&session_id).is_none() {
+            return ToolResponse::error(format!("Session not found: {session_id}"));
+        }
+
+        let mut document = self.store.scene_document(&session_id);
+        if arguments.get("redact").and_then(serde_json::Value::as_bool) == Some(true) {
+            if let Some(scene) = self.store.get(&session_id) {
+                document =
+                    SceneDocument::from_scene(&session_id, &scene.redacted(), document.timestamp);
             }
-        },
-        "required": ["interaction_type"]
-    })
-}
+        }
 
-/// Schema for `canvas_export` tool.
-fn export_tool_schema() -> serde_json::Value {
-    serde_json::json!({
-        "type": "object",
-        "properties": {
-            "session_id": session_id_property(),
-            "format": {
-                "type": "string",
-                "enum": ["png", "jpeg", "pdf", "svg"],
-                "description": "Export format"
-            },
-            "quality": {
-                "type": "number",
-                "minimum": 0,
-                "maximum": 100,
-                "description": "Export quality (for lossy formats)"
+        ToolResponse::success(serde_json::json!({
+            "session_id": session_id,
+            "scene": document,
+        }))
+    }
+
+    /// Call `canvas_validate` tool - report integrity issues and statistics.
+    #[allow(clippy::needless_pass_by_value)]
+    fn call_canvas_validate(&self, arguments: serde_json::Value) -> ToolResponse {
+        let session_id = extract_session_id(&arguments);
+
+        let Some(scene) = self.store.get(&session_id) else {
+            return ToolResponse::error(format!("Session not found: {session_id}"));
+        };
+
+        let issues = scene.validate();
+        let valid = issues
+            .iter()
+            .all(|issue| issue.severity != IssueSeverity::Error);
+        let summary: Vec<String> = issues.iter().map(ToString::to_string).collect();
+        ToolResponse::success(serde_json::json!({
+            "session_id": session_id,
+            "valid": valid,
+            "issues": issues,
+            "summary": summary,
+            "stats": scene.stats(),
+        }))
+    }
+
+    /// Call `canvas_measure` tool - distance and angle in scene units.
+    #[allow(clippy::needless_pass_by_value)]
+    fn call_canvas_measure(&self, arguments: serde_json::Value) -> ToolResponse {
+        let session_id = extract_session_id(&arguments);
+
+        let Some(scene) = self.store.get(&session_id) else {
+            return ToolResponse::error(format!("Session not found: {session_id}"));
+        };
+
+        let point = |field: &str| {
+            arguments
+                .get(field)
+                .map(|v| serde_json::from_value::<[f32; 2]>(v.clone()))
+        };
+        let coordinates = scene.coordinate_system();
+        match (point("start"), point("end")) {
+            (Some(Ok(start)), Some(Ok(end))) => {
+                let (start, end) =
+                    if arguments.get("world").and_then(serde_json::Value::as_bool) == Some(true) {
+                        (coordinates.to_canvas(start), coordinates.to_canvas(end))
+                    } else {
+                        (start, end)
+                    };
+                ToolResponse::success(serde_json::json!({
+                    "session_id": session_id,
+                    "coordinates": coordinates,
+                    "start": coordinates.to_world(start),
+                    "end": coordinates.to_world(end),
+                    "measurement": scene.measure(start, end),
+                }))
             }
-        },
-        "required": ["format"]
-    })
-}
+            (None, None) => {
+                let measurements: Vec<serde_json::Value> = scene
+                    .measurements()
+                    .into_iter()
+                    .map(|(id, measurement)| {
+                        let label = scene
+                            .get_element(id)
+                            .and_then(MeasurementLayout::from_element)
+                            .map(|layout| layout.label);
+                        serde_json::json!({
+                            "element_id": id.to_string(),
+                            "label": label,
+                            "measurement": measurement,
+                        })
+                    })
+                    .collect();
+                ToolResponse::success(serde_json::json!({
+                    "session_id": session_id,
+                    "coordinates": coordinates,
+                    "measurements": measurements,
+                }))
+            }
+            _ => ToolResponse::error("start and end must both be [x, y] pairs"),
+        }
+    }
 
-/// Schema for `canvas_clear` tool.
-fn clear_tool_schema() -> serde_json::Value {
-    serde_json::json!({
-        "type": "object",
-        "properties": {
-            "session_id": session_id_property()
+    /// Call `canvas_lint` tool - report layout and legibility problems, and
+    /// optionally apply their suggested fixes.
+    async fn call_canvas_lint(&self, arguments: serde_json::Value) -> ToolResponse {
+        let session_id = extract_session_id(&arguments);
+
+        let config: LintConfig = match serde_json::from_value(arguments.clone()) {
+            Ok(config) => config,
+            Err(e) => return ToolResponse::error(format!("Invalid lint options: {e}")),
+        };
+        if !(config.min_font_size.is_finite() && config.min_font_size > 0.0) {
+            return ToolResponse::error("min_font_size must be a positive number");
         }
-    })
-}
+        if !(1.0..=21.0).contains(&config.min_contrast) {
+            return ToolResponse::error("min_contrast must be between 1 and 21");
+        }
+        let apply = arguments
+            .get("apply")
+            .and_then(serde_json::Value::as_bool)
+            .unwrap_or(false);
 
-/// Schema for `canvas_add_element` tool.
-fn add_element_tool_schema() -> serde_json::Value {
-    serde_json::json!({
-        "type": "object",
-        "properties": {
-            "session_id": session_id_property(),
-            "kind": {
-                "type": "object",
-                "description": "Element type and data",
-                "oneOf": [
-                    {
-                        "type": "object",
-                        "properties": {
-                            "type": { "const": "Text" },
-                            "data": {
-                                "type": "object",
-                                "properties": {
-                                    "content": { "type": "string" },
-                                    "font_size": { "type": "number" },
-                                    "color": { "type": "string" }
-                                },
-                                "required": ["content", "font_size", "color"]
-                            }
-                        },
-                        "required": ["type", "data"]
-                    },
-                    {
-                        "type": "object",
-                        "properties": {
-                            "type": { "const": "Chart" },
-                            "data": {
-                                "type": "object",
-                                "properties": {
-                                    "chart_type": { "type": "string" },
-                                    "data": { "type": "object" }
-                                },
-                                "required": ["chart_type", "data"]
-                            }
-                        },
-                        "required": ["type", "data"]
-                    },
-                    {
-                        "type": "object",
-                        "properties": {
-                            "type": { "const": "Image" },
-                            "data": {
-                                "type": "object",
-                                "properties": {
-                                    "src": { "type": "string" },
-                                    "format": { "type": "string" }
-                                },
-                                "required": ["src", "format"]
-                            }
-                        },
-                        "required": ["type", "data"]
+        let Some(scene) = self.store.get(&session_id) else {
+            return ToolResponse::error(format!("Session not found: {session_id}"));
+        };
+        let findings = scene.lint(&config);
+        if !apply {
+            return ToolResponse::success(serde_json::json!({
+                "session_id": session_id,
+                "findings": findings,
+            }));
+        }
+
+        let mut fixed = Vec::new();
+        let mut remaining = Vec::new();
+        if let Err(e) = self.store.update(&session_id, |scene| {
+            fixed = scene.apply_lint_fixes(&findings);
+            remaining = scene.lint(&config);
+        }) {
+            return ToolResponse::error(format!("Failed to apply fixes: {e}"));
+        }
+        if !fixed.is_empty() {
+            self.finish_structure_change(&session_id).await;
+        }
+        let fixed: Vec<String> = fixed.iter().map(ToString::to_string).collect();
+        ToolResponse::success(serde_json::json!({
+            "session_id": session_id,
+            "findings": findings,
+            "fixed": fixed,
+            "remaining": remaining,
+        }))
+    }
+
+    /// Call `canvas_spotlight` tool - highlight elements by dimming the rest.
+    async fn call_canvas_spotlight(&self, arguments: serde_json::Value) -> ToolResponse {
+        let session_id = extract_session_id(&arguments);
+
+        let Some(scene) = self.store.get(&session_id) else {
+            return ToolResponse::error(format!("Session not found: {session_id}"));
+        };
+
+        let clear = arguments
+            .get("clear")
+            .and_then(serde_json::Value::as_bool)
+            .unwrap_or(false);
+
+        let spotlight = if clear {
+            None
+        } else {
+            let element_ids = if let Some(ids) = arguments.get("element_ids") {
+                let Some(ids) = ids.as_array() else {
+                    return ToolResponse::error("element_ids must be an array of strings");
+                };
+                let mut parsed = Vec::with_capacity(ids.len());
+                for id in ids {
+                    match id.as_str().map(ElementId::parse) {
+                        Some(Ok(id)) => parsed.push(id),
+                        _ => return ToolResponse::error(format!("Invalid element_id: {id}")),
                     }
-                ]
-            },
-            "transform": transform_property(),
-            "interactive": {
-                "type": "boolean",
-                "description": "Whether the element responds to interactions",
-                "default": true
+                }
+                parsed
+            } else if let Some(query) = arguments.get("query").and_then(|v| v.as_str()) {
+                match_elements(&scene, query)
+            } else {
+                return ToolResponse::error("Provide element_ids, query, or clear");
+            };
+            if element_ids.is_empty() {
+                return ToolResponse::error("No elements matched the spotlight request");
             }
-        },
-        "required": ["kind"]
-    })
-}
 
-/// Schema for `canvas_remove_element` tool.
-fn remove_element_tool_schema() -> serde_json::Value {
-    serde_json::json!({
-        "type": "object",
-        "properties": {
-            "session_id": session_id_property(),
-            "element_id": element_id_property()
-        },
-        "required": ["element_id"]
-    })
-}
+            let mut spotlight = Spotlight::new(element_ids).with_zoom_to_fit(
+                arguments
+                    .get("zoom_to_fit")
+                    .and_then(serde_json::Value::as_bool)
+                    .unwrap_or(true),
+            );
+            if let Some(opacity) = arguments
+                .get("dim_opacity")
+                .and_then(serde_json::Value::as_f64)
+            {
+                #[allow(clippy::cast_possible_truncation)]
+                let opacity = opacity as f32;
+                spotlight = spotlight.with_dim_opacity(opacity);
+            }
+            if let Some(timeout) = arguments
+                .get("timeout_ms")
+                .and_then(serde_json::Value::as_u64)
+            {
+                spotlight = spotlight.with_timeout(now_millis(), timeout);
+                self.schedule_spotlight_expiry(&session_id, spotlight.clone(), timeout);
+            }
+            Some(spotlight)
+        };
+
+        let mut result = Ok(());
+        let update = self
+            .store
+            .update(&session_id, |scene| match spotlight.clone() {
+                Some(spotlight) => result = scene.set_spotlight(spotlight),
+                None => {
+                    scene.clear_spotlight();
+                }
+            });
+        if let Err(e) = update {
+            return ToolResponse::error(format!("Failed to update spotlight: {e}"));
+        }
+        if let Err(e) = result {
+            return ToolResponse::error(format!("Failed to set spotlight: {e}"));
+        }
+
+        let mut metadata = self.session_metadata.write().await;
+        if let Some(session) = metadata.get_mut(&session_id) {
+            session.modified_at = chrono_now();
+        }
+        drop(metadata);
+
+        // Notify change callback
+        if let Some(ref callback) = self.on_change {
+            if let Some(scene) = self.store.get(&session_id) {
+                callback(&session_id, &scene);
+            }
+        }
+
+        ToolResponse::success(serde_json::json!({
+            "session_id": session_id,
+            "spotlight": spotlight,
+        }))
+    }
+
+    /// Call `canvas_camera` tool - fit the view to content or elements, or reset it.
+    async fn call_canvas_camera(&self, arguments: serde_json::Value) -> ToolResponse {
+        let session_id = extract_session_id(&arguments);
+
+        if self.store.get(&session_id).is_none() {
+            return ToolResponse::error(format!("Session not found: {session_id}"));
+        }
+
+        let command: CameraCommand = match serde_json::from_value(arguments.clone()) {
+            Ok(command) => command,
+            Err(e) => return ToolResponse::error(format!("Invalid camera command: {e}")),
+        };
+        let options: FitOptions = match serde_json::from_value(arguments) {
+            Ok(options) => options,
+            Err(e) => return ToolResponse::error(format!("Invalid fit options: {e}")),
+        };
+
+        let mut result = Err(format!("Session not found: {session_id}"));
+        let update = self.store.update(&session_id, |scene| {
+            result = command.apply(scene, &options).map_err(|e| e.to_string());
+        });
+        if let Err(e) = update {
+            return ToolResponse::error(format!("Failed to update camera: {e}"));
+        }
+        let frame = match result {
+            Ok(frame) => frame,
+            Err(e) => return ToolResponse::error(format!("Failed to move camera: {e}")),
+        };
+
+        let mut metadata = self.session_metadata.write().await;
+        if let Some(session) = metadata.get_mut(&session_id) {
+            session.modified_at = chrono_now();
+        }
+        drop(metadata);
+
+        // Notify change callback
+        if let Some(ref callback) = self.on_change {
+            if let Some(scene) = self.store.get(&session_id) {
+                callback(&session_id, &scene);
+            }
+        }
+
+        ToolResponse::success(serde_json::json!({
+            "session_id": session_id,
+            "camera": frame,
+        }))
+    }
+
+    /// Call `canvas_anchor` tool - save or delete a named view.
+    async fn call_canvas_anchor(&self, arguments: serde_json::Value) -> ToolResponse {
+        let session_id = extract_session_id(&arguments);
+        let Some(name) = arguments.get("name").and_then(|v| v.as_str()) else {
+            return ToolResponse::error("Missing required field: name");
+        };
+        let remove = arguments
+            .get("remove")
+            .and_then(serde_json::Value::as_bool)
+            .unwrap_or(false);
+        let element_ids = if arguments.get("element_ids").is_some() {
+            match extract_element_ids(&arguments) {
+                Ok(ids) => Some(ids),
+                Err(response) => return response,
+            }
+        } else {
+            None
+        };
+        let options: FitOptions = match serde_json::from_value(arguments.clone()) {
+            Ok(options) => options,
+            Err(e) => return ToolResponse::error(format!("Invalid fit options: {e}")),
+        };
+        #[allow(clippy::cast_possible_truncation)]
+        let number = |key: &str| {
+            arguments
+                .get(key)
+                .and_then(serde_json::Value::as_f64)
+                .map(|v| v as f32)
+        };
+        let (x, y, zoom) = (number("x"), number("y"), number("zoom"));
+
+        let mut result = Err(format!("Session not found: {session_id}"));
+        let update = self.store.update(&session_id, |scene| {
+            if remove {
+                result = scene
+                    .remove_anchor(name)
+                    .ok_or_else(|| format!("No anchor named {name}"));
+                return;
+            }
+            let anchor = match (&element_ids, x, y) {
+                (Some(ids), _, _) => CameraCommand::FitToElements {
+                    element_ids: ids.clone(),
+                }
+                .target_frame(scene, &options)
+                .map(|frame| Anchor::from_frame(name, &frame, scene))
+                .map_err(|e| e.to_string()),
+                (None, Some(x), Some(y)) => Ok(Anchor::new(name, x, y, zoom.unwrap_or(1.0))),
+                (None, None, None) => {
+                    let mut anchor = Anchor::capture(name, scene);
+                    anchor.zoom = zoom.unwrap_or(anchor.zoom);
+                    Ok(anchor)
+                }
+                _ => Err("Provide both x and y".to_string()),
+            };
+            result = anchor.and_then(|anchor| {
+                scene
+                    .set_anchor(anchor.clone())
+                    .map(|()| anchor)
+                    .map_err(|e| e.to_string())
+            });
+        });
+        if let Err(e) = update {
+            return ToolResponse::error(format!("Failed to update anchors: {e}"));
+        }
+        let anchor = match result {
+            Ok(anchor) => anchor,
+            Err(e) => return ToolResponse::error(format!("Failed to update anchor: {e}")),
+        };
+
+        self.finish_structure_change(&session_id).await;
+        let anchors = self
+            .store
+            .get(&session_id)
+            .map(|scene| scene.anchors().to_vec())
+            .unwrap_or_default();
+        ToolResponse::success(serde_json::json!({
+            "session_id": session_id,
+            "anchor": anchor,
+            "removed": remove,
+            "anchors": anchors.iter().map(|a| a.name.as_str()).collect::<Vec<_>>(),
+        }))
+    }
+
+    /// Call `canvas_set_theme` tool - replace the scene's palettes and variables.
+    async fn call_canvas_set_theme(&self, arguments: serde_json::Value) -> ToolResponse {
+        let session_id = extract_session_id(&arguments);
+        let theme: Theme = match arguments.get("theme").cloned() {
+            Some(theme) => match serde_json::from_value(theme) {
+                Ok(theme) => theme,
+                Err(e) => return ToolResponse::error(format!("Invalid theme: {e}")),
+            },
+            None => return ToolResponse::error("Missing required field: theme"),
+        };
+
+        let mut result = Ok(());
+        let update = self.store.update(&session_id, |scene| {
+            result = scene.set_theme(theme.clone());
+        });
+        if let Err(e) = update {
+            return ToolResponse::error(format!("Failed to set theme: {e}"));
+        }
+        if let Err(e) = result {
+            return ToolResponse::error(format!("Invalid theme: {e}"));
+        }
+
+        self.finish_structure_change(&session_id).await;
+        ToolResponse::success(serde_json::json!({
+            "session_id": session_id,
+            "theme": theme,
+        }))
+    }
+
+    /// Call `canvas_branding` tool - set or clear the session's logo and
+    /// watermark.
+    async fn call_canvas_branding(&self, arguments: serde_json::Value) -> ToolResponse {
+        let session_id = extract_session_id(&arguments);
+        let clear = arguments
+            .get("clear")
+            .and_then(serde_json::Value::as_bool)
+            .unwrap_or(false);
+        let branding: Option<Branding> = if clear {
+            None
+        } else {
+            match serde_json::from_value(arguments.clone()) {
+                Ok(branding) => Some(branding),
+                Err(e) => return ToolResponse::error(format!("Invalid branding: {e}")),
+            }
+        };
+
+        let mut result = Ok(());
+        let update = self
+            .store
+            .update(&session_id, |scene| match branding.clone() {
+                Some(branding) => result = scene.set_branding(branding),
+                None => {
+                    scene.clear_branding();
+                }
+            });
+        if let Err(e) = update {
+            return ToolResponse::error(format!("Failed to set branding: {e}"));
+        }
+        if let Err(e) = result {
+            return ToolResponse::error(format!("Invalid branding: {e}"));
+        }
+
+        self.finish_structure_change(&session_id).await;
+        ToolResponse::success(serde_json::json!({
+            "session_id": session_id,
+            "branding": branding,
+        }))
+    }
+
+    /// Call `canvas_ocr` tool - read the text in an image and store it on
+    /// the image, where search and spotlight can find it.
+    async fn call_canvas_ocr(&self, arguments: serde_json::Value) -> ToolResponse {
+        let session_id = extract_session_id(&arguments);
+        let id = match extract_element_id(&arguments) {
+            Ok(id) => id,
+            Err(response) => return response,
+        };
+        let Some(engine) = self.ocr_engine.clone() else {
+            return ToolResponse::error(OcrError::NoEngine.to_string());
+        };
+        let src = match self
+            .store
+            .get(&session_id)
+            .and_then(|scene| scene.get_element(id).map(|element| element.kind.clone()))
+        {
+            Some(ElementKind::Image { src, .. }) => src,
+            Some(other) => {
+                return ToolResponse::error(format!(
+                    "Text can only be extracted from an image, not a {}",
+                    other.type_name()
+                ))
+            }
+            None => return ToolResponse::error(format!("Element not found: {id}")),
+        };
+        let (mime_type, bytes) = match ocr::image_bytes(&src, None) {
+            Ok(image) => image,
+            Err(e) => return ToolResponse::error(e.to_string()),
+        };
+
+        // Engines may shell out or run a model, so keep them off the runtime
+        let recognized = tokio::task::spawn_blocking(move || -> Result<_, OcrError> {
+            let mut text = engine.recognize(&bytes, &mime_type)?;
+            if text.engine.is_empty() {
+                text.engine = engine.name().to_string();
+            }
+            Ok(text)
+        })
+        .await;
+        let text = match recognized {
+            Ok(Ok(text)) => text,
+            Ok(Err(e)) => return ToolResponse::error(e.to_string()),
+            Err(e) => return ToolResponse::error(format!("OCR failed: {e}")),
+        };
+
+        let mut result = Ok(());
+        let stored = text.clone();
+        let update = self.store.update(&session_id, |scene| {
+            result = scene.set_extracted_text(id, stored);
+        });
+        if let Err(e) = update
+            .map_err(|e| e.to_string())
+            .and(result.map_err(|e| e.to_string()))
+        {
+            return ToolResponse::error(format!("Failed to store extracted text: {e}"));
+        }
+
+        self.finish_structure_change(&session_id).await;
+        ToolResponse::success(serde_json::json!({
+            "session_id": session_id,
+            "element_id": id.to_string(),
+            "engine": text.engine,
+            "text": text.text,
+            "blocks": text.blocks,
+        }))
+    }
+
+    /// Call `canvas_archive` tool - pack the session with its assets and
+    /// earlier archives, and keep it in the archive store.
+    async fn call_canvas_archive(&self, arguments: serde_json::Value) -> ToolResponse {
+        let session_id = extract_session_id(&arguments);
+        let Some(store) = self.archive_store.clone() else {
+            return ToolResponse::error(ArchiveError::NoStore.to_string());
+        };
+        let Some(scene) = self.store.get(&session_id) else {
+            return ToolResponse::error(format!("Session not found: {session_id}"));
+        };
+        let history = self
+            .archive_history
+            .read()
+            .await
+            .get(&session_id)
+            .cloned()
+            .unwrap_or_default();
+
+        let archive = SessionArchive::pack(&session_id, &scene, history, now_millis());
+        let reference = match store.put(&archive).await {
+            Ok(reference) => reference,
+            Err(e) => return ToolResponse::error(e.to_string()),
+        };
+        self.archive_history
+            .write()
+            .await
+            .insert(session_id.clone(), archive.lineage(&reference));
+
+        ToolResponse::success(serde_json::json!({
+            "session_id": session_id,
+            "reference": reference,
+            "store": store.name(),
+            "revision": archive.revision,
+            "archived_at": archive.archived_at,
+            "element_count": archive.scene.elements.len(),
+            "asset_count": archive.assets.len(),
+            "history": archive.history,
+        }))
+    }
+
+    /// Call `canvas_restore` tool - replace the session with an archive
+    /// fetched from the archive store by reference.
+    async fn call_canvas_restore(&self, arguments: serde_json::Value) -> ToolResponse {
+        let session_id = extract_session_id(&arguments);
+        let Some(reference) = arguments.get("reference").and_then(|v| v.as_str()) else {
+            return ToolResponse::error("Missing required field: reference");
+        };
+        let Some(store) = self.archive_store.clone() else {
+            return ToolResponse::error(ArchiveError::NoStore.to_string());
+        };
+        let archive = match store.get(reference).await {
+            Ok(archive) => archive,
+            Err(e) => return ToolResponse::error(e.to_string()),
+        };
+        let scene = match archive.unpack() {
+            Ok(scene) => scene,
+            Err(e) => return ToolResponse::error(e.to_string()),
+        };
+        let element_count = scene.element_count();
+        if let Err(e) = self.store.replace(&session_id, scene) {
+            return ToolResponse::error(format!("Failed to restore archive: {e}"));
+        }
+
+        let viewport = archive.scene.viewport;
+        let mut metadata = self.session_metadata.write().await;
+        let entry = metadata.entry(session_id.clone()).or_insert_with(|| {
+            create_session_metadata(&session_id, viewport.width, viewport.height)
+        });
+        entry.width = viewport.width;
+        entry.height = viewport.height;
+        drop(metadata);
+        let history = archive.lineage(reference);
+        self.archive_history
+            .write()
+            .await
+            .insert(session_id.clone(), history.clone());
+
+        self.finish_structure_change(&session_id).await;
+        ToolResponse::success(serde_json::json!({
+            "session_id": session_id,
+            "reference": reference,
+            "archived_from": archive.session_id,
+            "revision": archive.revision,
+            "archived_at": archive.archived_at,
+            "element_count": element_count,
+            "asset_count": archive.assets.len(),
+            "history": history,
+        }))
+    }
+
+    /// Call `canvas_data` tool - put, append to or remove a dataset.
+    async fn call_canvas_data(&self, arguments: serde_json::Value) -> ToolResponse {
+        let session_id = extract_session_id(&arguments);
+        let Some(id) = arguments.get("dataset").and_then(|v| v.as_str()) else {
+            return ToolResponse::error("Missing required field: dataset");
+        };
+        let rows = || -> Result<Vec<Vec<serde_json::Value>>, ToolResponse> {
+            arguments.get("rows").cloned().map_or_else(
+                || Ok(Vec::new()),
+                |rows| {
+                    serde_json::from_value(rows)
+                        .map_err(|e| ToolResponse::error(format!("Invalid rows: {e}")))
+                },
+            )
+        };
+        let mode = arguments
+            .get("mode")
+            .and_then(|v| v.as_str())
+            .unwrap_or("put");
+        let delta = match mode {
+            "put" => {
+                let columns = match arguments
+                    .get("columns")
+                    .cloned()
+                    .map(serde_json::from_value)
+                {
+                    Some(Ok(columns)) => columns,
+                    Some(Err(e)) => return ToolResponse::error(format!("Invalid columns: {e}")),
+                    None => return ToolResponse::error("Missing required field: columns"),
+                };
+                match rows() {
+                    Ok(rows) => DatasetDelta::Put {
+                        dataset: Dataset::new(id, columns, rows),
+                    },
+                    Err(response) => return response,
+                }
+            }
+            "append" => match rows() {
+                Ok(rows) => DatasetDelta::Append {
+                    id: id.to_string(),
+                    rows,
+                    keep_last: arguments
+                        .get("keep_last")
+                        .and_then(serde_json::Value::as_u64)
+                        .and_then(|n| usize::try_from(n).ok()),
+                },
+                Err(response) => return response,
+            },
+            "remove" => DatasetDelta::Remove { id: id.to_string() },
+            other => return ToolResponse::error(format!("Unknown mode: {other}")),
+        };
+
+        let mut result = Ok(Vec::new());
+        let update = self.store.update(&session_id, |scene| {
+            result = scene.apply_data_delta(&delta);
+        });
+        if let Err(e) = update {
+            return ToolResponse::error(format!("Failed to update dataset: {e}"));
+        }
+        let refreshed = match result {
+            Ok(refreshed) => refreshed,
+            Err(e) => return ToolResponse::error(format!("Invalid dataset change: {e}")),
+        };
+
+        self.finish_structure_change(&session_id).await;
+        let rows = self
+            .store
+            .get(&session_id)
+            .and_then(|scene| scene.data().get(id).map(|dataset| dataset.rows.len()));
+        ToolResponse::success(serde_json::json!({
+            "session_id": session_id,
+            "dataset": id,
+            "mode": mode,
+            "rows": rows,
+            "refreshed_charts": refreshed.iter().map(ToString::to_string).collect::<Vec<_>>(),
+        }))
+    }
+
+    /// Clear `spotlight` after `timeout_ms` unless it has been replaced since.
+    fn schedule_spotlight_expiry(&self, session_id: &str, spotlight: Spotlight, timeout_ms: u64) {
+        let store = self.store.clone();
+        let on_change = self.on_change.clone();
+        let session_id = session_id.to_string();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(timeout_ms)).await;
+
+            let mut cleared = false;
+            let _ = store.update(&session_id, |scene| {
+                if scene.spotlight() == Some(&spotlight) {
+                    scene.clear_spotlight();
+                    cleared = true;
+                }
+            });
+            if cleared {
+                if let (Some(callback), Some(scene)) = (on_change, store.get(&session_id)) {
+                    callback(&session_id, &scene);
+                }
+            }
+        });
+    }
+
+    /// Call `canvas_save_template` tool - save elements as a named template.
+    async fn call_canvas_save_template(&self, arguments: serde_json::Value) -> ToolResponse {
+        let session_id = extract_session_id(&arguments);
+        let Some(name) = arguments.get("name").and_then(|v| v.as_str()) else {
+            return ToolResponse::error("Missing required field: name");
+        };
+        let element_ids: Vec<ElementId> = match arguments
+            .get("element_ids")
+            .and_then(|v| serde_json::from_value::<Vec<String>>(v.clone()).ok())
+            .ok_or("Missing required field: element_ids")
+            .and_then(|ids| {
+                ids.iter()
+                    .map(|id| ElementId::parse(id).map_err(|_| "Invalid element_ids"))
+                    .collect()
+            }) {
+            Ok(ids) => ids,
+            Err(e) => return ToolResponse::error(e),
+        };
+
+        let Some(scene) = self.store.get(&session_id) else {
+            return ToolResponse::error(format!("Session not found: {session_id}"));
+        };
+        let mut template = match Template::capture(name, &scene, &element_ids) {
+            Ok(template) => template,
+            Err(e) => return ToolResponse::error(format!("Failed to save template: {e}")),
+        };
+        if let Some(description) = arguments.get("description").and_then(|v| v.as_str()) {
+            template = template.with_description(description);
+        }
+
+        let element_count = template.elements.len();
+        let replaced = self.templates.write().await.insert(template).is_some();
+
+        ToolResponse::success(serde_json::json!({
+            "name": name,
+            "uri": format!("canvas://template/{name}"),
+            "element_count": element_count,
+            "replaced": replaced
+        }))
+    }
+
+    /// Call `canvas_instantiate_template` tool - stamp a template onto the canvas.
+    async fn call_canvas_instantiate_template(&self, arguments: serde_json::Value) -> ToolResponse {
+        let session_id = extract_session_id(&arguments);
+        let Some(name) = arguments.get("name").and_then(|v| v.as_str()) else {
+            return ToolResponse::error("Missing required field: name");
+        };
+        let Some(template) = self.template(name).await else {
+            return ToolResponse::error(format!("Template not found: {name}"));
+        };
+        let (x, y) = template_offset(&arguments);
+
+        let mut created = Vec::new();
+        let _ = self.store.get_or_create(&session_id);
+        if let Err(e) = self.store.update(&session_id, |scene| {
+            created = scene.instantiate_template(&template, x, y);
+        }) {
+            return ToolResponse::error(format!("Failed to instantiate template: {e}"));
+        }
+
+        let element_count = self.store.get(&session_id).map_or(0, |s| s.element_count());
+        let mut metadata = self.session_metadata.write().await;
+        let session = metadata
+            .entry(session_id.clone())
+            .or_insert_with(|| create_session_metadata(&session_id, 800.0, 600.0));
+        update_session_metadata(session, element_count);
+        drop(metadata);
+
+        if let Some(ref callback) = self.on_change {
+            if let Some(scene) = self.store.get(&session_id) {
+                callback(&session_id, &scene);
+            }
+        }
+
+        let root_ids: Vec<String> = created
+            .iter()
+            .zip(&template.elements)
+            .filter(|(_, source)| source.parent.is_none())
+            .map(|(id, _)| id.to_string())
+            .collect();
+        ToolResponse::success(serde_json::json!({
+            "session_id": session_id,
+            "template": name,
+            "element_ids": created.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            "root_ids": root_ids,
+            "element_count": element_count
+        }))
+    }
+
+    /// Where a `canvas_instantiate_template` call would place its template.
+    ///
+    /// Returns `None` if the template does not exist or is empty.
+    async fn template_placement(&self, arguments: &serde_json::Value) -> Option<SceneBounds> {
+        let name = arguments.get("name").and_then(|v| v.as_str())?;
+        let bounds = self.templates.read().await.get(name)?.bounds()?;
+        let (x, y) = template_offset(arguments);
+        Some(SceneBounds {
+            min_x: bounds.min_x + x,
+            min_y: bounds.min_y + y,
+            max_x: bounds.max_x + x,
+            max_y: bounds.max_y + y,
+        })
+    }
+
+    /// Call `canvas_begin_transaction`, `canvas_commit_transaction` or
+    /// `canvas_rollback_transaction`.
+    async fn call_transaction_tool(
+        &self,
+        name: &str,
+        arguments: &serde_json::Value,
+        agent_id: Option<&str>,
+    ) -> ToolResponse {
+        if name == "canvas_begin_transaction" {
+            return self.begin_transaction(arguments, agent_id).await;
+        }
+
+        let Some(transaction_id) = arguments.get("transaction_id").and_then(|v| v.as_str()) else {
+            return ToolResponse::error("Missing required field: transaction_id");
+        };
+        if let Err(response) = self.open_transaction(transaction_id, agent_id).await {
+            return response;
+        }
+        let Some(tx) = self.transactions.write().await.remove(transaction_id) else {
+            return ToolResponse::error(format!("Transaction not found: {transaction_id}"));
+        };
+        let staged = tx.staged.read().await.len();
+
+        if name == "canvas_rollback_transaction" {
+            return ToolResponse::success(serde_json::json!({
+                "session_id": tx.session_id,
+                "transaction_id": transaction_id,
+                "rolled_back": true,
+                "operations": staged
+            }));
+        }
+
+        match self.commit_transaction(&tx).await {
+            Ok(patch) => ToolResponse::success(serde_json::json!({
+                "session_id": tx.session_id,
+                "transaction_id": transaction_id,
+                "committed": true,
+                "operations": staged,
+                "patch": patch
+            })),
+            Err(e) => ToolResponse::error(format!("Failed to commit transaction: {e}")),
+        }
+    }
+
+    /// Open a transaction on a copy of the session.
+    async fn begin_transaction(
+        &self,
+        arguments: &serde_json::Value,
+        agent_id: Option<&str>,
+    ) -> ToolResponse {
+        let session_id = extract_session_id(arguments);
+        let mut scratch = Self::new(SceneStore::new());
+        scratch.templates = Arc::clone(&self.templates);
+        scratch.ocr_engine.clone_from(&self.ocr_engine);
+        scratch.archive_store.clone_from(&self.archive_store);
+        let scene = self.store.get_or_create(&session_id);
+        if let Err(e) = scratch.store.replace(&session_id, scene) {
+            return ToolResponse::error(format!("Failed to begin transaction: {e}"));
+        }
+        if let Some(owners) = self.element_owners.read().await.get(&session_id) {
+            scratch
+                .element_owners
+                .write()
+                .await
+                .insert(session_id.clone(), owners.clone());
+        }
+
+        let transaction_id = uuid::Uuid::new_v4().to_string();
+        let tx = Transaction {
+            session_id: session_id.clone(),
+            agent_id: agent_id.map(str::to_string),
+            base: scratch.store.scene_document(&session_id),
+            scratch,
+            staged: RwLock::new(Vec::new()),
+            started: Instant::now(),
+        };
+        // Abandoned transactions would otherwise hold a scene copy forever
+        let mut transactions = self.transactions.write().await;
+        transactions.retain(|_, tx| tx.started.elapsed() < TRANSACTION_TTL);
+        let open = transactions
+            .values()
+            .filter(|tx| tx.agent_id.as_deref() == agent_id)
+            .count();
+        if open >= MAX_TRANSACTIONS_PER_AGENT {
+            return ToolResponse::error(format!(
+                "At most {MAX_TRANSACTIONS_PER_AGENT} transactions may be open at once; \
+                 commit or roll one back first"
+            ));
+        }
+        transactions.insert(transaction_id.clone(), Arc::new(tx));
+        drop(transactions);
+
+        ToolResponse::success(serde_json::json!({
+            "session_id": session_id,
+            "transaction_id": transaction_id
+        }))
+    }
+
+    /// Apply a transaction's staged changes to the live session as one
+    /// update, notifying the change callback once.
+    ///
+    /// If the session changed since the transaction began, only the elements
+    /// the transaction touched are written, so concurrent changes to other
+    /// elements are kept.
+    async fn commit_transaction(
+        &self,
+        tx: &Transaction,
+    ) -> Result<canvas_core::ScenePatch, String> {
+        let session_id = &tx.session_id;
+        let staged = tx.scratch.store.get_or_create(session_id);
+        let patch = tx.base.diff(&SceneDocument::from_scene(
+            session_id.as_str(),
+            &staged,
+            now_millis(),
+        ));
+        if patch.is_empty() {
+            return Ok(patch);
+        }
+
+        let _ = self.store.get_or_create(session_id);
+        let mut applied = Ok(());
+        self.store
+            .update(session_id, |scene| {
+                let current = SceneDocument::from_scene(session_id.as_str(), scene, 0);
+                if tx.base.diff(&current).is_empty() {
+                    // Untouched since begin: take the staged scene as-is,
+                    // which also keeps group membership intact
+                    *scene = staged;
+                    return;
+                }
+                // Apply to a draft so a failing op leaves the scene untouched
+                let mut draft = scene.clone();
+                applied = draft.apply_patch(&patch);
+                if applied.is_ok() {
+                    *scene = draft;
+                }
+            })
+            .map_err(|e| e.to_string())?;
+        applied.map_err(|e| e.to_string())?;
+
+        if let Some(staged_owners) = tx.scratch.element_owners.read().await.get(session_id) {
+            let mut owners = self.element_owners.write().await;
+            let session = owners.entry(session_id.clone()).or_default();
+            for op in &patch.ops {
+                if let canvas_core::PatchOp::Remove { id } = op {
+                    if let Ok(id) = ElementId::parse(id) {
+                        session.remove(&id);
+                    }
+                }
+            }
+            session.extend(staged_owners.iter().map(|(id, owner)| (*id, owner.clone())));
+        }
+
+        let element_count = self.store.get(session_id).map_or(0, |s| s.element_count());
+        let mut metadata = self.session_metadata.write().await;
+        let session = metadata
+            .entry(session_id.clone())
+            .or_insert_with(|| create_session_metadata(session_id, 800.0, 600.0));
+        update_session_metadata(session, element_count);
+        drop(metadata);
+
+        if let Some(ref callback) = self.on_change {
+            if let Some(scene) = self.store.get(session_id) {
+                callback(session_id, &scene);
+            }
+        }
+        Ok(patch)
+    }
+
+    /// Handle resources/list request.
+    async fn handle_resources_list(&self, id: serde_json::Value) -> JsonRpcResponse {
+        let session_ids = self.store.session_ids();
+
+        let mut resource_list: Vec<Resource> = session_ids
+            .iter()
+            .flat_map(|session_id| {
+                [
+                    Resource {
+                        uri: format!("canvas://session/{session_id}"),
+                        name: format!("Canvas Session: {session_id}"),
+                        description: "A canvas session with visual elements".to_string(),
+                        mime_type: Encoding::Json.mime_type().to_string(),
+                    },
+                    Resource {
+                        uri: format!("canvas://session/{session_id}?format=msgpack"),
+                        name: format!("Canvas Session: {session_id} (MessagePack)"),
+                        description: "The canvas session scene document as MessagePack".to_string(),
+                        mime_type: Encoding::MessagePack.mime_type().to_string(),
+                    },
+                ]
+            })
+            .collect();
+
+        // Session thumbnails, so agents can see what each canvas holds
+        if cfg!(feature = "preview") {
+            resource_list.extend(session_ids.iter().map(|session_id| Resource {
+                uri: format!("canvas://session/{session_id}/thumbnail.png"),
+                name: format!("Canvas Session: {session_id} (thumbnail)"),
+                description: "A PNG preview of the canvas session".to_string(),
+                mime_type: "image/png".to_string(),
+            }));
+        }
+
+        // Add chart templates
+        for chart_type in &["bar", "line", "pie", "area", "scatter"] {
+            let capitalized = capitalize(chart_type);
+            resource_list.push(Resource {
+                uri: format!("canvas://chart/{chart_type}"),
+                name: format!("{capitalized} Chart Template"),
+                description: format!("Template for creating {chart_type} charts"),
+                mime_type: "application/json".to_string(),
+            });
+        }
+
+        // Add saved element templates
+        for template in self.templates.read().await.iter() {
+            resource_list.push(Resource {
+                uri: format!("canvas://template/{}", template.name),
+                name: format!("Element Template: {}", template.name),
+                description: template.description.clone().unwrap_or_else(|| {
+                    format!("Reusable template of {} elements", template.elements.len())
+                }),
+                mime_type: Encoding::Json.mime_type().to_string(),
+            });
+        }
+
+        JsonRpcResponse::success(id, serde_json::json!({ "resources": resource_list }))
+    }
+
+    /// Handle resources/read request.
+    #[allow(clippy::needless_pass_by_value)]
+    async fn handle_resources_read(
+        &self,
+        id: serde_json::Value,
+        params: serde_json::Value,
+    ) -> JsonRpcResponse {
+        let uri = params
+            .get("uri")
+            .and_then(|v| v.as_str())
+            .unwrap_or_default();
+
+        let (path, format) = resources::uri::split_format(uri);
+        let encoding = match format.map(Encoding::from_name) {
+            None => Encoding::Json,
+            Some(Some(encoding)) => encoding,
+            Some(None) => {
+                return JsonRpcResponse::error(
+                    id,
+                    -32002,
+                    format!(
+                        "Unsupported resource format: {}",
+                        format.unwrap_or_default()
+                    ),
+                );
+            }
+        };
+
+        let content = match resources::uri::parse(path) {
+            // Live sessions are served from the scene store
+            Some(resources::uri::CanvasUri::Session(session_id))
+                if self.store.get(&session_id).is_some() =>
+            {
+                let document = self.store.scene_document(&session_id);
+                resources::encode_resource(&document, encoding)
+            }
+            Some(resources::uri::CanvasUri::Thumbnail(session_id)) => {
+                let redact = resources::uri::query_param(uri, "redact")
+                    .map(|name| {
+                        serde_json::from_value(serde_json::json!(name))
+                            .map_err(|_| format!("Unsupported redaction: {name}"))
+                    })
+                    .transpose();
+                match (self.store.get(&session_id), redact) {
+                    (_, Err(e)) => Err(e),
+                    (Some(scene), Ok(redact)) => render_thumbnail(&scene, redact),
+                    (None, _) => Err(format!("Session {session_id} not found")),
+                }
+            }
+            Some(resources::uri::CanvasUri::Template(name)) => {
+                match self.templates.read().await.get(&name) {
+                    Some(template) => resources::encode_resource(template, encoding),
+                    None => Err(format!("Template {name} not found")),
+                }
+            }
+            _ => resources::get_resource(path),
+        };
+
+        match content {
+            Ok(content) => {
+                let mut entry = serde_json::json!({ "uri": uri });
+                match content {
+                    crate::ResourceContent::Json(v) => {
+                        entry["mimeType"] = serde_json::json!("application/json");
+                        entry["text"] =
+                            serde_json::json!(serde_json::to_string_pretty(&v).unwrap_or_default());
+                    }
+                    crate::ResourceContent::Text(s) => {
+                        entry["mimeType"] = serde_json::json!("text/plain");
+                        entry["text"] = serde_json::json!(s);
+                    }
+                    crate::ResourceContent::Binary { data, mime_type } => {
+                        entry["mimeType"] = serde_json::json!(mime_type);
+                        entry["blob"] = serde_json::json!(data);
+                    }
+                }
+                JsonRpcResponse::success(id, serde_json::json!({ "contents": [entry] }))
+            }
+            Err(e) => JsonRpcResponse::error(id, -32002, e),
+        }
+    }
+}
+
+// ============================================================================
+// Utility Functions
+// ============================================================================
+
+/// Get current Unix time in milliseconds.
+fn now_millis() -> u64 {
+    use std::time::{SystemTime, UNIX_EPOCH};
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| u64::try_from(d.as_millis()).unwrap_or(u64::MAX))
+}
+
+/// Get current timestamp in ISO 8601 format.
+fn chrono_now() -> String {
+    use std::time::{SystemTime, UNIX_EPOCH};
+    let duration = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    let secs = duration.as_secs();
+    format!("{secs}")
+}
+
+/// Detect image format from source string (URL or data URI).
+fn detect_image_format(src: &str) -> ImageFormat {
+    let src_lower = src.to_lowercase();
+    if src_lower.contains("png") || src_lower.starts_with("data:image/png") {
+        ImageFormat::Png
+    } else if src_lower.contains("jpg")
+        || src_lower.contains("jpeg")
+        || src_lower.starts_with("data:image/jpeg")
+    {
+        ImageFormat::Jpeg
+    } else if src_lower.contains("svg") || src_lower.starts_with("data:image/svg") {
+        ImageFormat::Svg
+    } else if src_lower.contains("webp") || src_lower.starts_with("data:image/webp") {
+        ImageFormat::WebP
+    } else {
+        // Default to PNG
+        ImageFormat::Png
+    }
+}
+
+/// Capitalize the first letter of a string.
+fn capitalize(s: &str) -> String {
+    let mut chars = s.chars();
+    match chars.next() {
+        None => String::new(),
+        Some(c) => c.to_uppercase().chain(chars).collect(),
+    }
+}
+
+// ============================================================================
+// Tool Schemas
+// ============================================================================
+
+/// Get the list of available MCP tools.
+#[allow(clippy::too_many_lines)]
+fn get_available_tools() -> Vec<Tool> {
+    let mut tools = vec![
+        Tool {
+            name: "canvas_render".to_string(),
+            description: "Render content (chart, image, text, 3D model) to the canvas".to_string(),
+            input_schema: render_tool_schema(),
+        },
+        Tool {
+            name: "canvas_render_a2ui".to_string(),
+            description: "Render an A2UI component tree to the canvas. Supports Container, Text, Image, Button, Chart, Sparkline, and VideoFeed components with automatic layout.".to_string(),
+            input_schema: render_a2ui_tool_schema(),
+        },
+        Tool {
+            name: "canvas_interact".to_string(),
+            description: "Report user interaction (touch, voice, selection) on the canvas"
+                .to_string(),
+            input_schema: interact_tool_schema(),
+        },
+        Tool {
+            name: "canvas_export".to_string(),
+            description: "Export the canvas, or a single element, to an image or PDF"
+                .to_string(),
+            input_schema: export_tool_schema(),
+        },
+        Tool {
+            name: "canvas_clear".to_string(),
+            description: "Clear all elements from the canvas".to_string(),
+            input_schema: clear_tool_schema(),
+        },
+        Tool {
+            name: "canvas_add_element".to_string(),
+            description: "Add an element to the canvas with full control over type, transform, and properties".to_string(),
+            input_schema: add_element_tool_schema(),
+        },
+        Tool {
+            name: "canvas_remove_element".to_string(),
+            description: "Remove an element from the canvas by its ID".to_string(),
+            input_schema: remove_element_tool_schema(),
+        },
+        Tool {
+            name: "canvas_update_element".to_string(),
+            description: "Update an existing element's transform or properties".to_string(),
+            input_schema: update_element_tool_schema(),
+        },
+        Tool {
+            name: "canvas_group".to_string(),
+            description: "Wrap sibling elements in a new group without moving them".to_string(),
+            input_schema: group_tool_schema(),
+        },
+        Tool {
+            name: "canvas_ungroup".to_string(),
+            description: "Dissolve a group, keeping its children where they are".to_string(),
+            input_schema: remove_element_tool_schema(),
+        },
+        Tool {
+            name: "canvas_poll".to_string(),
+            description: "Add a poll: peers vote by tapping an option and see live result bars. Read the tally with canvas_poll_results; close voting with canvas_update_element data {\"closed\": true}.".to_string(),
+            input_schema: poll_tool_schema(),
+        },
+        Tool {
+            name: "canvas_agenda".to_string(),
+            description: "Drive a meeting agenda added with canvas_render (type Agenda): start it now or after a countdown (in_secs), advance to the next item, go back, jump to an item, reset, or read its status. Every participant sees the running item highlighted with the time left.".to_string(),
+            input_schema: agenda_tool_schema(),
+        },
+        Tool {
+            name: "canvas_log_append".to_string(),
+            description: "Stream output into a log view added with canvas_render (type LogView): appends text line by line, keeping ANSI colors, and drops the oldest lines beyond max_lines. Set clear to start over. Ideal for build, test or long tool output.".to_string(),
+            input_schema: log_append_tool_schema(),
+        },
+        Tool {
+            name: "canvas_tree".to_string(),
+            description: "Add an org chart or decision tree from nested nodes ({label, detail, children}), laid out automatically as a tidy tree. Peers tap a node to collapse or expand its branch.".to_string(),
+            input_schema: tree_tool_schema(),
+        },
+        Tool {
+            name: "canvas_barcode".to_string(),
+            description: "Add a scannable QR code or Code 128 barcode generated from a payload: a link, Wi-Fi credentials (wifi: {ssid, password}) or a ticket number. Drawn as crisp squares at any size.".to_string(),
+            input_schema: barcode_tool_schema(),
+        },
+        Tool {
+            name: "canvas_annotate".to_string(),
+            description: "Point at part of the canvas while explaining it: a measurement line between two points labeled with its length (dimension or ruler), an arrow, a tinted region highlight with a caption, an ink stroke or a text note. With element_id, coordinates are relative to that element, e.g. a chart or video frame; marks over an image are pinned to it so they move and scale with it, and canvas_export with flatten renders the marked-up image.".to_string(),
+            input_schema: annotate_tool_schema(),
+        },
+        Tool {
+            name: "canvas_poll_results".to_string(),
+            description: "Get a poll's aggregated results: votes and share per option, total votes and whether voting is closed".to_string(),
+            input_schema: remove_element_tool_schema(),
+        },
+        Tool {
+            name: "canvas_get_scene".to_string(),
+            description: "Get the current scene state as a JSON document; with redact=true sensitive elements are replaced with placeholders, for sharing outside the team".to_string(),
+            input_schema: scene_document_tool_schema(),
+        },
+        Tool {
+            name: "canvas_validate".to_string(),
+            description: "Check the scene for integrity issues (orphaned group children, duplicate IDs, NaN transforms, zero-size elements) and report statistics (counts per kind, bounds, memory estimate)".to_string(),
+            input_schema: get_scene_tool_schema(),
+        },
+        Tool {
+            name: "canvas_measure".to_string(),
+            description: "Measure on the canvas in the scene's world units: the distance, x/y offsets and angle (degrees counterclockwise from the x axis) between start and end, or, without points, every measurement line on the canvas. Persist a measurement with canvas_annotate type measurement.".to_string(),
+            input_schema: measure_tool_schema(),
+        },
+        Tool {
+            name: "canvas_lint".to_string(),
+            description: "Check the scene for layout and legibility problems: overlapping text, tiny fonts, low-contrast text, elements outside the canvas and callouts or attachments pointing at missing elements. Each finding names the element and a suggested fix (move, set_font_size, set_color, clear_callout_target, detach); with apply=true the fixes are made and the remaining findings returned.".to_string(),
+            input_schema: lint_tool_schema(),
+        },
+        Tool {
+            name: "canvas_spotlight".to_string(),
+            description: "Highlight elements by dimming everything else, optionally zooming to fit them. Call with clear=true to remove the spotlight.".to_string(),
+            input_schema: spotlight_tool_schema(),
+        },
+        Tool {
+            name: "canvas_camera".to_string(),
+            description: "Move the viewer camera: fit_to_content frames every element, fit_to_elements frames the given elements, reset_view returns to 100% zoom, go_to_anchor moves to a named anchor.".to_string(),
+            input_schema: camera_tool_schema(),
+        },
+        Tool {
+            name: "canvas_anchor".to_string(),
+            description: "Save a named view (anchor) that canvas_camera's go_to_anchor can return to: frame the given elements, center x/y at a zoom, or capture the current view. Call with remove=true to delete it.".to_string(),
+            input_schema: anchor_tool_schema(),
+        },
+        Tool {
+            name: "canvas_set_theme".to_string(),
+            description: "Replace the scene theme: named palettes and color variables that element colors reference as palette(name, index) and var(--name). Every referencing element restyles at once.".to_string(),
+            input_schema: set_theme_tool_schema(),
+        },
+        Tool {
+            name: "canvas_branding".to_string(),
+            description: "Stamp a logo and/or watermark text over the canvas for every viewer, pinned to a corner or the center of the screen, and into PNG, PDF and quilt exports. Call with clear=true to remove it.".to_string(),
+            input_schema: branding_tool_schema(),
+        },
+        Tool {
+            name: "canvas_data".to_string(),
+            description: "Store, extend or remove a named dataset. Charts whose binding names the dataset re-render from it, and viewers receive only the change.".to_string(),
+            input_schema: data_tool_schema(),
+        },
+        Tool {
+            name: "canvas_ocr".to_string(),
+            description: "Read the text in an image element with the server's OCR engine. The text is stored on the image, so canvas_get_scene shows it and canvas_spotlight can find the image by the words in it.".to_string(),
+            input_schema: remove_element_tool_schema(),
+        },
+        Tool {
+            name: "canvas_archive".to_string(),
+            description: "Archive the session (scene, image and model content, and the list of earlier archives) to the server's archive store, such as the user's Communitas space. Returns a reference that canvas_restore accepts.".to_string(),
+            input_schema: get_scene_tool_schema(),
+        },
+        Tool {
+            name: "canvas_restore".to_string(),
+            description: "Replace the session with an archive made by canvas_archive, fetched from the archive store by reference. The archive's history carries over, so later archives list it as an earlier version.".to_string(),
+            input_schema: restore_tool_schema(),
+        },
+        Tool {
+            name: "canvas_save_template".to_string(),
+            description: "Save elements (with their children) as a named template, readable at canvas://template/{name}".to_string(),
+            input_schema: save_template_tool_schema(),
+        },
+        Tool {
+            name: "canvas_instantiate_template".to_string(),
+            description: "Stamp a saved template onto the canvas at a position; every copy gets new element IDs".to_string(),
+            input_schema: instantiate_template_tool_schema(),
+        },
+        Tool {
+            name: "canvas_begin_transaction".to_string(),
+            description: "Begin a transaction: mutating tool calls that pass the returned transaction_id are staged instead of applied".to_string(),
+            input_schema: begin_transaction_tool_schema(),
+        },
+        Tool {
+            name: "canvas_commit_transaction".to_string(),
+            description: "Apply every staged call of a transaction as one atomic change with a single broadcast".to_string(),
+            input_schema: transaction_tool_schema(),
+        },
+        Tool {
+            name: "canvas_rollback_transaction".to_string(),
+            description: "Discard a transaction and everything staged in it".to_string(),
+            input_schema: transaction_tool_schema(),
+        },
+    ];
+
+    for tool in &mut tools {
+        if !MUTATING_TOOLS.contains(&tool.name.as_str()) {
+            continue;
+        }
+        if let Some(properties) = tool
+            .input_schema
+            .get_mut("properties")
+            .and_then(serde_json::Value::as_object_mut)
+        {
+            properties.insert("dry_run".to_string(), dry_run_property());
+            properties.insert("preview".to_string(), preview_property());
+            properties.insert("transaction_id".to_string(), transaction_id_property());
+        }
+    }
+    tools
+}
+
+/// Common `dry_run` property schema for mutating tools.
+fn dry_run_property() -> serde_json::Value {
+    serde_json::json!({
+        "type": "boolean",
+        "description": "Validate and return the resulting scene patch without applying it",
+        "default": false
+    })
+}
+
+/// Common `preview` property schema for mutating tools.
+fn preview_property() -> serde_json::Value {
+    serde_json::json!({
+        "type": "boolean",
+        "description": "With dry_run, also return a PNG thumbnail of the resulting scene",
+        "default": false
+    })
+}
+
+/// Common `transaction_id` property schema.
+fn transaction_id_property() -> serde_json::Value {
+    serde_json::json!({
+        "type": "string",
+        "description": "Stage this call in an open transaction instead of applying it"
+    })
+}
+
+/// Common `session_id` property schema.
+fn session_id_property() -> serde_json::Value {
+    serde_json::json!({
+        "type": "string",
+        "description": "Canvas session ID (defaults to 'default')",
+        "default": "default"
+    })
+}
+
+/// Common `element_id` property schema.
+fn element_id_property() -> serde_json::Value {
+    serde_json::json!({
+        "type": "string",
+        "description": "Unique element identifier (UUID format)"
+    })
+}
+
+/// `canvas_render` content schema for a `FloorPlan`.
+fn floor_plan_content_schema() -> serde_json::Value {
+    serde_json::json!({
+        "type": "object",
+        "properties": {
+            "type": { "const": "FloorPlan" },
+            "data": {
+                "type": "object",
+                "properties": {
+                    "background": { "type": "string", "description": "Background image URL or data URI, stretched over the plan" },
+                    "plan_width": { "type": "number", "description": "Plan width in plan units (default 1000)" },
+                    "plan_height": { "type": "number", "description": "Plan height in plan units (default 600)" },
+                    "paths": {
+                        "type": "array",
+                        "description": "Walls and outlines",
+                        "items": {
+                            "type": "object",
+                            "properties": {
+                                "points": { "type": "array", "items": { "type": "array", "items": { "type": "number" }, "minItems": 2, "maxItems": 2 } },
+                                "closed": { "type": "boolean", "default": false }
+                            },
+                            "required": ["points"]
+                        }
+                    },
+                    "markers": {
+                        "type": "array",
+                        "maxItems": 500,
+                        "items": {
+                            "type": "object",
+                            "properties": {
+                                "id": { "type": "string", "description": "Unique marker ID, e.g. \"A1\"" },
+                                "label": { "type": "string", "description": "Text shown on the marker (default: the ID)" },
+                                "shape": { "type": "string", "enum": ["seat", "zone"], "default": "seat" },
+                                "x": { "type": "number", "description": "Center X in plan units" },
+                                "y": { "type": "number", "description": "Center Y in plan units" },
+                                "width": { "type": "number", "description": "Plan units (default 24 for seats, 120 for zones)" },
+                                "height": { "type": "number", "description": "Plan units (default 24 for seats, 80 for zones)" },
+                                "occupancy": { "type": "string", "enum": ["free", "occupied", "reserved", "blocked"], "default": "free" },
+                                "occupant": { "type": "string", "description": "Who occupies or reserved it" }
+                            },
+                            "required": ["id", "x", "y"]
+                        }
+                    },
+                    "locked": { "type": "boolean", "description": "Stop users dragging markers", "default": false }
+                }
+            }
+        },
+        "required": ["type", "data"]
+    })
+}
+
+/// `canvas_render` content schema for an `Agenda`.
+fn agenda_content_schema() -> serde_json::Value {
+    serde_json::json!({
+        "type": "object",
+        "properties": {
+            "type": { "const": "Agenda" },
+            "data": {
+                "type": "object",
+                "properties": {
+                    "title": { "type": "string", "description": "Heading, e.g. the meeting name" },
+                    "items": {
+                        "type": "array",
+                        "minItems": 1,
+                        "maxItems": MAX_AGENDA_ITEMS,
+                        "items": {
+                            "type": "object",
+                            "properties": {
+                                "title": { "type": "string" },
+                                "duration_secs": { "type": "integer", "minimum": 1, "maximum": MAX_AGENDA_ITEM_SECS },
+                                "owner": { "type": "string", "description": "Who leads the item" }
+                            },
+                            "required": ["title", "duration_secs"]
+                        }
+                    },
+                    "starts_in_secs": { "type": "integer", "minimum": 0, "description": "Count down to a start this many seconds from now; omit to start with canvas_agenda" }
+                },
+                "required": ["items"]
+            }
+        },
+        "required": ["type", "data"]
+    })
+}
+
+/// Threshold and number format properties shared by gauges and KPI cards.
+fn dashboard_properties() -> (serde_json::Value, serde_json::Value) {
+    (
+        serde_json::json!({
+            "type": "array",
+            "description": "Colors from each threshold value upwards, e.g. [{\"value\": 0, \"color\": \"#c62828\"}, {\"value\": 70, \"color\": \"#2e7d32\"}]",
+            "maxItems": 8,
+            "items": {
+                "type": "object",
+                "properties": {
+                    "value": { "type": "number" },
+                    "color": { "type": "string", "description": "Hex color, var(--name) or palette(name, index)" }
+                },
+                "required": ["value", "color"]
+            }
+        }),
+        serde_json::json!({
+            "type": "object",
+            "description": "How numbers are printed, e.g. {\"prefix\": \"$\", \"compact\": true} for $1.2M",
+            "properties": {
+                "decimals": { "type": "integer", "minimum": 0, "maximum": 6, "default": 0 },
+                "prefix": { "type": "string" },
+                "suffix": { "type": "string" },
+                "compact": { "type": "boolean", "description": "Abbreviate as k, M and B", "default": false }
+            }
+        }),
+    )
+}
+
+/// `canvas_render` content schema for a `Gauge`.
+fn gauge_content_schema() -> serde_json::Value {
+    let (thresholds, format) = dashboard_properties();
+    serde_json::json!({
+        "type": "object",
+        "properties": {
+            "type": { "const": "Gauge" },
+            "data": {
+                "type": "object",
+                "properties": {
+                    "value": { "type": "number" },
+                    "min": { "type": "number", "default": 0 },
+                    "max": { "type": "number", "default": 100 },
+                    "shape": { "type": "string", "enum": ["radial", "linear"], "default": "radial" },
+                    "label": { "type": "string", "description": "Caption under the value" },
+                    "thresholds": thresholds,
+                    "format": format
+                },
+                "required": ["value"]
+            }
+        },
+        "required": ["type", "data"]
+    })
+}
+
+/// `canvas_render` content schema for a `Kpi` card.
+fn kpi_content_schema() -> serde_json::Value {
+    let (thresholds, format) = dashboard_properties();
+    serde_json::json!({
+        "type": "object",
+        "properties": {
+            "type": { "const": "Kpi" },
+            "data": {
+                "type": "object",
+                "properties": {
+                    "label": { "type": "string", "description": "Metric name" },
+                    "value": { "type": "number" },
+                    "previous": { "type": "number", "description": "Earlier value; shows the change with an up or down arrow" },
+                    "sparkline": { "type": "array", "items": { "type": "number" }, "maxItems": 256, "description": "Recent values, oldest first" },
+                    "sparkline_kind": { "type": "string", "enum": ["line", "bar"], "default": "line" },
+                    "thresholds": thresholds,
+                    "format": format,
+                    "higher_is_better": { "type": "boolean", "description": "Color increases green and decreases red (false flips this)", "default": true }
+                },
+                "required": ["label", "value"]
+            }
+        },
+        "required": ["type", "data"]
+    })
+}
+
+/// Schema of a tree node; `children` nest the same shape.
+fn tree_node_property() -> serde_json::Value {
+    serde_json::json!({
+        "type": "object",
+        "description": "A node and its children, e.g. {\"label\": \"Ada\", \"detail\": \"CEO\", \"children\": [{\"label\": \"Grace\", \"detail\": \"CTO\"}]}",
+        "properties": {
+            "id": { "type": "string", "description": "Unique ID; defaults to the node's position, e.g. \"1.2\"" },
+            "label": { "type": "string" },
+            "detail": { "type": "string", "description": "Second line, e.g. a job title or answer" },
+            "color": { "type": "string", "description": "Box fill: hex color, var(--name) or palette(name, index)" },
+            "collapsed": { "type": "boolean", "description": "Hide the children", "default": false },
+            "children": {
+                "type": "array",
+                "items": { "type": "object" },
+                "description": "Child nodes with the same fields"
+            }
+        },
+        "required": ["label"]
+    })
+}
+
+/// `canvas_render` content schema for a `Tree`.
+fn tree_content_schema() -> serde_json::Value {
+    serde_json::json!({
+        "type": "object",
+        "properties": {
+            "type": { "const": "Tree" },
+            "data": {
+                "type": "object",
+                "properties": {
+                    "root": tree_node_property(),
+                    "direction": { "type": "string", "enum": ["down", "right"], "default": "down" }
+                },
+                "required": ["root"]
+            }
+        },
+        "required": ["type", "data"]
+    })
+}
+
+/// `canvas_render` content schema for a `Sparkline`.
+fn sparkline_content_schema() -> serde_json::Value {
+    serde_json::json!({
+        "type": "object",
+        "properties": {
+            "type": { "const": "Sparkline" },
+            "data": {
+                "type": "object",
+                "properties": {
+                    "values": { "type": "array", "items": { "type": "number" }, "maxItems": 256, "description": "Values, oldest first" },
+                    "kind": { "type": "string", "enum": ["line", "bar"], "default": "line" },
+                    "color": { "type": "string", "description": "Hex color, var(--name) or palette(name, index)", "default": "#1e88e5" }
+                },
+                "required": ["values"]
+            }
+        },
+        "required": ["type", "data"]
+    })
+}
+
+/// Common transform property schema.
+fn transform_property() -> serde_json::Value {
+    serde_json::json!({
+        "type": "object",
+        "description": "Element transform (position, size, rotation)",
+        "properties": {
+            "x": { "type": "number", "description": "X position in pixels" },
+            "y": { "type": "number", "description": "Y position in pixels" },
+            "width": { "type": "number", "description": "Width in pixels" },
+            "height": { "type": "number", "description": "Height in pixels" },
+            "rotation": { "type": "number", "description": "Rotation in degrees" },
+            "z_index": { "type": "integer", "description": "Stack order (higher = front)" },
+            "auto_size": {
+                "type": "boolean",
+                "description": "Size the element to fit its content (default: true when width and height are omitted)"
+            }
+        }
+    })
+}
+
+/// Common visibility property schema.
+fn visible_property() -> serde_json::Value {
+    serde_json::json!({
+        "type": "boolean",
+        "description": "Whether the element is drawn and can be tapped; hiding a group hides its contents",
+        "default": true
+    })
+}
+
+/// Common element opacity property schema.
+fn opacity_property() -> serde_json::Value {
+    serde_json::json!({
+        "type": "number",
+        "minimum": 0,
+        "maximum": 1,
+        "description": "Opacity of the whole element, multiplied with style.opacity",
+        "default": 1
+    })
+}
+
+/// Common screen anchoring property schema.
+fn screen_anchored_property() -> serde_json::Value {
+    serde_json::json!({
+        "type": "boolean",
+        "description": "Pin the element to the viewport (logos, timers, status badges): its transform is in screen pixels and ignores pan and zoom",
+        "default": false
+    })
+}
+
+/// Common sensitivity property schema.
+fn sensitive_property() -> serde_json::Value {
+    serde_json::json!({
+        "type": "boolean",
+        "description": "Mark the element confidential: exports with redact blur it or replace it with a placeholder, children included",
+        "default": false
+    })
+}
+
+/// Common attachment property schema.
+fn attach_to_property() -> serde_json::Value {
+    let point = serde_json::json!({
+        "type": "string",
+        "enum": ["top_left", "top", "top_right", "left", "center", "right", "bottom_left", "bottom", "bottom_right"],
+        "default": "center"
+    });
+    serde_json::json!({
+        "type": ["object", "null"],
+        "description": "Position the element relative to another one so it follows when the target moves or resizes (labels, badges, callouts). The element's 'origin' point is placed on the target's 'at' point, plus the offset. Moving the element directly adjusts the offset. null detaches it.",
+        "properties": {
+            "target": { "type": "string", "description": "ID of the element to follow" },
+            "at": point,
+            "origin": point,
+            "offset_x": { "type": "number", "default": 0 },
+            "offset_y": { "type": "number", "default": 0 }
+        },
+        "required": ["target"]
+    })
+}
+
+/// Common style property schema.
+fn style_property() -> serde_json::Value {
+    let stops = serde_json::json!({
+        "type": "array",
+        "items": {
+            "type": "object",
+            "properties": {
+                "offset": { "type": "number", "description": "Position along the gradient (0-1)" },
+                "color": { "type": "string", "description": "CSS color" }
+            },
+            "required": ["offset", "color"]
+        }
+    });
+    serde_json::json!({
+        "type": "object",
+        "description": "Visual style (fill, stroke, corner radius, shadow, opacity)",
+        "properties": {
+            "fill": {
+                "type": "object",
+                "description": "Solid color or gradient fill",
+                "properties": {
+                    "type": { "type": "string", "enum": ["solid", "linear_gradient", "radial_gradient"] },
+                    "color": { "type": "string", "description": "CSS color (solid fills)" },
+                    "angle": { "type": "number", "description": "Linear gradient angle in radians" },
+                    "center_x": { "type": "number", "description": "Radial center X (0-1)" },
+                    "center_y": { "type": "number", "description": "Radial center Y (0-1)" },
+                    "radius": { "type": "number", "description": "Radial radius (0-1)" },
+                    "stops": stops
+                },
+                "required": ["type"]
+            },
+            "stroke": {
+                "type": "object",
+                "properties": {
+                    "color": { "type": "string" },
+                    "width": { "type": "number" }
+                },
+                "required": ["color", "width"]
+            },
+            "corner_radius": { "type": "number", "description": "Corner radius in pixels" },
+            "shadow": {
+                "type": "object",
+                "properties": {
+                    "color": { "type": "string" },
+                    "offset_x": { "type": "number" },
+                    "offset_y": { "type": "number" },
+                    "blur": { "type": "number" }
+                },
+                "required": ["color"]
+            },
+            "opacity": { "type": "number", "description": "Opacity (0-1)", "default": 1.0 }
+        }
+    })
+}
+
+/// Common interactions property schema.
+fn interactions_property() -> serde_json::Value {
+    serde_json::json!({
+        "type": "object",
+        "description": "Declarative behaviors, reported to the agent as AG-UI interaction events",
+        "properties": {
+            "on_tap": {
+                "type": "object",
+                "description": "Emit an action when tapped",
+                "properties": {
+                    "action": { "type": "string" },
+                    "payload": { "description": "Extra data reported with the action" }
+                },
+                "required": ["action"]
+            },
+            "on_drag": {
+                "type": "object",
+                "description": "Let the user drag the element",
+                "properties": {
+                    "axis": { "type": "string", "enum": ["both", "x", "y"], "default": "both" },
+                    "action": { "type": "string" }
+                }
+            },
+            "on_long_press": {
+                "type": "object",
+                "description": "Show a context menu on long press",
+                "properties": {
+                    "items": {
+                        "type": "array",
+                        "items": {
+                            "type": "object",
+                            "properties": {
+                                "label": { "type": "string" },
+                                "action": { "type": "string" }
+                            },
+                            "required": ["label", "action"]
+                        }
+                    }
+                },
+                "required": ["items"]
+            }
+        }
+    })
+}
+
+/// Schema for `canvas_render` tool.
+#[allow(clippy::too_many_lines)]
+fn render_tool_schema() -> serde_json::Value {
+    serde_json::json!({
+        "type": "object",
+        "properties": {
+            "session_id": session_id_property(),
+            "content": {
+                "type": "object",
+                "description": "Content to render",
+                "oneOf": [
+                    {
+                        "type": "object",
+                        "properties": {
+                            "type": { "const": "Chart" },
+                            "data": {
+                                "type": "object",
+                                "properties": {
+                                    "chart_type": {
+                                        "type": "string",
+                                        "enum": [
+                                            "bar", "bar_horizontal", "line", "area", "pie", "donut",
+                                            "scatter", "candlestick", "heatmap", "histogram",
+                                            "stacked_bar", "stacked_area", "scatter3d", "surface"
+                                        ]
+                                    },
+                                    "data": { "type": "object" },
+                                    "binding": chart_binding_property()
+                                },
+                                "required": ["chart_type", "data"]
+                            }
+                        },
+                        "required": ["type", "data"]
+                    },
+                    {
+                        "type": "object",
+                        "properties": {
+                            "type": { "const": "Image" },
+                            "data": {
+                                "type": "object",
+                                "properties": {
+                                    "src": { "type": "string" }
+                                },
+                                "required": ["src"]
+                            }
+                        },
+                        "required": ["type", "data"]
+                    },
+                    {
+                        "type": "object",
+                        "properties": {
+                            "type": { "const": "Text" },
+                            "data": {
+                                "type": "object",
+                                "properties": {
+                                    "content": { "type": "string" },
+                                    "font_size": { "type": "number" }
+                                },
+                                "required": ["content"]
+                            }
+                        },
+                        "required": ["type", "data"]
+                    },
+                    {
+                        "type": "object",
+                        "properties": {
+                            "type": { "const": "Math" },
+                            "data": {
+                                "type": "object",
+                                "properties": {
+                                    "tex": { "type": "string", "description": "TeX formula, e.g. \\frac{a}{b}" },
+                                    "font_size": { "type": "number", "description": "Font size (default 24)" },
+                                    "color": { "type": "string", "description": "Text color (default #000000)" }
+                                },
+                                "required": ["tex"]
+                            }
+                        },
+                        "required": ["type", "data"]
+                    },
+                    {
+                        "type": "object",
+                        "properties": {
+                            "type": { "const": "Code" },
+                            "data": {
+                                "type": "object",
+                                "properties": {
+                                    "source": { "type": "string" },
+                                    "language": { "type": "string", "description": "rust, python, javascript, typescript, go, c, cpp, bash or json" }
+                                },
+                                "required": ["source"]
+                            }
+                        },
+                        "required": ["type", "data"]
+                    },
+                    {
+                        "type": "object",
+                        "properties": {
+                            "type": { "const": "LogView" },
+                            "data": {
+                                "type": "object",
+                                "properties": {
+                                    "title": { "type": "string" },
+                                    "text": { "type": "string", "description": "Initial output; ANSI color codes are kept" },
+                                    "max_lines": { "type": "integer", "minimum": 1, "maximum": MAX_LOG_LINES, "description": "Lines kept (default 500)" },
+                                    "follow": { "type": "boolean", "description": "Keep the newest line in view (default true)" }
+                                }
+                            }
+                        },
+                        "required": ["type", "data"]
+                    },
+                    {
+                        "type": "object",
+                        "properties": {
+                            "type": { "const": "Shader" },
+                            "data": {
+                                "type": "object",
+                                "properties": {
+                                    "wgsl": { "type": "string", "maxLength": MAX_SHADER_LEN, "description": "WGSL defining fn shade(uv: vec2<f32>) -> vec4<f32>; uv is (0,0) top left. Reads canvas.time (seconds), canvas.resolution (pixels) and param(i). No bindings, attributes, loop or while; for loops count a variable up to a constant, at most 256 iterations nested" },
+                                    "uniforms": { "type": "array", "items": { "type": "number" }, "maxItems": MAX_SHADER_UNIFORMS, "description": "Custom values read with param(0u), param(1u), ..." }
+                                },
+                                "required": ["wgsl"]
+                            }
+                        },
+                        "required": ["type", "data"]
+                    },
+                    {
+                        "type": "object",
+                        "properties": {
+                            "type": { "const": "StickyNote" },
+                            "data": {
+                                "type": "object",
+                                "properties": {
+                                    "content": { "type": "string" },
+                                    "color": { "type": "string", "description": "Background color (default #fff59d)" },
+                                    "author": { "type": "string", "description": "Defaults to agent_id" }
+                                },
+                                "required": ["content"]
+                            }
+                        },
+                        "required": ["type", "data"]
+                    },
+                    {
+                        "type": "object",
+                        "properties": {
+                            "type": { "const": "Callout" },
+                            "data": {
+                                "type": "object",
+                                "properties": {
+                                    "content": { "type": "string" },
+                                    "color": { "type": "string", "description": "Background color (default #ffffff)" },
+                                    "author": { "type": "string", "description": "Defaults to agent_id" },
+                                    "target": callout_target_property()
+                                },
+                                "required": ["content"]
+                            }
+                        },
+                        "required": ["type", "data"]
+                    },
+                    {
+                        "type": "object",
+                        "properties": {
+                            "type": { "const": "Audio" },
+                            "data": {
+                                "type": "object",
+                                "properties": {
+                                    "stream_id": { "type": "string", "description": "Peer ID or media URL of the clip" },
+                                    "waveform": { "type": "array", "items": { "type": "number" }, "description": "Amplitudes in -1..1, downsampled to at most 512 bars" }
+                                },
+                                "required": ["stream_id"]
+                            }
+                        },
+                        "required": ["type", "data"]
+                    },
+                    {
+                        "type": "object",
+                        "properties": {
+                            "type": { "const": "Calendar" },
+                            "data": {
+                                "type": "object",
+                                "properties": {
+                                    "view": { "type": "string", "enum": ["month", "week"], "default": "month" },
+                                    "date": { "type": "string", "description": "Any day in the month or week to show, YYYY-MM-DD" },
+                                    "events": {
+                                        "type": "array",
+                                        "items": {
+                                            "type": "object",
+                                            "properties": {
+                                                "date": { "type": "string", "description": "YYYY-MM-DD" },
+                                                "title": { "type": "string" },
+                                                "time": { "type": "string", "description": "Free-form time label, e.g. \"09:30\"" },
+                                                "color": { "type": "string", "description": "Marker color" }
+                                            },
+                                            "required": ["date", "title"]
+                                        }
+                                    },
+                                    "selected": { "type": "string", "description": "Initially selected day, YYYY-MM-DD" }
+                                },
+                                "required": ["date"]
+                            }
+                        },
+                        "required": ["type", "data"]
+                    },
+                    {
+                        "type": "object",
+                        "properties": {
+                            "type": { "const": "Randomizer" },
+                            "data": {
+                                "type": "object",
+                                "properties": {
+                                    "source": {
+                                        "type": "object",
+                                        "description": "What is drawn: dice ({count, sides}), a wheel ({segments}) or a shuffled list ({items})",
+                                        "properties": {
+                                            "mode": { "type": "string", "enum": ["dice", "wheel", "shuffle"] },
+                                            "count": { "type": "integer", "minimum": 1, "maximum": 10, "default": 1 },
+                                            "sides": { "type": "integer", "minimum": 2, "maximum": 100, "default": 6 },
+                                            "segments": { "type": "array", "items": { "type": "string" }, "minItems": 2, "maxItems": 24 },
+                                            "items": { "type": "array", "items": { "type": "string" }, "minItems": 2, "maxItems": 24 }
+                                        },
+                                        "required": ["mode"]
+                                    }
+                                },
+                                "required": ["source"]
+                            }
+                        },
+                        "required": ["type", "data"]
+                    },
+                    agenda_content_schema(),
+                    floor_plan_content_schema(),
+                    gauge_content_schema(),
+                    kpi_content_schema(),
+                    sparkline_content_schema(),
+                    tree_content_schema(),
+                    {
+                        "type": "object",
+                        "properties": {
+                            "type": { "const": "Embed" },
+                            "data": {
+                                "type": "object",
+                                "properties": {
+                                    "url": { "type": "string", "description": "Absolute http(s) URL of a dashboard or document" },
+                                    "sandbox": { "type": "string", "description": "Iframe sandbox flags (default \"allow-scripts allow-forms allow-popups\")" }
+                                },
+                                "required": ["url"]
+                            }
+                        },
+                        "required": ["type", "data"]
+                    }
+                ]
+            },
+            "position": {
+                "type": "object",
+                "properties": {
+                    "x": { "type": "number" },
+                    "y": { "type": "number" },
+                    "width": { "type": "number" },
+                    "height": { "type": "number" }
+                },
+                "required": ["x", "y"]
+            }
+        },
+        "required": ["content"]
+    })
+}
+
+/// Schema for the tail target of a callout.
+fn callout_target_property() -> serde_json::Value {
+    serde_json::json!({
+        "description": "What the callout's tail points at",
+        "oneOf": [
+            {
+                "type": "object",
+                "properties": {
+                    "kind": { "const": "element" },
+                    "element_id": { "type": "string" }
+                },
+                "required": ["kind", "element_id"]
+            },
+            {
+                "type": "object",
+                "properties": {
+                    "kind": { "const": "point" },
+                    "x": { "type": "number" },
+                    "y": { "type": "number" }
+                },
+                "required": ["kind", "x", "y"]
+            }
+        ]
+    })
+}
+
+/// Schema for `canvas_render_a2ui` tool.
+fn render_a2ui_tool_schema() -> serde_json::Value {
+    serde_json::json!({
+        "type": "object",
+        "properties": {
+            "session_id": session_id_property(),
+            "tree": {
+                "type": "object",
+                "description": "A2UI component tree to render",
+                "properties": {
+                    "root": {
+                        "type": "object",
+                        "description": "Root A2UI node (Container, Text, Image, Button, Chart, Sparkline, or VideoFeed). Sparkline takes values, kind (line or bar) and style.color"
+                    },
+                    "data_model": {
+                        "type": "object",
+                        "description": "Optional data bindings for dynamic content"
+                    }
+                },
+                "required": ["root"]
+            },
+            "merge": {
+                "type": "boolean",
+                "description": "If true, merge with existing scene. If false (default), replace scene.",
+                "default": false
+            },
+            "offset_x": {
+                "type": "number",
+                "description": "X offset to apply to all elements (default: 0)",
+                "default": 0
+            },
+            "offset_y": {
+                "type": "number",
+                "description": "Y offset to apply to all elements (default: 0)",
+                "default": 0
+            }
+        },
+        "required": ["tree"]
+    })
+}
+
+/// Schema for `canvas_interact` tool.
+fn interact_tool_schema() -> serde_json::Value {
+    serde_json::json!({
+        "type": "object",
+        "properties": {
+            "session_id": session_id_property(),
+            "interaction_type": {
+                "type": "string",
+                "enum": ["touch", "voice", "selection"],
+                "description": "Type of interaction"
+            },
+            "data": {
+                "type": "object",
+                "description": "Interaction-specific data"
+            }
+        },
+        "required": ["interaction_type"]
+    })
+}
+
+/// Schema for `canvas_export` tool.
+fn export_tool_schema() -> serde_json::Value {
+    serde_json::json!({
+        "type": "object",
+        "properties": {
+            "session_id": session_id_property(),
+            "format": {
+                "type": "string",
+                "enum": ["png", "jpeg", "pdf", "svg"],
+                "description": "Export format"
+            },
+            "quality": {
+                "type": "number",
+                "minimum": 0,
+                "maximum": 100,
+                "description": "Export quality (for lossy formats)"
+            },
+            "element_id": {
+                "type": "string",
+                "description": "Export only this element (e.g. a chart or table) and its children, cropped to its bounds"
+            },
+            "flatten": {
+                "type": "boolean",
+                "default": false,
+                "description": "With element_id of an image: render the image with its pinned markup, cropped exactly to the image, for sharing a marked-up screenshot"
+            },
+            "width": {
+                "type": "integer",
+                "minimum": 1,
+                "description": "Output width in pixels (default: the session viewport); for element exports height follows the aspect ratio unless set"
+            },
+            "height": {
+                "type": "integer",
+                "minimum": 1,
+                "description": "Output height in pixels (default: the session viewport)"
+            },
+            "scale": {
+                "type": "number",
+                "exclusiveMinimum": 0,
+                "description": "Scale factor (e.g. 2 for retina)"
+            },
+            "print": {
+                "type": "object",
+                "description": "Paginate the session onto paper pages with a header and page numbers (pdf only), for handouts",
+                "properties": {
+                    "paper": { "type": "string", "enum": ["a4", "letter", "a3", "a5", "legal", "tabloid"], "description": "Paper size (default a4)" },
+                    "landscape": { "type": "boolean", "description": "Landscape pages (default portrait)" },
+                    "margin_mm": { "type": "number", "minimum": 0, "description": "Margin in millimetres (default 12)" },
+                    "scale": { "type": "number", "exclusiveMinimum": 0, "description": "Millimetres per canvas unit (default: fit the content width)" },
+                    "title": { "type": "string", "description": "Header title (default: the session ID)" },
+                    "dpi": { "type": "number", "minimum": 36, "maximum": 600, "description": "Page raster resolution (default 150)" }
+                }
+            },
+            "redact": {
+                "type": "string",
+                "enum": ["blur", "placeholder"],
+                "description": "Hide elements marked sensitive (and their children) for sharing outside the team: blur them beyond reading, or replace them with gray boxes"
+            }
+        },
+        "required": ["format"]
+    })
+}
+
+/// Schema for `canvas_clear` tool.
+fn clear_tool_schema() -> serde_json::Value {
+    serde_json::json!({
+        "type": "object",
+        "properties": {
+            "session_id": session_id_property()
+        }
+    })
+}
+
+/// Schema for `canvas_add_element` tool.
+fn add_element_tool_schema() -> serde_json::Value {
+    serde_json::json!({
+        "type": "object",
+        "properties": {
+            "session_id": session_id_property(),
+            "kind": {
+                "type": "object",
+                "description": "Element type and data",
+                "oneOf": [
+                    {
+                        "type": "object",
+                        "properties": {
+                            "type": { "const": "Text" },
+                            "data": {
+                                "type": "object",
+                                "properties": {
+                                    "content": { "type": "string" },
+                                    "font_size": { "type": "number" },
+                                    "color": { "type": "string" }
+                                },
+                                "required": ["content", "font_size", "color"]
+                            }
+                        },
+                        "required": ["type", "data"]
+                    },
+                    {
+                        "type": "object",
+                        "properties": {
+                            "type": { "const": "Chart" },
+                            "data": {
+                                "type": "object",
+                                "properties": {
+                                    "chart_type": { "type": "string" },
+                                    "data": { "type": "object" },
+                                    "binding": chart_binding_property()
+                                },
+                                "required": ["chart_type", "data"]
+                            }
+                        },
+                        "required": ["type", "data"]
+                    },
+                    {
+                        "type": "object",
+                        "properties": {
+                            "type": { "const": "Image" },
+                            "data": {
+                                "type": "object",
+                                "properties": {
+                                    "src": { "type": "string" },
+                                    "format": { "type": "string" }
+                                },
+                                "required": ["src", "format"]
+                            }
+                        },
+                        "required": ["type", "data"]
+                    },
+                    model3d_kind_schema()
+                ]
+            },
+            "transform": transform_property(),
+            "interactive": {
+                "type": "boolean",
+                "description": "Whether the element responds to interactions",
+                "default": true
+            },
+            "visible": visible_property(),
+            "opacity": opacity_property(),
+            "screen_anchored": screen_anchored_property(),
+            "sensitive": sensitive_property(),
+            "style": style_property(),
+            "interactions": interactions_property(),
+            "attach_to": attach_to_property()
+        },
+        "required": ["kind"]
+    })
+}
+
+/// Schema for a `Model3D` element kind in `canvas_add_element`.
+fn model3d_kind_schema() -> serde_json::Value {
+    serde_json::json!({
+        "type": "object",
+        "properties": {
+            "type": { "const": "Model3D" },
+            "data": {
+                "type": "object",
+                "properties": {
+                    "src": { "type": "string", "description": "glTF URL or asset: reference" },
+                    "rotation": { "type": "array", "items": { "type": "number" }, "minItems": 3, "maxItems": 3, "description": "Euler angles in radians (X, then Y, then Z)" },
+                    "scale": { "type": "number", "exclusiveMinimum": 0 },
+                    "pose": {
+                        "type": "object",
+                        "description": "Position, quaternion rotation [x, y, z, w] and per-axis scale; replaces rotation and scale",
+                        "properties": {
+                            "position": { "type": "array", "items": { "type": "number" }, "minItems": 3, "maxItems": 3 },
+                            "rotation": { "type": "array", "items": { "type": "number" }, "minItems": 4, "maxItems": 4 },
+                            "scale": { "type": "array", "items": { "type": "number" }, "minItems": 3, "maxItems": 3 }
+                        }
+                    },
+                    "camera": {
+                        "type": "object",
+                        "description": "How 3D views frame the model: orbit target, distance, yaw and pitch and fov in radians",
+                        "properties": {
+                            "target": { "type": "array", "items": { "type": "number" }, "minItems": 3, "maxItems": 3 },
+                            "distance": { "type": "number", "exclusiveMinimum": 0 },
+                            "yaw": { "type": "number" },
+                            "pitch": { "type": "number" },
+                            "fov": { "type": "number" }
+                        }
+                    },
+                    "lighting": {
+                        "type": "object",
+                        "description": "Ambient level (0-1) and up to 8 lights: {type: directional, direction} or {type: point, position}, each with color and intensity",
+                        "properties": {
+                            "ambient": { "type": "number", "minimum": 0, "maximum": 1 },
+                            "lights": { "type": "array", "items": { "type": "object" }, "maxItems": MAX_MODEL_LIGHTS }
+                        }
+                    }
+                },
+                "required": ["src", "rotation", "scale"]
+            }
+        },
+        "required": ["type", "data"]
+    })
+}
+
+/// Schema for `canvas_remove_element` tool.
+fn remove_element_tool_schema() -> serde_json::Value {
+    serde_json::json!({
+        "type": "object",
+        "properties": {
+            "session_id": session_id_property(),
+            "element_id": element_id_property()
+        },
+        "required": ["element_id"]
+    })
+}
+
+/// Schema for `canvas_group` tool.
+fn group_tool_schema() -> serde_json::Value {
+    serde_json::json!({
+        "type": "object",
+        "properties": {
+            "session_id": session_id_property(),
+            "element_ids": {
+                "type": "array",
+                "items": element_id_property(),
+                "minItems": 1,
+                "description": "Elements to group; they must share a parent"
+            }
+        },
+        "required": ["element_ids"]
+    })
+}
+
+/// Schema for `canvas_tree` tool.
+fn tree_tool_schema() -> serde_json::Value {
+    serde_json::json!({
+        "type": "object",
+        "properties": {
+            "session_id": session_id_property(),
+            "root": tree_node_property(),
+            "direction": {
+                "type": "string",
+                "enum": ["down", "right"],
+                "default": "down",
+                "description": "down: root at the top (org charts); right: root on the left (decision trees)"
+            },
+            "position": {
+                "type": "object",
+                "description": "Placement; sized to fit the tree unless width and height are given",
+                "properties": {
+                    "x": { "type": "number" },
+                    "y": { "type": "number" },
+                    "width": { "type": "number" },
+                    "height": { "type": "number" }
+                },
+                "required": ["x", "y"]
+            }
+        },
+        "required": ["root"]
+    })
+}
+
+/// Schema for `canvas_barcode` tool.
+fn barcode_tool_schema() -> serde_json::Value {
+    serde_json::json!({
+        "type": "object",
+        "properties": {
+            "session_id": session_id_property(),
+            "payload": {
+                "type": "string",
+                "maxLength": MAX_BARCODE_PAYLOAD_LEN,
+                "description": "Text to encode, e.g. a URL or ticket number"
+            },
+            "wifi": {
+                "type": "object",
+                "description": "Encode credentials that join a Wi-Fi network instead of a payload (QR only)",
+                "properties": {
+                    "ssid": { "type": "string" },
+                    "password": { "type": "string", "description": "Omit for an open network" },
+                    "hidden": { "type": "boolean", "default": false }
+                },
+                "required": ["ssid"]
+            },
+            "symbology": {
+                "type": "string",
+                "enum": ["qr", "code128"],
+                "default": "qr",
+                "description": "code128 takes up to 80 printable ASCII characters"
+            },
+            "error_correction": {
+                "type": "string",
+                "enum": ["low", "medium", "quartile", "high"],
+                "default": "medium",
+                "description": "Share of a QR code that can be damaged and still scan"
+            },
+            "color": { "type": "string", "description": "Color of the dark modules (default black)" },
+            "position": {
+                "type": "object",
+                "description": "Placement; sized to the code unless width and height are given",
+                "properties": {
+                    "x": { "type": "number" },
+                    "y": { "type": "number" },
+                    "width": { "type": "number" },
+                    "height": { "type": "number" }
+                },
+                "required": ["x", "y"]
+            }
+        }
+    })
+}
+
+/// Schema for `canvas_agenda` tool.
+fn agenda_tool_schema() -> serde_json::Value {
+    serde_json::json!({
+        "type": "object",
+        "properties": {
+            "session_id": session_id_property(),
+            "element_id": {
+                "type": "string",
+                "description": "ID of the agenda"
+            },
+            "action": {
+                "type": "string",
+                "enum": ["start", "advance", "back", "goto", "reset", "status"],
+                "description": "start runs the first item (after in_secs); advance ends the running item; back restarts the previous one; goto starts item; status only reads"
+            },
+            "item": {
+                "type": "integer",
+                "minimum": 0,
+                "description": "Item to start for goto, counting from 0"
+            },
+            "in_secs": {
+                "type": "number",
+                "minimum": 0,
+                "description": "For start: count down this many seconds first"
+            }
+        },
+        "required": ["element_id", "action"]
+    })
+}
+
+/// Schema for `canvas_log_append` tool.
+fn log_append_tool_schema() -> serde_json::Value {
+    serde_json::json!({
+        "type": "object",
+        "properties": {
+            "session_id": session_id_property(),
+            "element_id": {
+                "type": "string",
+                "description": "ID of the log view"
+            },
+            "text": {
+                "type": "string",
+                "description": "Output to append; split into lines at newlines, a carriage return rewrites its line"
+            },
+            "clear": {
+                "type": "boolean",
+                "description": "Empty the log before appending"
+            }
+        },
+        "required": ["element_id"]
+    })
+}
+
+/// Schema for `canvas_annotate` tool.
+fn measure_tool_schema() -> serde_json::Value {
+    serde_json::json!({
+        "type": "object",
+        "properties": {
+            "session_id": session_id_property(),
+            "start": {
+                "type": "array",
+                "items": { "type": "number" },
+                "minItems": 2,
+                "maxItems": 2,
+                "description": "Start point [x, y]; omit with end to list the canvas's measurement lines"
+            },
+            "end": {
+                "type": "array",
+                "items": { "type": "number" },
+                "minItems": 2,
+                "maxItems": 2,
+                "description": "End point [x, y]"
+            },
+            "world": {
+                "type": "boolean",
+                "default": false,
+                "description": "Points are world coordinates rather than canvas pixels"
+            }
+        }
+    })
+}
+
+fn lint_tool_schema() -> serde_json::Value {
+    serde_json::json!({
+        "type": "object",
+        "properties": {
+            "session_id": session_id_property(),
+            "rules": {
+                "type": "array",
+                "items": {
+                    "type": "string",
+                    "enum": ["overlapping_text", "tiny_font", "low_contrast", "out_of_bounds", "orphan_connector"]
+                },
+                "description": "Rules to run (default: all)"
+            },
+            "min_font_size": {
+                "type": "number",
+                "default": 10,
+                "description": "Smallest acceptable text size in pixels"
+            },
+            "min_contrast": {
+                "type": "number",
+                "default": 4.5,
+                "description": "Smallest acceptable contrast ratio between text and what is behind it (4.5 is WCAG AA)"
+            },
+            "apply": {
+                "type": "boolean",
+                "default": false,
+                "description": "Apply every suggested fix"
+            }
+        }
+    })
+}
+
+fn annotate_tool_schema() -> serde_json::Value {
+    serde_json::json!({
+        "type": "object",
+        "properties": {
+            "session_id": session_id_property(),
+            "type": {
+                "type": "string",
+                "enum": ["measurement", "arrow", "region", "ink", "text"],
+                "description": "region doubles as a highlight"
+            },
+            "element_id": {
+                "type": "string",
+                "description": "Element the coordinates are relative to (its top-left corner); omit for canvas coordinates. Marks made over an image are pinned to it and move and scale with it"
+            },
+            "start": {
+                "type": "array",
+                "items": { "type": "number" },
+                "minItems": 2,
+                "maxItems": 2,
+                "description": "Measurement or arrow start [x, y]"
+            },
+            "end": {
+                "type": "array",
+                "items": { "type": "number" },
+                "minItems": 2,
+                "maxItems": 2,
+                "description": "Measurement end or arrow tip [x, y]"
+            },
+            "kind": {
+                "type": "string",
+                "enum": ["dimension", "ruler", "arrow"],
+                "default": "dimension",
+                "description": "dimension: end caps; ruler: ticks in round units; arrow: arrowhead at the end"
+            },
+            "label": {
+                "type": "string",
+                "description": "Measurement or arrow label; measurements default to the measured length"
+            },
+            "unit": {
+                "type": "string",
+                "description": "Unit written after the length; defaults to the scene's world unit (px unless set)"
+            },
+            "scale": {
+                "type": "number",
+                "description": "Units per canvas pixel, e.g. 0.5 for a plan drawn at 2px per meter; defaults to the scene's world unit scale"
+            },
+            "x": { "type": "number", "description": "Region or text left edge" },
+            "y": { "type": "number", "description": "Region or text top edge" },
+            "width": {
+                "type": "number",
+                "description": "Region or text width, or ink stroke width (default 4)"
+            },
+            "height": { "type": "number" },
+            "caption": { "type": "string", "description": "Region caption" },
+            "points": {
+                "type": "array",
+                "items": {
+                    "type": "array",
+                    "items": { "type": "number" },
+                    "minItems": 2,
+                    "maxItems": 2
+                },
+                "description": "Ink stroke points [[x, y], ...]"
+            },
+            "content": { "type": "string", "description": "Text content" },
+            "font_size": { "type": "number", "default": 16 },
+            "color": {
+                "type": "string",
+                "description": "Hex color, var(--name) or palette(name, index)",
+                "default": "#e53935"
+            }
+        },
+        "required": ["type"]
+    })
+}
+
+/// Schema for `canvas_poll` tool.
+fn poll_tool_schema() -> serde_json::Value {
+    serde_json::json!({
+        "type": "object",
+        "properties": {
+            "session_id": session_id_property(),
+            "question": {
+                "type": "string",
+                "description": "The question asked"
+            },
+            "options": {
+                "type": "array",
+                "items": { "type": "string" },
+                "minItems": 2,
+                "maxItems": MAX_POLL_OPTIONS,
+                "description": "Option labels, in display order"
+            },
+            "position": {
+                "type": "object",
+                "description": "Placement; defaults to a 320px wide card at the origin, 40px per option",
+                "properties": {
+                    "x": { "type": "number" },
+                    "y": { "type": "number" },
+                    "width": { "type": "number" },
+                    "height": { "type": "number" }
+                },
+                "required": ["x", "y"]
+            }
+        },
+        "required": ["question", "options"]
+    })
+}
+
+/// Schema for `canvas_update_element` tool.
+fn update_element_tool_schema() -> serde_json::Value {
+    let mut transform = transform_property();
+    transform["description"] = serde_json::json!("New transform values (partial update supported)");
+
+    serde_json::json!({
+        "type": "object",
+        "properties": {
+            "session_id": session_id_property(),
+            "element_id": element_id_property(),
+            "transform": transform,
+            "interactive": {
+                "type": "boolean",
+                "description": "Whether the element responds to interactions"
+            },
+            "visible": visible_property(),
+            "opacity": opacity_property(),
+            "screen_anchored": screen_anchored_property(),
+            "sensitive": sensitive_property(),
+            "style": style_property(),
+            "interactions": interactions_property(),
+            "attach_to": attach_to_property(),
+            "data": {
+                "type": "object",
+                "description": "Fields to merge into the element's content data, e.g. { \"events\": [...] } for a Calendar. FloorPlan markers merge by id: { \"markers\": [{ \"id\": \"A1\", \"occupancy\": \"occupied\" }] }; add \"remove\": true to delete one. The element type cannot change."
+            }
+        },
+        "required": ["element_id"]
+    })
+}
+
+/// Schema for `canvas_get_scene` tool.
+fn get_scene_tool_schema() -> serde_json::Value {
+    serde_json::json!({
+        "type": "object",
+        "properties": {
+            "session_id": session_id_property()
+        }
+    })
+}
+
+/// Schema for `canvas_restore` tool.
+fn restore_tool_schema() -> serde_json::Value {
+    serde_json::json!({
+        "type": "object",
+        "properties": {
+            "session_id": session_id_property(),
+            "reference": {
+                "type": "string",
+                "description": "Reference returned by canvas_archive"
+            }
+        },
+        "required": ["reference"]
+    })
+}
+
+/// Schema for `canvas_get_scene` tool.
+fn scene_document_tool_schema() -> serde_json::Value {
+    serde_json::json!({
+        "type": "object",
+        "properties": {
+            "session_id": session_id_property(),
+            "redact": {
+                "type": "boolean",
+                "default": false,
+                "description": "Replace elements marked sensitive, and their children, with gray placeholders and drop their text and data"
+            }
+        }
+    })
+}
+
+/// Schema for `canvas_spotlight` tool.
+fn spotlight_tool_schema() -> serde_json::Value {
+    serde_json::json!({
+        "type": "object",
+        "properties": {
+            "session_id": session_id_property(),
+            "element_ids": {
+                "type": "array",
+                "items": element_id_property(),
+                "description": "Elements to spotlight"
+            },
+            "query": {
+                "type": "string",
+                "description": "Describe the elements to spotlight instead of listing IDs (e.g. 'revenue chart')"
+            },
+            "dim_opacity": {
+                "type": "number",
+                "minimum": 0,
+                "maximum": 1,
+                "default": Spotlight::DEFAULT_DIM_OPACITY,
+                "description": "Opacity of the overlay dimming other elements"
+            },
+            "zoom_to_fit": {
+                "type": "boolean",
+                "default": true,
+                "description": "Zoom viewers' cameras to fit the spotlighted elements"
+            },
+            "timeout_ms": {
+                "type": "integer",
+                "minimum": 0,
+                "description": "Clear the spotlight automatically after this many milliseconds"
+            },
+            "clear": {
+                "type": "boolean",
+                "default": false,
+                "description": "Remove the current spotlight"
+            }
+        }
+    })
+}
+
+/// Schema for `canvas_save_template` tool.
+fn save_template_tool_schema() -> serde_json::Value {
+    serde_json::json!({
+        "type": "object",
+        "properties": {
+            "session_id": session_id_property(),
+            "name": {
+                "type": "string",
+                "description": "Template name (no '/' or '?'); replaces any template with the same name"
+            },
+            "element_ids": {
+                "type": "array",
+                "items": element_id_property(),
+                "description": "Root elements of the template; their children are included"
+            },
+            "description": {
+                "type": "string",
+                "description": "What the template is for"
+            }
+        },
+        "required": ["name", "element_ids"]
+    })
+}
+
+/// Schema for `canvas_instantiate_template` tool.
+fn instantiate_template_tool_schema() -> serde_json::Value {
+    serde_json::json!({
+        "type": "object",
+        "properties": {
+            "session_id": session_id_property(),
+            "name": {
+                "type": "string",
+                "description": "Template to instantiate"
+            },
+            "x": { "type": "number", "default": 0, "description": "X position of the template's top-left corner" },
+            "y": { "type": "number", "default": 0, "description": "Y position of the template's top-left corner" }
+        },
+        "required": ["name"]
+    })
+}
+
+/// Schema for `canvas_begin_transaction` tool.
+fn begin_transaction_tool_schema() -> serde_json::Value {
+    serde_json::json!({
+        "type": "object",
+        "properties": {
+            "session_id": session_id_property()
+        }
+    })
+}
+
+/// Schema for `canvas_commit_transaction` and `canvas_rollback_transaction`.
+fn transaction_tool_schema() -> serde_json::Value {
+    serde_json::json!({
+        "type": "object",
+        "properties": {
+            "transaction_id": {
+                "type": "string",
+                "description": "ID returned by canvas_begin_transaction"
+            }
+        },
+        "required": ["transaction_id"]
+    })
+}
+
+/// Schema for `canvas_camera` tool.
+fn camera_tool_schema() -> serde_json::Value {
+    serde_json::json!({
+        "type": "object",
+        "properties": {
+            "session_id": session_id_property(),
+            "command": {
+                "type": "string",
+                "enum": ["fit_to_content", "fit_to_elements", "reset_view", "go_to_anchor"],
+                "description": "Camera command to run"
+            },
+            "element_ids": {
+                "type": "array",
+                "items": element_id_property(),
+                "description": "Elements to frame (fit_to_elements only)"
+            },
+            "name": {
+                "type": "string",
+                "description": "Anchor to move to (go_to_anchor only)"
+            },
+            "padding": {
+                "type": "number",
+                "minimum": 0,
+                "default": 48,
+                "description": "Screen-space padding around the framed content, in pixels"
+            },
+            "min_zoom": {
+                "type": "number",
+                "default": 0.1,
+                "description": "Minimum zoom level"
+            },
+            "max_zoom": {
+                "type": "number",
+                "default": 4.0,
+                "description": "Maximum zoom level"
+            }
+        },
+        "required": ["command"]
+    })
+}
+
+/// Schema for `canvas_anchor` tool.
+fn anchor_tool_schema() -> serde_json::Value {
+    serde_json::json!({
+        "type": "object",
+        "properties": {
+            "session_id": session_id_property(),
+            "name": {
+                "type": "string",
+                "maxLength": MAX_ANCHOR_NAME_LEN,
+                "description": "Anchor name, e.g. 'summary'; saving an existing name replaces it"
+            },
+            "element_ids": {
+                "type": "array",
+                "items": element_id_property(),
+                "description": "Frame these elements (uses padding and zoom limits)"
+            },
+            "x": {
+                "type": "number",
+                "description": "Canvas X coordinate to center"
+            },
+            "y": {
+                "type": "number",
+                "description": "Canvas Y coordinate to center"
+            },
+            "zoom": {
+                "type": "number",
+                "exclusiveMinimum": 0,
+                "description": "Zoom level; defaults to 1.0 with x/y, or the current zoom"
+            },
+            "padding": {
+                "type": "number",
+                "minimum": 0,
+                "default": 48,
+                "description": "Screen-space padding around framed elements, in pixels"
+            },
+            "min_zoom": {
+                "type": "number",
+                "default": 0.1,
+                "description": "Minimum zoom level when framing elements"
+            },
+            "max_zoom": {
+                "type": "number",
+                "default": 4.0,
+                "description": "Maximum zoom level when framing elements"
+            },
+            "remove": {
+                "type": "boolean",
+                "default": false,
+                "description": "Delete the anchor instead of saving it"
+            }
+        },
+        "required": ["name"]
+    })
+}
+
+/// Schema for `canvas_set_theme` tool.
+fn set_theme_tool_schema() -> serde_json::Value {
+    serde_json::json!({
+        "type": "object",
+        "properties": {
+            "session_id": session_id_property(),
+            "theme": {
+                "type": "object",
+                "properties": {
+                    "variables": {
+                        "type": "object",
+                        "maxProperties": MAX_THEME_ENTRIES,
+                        "additionalProperties": {
+                            "type": "string",
+                            "description": "CSS color or palette(name, index)"
+                        },
+                        "description": "Color variables by name, e.g. {\"accent\": \"#1976d2\"}"
+                    },
+                    "palettes": {
+                        "type": "object",
+                        "maxProperties": MAX_THEME_ENTRIES,
+                        "additionalProperties": {
+                            "type": "array",
+                            "items": { "type": "string" },
+                            "minItems": 1,
+                            "maxItems": MAX_PALETTE_COLORS
+                        },
+                        "description": "Named color lists, e.g. {\"brand\": [\"#1976d2\", \"#ff9800\"]}"
+                    }
+                },
+                "description": "The full theme; omitted variables and palettes are removed"
+            }
+        },
+        "required": ["theme"]
+    })
+}
+
+/// Schema for `canvas_branding` tool.
+fn branding_tool_schema() -> serde_json::Value {
+    serde_json::json!({
+        "type": "object",
+        "properties": {
+            "session_id": session_id_property(),
+            "logo": {
+                "type": "string",
+                "description": "Logo image: data URI, URL or asset: reference"
+            },
+            "watermark": {
+                "type": "string",
+                "maxLength": MAX_WATERMARK_LEN,
+                "description": "Watermark text, e.g. \"CONFIDENTIAL\""
+            },
+            "position": {
+                "type": "string",
+                "enum": ["top_left", "top_right", "bottom_left", "bottom_right", "center"],
+                "description": "Where the branding sits (default bottom_right)"
+            },
+            "opacity": {
+                "type": "number",
+                "minimum": 0.0,
+                "maximum": 1.0,
+                "description": "Opacity of the logo and watermark (default 0.5)"
+            },
+            "clear": {
+                "type": "boolean",
+                "description": "Remove the branding instead"
+            }
+        }
+    })
+}
+
+/// Schema for a chart's `binding` to a dataset.
+fn chart_binding_property() -> serde_json::Value {
+    serde_json::json!({
+        "type": "object",
+        "properties": {
+            "dataset": {
+                "type": "string",
+                "description": "ID of a dataset stored with canvas_data"
+            },
+            "query": {
+                "type": "object",
+                "properties": {
+                    "x": { "type": "string", "description": "Column of category labels" },
+                    "y": {
+                        "type": "array",
+                        "items": { "type": "string" },
+                        "description": "Columns plotted as series; default every numeric column"
+                    },
+                    "filter": {
+                        "type": "array",
+                        "items": {
+                            "type": "object",
+                            "properties": {
+                                "column": { "type": "string" },
+                                "op": { "type": "string", "enum": ["eq", "ne", "lt", "le", "gt", "ge"] },
+                                "value": {}
+                            },
+                            "required": ["column", "value"]
+                        }
+                    },
+                    "aggregate": {
+                        "type": "string",
+                        "enum": ["sum", "mean", "min", "max", "count"],
+                        "description": "Combine rows with the same x value"
+                    },
+                    "sort": {
+                        "type": "object",
+                        "properties": {
+                            "column": { "type": "string" },
+                            "descending": { "type": "boolean", "default": false }
+                        },
+                        "required": ["column"]
+                    },
+                    "limit": { "type": "integer", "minimum": 1 }
+                }
+            }
+        },
+        "required": ["dataset"],
+        "description": "Plot a named dataset; the chart's x_labels and series follow it"
+    })
+}
+
+/// Schema for `canvas_data` tool.
+fn data_tool_schema() -> serde_json::Value {
+    serde_json::json!({
+        "type": "object",
+        "properties": {
+            "session_id": session_id_property(),
+            "dataset": {
+                "type": "string",
+                "maxLength": MAX_DATASET_NAME_LEN,
+                "description": "Dataset ID that chart bindings refer to, e.g. 'sales'"
+            },
+            "mode": {
+                "type": "string",
+                "enum": ["put", "append", "remove"],
+                "default": "put",
+                "description": "put replaces the dataset, append adds rows to it, remove deletes it"
+            },
+            "columns": {
+                "type": "array",
+                "items": { "type": "string" },
+                "maxItems": MAX_DATASET_COLUMNS,
+                "description": "Column names (required for put)"
+            },
+            "rows": {
+                "type": "array",
+                "items": { "type": "array" },
+                "maxItems": MAX_DATASET_ROWS,
+                "description": "Rows with one value per column, e.g. [[\"Jan\", 120], [\"Feb\", 95]]"
+            },
+            "keep_last": {
+                "type": "integer",
+                "minimum": 1,
+                "description": "With append, keep only this many of the newest rows"
+            }
+        },
+        "required": ["dataset"]
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use canvas_core::{
+        CalendarView, Color, OcrText, SparklineKind, ThemeColor, MAX_SPARKLINE_POINTS,
+    };
+
+    #[tokio::test]
+    async fn test_initialize() {
+        let server = CanvasMcpServer::new(SceneStore::new());
+        let response = server
+            .handle_request(JsonRpcRequest {
+                jsonrpc: "2.0".to_string(),
+                id: serde_json::json!(1),
+                method: "initialize".to_string(),
+                params: serde_json::json!({}),
+            })
+            .await;
+
+        assert!(response.result.is_some());
+        assert!(response.error.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_tools_list() {
+        let server = CanvasMcpServer::new(SceneStore::new());
+        let response = server
+            .handle_request(JsonRpcRequest {
+                jsonrpc: "2.0".to_string(),
+                id: serde_json::json!(1),
+                method: "tools/list".to_string(),
+                params: serde_json::json!({}),
+            })
+            .await;
+
+        assert!(response.result.is_some());
+        let result = response.result.unwrap();
+        let tools = result["tools"].as_array().unwrap();
+        assert!(!tools.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_canvas_render() {
+        let server = CanvasMcpServer::new(SceneStore::new());
+
+        // Initialize first
+        server
+            .handle_request(JsonRpcRequest {
+                jsonrpc: "2.0".to_string(),
+                id: serde_json::json!(0),
+                method: "initialize".to_string(),
+                params: serde_json::json!({}),
+            })
+            .await;
+
+        // Render a chart
+        let response = server
+            .handle_request(JsonRpcRequest {
+                jsonrpc: "2.0".to_string(),
+                id: serde_json::json!(1),
+                method: "tools/call".to_string(),
+                params: serde_json::json!({
+                    "name": "canvas_render",
+                    "arguments": {
+                        "session_id": "default",
+                        "content": {
+                            "type": "Chart",
+                            "data": {
+                                "chart_type": "bar",
+                                "data": {
+                                    "labels": ["A", "B", "C"],
+                                    "values": [10, 20, 30]
+                                }
+                            }
+                        }
+                    }
+                }),
+            })
+            .await;
+
+        assert!(response.result.is_some());
+        assert!(response.error.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_canvas_add_element() {
+        let server = CanvasMcpServer::new(SceneStore::new());
+
+        // Initialize first
+        server
+            .handle_request(JsonRpcRequest {
+                jsonrpc: "2.0".to_string(),
+                id: serde_json::json!(0),
+                method: "initialize".to_string(),
+                params: serde_json::json!({}),
+            })
+            .await;
+
+        // Add a text element
+        let response = server
+            .handle_request(JsonRpcRequest {
+                jsonrpc: "2.0".to_string(),
+                id: serde_json::json!(1),
+                method: "tools/call".to_string(),
+                params: serde_json::json!({
+                    "name": "canvas_add_element",
+                    "arguments": {
+                        "session_id": "default",
+                        "kind": {
+                            "type": "Text",
+                            "data": {
+                                "content": "Hello World",
+                                "font_size": 24.0,
+                                "color": "#ff0000"
+                            }
+                        },
+                        "transform": {
+                            "x": 100.0,
+                            "y": 200.0,
+                            "width": 300.0,
+                            "height": 50.0
+                        }
+                    }
+                }),
+            })
+            .await;
+
+        assert!(response.result.is_some());
+        assert!(response.error.is_none());
+
+        // Verify content has element_id
+        let result = response.result.unwrap();
+        let content = result["content"].as_array().unwrap();
+        let text = content[0]["text"].as_str().unwrap();
+        assert!(text.contains("element_id"));
+    }
+
+    #[tokio::test]
+    async fn test_canvas_get_scene() {
+        let server = CanvasMcpServer::new(SceneStore::new());
+
+        // Initialize first
+        server
+            .handle_request(JsonRpcRequest {
+                jsonrpc: "2.0".to_string(),
+                id: serde_json::json!(0),
+                method: "initialize".to_string(),
+                params: serde_json::json!({}),
+            })
+            .await;
+
+        // Get scene
+        let response = server
+            .handle_request(JsonRpcRequest {
+                jsonrpc: "2.0".to_string(),
+                id: serde_json::json!(1),
+                method: "tools/call".to_string(),
+                params: serde_json::json!({
+                    "name": "canvas_get_scene",
+                    "arguments": {
+                        "session_id": "default"
+                    }
+                }),
+            })
+            .await;
+
+        assert!(response.result.is_some());
+        assert!(response.error.is_none());
+
+        // Verify response contains scene document
+        let result = response.result.unwrap();
+        let content = result["content"].as_array().unwrap();
+        let text = content[0]["text"].as_str().unwrap();
+        let scene_payload: serde_json::Value =
+            serde_json::from_str(text).expect("scene payload should parse");
+        assert!(scene_payload.get("scene").is_some());
+    }
+
+    #[tokio::test]
+    async fn test_canvas_remove_element() {
+        let server = CanvasMcpServer::new(SceneStore::new());
+
+        // Initialize first
+        server
+            .handle_request(JsonRpcRequest {
+                jsonrpc: "2.0".to_string(),
+                id: serde_json::json!(0),
+                method: "initialize".to_string(),
+                params: serde_json::json!({}),
+            })
+            .await;
+
+        // Add an element first
+        let add_response = server
+            .handle_request(JsonRpcRequest {
+                jsonrpc: "2.0".to_string(),
+                id: serde_json::json!(1),
+                method: "tools/call".to_string(),
+                params: serde_json::json!({
+                    "name": "canvas_add_element",
+                    "arguments": {
+                        "session_id": "default",
+                        "kind": {
+                            "type": "Text",
+                            "data": {
+                                "content": "To Remove",
+                                "font_size": 16.0,
+                                "color": "#000000"
+                            }
+                        }
+                    }
+                }),
+            })
+            .await;
+
+        assert!(add_response.result.is_some());
+        let add_result = add_response.result.unwrap();
+        let content = add_result["content"].as_array().unwrap();
+        let text = content[0]["text"].as_str().unwrap();
+        let data: serde_json::Value = serde_json::from_str(text).unwrap();
+        let element_id = data["element_id"].as_str().unwrap();
+
+        // Remove the element
+        let remove_response = server
+            .handle_request(JsonRpcRequest {
+                jsonrpc: "2.0".to_string(),
+                id: serde_json::json!(2),
+                method: "tools/call".to_string(),
+                params: serde_json::json!({
+                    "name": "canvas_remove_element",
+                    "arguments": {
+                        "session_id": "default",
+                        "element_id": element_id
+                    }
+                }),
+            })
+            .await;
+
+        assert!(remove_response.result.is_some());
+        assert!(remove_response.error.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_canvas_update_element() {
+        let server = CanvasMcpServer::new(SceneStore::new());
+
+        // Initialize first
+        server
+            .handle_request(JsonRpcRequest {
+                jsonrpc: "2.0".to_string(),
+                id: serde_json::json!(0),
+                method: "initialize".to_string(),
+                params: serde_json::json!({}),
+            })
+            .await;
+
+        // Add an element first
+        let add_response = server
+            .handle_request(JsonRpcRequest {
+                jsonrpc: "2.0".to_string(),
+                id: serde_json::json!(1),
+                method: "tools/call".to_string(),
+                params: serde_json::json!({
+                    "name": "canvas_add_element",
+                    "arguments": {
+                        "session_id": "default",
+                        "kind": {
+                            "type": "Text",
+                            "data": {
+                                "content": "To Update",
+                                "font_size": 16.0,
+                                "color": "#000000"
+                            }
+                        },
+                        "transform": {
+                            "x": 0.0,
+                            "y": 0.0,
+                            "width": 100.0,
+                            "height": 50.0
+                        }
+                    }
+                }),
+            })
+            .await;
+
+        assert!(add_response.result.is_some());
+        let add_result = add_response.result.unwrap();
+        let content = add_result["content"].as_array().unwrap();
+        let text = content[0]["text"].as_str().unwrap();
+        let data: serde_json::Value = serde_json::from_str(text).unwrap();
+        let element_id = data["element_id"].as_str().unwrap();
+
+        // Update the element position
+        let update_response = server
+            .handle_request(JsonRpcRequest {
+                jsonrpc: "2.0".to_string(),
+                id: serde_json::json!(2),
+                method: "tools/call".to_string(),
+                params: serde_json::json!({
+                    "name": "canvas_update_element",
+                    "arguments": {
+                        "session_id": "default",
+                        "element_id": element_id,
+                        "transform": {
+                            "x": 100.0,
+                            "y": 200.0
+                        }
+                    }
+                }),
+            })
+            .await;
+
+        assert!(update_response.result.is_some());
+        assert!(update_response.error.is_none());
+
+        // Verify the update via get_scene
+        let get_response = server
+            .handle_request(JsonRpcRequest {
+                jsonrpc: "2.0".to_string(),
+                id: serde_json::json!(3),
+                method: "tools/call".to_string(),
+                params: serde_json::json!({
+                    "name": "canvas_get_scene",
+                    "arguments": {
+                        "session_id": "default"
+                    }
+                }),
+            })
+            .await;
+
+        assert!(get_response.result.is_some());
+        let get_result = get_response.result.unwrap();
+        let content = get_result["content"].as_array().unwrap();
+        let text = content[0]["text"].as_str().unwrap();
+        // Verify position was updated
+        assert!(text.contains("100"));
+        assert!(text.contains("200"));
+    }
+
+    #[tokio::test]
+    async fn test_canvas_add_and_update_element_style() {
+        let store = SceneStore::new();
+        let server = CanvasMcpServer::new(store.clone());
+
+        let add_response = server
+            .handle_request(JsonRpcRequest {
+                jsonrpc: "2.0".to_string(),
+                id: serde_json::json!(1),
+                method: "tools/call".to_string(),
+                params: serde_json::json!({
+                    "name": "canvas_add_element",
+                    "arguments": {
+                        "kind": {
+                            "type": "Text",
+                            "data": { "content": "Styled", "font_size": 16.0, "color": "#000000" }
+                        },
+                        "style": {
+                            "fill": {
+                                "type": "linear_gradient",
+                                "stops": [
+                                    { "offset": 0.0, "color": "#ffffff" },
+                                    { "offset": 1.0, "color": "#1976d2" }
+                                ]
+                            },
+                            "corner_radius": 8.0
+                        }
+                    }
+                }),
+            })
+            .await;
+
+        let add_result = add_response.result.expect("add should succeed");
+        let text = add_result["content"][0]["text"].as_str().unwrap();
+        let data: serde_json::Value = serde_json::from_str(text).unwrap();
+        let element_id = ElementId::parse(data["element_id"].as_str().unwrap()).unwrap();
+
+        let scene = store.get("default").unwrap();
+        let element = scene.get_element(element_id).unwrap();
+        assert!(matches!(
+            element.style.fill,
+            Some(canvas_core::Fill::LinearGradient { .. })
+        ));
+        assert!((element.style.corner_radius - 8.0).abs() < f32::EPSILON);
+
+        let update_response = server
+            .handle_request(JsonRpcRequest {
+                jsonrpc: "2.0".to_string(),
+                id: serde_json::json!(2),
+                method: "tools/call".to_string(),
+                params: serde_json::json!({
+                    "name": "canvas_update_element",
+                    "arguments": {
+                        "element_id": element_id.to_string(),
+                        "style": { "opacity": 0.25 },
+                        "visible": false,
+                        "opacity": 0.5,
+                        "screen_anchored": true
+                    }
+                }),
+            })
+            .await;
+        assert!(update_response.error.is_none());
+
+        let scene = store.get("default").unwrap();
+        let element = scene.get_element(element_id).unwrap();
+        assert!(element.style.fill.is_none());
+        assert!((element.style.opacity - 0.25).abs() < f32::EPSILON);
+        assert!(!element.visible);
+        assert!((element.opacity - 0.5).abs() < f32::EPSILON);
+        assert!(element.screen_anchored);
+
+        let invalid_opacity = server
+            .handle_request(JsonRpcRequest {
+                jsonrpc: "2.0".to_string(),
+                id: serde_json::json!(3),
+                method: "tools/call".to_string(),
+                params: serde_json::json!({
+                    "name": "canvas_update_element",
+                    "arguments": { "element_id": element_id.to_string(), "opacity": 2 }
+                }),
+            })
+            .await;
+        assert!(invalid_opacity.error.is_some());
+
+        let invalid_response = server
+            .handle_request(JsonRpcRequest {
+                jsonrpc: "2.0".to_string(),
+                id: serde_json::json!(3),
+                method: "tools/call".to_string(),
+                params: serde_json::json!({
+                    "name": "canvas_update_element",
+                    "arguments": {
+                        "element_id": element_id.to_string(),
+                        "style": { "fill": { "type": "plaid" } }
+                    }
+                }),
+            })
+            .await;
+        assert!(invalid_response.error.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_canvas_add_shape_element() {
+        let store = SceneStore::new();
+        let server = CanvasMcpServer::new(store.clone());
+        let add = |id: u64, shape: serde_json::Value| JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: serde_json::json!(id),
+            method: "tools/call".to_string(),
+            params: serde_json::json!({
+                "name": "canvas_add_element",
+                "arguments": {
+                    "kind": { "type": "Shape", "data": { "shape": shape } },
+                    "style": {
+                        "fill": { "type": "solid", "color": "#fdd835" },
+                        "stroke": { "color": "#f57f17", "width": 2.0 }
+                    }
+                }
+            }),
+        };
+
+        let response = server
+            .handle_request(add(1, serde_json::json!({ "kind": "star", "points": 6 })))
+            .await;
+        let result = response.result.expect("add should succeed");
+        let data: serde_json::Value =
+            serde_json::from_str(result["content"][0]["text"].as_str().unwrap()).unwrap();
+        let element_id = ElementId::parse(data["element_id"].as_str().unwrap()).unwrap();
+        let scene = store.get("default").unwrap();
+        let element = scene.get_element(element_id).unwrap();
+        assert_eq!(
+            element.kind,
+            ElementKind::Shape {
+                shape: canvas_core::ShapeKind::Star {
+                    points: 6,
+                    inner_ratio: 0.5
+                }
+            }
+        );
+        assert!(element.style.stroke.is_some());
+
+        let invalid = server
+            .handle_request(add(2, serde_json::json!({ "kind": "polygon", "sides": 2 })))
+            .await;
+        assert!(invalid.error.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_canvas_add_model3d_with_pose_and_lighting() {
+        let store = SceneStore::new();
+        let server = CanvasMcpServer::new(store.clone());
+        let add = |id: u64, data: serde_json::Value| JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: serde_json::json!(id),
+            method: "tools/call".to_string(),
+            params: serde_json::json!({
+                "name": "canvas_add_element",
+                "arguments": { "kind": { "type": "Model3D", "data": data } }
+            }),
+        };
+
+        let response = server
+            .handle_request(add(
+                1,
+                serde_json::json!({
+                    "src": "https://example.com/robot.glb",
+                    "rotation": [0.0, 0.0, 0.0],
+                    "scale": 1.0,
+                    "pose": { "position": [0.0, -0.5, 0.0], "rotation": [0.0, 0.6, 0.0, 0.8] },
+                    "camera": { "distance": 4.0, "pitch": 0.3 },
+                    "lighting": { "ambient": 0.2, "lights": [{ "type": "point", "position": [1.0, 2.0, 3.0] }] }
+                }),
+            ))
+            .await;
+        assert!(response.error.is_none(), "{:?}", response.error);
+        let scene = store.get("default").unwrap();
+        let element = scene.elements().next().unwrap();
+        let pose = canvas_core::model3d::pose(&element.kind).expect("model pose");
+        assert_eq!(
+            pose,
+            canvas_core::Transform3D {
+                position: [0.0, -0.5, 0.0],
+                rotation: [0.0, 0.6, 0.0, 0.8],
+                scale: [1.0; 3],
+            }
+        );
+
+        let invalid = server
+            .handle_request(add(
+                2,
+                serde_json::json!({
+                    "src": "https://example.com/robot.glb",
+                    "rotation": [0.0, 0.0, 0.0],
+                    "scale": 1.0,
+                    "lighting": { "ambient": 2.0 }
+                }),
+            ))
+            .await;
+        assert!(invalid.error.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_canvas_render_shader_reports_compile_errors() {
+        let store = SceneStore::new();
+        let server = CanvasMcpServer::new(store.clone());
+        let render = |wgsl: &str| JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: serde_json::json!(1),
+            method: "tools/call".to_string(),
+            params: serde_json::json!({
+                "name": "canvas_render",
+                "arguments": {
+                    "session_id": "default",
+                    "content": { "type": "Shader", "data": { "wgsl": wgsl, "uniforms": [0.5] } }
+                }
+            }),
+        };
+
+        let response = server
+            .handle_request(render(
+                "fn shade(uv: vec2<f32>) -> vec4<f32> {\n    return vec4<f32>(uv, param(0u), 1.0);\n}",
+            ))
+            .await;
+        assert!(response.error.is_none(), "{:?}", response.error);
+        let scene = store.get("default").unwrap();
+        assert!(scene.elements().any(|e| matches!(
+            &e.kind,
+            ElementKind::Shader { uniforms, .. } if uniforms.len() == 1
+        )));
+
+        let response = server
+            .handle_request(render(
+                "fn shade(uv: vec2<f32>) -> vec4<f32> {\n    return vec4<f32>(uv, 0.0 1.0);\n}",
+            ))
+            .await;
+        let error = response.error.expect("missing comma");
+        assert!(error.message.contains("wgsl:2:"), "{}", error.message);
+        let response = server
+            .handle_request(render(
+                "@group(0) @binding(1) var<storage> data: array<f32>;\nfn shade(uv: vec2<f32>) -> vec4<f32> { return vec4<f32>(1.0); }",
+            ))
+            .await;
+        assert!(response.error.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_canvas_log_append_streams_bounded_output() {
+        let store = SceneStore::new();
+        let server = CanvasMcpServer::new(store.clone());
+        let call = |name: &str, arguments: serde_json::Value| JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: serde_json::json!(1),
+            method: "tools/call".to_string(),
+            params: serde_json::json!({ "name": name, "arguments": arguments }),
+        };
+        let data = |response: JsonRpcResponse| -> serde_json::Value {
+            let result = response.result.expect("tool result");
+            let text = result["content"][0]["text"].as_str().expect("text");
+            serde_json::from_str(text).expect("json")
+        };
+
+        let response = server
+            .handle_request(call(
+                "canvas_render",
+                serde_json::json!({
+                    "session_id": "default",
+                    "content": {
+                        "type": "LogView",
+                        "data": { "title": "cargo test", "text": "running 3 tests\n", "max_lines": 3 }
+                    }
+                }),
+            ))
+            .await;
+        let element_id = data(response)["element_id"]
+            .as_str()
+            .expect("id")
+            .to_string();
+        let append = |arguments: serde_json::Value| {
+            let mut arguments = arguments;
+            arguments["element_id"] = serde_json::json!(element_id);
+            call("canvas_log_append", arguments)
+        };
+
+        let result = data(
+            server
+                .handle_request(append(serde_json::json!({
+                    "text": "test a ... \u{1b}[32mok\u{1b}[0m\ntest b ... ok\ntest c ... ok\n"
+                })))
+                .await,
+        );
+        assert_eq!(result["appended"], 3);
+        assert_eq!(result["dropped"], 1);
+        assert_eq!(result["total"], 3);
+        let scene = store.get("default").unwrap();
+        let id = ElementId::parse(&element_id).unwrap();
+        let Some(ElementKind::LogView { lines, .. }) = scene.get_element(id).map(|e| &e.kind)
+        else {
+            panic!("expected a log view");
+        };
+        assert_eq!(lines[0], "test a ... \u{1b}[32mok\u{1b}[0m");
+
+        let result = data(
+            server
+                .handle_request(append(serde_json::json!({ "text": "done", "clear": true })))
+                .await,
+        );
+        assert_eq!(result["total"], 1);
+
+        // Only log views take appends
+        let note = data(
+            server
+                .handle_request(call(
+                    "canvas_render",
+                    serde_json::json!({ "session_id": "default", "content": { "type": "Text", "data": { "content": "Hi" } } }),
+                ))
+                .await,
+        );
+        let rejected = server
+            .handle_request(call(
+                "canvas_log_append",
+                serde_json::json!({ "element_id": note["element_id"], "text": "x" }),
+            ))
+            .await;
+        assert!(rejected.error.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_canvas_agenda_runs_schedule() {
+        let store = SceneStore::new();
+        let server = CanvasMcpServer::new(store.clone());
+        let call = |name: &str, arguments: serde_json::Value| JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: serde_json::json!(1),
+            method: "tools/call".to_string(),
+            params: serde_json::json!({ "name": name, "arguments": arguments }),
+        };
+        let data = |response: JsonRpcResponse| -> serde_json::Value {
+            let result = response.result.expect("tool result");
+            let text = result["content"][0]["text"].as_str().expect("text");
+            serde_json::from_str(text).expect("json")
+        };
+
+        let response = server
+            .handle_request(call(
+                "canvas_render",
+                serde_json::json!({
+                    "session_id": "default",
+                    "content": {
+                        "type": "Agenda",
+                        "data": {
+                            "title": "Weekly sync",
+                            "items": [
+                                { "title": "Welcome", "duration_secs": 300 },
+                                { "title": "Roadmap", "duration_secs": 900, "owner": "Ada" }
+                            ]
+                        }
+                    }
+                }),
+            ))
+            .await;
+        let element_id = data(response)["element_id"]
+            .as_str()
+            .expect("id")
+            .to_string();
+        let agenda = |action: &str, extra: serde_json::Value| {
+            let mut arguments = serde_json::json!({ "element_id": element_id, "action": action });
+            if let (Some(arguments), Some(extra)) = (arguments.as_object_mut(), extra.as_object()) {
+                arguments.extend(extra.clone());
+            }
+            call("canvas_agenda", arguments)
+        };
+
+        let status = data(
+            server
+                .handle_request(agenda("start", serde_json::json!({ "in_secs": 60 })))
+                .await,
+        );
+        assert_eq!(status["status"]["phase"], "countdown");
+        let status = data(
+            server
+                .handle_request(agenda("advance", serde_json::json!({})))
+                .await,
+        );
+        assert_eq!(status["status"]["phase"], "running");
+        assert_eq!(status["item"], "Welcome");
+        let status = data(
+            server
+                .handle_request(agenda("advance", serde_json::json!({})))
+                .await,
+        );
+        assert_eq!(status["status"]["index"], 1);
+        assert_eq!(status["item"], "Roadmap");
+
+        // The schedule is stored on the element for every client to follow
+        let scene = store.get("default").unwrap();
+        let id = ElementId::parse(&element_id).unwrap();
+        let Some(ElementKind::Agenda {
+            current,
+            started_at_ms,
+            ..
+        }) = scene.get_element(id).map(|e| &e.kind)
+        else {
+            panic!("expected an agenda");
+        };
+        assert_eq!(*current, 1);
+        assert!(started_at_ms.is_some());
+
+        let missing = server
+            .handle_request(agenda("goto", serde_json::json!({ "item": 5 })))
+            .await;
+        assert!(missing.error.is_some());
+        let status = data(
+            server
+                .handle_request(agenda("reset", serde_json::json!({})))
+                .await,
+        );
+        assert_eq!(status["status"]["phase"], "idle");
+        let rejected = server
+            .handle_request(agenda("skip", serde_json::json!({})))
+            .await;
+        assert!(rejected.error.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_canvas_barcode_encodes_payload() {
+        let store = SceneStore::new();
+        let server = CanvasMcpServer::new(store.clone());
+        let call = |arguments: serde_json::Value| JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: serde_json::json!(1),
+            method: "tools/call".to_string(),
+            params: serde_json::json!({ "name": "canvas_barcode", "arguments": arguments }),
+        };
+
+        let response = server
+            .handle_request(call(serde_json::json!({
+                "wifi": { "ssid": "Lobby", "password": "welcome1" },
+                "position": { "x": 40, "y": 40 }
+            })))
+            .await;
+        let result = response.result.expect("qr result");
+        let data: serde_json::Value =
+            serde_json::from_str(result["content"][0]["text"].as_str().unwrap()).unwrap();
+        assert_eq!(data["payload"], "WIFI:T:WPA;S:Lobby;P:welcome1;;");
+        let element_id = ElementId::parse(data["element_id"].as_str().unwrap()).unwrap();
+        let scene = store.get("default").unwrap();
+        let element = scene.get_element(element_id).unwrap();
+        assert!(canvas_core::barcode::validate(&element.kind).is_ok());
+        // Square, sized to the code and its quiet zone
+        let side = element.transform.width;
+        assert!((side - element.transform.height).abs() < f32::EPSILON);
+        let ElementKind::Barcode { modules, .. } = &element.kind else {
+            panic!("expected a barcode");
+        };
+        let natural = canvas_core::barcode::natural_size(canvas_core::Symbology::Qr, modules);
+        assert!((side - natural.width).abs() < 1e-3);
+
+        let response = server
+            .handle_request(call(serde_json::json!({
+                "payload": "TKT-2041",
+                "symbology": "code128",
+                "color": "#1a237e"
+            })))
+            .await;
+        assert!(response.error.is_none(), "{:?}", response.error);
+
+        for arguments in [
+            serde_json::json!({ "payload": "café", "symbology": "code128" }),
+            serde_json::json!({ "payload": "x", "wifi": { "ssid": "Lobby" } }),
+            serde_json::json!({ "payload": "" }),
+        ] {
+            assert!(server.handle_request(call(arguments)).await.error.is_some());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_canvas_add_element_with_interactions() {
+        let store = SceneStore::new();
+        let server = CanvasMcpServer::new(store.clone());
+
+        let response = server
+            .handle_request(JsonRpcRequest {
+                jsonrpc: "2.0".to_string(),
+                id: serde_json::json!(1),
+                method: "tools/call".to_string(),
+                params: serde_json::json!({
+                    "name": "canvas_add_element",
+                    "arguments": {
+                        "kind": {
+                            "type": "Text",
+                            "data": { "content": "Approve", "font_size": 16.0, "color": "#000000" }
+                        },
+                        "interactions": {
+                            "on_tap": { "action": "approve" },
+                            "on_drag": { "axis": "y" }
+                        }
+                    }
+                }),
+            })
+            .await;
+
+        let result = response.result.expect("add should succeed");
+        let text = result["content"][0]["text"].as_str().unwrap();
+        let data: serde_json::Value = serde_json::from_str(text).unwrap();
+        let element_id = ElementId::parse(data["element_id"].as_str().unwrap()).unwrap();
+
+        let scene = store.get("default").unwrap();
+        let interactions = &scene.get_element(element_id).unwrap().interactions;
+        assert_eq!(
+            interactions.on_tap.as_ref().map(|tap| tap.action.as_str()),
+            Some("approve")
+        );
+        assert_eq!(
+            interactions.on_drag.as_ref().map(|drag| drag.axis),
+            Some(canvas_core::DragAxis::Y)
+        );
+        assert!(interactions.on_long_press.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_tools_list_includes_new_tools() {
+        let server = CanvasMcpServer::new(SceneStore::new());
+        let response = server
+            .handle_request(JsonRpcRequest {
+                jsonrpc: "2.0".to_string(),
+                id: serde_json::json!(1),
+                method: "tools/list".to_string(),
+                params: serde_json::json!({}),
+            })
+            .await;
+
+        assert!(response.result.is_some());
+        let result = response.result.unwrap();
+        let tools = result["tools"].as_array().unwrap();
+
+        // Should have 34 tools total
+        assert_eq!(tools.len(), 35);
+
+        // Verify all tool names are present
+        let tool_names: Vec<&str> = tools.iter().filter_map(|t| t["name"].as_str()).collect();
+        assert!(tool_names.contains(&"canvas_render"));
+        assert!(tool_names.contains(&"canvas_render_a2ui"));
+        assert!(tool_names.contains(&"canvas_interact"));
+        assert!(tool_names.contains(&"canvas_export"));
+        assert!(tool_names.contains(&"canvas_clear"));
+        assert!(tool_names.contains(&"canvas_add_element"));
+        assert!(tool_names.contains(&"canvas_remove_element"));
+        assert!(tool_names.contains(&"canvas_update_element"));
+        assert!(tool_names.contains(&"canvas_group"));
+        assert!(tool_names.contains(&"canvas_ungroup"));
+        assert!(tool_names.contains(&"canvas_poll"));
+        assert!(tool_names.contains(&"canvas_agenda"));
+        assert!(tool_names.contains(&"canvas_log_append"));
+        assert!(tool_names.contains(&"canvas_poll_results"));
+        assert!(tool_names.contains(&"canvas_tree"));
+        assert!(tool_names.contains(&"canvas_annotate"));
+        assert!(tool_names.contains(&"canvas_get_scene"));
+        assert!(tool_names.contains(&"canvas_validate"));
+        assert!(tool_names.contains(&"canvas_measure"));
+        assert!(tool_names.contains(&"canvas_lint"));
+        assert!(tool_names.contains(&"canvas_spotlight"));
+        assert!(tool_names.contains(&"canvas_camera"));
+        assert!(tool_names.contains(&"canvas_anchor"));
+        assert!(tool_names.contains(&"canvas_set_theme"));
+        assert!(tool_names.contains(&"canvas_branding"));
+        assert!(tool_names.contains(&"canvas_data"));
+        assert!(tool_names.contains(&"canvas_archive"));
+        assert!(tool_names.contains(&"canvas_restore"));
+        assert!(tool_names.contains(&"canvas_save_template"));
+        assert!(tool_names.contains(&"canvas_instantiate_template"));
+    }
+
+    #[tokio::test]
+    async fn test_canvas_render_a2ui() {
+        let server = CanvasMcpServer::new(SceneStore::new());
+
+        // Initialize first
+        server
+            .handle_request(JsonRpcRequest {
+                jsonrpc: "2.0".to_string(),
+                id: serde_json::json!(0),
+                method: "initialize".to_string(),
+                params: serde_json::json!({}),
+            })
+            .await;
+
+        // Render an A2UI tree with a container and text
+        // Note: A2UI uses "component" tag with snake_case values
+        let response = server
+            .handle_request(JsonRpcRequest {
+                jsonrpc: "2.0".to_string(),
+                id: serde_json::json!(1),
+                method: "tools/call".to_string(),
+                params: serde_json::json!({
+                    "name": "canvas_render_a2ui",
+                    "arguments": {
+                        "session_id": "default",
+                        "tree": {
+                            "root": {
+                                "component": "container",
+                                "children": [
+                                    {
+                                        "component": "text",
+                                        "content": "Hello A2UI"
+                                    },
+                                    {
+                                        "component": "button",
+                                        "label": "Click Me",
+                                        "action": "submit"
+                                    }
+                                ],
+                                "layout": "column"
+                            }
+                        }
+                    }
+                }),
+            })
+            .await;
+
+        assert!(response.result.is_some());
+        assert!(response.error.is_none());
+
+        // Verify the response contains element_ids
+        let result = response.result.unwrap();
+        let content = result["content"].as_array().unwrap();
+        let text = content[0]["text"].as_str().unwrap();
+        assert!(text.contains("element_ids"));
+        assert!(text.contains("rendered"));
+    }
+
+    #[tokio::test]
+    async fn test_canvas_render_a2ui_with_merge() {
+        let server = CanvasMcpServer::new(SceneStore::new());
+
+        // Initialize first
+        server
+            .handle_request(JsonRpcRequest {
+                jsonrpc: "2.0".to_string(),
+                id: serde_json::json!(0),
+                method: "initialize".to_string(),
+                params: serde_json::json!({}),
+            })
+            .await;
+
+        // First render (A2UI uses "component" tag with snake_case)
+        server
+            .handle_request(JsonRpcRequest {
+                jsonrpc: "2.0".to_string(),
+                id: serde_json::json!(1),
+                method: "tools/call".to_string(),
+                params: serde_json::json!({
+                    "name": "canvas_render_a2ui",
+                    "arguments": {
+                        "session_id": "default",
+                        "tree": {
+                            "root": {
+                                "component": "text",
+                                "content": "First"
+                            }
+                        }
+                    }
+                }),
+            })
+            .await;
+
+        // Second render with merge=true
+        let response = server
+            .handle_request(JsonRpcRequest {
+                jsonrpc: "2.0".to_string(),
+                id: serde_json::json!(2),
+                method: "tools/call".to_string(),
+                params: serde_json::json!({
+                    "name": "canvas_render_a2ui",
+                    "arguments": {
+                        "session_id": "default",
+                        "tree": {
+                            "root": {
+                                "component": "text",
+                                "content": "Second"
+                            }
+                        },
+                        "merge": true,
+                        "offset_x": 100.0,
+                        "offset_y": 50.0
+                    }
+                }),
+            })
+            .await;
+
+        assert!(response.result.is_some());
+
+        // Get scene and verify both elements exist
+        let get_response = server
+            .handle_request(JsonRpcRequest {
+                jsonrpc: "2.0".to_string(),
+                id: serde_json::json!(3),
+                method: "tools/call".to_string(),
+                params: serde_json::json!({
+                    "name": "canvas_get_scene",
+                    "arguments": {
+                        "session_id": "default"
+                    }
+                }),
+            })
+            .await;
+
+        let get_result = get_response.result.unwrap();
+        let content = get_result["content"].as_array().unwrap();
+        let text = content[0]["text"].as_str().unwrap();
+
+        // Both "First" and "Second" should be in the scene
+        assert!(text.contains("First"));
+        assert!(text.contains("Second"));
+    }
+
+    #[tokio::test]
+    async fn test_canvas_spotlight() {
+        let store = SceneStore::new();
+        let server = CanvasMcpServer::new(store.clone());
+        let chart_id = store
+            .add_element(
+                "default",
+                Element::new(ElementKind::Chart {
+                    chart_type: "line".to_string(),
+                    data: serde_json::json!({ "title": "Revenue" }),
+                    binding: None,
+                }),
+            )
+            .unwrap();
+        store
+            .add_element(
+                "default",
+                Element::new(ElementKind::Text {
+                    content: "Notes".to_string(),
+                    font_size: 16.0,
+                    color: Color::BLACK.into(),
+                }),
+            )
+            .unwrap();
+
+        let call = |id: i64, arguments: serde_json::Value| JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: serde_json::json!(id),
+            method: "tools/call".to_string(),
+            params: serde_json::json!({ "name": "canvas_spotlight", "arguments": arguments }),
+        };
+
+        let response = server
+            .handle_request(call(
+                1,
+                serde_json::json!({ "query": "the revenue chart", "timeout_ms": 5000 }),
+            ))
+            .await;
+        assert!(response.error.is_none());
+        let spotlight = store.get("default").unwrap().spotlight().cloned().unwrap();
+        assert_eq!(spotlight.element_ids, vec![chart_id]);
+        assert!(spotlight.zoom_to_fit);
+        assert!(spotlight.expires_at_ms.is_some());
+
+        let response = server
+            .handle_request(call(2, serde_json::json!({ "query": "pie chart" })))
+            .await;
+        assert!(response.error.is_some());
+
+        let response = server
+            .handle_request(call(3, serde_json::json!({ "clear": true })))
+            .await;
+        assert!(response.error.is_none());
+        assert!(store.get("default").unwrap().spotlight().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_canvas_camera() {
+        let store = SceneStore::new();
+        let server = CanvasMcpServer::new(store.clone());
+        let chart_id = store
+            .add_element(
+                "default",
+                Element::new(ElementKind::Chart {
+                    chart_type: "bar".to_string(),
+                    data: serde_json::json!({}),
+                    binding: None,
+                })
+                .with_transform(Transform {
+                    x: 1000.0,
+                    y: 1000.0,
+                    width: 200.0,
+                    height: 150.0,
+                    rotation: 0.0,
+                    z_index: 0,
+                    auto_size: false,
+                }),
+            )
+            .unwrap();
+
+        let call = |id: i64, arguments: serde_json::Value| JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: serde_json::json!(id),
+            method: "tools/call".to_string(),
+            params: serde_json::json!({ "name": "canvas_camera", "arguments": arguments }),
+        };
+
+        let response = server
+            .handle_request(call(
+                1,
+                serde_json::json!({
+                    "command": "fit_to_elements",
+                    "element_ids": [chart_id.to_string()],
+                    "max_zoom": 1.0
+                }),
+            ))
+            .await;
+        assert!(response.error.is_none());
+        let scene = store.get("default").unwrap();
+        assert!((scene.zoom - 1.0).abs() < f32::EPSILON);
+        assert!((scene.pan_x - (scene.viewport_width / 2.0 - 1100.0)).abs() < 0.01);
+
+        let response = server
+            .handle_request(call(2, serde_json::json!({ "command": "reset_view" })))
+            .await;
+        assert!(response.error.is_none());
+        let scene = store.get("default").unwrap();
+        assert!(scene.pan_x.abs() < f32::EPSILON && scene.pan_y.abs() < f32::EPSILON);
+
+        let response = server
+            .handle_request(call(3, serde_json::json!({ "command": "spin" })))
+            .await;
+        assert!(response.error.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_canvas_anchor_and_go_to_anchor() {
+        let store = SceneStore::new();
+        let server = CanvasMcpServer::new(store.clone());
+        let chart_id = store
+            .add_element(
+                "default",
+                Element::new(ElementKind::Chart {
+                    chart_type: "bar".to_string(),
+                    data: serde_json::json!({}),
+                    binding: None,
+                })
+                .with_transform(Transform {
+                    x: 1000.0,
+                    y: 1000.0,
+                    width: 200.0,
+                    height: 150.0,
+                    rotation: 0.0,
+                    z_index: 0,
+                    auto_size: false,
+                }),
+            )
+            .unwrap();
+
+        let call = |name: &str, arguments: serde_json::Value| JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: serde_json::json!(1),
+            method: "tools/call".to_string(),
+            params: serde_json::json!({ "name": name, "arguments": arguments }),
+        };
+        let data = |response: JsonRpcResponse| -> serde_json::Value {
+            let result = response.result.expect("tool result");
+            let text = result["content"][0]["text"].as_str().expect("text");
+            serde_json::from_str(text).expect("json")
+        };
+
+        let response = server
+            .handle_request(call(
+                "canvas_anchor",
+                serde_json::json!({
+                    "name": "details",
+                    "element_ids": [chart_id.to_string()],
+                    "max_zoom": 1.0
+                }),
+            ))
+            .await;
+        assert!(response.error.is_none(), "{:?}", response.error);
+        let anchor = data(response)["anchor"].clone();
+        assert_eq!(anchor["x"], 1100.0);
+        assert_eq!(anchor["zoom"], 1.0);
+
+        // The current view, saved before moving
+        let response = server
+            .handle_request(call("canvas_anchor", serde_json::json!({ "name": "home" })))
+            .await;
+        assert_eq!(
+            data(response)["anchors"],
+            serde_json::json!(["details", "home"])
+        );
+
+        let response = server
+            .handle_request(call(
+                "canvas_camera",
+                serde_json::json!({ "command": "go_to_anchor", "name": "details" }),
+            ))
+            .await;
+        assert!(response.error.is_none(), "{:?}", response.error);
+        let scene = store.get("default").unwrap();
+        assert!((scene.pan_x - (scene.viewport_width / 2.0 - 1100.0)).abs() < 0.01);
+
+        for arguments in [
+            serde_json::json!({ "name": "half", "x": 10.0 }),
+            serde_json::json!({ "name": "flat", "x": 0.0, "y": 0.0, "zoom": 0.0 }),
+            serde_json::json!({ "name": "nowhere", "remove": true }),
+        ] {
+            let response = server
+                .handle_request(call("canvas_anchor", arguments))
+                .await;
+            assert!(response.error.is_some());
+        }
+
+        let response = server
+            .handle_request(call(
+                "canvas_anchor",
+                serde_json::json!({ "name": "details", "remove": true }),
+            ))
+            .await;
+        assert_eq!(data(response)["anchors"], serde_json::json!(["home"]));
+        let response = server
+            .handle_request(call(
+                "canvas_camera",
+                serde_json::json!({ "command": "go_to_anchor", "name": "details" }),
+            ))
+            .await;
+        assert!(response.error.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_canvas_set_theme_restyles_elements() {
+        let store = SceneStore::new();
+        let server = CanvasMcpServer::new(store.clone());
+        let id = store
+            .add_element(
+                "default",
+                Element::new(ElementKind::Text {
+                    content: "Title".to_string(),
+                    font_size: 24.0,
+                    color: ThemeColor::variable("accent"),
+                }),
+            )
+            .unwrap();
+        let call = |theme: serde_json::Value| JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: serde_json::json!(1),
+            method: "tools/call".to_string(),
+            params: serde_json::json!({
+                "name": "canvas_set_theme",
+                "arguments": { "theme": theme }
+            }),
+        };
+
+        let response = server
+            .handle_request(call(serde_json::json!({
+                "variables": { "accent": "palette(brand, 1)" },
+                "palettes": { "brand": ["#1976d2", "#ff9800"] }
+            })))
+            .await;
+        assert!(response.error.is_none(), "{:?}", response.error);
+        let scene = store.get("default").unwrap();
+        let kind = &scene.get_element(id).unwrap().kind;
+        assert_eq!(
+            kind.resolved_color(scene.theme()),
+            Some(Color::from_rgb(0xff_9800))
+        );
+
+        for theme in [
+            serde_json::json!({ "variables": { "accent": "chartreuse-ish" } }),
+            serde_json::json!({ "variables": { "a": "var(--b)", "b": "#fff" } }),
+            serde_json::json!({ "palettes": { "empty": [] } }),
+        ] {
+            let response = server.handle_request(call(theme)).await;
+            assert!(response.error.is_some());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_canvas_branding_sets_and_clears() {
+        let store = SceneStore::new();
+        let server = CanvasMcpServer::new(store.clone());
+        let call = |arguments: serde_json::Value| JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: serde_json::json!(1),
+            method: "tools/call".to_string(),
+            params: serde_json::json!({ "name": "canvas_branding", "arguments": arguments }),
+        };
+
+        let response = server
+            .handle_request(call(serde_json::json!({
+                "watermark": "CONFIDENTIAL",
+                "position": "top_left",
+                "opacity": 0.3
+            })))
+            .await;
+        assert!(response.error.is_none(), "{:?}", response.error);
+        let scene = store.get("default").unwrap();
+        let branding = scene.branding().expect("branding");
+        assert_eq!(branding.watermark.as_deref(), Some("CONFIDENTIAL"));
+        assert_eq!(branding.position, canvas_core::BrandingPosition::TopLeft);
+
+        for arguments in [
+            serde_json::json!({}),
+            serde_json::json!({ "watermark": "DRAFT", "opacity": 2.0 }),
+            serde_json::json!({ "watermark": "DRAFT", "position": "middle" }),
+        ] {
+            let response = server.handle_request(call(arguments)).await;
+            assert!(response.error.is_some());
+        }
+        assert!(store.get("default").unwrap().branding().is_some());
+
+        let response = server
+            .handle_request(call(serde_json::json!({ "clear": true })))
+            .await;
+        assert!(response.error.is_none(), "{:?}", response.error);
+        assert!(store.get("default").unwrap().branding().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_canvas_data_refills_bound_charts() {
+        let store = SceneStore::new();
+        let server = CanvasMcpServer::new(store.clone());
+        let call = |name: &str, arguments: serde_json::Value| JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: serde_json::json!(1),
+            method: "tools/call".to_string(),
+            params: serde_json::json!({ "name": name, "arguments": arguments }),
+        };
+
+        let response = server
+            .handle_request(call(
+                "canvas_data",
+                serde_json::json!({
+                    "dataset": "sales",
+                    "columns": ["month", "revenue"],
+                    "rows": [["Jan", 100], ["Feb", 120]]
+                }),
+            ))
+            .await;
+        assert!(response.error.is_none(), "{:?}", response.error);
+        let response = server
+            .handle_request(call(
+                "canvas_add_element",
+                serde_json::json!({
+                    "kind": {
+                        "type": "Chart",
+                        "data": {
+                            "chart_type": "line",
+                            "data": { "title": "Revenue" },
+                            "binding": { "dataset": "sales", "query": { "x": "month" } }
+                        }
+                    }
+                }),
+            ))
+            .await;
+        assert!(response.error.is_none(), "{:?}", response.error);
+
+        let response = server
+            .handle_request(call(
+                "canvas_data",
+                serde_json::json!({
+                    "dataset": "sales",
+                    "mode": "append",
+                    "rows": [["Mar", 90]],
+                    "keep_last": 2
+                }),
+            ))
+            .await;
+        let result = response.result.expect("append result");
+        let text = result["content"][0]["text"].as_str().unwrap();
+        let data: serde_json::Value = serde_json::from_str(text).unwrap();
+        assert_eq!(data["rows"], 2);
+        assert_eq!(data["refreshed_charts"].as_array().map(Vec::len), Some(1));
+        let scene = store.get("default").unwrap();
+        let chart = scene.elements().next().unwrap();
+        let ElementKind::Chart { data, .. } = &chart.kind else {
+            panic!("expected chart");
+        };
+        assert_eq!(data["title"], "Revenue");
+        assert_eq!(data["x_labels"], serde_json::json!(["Feb", "Mar"]));
+
+        for arguments in [
+            serde_json::json!({ "dataset": "missing", "mode": "append", "rows": [[1]] }),
+            serde_json::json!({ "dataset": "sales", "columns": ["a"], "rows": [[1, 2]] }),
+            serde_json::json!({ "dataset": "sales", "mode": "merge" }),
+        ] {
+            let response = server.handle_request(call("canvas_data", arguments)).await;
+            assert!(response.error.is_some());
+        }
+
+        // Agents limited to their own elements cannot refill others' charts
+        server
+            .set_agent_scope("agent-a", AgentScope::new().own_elements_only())
+            .await;
+        let response = server
+            .handle_request(call(
+                "canvas_data",
+                serde_json::json!({
+                    "agent_id": "agent-a",
+                    "dataset": "sales",
+                    "mode": "append",
+                    "rows": [["Apr", 80]]
+                }),
+            ))
+            .await;
+        assert_eq!(
+            response.error.and_then(|e| e.data).unwrap()["reason"],
+            "not_owner"
+        );
+        assert_eq!(
+            store
+                .get("default")
+                .unwrap()
+                .data()
+                .get("sales")
+                .unwrap()
+                .rows
+                .len(),
+            2
+        );
+    }
+
+    struct FixedOcr;
+
+    impl OcrEngine for FixedOcr {
+        fn name(&self) -> &'static str {
+            "fixed"
+        }
+
+        fn recognize(&self, image: &[u8], mime_type: &str) -> Result<OcrText, OcrError> {
+            assert_eq!((image, mime_type), (&b"hello"[..], "image/png"));
+            Ok(OcrText::new("Error 404: page not found", ""))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_canvas_ocr_stores_searchable_text() {
+        let store = SceneStore::new();
+        let mut server = CanvasMcpServer::new(store.clone());
+        let call = |name: &str, arguments: serde_json::Value| JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: serde_json::json!(1),
+            method: "tools/call".to_string(),
+            params: serde_json::json!({ "name": name, "arguments": arguments }),
+        };
+        let image = Element::new(ElementKind::Image {
+            src: "data:image/png;base64,aGVsbG8=".to_string(),
+            format: ImageFormat::Png,
+            annotations: Vec::new(),
+            extracted_text: None,
+        });
+        let id = image.id;
+        store
+            .update("default", |scene| {
+                scene.add_element(image);
+            })
+            .unwrap();
+        let arguments = serde_json::json!({ "element_id": id.to_string() });
+
+        let response = server
+            .handle_request(call("canvas_ocr", arguments.clone()))
+            .await;
+        assert!(response.error.is_some(), "no engine configured");
+
+        server.set_ocr_engine(Arc::new(FixedOcr));
+        server
+            .set_agent_scope("agent-a", AgentScope::new().own_elements_only())
+            .await;
+        let response = server
+            .handle_request(call(
+                "canvas_ocr",
+                serde_json::json!({ "agent_id": "agent-a", "element_id": id.to_string() }),
+            ))
+            .await;
+        assert_eq!(
+            response.error.and_then(|e| e.data).unwrap()["reason"],
+            "not_owner"
+        );
+
+        let response = server.handle_request(call("canvas_ocr", arguments)).await;
+        let result = response.result.expect("ocr result");
+        let text = result["content"][0]["text"].as_str().unwrap();
+        let data: serde_json::Value = serde_json::from_str(text).unwrap();
+        assert_eq!(data["engine"], "fixed");
+        assert_eq!(data["text"], "Error 404: page not found");
+
+        let scene = store.get("default").unwrap();
+        assert_eq!(
+            canvas_core::match_elements(&scene, "page not found"),
+            vec![id]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_canvas_ocr_runs_inside_a_transaction() {
+        let store = SceneStore::new();
+        let mut server = CanvasMcpServer::new(store.clone());
+        server.set_ocr_engine(Arc::new(FixedOcr));
+        let call = |name: &str, arguments: serde_json::Value| JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: serde_json::json!(1),
+            method: "tools/call".to_string(),
+            params: serde_json::json!({ "name": name, "arguments": arguments }),
+        };
+        let data = |response: JsonRpcResponse| -> serde_json::Value {
+            let result = response.result.expect("tool result");
+            serde_json::from_str(result["content"][0]["text"].as_str().unwrap()).unwrap()
+        };
+        let image = Element::new(ElementKind::Image {
+            src: "data:image/png;base64,aGVsbG8=".to_string(),
+            format: ImageFormat::Png,
+            annotations: Vec::new(),
+            extracted_text: None,
+        });
+        let id = image.id;
+        store
+            .update("default", |scene| {
+                scene.add_element(image);
+            })
+            .unwrap();
+
+        let begun = data(
+            server
+                .handle_request(call("canvas_begin_transaction", serde_json::json!({})))
+                .await,
+        );
+        let tx_id = begun["transaction_id"].clone();
+        let response = server
+            .handle_request(call(
+                "canvas_ocr",
+                serde_json::json!({ "transaction_id": tx_id, "element_id": id.to_string() }),
+            ))
+            .await;
+        assert!(response.error.is_none(), "{:?}", response.error);
+        let scene = store.get("default").unwrap();
+        assert!(canvas_core::match_elements(&scene, "page not found").is_empty());
+
+        server
+            .handle_request(call(
+                "canvas_commit_transaction",
+                serde_json::json!({ "transaction_id": tx_id }),
+            ))
+            .await;
+        let scene = store.get("default").unwrap();
+        assert_eq!(
+            canvas_core::match_elements(&scene, "page not found"),
+            vec![id]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_canvas_archive_restores_by_reference() {
+        let store = SceneStore::new();
+        let mut server = CanvasMcpServer::new(store.clone());
+        let call = |name: &str, arguments: serde_json::Value| JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: serde_json::json!(1),
+            method: "tools/call".to_string(),
+            params: serde_json::json!({ "name": name, "arguments": arguments }),
+        };
+        let data = |response: JsonRpcResponse| -> serde_json::Value {
+            let result = response.result.expect("tool result");
+            serde_json::from_str(result["content"][0]["text"].as_str().unwrap()).unwrap()
+        };
+        let image = Element::new(ElementKind::Image {
+            src: "data:image/png;base64,aGVsbG8=".to_string(),
+            format: ImageFormat::Png,
+            annotations: Vec::new(),
+            extracted_text: None,
+        });
+        let id = image.id;
+        store
+            .update("default", |scene| {
+                scene.add_element(image);
+            })
+            .unwrap();
+        let arguments = serde_json::json!({ "session_id": "default" });
+
+        let response = server
+            .handle_request(call("canvas_archive", arguments.clone()))
+            .await;
+        assert!(response.error.is_some(), "no store configured");
+
+        server.set_archive_store(Arc::new(crate::MemoryArchiveStore::new()));
+        let first = data(
+            server
+                .handle_request(call("canvas_archive", arguments.clone()))
+                .await,
+        );
+        assert_eq!(first["store"], "memory");
+        assert_eq!(first["asset_count"], 1);
+        let second = data(
+            server
+                .handle_request(call("canvas_archive", arguments))
+                .await,
+        );
+        assert_eq!(second["history"][0]["reference"], first["reference"]);
+
+        let restored = data(
+            server
+                .handle_request(call(
+                    "canvas_restore",
+                    serde_json::json!({ "session_id": "copy", "reference": first["reference"] }),
+                ))
+                .await,
+        );
+        assert_eq!(restored["archived_from"], "default");
+        assert_eq!(restored["history"][0]["reference"], first["reference"]);
+        let scene = store.get("copy").expect("restored session");
+        let ElementKind::Image { src, .. } = &scene.get_element(id).expect("image").kind else {
+            panic!("expected image");
+        };
+        assert_eq!(src, "data:image/png;base64,aGVsbG8=");
+
+        let response = server
+            .handle_request(call(
+                "canvas_restore",
+                serde_json::json!({ "reference": "memory:missing" }),
+            ))
+            .await;
+        assert!(response.error.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_returns_patch_without_applying() {
+        let store = SceneStore::new();
+        let mut server = CanvasMcpServer::new(store.clone());
+        let changes = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counter = Arc::clone(&changes);
+        server.set_on_change(move |_, _| {
+            counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        });
+
+        let response = server
+            .handle_request(JsonRpcRequest {
+                jsonrpc: "2.0".to_string(),
+                id: serde_json::json!(1),
+                method: "tools/call".to_string(),
+                params: serde_json::json!({
+                    "name": "canvas_add_element",
+                    "arguments": {
+                        "dry_run": true,
+                        "preview": true,
+                        "kind": {
+                            "type": "Text",
+                            "data": { "content": "Draft", "font_size": 16.0, "color": "#000000" }
+                        }
+                    }
+                }),
+            })
+            .await;
+
+        let result = response.result.expect("dry run result");
+        let text = result["content"][0]["text"].as_str().unwrap();
+        let data: serde_json::Value = serde_json::from_str(text).unwrap();
+        assert_eq!(data["dry_run"], true);
+        assert_eq!(data["patch"]["ops"][0]["op"], "add");
+        assert_eq!(
+            data["patch"]["ops"][0]["element"]["id"],
+            data["result"]["element_id"]
+        );
+        #[cfg(feature = "preview")]
+        assert_eq!(data["preview"]["mime_type"], "image/png");
+
+        // Nothing was applied or broadcast
+        assert!(store.get("default").is_none_or(|s| s.is_empty()));
+        assert_eq!(changes.load(std::sync::atomic::Ordering::SeqCst), 0);
+
+        // Validation errors are still reported
+        let response = server
+            .handle_request(JsonRpcRequest {
+                jsonrpc: "2.0".to_string(),
+                id: serde_json::json!(2),
+                method: "tools/call".to_string(),
+                params: serde_json::json!({
+                    "name": "canvas_remove_element",
+                    "arguments": { "dry_run": true, "element_id": "not-a-uuid" }
+                }),
+            })
+            .await;
+        assert!(response.error.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_canvas_validate() {
+        let store = SceneStore::new();
+        let server = CanvasMcpServer::new(store.clone());
+        let mut stray = Element::new(ElementKind::Text {
+            content: "Stray".to_string(),
+            font_size: 16.0,
+            color: Color::BLACK.into(),
+        });
+        stray.parent = Some(ElementId::new());
+        let _ = store.get_or_create("default");
+        store
+            .update("default", |scene| {
+                scene.add_element(stray);
+            })
+            .expect("seed");
+
+        let response = server
+            .handle_request(JsonRpcRequest {
+                jsonrpc: "2.0".to_string(),
+                id: serde_json::json!(1),
+                method: "tools/call".to_string(),
+                params: serde_json::json!({
+                    "name": "canvas_validate",
+                    "arguments": { "session_id": "default" }
+                }),
+            })
+            .await;
+
+        let result = response.result.expect("validate result");
+        let text = result["content"][0]["text"].as_str().unwrap();
+        let data: serde_json::Value = serde_json::from_str(text).unwrap();
+        assert_eq!(data["valid"], false);
+        assert_eq!(data["issues"][0]["issue"], "missing_parent");
+        assert!(data["summary"][0]
+            .as_str()
+            .unwrap()
+            .contains("not in the scene"));
+        assert_eq!(data["stats"]["by_kind"]["Text"], 1);
+    }
+
+    #[tokio::test]
+    async fn test_canvas_lint_reports_and_applies_fixes() {
+        let store = SceneStore::new();
+        let server = CanvasMcpServer::new(store.clone());
+        let text = |x: f32, font_size: f32| {
+            Element::new(ElementKind::Text {
+                content: "Label".to_string(),
+                font_size,
+                color: Color::BLACK.into(),
+            })
+            .with_transform(Transform {
+                x,
+                y: 10.0,
+                width: 100.0,
+                height: 20.0,
+                ..Transform::default()
+            })
+        };
+        let tiny = text(10.0, 6.0);
+        let tiny_id = tiny.id;
+        let _ = store.get_or_create("default");
+        store
+            .update("default", |scene| {
+                scene.add_element(tiny);
+                scene.add_element(text(790.0, 16.0));
+            })
+            .expect("seed");
+        let lint = |id: i64, arguments: serde_json::Value| JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: serde_json::json!(id),
+            method: "tools/call".to_string(),
+            params: serde_json::json!({ "name": "canvas_lint", "arguments": arguments }),
+        };
+        let data = |response: JsonRpcResponse| -> serde_json::Value {
+            let result = response.result.expect("lint result");
+            serde_json::from_str(result["content"][0]["text"].as_str().unwrap()).unwrap()
+        };
+
+        let report = data(
+            server
+                .handle_request(lint(1, serde_json::json!({ "session_id": "default" })))
+                .await,
+        );
+        let findings = report["findings"].as_array().expect("findings");
+        assert_eq!(findings.len(), 2);
+        assert_eq!(findings[0]["rule"], "tiny_font");
+        assert_eq!(findings[0]["element_id"], tiny_id.to_string());
+        assert_eq!(findings[0]["fix"]["action"], "set_font_size");
+        assert_eq!(findings[1]["rule"], "out_of_bounds");
+        assert_eq!(findings[1]["fix"]["action"], "move");
+
+        let only_fonts = data(
+            server
+                .handle_request(lint(
+                    2,
+                    serde_json::json!({ "session_id": "default", "rules": ["tiny_font"], "min_font_size": 5 }),
+                ))
+                .await,
+        );
+        assert_eq!(only_fonts["findings"], serde_json::json!([]));
+
+        let applied = data(
+            server
+                .handle_request(lint(
+                    3,
+                    serde_json::json!({ "session_id": "default", "apply": true }),
+                ))
+                .await,
+        );
+        assert_eq!(applied["fixed"].as_array().map(Vec::len), Some(2));
+        assert_eq!(applied["remaining"], serde_json::json!([]));
+        assert!(store
+            .get("default")
+            .expect("scene")
+            .lint(&LintConfig::default())
+            .is_empty());
+
+        for arguments in [
+            serde_json::json!({ "session_id": "default", "rules": ["spelling"] }),
+            serde_json::json!({ "session_id": "default", "min_contrast": 0 }),
+            serde_json::json!({ "session_id": "missing" }),
+        ] {
+            assert!(server
+                .handle_request(lint(4, arguments))
+                .await
+                .error
+                .is_some());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_export_single_element() {
+        let store = SceneStore::new();
+        let server = CanvasMcpServer::new(store.clone());
+        let chart = Element::new(ElementKind::Chart {
+            chart_type: "bar".to_string(),
+            data: serde_json::json!({"labels": ["a", "b"], "values": [1, 2]}),
+            binding: None,
+        });
+        let chart_id = chart.id;
+        let _ = store.get_or_create("default");
+        store
+            .update("default", |scene| {
+                scene.add_element(chart);
+            })
+            .expect("seed");
+        let export = |id: i64, arguments: serde_json::Value| JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: serde_json::json!(id),
+            method: "tools/call".to_string(),
+            params: serde_json::json!({ "name": "canvas_export", "arguments": arguments }),
+        };
+
+        let response = server
+            .handle_request(export(
+                1,
+                serde_json::json!({
+                    "session_id": "default",
+                    "format": "svg",
+                    "element_id": chart_id.to_string(),
+                    "width": 400
+                }),
+            ))
+            .await;
+        #[cfg(feature = "preview")]
+        {
+            let result = response.result.expect("element export");
+            let text = result["content"][0]["text"].as_str().unwrap();
+            let data: serde_json::Value = serde_json::from_str(text).unwrap();
+            assert_eq!(data["mime_type"], "image/svg+xml");
+            assert!(data["data"].as_str().unwrap().contains("bar chart"));
+        }
+        #[cfg(not(feature = "preview"))]
+        assert!(response.error.is_some());
+
+        let response = server
+            .handle_request(export(
+                2,
+                serde_json::json!({
+                    "session_id": "default",
+                    "format": "png",
+                    "element_id": ElementId::new().to_string()
+                }),
+            ))
+            .await;
+        let error = response.error.expect("missing element");
+        assert!(error.message.contains("Element not found"));
+    }
+
+    #[tokio::test]
+    async fn test_export_session() {
+        let store = SceneStore::new();
+        let server = CanvasMcpServer::new(store.clone());
+        let _ = store.get_or_create("default");
+        store
+            .update("default", |scene| {
+                scene.add_element(Element::new(ElementKind::Chart {
+                    chart_type: "line".to_string(),
+                    data: serde_json::json!({"labels": ["a", "b"], "values": [1, 2]}),
+                    binding: None,
+                }));
+            })
+            .expect("seed");
+        let export = |id: i64, arguments: serde_json::Value| JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: serde_json::json!(id),
+            method: "tools/call".to_string(),
+            params: serde_json::json!({ "name": "canvas_export", "arguments": arguments }),
+        };
+
+        let response = server
+            .handle_request(export(
+                1,
+                serde_json::json!({ "session_id": "default", "format": "svg", "width": 640, "height": 480 }),
+            ))
+            .await;
+        #[cfg(feature = "preview")]
+        {
+            let result = response.result.expect("session export");
+            let text = result["content"][0]["text"].as_str().unwrap();
+            let data: serde_json::Value = serde_json::from_str(text).unwrap();
+            assert_eq!(data["mime_type"], "image/svg+xml");
+            let svg = data["data"].as_str().unwrap();
+            assert!(svg.starts_with("<svg"));
+            assert!(svg.contains("width=\"640\" height=\"480\""));
+        }
+        #[cfg(not(feature = "preview"))]
+        assert!(response.error.is_some());
+
+        let response = server
+            .handle_request(export(
+                2,
+                serde_json::json!({ "session_id": "missing", "format": "png" }),
+            ))
+            .await;
+        let error = response.error.expect("missing session");
+        assert!(error.message.contains("Session not found"));
+    }
+
+    #[tokio::test]
+    async fn test_redacted_scene_and_export() {
+        let store = SceneStore::new();
+        let server = CanvasMcpServer::new(store.clone());
+        let _ = store.get_or_create("default");
+        store
+            .update("default", |scene| {
+                scene.add_element(
+                    Element::new(ElementKind::Text {
+                        content: "Acquiring Initech".to_string(),
+                        font_size: 16.0,
+                        color: canvas_core::Color::BLACK.into(),
+                    })
+                    .with_sensitive(true),
+                );
+            })
+            .expect("seed");
+        let call = |id: i64, name: &str, arguments: serde_json::Value| JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: serde_json::json!(id),
+            method: "tools/call".to_string(),
+            params: serde_json::json!({ "name": name, "arguments": arguments }),
+        };
+        let text = |response: JsonRpcResponse| {
+            let result = response.result.expect("result");
+            result["content"][0]["text"].as_str().unwrap().to_string()
+        };
+
+        let plain = text(
+            server
+                .handle_request(call(1, "canvas_get_scene", serde_json::json!({})))
+                .await,
+        );
+        assert!(plain.contains("Initech"));
+        let redacted = text(
+            server
+                .handle_request(call(
+                    2,
+                    "canvas_get_scene",
+                    serde_json::json!({ "redact": true }),
+                ))
+                .await,
+        );
+        assert!(!redacted.contains("Initech"));
+        assert!(redacted.contains("\"sensitive\": true"));
 
-/// Schema for `canvas_update_element` tool.
-fn update_element_tool_schema() -> serde_json::Value {
-    let mut transform = transform_property();
-    transform["description"] = serde_json::json!("New transform values (partial update supported)");
+        let response = server
+            .handle_request(call(
+                3,
+                "canvas_export",
+                serde_json::json!({ "session_id": "default", "format": "svg", "redact": "placeholder" }),
+            ))
+            .await;
+        #[cfg(feature = "preview")]
+        assert!(!text(response).contains("Initech"));
+        #[cfg(not(feature = "preview"))]
+        assert!(response.error.is_some());
+    }
 
-    serde_json::json!({
-        "type": "object",
-        "properties": {
-            "session_id": session_id_property(),
-            "element_id": element_id_property(),
-            "transform": transform,
-            "interactive": {
-                "type": "boolean",
-                "description": "Whether the element responds to interactions"
-            }
-        },
-        "required": ["element_id"]
-    })
-}
+    #[tokio::test]
+    async fn test_export_print_layout() {
+        let store = SceneStore::new();
+        let server = CanvasMcpServer::new(store.clone());
+        for y in [0.0, 1500.0, 3000.0] {
+            store
+                .add_element(
+                    "notes",
+                    Element::new(ElementKind::Text {
+                        content: "Minutes".to_string(),
+                        font_size: 16.0,
+                        color: Color::BLACK.into(),
+                    })
+                    .with_transform(Transform {
+                        x: 0.0,
+                        y,
+                        width: 300.0,
+                        height: 100.0,
+                        rotation: 0.0,
+                        z_index: 0,
+                        auto_size: false,
+                    }),
+                )
+                .expect("seed");
+        }
+        let export = |format: &str| JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: serde_json::json!(1),
+            method: "tools/call".to_string(),
+            params: serde_json::json!({
+                "name": "canvas_export",
+                "arguments": {
+                    "session_id": "notes",
+                    "format": format,
+                    "print": { "paper": "letter", "dpi": 96 }
+                }
+            }),
+        };
 
-/// Schema for `canvas_get_scene` tool.
-fn get_scene_tool_schema() -> serde_json::Value {
-    serde_json::json!({
-        "type": "object",
-        "properties": {
-            "session_id": session_id_property()
+        let response = server.handle_request(export("pdf")).await;
+        #[cfg(feature = "preview")]
+        {
+            let result = response.result.expect("print export");
+            let text = result["content"][0]["text"].as_str().unwrap();
+            let data: serde_json::Value = serde_json::from_str(text).unwrap();
+            assert_eq!(data["mime_type"], "application/pdf");
+            assert_eq!(data["pages"], 3);
         }
-    })
-}
+        #[cfg(not(feature = "preview"))]
+        assert!(response.error.is_some());
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+        let response = server.handle_request(export("png")).await;
+        let error = response.error.expect("print needs pdf");
+        assert!(error.message.contains("pdf"));
+    }
 
     #[tokio::test]
-    async fn test_initialize() {
-        let server = CanvasMcpServer::new(SceneStore::new());
+    async fn test_read_only_rejects_writes() {
+        let store = SceneStore::new();
+        let mut server = CanvasMcpServer::new(store.clone());
+        server.set_read_only(true);
+        let call = |id: i64, name: &str, arguments: serde_json::Value| JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: serde_json::json!(id),
+            method: "tools/call".to_string(),
+            params: serde_json::json!({ "name": name, "arguments": arguments }),
+        };
+        let text = serde_json::json!({
+            "kind": {
+                "type": "Text",
+                "data": { "content": "Hi", "font_size": 16.0, "color": "#000000" }
+            }
+        });
+
         let response = server
-            .handle_request(JsonRpcRequest {
-                jsonrpc: "2.0".to_string(),
-                id: serde_json::json!(1),
-                method: "initialize".to_string(),
-                params: serde_json::json!({}),
-            })
+            .handle_request(call(1, "canvas_add_element", text.clone()))
+            .await;
+        let error = response.error.expect("write rejected");
+        assert!(error.message.contains("Read replica"));
+        assert!(CanvasMcpServer::is_write(&call(
+            1,
+            "canvas_add_element",
+            text.clone()
+        )));
+        assert!(!CanvasMcpServer::is_write(&call(
+            1,
+            "canvas_get_scene",
+            serde_json::json!({})
+        )));
+        assert!(CanvasMcpServer::is_write(&call(
+            1,
+            "canvas_lint",
+            serde_json::json!({ "apply": true })
+        )));
+        assert!(!CanvasMcpServer::is_write(&call(
+            1,
+            "canvas_lint",
+            serde_json::json!({})
+        )));
+        assert!(store.get("default").is_none_or(|s| s.is_empty()));
+
+        let response = server
+            .handle_request(call(2, "canvas_begin_transaction", serde_json::json!({})))
             .await;
+        assert!(response.error.is_some());
 
+        // Reads and dry runs still work
+        let mut dry_run = text;
+        dry_run["dry_run"] = serde_json::json!(true);
+        let response = server
+            .handle_request(call(3, "canvas_add_element", dry_run))
+            .await;
         assert!(response.result.is_some());
+        let response = server
+            .handle_request(call(4, "canvas_get_scene", serde_json::json!({})))
+            .await;
+        assert!(response.result.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_agent_scope_enforced() {
+        let store = SceneStore::new();
+        let server = CanvasMcpServer::new(store.clone());
+        let layer = store
+            .add_element(
+                "default",
+                Element::new(ElementKind::OverlayLayer {
+                    children: Vec::new(),
+                    opacity: 0.0,
+                    clip: false,
+                }),
+            )
+            .unwrap();
+        let foreign = store
+            .add_element(
+                "default",
+                Element::new(ElementKind::Text {
+                    content: "Human note".to_string(),
+                    font_size: 16.0,
+                    color: Color::BLACK.into(),
+                }),
+            )
+            .unwrap();
+        server
+            .set_agent_scope(
+                "agent-a",
+                AgentScope::new()
+                    .with_tools([
+                        "canvas_add_element",
+                        "canvas_update_element",
+                        "canvas_remove_element",
+                    ])
+                    .own_elements_only()
+                    .within_layer(layer),
+            )
+            .await;
+
+        let call = |id: i64, name: &str, arguments: serde_json::Value| JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: serde_json::json!(id),
+            method: "tools/call".to_string(),
+            params: serde_json::json!({ "name": name, "arguments": arguments }),
+        };
+
+        // Tools outside the allowed list are rejected
+        let response = server
+            .handle_request(call(
+                1,
+                "canvas_clear",
+                serde_json::json!({ "agent_id": "agent-a" }),
+            ))
+            .await;
+        let error = response.error.expect("scope violation");
+        assert_eq!(error.code, SCOPE_VIOLATION_CODE);
+        let data = error.data.expect("violation data");
+        assert_eq!(data["reason"], "tool_not_allowed");
+        assert_eq!(data["agent_id"], "agent-a");
+
+        // New elements are owned by the agent and placed in its layer
+        let response = server
+            .handle_request(call(
+                2,
+                "canvas_add_element",
+                serde_json::json!({
+                    "agent_id": "agent-a",
+                    "kind": {
+                        "type": "Text",
+                        "data": { "content": "Mine", "font_size": 14.0, "color": "#000000" }
+                    }
+                }),
+            ))
+            .await;
+        assert!(response.error.is_none());
+        let scene = store.get("default").unwrap();
+        let created = scene.get_element(layer).unwrap().kind.children()[0];
+        assert_eq!(
+            server.element_owner("default", created).await.as_deref(),
+            Some("agent-a")
+        );
+
+        // Elements created by someone else cannot be modified
+        let response = server
+            .handle_request(call(
+                3,
+                "canvas_remove_element",
+                serde_json::json!({ "agent_id": "agent-a", "element_id": foreign.to_string() }),
+            ))
+            .await;
+        assert_eq!(
+            response.error.and_then(|e| e.data).unwrap()["reason"],
+            "not_owner"
+        );
+        assert!(store.get("default").unwrap().get_element(foreign).is_some());
+
+        // Unscoped callers are unaffected
+        let response = server
+            .handle_request(call(
+                5,
+                "canvas_remove_element",
+                serde_json::json!({ "element_id": foreign.to_string() }),
+            ))
+            .await;
         assert!(response.error.is_none());
     }
 
     #[tokio::test]
-    async fn test_tools_list() {
+    async fn test_scoped_agents_cannot_replace_the_scene() {
         let server = CanvasMcpServer::new(SceneStore::new());
+        let scope = AgentScope::new().own_elements_only();
+        for name in [
+            "canvas_clear",
+            "canvas_branding",
+            "canvas_set_theme",
+            "canvas_anchor",
+            "canvas_restore",
+        ] {
+            let result = server
+                .authorize(name, &serde_json::json!({}), Some("agent-a"), &scope)
+                .await;
+            assert_eq!(
+                result,
+                Err(ScopeViolation::SceneWide {
+                    tool: name.to_string()
+                }),
+                "{name}"
+            );
+        }
+        let apply = serde_json::json!({ "apply": true });
+        assert_eq!(
+            server
+                .authorize("canvas_lint", &apply, Some("agent-a"), &scope)
+                .await,
+            Err(ScopeViolation::SceneWide {
+                tool: "canvas_lint".to_string()
+            })
+        );
+        assert!(server
+            .authorize(
+                "canvas_lint",
+                &serde_json::json!({}),
+                Some("agent-a"),
+                &scope
+            )
+            .await
+            .is_ok());
+        let unscoped = AgentScope::new();
+        assert!(server
+            .authorize("canvas_restore", &serde_json::json!({}), None, &unscoped)
+            .await
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_every_mutating_tool_is_checked_for_scoped_agents() {
+        let store = SceneStore::new();
+        let server = CanvasMcpServer::new(store.clone());
+        let foreign = store
+            .add_element(
+                "default",
+                Element::new(ElementKind::Chart {
+                    chart_type: "line".to_string(),
+                    data: serde_json::json!({}),
+                    binding: Some(
+                        serde_json::from_value(serde_json::json!({ "dataset": "sales" })).unwrap(),
+                    ),
+                }),
+            )
+            .unwrap();
+        let spotlight = Spotlight::new(vec![foreign]);
+        store
+            .update("default", |scene| scene.set_spotlight(spotlight).unwrap())
+            .unwrap();
+        let scope = AgentScope::new().own_elements_only();
+
+        // Tools that only add the agent's own elements; every other tool
+        // must refuse to touch someone else's chart or the shared scene
+        let creates = [
+            "canvas_render",
+            "canvas_add_element",
+            "canvas_poll",
+            "canvas_tree",
+            "canvas_barcode",
+            "canvas_annotate",
+            "canvas_instantiate_template",
+        ];
+        for &name in MUTATING_TOOLS {
+            let arguments = serde_json::json!({
+                "element_id": foreign.to_string(),
+                "element_ids": [foreign.to_string()],
+                "dataset": "sales",
+            });
+            let result = server
+                .authorize(name, &arguments, Some("agent-a"), &scope)
+                .await;
+            assert_eq!(
+                result.is_ok(),
+                creates.contains(&name),
+                "{name}: {result:?}"
+            );
+        }
+        let clear = serde_json::json!({ "clear": true });
+        assert!(server
+            .authorize("canvas_spotlight", &clear, Some("agent-a"), &scope)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_read_session_resource_as_msgpack() {
+        use base64::Engine;
+
+        let store = SceneStore::new();
+        let server = CanvasMcpServer::new(store.clone());
+        store
+            .add_element(
+                "default",
+                Element::new(ElementKind::Text {
+                    content: "Hello".to_string(),
+                    font_size: 16.0,
+                    color: Color::BLACK.into(),
+                }),
+            )
+            .unwrap();
+
+        let read = |id: i64, uri: &str| JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: serde_json::json!(id),
+            method: "resources/read".to_string(),
+            params: serde_json::json!({ "uri": uri }),
+        };
+
+        let response = server
+            .handle_request(read(1, "canvas://session/default?format=msgpack"))
+            .await;
+        let result = response.result.expect("msgpack result");
+        let content = &result["contents"][0];
+        assert_eq!(content["mimeType"], "application/msgpack");
+        let bytes = base64::engine::general_purpose::STANDARD
+            .decode(content["blob"].as_str().expect("blob"))
+            .expect("base64");
+        let document: SceneDocument = Encoding::MessagePack.decode(&bytes).expect("decode");
+        assert_eq!(document.elements, store.scene_document("default").elements);
+
+        let response = server
+            .handle_request(read(2, "canvas://session/default"))
+            .await;
+        let result = response.result.expect("json result");
+        let text = result["contents"][0]["text"].as_str().expect("text");
+        let from_json: SceneDocument = serde_json::from_str(text).expect("json");
+        assert_eq!(from_json.elements, document.elements);
+
+        let response = server
+            .handle_request(read(3, "canvas://session/default?format=xml"))
+            .await;
+        assert!(response.error.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_session_thumbnails_are_listed_and_read_as_png() {
+        let store = SceneStore::new();
+        let server = CanvasMcpServer::new(store.clone());
+        store
+            .add_element(
+                "default",
+                Element::new(ElementKind::Text {
+                    content: "Hello".to_string(),
+                    font_size: 16.0,
+                    color: Color::BLACK.into(),
+                }),
+            )
+            .unwrap();
+
         let response = server
             .handle_request(JsonRpcRequest {
                 jsonrpc: "2.0".to_string(),
                 id: serde_json::json!(1),
-                method: "tools/list".to_string(),
+                method: "resources/list".to_string(),
                 params: serde_json::json!({}),
             })
             .await;
+        let listed = serde_json::to_string(&response.result.expect("list")).unwrap();
+        assert_eq!(
+            listed.contains("canvas://session/default/thumbnail.png"),
+            cfg!(feature = "preview")
+        );
 
-        assert!(response.result.is_some());
-        let result = response.result.unwrap();
-        let tools = result["tools"].as_array().unwrap();
-        assert!(!tools.is_empty());
+        let response = server
+            .handle_request(JsonRpcRequest {
+                jsonrpc: "2.0".to_string(),
+                id: serde_json::json!(2),
+                method: "resources/read".to_string(),
+                params: serde_json::json!({ "uri": "canvas://session/default/thumbnail.png" }),
+            })
+            .await;
+        #[cfg(feature = "preview")]
+        {
+            use base64::Engine;
+
+            let result = response.result.expect("thumbnail");
+            let content = &result["contents"][0];
+            assert_eq!(content["mimeType"], "image/png");
+            let png = base64::engine::general_purpose::STANDARD
+                .decode(content["blob"].as_str().expect("blob"))
+                .expect("base64");
+            assert!(png.starts_with(&[0x89, b'P', b'N', b'G']));
+        }
+        #[cfg(not(feature = "preview"))]
+        assert!(response.error.is_some());
+
+        // Redacted thumbnails hide sensitive elements like other exports
+        store
+            .update("default", |scene| {
+                for element in scene.elements_mut() {
+                    element.sensitive = true;
+                }
+            })
+            .unwrap();
+        let read = |redact: &str| JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: serde_json::json!(3),
+            method: "resources/read".to_string(),
+            params: serde_json::json!({
+                "uri": format!("canvas://session/default/thumbnail.png?redact={redact}")
+            }),
+        };
+        let response = server.handle_request(read("placeholder")).await;
+        assert_eq!(response.result.is_some(), cfg!(feature = "preview"));
+        let response = server.handle_request(read("smudge")).await;
+        assert!(response.error.is_some());
     }
 
     #[tokio::test]
-    async fn test_canvas_render() {
-        let server = CanvasMcpServer::new(SceneStore::new());
+    async fn test_render_callout_pointing_at_chart() {
+        let store = SceneStore::new();
+        let server = CanvasMcpServer::new(store.clone());
+        let chart_id = store
+            .add_element(
+                "default",
+                Element::new(ElementKind::Chart {
+                    chart_type: "line".to_string(),
+                    data: serde_json::json!({}),
+                    binding: None,
+                }),
+            )
+            .unwrap();
+        let render = |target: serde_json::Value| JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: serde_json::json!(1),
+            method: "tools/call".to_string(),
+            params: serde_json::json!({
+                "name": "canvas_render",
+                "arguments": {
+                    "session_id": "default",
+                    "agent_id": "analyst",
+                    "content": {
+                        "type": "Callout",
+                        "data": { "content": "Outage starts here", "target": target }
+                    },
+                    "position": { "x": 400.0, "y": 20.0 }
+                }
+            }),
+        };
 
-        // Initialize first
-        server
+        let response = server
+            .handle_request(render(serde_json::json!({
+                "kind": "element",
+                "element_id": chart_id.to_string()
+            })))
+            .await;
+        assert!(response.error.is_none(), "{:?}", response.error);
+        let scene = store.get("default").unwrap();
+        let callout = scene
+            .elements()
+            .find(|e| matches!(e.kind, ElementKind::Callout { .. }))
+            .expect("callout added");
+        assert_eq!(
+            callout.kind.note(),
+            Some(("Outage starts here", Some("analyst")))
+        );
+        assert!(scene.callout_tail(callout.id).is_some());
+
+        let missing = server
+            .handle_request(render(serde_json::json!({
+                "kind": "element",
+                "element_id": ElementId::new().to_string()
+            })))
+            .await;
+        assert!(missing.error.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_render_code_is_highlighted() {
+        let store = SceneStore::new();
+        let server = CanvasMcpServer::new(store.clone());
+        let response = server
             .handle_request(JsonRpcRequest {
                 jsonrpc: "2.0".to_string(),
-                id: serde_json::json!(0),
-                method: "initialize".to_string(),
-                params: serde_json::json!({}),
+                id: serde_json::json!(1),
+                method: "tools/call".to_string(),
+                params: serde_json::json!({
+                    "name": "canvas_render",
+                    "arguments": {
+                        "session_id": "default",
+                        "content": {
+                            "type": "Code",
+                            "data": { "language": "python", "source": "def f():\n    return 1" }
+                        }
+                    }
+                }),
             })
             .await;
+        assert!(response.error.is_none(), "{:?}", response.error);
+
+        let scene = store.get("default").unwrap();
+        let ElementKind::Code {
+            language, spans, ..
+        } = &scene.elements().next().expect("code added").kind
+        else {
+            panic!("expected a code element");
+        };
+        assert_eq!(language, "python");
+        assert!(spans
+            .iter()
+            .any(|span| span.kind == canvas_core::TokenKind::Keyword));
+    }
 
-        // Render a chart
+    #[tokio::test]
+    async fn test_render_audio() {
+        let store = SceneStore::new();
+        let server = CanvasMcpServer::new(store.clone());
+        let samples: Vec<f32> = (0..2000)
+            .map(|i| if i % 2 == 0 { 0.5 } else { -0.8 })
+            .collect();
         let response = server
             .handle_request(JsonRpcRequest {
                 jsonrpc: "2.0".to_string(),
@@ -1330,451 +8822,1337 @@ mod tests {
                     "arguments": {
                         "session_id": "default",
                         "content": {
-                            "type": "Chart",
-                            "data": {
-                                "chart_type": "bar",
-                                "data": {
-                                    "labels": ["A", "B", "C"],
-                                    "values": [10, 20, 30]
-                                }
-                            }
+                            "type": "Audio",
+                            "data": { "stream_id": "voice-note-7", "waveform": samples }
                         }
                     }
                 }),
-            })
+            })
+            .await;
+        assert!(response.error.is_none(), "{:?}", response.error);
+
+        let scene = store.get("default").unwrap();
+        let ElementKind::Audio {
+            stream_id,
+            waveform,
+            state,
+        } = &scene.elements().next().expect("audio added").kind
+        else {
+            panic!("expected an audio element");
+        };
+        assert_eq!(stream_id, "voice-note-7");
+        assert_eq!(waveform.len(), MAX_WAVEFORM_POINTS);
+        assert!(waveform.iter().all(|v| (v - 0.8).abs() < 1e-6));
+        assert_eq!(*state, PlaybackState::Paused);
+    }
+
+    #[tokio::test]
+    async fn test_render_embed() {
+        let store = SceneStore::new();
+        let server = CanvasMcpServer::new(store.clone());
+        let render = |url: &str| JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: serde_json::json!(1),
+            method: "tools/call".to_string(),
+            params: serde_json::json!({
+                "name": "canvas_render",
+                "arguments": {
+                    "session_id": "default",
+                    "content": { "type": "Embed", "data": { "url": url } },
+                    "position": { "x": 0, "y": 0, "width": 640, "height": 480 }
+                }
+            }),
+        };
+
+        let response = server
+            .handle_request(render("https://grafana.example.com/d/abc"))
+            .await;
+        assert!(response.error.is_none(), "{:?}", response.error);
+        let scene = store.get("default").unwrap();
+        let ElementKind::Embed { url, sandbox } = &scene.elements().next().expect("embed").kind
+        else {
+            panic!("expected an embed element");
+        };
+        assert_eq!(url, "https://grafana.example.com/d/abc");
+        assert_eq!(sandbox, &ElementKind::default_embed_sandbox());
+
+        let rejected = server.handle_request(render("javascript:alert(1)")).await;
+        assert!(rejected.error.is_some());
+        assert_eq!(store.get("default").unwrap().element_count(), 1);
+
+        let escaping = ElementKind::Embed {
+            url: "https://grafana.example.com/d/abc".to_string(),
+            sandbox: "allow-scripts allow-same-origin".to_string(),
+        };
+        assert!(validate_kind(&escaping)
+            .unwrap_err()
+            .contains("allow-same-origin"));
+    }
+
+    #[tokio::test]
+    async fn test_group_and_ungroup_tools() {
+        let store = SceneStore::new();
+        let server = CanvasMcpServer::new(store.clone());
+        let mut ids = Vec::new();
+        for x in [0.0, 150.0] {
+            ids.push(
+                store
+                    .add_element(
+                        "default",
+                        Element::new(ElementKind::Text {
+                            content: "Card".to_string(),
+                            font_size: 16.0,
+                            color: Color::BLACK.into(),
+                        })
+                        .with_transform(Transform {
+                            x,
+                            y: 0.0,
+                            width: 100.0,
+                            height: 50.0,
+                            rotation: 0.0,
+                            z_index: 0,
+                            auto_size: false,
+                        }),
+                    )
+                    .unwrap(),
+            );
+        }
+        let call = |name: &str, arguments: serde_json::Value| JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: serde_json::json!(1),
+            method: "tools/call".to_string(),
+            params: serde_json::json!({ "name": name, "arguments": arguments }),
+        };
+        let data = |response: JsonRpcResponse| -> serde_json::Value {
+            let result = response.result.expect("tool result");
+            let text = result["content"][0]["text"].as_str().expect("text");
+            serde_json::from_str(text).expect("json")
+        };
+
+        let response = server
+            .handle_request(call(
+                "canvas_group",
+                serde_json::json!({
+                    "element_ids": ids.iter().map(ToString::to_string).collect::<Vec<_>>()
+                }),
+            ))
+            .await;
+        assert!(response.error.is_none(), "{:?}", response.error);
+        let group =
+            ElementId::parse(data(response)["element_id"].as_str().expect("id")).expect("group id");
+        let scene = store.get("default").unwrap();
+        assert_eq!(
+            scene.get_element(group).map(|e| e.kind.children().to_vec()),
+            Some(ids.clone())
+        );
+        assert_eq!(
+            scene.get_element(ids[1]).map(|e| e.transform.x),
+            Some(150.0)
+        );
+
+        let rejected = server
+            .handle_request(call(
+                "canvas_group",
+                serde_json::json!({ "element_ids": [] }),
+            ))
+            .await;
+        assert!(rejected.error.is_some());
+
+        let response = server
+            .handle_request(call(
+                "canvas_ungroup",
+                serde_json::json!({ "element_id": group.to_string() }),
+            ))
+            .await;
+        assert!(response.error.is_none(), "{:?}", response.error);
+        assert_eq!(data(response)["children"].as_array().map(Vec::len), Some(2));
+        let scene = store.get("default").unwrap();
+        assert_eq!(scene.element_count(), 2);
+        assert!(scene
+            .get_element(ids[0])
+            .is_some_and(|e| e.parent.is_none()));
+    }
+
+    #[tokio::test]
+    async fn test_poll_tools() {
+        let store = SceneStore::new();
+        let server = CanvasMcpServer::new(store.clone());
+        let call = |name: &str, arguments: serde_json::Value| JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: serde_json::json!(1),
+            method: "tools/call".to_string(),
+            params: serde_json::json!({ "name": name, "arguments": arguments }),
+        };
+        let data = |response: JsonRpcResponse| -> serde_json::Value {
+            let result = response.result.expect("tool result");
+            let text = result["content"][0]["text"].as_str().expect("text");
+            serde_json::from_str(text).expect("json")
+        };
+
+        let response = server
+            .handle_request(call(
+                "canvas_poll",
+                serde_json::json!({
+                    "question": "Next topic?",
+                    "options": ["Pricing", "Roadmap", "Hiring"]
+                }),
+            ))
+            .await;
+        assert!(response.error.is_none(), "{:?}", response.error);
+        let poll =
+            ElementId::parse(data(response)["element_id"].as_str().expect("id")).expect("poll id");
+        let scene = store.get("default").unwrap();
+        assert_eq!(
+            scene.get_element(poll).map(|e| e.transform.height),
+            Some(168.0)
+        );
+
+        let rejected = server
+            .handle_request(call(
+                "canvas_poll",
+                serde_json::json!({ "question": "Only one?", "options": ["Yes"] }),
+            ))
+            .await;
+        assert!(rejected.error.is_some());
+
+        // Peers vote through the sync server
+        store
+            .update("default", |scene| {
+                let kind = &mut scene.get_element_mut(poll).expect("poll").kind;
+                for (voter, option) in [("alice", 1), ("bob", 1), ("carol", 0)] {
+                    canvas_core::poll::cast_vote(kind, voter, option).expect("vote");
+                }
+            })
+            .unwrap();
+
+        let results_call = || {
+            call(
+                "canvas_poll_results",
+                serde_json::json!({ "element_id": poll.to_string() }),
+            )
+        };
+        let results = data(server.handle_request(results_call()).await)["results"].clone();
+        assert_eq!(results["total"], 3);
+        assert_eq!(results["options"][1]["label"], "Roadmap");
+        assert_eq!(results["options"][1]["votes"], 2);
+        assert_eq!(results["closed"], false);
+
+        let response = server
+            .handle_request(call(
+                "canvas_update_element",
+                serde_json::json!({
+                    "element_id": poll.to_string(),
+                    "data": { "closed": true }
+                }),
+            ))
             .await;
-
-        assert!(response.result.is_some());
-        assert!(response.error.is_none());
+        assert!(response.error.is_none(), "{:?}", response.error);
+        let results = data(server.handle_request(results_call()).await)["results"].clone();
+        assert_eq!(results["closed"], true);
+        assert_eq!(results["total"], 3);
     }
 
     #[tokio::test]
-    async fn test_canvas_add_element() {
-        let server = CanvasMcpServer::new(SceneStore::new());
+    async fn test_render_randomizer() {
+        let store = SceneStore::new();
+        let server = CanvasMcpServer::new(store.clone());
+        let render = |source: serde_json::Value| JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: serde_json::json!(1),
+            method: "tools/call".to_string(),
+            params: serde_json::json!({
+                "name": "canvas_render",
+                "arguments": {
+                    "session_id": "default",
+                    "content": { "type": "Randomizer", "data": { "source": source } }
+                }
+            }),
+        };
 
-        // Initialize first
-        server
-            .handle_request(JsonRpcRequest {
-                jsonrpc: "2.0".to_string(),
-                id: serde_json::json!(0),
-                method: "initialize".to_string(),
-                params: serde_json::json!({}),
-            })
+        let response = server
+            .handle_request(render(serde_json::json!({
+                "mode": "wheel",
+                "segments": ["Ada", "Bea", "Cy"]
+            })))
             .await;
+        assert!(response.error.is_none(), "{:?}", response.error);
+        let scene = store.get("default").unwrap();
+        assert!(scene.elements().any(|e| matches!(
+            &e.kind,
+            ElementKind::Randomizer { source: canvas_core::RandomSource::Wheel { segments }, outcome: None }
+                if segments.len() == 3
+        )));
+
+        let rejected = server
+            .handle_request(render(serde_json::json!({ "mode": "dice", "count": 50 })))
+            .await;
+        assert!(rejected.error.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_render_gauge_and_kpi() {
+        let store = SceneStore::new();
+        let server = CanvasMcpServer::new(store.clone());
+        let render = |content: serde_json::Value| JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: serde_json::json!(1),
+            method: "tools/call".to_string(),
+            params: serde_json::json!({
+                "name": "canvas_render",
+                "arguments": { "session_id": "default", "content": content }
+            }),
+        };
 
-        // Add a text element
         let response = server
-            .handle_request(JsonRpcRequest {
-                jsonrpc: "2.0".to_string(),
-                id: serde_json::json!(1),
-                method: "tools/call".to_string(),
-                params: serde_json::json!({
-                    "name": "canvas_add_element",
-                    "arguments": {
-                        "session_id": "default",
-                        "kind": {
-                            "type": "Text",
-                            "data": {
-                                "content": "Hello World",
-                                "font_size": 24.0,
-                                "color": "#ff0000"
-                            }
-                        },
-                        "transform": {
-                            "x": 100.0,
-                            "y": 200.0,
-                            "width": 300.0,
-                            "height": 50.0
-                        }
-                    }
-                }),
-            })
+            .handle_request(render(serde_json::json!({
+                "type": "Gauge",
+                "data": {
+                    "value": 72,
+                    "label": "CPU",
+                    "thresholds": [{ "value": 80, "color": "#c62828" }],
+                    "format": { "suffix": "%" }
+                }
+            })))
             .await;
+        assert!(response.error.is_none(), "{:?}", response.error);
+        let response = server
+            .handle_request(render(serde_json::json!({
+                "type": "Kpi",
+                "data": {
+                    "label": "Revenue",
+                    "value": 1_200_000,
+                    "previous": 1_080_000,
+                    "sparkline": [3, 4, 6, 5, 8],
+                    "format": { "prefix": "$", "compact": true }
+                }
+            })))
+            .await;
+        assert!(response.error.is_none(), "{:?}", response.error);
+
+        let scene = store.get("default").unwrap();
+        assert!(scene.elements().any(|e| matches!(
+            &e.kind,
+            ElementKind::Gauge { value, max, .. } if (*value - 72.0).abs() < f64::EPSILON && (*max - 100.0).abs() < f64::EPSILON
+        )));
+        assert!(scene.elements().any(|e| matches!(
+            &e.kind,
+            ElementKind::Kpi {
+                previous: Some(_),
+                higher_is_better: true,
+                ..
+            }
+        )));
 
-        assert!(response.result.is_some());
-        assert!(response.error.is_none());
-
-        // Verify content has element_id
-        let result = response.result.unwrap();
-        let content = result["content"].as_array().unwrap();
-        let text = content[0]["text"].as_str().unwrap();
-        assert!(text.contains("element_id"));
+        let rejected = server
+            .handle_request(render(serde_json::json!({
+                "type": "Gauge",
+                "data": { "value": 5, "min": 10, "max": 10 }
+            })))
+            .await;
+        assert!(rejected.error.is_some());
     }
 
     #[tokio::test]
-    async fn test_canvas_get_scene() {
-        let server = CanvasMcpServer::new(SceneStore::new());
+    async fn test_canvas_tree_from_nested_json() {
+        let store = SceneStore::new();
+        let server = CanvasMcpServer::new(store.clone());
+        let call = |name: &str, arguments: serde_json::Value| JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: serde_json::json!(1),
+            method: "tools/call".to_string(),
+            params: serde_json::json!({ "name": name, "arguments": arguments }),
+        };
+        let data = |response: JsonRpcResponse| -> serde_json::Value {
+            let result = response.result.expect("tool result");
+            let text = result["content"][0]["text"].as_str().expect("text");
+            serde_json::from_str(text).expect("json")
+        };
 
-        // Initialize first
-        server
-            .handle_request(JsonRpcRequest {
-                jsonrpc: "2.0".to_string(),
-                id: serde_json::json!(0),
-                method: "initialize".to_string(),
-                params: serde_json::json!({}),
-            })
+        let response = server
+            .handle_request(call(
+                "canvas_tree",
+                serde_json::json!({
+                    "root": {
+                        "label": "Ada",
+                        "detail": "CEO",
+                        "children": [
+                            { "label": "Grace", "detail": "CTO", "children": [{ "label": "Linus" }] },
+                            { "id": "cfo", "label": "Barbara", "detail": "CFO" }
+                        ]
+                    },
+                    "position": { "x": 40, "y": 40 }
+                }),
+            ))
+            .await;
+        assert!(response.error.is_none(), "{:?}", response.error);
+        let data = data(response);
+        assert_eq!(data["nodes"], 4);
+        let id = ElementId::parse(data["element_id"].as_str().expect("id")).expect("tree id");
+        let scene = store.get("default").unwrap();
+        let element = scene.get_element(id).expect("tree");
+        let ElementKind::Tree { root, direction } = &element.kind else {
+            panic!("expected a tree");
+        };
+        assert_eq!(*direction, TreeDirection::Down);
+        assert_eq!(root.children[0].children[0].id, "1.1.1");
+        assert_eq!(root.children[1].id, "cfo");
+        // Sized to fit: three levels of nodes
+        assert!(element.transform.auto_size);
+        let (width, height) = canvas_core::TreeLayout::natural_size(root, *direction);
+        assert!((element.transform.width - width).abs() < f32::EPSILON);
+        assert!((element.transform.height - height).abs() < f32::EPSILON);
+
+        // Replacing the tree fills in IDs again; duplicates are rejected
+        let update = |root: serde_json::Value| {
+            call(
+                "canvas_update_element",
+                serde_json::json!({ "element_id": id.to_string(), "data": { "root": root } }),
+            )
+        };
+        let response = server
+            .handle_request(update(serde_json::json!({
+                "label": "Ada",
+                "children": [{ "label": "Grace" }]
+            })))
+            .await;
+        assert!(response.error.is_none(), "{:?}", response.error);
+        let response = server
+            .handle_request(update(serde_json::json!({
+                "id": "a",
+                "label": "Ada",
+                "children": [{ "id": "a", "label": "Grace" }]
+            })))
             .await;
+        assert!(response.error.is_some());
 
-        // Get scene
         let response = server
-            .handle_request(JsonRpcRequest {
-                jsonrpc: "2.0".to_string(),
-                id: serde_json::json!(1),
-                method: "tools/call".to_string(),
-                params: serde_json::json!({
-                    "name": "canvas_get_scene",
-                    "arguments": {
-                        "session_id": "default"
+            .handle_request(call(
+                "canvas_render",
+                serde_json::json!({
+                    "session_id": "default",
+                    "content": {
+                        "type": "Tree",
+                        "data": { "root": { "label": "Start?" }, "direction": "right" }
                     }
                 }),
-            })
+            ))
+            .await;
+        assert!(response.error.is_none(), "{:?}", response.error);
+        let rejected = server
+            .handle_request(call(
+                "canvas_tree",
+                serde_json::json!({ "root": { "id": "x" } }),
+            ))
             .await;
+        assert!(rejected.error.is_some());
+    }
 
-        assert!(response.result.is_some());
-        assert!(response.error.is_none());
+    #[tokio::test]
+    async fn test_canvas_annotate_relative_to_element() {
+        let store = SceneStore::new();
+        let server = CanvasMcpServer::new(store.clone());
+        let call = |arguments: serde_json::Value| JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: serde_json::json!(1),
+            method: "tools/call".to_string(),
+            params: serde_json::json!({ "name": "canvas_annotate", "arguments": arguments }),
+        };
+        let data = |response: JsonRpcResponse| -> serde_json::Value {
+            let result = response.result.expect("tool result");
+            let text = result["content"][0]["text"].as_str().expect("text");
+            serde_json::from_str(text).expect("json")
+        };
+        let chart = store
+            .add_element(
+                "default",
+                Element::new(ElementKind::Video {
+                    stream_id: "cam".to_string(),
+                    is_live: true,
+                    mirror: false,
+                    crop: None,
+                    media_config: None,
+                })
+                .with_transform(Transform {
+                    x: 100.0,
+                    y: 50.0,
+                    width: 320.0,
+                    height: 240.0,
+                    rotation: 0.0,
+                    z_index: 3,
+                    auto_size: false,
+                }),
+            )
+            .expect("add video");
 
-        // Verify response contains scene document
-        let result = response.result.unwrap();
-        let content = result["content"].as_array().unwrap();
-        let text = content[0]["text"].as_str().unwrap();
-        let scene_payload: serde_json::Value =
-            serde_json::from_str(text).expect("scene payload should parse");
-        assert!(scene_payload.get("scene").is_some());
+        let response = server
+            .handle_request(call(serde_json::json!({
+                "type": "measurement",
+                "element_id": chart.to_string(),
+                "start": [10, 200],
+                "end": [110, 200],
+                "unit": "cm",
+                "scale": 0.5
+            })))
+            .await;
+        assert!(response.error.is_none(), "{:?}", response.error);
+        let measured = data(response);
+        assert_eq!(measured["label"], "50 cm");
+        let id = ElementId::parse(measured["element_id"].as_str().expect("id")).expect("id");
+        let scene = store.get("default").expect("scene");
+        let element = scene.get_element(id).expect("measurement");
+        assert_eq!(element.transform.z_index, 4);
+        let layout = MeasurementLayout::from_element(element).expect("layout");
+        assert!((layout.start[0] - 110.0).abs() < 1e-3 && (layout.start[1] - 250.0).abs() < 1e-3);
+
+        let response = server
+            .handle_request(call(serde_json::json!({
+                "type": "region",
+                "element_id": chart.to_string(),
+                "x": 40, "y": 20, "width": 80, "height": 60,
+                "caption": "Intruder",
+                "color": "#43a047"
+            })))
+            .await;
+        assert!(response.error.is_none(), "{:?}", response.error);
+        let id = ElementId::parse(data(response)["element_id"].as_str().expect("id")).expect("id");
+        let scene = store.get("default").expect("scene");
+        let element = scene.get_element(id).expect("region");
+        assert!((element.transform.x - 140.0).abs() < f32::EPSILON);
+        assert!(matches!(
+            &element.kind,
+            ElementKind::Region { caption: Some(caption), .. } if caption == "Intruder"
+        ));
+
+        for rejected in [
+            serde_json::json!({ "type": "arrow" }),
+            serde_json::json!({ "type": "region", "x": 0, "y": 0 }),
+            serde_json::json!({ "type": "measurement", "start": [0, 0], "end": [1, 1], "scale": 0 }),
+            serde_json::json!({
+                "type": "measurement", "start": [0, 0], "end": [1, 1],
+                "element_id": ElementId::new().to_string()
+            }),
+        ] {
+            assert!(server.handle_request(call(rejected)).await.error.is_some());
+        }
     }
 
     #[tokio::test]
-    async fn test_canvas_remove_element() {
-        let server = CanvasMcpServer::new(SceneStore::new());
-
-        // Initialize first
-        server
-            .handle_request(JsonRpcRequest {
-                jsonrpc: "2.0".to_string(),
-                id: serde_json::json!(0),
-                method: "initialize".to_string(),
-                params: serde_json::json!({}),
+    async fn test_canvas_measure_in_scene_units() {
+        let store = SceneStore::new();
+        let server = CanvasMcpServer::new(store.clone());
+        let call = |name: &str, arguments: serde_json::Value| JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: serde_json::json!(1),
+            method: "tools/call".to_string(),
+            params: serde_json::json!({ "name": name, "arguments": arguments }),
+        };
+        let data = |response: JsonRpcResponse| -> serde_json::Value {
+            let result = response.result.expect("tool result");
+            let text = result["content"][0]["text"].as_str().expect("text");
+            serde_json::from_str(text).expect("json")
+        };
+        let _ = store.get_or_create("default");
+        store
+            .update("default", |scene| {
+                let cad = canvas_core::CoordinateSystem::pixels()
+                    .with_unit(canvas_core::WorldUnit::In)
+                    .with_origin(0.0, 600.0)
+                    .with_y_axis(canvas_core::YAxis::Up);
+                scene.set_coordinate_system(cad).expect("units");
             })
+            .expect("seed");
+
+        let measured = data(
+            server
+                .handle_request(call(
+                    "canvas_measure",
+                    serde_json::json!({ "start": [0, 600], "end": [288, 216] }),
+                ))
+                .await,
+        );
+        let reading = &measured["measurement"];
+        assert!((reading["length"].as_f64().unwrap() - 5.0).abs() < 1e-3);
+        assert!((reading["angle"].as_f64().unwrap() - 53.13).abs() < 1e-2);
+        assert_eq!(reading["unit"], "in");
+        assert_eq!(measured["end"], serde_json::json!([3.0, 4.0]));
+
+        let world = data(
+            server
+                .handle_request(call(
+                    "canvas_measure",
+                    serde_json::json!({ "start": [0, 0], "end": [0, 2], "world": true }),
+                ))
+                .await,
+        );
+        assert!((world["measurement"]["dy"].as_f64().unwrap() - 2.0).abs() < 1e-4);
+
+        // Persisted lines default to the scene's units and are listed
+        let annotated = data(
+            server
+                .handle_request(call(
+                    "canvas_annotate",
+                    serde_json::json!({
+                        "type": "measurement", "start": [10, 100], "end": [154, 100]
+                    }),
+                ))
+                .await,
+        );
+        assert_eq!(annotated["label"], "1.5 in");
+        let listed = data(
+            server
+                .handle_request(call("canvas_measure", serde_json::json!({})))
+                .await,
+        );
+        assert_eq!(
+            listed["measurements"][0]["element_id"],
+            annotated["element_id"]
+        );
+        assert_eq!(listed["measurements"][0]["label"], "1.5 in");
+        assert_eq!(listed["coordinates"]["unit"], "in");
+
+        let rejected = server
+            .handle_request(call(
+                "canvas_measure",
+                serde_json::json!({ "start": [0, 0] }),
+            ))
             .await;
+        assert!(rejected.error.is_some());
+    }
 
-        // Add an element first
-        let add_response = server
-            .handle_request(JsonRpcRequest {
-                jsonrpc: "2.0".to_string(),
-                id: serde_json::json!(1),
-                method: "tools/call".to_string(),
-                params: serde_json::json!({
-                    "name": "canvas_add_element",
-                    "arguments": {
-                        "session_id": "default",
-                        "kind": {
-                            "type": "Text",
-                            "data": {
-                                "content": "To Remove",
-                                "font_size": 16.0,
-                                "color": "#000000"
-                            }
-                        }
-                    }
-                }),
-            })
+    #[tokio::test]
+    async fn test_attach_to_follows_target() {
+        let store = SceneStore::new();
+        let server = CanvasMcpServer::new(store.clone());
+        let call = |name: &str, arguments: serde_json::Value| JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: serde_json::json!(1),
+            method: "tools/call".to_string(),
+            params: serde_json::json!({ "name": name, "arguments": arguments }),
+        };
+        let data = |response: JsonRpcResponse| -> serde_json::Value {
+            let result = response.result.expect("tool result");
+            let text = result["content"][0]["text"].as_str().expect("text");
+            serde_json::from_str(text).expect("json")
+        };
+        let text = serde_json::json!({
+            "type": "Text",
+            "data": { "content": "x", "font_size": 14.0, "color": "#000000" }
+        });
+
+        let card = data(
+            server
+                .handle_request(call(
+                    "canvas_add_element",
+                    serde_json::json!({
+                        "kind": text,
+                        "transform": { "x": 100, "y": 100, "width": 200, "height": 80 }
+                    }),
+                ))
+                .await,
+        )["element_id"]
+            .as_str()
+            .expect("id")
+            .to_string();
+        let label = data(
+            server
+                .handle_request(call(
+                    "canvas_add_element",
+                    serde_json::json!({
+                        "kind": text,
+                        "transform": { "width": 60, "height": 20 },
+                        "attach_to": { "target": card, "at": "bottom", "origin": "top", "offset_y": 8 }
+                    }),
+                ))
+                .await,
+        )["element_id"]
+            .as_str()
+            .expect("id")
+            .to_string();
+        let label_id = ElementId::parse(&label).expect("uuid");
+        let position = || {
+            let scene = store.get("default").expect("scene");
+            let t = scene.get_element(label_id).expect("label").transform;
+            (t.x, t.y)
+        };
+        assert_eq!(position(), (170.0, 188.0));
+
+        let moved = server
+            .handle_request(call(
+                "canvas_update_element",
+                serde_json::json!({ "element_id": card, "transform": { "x": 0, "y": 0 } }),
+            ))
+            .await;
+        assert!(moved.error.is_none());
+        assert_eq!(position(), (70.0, 88.0));
+
+        // The card cannot follow its own label
+        let cycle = server
+            .handle_request(call(
+                "canvas_update_element",
+                serde_json::json!({ "element_id": card, "attach_to": { "target": label } }),
+            ))
             .await;
+        assert!(cycle.error.is_some());
+        let missing = server
+            .handle_request(call(
+                "canvas_add_element",
+                serde_json::json!({ "kind": text, "attach_to": { "target": ElementId::new().to_string() } }),
+            ))
+            .await;
+        assert!(missing.error.is_some());
 
-        assert!(add_response.result.is_some());
-        let add_result = add_response.result.unwrap();
-        let content = add_result["content"].as_array().unwrap();
-        let text = content[0]["text"].as_str().unwrap();
-        let data: serde_json::Value = serde_json::from_str(text).unwrap();
-        let element_id = data["element_id"].as_str().unwrap();
+        let detached = server
+            .handle_request(call(
+                "canvas_update_element",
+                serde_json::json!({ "element_id": label, "attach_to": null }),
+            ))
+            .await;
+        assert!(detached.error.is_none());
+        let scene = store.get("default").expect("scene");
+        assert!(scene
+            .get_element(label_id)
+            .is_some_and(|e| e.attachment.is_none()));
+    }
 
-        // Remove the element
-        let remove_response = server
-            .handle_request(JsonRpcRequest {
-                jsonrpc: "2.0".to_string(),
-                id: serde_json::json!(2),
-                method: "tools/call".to_string(),
-                params: serde_json::json!({
-                    "name": "canvas_remove_element",
-                    "arguments": {
-                        "session_id": "default",
-                        "element_id": element_id
-                    }
+    #[tokio::test]
+    async fn test_canvas_annotate_pins_markup_to_image() {
+        let store = SceneStore::new();
+        let server = CanvasMcpServer::new(store.clone());
+        let call = |name: &str, arguments: serde_json::Value| JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: serde_json::json!(1),
+            method: "tools/call".to_string(),
+            params: serde_json::json!({ "name": name, "arguments": arguments }),
+        };
+        let image = store
+            .add_element(
+                "default",
+                Element::new(ElementKind::Image {
+                    src: "screenshot.png".to_string(),
+                    format: canvas_core::ImageFormat::Png,
+                    annotations: Vec::new(),
+                    extracted_text: None,
+                })
+                .with_transform(Transform {
+                    x: 100.0,
+                    y: 100.0,
+                    width: 400.0,
+                    height: 300.0,
+                    ..Transform::default()
                 }),
-            })
+            )
+            .expect("add image");
+
+        for mark in [
+            serde_json::json!({ "type": "arrow", "start": [20, 20], "end": [120, 80] }),
+            serde_json::json!({ "type": "ink", "points": [[10, 10], [60, 40], [90, 20]] }),
+            serde_json::json!({ "type": "text", "x": 200, "y": 150, "content": "Broken" }),
+        ] {
+            let mut arguments = mark;
+            arguments["element_id"] = serde_json::json!(image.to_string());
+            let response = server
+                .handle_request(call("canvas_annotate", arguments))
+                .await;
+            let result = response.result.expect("annotated");
+            let text = result["content"][0]["text"].as_str().expect("text");
+            let data: serde_json::Value = serde_json::from_str(text).expect("json");
+            assert_eq!(data["pinned_to"], image.to_string());
+            assert!(data.get("label").is_none());
+        }
+        let scene = store.get("default").expect("scene");
+        let pinned = scene.get_element(image).expect("image").kind.children();
+        assert_eq!(pinned.len(), 3);
+        let arrow = scene.get_element(pinned[0]).expect("arrow");
+        assert_eq!(arrow.parent, Some(image));
+        assert!(arrow.transform.z_index > 0);
+
+        let response = server
+            .handle_request(call(
+                "canvas_export",
+                serde_json::json!({
+                    "session_id": "default",
+                    "format": "svg",
+                    "element_id": image.to_string(),
+                    "flatten": true
+                }),
+            ))
             .await;
+        #[cfg(feature = "preview")]
+        {
+            let result = response.result.expect("flattened export");
+            let text = result["content"][0]["text"].as_str().unwrap();
+            let data: serde_json::Value = serde_json::from_str(text).unwrap();
+            let svg = data["data"].as_str().unwrap();
+            assert!(svg.contains("viewBox=\"100 100 400 300\""));
+            assert!(svg.contains("Broken"));
+        }
+        #[cfg(not(feature = "preview"))]
+        assert!(response.error.is_some());
 
-        assert!(remove_response.result.is_some());
-        assert!(remove_response.error.is_none());
+        let response = server
+            .handle_request(call(
+                "canvas_export",
+                serde_json::json!({ "session_id": "default", "format": "png", "flatten": true }),
+            ))
+            .await;
+        assert!(response.error.is_some());
     }
 
     #[tokio::test]
-    async fn test_canvas_update_element() {
-        let server = CanvasMcpServer::new(SceneStore::new());
+    async fn test_render_sparkline() {
+        let store = SceneStore::new();
+        let server = CanvasMcpServer::new(store.clone());
+        let render = |content: serde_json::Value| JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: serde_json::json!(1),
+            method: "tools/call".to_string(),
+            params: serde_json::json!({
+                "name": "canvas_render",
+                "arguments": { "session_id": "default", "content": content }
+            }),
+        };
 
-        // Initialize first
-        server
-            .handle_request(JsonRpcRequest {
-                jsonrpc: "2.0".to_string(),
-                id: serde_json::json!(0),
-                method: "initialize".to_string(),
-                params: serde_json::json!({}),
-            })
+        let response = server
+            .handle_request(render(serde_json::json!({
+                "type": "Sparkline",
+                "data": { "values": [3, 1, 4, 1, 5], "kind": "bar", "color": "var(--accent)" }
+            })))
             .await;
+        assert!(response.error.is_none(), "{:?}", response.error);
+        let scene = store.get("default").unwrap();
+        assert!(scene.elements().any(|e| matches!(
+            &e.kind,
+            ElementKind::Sparkline { values, kind: SparklineKind::Bar, .. } if values.len() == 5
+        )));
+
+        let rejected = server
+            .handle_request(render(serde_json::json!({
+                "type": "Sparkline",
+                "data": { "values": vec![1.0; MAX_SPARKLINE_POINTS + 1] }
+            })))
+            .await;
+        assert!(rejected.error.is_some());
+    }
 
-        // Add an element first
-        let add_response = server
-            .handle_request(JsonRpcRequest {
-                jsonrpc: "2.0".to_string(),
-                id: serde_json::json!(1),
-                method: "tools/call".to_string(),
-                params: serde_json::json!({
-                    "name": "canvas_add_element",
-                    "arguments": {
-                        "session_id": "default",
-                        "kind": {
-                            "type": "Text",
-                            "data": {
-                                "content": "To Update",
-                                "font_size": 16.0,
-                                "color": "#000000"
-                            }
-                        },
-                        "transform": {
-                            "x": 0.0,
-                            "y": 0.0,
-                            "width": 100.0,
-                            "height": 50.0
+    #[tokio::test]
+    async fn test_render_calendar_and_update_events() {
+        let store = SceneStore::new();
+        let server = CanvasMcpServer::new(store.clone());
+        let call = |name: &str, arguments: serde_json::Value| JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: serde_json::json!(1),
+            method: "tools/call".to_string(),
+            params: serde_json::json!({ "name": name, "arguments": arguments }),
+        };
+
+        let response = server
+            .handle_request(call(
+                "canvas_render",
+                serde_json::json!({
+                    "session_id": "default",
+                    "content": {
+                        "type": "Calendar",
+                        "data": {
+                            "view": "week",
+                            "date": "2026-10-17",
+                            "events": [{ "date": "2026-10-14", "title": "Standup", "time": "09:30" }]
                         }
+                    },
+                    "position": { "x": 0, "y": 0, "width": 700, "height": 240 }
+                }),
+            ))
+            .await;
+        assert!(response.error.is_none(), "{:?}", response.error);
+        let element_id = store
+            .get("default")
+            .and_then(|scene| scene.elements().next().map(|e| e.id))
+            .expect("calendar added");
+
+        // Agents add events by merging into the element's data
+        let response = server
+            .handle_request(call(
+                "canvas_update_element",
+                serde_json::json!({
+                    "element_id": element_id.to_string(),
+                    "data": {
+                        "events": [
+                            { "date": "2026-10-14", "title": "Standup" },
+                            { "date": "2026-10-16", "title": "Demo", "color": "#e53935" }
+                        ]
                     }
                 }),
-            })
+            ))
+            .await;
+        assert!(response.error.is_none(), "{:?}", response.error);
+        let scene = store.get("default").unwrap();
+        let Some(ElementKind::Calendar {
+            view, date, events, ..
+        }) = scene.get_element(element_id).map(|e| &e.kind)
+        else {
+            panic!("expected a calendar element");
+        };
+        assert_eq!(*view, CalendarView::Week);
+        assert_eq!(date, "2026-10-17");
+        assert_eq!(events.len(), 2);
+
+        // Invalid dates and values are rejected without changes
+        for data in [
+            serde_json::json!({ "date": "next tuesday" }),
+            serde_json::json!({ "events": [{ "date": "2026-02-30", "title": "Nope" }] }),
+            serde_json::json!({ "view": "year" }),
+            serde_json::json!("2026-10-01"),
+        ] {
+            let rejected = server
+                .handle_request(call(
+                    "canvas_update_element",
+                    serde_json::json!({ "element_id": element_id.to_string(), "data": data }),
+                ))
+                .await;
+            assert!(rejected.error.is_some(), "accepted {data}");
+        }
+        let rejected = server
+            .handle_request(call(
+                "canvas_render",
+                serde_json::json!({
+                    "session_id": "default",
+                    "content": { "type": "Calendar", "data": { "date": "soon" } }
+                }),
+            ))
             .await;
+        assert!(rejected.error.is_some());
+        assert_eq!(store.get("default").unwrap().element_count(), 1);
+    }
 
-        assert!(add_response.result.is_some());
-        let add_result = add_response.result.unwrap();
-        let content = add_result["content"].as_array().unwrap();
-        let text = content[0]["text"].as_str().unwrap();
-        let data: serde_json::Value = serde_json::from_str(text).unwrap();
-        let element_id = data["element_id"].as_str().unwrap();
+    #[tokio::test]
+    async fn test_render_floor_plan_and_update_occupancy() {
+        let store = SceneStore::new();
+        let server = CanvasMcpServer::new(store.clone());
+        let call = |name: &str, arguments: serde_json::Value| JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: serde_json::json!(1),
+            method: "tools/call".to_string(),
+            params: serde_json::json!({ "name": name, "arguments": arguments }),
+        };
 
-        // Update the element position
-        let update_response = server
-            .handle_request(JsonRpcRequest {
-                jsonrpc: "2.0".to_string(),
-                id: serde_json::json!(2),
-                method: "tools/call".to_string(),
-                params: serde_json::json!({
-                    "name": "canvas_update_element",
-                    "arguments": {
-                        "session_id": "default",
-                        "element_id": element_id,
-                        "transform": {
-                            "x": 100.0,
-                            "y": 200.0
+        let response = server
+            .handle_request(call(
+                "canvas_render",
+                serde_json::json!({
+                    "session_id": "default",
+                    "content": {
+                        "type": "FloorPlan",
+                        "data": {
+                            "plan_width": 400,
+                            "plan_height": 200,
+                            "paths": [{ "points": [[0, 0], [400, 0], [400, 200], [0, 200]], "closed": true }],
+                            "markers": [
+                                { "id": "A1", "x": 40, "y": 40 },
+                                { "id": "A2", "x": 80, "y": 40 },
+                                { "id": "stage", "shape": "zone", "x": 300, "y": 100 }
+                            ]
                         }
                     }
                 }),
-            })
+            ))
             .await;
+        assert!(response.error.is_none(), "{:?}", response.error);
+        let element_id = store
+            .get("default")
+            .and_then(|scene| scene.elements().next().map(|e| e.id))
+            .expect("floor plan added");
 
-        assert!(update_response.result.is_some());
-        assert!(update_response.error.is_none());
-
-        // Verify the update via get_scene
-        let get_response = server
-            .handle_request(JsonRpcRequest {
-                jsonrpc: "2.0".to_string(),
-                id: serde_json::json!(3),
-                method: "tools/call".to_string(),
-                params: serde_json::json!({
-                    "name": "canvas_get_scene",
-                    "arguments": {
-                        "session_id": "default"
+        // Occupancy updates merge into markers by ID
+        let response = server
+            .handle_request(call(
+                "canvas_update_element",
+                serde_json::json!({
+                    "element_id": element_id.to_string(),
+                    "data": {
+                        "markers": [
+                            { "id": "A1", "occupancy": "occupied", "occupant": "Ada" },
+                            { "id": "A2", "remove": true },
+                            { "id": "B1", "x": 40, "y": 120, "occupancy": "reserved" }
+                        ]
                     }
                 }),
-            })
+            ))
             .await;
-
-        assert!(get_response.result.is_some());
-        let get_result = get_response.result.unwrap();
-        let content = get_result["content"].as_array().unwrap();
-        let text = content[0]["text"].as_str().unwrap();
-        // Verify position was updated
-        assert!(text.contains("100"));
-        assert!(text.contains("200"));
+        assert!(response.error.is_none(), "{:?}", response.error);
+        let scene = store.get("default").unwrap();
+        let Some(ElementKind::FloorPlan { markers, paths, .. }) =
+            scene.get_element(element_id).map(|e| &e.kind)
+        else {
+            panic!("expected a floor plan element");
+        };
+        let ids: Vec<&str> = markers.iter().map(|m| m.id.as_str()).collect();
+        assert_eq!(ids, ["A1", "stage", "B1"]);
+        assert_eq!(markers[0].occupancy, canvas_core::Occupancy::Occupied);
+        assert_eq!(markers[0].occupant.as_deref(), Some("Ada"));
+        assert!((markers[0].x - 40.0).abs() < f32::EPSILON);
+        assert_eq!(paths.len(), 1);
+
+        // Invalid markers are rejected without changes
+        for data in [
+            serde_json::json!({ "markers": [{ "occupancy": "occupied" }] }),
+            serde_json::json!({ "markers": [{ "id": "A1", "occupancy": "maybe" }] }),
+            serde_json::json!({ "markers": [{ "id": "Z9", "remove": true }] }),
+            serde_json::json!({ "plan_width": 0 }),
+        ] {
+            let rejected = server
+                .handle_request(call(
+                    "canvas_update_element",
+                    serde_json::json!({ "element_id": element_id.to_string(), "data": data }),
+                ))
+                .await;
+            assert!(rejected.error.is_some(), "accepted {data}");
+        }
+        let rejected = server
+            .handle_request(call(
+                "canvas_render",
+                serde_json::json!({
+                    "session_id": "default",
+                    "content": {
+                        "type": "FloorPlan",
+                        "data": { "markers": [{ "id": "A1", "x": 0, "y": 0 }, { "id": "A1", "x": 5, "y": 5 }] }
+                    }
+                }),
+            ))
+            .await;
+        assert!(rejected.error.is_some());
+        assert_eq!(store.get("default").unwrap().element_count(), 1);
     }
 
     #[tokio::test]
-    async fn test_tools_list_includes_new_tools() {
-        let server = CanvasMcpServer::new(SceneStore::new());
-        let response = server
-            .handle_request(JsonRpcRequest {
-                jsonrpc: "2.0".to_string(),
-                id: serde_json::json!(1),
-                method: "tools/list".to_string(),
-                params: serde_json::json!({}),
-            })
-            .await;
+    async fn test_save_and_instantiate_template() {
+        let store = SceneStore::new();
+        let server = CanvasMcpServer::new(store.clone());
+        let element_id = store
+            .add_element(
+                "default",
+                Element::new(ElementKind::Text {
+                    content: "Card".to_string(),
+                    font_size: 16.0,
+                    color: Color::BLACK.into(),
+                })
+                .with_transform(Transform {
+                    x: 40.0,
+                    y: 40.0,
+                    width: 100.0,
+                    height: 50.0,
+                    rotation: 0.0,
+                    z_index: 0,
+                    auto_size: false,
+                }),
+            )
+            .unwrap();
+        let request = |id: i64, method: &str, params: serde_json::Value| JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: serde_json::json!(id),
+            method: method.to_string(),
+            params,
+        };
+        let tool_data = |response: JsonRpcResponse| -> serde_json::Value {
+            let result = response.result.expect("tool result");
+            serde_json::from_str(result["content"][0]["text"].as_str().unwrap()).unwrap()
+        };
 
-        assert!(response.result.is_some());
-        let result = response.result.unwrap();
-        let tools = result["tools"].as_array().unwrap();
+        let saved = tool_data(
+            server
+                .handle_request(request(
+                    1,
+                    "tools/call",
+                    serde_json::json!({
+                        "name": "canvas_save_template",
+                        "arguments": { "name": "card", "element_ids": [element_id.to_string()] }
+                    }),
+                ))
+                .await,
+        );
+        assert_eq!(saved["uri"], "canvas://template/card");
 
-        // Should have 9 tools total
-        assert_eq!(tools.len(), 9);
+        let response = server
+            .handle_request(request(
+                2,
+                "resources/read",
+                serde_json::json!({ "uri": "canvas://template/card" }),
+            ))
+            .await;
+        let text = response.result.expect("template resource")["contents"][0]["text"]
+            .as_str()
+            .unwrap()
+            .to_string();
+        let template: Template = serde_json::from_str(&text).unwrap();
+        assert!(template.elements[0].transform.x.abs() < f32::EPSILON);
+
+        let stamp = || {
+            request(
+                3,
+                "tools/call",
+                serde_json::json!({
+                    "name": "canvas_instantiate_template",
+                    "arguments": { "name": "card", "x": 300.0, "y": 100.0 }
+                }),
+            )
+        };
+        let first = tool_data(server.handle_request(stamp()).await);
+        let second = tool_data(server.handle_request(stamp()).await);
+        assert_ne!(first["element_ids"], second["element_ids"]);
+        let copy_id = ElementId::parse(first["root_ids"][0].as_str().unwrap()).unwrap();
+        let scene = store.get("default").unwrap();
+        assert_eq!(scene.element_count(), 3);
+        assert!((scene.get_element(copy_id).unwrap().transform.x - 300.0).abs() < f32::EPSILON);
 
-        // Verify all tool names are present
-        let tool_names: Vec<&str> = tools.iter().filter_map(|t| t["name"].as_str()).collect();
-        assert!(tool_names.contains(&"canvas_render"));
-        assert!(tool_names.contains(&"canvas_render_a2ui"));
-        assert!(tool_names.contains(&"canvas_interact"));
-        assert!(tool_names.contains(&"canvas_export"));
-        assert!(tool_names.contains(&"canvas_clear"));
-        assert!(tool_names.contains(&"canvas_add_element"));
-        assert!(tool_names.contains(&"canvas_remove_element"));
-        assert!(tool_names.contains(&"canvas_update_element"));
-        assert!(tool_names.contains(&"canvas_get_scene"));
+        let response = server
+            .handle_request(request(4, "resources/list", serde_json::json!({})))
+            .await;
+        let listed = response.result.expect("resources")["resources"].to_string();
+        assert!(listed.contains("canvas://template/card"));
     }
 
     #[tokio::test]
-    async fn test_canvas_render_a2ui() {
-        let server = CanvasMcpServer::new(SceneStore::new());
+    async fn test_transaction_commits_atomically() {
+        let store = SceneStore::new();
+        let mut server = CanvasMcpServer::new(store.clone());
+        let changes = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counter = Arc::clone(&changes);
+        server.set_on_change(move |_, _| {
+            counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        });
+        let call = |name: &str, arguments: serde_json::Value| JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: serde_json::json!(1),
+            method: "tools/call".to_string(),
+            params: serde_json::json!({ "name": name, "arguments": arguments }),
+        };
+        let tool_data = |response: JsonRpcResponse| -> serde_json::Value {
+            let result = response.result.expect("tool result");
+            serde_json::from_str(result["content"][0]["text"].as_str().unwrap()).unwrap()
+        };
+        let text = serde_json::json!({
+            "type": "Text",
+            "data": { "content": "Staged", "font_size": 16.0, "color": "#000000" }
+        });
+
+        let begun = tool_data(
+            server
+                .handle_request(call("canvas_begin_transaction", serde_json::json!({})))
+                .await,
+        );
+        let tx_id = begun["transaction_id"].clone();
+        for _ in 0..2 {
+            let staged = tool_data(
+                server
+                    .handle_request(call(
+                        "canvas_add_element",
+                        serde_json::json!({ "transaction_id": tx_id, "kind": text }),
+                    ))
+                    .await,
+            );
+            assert_eq!(staged["staged"], true);
+        }
 
-        // Initialize first
+        // Nothing is applied or broadcast until commit
+        assert!(store.get("default").is_none_or(|s| s.is_empty()));
+        assert_eq!(changes.load(std::sync::atomic::Ordering::SeqCst), 0);
+
+        let committed = tool_data(
+            server
+                .handle_request(call(
+                    "canvas_commit_transaction",
+                    serde_json::json!({ "transaction_id": tx_id }),
+                ))
+                .await,
+        );
+        assert_eq!(committed["operations"], 2);
+        assert_eq!(store.get("default").unwrap().element_count(), 2);
+        assert_eq!(changes.load(std::sync::atomic::Ordering::SeqCst), 1);
+
+        // A committed transaction is closed
+        let response = server
+            .handle_request(call(
+                "canvas_add_element",
+                serde_json::json!({ "transaction_id": tx_id, "kind": text }),
+            ))
+            .await;
+        assert!(response.error.is_some());
+
+        // Rolled-back changes are discarded
+        let begun = tool_data(
+            server
+                .handle_request(call("canvas_begin_transaction", serde_json::json!({})))
+                .await,
+        );
+        let tx_id = begun["transaction_id"].clone();
         server
-            .handle_request(JsonRpcRequest {
-                jsonrpc: "2.0".to_string(),
-                id: serde_json::json!(0),
-                method: "initialize".to_string(),
-                params: serde_json::json!({}),
-            })
+            .handle_request(call(
+                "canvas_clear",
+                serde_json::json!({ "transaction_id": tx_id }),
+            ))
             .await;
-
-        // Render an A2UI tree with a container and text
-        // Note: A2UI uses "component" tag with snake_case values
-        let response = server
-            .handle_request(JsonRpcRequest {
-                jsonrpc: "2.0".to_string(),
-                id: serde_json::json!(1),
-                method: "tools/call".to_string(),
-                params: serde_json::json!({
-                    "name": "canvas_render_a2ui",
-                    "arguments": {
-                        "session_id": "default",
-                        "tree": {
-                            "root": {
-                                "component": "container",
-                                "children": [
-                                    {
-                                        "component": "text",
-                                        "content": "Hello A2UI"
-                                    },
-                                    {
-                                        "component": "button",
-                                        "label": "Click Me",
-                                        "action": "submit"
-                                    }
-                                ],
-                                "layout": "column"
-                            }
-                        }
-                    }
-                }),
-            })
+        server
+            .handle_request(call(
+                "canvas_rollback_transaction",
+                serde_json::json!({ "transaction_id": tx_id }),
+            ))
             .await;
-
-        assert!(response.result.is_some());
-        assert!(response.error.is_none());
-
-        // Verify the response contains element_ids
-        let result = response.result.unwrap();
-        let content = result["content"].as_array().unwrap();
-        let text = content[0]["text"].as_str().unwrap();
-        assert!(text.contains("element_ids"));
-        assert!(text.contains("rendered"));
+        assert_eq!(store.get("default").unwrap().element_count(), 2);
     }
 
     #[tokio::test]
-    async fn test_canvas_render_a2ui_with_merge() {
+    async fn test_open_transactions_are_limited() {
         let server = CanvasMcpServer::new(SceneStore::new());
+        let begin = |agent_id: &str| JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: serde_json::json!(1),
+            method: "tools/call".to_string(),
+            params: serde_json::json!({
+                "name": "canvas_begin_transaction",
+                "arguments": { "agent_id": agent_id }
+            }),
+        };
 
-        // Initialize first
-        server
-            .handle_request(JsonRpcRequest {
-                jsonrpc: "2.0".to_string(),
-                id: serde_json::json!(0),
-                method: "initialize".to_string(),
-                params: serde_json::json!({}),
-            })
-            .await;
+        for _ in 0..MAX_TRANSACTIONS_PER_AGENT {
+            let response = server.handle_request(begin("agent-a")).await;
+            assert!(response.error.is_none(), "{:?}", response.error);
+        }
+        assert!(server
+            .handle_request(begin("agent-a"))
+            .await
+            .error
+            .is_some());
+        // Other agents have their own allowance
+        assert!(server
+            .handle_request(begin("agent-b"))
+            .await
+            .error
+            .is_none());
+
+        // Transactions past their TTL are dropped when another begins
+        let Some(expired) = Instant::now().checked_sub(TRANSACTION_TTL + Duration::from_secs(1))
+        else {
+            return;
+        };
+        let mut transactions = server.transactions.write().await;
+        let stale: Vec<String> = transactions
+            .iter()
+            .filter(|(_, tx)| tx.agent_id.as_deref() == Some("agent-a"))
+            .map(|(id, _)| id.clone())
+            .collect();
+        for id in &stale {
+            let tx = transactions.remove(id).expect("open transaction");
+            let mut tx = Arc::try_unwrap(tx).unwrap_or_else(|_| panic!("shared transaction"));
+            tx.started = expired;
+            transactions.insert(id.clone(), Arc::new(tx));
+        }
+        drop(transactions);
+        assert!(server
+            .handle_request(begin("agent-a"))
+            .await
+            .error
+            .is_none());
+        let transactions = server.transactions.read().await;
+        assert!(stale.iter().all(|id| !transactions.contains_key(id)));
+        assert_eq!(transactions.len(), 2);
+    }
 
-        // First render (A2UI uses "component" tag with snake_case)
-        server
-            .handle_request(JsonRpcRequest {
-                jsonrpc: "2.0".to_string(),
-                id: serde_json::json!(1),
-                method: "tools/call".to_string(),
-                params: serde_json::json!({
-                    "name": "canvas_render_a2ui",
-                    "arguments": {
-                        "session_id": "default",
-                        "tree": {
-                            "root": {
-                                "component": "text",
-                                "content": "First"
-                            }
-                        }
+    #[tokio::test]
+    async fn test_auto_sized_elements_fit_content() {
+        let store = SceneStore::new();
+        let server = CanvasMcpServer::new(store.clone());
+        let call = |name: &str, arguments: serde_json::Value| JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: serde_json::json!(1),
+            method: "tools/call".to_string(),
+            params: serde_json::json!({ "name": name, "arguments": arguments }),
+        };
+        let data = |response: JsonRpcResponse| -> serde_json::Value {
+            let result = response.result.expect("tool result");
+            let text = result["content"][0]["text"].as_str().expect("text");
+            serde_json::from_str(text).expect("json")
+        };
+        let transform = |id: &str| {
+            let id = ElementId::parse(id).expect("id");
+            store
+                .get("default")
+                .and_then(|scene| scene.get_element(id).map(|e| e.transform))
+                .expect("element")
+        };
+
+        // No position: the code block is measured
+        let response = server
+            .handle_request(call(
+                "canvas_render",
+                serde_json::json!({
+                    "session_id": "default",
+                    "content": {
+                        "type": "Code",
+                        "data": { "language": "rust", "source": "fn main() {\n}" }
                     }
                 }),
-            })
+            ))
             .await;
+        let code_id = data(response)["element_id"].as_str().unwrap().to_string();
+        let code = transform(&code_id);
+        assert!(code.auto_size);
+        assert!((code.width - 160.0).abs() < f32::EPSILON);
 
-        // Second render with merge=true
+        // An explicit size is kept
         let response = server
-            .handle_request(JsonRpcRequest {
-                jsonrpc: "2.0".to_string(),
-                id: serde_json::json!(2),
-                method: "tools/call".to_string(),
-                params: serde_json::json!({
-                    "name": "canvas_render_a2ui",
-                    "arguments": {
-                        "session_id": "default",
-                        "tree": {
-                            "root": {
-                                "component": "text",
-                                "content": "Second"
-                            }
-                        },
-                        "merge": true,
-                        "offset_x": 100.0,
-                        "offset_y": 50.0
-                    }
+            .handle_request(call(
+                "canvas_add_element",
+                serde_json::json!({
+                    "kind": {
+                        "type": "Text",
+                        "data": { "content": "Quarterly revenue", "font_size": 24.0, "color": "#000000" }
+                    },
+                    "transform": { "x": 10.0, "y": 10.0, "width": 50.0, "height": 20.0 }
                 }),
-            })
+            ))
             .await;
+        let sized_id = data(response)["element_id"].as_str().unwrap().to_string();
+        let sized = transform(&sized_id);
+        assert!(!sized.auto_size);
+        assert!((sized.width - 50.0).abs() < f32::EPSILON);
 
-        assert!(response.result.is_some());
-
-        // Get scene and verify both elements exist
-        let get_response = server
-            .handle_request(JsonRpcRequest {
-                jsonrpc: "2.0".to_string(),
-                id: serde_json::json!(3),
-                method: "tools/call".to_string(),
-                params: serde_json::json!({
-                    "name": "canvas_get_scene",
-                    "arguments": {
-                        "session_id": "default"
+        // Auto-sized text grows when its content changes
+        let response = server
+            .handle_request(call(
+                "canvas_add_element",
+                serde_json::json!({
+                    "kind": {
+                        "type": "Text",
+                        "data": { "content": "Hi", "font_size": 24.0, "color": "#000000" }
                     }
                 }),
-            })
+            ))
             .await;
+        let text_id = data(response)["element_id"].as_str().unwrap().to_string();
+        let before = transform(&text_id);
+        assert!(before.auto_size);
+        server
+            .handle_request(call(
+                "canvas_update_element",
+                serde_json::json!({
+                    "element_id": text_id,
+                    "data": { "content": "Quarterly revenue" }
+                }),
+            ))
+            .await;
+        let after = transform(&text_id);
+        assert!(after.width > before.width);
 
-        let get_result = get_response.result.unwrap();
-        let content = get_result["content"].as_array().unwrap();
-        let text = content[0]["text"].as_str().unwrap();
-
-        // Both "First" and "Second" should be in the scene
-        assert!(text.contains("First"));
-        assert!(text.contains("Second"));
+        // Resizing turns auto-sizing off
+        server
+            .handle_request(call(
+                "canvas_update_element",
+                serde_json::json!({
+                    "element_id": text_id,
+                    "transform": { "width": 400.0 }
+                }),
+            ))
+            .await;
+        let resized = transform(&text_id);
+        assert!(!resized.auto_size);
+        assert!((resized.width - 400.0).abs() < f32::EPSILON);
     }
 }
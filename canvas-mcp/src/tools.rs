@@ -1,5 +1,10 @@
 //! MCP tools for canvas operations.
 
+use canvas_core::{
+    AgendaItem, CalendarEvent, CalendarView, CalloutTarget, DataBinding, ElementKind, FloorMarker,
+    GaugeShape, NumberFormat, PlanPath, RandomSource, Redaction, SparklineKind, ThemeColor,
+    Threshold, TreeDirection, TreeNode,
+};
 use serde::{Deserialize, Serialize};
 
 use crate::ToolResponse;
@@ -26,6 +31,9 @@ pub enum RenderContent {
         chart_type: String,
         /// Chart data.
         data: serde_json::Value,
+        /// Dataset the chart plots instead of inline series.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        binding: Option<DataBinding>,
         /// Chart title.
         #[serde(skip_serializing_if = "Option::is_none")]
         title: Option<String>,
@@ -54,6 +62,217 @@ pub enum RenderContent {
         #[serde(skip_serializing_if = "Option::is_none")]
         font_size: Option<f32>,
     },
+    /// A math formula in TeX notation.
+    Math {
+        /// TeX source, e.g. `\frac{a}{b}`.
+        tex: String,
+        /// Font size.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        font_size: Option<f32>,
+        /// Text color (defaults to black).
+        #[serde(skip_serializing_if = "Option::is_none")]
+        color: Option<ThemeColor>,
+    },
+    /// A code snippet with syntax highlighting.
+    Code {
+        /// Source text.
+        source: String,
+        /// Language name, e.g. `rust` or `python` (plain text if omitted or
+        /// unknown).
+        #[serde(skip_serializing_if = "Option::is_none")]
+        language: Option<String>,
+    },
+    /// A terminal-style log that keeps its newest lines, for build or tool
+    /// output streamed in with `canvas_log_append`.
+    LogView {
+        /// Title shown above the log.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        title: Option<String>,
+        /// Initial output; may contain ANSI color codes.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        text: Option<String>,
+        /// Lines kept before the oldest are dropped (default 500).
+        #[serde(skip_serializing_if = "Option::is_none")]
+        max_lines: Option<usize>,
+        /// Keep the newest line in view (default true).
+        #[serde(skip_serializing_if = "Option::is_none")]
+        follow: Option<bool>,
+    },
+    /// A generative visual: a WGSL `shade` function run over the element's
+    /// box by GPU renderers, drawn as a placeholder elsewhere.
+    Shader {
+        /// WGSL source defining `fn shade(uv: vec2<f32>) -> vec4<f32>`.
+        wgsl: String,
+        /// Custom uniforms, read in the source with `param(i)`.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        uniforms: Option<Vec<f32>>,
+    },
+    /// A sticky note.
+    StickyNote {
+        /// Note text.
+        content: String,
+        /// Background color (defaults to yellow).
+        #[serde(skip_serializing_if = "Option::is_none")]
+        color: Option<ThemeColor>,
+        /// Author (defaults to the calling agent).
+        #[serde(skip_serializing_if = "Option::is_none")]
+        author: Option<String>,
+    },
+    /// A callout pointing at an element or canvas position.
+    Callout {
+        /// Callout text.
+        content: String,
+        /// Background color (defaults to white).
+        #[serde(skip_serializing_if = "Option::is_none")]
+        color: Option<ThemeColor>,
+        /// Author (defaults to the calling agent).
+        #[serde(skip_serializing_if = "Option::is_none")]
+        author: Option<String>,
+        /// What the tail points at.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        target: Option<CalloutTarget>,
+    },
+    /// An audio clip or stream, e.g. a voice note or text-to-speech output.
+    Audio {
+        /// Stream identifier (peer ID or media URL).
+        stream_id: String,
+        /// Amplitudes in `-1.0..=1.0`; downsampled for display.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        waveform: Option<Vec<f32>>,
+    },
+    /// A month or week calendar with events; tapping a day selects it.
+    Calendar {
+        /// Month or week view.
+        #[serde(default)]
+        view: CalendarView,
+        /// Any day in the month or week to show, `YYYY-MM-DD`.
+        date: String,
+        /// Events to list on their days.
+        #[serde(default)]
+        events: Vec<CalendarEvent>,
+        /// Initially selected day, `YYYY-MM-DD`.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        selected: Option<String>,
+    },
+    /// Dice, a wheel spinner or a shuffled list; tapping it draws a new
+    /// outcome on the server.
+    Randomizer {
+        /// What is drawn, e.g. `{"mode": "dice", "count": 2}`.
+        source: RandomSource,
+    },
+    /// A meeting agenda of timed items; the running item is highlighted on
+    /// every participant's screen. Control it with `canvas_agenda`.
+    Agenda {
+        /// Heading, e.g. the meeting name.
+        #[serde(default, skip_serializing_if = "String::is_empty")]
+        title: String,
+        /// Items, in running order.
+        items: Vec<AgendaItem>,
+        /// Count down to a start this many seconds from now; omit to start
+        /// later with `canvas_agenda`.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        starts_in_secs: Option<u64>,
+    },
+    /// A floor plan or seating chart with seat and zone markers colored by
+    /// occupancy; markers can be dragged unless the plan is locked.
+    FloorPlan {
+        /// Background image URL or data URI.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        background: Option<String>,
+        /// Plan width in plan units.
+        #[serde(default = "ElementKind::default_plan_width")]
+        plan_width: f32,
+        /// Plan height in plan units.
+        #[serde(default = "ElementKind::default_plan_height")]
+        plan_height: f32,
+        /// Walls and outlines.
+        #[serde(default)]
+        paths: Vec<PlanPath>,
+        /// Seats and zones.
+        #[serde(default)]
+        markers: Vec<FloorMarker>,
+        /// Whether markers stay put when dragged.
+        #[serde(default)]
+        locked: bool,
+    },
+    /// A radial or linear gauge showing a value between `min` and `max`,
+    /// colored by thresholds.
+    Gauge {
+        /// Current value.
+        value: f64,
+        /// Value at the start of the dial.
+        #[serde(default)]
+        min: f64,
+        /// Value at the end of the dial.
+        #[serde(default = "ElementKind::default_gauge_max")]
+        max: f64,
+        /// Radial arc or linear bar.
+        #[serde(default)]
+        shape: GaugeShape,
+        /// Caption under the value.
+        #[serde(default, skip_serializing_if = "String::is_empty")]
+        label: String,
+        /// Colors from each threshold value upwards.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        thresholds: Vec<Threshold>,
+        /// How the value is printed.
+        #[serde(default)]
+        format: NumberFormat,
+    },
+    /// A KPI card: a big number with its change since `previous` and an
+    /// optional sparkline.
+    Kpi {
+        /// Metric name.
+        label: String,
+        /// Current value.
+        value: f64,
+        /// Earlier value the change is measured against.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        previous: Option<f64>,
+        /// Recent values, oldest first.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        sparkline: Vec<f32>,
+        /// Whether the sparkline is a line or bars.
+        #[serde(default)]
+        sparkline_kind: SparklineKind,
+        /// Colors the value from each threshold upwards.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        thresholds: Vec<Threshold>,
+        /// How the value and change are printed.
+        #[serde(default)]
+        format: NumberFormat,
+        /// Whether an increase is good news (green) rather than bad (red).
+        #[serde(default = "ElementKind::default_higher_is_better")]
+        higher_is_better: bool,
+    },
+    /// A word-sized trend line or bar series without axes or labels.
+    Sparkline {
+        /// Values, oldest first.
+        values: Vec<f32>,
+        /// Line or bars.
+        #[serde(default)]
+        kind: SparklineKind,
+        /// Color of the line or bars.
+        #[serde(default = "ElementKind::default_sparkline_color")]
+        color: ThemeColor,
+    },
+    /// An org chart or decision tree from nested nodes, laid out
+    /// automatically.
+    Tree {
+        /// Root node; nodes without an `id` get one from their position.
+        root: TreeNode,
+        /// Which way the tree grows.
+        #[serde(default)]
+        direction: TreeDirection,
+    },
+    /// An embedded web page (dashboard, docs) shown as an iframe in the PWA.
+    Embed {
+        /// Absolute `http` or `https` URL.
+        url: String,
+        /// Iframe sandbox flags (defaults to scripts, forms, and popups).
+        #[serde(skip_serializing_if = "Option::is_none")]
+        sandbox: Option<String>,
+    },
 }
 
 /// Position specification.
@@ -178,6 +397,70 @@ pub struct ExportParams {
     /// Quality (0-100, for lossy formats).
     #[serde(default = "default_quality")]
     pub quality: u8,
+    /// Export only this element and its children, cropped to their bounds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub element_id: Option<String>,
+    /// Flatten the image `element_id` names with its pinned markup, cropped
+    /// exactly to the image.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub flatten: bool,
+    /// Output width in pixels.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub width: Option<u32>,
+    /// Output height in pixels.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub height: Option<u32>,
+    /// Scale factor, e.g. 2.0 for retina.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scale: Option<f32>,
+    /// Paginate the session onto paper (PDF exports only).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub print: Option<PrintOptions>,
+    /// Hide sensitive elements, for sharing outside the team.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub redact: Option<Redaction>,
+}
+
+/// Page layout for a paginated PDF export.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PrintOptions {
+    /// Paper size (default A4).
+    #[serde(default)]
+    pub paper: PaperSize,
+    /// Landscape instead of portrait pages.
+    #[serde(default)]
+    pub landscape: bool,
+    /// Margin on every side in millimetres (default 12).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub margin_mm: Option<f32>,
+    /// Millimetres per canvas unit (default: fit the content to the page width).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scale: Option<f32>,
+    /// Header title (defaults to the session ID).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// Page raster resolution (default 150).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dpi: Option<f32>,
+}
+
+/// Paper sizes for print exports.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PaperSize {
+    /// ISO A4.
+    #[default]
+    A4,
+    /// US Letter.
+    Letter,
+    /// ISO A3.
+    A3,
+    /// ISO A5.
+    A5,
+    /// US Legal.
+    Legal,
+    /// US Tabloid.
+    Tabloid,
 }
 
 fn default_quality() -> u8 {
@@ -200,26 +483,16 @@ pub enum ExportFormat {
     WebP,
 }
 
-/// Execute the `canvas_export` tool.
-pub fn canvas_export(params: &ExportParams) -> ToolResponse {
-    tracing::info!(
-        "Exporting session {} as {:?}",
-        params.session_id,
-        params.format
-    );
-
-    // TODO: Actually export the canvas
-
-    ToolResponse::success(serde_json::json!({
-        "session_id": &params.session_id,
-        "format": params.format,
-        "data": "base64_encoded_data_here",
-        "mime_type": match params.format {
-            ExportFormat::Png => "image/png",
-            ExportFormat::Jpeg => "image/jpeg",
-            ExportFormat::Svg => "image/svg+xml",
-            ExportFormat::Pdf => "application/pdf",
-            ExportFormat::WebP => "image/webp",
+impl ExportFormat {
+    /// MIME type of the exported data.
+    #[must_use]
+    pub fn mime_type(self) -> &'static str {
+        match self {
+            Self::Png => "image/png",
+            Self::Jpeg => "image/jpeg",
+            Self::Svg => "image/svg+xml",
+            Self::Pdf => "application/pdf",
+            Self::WebP => "image/webp",
         }
-    }))
+    }
 }
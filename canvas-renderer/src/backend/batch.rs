@@ -0,0 +1,288 @@
+//! Draw batching for the wgpu backend.
+//!
+//! Elements are queued as they are visited instead of each getting a
+//! render pass of its own. Consecutive draws that share a pipeline, a
+//! texture, a camera mode, and a scissor rect merge into one batch, drawn
+//! with a single instanced (or, for glyphs and chart fills, a single
+//! non-indexed) call.
+//! Queued draws keep their order, so the painter's algorithm still holds:
+//! a texture or scissor change between two quads splits them into separate
+//! batches rather than reordering them.
+
+use std::ops::Range;
+
+use super::glyph_atlas::GlyphVertex;
+use super::quad::QuadStyle;
+use crate::chart_mesh::ChartVertex;
+
+/// Per-instance data for one quad, matching the instanced shaders.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub(crate) struct QuadInstance {
+    /// x, y, width, height
+    pub(crate) rect: [f32; 4],
+    /// Fill color, or texture tint
+    pub(crate) color: [f32; 4],
+    /// Gradient end color
+    pub(crate) fill_end: [f32; 4],
+    /// Stroke color
+    pub(crate) stroke_color: [f32; 4],
+    /// Fill mode, gradient angle or radius, corner radius, stroke width
+    pub(crate) style_params: [f32; 4],
+    /// Edge softness, radial center x, radial center y, reserved
+    pub(crate) style_extra: [f32; 4],
+}
+
+impl QuadInstance {
+    /// A quad covering `rect` in `color`, styled by `style`.
+    pub(crate) fn new(rect: [f32; 4], color: [f32; 4], style: &QuadStyle) -> Self {
+        Self {
+            rect,
+            color,
+            fill_end: style.fill_end,
+            stroke_color: style.stroke_color,
+            style_params: style.params,
+            style_extra: style.extra,
+        }
+    }
+}
+
+/// What a batch draws, and with which texture.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum BatchKind<T> {
+    /// Colored quads from the instance list.
+    Quads,
+    /// Quads from the instance list sampling one texture.
+    Textured(T),
+    /// Triangles from the glyph vertex list, sampling the text atlas.
+    Glyphs,
+    /// Solid triangles from the fill vertex list.
+    Fills,
+}
+
+/// A run of draws sharing all their state.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Batch<T> {
+    /// Pipeline and texture.
+    pub(crate) kind: BatchKind<T>,
+    /// Whether positions go through the camera's view-projection.
+    pub(crate) camera: bool,
+    /// Scissor rect in target pixels: x, y, width, height.
+    pub(crate) scissor: (u32, u32, u32, u32),
+    /// Instances (quads) or vertices (glyphs, fills) in the pending lists.
+    pub(crate) range: Range<u32>,
+}
+
+/// Draws queued since the last flush.
+#[derive(Debug)]
+pub(crate) struct FrameBatches<T> {
+    quads: Vec<QuadInstance>,
+    glyphs: Vec<GlyphVertex>,
+    fills: Vec<ChartVertex>,
+    batches: Vec<Batch<T>>,
+}
+
+impl<T> Default for FrameBatches<T> {
+    fn default() -> Self {
+        Self {
+            quads: Vec::new(),
+            glyphs: Vec::new(),
+            fills: Vec::new(),
+            batches: Vec::new(),
+        }
+    }
+}
+
+impl<T: PartialEq> FrameBatches<T> {
+    /// Queue a quad, extending the last batch if it has the same state.
+    pub(crate) fn push_quad(
+        &mut self,
+        kind: BatchKind<T>,
+        camera: bool,
+        scissor: (u32, u32, u32, u32),
+        instance: QuadInstance,
+    ) {
+        let index = count(self.quads.len());
+        self.quads.push(instance);
+        self.extend(kind, camera, scissor, index..index + 1);
+    }
+
+    /// Queue glyph triangles, extending the last batch if it has the same state.
+    pub(crate) fn push_glyphs(
+        &mut self,
+        camera: bool,
+        scissor: (u32, u32, u32, u32),
+        vertices: &[GlyphVertex],
+    ) {
+        if vertices.is_empty() {
+            return;
+        }
+        let start = count(self.glyphs.len());
+        self.glyphs.extend_from_slice(vertices);
+        let end = count(self.glyphs.len());
+        self.extend(BatchKind::Glyphs, camera, scissor, start..end);
+    }
+
+    /// Queue solid triangles, extending the last batch if it has the same state.
+    pub(crate) fn push_fills(
+        &mut self,
+        camera: bool,
+        scissor: (u32, u32, u32, u32),
+        vertices: &[ChartVertex],
+    ) {
+        if vertices.is_empty() {
+            return;
+        }
+        let start = count(self.fills.len());
+        self.fills.extend_from_slice(vertices);
+        let end = count(self.fills.len());
+        self.extend(BatchKind::Fills, camera, scissor, start..end);
+    }
+
+    fn extend(
+        &mut self,
+        kind: BatchKind<T>,
+        camera: bool,
+        scissor: (u32, u32, u32, u32),
+        range: Range<u32>,
+    ) {
+        if let Some(last) = self.batches.last_mut() {
+            if last.kind == kind
+                && last.camera == camera
+                && last.scissor == scissor
+                && last.range.end == range.start
+            {
+                last.range.end = range.end;
+                return;
+            }
+        }
+        self.batches.push(Batch {
+            kind,
+            camera,
+            scissor,
+            range,
+        });
+    }
+
+    /// Whether nothing is queued.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.batches.is_empty()
+    }
+
+    /// Queued quad instances.
+    pub(crate) fn quads(&self) -> &[QuadInstance] {
+        &self.quads
+    }
+
+    /// Queued glyph vertices.
+    pub(crate) fn glyphs(&self) -> &[GlyphVertex] {
+        &self.glyphs
+    }
+
+    /// Queued fill vertices.
+    pub(crate) fn fills(&self) -> &[ChartVertex] {
+        &self.fills
+    }
+
+    /// Queued batches, in draw order.
+    pub(crate) fn batches(&self) -> &[Batch<T>] {
+        &self.batches
+    }
+
+    /// Drop everything queued, keeping the allocations.
+    pub(crate) fn clear(&mut self) {
+        self.quads.clear();
+        self.glyphs.clear();
+        self.fills.clear();
+        self.batches.clear();
+    }
+}
+
+/// A list length as a draw range bound.
+#[allow(clippy::cast_possible_truncation)] // Buffers are far smaller than 4G items
+fn count(len: usize) -> u32 {
+    len as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FULL: (u32, u32, u32, u32) = (0, 0, 800, 600);
+
+    fn quad() -> QuadInstance {
+        QuadInstance::new([0.0, 0.0, 10.0, 10.0], [1.0; 4], &QuadStyle::FLAT)
+    }
+
+    fn glyph() -> GlyphVertex {
+        GlyphVertex {
+            position: [0.0, 0.0],
+            uv: [0.0, 0.0],
+            color: [1.0; 4],
+        }
+    }
+
+    #[test]
+    fn test_consecutive_quads_share_a_batch() {
+        let mut frame = FrameBatches::<u8>::default();
+        for _ in 0..10_000 {
+            frame.push_quad(BatchKind::Quads, false, FULL, quad());
+        }
+        assert_eq!(frame.quads().len(), 10_000);
+        assert_eq!(frame.batches().len(), 1);
+        assert_eq!(frame.batches()[0].range, 0..10_000);
+    }
+
+    #[test]
+    fn test_interleaved_kinds_keep_draw_order() {
+        let mut frame = FrameBatches::<u8>::default();
+        frame.push_quad(BatchKind::Quads, false, FULL, quad());
+        frame.push_glyphs(false, FULL, &[glyph(); 6]);
+        frame.push_glyphs(false, FULL, &[glyph(); 12]);
+        frame.push_quad(BatchKind::Quads, false, FULL, quad());
+        let fill = ChartVertex {
+            position: [0.0, 0.0],
+            color: [1.0; 4],
+        };
+        frame.push_fills(false, FULL, &[fill; 3]);
+        frame.push_fills(false, FULL, &[]);
+
+        let kinds: Vec<_> = frame.batches().iter().map(|b| b.kind.clone()).collect();
+        assert_eq!(
+            kinds,
+            [
+                BatchKind::Quads,
+                BatchKind::Glyphs,
+                BatchKind::Quads,
+                BatchKind::Fills
+            ]
+        );
+        assert_eq!(frame.batches()[1].range, 0..18);
+        assert_eq!(frame.batches()[2].range, 1..2);
+        assert_eq!(frame.batches()[3].range, 0..3);
+    }
+
+    #[test]
+    fn test_textures_share_a_batch_only_when_equal() {
+        let mut frame = FrameBatches::default();
+        frame.push_quad(BatchKind::Textured(1), false, FULL, quad());
+        frame.push_quad(BatchKind::Textured(1), false, FULL, quad());
+        frame.push_quad(BatchKind::Textured(2), false, FULL, quad());
+        assert_eq!(frame.batches().len(), 2);
+        assert_eq!(frame.batches()[0].range, 0..2);
+    }
+
+    #[test]
+    fn test_scissor_and_camera_changes_split_batches() {
+        let mut frame = FrameBatches::<u8>::default();
+        frame.push_quad(BatchKind::Quads, true, FULL, quad());
+        frame.push_quad(BatchKind::Quads, true, (10, 10, 50, 50), quad());
+        frame.push_quad(BatchKind::Quads, false, (10, 10, 50, 50), quad());
+        assert_eq!(frame.batches().len(), 3);
+
+        frame.clear();
+        assert!(frame.is_empty());
+        assert!(frame.quads().is_empty());
+        assert!(frame.fills().is_empty());
+    }
+}
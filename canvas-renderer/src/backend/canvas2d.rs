@@ -3,9 +3,9 @@
 //! This backend uses pure 2D drawing (SVG/Canvas2D in browsers)
 //! when WebGPU/WebGL are unavailable.
 
-use canvas_core::{Element, ElementKind, Scene};
+use canvas_core::{Element, ElementKind, KpiDelta, OccupancySummary, Scene};
 
-use crate::{BackendType, RenderResult};
+use crate::{BackendType, DamageRect, FrameStats, RenderResult};
 
 use super::RenderBackend;
 
@@ -13,6 +13,7 @@ use super::RenderBackend;
 pub struct Canvas2DBackend {
     width: u32,
     height: u32,
+    last_frame: FrameStats,
 }
 
 impl Canvas2DBackend {
@@ -22,6 +23,7 @@ impl Canvas2DBackend {
         Self {
             width: 800,
             height: 600,
+            last_frame: FrameStats::default(),
         }
     }
 
@@ -32,16 +34,26 @@ impl Canvas2DBackend {
         let t = &element.transform;
         let (kind_name, details) = Self::element_description(&element.kind);
 
+        let style = &element.style;
+        let fill = style
+            .fill
+            .as_ref()
+            .and_then(canvas_core::Fill::primary_color)
+            .map_or_else(|| "default".to_string(), ToString::to_string);
+
         tracing::trace!(
-            "Render {kind_name} at ({}, {}) size {}x{}{details}",
+            "Render {kind_name} at ({}, {}) size {}x{}{details} fill={fill} radius={} opacity={}",
             t.x,
             t.y,
             t.width,
-            t.height
+            t.height,
+            style.effective_corner_radius(t.width, t.height),
+            element.effective_opacity()
         );
     }
 
     /// Get a description of an element kind for logging.
+    #[allow(clippy::too_many_lines)]
     fn element_description(kind: &ElementKind) -> (&'static str, String) {
         match kind {
             ElementKind::Text {
@@ -52,7 +64,44 @@ impl Canvas2DBackend {
                 "text",
                 format!(" content='{content}' font={font_size} color={color}"),
             ),
-            ElementKind::Image { src, format } => {
+            ElementKind::Math {
+                tex,
+                font_size,
+                color,
+            } => (
+                "math",
+                format!(" tex='{tex}' font={font_size} color={color}"),
+            ),
+            ElementKind::Code {
+                language, source, ..
+            } => (
+                "code",
+                format!(" language={language} lines={}", source.lines().count()),
+            ),
+            ElementKind::LogView {
+                title, lines, follow, ..
+            } => (
+                "log view",
+                format!(" title='{title}' lines={} follow={follow}", lines.len()),
+            ),
+            ElementKind::StickyNote {
+                content,
+                color,
+                author,
+            } => (
+                "sticky note",
+                format!(" content='{content}' color={color} author={author:?}"),
+            ),
+            ElementKind::Callout {
+                content,
+                color,
+                author,
+                target,
+            } => (
+                "callout",
+                format!(" content='{content}' color={color} author={author:?} target={target:?}"),
+            ),
+            ElementKind::Image { src, format, .. } => {
                 ("image", format!(" src={src} format={format:?}"))
             }
             ElementKind::Chart { chart_type, .. } => ("chart", format!(" type={chart_type}")),
@@ -60,14 +109,188 @@ impl Canvas2DBackend {
                 "3D placeholder",
                 " (3D not supported in 2D mode)".to_string(),
             ),
+            ElementKind::Shader { wgsl, uniforms } => (
+                "shader placeholder",
+                format!(
+                    " bytes={} uniforms={} (shaders need the wgpu backend)",
+                    wgsl.len(),
+                    uniforms.len()
+                ),
+            ),
             ElementKind::Video { stream_id, .. } => ("video", format!(" stream={stream_id}")),
-            ElementKind::OverlayLayer { children, opacity } => {
+            ElementKind::Audio {
+                stream_id,
+                waveform,
+                state,
+            } => (
+                "audio",
+                format!(
+                    " stream={stream_id} points={} state={state:?}",
+                    waveform.len()
+                ),
+            ),
+            ElementKind::Calendar {
+                view,
+                date,
+                events,
+                selected,
+            } => (
+                "calendar",
+                format!(
+                    " view={view:?} date={date} events={} selected={selected:?}",
+                    events.len()
+                ),
+            ),
+            ElementKind::Poll {
+                question,
+                options,
+                votes,
+                closed,
+            } => (
+                "poll",
+                format!(
+                    " question='{question}' options={} votes={} closed={closed}",
+                    options.len(),
+                    votes.len()
+                ),
+            ),
+            ElementKind::Agenda {
+                title,
+                items,
+                current,
+                started_at_ms,
+            } => (
+                "agenda",
+                format!(
+                    " title='{title}' items={} current={current} started_at_ms={started_at_ms:?}",
+                    items.len()
+                ),
+            ),
+            ElementKind::Randomizer { source, outcome } => (
+                "randomizer",
+                format!(
+                    " mode={} result='{}'",
+                    source.mode(),
+                    source.caption(outcome.as_ref())
+                ),
+            ),
+            ElementKind::FloorPlan {
+                background,
+                plan_width,
+                plan_height,
+                paths,
+                markers,
+                locked,
+            } => (
+                "floor plan",
+                format!(
+                    " plan={plan_width}x{plan_height} background={} paths={} markers={} ({}) locked={locked}",
+                    background.is_some(),
+                    paths.len(),
+                    markers.len(),
+                    OccupancySummary::from_markers(markers).caption()
+                ),
+            ),
+            ElementKind::Gauge {
+                value,
+                min,
+                max,
+                shape,
+                label,
+                thresholds,
+                format,
+            } => (
+                "gauge",
+                format!(
+                    " shape={shape:?} label='{label}' value={} range={min}..{max} thresholds={}",
+                    format.format(*value),
+                    thresholds.len()
+                ),
+            ),
+            ElementKind::Kpi {
+                label,
+                value,
+                previous,
+                sparkline,
+                sparkline_kind,
+                format,
+                higher_is_better,
+                ..
+            } => (
+                "kpi card",
+                format!(
+                    " label='{label}' value={} change='{}' sparkline={} ({sparkline_kind:?})",
+                    format.format(*value),
+                    previous
+                        .map(|previous| {
+                            KpiDelta::new(*value, previous, format, *higher_is_better).text
+                        })
+                        .unwrap_or_default(),
+                    sparkline.len()
+                ),
+            ),
+            ElementKind::Sparkline { values, kind, .. } => (
+                "sparkline",
+                format!(
+                    " kind={kind:?} points={} last={}",
+                    values.len(),
+                    values.last().map_or_else(String::new, ToString::to_string)
+                ),
+            ),
+            ElementKind::Ink { points, width, .. } => (
+                "ink stroke",
+                format!(" points={} width={width}", points.len()),
+            ),
+            ElementKind::Measurement {
+                kind, label, unit, ..
+            } => (
+                "measurement",
+                format!(
+                    " kind={kind:?} label='{}' unit={unit}",
+                    label.as_deref().unwrap_or_default()
+                ),
+            ),
+            ElementKind::Region { caption, .. } => (
+                "region highlight",
+                format!(" caption='{}'", caption.as_deref().unwrap_or_default()),
+            ),
+            ElementKind::Shape { shape } => ("shape", format!(" {shape:?}")),
+            ElementKind::Barcode {
+                symbology, payload, ..
+            } => ("barcode", format!(" {symbology:?} payload='{payload}'")),
+            ElementKind::Tree { root, direction } => (
+                "tree",
+                format!(
+                    " root='{}' nodes={} direction={direction:?}",
+                    root.label,
+                    root.count()
+                ),
+            ),
+            ElementKind::Embed { url, sandbox } => (
+                "embed placeholder",
+                format!(" url={url} sandbox='{sandbox}' (iframes need a DOM host)"),
+            ),
+            ElementKind::OverlayLayer {
+                children,
+                opacity,
+                clip,
+            } => {
                 let count = children.len();
-                ("overlay", format!(" children={count} opacity={opacity}"))
+                (
+                    "overlay",
+                    format!(" children={count} opacity={opacity} clip={clip}"),
+                )
             }
-            ElementKind::Group { children } => {
+            ElementKind::Group {
+                children,
+                clip,
+                opacity,
+            } => {
                 let count = children.len();
-                ("group", format!(" children={count}"))
+                (
+                    "group",
+                    format!(" children={count} opacity={opacity} clip={clip}"),
+                )
             }
         }
     }
@@ -84,6 +307,7 @@ impl RenderBackend for Canvas2DBackend {
         BackendType::Canvas2D
     }
 
+    #[allow(clippy::cast_precision_loss)] // Viewport dimensions fit in f32
     fn render(&mut self, scene: &Scene) -> RenderResult<()> {
         tracing::trace!(
             "Canvas2D render: {} elements, viewport {}x{}",
@@ -92,14 +316,79 @@ impl RenderBackend for Canvas2DBackend {
             self.height
         );
 
-        // Sort elements by z-index and render
-        let mut elements: Vec<_> = scene.elements().collect();
-        elements.sort_by_key(|e| e.transform.z_index);
+        // Cull offscreen elements, sort the rest by z-index with
+        // screen-anchored ones on top, and render
+        let mut elements: Vec<_> = scene.visible_elements().collect();
+        elements.sort_by_key(|e| (e.screen_anchored, e.transform.z_index));
+        self.last_frame = FrameStats::new(
+            scene.element_count(),
+            scene.element_count() - elements.len(),
+        );
 
         for element in elements {
             Self::render_element(element);
         }
 
+        if let Some(spotlight) = scene.spotlight() {
+            tracing::trace!(
+                "Spotlight: {} elements, dim {:.2}",
+                spotlight.element_ids.len(),
+                spotlight.dim_opacity
+            );
+        }
+
+        // Branding sits over everything, pinned to the viewport
+        if let Some(branding) = scene.branding() {
+            for element in branding.overlay(self.width as f32, self.height as f32) {
+                Self::render_element(&element);
+            }
+        }
+
+        Ok(())
+    }
+
+    #[allow(clippy::cast_precision_loss)] // Viewport dimensions fit in f32
+    fn render_damage(&mut self, scene: &Scene, regions: &[DamageRect]) -> RenderResult<()> {
+        tracing::trace!(
+            "Canvas2D partial render: {} regions of {} elements",
+            regions.len(),
+            scene.element_count()
+        );
+
+        // Clear each region to the background, then redraw only what
+        // overlaps one, clipped to the regions
+        for region in regions {
+            tracing::trace!(
+                "Clip and clear ({}, {}) size {}x{}",
+                region.x,
+                region.y,
+                region.width,
+                region.height
+            );
+        }
+        let damaged = |rect: DamageRect| regions.iter().any(|region| region.intersects(&rect));
+        let mut elements: Vec<_> = scene
+            .visible_elements()
+            .filter(|e| damaged(DamageRect::of_element(scene, e)))
+            .collect();
+        elements.sort_by_key(|e| (e.screen_anchored, e.transform.z_index));
+        self.last_frame = FrameStats::new(
+            scene.element_count(),
+            scene.element_count() - elements.len(),
+        );
+
+        for element in elements {
+            Self::render_element(element);
+        }
+
+        if let Some(branding) = scene.branding() {
+            for element in branding.overlay(self.width as f32, self.height as f32) {
+                if damaged(DamageRect::of_element(scene, &element)) {
+                    Self::render_element(&element);
+                }
+            }
+        }
+
         Ok(())
     }
 
@@ -109,4 +398,8 @@ impl RenderBackend for Canvas2DBackend {
         tracing::debug!("Canvas2D resized to {}x{}", width, height);
         Ok(())
     }
+
+    fn frame_stats(&self) -> FrameStats {
+        self.last_frame
+    }
 }
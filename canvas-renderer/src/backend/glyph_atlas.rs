@@ -0,0 +1,414 @@
+//! Glyph atlas for text in the wgpu backend.
+//!
+//! Glyphs are rasterized on the CPU with `ab_glyph` into a single-channel
+//! coverage atlas, keyed by glyph, pixel size and horizontal subpixel
+//! offset, and drawn as one quad per glyph tinted with the text color.
+//! Lines are broken by [`MeasureContext::wrap`], as the other backends do,
+//! so text wraps at the same words everywhere.
+//!
+//! Glyphs are rasterized at the size they appear on screen: with the scene
+//! zoomed to 2x, 16px text is rasterized at 32px rather than stretched.
+
+use std::collections::HashMap;
+
+use ab_glyph::{Font, FontArc, FontVec, GlyphId, PxScale, ScaleFont};
+use canvas_core::{CameraFrame, MeasureContext};
+
+use crate::{RenderError, RenderResult};
+
+/// Side length of the square atlas texture, in pixels.
+pub(crate) const ATLAS_SIZE: u32 = 1024;
+
+/// Horizontal subpixel positions each glyph can be rasterized at.
+const SUBPIXEL_STEPS: f32 = 4.0;
+
+/// Empty pixels around each glyph, so linear filtering never samples a
+/// neighbor.
+const PADDING: u32 = 1;
+
+/// Largest on-screen size glyphs are rasterized at; bigger text is
+/// stretched rather than filling the atlas with a few huge glyphs.
+const MAX_RASTER_SIZE: f32 = 256.0;
+
+/// One corner of a glyph quad.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub(crate) struct GlyphVertex {
+    /// Canvas coordinates.
+    pub(crate) position: [f32; 2],
+    /// Atlas coordinates, normalized.
+    pub(crate) uv: [f32; 2],
+    /// Text color with opacity applied.
+    pub(crate) color: [f32; 4],
+}
+
+/// A glyph rasterized at one size and subpixel offset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct GlyphKey {
+    glyph: GlyphId,
+    /// Pixel size in quarter pixels.
+    size: u32,
+    /// Subpixel step, `0..SUBPIXEL_STEPS`.
+    subpixel: u8,
+}
+
+/// Where a rasterized glyph sits in the atlas.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct AtlasGlyph {
+    /// Texel rect: x, y, width, height.
+    rect: [u32; 4],
+    /// Bitmap's top-left corner relative to the whole pixel the pen is in
+    /// on the baseline, in pixels.
+    offset: [f32; 2],
+}
+
+/// Packs rectangles into rows ("shelves") as tall as their tallest entry.
+#[derive(Debug, Default)]
+struct ShelfPacker {
+    /// Next free column on the current shelf.
+    x: u32,
+    /// Top of the current shelf.
+    y: u32,
+    /// Height of the current shelf.
+    height: u32,
+}
+
+impl ShelfPacker {
+    /// Reserve a `width` × `height` rect, or `None` if the atlas is full.
+    fn allocate(&mut self, width: u32, height: u32) -> Option<(u32, u32)> {
+        if width > ATLAS_SIZE || height > ATLAS_SIZE {
+            return None;
+        }
+        if self.x + width > ATLAS_SIZE {
+            self.y += self.height;
+            self.x = 0;
+            self.height = 0;
+        }
+        if self.y + height > ATLAS_SIZE {
+            return None;
+        }
+        let origin = (self.x, self.y);
+        self.x += width;
+        self.height = self.height.max(height);
+        Some(origin)
+    }
+}
+
+/// Text to lay out: content, style and the element's box.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct TextBlock<'a> {
+    pub(crate) content: &'a str,
+    pub(crate) font_size: f32,
+    /// Text color with opacity applied.
+    pub(crate) color: [f32; 4],
+    /// Element box: x, y, width, height, in canvas coordinates.
+    pub(crate) rect: [f32; 4],
+}
+
+/// Glyph cache and coverage atlas for one font.
+pub(crate) struct GlyphAtlas {
+    font: FontArc,
+    /// Coverage, one byte per texel, `ATLAS_SIZE` × `ATLAS_SIZE`.
+    pixels: Vec<u8>,
+    packer: ShelfPacker,
+    /// Rasterized glyphs; `None` for glyphs with no outline, like spaces.
+    glyphs: HashMap<GlyphKey, Option<AtlasGlyph>>,
+    /// Whether `pixels` changed since the last [`take_upload`](Self::take_upload).
+    dirty: bool,
+}
+
+impl GlyphAtlas {
+    /// Create an empty atlas for `font`.
+    pub(crate) fn new(font: FontArc) -> Self {
+        Self {
+            font,
+            pixels: vec![0; (ATLAS_SIZE * ATLAS_SIZE) as usize],
+            packer: ShelfPacker::default(),
+            glyphs: HashMap::new(),
+            dirty: true,
+        }
+    }
+
+    /// Create an atlas from TrueType or OpenType font data.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the data is not a font `ab_glyph` can read.
+    pub(crate) fn from_font_data(data: Vec<u8>) -> RenderResult<Self> {
+        FontVec::try_from_vec(data)
+            .map(|font| Self::new(FontArc::new(font)))
+            .map_err(|e| RenderError::Resource(format!("Invalid font: {e}")))
+    }
+
+    /// Create an atlas for the system's sans-serif font, or any installed
+    /// font if there is none. Returns `None` when no font can be loaded,
+    /// e.g. in the browser.
+    pub(crate) fn system() -> Option<Self> {
+        let mut db = fontdb::Database::new();
+        db.load_system_fonts();
+        let id = db
+            .query(&fontdb::Query {
+                families: &[fontdb::Family::SansSerif],
+                ..fontdb::Query::default()
+            })
+            .or_else(|| db.faces().next().map(|face| face.id))?;
+        let font = db.with_face_data(id, |data, index| {
+            FontVec::try_from_vec_and_index(data.to_vec(), index)
+        })?;
+        match font {
+            Ok(font) => Some(Self::new(FontArc::new(font))),
+            Err(e) => {
+                tracing::warn!("System font could not be read: {e}");
+                None
+            }
+        }
+    }
+
+    /// Drop every rasterized glyph, freeing the whole atlas.
+    pub(crate) fn clear(&mut self) {
+        self.pixels.fill(0);
+        self.packer = ShelfPacker::default();
+        self.glyphs.clear();
+        self.dirty = true;
+    }
+
+    /// The atlas pixels, if they changed since the last call.
+    pub(crate) fn take_upload(&mut self) -> Option<&[u8]> {
+        if !self.dirty {
+            return None;
+        }
+        self.dirty = false;
+        Some(&self.pixels)
+    }
+
+    /// Lay out `text` as glyph quads, six vertices each, rasterizing any
+    /// glyphs not yet in the atlas.
+    ///
+    /// `camera` maps the canvas to the screen; glyphs are rasterized at
+    /// their screen size and snapped to screen pixels vertically and to
+    /// quarter pixels horizontally. Returns `None` if the atlas is full;
+    /// [`clear`](Self::clear) it and lay the frame out again.
+    #[allow(
+        clippy::cast_precision_loss,
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss
+    )]
+    pub(crate) fn layout(
+        &mut self,
+        text: &TextBlock<'_>,
+        camera: &CameraFrame,
+    ) -> Option<Vec<GlyphVertex>> {
+        let zoom = if camera.zoom > 0.0 { camera.zoom } else { 1.0 };
+        let raster_size = (text.font_size * zoom).clamp(1.0, MAX_RASTER_SIZE);
+        // Screen pixels per rasterized pixel, usually 1
+        let stretch = text.font_size * zoom / raster_size;
+        let size = (raster_size * 4.0).round() as u32;
+        let font = self.font.clone();
+        let scaled = font.as_scaled(PxScale::from(raster_size));
+
+        let measure = MeasureContext::default();
+        let line_height = text.font_size * measure.line_height;
+        let [left_edge, top_edge, width, _] = text.rect;
+        let mut vertices = Vec::new();
+        for (row, line) in measure
+            .wrap(text.content, text.font_size, width)
+            .iter()
+            .enumerate()
+        {
+            // Pen position on screen; text hangs from the top of its line
+            let baseline = ((top_edge + row as f32 * line_height) * zoom
+                + camera.pan_y
+                + scaled.ascent() * stretch)
+                .round();
+            let mut pen = left_edge * zoom + camera.pan_x;
+            let mut previous: Option<GlyphId> = None;
+            for c in line.chars() {
+                let glyph = scaled.glyph_id(c);
+                if let Some(previous) = previous {
+                    pen += scaled.kern(previous, glyph) * stretch;
+                }
+                previous = Some(glyph);
+
+                let key = GlyphKey {
+                    glyph,
+                    size,
+                    subpixel: subpixel_step(pen),
+                };
+                if let Some(placed) = self.rasterize(key, raster_size)? {
+                    let left = pen.floor() + placed.offset[0] * stretch;
+                    let top = baseline + placed.offset[1] * stretch;
+                    let [.., texel_w, texel_h] = placed.rect;
+                    push_quad(
+                        &mut vertices,
+                        [
+                            (left - camera.pan_x) / zoom,
+                            (top - camera.pan_y) / zoom,
+                            texel_w as f32 * stretch / zoom,
+                            texel_h as f32 * stretch / zoom,
+                        ],
+                        placed.rect,
+                        text.color,
+                    );
+                }
+                pen += scaled.h_advance(glyph) * stretch;
+            }
+        }
+        Some(vertices)
+    }
+
+    /// Look up or rasterize a glyph. The outer `None` means the atlas is
+    /// full; the inner one that the glyph has no outline.
+    #[allow(
+        clippy::cast_precision_loss,
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss,
+        clippy::option_option
+    )]
+    fn rasterize(&mut self, key: GlyphKey, raster_size: f32) -> Option<Option<AtlasGlyph>> {
+        if let Some(placed) = self.glyphs.get(&key) {
+            return Some(*placed);
+        }
+        let offset = f32::from(key.subpixel) / SUBPIXEL_STEPS;
+        let glyph = key
+            .glyph
+            .with_scale_and_position(raster_size, ab_glyph::point(offset, 0.0));
+        let Some(outlined) = self.font.outline_glyph(glyph) else {
+            self.glyphs.insert(key, None);
+            return Some(None);
+        };
+        let bounds = outlined.px_bounds();
+        let (width, height) = (bounds.width() as u32, bounds.height() as u32);
+        let (x, y) = self
+            .packer
+            .allocate(width + 2 * PADDING, height + 2 * PADDING)?;
+        let (x, y) = (x + PADDING, y + PADDING);
+        outlined.draw(|gx, gy, coverage| {
+            let index = ((y + gy) * ATLAS_SIZE + x + gx) as usize;
+            if let Some(pixel) = self.pixels.get_mut(index) {
+                *pixel = (coverage.clamp(0.0, 1.0) * 255.0).round() as u8;
+            }
+        });
+        self.dirty = true;
+
+        let placed = Some(AtlasGlyph {
+            rect: [x, y, width, height],
+            offset: [bounds.min.x, bounds.min.y],
+        });
+        self.glyphs.insert(key, placed);
+        Some(placed)
+    }
+}
+
+/// The subpixel step a pen position falls in.
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)] // In 0..SUBPIXEL_STEPS
+fn subpixel_step(pen: f32) -> u8 {
+    let step = ((pen - pen.floor()) * SUBPIXEL_STEPS).floor() as u8;
+    step.min(SUBPIXEL_STEPS as u8 - 1)
+}
+
+/// Append two triangles covering `rect` (canvas coordinates), textured
+/// with the atlas texels in `texels`.
+#[allow(clippy::cast_precision_loss)] // Atlas coordinates are small
+fn push_quad(vertices: &mut Vec<GlyphVertex>, rect: [f32; 4], texels: [u32; 4], color: [f32; 4]) {
+    let size = ATLAS_SIZE as f32;
+    let uv = texels.map(|t| t as f32 / size);
+    let corner = |dx: f32, dy: f32| GlyphVertex {
+        position: [rect[0] + dx * rect[2], rect[1] + dy * rect[3]],
+        uv: [uv[0] + dx * uv[2], uv[1] + dy * uv[3]],
+        color,
+    };
+    let (tl, tr, br, bl) = (
+        corner(0.0, 0.0),
+        corner(1.0, 0.0),
+        corner(1.0, 1.0),
+        corner(0.0, 1.0),
+    );
+    vertices.extend([tl, tr, br, tl, br, bl]);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shelf_packer_fills_rows_then_reports_full() {
+        let mut packer = ShelfPacker::default();
+        assert_eq!(packer.allocate(600, 20), Some((0, 0)));
+        assert_eq!(packer.allocate(300, 30), Some((600, 0)));
+        // Too wide for the rest of the row: starts a shelf below the tallest
+        assert_eq!(packer.allocate(200, 10), Some((0, 30)));
+        assert_eq!(packer.allocate(ATLAS_SIZE + 1, 1), None);
+        assert_eq!(packer.allocate(ATLAS_SIZE, ATLAS_SIZE), None);
+    }
+
+    #[test]
+    fn test_subpixel_step_quantizes_pen_fraction() {
+        assert_eq!(subpixel_step(10.0), 0);
+        assert_eq!(subpixel_step(10.3), 1);
+        assert_eq!(subpixel_step(10.5), 2);
+        assert_eq!(subpixel_step(10.99), 3);
+        assert_eq!(subpixel_step(-0.25), 3);
+    }
+
+    #[test]
+    fn test_push_quad_maps_texels_to_uvs() {
+        let mut vertices = Vec::new();
+        let color = [1.0, 0.0, 0.0, 0.5];
+        push_quad(
+            &mut vertices,
+            [10.0, 20.0, 8.0, 12.0],
+            [0, 512, 8, 12],
+            color,
+        );
+        assert_eq!(vertices.len(), 6);
+        assert_eq!(
+            vertices[0],
+            GlyphVertex {
+                position: [10.0, 20.0],
+                uv: [0.0, 0.5],
+                color,
+            }
+        );
+        let corner = |x, y| GlyphVertex {
+            position: [18.0, 32.0],
+            uv: [x, y],
+            color,
+        };
+        assert_eq!(vertices[2], corner(8.0 / 1024.0, 524.0 / 1024.0));
+    }
+
+    #[test]
+    fn test_layout_wraps_and_follows_zoom() {
+        // Needs an installed font; sandboxed CI images may have none
+        let Some(mut atlas) = GlyphAtlas::system() else {
+            return;
+        };
+        let text = TextBlock {
+            content: "Hello world",
+            font_size: 16.0,
+            color: [0.0, 0.0, 0.0, 1.0],
+            rect: [100.0, 50.0, 50.0, 100.0],
+        };
+        let vertices = atlas.layout(&text, &CameraFrame::reset()).expect("fits");
+        assert_eq!(vertices.len(), 10 * 6, "one quad per visible glyph");
+        let top = |v: &[GlyphVertex]| v.iter().map(|v| v.position[1]).fold(f32::MAX, f32::min);
+        // "world" wraps onto the second line
+        assert!(vertices[5 * 6..]
+            .iter()
+            .all(|v| v.position[1] > top(&vertices[..6])));
+        assert!(atlas.take_upload().is_some());
+        assert!(atlas.take_upload().is_none());
+
+        // Same canvas layout at 2x, from glyphs rasterized twice as large
+        let zoomed = CameraFrame {
+            zoom: 2.0,
+            pan_x: 0.0,
+            pan_y: 0.0,
+        };
+        let vertices = atlas.layout(&text, &zoomed).expect("fits");
+        assert_eq!(vertices.len(), 10 * 6);
+        assert!(atlas.take_upload().is_some());
+        atlas.clear();
+        assert!(atlas.glyphs.is_empty());
+    }
+}
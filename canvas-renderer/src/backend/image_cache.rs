@@ -0,0 +1,224 @@
+//! Decoded image textures for the wgpu backend.
+//!
+//! Textures are keyed by a hash of the encoded image bytes, so elements
+//! showing the same picture share one upload however their `src` spells it,
+//! and an element whose `src` changes picks up the new image. Once the
+//! cache is over its byte budget, the least recently drawn textures are
+//! dropped; textures drawn in the current frame are never evicted.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::Hasher;
+
+/// Default GPU memory budget for decoded images.
+pub(crate) const DEFAULT_BUDGET_BYTES: usize = 256 * 1024 * 1024;
+
+/// Hash identifying encoded image content.
+pub(crate) fn content_hash(bytes: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    hasher.write(bytes);
+    hasher.finish()
+}
+
+/// Result of looking up an image source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Lookup {
+    /// The texture is cached under this content hash.
+    Hit(u64),
+    /// The source failed to load; it is not retried until forgotten.
+    Failed,
+    /// The source has not been loaded, or its texture was evicted.
+    Miss,
+}
+
+struct Entry<T> {
+    value: T,
+    bytes: usize,
+    last_used: u64,
+}
+
+/// Least-recently-used texture cache keyed by content hash.
+pub(crate) struct ImageCache<T> {
+    textures: HashMap<u64, Entry<T>>,
+    /// Content hash each `src` loaded as, or `None` if it failed to load.
+    sources: HashMap<String, Option<u64>>,
+    budget: usize,
+    size: usize,
+    frame: u64,
+}
+
+impl<T> ImageCache<T> {
+    pub(crate) fn new(budget: usize) -> Self {
+        Self {
+            textures: HashMap::new(),
+            sources: HashMap::new(),
+            budget,
+            size: 0,
+            frame: 0,
+        }
+    }
+
+    /// Start a frame; textures used from here on are kept until the next.
+    pub(crate) fn begin_frame(&mut self) {
+        self.frame += 1;
+    }
+
+    /// Change the byte budget, evicting textures not used this frame if the
+    /// cache is now over it.
+    pub(crate) fn set_budget(&mut self, budget: usize) {
+        self.budget = budget;
+        self.evict_to_fit(0);
+    }
+
+    /// Bytes of the cached textures.
+    pub(crate) fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Look up the texture for `src`, marking it used this frame.
+    pub(crate) fn lookup(&mut self, src: &str) -> Lookup {
+        match self.sources.get(src) {
+            Some(None) => Lookup::Failed,
+            Some(Some(hash)) => {
+                let hash = *hash;
+                if self.touch(hash) {
+                    Lookup::Hit(hash)
+                } else {
+                    Lookup::Miss
+                }
+            }
+            None => Lookup::Miss,
+        }
+    }
+
+    /// Mark the texture for `hash` used this frame, returning whether it is
+    /// cached.
+    pub(crate) fn touch(&mut self, hash: u64) -> bool {
+        let frame = self.frame;
+        self.textures
+            .get_mut(&hash)
+            .map(|entry| entry.last_used = frame)
+            .is_some()
+    }
+
+    /// Record what `src` loaded as: a content hash, or `None` on failure.
+    pub(crate) fn set_source(&mut self, src: &str, hash: Option<u64>) {
+        self.sources.insert(src.to_string(), hash);
+    }
+
+    /// Forget what `src` loaded as, so it is loaded again when next drawn.
+    pub(crate) fn forget_source(&mut self, src: &str) {
+        self.sources.remove(src);
+    }
+
+    /// Cache a texture of `bytes` bytes under `hash`, used this frame.
+    pub(crate) fn insert(&mut self, hash: u64, value: T, bytes: usize) {
+        if let Some(old) = self.textures.remove(&hash) {
+            self.size -= old.bytes;
+        }
+        self.evict_to_fit(bytes);
+        self.size += bytes;
+        self.textures.insert(
+            hash,
+            Entry {
+                value,
+                bytes,
+                last_used: self.frame,
+            },
+        );
+    }
+
+    pub(crate) fn get(&self, hash: u64) -> Option<&T> {
+        self.textures.get(&hash).map(|entry| &entry.value)
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.textures.clear();
+        self.sources.clear();
+        self.size = 0;
+    }
+
+    /// Evict least recently used textures until `incoming` more bytes fit,
+    /// or only textures used this frame are left.
+    fn evict_to_fit(&mut self, incoming: usize) {
+        while self.size + incoming > self.budget {
+            let Some(hash) = self
+                .textures
+                .iter()
+                .filter(|(_, entry)| entry.last_used < self.frame)
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(hash, _)| *hash)
+            else {
+                break;
+            };
+            if let Some(entry) = self.textures.remove(&hash) {
+                self.size -= entry.bytes;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lookup_follows_source_to_shared_texture() {
+        let mut cache = ImageCache::new(100);
+        assert_eq!(cache.lookup("a.png"), Lookup::Miss);
+
+        let hash = content_hash(b"png bytes");
+        cache.insert(hash, "texture", 10);
+        cache.set_source("a.png", Some(hash));
+        cache.set_source("data:image/png;base64,AA==", Some(hash));
+        cache.set_source("broken.png", None);
+
+        assert_eq!(cache.lookup("a.png"), Lookup::Hit(hash));
+        assert_eq!(
+            cache.lookup("data:image/png;base64,AA=="),
+            Lookup::Hit(hash)
+        );
+        assert_eq!(cache.lookup("broken.png"), Lookup::Failed);
+        assert_eq!(cache.get(hash), Some(&"texture"));
+
+        cache.forget_source("broken.png");
+        assert_eq!(cache.lookup("broken.png"), Lookup::Miss);
+    }
+
+    #[test]
+    fn test_evicts_least_recently_used_outside_current_frame() {
+        let mut cache = ImageCache::new(30);
+        cache.begin_frame();
+        cache.insert(1, (), 10);
+        cache.insert(2, (), 10);
+        cache.begin_frame();
+        cache.insert(3, (), 10);
+        assert!(cache.touch(1));
+
+        // Texture 2 is the least recently used one
+        cache.begin_frame();
+        cache.insert(4, (), 10);
+        assert!(cache.get(2).is_none());
+        assert!(cache.get(1).is_some() && cache.get(3).is_some());
+
+        // Everything left was used this frame, so the cache goes over budget
+        assert!(cache.touch(1) && cache.touch(3));
+        cache.insert(5, (), 10);
+        assert_eq!(cache.textures.len(), 4);
+
+        cache.begin_frame();
+        cache.set_budget(10);
+        assert_eq!(cache.textures.len(), 1);
+        assert_eq!(cache.size, 10);
+    }
+
+    #[test]
+    fn test_evicted_source_misses() {
+        let mut cache = ImageCache::new(10);
+        cache.insert(1, (), 10);
+        cache.set_source("a.png", Some(1));
+        cache.begin_frame();
+        cache.insert(2, (), 10);
+        assert_eq!(cache.lookup("a.png"), Lookup::Miss);
+    }
+}
@@ -0,0 +1,286 @@
+//! Lightfield compositing pass of the wgpu backend.
+//!
+//! Uploads a quilt and runs `lightfield.wgsl` over the target in one
+//! full-screen pass, turning the quilt into the image a Looking Glass
+//! display shows. See [`crate::lightfield`] for the lens model.
+
+use std::collections::HashMap;
+
+use wgpu::util::DeviceExt;
+
+use crate::error::{RenderError, RenderResult};
+use crate::lightfield::LensCalibration;
+use crate::quilt::QuiltRenderTarget;
+use crate::spatial::HolographicConfig;
+
+/// Uniforms of the pass, matching `lightfield.wgsl`.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct LensUniforms {
+    lens: [f32; 4],
+    quilt: [f32; 4],
+    flags: [f32; 4],
+}
+
+impl LensUniforms {
+    #[allow(clippy::cast_precision_loss)] // Quilt grids are small
+    fn new(layout: &HolographicConfig, calibration: &LensCalibration) -> Self {
+        let flag = |set: bool| if set { 1.0 } else { 0.0 };
+        Self {
+            lens: [
+                calibration.pitch,
+                calibration.tilt,
+                calibration.center,
+                calibration.subpixel,
+            ],
+            quilt: [
+                layout.quilt_columns as f32,
+                layout.quilt_rows as f32,
+                layout.num_views as f32,
+                0.0,
+            ],
+            flags: [
+                flag(calibration.invert_views),
+                flag(calibration.bgr),
+                flag(calibration.flip_x),
+                0.0,
+            ],
+        }
+    }
+}
+
+/// Pipeline state for composing quilts, with a pipeline per target format
+/// created on first use.
+pub(crate) struct LightfieldCompositor {
+    bind_group_layout: wgpu::BindGroupLayout,
+    sampler: wgpu::Sampler,
+    shader: wgpu::ShaderModule,
+    pipeline_layout: wgpu::PipelineLayout,
+    pipelines: HashMap<wgpu::TextureFormat, wgpu::RenderPipeline>,
+}
+
+impl LightfieldCompositor {
+    pub(crate) fn new(device: &wgpu::Device) -> Self {
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Lightfield Bind Group Layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        });
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Lightfield Sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        });
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Lightfield Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("../shaders/lightfield.wgsl").into()),
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Lightfield Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        Self {
+            bind_group_layout,
+            sampler,
+            shader,
+            pipeline_layout,
+            pipelines: HashMap::new(),
+        }
+    }
+
+    /// Upload `quilt`, laid out as `layout`, and encode the pass composing
+    /// it for `calibration` into `target`, a texture in `format`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the quilt is not `layout`'s size or is larger
+    /// than the device's textures.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn compose(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        encoder: &mut wgpu::CommandEncoder,
+        quilt: &QuiltRenderTarget,
+        layout: &HolographicConfig,
+        calibration: &LensCalibration,
+        target: &wgpu::TextureView,
+        format: wgpu::TextureFormat,
+    ) -> RenderResult<()> {
+        let (width, height) = (layout.quilt_width(), layout.quilt_height());
+        if quilt.width != width
+            || quilt.height != height
+            || quilt.pixels.len() != width as usize * height as usize * 4
+            || layout.num_views == 0
+        {
+            return Err(RenderError::Frame(format!(
+                "Quilt is {}x{}, its layout {width}x{height}",
+                quilt.width, quilt.height
+            )));
+        }
+        let limit = device.limits().max_texture_dimension_2d;
+        if width > limit || height > limit {
+            return Err(RenderError::Frame(format!(
+                "Quilt {width}x{height} exceeds the GPU's {limit} pixel textures"
+            )));
+        }
+
+        // Quilt bytes are sRGB, as every scene color is
+        let texture = device.create_texture_with_data(
+            queue,
+            &wgpu::TextureDescriptor {
+                label: Some("Lightfield Quilt"),
+                size: wgpu::Extent3d {
+                    width,
+                    height,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: wgpu::TextureFormat::Rgba8UnormSrgb,
+                usage: wgpu::TextureUsages::TEXTURE_BINDING,
+                view_formats: &[],
+            },
+            wgpu::util::TextureDataOrder::LayerMajor,
+            &quilt.pixels,
+        );
+        let quilt_view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let uniforms = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Lightfield Uniforms"),
+            contents: bytemuck::cast_slice(&[LensUniforms::new(layout, calibration)]),
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Lightfield Bind Group"),
+            layout: &self.bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: uniforms.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(&quilt_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::Sampler(&self.sampler),
+                },
+            ],
+        });
+
+        let pipeline = self.pipeline(device, format);
+        let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Lightfield Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: target,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        pass.set_pipeline(pipeline);
+        pass.set_bind_group(0, &bind_group, &[]);
+        pass.draw(0..3, 0..1);
+        Ok(())
+    }
+
+    /// The pipeline writing `format`, created on first use.
+    fn pipeline(
+        &mut self,
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+    ) -> &wgpu::RenderPipeline {
+        self.pipelines.entry(format).or_insert_with(|| {
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some("Lightfield Pipeline"),
+                layout: Some(&self.pipeline_layout),
+                vertex: wgpu::VertexState {
+                    module: &self.shader,
+                    entry_point: Some("vs_main"),
+                    compilation_options: wgpu::PipelineCompilationOptions::default(),
+                    buffers: &[],
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &self.shader,
+                    entry_point: Some("fs_main"),
+                    compilation_options: wgpu::PipelineCompilationOptions::default(),
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format,
+                        blend: None,
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                }),
+                primitive: wgpu::PrimitiveState::default(),
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState::default(),
+                multiview: None,
+                cache: None,
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_uniforms_pack_the_lens_grid_and_flags() {
+        let layout = HolographicConfig::looking_glass_portrait();
+        let lens = LensCalibration {
+            invert_views: true,
+            bgr: true,
+            ..LensCalibration::from_measurements(52.58, -7.22, 0.08, 324.0, 1536.0, 2048.0)
+                .expect("calibration")
+        };
+        let uniforms = LensUniforms::new(&layout, &lens);
+        let expected = LensUniforms {
+            lens: [lens.pitch, lens.tilt, lens.center, lens.subpixel],
+            quilt: [5.0, 9.0, 45.0, 0.0],
+            flags: [1.0, 1.0, 0.0, 0.0],
+        };
+        assert_eq!(bytemuck::bytes_of(&uniforms), bytemuck::bytes_of(&expected));
+        // Three vec4s, as the shader declares
+        assert_eq!(std::mem::size_of::<LensUniforms>(), 48);
+    }
+}
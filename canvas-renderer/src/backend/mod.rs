@@ -1,12 +1,33 @@
 //! Rendering backend implementations.
 
+#[cfg(feature = "gpu")]
+mod batch;
 pub mod canvas2d;
 #[cfg(feature = "gpu")]
+mod glyph_atlas;
+#[cfg(feature = "gpu")]
+mod image_cache;
+#[cfg(feature = "gpu")]
+mod lightfield_pass;
+#[cfg(feature = "gpu")]
+mod post_process;
+#[cfg(any(feature = "gpu", feature = "wasm"))]
+mod quad;
+#[cfg(feature = "export")]
+pub mod software;
+#[cfg(feature = "export")]
+pub mod svg;
+#[cfg(feature = "wasm")]
+pub mod webgl2;
+#[cfg(feature = "gpu")]
 pub mod wgpu;
 
 use canvas_core::Scene;
 
-use crate::{BackendType, RenderResult};
+use crate::{
+    AntiAliasing, BackendType, ChartStyle, ColorSpace, DamageRect, FrameStats, PostEffects,
+    RenderResult, RgbaImage, VisibleSet,
+};
 
 /// Trait for rendering backends.
 pub trait RenderBackend {
@@ -20,10 +41,106 @@ pub trait RenderBackend {
     /// Returns an error if rendering fails.
     fn render(&mut self, scene: &Scene) -> RenderResult<()>;
 
+    /// Repaint only `regions` of the previous frame, in viewport pixels.
+    ///
+    /// Called instead of [`render`](Self::render) when damage tracking is
+    /// on and the last frame is still on screen outside `regions`. Backends
+    /// that cannot keep their last frame repaint everything.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if rendering fails.
+    fn render_damage(&mut self, scene: &Scene, regions: &[DamageRect]) -> RenderResult<()> {
+        let _ = regions;
+        self.render(scene)
+    }
+
+    /// Limit the next [`render`](Self::render) or
+    /// [`render_damage`](Self::render_damage) to `visible`, the elements
+    /// the renderer's culling pass found in view.
+    ///
+    /// Backends that do not override this draw every shown element and cull
+    /// for themselves, if at all.
+    fn set_visible_elements(&mut self, visible: VisibleSet) {
+        let _ = visible;
+    }
+
+    /// Use `style` as the default look of charts, under each chart's own
+    /// `data.style`.
+    ///
+    /// Backends that do not draw charts ignore it.
+    fn set_chart_style(&mut self, style: ChartStyle) {
+        let _ = style;
+    }
+
+    /// Smooth edges from the next frame on as `mode` says.
+    ///
+    /// Backends without a choice of anti-aliasing ignore it.
+    fn set_antialiasing(&mut self, mode: AntiAliasing) {
+        let _ = mode;
+    }
+
+    /// Present frames in `space` from the next frame on, or in the nearest
+    /// space the display has.
+    ///
+    /// Backends that only present sRGB ignore it.
+    fn set_color_space(&mut self, space: ColorSpace) {
+        let _ = space;
+    }
+
+    /// The color space frames are presented in.
+    fn color_space(&self) -> ColorSpace {
+        ColorSpace::Srgb
+    }
+
+    /// Set the time `Shader` elements see as `canvas.time`, in seconds.
+    ///
+    /// Backends that cannot run shaders ignore it.
+    fn set_shader_time(&mut self, seconds: f32) {
+        let _ = seconds;
+    }
+
+    /// Apply `effects` to frames and overlay layers from the next frame on.
+    ///
+    /// Backends without a post-processing stage ignore them.
+    fn set_post_effects(&mut self, effects: &PostEffects) {
+        let _ = effects;
+    }
+
     /// Resize the rendering surface.
     ///
     /// # Errors
     ///
     /// Returns an error if resizing fails.
     fn resize(&mut self, width: u32, height: u32) -> RenderResult<()>;
+
+    /// Element counts from the most recent [`render`](Self::render).
+    fn frame_stats(&self) -> FrameStats {
+        FrameStats::default()
+    }
+
+    /// GPU time of the most recent frame whose timing is available, in
+    /// milliseconds; `None` if the backend cannot time the GPU or no new
+    /// timing has arrived.
+    fn gpu_time_ms(&mut self) -> Option<f64> {
+        None
+    }
+
+    /// Render a scene into a `width` x `height` offscreen image, leaving the
+    /// surface and its size alone.
+    ///
+    /// Returns `None` if the backend cannot render offscreen, in which case
+    /// the renderer falls back to the CPU rasterizer.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if rendering or readback fails.
+    fn render_to_image(
+        &mut self,
+        _scene: &Scene,
+        _width: u32,
+        _height: u32,
+    ) -> RenderResult<Option<RgbaImage>> {
+        Ok(None)
+    }
 }
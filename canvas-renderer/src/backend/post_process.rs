@@ -0,0 +1,564 @@
+//! Post-processing stage of the wgpu backend.
+//!
+//! Runs a chain of [`PostEffect`]s from one texture into another as
+//! full-screen passes (`post.wgsl`), one per effect except blurs, which
+//! take a horizontal and a vertical pass, and bloom, which takes four:
+//!
+//! ```text
+//! input ─▶ effect ─▶ A ─▶ effect ─▶ B ─▶ effect ─▶ output
+//!   blur: source ─▶ C (horizontal) ─▶ target (vertical)
+//!  bloom: source ─▶ C (bright) ─▶ D ─▶ C (blurred) ─▶ target (source + C)
+//! ```
+//!
+//! Presenting in HDR10 adds a last pass that encodes the linear result
+//! with the PQ curve into the 10-bit surface.
+//!
+//! Scratch textures are kept between frames and recreated when the size
+//! changes.
+
+use std::collections::HashMap;
+
+use wgpu::util::DeviceExt;
+
+use crate::post::{blur_taps, PostEffect};
+
+/// Uniforms of one pass, matching `post.wgsl`.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct PostUniforms {
+    params: [f32; 4],
+    texel: [f32; 4],
+}
+
+/// Fragment entry point of a pass.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Stage {
+    Blur,
+    Bright,
+    Bloom,
+    Vignette,
+    Grade,
+    EncodePq,
+}
+
+impl Stage {
+    const fn entry_point(self) -> &'static str {
+        match self {
+            Self::Blur => "fs_blur",
+            Self::Bright => "fs_bright",
+            Self::Bloom => "fs_bloom",
+            Self::Vignette => "fs_vignette",
+            Self::Grade => "fs_grade",
+            Self::EncodePq => "fs_encode_pq",
+        }
+    }
+}
+
+/// A texture a pass reads or writes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Slot {
+    Input,
+    Output,
+    Scratch(usize),
+}
+
+/// One full-screen pass.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Pass {
+    stage: Stage,
+    source: Slot,
+    /// Second texture, read by the bloom composite only.
+    base: Slot,
+    target: Slot,
+    params: [f32; 4],
+}
+
+/// Scratch slots for the glow of a bloom and the middle of a blur.
+const GLOW: Slot = Slot::Scratch(2);
+const GLOW_SPARE: Slot = Slot::Scratch(3);
+
+/// Where the last pass of a chain writes.
+pub(crate) struct PostOutput<'a> {
+    pub(crate) view: &'a wgpu::TextureView,
+    pub(crate) format: wgpu::TextureFormat,
+    /// Encode for HDR10, with linear 1.0 shown at this many nits.
+    pub(crate) pq_white: Option<f32>,
+}
+
+/// The passes that run `effects` from the input into the output, then
+/// encode for HDR10 if `pq_white` is set. With neither, the input is
+/// copied through a zero-tap blur.
+fn plan(effects: &[PostEffect], pq_white: Option<f32>) -> Vec<Pass> {
+    fn blur(passes: &mut Vec<Pass>, radius: f32, source: Slot, middle: Slot, target: Slot) {
+        let (taps, stride) = blur_taps(radius);
+        for (from, to, step) in [
+            (source, middle, [stride, 0.0]),
+            (middle, target, [0.0, stride]),
+        ] {
+            passes.push(Pass {
+                stage: Stage::Blur,
+                source: from,
+                base: from,
+                target: to,
+                params: [step[0], step[1], taps, 0.0],
+            });
+        }
+    }
+
+    let mut passes = Vec::new();
+    if effects.is_empty() && pq_white.is_none() {
+        passes.push(Pass {
+            stage: Stage::Blur,
+            source: Slot::Input,
+            base: Slot::Input,
+            target: Slot::Output,
+            params: [0.0; 4],
+        });
+        return passes;
+    }
+    let mut source = Slot::Input;
+    for (index, effect) in effects.iter().enumerate() {
+        let target = if index + 1 == effects.len() && pq_white.is_none() {
+            Slot::Output
+        } else {
+            Slot::Scratch(index % 2)
+        };
+        let single = |stage, params| Pass {
+            stage,
+            source,
+            base: source,
+            target,
+            params,
+        };
+        match *effect {
+            PostEffect::Blur { radius } => blur(&mut passes, radius, source, GLOW, target),
+            PostEffect::Vignette {
+                strength,
+                radius,
+                softness,
+            } => passes.push(single(Stage::Vignette, [strength, radius, softness, 0.0])),
+            PostEffect::ColorGrade {
+                brightness,
+                contrast,
+                saturation,
+                temperature,
+            } => passes.push(single(
+                Stage::Grade,
+                [brightness, contrast, saturation, temperature],
+            )),
+            PostEffect::Bloom {
+                threshold,
+                intensity,
+                radius,
+            } => {
+                passes.push(Pass {
+                    target: GLOW,
+                    ..single(Stage::Bright, [threshold, 0.0, 0.0, 0.0])
+                });
+                blur(&mut passes, radius, GLOW, GLOW_SPARE, GLOW);
+                passes.push(Pass {
+                    stage: Stage::Bloom,
+                    source: GLOW,
+                    base: source,
+                    target,
+                    params: [intensity, 0.0, 0.0, 0.0],
+                });
+            }
+        }
+        source = target;
+    }
+    if let Some(white) = pq_white {
+        passes.push(Pass {
+            stage: Stage::EncodePq,
+            source,
+            base: source,
+            target: Slot::Output,
+            params: [white, 0.0, 0.0, 0.0],
+        });
+    }
+    passes
+}
+
+/// Pipelines and scratch textures for post-processing in one format.
+pub(crate) struct PostProcessor {
+    format: wgpu::TextureFormat,
+    bind_group_layout: wgpu::BindGroupLayout,
+    sampler: wgpu::Sampler,
+    shader: wgpu::ShaderModule,
+    pipeline_layout: wgpu::PipelineLayout,
+    /// By stage and the format it writes, created on first use.
+    pipelines: HashMap<(Stage, wgpu::TextureFormat), wgpu::RenderPipeline>,
+    /// Size of the textures below.
+    size: (u32, u32),
+    /// What a frame is drawn into before its effects run.
+    frame: Option<wgpu::Texture>,
+    scratch: Vec<wgpu::Texture>,
+}
+
+impl PostProcessor {
+    /// Create a processor working in `format`; pipelines and textures are
+    /// created on first use.
+    pub(crate) fn new(device: &wgpu::Device, format: wgpu::TextureFormat) -> Self {
+        let texture_entry = |binding| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                view_dimension: wgpu::TextureViewDimension::D2,
+                multisampled: false,
+            },
+            count: None,
+        };
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Post Bind Group Layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                texture_entry(1),
+                texture_entry(2),
+                wgpu::BindGroupLayoutEntry {
+                    binding: 3,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        });
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Post Sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        });
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Post Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("../shaders/post.wgsl").into()),
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Post Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        Self {
+            format,
+            bind_group_layout,
+            sampler,
+            shader,
+            pipeline_layout,
+            pipelines: HashMap::new(),
+            size: (0, 0),
+            frame: None,
+            scratch: Vec::new(),
+        }
+    }
+
+    /// The format the pipelines write.
+    pub(crate) const fn format(&self) -> wgpu::TextureFormat {
+        self.format
+    }
+
+    /// A `size` texture to draw a frame into before its effects run.
+    pub(crate) fn frame_view(
+        &mut self,
+        device: &wgpu::Device,
+        size: (u32, u32),
+    ) -> wgpu::TextureView {
+        self.resize(size);
+        let frame = match self.frame.take() {
+            Some(frame) => frame,
+            None => self.create_texture(device, "Post Frame"),
+        };
+        let view = frame.create_view(&wgpu::TextureViewDescriptor::default());
+        self.frame = Some(frame);
+        view
+    }
+
+    /// Encode `effects` from `input`, a `size` texture in [`Self::format`],
+    /// into `output`, a `size` texture in its own format. Returns the number
+    /// of passes.
+    pub(crate) fn apply(
+        &mut self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        input: &wgpu::TextureView,
+        output: &PostOutput<'_>,
+        size: (u32, u32),
+        effects: &[PostEffect],
+    ) -> usize {
+        self.resize(size);
+        let passes = plan(effects, output.pq_white);
+        let format = |slot| match slot {
+            Slot::Output => output.format,
+            _ => self.format,
+        };
+        let needed: Vec<_> = passes
+            .iter()
+            .map(|pass| (pass.stage, format(pass.target)))
+            .collect();
+        for (stage, format) in needed {
+            self.create_pipeline(device, stage, format);
+        }
+        let scratch_needed = passes
+            .iter()
+            .flat_map(|pass| [pass.source, pass.target])
+            .filter_map(|slot| match slot {
+                Slot::Scratch(index) => Some(index + 1),
+                _ => None,
+            })
+            .max()
+            .unwrap_or(0);
+        while self.scratch.len() < scratch_needed {
+            let texture = self.create_texture(device, "Post Scratch");
+            self.scratch.push(texture);
+        }
+        let scratch: Vec<wgpu::TextureView> = self
+            .scratch
+            .iter()
+            .map(|texture| texture.create_view(&wgpu::TextureViewDescriptor::default()))
+            .collect();
+        let view = |slot| match slot {
+            Slot::Input => input,
+            Slot::Output => output.view,
+            Slot::Scratch(index) => &scratch[index],
+        };
+
+        #[allow(clippy::cast_precision_loss)] // Texture sizes fit in f32
+        let texel = [
+            1.0 / size.0.max(1) as f32,
+            1.0 / size.1.max(1) as f32,
+            0.0,
+            0.0,
+        ];
+        for pass in &passes {
+            let uniforms = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Post Uniforms"),
+                contents: bytemuck::cast_slice(&[PostUniforms {
+                    params: pass.params,
+                    texel,
+                }]),
+                usage: wgpu::BufferUsages::UNIFORM,
+            });
+            let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("Post Bind Group"),
+                layout: &self.bind_group_layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: uniforms.as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::TextureView(view(pass.source)),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 2,
+                        resource: wgpu::BindingResource::TextureView(view(pass.base)),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 3,
+                        resource: wgpu::BindingResource::Sampler(&self.sampler),
+                    },
+                ],
+            });
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Post Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: view(pass.target),
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            let format = match pass.target {
+                Slot::Output => output.format,
+                _ => self.format,
+            };
+            render_pass.set_pipeline(&self.pipelines[&(pass.stage, format)]);
+            render_pass.set_bind_group(0, &bind_group, &[]);
+            render_pass.draw(0..3, 0..1);
+        }
+        passes.len()
+    }
+
+    /// Create the pipeline running `stage` into `format`, once.
+    fn create_pipeline(
+        &mut self,
+        device: &wgpu::Device,
+        stage: Stage,
+        format: wgpu::TextureFormat,
+    ) {
+        if self.pipelines.contains_key(&(stage, format)) {
+            return;
+        }
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some(&format!("Post {stage:?} Pipeline")),
+            layout: Some(&self.pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &self.shader,
+                entry_point: Some("vs_main"),
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &self.shader,
+                entry_point: Some(stage.entry_point()),
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+        self.pipelines.insert((stage, format), pipeline);
+    }
+
+    /// Textures held between frames, for memory accounting.
+    pub(crate) fn textures(&self) -> impl Iterator<Item = &wgpu::Texture> {
+        self.frame.iter().chain(&self.scratch)
+    }
+
+    /// Drop textures of another size.
+    fn resize(&mut self, size: (u32, u32)) {
+        let size = (size.0.max(1), size.1.max(1));
+        if self.size != size {
+            self.size = size;
+            self.frame = None;
+            self.scratch.clear();
+        }
+    }
+
+    fn create_texture(&self, device: &wgpu::Device, label: &str) -> wgpu::Texture {
+        device.create_texture(&wgpu::TextureDescriptor {
+            label: Some(label),
+            size: wgpu::Extent3d {
+                width: self.size.0,
+                height: self.size.1,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: self.format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_effects_chain_through_alternating_scratch_textures() {
+        let vignette = PostEffect::Vignette {
+            strength: 0.5,
+            radius: 0.5,
+            softness: 0.5,
+        };
+        let grade: PostEffect = "grade".parse().expect("grade");
+        let passes = plan(&[vignette, grade, vignette], None);
+        let route: Vec<_> = passes
+            .iter()
+            .map(|pass| (pass.source, pass.target))
+            .collect();
+        assert_eq!(
+            route,
+            [
+                (Slot::Input, Slot::Scratch(0)),
+                (Slot::Scratch(0), Slot::Scratch(1)),
+                (Slot::Scratch(1), Slot::Output),
+            ]
+        );
+
+        let copy = plan(&[], None);
+        assert_eq!(copy.len(), 1);
+        assert_eq!(
+            (copy[0].source, copy[0].target),
+            (Slot::Input, Slot::Output)
+        );
+    }
+
+    #[test]
+    fn test_bloom_glows_from_a_blurred_bright_pass() {
+        let passes = plan(
+            &[PostEffect::Bloom {
+                threshold: 0.7,
+                intensity: 1.0,
+                radius: 8.0,
+            }],
+            None,
+        );
+        let stages: Vec<_> = passes.iter().map(|pass| pass.stage).collect();
+        assert_eq!(
+            stages,
+            [Stage::Bright, Stage::Blur, Stage::Blur, Stage::Bloom]
+        );
+        let composite = passes[3];
+        assert_eq!(
+            (composite.source, composite.base, composite.target),
+            (GLOW, Slot::Input, Slot::Output)
+        );
+        // Horizontal then vertical, eight taps a pixel apart
+        let close = |params: [f32; 4], expected: [f32; 4]| {
+            params
+                .iter()
+                .zip(expected)
+                .all(|(value, expected)| (value - expected).abs() < 1e-6)
+        };
+        assert!(close(passes[1].params, [1.0, 0.0, 8.0, 0.0]));
+        assert!(close(passes[2].params, [0.0, 1.0, 8.0, 0.0]));
+    }
+
+    #[test]
+    fn test_hdr10_output_ends_with_an_encode_pass() {
+        let encode = plan(&[], Some(203.0));
+        assert_eq!(encode.len(), 1);
+        assert_eq!(
+            (encode[0].stage, encode[0].source, encode[0].target),
+            (Stage::EncodePq, Slot::Input, Slot::Output)
+        );
+
+        // The last effect writes a scratch texture the encode reads
+        let grade: PostEffect = "grade".parse().expect("grade");
+        let passes = plan(&[grade, grade], Some(203.0));
+        let route: Vec<_> = passes
+            .iter()
+            .map(|pass| (pass.stage, pass.source, pass.target))
+            .collect();
+        assert_eq!(
+            route,
+            [
+                (Stage::Grade, Slot::Input, Slot::Scratch(0)),
+                (Stage::Grade, Slot::Scratch(0), Slot::Scratch(1)),
+                (Stage::EncodePq, Slot::Scratch(1), Slot::Output),
+            ]
+        );
+        assert!((passes[2].params[0] - 203.0).abs() < f32::EPSILON);
+    }
+}
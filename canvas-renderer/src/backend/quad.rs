@@ -0,0 +1,237 @@
+//! Quad colors and styles shared by the GPU backends.
+//!
+//! Both the wgpu and WebGL2 backends draw every element as a unit quad whose
+//! fragment shader handles rounded corners, two-color gradients, strokes and
+//! soft shadow edges; this module turns an element's kind and style into
+//! those shader parameters.
+
+use std::collections::HashMap;
+
+use canvas_core::highlight::CODE_BACKGROUND;
+use canvas_core::log_view::LOG_BACKGROUND;
+use canvas_core::model3d;
+use canvas_core::shader::SHADER_PLACEHOLDER;
+use canvas_core::{Color, Element, ElementId, ElementKind, Fill, Theme};
+
+/// Per-draw style parameters for the quad shader.
+///
+/// The shader supports two-color gradients; multi-stop gradients are
+/// approximated by their first and last stops.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) struct QuadStyle {
+    pub(crate) fill_end: [f32; 4],
+    pub(crate) stroke_color: [f32; 4],
+    pub(crate) params: [f32; 4],
+    pub(crate) extra: [f32; 4],
+}
+
+impl QuadStyle {
+    /// A flat, square-cornered quad with no stroke.
+    pub(crate) const FLAT: Self = Self {
+        fill_end: [0.0; 4],
+        stroke_color: [0.0; 4],
+        params: [0.0; 4],
+        extra: [0.0; 4],
+    };
+}
+
+/// Get the display color for an element based on its kind, resolving
+/// theme references against `theme`.
+pub(crate) fn element_color(element: &Element, theme: &Theme) -> [f32; 4] {
+    // If selected, use selection color
+    if element.selected {
+        return [0.2, 0.6, 1.0, 0.8]; // Blue selection highlight
+    }
+
+    // An explicit style fill takes precedence over the per-kind default
+    if let Some(color) = element
+        .style
+        .fill
+        .as_ref()
+        .and_then(Fill::primary_color)
+        .and_then(|color| theme.resolve(color))
+    {
+        return color.to_rgba_f32();
+    }
+
+    match &element.kind {
+        // Light blue for charts and tree nodes
+        ElementKind::Chart { .. } | ElementKind::Tree { .. } => [0.9, 0.95, 1.0, 1.0],
+        ElementKind::Image { .. } => [0.95, 0.95, 0.95, 1.0], // Light gray placeholder
+        // Light green for 3D, lit as the model's front face
+        ElementKind::Model3D { .. } => {
+            let [r, g, b] =
+                model3d::shade_front(&element.kind, [0.8, 0.9, 0.8]).unwrap_or([0.8, 0.9, 0.8]);
+            [r, g, b, 1.0]
+        }
+        ElementKind::Video { .. } => [0.2, 0.2, 0.2, 1.0], // Dark for video
+        // Where a shader cannot run, or while it compiles
+        ElementKind::Shader { .. } => {
+            Color::parse(SHADER_PLACEHOLDER).map_or([0.17, 0.18, 0.26, 1.0], Color::to_rgba_f32)
+        }
+        ElementKind::Audio { .. } => [0.15, 0.2, 0.22, 1.0], // Dark slate for audio
+        ElementKind::Embed { .. } => [0.93, 0.94, 0.96, 1.0], // Pale slate for web embeds
+        ElementKind::Sparkline { .. } => [0.12, 0.53, 0.9, 1.0], // Sparkline blue
+        ElementKind::Ink { .. } => [0.13, 0.13, 0.13, 1.0],  // Near-black ink
+        // Annotation red: solid line, translucent region tint
+        ElementKind::Measurement { .. } => [0.9, 0.22, 0.21, 1.0],
+        ElementKind::Region { .. } => [0.9, 0.22, 0.21, 0.15],
+        ElementKind::Shape { .. } => ElementKind::SHAPE_COLOR.to_rgba_f32(),
+        // White calendar page, poll, agenda or dashboard card; barcodes
+        // are white too, as scanners need a light background
+        ElementKind::Barcode { .. }
+        | ElementKind::Calendar { .. }
+        | ElementKind::Poll { .. }
+        | ElementKind::Agenda { .. }
+        | ElementKind::Gauge { .. }
+        | ElementKind::Kpi { .. } => [1.0, 1.0, 1.0, 1.0],
+        ElementKind::Randomizer { .. } => [0.98, 0.98, 0.98, 1.0], // Off-white randomizer card
+        ElementKind::FloorPlan { .. } => [0.96, 0.97, 0.97, 1.0],  // Pale floor
+        ElementKind::OverlayLayer { opacity, .. } => [1.0, 1.0, 1.0, *opacity], // Transparent
+        ElementKind::Text { .. }
+        | ElementKind::Math { .. }
+        | ElementKind::StickyNote { .. }
+        | ElementKind::Callout { .. } => element
+            .kind
+            .resolved_color(theme)
+            .unwrap_or(ElementKind::TEXT_COLOR)
+            .to_rgba_f32(),
+        ElementKind::Code { .. } => {
+            Color::parse(CODE_BACKGROUND).map_or([0.12, 0.12, 0.12, 1.0], Color::to_rgba_f32)
+        }
+        ElementKind::LogView { .. } => {
+            Color::parse(LOG_BACKGROUND).map_or([0.05, 0.05, 0.05, 1.0], Color::to_rgba_f32)
+        }
+        ElementKind::Group { .. } => [0.95, 0.95, 0.9, 0.5], // Transparent yellow for groups
+    }
+}
+
+/// Build the shader style parameters for an element.
+///
+/// `opacity` is the combined inherited and element opacity, applied to the
+/// gradient end and stroke colors.
+pub(crate) fn element_quad_style(element: &Element, theme: &Theme, opacity: f32) -> QuadStyle {
+    let style = &element.style;
+    let mut quad = QuadStyle::FLAT;
+    quad.params[2] =
+        style.effective_corner_radius(element.transform.width, element.transform.height);
+
+    // Selection highlight replaces the fill, so gradients only apply when unselected
+    if !element.selected {
+        if let Some(fill) = &style.fill {
+            if let Some(end) = fill.secondary_color().and_then(|c| theme.resolve(c)) {
+                let mut end = end.to_rgba_f32();
+                end[3] *= opacity;
+                quad.fill_end = end;
+            }
+            match fill {
+                Fill::Solid { .. } => {}
+                Fill::LinearGradient { angle, .. } => {
+                    quad.params[0] = 1.0;
+                    quad.params[1] = *angle;
+                }
+                Fill::RadialGradient {
+                    center_x,
+                    center_y,
+                    radius,
+                    ..
+                } => {
+                    quad.params[0] = 2.0;
+                    quad.params[1] = *radius;
+                    quad.extra[1] = *center_x;
+                    quad.extra[2] = *center_y;
+                }
+            }
+        }
+    }
+
+    if let Some(stroke) = &style.stroke {
+        if let Some(color) = theme.resolve(&stroke.color) {
+            if stroke.width.is_finite() && stroke.width > 0.0 {
+                let mut color = color.to_rgba_f32();
+                color[3] *= opacity;
+                quad.stroke_color = color;
+                quad.params[3] = stroke.width;
+            }
+        }
+    }
+
+    quad
+}
+
+/// Compute the blurred quad for an element's drop shadow, if it has one.
+///
+/// Returns the shadow rect, its color, and its shader style.
+pub(crate) fn shadow_quad(
+    element: &Element,
+    theme: &Theme,
+    rect: [f32; 4],
+    opacity: f32,
+) -> Option<([f32; 4], [f32; 4], QuadStyle)> {
+    let shadow = element.style.shadow.as_ref()?;
+    let mut color = theme.resolve(&shadow.color)?.to_rgba_f32();
+    color[3] *= opacity;
+
+    let blur = if shadow.blur.is_finite() {
+        shadow.blur.max(0.0)
+    } else {
+        0.0
+    };
+    let spread = blur / 2.0;
+    let shadow_rect = [
+        rect[0] + shadow.offset_x - spread,
+        rect[1] + shadow.offset_y - spread,
+        rect[2] + blur,
+        rect[3] + blur,
+    ];
+
+    let mut style = QuadStyle::FLAT;
+    style.params[2] = element.style.effective_corner_radius(rect[2], rect[3]) + spread;
+    style.extra[0] = blur;
+
+    Some((shadow_rect, color, style))
+}
+
+/// Build a map of element ID to inherited opacity from parent `OverlayLayer`s.
+///
+/// Handles nested overlays by multiplying opacities.
+pub(crate) fn build_opacity_map(elements: &[Element]) -> HashMap<ElementId, f32> {
+    let mut opacity_map = HashMap::new();
+
+    // Create a lookup from element ID to element for nested resolution
+    let element_lookup: HashMap<_, _> = elements.iter().map(|e| (e.id, e)).collect();
+
+    // Process each OverlayLayer
+    for element in elements {
+        if let ElementKind::OverlayLayer {
+            children, opacity, ..
+        } = &element.kind
+        {
+            // Get the overlay's own inherited opacity (in case it's nested)
+            let parent_opacity = opacity_map.get(&element.id).copied().unwrap_or(1.0);
+            let effective_opacity = parent_opacity * opacity;
+
+            // Apply to all children
+            for child_id in children {
+                let child_opacity = opacity_map.get(child_id).copied().unwrap_or(1.0);
+                opacity_map.insert(*child_id, child_opacity * effective_opacity);
+
+                // If the child is also an OverlayLayer, propagate to its children
+                if let Some(child_element) = element_lookup.get(child_id) {
+                    if let ElementKind::OverlayLayer {
+                        children: grandchildren,
+                        ..
+                    } = &child_element.kind
+                    {
+                        for grandchild_id in grandchildren {
+                            let gc_opacity = opacity_map.get(grandchild_id).copied().unwrap_or(1.0);
+                            opacity_map.insert(*grandchild_id, gc_opacity * effective_opacity);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    opacity_map
+}
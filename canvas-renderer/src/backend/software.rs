@@ -0,0 +1,282 @@
+//! Software backend for headless rendering.
+//!
+//! Rasterizes scenes on the CPU into an RGBA buffer, with no GPU or window
+//! system, so servers in containers can produce exports and thumbnails. The
+//! scene is drawn as [`SvgBackend`] draws it, with vector charts and
+//! embedded images, and the document is rasterized with resvg and
+//! tiny-skia.
+
+use canvas_core::{Redaction, Scene, SceneBounds};
+
+use crate::export::SceneExporter;
+use crate::snapshot;
+use crate::{BackendType, ChartStyle, FrameStats, RenderResult, RgbaImage};
+
+use super::svg::SvgBackend;
+use super::RenderBackend;
+
+/// Renders scenes into RGBA images on the CPU.
+pub struct SoftwareBackend {
+    svg: SvgBackend,
+    frame: Option<RgbaImage>,
+}
+
+impl SoftwareBackend {
+    /// Create a backend showing `width` x `height` canvas units, one pixel
+    /// per unit unless [`with_scale`](Self::with_scale) says otherwise.
+    #[must_use]
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            svg: SvgBackend::new(width, height),
+            frame: None,
+        }
+    }
+
+    /// Set the background color as RGBA bytes.
+    #[must_use]
+    pub fn with_background(mut self, background: [u8; 4]) -> Self {
+        self.svg = self.svg.with_background(background);
+        self
+    }
+
+    /// Set the pixels per canvas unit, e.g. 0.25 for a thumbnail showing
+    /// the same part of the canvas.
+    #[must_use]
+    pub fn with_scale(mut self, scale: f32) -> Self {
+        self.svg = self.svg.with_scale(scale);
+        self
+    }
+
+    /// Hide [`sensitive`](canvas_core::Element::sensitive) elements the
+    /// given way, or draw them as they are with `None`.
+    #[must_use]
+    pub fn with_redaction(mut self, redaction: Option<Redaction>) -> Self {
+        self.svg = self.svg.with_redaction(redaction);
+        self
+    }
+
+    /// Draw charts in `style` by default, under each chart's own
+    /// `data.style`.
+    #[must_use]
+    pub fn with_chart_style(mut self, style: ChartStyle) -> Self {
+        self.svg = self.svg.with_chart_style(style);
+        self
+    }
+
+    /// Supply the encoded bytes of a remote image `src`, which the backend
+    /// cannot fetch itself. Remote images without bytes are left out.
+    pub fn provide_image(&mut self, src: impl Into<String>, bytes: Vec<u8>) {
+        self.svg.provide_image(src, bytes);
+    }
+
+    /// The image from the most recent [`render`](RenderBackend::render).
+    #[must_use]
+    pub fn frame(&self) -> Option<&RgbaImage> {
+        self.frame.as_ref()
+    }
+
+    /// Take the image from the most recent render.
+    pub fn take_frame(&mut self) -> Option<RgbaImage> {
+        self.frame.take()
+    }
+
+    /// Rasterize `scene` without keeping the image.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the scene's document cannot be rasterized.
+    pub fn render_to_rgba(&self, scene: &Scene) -> RenderResult<RgbaImage> {
+        SceneExporter::image_from_svg(&self.svg.render_to_string(scene))
+    }
+
+    /// Rasterize a preview of `scene` whose longer side is `max_dim` pixels.
+    ///
+    /// The preview shows the viewport, grown to take in any content outside
+    /// it, keeping its aspect ratio; the backend's own size and scale are
+    /// ignored.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `max_dim` is zero or larger than
+    /// [`MAX_SNAPSHOT_SIZE`](crate::MAX_SNAPSHOT_SIZE), or if the preview
+    /// cannot be rasterized.
+    pub fn render_thumbnail(&self, scene: &Scene, max_dim: u32) -> RenderResult<RgbaImage> {
+        let (view, width, height) = thumbnail_frame(scene, max_dim)?;
+        SceneExporter::image_from_svg(&self.svg.render_view_to_string(scene, &view, width, height))
+    }
+}
+
+/// The part of `scene` a thumbnail shows and its size in pixels, the longer
+/// side `max_dim`.
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_precision_loss,
+    clippy::cast_sign_loss
+)]
+fn thumbnail_frame(scene: &Scene, max_dim: u32) -> RenderResult<(SceneBounds, u32, u32)> {
+    snapshot::check_size(max_dim, max_dim)?;
+    let page = SceneBounds {
+        min_x: 0.0,
+        min_y: 0.0,
+        max_x: scene.viewport_width.max(1.0),
+        max_y: scene.viewport_height.max(1.0),
+    };
+    let view = scene
+        .content_bounds()
+        .map_or(page, |content| page.union(&content));
+    let scale = max_dim as f32 / view.width().max(view.height());
+    let side = |length: f32| ((length * scale).round() as u32).clamp(1, max_dim);
+    Ok((view, side(view.width()), side(view.height())))
+}
+
+impl RenderBackend for SoftwareBackend {
+    fn backend_type(&self) -> BackendType {
+        BackendType::Software
+    }
+
+    fn render(&mut self, scene: &Scene) -> RenderResult<()> {
+        self.svg.render(scene)?;
+        let document = self.svg.take_svg().unwrap_or_default();
+        self.frame = Some(SceneExporter::image_from_svg(&document)?);
+        Ok(())
+    }
+
+    fn resize(&mut self, width: u32, height: u32) -> RenderResult<()> {
+        self.frame = None;
+        self.svg.resize(width, height)
+    }
+
+    fn set_chart_style(&mut self, style: ChartStyle) {
+        self.svg.set_chart_style(style);
+    }
+
+    fn frame_stats(&self) -> FrameStats {
+        self.svg.frame_stats()
+    }
+
+    fn render_to_image(
+        &mut self,
+        scene: &Scene,
+        width: u32,
+        height: u32,
+    ) -> RenderResult<Option<RgbaImage>> {
+        self.svg.render_to_image(scene, width, height)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Renderer, RendererConfig};
+    use canvas_core::{Element, ElementKind, Fill, ShapeKind, Style, ThemeColor, Transform};
+
+    fn rect(x: f32, y: f32, color: &str) -> Element {
+        Element::new(ElementKind::Shape {
+            shape: ShapeKind::Rectangle,
+        })
+        .with_transform(Transform {
+            x,
+            y,
+            width: 100.0,
+            height: 80.0,
+            ..Transform::default()
+        })
+        .with_style(Style {
+            fill: Some(Fill::Solid {
+                color: ThemeColor::parse(color).unwrap(),
+            }),
+            ..Style::default()
+        })
+    }
+
+    fn red_rect() -> Element {
+        rect(10.0, 20.0, "#ff0000")
+    }
+
+    #[test]
+    fn test_render_rasterizes_into_an_rgba_frame() {
+        let mut scene = Scene::new(400.0, 300.0);
+        scene.add_element(red_rect());
+        let mut backend = SoftwareBackend::new(400, 300);
+        assert_eq!(backend.backend_type(), BackendType::Software);
+        assert!(backend.frame().is_none());
+
+        backend.render(&scene).unwrap();
+        let frame = backend.frame().unwrap();
+        assert_eq!((frame.width(), frame.height()), (400, 300));
+        assert_eq!(frame.pixel(60, 60), Some([255, 0, 0, 255]));
+        assert_eq!(frame.pixel(300, 200), Some([255, 255, 255, 255]));
+        assert_eq!(backend.frame_stats().elements_drawn, 1);
+        assert!(backend.take_frame().is_some());
+        assert!(backend.frame().is_none());
+
+        // A quarter-size thumbnail of the same view
+        let thumbnail = SoftwareBackend::new(400, 300)
+            .with_scale(0.25)
+            .with_background([0, 0, 0, 0])
+            .render_to_rgba(&scene)
+            .unwrap();
+        assert_eq!((thumbnail.width(), thumbnail.height()), (100, 75));
+        assert_eq!(thumbnail.pixel(15, 15), Some([255, 0, 0, 255]));
+        assert_eq!(thumbnail.pixel(90, 70).map(|p| p[3]), Some(0));
+    }
+
+    #[test]
+    fn test_renderer_uses_it_when_asked() {
+        let mut renderer = Renderer::new(RendererConfig {
+            preferred_backend: BackendType::Software,
+            ..RendererConfig::default()
+        })
+        .unwrap();
+        assert_eq!(renderer.active_backend(), BackendType::Software);
+
+        let mut scene = Scene::new(400.0, 300.0);
+        scene.add_element(red_rect());
+        renderer.render(&scene).unwrap();
+        let image = renderer.render_to_image(&scene, 200, 150).unwrap();
+        assert_eq!(image.pixel(30, 30), Some([255, 0, 0, 255]));
+    }
+
+    #[test]
+    fn test_thumbnails_keep_the_aspect_and_take_in_offscreen_content() {
+        let renderer = Renderer::new(RendererConfig {
+            preferred_backend: BackendType::Software,
+            ..RendererConfig::default()
+        })
+        .unwrap();
+        let mut scene = Scene::new(400.0, 300.0);
+
+        // An empty scene shows its viewport
+        let empty = renderer.render_thumbnail(&scene, 100, None).unwrap();
+        assert_eq!((empty.width(), empty.height()), (100, 75));
+
+        // Content past the right edge widens the view to 0..700 x 0..300
+        scene.add_element(red_rect());
+        scene.add_element(rect(600.0, 200.0, "#0000ff"));
+        let thumbnail = renderer.render_thumbnail(&scene, 140, None).unwrap();
+        assert_eq!((thumbnail.width(), thumbnail.height()), (140, 60));
+        assert_eq!(thumbnail.pixel(12, 12), Some([255, 0, 0, 255]));
+        assert_eq!(thumbnail.pixel(130, 50), Some([0, 0, 255, 255]));
+        assert_eq!(thumbnail.pixel(80, 30), Some([255, 255, 255, 255]));
+
+        assert!(renderer.render_thumbnail(&scene, 0, None).is_err());
+    }
+
+    #[test]
+    fn test_redacted_thumbnails_hide_sensitive_elements() {
+        let renderer = Renderer::new(RendererConfig {
+            preferred_backend: BackendType::Software,
+            ..RendererConfig::default()
+        })
+        .unwrap();
+        let mut scene = Scene::new(400.0, 300.0);
+        scene.add_element(red_rect().with_sensitive(true));
+
+        let plain = renderer.render_thumbnail(&scene, 200, None).unwrap();
+        assert_eq!(plain.pixel(30, 30), Some([255, 0, 0, 255]));
+        let redacted = renderer
+            .render_thumbnail(&scene, 200, Some(Redaction::Placeholder))
+            .unwrap();
+        assert_eq!(redacted.pixel(30, 30), Some([0x9e, 0x9e, 0x9e, 0xff]));
+    }
+}
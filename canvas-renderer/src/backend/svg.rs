@@ -0,0 +1,368 @@
+//! SVG backend for vector export.
+//!
+//! Serializes a scene to a standalone SVG document instead of drawing it:
+//! shapes and text stay vectors, images are embedded as data URIs so the
+//! document has no outside references, and charts are drawn as vector paths
+//! (via plotters with the `charts` feature).
+
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use base64::Engine;
+use canvas_core::{Element, ElementKind, Redaction, Scene, SceneBounds};
+
+use crate::export::{escape_xml, ExportConfig, SceneExporter};
+use crate::image::{ImageFormat, ImageSource};
+use crate::{BackendType, ChartStyle, FrameStats, RenderResult, RgbaImage};
+
+use super::RenderBackend;
+
+/// Renders scenes to SVG documents.
+pub struct SvgBackend {
+    width: u32,
+    height: u32,
+    background: [u8; 4],
+    scale: f32,
+    redaction: Option<Redaction>,
+    images: HashMap<String, Vec<u8>>,
+    chart_style: ChartStyle,
+    document: Option<String>,
+    last_frame: FrameStats,
+}
+
+impl SvgBackend {
+    /// Create a backend showing `width` x `height` canvas units, one pixel
+    /// per unit unless [`with_scale`](Self::with_scale) says otherwise.
+    #[must_use]
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width: width.max(1),
+            height: height.max(1),
+            background: [255, 255, 255, 255],
+            scale: 1.0,
+            redaction: None,
+            images: HashMap::new(),
+            chart_style: ChartStyle::default(),
+            document: None,
+            last_frame: FrameStats::default(),
+        }
+    }
+
+    /// Set the background color as RGBA bytes.
+    #[must_use]
+    pub fn with_background(mut self, background: [u8; 4]) -> Self {
+        self.background = background;
+        self
+    }
+
+    /// Set the pixels per canvas unit, e.g. 2.0 for a document twice the
+    /// size showing the same part of the canvas.
+    #[must_use]
+    pub fn with_scale(mut self, scale: f32) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    /// Hide [`sensitive`](Element::sensitive) elements the given way, or
+    /// draw them as they are with `None`.
+    #[must_use]
+    pub fn with_redaction(mut self, redaction: Option<Redaction>) -> Self {
+        self.redaction = redaction;
+        self
+    }
+
+    /// Draw charts in `style` by default, under each chart's own
+    /// `data.style`.
+    #[must_use]
+    pub fn with_chart_style(mut self, style: ChartStyle) -> Self {
+        self.chart_style = style;
+        self
+    }
+
+    /// Supply the encoded bytes of a remote image `src`, which the backend
+    /// cannot fetch itself. Remote images without bytes keep their URL.
+    pub fn provide_image(&mut self, src: impl Into<String>, bytes: Vec<u8>) {
+        self.images.insert(src.into(), bytes);
+    }
+
+    /// The document from the most recent [`render`](RenderBackend::render).
+    #[must_use]
+    pub fn svg(&self) -> Option<&str> {
+        self.document.as_deref()
+    }
+
+    /// Take the document from the most recent render.
+    pub fn take_svg(&mut self) -> Option<String> {
+        self.document.take()
+    }
+
+    /// Serialize `scene` to an SVG document without keeping it.
+    #[must_use]
+    pub fn render_to_string(&self, scene: &Scene) -> String {
+        self.exporter(self.width, self.height, self.scale)
+            .render_to_svg_with(scene, &|svg, element| self.write_element(svg, element))
+    }
+
+    /// Render `view` (in canvas coordinates) into a `width` x `height`
+    /// document, whatever the backend's own size and scale.
+    #[must_use]
+    pub fn render_view_to_string(
+        &self,
+        scene: &Scene,
+        view: &SceneBounds,
+        width: u32,
+        height: u32,
+    ) -> String {
+        self.exporter(width, height, 1.0).render_view_to_svg_with(
+            scene,
+            view,
+            (width, height),
+            &|svg, element| self.write_element(svg, element),
+        )
+    }
+
+    fn exporter(&self, width: u32, height: u32, scale: f32) -> SceneExporter {
+        SceneExporter::new(ExportConfig {
+            width: Some(width),
+            height: Some(height),
+            background: self.background,
+            scale,
+            redaction: self.redaction,
+            ..ExportConfig::default()
+        })
+    }
+
+    /// Draw the elements the plain exporter cannot draw faithfully.
+    fn write_element(&self, svg: &mut String, element: &Element) -> bool {
+        match &element.kind {
+            ElementKind::Image { src, .. } => {
+                let Some(href) = self.embedded_href(src) else {
+                    return false;
+                };
+                let tf = &element.transform;
+                let _ = write!(
+                    svg,
+                    "<image x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" preserveAspectRatio=\"none\" href=\"{}\"/>",
+                    tf.x,
+                    tf.y,
+                    tf.width,
+                    tf.height,
+                    escape_xml(&href),
+                );
+                true
+            }
+            #[cfg(feature = "charts")]
+            ElementKind::Chart {
+                chart_type, data, ..
+            } => write_chart(svg, element, chart_type, data, &self.chart_style),
+            _ => false,
+        }
+    }
+
+    /// A data URI for an image source, or `None` to leave the source as is.
+    fn embedded_href(&self, src: &str) -> Option<String> {
+        let bytes = match ImageSource::parse(src) {
+            ImageSource::DataUri(_) => return None,
+            ImageSource::Remote(_) => self.images.get(src)?.clone(),
+            source @ ImageSource::File(_) => match source.read() {
+                Ok(bytes) => bytes?,
+                Err(e) => {
+                    tracing::warn!("SVG export cannot embed image {src}: {e}");
+                    return None;
+                }
+            },
+        };
+        Some(format!(
+            "data:{};base64,{}",
+            ImageFormat::from_magic_bytes(&bytes).mime_type(),
+            base64::engine::general_purpose::STANDARD.encode(bytes)
+        ))
+    }
+}
+
+/// Draw a chart as plotters vector output, with its `data.style` laid over
+/// `style`, falling back to the exporter's built-in chart drawing if the
+/// chart cannot be parsed.
+#[cfg(feature = "charts")]
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn write_chart(
+    svg: &mut String,
+    element: &Element,
+    chart_type: &str,
+    data: &serde_json::Value,
+    style: &ChartStyle,
+) -> bool {
+    let tf = &element.transform;
+    let (width, height) = (
+        tf.width.round().max(1.0) as u32,
+        tf.height.round().max(1.0) as u32,
+    );
+    let chart = crate::chart::parse_chart_config_with_style(chart_type, data, width, height, style)
+        .and_then(|config| crate::chart::render_chart_to_svg(&config));
+    match chart {
+        Ok(chart) => {
+            let _ = write!(
+                svg,
+                "<g transform=\"translate({} {})\">{chart}</g>",
+                tf.x, tf.y
+            );
+            true
+        }
+        Err(e) => {
+            tracing::debug!("Vector chart unavailable, using the built-in drawing: {e}");
+            false
+        }
+    }
+}
+
+impl RenderBackend for SvgBackend {
+    fn backend_type(&self) -> BackendType {
+        BackendType::Svg
+    }
+
+    fn render(&mut self, scene: &Scene) -> RenderResult<()> {
+        let shown = scene.elements().filter(|e| scene.is_shown(e)).count();
+        self.last_frame = FrameStats::new(scene.element_count(), scene.element_count() - shown);
+        self.document = Some(self.render_to_string(scene));
+        Ok(())
+    }
+
+    fn resize(&mut self, width: u32, height: u32) -> RenderResult<()> {
+        self.width = width.max(1);
+        self.height = height.max(1);
+        tracing::debug!("SVG backend resized to {}x{}", self.width, self.height);
+        Ok(())
+    }
+
+    fn set_chart_style(&mut self, style: ChartStyle) {
+        self.chart_style = style;
+    }
+
+    fn frame_stats(&self) -> FrameStats {
+        self.last_frame
+    }
+
+    fn render_to_image(
+        &mut self,
+        scene: &Scene,
+        width: u32,
+        height: u32,
+    ) -> RenderResult<Option<RgbaImage>> {
+        let svg = self
+            .exporter(width, height, 1.0)
+            .render_to_svg_with(scene, &|svg, element| self.write_element(svg, element));
+        SceneExporter::image_from_svg(&svg).map(Some)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use canvas_core::Transform;
+
+    fn placed(kind: ElementKind) -> Element {
+        Element::new(kind).with_transform(Transform {
+            x: 10.0,
+            y: 20.0,
+            width: 100.0,
+            height: 80.0,
+            ..Transform::default()
+        })
+    }
+
+    fn png() -> Vec<u8> {
+        crate::snapshot::RgbaImage::new(1, 1, vec![255, 0, 0, 255])
+            .unwrap()
+            .encode_png()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_render_produces_document() {
+        let mut scene = Scene::new(400.0, 300.0);
+        scene.add_element(placed(ElementKind::Text {
+            content: "Tom & Jerry".to_string(),
+            font_size: 16.0,
+            color: canvas_core::Color::BLACK.into(),
+        }));
+        let mut backend = SvgBackend::new(400, 300);
+        assert_eq!(backend.backend_type(), BackendType::Svg);
+        assert!(backend.svg().is_none());
+
+        backend.render(&scene).unwrap();
+        let svg = backend.svg().unwrap();
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.contains("width=\"400\" height=\"300\""));
+        assert!(svg.contains("Tom &amp; Jerry"));
+        assert_eq!(backend.frame_stats().elements_drawn, 1);
+        assert!(backend.take_svg().is_some());
+        assert!(backend.svg().is_none());
+
+        let svg = SvgBackend::new(400, 300)
+            .with_scale(2.0)
+            .render_to_string(&scene);
+        assert!(svg.contains("width=\"800\" height=\"600\" viewBox=\"0 0 400 300\""));
+    }
+
+    #[test]
+    fn test_remote_images_are_embedded_when_provided() {
+        let mut scene = Scene::new(400.0, 300.0);
+        scene.add_element(placed(ElementKind::Image {
+            src: "https://example.com/a.png".to_string(),
+            format: canvas_core::ImageFormat::Png,
+            annotations: Vec::new(),
+            extracted_text: None,
+        }));
+        let mut backend = SvgBackend::new(400, 300);
+        assert!(backend
+            .render_to_string(&scene)
+            .contains("href=\"https://example.com/a.png\""));
+
+        backend.provide_image("https://example.com/a.png", png());
+        let svg = backend.render_to_string(&scene);
+        assert!(svg.contains("href=\"data:image/png;base64,"));
+        assert!(!svg.contains("example.com"));
+    }
+
+    #[test]
+    fn test_file_images_are_embedded() {
+        let path = std::env::temp_dir().join(format!("svg-backend-{}.png", std::process::id()));
+        std::fs::write(&path, png()).unwrap();
+        let mut scene = Scene::new(400.0, 300.0);
+        scene.add_element(placed(ElementKind::Image {
+            src: path.display().to_string(),
+            format: canvas_core::ImageFormat::Png,
+            annotations: Vec::new(),
+            extracted_text: None,
+        }));
+        let svg = SvgBackend::new(400, 300).render_to_string(&scene);
+        let _ = std::fs::remove_file(&path);
+        assert!(svg.contains("href=\"data:image/png;base64,"));
+    }
+
+    #[cfg(feature = "charts")]
+    #[test]
+    fn test_charts_are_vector_paths() {
+        let mut scene = Scene::new(400.0, 300.0);
+        scene.add_element(placed(ElementKind::Chart {
+            chart_type: "line".to_string(),
+            data: serde_json::json!({
+                "labels": ["a", "b", "c"],
+                "values": [1.0, 3.0, 2.0],
+            }),
+            binding: None,
+        }));
+        let svg = SvgBackend::new(400, 300).render_to_string(&scene);
+        assert!(svg.contains("<g transform=\"translate(10 20)\"><svg"));
+        assert!(svg.contains("<polyline") || svg.contains("<path"));
+    }
+
+    #[test]
+    fn test_render_to_image_rasterizes_document() {
+        let scene = Scene::new(400.0, 300.0);
+        let mut backend = SvgBackend::new(400, 300).with_background([0, 0, 255, 255]);
+        let image = backend.render_to_image(&scene, 40, 30).unwrap().unwrap();
+        assert_eq!((image.width(), image.height()), (40, 30));
+        assert_eq!(image.pixel(5, 5), Some([0, 0, 255, 255]));
+    }
+}
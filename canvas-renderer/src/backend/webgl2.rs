@@ -0,0 +1,1346 @@
+//! WebGL2 rendering backend for browsers without WebGPU.
+//!
+//! Draws the same styled quads as the wgpu backend (rounded corners,
+//! gradients, strokes and soft shadows) through GLSL ES 3.0 ports of its
+//! shaders, so older GPUs still get hardware acceleration. Text, and charts
+//! laid out by [`chart_mesh`](crate::chart_mesh), are rasterized with the
+//! browser's 2D canvas and uploaded as textures; images and video frames
+//! are uploaded straight from their DOM elements.
+//!
+//! Each frame is first planned as a list of draws in paint order
+//! ([`FramePlan`]), then replayed against the GL context:
+//!
+//! ```text
+//! Scene ──plan──▶ [shadow, quad, text, chart, image, …] ──draw──▶ WebGL2
+//! ```
+//!
+//! Translucent groups fade each descendant instead of compositing an
+//! offscreen layer, and code and callout tails are drawn as plain cards.
+
+use std::collections::{HashMap, HashSet};
+
+use canvas_core::{
+    CameraFrame, Color, Element, ElementId, ElementKind, MeasureContext, OrientedRect, Scene,
+    SceneBounds, Theme,
+};
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::{
+    CanvasRenderingContext2d, HtmlCanvasElement, HtmlImageElement, HtmlMediaElement,
+    HtmlVideoElement, WebGl2RenderingContext as Gl, WebGlProgram, WebGlShader, WebGlTexture,
+    WebGlUniformLocation, WebGlVertexArrayObject,
+};
+
+use super::quad::{self, QuadStyle};
+use super::RenderBackend;
+use crate::chart::parse_chart_config_with_style;
+use crate::chart_mesh::{ChartGeometry, ChartPath};
+use crate::{BackendType, ChartStyle, FrameStats, RenderError, RenderResult, VisibleSet};
+
+/// Unit quad (0,0 to 1,1) drawn as a triangle fan; positions double as UVs.
+const QUAD_VERTICES: [f32; 8] = [0.0, 0.0, 1.0, 0.0, 1.0, 1.0, 0.0, 1.0];
+
+/// Canvas size used by [`WebGl2Backend::new`], matching the 2D fallback.
+const DEFAULT_SIZE: (u32, u32) = (800, 600);
+
+/// Scene camera uniform (zoom, pan x, pan y) when the scene is not zoomed.
+const IDENTITY_CAMERA: [f32; 3] = [1.0, 0.0, 0.0];
+
+/// What a planned draw paints.
+#[derive(Debug, Clone, PartialEq)]
+enum DrawSource {
+    /// A styled quad in the draw's color.
+    Quad,
+    /// Text rasterized by the browser over a transparent background.
+    Text {
+        content: String,
+        font_size: f32,
+        color: Color,
+    },
+    /// A chart's shapes and labels, rasterized by the browser; the styled
+    /// quad stands in if its data is invalid.
+    Chart {
+        chart_type: String,
+        data: serde_json::Value,
+    },
+    /// An image; the styled quad stands in until it has loaded.
+    Image { src: String },
+    /// A video stream's latest frame; the styled quad stands in until one
+    /// arrives.
+    Video { stream_id: String },
+    /// The spotlight's dimming overlay, covering the whole canvas.
+    Dim,
+}
+
+/// One draw call of a planned frame, in canvas coordinates.
+#[derive(Debug, Clone, PartialEq)]
+struct Draw {
+    /// Element drawn, for texture lookups (`None` for the spotlight overlay).
+    element: Option<ElementId>,
+    /// x, y, width, height.
+    rect: [f32; 4],
+    /// Quad color, with opacity applied.
+    color: [f32; 4],
+    style: QuadStyle,
+    /// Combined element and inherited opacity, applied to textures.
+    opacity: f32,
+    /// Clip from ancestor containers, in canvas coordinates.
+    clip: Option<[f32; 4]>,
+    /// Drawn in viewport pixels, ignoring the scene camera.
+    screen: bool,
+    source: DrawSource,
+}
+
+/// A frame's draws in paint order, planned without touching the GL context.
+#[derive(Debug, Default)]
+struct FramePlan {
+    draws: Vec<Draw>,
+    stats: FrameStats,
+}
+
+impl FramePlan {
+    /// Plan a frame of `scene`, skipping leaf elements outside `cull`, and
+    /// any not in `visible` when the renderer culled the scene already.
+    ///
+    /// Screen-anchored elements are planned last, over the spotlight, and
+    /// are never culled.
+    fn new(scene: &Scene, cull: Option<&SceneBounds>, visible: Option<&VisibleSet>) -> Self {
+        let mut elements: Vec<_> = match visible {
+            Some(visible) => visible.elements(scene).cloned().collect(),
+            None => scene
+                .elements()
+                .filter(|e| scene.is_shown(e))
+                .cloned()
+                .collect(),
+        };
+        elements.sort_by_key(|e| e.transform.z_index);
+
+        let overlay_opacity = quad::build_opacity_map(&elements);
+        let group_fades = group_fades(&elements);
+        let inherited = |id: &ElementId| {
+            overlay_opacity.get(id).copied().unwrap_or(1.0)
+                * group_fades.get(id).copied().unwrap_or(1.0)
+        };
+        let clips = scene.clip_rects();
+        let theme = scene.theme();
+
+        let mut plan = Self::default();
+        let mut culled = visible.map_or(0, VisibleSet::culled);
+        for element in elements.iter().filter(|e| !e.screen_anchored) {
+            // Containers are always visited since children may extend beyond them
+            if let Some(bounds) = cull {
+                if element.kind.children().is_empty()
+                    && !OrientedRect::from_transform(&element.transform).intersects_bounds(bounds)
+                {
+                    culled += 1;
+                    continue;
+                }
+            }
+            let clip = clips.get(&element.id).copied();
+            plan.push_element(element, theme, inherited(&element.id), clip);
+        }
+
+        // Dim everything drawn so far, then redraw the spotlighted elements
+        // and their descendants on top
+        if let Some(spotlight) = scene.spotlight() {
+            plan.draws.push(Draw {
+                element: None,
+                rect: [0.0; 4],
+                color: [0.0, 0.0, 0.0, spotlight.dim_opacity],
+                style: QuadStyle::FLAT,
+                opacity: 1.0,
+                clip: None,
+                screen: true,
+                source: DrawSource::Dim,
+            });
+
+            let lookup: HashMap<_, _> = elements.iter().map(|e| (e.id, e)).collect();
+            let mut highlighted = HashSet::new();
+            let mut stack = spotlight.element_ids.clone();
+            while let Some(id) = stack.pop() {
+                if highlighted.insert(id) {
+                    if let Some(element) = lookup.get(&id) {
+                        stack.extend(element.kind.children().iter().copied());
+                    }
+                }
+            }
+            for element in elements
+                .iter()
+                .filter(|e| !e.screen_anchored && highlighted.contains(&e.id))
+            {
+                let clip = clips.get(&element.id).copied();
+                plan.push_element(element, theme, inherited(&element.id), clip);
+            }
+        }
+
+        for element in elements.iter().filter(|e| e.screen_anchored) {
+            let clip = clips.get(&element.id).copied();
+            plan.push_element(element, theme, inherited(&element.id), clip);
+        }
+
+        let total = visible.map_or(elements.len(), VisibleSet::total);
+        plan.stats = FrameStats::new(total, culled);
+        plan
+    }
+
+    /// Append the scene's branding over everything else, laid out for a
+    /// `width` x `height` viewport.
+    fn push_branding(&mut self, scene: &Scene, width: f32, height: f32) {
+        let Some(branding) = scene.branding() else {
+            return;
+        };
+        for element in branding.overlay(width, height) {
+            self.push_element(&element, scene.theme(), 1.0, None);
+        }
+    }
+
+    /// Append the draws for one element: its shadow, then its body.
+    fn push_element(
+        &mut self,
+        element: &Element,
+        theme: &Theme,
+        inherited_opacity: f32,
+        clip: Option<[f32; 4]>,
+    ) {
+        // Overlay containers are invisible; only their children render
+        if matches!(element.kind, ElementKind::OverlayLayer { .. }) {
+            return;
+        }
+
+        let opacity = inherited_opacity * element.effective_opacity();
+        let t = &element.transform;
+        let rect = [t.x, t.y, t.width, t.height];
+        let draw = |rect, color, style, source| Draw {
+            element: Some(element.id),
+            rect,
+            color,
+            style,
+            opacity,
+            clip,
+            screen: element.screen_anchored,
+            source,
+        };
+
+        if let Some((shadow_rect, shadow_color, shadow_style)) =
+            quad::shadow_quad(element, theme, rect, opacity)
+        {
+            self.draws.push(draw(
+                shadow_rect,
+                shadow_color,
+                shadow_style,
+                DrawSource::Quad,
+            ));
+        }
+
+        let mut color = quad::element_color(element, theme);
+        color[3] *= opacity;
+        let style = quad::element_quad_style(element, theme, opacity);
+
+        let source = match &element.kind {
+            ElementKind::Text {
+                content, font_size, ..
+            } => {
+                // Text only sits on a card when it is filled or selected
+                if element.selected || element.style.fill.is_some() {
+                    self.draws.push(draw(rect, color, style, DrawSource::Quad));
+                }
+                DrawSource::Text {
+                    content: content.clone(),
+                    font_size: *font_size,
+                    color: element
+                        .kind
+                        .resolved_color(theme)
+                        .unwrap_or(ElementKind::TEXT_COLOR),
+                }
+            }
+            ElementKind::Chart {
+                chart_type, data, ..
+            } => DrawSource::Chart {
+                chart_type: chart_type.clone(),
+                data: data.clone(),
+            },
+            ElementKind::Image { src, .. } => DrawSource::Image { src: src.clone() },
+            ElementKind::Video { stream_id, .. } => DrawSource::Video {
+                stream_id: stream_id.clone(),
+            },
+            _ => DrawSource::Quad,
+        };
+        self.draws.push(draw(rect, color, style, source));
+    }
+}
+
+/// Opacity each translucent group, and everything inside it, fades to.
+///
+/// Nested translucent groups multiply.
+fn group_fades(elements: &[Element]) -> HashMap<ElementId, f32> {
+    let lookup: HashMap<_, _> = elements.iter().map(|e| (e.id, e)).collect();
+    let mut fades = HashMap::new();
+    for group in elements {
+        let Some(opacity) = group.kind.group_opacity() else {
+            continue;
+        };
+        let mut seen = HashSet::new();
+        let mut stack = vec![group.id];
+        while let Some(id) = stack.pop() {
+            if seen.insert(id) {
+                *fades.entry(id).or_insert(1.0) *= opacity;
+                if let Some(element) = lookup.get(&id) {
+                    stack.extend(element.kind.children().iter().copied());
+                }
+            }
+        }
+    }
+    fades
+}
+
+/// Scissor box (x, y, width, height) in GL's bottom-left pixel origin for a
+/// canvas-space clip, after the scene camera and clamped to the canvas.
+#[allow(clippy::cast_possible_truncation, clippy::cast_precision_loss)] // Clip coordinates are clamped to the canvas size
+fn scissor_box(clip: [f32; 4], camera: [f32; 3], width: u32, height: u32) -> [i32; 4] {
+    let [zoom, pan_x, pan_y] = camera;
+    let (max_x, max_y) = (width as f32, height as f32);
+    let left = (clip[0] * zoom + pan_x).floor().clamp(0.0, max_x);
+    let top = (clip[1] * zoom + pan_y).floor().clamp(0.0, max_y);
+    let right = ((clip[0] + clip[2]) * zoom + pan_x)
+        .ceil()
+        .clamp(left, max_x);
+    let bottom = ((clip[1] + clip[3]) * zoom + pan_y)
+        .ceil()
+        .clamp(top, max_y);
+    [
+        left as i32,
+        (max_y - bottom) as i32,
+        (right - left) as i32,
+        (bottom - top) as i32,
+    ]
+}
+
+/// Describe a JavaScript exception for an error message.
+fn js_error(context: &str, err: &JsValue) -> String {
+    format!("{context}: {err:?}")
+}
+
+/// Uniform locations shared by both shader programs.
+struct QuadUniforms {
+    transform: Option<WebGlUniformLocation>,
+    canvas_size: Option<WebGlUniformLocation>,
+    camera: Option<WebGlUniformLocation>,
+}
+
+impl QuadUniforms {
+    fn new(gl: &Gl, program: &WebGlProgram) -> Self {
+        Self {
+            transform: gl.get_uniform_location(program, "u_transform"),
+            canvas_size: gl.get_uniform_location(program, "u_canvas_size"),
+            camera: gl.get_uniform_location(program, "u_camera"),
+        }
+    }
+}
+
+/// Program for styled solid and gradient quads.
+struct StyledProgram {
+    program: WebGlProgram,
+    quad: QuadUniforms,
+    color: Option<WebGlUniformLocation>,
+    fill_end: Option<WebGlUniformLocation>,
+    stroke_color: Option<WebGlUniformLocation>,
+    style_params: Option<WebGlUniformLocation>,
+    style_extra: Option<WebGlUniformLocation>,
+}
+
+impl StyledProgram {
+    fn new(gl: &Gl) -> RenderResult<Self> {
+        let program = link_program(gl, include_str!("../shaders/webgl2_quad.frag"))?;
+        Ok(Self {
+            quad: QuadUniforms::new(gl, &program),
+            color: gl.get_uniform_location(&program, "u_color"),
+            fill_end: gl.get_uniform_location(&program, "u_fill_end"),
+            stroke_color: gl.get_uniform_location(&program, "u_stroke_color"),
+            style_params: gl.get_uniform_location(&program, "u_style_params"),
+            style_extra: gl.get_uniform_location(&program, "u_style_extra"),
+            program,
+        })
+    }
+}
+
+/// Program for textured quads (images, video frames and text).
+struct TexturedProgram {
+    program: WebGlProgram,
+    quad: QuadUniforms,
+    tint: Option<WebGlUniformLocation>,
+    texture: Option<WebGlUniformLocation>,
+}
+
+impl TexturedProgram {
+    fn new(gl: &Gl) -> RenderResult<Self> {
+        let program = link_program(gl, include_str!("../shaders/webgl2_textured.frag"))?;
+        Ok(Self {
+            quad: QuadUniforms::new(gl, &program),
+            tint: gl.get_uniform_location(&program, "u_tint"),
+            texture: gl.get_uniform_location(&program, "u_texture"),
+            program,
+        })
+    }
+}
+
+/// Compile one shader stage.
+fn compile_shader(gl: &Gl, kind: u32, source: &str) -> RenderResult<WebGlShader> {
+    let shader = gl
+        .create_shader(kind)
+        .ok_or_else(|| RenderError::Shader("Unable to create shader".to_string()))?;
+    gl.shader_source(&shader, source);
+    gl.compile_shader(&shader);
+    if gl
+        .get_shader_parameter(&shader, Gl::COMPILE_STATUS)
+        .as_bool()
+        .unwrap_or(false)
+    {
+        Ok(shader)
+    } else {
+        let log = gl.get_shader_info_log(&shader).unwrap_or_default();
+        gl.delete_shader(Some(&shader));
+        Err(RenderError::Shader(log))
+    }
+}
+
+/// Link the shared quad vertex shader with `fragment_source`.
+fn link_program(gl: &Gl, fragment_source: &str) -> RenderResult<WebGlProgram> {
+    let vertex = compile_shader(
+        gl,
+        Gl::VERTEX_SHADER,
+        include_str!("../shaders/webgl2_quad.vert"),
+    )?;
+    let fragment = compile_shader(gl, Gl::FRAGMENT_SHADER, fragment_source)?;
+    let program = gl
+        .create_program()
+        .ok_or_else(|| RenderError::Shader("Unable to create program".to_string()))?;
+    gl.attach_shader(&program, &vertex);
+    gl.attach_shader(&program, &fragment);
+    gl.link_program(&program);
+    // Linked programs keep working once their shaders are flagged for deletion
+    gl.delete_shader(Some(&vertex));
+    gl.delete_shader(Some(&fragment));
+    if gl
+        .get_program_parameter(&program, Gl::LINK_STATUS)
+        .as_bool()
+        .unwrap_or(false)
+    {
+        Ok(program)
+    } else {
+        let log = gl.get_program_info_log(&program).unwrap_or_default();
+        gl.delete_program(Some(&program));
+        Err(RenderError::Shader(log))
+    }
+}
+
+/// Create a texture that samples linearly and clamps at its edges, which
+/// WebGL requires for sizes that are not powers of two.
+#[allow(clippy::cast_possible_wrap)] // GL enum values fit in i32
+fn create_texture(gl: &Gl) -> RenderResult<WebGlTexture> {
+    let texture = gl
+        .create_texture()
+        .ok_or_else(|| RenderError::Resource("Unable to create WebGL texture".to_string()))?;
+    gl.bind_texture(Gl::TEXTURE_2D, Some(&texture));
+    for (name, value) in [
+        (Gl::TEXTURE_MIN_FILTER, Gl::LINEAR),
+        (Gl::TEXTURE_MAG_FILTER, Gl::LINEAR),
+        (Gl::TEXTURE_WRAP_S, Gl::CLAMP_TO_EDGE),
+        (Gl::TEXTURE_WRAP_T, Gl::CLAMP_TO_EDGE),
+    ] {
+        gl.tex_parameteri(Gl::TEXTURE_2D, name, value as i32);
+    }
+    Ok(texture)
+}
+
+/// Texture drawn for an image or text element.
+struct ElementTexture {
+    /// What the texture shows (image source or text and its style); a
+    /// change re-creates the texture.
+    signature: String,
+    texture: WebGlTexture,
+    /// Image still loading, uploaded once it completes.
+    pending: Option<HtmlImageElement>,
+}
+
+/// Texture holding a video stream's latest frame.
+struct VideoTexture {
+    texture: WebGlTexture,
+    /// Element to copy a frame from on every render, if the stream plays in
+    /// the page.
+    element: Option<HtmlVideoElement>,
+    /// Whether a frame has been uploaded yet.
+    has_frame: bool,
+}
+
+/// WebGL2 renderer.
+pub struct WebGl2Backend {
+    gl: Gl,
+    canvas: HtmlCanvasElement,
+    styled: StyledProgram,
+    textured: TexturedProgram,
+    vertex_array: WebGlVertexArrayObject,
+    /// Scratch canvas text is rasterized on before upload.
+    text_canvas: HtmlCanvasElement,
+    text_context: CanvasRenderingContext2d,
+    /// Image and text textures by element ID.
+    textures: HashMap<ElementId, ElementTexture>,
+    /// Video textures by stream ID.
+    videos: HashMap<String, VideoTexture>,
+    width: u32,
+    height: u32,
+    background_color: [f32; 4],
+    /// Elements the renderer's culling pass found in view, for the next
+    /// frame only.
+    visible: Option<VisibleSet>,
+    /// Default look of charts, under each chart's own `data.style`.
+    chart_style: ChartStyle,
+    /// Element counts from the most recent scene render.
+    last_frame: FrameStats,
+}
+
+impl WebGl2Backend {
+    /// Create a WebGL2 backend drawing to a new, detached canvas.
+    ///
+    /// Attach [`canvas`](Self::canvas) to the page to show it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error outside a browser or if WebGL2 is unavailable.
+    pub fn new() -> RenderResult<Self> {
+        let canvas = Self::create_canvas()?;
+        canvas.set_width(DEFAULT_SIZE.0);
+        canvas.set_height(DEFAULT_SIZE.1);
+        Self::from_canvas(canvas)
+    }
+
+    /// Create a WebGL2 backend drawing to a browser canvas element.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the canvas has no WebGL2 context or the shaders
+    /// fail to compile.
+    pub fn from_canvas(canvas: HtmlCanvasElement) -> RenderResult<Self> {
+        let gl = canvas
+            .get_context("webgl2")
+            .map_err(|e| RenderError::GpuInit(js_error("WebGL2 context request failed", &e)))?
+            .ok_or_else(|| RenderError::GpuInit("WebGL2 is not supported".to_string()))?
+            .dyn_into::<Gl>()
+            .map_err(|_| RenderError::GpuInit("Canvas context is not WebGL2".to_string()))?;
+
+        let styled = StyledProgram::new(&gl)?;
+        let textured = TexturedProgram::new(&gl)?;
+
+        let vertex_array = gl
+            .create_vertex_array()
+            .ok_or_else(|| RenderError::GpuInit("Unable to create vertex array".to_string()))?;
+        let vertex_buffer = gl
+            .create_buffer()
+            .ok_or_else(|| RenderError::GpuInit("Unable to create vertex buffer".to_string()))?;
+        gl.bind_vertex_array(Some(&vertex_array));
+        gl.bind_buffer(Gl::ARRAY_BUFFER, Some(&vertex_buffer));
+        gl.buffer_data_with_u8_array(
+            Gl::ARRAY_BUFFER,
+            bytemuck::cast_slice(&QUAD_VERTICES),
+            Gl::STATIC_DRAW,
+        );
+        gl.enable_vertex_attrib_array(0);
+        gl.vertex_attrib_pointer_with_i32(0, 2, Gl::FLOAT, false, 0, 0);
+        gl.bind_vertex_array(None);
+
+        let text_canvas = Self::create_canvas()?;
+        let text_context = text_canvas
+            .get_context("2d")
+            .map_err(|e| RenderError::GpuInit(js_error("2D context request failed", &e)))?
+            .ok_or_else(|| RenderError::GpuInit("2D canvas is not supported".to_string()))?
+            .dyn_into::<CanvasRenderingContext2d>()
+            .map_err(|_| RenderError::GpuInit("Canvas context is not 2D".to_string()))?;
+
+        let (width, height) = (canvas.width(), canvas.height());
+        tracing::info!("WebGL2 backend initialized ({width}x{height})");
+
+        Ok(Self {
+            gl,
+            canvas,
+            styled,
+            textured,
+            vertex_array,
+            text_canvas,
+            text_context,
+            textures: HashMap::new(),
+            videos: HashMap::new(),
+            width,
+            height,
+            background_color: [1.0, 1.0, 1.0, 1.0],
+            visible: None,
+            chart_style: ChartStyle::default(),
+            last_frame: FrameStats::default(),
+        })
+    }
+
+    /// Create a detached canvas element.
+    fn create_canvas() -> RenderResult<HtmlCanvasElement> {
+        web_sys::window()
+            .and_then(|window| window.document())
+            .ok_or_else(|| RenderError::GpuInit("No document to create a canvas in".to_string()))?
+            .create_element("canvas")
+            .map_err(|e| RenderError::GpuInit(js_error("Unable to create a canvas", &e)))?
+            .dyn_into::<HtmlCanvasElement>()
+            .map_err(|_| RenderError::GpuInit("Created element is not a canvas".to_string()))
+    }
+
+    /// The canvas this backend draws to.
+    #[must_use]
+    pub fn canvas(&self) -> &HtmlCanvasElement {
+        &self.canvas
+    }
+
+    /// Set the background color.
+    #[allow(clippy::cast_possible_truncation)] // Color channels are 0..=1
+    pub fn set_background_color(&mut self, r: f64, g: f64, b: f64, a: f64) {
+        self.background_color = [r as f32, g as f32, b as f32, a as f32];
+    }
+
+    /// Play a video stream from a `<video>` element; its current frame is
+    /// uploaded on every render.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the texture cannot be created.
+    pub fn set_video_element(
+        &mut self,
+        stream_id: &str,
+        element: HtmlVideoElement,
+    ) -> RenderResult<()> {
+        self.video_texture(stream_id)?.element = Some(element);
+        tracing::debug!("Video stream {stream_id} attached to a video element");
+        Ok(())
+    }
+
+    /// Update a video frame for a specific stream from RGBA pixels.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the data does not match the dimensions or the
+    /// upload fails.
+    #[allow(clippy::cast_possible_wrap)] // GL enum values fit in i32
+    pub fn update_video_frame(
+        &mut self,
+        stream_id: &str,
+        width: u32,
+        height: u32,
+        rgba_data: &[u8],
+    ) -> RenderResult<()> {
+        let expected_size = (width as usize)
+            .checked_mul(height as usize)
+            .and_then(|pixels| pixels.checked_mul(4))
+            .ok_or_else(|| {
+                RenderError::Frame(format!(
+                    "Video frame dimensions {width}x{height} would overflow"
+                ))
+            })?;
+        if rgba_data.len() != expected_size {
+            return Err(RenderError::Frame(format!(
+                "Invalid video frame data size: expected {expected_size}, got {}",
+                rgba_data.len()
+            )));
+        }
+        let too_large =
+            |_| RenderError::Frame(format!("Video frame {width}x{height} is too large"));
+        let (gl_width, gl_height) = (
+            i32::try_from(width).map_err(too_large)?,
+            i32::try_from(height).map_err(too_large)?,
+        );
+
+        let gl = self.gl.clone();
+        let video = self.video_texture(stream_id)?;
+        gl.bind_texture(Gl::TEXTURE_2D, Some(&video.texture));
+        gl.tex_image_2d_with_i32_and_i32_and_i32_and_format_and_type_and_opt_u8_array(
+            Gl::TEXTURE_2D,
+            0,
+            Gl::RGBA as i32,
+            gl_width,
+            gl_height,
+            0,
+            Gl::RGBA,
+            Gl::UNSIGNED_BYTE,
+            Some(rgba_data),
+        )
+        .map_err(|e| RenderError::Frame(js_error("Video frame upload failed", &e)))?;
+        video.has_frame = true;
+
+        tracing::trace!("Updated video frame {width}x{height} for stream {stream_id}");
+        Ok(())
+    }
+
+    /// Remove a video stream texture.
+    ///
+    /// Call this when a video stream ends.
+    pub fn remove_video_stream(&mut self, stream_id: &str) {
+        if let Some(video) = self.videos.remove(stream_id) {
+            self.gl.delete_texture(Some(&video.texture));
+        }
+        tracing::debug!("Removed video stream texture: {stream_id}");
+    }
+
+    /// Clear all video stream textures.
+    pub fn clear_video_textures(&mut self) {
+        for (_, video) in self.videos.drain() {
+            self.gl.delete_texture(Some(&video.texture));
+        }
+        tracing::debug!("Cleared all video textures");
+    }
+
+    /// The texture for `stream_id`, created on first use.
+    fn video_texture(&mut self, stream_id: &str) -> RenderResult<&mut VideoTexture> {
+        if !self.videos.contains_key(stream_id) {
+            let texture = create_texture(&self.gl)?;
+            self.videos.insert(
+                stream_id.to_string(),
+                VideoTexture {
+                    texture,
+                    element: None,
+                    has_frame: false,
+                },
+            );
+        }
+        self.videos
+            .get_mut(stream_id)
+            .ok_or_else(|| RenderError::Resource(format!("Video stream {stream_id} missing")))
+    }
+
+    /// Copy the current frame of every attached `<video>` that has one.
+    #[allow(clippy::cast_possible_wrap)] // GL enum values fit in i32
+    fn upload_video_frames(&mut self) {
+        for (stream_id, video) in &mut self.videos {
+            let Some(element) = &video.element else {
+                continue;
+            };
+            if element.ready_state() < HtmlMediaElement::HAVE_CURRENT_DATA {
+                continue;
+            }
+            self.gl.bind_texture(Gl::TEXTURE_2D, Some(&video.texture));
+            match self
+                .gl
+                .tex_image_2d_with_u32_and_u32_and_html_video_element(
+                    Gl::TEXTURE_2D,
+                    0,
+                    Gl::RGBA as i32,
+                    Gl::RGBA,
+                    Gl::UNSIGNED_BYTE,
+                    element,
+                ) {
+                Ok(()) => video.has_frame = true,
+                Err(e) => tracing::warn!("{}", js_error(&format!("Video {stream_id}"), &e)),
+            }
+        }
+    }
+
+    /// Create, refresh or drop element textures to match the plan.
+    ///
+    /// Upload errors are logged rather than propagated, so one bad element
+    /// falls back to its placeholder quad instead of blocking the frame.
+    fn prepare_textures(&mut self, plan: &FramePlan) {
+        let mut used = HashSet::new();
+        for draw in &plan.draws {
+            let Some(id) = draw.element else {
+                continue;
+            };
+            let signature = match &draw.source {
+                DrawSource::Image { src } => src.clone(),
+                DrawSource::Text {
+                    content,
+                    font_size,
+                    color,
+                } => format!(
+                    "{content}\n{font_size}\n{}\n{}x{}",
+                    color.to_hex(),
+                    draw.rect[2],
+                    draw.rect[3]
+                ),
+                DrawSource::Chart { chart_type, data } => {
+                    format!("{chart_type}\n{data}\n{}x{}", draw.rect[2], draw.rect[3])
+                }
+                _ => continue,
+            };
+            used.insert(id);
+            if self
+                .textures
+                .get(&id)
+                .is_some_and(|cached| cached.signature == signature)
+            {
+                continue;
+            }
+            let created = match &draw.source {
+                DrawSource::Image { src } => self.load_image(src),
+                DrawSource::Text {
+                    content,
+                    font_size,
+                    color,
+                } => self.rasterize_text(draw.rect, content, *font_size, *color),
+                DrawSource::Chart { chart_type, data } => {
+                    self.rasterize_chart(draw.rect, chart_type, data)
+                }
+                _ => continue,
+            };
+            match created {
+                Ok((texture, pending)) => {
+                    let replaced = self.textures.insert(
+                        id,
+                        ElementTexture {
+                            signature,
+                            texture,
+                            pending,
+                        },
+                    );
+                    if let Some(old) = replaced {
+                        self.gl.delete_texture(Some(&old.texture));
+                    }
+                }
+                Err(e) => tracing::warn!("Failed to prepare texture for {id}: {e}"),
+            }
+        }
+
+        // Drop textures of elements that left the scene or changed kind
+        let gl = &self.gl;
+        self.textures.retain(|id, cached| {
+            let keep = used.contains(id);
+            if !keep {
+                gl.delete_texture(Some(&cached.texture));
+            }
+            keep
+        });
+
+        self.upload_loaded_images();
+    }
+
+    /// Start loading an image; it is uploaded once it completes.
+    fn load_image(&self, src: &str) -> RenderResult<(WebGlTexture, Option<HtmlImageElement>)> {
+        let image = HtmlImageElement::new()
+            .map_err(|e| RenderError::Resource(js_error("Unable to create an image", &e)))?;
+        if !src.starts_with("data:") {
+            // Cross-origin images must opt in to CORS to be uploaded
+            image.set_cross_origin(Some("anonymous"));
+        }
+        image.set_src(src);
+        Ok((create_texture(&self.gl)?, Some(image)))
+    }
+
+    /// Upload images that finished loading since the last frame.
+    #[allow(clippy::cast_possible_wrap)] // GL enum values fit in i32
+    fn upload_loaded_images(&mut self) {
+        for (id, cached) in &mut self.textures {
+            let Some(image) = &cached.pending else {
+                continue;
+            };
+            // Broken images report complete with no natural size; they keep
+            // their placeholder
+            if !image.complete() || image.natural_width() == 0 {
+                continue;
+            }
+            self.gl.bind_texture(Gl::TEXTURE_2D, Some(&cached.texture));
+            match self
+                .gl
+                .tex_image_2d_with_u32_and_u32_and_html_image_element(
+                    Gl::TEXTURE_2D,
+                    0,
+                    Gl::RGBA as i32,
+                    Gl::RGBA,
+                    Gl::UNSIGNED_BYTE,
+                    image,
+                ) {
+                Ok(()) => {
+                    tracing::debug!(
+                        "Uploaded image texture {}x{} for {id}",
+                        image.natural_width(),
+                        image.natural_height()
+                    );
+                    cached.pending = None;
+                }
+                Err(e) => tracing::warn!("{}", js_error(&format!("Image for {id}"), &e)),
+            }
+        }
+    }
+
+    /// Draw wrapped text onto the scratch canvas and upload it.
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss,
+        clippy::cast_precision_loss
+    )] // Element sizes are small positive numbers
+    fn rasterize_text(
+        &self,
+        rect: [f32; 4],
+        content: &str,
+        font_size: f32,
+        color: Color,
+    ) -> RenderResult<(WebGlTexture, Option<HtmlImageElement>)> {
+        // Resizing also clears the canvas and resets its drawing state
+        self.text_canvas.set_width(rect[2].ceil().max(1.0) as u32);
+        self.text_canvas.set_height(rect[3].ceil().max(1.0) as u32);
+
+        let ctx = &self.text_context;
+        ctx.set_font(&format!("{font_size}px sans-serif"));
+        ctx.set_fill_style_str(&color.to_hex());
+        ctx.set_text_baseline("top");
+        let measure = MeasureContext::default();
+        let line_height = f64::from(font_size * measure.line_height);
+        for (row, line) in measure.wrap(content, font_size, rect[2]).iter().enumerate() {
+            ctx.fill_text(line, 0.0, row as f64 * line_height)
+                .map_err(|e| RenderError::Resource(js_error("Unable to draw text", &e)))?;
+        }
+        Ok((self.upload_scratch("Text")?, None))
+    }
+
+    /// Draw a chart's shapes and labels onto the scratch canvas and upload
+    /// it.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)] // Element sizes are small positive numbers
+    fn rasterize_chart(
+        &self,
+        rect: [f32; 4],
+        chart_type: &str,
+        data: &serde_json::Value,
+    ) -> RenderResult<(WebGlTexture, Option<HtmlImageElement>)> {
+        let (width, height) = (rect[2].ceil().max(1.0), rect[3].ceil().max(1.0));
+        let config = parse_chart_config_with_style(
+            chart_type,
+            data,
+            width as u32,
+            height as u32,
+            &self.chart_style,
+        )?;
+        let geometry = ChartGeometry::new(&config, [0.0, 0.0, rect[2], rect[3]]);
+        self.text_canvas.set_width(width as u32);
+        self.text_canvas.set_height(height as u32);
+
+        let ctx = &self.text_context;
+        for path in &geometry.paths {
+            let (ChartPath::Fill { points, .. } | ChartPath::Stroke { points, .. }) = path;
+            ctx.begin_path();
+            for (i, [x, y]) in points.iter().enumerate() {
+                if i == 0 {
+                    ctx.move_to(f64::from(*x), f64::from(*y));
+                } else {
+                    ctx.line_to(f64::from(*x), f64::from(*y));
+                }
+            }
+            match path {
+                ChartPath::Fill { color, .. } => {
+                    ctx.set_fill_style_str(&color.to_hex());
+                    ctx.fill();
+                }
+                ChartPath::Stroke { width, color, .. } => {
+                    ctx.set_stroke_style_str(&color.to_hex());
+                    ctx.set_line_width(f64::from(*width));
+                    ctx.stroke();
+                }
+            }
+        }
+        ctx.set_text_baseline("top");
+        for label in &geometry.labels {
+            ctx.set_font(&format!("{}px sans-serif", label.font_size));
+            ctx.set_fill_style_str(&label.color.to_hex());
+            let [x, y, ..] = label.rect;
+            ctx.fill_text(&label.text, f64::from(x), f64::from(y))
+                .map_err(|e| RenderError::Resource(js_error("Unable to draw chart", &e)))?;
+        }
+        Ok((self.upload_scratch("Chart")?, None))
+    }
+
+    /// Upload the scratch canvas as a new texture.
+    #[allow(clippy::cast_possible_wrap)] // GL enum values fit in i32
+    fn upload_scratch(&self, what: &str) -> RenderResult<WebGlTexture> {
+        let texture = create_texture(&self.gl)?;
+        self.gl
+            .tex_image_2d_with_u32_and_u32_and_html_canvas_element(
+                Gl::TEXTURE_2D,
+                0,
+                Gl::RGBA as i32,
+                Gl::RGBA,
+                Gl::UNSIGNED_BYTE,
+                &self.text_canvas,
+            )
+            .map_err(|e| RenderError::Resource(js_error(&format!("{what} upload failed"), &e)))?;
+        Ok(texture)
+    }
+
+    /// Set the uniforms both programs share.
+    #[allow(clippy::cast_precision_loss)] // Canvas dimensions fit in f32 mantissa
+    fn set_quad_uniforms(&self, uniforms: &QuadUniforms, rect: [f32; 4], camera: [f32; 3]) {
+        self.gl
+            .uniform4fv_with_f32_array(uniforms.transform.as_ref(), &rect);
+        self.gl.uniform2f(
+            uniforms.canvas_size.as_ref(),
+            self.width as f32,
+            self.height as f32,
+        );
+        self.gl
+            .uniform3fv_with_f32_array(uniforms.camera.as_ref(), &camera);
+    }
+
+    /// Draw a styled quad.
+    fn draw_styled(&self, rect: [f32; 4], color: [f32; 4], style: &QuadStyle, camera: [f32; 3]) {
+        let program = &self.styled;
+        self.gl.use_program(Some(&program.program));
+        self.set_quad_uniforms(&program.quad, rect, camera);
+        self.gl
+            .uniform4fv_with_f32_array(program.color.as_ref(), &color);
+        self.gl
+            .uniform4fv_with_f32_array(program.fill_end.as_ref(), &style.fill_end);
+        self.gl
+            .uniform4fv_with_f32_array(program.stroke_color.as_ref(), &style.stroke_color);
+        self.gl
+            .uniform4fv_with_f32_array(program.style_params.as_ref(), &style.params);
+        self.gl
+            .uniform4fv_with_f32_array(program.style_extra.as_ref(), &style.extra);
+        self.gl.draw_arrays(Gl::TRIANGLE_FAN, 0, 4);
+    }
+
+    /// Draw a texture stretched over `rect`.
+    fn draw_textured(
+        &self,
+        rect: [f32; 4],
+        texture: &WebGlTexture,
+        opacity: f32,
+        camera: [f32; 3],
+    ) {
+        let program = &self.textured;
+        self.gl.use_program(Some(&program.program));
+        self.set_quad_uniforms(&program.quad, rect, camera);
+        self.gl
+            .uniform4f(program.tint.as_ref(), 1.0, 1.0, 1.0, opacity);
+        self.gl.active_texture(Gl::TEXTURE0);
+        self.gl.bind_texture(Gl::TEXTURE_2D, Some(texture));
+        self.gl.uniform1i(program.texture.as_ref(), 0);
+        self.gl.draw_arrays(Gl::TRIANGLE_FAN, 0, 4);
+    }
+
+    /// Replay one planned draw, returning whether it issued a draw call.
+    #[allow(clippy::cast_precision_loss)] // Canvas dimensions fit in f32 mantissa
+    fn execute(&self, draw: &Draw, camera: [f32; 3]) -> bool {
+        let camera = if draw.screen { IDENTITY_CAMERA } else { camera };
+        match draw.clip {
+            Some(clip) => {
+                let [x, y, width, height] = scissor_box(clip, camera, self.width, self.height);
+                self.gl.enable(Gl::SCISSOR_TEST);
+                self.gl.scissor(x, y, width, height);
+            }
+            None => self.gl.disable(Gl::SCISSOR_TEST),
+        }
+
+        let texture = match &draw.source {
+            DrawSource::Quad => None,
+            DrawSource::Dim => {
+                // The overlay covers the whole canvas regardless of camera
+                let rect = [0.0, 0.0, self.width as f32, self.height as f32];
+                self.draw_styled(rect, draw.color, &draw.style, IDENTITY_CAMERA);
+                return true;
+            }
+            DrawSource::Text { .. } | DrawSource::Chart { .. } | DrawSource::Image { .. } => draw
+                .element
+                .and_then(|id| self.textures.get(&id))
+                .filter(|cached| cached.pending.is_none())
+                .map(|cached| &cached.texture),
+            DrawSource::Video { stream_id } => self
+                .videos
+                .get(stream_id)
+                .filter(|video| video.has_frame)
+                .map(|video| &video.texture),
+        };
+        match (texture, &draw.source) {
+            (Some(texture), _) => self.draw_textured(draw.rect, texture, draw.opacity, camera),
+            // Text that failed to rasterize draws nothing
+            (None, DrawSource::Text { .. }) => return false,
+            (None, _) => self.draw_styled(draw.rect, draw.color, &draw.style, camera),
+        }
+        true
+    }
+}
+
+impl RenderBackend for WebGl2Backend {
+    fn backend_type(&self) -> BackendType {
+        BackendType::WebGl2
+    }
+
+    #[allow(clippy::cast_possible_wrap, clippy::cast_precision_loss)] // Canvas dimensions fit in i32 and f32
+    fn render(&mut self, scene: &Scene) -> RenderResult<()> {
+        if self.gl.is_context_lost() {
+            tracing::warn!("WebGL2 context lost, skipping frame");
+            return Ok(());
+        }
+
+        // Apply the scene's zoom/pan, if any, and cull offscreen leaves
+        let frame = CameraFrame::from_scene(scene);
+        let camera = [frame.zoom, frame.pan_x, frame.pan_y];
+        let visible = self.visible.take();
+        let mut plan = FramePlan::new(scene, Some(&scene.visible_bounds()), visible.as_ref());
+        plan.push_branding(scene, self.width as f32, self.height as f32);
+        self.prepare_textures(&plan);
+        self.upload_video_frames();
+
+        let gl = &self.gl;
+        gl.viewport(0, 0, self.width as i32, self.height as i32);
+        gl.disable(Gl::SCISSOR_TEST);
+        let [r, g, b, a] = self.background_color;
+        gl.clear_color(r, g, b, a);
+        gl.clear(Gl::COLOR_BUFFER_BIT);
+        gl.enable(Gl::BLEND);
+        gl.blend_func_separate(
+            Gl::SRC_ALPHA,
+            Gl::ONE_MINUS_SRC_ALPHA,
+            Gl::ONE,
+            Gl::ONE_MINUS_SRC_ALPHA,
+        );
+        gl.bind_vertex_array(Some(&self.vertex_array));
+
+        let mut draw_calls = 0;
+        for draw in &plan.draws {
+            draw_calls += usize::from(self.execute(draw, camera));
+        }
+
+        self.gl.disable(Gl::SCISSOR_TEST);
+        self.gl.bind_vertex_array(None);
+        // Every draw is one two-triangle fan
+        self.last_frame = FrameStats {
+            draw_calls,
+            triangles: draw_calls * 2,
+            ..plan.stats
+        };
+
+        tracing::trace!(
+            "Rendered {} draws for {} elements to {}x{} WebGL2 canvas",
+            plan.draws.len(),
+            scene.element_count(),
+            self.width,
+            self.height
+        );
+        Ok(())
+    }
+
+    fn resize(&mut self, width: u32, height: u32) -> RenderResult<()> {
+        if width == 0 || height == 0 {
+            return Ok(());
+        }
+        self.width = width;
+        self.height = height;
+        self.canvas.set_width(width);
+        self.canvas.set_height(height);
+        tracing::debug!("WebGL2 canvas resized to {width}x{height}");
+        Ok(())
+    }
+
+    fn set_visible_elements(&mut self, visible: VisibleSet) {
+        self.visible = Some(visible);
+    }
+
+    fn set_chart_style(&mut self, style: ChartStyle) {
+        self.chart_style = style;
+    }
+
+    fn frame_stats(&self) -> FrameStats {
+        self.last_frame
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use canvas_core::{Branding, Spotlight, ThemeColor, Transform};
+
+    fn element(kind: ElementKind, x: f32, z_index: i32) -> Element {
+        Element::new(kind).with_transform(Transform {
+            x,
+            y: 10.0,
+            width: 100.0,
+            height: 40.0,
+            rotation: 0.0,
+            z_index,
+            auto_size: false,
+        })
+    }
+
+    fn text(content: &str) -> ElementKind {
+        ElementKind::Text {
+            content: content.to_string(),
+            font_size: 16.0,
+            color: ThemeColor::parse("#ff0000").expect("color"),
+        }
+    }
+
+    #[test]
+    fn test_plan_orders_by_z_index_and_maps_sources() {
+        let mut scene = Scene::new(800.0, 600.0);
+        scene.add_element(element(
+            ElementKind::Video {
+                stream_id: "cam".to_string(),
+                is_live: true,
+                mirror: false,
+                crop: None,
+                media_config: None,
+            },
+            300.0,
+            2,
+        ));
+        scene.add_element(element(text("Hello"), 0.0, 0));
+        scene.add_element(element(
+            ElementKind::Chart {
+                chart_type: "pie".to_string(),
+                data: serde_json::json!({ "values": [1, 2] }),
+                binding: None,
+            },
+            450.0,
+            3,
+        ));
+        scene.add_element(element(
+            ElementKind::Image {
+                src: "https://example.com/a.png".to_string(),
+                format: canvas_core::ImageFormat::Png,
+                annotations: Vec::new(),
+                extracted_text: None,
+            },
+            150.0,
+            1,
+        ));
+
+        let plan = FramePlan::new(&scene, None, None);
+        let sources: Vec<_> = plan.draws.iter().map(|d| d.source.clone()).collect();
+        assert_eq!(
+            sources,
+            vec![
+                DrawSource::Text {
+                    content: "Hello".to_string(),
+                    font_size: 16.0,
+                    color: Color::rgb(0xff, 0, 0),
+                },
+                DrawSource::Image {
+                    src: "https://example.com/a.png".to_string()
+                },
+                DrawSource::Video {
+                    stream_id: "cam".to_string()
+                },
+                DrawSource::Chart {
+                    chart_type: "pie".to_string(),
+                    data: serde_json::json!({ "values": [1, 2] }),
+                },
+            ]
+        );
+        assert_eq!(plan.stats, FrameStats::new(4, 0));
+    }
+
+    #[test]
+    fn test_plan_culls_leaves_and_fades_translucent_groups() {
+        let mut scene = Scene::new(800.0, 600.0);
+        let shape = ElementKind::Shape {
+            shape: canvas_core::ShapeKind::Rectangle,
+        };
+        let child = element(shape.clone(), 0.0, 1);
+        let child_id = child.id;
+        scene.add_element(child);
+        scene.add_element(element(shape, 5_000.0, 1));
+        scene.add_element(element(
+            ElementKind::Group {
+                children: vec![child_id],
+                opacity: 0.5,
+                clip: false,
+            },
+            0.0,
+            0,
+        ));
+
+        let bounds = scene.visible_bounds();
+        let plan = FramePlan::new(&scene, Some(&bounds), None);
+        assert_eq!(plan.stats, FrameStats::new(3, 1));
+        // The renderer's culling pass leaves out the same element up front
+        let visible = VisibleSet::from_scene(&scene);
+        let culled = FramePlan::new(&scene, Some(&bounds), Some(&visible));
+        assert_eq!(culled.stats, plan.stats);
+        assert_eq!(culled.draws.len(), plan.draws.len());
+        let child_draw = plan
+            .draws
+            .iter()
+            .find(|d| d.element == Some(child_id))
+            .map(|d| d.opacity);
+        assert_eq!(child_draw, Some(0.5));
+    }
+
+    #[test]
+    fn test_plan_spotlight_redraws_over_dim_overlay() {
+        let mut scene = Scene::new(800.0, 600.0);
+        let focus = element(text("Focus"), 0.0, 0);
+        let focus_id = focus.id;
+        scene.add_element(focus);
+        scene.add_element(element(text("Other"), 200.0, 0));
+        scene
+            .set_spotlight(Spotlight::new([focus_id]))
+            .expect("spotlight");
+
+        let plan = FramePlan::new(&scene, None, None);
+        let after_dim: Vec<_> = plan
+            .draws
+            .iter()
+            .skip_while(|d| d.source != DrawSource::Dim)
+            .skip(1)
+            .map(|d| d.element)
+            .collect();
+        assert_eq!(after_dim, vec![Some(focus_id)]);
+    }
+
+    #[test]
+    fn test_plan_draws_screen_anchored_last_without_culling() {
+        let mut scene = Scene::new(800.0, 600.0);
+        let badge = element(text("LIVE"), 5000.0, 10).with_screen_anchored(true);
+        let badge_id = badge.id;
+        scene.add_element(badge);
+        let card = element(text("Card"), 0.0, 0);
+        let card_id = card.id;
+        scene.add_element(card);
+        scene
+            .set_spotlight(Spotlight::new([card_id]))
+            .expect("spotlight");
+
+        // Far off the canvas view, yet kept: its rect is in viewport pixels
+        let bounds = scene.visible_bounds();
+        let plan = FramePlan::new(&scene, Some(&bounds), None);
+        let last = plan.draws.last().expect("draws");
+        assert_eq!(last.element, Some(badge_id));
+        assert!(last.screen);
+        assert_eq!(plan.stats, FrameStats::new(2, 0));
+        assert!(plan
+            .draws
+            .iter()
+            .filter(|d| d.element == Some(card_id))
+            .all(|d| !d.screen));
+    }
+
+    #[test]
+    fn test_plan_draws_branding_over_screen_anchored() {
+        let mut scene = Scene::new(800.0, 600.0);
+        let badge = element(text("LIVE"), 0.0, 10).with_screen_anchored(true);
+        scene.add_element(badge);
+        let mut plan = FramePlan::new(&scene, None, None);
+        plan.push_branding(&scene, 800.0, 600.0);
+        assert_eq!(plan.draws.len(), 1);
+
+        scene
+            .set_branding(Branding::default().with_watermark("Draft"))
+            .expect("branding");
+        let mut plan = FramePlan::new(&scene, None, None);
+        plan.push_branding(&scene, 800.0, 600.0);
+        let last = plan.draws.last().expect("draws");
+        assert!(last.screen);
+        assert!(matches!(&last.source, DrawSource::Text { content, .. } if content == "Draft"));
+        assert!((last.opacity - Branding::DEFAULT_OPACITY).abs() < f32::EPSILON);
+        assert!(last.rect[0] + last.rect[2] <= 800.0);
+    }
+
+    #[test]
+    fn test_scissor_box_applies_camera_and_flips_y() {
+        assert_eq!(
+            scissor_box([10.0, 20.0, 100.0, 50.0], IDENTITY_CAMERA, 800, 600),
+            [10, 530, 100, 50]
+        );
+        // Zoomed 2x and panned, then clamped to the canvas
+        assert_eq!(
+            scissor_box([-10.0, 0.0, 100.0, 400.0], [2.0, 10.0, 0.0], 800, 600),
+            [0, 0, 190, 600]
+        );
+    }
+}
@@ -3,21 +3,43 @@
 //! This is the primary high-performance backend using the wgpu library.
 //! Supports WebGPU (native and web) with automatic fallbacks.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 
-use canvas_core::{Element, ElementId, ElementKind, Scene};
+use canvas_core::highlight::{
+    self, HighlightSpan, CODE_CHAR_WIDTH, CODE_FONT_SIZE, CODE_LINE_HEIGHT, CODE_PADDING,
+};
+use canvas_core::log_view::{self, LOG_FOREGROUND};
+use canvas_core::model3d::{self as pose3d, Light, Lighting, ModelCamera, MAX_MODEL_LIGHTS};
+use canvas_core::{
+    Branding, CameraFrame, Color, Element, ElementId, ElementKind, OrientedRect, Scene,
+    SceneBounds, Spotlight, Theme,
+};
 use wgpu::util::DeviceExt;
 
-use crate::chart::{parse_chart_config, render_chart_to_buffer};
-use crate::image::{create_placeholder, load_image_from_data_uri};
-use crate::quilt::QuiltView;
-use crate::spatial::Camera;
-use crate::{BackendType, RenderError, RenderResult};
+use crate::chart_mesh::{ChartGeometry, ChartVertex};
+use crate::color::{self, ColorSpace, SDR_WHITE_NITS};
+use crate::image::{load_image_from_bytes, ImageSource};
+use crate::lightfield::LensCalibration;
+use crate::model3d::{Material, Model, ModelLoader, ModelState};
+use crate::post::{PostEffect, PostEffects};
+use crate::quilt::{QuiltRenderTarget, QuiltView};
+use crate::shader;
+use crate::spatial::{Camera, CanvasDepth, HolographicConfig, Mat4};
+use crate::{
+    AntiAliasing, BackendType, ChartStyle, DamageRect, FrameStats, RenderError, RenderResult,
+    RgbaImage, VisibleSet,
+};
 
 #[cfg(target_arch = "wasm32")]
 use web_sys::HtmlCanvasElement;
 
+use super::batch::{BatchKind, FrameBatches, QuadInstance};
+use super::glyph_atlas::{GlyphAtlas, GlyphVertex, TextBlock, ATLAS_SIZE};
+use super::image_cache::{content_hash, ImageCache, Lookup, DEFAULT_BUDGET_BYTES};
+use super::lightfield_pass::LightfieldCompositor;
+use super::post_process::{PostOutput, PostProcessor};
+use super::quad::{self, QuadStyle};
 use super::RenderBackend;
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -46,6 +68,56 @@ impl Vertex {
     }
 }
 
+impl GlyphVertex {
+    const ATTRIBS: [wgpu::VertexAttribute; 3] = wgpu::vertex_attr_array![
+        0 => Float32x2,
+        1 => Float32x2,
+        2 => Float32x4,
+    ];
+
+    fn desc() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<GlyphVertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &Self::ATTRIBS,
+        }
+    }
+}
+
+impl ChartVertex {
+    const ATTRIBS: [wgpu::VertexAttribute; 2] = wgpu::vertex_attr_array![
+        0 => Float32x2,
+        1 => Float32x4,
+    ];
+
+    fn desc() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<ChartVertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &Self::ATTRIBS,
+        }
+    }
+}
+
+impl QuadInstance {
+    const ATTRIBS: [wgpu::VertexAttribute; 6] = wgpu::vertex_attr_array![
+        2 => Float32x4,
+        3 => Float32x4,
+        4 => Float32x4,
+        5 => Float32x4,
+        6 => Float32x4,
+        7 => Float32x4,
+    ];
+
+    fn desc() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<QuadInstance>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &Self::ATTRIBS,
+        }
+    }
+}
+
 /// Unit quad vertices (0,0 to 1,1).
 const QUAD_VERTICES: &[Vertex] = &[
     Vertex {
@@ -90,6 +162,341 @@ struct QuadUniforms {
     color: [f32; 4],
     /// View-projection matrix (column-major, 4x4)
     view_projection: [f32; 16],
+    /// Gradient end color: r, g, b, a
+    fill_end: [f32; 4],
+    /// Stroke color: r, g, b, a
+    stroke_color: [f32; 4],
+    /// Style: fill mode (0 = solid, 1 = linear, 2 = radial), gradient angle or radius,
+    /// corner radius, stroke width
+    style_params: [f32; 4],
+    /// Style: edge softness, radial center x, radial center y, reserved
+    style_extra: [f32; 4],
+}
+
+/// Uniform data shared by a batch of draws: the text shader and the
+/// instanced quad shaders.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct FrameUniforms {
+    /// Canvas dimensions: width, height, `use_camera` (1.0 = yes), reserved
+    canvas_size: [f32; 4],
+    /// View-projection matrix (column-major, 4x4)
+    view_projection: [f32; 16],
+}
+
+/// Vertex data for model meshes.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct MeshVertex {
+    position: [f32; 3],
+    normal: [f32; 3],
+}
+
+impl MeshVertex {
+    const ATTRIBS: [wgpu::VertexAttribute; 2] = wgpu::vertex_attr_array![
+        0 => Float32x3,
+        1 => Float32x3,
+    ];
+
+    fn desc() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<MeshVertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &Self::ATTRIBS,
+        }
+    }
+}
+
+/// A light as the mesh shader reads it.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default, bytemuck::Pod, bytemuck::Zeroable)]
+struct MeshLight {
+    /// Direction the light travels in (w = 0) or its position (w = 1)
+    position: [f32; 4],
+    /// Color multiplied by intensity
+    color: [f32; 4],
+}
+
+/// Uniform data for the mesh shader.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct MeshUniforms {
+    /// Projection * view * pose (column-major, 4x4)
+    clip_from_model: [f32; 16],
+    /// Pose (column-major, 4x4)
+    world_from_model: [f32; 16],
+    /// Pose rotation with inverted scale, for normals (column-major, 4x4)
+    normal_from_model: [f32; 16],
+    /// Linear RGBA base color
+    base_color: [f32; 4],
+    /// Linear RGB emissive color, reserved
+    emissive: [f32; 4],
+    /// Metallic, roughness, ambient light, light count
+    params: [f32; 4],
+    /// Camera position, reserved
+    eye: [f32; 4],
+    lights: [MeshLight; MAX_MODEL_LIGHTS],
+}
+
+/// Source of a 2D shader in `shaders/`, after the color helpers it calls.
+macro_rules! shader_2d {
+    ($file:literal) => {
+        concat!(
+            include_str!("../shaders/color.wgsl"),
+            include_str!(concat!("../shaders/", $file))
+        )
+    };
+}
+
+/// Uniform data for `Shader` elements, the `canvas` inputs of
+/// `element_shader.wgsl`.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct ShaderUniforms {
+    /// Element box in clip space: left, bottom, right, top
+    rect: [f32; 4],
+    /// Element size on screen, in pixels
+    resolution: [f32; 2],
+    /// Seconds since the renderer started
+    time: f32,
+    opacity: f32,
+    /// The element's uniforms, four to a vector
+    params: [[f32; 4]; 4],
+}
+
+/// Depth buffer format for model meshes.
+const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+
+/// One primitive of a model, uploaded to the GPU.
+struct GpuMesh {
+    vertices: wgpu::Buffer,
+    indices: wgpu::Buffer,
+    index_count: u32,
+    material: Material,
+}
+
+/// A model's meshes on the GPU, with the sphere they fit in.
+struct GpuModel {
+    meshes: Vec<GpuMesh>,
+    center: [f32; 3],
+    radius: f32,
+}
+
+/// Where and how to draw a model.
+struct ModelDraw<'a> {
+    /// Projection * view * pose.
+    clip_from_model: Mat4,
+    /// The model's pose.
+    pose: pose3d::Transform3D,
+    /// Camera position in model space.
+    eye: [f32; 3],
+    lighting: &'a Lighting,
+    /// Viewport in target pixels: x, y, width, height.
+    viewport: [f32; 4],
+    /// Scissor rect in target pixels.
+    scissor: (u32, u32, u32, u32),
+}
+
+/// Plot box in canvas coordinates and camera of a 3D chart.
+type ChartPlot = ([f32; 4], ModelCamera);
+
+/// A model drawn inside a box on the canvas.
+struct PlacedModel<'a> {
+    /// The meshes.
+    model: &'a GpuModel,
+    /// The box in normalized device coordinates: left, bottom, right, top.
+    ndc: [f32; 4],
+    /// The model's pose.
+    pose: pose3d::Transform3D,
+    /// Camera framing the model in the box.
+    camera: ModelCamera,
+    /// Lights shading the model.
+    lighting: &'a Lighting,
+}
+
+/// Glyph atlas for text and its copy on the GPU.
+struct TextAtlas {
+    glyphs: GlyphAtlas,
+    texture: CachedTexture,
+}
+
+/// Draws queued for batching, and the GPU resources that record them.
+///
+/// Quads, textured quads, glyphs, and chart fills are queued as elements are visited and
+/// recorded in one render pass per target when the target changes, before
+/// anything drawn outside the batches (models), and at the end of the frame.
+struct BatchState {
+    /// Pipeline for instanced colored quads.
+    quad_pipeline: wgpu::RenderPipeline,
+    /// Pipeline for instanced textured quads.
+    textured_pipeline: wgpu::RenderPipeline,
+    /// Pipeline for solid-color triangles.
+    fill_pipeline: wgpu::RenderPipeline,
+    /// Quad instances for the frame, replaced by a larger buffer when full.
+    instances: wgpu::Buffer,
+    /// Glyph vertices for the frame, replaced by a larger buffer when full.
+    glyphs: wgpu::Buffer,
+    /// Fill vertices for the frame, replaced by a larger buffer when full.
+    fills: wgpu::Buffer,
+    /// Bytes of `instances`, `glyphs` and `fills` written since the frame
+    /// began.
+    cursors: (u64, u64, u64),
+    /// Draws not yet recorded.
+    queued: FrameBatches<wgpu::TextureView>,
+    /// Target of the queued draws.
+    target: Option<wgpu::TextureView>,
+    /// Whether the target is cleared to the background before the draws.
+    clear: bool,
+    /// View-projection of the queued camera-mode draws.
+    view_projection: [f32; 16],
+    /// Render passes' draw calls recorded since the frame began.
+    draw_calls: usize,
+    /// Triangles those draw calls rasterized.
+    triangles: usize,
+}
+
+impl BatchState {
+    /// Initial size of the instance, glyph and fill buffers.
+    const INITIAL_BYTES: u64 = 64 * 1024;
+
+    fn new(
+        device: &wgpu::Device,
+        uniform_layout: &wgpu::BindGroupLayout,
+        textured_layout: &wgpu::BindGroupLayout,
+        format: wgpu::TextureFormat,
+        samples: u32,
+    ) -> Self {
+        Self {
+            quad_pipeline: WgpuBackend::create_instanced_pipeline(
+                device,
+                uniform_layout,
+                format,
+                samples,
+                "Instanced Quad",
+                shader_2d!("quad_instanced.wgsl"),
+            ),
+            textured_pipeline: WgpuBackend::create_instanced_pipeline(
+                device,
+                textured_layout,
+                format,
+                samples,
+                "Instanced Textured",
+                shader_2d!("textured.wgsl"),
+            ),
+            fill_pipeline: WgpuBackend::create_triangle_pipeline(
+                device,
+                uniform_layout,
+                format,
+                samples,
+                "Fill",
+                shader_2d!("fill.wgsl"),
+                ChartVertex::desc(),
+            ),
+            instances: Self::create_buffer(device, "Quad Instance Buffer", Self::INITIAL_BYTES),
+            glyphs: Self::create_buffer(device, "Glyph Vertex Buffer", Self::INITIAL_BYTES),
+            fills: Self::create_buffer(device, "Fill Vertex Buffer", Self::INITIAL_BYTES),
+            cursors: (0, 0, 0),
+            queued: FrameBatches::default(),
+            target: None,
+            clear: false,
+            view_projection: IDENTITY_MATRIX,
+            draw_calls: 0,
+            triangles: 0,
+        }
+    }
+
+    fn create_buffer(device: &wgpu::Device, label: &str, size: u64) -> wgpu::Buffer {
+        device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some(label),
+            size,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        })
+    }
+
+    /// Start a command encoder's worth of draws, reusing the buffers from
+    /// the top. Only safe once the previous encoder has been submitted.
+    fn begin_frame(&mut self) {
+        self.cursors = (0, 0, 0);
+        self.draw_calls = 0;
+        self.triangles = 0;
+    }
+
+    /// Upload the queued quad instances, glyph vertices and fill vertices,
+    /// returning the offsets each was written at.
+    fn stage_queued(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) -> (u64, u64, u64) {
+        let quads = Self::stage(
+            device,
+            queue,
+            &mut self.instances,
+            &mut self.cursors.0,
+            bytemuck::cast_slice(self.queued.quads()),
+        );
+        let glyphs = Self::stage(
+            device,
+            queue,
+            &mut self.glyphs,
+            &mut self.cursors.1,
+            bytemuck::cast_slice(self.queued.glyphs()),
+        );
+        let fills = Self::stage(
+            device,
+            queue,
+            &mut self.fills,
+            &mut self.cursors.2,
+            bytemuck::cast_slice(self.queued.fills()),
+        );
+        (quads, glyphs, fills)
+    }
+
+    /// Write `bytes` into `buffer` after `cursor`, swapping in a larger
+    /// buffer if they don't fit. Returns the offset written at.
+    ///
+    /// Passes already recorded keep the buffer they were recorded with, so
+    /// replacing it mid-frame is safe.
+    fn stage(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        buffer: &mut wgpu::Buffer,
+        cursor: &mut u64,
+        bytes: &[u8],
+    ) -> u64 {
+        let len = bytes.len() as u64;
+        if len == 0 {
+            return *cursor;
+        }
+        if *cursor + len > buffer.size() {
+            let size = (len * 2).next_power_of_two().max(buffer.size());
+            *buffer = Self::create_buffer(device, "Batch Buffer", size);
+            *cursor = 0;
+        }
+        let offset = *cursor;
+        queue.write_buffer(buffer, offset, bytes);
+        *cursor += len;
+        offset
+    }
+}
+
+/// Per-frame lookups shared by the scene element render helpers.
+struct SceneRenderContext<'a> {
+    /// Element lookup by ID.
+    lookup: HashMap<ElementId, &'a Element>,
+    /// Inherited opacity from parent `OverlayLayer`s.
+    opacity_map: &'a HashMap<ElementId, f32>,
+    /// Clip rects from ancestor containers with `clip` enabled.
+    clips: &'a HashMap<ElementId, [f32; 4]>,
+    /// Tail triangles of callouts that point somewhere.
+    callout_tails: HashMap<ElementId, [(f32, f32); 3]>,
+    /// Scene theme that element colors are resolved against.
+    theme: &'a Theme,
+    /// Laid-out glyphs of text elements and chart labels.
+    glyphs: HashMap<ElementId, Vec<GlyphVertex>>,
+    /// Tessellated shapes of chart elements.
+    charts: HashMap<ElementId, Vec<ChartVertex>>,
+    /// Plots of 3D charts, whose models are in `chart_models`.
+    chart_scenes: HashMap<ElementId, ChartPlot>,
+    /// Content hash of the loaded texture of each image element.
+    images: HashMap<ElementId, u64>,
 }
 
 /// Context for quilt rendering operations.
@@ -100,6 +507,11 @@ struct QuadUniforms {
 struct QuiltRenderContext {
     /// View-projection matrix for 3D transformation.
     view_projection: [f32; 16],
+    /// Clip space of canvas points `(x, y, depth)` placed in front of the
+    /// focal plane.
+    canvas_projection: [f32; 16],
+    /// Depths of canvas elements.
+    depth: CanvasDepth,
     /// Viewport X offset in the target texture.
     viewport_x: u32,
     /// Viewport Y offset in the target texture.
@@ -112,27 +524,37 @@ struct QuiltRenderContext {
     texture_width: u32,
     /// Total texture height (for shader uniform).
     texture_height: u32,
+    /// Camera position, for specular highlights on models.
+    eye: [f32; 3],
 }
 
 impl QuiltRenderContext {
-    /// Create a new quilt render context.
-    const fn new(
-        view_projection: [f32; 16],
-        viewport_x: u32,
-        viewport_y: u32,
-        viewport_width: u32,
-        viewport_height: u32,
+    /// Context for drawing `view` into a quilt texture of the given size,
+    /// with canvas content placed by `canvas_to_world`.
+    #[allow(clippy::cast_precision_loss)] // View dimensions fit in f32 mantissa
+    fn for_view(
+        view: &QuiltView,
         texture_width: u32,
         texture_height: u32,
+        canvas_to_world: &Mat4,
+        depth: CanvasDepth,
     ) -> Self {
+        let aspect = view.width as f32 / view.height.max(1) as f32;
+        let view_matrix = view.camera.view_matrix();
+        let proj_matrix = view.camera.projection_matrix(aspect);
+        let eye = view.camera.position;
+        let view_projection = proj_matrix.mul(&view_matrix);
         Self {
-            view_projection,
-            viewport_x,
-            viewport_y,
-            viewport_width,
-            viewport_height,
+            view_projection: view_projection.data,
+            canvas_projection: view_projection.mul(canvas_to_world).data,
+            depth,
+            viewport_x: view.x_offset,
+            viewport_y: view.y_offset,
+            viewport_width: view.width,
+            viewport_height: view.height,
             texture_width,
             texture_height,
+            eye: [eye.x, eye.y, eye.z],
         }
     }
 
@@ -142,10 +564,126 @@ impl QuiltRenderContext {
     }
 }
 
+/// Timestamps bracketing a frame's commands, read back without stalling.
+///
+/// Only one frame is timed at a time: frames submitted while the last
+/// timing is still being mapped go untimed, so the reported GPU time lags
+/// a frame or two behind.
+struct GpuTimer {
+    /// Start and end timestamps.
+    queries: wgpu::QuerySet,
+    /// Destination of the resolved timestamps.
+    resolve: wgpu::Buffer,
+    /// Mappable copy of `resolve`.
+    readback: wgpu::Buffer,
+    /// Nanoseconds per timestamp tick.
+    period: f32,
+    /// Result of mapping `readback`, while a timed frame is in flight.
+    pending: Option<std::sync::mpsc::Receiver<Result<(), wgpu::BufferAsyncError>>>,
+}
+
+impl GpuTimer {
+    /// Features timing a frame needs.
+    const FEATURES: wgpu::Features =
+        wgpu::Features::TIMESTAMP_QUERY.union(wgpu::Features::TIMESTAMP_QUERY_INSIDE_ENCODERS);
+
+    /// Bytes of the two resolved timestamps.
+    const BYTES: u64 = 2 * std::mem::size_of::<u64>() as u64;
+
+    /// A timer, if `device` was created with timestamp queries.
+    fn new(device: &wgpu::Device, queue: &wgpu::Queue) -> Option<Self> {
+        if !device.features().contains(Self::FEATURES) {
+            return None;
+        }
+        let buffer = |label, usage| {
+            device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some(label),
+                size: Self::BYTES,
+                usage,
+                mapped_at_creation: false,
+            })
+        };
+        Some(Self {
+            queries: device.create_query_set(&wgpu::QuerySetDescriptor {
+                label: Some("Frame Timestamps"),
+                ty: wgpu::QueryType::Timestamp,
+                count: 2,
+            }),
+            resolve: buffer(
+                "Timestamp Resolve Buffer",
+                wgpu::BufferUsages::QUERY_RESOLVE | wgpu::BufferUsages::COPY_SRC,
+            ),
+            readback: buffer(
+                "Timestamp Readback Buffer",
+                wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            ),
+            period: queue.get_timestamp_period(),
+            pending: None,
+        })
+    }
+
+    /// Write the start timestamp, unless the last timing is still in
+    /// flight. Returns whether this frame is timed.
+    fn begin(&self, encoder: &mut wgpu::CommandEncoder) -> bool {
+        if self.pending.is_some() {
+            return false;
+        }
+        encoder.write_timestamp(&self.queries, 0);
+        true
+    }
+
+    /// Write the end timestamp and copy both somewhere mappable.
+    fn end(&self, encoder: &mut wgpu::CommandEncoder) {
+        encoder.write_timestamp(&self.queries, 1);
+        encoder.resolve_query_set(&self.queries, 0..2, &self.resolve, 0);
+        encoder.copy_buffer_to_buffer(&self.resolve, 0, &self.readback, 0, Self::BYTES);
+    }
+
+    /// Start mapping the timestamps once the timed frame is submitted.
+    fn submitted(&mut self) {
+        let (tx, rx) = std::sync::mpsc::channel();
+        self.readback
+            .slice(..)
+            .map_async(wgpu::MapMode::Read, move |result| {
+                let _ = tx.send(result);
+            });
+        self.pending = Some(rx);
+    }
+
+    /// Milliseconds the timed frame took on the GPU, once its timestamps
+    /// have been mapped; `None` while they are in flight or if mapping
+    /// failed.
+    fn poll(&mut self, device: &wgpu::Device) -> Option<f64> {
+        let rx = self.pending.as_ref()?;
+        device.poll(wgpu::Maintain::Poll);
+        let result = match rx.try_recv() {
+            Ok(result) => result,
+            Err(std::sync::mpsc::TryRecvError::Empty) => return None,
+            Err(std::sync::mpsc::TryRecvError::Disconnected) => {
+                self.pending = None;
+                return None;
+            }
+        };
+        self.pending = None;
+        if let Err(e) = result {
+            tracing::debug!("GPU timestamp readback failed: {e}");
+            return None;
+        }
+        let slice = self.readback.slice(..);
+        let data = slice.get_mapped_range();
+        let ticks: [u64; 2] = bytemuck::pod_read_unaligned(&data);
+        drop(data);
+        self.readback.unmap();
+        #[allow(clippy::cast_precision_loss)] // Frame durations fit in f64 mantissa
+        let nanos = ticks[1].saturating_sub(ticks[0]) as f64 * f64::from(self.period);
+        Some(nanos / 1_000_000.0)
+    }
+}
+
 /// Cached texture with GPU resources.
 struct CachedTexture {
-    /// The underlying GPU texture (kept alive to prevent deallocation).
-    #[allow(dead_code)]
+    /// The underlying GPU texture (kept alive to prevent deallocation, and
+    /// measured for frame stats).
     texture: wgpu::Texture,
     /// Texture view used for binding to shaders.
     view: wgpu::TextureView,
@@ -216,19 +754,92 @@ impl Viewport {
     }
 }
 
+/// Formats for presenting frames in a color space.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ColorFormats {
+    /// What the surface is configured with.
+    surface: wgpu::TextureFormat,
+    /// What the pipelines draw into: the surface, an sRGB view of it, or,
+    /// for HDR10, a frame texture a last pass encodes onto it.
+    target: wgpu::TextureFormat,
+    /// The space these present.
+    space: ColorSpace,
+}
+
+impl ColorFormats {
+    /// Formats for `space` among those a surface `offers`, falling back
+    /// through [`ColorSpace::fallback`]. `None` if it offers none.
+    fn choose(offers: &[wgpu::TextureFormat], space: ColorSpace) -> Option<Self> {
+        use wgpu::TextureFormat::{Rgb10a2Unorm, Rgba16Float};
+
+        let mut next = Some(space);
+        while let Some(space) = next {
+            let formats = match space {
+                ColorSpace::Hdr10 => offers
+                    .contains(&Rgb10a2Unorm)
+                    .then_some((Rgb10a2Unorm, Rgba16Float)),
+                ColorSpace::ExtendedSrgb => offers
+                    .contains(&Rgba16Float)
+                    .then_some((Rgba16Float, Rgba16Float)),
+                // Surfaces offering only linear 8-bit formats are drawn
+                // through an sRGB view, so the GPU still encodes
+                ColorSpace::Srgb => offers
+                    .iter()
+                    .find(|f| f.is_srgb())
+                    .or_else(|| offers.first())
+                    .map(|&f| (f, f.add_srgb_suffix())),
+            };
+            if let Some((surface, target)) = formats {
+                return Some(Self {
+                    surface,
+                    target,
+                    space,
+                });
+            }
+            next = space.fallback();
+        }
+        None
+    }
+
+    /// Whether the target is a view of the surface's textures rather than
+    /// a texture of its own.
+    fn views_surface(self) -> bool {
+        self.surface.remove_srgb_suffix() == self.target.remove_srgb_suffix()
+    }
+
+    /// Other formats the surface's textures are viewed as.
+    fn view_formats(self) -> Vec<wgpu::TextureFormat> {
+        if self.views_surface() && self.surface != self.target {
+            vec![self.target]
+        } else {
+            Vec::new()
+        }
+    }
+}
+
 /// wgpu-based GPU renderer.
 pub struct WgpuBackend {
     device: Arc<wgpu::Device>,
     queue: Arc<wgpu::Queue>,
+    /// The adapter the device was created from.
+    adapter_info: wgpu::AdapterInfo,
     surface: Option<wgpu::Surface<'static>>,
     surface_config: Option<wgpu::SurfaceConfiguration>,
+    /// Formats the surface offers; empty without one.
+    surface_formats: Vec<wgpu::TextureFormat>,
+    /// Color space asked for.
+    requested_color_space: ColorSpace,
+    /// Color space frames are presented in: the one asked for, or the
+    /// nearest the surface offers.
+    presented_color_space: ColorSpace,
     /// Pipeline for solid color quads.
     quad_pipeline: wgpu::RenderPipeline,
-    /// Pipeline for textured quads.
-    textured_pipeline: wgpu::RenderPipeline,
+    /// Pipeline for text glyphs (shares the textured bind group layout).
+    text_pipeline: wgpu::RenderPipeline,
+    /// Pipeline for model meshes (shares the quad bind group layout).
+    mesh_pipeline: wgpu::RenderPipeline,
     vertex_buffer: wgpu::Buffer,
     index_buffer: wgpu::Buffer,
-    uniform_buffer: wgpu::Buffer,
     /// Bind group layout for solid color quads.
     uniform_bind_group_layout: wgpu::BindGroupLayout,
     /// Bind group layout for textured quads.
@@ -237,8 +848,34 @@ pub struct WgpuBackend {
     sampler: wgpu::Sampler,
     /// Cached textures by element ID.
     texture_cache: HashMap<String, CachedTexture>,
+    /// Decoded image textures by content hash.
+    images: ImageCache<CachedTexture>,
+    /// Encoded bytes the host supplied for remote image sources.
+    remote_images: HashMap<String, Vec<u8>>,
+    /// Remote image sources drawn without their bytes supplied yet.
+    pending_images: HashSet<String>,
     /// Cached video textures by stream ID.
     video_textures: HashMap<String, CachedTexture>,
+    /// Loads the glTF files of `Model3D` elements.
+    models: ModelLoader,
+    /// Loaded models on the GPU, by source.
+    gpu_models: HashMap<String, Arc<GpuModel>>,
+    /// Meshes of 3D charts on the GPU, with the model they were built from.
+    chart_models: HashMap<ElementId, (Model, Arc<GpuModel>)>,
+    /// Pipelines of `Shader` elements by source hash; `None` for sources
+    /// that did not compile, which draw as placeholders.
+    shader_pipelines: HashMap<u64, Option<wgpu::RenderPipeline>>,
+    /// `canvas.time` for `Shader` elements, in seconds.
+    shader_time: f32,
+    /// Depth buffer for model meshes, with the target size and sample count
+    /// it was made for.
+    depth_target: Option<(u32, u32, u32, wgpu::TextureView)>,
+    /// Glyph atlas for text; `None` until text is first drawn, or if no
+    /// font is available.
+    text_atlas: Option<TextAtlas>,
+    /// Whether the system font has been looked up, so a missing one is not
+    /// searched for every frame.
+    font_searched: bool,
     width: u32,
     height: u32,
     background_color: wgpu::Color,
@@ -248,6 +885,50 @@ pub struct WgpuBackend {
     current_viewport: Option<Viewport>,
     /// Active view-projection matrix for camera rendering (None = 2D mode).
     active_view_projection: Option<[f32; 16]>,
+    /// Active clip rect in canvas coordinates (x, y, width, height), from
+    /// ancestor containers with `clip` enabled. Only applied in 2D mode.
+    active_clip: Option<[f32; 4]>,
+    /// Scene zoom/pan applied in 2D mode (None = identity camera).
+    scene_camera: Option<CameraFrame>,
+    /// Visible canvas region when rendering a scene in 2D mode; leaf
+    /// elements outside it are skipped.
+    cull_bounds: Option<SceneBounds>,
+    /// Elements the renderer's culling pass found in view, for the next
+    /// frame only; `None` draws every shown element.
+    visible: Option<VisibleSet>,
+    /// Default look of charts, under each chart's own `data.style`.
+    chart_style: ChartStyle,
+    /// Damaged part of the target in pixels (x, y, width, height) while
+    /// repainting regions; narrows every scissor rect.
+    damage_scissor: Option<(u32, u32, u32, u32)>,
+    /// The last frame, kept for damage tracking and copied to the surface,
+    /// whose contents do not survive presenting. Created by the first
+    /// [`render_damage`](RenderBackend::render_damage).
+    retained: Option<wgpu::Texture>,
+    /// Queued quad and glyph draws.
+    batches: BatchState,
+    /// Element counts from the most recent scene render.
+    last_frame: FrameStats,
+    /// Texture format the pipelines render to (used for offscreen group layers).
+    target_format: wgpu::TextureFormat,
+    /// Anti-aliasing the pipelines are built for.
+    antialiasing: AntiAliasing,
+    /// Sample counts above 1 the device can multisample targets with.
+    msaa_sample_counts: Vec<u32>,
+    /// Multisampled companion of the surface or retained frame, kept across
+    /// frames so damaged regions repaint over the last one.
+    frame_msaa: Option<wgpu::Texture>,
+    /// Targets drawn through a multisampled companion while MSAA is on,
+    /// resolved into at the end of every pass: (target, companion).
+    msaa_views: Vec<(wgpu::TextureView, wgpu::TextureView)>,
+    /// Times frames on the GPU, if the device has timestamp queries.
+    gpu_timer: Option<GpuTimer>,
+    /// Effects over frames and overlay layers.
+    post_effects: PostEffects,
+    /// Post-processing pipelines, created the first time an effect runs.
+    post: Option<PostProcessor>,
+    /// Lightfield compositing, created the first time a quilt is composed.
+    lightfield: Option<LightfieldCompositor>,
 }
 
 impl WgpuBackend {
@@ -295,13 +976,14 @@ impl WgpuBackend {
             })
             .await
             .ok_or_else(|| RenderError::GpuInit("No suitable GPU adapter found".to_string()))?;
+        let adapter_info = adapter.get_info();
 
         // Create device from this specific adapter
         let (device, queue) = adapter
             .request_device(
                 &wgpu::DeviceDescriptor {
                     label: Some("Saorsa Canvas Device (WASM)"),
-                    required_features: wgpu::Features::empty(),
+                    required_features: Self::optional_features(&adapter),
                     required_limits: wgpu::Limits::downlevel_webgl2_defaults(),
                     memory_hints: wgpu::MemoryHints::default(),
                 },
@@ -315,18 +997,13 @@ impl WgpuBackend {
 
         // Configure surface with adapter capabilities
         let caps = surface.get_capabilities(&adapter);
-        let format =
-            caps.formats
-                .iter()
-                .find(|f| f.is_srgb())
-                .copied()
-                .unwrap_or(caps.formats.first().copied().ok_or_else(|| {
-                    RenderError::GpuInit("No surface formats available".to_string())
-                })?);
+        let formats = ColorFormats::choose(&caps.formats, ColorSpace::Srgb)
+            .ok_or_else(|| RenderError::GpuInit("No surface formats available".to_string()))?;
+        let format = formats.target;
 
         let config = wgpu::SurfaceConfiguration {
-            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
-            format,
+            usage: Self::surface_usage(&caps),
+            format: formats.surface,
             width,
             height,
             present_mode: wgpu::PresentMode::AutoVsync,
@@ -335,44 +1012,66 @@ impl WgpuBackend {
                 .first()
                 .copied()
                 .ok_or_else(|| RenderError::GpuInit("No alpha modes available".to_string()))?,
-            view_formats: vec![],
+            view_formats: formats.view_formats(),
             desired_maximum_frame_latency: 2,
         };
 
         surface.configure(&device, &config);
+        let msaa_sample_counts = Self::msaa_sample_counts(&adapter, &device, format);
 
         // Create buffers and pipeline
-        let (vertex_buffer, index_buffer, uniform_buffer) = Self::create_buffers(&device);
+        let (vertex_buffer, index_buffer) = Self::create_buffers(&device);
         let uniform_bind_group_layout = Self::create_bind_group_layout(&device);
         let quad_pipeline =
-            Self::create_quad_pipeline_with_format(&device, &uniform_bind_group_layout, format);
+            Self::create_quad_pipeline_with_format(&device, &uniform_bind_group_layout, format, 1);
 
-        // Textured pipeline setup
         let textured_bind_group_layout = Self::create_textured_bind_group_layout(&device);
-        let textured_pipeline = Self::create_textured_pipeline_with_format(
+        let text_pipeline =
+            Self::create_text_pipeline_with_format(&device, &textured_bind_group_layout, format, 1);
+        let mesh_pipeline =
+            Self::create_mesh_pipeline_with_format(&device, &uniform_bind_group_layout, format, 1);
+        let sampler = Self::create_sampler(&device);
+        let batches = BatchState::new(
             &device,
+            &uniform_bind_group_layout,
             &textured_bind_group_layout,
             format,
+            1,
         );
-        let sampler = Self::create_sampler(&device);
 
         tracing::info!("wgpu backend initialized with canvas: {}x{}", width, height);
 
+        let gpu_timer = GpuTimer::new(&device, &queue);
         Ok(Self {
             device,
             queue,
+            adapter_info,
             surface: Some(surface),
             surface_config: Some(config),
+            surface_formats: caps.formats.clone(),
+            requested_color_space: ColorSpace::Srgb,
+            presented_color_space: formats.space,
             quad_pipeline,
-            textured_pipeline,
+            text_pipeline,
+            mesh_pipeline,
             vertex_buffer,
             index_buffer,
-            uniform_buffer,
             uniform_bind_group_layout,
             textured_bind_group_layout,
             sampler,
             texture_cache: HashMap::new(),
+            images: ImageCache::new(DEFAULT_BUDGET_BYTES),
+            remote_images: HashMap::new(),
+            pending_images: HashSet::new(),
             video_textures: HashMap::new(),
+            models: ModelLoader::new(),
+            gpu_models: HashMap::new(),
+            chart_models: HashMap::new(),
+            shader_pipelines: HashMap::new(),
+            shader_time: 0.0,
+            depth_target: None,
+            text_atlas: None,
+            font_searched: false,
             width,
             height,
             background_color: wgpu::Color {
@@ -384,6 +1083,24 @@ impl WgpuBackend {
             scale_factor: 1.0,
             current_viewport: None,
             active_view_projection: None,
+            active_clip: None,
+            scene_camera: None,
+            cull_bounds: None,
+            visible: None,
+            chart_style: ChartStyle::default(),
+            damage_scissor: None,
+            retained: None,
+            batches,
+            last_frame: FrameStats::default(),
+            target_format: format,
+            antialiasing: AntiAliasing::Off,
+            msaa_sample_counts,
+            frame_msaa: None,
+            msaa_views: Vec::new(),
+            gpu_timer,
+            post_effects: PostEffects::default(),
+            post: None,
+            lightfield: None,
         })
     }
 
@@ -405,40 +1122,70 @@ impl WgpuBackend {
     ///
     /// Returns an error if GPU initialization fails.
     pub async fn new_async() -> RenderResult<Self> {
-        let (device, queue) = Self::init_device_and_queue(None).await?;
+        let (device, queue, adapter_info, msaa_sample_counts) =
+            Self::init_device_and_queue(None).await?;
         let device = Arc::new(device);
         let queue = Arc::new(queue);
 
-        let (vertex_buffer, index_buffer, uniform_buffer) = Self::create_buffers(&device);
+        let (vertex_buffer, index_buffer) = Self::create_buffers(&device);
         let uniform_bind_group_layout = Self::create_bind_group_layout(&device);
         let quad_pipeline = Self::create_quad_pipeline(&device, &uniform_bind_group_layout);
 
-        // Textured pipeline setup
         let textured_bind_group_layout = Self::create_textured_bind_group_layout(&device);
-        let textured_pipeline = Self::create_textured_pipeline_with_format(
+        let text_pipeline = Self::create_text_pipeline_with_format(
             &device,
             &textured_bind_group_layout,
             wgpu::TextureFormat::Bgra8UnormSrgb,
+            1,
+        );
+        let mesh_pipeline = Self::create_mesh_pipeline_with_format(
+            &device,
+            &uniform_bind_group_layout,
+            wgpu::TextureFormat::Bgra8UnormSrgb,
+            1,
         );
         let sampler = Self::create_sampler(&device);
+        let batches = BatchState::new(
+            &device,
+            &uniform_bind_group_layout,
+            &textured_bind_group_layout,
+            wgpu::TextureFormat::Bgra8UnormSrgb,
+            1,
+        );
 
         tracing::info!("wgpu backend initialized successfully");
 
+        let gpu_timer = GpuTimer::new(&device, &queue);
         Ok(Self {
             device,
             queue,
+            adapter_info,
             surface: None,
             surface_config: None,
+            surface_formats: Vec::new(),
+            requested_color_space: ColorSpace::Srgb,
+            presented_color_space: ColorSpace::Srgb,
             quad_pipeline,
-            textured_pipeline,
+            text_pipeline,
+            mesh_pipeline,
             vertex_buffer,
             index_buffer,
-            uniform_buffer,
             uniform_bind_group_layout,
             textured_bind_group_layout,
             sampler,
             texture_cache: HashMap::new(),
+            images: ImageCache::new(DEFAULT_BUDGET_BYTES),
+            remote_images: HashMap::new(),
+            pending_images: HashSet::new(),
             video_textures: HashMap::new(),
+            models: ModelLoader::new(),
+            gpu_models: HashMap::new(),
+            chart_models: HashMap::new(),
+            shader_pipelines: HashMap::new(),
+            shader_time: 0.0,
+            depth_target: None,
+            text_atlas: None,
+            font_searched: false,
             width: 800,
             height: 600,
             background_color: wgpu::Color {
@@ -450,6 +1197,24 @@ impl WgpuBackend {
             scale_factor: 1.0,
             current_viewport: None,
             active_view_projection: None,
+            active_clip: None,
+            scene_camera: None,
+            cull_bounds: None,
+            visible: None,
+            chart_style: ChartStyle::default(),
+            damage_scissor: None,
+            retained: None,
+            batches,
+            last_frame: FrameStats::default(),
+            target_format: wgpu::TextureFormat::Bgra8UnormSrgb,
+            antialiasing: AntiAliasing::Off,
+            msaa_sample_counts,
+            frame_msaa: None,
+            msaa_views: Vec::new(),
+            gpu_timer,
+            post_effects: PostEffects::default(),
+            post: None,
+            lightfield: None,
         })
     }
 
@@ -500,14 +1265,15 @@ impl WgpuBackend {
             .await
             .ok_or_else(|| RenderError::GpuInit("No suitable GPU adapter found".to_string()))?;
 
-        tracing::info!("Using GPU adapter: {:?}", adapter.get_info());
+        let adapter_info = adapter.get_info();
+        tracing::info!("Using GPU adapter: {adapter_info:?}");
 
         // Create device from this adapter
         let (device, queue) = adapter
             .request_device(
                 &wgpu::DeviceDescriptor {
                     label: Some("Saorsa Canvas Device"),
-                    required_features: wgpu::Features::empty(),
+                    required_features: Self::optional_features(&adapter),
                     required_limits: wgpu::Limits::downlevel_webgl2_defaults(),
                     memory_hints: wgpu::MemoryHints::default(),
                 },
@@ -521,13 +1287,9 @@ impl WgpuBackend {
 
         // Configure surface
         let caps = surface.get_capabilities(&adapter);
-        let format = caps
-            .formats
-            .iter()
-            .find(|f| f.is_srgb())
-            .copied()
-            .or_else(|| caps.formats.first().copied())
+        let formats = ColorFormats::choose(&caps.formats, ColorSpace::Srgb)
             .ok_or_else(|| RenderError::GpuInit("No surface formats available".to_string()))?;
+        let format = formats.target;
 
         let alpha_mode = caps
             .alpha_modes
@@ -536,32 +1298,38 @@ impl WgpuBackend {
             .ok_or_else(|| RenderError::GpuInit("No alpha modes available".to_string()))?;
 
         let config = wgpu::SurfaceConfiguration {
-            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
-            format,
+            usage: Self::surface_usage(&caps),
+            format: formats.surface,
             width,
             height,
             present_mode: wgpu::PresentMode::AutoVsync,
             alpha_mode,
-            view_formats: vec![],
+            view_formats: formats.view_formats(),
             desired_maximum_frame_latency: 2,
         };
 
         surface.configure(&device, &config);
+        let msaa_sample_counts = Self::msaa_sample_counts(&adapter, &device, format);
 
         // Create buffers and pipeline
-        let (vertex_buffer, index_buffer, uniform_buffer) = Self::create_buffers(&device);
+        let (vertex_buffer, index_buffer) = Self::create_buffers(&device);
         let uniform_bind_group_layout = Self::create_bind_group_layout(&device);
         let quad_pipeline =
-            Self::create_quad_pipeline_with_format(&device, &uniform_bind_group_layout, format);
+            Self::create_quad_pipeline_with_format(&device, &uniform_bind_group_layout, format, 1);
 
-        // Textured pipeline setup
         let textured_bind_group_layout = Self::create_textured_bind_group_layout(&device);
-        let textured_pipeline = Self::create_textured_pipeline_with_format(
+        let text_pipeline =
+            Self::create_text_pipeline_with_format(&device, &textured_bind_group_layout, format, 1);
+        let mesh_pipeline =
+            Self::create_mesh_pipeline_with_format(&device, &uniform_bind_group_layout, format, 1);
+        let sampler = Self::create_sampler(&device);
+        let batches = BatchState::new(
             &device,
+            &uniform_bind_group_layout,
             &textured_bind_group_layout,
             format,
+            1,
         );
-        let sampler = Self::create_sampler(&device);
 
         tracing::info!(
             "wgpu backend initialized with window: {}x{} (scale: {})",
@@ -570,21 +1338,37 @@ impl WgpuBackend {
             scale_factor
         );
 
+        let gpu_timer = GpuTimer::new(&device, &queue);
         Ok(Self {
             device,
             queue,
+            adapter_info,
             surface: Some(surface),
             surface_config: Some(config),
+            surface_formats: caps.formats.clone(),
+            requested_color_space: ColorSpace::Srgb,
+            presented_color_space: formats.space,
             quad_pipeline,
-            textured_pipeline,
+            text_pipeline,
+            mesh_pipeline,
             vertex_buffer,
             index_buffer,
-            uniform_buffer,
             uniform_bind_group_layout,
             textured_bind_group_layout,
             sampler,
             texture_cache: HashMap::new(),
+            images: ImageCache::new(DEFAULT_BUDGET_BYTES),
+            remote_images: HashMap::new(),
+            pending_images: HashSet::new(),
             video_textures: HashMap::new(),
+            models: ModelLoader::new(),
+            gpu_models: HashMap::new(),
+            chart_models: HashMap::new(),
+            shader_pipelines: HashMap::new(),
+            shader_time: 0.0,
+            depth_target: None,
+            text_atlas: None,
+            font_searched: false,
             width,
             height,
             background_color: wgpu::Color {
@@ -596,13 +1380,31 @@ impl WgpuBackend {
             scale_factor,
             current_viewport: None,
             active_view_projection: None,
+            active_clip: None,
+            scene_camera: None,
+            cull_bounds: None,
+            visible: None,
+            chart_style: ChartStyle::default(),
+            damage_scissor: None,
+            retained: None,
+            batches,
+            last_frame: FrameStats::default(),
+            target_format: format,
+            antialiasing: AntiAliasing::Off,
+            msaa_sample_counts,
+            frame_msaa: None,
+            msaa_views: Vec::new(),
+            gpu_timer,
+            post_effects: PostEffects::default(),
+            post: None,
+            lightfield: None,
         })
     }
 
     /// Initialize the GPU device and queue.
     async fn init_device_and_queue(
         _surface: Option<&wgpu::Surface<'_>>,
-    ) -> RenderResult<(wgpu::Device, wgpu::Queue)> {
+    ) -> RenderResult<(wgpu::Device, wgpu::Queue, wgpu::AdapterInfo, Vec<u32>)> {
         let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor {
             backends: wgpu::Backends::all(),
             ..Default::default()
@@ -617,24 +1419,58 @@ impl WgpuBackend {
             .await
             .ok_or_else(|| RenderError::GpuInit("No suitable GPU adapter found".to_string()))?;
 
-        tracing::info!("Using GPU adapter: {:?}", adapter.get_info());
+        let adapter_info = adapter.get_info();
+        tracing::info!("Using GPU adapter: {adapter_info:?}");
 
-        adapter
+        let (device, queue) = adapter
             .request_device(
                 &wgpu::DeviceDescriptor {
                     label: Some("Saorsa Canvas Device"),
-                    required_features: wgpu::Features::empty(),
+                    required_features: Self::optional_features(&adapter),
                     required_limits: wgpu::Limits::downlevel_webgl2_defaults(),
                     memory_hints: wgpu::MemoryHints::default(),
                 },
                 None,
             )
             .await
-            .map_err(|e| RenderError::GpuInit(e.to_string()))
+            .map_err(|e| RenderError::GpuInit(e.to_string()))?;
+        let msaa_sample_counts =
+            Self::msaa_sample_counts(&adapter, &device, wgpu::TextureFormat::Bgra8UnormSrgb);
+        Ok((device, queue, adapter_info, msaa_sample_counts))
+    }
+
+    /// Device features to ask for when the adapter has them: per-adapter
+    /// format features let MSAA use 2 and 8 samples, not just 4, and
+    /// timestamp queries time frames on the GPU.
+    fn optional_features(adapter: &wgpu::Adapter) -> wgpu::Features {
+        adapter.features()
+            & (wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES | GpuTimer::FEATURES)
+    }
+
+    /// Sample counts above 1 that `format` targets and their depth buffers
+    /// can be multisampled with on `device`.
+    fn msaa_sample_counts(
+        adapter: &wgpu::Adapter,
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+    ) -> Vec<u32> {
+        if !device
+            .features()
+            .contains(wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES)
+        {
+            // WebGPU guarantees 4x for every renderable format
+            return vec![4];
+        }
+        let color = adapter.get_texture_format_features(format).flags;
+        let depth = adapter.get_texture_format_features(DEPTH_FORMAT).flags;
+        [2, 4, 8]
+            .into_iter()
+            .filter(|&n| color.sample_count_supported(n) && depth.sample_count_supported(n))
+            .collect()
     }
 
-    /// Create vertex, index, and uniform buffers.
-    fn create_buffers(device: &wgpu::Device) -> (wgpu::Buffer, wgpu::Buffer, wgpu::Buffer) {
+    /// Create the quad's vertex and index buffers.
+    fn create_buffers(device: &wgpu::Device) -> (wgpu::Buffer, wgpu::Buffer) {
         let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("Quad Vertex Buffer"),
             contents: bytemuck::cast_slice(QUAD_VERTICES),
@@ -647,14 +1483,7 @@ impl WgpuBackend {
             usage: wgpu::BufferUsages::INDEX,
         });
 
-        let uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
-            label: Some("Quad Uniform Buffer"),
-            size: std::mem::size_of::<QuadUniforms>() as u64,
-            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
-            mapped_at_creation: false,
-        });
-
-        (vertex_buffer, index_buffer, uniform_buffer)
+        (vertex_buffer, index_buffer)
     }
 
     /// Create the uniform bind group layout.
@@ -683,18 +1512,21 @@ impl WgpuBackend {
             device,
             bind_group_layout,
             wgpu::TextureFormat::Bgra8UnormSrgb,
+            1,
         )
     }
 
-    /// Create the quad render pipeline with a specific texture format.
+    /// Create the quad render pipeline with a specific texture format and
+    /// sample count.
     fn create_quad_pipeline_with_format(
         device: &wgpu::Device,
         bind_group_layout: &wgpu::BindGroupLayout,
         format: wgpu::TextureFormat,
+        samples: u32,
     ) -> wgpu::RenderPipeline {
         let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
             label: Some("Quad Shader"),
-            source: wgpu::ShaderSource::Wgsl(include_str!("../shaders/quad.wgsl").into()),
+            source: wgpu::ShaderSource::Wgsl(shader_2d!("quad.wgsl").into()),
         });
 
         let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
@@ -726,14 +1558,16 @@ impl WgpuBackend {
                 topology: wgpu::PrimitiveTopology::TriangleList,
                 strip_index_format: None,
                 front_face: wgpu::FrontFace::Ccw,
-                cull_mode: Some(wgpu::Face::Back),
+                // Quads have no back; canvas y runs down, which mirrors
+                // their winding once placed in a holographic view
+                cull_mode: None,
                 polygon_mode: wgpu::PolygonMode::Fill,
                 unclipped_depth: false,
                 conservative: false,
             },
             depth_stencil: None,
             multisample: wgpu::MultisampleState {
-                count: 1,
+                count: samples,
                 mask: !0,
                 alpha_to_coverage_enabled: false,
             },
@@ -780,31 +1614,54 @@ impl WgpuBackend {
         })
     }
 
-    /// Create the textured quad pipeline with a specific texture format.
-    fn create_textured_pipeline_with_format(
+    /// Create the text pipeline with a specific texture format and sample
+    /// count.
+    fn create_text_pipeline_with_format(
+        device: &wgpu::Device,
+        bind_group_layout: &wgpu::BindGroupLayout,
+        format: wgpu::TextureFormat,
+        samples: u32,
+    ) -> wgpu::RenderPipeline {
+        Self::create_triangle_pipeline(
+            device,
+            bind_group_layout,
+            format,
+            samples,
+            "Text",
+            shader_2d!("text.wgsl"),
+            GlyphVertex::desc(),
+        )
+    }
+
+    /// Create a pipeline drawing triangle lists from one vertex buffer.
+    fn create_triangle_pipeline(
         device: &wgpu::Device,
         bind_group_layout: &wgpu::BindGroupLayout,
         format: wgpu::TextureFormat,
+        samples: u32,
+        label: &str,
+        source: &str,
+        vertices: wgpu::VertexBufferLayout<'static>,
     ) -> wgpu::RenderPipeline {
         let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
-            label: Some("Textured Shader"),
-            source: wgpu::ShaderSource::Wgsl(include_str!("../shaders/textured.wgsl").into()),
+            label: Some(&format!("{label} Shader")),
+            source: wgpu::ShaderSource::Wgsl(source.into()),
         });
 
         let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-            label: Some("Textured Pipeline Layout"),
+            label: Some(&format!("{label} Pipeline Layout")),
             bind_group_layouts: &[bind_group_layout],
             push_constant_ranges: &[],
         });
 
         device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: Some("Textured Render Pipeline"),
+            label: Some(&format!("{label} Render Pipeline")),
             layout: Some(&pipeline_layout),
             vertex: wgpu::VertexState {
                 module: &shader,
                 entry_point: Some("vs_main"),
                 compilation_options: wgpu::PipelineCompilationOptions::default(),
-                buffers: &[Vertex::desc()],
+                buffers: &[vertices],
             },
             fragment: Some(wgpu::FragmentState {
                 module: &shader,
@@ -820,14 +1677,14 @@ impl WgpuBackend {
                 topology: wgpu::PrimitiveTopology::TriangleList,
                 strip_index_format: None,
                 front_face: wgpu::FrontFace::Ccw,
-                cull_mode: Some(wgpu::Face::Back),
+                cull_mode: None,
                 polygon_mode: wgpu::PolygonMode::Fill,
                 unclipped_depth: false,
                 conservative: false,
             },
             depth_stencil: None,
             multisample: wgpu::MultisampleState {
-                count: 1,
+                count: samples,
                 mask: !0,
                 alpha_to_coverage_enabled: false,
             },
@@ -836,47 +1693,173 @@ impl WgpuBackend {
         })
     }
 
-    /// Create a linear filtering sampler.
-    fn create_sampler(device: &wgpu::Device) -> wgpu::Sampler {
-        device.create_sampler(&wgpu::SamplerDescriptor {
-            label: Some("Texture Sampler"),
-            address_mode_u: wgpu::AddressMode::ClampToEdge,
-            address_mode_v: wgpu::AddressMode::ClampToEdge,
-            address_mode_w: wgpu::AddressMode::ClampToEdge,
-            mag_filter: wgpu::FilterMode::Linear,
-            min_filter: wgpu::FilterMode::Linear,
-            mipmap_filter: wgpu::FilterMode::Nearest,
-            ..Default::default()
-        })
-    }
-
-    /// Create a GPU texture from RGBA pixel data.
-    fn texture_from_rgba(
-        &self,
-        data: &[u8],
-        width: u32,
-        height: u32,
+    /// Create an instanced quad pipeline: the unit quad in slot 0 and a
+    /// [`QuadInstance`] per quad in slot 1.
+    fn create_instanced_pipeline(
+        device: &wgpu::Device,
+        bind_group_layout: &wgpu::BindGroupLayout,
+        format: wgpu::TextureFormat,
+        samples: u32,
         label: &str,
-    ) -> RenderResult<CachedTexture> {
-        let expected_size = (width * height * 4) as usize;
-        if data.len() != expected_size {
-            return Err(RenderError::Frame(format!(
-                "Invalid texture data size: expected {expected_size}, got {}",
-                data.len()
-            )));
-        }
+        source: &str,
+    ) -> wgpu::RenderPipeline {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some(&format!("{label} Shader")),
+            source: wgpu::ShaderSource::Wgsl(source.into()),
+        });
 
-        let texture = self.device.create_texture(&wgpu::TextureDescriptor {
-            label: Some(label),
-            size: wgpu::Extent3d {
-                width,
-                height,
-                depth_or_array_layers: 1,
-            },
-            mip_level_count: 1,
-            sample_count: 1,
-            dimension: wgpu::TextureDimension::D2,
-            format: wgpu::TextureFormat::Rgba8UnormSrgb,
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some(&format!("{label} Pipeline Layout")),
+            bind_group_layouts: &[bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some(&format!("{label} Render Pipeline")),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vs_main"),
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+                buffers: &[Vertex::desc(), QuadInstance::desc()],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some("fs_main"),
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                // Screen space flips the quad's winding relative to camera space
+                cull_mode: None,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState {
+                count: samples,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+            cache: None,
+        })
+    }
+
+    /// Create the model mesh pipeline, depth tested, with a specific texture
+    /// format and sample count.
+    fn create_mesh_pipeline_with_format(
+        device: &wgpu::Device,
+        bind_group_layout: &wgpu::BindGroupLayout,
+        format: wgpu::TextureFormat,
+        samples: u32,
+    ) -> wgpu::RenderPipeline {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Mesh Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("../shaders/mesh.wgsl").into()),
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Mesh Pipeline Layout"),
+            bind_group_layouts: &[bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Mesh Render Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vs_main"),
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+                buffers: &[MeshVertex::desc()],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some("fs_main"),
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                // glTF materials may be double-sided; the shader flips back faces
+                cull_mode: None,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: DEPTH_FORMAT,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::Less,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState {
+                count: samples,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+            cache: None,
+        })
+    }
+
+    /// Create a linear filtering sampler.
+    fn create_sampler(device: &wgpu::Device) -> wgpu::Sampler {
+        device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Texture Sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        })
+    }
+
+    /// Create a GPU texture from RGBA pixel data.
+    fn texture_from_rgba(
+        &self,
+        data: &[u8],
+        width: u32,
+        height: u32,
+        label: &str,
+    ) -> RenderResult<CachedTexture> {
+        let expected_size = (width * height * 4) as usize;
+        if data.len() != expected_size {
+            return Err(RenderError::Frame(format!(
+                "Invalid texture data size: expected {expected_size}, got {}",
+                data.len()
+            )));
+        }
+
+        let texture = self.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some(label),
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8UnormSrgb,
             usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
             view_formats: &[],
         });
@@ -914,910 +1897,2969 @@ impl WgpuBackend {
     /// Clear all cached textures.
     pub fn clear_texture_cache(&mut self) {
         self.texture_cache.clear();
+        self.images.clear();
         tracing::debug!("Texture cache cleared");
     }
 
-    /// Set the background color.
-    pub fn set_background_color(&mut self, r: f64, g: f64, b: f64, a: f64) {
-        self.background_color = wgpu::Color { r, g, b, a };
+    /// Supply the encoded bytes (PNG, JPEG or WebP) of a remote image
+    /// source, an `http(s):` URL or `asset:` reference, which the renderer
+    /// does not fetch itself. Elements showing it draw it from the next frame.
+    pub fn provide_image(&mut self, src: &str, data: Vec<u8>) {
+        self.pending_images.remove(src);
+        self.images.forget_source(src);
+        self.remote_images.insert(src.to_string(), data);
     }
 
-    /// Reconfigure the surface with current settings.
-    ///
-    /// Call this after surface lost/outdated errors.
-    ///
-    /// # Errors
-    ///
-    /// Returns an error if no surface is configured.
-    pub fn reconfigure_surface(&mut self) -> RenderResult<()> {
-        if let (Some(surface), Some(config)) = (&self.surface, &self.surface_config) {
-            surface.configure(&self.device, config);
-            tracing::debug!("Surface reconfigured: {}x{}", config.width, config.height);
-            Ok(())
-        } else {
-            Err(RenderError::Surface(
-                "No surface to reconfigure".to_string(),
-            ))
-        }
+    /// Remote image sources that elements have tried to draw before their
+    /// bytes were supplied with [`Self::provide_image`].
+    pub fn pending_images(&self) -> impl Iterator<Item = &str> {
+        self.pending_images.iter().map(String::as_str)
     }
 
-    /// Drop the surface (for suspend/minimize).
+    /// Loading state of a `Model3D` source, once an element showing it has
+    /// been drawn or it has been supplied with [`Self::provide_model`].
     ///
-    /// The surface can be recreated by calling `from_window` again.
-    pub fn drop_surface(&mut self) {
-        self.surface = None;
-        self.surface_config = None;
-        tracing::debug!("Surface dropped");
+    /// Models load off the render thread; elements show a placeholder box
+    /// until theirs is [`ModelState::Ready`].
+    #[must_use]
+    pub fn model_state(&self, src: &str) -> Option<&ModelState> {
+        self.models.state(src)
     }
 
-    /// Check if a surface is configured.
-    #[must_use]
-    pub fn has_surface(&self) -> bool {
-        self.surface.is_some()
+    /// Supply the bytes (`.glb`, or `.gltf` with embedded buffers) of a
+    /// remote model source, which the renderer does not fetch itself.
+    pub fn provide_model(&mut self, src: &str, data: Vec<u8>) {
+        self.gpu_models.remove(src);
+        self.models.provide(src, data);
     }
 
-    /// Get the current scale factor.
-    #[must_use]
-    pub fn scale_factor(&self) -> f64 {
-        self.scale_factor
+    /// Remote model sources that elements have tried to draw before their
+    /// bytes were supplied with [`Self::provide_model`].
+    pub fn pending_models(&self) -> impl Iterator<Item = &str> {
+        self.models.pending()
     }
 
-    /// Set the scale factor (for Retina/HiDPI displays).
-    pub fn set_scale_factor(&mut self, factor: f64) {
-        if (self.scale_factor - factor).abs() > f64::EPSILON {
-            tracing::info!("Scale factor changed: {} -> {}", self.scale_factor, factor);
-            self.scale_factor = factor;
+    /// Collect models that finished loading since the last frame. Returns
+    /// whether any did, so the caller knows to draw again.
+    pub fn poll_models(&mut self) -> bool {
+        self.models.poll()
+    }
+
+    /// Start loading a model source and, once it is parsed, upload its
+    /// meshes.
+    fn prepare_model(&mut self, src: &str) {
+        if self.gpu_models.contains_key(src) {
+            return;
+        }
+        if let ModelState::Ready(model) = self.models.request(src) {
+            let model = Arc::clone(model);
+            let gpu_model = self.upload_model(&model);
+            self.gpu_models.insert(src.to_string(), Arc::new(gpu_model));
         }
     }
 
-    /// Set the viewport for subsequent rendering operations.
-    ///
-    /// The viewport defines a rectangular region within the canvas where
-    /// rendering will occur. Elements outside the viewport are clipped.
-    ///
-    /// # Errors
-    ///
-    /// Returns an error if the viewport bounds are invalid:
-    /// - Zero width or height
-    /// - Viewport extends beyond canvas bounds
-    pub fn set_viewport(
-        &mut self,
-        x: u32,
-        y: u32,
-        width: u32,
-        height: u32,
-    ) -> crate::RenderResult<()> {
-        // Validate dimensions
-        if width == 0 || height == 0 {
-            return Err(crate::RenderError::Viewport(
-                "Viewport width and height must be greater than zero".to_string(),
-            ));
+    /// Collect finished loads and prepare the models of every `Model3D`
+    /// and 3D chart.
+    fn prepare_scene_models(&mut self, scene: &Scene) {
+        self.models.poll();
+        for element in scene.elements() {
+            match &element.kind {
+                ElementKind::Model3D { src, .. } => self.prepare_model(src),
+                ElementKind::Chart { .. } => {
+                    let scene = ChartGeometry::from_element(element, &self.chart_style)
+                        .ok()
+                        .and_then(|geometry| geometry.scene);
+                    if let Some(scene) = scene {
+                        self.prepare_chart_model(element.id, scene.model);
+                    }
+                }
+                _ => {}
+            }
         }
+        self.prepare_shaders(scene.elements());
+    }
 
-        // Validate bounds
-        let max_x = x.saturating_add(width);
-        let max_y = y.saturating_add(height);
+    /// Lay out a chart. A 3D chart's plot is drawn from its model rather
+    /// than its projected paths, so those are dropped and the model uploaded,
+    /// and its plot box and camera returned with the rest of the chart.
+    fn prepare_chart(&mut self, element: &Element) -> Option<(ChartGeometry, Option<ChartPlot>)> {
+        let mut geometry = ChartGeometry::from_element(element, &self.chart_style)
+            .map_err(|e| tracing::warn!("Failed to lay out chart: {e}"))
+            .ok()?;
+        let Some(scene) = geometry.scene.take() else {
+            return Some((geometry, None));
+        };
+        geometry.paths.drain(scene.paths);
+        self.prepare_chart_model(element.id, scene.model);
+        Some((geometry, Some((scene.rect, scene.camera))))
+    }
 
-        if max_x > self.width || max_y > self.height {
-            return Err(crate::RenderError::Viewport(format!(
-                "Viewport ({}, {}, {}x{}) extends beyond canvas bounds ({}x{})",
-                x, y, width, height, self.width, self.height
-            )));
+    /// Upload a 3D chart's model, unless it is unchanged since last time.
+    fn prepare_chart_model(&mut self, id: ElementId, model: Model) {
+        if self
+            .chart_models
+            .get(&id)
+            .is_some_and(|(cached, _)| *cached == model)
+        {
+            return;
         }
+        let gpu_model = Arc::new(self.upload_model(&model));
+        self.chart_models.insert(id, (model, gpu_model));
+    }
 
-        self.current_viewport = Some(Viewport::new(x, y, width, height));
-        tracing::debug!("Viewport set to ({}, {}, {}x{})", x, y, width, height);
-        Ok(())
+    /// Copy a model's meshes into vertex and index buffers.
+    fn upload_model(&self, model: &Model) -> GpuModel {
+        let meshes = model
+            .primitives
+            .iter()
+            .map(|primitive| {
+                let vertices: Vec<MeshVertex> = primitive
+                    .positions
+                    .iter()
+                    .zip(&primitive.normals)
+                    .map(|(&position, &normal)| MeshVertex { position, normal })
+                    .collect();
+                GpuMesh {
+                    vertices: self
+                        .device
+                        .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                            label: Some("Mesh Vertex Buffer"),
+                            contents: bytemuck::cast_slice(&vertices),
+                            usage: wgpu::BufferUsages::VERTEX,
+                        }),
+                    indices: self
+                        .device
+                        .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                            label: Some("Mesh Index Buffer"),
+                            contents: bytemuck::cast_slice(&primitive.indices),
+                            usage: wgpu::BufferUsages::INDEX,
+                        }),
+                    index_count: u32::try_from(primitive.indices.len()).unwrap_or(u32::MAX),
+                    material: primitive.material,
+                }
+            })
+            .collect();
+        let (center, radius) = model.bounding_sphere();
+        GpuModel {
+            meshes,
+            center,
+            radius,
+        }
     }
 
-    /// Reset the viewport to the full canvas.
-    ///
-    /// After calling this method, rendering will use the entire canvas.
-    /// Returns the previous viewport if one was set.
-    pub fn reset_viewport(&mut self) -> Option<Viewport> {
-        let previous = self.current_viewport.take();
-        if previous.is_some() {
-            tracing::debug!("Viewport reset to full canvas");
+    /// Depth buffer matching a `width` x `height` render target, remade
+    /// when the size or sample count changes.
+    fn depth_view(&mut self, width: u32, height: u32) -> wgpu::TextureView {
+        let samples = self.antialiasing.sample_count();
+        if let Some((w, h, n, view)) = &self.depth_target {
+            if (*w, *h, *n) == (width, height, samples) {
+                return view.clone();
+            }
         }
-        previous
+        let texture = self.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Mesh Depth Texture"),
+            size: wgpu::Extent3d {
+                width: width.max(1),
+                height: height.max(1),
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: samples,
+            dimension: wgpu::TextureDimension::D2,
+            format: DEPTH_FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        self.depth_target = Some((width, height, samples, view.clone()));
+        view
     }
 
-    /// Get the current viewport, if any.
-    ///
-    /// Returns `None` if rendering uses the full canvas.
+    /// Current anti-aliasing mode; a sample count the device lacks has
+    /// already fallen back to 4x.
     #[must_use]
-    pub fn viewport(&self) -> Option<Viewport> {
-        self.current_viewport
+    pub fn antialiasing(&self) -> AntiAliasing {
+        self.antialiasing
     }
 
-    /// Render a scene with a camera for 3D/holographic rendering.
-    ///
-    /// This method sets up the view-projection matrix from the camera
-    /// and renders the scene using 3D transformations. The viewport
-    /// can be optionally set to render to a specific region.
-    ///
-    /// For 2D rendering (orthographic), use the standard `render` method
-    /// or pass `None` as the camera.
-    ///
-    /// # Arguments
-    ///
-    /// * `scene` - The scene to render.
-    /// * `camera` - Optional camera for 3D rendering. If `None`, uses 2D mode.
-    /// * `viewport` - Optional viewport for sub-region rendering.
-    ///
-    /// # Errors
-    ///
-    /// Returns an error if rendering fails.
-    #[allow(clippy::cast_precision_loss)] // Viewport dimensions fit in f32 for aspect ratio
-    pub fn render_with_camera(
-        &mut self,
-        scene: &Scene,
-        camera: Option<&Camera>,
-        viewport: Option<Viewport>,
-    ) -> RenderResult<()> {
-        // Set viewport if specified
-        let old_viewport = self.current_viewport;
-        if let Some(vp) = viewport {
-            self.set_viewport(vp.x, vp.y, vp.width, vp.height)?;
+    /// `mode`, or 4x MSAA if the device cannot use its sample count.
+    fn supported_antialiasing(&self, mode: AntiAliasing) -> AntiAliasing {
+        let samples = mode.sample_count();
+        if samples == 1 || self.msaa_sample_counts.contains(&samples) {
+            return mode;
         }
+        tracing::warn!("{samples}x MSAA is not supported by this device, using 4x");
+        AntiAliasing::Msaa4
+    }
 
-        // Set camera view-projection matrix if camera is provided
-        self.active_view_projection = camera.map(|cam| {
-            // Calculate aspect ratio from viewport or canvas dimensions
-            let (width, height) = if let Some(vp) = self.current_viewport {
-                (vp.width, vp.height)
-            } else {
-                (self.width, self.height)
-            };
-            let aspect = width as f32 / height as f32;
-
-            // Compute view-projection matrix
-            let view = cam.view_matrix();
-            let proj = cam.projection_matrix(aspect);
-            let view_proj = proj.mul(&view);
-            view_proj.data
-        });
-
-        // Render using the standard render path (which now checks active_view_projection)
-        let result = self.render_internal(scene);
-
-        // Restore state
-        self.active_view_projection = None;
-        self.current_viewport = old_viewport;
-
-        result
+    /// Draw into `formats.target` and present in `formats.space`, rebuilding
+    /// the pipelines if the target format changes.
+    fn use_color_formats(&mut self, formats: ColorFormats) {
+        if formats.space != self.requested_color_space {
+            tracing::warn!(
+                "{} output is not available on this display, presenting {}",
+                self.requested_color_space.name(),
+                formats.space.name()
+            );
+        }
+        self.presented_color_space = formats.space;
+        if formats.target == self.target_format {
+            return;
+        }
+        self.target_format = formats.target;
+        // WebGPU guarantees 4x multisampling in every renderable format;
+        // counts measured for the old format may not carry over
+        self.msaa_sample_counts.retain(|&samples| samples <= 4);
+        self.antialiasing = self.supported_antialiasing(self.antialiasing);
+        self.rebuild_pipelines(self.antialiasing.sample_count());
+        self.frame_msaa = None;
+        self.retained = None;
     }
 
-    /// Internal render method used by both `render` and `render_with_camera`.
-    fn render_internal(&mut self, scene: &Scene) -> RenderResult<()> {
-        let Some(surface) = &self.surface else {
-            tracing::trace!("No surface configured, skipping render");
-            return Ok(());
-        };
+    /// A view to draw a surface texture through: in the target format when
+    /// that is the sRGB form of the surface's.
+    fn surface_view(&self, texture: &wgpu::Texture) -> wgpu::TextureView {
+        let format = (texture.format() != self.target_format
+            && texture.format().remove_srgb_suffix() == self.target_format.remove_srgb_suffix())
+        .then_some(self.target_format);
+        texture.create_view(&wgpu::TextureViewDescriptor {
+            format,
+            ..Default::default()
+        })
+    }
 
-        // Handle surface errors with recovery
-        let output = match surface.get_current_texture() {
-            Ok(output) => output,
-            Err(wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated) => {
-                tracing::warn!("Surface lost/outdated, reconfiguring...");
-                self.reconfigure_surface()?;
-                self.surface
-                    .as_ref()
-                    .ok_or_else(|| RenderError::Surface("Surface lost during reconfigure".into()))?
-                    .get_current_texture()
-                    .map_err(|e| RenderError::Surface(format!("Failed after reconfigure: {e}")))?
-            }
-            Err(wgpu::SurfaceError::Timeout) => {
-                tracing::warn!("Surface timeout, skipping frame");
-                return Ok(());
-            }
-            Err(wgpu::SurfaceError::OutOfMemory) => {
-                return Err(RenderError::Surface("GPU out of memory".to_string()));
-            }
-            Err(wgpu::SurfaceError::Other) => {
-                return Err(RenderError::Surface("Unknown surface error".to_string()));
+    /// Format offscreen renders are read back in: the target's when it is
+    /// 8-bit sRGB, as snapshots and exports are, else sRGB BGRA.
+    fn readback_format(&self) -> wgpu::TextureFormat {
+        match self.target_format {
+            wgpu::TextureFormat::Bgra8UnormSrgb | wgpu::TextureFormat::Rgba8UnormSrgb => {
+                self.target_format
             }
-        };
-
-        let view = output
-            .texture
-            .create_view(&wgpu::TextureViewDescriptor::default());
-
-        let mut encoder = self
-            .device
-            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
-                label: Some("Render Encoder"),
-            });
-
-        self.render_scene_elements(&mut encoder, &view, scene);
-
-        self.queue.submit(std::iter::once(encoder.finish()));
-        output.present();
+            _ => wgpu::TextureFormat::Bgra8UnormSrgb,
+        }
+    }
 
-        tracing::trace!(
-            "Rendered {} elements to {}x{} surface (camera: {})",
-            scene.element_count(),
-            self.width,
-            self.height,
-            self.active_view_projection.is_some()
+    /// Rebuild the pipelines to draw with `samples` samples per pixel.
+    fn rebuild_pipelines(&mut self, samples: u32) {
+        let format = self.target_format;
+        self.quad_pipeline = Self::create_quad_pipeline_with_format(
+            &self.device,
+            &self.uniform_bind_group_layout,
+            format,
+            samples,
         );
-
-        Ok(())
+        self.text_pipeline = Self::create_text_pipeline_with_format(
+            &self.device,
+            &self.textured_bind_group_layout,
+            format,
+            samples,
+        );
+        self.mesh_pipeline = Self::create_mesh_pipeline_with_format(
+            &self.device,
+            &self.uniform_bind_group_layout,
+            format,
+            samples,
+        );
+        self.batches = BatchState::new(
+            &self.device,
+            &self.uniform_bind_group_layout,
+            &self.textured_bind_group_layout,
+            format,
+            samples,
+        );
+        // Recompiled for the new sample count as they are next drawn
+        self.shader_pipelines.clear();
     }
 
-    /// Apply the current viewport and scissor rect to a render pass.
-    ///
-    /// This should be called after setting the pipeline but before drawing.
-    #[allow(clippy::cast_precision_loss)] // Viewport dimensions fit in f32
-    fn apply_viewport_to_render_pass(&self, render_pass: &mut wgpu::RenderPass<'_>) {
-        let (x, y, width, height) = if let Some(vp) = self.current_viewport {
-            (vp.x, vp.y, vp.width, vp.height)
+    /// Draw into `view`, a `width` x `height` target, through a
+    /// multisampled companion while MSAA is on, until
+    /// [`release_msaa`](Self::release_msaa). `frame` marks the surface or
+    /// retained frame, whose companion is kept for the next frame.
+    fn attach_msaa(
+        &mut self,
+        view: &wgpu::TextureView,
+        width: u32,
+        height: u32,
+        format: wgpu::TextureFormat,
+        frame: bool,
+    ) {
+        let samples = self.antialiasing.sample_count();
+        if samples == 1 {
+            return;
+        }
+        let size = wgpu::Extent3d {
+            width: width.max(1),
+            height: height.max(1),
+            depth_or_array_layers: 1,
+        };
+        let create = || {
+            self.device.create_texture(&wgpu::TextureDescriptor {
+                label: Some("MSAA Target"),
+                size,
+                mip_level_count: 1,
+                sample_count: samples,
+                dimension: wgpu::TextureDimension::D2,
+                format,
+                usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+                view_formats: &[],
+            })
+        };
+        let companion = if frame {
+            let texture = match self.frame_msaa.take() {
+                Some(texture)
+                    if texture.size() == size
+                        && texture.format() == format
+                        && texture.sample_count() == samples =>
+                {
+                    texture
+                }
+                _ => create(),
+            };
+            let companion = texture.create_view(&wgpu::TextureViewDescriptor::default());
+            self.frame_msaa = Some(texture);
+            companion
         } else {
-            (0, 0, self.width, self.height)
+            create().create_view(&wgpu::TextureViewDescriptor::default())
         };
+        self.msaa_views.push((view.clone(), companion));
+    }
 
-        // wgpu viewport uses f32 for coordinates and depths
-        render_pass.set_viewport(
-            x as f32,
-            y as f32,
-            width as f32,
-            height as f32,
-            0.0, // min_depth
-            1.0, // max_depth
-        );
-
-        // Scissor rect clips rendering to the viewport bounds
-        render_pass.set_scissor_rect(x, y, width, height);
+    /// Stop drawing through the companions of [`attach_msaa`](Self::attach_msaa),
+    /// once their command encoders are submitted.
+    fn release_msaa(&mut self) {
+        self.msaa_views.clear();
     }
 
-    /// Get the wgpu device.
-    #[must_use]
-    pub fn device(&self) -> &Arc<wgpu::Device> {
-        &self.device
+    /// Color attachment drawing into `view`, through its multisampled
+    /// companion if it has one. Draws are always stored, so later passes
+    /// build on them.
+    fn color_attachment<'a>(
+        &'a self,
+        view: &'a wgpu::TextureView,
+        load: wgpu::LoadOp<wgpu::Color>,
+    ) -> wgpu::RenderPassColorAttachment<'a> {
+        let companion = self
+            .msaa_views
+            .iter()
+            .find(|(target, _)| target == view)
+            .map(|(_, companion)| companion);
+        wgpu::RenderPassColorAttachment {
+            view: companion.unwrap_or(view),
+            resolve_target: companion.map(|_| view),
+            ops: wgpu::Operations {
+                load,
+                store: wgpu::StoreOp::Store,
+            },
+        }
     }
 
-    /// Get the wgpu queue.
-    #[must_use]
-    pub fn queue(&self) -> &Arc<wgpu::Queue> {
-        &self.queue
+    /// Set how many bytes of decoded images are kept on the GPU (256 MB by
+    /// default). Least recently drawn images are dropped first.
+    pub fn set_image_cache_budget(&mut self, bytes: usize) {
+        self.images.set_budget(bytes);
     }
 
-    /// Configure a surface for rendering.
+    /// Draw text with the given TrueType or OpenType font instead of the
+    /// system's sans-serif.
     ///
     /// # Errors
     ///
-    /// Returns an error if surface configuration fails.
-    pub fn configure_surface(
-        &mut self,
-        surface: wgpu::Surface<'static>,
-        width: u32,
-        height: u32,
-    ) -> RenderResult<()> {
-        let caps = surface.get_capabilities(&self.get_adapter(Some(&surface))?);
-        let format = caps
-            .formats
-            .iter()
-            .find(|f| f.is_srgb())
-            .copied()
-            .or_else(|| caps.formats.first().copied())
-            .ok_or_else(|| RenderError::GpuInit("No surface formats available".to_string()))?;
-
-        let alpha_mode = caps
-            .alpha_modes
-            .first()
-            .copied()
-            .ok_or_else(|| RenderError::GpuInit("No alpha modes available".to_string()))?;
-
-        let config = wgpu::SurfaceConfiguration {
-            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
-            format,
-            width,
-            height,
-            present_mode: wgpu::PresentMode::AutoVsync,
-            alpha_mode,
-            view_formats: vec![],
-            desired_maximum_frame_latency: 2,
-        };
-
-        surface.configure(&self.device, &config);
-
-        self.surface = Some(surface);
-        self.surface_config = Some(config);
-        self.width = width;
-        self.height = height;
-
-        tracing::debug!("Surface configured: {width}x{height}, format: {format:?}");
-
+    /// Returns an error if the data is not a font that can be read.
+    pub fn set_font(&mut self, data: Vec<u8>) -> RenderResult<()> {
+        let glyphs = GlyphAtlas::from_font_data(data)?;
+        self.text_atlas = Some(self.create_text_atlas(glyphs));
+        self.font_searched = true;
         Ok(())
     }
 
-    /// Get the adapter (recreate if needed).
-    fn get_adapter(
-        &self,
-        compat_surface: Option<&wgpu::Surface<'_>>,
-    ) -> RenderResult<wgpu::Adapter> {
-        let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor {
-            backends: wgpu::Backends::all(),
-            ..Default::default()
+    /// Create the GPU texture for a glyph atlas.
+    fn create_text_atlas(&self, glyphs: GlyphAtlas) -> TextAtlas {
+        let texture = self.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Glyph Atlas"),
+            size: wgpu::Extent3d {
+                width: ATLAS_SIZE,
+                height: ATLAS_SIZE,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::R8Unorm,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
         });
-
-        pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
-            power_preference: wgpu::PowerPreference::LowPower,
-            compatible_surface: compat_surface.or(self.surface.as_ref()),
-            force_fallback_adapter: false,
-        }))
-        .ok_or_else(|| RenderError::GpuInit("Failed to get adapter".to_string()))
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        TextAtlas {
+            glyphs,
+            texture: CachedTexture { texture, view },
+        }
     }
 
-    #[cfg(target_arch = "wasm32")]
-    fn configure_canvas_surface(
+    /// Lay out the glyphs of every text element and chart label in the
+    /// frame and upload newly rasterized ones.
+    ///
+    /// When the atlas fills up it is cleared and the frame laid out again,
+    /// so every glyph drawn in a frame is in the atlas at once. Without a
+    /// font, text falls back to placeholder blocks.
+    fn prepare_text(
         &mut self,
-        canvas: HtmlCanvasElement,
-        width: u32,
-        height: u32,
-    ) -> RenderResult<()> {
-        let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor {
-            backends: wgpu::Backends::all(),
-            ..Default::default()
-        });
+        elements: &[Element],
+        charts: &HashMap<ElementId, ChartGeometry>,
+        theme: &Theme,
+        opacity_map: &HashMap<ElementId, f32>,
+    ) -> HashMap<ElementId, Vec<GlyphVertex>> {
+        let scene_camera = self.scene_camera.unwrap_or_else(CameraFrame::reset);
+        let mut texts = Vec::new();
+        for element in elements {
+            let opacity =
+                opacity_map.get(&element.id).copied().unwrap_or(1.0) * element.effective_opacity();
+            // Screen-anchored text is drawn without the scene camera
+            let camera = if element.screen_anchored {
+                CameraFrame::reset()
+            } else {
+                scene_camera
+            };
+            if let ElementKind::Text {
+                content, font_size, ..
+            } = &element.kind
+            {
+                let t = &element.transform;
+                let mut color = element
+                    .kind
+                    .resolved_color(theme)
+                    .unwrap_or(ElementKind::TEXT_COLOR)
+                    .to_rgba_f32();
+                color[3] *= opacity;
+                let block = TextBlock {
+                    content,
+                    font_size: *font_size,
+                    color,
+                    rect: [t.x, t.y, t.width, t.height],
+                };
+                texts.push((element.id, block, camera));
+            } else if let Some(geometry) = charts.get(&element.id) {
+                for label in &geometry.labels {
+                    let mut color = label.color.to_rgba_f32();
+                    color[3] *= opacity;
+                    let block = TextBlock {
+                        content: &label.text,
+                        font_size: label.font_size,
+                        color,
+                        rect: label.rect,
+                    };
+                    texts.push((element.id, block, camera));
+                }
+            }
+        }
+        if texts.is_empty() {
+            return HashMap::new();
+        }
 
-        let surface = instance
-            .create_surface(wgpu::SurfaceTarget::Canvas(canvas))
-            .map_err(|e| RenderError::Surface(e.to_string()))?;
+        if !self.font_searched {
+            self.font_searched = true;
+            if let Some(glyphs) = GlyphAtlas::system() {
+                self.text_atlas = Some(self.create_text_atlas(glyphs));
+            } else {
+                tracing::warn!("No font available, drawing text as placeholders");
+            }
+        }
+        let Some(atlas) = self.text_atlas.as_mut() else {
+            return HashMap::new();
+        };
 
-        self.configure_surface(surface, width, height)
+        let mut meshes = HashMap::new();
+        for retry in [false, true] {
+            meshes.clear();
+            let mut full = false;
+            for (id, block, camera) in &texts {
+                if let Some(vertices) = atlas.glyphs.layout(block, camera) {
+                    meshes.entry(*id).or_insert_with(Vec::new).extend(vertices);
+                } else {
+                    full = true;
+                }
+            }
+            if !full {
+                break;
+            }
+            if retry {
+                tracing::warn!("Glyph atlas too small for this frame's text");
+            } else {
+                atlas.glyphs.clear();
+            }
+        }
+
+        if let Some(pixels) = atlas.glyphs.take_upload() {
+            self.queue.write_texture(
+                wgpu::TexelCopyTextureInfo {
+                    texture: &atlas.texture.texture,
+                    mip_level: 0,
+                    origin: wgpu::Origin3d::ZERO,
+                    aspect: wgpu::TextureAspect::All,
+                },
+                pixels,
+                wgpu::TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: Some(ATLAS_SIZE),
+                    rows_per_image: Some(ATLAS_SIZE),
+                },
+                wgpu::Extent3d {
+                    width: ATLAS_SIZE,
+                    height: ATLAS_SIZE,
+                    depth_or_array_layers: 1,
+                },
+            );
+        }
+        meshes
     }
 
-    /// Render a single element as a colored quad with optional opacity.
-    #[allow(clippy::cast_precision_loss)] // Canvas dimensions fit in f32 mantissa (max ~16M)
-    fn render_element_quad_with_opacity(
-        &self,
+    /// Tessellate the laid-out charts in the frame, with their inherited
+    /// and own opacity applied.
+    fn prepare_charts(
+        elements: &[Element],
+        charts: &HashMap<ElementId, ChartGeometry>,
+        opacity_map: &HashMap<ElementId, f32>,
+    ) -> HashMap<ElementId, Vec<ChartVertex>> {
+        elements
+            .iter()
+            .filter_map(|element| {
+                let geometry = charts.get(&element.id)?;
+                let opacity = opacity_map.get(&element.id).copied().unwrap_or(1.0)
+                    * element.effective_opacity();
+                Some((element.id, geometry.tessellate(opacity)))
+            })
+            .collect()
+    }
+
+    /// Queue tessellated chart shapes.
+    fn render_fills(
+        &mut self,
         encoder: &mut wgpu::CommandEncoder,
         view: &wgpu::TextureView,
-        element: &Element,
+        vertices: &[ChartVertex],
         is_first: bool,
-        opacity: f32,
     ) {
-        // Determine element color based on kind
-        let mut color = Self::get_element_color(element);
-        // Apply opacity multiplier from parent OverlayLayer
-        color[3] *= opacity;
-
-        self.render_element_quad_impl(encoder, view, element, is_first, color);
+        let camera = self.batch_target(encoder, view, is_first, self.active_view_projection);
+        let scissor = self.scissor_rect();
+        self.batches.queued.push_fills(camera, scissor, vertices);
     }
 
-    /// Render a single element as a colored quad (implementation).
-    #[allow(clippy::cast_precision_loss)] // Canvas dimensions fit in f32 mantissa (max ~16M)
-    fn render_element_quad_impl(
-        &self,
+    /// Queue laid-out glyphs from the text atlas.
+    fn render_glyphs(
+        &mut self,
         encoder: &mut wgpu::CommandEncoder,
         view: &wgpu::TextureView,
-        element: &Element,
+        vertices: &[GlyphVertex],
         is_first: bool,
-        color: [f32; 4],
     ) {
-        // Create uniforms (use_camera flag in canvas_size.z)
-        let (use_camera, view_proj) = match self.active_view_projection {
-            Some(vp) => (1.0, vp),
-            None => (0.0, IDENTITY_MATRIX),
-        };
-        let uniforms = QuadUniforms {
-            transform: [
-                element.transform.x,
-                element.transform.y,
-                element.transform.width,
-                element.transform.height,
-            ],
-            canvas_size: [self.width as f32, self.height as f32, use_camera, 0.0],
-            color,
-            view_projection: view_proj,
+        if self.text_atlas.is_none() {
+            return;
+        }
+        let camera = self.batch_target(encoder, view, is_first, self.active_view_projection);
+        let scissor = self.scissor_rect();
+        self.batches.queued.push_glyphs(camera, scissor, vertices);
+    }
+
+    /// Point batching at `view`, recording draws queued for another target
+    /// (or another camera) first. Returns whether draws with
+    /// `view_projection` use the camera.
+    fn batch_target(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        view: &wgpu::TextureView,
+        is_first: bool,
+        view_projection: Option<[f32; 16]>,
+    ) -> bool {
+        if self.batches.target.as_ref() != Some(view) {
+            self.flush_batches(encoder);
+            self.batches.target = Some(view.clone());
+        }
+        if let Some(vp) = view_projection {
+            if bytemuck::bytes_of(&vp) != bytemuck::bytes_of(&self.batches.view_projection) {
+                self.flush_batches(encoder);
+                self.batches.view_projection = vp;
+            }
+        }
+        self.batches.clear |= is_first;
+        view_projection.is_some()
+    }
+
+    /// Record the queued draws in one render pass on their target, one
+    /// draw call per batch.
+    #[allow(clippy::cast_precision_loss)] // Canvas dimensions fit in f32 mantissa
+    fn flush_batches(&mut self, encoder: &mut wgpu::CommandEncoder) {
+        let Some(target) = self.batches.target.clone() else {
+            return;
         };
+        if self.batches.queued.is_empty() && !self.batches.clear {
+            return;
+        }
 
-        // Update uniform buffer
-        self.queue
-            .write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&[uniforms]));
+        let (instance_offset, glyph_offset, fill_offset) =
+            self.batches.stage_queued(&self.device, &self.queue);
 
-        // Create bind group
-        let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
-            label: Some("Element Bind Group"),
-            layout: &self.uniform_bind_group_layout,
-            entries: &[wgpu::BindGroupEntry {
-                binding: 0,
-                resource: self.uniform_buffer.as_entire_binding(),
-            }],
+        let uniforms = self.batch_uniforms();
+        let quad_bind_groups = uniforms.each_ref().map(|buffer| {
+            self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("Batch Quad Bind Group"),
+                layout: &self.uniform_bind_group_layout,
+                entries: &[wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: buffer.as_entire_binding(),
+                }],
+            })
+        });
+        let glyph_bind_groups = self.text_atlas.as_ref().map(|atlas| {
+            uniforms
+                .each_ref()
+                .map(|buffer| self.batch_bind_group(buffer, &atlas.texture.view))
         });
 
-        // Create render pass
-        let load_op = if is_first {
-            wgpu::LoadOp::Clear(self.background_color)
+        let load_op = if self.batches.clear {
+            wgpu::LoadOp::Clear(self.clear_color())
         } else {
             wgpu::LoadOp::Load
         };
-
         let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-            label: Some("Element Render Pass"),
-            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                view,
-                resolve_target: None,
-                ops: wgpu::Operations {
-                    load: load_op,
-                    store: wgpu::StoreOp::Store,
-                },
-            })],
+            label: Some("Batch Render Pass"),
+            color_attachments: &[Some(self.color_attachment(&target, load_op))],
             depth_stencil_attachment: None,
             timestamp_writes: None,
             occlusion_query_set: None,
         });
-
-        render_pass.set_pipeline(&self.quad_pipeline);
-        self.apply_viewport_to_render_pass(&mut render_pass);
-        render_pass.set_bind_group(0, &bind_group, &[]);
-        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        let (x, y, width, height) = self.viewport_rect();
+        render_pass.set_viewport(x as f32, y as f32, width as f32, height as f32, 0.0, 1.0);
         render_pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
-        render_pass.draw_indexed(0..6, 0, 0..1);
+
+        let state = &self.batches;
+        let (mut draw_calls, mut triangles) = (0, 0);
+        for batch in state.queued.batches() {
+            let (x, y, width, height) = batch.scissor;
+            if width == 0 || height == 0 {
+                continue;
+            }
+            render_pass.set_scissor_rect(x, y, width, height);
+            let camera = usize::from(batch.camera);
+            match &batch.kind {
+                BatchKind::Quads => {
+                    render_pass.set_pipeline(&state.quad_pipeline);
+                    render_pass.set_bind_group(0, &quad_bind_groups[camera], &[]);
+                }
+                BatchKind::Textured(texture) => {
+                    render_pass.set_pipeline(&state.textured_pipeline);
+                    let bind_group = self.batch_bind_group(&uniforms[camera], texture);
+                    render_pass.set_bind_group(0, &bind_group, &[]);
+                }
+                BatchKind::Glyphs => {
+                    let Some(bind_groups) = &glyph_bind_groups else {
+                        continue;
+                    };
+                    render_pass.set_pipeline(&self.text_pipeline);
+                    render_pass.set_bind_group(0, &bind_groups[camera], &[]);
+                    render_pass.set_vertex_buffer(0, state.glyphs.slice(glyph_offset..));
+                    render_pass.draw(batch.range.clone(), 0..1);
+                    draw_calls += 1;
+                    triangles += batch.range.len() / 3;
+                    continue;
+                }
+                BatchKind::Fills => {
+                    render_pass.set_pipeline(&state.fill_pipeline);
+                    render_pass.set_bind_group(0, &quad_bind_groups[camera], &[]);
+                    render_pass.set_vertex_buffer(0, state.fills.slice(fill_offset..));
+                    render_pass.draw(batch.range.clone(), 0..1);
+                    draw_calls += 1;
+                    triangles += batch.range.len() / 3;
+                    continue;
+                }
+            }
+            render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+            render_pass.set_vertex_buffer(1, state.instances.slice(instance_offset..));
+            render_pass.draw_indexed(0..6, 0, batch.range.clone());
+            draw_calls += 1;
+            triangles += batch.range.len() * 2;
+        }
+        drop(render_pass);
+
+        self.batches.draw_calls += draw_calls;
+        self.batches.triangles += triangles;
+        self.batches.queued.clear();
+        self.batches.clear = false;
     }
 
-    /// Render a single element as a textured quad with optional opacity.
-    #[allow(clippy::cast_precision_loss)] // Canvas dimensions fit in f32 mantissa (max ~16M)
-    fn render_textured_element_with_opacity(
-        &self,
-        encoder: &mut wgpu::CommandEncoder,
-        view: &wgpu::TextureView,
-        element: &Element,
-        texture_view: &wgpu::TextureView,
-        is_first: bool,
-        opacity: f32,
-    ) {
-        // Create uniforms with opacity applied to alpha channel (use_camera flag in canvas_size.z)
-        let (use_camera, view_proj) = match self.active_view_projection {
-            Some(vp) => (1.0, vp),
-            None => (0.0, IDENTITY_MATRIX),
-        };
-        let uniforms = QuadUniforms {
-            transform: [
-                element.transform.x,
-                element.transform.y,
-                element.transform.width,
-                element.transform.height,
-            ],
-            canvas_size: [self.width as f32, self.height as f32, use_camera, 0.0],
-            color: [1.0, 1.0, 1.0, opacity], // Apply opacity to alpha channel
-            view_projection: view_proj,
-        };
+    /// Uniforms for screen-space and camera batches.
+    ///
+    /// Fresh buffers per flush, since one encoder can flush batches for
+    /// different canvas sizes (quilt views).
+    #[allow(clippy::cast_precision_loss)] // Canvas dimensions fit in f32 mantissa
+    fn batch_uniforms(&self) -> [wgpu::Buffer; 2] {
+        [false, true].map(|camera| {
+            let uniforms = FrameUniforms {
+                canvas_size: [
+                    self.width as f32,
+                    self.height as f32,
+                    if camera { 1.0 } else { 0.0 },
+                    self.analytic_padding(),
+                ],
+                view_projection: if camera {
+                    self.batches.view_projection
+                } else {
+                    IDENTITY_MATRIX
+                },
+            };
+            self.device
+                .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some("Batch Uniform Buffer"),
+                    contents: bytemuck::cast_slice(&[uniforms]),
+                    usage: wgpu::BufferUsages::UNIFORM,
+                })
+        })
+    }
 
-        // Update uniform buffer
-        self.queue
-            .write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&[uniforms]));
+    /// How far quads grow past their edges for analytic anti-aliasing, in
+    /// canvas units: one pixel at the scene's zoom, or 0 when off.
+    fn analytic_padding(&self) -> f32 {
+        if !self.antialiasing.is_analytic() {
+            return 0.0;
+        }
+        self.scene_camera
+            .map_or(1.0, |camera| 1.0 / camera.zoom.max(f32::EPSILON))
+    }
 
-        // Create bind group with texture
-        let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
-            label: Some("Textured Element Bind Group"),
+    /// Bind group for a textured or glyph batch.
+    fn batch_bind_group(
+        &self,
+        uniforms: &wgpu::Buffer,
+        texture: &wgpu::TextureView,
+    ) -> wgpu::BindGroup {
+        self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Batch Textured Bind Group"),
             layout: &self.textured_bind_group_layout,
             entries: &[
                 wgpu::BindGroupEntry {
                     binding: 0,
-                    resource: self.uniform_buffer.as_entire_binding(),
+                    resource: uniforms.as_entire_binding(),
                 },
                 wgpu::BindGroupEntry {
                     binding: 1,
-                    resource: wgpu::BindingResource::TextureView(texture_view),
+                    resource: wgpu::BindingResource::TextureView(texture),
                 },
                 wgpu::BindGroupEntry {
                     binding: 2,
                     resource: wgpu::BindingResource::Sampler(&self.sampler),
                 },
             ],
-        });
-
-        // Create render pass
-        let load_op = if is_first {
-            wgpu::LoadOp::Clear(self.background_color)
-        } else {
-            wgpu::LoadOp::Load
-        };
-
-        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-            label: Some("Textured Element Render Pass"),
-            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                view,
-                resolve_target: None,
-                ops: wgpu::Operations {
-                    load: load_op,
-                    store: wgpu::StoreOp::Store,
-                },
-            })],
-            depth_stencil_attachment: None,
-            timestamp_writes: None,
-            occlusion_query_set: None,
-        });
-
-        render_pass.set_pipeline(&self.textured_pipeline);
-        self.apply_viewport_to_render_pass(&mut render_pass);
-        render_pass.set_bind_group(0, &bind_group, &[]);
-        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
-        render_pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
-        render_pass.draw_indexed(0..6, 0, 0..1);
+        })
     }
 
-    /// Render a chart element to a texture, caching the result.
-    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
-    fn render_chart_texture(
-        &mut self,
-        element: &Element,
-        chart_type: &str,
-        data: &serde_json::Value,
-    ) -> RenderResult<()> {
-        let key = element.id.to_string();
-
-        // Skip if already cached
-        if self.texture_cache.contains_key(&key) {
-            return Ok(());
-        }
-
-        // Parse chart config and render to buffer
-        let width = element.transform.width as u32;
-        let height = element.transform.height as u32;
-
-        let config = parse_chart_config(chart_type, data, width, height)?;
-        let rgba_data = render_chart_to_buffer(&config)?;
-
-        // Create GPU texture
-        let label = format!("Chart: {key}");
-        let cached = self.texture_from_rgba(&rgba_data, width, height, &label)?;
-        self.texture_cache.insert(key, cached);
-
-        tracing::debug!("Created chart texture {}x{}", width, height);
-
-        Ok(())
+    /// Record the queued draws and let go of their target, ending the
+    /// batches for this command encoder.
+    fn finish_batches(&mut self, encoder: &mut wgpu::CommandEncoder) {
+        self.flush_batches(encoder);
+        self.batches.target = None;
     }
 
-    /// Render an image element to a texture, caching the result.
-    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
-    fn render_image_texture(&mut self, element: &Element, src: &str) -> RenderResult<()> {
-        let key = element.id.to_string();
+    /// Set the background color, in sRGB like every scene color.
+    pub fn set_background_color(&mut self, r: f64, g: f64, b: f64, a: f64) {
+        self.background_color = wgpu::Color { r, g, b, a };
+    }
 
-        // Skip if already cached
-        if self.texture_cache.contains_key(&key) {
-            return Ok(());
+    /// The background as a clear color, in the targets' linear light.
+    #[allow(clippy::cast_possible_truncation)] // Colors narrow to f32 and back
+    fn clear_color(&self) -> wgpu::Color {
+        let wgpu::Color { r, g, b, a } = self.background_color;
+        let linear = |channel: f64| f64::from(color::srgb_to_linear(channel as f32));
+        wgpu::Color {
+            r: linear(r),
+            g: linear(g),
+            b: linear(b),
+            a,
         }
-
-        // Load image from data URI or create placeholder
-        let texture_data = if src.starts_with("data:") {
-            load_image_from_data_uri(src)?
-        } else {
-            // For non-data URIs, create a placeholder
-            // TODO: Support file loading in native builds
-            tracing::warn!("Non-data URI images not yet supported: {}", src);
-            let width = element.transform.width as u32;
-            let height = element.transform.height as u32;
-            create_placeholder(width, height)
-        };
-
-        // Create GPU texture
-        let label = format!("Image: {key}");
-        let cached = self.texture_from_rgba(
-            &texture_data.data,
-            texture_data.width,
-            texture_data.height,
-            &label,
-        )?;
-        self.texture_cache.insert(key, cached);
-
-        tracing::debug!(
-            "Created image texture {}x{}",
-            texture_data.width,
-            texture_data.height
-        );
-
-        Ok(())
     }
 
-    /// Render a video element to a texture, using placeholder if stream not available.
+    /// Reconfigure the surface with current settings.
     ///
-    /// For video elements, we check if a video texture exists for the `stream_id`.
-    /// If not, we create a placeholder texture. The actual video frames are
-    /// uploaded separately via `update_video_frame()`.
-    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
-    fn render_video_texture(&mut self, element: &Element, stream_id: &str) -> RenderResult<()> {
-        let key = element.id.to_string();
-
-        // Skip if already cached
-        if self.texture_cache.contains_key(&key) {
-            return Ok(());
-        }
-
-        // Check if we have a video texture for this stream
-        let video_key = Self::video_texture_key(stream_id);
-        if self.video_textures.contains_key(&video_key) {
-            tracing::debug!(
-                "Video stream {stream_id} has cached texture, using placeholder for now"
-            );
+    /// Call this after surface lost/outdated errors.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no surface is configured.
+    pub fn reconfigure_surface(&mut self) -> RenderResult<()> {
+        if let (Some(surface), Some(config)) = (&self.surface, &self.surface_config) {
+            surface.configure(&self.device, config);
+            tracing::debug!("Surface reconfigured: {}x{}", config.width, config.height);
+            Ok(())
+        } else {
+            Err(RenderError::Surface(
+                "No surface to reconfigure".to_string(),
+            ))
         }
+    }
 
-        // Create placeholder texture for video (dark gray)
-        let width = element.transform.width.max(1.0) as u32;
-        let height = element.transform.height.max(1.0) as u32;
-        let placeholder = crate::image::create_solid_color(width, height, 32, 32, 32, 255);
+    /// Drop the surface (for suspend/minimize).
+    ///
+    /// The surface can be recreated by calling `from_window` again.
+    pub fn drop_surface(&mut self) {
+        self.surface = None;
+        self.surface_config = None;
+        tracing::debug!("Surface dropped");
+    }
 
-        // Create GPU texture
-        let label = format!("Video: {key}");
-        let cached = self.texture_from_rgba(
-            &placeholder.data,
-            placeholder.width,
-            placeholder.height,
-            &label,
-        )?;
-        self.texture_cache.insert(key, cached);
+    /// Check if a surface is configured.
+    #[must_use]
+    pub fn has_surface(&self) -> bool {
+        self.surface.is_some()
+    }
 
-        tracing::debug!(
-            "Created video placeholder texture {}x{} for stream {stream_id}",
-            width,
-            height
-        );
+    /// Get the current scale factor.
+    #[must_use]
+    pub fn scale_factor(&self) -> f64 {
+        self.scale_factor
+    }
 
-        Ok(())
+    /// Set the scale factor (for Retina/HiDPI displays).
+    pub fn set_scale_factor(&mut self, factor: f64) {
+        if (self.scale_factor - factor).abs() > f64::EPSILON {
+            tracing::info!("Scale factor changed: {} -> {}", self.scale_factor, factor);
+            self.scale_factor = factor;
+        }
     }
 
-    /// Update a video frame for a specific stream.
+    /// Set the viewport for subsequent rendering operations.
     ///
-    /// Call this when new video frame data is available from a WebRTC stream
-    /// or media playback. The frame data should be RGBA format.
+    /// The viewport defines a rectangular region within the canvas where
+    /// rendering will occur. Elements outside the viewport are clipped.
     ///
     /// # Errors
     ///
-    /// Returns an error if the texture cannot be created or dimensions overflow.
-    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
-    pub fn update_video_frame(
+    /// Returns an error if the viewport bounds are invalid:
+    /// - Zero width or height
+    /// - Viewport extends beyond canvas bounds
+    pub fn set_viewport(
         &mut self,
-        stream_id: &str,
+        x: u32,
+        y: u32,
         width: u32,
         height: u32,
-        rgba_data: &[u8],
-    ) -> RenderResult<()> {
-        let expected_size = (width as usize)
-            .checked_mul(height as usize)
-            .and_then(|pixels| pixels.checked_mul(4))
-            .ok_or_else(|| {
-                RenderError::Frame(format!(
-                    "Video frame dimensions {width}x{height} would overflow"
-                ))
-            })?;
-        if rgba_data.len() != expected_size {
-            return Err(RenderError::Frame(format!(
-                "Invalid video frame data size: expected {expected_size}, got {}",
-                rgba_data.len()
-            )));
+    ) -> crate::RenderResult<()> {
+        // Validate dimensions
+        if width == 0 || height == 0 {
+            return Err(crate::RenderError::Viewport(
+                "Viewport width and height must be greater than zero".to_string(),
+            ));
         }
 
-        let video_key = Self::video_texture_key(stream_id);
-        let label = format!("Video Stream: {stream_id}");
+        // Validate bounds
+        let max_x = x.saturating_add(width);
+        let max_y = y.saturating_add(height);
 
-        let cached = self.texture_from_rgba(rgba_data, width, height, &label)?;
-        self.video_textures.insert(video_key, cached);
+        if max_x > self.width || max_y > self.height {
+            return Err(crate::RenderError::Viewport(format!(
+                "Viewport ({}, {}, {}x{}) extends beyond canvas bounds ({}x{})",
+                x, y, width, height, self.width, self.height
+            )));
+        }
+
+        self.current_viewport = Some(Viewport::new(x, y, width, height));
+        tracing::debug!("Viewport set to ({}, {}, {}x{})", x, y, width, height);
+        Ok(())
+    }
+
+    /// Reset the viewport to the full canvas.
+    ///
+    /// After calling this method, rendering will use the entire canvas.
+    /// Returns the previous viewport if one was set.
+    pub fn reset_viewport(&mut self) -> Option<Viewport> {
+        let previous = self.current_viewport.take();
+        if previous.is_some() {
+            tracing::debug!("Viewport reset to full canvas");
+        }
+        previous
+    }
+
+    /// Get the current viewport, if any.
+    ///
+    /// Returns `None` if rendering uses the full canvas.
+    #[must_use]
+    pub fn viewport(&self) -> Option<Viewport> {
+        self.current_viewport
+    }
+
+    /// Render a scene with a camera for 3D/holographic rendering.
+    ///
+    /// This method sets up the view-projection matrix from the camera
+    /// and renders the scene using 3D transformations. The viewport
+    /// can be optionally set to render to a specific region.
+    ///
+    /// For 2D rendering (orthographic), use the standard `render` method
+    /// or pass `None` as the camera.
+    ///
+    /// # Arguments
+    ///
+    /// * `scene` - The scene to render.
+    /// * `camera` - Optional camera for 3D rendering. If `None`, uses 2D mode.
+    /// * `viewport` - Optional viewport for sub-region rendering.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if rendering fails.
+    #[allow(clippy::cast_precision_loss)] // Viewport dimensions fit in f32 for aspect ratio
+    pub fn render_with_camera(
+        &mut self,
+        scene: &Scene,
+        camera: Option<&Camera>,
+        viewport: Option<Viewport>,
+    ) -> RenderResult<()> {
+        // Set viewport if specified
+        let old_viewport = self.current_viewport;
+        if let Some(vp) = viewport {
+            self.set_viewport(vp.x, vp.y, vp.width, vp.height)?;
+        }
+
+        // Set camera view-projection matrix if camera is provided
+        self.active_view_projection = camera.map(|cam| {
+            // Calculate aspect ratio from viewport or canvas dimensions
+            let (width, height) = if let Some(vp) = self.current_viewport {
+                (vp.width, vp.height)
+            } else {
+                (self.width, self.height)
+            };
+            let aspect = width as f32 / height as f32;
+
+            // Compute view-projection matrix
+            let view = cam.view_matrix();
+            let proj = cam.projection_matrix(aspect);
+            let view_proj = proj.mul(&view);
+            view_proj.data
+        });
+
+        // Render using the standard render path (which now checks active_view_projection)
+        let result = self.render_internal(scene);
+
+        // Restore state
+        self.active_view_projection = None;
+        self.current_viewport = old_viewport;
+
+        result
+    }
+
+    /// Internal render method used by both `render` and `render_with_camera`.
+    fn render_internal(&mut self, scene: &Scene) -> RenderResult<()> {
+        let Some(output) = self.acquire_frame()? else {
+            return Ok(());
+        };
+
+        let view = self.surface_view(&output.texture);
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Render Encoder"),
+            });
+        let timed = self
+            .gpu_timer
+            .as_ref()
+            .is_some_and(|timer| timer.begin(&mut encoder));
+
+        let retained = self
+            .retained
+            .as_ref()
+            .map(|texture| texture.create_view(&wgpu::TextureViewDescriptor::default()));
+        let (width, height, format) = (self.width, self.height, self.target_format);
+        // With frame effects, or for HDR10, the scene is drawn into a
+        // texture the post stage reads
+        let effects = self.post_effects.frame.clone();
+        let hdr10 = self.presented_color_space == ColorSpace::Hdr10;
+        let post = !effects.is_empty() || hdr10;
+        let unprocessed = match &retained {
+            None if post => Some(self.post_frame_view(width, height)),
+            _ => None,
+        };
+        let scene_view = retained.as_ref().or(unprocessed.as_ref()).unwrap_or(&view);
+        self.attach_msaa(scene_view, width, height, format, true);
+        match (&retained, self.damage_scissor) {
+            (Some(retained), Some(damage)) => {
+                self.clear_damage(&mut encoder, retained, damage);
+                self.render_scene_elements(&mut encoder, retained, scene, false);
+            }
+            _ => self.render_scene_elements(&mut encoder, scene_view, scene, true),
+        }
+        if post {
+            let frame = PostOutput {
+                view: &view,
+                format: if hdr10 {
+                    output.texture.format()
+                } else {
+                    format
+                },
+                pq_white: hdr10.then_some(SDR_WHITE_NITS),
+            };
+            self.apply_frame_effects(&mut encoder, scene_view, &frame, &effects);
+        } else if let Some(retained) = &self.retained {
+            encoder.copy_texture_to_texture(
+                retained.as_image_copy(),
+                output.texture.as_image_copy(),
+                retained.size(),
+            );
+        }
+
+        self.finish_timed(&mut encoder, timed);
+        self.queue.submit(std::iter::once(encoder.finish()));
+        self.submitted_timed(timed);
+        self.release_msaa();
+        output.present();
 
         tracing::trace!(
-            "Updated video frame {}x{} for stream {stream_id}",
-            width,
-            height
+            "Rendered {} elements to {}x{} surface (camera: {}, damage: {:?})",
+            scene.element_count(),
+            self.width,
+            self.height,
+            self.active_view_projection.is_some(),
+            self.damage_scissor
         );
 
         Ok(())
     }
 
-    /// Remove a video stream texture.
+    /// Get the next surface texture, reconfiguring a lost surface.
     ///
-    /// Call this when a video stream ends.
-    pub fn remove_video_stream(&mut self, stream_id: &str) {
-        let video_key = Self::video_texture_key(stream_id);
-        self.video_textures.remove(&video_key);
-        tracing::debug!("Removed video stream texture: {stream_id}");
+    /// Returns `None` if there is no surface or the frame timed out.
+    fn acquire_frame(&mut self) -> RenderResult<Option<wgpu::SurfaceTexture>> {
+        let Some(surface) = &self.surface else {
+            tracing::trace!("No surface configured, skipping render");
+            return Ok(None);
+        };
+
+        // Handle surface errors with recovery
+        let output = match surface.get_current_texture() {
+            Ok(output) => output,
+            Err(wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated) => {
+                tracing::warn!("Surface lost/outdated, reconfiguring...");
+                self.reconfigure_surface()?;
+                self.surface
+                    .as_ref()
+                    .ok_or_else(|| RenderError::Surface("Surface lost during reconfigure".into()))?
+                    .get_current_texture()
+                    .map_err(|e| RenderError::Surface(format!("Failed after reconfigure: {e}")))?
+            }
+            Err(wgpu::SurfaceError::Timeout) => {
+                tracing::warn!("Surface timeout, skipping frame");
+                return Ok(None);
+            }
+            Err(wgpu::SurfaceError::OutOfMemory) => {
+                return Err(RenderError::Surface("GPU out of memory".to_string()));
+            }
+            Err(wgpu::SurfaceError::Other) => {
+                return Err(RenderError::Surface("Unknown surface error".to_string()));
+            }
+        };
+
+        Ok(Some(output))
     }
 
-    /// Clear all video stream textures.
-    pub fn clear_video_textures(&mut self) {
-        self.video_textures.clear();
-        tracing::debug!("Cleared all video textures");
+    /// The current viewport in target pixels: x, y, width, height.
+    fn viewport_rect(&self) -> (u32, u32, u32, u32) {
+        if let Some(vp) = self.current_viewport {
+            (vp.x, vp.y, vp.width, vp.height)
+        } else {
+            (0, 0, self.width, self.height)
+        }
     }
 
-    /// Generate a consistent cache key for a video stream.
-    #[inline]
-    fn video_texture_key(stream_id: &str) -> String {
-        format!("video:{stream_id}")
+    /// Scissor rect clipping rendering to the viewport bounds, narrowed by
+    /// the active container clip in 2D mode.
+    fn scissor_rect(&self) -> (u32, u32, u32, u32) {
+        let (x, y, width, height) = self.viewport_rect();
+        let clip = match (self.active_clip, self.scene_camera) {
+            (Some(clip), Some(camera)) => Some([
+                clip[0] * camera.zoom + camera.pan_x,
+                clip[1] * camera.zoom + camera.pan_y,
+                clip[2] * camera.zoom,
+                clip[3] * camera.zoom,
+            ]),
+            (Some(clip), None) if self.active_view_projection.is_none() => Some(clip),
+            _ => None,
+        };
+        let scissor = match clip {
+            Some(clip) => Self::clip_scissor(clip, (x, y, width, height)),
+            None => (x, y, width, height),
+        };
+        match self.damage_scissor {
+            Some(damage) => Self::intersect_scissor(scissor, damage),
+            None => scissor,
+        }
+    }
+
+    /// Intersect two pixel scissor rects, leaving an empty rect at `a`'s
+    /// origin if they do not overlap.
+    fn intersect_scissor(a: (u32, u32, u32, u32), b: (u32, u32, u32, u32)) -> (u32, u32, u32, u32) {
+        let left = a.0.max(b.0);
+        let top = a.1.max(b.1);
+        let right = (a.0 + a.2).min(b.0 + b.2);
+        let bottom = (a.1 + a.3).min(b.1 + b.3);
+        if right <= left || bottom <= top {
+            return (a.0, a.1, 0, 0);
+        }
+        (left, top, right - left, bottom - top)
+    }
+
+    /// Usage for a surface: a render target, and a copy destination when
+    /// supported so a retained frame can be copied onto it.
+    fn surface_usage(caps: &wgpu::SurfaceCapabilities) -> wgpu::TextureUsages {
+        wgpu::TextureUsages::RENDER_ATTACHMENT | (caps.usages & wgpu::TextureUsages::COPY_DST)
+    }
+
+    /// Whether partial repaints are possible: the surface accepts copies
+    /// from a retained frame in the pipelines' format, and the background
+    /// is opaque so damaged regions can be painted over.
+    fn can_retain(&self) -> bool {
+        self.surface_config.as_ref().is_some_and(|config| {
+            config.usage.contains(wgpu::TextureUsages::COPY_DST)
+                && config.format.remove_srgb_suffix() == self.target_format.remove_srgb_suffix()
+        }) && self.background_color.a >= 1.0
+    }
+
+    /// Create the texture that keeps the last frame for partial repaints.
+    fn create_retained(&self) -> wgpu::Texture {
+        self.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Retained Frame"),
+            size: wgpu::Extent3d {
+                width: self.width.max(1),
+                height: self.height.max(1),
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: self.target_format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                | wgpu::TextureUsages::COPY_SRC
+                | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        })
+    }
+
+    /// Paint the damaged rect of the retained frame with the background.
+    ///
+    /// A clear load op would wipe the whole target regardless of the
+    /// scissor, so this draws a background quad instead.
+    #[allow(clippy::cast_precision_loss, clippy::cast_possible_truncation)] // Pixel rect fits in f32; colors narrow to f32
+    fn clear_damage(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        view: &wgpu::TextureView,
+        damage: (u32, u32, u32, u32),
+    ) {
+        let background = self.background_color;
+        let uniforms = QuadUniforms {
+            transform: [
+                damage.0 as f32,
+                damage.1 as f32,
+                damage.2 as f32,
+                damage.3 as f32,
+            ],
+            canvas_size: [self.width as f32, self.height as f32, 0.0, 0.0],
+            color: [
+                background.r as f32,
+                background.g as f32,
+                background.b as f32,
+                1.0,
+            ],
+            view_projection: IDENTITY_MATRIX,
+            fill_end: QuadStyle::FLAT.fill_end,
+            stroke_color: QuadStyle::FLAT.stroke_color,
+            style_params: QuadStyle::FLAT.params,
+            style_extra: QuadStyle::FLAT.extra,
+        };
+        // A buffer of its own, as the frame's other draws are recorded too
+        let uniform_buffer = self
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Damage Uniform Buffer"),
+                contents: bytemuck::cast_slice(&[uniforms]),
+                usage: wgpu::BufferUsages::UNIFORM,
+            });
+        let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Damage Bind Group"),
+            layout: &self.uniform_bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: uniform_buffer.as_entire_binding(),
+            }],
+        });
+
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Damage Clear Pass"),
+            color_attachments: &[Some(self.color_attachment(view, wgpu::LoadOp::Load))],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        render_pass.set_pipeline(&self.quad_pipeline);
+        let (x, y, width, height) =
+            Self::intersect_scissor((0, 0, self.width, self.height), damage);
+        render_pass.set_scissor_rect(x, y, width, height);
+        render_pass.set_bind_group(0, &bind_group, &[]);
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        render_pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+        render_pass.draw_indexed(0..6, 0, 0..1);
+    }
+
+    /// Orthographic matrix mapping canvas coordinates through the scene's
+    /// zoom/pan to normalized device coordinates (matches the 2D shader path).
+    #[allow(clippy::cast_precision_loss)] // Canvas dimensions fit in f32 mantissa
+    fn scene_camera_matrix(&self, camera: &CameraFrame) -> [f32; 16] {
+        let (width, height) = (self.width.max(1) as f32, self.height.max(1) as f32);
+        let scale_x = 2.0 * camera.zoom / width;
+        let scale_y = -2.0 * camera.zoom / height;
+        let offset_x = 2.0 * camera.pan_x / width - 1.0;
+        let offset_y = 1.0 - 2.0 * camera.pan_y / height;
+        #[rustfmt::skip]
+        let matrix = [
+            scale_x, 0.0, 0.0, 0.0,
+            0.0, scale_y, 0.0, 0.0,
+            0.0, 0.0, 1.0, 0.0,
+            offset_x, offset_y, 0.0, 1.0,
+        ];
+        matrix
+    }
+
+    /// Intersect a canvas-space clip rect with a pixel scissor rect.
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss,
+        clippy::cast_precision_loss
+    )] // Clip coordinates are clamped to the non-negative viewport range
+    fn clip_scissor(clip: [f32; 4], scissor: (u32, u32, u32, u32)) -> (u32, u32, u32, u32) {
+        let (x, y, width, height) = scissor;
+        let (min_x, max_x) = (x as f32, (x + width) as f32);
+        let (min_y, max_y) = (y as f32, (y + height) as f32);
+        let left = clip[0].floor().clamp(min_x, max_x);
+        let top = clip[1].floor().clamp(min_y, max_y);
+        let right = (clip[0] + clip[2]).ceil().clamp(left, max_x);
+        let bottom = (clip[1] + clip[3]).ceil().clamp(top, max_y);
+        (
+            left as u32,
+            top as u32,
+            (right - left) as u32,
+            (bottom - top) as u32,
+        )
+    }
+
+    /// Get the wgpu device.
+    #[must_use]
+    pub fn device(&self) -> &Arc<wgpu::Device> {
+        &self.device
+    }
+
+    /// Get the wgpu queue.
+    #[must_use]
+    pub fn queue(&self) -> &Arc<wgpu::Queue> {
+        &self.queue
+    }
+
+    /// The GPU adapter in use: its name, driver and graphics API.
+    #[must_use]
+    pub fn adapter_info(&self) -> &wgpu::AdapterInfo {
+        &self.adapter_info
     }
 
-    /// Get the display color for an element based on its kind.
-    fn get_element_color(element: &Element) -> [f32; 4] {
-        // If selected, use selection color
-        if element.selected {
-            return [0.2, 0.6, 1.0, 0.8]; // Blue selection highlight
-        }
+    /// Sample counts above 1 the device can multisample the target with.
+    #[must_use]
+    pub fn msaa_samples(&self) -> &[u32] {
+        &self.msaa_sample_counts
+    }
+
+    /// Configure a surface for rendering.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if surface configuration fails.
+    pub fn configure_surface(
+        &mut self,
+        surface: wgpu::Surface<'static>,
+        width: u32,
+        height: u32,
+    ) -> RenderResult<()> {
+        let caps = surface.get_capabilities(&self.get_adapter(Some(&surface))?);
+        let formats = ColorFormats::choose(&caps.formats, self.requested_color_space)
+            .ok_or_else(|| RenderError::GpuInit("No surface formats available".to_string()))?;
+
+        let alpha_mode = caps
+            .alpha_modes
+            .first()
+            .copied()
+            .ok_or_else(|| RenderError::GpuInit("No alpha modes available".to_string()))?;
+
+        let config = wgpu::SurfaceConfiguration {
+            usage: Self::surface_usage(&caps),
+            format: formats.surface,
+            width,
+            height,
+            present_mode: wgpu::PresentMode::AutoVsync,
+            alpha_mode,
+            view_formats: formats.view_formats(),
+            desired_maximum_frame_latency: 2,
+        };
+
+        surface.configure(&self.device, &config);
+
+        self.surface = Some(surface);
+        self.surface_config = Some(config);
+        self.surface_formats = caps.formats;
+        self.width = width;
+        self.height = height;
+        self.use_color_formats(formats);
+
+        tracing::debug!(
+            "Surface configured: {width}x{height}, format: {:?}",
+            formats.surface
+        );
+
+        Ok(())
+    }
+
+    /// Get the adapter (recreate if needed).
+    fn get_adapter(
+        &self,
+        compat_surface: Option<&wgpu::Surface<'_>>,
+    ) -> RenderResult<wgpu::Adapter> {
+        let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor {
+            backends: wgpu::Backends::all(),
+            ..Default::default()
+        });
+
+        pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: wgpu::PowerPreference::LowPower,
+            compatible_surface: compat_surface.or(self.surface.as_ref()),
+            force_fallback_adapter: false,
+        }))
+        .ok_or_else(|| RenderError::GpuInit("Failed to get adapter".to_string()))
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn configure_canvas_surface(
+        &mut self,
+        canvas: HtmlCanvasElement,
+        width: u32,
+        height: u32,
+    ) -> RenderResult<()> {
+        let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor {
+            backends: wgpu::Backends::all(),
+            ..Default::default()
+        });
+
+        let surface = instance
+            .create_surface(wgpu::SurfaceTarget::Canvas(canvas))
+            .map_err(|e| RenderError::Surface(e.to_string()))?;
+
+        self.configure_surface(surface, width, height)
+    }
+
+    /// Render a single element as a colored quad with optional opacity.
+    ///
+    /// Honors the element's [`Style`](canvas_core::Style): the drop shadow is
+    /// drawn first as a blurred quad, then the element with its fill, stroke,
+    /// and corner radius.
+    #[allow(clippy::cast_precision_loss)] // Canvas dimensions fit in f32 mantissa (max ~16M)
+    fn render_element_quad_with_opacity(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        view: &wgpu::TextureView,
+        element: &Element,
+        theme: &Theme,
+        is_first: bool,
+        opacity: f32,
+    ) {
+        let opacity = opacity * element.effective_opacity();
+        let rect = [
+            element.transform.x,
+            element.transform.y,
+            element.transform.width,
+            element.transform.height,
+        ];
+
+        let mut is_first = is_first;
+        if let Some((shadow_rect, shadow_color, shadow_style)) =
+            quad::shadow_quad(element, theme, rect, opacity)
+        {
+            self.render_element_quad_impl(
+                encoder,
+                view,
+                shadow_rect,
+                is_first,
+                shadow_color,
+                &shadow_style,
+            );
+            is_first = false;
+        }
+
+        // Determine element color based on kind and style
+        let mut color = quad::element_color(element, theme);
+        // Apply opacity multiplier from parent OverlayLayer and element style
+        color[3] *= opacity;
+        let style = quad::element_quad_style(element, theme, opacity);
+
+        self.render_element_quad_impl(encoder, view, rect, is_first, color, &style);
+    }
+
+    /// Queue a colored quad covering `rect` (x, y, width, height) (implementation).
+    fn render_element_quad_impl(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        view: &wgpu::TextureView,
+        rect: [f32; 4],
+        is_first: bool,
+        color: [f32; 4],
+        style: &QuadStyle,
+    ) {
+        let camera = self.batch_target(encoder, view, is_first, self.active_view_projection);
+        let scissor = self.scissor_rect();
+        self.batches.queued.push_quad(
+            BatchKind::Quads,
+            camera,
+            scissor,
+            QuadInstance::new(rect, color, style),
+        );
+    }
+
+    /// Render a single element as a textured quad with optional opacity.
+    fn render_textured_element_with_opacity(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        view: &wgpu::TextureView,
+        element: &Element,
+        texture_view: &wgpu::TextureView,
+        is_first: bool,
+        opacity: f32,
+    ) {
+        let rect = [
+            element.transform.x,
+            element.transform.y,
+            element.transform.width,
+            element.transform.height,
+        ];
+        self.render_textured_quad_impl(
+            encoder,
+            view,
+            rect,
+            texture_view,
+            is_first,
+            opacity * element.effective_opacity(),
+            self.active_view_projection,
+        );
+    }
+
+    /// Queue a texture covering `rect` (x, y, width, height) (implementation).
+    ///
+    /// `view_projection` selects 3D camera mode; `None` draws in screen space.
+    #[allow(clippy::too_many_arguments)]
+    fn render_textured_quad_impl(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        view: &wgpu::TextureView,
+        rect: [f32; 4],
+        texture_view: &wgpu::TextureView,
+        is_first: bool,
+        opacity: f32,
+        view_projection: Option<[f32; 16]>,
+    ) {
+        let camera = self.batch_target(encoder, view, is_first, view_projection);
+        let scissor = self.scissor_rect();
+        // Opacity is applied through the tint's alpha channel
+        self.batches.queued.push_quad(
+            BatchKind::Textured(texture_view.clone()),
+            camera,
+            scissor,
+            QuadInstance::new(rect, [1.0, 1.0, 1.0, opacity], &QuadStyle::FLAT),
+        );
+    }
+
+    /// Load the texture for an image `src`, returning its content hash.
+    ///
+    /// Returns `Ok(None)` while a remote source's bytes have not been
+    /// supplied. Sources that fail to load are remembered, so the error is
+    /// only reported once.
+    fn prepare_image(&mut self, src: &str) -> RenderResult<Option<u64>> {
+        match self.images.lookup(src) {
+            Lookup::Hit(hash) => return Ok(Some(hash)),
+            Lookup::Failed => return Ok(None),
+            Lookup::Miss => {}
+        }
+
+        let bytes = match ImageSource::parse(src) {
+            ImageSource::Remote(_) => {
+                let Some(bytes) = self.remote_images.get(src) else {
+                    self.pending_images.insert(src.to_string());
+                    return Ok(None);
+                };
+                Ok(bytes.clone())
+            }
+            source => source.read().map(Option::unwrap_or_default),
+        };
+        let loaded = bytes.and_then(|bytes| {
+            let hash = content_hash(&bytes);
+            if !self.images.touch(hash) {
+                let texture_data = load_image_from_bytes(&bytes)?;
+                let label = format!("Image: {hash:016x}");
+                let cached = self.texture_from_rgba(
+                    &texture_data.data,
+                    texture_data.width,
+                    texture_data.height,
+                    &label,
+                )?;
+                self.images.insert(hash, cached, texture_data.data.len());
+                tracing::debug!(
+                    "Created image texture {}x{}",
+                    texture_data.width,
+                    texture_data.height
+                );
+            }
+            Ok(hash)
+        });
+
+        self.images.set_source(src, loaded.as_ref().ok().copied());
+        loaded.map(Some)
+    }
+
+    /// Render a video element to a texture, using placeholder if stream not available.
+    ///
+    /// For video elements, we check if a video texture exists for the `stream_id`.
+    /// If not, we create a placeholder texture. The actual video frames are
+    /// uploaded separately via `update_video_frame()`.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn render_video_texture(&mut self, element: &Element, stream_id: &str) -> RenderResult<()> {
+        let key = element.id.to_string();
+
+        // Skip if already cached
+        if self.texture_cache.contains_key(&key) {
+            return Ok(());
+        }
+
+        // Check if we have a video texture for this stream
+        let video_key = Self::video_texture_key(stream_id);
+        if self.video_textures.contains_key(&video_key) {
+            tracing::debug!(
+                "Video stream {stream_id} has cached texture, using placeholder for now"
+            );
+        }
+
+        // Create placeholder texture for video (dark gray)
+        let width = element.transform.width.max(1.0) as u32;
+        let height = element.transform.height.max(1.0) as u32;
+        let placeholder = crate::image::create_solid_color(width, height, 32, 32, 32, 255);
+
+        // Create GPU texture
+        let label = format!("Video: {key}");
+        let cached = self.texture_from_rgba(
+            &placeholder.data,
+            placeholder.width,
+            placeholder.height,
+            &label,
+        )?;
+        self.texture_cache.insert(key, cached);
+
+        tracing::debug!(
+            "Created video placeholder texture {}x{} for stream {stream_id}",
+            width,
+            height
+        );
+
+        Ok(())
+    }
+
+    /// Update a video frame for a specific stream.
+    ///
+    /// Call this when new video frame data is available from a WebRTC stream
+    /// or media playback. The frame data should be RGBA format.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the texture cannot be created or dimensions overflow.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    pub fn update_video_frame(
+        &mut self,
+        stream_id: &str,
+        width: u32,
+        height: u32,
+        rgba_data: &[u8],
+    ) -> RenderResult<()> {
+        let expected_size = (width as usize)
+            .checked_mul(height as usize)
+            .and_then(|pixels| pixels.checked_mul(4))
+            .ok_or_else(|| {
+                RenderError::Frame(format!(
+                    "Video frame dimensions {width}x{height} would overflow"
+                ))
+            })?;
+        if rgba_data.len() != expected_size {
+            return Err(RenderError::Frame(format!(
+                "Invalid video frame data size: expected {expected_size}, got {}",
+                rgba_data.len()
+            )));
+        }
+
+        let video_key = Self::video_texture_key(stream_id);
+        let label = format!("Video Stream: {stream_id}");
+
+        let cached = self.texture_from_rgba(rgba_data, width, height, &label)?;
+        self.video_textures.insert(video_key, cached);
+
+        tracing::trace!(
+            "Updated video frame {}x{} for stream {stream_id}",
+            width,
+            height
+        );
+
+        Ok(())
+    }
+
+    /// Remove a video stream texture.
+    ///
+    /// Call this when a video stream ends.
+    pub fn remove_video_stream(&mut self, stream_id: &str) {
+        let video_key = Self::video_texture_key(stream_id);
+        self.video_textures.remove(&video_key);
+        tracing::debug!("Removed video stream texture: {stream_id}");
+    }
+
+    /// Clear all video stream textures.
+    pub fn clear_video_textures(&mut self) {
+        self.video_textures.clear();
+        tracing::debug!("Cleared all video textures");
+    }
+
+    /// Generate a consistent cache key for a video stream.
+    #[inline]
+    fn video_texture_key(stream_id: &str) -> String {
+        format!("video:{stream_id}")
+    }
+
+    /// Render scene elements to a texture view.
+    ///
+    /// Handles both empty scenes (clears to background) and scenes with elements.
+    /// Image elements draw textures and charts their tessellated shapes;
+    /// other elements draw colored quads.
+    /// The scene's branding is drawn last, pinned to the surface.
+    ///
+    /// With `clear` false the target keeps its contents, for repainting a
+    /// damaged region that was already painted with the background.
+    #[allow(clippy::cast_precision_loss)] // Surface dimensions fit in f32 mantissa
+    fn render_scene_elements(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        view: &wgpu::TextureView,
+        scene: &Scene,
+        clear: bool,
+    ) {
+        let (mut elements, scene_count, precull) = self.frame_elements(scene);
+        self.last_frame = FrameStats::new(scene_count, precull);
+        self.batches.begin_frame();
+        if let Some(branding) = scene.branding() {
+            elements.extend(branding.overlay(self.width as f32, self.height as f32));
+        }
+
+        if elements.is_empty() {
+            self.chart_models.clear();
+            // Clear to background color
+            if clear {
+                self.clear_view(encoder, view, self.clear_color());
+            }
+            return;
+        }
+
+        // Build opacity map from OverlayLayers.
+        // Children of an OverlayLayer inherit its opacity.
+        // Nested overlays multiply opacities (parent * child).
+        let opacity_map = quad::build_opacity_map(&elements);
+
+        // First pass: Lay out Chart elements, prepare textures for Image and
+        // Video elements, and meshes for Model3D elements.
+        // Note: Texture preparation errors are logged but not propagated to avoid
+        // a single failed element from blocking the entire render loop. The element
+        // will simply not appear or show a placeholder.
+        self.images.begin_frame();
+        self.models.poll();
+        let mut images = HashMap::new();
+        let mut charts = HashMap::new();
+        let mut chart_scenes = HashMap::new();
+        for element in &elements {
+            match &element.kind {
+                ElementKind::Chart { .. } => {
+                    if let Some((geometry, scene)) = self.prepare_chart(element) {
+                        charts.insert(element.id, geometry);
+                        chart_scenes.extend(scene.map(|scene| (element.id, scene)));
+                    }
+                }
+                ElementKind::Image { src, .. } => match self.prepare_image(src) {
+                    Ok(Some(hash)) => {
+                        images.insert(element.id, hash);
+                    }
+                    Ok(None) => {}
+                    Err(e) => tracing::warn!("Failed to load image {src:.64}: {e}"),
+                },
+                ElementKind::Video { stream_id, .. } => {
+                    if let Err(e) = self.render_video_texture(element, stream_id) {
+                        tracing::warn!("Failed to render video texture: {e}");
+                    }
+                }
+                ElementKind::Model3D { src, .. } => self.prepare_model(src),
+                _ => {}
+            }
+        }
+        self.chart_models
+            .retain(|id, _| chart_scenes.contains_key(id));
+        self.prepare_shaders(&elements);
+
+        let glyphs = self.prepare_text(&elements, &charts, scene.theme(), &opacity_map);
+
+        // Second pass: Render all elements.
+        // Descendants of translucent groups are drawn inside the group's layer.
+        let clips = scene.clip_rects();
+        let layered = self.layered_descendants(&elements);
+        let ctx = SceneRenderContext {
+            lookup: elements.iter().map(|e| (e.id, e)).collect(),
+            opacity_map: &opacity_map,
+            clips: &clips,
+            callout_tails: elements
+                .iter()
+                .filter_map(|e| scene.callout_tail(e.id).map(|tail| (e.id, tail)))
+                .collect(),
+            theme: scene.theme(),
+            glyphs,
+            charts: Self::prepare_charts(&elements, &charts, &opacity_map),
+            chart_scenes,
+            images,
+        };
+
+        let mut is_first = clear;
+        let mut culled = precull;
+        for element in &elements {
+            if layered.contains(&element.id) || element.screen_anchored {
+                continue;
+            }
+            // Containers are always visited since children may extend beyond them
+            if let Some(bounds) = &self.cull_bounds {
+                if element.kind.children().is_empty()
+                    && !OrientedRect::from_transform(&element.transform).intersects_bounds(bounds)
+                {
+                    culled += 1;
+                    continue;
+                }
+            }
+            let drawn = if let Some(group_opacity) = self.layer_opacity(element) {
+                self.render_group_layer(encoder, view, element, group_opacity, &ctx, is_first, 0)
+            } else {
+                self.render_single_element(encoder, view, element, &ctx, is_first)
+            };
+            is_first &= !drawn;
+        }
+        self.active_clip = None;
+        self.last_frame = FrameStats::new(scene_count, culled);
+
+        if is_first {
+            // Nothing was drawn (e.g. only overlay containers); still clear the frame
+            self.clear_view(encoder, view, self.clear_color());
+        }
+
+        if let Some(spotlight) = scene.spotlight() {
+            self.render_spotlight(encoder, view, spotlight, &elements, &layered, &ctx);
+        }
+        self.render_screen_anchored(encoder, view, &elements, &layered, &ctx);
+        self.finish_batches(encoder);
+        self.last_frame.draw_calls = self.batches.draw_calls;
+        self.last_frame.triangles = self.batches.triangles;
+        self.last_frame.texture_bytes = self.texture_bytes();
+    }
+
+    /// Bytes of GPU memory held by the backend's textures: cached images,
+    /// video frames, the glyph atlas, and the retained, multisampled,
+    /// post-processing and depth targets. The surface's own textures are not counted.
+    fn texture_bytes(&self) -> u64 {
+        fn bytes(texture: &wgpu::Texture) -> u64 {
+            let size = texture.size();
+            let texel = u64::from(texture.format().block_copy_size(None).unwrap_or(4));
+            u64::from(size.width)
+                * u64::from(size.height)
+                * u64::from(size.depth_or_array_layers)
+                * u64::from(texture.sample_count())
+                * texel
+        }
+        let cached = self
+            .texture_cache
+            .values()
+            .chain(self.video_textures.values())
+            .chain(self.text_atlas.as_ref().map(|atlas| &atlas.texture))
+            .map(|cached| bytes(&cached.texture));
+        let targets = self
+            .retained
+            .iter()
+            .chain(self.frame_msaa.as_ref())
+            .chain(self.post.iter().flat_map(PostProcessor::textures))
+            .map(bytes);
+        let depth = self
+            .depth_target
+            .as_ref()
+            .map_or(0, |(width, height, samples, _)| {
+                let texel = DEPTH_FORMAT.block_copy_size(None).unwrap_or(4);
+                u64::from(*width) * u64::from(*height) * u64::from(*samples) * u64::from(texel)
+            });
+        cached.chain(targets).sum::<u64>() + self.images.size() as u64 + depth
+    }
+
+    /// Post-processing pipelines for the target format, created on first
+    /// use.
+    fn post_processor(&mut self) -> &mut PostProcessor {
+        let format = self.target_format;
+        if self
+            .post
+            .as_ref()
+            .is_some_and(|post| post.format() != format)
+        {
+            self.post = None;
+        }
+        self.post
+            .get_or_insert_with(|| PostProcessor::new(&self.device, format))
+    }
+
+    /// A `width` x `height` texture to draw a frame into before its effects
+    /// run.
+    fn post_frame_view(&mut self, width: u32, height: u32) -> wgpu::TextureView {
+        let device = Arc::clone(&self.device);
+        self.post_processor().frame_view(&device, (width, height))
+    }
+
+    /// Run `effects` from `input` into `output`, both the size of the
+    /// target, after the draws queued into `input`.
+    fn apply_effects(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        input: &wgpu::TextureView,
+        output: &PostOutput<'_>,
+        effects: &[PostEffect],
+    ) {
+        self.flush_batches(encoder);
+        let (device, size) = (Arc::clone(&self.device), (self.width, self.height));
+        let passes = self
+            .post_processor()
+            .apply(&device, encoder, input, output, size, effects);
+        // Each pass is one full-screen triangle
+        self.batches.draw_calls += passes;
+        self.batches.triangles += passes;
+    }
+
+    /// Run the frame effects from the drawn scene into `output`, encoding
+    /// for it if needed, and count their passes in the frame's stats.
+    fn apply_frame_effects(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        scene_view: &wgpu::TextureView,
+        output: &PostOutput<'_>,
+        effects: &[PostEffect],
+    ) {
+        self.apply_effects(encoder, scene_view, output, effects);
+        self.last_frame.draw_calls = self.batches.draw_calls;
+        self.last_frame.triangles = self.batches.triangles;
+    }
+
+    /// Opacity to draw `element` into its own layer at, if it needs one:
+    /// translucent groups, and overlay layers or groups with effects.
+    fn layer_opacity(&self, element: &Element) -> Option<f32> {
+        element.kind.group_opacity().or_else(|| {
+            let container = matches!(
+                element.kind,
+                ElementKind::OverlayLayer { .. } | ElementKind::Group { .. }
+            );
+            (container && !self.post_effects.layer(element.id).is_empty()).then_some(1.0)
+        })
+    }
+
+    /// Close a frame's timing before its encoder is finished.
+    fn finish_timed(&self, encoder: &mut wgpu::CommandEncoder, timed: bool) {
+        if let Some(timer) = self.gpu_timer.as_ref().filter(|_| timed) {
+            timer.end(encoder);
+        }
+    }
+
+    /// Start reading a timed frame's timestamps back once it is submitted.
+    fn submitted_timed(&mut self, timed: bool) {
+        if let Some(timer) = self.gpu_timer.as_mut().filter(|_| timed) {
+            timer.submitted();
+        }
+    }
+
+    /// Elements to draw this frame: the renderer's visible set if it gave
+    /// one, else every shown element. Also returns the element count and
+    /// how many were culled already, for frame stats.
+    fn frame_elements(&mut self, scene: &Scene) -> (Vec<Element>, usize, usize) {
+        if let Some(visible) = self.visible.take() {
+            let elements = visible.elements(scene).cloned().collect();
+            return (elements, visible.total(), visible.culled());
+        }
+        let elements: Vec<_> = scene
+            .elements()
+            .filter(|e| scene.is_shown(e))
+            .cloned()
+            .collect();
+        let count = elements.len();
+        (elements, count, 0)
+    }
+
+    /// Draw screen-anchored elements over everything else, in viewport
+    /// pixels with the scene camera taken off.
+    fn render_screen_anchored(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        view: &wgpu::TextureView,
+        elements: &[Element],
+        layered: &HashSet<ElementId>,
+        ctx: &SceneRenderContext<'_>,
+    ) {
+        let mut pinned = elements
+            .iter()
+            .filter(|e| e.screen_anchored && !layered.contains(&e.id))
+            .peekable();
+        if pinned.peek().is_none() {
+            return;
+        }
+        let view_projection = self.active_view_projection.take();
+        let camera = self.scene_camera.take();
+        for element in pinned {
+            if let Some(group_opacity) = self.layer_opacity(element) {
+                self.render_group_layer(encoder, view, element, group_opacity, ctx, false, 0);
+            } else {
+                self.render_single_element(encoder, view, element, ctx, false);
+            }
+        }
+        self.active_clip = None;
+        self.active_view_projection = view_projection;
+        self.scene_camera = camera;
+    }
+
+    /// Dim everything drawn so far, then redraw the spotlighted elements
+    /// (and their descendants) on top.
+    ///
+    /// Expiry is not checked here; whoever owns the scene clears expired
+    /// spotlights.
+    #[allow(clippy::cast_precision_loss)] // Canvas dimensions fit in f32 mantissa
+    fn render_spotlight(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        view: &wgpu::TextureView,
+        spotlight: &Spotlight,
+        elements: &[Element],
+        layered: &HashSet<ElementId>,
+        ctx: &SceneRenderContext<'_>,
+    ) {
+        // The overlay covers the whole target regardless of camera
+        let view_projection = self.active_view_projection.take();
+        self.render_element_quad_impl(
+            encoder,
+            view,
+            [0.0, 0.0, self.width as f32, self.height as f32],
+            false,
+            [0.0, 0.0, 0.0, spotlight.dim_opacity],
+            &QuadStyle::FLAT,
+        );
+        self.active_view_projection = view_projection;
+
+        let mut highlighted = HashSet::new();
+        let mut stack = spotlight.element_ids.clone();
+        while let Some(id) = stack.pop() {
+            if highlighted.insert(id) {
+                if let Some(element) = ctx.lookup.get(&id) {
+                    stack.extend(element.kind.children().iter().copied());
+                }
+            }
+        }
+
+        for element in elements {
+            // Layered descendants are drawn by their group unless spotlighted directly
+            if !highlighted.contains(&element.id)
+                || (layered.contains(&element.id) && !spotlight.contains(element.id))
+            {
+                continue;
+            }
+            if let Some(group_opacity) = self.layer_opacity(element) {
+                self.render_group_layer(encoder, view, element, group_opacity, ctx, false, 0);
+            } else {
+                self.render_single_element(encoder, view, element, ctx, false);
+            }
+        }
+        self.active_clip = None;
+    }
+
+    /// Clear a texture view to a solid color.
+    fn clear_view(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        view: &wgpu::TextureView,
+        color: wgpu::Color,
+    ) {
+        encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Clear Pass"),
+            color_attachments: &[Some(
+                self.color_attachment(view, wgpu::LoadOp::Clear(color)),
+            )],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+    }
+
+    /// Render one element (textured or colored quad) with its inherited
+    /// opacity and container clip.
+    ///
+    /// Returns `false` if the element draws nothing itself (overlay containers).
+    fn render_single_element(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        view: &wgpu::TextureView,
+        element: &Element,
+        ctx: &SceneRenderContext<'_>,
+        is_first: bool,
+    ) -> bool {
+        // Skip OverlayLayer containers - they're invisible, only their children render
+        if matches!(element.kind, ElementKind::OverlayLayer { .. }) {
+            return false;
+        }
+
+        // Get opacity from parent OverlayLayer(s), default to 1.0
+        let opacity = ctx.opacity_map.get(&element.id).copied().unwrap_or(1.0);
+        self.active_clip = ctx.clips.get(&element.id).copied();
+
+        // Models still loading fall through to a placeholder quad
+        if self.render_model(encoder, view, element, is_first)
+            || self.render_shader(encoder, view, element, opacity, is_first)
+        {
+            return true;
+        }
+
+        // Check if we have a cached texture for this element
+        let key = element.id.to_string();
+        let image = ctx
+            .images
+            .get(&element.id)
+            .and_then(|h| self.images.get(*h));
+        let texture = image
+            .or_else(|| self.texture_cache.get(&key))
+            .map(|cached| cached.view.clone());
+        if let Some(texture) = texture {
+            self.render_textured_element_with_opacity(
+                encoder, view, element, &texture, is_first, opacity,
+            );
+        } else if let Some(fills) = ctx.charts.get(&element.id) {
+            self.render_fills(encoder, view, fills, is_first);
+            if let Some((rect, camera)) = ctx.chart_scenes.get(&element.id) {
+                self.render_chart_model(encoder, view, element.id, *rect, camera);
+            }
+            if let Some(glyphs) = ctx.glyphs.get(&element.id) {
+                self.render_glyphs(encoder, view, glyphs, false);
+            }
+        } else if let Some(glyphs) = ctx.glyphs.get(&element.id) {
+            // Text only sits on a card when it is filled or selected
+            let has_card = element.selected || element.style.fill.is_some();
+            if has_card {
+                self.render_element_quad_with_opacity(
+                    encoder, view, element, ctx.theme, is_first, opacity,
+                );
+            }
+            self.render_glyphs(encoder, view, glyphs, is_first && !has_card);
+        } else {
+            // Fallback to colored quad for non-textured elements
+            self.render_element_quad_with_opacity(
+                encoder, view, element, ctx.theme, is_first, opacity,
+            );
+            match &element.kind {
+                ElementKind::Code { source, spans, .. } => {
+                    self.render_code_tokens(encoder, view, element, source, spans, opacity);
+                }
+                ElementKind::LogView {
+                    title,
+                    lines,
+                    follow,
+                    ..
+                } => self.render_log_tokens(encoder, view, element, title, lines, *follow, opacity),
+                _ => {}
+            }
+        }
+        if let Some(tail) = ctx.callout_tails.get(&element.id) {
+            self.render_callout_tail(encoder, view, element, ctx.theme, tail, opacity);
+        }
+        true
+    }
+
+    /// Draw a loaded `Model3D` inside its element box, framed by the
+    /// element's camera hint or, without one, so the whole model fits.
+    /// Returns `false` if the model is not ready or the box is offscreen.
+    fn render_model(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        view: &wgpu::TextureView,
+        element: &Element,
+        is_first: bool,
+    ) -> bool {
+        let ElementKind::Model3D {
+            src,
+            camera,
+            lighting,
+            ..
+        } = &element.kind
+        else {
+            return false;
+        };
+        let (Some(model), Some(pose)) = (self.gpu_models.get(src), pose3d::pose(&element.kind))
+        else {
+            return false;
+        };
+        let model = Arc::clone(model);
+        let Some(ndc) = self.element_ndc_rect(element) else {
+            return false;
+        };
+        let camera = camera.unwrap_or_else(|| {
+            let scale = pose.scale.iter().fold(0.0f32, |m, s| m.max(s.abs()));
+            ModelCamera::fit(pose.transform_point(model.center), model.radius * scale)
+        });
+        let default_lighting = Lighting::default();
+        self.render_placed_model(
+            encoder,
+            view,
+            &PlacedModel {
+                model: &model,
+                ndc,
+                pose,
+                camera,
+                lighting: lighting.as_ref().unwrap_or(&default_lighting),
+            },
+            is_first,
+        )
+    }
+
+    /// Compile the sources of the frame's `Shader` elements, dropping the
+    /// pipelines of sources no longer shown.
+    fn prepare_shaders<'a>(&mut self, elements: impl IntoIterator<Item = &'a Element>) {
+        let mut shown = HashSet::new();
+        for element in elements {
+            if let ElementKind::Shader { wgsl, .. } = &element.kind {
+                self.prepare_shader(&element.kind);
+                shown.insert(content_hash(wgsl.as_bytes()));
+            }
+        }
+        self.shader_pipelines.retain(|hash, _| shown.contains(hash));
+    }
+
+    /// Compile a `Shader` element's source into a pipeline, once per
+    /// source. Sources that fail the sandbox or the compiler are remembered
+    /// as failed and draw as placeholders.
+    fn prepare_shader(&mut self, kind: &ElementKind) {
+        let ElementKind::Shader { wgsl, .. } = kind else {
+            return;
+        };
+        let hash = content_hash(wgsl.as_bytes());
+        if self.shader_pipelines.contains_key(&hash) {
+            return;
+        }
+        let pipeline = match shader::check(kind) {
+            Ok(()) => Some(self.create_element_shader_pipeline(wgsl)),
+            Err(e) => {
+                tracing::warn!("Shader element drawn as a placeholder: {e}");
+                None
+            }
+        };
+        self.shader_pipelines.insert(hash, pipeline);
+    }
+
+    /// Create the pipeline for a checked `Shader` element source.
+    fn create_element_shader_pipeline(&self, wgsl: &str) -> wgpu::RenderPipeline {
+        let module = self
+            .device
+            .create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some("Element Shader"),
+                source: wgpu::ShaderSource::Wgsl(shader::wrap(wgsl).into()),
+            });
+        let layout = self
+            .device
+            .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Element Shader Pipeline Layout"),
+                bind_group_layouts: &[&self.uniform_bind_group_layout],
+                push_constant_ranges: &[],
+            });
+        self.device
+            .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some("Element Shader Pipeline"),
+                layout: Some(&layout),
+                vertex: wgpu::VertexState {
+                    module: &module,
+                    entry_point: Some("canvas_vs"),
+                    compilation_options: wgpu::PipelineCompilationOptions::default(),
+                    buffers: &[],
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &module,
+                    entry_point: Some("canvas_fs"),
+                    compilation_options: wgpu::PipelineCompilationOptions::default(),
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format: self.target_format,
+                        blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                }),
+                primitive: wgpu::PrimitiveState::default(),
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState {
+                    count: self.antialiasing.sample_count(),
+                    mask: !0,
+                    alpha_to_coverage_enabled: false,
+                },
+                multiview: None,
+                cache: None,
+            })
+    }
+
+    /// Run a `Shader` element's pipeline over its box. Returns false, so the
+    /// placeholder quad is drawn instead, if the source did not compile.
+    #[allow(clippy::cast_precision_loss)] // Viewport dimensions fit in f32
+    fn render_shader(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        view: &wgpu::TextureView,
+        element: &Element,
+        opacity: f32,
+        is_first: bool,
+    ) -> bool {
+        let ElementKind::Shader { wgsl, uniforms } = &element.kind else {
+            return false;
+        };
+        let Some(Some(pipeline)) = self.shader_pipelines.get(&content_hash(wgsl.as_bytes())) else {
+            return false;
+        };
+        let pipeline = pipeline.clone();
+        let Some(ndc) = self.element_ndc_rect(element) else {
+            return false;
+        };
+        let [left, bottom, right, top] = ndc;
+
+        // The box in target pixels
+        let (vx, vy, vw, vh) = self.viewport_rect();
+        let (vx, vy, vw, vh) = (vx as f32, vy as f32, vw as f32, vh as f32);
+        let rect = [
+            vx + f32::midpoint(left, 1.0) * vw,
+            vy + (1.0 - top) / 2.0 * vh,
+            (right - left) / 2.0 * vw,
+            (top - bottom) / 2.0 * vh,
+        ];
+        let scissor = Self::clip_scissor(rect, self.scissor_rect());
+        if scissor.2 == 0 || scissor.3 == 0 || rect[2] <= 0.0 || rect[3] <= 0.0 {
+            return false;
+        }
+
+        let uniforms = ShaderUniforms {
+            rect: ndc,
+            resolution: [rect[2], rect[3]],
+            time: self.shader_time,
+            opacity: opacity * element.effective_opacity(),
+            params: shader::pack_uniforms(uniforms),
+        };
+        let buffer = self
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Element Shader Uniforms"),
+                contents: bytemuck::cast_slice(&[uniforms]),
+                usage: wgpu::BufferUsages::UNIFORM,
+            });
+        let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Element Shader Bind Group"),
+            layout: &self.uniform_bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: buffer.as_entire_binding(),
+            }],
+        });
+
+        // Shaders draw outside the batches, over whatever is queued
+        self.flush_batches(encoder);
+        if is_first {
+            self.clear_view(encoder, view, self.clear_color());
+        }
+        {
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Element Shader Pass"),
+                color_attachments: &[Some(self.color_attachment(view, wgpu::LoadOp::Load))],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            render_pass.set_viewport(vx, vy, vw, vh, 0.0, 1.0);
+            let (x, y, width, height) = scissor;
+            render_pass.set_scissor_rect(x, y, width, height);
+            render_pass.set_pipeline(&pipeline);
+            render_pass.set_bind_group(0, &bind_group, &[]);
+            render_pass.draw(0..6, 0..1);
+        }
+        self.batches.draw_calls += 1;
+        self.batches.triangles += 2;
+        true
+    }
+
+    /// Draw a 3D chart's meshes over its plot box, which `rect` gives in
+    /// canvas coordinates.
+    fn render_chart_model(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        view: &wgpu::TextureView,
+        id: ElementId,
+        rect: [f32; 4],
+        camera: &ModelCamera,
+    ) {
+        let Some((_, model)) = self.chart_models.get(&id) else {
+            return;
+        };
+        let model = Arc::clone(model);
+        let Some(ndc) = self.canvas_ndc_rect(rect) else {
+            return;
+        };
+        self.render_placed_model(
+            encoder,
+            view,
+            &PlacedModel {
+                model: &model,
+                ndc,
+                pose: pose3d::Transform3D::IDENTITY,
+                camera: *camera,
+                lighting: &Lighting::default(),
+            },
+            false,
+        );
+    }
+
+    /// Draw a model inside its box, seen through its camera. Returns `false`
+    /// if the box is offscreen.
+    #[allow(clippy::cast_precision_loss)] // Viewport dimensions fit in f32 mantissa
+    fn render_placed_model(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        view: &wgpu::TextureView,
+        placed: &PlacedModel<'_>,
+        is_first: bool,
+    ) -> bool {
+        let [left, bottom, right, top] = placed.ndc;
+
+        // The box in target pixels
+        let (vx, vy, vw, vh) = self.viewport_rect();
+        let (vx, vy, vw, vh) = (vx as f32, vy as f32, vw as f32, vh as f32);
+        let rect = [
+            vx + f32::midpoint(left, 1.0) * vw,
+            vy + (1.0 - top) / 2.0 * vh,
+            (right - left) / 2.0 * vw,
+            (top - bottom) / 2.0 * vh,
+        ];
+        let scissor = Self::clip_scissor(rect, self.scissor_rect());
+        if scissor.2 == 0 || scissor.3 == 0 || rect[2] <= 0.0 || rect[3] <= 0.0 {
+            return false;
+        }
+
+        let camera = Camera::from_model(&placed.camera);
+        let projection = camera.projection_matrix(rect[2] / rect[3]);
+        // Squeeze the camera's full clip space into the box
+        #[rustfmt::skip]
+        let fit = Mat4 { data: [
+            (right - left) / 2.0, 0.0, 0.0, 0.0,
+            0.0, (top - bottom) / 2.0, 0.0, 0.0,
+            0.0, 0.0, 1.0, 0.0,
+            f32::midpoint(left, right), f32::midpoint(bottom, top), 0.0, 1.0,
+        ] };
+        let clip_from_model = fit
+            .mul(&projection)
+            .mul(&camera.view_matrix())
+            .mul(&Mat4::from_pose(&placed.pose));
+
+        // Models draw outside the batches, over whatever is queued
+        self.flush_batches(encoder);
+        if is_first {
+            self.clear_view(encoder, view, self.clear_color());
+        }
+        let depth = self.depth_view(self.width, self.height);
+        let model = placed.model;
+        self.batches.draw_calls += model.meshes.len();
+        self.batches.triangles += model
+            .meshes
+            .iter()
+            .map(|mesh| mesh.index_count as usize / 3)
+            .sum::<usize>();
+        self.draw_model(
+            encoder,
+            view,
+            &depth,
+            model,
+            &ModelDraw {
+                clip_from_model,
+                pose: placed.pose,
+                eye: placed.camera.position(),
+                lighting: placed.lighting,
+                viewport: [vx, vy, vw, vh],
+                scissor,
+            },
+            wgpu::LoadOp::Load,
+        );
+        true
+    }
+
+    /// An element's box in normalized device coordinates (left, bottom,
+    /// right, top), through the active camera. `None` if part of it is
+    /// behind the camera.
+    fn element_ndc_rect(&self, element: &Element) -> Option<[f32; 4]> {
+        let t = &element.transform;
+        self.canvas_ndc_rect([t.x, t.y, t.width, t.height])
+    }
+
+    /// A box in canvas coordinates (x, y, width, height) in normalized
+    /// device coordinates, as for [`Self::element_ndc_rect`].
+    #[allow(clippy::cast_precision_loss)] // Canvas dimensions fit in f32 mantissa
+    fn canvas_ndc_rect(&self, [x, y, width, height]: [f32; 4]) -> Option<[f32; 4]> {
+        let corners = [
+            (x, y),
+            (x + width, y),
+            (x, y + height),
+            (x + width, y + height),
+        ];
+        let mut rect = [
+            f32::INFINITY,
+            f32::INFINITY,
+            f32::NEG_INFINITY,
+            f32::NEG_INFINITY,
+        ];
+        for (x, y) in corners {
+            let (nx, ny) = match self.active_view_projection {
+                Some(m) => {
+                    let w = m[3] * x + m[7] * y + m[15];
+                    if w <= f32::EPSILON {
+                        return None;
+                    }
+                    (
+                        (m[0] * x + m[4] * y + m[12]) / w,
+                        (m[1] * x + m[5] * y + m[13]) / w,
+                    )
+                }
+                // Same mapping as the 2D path of the quad shader
+                None => (
+                    x / self.width.max(1) as f32 * 2.0 - 1.0,
+                    1.0 - y / self.height.max(1) as f32 * 2.0,
+                ),
+            };
+            rect = [
+                rect[0].min(nx),
+                rect[1].min(ny),
+                rect[2].max(nx),
+                rect[3].max(ny),
+            ];
+        }
+        Some(rect)
+    }
+
+    /// Draw every mesh of a model in one depth-tested pass.
+    fn draw_model(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        view: &wgpu::TextureView,
+        depth: &wgpu::TextureView,
+        model: &GpuModel,
+        draw: &ModelDraw<'_>,
+        load: wgpu::LoadOp<wgpu::Color>,
+    ) {
+        let bind_groups = self.mesh_bind_groups(model, draw);
+
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Mesh Render Pass"),
+            color_attachments: &[Some(self.color_attachment(view, load))],
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: depth,
+                depth_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(1.0),
+                    store: wgpu::StoreOp::Discard,
+                }),
+                stencil_ops: None,
+            }),
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
 
-        match &element.kind {
-            ElementKind::Chart { .. } => [0.9, 0.95, 1.0, 1.0], // Light blue for charts
-            ElementKind::Image { .. } => [0.95, 0.95, 0.95, 1.0], // Light gray placeholder
-            ElementKind::Model3D { .. } => [0.8, 0.9, 0.8, 1.0], // Light green for 3D
-            ElementKind::Video { .. } => [0.2, 0.2, 0.2, 1.0],  // Dark for video
-            ElementKind::OverlayLayer { opacity, .. } => [1.0, 1.0, 1.0, *opacity], // Transparent
-            ElementKind::Text { color, .. } => {
-                // Parse hex color
-                Self::parse_hex_color(color).unwrap_or([0.0, 0.0, 0.0, 1.0])
-            }
-            ElementKind::Group { .. } => [0.95, 0.95, 0.9, 0.5], // Transparent yellow for groups
+        let [x, y, width, height] = draw.viewport;
+        render_pass.set_viewport(x, y, width, height, 0.0, 1.0);
+        let (x, y, width, height) = draw.scissor;
+        render_pass.set_scissor_rect(x, y, width, height);
+        render_pass.set_pipeline(&self.mesh_pipeline);
+        for (mesh, bind_group) in model.meshes.iter().zip(&bind_groups) {
+            render_pass.set_bind_group(0, bind_group, &[]);
+            render_pass.set_vertex_buffer(0, mesh.vertices.slice(..));
+            render_pass.set_index_buffer(mesh.indices.slice(..), wgpu::IndexFormat::Uint32);
+            render_pass.draw_indexed(0..mesh.index_count, 0, 0..1);
         }
     }
 
-    /// Parse a hex color string to RGBA floats.
-    fn parse_hex_color(hex: &str) -> Option<[f32; 4]> {
-        let hex = hex.trim_start_matches('#');
+    /// One uniform bind group per mesh of a model, with its material and
+    /// the draw's transforms and lights.
+    fn mesh_bind_groups(&self, model: &GpuModel, draw: &ModelDraw<'_>) -> Vec<wgpu::BindGroup> {
+        let mut lights = [MeshLight::default(); MAX_MODEL_LIGHTS];
+        for (slot, light) in lights.iter_mut().zip(&draw.lighting.lights) {
+            let (position, kind, color, intensity) = match light {
+                Light::Directional {
+                    direction,
+                    color,
+                    intensity,
+                } => (direction, 0.0, color, intensity),
+                Light::Point {
+                    position,
+                    color,
+                    intensity,
+                } => (position, 1.0, color, intensity),
+            };
+            let [r, g, b, _] = color.to_rgba_f32();
+            *slot = MeshLight {
+                position: [position[0], position[1], position[2], kind],
+                color: [r * intensity, g * intensity, b * intensity, 1.0],
+            };
+        }
+        // Normals turn with the pose but scale inversely
+        let normal_from_model = pose3d::Transform3D {
+            position: [0.0; 3],
+            scale: draw
+                .pose
+                .scale
+                .map(|s| 1.0 / s.abs().max(f32::EPSILON) * s.signum()),
+            ..draw.pose
+        }
+        .matrix();
+        #[allow(clippy::cast_precision_loss)] // At most MAX_MODEL_LIGHTS
+        let light_count = draw.lighting.lights.len().min(MAX_MODEL_LIGHTS) as f32;
+        let [ex, ey, ez] = draw.eye;
 
-        // Helper to parse a hex byte at a given offset
-        let parse_byte = |offset: usize| -> Option<f32> {
-            let byte = u8::from_str_radix(&hex[offset..offset + 2], 16).ok()?;
-            Some(f32::from(byte) / 255.0)
-        };
+        model
+            .meshes
+            .iter()
+            .map(|mesh| {
+                let uniforms = MeshUniforms {
+                    clip_from_model: draw.clip_from_model.data,
+                    world_from_model: draw.pose.matrix(),
+                    normal_from_model,
+                    base_color: mesh.material.base_color,
+                    emissive: [
+                        mesh.material.emissive[0],
+                        mesh.material.emissive[1],
+                        mesh.material.emissive[2],
+                        0.0,
+                    ],
+                    params: [
+                        mesh.material.metallic,
+                        mesh.material.roughness,
+                        draw.lighting.ambient,
+                        light_count,
+                    ],
+                    eye: [ex, ey, ez, 1.0],
+                    lights,
+                };
+                let buffer = self
+                    .device
+                    .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                        label: Some("Mesh Uniform Buffer"),
+                        contents: bytemuck::cast_slice(&[uniforms]),
+                        usage: wgpu::BufferUsages::UNIFORM,
+                    });
+                self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+                    label: Some("Mesh Bind Group"),
+                    layout: &self.uniform_bind_group_layout,
+                    entries: &[wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: buffer.as_entire_binding(),
+                    }],
+                })
+            })
+            .collect()
+    }
 
-        match hex.len() {
-            6 => Some([parse_byte(0)?, parse_byte(2)?, parse_byte(4)?, 1.0]),
-            8 => Some([
-                parse_byte(0)?,
-                parse_byte(2)?,
-                parse_byte(4)?,
-                parse_byte(6)?,
-            ]),
-            _ => None,
+    /// Draw code as one bar per token in its syntax color (the quad pipeline
+    /// has no glyphs), laid out on the monospace grid the DOM renderer uses.
+    #[allow(clippy::cast_precision_loss)] // Line and column counts are small
+    fn render_code_tokens(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        view: &wgpu::TextureView,
+        element: &Element,
+        source: &str,
+        spans: &[HighlightSpan],
+        opacity: f32,
+    ) {
+        /// Bound the number of bars queued for large snippets.
+        const MAX_BARS: usize = 512;
+        const TAB_COLUMNS: usize = 4;
+
+        let t = &element.transform;
+        let opacity = opacity * element.effective_opacity();
+        let char_width = CODE_FONT_SIZE * CODE_CHAR_WIDTH;
+        let bar_height = CODE_FONT_SIZE * 0.6;
+        let right = t.x + t.width - CODE_PADDING;
+        let bottom = t.y + t.height - CODE_PADDING;
+
+        let mut bars = 0;
+        for (row, line) in highlight::lines(source, spans).iter().enumerate() {
+            let top = t.y + CODE_PADDING + row as f32 * CODE_LINE_HEIGHT;
+            if top + CODE_LINE_HEIGHT > bottom {
+                break;
+            }
+            let y = top + (CODE_LINE_HEIGHT - bar_height) / 2.0;
+
+            // Runs of non-whitespace characters become bars
+            let mut column = 0;
+            for (kind, text) in line {
+                let Ok(color) = Color::parse(kind.color()) else {
+                    continue;
+                };
+                let mut color = color.to_rgba_f32();
+                color[3] *= opacity;
+                let mut run_start = None;
+                for c in text.chars().chain(std::iter::once(' ')) {
+                    if c.is_whitespace() {
+                        if let Some(start) = run_start.take() {
+                            let x = t.x + CODE_PADDING + start as f32 * char_width;
+                            let width = ((column - start) as f32 * char_width).min(right - x);
+                            if width > 0.0 && bars < MAX_BARS {
+                                let rect = [x, y, width, bar_height];
+                                self.render_element_quad_impl(
+                                    encoder,
+                                    view,
+                                    rect,
+                                    false,
+                                    color,
+                                    &QuadStyle::FLAT,
+                                );
+                                bars += 1;
+                            }
+                        }
+                    } else if run_start.is_none() {
+                        run_start = Some(column);
+                    }
+                    column += if c == '\t' { TAB_COLUMNS } else { 1 };
+                }
+                // The sentinel space is not part of the line
+                column -= 1;
+            }
         }
     }
 
-    /// Render scene elements to a texture view.
-    ///
-    /// Handles both empty scenes (clears to background) and scenes with elements.
-    /// For Chart and Image elements, renders textures; otherwise renders colored quads.
-    fn render_scene_elements(
+    /// Draw a log view's visible rows as bars in their ANSI colors, over
+    /// their ANSI backgrounds, on the same grid as code.
+    #[allow(clippy::cast_precision_loss)] // Line and column counts are small
+    #[allow(clippy::too_many_arguments)]
+    fn render_log_tokens(
         &mut self,
         encoder: &mut wgpu::CommandEncoder,
         view: &wgpu::TextureView,
-        scene: &Scene,
+        element: &Element,
+        title: &str,
+        lines: &[String],
+        follow: bool,
+        opacity: f32,
     ) {
-        let elements: Vec<_> = scene.elements().cloned().collect();
+        /// Bound the number of quads queued for busy logs.
+        const MAX_BARS: usize = 512;
+
+        let t = &element.transform;
+        let opacity = opacity * element.effective_opacity();
+        let char_width = CODE_FONT_SIZE * CODE_CHAR_WIDTH;
+        let bar_height = CODE_FONT_SIZE * 0.6;
+        let left = t.x + CODE_PADDING;
+        let right = t.x + t.width - CODE_PADDING;
+        let rgba = |color: Color| {
+            let mut color = color.to_rgba_f32();
+            color[3] *= opacity;
+            color
+        };
 
-        if elements.is_empty() {
-            // Clear to background color
-            encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: Some("Clear Pass"),
-                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+        let mut bars = 0;
+        let mut bar = |renderer: &mut Self, rect: [f32; 4], color: [f32; 4]| {
+            let width = rect[2].min(right - rect[0]);
+            if width > 0.0 && bars < MAX_BARS {
+                renderer.render_element_quad_impl(
+                    encoder,
                     view,
-                    resolve_target: None,
-                    ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(self.background_color),
-                        store: wgpu::StoreOp::Store,
-                    },
-                })],
-                depth_stencil_attachment: None,
-                timestamp_writes: None,
-                occlusion_query_set: None,
-            });
-            return;
+                    [rect[0], rect[1], width, rect[3]],
+                    false,
+                    color,
+                    &QuadStyle::FLAT,
+                );
+                bars += 1;
+            }
+        };
+        if !title.is_empty() {
+            let y = t.y + CODE_PADDING + (CODE_LINE_HEIGHT - bar_height) / 2.0;
+            let width = title.chars().count() as f32 * char_width;
+            let color = Color::parse(LOG_FOREGROUND).unwrap_or(Color::WHITE);
+            bar(self, [left, y, width, bar_height], rgba(color));
         }
-
-        // Build opacity map from OverlayLayers.
-        // Children of an OverlayLayer inherit its opacity.
-        // Nested overlays multiply opacities (parent * child).
-        let opacity_map = Self::build_opacity_map(&elements);
-
-        // First pass: Prepare textures for Chart, Image, and Video elements.
-        // Note: Texture preparation errors are logged but not propagated to avoid
-        // a single failed element from blocking the entire render loop. The element
-        // will simply not appear or show a placeholder.
-        for element in &elements {
-            match &element.kind {
-                ElementKind::Chart { chart_type, data } => {
-                    if let Err(e) = self.render_chart_texture(element, chart_type, data) {
-                        tracing::warn!("Failed to render chart texture: {e}");
-                    }
+        let body_top = t.y + log_view::body_top(title);
+        let range = log_view::visible_range(title, lines.len(), t.height, follow);
+        for (row, line) in lines[range].iter().enumerate() {
+            let top = body_top + row as f32 * CODE_LINE_HEIGHT;
+            let y = top + (CODE_LINE_HEIGHT - bar_height) / 2.0;
+            let mut column = 0;
+            for span in log_view::spans(line) {
+                let start = column;
+                let x = |column: usize| left + column as f32 * char_width;
+                let width = span.text.chars().count();
+                if let Some(background) = span.style.background() {
+                    let rect = [x(start), top, width as f32 * char_width, CODE_LINE_HEIGHT];
+                    bar(self, rect, rgba(background));
                 }
-                ElementKind::Image { src, .. } => {
-                    if let Err(e) = self.render_image_texture(element, src) {
-                        tracing::warn!("Failed to render image texture: {e}");
-                    }
-                }
-                ElementKind::Video { stream_id, .. } => {
-                    if let Err(e) = self.render_video_texture(element, stream_id) {
-                        tracing::warn!("Failed to render video texture: {e}");
+                // Runs of non-whitespace characters become bars
+                let color = rgba(span.style.foreground());
+                let mut run_start = None;
+                for c in span.text.chars().chain(std::iter::once(' ')) {
+                    if c.is_whitespace() {
+                        if let Some(run) = run_start.take() {
+                            let rect = [x(run), y, (column - run) as f32 * char_width, bar_height];
+                            bar(self, rect, color);
+                        }
+                    } else if run_start.is_none() {
+                        run_start = Some(column);
                     }
+                    column += 1;
                 }
-                _ => {}
+                // The sentinel space is not part of the line
+                column -= 1;
             }
         }
+    }
 
-        // Second pass: Render all elements
-        for (i, element) in elements.iter().enumerate() {
-            let is_first = i == 0;
-            let key = element.id.to_string();
+    /// Draw a callout's tail as a dotted leader from the callout edge to its
+    /// tip (the quad pipeline has no triangles), ending in a larger dot.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    #[allow(clippy::cast_precision_loss)] // Dot counts are tiny
+    fn render_callout_tail(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        view: &wgpu::TextureView,
+        element: &Element,
+        theme: &Theme,
+        tail: &[(f32, f32); 3],
+        opacity: f32,
+    ) {
+        const DOT_SPACING: f32 = 12.0;
+        const MAX_DOTS: usize = 24;
 
-            // Skip OverlayLayer containers - they're invisible, only their children render
-            if matches!(element.kind, ElementKind::OverlayLayer { .. }) {
-                continue;
-            }
+        let [base_a, base_b, tip] = *tail;
+        let start = (
+            f32::midpoint(base_a.0, base_b.0),
+            f32::midpoint(base_a.1, base_b.1),
+        );
+        let length = (tip.0 - start.0).hypot(tip.1 - start.1);
+        let dots = ((length / DOT_SPACING) as usize).clamp(1, MAX_DOTS);
+
+        let mut color = quad::element_color(element, theme);
+        color[3] *= opacity * element.effective_opacity();
+        let mut style = QuadStyle::FLAT;
+        for i in 1..=dots {
+            let progress = i as f32 / dots as f32;
+            let size = if i == dots { 8.0 } else { 4.0 };
+            // Round dots: corner radius of half the size
+            style.params[2] = size / 2.0;
+            let (x, y) = (
+                start.0 + (tip.0 - start.0) * progress,
+                start.1 + (tip.1 - start.1) * progress,
+            );
+            let rect = [x - size / 2.0, y - size / 2.0, size, size];
+            self.render_element_quad_impl(encoder, view, rect, false, color, &style);
+        }
+    }
 
-            // Get opacity from parent OverlayLayer(s), default to 1.0
-            let opacity = opacity_map.get(&element.id).copied().unwrap_or(1.0);
+    /// Render a translucent group and its descendants to an offscreen layer,
+    /// run the layer's effects over it, if any, then composite the layer
+    /// onto `view` at the group opacity.
+    ///
+    /// This fades the group as a unit, so overlapping children don't show
+    /// through each other.
+    #[allow(clippy::too_many_arguments)]
+    fn render_group_layer(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        view: &wgpu::TextureView,
+        group: &Element,
+        group_opacity: f32,
+        ctx: &SceneRenderContext<'_>,
+        is_first: bool,
+        depth: usize,
+    ) -> bool {
+        // Guard against cyclic children lists
+        const MAX_DEPTH: usize = 32;
+        if depth > MAX_DEPTH {
+            return false;
+        }
 
-            // Check if we have a cached texture for this element
-            if let Some(cached) = self.texture_cache.get(&key) {
-                self.render_textured_element_with_opacity(
-                    encoder,
-                    view,
-                    element,
-                    &cached.view,
-                    is_first,
-                    opacity,
-                );
-            } else {
-                // Fallback to colored quad for non-textured elements
-                self.render_element_quad_with_opacity(encoder, view, element, is_first, opacity);
-            }
+        let layer_view = self.create_layer_view("Group Layer");
+        let (width, height, format) = (self.width, self.height, self.target_format);
+        self.attach_msaa(&layer_view, width, height, format, false);
+        self.clear_view(encoder, &layer_view, wgpu::Color::TRANSPARENT);
+
+        self.render_single_element(encoder, &layer_view, group, ctx, false);
+        self.render_layer_children(encoder, &layer_view, group, ctx, depth);
+        let effects = self.post_effects.layer(group.id).to_vec();
+        let processed = (!effects.is_empty()).then(|| {
+            let processed = self.create_layer_view("Processed Layer");
+            let output = PostOutput {
+                view: &processed,
+                format: self.target_format,
+                pq_white: None,
+            };
+            self.apply_effects(encoder, &layer_view, &output, &effects);
+            processed
+        });
+
+        // Composite in screen space, clipped by the group's own containers
+        self.active_clip = ctx.clips.get(&group.id).copied();
+        #[allow(clippy::cast_precision_loss)] // Canvas dimensions fit in f32 mantissa
+        let rect = [0.0, 0.0, self.width as f32, self.height as f32];
+        // The layer already has the scene camera applied
+        let view_projection = if self.scene_camera.is_some() {
+            self.active_view_projection.take()
+        } else {
+            None
+        };
+        self.render_textured_quad_impl(
+            encoder,
+            view,
+            rect,
+            processed.as_ref().unwrap_or(&layer_view),
+            is_first,
+            group_opacity,
+            None,
+        );
+        if view_projection.is_some() {
+            self.active_view_projection = view_projection;
         }
+        true
     }
 
-    /// Build a map of element ID to inherited opacity from parent `OverlayLayer`s.
-    ///
-    /// Handles nested overlays by multiplying opacities.
-    fn build_opacity_map(elements: &[Element]) -> HashMap<ElementId, f32> {
-        let mut opacity_map = HashMap::new();
+    /// A transparent-by-default texture the size of the target that can be
+    /// drawn into and sampled.
+    fn create_layer_view(&self, label: &str) -> wgpu::TextureView {
+        self.device
+            .create_texture(&wgpu::TextureDescriptor {
+                label: Some(label),
+                size: wgpu::Extent3d {
+                    width: self.width.max(1),
+                    height: self.height.max(1),
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: self.target_format,
+                usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                    | wgpu::TextureUsages::TEXTURE_BINDING,
+                view_formats: &[],
+            })
+            .create_view(&wgpu::TextureViewDescriptor::default())
+    }
 
-        // Create a lookup from element ID to element for nested resolution
-        let element_lookup: HashMap<_, _> = elements.iter().map(|e| (e.id, e)).collect();
+    /// Render the descendants of a container into a group layer, in z order.
+    fn render_layer_children(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        layer_view: &wgpu::TextureView,
+        container: &Element,
+        ctx: &SceneRenderContext<'_>,
+        depth: usize,
+    ) {
+        let mut children: Vec<&Element> = container
+            .kind
+            .children()
+            .iter()
+            .filter_map(|id| ctx.lookup.get(id).copied())
+            .collect();
+        children.sort_by_key(|e| e.transform.z_index);
+
+        for child in children {
+            if let Some(opacity) = self.layer_opacity(child) {
+                self.render_group_layer(encoder, layer_view, child, opacity, ctx, false, depth + 1);
+            } else if depth < 32 {
+                self.render_single_element(encoder, layer_view, child, ctx, false);
+                self.render_layer_children(encoder, layer_view, child, ctx, depth + 1);
+            }
+        }
+    }
 
-        // Process each OverlayLayer
-        for element in elements {
-            if let ElementKind::OverlayLayer { children, opacity } = &element.kind {
-                // Get the overlay's own inherited opacity (in case it's nested)
-                let parent_opacity = opacity_map.get(&element.id).copied().unwrap_or(1.0);
-                let effective_opacity = parent_opacity * opacity;
-
-                // Apply to all children
-                for child_id in children {
-                    let child_opacity = opacity_map.get(child_id).copied().unwrap_or(1.0);
-                    opacity_map.insert(*child_id, child_opacity * effective_opacity);
-
-                    // If the child is also an OverlayLayer, propagate to its children
-                    if let Some(child_element) = element_lookup.get(child_id) {
-                        if let ElementKind::OverlayLayer {
-                            children: grandchildren,
-                            ..
-                        } = &child_element.kind
-                        {
-                            for grandchild_id in grandchildren {
-                                let gc_opacity =
-                                    opacity_map.get(grandchild_id).copied().unwrap_or(1.0);
-                                opacity_map.insert(*grandchild_id, gc_opacity * effective_opacity);
-                            }
-                        }
-                    }
+    /// Collect every element drawn inside a group's layer.
+    fn layered_descendants(&self, elements: &[Element]) -> HashSet<ElementId> {
+        let lookup: HashMap<_, _> = elements.iter().map(|e| (e.id, e)).collect();
+        let mut layered = HashSet::new();
+        let mut stack: Vec<ElementId> = elements
+            .iter()
+            .filter(|e| self.layer_opacity(e).is_some())
+            .flat_map(|e| e.kind.children().iter().copied())
+            .collect();
+
+        while let Some(id) = stack.pop() {
+            if layered.insert(id) {
+                if let Some(element) = lookup.get(&id) {
+                    stack.extend(element.kind.children().iter().copied());
                 }
             }
         }
+        layered
+    }
 
-        opacity_map
+    /// Run `draw` in 2D mode: apply the scene's zoom/pan, if any, and cull
+    /// offscreen leaves.
+    fn with_scene_camera<T>(
+        &mut self,
+        scene: &Scene,
+        draw: impl FnOnce(&mut Self, &Scene) -> T,
+    ) -> T {
+        self.cull_bounds = Some(scene.visible_bounds());
+        let camera = CameraFrame::from_scene(scene);
+        let result = if camera.approx_eq(&CameraFrame::reset()) {
+            draw(self, scene)
+        } else {
+            self.active_view_projection = Some(self.scene_camera_matrix(&camera));
+            self.scene_camera = Some(camera);
+            let result = draw(self, scene);
+            self.active_view_projection = None;
+            self.scene_camera = None;
+            result
+        };
+        self.cull_bounds = None;
+        result
     }
 
     /// Render to a texture (for headless/offscreen rendering).
     ///
+    /// Returns 8-bit sRGB pixels, BGRA unless a surface chose RGBA; use
+    /// [`Renderer::render_to_image`](crate::Renderer::render_to_image) for RGBA.
+    ///
     /// # Errors
     ///
     /// Returns an error if rendering fails.
     pub fn render_to_texture(&mut self, scene: &Scene) -> RenderResult<Vec<u8>> {
+        let (width, height) = (self.width, self.height);
+        self.render_offscreen(scene, width, height)
+    }
+
+    /// Render into a fresh `width` x `height` texture in the
+    /// [readback format](Self::readback_format) and read its rows back,
+    /// unpadded.
+    fn render_offscreen(
+        &mut self,
+        scene: &Scene,
+        width: u32,
+        height: u32,
+    ) -> RenderResult<Vec<u8>> {
         let texture = self.device.create_texture(&wgpu::TextureDescriptor {
             label: Some("Offscreen Texture"),
             size: wgpu::Extent3d {
-                width: self.width,
-                height: self.height,
+                width,
+                height,
                 depth_or_array_layers: 1,
             },
             mip_level_count: 1,
             sample_count: 1,
             dimension: wgpu::TextureDimension::D2,
-            format: wgpu::TextureFormat::Bgra8UnormSrgb,
+            format: self.readback_format(),
             usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
             view_formats: &[],
         });
@@ -1828,21 +4870,38 @@ impl WgpuBackend {
             .create_command_encoder(&wgpu::CommandEncoderDescriptor {
                 label: Some("Offscreen Encoder"),
             });
+        let timed = self
+            .gpu_timer
+            .as_ref()
+            .is_some_and(|timer| timer.begin(&mut encoder));
+
+        // Extended-range frames are drawn apart and converted to 8 bits
+        let effects = self.post_effects.frame.clone();
+        let post = !effects.is_empty() || texture.format() != self.target_format;
+        let unprocessed = post.then(|| self.post_frame_view(width, height));
+        let scene_view = unprocessed.as_ref().unwrap_or(&view);
+        self.attach_msaa(scene_view, width, height, self.target_format, false);
+        self.render_scene_elements(&mut encoder, scene_view, scene, true);
+        if post {
+            let output = PostOutput {
+                view: &view,
+                format: texture.format(),
+                pq_white: None,
+            };
+            self.apply_frame_effects(&mut encoder, scene_view, &output, &effects);
+        }
+        self.release_msaa();
 
-        self.render_scene_elements(&mut encoder, &view, scene);
-
-        // Copy texture to buffer
-        let buffer_size = u64::from(self.width) * u64::from(self.height) * 4;
+        // Copy texture to buffer; rows are padded to wgpu's copy alignment
+        let bytes_per_row = width * 4;
+        let padded_bytes_per_row = (bytes_per_row + 255) & !255;
         let output_buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
             label: Some("Output Buffer"),
-            size: buffer_size,
+            size: u64::from(padded_bytes_per_row) * u64::from(height),
             usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
             mapped_at_creation: false,
         });
 
-        let bytes_per_row = self.width * 4;
-        let padded_bytes_per_row = (bytes_per_row + 255) & !255;
-
         encoder.copy_texture_to_buffer(
             wgpu::TexelCopyTextureInfo {
                 texture: &texture,
@@ -1855,17 +4914,19 @@ impl WgpuBackend {
                 layout: wgpu::TexelCopyBufferLayout {
                     offset: 0,
                     bytes_per_row: Some(padded_bytes_per_row),
-                    rows_per_image: Some(self.height),
+                    rows_per_image: Some(height),
                 },
             },
             wgpu::Extent3d {
-                width: self.width,
-                height: self.height,
+                width,
+                height,
                 depth_or_array_layers: 1,
             },
         );
 
+        self.finish_timed(&mut encoder, timed);
         self.queue.submit(std::iter::once(encoder.finish()));
+        self.submitted_timed(timed);
 
         // Read back the buffer
         let buffer_slice = output_buffer.slice(..);
@@ -1883,8 +4944,8 @@ impl WgpuBackend {
         let data = buffer_slice.get_mapped_range();
 
         // Remove padding if present
-        let mut result = Vec::with_capacity((self.width * self.height * 4) as usize);
-        for row in 0..self.height {
+        let mut result = Vec::with_capacity((width * height * 4) as usize);
+        for row in 0..height {
             let start = (row * padded_bytes_per_row) as usize;
             let end = start + bytes_per_row as usize;
             result.extend_from_slice(&data[start..end]);
@@ -1900,6 +4961,9 @@ impl WgpuBackend {
     ///
     /// Creates an offscreen texture at the specified quilt dimensions, renders
     /// each view with its camera and viewport, then reads back the pixel data.
+    /// All views are recorded into one command encoder and submitted
+    /// together, so the GPU works through the quilt without waiting on the
+    /// CPU between views.
     ///
     /// # Arguments
     ///
@@ -1907,6 +4971,9 @@ impl WgpuBackend {
     /// * `height` - Total quilt texture height
     /// * `views` - Slice of quilt views, each with camera and viewport info
     /// * `scene` - The scene to render
+    /// * `base_camera` - The camera the views are arranged around, whose
+    ///   focal plane the canvas lies on
+    /// * `depth` - How far canvas elements stand from the focal plane
     ///
     /// # Errors
     ///
@@ -1914,13 +4981,15 @@ impl WgpuBackend {
     /// - Texture creation fails
     /// - View rendering fails
     /// - Buffer readback fails
-    #[allow(clippy::cast_precision_loss)]
+    #[allow(clippy::cast_precision_loss, clippy::too_many_lines)]
     pub fn render_quilt_to_buffer(
         &mut self,
         width: u32,
         height: u32,
         views: &[QuiltView],
         scene: &Scene,
+        base_camera: &Camera,
+        depth: &CanvasDepth,
     ) -> RenderResult<Vec<u8>> {
         // Create offscreen texture at quilt dimensions
         let texture = self.device.create_texture(&wgpu::TextureDescriptor {
@@ -1939,34 +5008,42 @@ impl WgpuBackend {
         });
 
         let texture_view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let depth_view = self.depth_view(width, height);
+        self.attach_msaa(
+            &texture_view,
+            width,
+            height,
+            wgpu::TextureFormat::Bgra8UnormSrgb,
+            false,
+        );
+        self.prepare_scene_models(scene);
 
-        // Render each view with its camera and viewport
+        // Every view and the readback copy go in one encoder and one
+        // submission; each draw has its own uniforms and batched geometry
+        // is staged at increasing offsets, so views don't overwrite each
+        // other's data before the GPU reads it
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Quilt Encoder"),
+            });
+        self.batches.begin_frame();
+        let canvas_to_world =
+            CanvasDepth::canvas_to_world(base_camera, scene.viewport_width, scene.viewport_height);
         for view in views {
-            let aspect = view.width as f32 / view.height.max(1) as f32;
-            let view_matrix = view.camera.view_matrix();
-            let proj_matrix = view.camera.projection_matrix(aspect);
-            let view_proj = proj_matrix.mul(&view_matrix);
-
-            let mut encoder = self
-                .device
-                .create_command_encoder(&wgpu::CommandEncoderDescriptor {
-                    label: Some("Quilt View Encoder"),
-                });
-
-            // Render scene elements for this view
-            let ctx = QuiltRenderContext::new(
-                view_proj.data,
-                view.x_offset,
-                view.y_offset,
-                view.width,
-                view.height,
-                width,
-                height,
+            let ctx = QuiltRenderContext::for_view(view, width, height, &canvas_to_world, *depth);
+            self.render_scene_elements_with_camera(
+                &mut encoder,
+                &texture_view,
+                &depth_view,
+                scene,
+                &ctx,
             );
-            self.render_scene_elements_with_camera(&mut encoder, &texture_view, scene, &ctx);
-
-            self.queue.submit(std::iter::once(encoder.finish()));
+            if let Some(branding) = scene.branding() {
+                self.render_quilt_branding(&mut encoder, &texture_view, view, branding);
+            }
         }
+        self.release_msaa();
 
         // Copy texture to buffer
         let buffer_size = u64::from(width) * u64::from(height) * 4;
@@ -1976,16 +5053,148 @@ impl WgpuBackend {
             usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
             mapped_at_creation: false,
         });
-
-        let bytes_per_row = width * 4;
-        let padded_bytes_per_row = (bytes_per_row + 255) & !255;
-
+
+        let bytes_per_row = width * 4;
+        let padded_bytes_per_row = (bytes_per_row + 255) & !255;
+
+        encoder.copy_texture_to_buffer(
+            wgpu::TexelCopyTextureInfo {
+                texture: &texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            wgpu::TexelCopyBufferInfo {
+                buffer: &output_buffer,
+                layout: wgpu::TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_bytes_per_row),
+                    rows_per_image: Some(height),
+                },
+            },
+            wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+        );
+
+        self.queue.submit(std::iter::once(encoder.finish()));
+
+        // Read back the buffer
+        let buffer_slice = output_buffer.slice(..);
+
+        let (tx, rx) = std::sync::mpsc::channel();
+        buffer_slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = tx.send(result);
+        });
+        self.device.poll(wgpu::Maintain::Wait);
+
+        rx.recv()
+            .map_err(|e| RenderError::Frame(e.to_string()))?
+            .map_err(|e| RenderError::Frame(e.to_string()))?;
+
+        let data = buffer_slice.get_mapped_range();
+
+        // Remove padding if present and convert BGRA to RGBA
+        let mut result = Vec::with_capacity((width * height * 4) as usize);
+        for row in 0..height {
+            let start = (row * padded_bytes_per_row) as usize;
+            let end = start + bytes_per_row as usize;
+            // Convert BGRA to RGBA
+            for chunk in data[start..end].chunks_exact(4) {
+                result.push(chunk[2]); // R (was B)
+                result.push(chunk[1]); // G
+                result.push(chunk[0]); // B (was R)
+                result.push(chunk[3]); // A
+            }
+        }
+
+        drop(data);
+        output_buffer.unmap();
+
+        Ok(result)
+    }
+
+    /// Compose `quilt`, laid out as `layout`, into the lightfield image for
+    /// a Looking Glass display with `calibration`, and present it on the
+    /// surface, which should be on that display.
+    ///
+    /// Does nothing without a surface.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `RenderError` if the quilt does not match its layout or is
+    /// too large for the GPU, or the surface fails.
+    pub fn present_lightfield(
+        &mut self,
+        quilt: &QuiltRenderTarget,
+        layout: &HolographicConfig,
+        calibration: &LensCalibration,
+    ) -> RenderResult<()> {
+        let Some(output) = self.acquire_frame()? else {
+            return Ok(());
+        };
+        let view = self.surface_view(&output.texture);
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Lightfield Encoder"),
+            });
+        let format = self.target_format;
+        self.compose_lightfield(&mut encoder, quilt, layout, calibration, &view, format)?;
+        self.queue.submit(std::iter::once(encoder.finish()));
+        output.present();
+        Ok(())
+    }
+
+    /// Compose `quilt` as [`present_lightfield`](Self::present_lightfield)
+    /// does, into an image of the display's size instead of the surface.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `RenderError` if the quilt does not match its layout or is
+    /// too large for the GPU, the screen size is invalid, or readback fails.
+    pub fn render_lightfield_to_image(
+        &mut self,
+        quilt: &QuiltRenderTarget,
+        layout: &HolographicConfig,
+        calibration: &LensCalibration,
+    ) -> RenderResult<RgbaImage> {
+        let (width, height) = (calibration.screen_width, calibration.screen_height);
+        crate::snapshot::check_size(width, height)?;
+        let format = self.readback_format();
+        let texture = self.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Lightfield Texture"),
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
         let mut encoder = self
             .device
             .create_command_encoder(&wgpu::CommandEncoderDescriptor {
-                label: Some("Quilt Copy Encoder"),
+                label: Some("Lightfield Encoder"),
             });
+        self.compose_lightfield(&mut encoder, quilt, layout, calibration, &view, format)?;
 
+        // Rows are padded to wgpu's copy alignment
+        let bytes_per_row = width * 4;
+        let padded_bytes_per_row = (bytes_per_row + 255) & !255;
+        let output_buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Lightfield Output Buffer"),
+            size: u64::from(padded_bytes_per_row) * u64::from(height),
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
         encoder.copy_texture_to_buffer(
             wgpu::TexelCopyTextureInfo {
                 texture: &texture,
@@ -2007,42 +5216,130 @@ impl WgpuBackend {
                 depth_or_array_layers: 1,
             },
         );
-
         self.queue.submit(std::iter::once(encoder.finish()));
 
-        // Read back the buffer
         let buffer_slice = output_buffer.slice(..);
-
         let (tx, rx) = std::sync::mpsc::channel();
         buffer_slice.map_async(wgpu::MapMode::Read, move |result| {
             let _ = tx.send(result);
         });
         self.device.poll(wgpu::Maintain::Wait);
-
         rx.recv()
             .map_err(|e| RenderError::Frame(e.to_string()))?
             .map_err(|e| RenderError::Frame(e.to_string()))?;
 
         let data = buffer_slice.get_mapped_range();
-
-        // Remove padding if present and convert BGRA to RGBA
-        let mut result = Vec::with_capacity((width * height * 4) as usize);
-        for row in 0..height {
-            let start = (row * padded_bytes_per_row) as usize;
-            let end = start + bytes_per_row as usize;
-            // Convert BGRA to RGBA
-            for chunk in data[start..end].chunks_exact(4) {
-                result.push(chunk[2]); // R (was B)
-                result.push(chunk[1]); // G
-                result.push(chunk[0]); // B (was R)
-                result.push(chunk[3]); // A
+        let bgra = format.remove_srgb_suffix() == wgpu::TextureFormat::Bgra8Unorm;
+        let mut pixels = Vec::with_capacity(bytes_per_row as usize * height as usize);
+        for row in data.chunks(padded_bytes_per_row as usize) {
+            for pixel in row[..bytes_per_row as usize].chunks_exact(4) {
+                if bgra {
+                    pixels.extend_from_slice(&[pixel[2], pixel[1], pixel[0], pixel[3]]);
+                } else {
+                    pixels.extend_from_slice(pixel);
+                }
             }
         }
-
         drop(data);
         output_buffer.unmap();
+        RgbaImage::new(width, height, pixels)
+    }
 
-        Ok(result)
+    /// Encode the lightfield pass composing `quilt` into `target`, a texture
+    /// in `format`.
+    fn compose_lightfield(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        quilt: &QuiltRenderTarget,
+        layout: &HolographicConfig,
+        calibration: &LensCalibration,
+        target: &wgpu::TextureView,
+        format: wgpu::TextureFormat,
+    ) -> RenderResult<()> {
+        let compositor = self
+            .lightfield
+            .get_or_insert_with(|| LightfieldCompositor::new(&self.device));
+        compositor.compose(
+            &self.device,
+            &self.queue,
+            encoder,
+            quilt,
+            layout,
+            calibration,
+            target,
+            format,
+        )
+    }
+
+    /// Stamp the branding into one quilt view, laid out in the view's pixels
+    /// and drawn flat rather than through the view's camera.
+    #[allow(clippy::cast_precision_loss)] // View dimensions fit in f32 mantissa
+    fn render_quilt_branding(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        texture_view: &wgpu::TextureView,
+        view: &QuiltView,
+        branding: &Branding,
+    ) {
+        let elements = branding.overlay(view.width as f32, view.height as f32);
+        let theme = Theme::default();
+        let opacity_map = HashMap::new();
+        let clips = HashMap::new();
+
+        // Draw in 2D mode as if the view were the whole canvas
+        let saved = (
+            self.width,
+            self.height,
+            self.current_viewport,
+            self.active_view_projection.take(),
+            self.scene_camera.take(),
+        );
+        self.width = view.width;
+        self.height = view.height;
+        self.current_viewport = Some(Viewport::new(
+            view.x_offset,
+            view.y_offset,
+            view.width,
+            view.height,
+        ));
+
+        let mut images = HashMap::new();
+        for element in &elements {
+            if let ElementKind::Image { src, .. } = &element.kind {
+                match self.prepare_image(src) {
+                    Ok(Some(hash)) => {
+                        images.insert(element.id, hash);
+                    }
+                    Ok(None) => {}
+                    Err(e) => tracing::warn!("Failed to load branding logo: {e}"),
+                }
+            }
+        }
+        let glyphs = self.prepare_text(&elements, &HashMap::new(), &theme, &opacity_map);
+        let ctx = SceneRenderContext {
+            lookup: elements.iter().map(|e| (e.id, e)).collect(),
+            opacity_map: &opacity_map,
+            clips: &clips,
+            callout_tails: HashMap::new(),
+            theme: &theme,
+            glyphs,
+            charts: HashMap::new(),
+            chart_scenes: HashMap::new(),
+            images,
+        };
+        for element in &elements {
+            self.render_single_element(encoder, texture_view, element, &ctx, false);
+        }
+        self.finish_batches(encoder);
+
+        self.active_clip = None;
+        (
+            self.width,
+            self.height,
+            self.current_viewport,
+            self.active_view_projection,
+            self.scene_camera,
+        ) = saved;
     }
 
     /// Render scene elements with a specific camera and viewport to a texture.
@@ -2051,29 +5348,27 @@ impl WgpuBackend {
         &self,
         encoder: &mut wgpu::CommandEncoder,
         texture_view: &wgpu::TextureView,
+        depth_view: &wgpu::TextureView,
         scene: &Scene,
         ctx: &QuiltRenderContext,
     ) {
-        let elements: Vec<_> = scene.elements().cloned().collect();
+        let elements: Vec<_> = scene
+            .elements()
+            .filter(|e| scene.is_shown(e))
+            .cloned()
+            .collect();
 
         if elements.is_empty() {
             // Just clear the viewport area
             let load_op = if ctx.is_first_view() {
-                wgpu::LoadOp::Clear(self.background_color)
+                wgpu::LoadOp::Clear(self.clear_color())
             } else {
                 wgpu::LoadOp::Load
             };
 
             let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                 label: Some("Quilt Clear Pass"),
-                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: texture_view,
-                    resolve_target: None,
-                    ops: wgpu::Operations {
-                        load: load_op,
-                        store: wgpu::StoreOp::Store,
-                    },
-                })],
+                color_attachments: &[Some(self.color_attachment(texture_view, load_op))],
                 depth_stencil_attachment: None,
                 timestamp_writes: None,
                 occlusion_query_set: None,
@@ -2107,12 +5402,91 @@ impl WgpuBackend {
             // First element of first view clears the texture
             let is_first_element = ctx.is_first_view() && i == 0;
 
+            if self.render_model_with_camera(
+                encoder,
+                texture_view,
+                depth_view,
+                element,
+                ctx,
+                is_first_element,
+            ) {
+                continue;
+            }
             self.render_element_with_camera(encoder, texture_view, element, ctx, is_first_element);
         }
     }
 
+    /// Draw a loaded `Model3D`, or a 3D chart's data cube, through the
+    /// view's camera, which shares the model space of the holographic
+    /// renderer's camera hint. Returns `false` if the model is not ready.
+    #[allow(clippy::cast_precision_loss)] // Viewport dimensions fit in f32 mantissa
+    fn render_model_with_camera(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        texture_view: &wgpu::TextureView,
+        depth_view: &wgpu::TextureView,
+        element: &Element,
+        ctx: &QuiltRenderContext,
+        is_first_element: bool,
+    ) -> bool {
+        let (model, pose, lighting) = match &element.kind {
+            ElementKind::Model3D { src, lighting, .. } => {
+                let (Some(model), Some(pose)) =
+                    (self.gpu_models.get(src), pose3d::pose(&element.kind))
+                else {
+                    return false;
+                };
+                (model, pose, lighting.as_ref())
+            }
+            ElementKind::Chart { .. } => {
+                let Some((_, model)) = self.chart_models.get(&element.id) else {
+                    return false;
+                };
+                (model, pose3d::Transform3D::IDENTITY, None)
+            }
+            _ => return false,
+        };
+        let load = if is_first_element {
+            wgpu::LoadOp::Clear(self.clear_color())
+        } else {
+            wgpu::LoadOp::Load
+        };
+        let view_projection = Mat4 {
+            data: ctx.view_projection,
+        };
+        let default_lighting = Lighting::default();
+        self.draw_model(
+            encoder,
+            texture_view,
+            depth_view,
+            model,
+            &ModelDraw {
+                clip_from_model: view_projection.mul(&Mat4::from_pose(&pose)),
+                pose,
+                eye: ctx.eye,
+                lighting: lighting.unwrap_or(&default_lighting),
+                viewport: [
+                    ctx.viewport_x as f32,
+                    ctx.viewport_y as f32,
+                    ctx.viewport_width as f32,
+                    ctx.viewport_height as f32,
+                ],
+                scissor: (
+                    ctx.viewport_x,
+                    ctx.viewport_y,
+                    ctx.viewport_width,
+                    ctx.viewport_height,
+                ),
+            },
+            load,
+        );
+        true
+    }
+
     /// Render a single element with a specific view-projection matrix.
     ///
+    /// The element lies on the canvas in front of the focal plane by its
+    /// depth, so it shifts between views as far as it stands out.
     /// For quilt rendering, this renders elements as solid colored quads.
     /// Full support for textures, images, and other element types would require
     /// additional texture caching infrastructure for offscreen rendering.
@@ -2131,12 +5505,12 @@ impl WgpuBackend {
             element.transform.width,
             element.transform.height,
         ];
-        // use_camera = 1.0 to enable 3D mode
+        // use_camera = 1.0 to enable 3D mode, at the element's depth
         let canvas_size = [
             ctx.texture_width as f32,
             ctx.texture_height as f32,
             1.0,
-            0.0,
+            ctx.depth.element_depth(element),
         ];
 
         // Default to a gray color for all elements (placeholder)
@@ -2147,40 +5521,40 @@ impl WgpuBackend {
             transform,
             canvas_size,
             color,
-            view_projection: ctx.view_projection,
+            view_projection: ctx.canvas_projection,
+            fill_end: QuadStyle::FLAT.fill_end,
+            stroke_color: QuadStyle::FLAT.stroke_color,
+            style_params: QuadStyle::FLAT.params,
+            style_extra: QuadStyle::FLAT.extra,
         };
 
-        // Update uniform buffer
-        self.queue
-            .write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&[uniforms]));
-
-        // Create bind group
+        // Uniforms of their own, as every view is recorded before any runs
+        let uniform_buffer = self
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Quilt Element Uniforms"),
+                contents: bytemuck::cast_slice(&[uniforms]),
+                usage: wgpu::BufferUsages::UNIFORM,
+            });
         let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
             label: Some("Quilt Element Bind Group"),
             layout: &self.uniform_bind_group_layout,
             entries: &[wgpu::BindGroupEntry {
                 binding: 0,
-                resource: self.uniform_buffer.as_entire_binding(),
+                resource: uniform_buffer.as_entire_binding(),
             }],
         });
 
         // Create render pass
         let load_op = if is_first_element {
-            wgpu::LoadOp::Clear(self.background_color)
+            wgpu::LoadOp::Clear(self.clear_color())
         } else {
             wgpu::LoadOp::Load
         };
 
         let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
             label: Some("Quilt Element Render Pass"),
-            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                view: texture_view,
-                resolve_target: None,
-                ops: wgpu::Operations {
-                    load: load_op,
-                    store: wgpu::StoreOp::Store,
-                },
-            })],
+            color_attachments: &[Some(self.color_attachment(texture_view, load_op))],
             depth_stencil_attachment: None,
             timestamp_writes: None,
             occlusion_query_set: None,
@@ -2216,8 +5590,100 @@ impl RenderBackend for WgpuBackend {
     }
 
     fn render(&mut self, scene: &Scene) -> RenderResult<()> {
-        // Delegate to internal render method (2D mode: no camera)
-        self.render_internal(scene)
+        let result = self.with_scene_camera(scene, Self::render_internal);
+        self.visible = None;
+        result
+    }
+
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss,
+        clippy::cast_precision_loss
+    )] // Regions are whole pixels within the viewport
+    fn render_damage(&mut self, scene: &Scene, regions: &[DamageRect]) -> RenderResult<()> {
+        if !self.can_retain() {
+            return self.render(scene);
+        }
+        // The first partial frame has nothing retained to build on
+        if self.retained.is_none() {
+            self.retained = Some(self.create_retained());
+            return self.render(scene);
+        }
+
+        // One scissor around all regions: the passes are per element, so
+        // repeating them per region would cost more than it saves
+        let Some(damage) = regions.iter().copied().reduce(|a, b| a.union(&b)) else {
+            return Ok(());
+        };
+        let damage = damage.round_out().intersection(&DamageRect::new(
+            0.0,
+            0.0,
+            self.width as f32,
+            self.height as f32,
+        ));
+        self.damage_scissor = Some((
+            damage.x as u32,
+            damage.y as u32,
+            damage.width as u32,
+            damage.height as u32,
+        ));
+        let result = self.with_scene_camera(scene, Self::render_internal);
+        self.damage_scissor = None;
+        self.visible = None;
+        result
+    }
+
+    fn set_visible_elements(&mut self, visible: VisibleSet) {
+        self.visible = Some(visible);
+    }
+
+    fn set_chart_style(&mut self, style: ChartStyle) {
+        self.chart_style = style;
+    }
+
+    fn set_antialiasing(&mut self, mode: AntiAliasing) {
+        let mode = self.supported_antialiasing(mode);
+        if mode == self.antialiasing {
+            return;
+        }
+        let samples = mode.sample_count();
+        if samples != self.antialiasing.sample_count() {
+            self.rebuild_pipelines(samples);
+            self.frame_msaa = None;
+        }
+        self.antialiasing = mode;
+        // Nothing retained was drawn with the new edges
+        self.retained = None;
+        tracing::debug!("wgpu anti-aliasing set to {}", mode.name());
+    }
+
+    fn set_color_space(&mut self, space: ColorSpace) {
+        self.requested_color_space = space;
+        let Some(formats) = ColorFormats::choose(&self.surface_formats, space) else {
+            tracing::debug!("No surface: offscreen renders stay 8-bit sRGB");
+            return;
+        };
+        if let (Some(surface), Some(config)) = (&self.surface, &mut self.surface_config) {
+            config.format = formats.surface;
+            config.view_formats = formats.view_formats();
+            surface.configure(&self.device, config);
+        }
+        self.use_color_formats(formats);
+        tracing::debug!("wgpu presenting {}", formats.space.name());
+    }
+
+    fn color_space(&self) -> ColorSpace {
+        self.presented_color_space
+    }
+
+    fn set_shader_time(&mut self, seconds: f32) {
+        self.shader_time = seconds;
+    }
+
+    fn set_post_effects(&mut self, effects: &PostEffects) {
+        self.post_effects = effects.clone();
+        // Layer effects are baked into the retained frame
+        self.retained = None;
     }
 
     fn resize(&mut self, width: u32, height: u32) -> RenderResult<()> {
@@ -2227,6 +5693,8 @@ impl RenderBackend for WgpuBackend {
 
         self.width = width;
         self.height = height;
+        // The renderer repaints in full after a resize, recreating it
+        self.retained = None;
 
         // Invalidate viewport if it extends beyond new canvas bounds
         if let Some(vp) = self.current_viewport {
@@ -2255,6 +5723,52 @@ impl RenderBackend for WgpuBackend {
 
         Ok(())
     }
+
+    fn frame_stats(&self) -> FrameStats {
+        self.last_frame
+    }
+
+    fn gpu_time_ms(&mut self) -> Option<f64> {
+        self.gpu_timer.as_mut()?.poll(&self.device)
+    }
+
+    fn render_to_image(
+        &mut self,
+        scene: &Scene,
+        width: u32,
+        height: u32,
+    ) -> RenderResult<Option<RgbaImage>> {
+        let bgra = self.readback_format().remove_srgb_suffix() == wgpu::TextureFormat::Bgra8Unorm;
+
+        // Draw at the requested size without disturbing the surface
+        let saved = (
+            self.width,
+            self.height,
+            self.current_viewport.take(),
+            self.last_frame,
+            self.visible.take(),
+        );
+        self.width = width;
+        self.height = height;
+        let result = self.with_scene_camera(scene, |backend, scene| {
+            backend.render_offscreen(scene, width, height)
+        });
+        (
+            self.width,
+            self.height,
+            self.current_viewport,
+            self.last_frame,
+            self.visible,
+        ) = saved;
+
+        let mut pixels = result?;
+        if bgra {
+            for pixel in pixels.chunks_exact_mut(4) {
+                pixel.swap(0, 2);
+            }
+        }
+        RgbaImage::new(width, height, pixels).map(Some)
+    }
 }
 
 // Re-export wgpu types for surface creation
@@ -2337,4 +5851,516 @@ mod tests {
         set.insert(Viewport::new(10, 10, 200, 200));
         assert_eq!(set.len(), 2);
     }
+
+    #[test]
+    fn test_same_kind_elements_share_one_draw_call() {
+        let Ok(mut backend) = WgpuBackend::new() else {
+            // No adapter in this environment
+            return;
+        };
+        let mut scene = Scene::new(800.0, 600.0);
+        for i in 0..10_000u16 {
+            let element = Element::new(ElementKind::Shape {
+                shape: canvas_core::ShapeKind::Rectangle,
+            })
+            .with_transform(canvas_core::Transform {
+                x: f32::from(i % 100) * 8.0,
+                y: f32::from(i / 100) * 6.0,
+                width: 6.0,
+                height: 4.0,
+                ..canvas_core::Transform::default()
+            });
+            scene.add_element(element);
+        }
+
+        let pixels = backend.render_to_texture(&scene).expect("render");
+        assert_eq!(backend.frame_stats().draw_calls, 1);
+        // The first rect is painted, the gap next to it is background
+        let pixel = |x: usize, y: usize| &pixels[(y * 800 + x) * 4..][..4];
+        assert_ne!(pixel(2, 2), [255, 255, 255, 255]);
+        assert_eq!(pixel(7, 2), [255, 255, 255, 255]);
+    }
+
+    #[test]
+    fn test_charts_draw_as_tessellated_fills() {
+        let Ok(mut backend) = WgpuBackend::new() else {
+            // No adapter in this environment
+            return;
+        };
+        let mut scene = Scene::new(800.0, 600.0);
+        scene.add_element(
+            Element::new(ElementKind::Chart {
+                chart_type: "bar".to_string(),
+                data: serde_json::json!({ "labels": ["a", "b"], "values": [10, 20] }),
+                binding: None,
+            })
+            .with_transform(canvas_core::Transform {
+                width: 400.0,
+                height: 300.0,
+                ..canvas_core::Transform::default()
+            }),
+        );
+
+        let pixels = backend.render_to_texture(&scene).expect("render");
+        // Fills, then labels when a font is installed
+        assert!((1..=2).contains(&backend.frame_stats().draw_calls));
+        let pixel = |x: usize, y: usize| &pixels[(y * 800 + x) * 4..][..4];
+        // Inside the second, taller bar, in the first series' blue (BGRA)
+        let bgra = pixel(300, 200);
+        assert!(bgra[0] > bgra[1] && bgra[1] > bgra[2]);
+        // Between the bars is the chart background
+        assert_eq!(pixel(230, 250), [255, 255, 255, 255]);
+    }
+
+    #[test]
+    fn test_3d_charts_draw_their_model() {
+        let Ok(mut backend) = WgpuBackend::new() else {
+            // No adapter in this environment
+            return;
+        };
+        let mut scene = Scene::new(800.0, 600.0);
+        let chart = Element::new(ElementKind::Chart {
+            chart_type: "surface".to_string(),
+            data: serde_json::json!({ "values": [[0, 1, 0], [1, 3, 1], [0, 1, 0]] }),
+            binding: None,
+        })
+        .with_transform(canvas_core::Transform {
+            width: 400.0,
+            height: 300.0,
+            ..canvas_core::Transform::default()
+        });
+        let id = chart.id;
+        scene.add_element(chart);
+
+        backend.render_to_texture(&scene).expect("render");
+        let (model, _) = backend.chart_models.get(&id).expect("chart model");
+        let model_triangles: usize = model.primitives.iter().map(|p| p.indices.len() / 3).sum();
+        // The floor and four cells, drawn over the background fill
+        assert_eq!(model_triangles, 2 + 4 * 2);
+        assert_eq!(backend.frame_stats().triangles, 2 + model_triangles);
+
+        // Gone charts let go of their meshes
+        scene.remove_element(&id).expect("remove");
+        backend.render_to_texture(&scene).expect("render");
+        assert!(backend.chart_models.is_empty());
+    }
+
+    #[test]
+    fn test_antialiasing_covers_pixels_past_fractional_edges() {
+        let Ok(mut backend) = WgpuBackend::new() else {
+            // No adapter in this environment
+            return;
+        };
+        let mut scene = Scene::new(800.0, 600.0);
+        // The right edge falls at x = 30.4, short of pixel 30's center
+        scene.add_element(
+            Element::new(ElementKind::Shape {
+                shape: canvas_core::ShapeKind::Rectangle,
+            })
+            .with_transform(canvas_core::Transform {
+                x: 10.4,
+                y: 10.0,
+                width: 20.0,
+                height: 20.0,
+                ..canvas_core::Transform::default()
+            }),
+        );
+        let edge_pixel = |backend: &mut WgpuBackend, mode| {
+            backend.set_antialiasing(mode);
+            let pixels = backend.render_to_texture(&scene).expect("render");
+            pixels[(20 * 800 + 30) * 4..][..4].to_vec()
+        };
+
+        assert_eq!(edge_pixel(&mut backend, AntiAliasing::Off), [255; 4]);
+        for mode in [AntiAliasing::Msaa4, AntiAliasing::Analytic] {
+            assert_ne!(edge_pixel(&mut backend, mode), [255; 4], "{mode:?}");
+            assert_eq!(backend.antialiasing(), mode);
+        }
+
+        // 8x falls back to 4x where the device lacks it
+        edge_pixel(&mut backend, AntiAliasing::Msaa8);
+        assert!(matches!(
+            backend.antialiasing(),
+            AntiAliasing::Msaa8 | AntiAliasing::Msaa4
+        ));
+    }
+
+    #[test]
+    fn test_visible_set_limits_the_frame() {
+        let Ok(mut backend) = WgpuBackend::new() else {
+            // No adapter in this environment
+            return;
+        };
+        let mut scene = Scene::new(800.0, 600.0);
+        // A tall scrolling page with one screenful in view
+        for i in 0..2_000u16 {
+            scene.add_element(
+                Element::new(ElementKind::Shape {
+                    shape: canvas_core::ShapeKind::Rectangle,
+                })
+                .with_transform(canvas_core::Transform {
+                    x: 10.0,
+                    y: f32::from(i) * 30.0 + 5.0,
+                    width: 100.0,
+                    height: 20.0,
+                    ..canvas_core::Transform::default()
+                }),
+            );
+        }
+
+        backend.set_visible_elements(VisibleSet::from_scene(&scene));
+        backend.render_to_texture(&scene).expect("render");
+        // Rows 0..=19 reach into the 600px viewport
+        let stats = backend.frame_stats();
+        assert_eq!(
+            (
+                stats.elements_total,
+                stats.elements_culled,
+                stats.draw_calls
+            ),
+            (2_000, 1_980, 1)
+        );
+        // One two-triangle quad per drawn row
+        assert_eq!(stats.triangles, 40);
+
+        // The set applies to one frame only
+        backend.render_to_texture(&scene).expect("render");
+        assert_eq!(backend.frame_stats().elements_drawn, 2_000);
+    }
+
+    #[test]
+    fn test_post_effects_process_the_frame_and_overlay_layers() {
+        let Ok(mut backend) = WgpuBackend::new() else {
+            // No adapter in this environment
+            return;
+        };
+        let mut scene = Scene::new(800.0, 600.0);
+        let rect = Element::new(ElementKind::Shape {
+            shape: canvas_core::ShapeKind::Rectangle,
+        })
+        .with_transform(canvas_core::Transform {
+            x: 100.0,
+            y: 100.0,
+            width: 100.0,
+            height: 100.0,
+            ..canvas_core::Transform::default()
+        });
+        let rect_id = rect.id;
+        scene.add_element(rect);
+        let layer = Element::new(ElementKind::OverlayLayer {
+            children: vec![rect_id],
+            opacity: 1.0,
+            clip: false,
+        });
+        let layer_id = layer.id;
+        scene.add_element(layer);
+        let pixel = |pixels: &[u8], x: usize, y: usize| pixels[(y * 800 + x) * 4..][..4].to_vec();
+
+        let plain = backend.render_to_texture(&scene).expect("render");
+        let plain_draws = backend.frame_stats().draw_calls;
+        assert_eq!(pixel(&plain, 0, 0), [255; 4]);
+        assert_eq!(pixel(&plain, 96, 150), [255; 4]);
+        assert_ne!(pixel(&plain, 150, 150), [255; 4]);
+
+        backend.set_post_effects(&PostEffects::frame(vec![PostEffect::Vignette {
+            strength: 1.0,
+            radius: 0.5,
+            softness: 0.5,
+        }]));
+        let vignetted = backend.render_to_texture(&scene).expect("render");
+        assert!(pixel(&vignetted, 0, 0)[0] < 16);
+        assert_eq!(pixel(&vignetted, 400, 300), pixel(&plain, 400, 300));
+        // One more full-screen pass
+        assert_eq!(backend.frame_stats().draw_calls, plain_draws + 1);
+
+        // Blurring the layer spreads the rect past its edge
+        let mut effects = PostEffects::default();
+        effects
+            .layers
+            .insert(layer_id, vec![PostEffect::Blur { radius: 8.0 }]);
+        backend.set_post_effects(&effects);
+        let blurred = backend.render_to_texture(&scene).expect("render");
+        assert_ne!(pixel(&blurred, 96, 150), [255; 4]);
+        assert_eq!(pixel(&blurred, 0, 0), [255; 4]);
+    }
+
+    #[test]
+    fn test_shader_elements_fill_their_box_or_fall_back_to_a_placeholder() {
+        let Ok(mut backend) = WgpuBackend::new() else {
+            // No adapter in this environment
+            return;
+        };
+        let shader = |wgsl: &str| {
+            Element::new(ElementKind::Shader {
+                wgsl: wgsl.to_string(),
+                uniforms: vec![1.0],
+            })
+            .with_transform(canvas_core::Transform {
+                x: 100.0,
+                y: 100.0,
+                width: 100.0,
+                height: 100.0,
+                ..canvas_core::Transform::default()
+            })
+        };
+        let pixel = |pixels: &[u8], x: usize, y: usize| pixels[(y * 800 + x) * 4..][..4].to_vec();
+
+        // Red from the first uniform, in BGRA
+        let mut scene = Scene::new(800.0, 600.0);
+        scene.add_element(shader(
+            "fn shade(uv: vec2<f32>) -> vec4<f32> { return vec4<f32>(param(0u), 0.0, 0.0, 1.0); }",
+        ));
+        let pixels = backend.render_to_texture(&scene).expect("render");
+        assert_eq!(pixel(&pixels, 150, 150), [0, 0, 255, 255]);
+        assert_eq!(pixel(&pixels, 96, 150), [255; 4]);
+        assert_eq!(backend.shader_pipelines.len(), 1);
+
+        // A source that does not compile draws the placeholder
+        let mut scene = Scene::new(800.0, 600.0);
+        scene.add_element(shader(
+            "fn shade(uv: vec2<f32>) -> vec4<f32> { return uv; }",
+        ));
+        let pixels = backend.render_to_texture(&scene).expect("render");
+        let placeholder = pixel(&pixels, 150, 150);
+        assert_ne!(placeholder, [0, 0, 255, 255]);
+        assert_ne!(placeholder, [255; 4]);
+        // Only the sources in the frame stay compiled
+        assert_eq!(backend.shader_pipelines.len(), 1);
+        assert!(backend.shader_pipelines.values().all(Option::is_none));
+    }
+
+    #[test]
+    fn test_srgb_colors_read_back_as_written() {
+        let Ok(mut backend) = WgpuBackend::new() else {
+            // No adapter in this environment
+            return;
+        };
+        // Middle gray, as Canvas2D and SVG draw it; blending in linear
+        // light must not darken or lighten it
+        backend.set_background_color(0.5, 0.5, 0.5, 1.0);
+        let fill = canvas_core::ThemeColor::parse("#808080").expect("color");
+        let mut scene = Scene::new(800.0, 600.0);
+        scene.add_element(
+            Element::new(ElementKind::Shape {
+                shape: canvas_core::ShapeKind::Rectangle,
+            })
+            .with_transform(canvas_core::Transform {
+                x: 100.0,
+                y: 100.0,
+                width: 100.0,
+                height: 100.0,
+                ..canvas_core::Transform::default()
+            })
+            .with_style(canvas_core::Style {
+                fill: Some(canvas_core::Fill::Solid { color: fill }),
+                ..canvas_core::Style::default()
+            }),
+        );
+        let pixels = backend.render_to_texture(&scene).expect("render");
+        for (x, y) in [(0, 0), (150, 150)] {
+            let pixel = &pixels[(y * 800 + x) * 4..][..3];
+            assert!(pixel.iter().all(|&c| c.abs_diff(128) <= 1), "{pixel:?}");
+        }
+
+        // Without a surface there is nothing to present HDR on
+        backend.set_color_space(ColorSpace::Hdr10);
+        assert_eq!(backend.color_space(), ColorSpace::Srgb);
+        assert_eq!(backend.render_to_texture(&scene).expect("render"), pixels);
+    }
+
+    #[test]
+    fn test_color_formats_fall_back_to_what_the_surface_offers() {
+        use wgpu::TextureFormat::{Bgra8Unorm, Bgra8UnormSrgb, Rgb10a2Unorm, Rgba16Float};
+
+        let hdr = [Bgra8UnormSrgb, Rgba16Float, Rgb10a2Unorm];
+        let formats = ColorFormats::choose(&hdr, ColorSpace::Hdr10).expect("formats");
+        assert_eq!(
+            (formats.surface, formats.target),
+            (Rgb10a2Unorm, Rgba16Float)
+        );
+        assert!(!formats.views_surface());
+        let formats = ColorFormats::choose(&hdr, ColorSpace::ExtendedSrgb).expect("formats");
+        assert_eq!(formats.space, ColorSpace::ExtendedSrgb);
+        assert!(formats.view_formats().is_empty());
+
+        // An sRGB-only display falls back all the way
+        let formats = ColorFormats::choose(&[Bgra8Unorm], ColorSpace::Hdr10).expect("formats");
+        assert_eq!(formats.space, ColorSpace::Srgb);
+        assert_eq!(formats.target, Bgra8UnormSrgb);
+        assert_eq!(formats.view_formats(), vec![Bgra8UnormSrgb]);
+        assert!(ColorFormats::choose(&[], ColorSpace::Srgb).is_none());
+    }
+
+    #[test]
+    fn test_quilt_views_recorded_together_match_views_rendered_alone() {
+        let Ok(mut backend) = WgpuBackend::new() else {
+            return;
+        };
+        let mut scene = Scene::new(800.0, 600.0);
+        for (x, y) in [(100.0, 100.0), (460.0, 300.0)] {
+            scene.add_element(
+                Element::new(ElementKind::Shape {
+                    shape: canvas_core::ShapeKind::Rectangle,
+                })
+                .with_transform(canvas_core::Transform {
+                    x,
+                    y,
+                    width: 240.0,
+                    height: 180.0,
+                    ..canvas_core::Transform::default()
+                }),
+            );
+        }
+        let layout = HolographicConfig {
+            num_views: 4,
+            quilt_columns: 2,
+            quilt_rows: 2,
+            view_width: 64,
+            view_height: 64,
+            ..HolographicConfig::looking_glass_portrait()
+        };
+        let camera = Camera::default();
+        let depth = CanvasDepth::default();
+        let quilt = crate::quilt::Quilt::new(layout, &camera);
+        let (width, height) = (quilt.total_width, quilt.total_height);
+        let together = backend
+            .render_quilt_to_buffer(width, height, &quilt.views, &scene, &camera, &depth)
+            .expect("quilt");
+        // Each view covers the same pixels of the white background as when
+        // it is the only one rendered, so no view drew with another's
+        // uniforms
+        for view in quilt.views.iter().skip(1) {
+            let alone = backend
+                .render_quilt_to_buffer(
+                    width,
+                    height,
+                    std::slice::from_ref(view),
+                    &scene,
+                    &camera,
+                    &depth,
+                )
+                .expect("view");
+            let mut covered = 0;
+            for y in view.y_offset..view.y_offset + view.height {
+                for x in view.x_offset..view.x_offset + view.width {
+                    let i = ((y * width + x) * 4) as usize;
+                    let drawn = alone[i + 3] > 0;
+                    assert_eq!(drawn, together[i..i + 4] != [255; 4], "({x}, {y})");
+                    covered += usize::from(drawn);
+                }
+            }
+            assert!(covered > 0, "view {} is empty", view.index);
+        }
+    }
+
+    #[test]
+    fn test_quilt_elements_shift_between_views_by_their_depth() {
+        let Ok(mut backend) = WgpuBackend::new() else {
+            return;
+        };
+        let layout = HolographicConfig {
+            num_views: 2,
+            quilt_columns: 2,
+            quilt_rows: 1,
+            view_width: 64,
+            view_height: 64,
+            ..HolographicConfig::looking_glass_portrait()
+        };
+        let camera = Camera::default();
+        let quilt = crate::quilt::Quilt::new(layout, &camera);
+        let (width, height) = (quilt.total_width, quilt.total_height);
+        // Left edge of the rectangle in each view, along the middle row
+        let mut left_edges = |depth: f32| {
+            let mut scene = Scene::new(400.0, 400.0);
+            scene.add_element(
+                Element::new(ElementKind::Shape {
+                    shape: canvas_core::ShapeKind::Rectangle,
+                })
+                .with_transform(canvas_core::Transform {
+                    x: 160.0,
+                    y: 160.0,
+                    width: 80.0,
+                    height: 80.0,
+                    ..canvas_core::Transform::default()
+                })
+                .with_depth(depth),
+            );
+            let pixels = backend
+                .render_quilt_to_buffer(
+                    width,
+                    height,
+                    &quilt.views,
+                    &scene,
+                    &camera,
+                    &CanvasDepth::default(),
+                )
+                .expect("quilt");
+            quilt
+                .views
+                .iter()
+                .map(move |view| {
+                    (0..view.width)
+                        .find(|x| {
+                            let i =
+                                (((view.y_offset + 32) * width + view.x_offset + x) * 4) as usize;
+                            pixels[i..i + 4] != [255; 4]
+                        })
+                        .expect("rectangle in view")
+                })
+                .collect::<Vec<_>>()
+        };
+        // On the focal plane the views agree; in front of it they differ
+        let flat = left_edges(0.0);
+        assert!(flat[0].abs_diff(flat[1]) <= 1, "{flat:?}");
+        let near = left_edges(100.0);
+        assert!(near[0].abs_diff(near[1]) > 2, "{near:?}");
+    }
+
+    #[test]
+    fn test_lightfield_pass_matches_the_cpu_compositor() {
+        let Ok(mut backend) = WgpuBackend::new() else {
+            return;
+        };
+        // Four 8x8 views in a row, each one shade
+        let layout = HolographicConfig {
+            num_views: 4,
+            quilt_columns: 4,
+            quilt_rows: 1,
+            view_width: 8,
+            view_height: 8,
+            ..HolographicConfig::looking_glass_portrait()
+        };
+        let mut quilt = QuiltRenderTarget::new(layout.quilt_width(), layout.quilt_height());
+        for view in 0..4 {
+            let shade = u8::try_from(view * 60).unwrap_or(255);
+            quilt.fill_rect(view * 8, 0, 8, 8, [shade, shade, shade, 255]);
+        }
+        // One lens cycle across eight pixels, subpixels a view apart
+        let lens = LensCalibration {
+            pitch: 1.0,
+            tilt: 0.0,
+            center: 0.0,
+            subpixel: 1.0 / 32.0,
+            screen_width: 8,
+            screen_height: 4,
+            invert_views: false,
+            bgr: false,
+            flip_x: false,
+        };
+
+        let gpu = backend
+            .render_lightfield_to_image(&quilt, &layout, &lens)
+            .expect("compose");
+        let cpu = crate::lightfield::compose(&quilt, &layout, &lens).expect("compose");
+        assert_eq!((gpu.width(), gpu.height()), (8, 4));
+        for (gpu, cpu) in gpu.pixels().iter().zip(cpu.pixels()) {
+            assert!(gpu.abs_diff(*cpu) <= 1, "{gpu} != {cpu}");
+        }
+
+        // A quilt must match its layout
+        let other = HolographicConfig::looking_glass_portrait();
+        assert!(backend
+            .render_lightfield_to_image(&quilt, &other, &lens)
+            .is_err());
+    }
 }
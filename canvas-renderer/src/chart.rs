@@ -1,19 +1,35 @@
-//! Chart rendering utilities using plotters.
+//! Chart data and rendering.
 //!
-//! Renders charts to RGBA pixel buffers that can be composited into the canvas.
+//! [`parse_chart_config`] reads a chart element's JSON into a
+//! [`ChartConfig`], which every build can draw through
+//! [`chart_mesh`](crate::chart_mesh). With the `charts` feature, plotters
+//! also renders charts to RGBA pixel buffers and SVG documents.
+//!
+//! A chart's look comes from its [`ChartStyle`]: the renderer's default,
+//! with the chart's own `data.style` laid over it.
 
+#[cfg(feature = "charts")]
 use plotters::prelude::*;
 
+use canvas_core::chart3d;
+use canvas_core::model3d::ModelCamera;
 use canvas_core::Element;
+// `Color` alone is the plotters trait
+use canvas_core::Color as CanvasColor;
 
+#[cfg(feature = "charts")]
+use crate::chart_mesh::{ChartGeometry, ChartPath};
+use crate::chart_style::ChartStyle;
 use crate::error::{RenderError, RenderResult};
 
 /// Helper trait to convert plotters errors to `RenderError::Frame`.
+#[cfg(feature = "charts")]
 trait ChartErrorExt<T> {
     /// Convert a drawing error to a frame render error with context.
     fn frame_err(self, context: &str) -> RenderResult<T>;
 }
 
+#[cfg(feature = "charts")]
 impl<T, E: std::fmt::Display> ChartErrorExt<T> for Result<T, E> {
     fn frame_err(self, context: &str) -> RenderResult<T> {
         self.map_err(|e| RenderError::Frame(format!("{context}: {e}")))
@@ -37,6 +53,50 @@ pub enum ChartType {
     Donut,
     /// Scatter plot.
     Scatter,
+    /// Candlestick (OHLC) chart, one candle per point.
+    Candlestick,
+    /// Heatmap grid, one row per series and one cell per point.
+    Heatmap,
+    /// Histogram of the first series' values, grouped into bins.
+    Histogram,
+    /// Vertical bars with each series stacked on the previous ones.
+    StackedBar,
+    /// Area chart with each series stacked on the previous ones.
+    StackedArea,
+    /// Points placed in 3D by their `x`, `y` and `z`, one color per series.
+    Scatter3D,
+    /// Surface over a grid, one row per series and one column per point,
+    /// with the values as heights.
+    Surface,
+}
+
+impl ChartType {
+    /// The `chart_type` string this type is selected by.
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Bar => "bar",
+            Self::BarHorizontal => "bar_horizontal",
+            Self::Line => "line",
+            Self::Area => "area",
+            Self::Pie => "pie",
+            Self::Donut => "donut",
+            Self::Scatter => "scatter",
+            Self::Candlestick => "candlestick",
+            Self::Heatmap => "heatmap",
+            Self::Histogram => "histogram",
+            Self::StackedBar => "stacked_bar",
+            Self::StackedArea => "stacked_area",
+            Self::Scatter3D => "scatter3d",
+            Self::Surface => "surface",
+        }
+    }
+
+    /// Whether this type is plotted in 3D (see [`canvas_core::chart3d`]).
+    #[must_use]
+    pub fn is_3d(self) -> bool {
+        matches!(self, Self::Scatter3D | Self::Surface)
+    }
 }
 
 impl std::str::FromStr for ChartType {
@@ -51,6 +111,13 @@ impl std::str::FromStr for ChartType {
             "pie" => Ok(Self::Pie),
             "donut" => Ok(Self::Donut),
             "scatter" => Ok(Self::Scatter),
+            "candlestick" | "ohlc" => Ok(Self::Candlestick),
+            "heatmap" => Ok(Self::Heatmap),
+            "histogram" => Ok(Self::Histogram),
+            "stacked_bar" => Ok(Self::StackedBar),
+            "stacked_area" => Ok(Self::StackedArea),
+            "scatter3d" | "scatter_3d" => Ok(Self::Scatter3D),
+            "surface" => Ok(Self::Surface),
             _ => Err(format!("Unknown chart type: {s}")),
         }
     }
@@ -65,6 +132,34 @@ pub struct DataPoint {
     pub y: f64,
     /// Optional label.
     pub label: Option<String>,
+    /// Open, high, low and close, for candlestick charts.
+    pub ohlc: Option<Ohlc>,
+    /// Depth, for 3D scatter plots.
+    pub z: Option<f64>,
+}
+
+/// Prices of one candlestick period.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ohlc {
+    /// Opening price.
+    pub open: f64,
+    /// Highest price.
+    pub high: f64,
+    /// Lowest price.
+    pub low: f64,
+    /// Closing price.
+    pub close: f64,
+}
+
+/// One bar of a histogram: values in `start..end`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HistogramBin {
+    /// Inclusive lower edge.
+    pub start: f64,
+    /// Exclusive upper edge (inclusive for the last bin).
+    pub end: f64,
+    /// Values falling in the bin.
+    pub count: usize,
 }
 
 /// Series data for multi-series charts.
@@ -72,8 +167,8 @@ pub struct DataPoint {
 pub struct DataSeries {
     /// Series name.
     pub name: String,
-    /// Series color as hex.
-    pub color: Option<String>,
+    /// Series color (palette color if `None`).
+    pub color: Option<CanvasColor>,
     /// Data points in this series.
     pub points: Vec<DataPoint>,
 }
@@ -97,10 +192,16 @@ pub struct ChartConfig {
     pub width: u32,
     /// Chart height in pixels.
     pub height: u32,
-    /// Background color as hex.
-    pub background: String,
+    /// Background color.
+    pub background: CanvasColor,
     /// Show legend.
     pub show_legend: bool,
+    /// Histogram bin count (Sturges' rule if `None`).
+    pub bins: Option<usize>,
+    /// Palette, fonts, axis formats and legend placement.
+    pub style: ChartStyle,
+    /// View of the data cube, for 3D charts.
+    pub camera: ModelCamera,
 }
 
 impl Default for ChartConfig {
@@ -114,44 +215,25 @@ impl Default for ChartConfig {
             series: Vec::new(),
             width: 400,
             height: 300,
-            background: "#ffffff".to_string(),
+            background: CanvasColor::WHITE,
             show_legend: true,
+            bins: None,
+            style: ChartStyle::default(),
+            camera: chart3d::default_camera(),
         }
     }
 }
 
-/// Parse a hex color string to RGB.
-fn parse_hex_color(hex: &str) -> RGBColor {
-    let hex = hex.trim_start_matches('#');
-    if hex.len() >= 6 {
-        let r = u8::from_str_radix(&hex[0..2], 16).unwrap_or(0);
-        let g = u8::from_str_radix(&hex[2..4], 16).unwrap_or(0);
-        let b = u8::from_str_radix(&hex[4..6], 16).unwrap_or(0);
-        RGBColor(r, g, b)
-    } else {
-        RGBColor(0, 0, 0)
-    }
+/// Convert to a plotters color (charts are drawn opaque).
+#[cfg(feature = "charts")]
+fn rgb(color: CanvasColor) -> RGBColor {
+    RGBColor(color.r, color.g, color.b)
 }
 
-/// Default color palette for charts.
-const PALETTE: &[RGBColor] = &[
-    RGBColor(54, 162, 235),  // Blue
-    RGBColor(255, 99, 132),  // Red
-    RGBColor(75, 192, 192),  // Teal
-    RGBColor(255, 205, 86),  // Yellow
-    RGBColor(153, 102, 255), // Purple
-    RGBColor(255, 159, 64),  // Orange
-    RGBColor(201, 203, 207), // Gray
-    RGBColor(100, 181, 246), // Light Blue
-];
-
-/// Get color for a series index.
-fn get_series_color(index: usize, custom: Option<&str>) -> RGBColor {
-    if let Some(hex) = custom {
-        parse_hex_color(hex)
-    } else {
-        PALETTE[index % PALETTE.len()]
-    }
+/// Get the plotters color for a series index.
+#[cfg(feature = "charts")]
+fn get_series_color(config: &ChartConfig, index: usize, custom: Option<CanvasColor>) -> RGBColor {
+    rgb(config.style.series_color(index, custom))
 }
 
 /// Render a chart to an RGBA image buffer.
@@ -159,25 +241,14 @@ fn get_series_color(index: usize, custom: Option<&str>) -> RGBColor {
 /// # Errors
 ///
 /// Returns an error if chart rendering fails.
-#[allow(clippy::too_many_lines)]
+#[cfg(feature = "charts")]
 pub fn render_chart_to_buffer(config: &ChartConfig) -> RenderResult<Vec<u8>> {
     let (width, height) = (config.width, config.height);
     let mut buffer = vec![0u8; (width * height * 3) as usize];
 
     {
         let root = BitMapBackend::with_buffer(&mut buffer, (width, height)).into_drawing_area();
-        let bg_color = parse_hex_color(&config.background);
-        root.fill(&bg_color)
-            .frame_err("Failed to fill background")?;
-
-        match config.chart_type {
-            ChartType::Bar | ChartType::BarHorizontal => render_bar_chart(&root, config)?,
-            ChartType::Line | ChartType::Area => render_line_chart(&root, config)?,
-            ChartType::Scatter => render_scatter_chart(&root, config)?,
-            ChartType::Pie | ChartType::Donut => render_pie_chart(&root, config)?,
-        }
-
-        root.present().frame_err("Failed to present chart")?;
+        draw_chart(&root, config)?;
     }
 
     // Convert RGB to RGBA
@@ -192,14 +263,64 @@ pub fn render_chart_to_buffer(config: &ChartConfig) -> RenderResult<Vec<u8>> {
     Ok(rgba_buffer)
 }
 
+/// Render a chart as a standalone SVG document of `config.width` x
+/// `config.height`, with vector paths and text instead of pixels.
+///
+/// # Errors
+///
+/// Returns an error if chart rendering fails.
+#[cfg(feature = "charts")]
+pub fn render_chart_to_svg(config: &ChartConfig) -> RenderResult<String> {
+    let mut svg = String::new();
+    {
+        let root =
+            SVGBackend::with_string(&mut svg, (config.width, config.height)).into_drawing_area();
+        draw_chart(&root, config)?;
+    }
+    Ok(svg)
+}
+
+/// Fill the background and draw the chart onto `root`.
+#[cfg(feature = "charts")]
+fn draw_chart<DB: DrawingBackend>(
+    root: &DrawingArea<DB, plotters::coord::Shift>,
+    config: &ChartConfig,
+) -> RenderResult<()> {
+    root.fill(&rgb(config.background))
+        .frame_err("Failed to fill background")?;
+
+    // The plotters charts only know the default look
+    if config.style != ChartStyle::default() {
+        render_chart_geometry(root, config)?;
+        return root.present().frame_err("Failed to present chart");
+    }
+
+    match config.chart_type {
+        ChartType::Bar | ChartType::BarHorizontal => render_bar_chart(root, config)?,
+        ChartType::Line | ChartType::Area => render_line_chart(root, config)?,
+        ChartType::Scatter => render_scatter_chart(root, config)?,
+        ChartType::Pie | ChartType::Donut => render_pie_chart(root, config)?,
+        ChartType::Candlestick
+        | ChartType::Heatmap
+        | ChartType::Histogram
+        | ChartType::StackedBar
+        | ChartType::StackedArea
+        | ChartType::Scatter3D
+        | ChartType::Surface => render_chart_geometry(root, config)?,
+    }
+
+    root.present().frame_err("Failed to present chart")
+}
+
 /// Render a bar chart.
+#[cfg(feature = "charts")]
 #[allow(
     clippy::cast_precision_loss,
     clippy::cast_possible_truncation,
     clippy::cast_sign_loss
 )]
-fn render_bar_chart(
-    root: &DrawingArea<BitMapBackend, plotters::coord::Shift>,
+fn render_bar_chart<DB: DrawingBackend>(
+    root: &DrawingArea<DB, plotters::coord::Shift>,
     config: &ChartConfig,
 ) -> RenderResult<()> {
     if config.series.is_empty() {
@@ -252,7 +373,7 @@ fn render_bar_chart(
     let bar_width = 0.8 / num_series as f64;
 
     for (series_idx, series) in config.series.iter().enumerate() {
-        let color = get_series_color(series_idx, series.color.as_deref());
+        let color = get_series_color(config, series_idx, series.color);
         let offset = (series_idx as f64 - (num_series as f64 - 1.0) / 2.0) * bar_width;
 
         chart
@@ -282,9 +403,10 @@ fn render_bar_chart(
 }
 
 /// Render a line or area chart.
+#[cfg(feature = "charts")]
 #[allow(clippy::cast_precision_loss)]
-fn render_line_chart(
-    root: &DrawingArea<BitMapBackend, plotters::coord::Shift>,
+fn render_line_chart<DB: DrawingBackend>(
+    root: &DrawingArea<DB, plotters::coord::Shift>,
     config: &ChartConfig,
 ) -> RenderResult<()> {
     if config.series.is_empty() {
@@ -337,7 +459,7 @@ fn render_line_chart(
     let is_area = config.chart_type == ChartType::Area;
 
     for (series_idx, series) in config.series.iter().enumerate() {
-        let color = get_series_color(series_idx, series.color.as_deref());
+        let color = get_series_color(config, series_idx, series.color);
 
         let points: Vec<(f64, f64)> = series.points.iter().map(|p| (p.x, p.y)).collect();
 
@@ -369,9 +491,10 @@ fn render_line_chart(
 }
 
 /// Render a scatter chart.
+#[cfg(feature = "charts")]
 #[allow(clippy::cast_precision_loss)]
-fn render_scatter_chart(
-    root: &DrawingArea<BitMapBackend, plotters::coord::Shift>,
+fn render_scatter_chart<DB: DrawingBackend>(
+    root: &DrawingArea<DB, plotters::coord::Shift>,
     config: &ChartConfig,
 ) -> RenderResult<()> {
     if config.series.is_empty() {
@@ -420,7 +543,7 @@ fn render_scatter_chart(
         .frame_err("Failed to draw mesh")?;
 
     for (series_idx, series) in config.series.iter().enumerate() {
-        let color = get_series_color(series_idx, series.color.as_deref());
+        let color = get_series_color(config, series_idx, series.color);
 
         chart
             .draw_series(
@@ -447,14 +570,15 @@ fn render_scatter_chart(
 }
 
 /// Render a pie or donut chart.
+#[cfg(feature = "charts")]
 #[allow(
     clippy::cast_precision_loss,
     clippy::cast_possible_truncation,
     clippy::cast_possible_wrap,
     clippy::cast_sign_loss
 )]
-fn render_pie_chart(
-    root: &DrawingArea<BitMapBackend, plotters::coord::Shift>,
+fn render_pie_chart<DB: DrawingBackend>(
+    root: &DrawingArea<DB, plotters::coord::Shift>,
     config: &ChartConfig,
 ) -> RenderResult<()> {
     if config.series.is_empty() {
@@ -498,7 +622,7 @@ fn render_pie_chart(
         let fraction = point.y.abs() / total;
         let sweep_angle = fraction * std::f64::consts::PI * 2.0;
         let end_angle = start_angle + sweep_angle;
-        let color = get_series_color(i, None);
+        let color = get_series_color(config, i, None);
 
         // Draw pie slice as filled polygon
         let num_segments = ((sweep_angle * 50.0) as usize).max(10);
@@ -536,7 +660,7 @@ fn render_pie_chart(
         let mut legend_y = 30;
 
         for (i, point) in series.points.iter().enumerate() {
-            let color = get_series_color(i, None);
+            let color = get_series_color(config, i, None);
             let default_label = format!("{:.0}", point.y);
             let label = point.label.as_deref().unwrap_or(&default_label);
 
@@ -560,22 +684,131 @@ fn render_pie_chart(
     Ok(())
 }
 
+/// Render a chart from its tessellated [`ChartGeometry`], the shapes and
+/// labels every backend draws.
+#[cfg(feature = "charts")]
+#[allow(
+    clippy::cast_precision_loss,
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss
+)]
+fn render_chart_geometry<DB: DrawingBackend>(
+    root: &DrawingArea<DB, plotters::coord::Shift>,
+    config: &ChartConfig,
+) -> RenderResult<()> {
+    let rect = [0.0, 0.0, config.width as f32, config.height as f32];
+    let geometry = ChartGeometry::new(config, rect);
+    let pixels = |points: &[[f32; 2]]| -> Vec<(i32, i32)> {
+        points
+            .iter()
+            .map(|[x, y]| (x.round() as i32, y.round() as i32))
+            .collect()
+    };
+    let rgba = |color: CanvasColor| RGBAColor(color.r, color.g, color.b, f64::from(color.alpha()));
+    for path in &geometry.paths {
+        match path {
+            ChartPath::Fill { points, color } => root
+                .draw(&Polygon::new(pixels(points), rgba(*color).filled()))
+                .frame_err("Failed to draw chart shape")?,
+            ChartPath::Stroke {
+                points,
+                width,
+                color,
+            } => root
+                .draw(&PathElement::new(
+                    pixels(points),
+                    rgba(*color).stroke_width(width.round().max(1.0) as u32),
+                ))
+                .frame_err("Failed to draw chart line")?,
+        }
+    }
+    for label in &geometry.labels {
+        let font = ("sans-serif", f64::from(label.font_size)).into_font();
+        let [x, y, ..] = label.rect;
+        root.draw(&Text::new(
+            label.text.clone(),
+            (x.round() as i32, y.round() as i32),
+            font.color(&rgb(label.color)),
+        ))
+        .frame_err("Failed to draw chart label")?;
+    }
+    Ok(())
+}
+
+/// Group `values` into `bins` equal-width bins spanning their range, using
+/// Sturges' rule when `bins` is `None`. Non-finite values are skipped.
+#[must_use]
+#[allow(
+    clippy::cast_precision_loss,
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss
+)]
+pub fn histogram(values: &[f64], bins: Option<usize>) -> Vec<HistogramBin> {
+    let values: Vec<f64> = values.iter().copied().filter(|v| v.is_finite()).collect();
+    let Some(min) = values.iter().copied().reduce(f64::min) else {
+        return Vec::new();
+    };
+    let max = values.iter().copied().fold(min, f64::max);
+    let count = bins
+        .unwrap_or_else(|| (values.len() as f64).log2().ceil() as usize + 1)
+        .clamp(1, MAX_BINS);
+    // Identical values get one unit-wide bin around them
+    let (start, width) = if max > min {
+        (min, (max - min) / count as f64)
+    } else {
+        (min - 0.5, 1.0 / count as f64)
+    };
+    let mut bins: Vec<_> = (0..count)
+        .map(|i| HistogramBin {
+            start: start + width * i as f64,
+            end: start + width * (i + 1) as f64,
+            count: 0,
+        })
+        .collect();
+    for value in values {
+        let index = (((value - start) / width) as usize).min(count - 1);
+        bins[index].count += 1;
+    }
+    bins
+}
+
+/// Most bins a histogram is split into.
+pub const MAX_BINS: usize = 200;
+
+/// Where each point of each series sits when the series are stacked: the
+/// `(from, to)` value span of `series[s].points[i]`. Points stack by index;
+/// positive values grow up from zero and negative values down.
+#[must_use]
+pub fn stack_series(series: &[DataSeries]) -> Vec<Vec<(f64, f64)>> {
+    let len = series.iter().map(|s| s.points.len()).max().unwrap_or(0);
+    let (mut above, mut below) = (vec![0.0; len], vec![0.0; len]);
+    series
+        .iter()
+        .map(|s| {
+            s.points
+                .iter()
+                .enumerate()
+                .map(|(i, p)| {
+                    let total = if p.y >= 0.0 {
+                        &mut above[i]
+                    } else {
+                        &mut below[i]
+                    };
+                    let from = *total;
+                    *total += p.y;
+                    (from, *total)
+                })
+                .collect()
+        })
+        .collect()
+}
+
 /// Create a chart element from configuration.
 #[must_use]
 #[allow(clippy::cast_precision_loss)]
 pub fn create_chart_element(config: &ChartConfig) -> Element {
     use canvas_core::{ElementKind, Transform};
 
-    let chart_type_str = match config.chart_type {
-        ChartType::Bar => "bar",
-        ChartType::BarHorizontal => "bar_horizontal",
-        ChartType::Line => "line",
-        ChartType::Area => "area",
-        ChartType::Pie => "pie",
-        ChartType::Donut => "donut",
-        ChartType::Scatter => "scatter",
-    };
-
     // Convert series to JSON
     let series_json: Vec<serde_json::Value> = config
         .series
@@ -585,18 +818,28 @@ pub fn create_chart_element(config: &ChartConfig) -> Element {
                 "name": s.name,
                 "color": s.color,
                 "points": s.points.iter().map(|p| {
-                    serde_json::json!({
+                    let mut point = serde_json::json!({
                         "x": p.x,
                         "y": p.y,
                         "label": p.label
-                    })
+                    });
+                    if let Some(z) = p.z {
+                        point["z"] = z.into();
+                    }
+                    if let Some(ohlc) = p.ohlc {
+                        point["open"] = ohlc.open.into();
+                        point["high"] = ohlc.high.into();
+                        point["low"] = ohlc.low.into();
+                        point["close"] = ohlc.close.into();
+                    }
+                    point
                 }).collect::<Vec<_>>()
             })
         })
         .collect();
 
     Element::new(ElementKind::Chart {
-        chart_type: chart_type_str.to_string(),
+        chart_type: config.chart_type.name().to_string(),
         data: serde_json::json!({
             "title": config.title,
             "x_label": config.x_label,
@@ -604,8 +847,12 @@ pub fn create_chart_element(config: &ChartConfig) -> Element {
             "x_labels": config.x_labels,
             "series": series_json,
             "background": config.background,
-            "show_legend": config.show_legend
+            "show_legend": config.show_legend,
+            "bins": config.bins,
+            "style": (config.style != ChartStyle::default()).then_some(&config.style),
+            "camera": config.chart_type.is_3d().then_some(config.camera)
         }),
+        binding: None,
     })
     .with_transform(Transform {
         x: 0.0,
@@ -614,20 +861,38 @@ pub fn create_chart_element(config: &ChartConfig) -> Element {
         height: config.height as f32,
         rotation: 0.0,
         z_index: 0,
+        auto_size: false,
     })
 }
 
-/// Parse chart configuration from JSON data.
+/// Parse chart configuration from JSON data, styled by the default
+/// [`ChartStyle`] and `data.style`.
 ///
 /// # Errors
 ///
 /// Returns an error if the JSON cannot be parsed.
-#[allow(clippy::cast_precision_loss)]
 pub fn parse_chart_config(
     chart_type: &str,
     data: &serde_json::Value,
     width: u32,
     height: u32,
+) -> RenderResult<ChartConfig> {
+    parse_chart_config_with_style(chart_type, data, width, height, &ChartStyle::default())
+}
+
+/// Parse chart configuration from JSON data, with `data.style` laid over
+/// `style`.
+///
+/// # Errors
+///
+/// Returns an error if the JSON cannot be parsed.
+#[allow(clippy::cast_precision_loss)]
+pub fn parse_chart_config_with_style(
+    chart_type: &str,
+    data: &serde_json::Value,
+    width: u32,
+    height: u32,
+    style: &ChartStyle,
 ) -> RenderResult<ChartConfig> {
     let chart_type: ChartType = chart_type
         .parse()
@@ -687,24 +952,36 @@ pub fn parse_chart_config(
                         .and_then(serde_json::Value::as_f64)
                         .unwrap_or(0.0),
                     label: label.as_str().map(String::from),
+                    ohlc: None,
+                    z: None,
                 })
                 .collect(),
         }]
+    } else if let Some(values) = data.get("values").and_then(|v| v.as_array()) {
+        parse_values(values, data.get("y_labels"))
     } else {
         Vec::new()
     };
 
+    let style = data
+        .get("style")
+        .map_or_else(|| style.clone(), |overrides| style.merged(overrides));
     let background = data
         .get("background")
         .and_then(serde_json::Value::as_str)
-        .unwrap_or("#ffffff")
-        .to_string();
+        .and_then(|s| CanvasColor::parse(s).ok())
+        .unwrap_or(style.background);
 
     let show_legend = data
         .get("show_legend")
         .and_then(serde_json::Value::as_bool)
         .unwrap_or(true);
 
+    let bins = data
+        .get("bins")
+        .and_then(serde_json::Value::as_u64)
+        .and_then(|bins| usize::try_from(bins).ok());
+
     Ok(ChartConfig {
         chart_type,
         title,
@@ -716,9 +993,53 @@ pub fn parse_chart_config(
         height,
         background,
         show_legend,
+        bins,
+        style,
+        camera: chart3d::camera(data),
     })
 }
 
+/// Parse a bare `values` array: numbers are one series, and rows of numbers
+/// (heatmaps) one series per row, named by `y_labels`.
+#[allow(clippy::cast_precision_loss)]
+fn parse_values(
+    values: &[serde_json::Value],
+    y_labels: Option<&serde_json::Value>,
+) -> Vec<DataSeries> {
+    let row = |values: &[serde_json::Value]| {
+        values
+            .iter()
+            .enumerate()
+            .map(|(i, v)| DataPoint {
+                x: i as f64,
+                y: v.as_f64().unwrap_or(0.0),
+                label: None,
+                ohlc: None,
+                z: None,
+            })
+            .collect()
+    };
+    if !values.iter().any(serde_json::Value::is_array) {
+        return vec![DataSeries {
+            name: "Data".to_string(),
+            color: None,
+            points: row(values),
+        }];
+    }
+    values
+        .iter()
+        .enumerate()
+        .map(|(i, v)| DataSeries {
+            name: y_labels
+                .and_then(|labels| labels.get(i))
+                .and_then(serde_json::Value::as_str)
+                .map_or_else(|| format!("Row {}", i + 1), String::from),
+            color: None,
+            points: v.as_array().map(|v| row(v)).unwrap_or_default(),
+        })
+        .collect()
+}
+
 /// Parse a data series from JSON.
 fn parse_series(value: &serde_json::Value, index: usize) -> DataSeries {
     let name = value
@@ -729,7 +1050,7 @@ fn parse_series(value: &serde_json::Value, index: usize) -> DataSeries {
     let color = value
         .get("color")
         .and_then(serde_json::Value::as_str)
-        .map(String::from);
+        .and_then(|s| CanvasColor::parse(s).ok());
 
     let points: Vec<DataPoint> = value
         .get("points")
@@ -746,27 +1067,40 @@ fn parse_series(value: &serde_json::Value, index: usize) -> DataSeries {
 
 /// Parse a data point from JSON.
 fn parse_data_point(value: &serde_json::Value) -> DataPoint {
+    let number = |key| value.get(key).and_then(serde_json::Value::as_f64);
+    let ohlc = match (
+        number("open"),
+        number("high"),
+        number("low"),
+        number("close"),
+    ) {
+        (Some(open), Some(high), Some(low), Some(close)) => Some(Ohlc {
+            open,
+            high,
+            low,
+            close,
+        }),
+        _ => None,
+    };
     DataPoint {
-        x: value
-            .get("x")
-            .and_then(serde_json::Value::as_f64)
-            .unwrap_or(0.0),
-        y: value
-            .get("y")
-            .and_then(serde_json::Value::as_f64)
-            .unwrap_or(0.0),
+        x: number("x").unwrap_or(0.0),
+        y: number("y").or(ohlc.map(|p| p.close)).unwrap_or(0.0),
         label: value
             .get("label")
             .and_then(serde_json::Value::as_str)
             .map(String::from),
+        ohlc,
+        z: number("z"),
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::chart_style::{LegendPosition, DEFAULT_PALETTE};
 
     #[test]
+    #[cfg(feature = "charts")]
     fn test_bar_chart_renders() {
         let config = ChartConfig {
             chart_type: ChartType::Bar,
@@ -780,16 +1114,22 @@ mod tests {
                         x: 0.0,
                         y: 10.0,
                         label: Some("A".to_string()),
+                        ohlc: None,
+                        z: None,
                     },
                     DataPoint {
                         x: 1.0,
                         y: 20.0,
                         label: Some("B".to_string()),
+                        ohlc: None,
+                        z: None,
                     },
                     DataPoint {
                         x: 2.0,
                         y: 15.0,
                         label: Some("C".to_string()),
+                        ohlc: None,
+                        z: None,
                     },
                 ],
             }],
@@ -805,28 +1145,35 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "charts")]
     fn test_line_chart_renders() {
         let config = ChartConfig {
             chart_type: ChartType::Line,
             title: Some("Test Line Chart".to_string()),
             series: vec![DataSeries {
                 name: "Series 1".to_string(),
-                color: Some("#ff0000".to_string()),
+                color: Some(CanvasColor::from_rgb(0xff_0000)),
                 points: vec![
                     DataPoint {
                         x: 0.0,
                         y: 5.0,
                         label: None,
+                        ohlc: None,
+                        z: None,
                     },
                     DataPoint {
                         x: 1.0,
                         y: 10.0,
                         label: None,
+                        ohlc: None,
+                        z: None,
                     },
                     DataPoint {
                         x: 2.0,
                         y: 7.0,
                         label: None,
+                        ohlc: None,
+                        z: None,
                     },
                 ],
             }],
@@ -840,6 +1187,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "charts")]
     fn test_pie_chart_renders() {
         let config = ChartConfig {
             chart_type: ChartType::Pie,
@@ -852,16 +1200,22 @@ mod tests {
                         x: 0.0,
                         y: 30.0,
                         label: Some("A".to_string()),
+                        ohlc: None,
+                        z: None,
                     },
                     DataPoint {
                         x: 1.0,
                         y: 50.0,
                         label: Some("B".to_string()),
+                        ohlc: None,
+                        z: None,
                     },
                     DataPoint {
                         x: 2.0,
                         y: 20.0,
                         label: Some("C".to_string()),
+                        ohlc: None,
+                        z: None,
                     },
                 ],
             }],
@@ -889,9 +1243,148 @@ mod tests {
     }
 
     #[test]
-    fn test_parse_hex_color() {
-        assert_eq!(parse_hex_color("#ff0000"), RGBColor(255, 0, 0));
-        assert_eq!(parse_hex_color("00ff00"), RGBColor(0, 255, 0));
-        assert_eq!(parse_hex_color("#0000ff"), RGBColor(0, 0, 255));
+    fn test_parse_candles_matrices_and_bins() {
+        let candles = serde_json::json!({
+            "series": [{ "points": [
+                { "x": 0, "open": 10, "high": 14, "low": 9, "close": 12 },
+                { "x": 1, "y": 3 }
+            ] }]
+        });
+        let config = parse_chart_config("ohlc", &candles, 400, 300).expect("Should parse");
+        assert_eq!(config.chart_type, ChartType::Candlestick);
+        let points = &config.series[0].points;
+        assert_eq!(
+            points[0].ohlc,
+            Some(Ohlc {
+                open: 10.0,
+                high: 14.0,
+                low: 9.0,
+                close: 12.0
+            })
+        );
+        // The close stands in for a missing y
+        assert!((points[0].y - 12.0).abs() < f64::EPSILON);
+        assert_eq!(points[1].ohlc, None);
+
+        let grid = serde_json::json!({
+            "values": [[1, 2, 3], [4, 5]],
+            "y_labels": ["Mon"]
+        });
+        let config = parse_chart_config("heatmap", &grid, 400, 300).expect("Should parse");
+        let names: Vec<_> = config.series.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, ["Mon", "Row 2"]);
+        assert_eq!(config.series[1].points.len(), 2);
+
+        let samples = serde_json::json!({ "values": [1, 2, 2, 3], "bins": 3 });
+        let config = parse_chart_config("histogram", &samples, 400, 300).expect("Should parse");
+        assert_eq!(config.bins, Some(3));
+        assert_eq!(config.series.len(), 1);
+        assert_eq!(config.series[0].points.len(), 4);
+    }
+
+    #[test]
+    fn test_histogram_bins_cover_the_range() {
+        let bins = histogram(&[0.0, 1.0, 2.5, 9.9, 10.0, f64::NAN], Some(4));
+        let counts: Vec<_> = bins.iter().map(|b| b.count).collect();
+        assert_eq!(counts, [2, 1, 0, 2]);
+        assert!((bins[0].start).abs() < f64::EPSILON);
+        assert!((bins[3].end - 10.0).abs() < f64::EPSILON);
+        // Sturges' rule: ceil(log2 8) + 1
+        assert_eq!(histogram(&[1.0; 8], None).len(), 4);
+        assert!(histogram(&[], None).is_empty());
+    }
+
+    #[test]
+    fn test_stacked_series_grow_away_from_zero() {
+        let series = |values: &[f64]| DataSeries {
+            name: String::new(),
+            color: None,
+            points: values
+                .iter()
+                .map(|&y| DataPoint {
+                    x: 0.0,
+                    y,
+                    label: None,
+                    ohlc: None,
+                    z: None,
+                })
+                .collect(),
+        };
+        let stacks = stack_series(&[series(&[1.0, -1.0]), series(&[2.0, -2.0, 5.0])]);
+        assert_eq!(stacks[0], [(0.0, 1.0), (0.0, -1.0)]);
+        assert_eq!(stacks[1], [(1.0, 3.0), (-1.0, -3.0), (0.0, 5.0)]);
+    }
+
+    #[test]
+    #[cfg(feature = "charts")]
+    fn test_new_chart_types_render() {
+        let data = serde_json::json!({
+            "series": [
+                { "points": [{ "x": 0, "y": 1, "open": 1, "high": 3, "low": 0, "close": 2 }] },
+                { "points": [{ "x": 0, "y": 2 }] }
+            ]
+        });
+        for chart_type in [
+            "candlestick",
+            "heatmap",
+            "histogram",
+            "stacked_bar",
+            "stacked_area",
+        ] {
+            let config = parse_chart_config(chart_type, &data, 200, 150).expect("Should parse");
+            let pixels = render_chart_to_buffer(&config).expect("Should render");
+            assert_eq!(pixels.len(), 200 * 150 * 4);
+        }
+    }
+
+    #[test]
+    fn test_series_colors() {
+        let data = serde_json::json!({
+            "series": [
+                { "name": "a", "color": "rgb(255, 0, 0)", "points": [] },
+                { "name": "b", "color": "not a color", "points": [] }
+            ],
+            "background": "#0000ff"
+        });
+        let config = parse_chart_config("line", &data, 400, 300).expect("Should parse");
+        assert_eq!(config.background, CanvasColor::from_rgb(0x00_00ff));
+        assert_eq!(
+            config.style.series_color(0, config.series[0].color),
+            CanvasColor::rgb(255, 0, 0)
+        );
+        // Unparseable colors fall back to the palette
+        assert_eq!(
+            config.style.series_color(1, config.series[1].color),
+            DEFAULT_PALETTE[1]
+        );
+    }
+
+    #[test]
+    fn test_chart_style_from_data_over_the_renderer_default() {
+        let data = serde_json::json!({
+            "style": { "dark": true, "legend": "top_left" },
+            "labels": ["a", "b"],
+            "values": [1, 2]
+        });
+        let renderer = ChartStyle {
+            palette: vec![CanvasColor::BLACK],
+            ..ChartStyle::default()
+        };
+        let config =
+            parse_chart_config_with_style("bar", &data, 400, 300, &renderer).expect("Should parse");
+        assert_eq!(config.background, ChartStyle::dark().background);
+        assert_eq!(config.style.legend, LegendPosition::TopLeft);
+        assert_eq!(config.style.series_color(0, None), CanvasColor::BLACK);
+
+        // Styled charts keep their style through the element
+        let element = create_chart_element(&config);
+        let canvas_core::ElementKind::Chart { data, .. } = &element.kind else {
+            panic!("not a chart");
+        };
+        let reparsed =
+            parse_chart_config_with_style("bar", data, 400, 300, &renderer).expect("Should parse");
+        assert_eq!(reparsed.style, config.style);
+        #[cfg(feature = "charts")]
+        render_chart_to_buffer(&config).expect("Should render");
     }
 }
@@ -0,0 +1,420 @@
+//! # 3D Chart Meshes
+//!
+//! Builds `scatter3d` and `surface` charts as a [`Model`] filling the data
+//! cube of [`canvas_core::chart3d`], and projects that model into flat, lit
+//! triangles for backends without a 3D pipeline.
+//!
+//! ```text
+//!                      ┌─▶ wgpu mesh pipeline (depth tested, lit per pixel)
+//! ChartConfig ─▶ Model ┤
+//!                      └─▶ project() ─▶ back-to-front fills ─▶ ChartGeometry
+//! ```
+//!
+//! Scatter points become small octahedra, one mesh per series. A surface's
+//! rows (series) run along Z and its columns (points) along X, with values
+//! up Y; its cells are split into [`HEAT_BANDS`] meshes by height so each
+//! takes one color of the heatmap scale. A floor under the cube grounds the
+//! data.
+
+use canvas_core::model3d::{Lighting, ModelCamera};
+use canvas_core::Color;
+
+use crate::chart::{ChartConfig, ChartType, DataPoint};
+use crate::chart_mesh::heat;
+use crate::model3d::{Material, Model, Primitive};
+use crate::spatial::{Camera, Mat4};
+
+/// Number of colors a surface is shaded in, lowest to highest.
+pub const HEAT_BANDS: usize = 8;
+
+/// Distance from a scatter point's center to its tips, in cube units.
+const MARKER_RADIUS: f32 = 0.05;
+
+/// How rough chart surfaces look.
+const ROUGHNESS: f32 = 0.8;
+
+/// Build a 3D chart's model. Other chart types get only the floor.
+#[must_use]
+pub fn chart_model(config: &ChartConfig) -> Model {
+    let mut primitives = vec![floor(config.style.grid_color)];
+    match config.chart_type {
+        ChartType::Scatter3D => primitives.extend(scatter(config)),
+        ChartType::Surface => primitives.extend(surface(config)),
+        _ => {}
+    }
+    Model {
+        primitives,
+        min: [-1.0; 3],
+        max: [1.0; 3],
+    }
+}
+
+/// Project `model`, seen through `camera`, into `rect` (x, y, width,
+/// height) as lit triangles ordered back to front, so painting them in
+/// order hides what is behind. Triangles lying on the floor go under
+/// everything when seen from above, and over it from below, since the data
+/// is all above the floor. Triangles reaching behind the camera are left
+/// out.
+#[must_use]
+pub fn project(model: &Model, camera: &ModelCamera, rect: [f32; 4]) -> Vec<([[f32; 2]; 3], Color)> {
+    let [x, y, width, height] = rect;
+    if width <= 0.0 || height <= 0.0 {
+        return Vec::new();
+    }
+    let view = Camera::from_model(camera);
+    let clip_from_model = view
+        .projection_matrix(width / height)
+        .mul(&view.view_matrix());
+    let eye = camera.position();
+    let lighting = Lighting::default();
+
+    let mut triangles = Vec::new();
+    for primitive in &model.primitives {
+        let [red, green, blue, _] = primitive.material.base_color;
+        'triangle: for triangle in primitive.indices.chunks_exact(3) {
+            let corners = [0, 1, 2].map(|i| primitive.positions[triangle[i] as usize]);
+            let mut points = [[0.0; 2]; 3];
+            let mut depth: f32 = 0.0;
+            for (point, corner) in points.iter_mut().zip(corners) {
+                let [cx, cy, _, cw] = transform(&clip_from_model, corner);
+                if cw <= view.near {
+                    continue 'triangle;
+                }
+                *point = [
+                    x + f32::midpoint(cx / cw, 1.0) * width,
+                    y + (1.0 - cy / cw) / 2.0 * height,
+                ];
+                depth += cw;
+            }
+            let centroid = std::array::from_fn(|axis| {
+                (corners[0][axis] + corners[1][axis] + corners[2][axis]) / 3.0
+            });
+            let mut normal = cross(sub(corners[1], corners[0]), sub(corners[2], corners[0]));
+            // Both sides of a surface are lit, so face the normal at the eye
+            if dot(normal, sub(eye, centroid)) < 0.0 {
+                normal = normal.map(|n| -n);
+            }
+            if corners.iter().all(|corner| corner[1] <= -1.0) {
+                depth = if eye[1] > -1.0 {
+                    f32::INFINITY
+                } else {
+                    f32::NEG_INFINITY
+                };
+            }
+            let [red, green, blue] = lighting.shade([red, green, blue], centroid, normal);
+            let color = Color::rgb(srgb(red), srgb(green), srgb(blue));
+            triangles.push((depth, points, color));
+        }
+    }
+    triangles.sort_by(|a, b| b.0.total_cmp(&a.0));
+    triangles
+        .into_iter()
+        .map(|(_, points, color)| (points, color))
+        .collect()
+}
+
+/// One octahedron per point, one mesh per series. Points without a `z`
+/// take their series' index, so each series gets its own row.
+#[allow(clippy::cast_precision_loss)] // Series counts are small
+fn scatter(config: &ChartConfig) -> Vec<Primitive> {
+    let depth = |index: usize, point: &DataPoint| point.z.unwrap_or(index as f64);
+    let points = || {
+        config
+            .series
+            .iter()
+            .enumerate()
+            .flat_map(|(i, s)| s.points.iter().map(move |p| (i, p)))
+    };
+    let xs = bounds(points().map(|(_, p)| p.x));
+    let ys = bounds(points().map(|(_, p)| p.y));
+    let zs = bounds(points().map(|(i, p)| depth(i, p)));
+
+    config
+        .series
+        .iter()
+        .enumerate()
+        .filter(|(_, series)| !series.points.is_empty())
+        .map(|(i, series)| {
+            let mut primitive = mesh(config.style.series_color(i, series.color));
+            for point in &series.points {
+                let center = [
+                    unit(xs, point.x),
+                    unit(ys, point.y),
+                    unit(zs, depth(i, point)),
+                ];
+                octahedron(&mut primitive, center);
+            }
+            primitive
+        })
+        .collect()
+}
+
+/// A smooth surface over the grid of series rows and point columns, cut
+/// off at the shortest row. Grids smaller than two by two have no surface.
+#[allow(
+    clippy::cast_precision_loss,
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss
+)]
+fn surface(config: &ChartConfig) -> Vec<Primitive> {
+    let rows = config.series.len();
+    let columns = config
+        .series
+        .iter()
+        .map(|s| s.points.len())
+        .min()
+        .unwrap_or(0);
+    if rows < 2 || columns < 2 {
+        return Vec::new();
+    }
+    let value = |r: usize, c: usize| config.series[r].points[c].y;
+    let range = bounds((0..rows).flat_map(|r| (0..columns).map(move |c| value(r, c))));
+    let height = |r: usize, c: usize| unit(range, value(r, c));
+    let along = |i: usize, count: usize| -1.0 + 2.0 * i as f32 / (count - 1) as f32;
+    let position = |r: usize, c: usize| [along(c, columns), height(r, c), along(r, rows)];
+    // Slopes from the neighbours on each side, or the one side at the edges
+    let normal = |r: usize, c: usize| {
+        let (left, right) = (c.saturating_sub(1), (c + 1).min(columns - 1));
+        let (back, front) = (r.saturating_sub(1), (r + 1).min(rows - 1));
+        let dx =
+            (height(r, right) - height(r, left)) / (along(right, columns) - along(left, columns));
+        let dz = (height(front, c) - height(back, c)) / (along(front, rows) - along(back, rows));
+        normalize([-dx, 1.0, -dz])
+    };
+
+    let mut bands: Vec<Primitive> = (0..HEAT_BANDS)
+        .map(|band| mesh(heat((band as f32 + 0.5) / HEAT_BANDS as f32)))
+        .collect();
+    for r in 0..rows - 1 {
+        for c in 0..columns - 1 {
+            let corners = [(r, c), (r, c + 1), (r + 1, c), (r + 1, c + 1)];
+            let mean = corners.iter().map(|&(r, c)| height(r, c)).sum::<f32>() / 4.0;
+            let band =
+                ((f32::midpoint(mean, 1.0) * HEAT_BANDS as f32) as usize).min(HEAT_BANDS - 1);
+            let primitive = &mut bands[band];
+            let first = primitive.positions.len() as u32;
+            for (r, c) in corners {
+                primitive.positions.push(position(r, c));
+                primitive.normals.push(normal(r, c));
+            }
+            primitive
+                .indices
+                .extend([0, 2, 1, 1, 2, 3].map(|i| first + i));
+        }
+    }
+    bands.retain(|band| !band.indices.is_empty());
+    bands
+}
+
+/// The square under the cube.
+fn floor(color: Color) -> Primitive {
+    let mut primitive = mesh(color);
+    primitive.positions = vec![
+        [-1.0, -1.0, -1.0],
+        [1.0, -1.0, -1.0],
+        [-1.0, -1.0, 1.0],
+        [1.0, -1.0, 1.0],
+    ];
+    primitive.normals = vec![[0.0, 1.0, 0.0]; 4];
+    primitive.indices = vec![0, 2, 1, 1, 2, 3];
+    primitive
+}
+
+/// An empty mesh in a matte `color`.
+fn mesh(color: Color) -> Primitive {
+    Primitive {
+        positions: Vec::new(),
+        normals: Vec::new(),
+        indices: Vec::new(),
+        material: Material {
+            base_color: [
+                linear(color.r),
+                linear(color.g),
+                linear(color.b),
+                f32::from(color.a) / 255.0,
+            ],
+            metallic: 0.0,
+            roughness: ROUGHNESS,
+            emissive: [0.0; 3],
+        },
+    }
+}
+
+/// Add an octahedron around `center`, flat shaded: each face has its own
+/// corners so its normal is its own.
+#[allow(clippy::cast_possible_truncation)] // Meshes stay far below u32::MAX vertices
+fn octahedron(primitive: &mut Primitive, center: [f32; 3]) {
+    for sx in [-1.0_f32, 1.0] {
+        for sy in [-1.0_f32, 1.0] {
+            for sz in [-1.0_f32, 1.0] {
+                let tip_x = [center[0] + sx * MARKER_RADIUS, center[1], center[2]];
+                let tip_y = [center[0], center[1] + sy * MARKER_RADIUS, center[2]];
+                let tip_z = [center[0], center[1], center[2] + sz * MARKER_RADIUS];
+                // Counter-clockwise seen from outside
+                let face = if sx * sy * sz > 0.0 {
+                    [tip_x, tip_y, tip_z]
+                } else {
+                    [tip_x, tip_z, tip_y]
+                };
+                let first = primitive.positions.len() as u32;
+                primitive.positions.extend(face);
+                primitive.normals.extend([normalize([sx, sy, sz]); 3]);
+                primitive.indices.extend([first, first + 1, first + 2]);
+            }
+        }
+    }
+}
+
+/// Lowest and highest of `values`, or `(0, 0)` for none.
+fn bounds(values: impl Iterator<Item = f64>) -> (f64, f64) {
+    values
+        .filter(|v| v.is_finite())
+        .fold(None, |range, v| match range {
+            None => Some((v, v)),
+            Some((lo, hi)) => Some((f64::min(lo, v), f64::max(hi, v))),
+        })
+        .unwrap_or((0.0, 0.0))
+}
+
+/// `value` scaled from `range` to -1..=1, or 0 when the range is a point.
+#[allow(clippy::cast_possible_truncation)] // Cube coordinates fit in f32
+fn unit((lo, hi): (f64, f64), value: f64) -> f32 {
+    if hi - lo <= f64::EPSILON || !value.is_finite() {
+        0.0
+    } else {
+        (((value - lo) / (hi - lo)) * 2.0 - 1.0).clamp(-1.0, 1.0) as f32
+    }
+}
+
+/// Transform a point by a column-major matrix into clip space.
+fn transform(m: &Mat4, [x, y, z]: [f32; 3]) -> [f32; 4] {
+    std::array::from_fn(|row| {
+        m.data[row] * x + m.data[4 + row] * y + m.data[8 + row] * z + m.data[12 + row]
+    })
+}
+
+fn linear(channel: u8) -> f32 {
+    let c = f32::from(channel) / 255.0;
+    if c <= 0.040_45 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)] // Clamped to 0..=255
+fn srgb(channel: f32) -> u8 {
+    let c = channel.clamp(0.0, 1.0);
+    let c = if c <= 0.003_130_8 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    };
+    (c * 255.0).round() as u8
+}
+
+fn sub(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn dot(a: [f32; 3], b: [f32; 3]) -> f32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn cross(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+fn normalize(v: [f32; 3]) -> [f32; 3] {
+    let length = dot(v, v).sqrt().max(f32::EPSILON);
+    v.map(|c| c / length)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chart::parse_chart_config;
+
+    fn chart_config(chart_type: &str, data: &serde_json::Value) -> ChartConfig {
+        parse_chart_config(chart_type, data, 400, 300).expect("valid chart")
+    }
+
+    #[test]
+    fn test_scatter_has_a_mesh_per_series() {
+        let config = chart_config(
+            "scatter3d",
+            &serde_json::json!({ "series": [
+                { "name": "a", "points": [{ "x": 0, "y": 0, "z": 0 }, { "x": 4, "y": 2, "z": 8 }] },
+                { "name": "b", "points": [{ "x": 2, "y": 1, "z": 4 }] },
+            ] }),
+        );
+        let model = chart_model(&config);
+        // Floor, then one mesh per series with eight faces per point
+        assert_eq!(model.primitives.len(), 3);
+        assert_eq!(model.primitives[1].indices.len(), 2 * 8 * 3);
+        assert_eq!(model.primitives[2].indices.len(), 8 * 3);
+        for primitive in &model.primitives {
+            assert_eq!(primitive.positions.len(), primitive.normals.len());
+            for position in &primitive.positions {
+                assert!(position
+                    .iter()
+                    .all(|c| c.abs() <= 1.0 + MARKER_RADIUS + f32::EPSILON));
+            }
+        }
+        // The highest point sits at the top corner of the cube
+        let top = model.primitives[1]
+            .positions
+            .iter()
+            .map(|p| p[1])
+            .fold(f32::MIN, f32::max);
+        assert!((top - 1.0 - MARKER_RADIUS).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_surface_cells_split_into_heat_bands() {
+        let config = chart_config(
+            "surface",
+            &serde_json::json!({ "values": [[0, 0, 0], [0, 9, 0], [0, 0, 0]] }),
+        );
+        let model = chart_model(&config);
+        let cells: usize = model.primitives[1..]
+            .iter()
+            .map(|p| p.indices.len() / 6)
+            .sum();
+        assert_eq!(cells, 4);
+        assert!(model.primitives.len() - 1 <= HEAT_BANDS);
+
+        // A single row has no surface, only the floor
+        let flat = chart_config("surface", &serde_json::json!({ "values": [1, 2, 3] }));
+        assert_eq!(chart_model(&flat).primitives.len(), 1);
+    }
+
+    #[test]
+    fn test_projection_stays_in_rect_back_to_front() {
+        let config = chart_config(
+            "surface",
+            &serde_json::json!({ "values": [[0, 1, 2], [1, 2, 3], [2, 3, 4]] }),
+        );
+        let model = chart_model(&config);
+        let rect = [20.0, 40.0, 300.0, 200.0];
+        let triangles = project(&model, &config.camera, rect);
+        assert_eq!(triangles.len(), 2 + 4 * 2);
+        for (points, _) in &triangles {
+            for [x, y] in points {
+                assert!((rect[0]..=rect[0] + rect[2]).contains(x));
+                assert!((rect[1]..=rect[1] + rect[3]).contains(y));
+            }
+        }
+        // Seen from above, the floor is painted first
+        let floor = Model {
+            primitives: vec![floor(config.style.grid_color)],
+            ..model.clone()
+        };
+        assert_eq!(triangles[..2], project(&floor, &config.camera, rect)[..]);
+        assert!(project(&model, &config.camera, [0.0, 0.0, 0.0, 10.0]).is_empty());
+    }
+}
@@ -0,0 +1,1265 @@
+//! # Chart Tessellation
+//!
+//! Draws charts without plotters, so they render in every build, WASM
+//! included. A [`ChartConfig`] is laid out as [`ChartGeometry`]: filled
+//! convex polygons and stroked polylines in canvas coordinates, plus the
+//! text labels that go with them.
+//!
+//! ```text
+//!                    ┌─▶ tessellate() ─▶ [ChartVertex] ─▶ wgpu triangles
+//! ChartConfig ─▶ ChartGeometry
+//!                    └─▶ paths + labels ───────────────▶ 2D canvas (WebGL2)
+//! ```
+//!
+//! Bars, candles, heatmap cells and legend swatches are rectangles, areas
+//! are trapezoids under each line segment (or between stacked ones), and pie
+//! slices are split into wedges of at most [`ARC_STEP`] radians, so every
+//! fill is convex and becomes a triangle fan.
+//! Strokes become one quad per segment, extended by half the line width at
+//! each end so joins have no gaps.
+//!
+//! 3D charts are projected by [`chart3d`] into flat triangles, recorded as
+//! a [`ChartScene`] so a backend with a 3D pipeline can draw the model
+//! itself in place of those paths.
+//!
+//! Fonts, colors, gridlines, tick formats and legend placement come from
+//! the config's [`ChartStyle`]. The margins below are for the default font
+//! sizes and grow with larger ones.
+
+use std::f32::consts::{FRAC_PI_2, TAU};
+
+use canvas_core::model3d::ModelCamera;
+use canvas_core::{Color, Element, ElementKind, MeasureContext};
+
+use crate::chart::{
+    histogram, parse_chart_config_with_style, stack_series, ChartConfig, ChartType, Ohlc,
+};
+use crate::chart3d;
+use crate::chart_style::ChartStyle;
+use crate::error::{RenderError, RenderResult};
+use crate::model3d::Model;
+
+/// Largest angle one pie wedge spans, in radians.
+pub const ARC_STEP: f32 = TAU / 64.0;
+
+/// Space around the plot for the title, tick labels and category labels.
+const MARGIN: f32 = 10.0;
+const TITLE_HEIGHT: f32 = 24.0;
+const VALUE_AXIS_SIZE: f32 = 44.0;
+const CATEGORY_AXIS_SIZE: f32 = 28.0;
+/// Extra space for each of the `x_label` and `y_label` axis titles.
+const AXIS_TITLE_SIZE: f32 = 16.0;
+/// Closest category labels are spaced before some are skipped.
+const MIN_LABEL_SPACING: f32 = 36.0;
+/// Height of a legend row, and the side of its swatch.
+const LEGEND_ROW: f32 = 18.0;
+const SWATCH_SIZE: f32 = 12.0;
+/// Width of the legend column beside a pie.
+const PIE_LEGEND_WIDTH: f32 = 100.0;
+
+/// Font sizes the sizes above are for.
+const TITLE_SIZE: f32 = 16.0;
+const LABEL_SIZE: f32 = 11.0;
+
+/// Stroke width of line series.
+const LINE_WIDTH: f32 = 2.0;
+/// Radius of scatter points.
+const POINT_RADIUS: f32 = 5.0;
+/// Opacity of the fill under area series.
+const AREA_ALPHA: u8 = 77;
+/// Opacity of stacked area bands.
+const STACKED_ALPHA: u8 = 179;
+/// Candles that closed at or above their open, and below it.
+const RISING_COLOR: Color = Color::from_rgb(0x26_a69a);
+const FALLING_COLOR: Color = Color::from_rgb(0xef_5350);
+/// Heatmap cells at the lowest and highest values.
+const HEAT_LOW: Color = Color::from_rgb(0xde_ebf7);
+const HEAT_HIGH: Color = Color::from_rgb(0x08_519c);
+/// Width of a heatmap's color scale, with its labels.
+const SCALE_WIDTH: f32 = 56.0;
+
+/// One shape of a chart, in canvas coordinates.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChartPath {
+    /// A filled convex polygon.
+    Fill {
+        /// Corners, in order.
+        points: Vec<[f32; 2]>,
+        /// Fill color.
+        color: Color,
+    },
+    /// An open polyline.
+    Stroke {
+        /// Points along the line.
+        points: Vec<[f32; 2]>,
+        /// Line width.
+        width: f32,
+        /// Line color.
+        color: Color,
+    },
+}
+
+/// A line of text on a chart.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChartLabel {
+    /// The text.
+    pub text: String,
+    /// Box the text hangs from the top of: x, y, width, height.
+    pub rect: [f32; 4],
+    /// Font size.
+    pub font_size: f32,
+    /// Text color.
+    pub color: Color,
+}
+
+/// A vertex of a tessellated chart: three make a triangle.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct ChartVertex {
+    /// Canvas coordinates.
+    pub position: [f32; 2],
+    /// Straight-alpha color, with opacity applied.
+    pub color: [f32; 4],
+}
+
+/// A chart laid out as shapes and labels, in paint order.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ChartGeometry {
+    /// Shapes, back to front.
+    pub paths: Vec<ChartPath>,
+    /// Labels, drawn over the shapes.
+    pub labels: Vec<ChartLabel>,
+    /// The 3D plot of a 3D chart.
+    pub scene: Option<ChartScene>,
+}
+
+/// The 3D plot of a `scatter3d` or `surface` chart.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChartScene {
+    /// Where the plot is drawn: x, y, width, height.
+    pub rect: [f32; 4],
+    /// The data cube's meshes.
+    pub model: Model,
+    /// View of the cube.
+    pub camera: ModelCamera,
+    /// The [`ChartGeometry::paths`] holding the projected plot, which
+    /// backends drawing `model` leave out.
+    pub paths: std::ops::Range<usize>,
+}
+
+impl ChartGeometry {
+    /// Lay out `config` over `rect` (x, y, width, height).
+    #[must_use]
+    pub fn new(config: &ChartConfig, rect: [f32; 4]) -> Self {
+        let mut geometry = Self::default();
+        geometry.fill(rect_points(rect), config.background);
+        let [x, y, width, height] = rect;
+        let style = &config.style;
+        let mut top = y + MARGIN;
+        if let Some(title) = config.title.as_deref().filter(|t| !t.is_empty()) {
+            let at = [x + width / 2.0, top];
+            geometry.text(
+                title,
+                at,
+                style.title_size,
+                style.title_color,
+                Anchor::Center,
+            );
+            top += TITLE_HEIGHT * style.title_size / TITLE_SIZE;
+        }
+        let area = [
+            x + MARGIN,
+            top,
+            width - 2.0 * MARGIN,
+            y + height - MARGIN - top,
+        ];
+        if area[2] <= 0.0 || area[3] <= 0.0 {
+            return geometry;
+        }
+
+        let plot = plot_rect(config, area);
+        match config.chart_type {
+            ChartType::Scatter3D | ChartType::Surface => {
+                geometry.scene(config, area);
+                return geometry;
+            }
+            ChartType::Pie | ChartType::Donut => {
+                geometry.pie(config, area);
+                return geometry;
+            }
+            ChartType::Bar | ChartType::BarHorizontal | ChartType::StackedBar => {
+                geometry.bars(config, plot);
+            }
+            ChartType::Line | ChartType::Area | ChartType::Scatter | ChartType::StackedArea => {
+                geometry.plot(config, plot);
+            }
+            ChartType::Candlestick => geometry.candlesticks(config, plot),
+            ChartType::Heatmap => geometry.heatmap(config, plot),
+            ChartType::Histogram => geometry.histogram(config, plot),
+        }
+        geometry.axis_titles(config, area, plot);
+        geometry
+    }
+
+    /// Lay out a chart element over its transform, with its `data.style`
+    /// laid over `style`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the element is not a chart or its chart type is
+    /// unknown.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    pub fn from_element(element: &Element, style: &ChartStyle) -> RenderResult<Self> {
+        let ElementKind::Chart {
+            chart_type, data, ..
+        } = &element.kind
+        else {
+            return Err(RenderError::Resource(format!(
+                "Not a chart: {}",
+                element.kind.type_name()
+            )));
+        };
+        let t = &element.transform;
+        let config = parse_chart_config_with_style(
+            chart_type,
+            data,
+            t.width.max(0.0) as u32,
+            t.height.max(0.0) as u32,
+            style,
+        )?;
+        Ok(Self::new(&config, [t.x, t.y, t.width, t.height]))
+    }
+
+    /// Triangles covering every path, with `opacity` applied to their
+    /// colors. Labels are left to the caller's text rendering.
+    #[must_use]
+    pub fn tessellate(&self, opacity: f32) -> Vec<ChartVertex> {
+        let mut vertices = Vec::new();
+        for path in &self.paths {
+            match path {
+                ChartPath::Fill { points, color } => {
+                    let color = rgba(*color, opacity);
+                    for pair in points.windows(2).skip(1) {
+                        for position in [points[0], pair[0], pair[1]] {
+                            vertices.push(ChartVertex { position, color });
+                        }
+                    }
+                }
+                ChartPath::Stroke {
+                    points,
+                    width,
+                    color,
+                } => {
+                    let color = rgba(*color, opacity);
+                    for segment in points.windows(2) {
+                        let Some(corners) = segment_quad(segment[0], segment[1], *width) else {
+                            continue;
+                        };
+                        for i in [0, 1, 2, 0, 2, 3] {
+                            vertices.push(ChartVertex {
+                                position: corners[i],
+                                color,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+        vertices
+    }
+
+    /// A 3D chart's projected plot, with a legend for scatter series.
+    fn scene(&mut self, config: &ChartConfig, area: [f32; 4]) {
+        let model = chart3d::chart_model(config);
+        let start = self.paths.len();
+        for (points, color) in chart3d::project(&model, &config.camera, area) {
+            self.fill(points.to_vec(), color);
+        }
+        self.scene = Some(ChartScene {
+            rect: area,
+            model,
+            camera: config.camera,
+            paths: start..self.paths.len(),
+        });
+        if config.chart_type == ChartType::Scatter3D {
+            self.legend(config, area);
+        }
+    }
+
+    fn fill(&mut self, points: Vec<[f32; 2]>, color: Color) {
+        self.paths.push(ChartPath::Fill { points, color });
+    }
+
+    fn stroke(&mut self, points: Vec<[f32; 2]>, width: f32, color: Color) {
+        self.paths.push(ChartPath::Stroke {
+            points,
+            width,
+            color,
+        });
+    }
+
+    /// Add a label in the style's label font whose `anchor` point sits at
+    /// `at` (the top of the text).
+    fn label(&mut self, text: &str, at: [f32; 2], style: &ChartStyle, anchor: Anchor) {
+        self.text(text, at, style.label_size, style.text_color, anchor);
+    }
+
+    /// Add text whose `anchor` point sits at `at` (the top of the text).
+    fn text(&mut self, text: &str, at: [f32; 2], font_size: f32, color: Color, anchor: Anchor) {
+        let size = MeasureContext::default().text_size(text, font_size, f32::INFINITY);
+        let x = match anchor {
+            Anchor::Start => at[0],
+            Anchor::Center => at[0] - size.width / 2.0,
+            Anchor::End => at[0] - size.width,
+        };
+        self.labels.push(ChartLabel {
+            text: text.to_string(),
+            // Slack so text renderers measuring a little wider do not wrap
+            rect: [x, at[1], size.width + font_size, size.height],
+            font_size,
+            color,
+        });
+    }
+
+    /// Gridlines and tick labels along the value axis, then the axes.
+    fn axes(&mut self, style: &ChartStyle, plot: [f32; 4], values: Range, horizontal: bool) {
+        let [x, y, width, height] = plot;
+        let ticks = style.ticks.max(2);
+        for i in 0..ticks {
+            #[allow(clippy::cast_precision_loss)] // A handful of lines
+            let t = i as f32 / (ticks - 1) as f32;
+            let value = values.lo + (values.hi - values.lo) * f64::from(t);
+            let text = style.value_axis.format(value);
+            let grid = style.grid && i > 0;
+            if horizontal {
+                let gx = x + t * width;
+                if grid {
+                    self.stroke(vec![[gx, y], [gx, y + height]], 1.0, style.grid_color);
+                }
+                self.label(&text, [gx, y + height + 4.0], style, Anchor::Center);
+            } else {
+                let gy = y + height - t * height;
+                if grid {
+                    self.stroke(vec![[x, gy], [x + width, gy]], 1.0, style.grid_color);
+                }
+                let at = [x - 4.0, gy - style.label_size / 2.0];
+                self.label(&text, at, style, Anchor::End);
+            }
+        }
+        self.stroke(
+            vec![[x, y], [x, y + height], [x + width, y + height]],
+            1.0,
+            style.axis_color,
+        );
+    }
+
+    /// Labels under (or, for horizontal bars, beside) each of `count`
+    /// equal slots along the category axis, skipping some when crowded.
+    #[allow(clippy::cast_precision_loss)]
+    fn category_labels(&mut self, config: &ChartConfig, plot: [f32; 4], count: usize) {
+        let horizontal = config.chart_type == ChartType::BarHorizontal;
+        let [x, y, width, height] = plot;
+        let extent = if horizontal { height } else { width };
+        let slot = extent / count as f32;
+        let first = config.series.first().map_or(&[][..], |s| &s.points[..]);
+        let style = &config.style;
+        for i in (0..count).step_by(label_step(style, count, extent)) {
+            let label = config
+                .x_labels
+                .get(i)
+                .cloned()
+                .or_else(|| first.get(i).and_then(|p| p.label.clone()))
+                .unwrap_or_else(|| i.to_string());
+            let center = i as f32 * slot + slot / 2.0;
+            if horizontal {
+                let at = [x - 4.0, y + center - style.label_size / 2.0];
+                self.label(&label, at, style, Anchor::End);
+            } else {
+                let at = [x + center, y + height + 4.0];
+                self.label(&label, at, style, Anchor::Center);
+            }
+        }
+    }
+
+    /// The `x_label` under the category labels and the `y_label` over the
+    /// value axis, in the space [`plot_rect`] leaves for them.
+    fn axis_titles(&mut self, config: &ChartConfig, area: [f32; 4], plot: [f32; 4]) {
+        let style = &config.style;
+        if let Some(title) = axis_title(config.x_label.as_ref()) {
+            let at = [
+                plot[0] + plot[2] / 2.0,
+                area[1] + area[3] - style.label_size - 2.0,
+            ];
+            self.label(title, at, style, Anchor::Center);
+        }
+        if let Some(title) = axis_title(config.y_label.as_ref()) {
+            self.label(title, [area[0], area[1]], style, Anchor::Start);
+        }
+    }
+
+    /// Swatches and names of the series, stacked in the plot corner the
+    /// style's legend position names.
+    fn legend(&mut self, config: &ChartConfig, plot: [f32; 4]) {
+        if !config.show_legend || config.series.len() < 2 {
+            return;
+        }
+        let style = &config.style;
+        let measure = MeasureContext::default();
+        let widest = config
+            .series
+            .iter()
+            .map(|s| {
+                measure
+                    .text_size(&s.name, style.label_size, f32::INFINITY)
+                    .width
+            })
+            .fold(0.0, f32::max);
+        let left = if style.legend.is_left() {
+            plot[0] + 8.0
+        } else {
+            plot[0] + plot[2] - widest - SWATCH_SIZE - 12.0
+        };
+        let entries = config
+            .series
+            .iter()
+            .enumerate()
+            .map(|(i, series)| (series.name.clone(), style.series_color(i, series.color)));
+        self.legend_rows(style, [left, plot[1], plot[3]], entries);
+    }
+
+    /// A swatch and name per entry, one row each, starting at `left` from
+    /// the top or bottom of the `top`..`top + height` span.
+    fn legend_rows(
+        &mut self,
+        style: &ChartStyle,
+        [left, top, height]: [f32; 3],
+        entries: impl ExactSizeIterator<Item = (String, Color)>,
+    ) {
+        let row = legend_row(style);
+        let first = if style.legend.is_bottom() {
+            #[allow(clippy::cast_precision_loss)] // A handful of entries
+            let rows = entries.len() as f32 * row;
+            top + height - 4.0 - rows + (row - SWATCH_SIZE)
+        } else {
+            top + 4.0
+        };
+        for (i, (name, color)) in entries.enumerate() {
+            #[allow(clippy::cast_precision_loss)]
+            let top = first + i as f32 * row;
+            self.fill(rect_points([left, top, SWATCH_SIZE, SWATCH_SIZE]), color);
+            self.label(&name, [left + SWATCH_SIZE + 6.0, top], style, Anchor::Start);
+        }
+    }
+
+    /// Vertical or horizontal bars, grouped or stacked by category.
+    #[allow(clippy::cast_precision_loss)]
+    fn bars(&mut self, config: &ChartConfig, plot: [f32; 4]) {
+        let horizontal = config.chart_type == ChartType::BarHorizontal;
+        let stacks =
+            (config.chart_type == ChartType::StackedBar).then(|| stack_series(&config.series));
+        let Some(categories) = config.series.iter().map(|s| s.points.len()).max() else {
+            return;
+        };
+        if categories == 0 {
+            return;
+        }
+        let range = match &stacks {
+            Some(stacks) => Range::of(stacks.iter().flatten().flat_map(|&(a, b)| [a, b])),
+            None => Range::of(config.series.iter().flat_map(|s| &s.points).map(|p| p.y)),
+        };
+        let (lo, hi) = if range.is_empty() {
+            (0.0, 0.0)
+        } else {
+            (range.lo, range.hi)
+        };
+        let values = Range::new(lo.min(0.0), if hi > 0.0 { hi * 1.1 } else { hi });
+        self.axes(&config.style, plot, values, horizontal);
+
+        let [x, y, width, height] = plot;
+        let extent = if horizontal { height } else { width };
+        let slot = extent / categories as f32;
+        let bar = match stacks {
+            Some(_) => slot * 0.8,
+            None => slot * 0.8 / config.series.len() as f32,
+        };
+        let base = values.fraction(0.0);
+        for (k, series) in config.series.iter().enumerate() {
+            let color = config.style.series_color(k, series.color);
+            for (i, point) in series.points.iter().enumerate() {
+                let (offset, (a, b)) = match &stacks {
+                    Some(stacks) => {
+                        let (from, to) = stacks[k][i];
+                        (0.0, (values.fraction(from), values.fraction(to)))
+                    }
+                    None => (k as f32 * bar, (base, values.fraction(point.y))),
+                };
+                let start = i as f32 * slot + slot * 0.1 + offset;
+                let (lo, hi) = (a.min(b), a.max(b));
+                let rect = if horizontal {
+                    [x + lo * width, y + start, (hi - lo) * width, bar]
+                } else {
+                    [x + start, y + height - hi * height, bar, (hi - lo) * height]
+                };
+                self.fill(rect_points(rect), color);
+            }
+        }
+        self.category_labels(config, plot, categories);
+        self.legend(config, plot);
+    }
+
+    /// Line, area, stacked area and scatter series over numeric x and y
+    /// axes.
+    fn plot(&mut self, config: &ChartConfig, plot: [f32; 4]) {
+        let stacks =
+            (config.chart_type == ChartType::StackedArea).then(|| stack_series(&config.series));
+        let points = || config.series.iter().flat_map(|s| &s.points);
+        let xs = Range::of(points().map(|p| p.x));
+        let ys = match &stacks {
+            Some(stacks) => Range::of(stacks.iter().flatten().flat_map(|&(a, b)| [a, b])),
+            None => Range::of(points().map(|p| p.y)),
+        };
+        if xs.is_empty() || ys.is_empty() {
+            return;
+        }
+        let scatter = config.chart_type == ChartType::Scatter;
+        let xs = xs.padded(if scatter { 0.1 } else { 0.05 });
+        let ys = if scatter {
+            ys.padded(0.1)
+        } else {
+            let ys = ys.including(0.0);
+            Range::new(ys.lo, ys.hi + (ys.hi - ys.lo) * 0.1)
+        };
+        self.axes(&config.style, plot, ys, false);
+
+        let [x, y, width, height] = plot;
+        let at = |px: f64, py: f64| {
+            [
+                x + xs.fraction(px) * width,
+                y + height - ys.fraction(py) * height,
+            ]
+        };
+        let baseline = y + height - ys.fraction(0.0) * height;
+        for (k, series) in config.series.iter().enumerate() {
+            let color = config.style.series_color(k, series.color);
+            if let Some(stacks) = &stacks {
+                let band: Vec<_> = series
+                    .points
+                    .iter()
+                    .zip(&stacks[k])
+                    .map(|(p, &(from, to))| (at(p.x, from), at(p.x, to)))
+                    .collect();
+                let fill = Color::rgba(color.r, color.g, color.b, STACKED_ALPHA);
+                for pair in band.windows(2) {
+                    let [(a0, a1), (b0, b1)] = [pair[0], pair[1]];
+                    self.fill(vec![a0, a1, b1, b0], fill);
+                }
+                self.stroke(
+                    band.iter().map(|&(_, top)| top).collect(),
+                    LINE_WIDTH,
+                    color,
+                );
+                continue;
+            }
+            let line: Vec<_> = series.points.iter().map(|p| at(p.x, p.y)).collect();
+            match config.chart_type {
+                ChartType::Scatter => {
+                    for center in line {
+                        self.fill(circle_points(center, POINT_RADIUS), color);
+                    }
+                }
+                chart_type => {
+                    if chart_type == ChartType::Area {
+                        let under = Color::rgba(color.r, color.g, color.b, AREA_ALPHA);
+                        for pair in line.windows(2) {
+                            let [a, b] = [pair[0], pair[1]];
+                            self.fill(vec![[a[0], baseline], a, b, [b[0], baseline]], under);
+                        }
+                    }
+                    self.stroke(line, LINE_WIDTH, color);
+                }
+            }
+        }
+        self.legend(config, plot);
+    }
+
+    /// A candle per point of the first series: a wick from low to high and
+    /// a body from open to close, colored by direction.
+    #[allow(clippy::cast_precision_loss)]
+    fn candlesticks(&mut self, config: &ChartConfig, plot: [f32; 4]) {
+        let Some(series) = config.series.first() else {
+            return;
+        };
+        let candles: Vec<_> = series
+            .points
+            .iter()
+            .map(|p| {
+                p.ohlc.unwrap_or(Ohlc {
+                    open: p.y,
+                    high: p.y,
+                    low: p.y,
+                    close: p.y,
+                })
+            })
+            .collect();
+        let prices = Range::of(candles.iter().flat_map(|c| [c.low, c.high]));
+        if prices.is_empty() {
+            return;
+        }
+        let prices = prices.padded(0.05);
+        self.axes(&config.style, plot, prices, false);
+
+        let [x, y, width, height] = plot;
+        let slot = width / candles.len() as f32;
+        let body = (slot * 0.6).max(1.0);
+        let at = |value: f64| y + height - prices.fraction(value) * height;
+        for (i, candle) in candles.iter().enumerate() {
+            let color = if candle.close >= candle.open {
+                RISING_COLOR
+            } else {
+                FALLING_COLOR
+            };
+            let center = x + (i as f32 + 0.5) * slot;
+            self.stroke(
+                vec![[center, at(candle.high)], [center, at(candle.low)]],
+                1.0,
+                color,
+            );
+            let top = at(candle.open.max(candle.close));
+            let bottom = at(candle.open.min(candle.close));
+            let rect = [center - body / 2.0, top, body, (bottom - top).max(1.0)];
+            self.fill(rect_points(rect), color);
+        }
+        self.category_labels(config, plot, candles.len());
+    }
+
+    /// A row of cells per series, shaded from the lowest value to the
+    /// highest, with a color scale on the right.
+    #[allow(clippy::cast_precision_loss)]
+    fn heatmap(&mut self, config: &ChartConfig, plot: [f32; 4]) {
+        let columns = config.series.iter().map(|s| s.points.len()).max();
+        let values = Range::of(config.series.iter().flat_map(|s| &s.points).map(|p| p.y));
+        let Some(columns) = columns.filter(|_| !values.is_empty()) else {
+            return;
+        };
+        let values = Range::new(values.lo, values.hi);
+        let style = &config.style;
+        let mut plot = plot;
+        if config.show_legend {
+            plot[2] = (plot[2] - SCALE_WIDTH * style.label_size / LABEL_SIZE).max(1.0);
+        }
+        let [x, y, width, height] = plot;
+        let (cell_width, cell_height) =
+            (width / columns as f32, height / config.series.len() as f32);
+        for (row, series) in config.series.iter().enumerate() {
+            let top = y + row as f32 * cell_height;
+            for (column, point) in series.points.iter().enumerate() {
+                let rect = [x + column as f32 * cell_width, top, cell_width, cell_height];
+                self.fill(rect_points(rect), heat(values.fraction(point.y)));
+            }
+            let at = [x - 4.0, top + cell_height / 2.0 - style.label_size / 2.0];
+            self.label(&series.name, at, style, Anchor::End);
+        }
+        self.category_labels(config, plot, columns);
+
+        if config.show_legend {
+            // Bands from the highest value at the top to the lowest
+            let left = x + width + 8.0;
+            let band = height / 8.0;
+            for i in 0..8_u8 {
+                let rect = [left, y + f32::from(i) * band, 12.0, band];
+                self.fill(rect_points(rect), heat(1.0 - f32::from(i) / 7.0));
+            }
+            let at = [left + 16.0, y];
+            self.label(
+                &style.value_axis.format(values.hi),
+                at,
+                style,
+                Anchor::Start,
+            );
+            let at = [left + 16.0, y + height - style.label_size * 1.3];
+            self.label(
+                &style.value_axis.format(values.lo),
+                at,
+                style,
+                Anchor::Start,
+            );
+        }
+    }
+
+    /// Adjacent bars counting the first series' values in each bin, with
+    /// the bin edges along the bottom.
+    #[allow(clippy::cast_precision_loss)]
+    fn histogram(&mut self, config: &ChartConfig, plot: [f32; 4]) {
+        let Some(series) = config.series.first() else {
+            return;
+        };
+        let values: Vec<_> = series.points.iter().map(|p| p.y).collect();
+        let bins = histogram(&values, config.bins);
+        let Some(most) = bins.iter().map(|b| b.count).max() else {
+            return;
+        };
+        let counts = Range::new(0.0, most as f64 * 1.1);
+        let style = &config.style;
+        self.axes(style, plot, counts, false);
+
+        let [x, y, width, height] = plot;
+        let bar = width / bins.len() as f32;
+        let color = style.series_color(0, series.color);
+        for (i, bin) in bins.iter().enumerate() {
+            let top = counts.fraction(bin.count as f64) * height;
+            let rect = [
+                x + i as f32 * bar + 0.5,
+                y + height - top,
+                (bar - 1.0).max(0.5),
+                top,
+            ];
+            self.fill(rect_points(rect), color);
+        }
+        let edges = bins.len() + 1;
+        for i in (0..edges).step_by(label_step(style, edges, width)) {
+            let edge = bins.get(i).map_or(bins[bins.len() - 1].end, |b| b.start);
+            let at = [x + i as f32 * bar, y + height + 4.0];
+            self.label(&style.x_axis.format(edge), at, style, Anchor::Center);
+        }
+    }
+
+    /// Pie or donut slices of the first series, with a legend column on the
+    /// side the style's legend position names.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn pie(&mut self, config: &ChartConfig, area: [f32; 4]) {
+        let Some(series) = config.series.first() else {
+            return;
+        };
+        let total: f64 = series.points.iter().map(|p| p.y.abs()).sum();
+        if total <= 0.0 {
+            return;
+        }
+        let [x, y, width, height] = area;
+        let style = &config.style;
+        let legend_width = if config.show_legend {
+            PIE_LEGEND_WIDTH * style.label_size / LABEL_SIZE
+        } else {
+            0.0
+        };
+        let pie_width = (width - legend_width).max(width / 2.0);
+        let pie_left = if style.legend.is_left() {
+            x + width - pie_width
+        } else {
+            x
+        };
+        let center = [pie_left + pie_width / 2.0, y + height / 2.0];
+        let radius = pie_width.min(height) / 2.0 * 0.9;
+        let inner = if config.chart_type == ChartType::Donut {
+            radius * 0.5
+        } else {
+            0.0
+        };
+
+        let mut start = -FRAC_PI_2;
+        for (i, point) in series.points.iter().enumerate() {
+            #[allow(clippy::cast_possible_truncation)] // Fractions of a turn
+            let sweep = (point.y.abs() / total) as f32 * TAU;
+            let color = style.series_color(i, None);
+            let steps = (sweep / ARC_STEP).ceil().max(1.0) as usize;
+            #[allow(clippy::cast_precision_loss)]
+            let step = sweep / steps as f32;
+            for s in 0..steps {
+                #[allow(clippy::cast_precision_loss)]
+                let a = start + s as f32 * step;
+                let b = a + step;
+                let outer = [polar(center, radius, a), polar(center, radius, b)];
+                let points = if inner > 0.0 {
+                    vec![
+                        polar(center, inner, a),
+                        outer[0],
+                        outer[1],
+                        polar(center, inner, b),
+                    ]
+                } else {
+                    vec![center, outer[0], outer[1]]
+                };
+                self.fill(points, color);
+            }
+            start += sweep;
+        }
+
+        if config.show_legend {
+            let left = if style.legend.is_left() {
+                x + 4.0
+            } else {
+                x + pie_width + 4.0
+            };
+            let entries = series.points.iter().enumerate().map(|(i, point)| {
+                let label = point
+                    .label
+                    .clone()
+                    .unwrap_or_else(|| format!("{:.0}", point.y));
+                (label, style.series_color(i, None))
+            });
+            self.legend_rows(style, [left, y, height], entries);
+        }
+    }
+}
+
+/// Which point of a label's box is placed.
+#[derive(Debug, Clone, Copy)]
+enum Anchor {
+    Start,
+    Center,
+    End,
+}
+
+/// A span of data values.
+#[derive(Debug, Clone, Copy)]
+struct Range {
+    lo: f64,
+    hi: f64,
+}
+
+impl Range {
+    const EMPTY: Self = Self {
+        lo: f64::INFINITY,
+        hi: f64::NEG_INFINITY,
+    };
+
+    /// `lo..hi`, widened to one unit when empty so values can be mapped.
+    fn new(lo: f64, hi: f64) -> Self {
+        if hi > lo {
+            Self { lo, hi }
+        } else {
+            Self {
+                lo: lo - 0.5,
+                hi: lo + 0.5,
+            }
+        }
+    }
+
+    /// The span of the finite `values`, empty if there are none.
+    fn of(values: impl IntoIterator<Item = f64>) -> Self {
+        values.into_iter().fold(Self::EMPTY, Self::including)
+    }
+
+    fn is_empty(self) -> bool {
+        self.lo > self.hi
+    }
+
+    fn including(self, value: f64) -> Self {
+        if value.is_finite() {
+            Self {
+                lo: self.lo.min(value),
+                hi: self.hi.max(value),
+            }
+        } else {
+            self
+        }
+    }
+
+    /// Grown by `fraction` of its span on each side.
+    fn padded(self, fraction: f64) -> Self {
+        let pad = (self.hi - self.lo) * fraction;
+        Self::new(self.lo - pad, self.hi + pad)
+    }
+
+    /// Where `value` falls, 0 at `lo` and 1 at `hi`.
+    #[allow(clippy::cast_possible_truncation)] // Screen positions fit in f32
+    fn fraction(self, value: f64) -> f32 {
+        ((value - self.lo) / (self.hi - self.lo)) as f32
+    }
+}
+
+/// The plot inside `area`, leaving room for axis labels and titles.
+fn plot_rect(config: &ChartConfig, area: [f32; 4]) -> [f32; 4] {
+    let [x, y, width, height] = area;
+    let scale = config.style.label_size / LABEL_SIZE;
+    // Horizontal bars put categories on the left and values below
+    let mut bottom = if config.chart_type == ChartType::BarHorizontal {
+        config.style.label_size + 8.0
+    } else {
+        CATEGORY_AXIS_SIZE * scale
+    };
+    let mut top = 0.0;
+    if axis_title(config.x_label.as_ref()).is_some() {
+        bottom += AXIS_TITLE_SIZE * scale;
+    }
+    if axis_title(config.y_label.as_ref()).is_some() {
+        top += AXIS_TITLE_SIZE * scale;
+    }
+    let value_axis = VALUE_AXIS_SIZE * scale;
+    [
+        x + value_axis,
+        y + top,
+        (width - value_axis).max(1.0),
+        (height - top - bottom).max(1.0),
+    ]
+}
+
+/// Distance between legend rows: [`LEGEND_ROW`] at the default label size.
+fn legend_row(style: &ChartStyle) -> f32 {
+    (style.label_size + LEGEND_ROW - LABEL_SIZE).max(LEGEND_ROW)
+}
+
+/// An axis title, if it has any text.
+fn axis_title(title: Option<&String>) -> Option<&str> {
+    title.map(String::as_str).filter(|t| !t.is_empty())
+}
+
+/// Every how many of `count` labels along `extent` pixels to draw.
+#[allow(
+    clippy::cast_precision_loss,
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss
+)]
+fn label_step(style: &ChartStyle, count: usize, extent: f32) -> usize {
+    let spacing = MIN_LABEL_SPACING * style.label_size / LABEL_SIZE;
+    let fit = (extent / spacing).floor().max(1.0) as usize;
+    count.div_ceil(fit).max(1)
+}
+
+/// Heatmap color `t` of the way from the lowest value to the highest.
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)] // Clamped to 0..=255
+pub(crate) fn heat(t: f32) -> Color {
+    let t = t.clamp(0.0, 1.0);
+    let mix = |a: u8, b: u8| (f32::from(a) + (f32::from(b) - f32::from(a)) * t).round() as u8;
+    Color::rgb(
+        mix(HEAT_LOW.r, HEAT_HIGH.r),
+        mix(HEAT_LOW.g, HEAT_HIGH.g),
+        mix(HEAT_LOW.b, HEAT_HIGH.b),
+    )
+}
+
+fn rect_points([x, y, width, height]: [f32; 4]) -> Vec<[f32; 2]> {
+    vec![
+        [x, y],
+        [x + width, y],
+        [x + width, y + height],
+        [x, y + height],
+    ]
+}
+
+fn circle_points(center: [f32; 2], radius: f32) -> Vec<[f32; 2]> {
+    (0..16_u8)
+        .map(|i| polar(center, radius, f32::from(i) * TAU / 16.0))
+        .collect()
+}
+
+fn polar(center: [f32; 2], radius: f32, angle: f32) -> [f32; 2] {
+    [
+        center[0] + radius * angle.cos(),
+        center[1] + radius * angle.sin(),
+    ]
+}
+
+/// Corners of a `width` thick segment from `a` to `b` with square caps, or
+/// `None` if the segment has no length.
+fn segment_quad(a: [f32; 2], b: [f32; 2], width: f32) -> Option<[[f32; 2]; 4]> {
+    let (dx, dy) = (b[0] - a[0], b[1] - a[1]);
+    let length = dx.hypot(dy);
+    if length <= f32::EPSILON {
+        return None;
+    }
+    let half = width / 2.0;
+    // Along the segment and across it, each half the width long
+    let (ux, uy) = (dx / length * half, dy / length * half);
+    let (nx, ny) = (-uy, ux);
+    Some([
+        [a[0] - ux + nx, a[1] - uy + ny],
+        [b[0] + ux + nx, b[1] + uy + ny],
+        [b[0] + ux - nx, b[1] + uy - ny],
+        [a[0] - ux - nx, a[1] - uy - ny],
+    ])
+}
+
+fn rgba(color: Color, opacity: f32) -> [f32; 4] {
+    let mut rgba = color.to_rgba_f32();
+    rgba[3] *= opacity;
+    rgba
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use canvas_core::Transform;
+
+    fn chart(chart_type: &str, data: serde_json::Value) -> Element {
+        Element::new(ElementKind::Chart {
+            chart_type: chart_type.to_string(),
+            data,
+            binding: None,
+        })
+        .with_transform(Transform {
+            x: 100.0,
+            y: 50.0,
+            width: 400.0,
+            height: 300.0,
+            ..Transform::default()
+        })
+    }
+
+    fn layout(element: &Element) -> RenderResult<ChartGeometry> {
+        ChartGeometry::from_element(element, &ChartStyle::default())
+    }
+
+    fn palette(index: usize) -> Color {
+        ChartStyle::default().series_color(index, None)
+    }
+
+    fn fills(geometry: &ChartGeometry, color: Color) -> Vec<&[[f32; 2]]> {
+        geometry
+            .paths
+            .iter()
+            .filter_map(|path| match path {
+                ChartPath::Fill { points, color: c } if *c == color => Some(&points[..]),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_bars_scale_with_values_inside_the_element() {
+        let geometry = layout(&chart(
+            "bar",
+            serde_json::json!({ "labels": ["a", "b"], "values": [10, 20], "title": "Sales" }),
+        ))
+        .expect("geometry");
+        let bars = fills(&geometry, palette(0));
+        assert_eq!(bars.len(), 2);
+        let height = |bar: &[[f32; 2]]| bar[2][1] - bar[0][1];
+        assert!((height(bars[1]) - 2.0 * height(bars[0])).abs() < 0.01);
+        for point in geometry.paths.iter().flat_map(|path| match path {
+            ChartPath::Fill { points, .. } | ChartPath::Stroke { points, .. } => points,
+        }) {
+            assert!((100.0..=500.0).contains(&point[0]) && (50.0..=350.0).contains(&point[1]));
+        }
+        let labels: Vec<_> = geometry.labels.iter().map(|l| l.text.as_str()).collect();
+        for expected in ["Sales", "a", "b", "0"] {
+            assert!(labels.contains(&expected), "{expected} missing");
+        }
+    }
+
+    #[test]
+    fn test_tessellation_covers_fills_and_strokes() {
+        let geometry = ChartGeometry {
+            paths: vec![
+                ChartPath::Fill {
+                    points: rect_points([0.0, 0.0, 10.0, 10.0]),
+                    color: Color::BLACK,
+                },
+                ChartPath::Stroke {
+                    points: vec![[0.0, 0.0], [10.0, 0.0], [10.0, 0.0], [10.0, 10.0]],
+                    width: 2.0,
+                    color: Color::WHITE,
+                },
+            ],
+            ..ChartGeometry::default()
+        };
+        let vertices = geometry.tessellate(0.5);
+        // Two triangles for the square, two per non-empty segment
+        assert_eq!(vertices.len(), 6 + 12);
+        assert!(vertices
+            .iter()
+            .all(|v| (v.color[3] - 0.5).abs() < f32::EPSILON));
+        // Square caps reach half the width past the ends
+        let xs = vertices[6..].iter().map(|v| v.position[0]);
+        assert!((xs.fold(f32::MIN, f32::max) - 11.0).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn test_pie_wedges_are_convex_and_cover_the_turn() {
+        for (chart_type, corners) in [("pie", 3), ("donut", 4)] {
+            let geometry = layout(&chart(
+                chart_type,
+                serde_json::json!({ "labels": ["a", "b", "c"], "values": [1, 1, 2] }),
+            ))
+            .expect("geometry");
+            let wedges: Vec<_> = (0..3)
+                .flat_map(|i| fills(&geometry, palette(i)))
+                .filter(|points| points.len() == corners)
+                .collect();
+            // A whole turn in steps of at most ARC_STEP
+            assert!(wedges.len() >= 64);
+            assert_eq!(geometry.labels.len(), 3);
+        }
+
+        let line = layout(&chart(
+            "area",
+            serde_json::json!({ "series": [{ "name": "s", "points": [
+                { "x": 0, "y": 1 }, { "x": 1, "y": 3 }, { "x": 2, "y": 2 }
+            ] }] }),
+        ))
+        .expect("geometry");
+        let color = palette(0);
+        let under = Color::rgba(color.r, color.g, color.b, AREA_ALPHA);
+        assert_eq!(fills(&line, under).len(), 2);
+        assert!(line.paths.iter().any(
+            |path| matches!(path, ChartPath::Stroke { points, color: c, .. } if points.len() == 3 && *c == color)
+        ));
+    }
+
+    #[test]
+    fn test_stacked_bars_sit_on_the_series_below() {
+        let geometry = layout(&chart(
+            "stacked_bar",
+            serde_json::json!({
+                "series": [
+                    { "name": "a", "points": [{ "x": 0, "y": 1 }, { "x": 1, "y": 2 }] },
+                    { "name": "b", "points": [{ "x": 0, "y": 3 }, { "x": 1, "y": 1 }] }
+                ],
+                "x_label": "Quarter",
+                "y_label": "Revenue"
+            }),
+        ))
+        .expect("geometry");
+        let lower = fills(&geometry, palette(0));
+        let upper = fills(&geometry, palette(1));
+        // Two bars each, then the legend swatches
+        for (lower, upper) in lower.iter().zip(&upper).take(2) {
+            // Same column, and the upper bar's bottom is the lower bar's top
+            assert!((lower[0][0] - upper[0][0]).abs() < 0.01);
+            assert!((upper[2][1] - lower[0][1]).abs() < 0.01);
+        }
+        let labels: Vec<_> = geometry.labels.iter().map(|l| l.text.as_str()).collect();
+        for expected in ["Quarter", "Revenue", "a", "b"] {
+            assert!(labels.contains(&expected), "{expected} missing");
+        }
+    }
+
+    #[test]
+    fn test_candles_heatmaps_and_histograms() {
+        let candles = layout(&chart(
+            "candlestick",
+            serde_json::json!({ "series": [{ "points": [
+                { "open": 10, "high": 12, "low": 8, "close": 11 },
+                { "open": 11, "high": 11, "low": 7, "close": 8 }
+            ] }] }),
+        ))
+        .expect("geometry");
+        assert_eq!(fills(&candles, RISING_COLOR).len(), 1);
+        assert_eq!(fills(&candles, FALLING_COLOR).len(), 1);
+        let wicks = candles.paths.iter().filter(
+            |path| matches!(path, ChartPath::Stroke { color, .. } if *color == RISING_COLOR || *color == FALLING_COLOR),
+        );
+        assert_eq!(wicks.count(), 2);
+
+        let heatmap = layout(&chart(
+            "heatmap",
+            serde_json::json!({ "values": [[0, 5], [10, 5]], "y_labels": ["Mon", "Tue"] }),
+        ))
+        .expect("geometry");
+        // The lowest and highest cells, then the top and bottom of the scale
+        assert_eq!(fills(&heatmap, HEAT_LOW).len(), 2);
+        assert_eq!(fills(&heatmap, HEAT_HIGH).len(), 2);
+        let labels: Vec<_> = heatmap.labels.iter().map(|l| l.text.as_str()).collect();
+        for expected in ["Mon", "Tue", "0", "10"] {
+            assert!(labels.contains(&expected), "{expected} missing");
+        }
+
+        let histogram = layout(&chart(
+            "histogram",
+            serde_json::json!({ "values": [1, 2, 2, 3, 3, 3, 4], "bins": 3 }),
+        ))
+        .expect("geometry");
+        let bars = fills(&histogram, palette(0));
+        assert_eq!(bars.len(), 3);
+        let heights: Vec<_> = bars.iter().map(|bar| bar[2][1] - bar[0][1]).collect();
+        // Counts of 1, 2 and 4 over [1, 2), [2, 3) and [3, 4]
+        assert!((heights[2] - 4.0 * heights[0]).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_3d_charts_record_their_scene() {
+        let geometry = layout(&chart(
+            "scatter3d",
+            serde_json::json!({ "series": [
+                { "name": "a", "points": [{ "x": 0, "y": 0, "z": 0 }, { "x": 1, "y": 1, "z": 1 }] },
+                { "name": "b", "points": [{ "x": 1, "y": 0, "z": 1 }] }
+            ], "title": "Cloud" }),
+        ))
+        .expect("geometry");
+        let scene = geometry.scene.as_ref().expect("3D scene");
+        // Background first, then the projection, then the legend swatches
+        assert_eq!(scene.paths.start, 1);
+        assert_eq!(geometry.paths.len(), scene.paths.end + 2);
+        let [x, y, width, height] = scene.rect;
+        assert!(x > 100.0 && y > 50.0 && x + width < 500.0 && y + height < 350.0);
+        for path in &geometry.paths[scene.paths.clone()] {
+            let ChartPath::Fill { points, .. } = path else {
+                panic!("projected plots are fills");
+            };
+            assert_eq!(points.len(), 3);
+        }
+        assert!(layout(&chart("bar", serde_json::json!({ "values": [1] })))
+            .expect("geometry")
+            .scene
+            .is_none());
+    }
+
+    #[test]
+    fn test_non_charts_and_unknown_types_are_errors() {
+        assert!(layout(&chart("radar", serde_json::json!({}))).is_err());
+        let text = Element::new(ElementKind::Text {
+            content: "hi".to_string(),
+            font_size: 16.0,
+            color: Color::BLACK.into(),
+        });
+        assert!(layout(&text).is_err());
+        // No data still draws the background
+        let empty = layout(&chart("line", serde_json::json!({}))).expect("geometry");
+        assert_eq!(empty.paths.len(), 1);
+    }
+
+    #[test]
+    fn test_styles_set_colors_gridlines_formats_and_legend_corner() {
+        let data = |style: serde_json::Value| {
+            chart(
+                "line",
+                serde_json::json!({
+                    "series": [
+                        { "name": "a", "points": [{ "x": 0, "y": 0.2 }, { "x": 1, "y": 0.4 }] },
+                        { "name": "b", "points": [{ "x": 0, "y": 0.1 }, { "x": 1, "y": 0.3 }] }
+                    ],
+                    "style": style
+                }),
+            )
+        };
+        let plain = layout(&data(serde_json::json!({}))).expect("geometry");
+        let styled = layout(&data(serde_json::json!({
+            "dark": true,
+            "grid": false,
+            "palette": ["#ff0000"],
+            "value_axis": { "format": "percent" },
+            "legend": "bottom_left"
+        })))
+        .expect("geometry");
+        let dark = ChartStyle::dark();
+
+        assert!(matches!(
+            &styled.paths[0],
+            ChartPath::Fill { color, .. } if *color == dark.background
+        ));
+        let strokes = |geometry: &ChartGeometry, color: Color| {
+            geometry
+                .paths
+                .iter()
+                .filter(|path| matches!(path, ChartPath::Stroke { color: c, .. } if *c == color))
+                .count()
+        };
+        assert_eq!(strokes(&plain, ChartStyle::default().grid_color), 4);
+        assert_eq!(strokes(&styled, dark.grid_color), 0);
+        // One palette color for both series
+        assert_eq!(strokes(&styled, Color::from_rgb(0xff_0000)), 2);
+        assert!(styled.labels.iter().any(|l| l.text.ends_with('%')));
+        assert!(styled.labels.iter().all(|l| l.color == dark.text_color));
+
+        // The legend moves from the top right to the bottom left
+        let swatch = |geometry: &ChartGeometry| {
+            let label = geometry
+                .labels
+                .iter()
+                .find(|l| l.text == "a")
+                .expect("legend");
+            [label.rect[0], label.rect[1]]
+        };
+        let (top_right, bottom_left) = (swatch(&plain), swatch(&styled));
+        assert!(bottom_left[0] < top_right[0] && bottom_left[1] > top_right[1]);
+    }
+}
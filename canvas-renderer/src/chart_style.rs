@@ -0,0 +1,336 @@
+//! # Chart Styles
+//!
+//! How charts look, apart from their data: series palette, font sizes,
+//! colors, gridlines, tick label formats, legend placement and dark mode.
+//!
+//! A chart's `data.style` object is laid over the renderer's default style
+//! ([`RendererConfig::chart_style`](crate::RendererConfig::chart_style)),
+//! so a host application sets its look once and agents only name what
+//! differs:
+//!
+//! ```json
+//! "style": {
+//!   "dark": true,
+//!   "palette": ["#7e57c2", "#26a69a"],
+//!   "label_size": 12,
+//!   "grid": false,
+//!   "value_axis": { "format": "compact", "prefix": "$" },
+//!   "legend": "bottom_left"
+//! }
+//! ```
+//!
+//! `dark` swaps the background, text, axis and grid colors for the dark or
+//! light set before the other fields apply. Fields that cannot be read are
+//! ignored, leaving the default's value.
+
+use canvas_core::Color;
+use serde::{Deserialize, Serialize};
+
+/// Series colors of charts whose style names none.
+pub const DEFAULT_PALETTE: &[Color] = &[
+    Color::rgb(54, 162, 235),  // Blue
+    Color::rgb(255, 99, 132),  // Red
+    Color::rgb(75, 192, 192),  // Teal
+    Color::rgb(255, 205, 86),  // Yellow
+    Color::rgb(153, 102, 255), // Purple
+    Color::rgb(255, 159, 64),  // Orange
+    Color::rgb(201, 203, 207), // Gray
+    Color::rgb(100, 181, 246), // Light Blue
+];
+
+/// Smallest and largest font sizes a style may set.
+pub const MIN_FONT_SIZE: f32 = 6.0;
+/// See [`MIN_FONT_SIZE`].
+pub const MAX_FONT_SIZE: f32 = 48.0;
+
+/// Most value-axis ticks a style may ask for.
+pub const MAX_TICKS: usize = 20;
+
+/// How a chart looks.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChartStyle {
+    /// Dark background with light text.
+    pub dark: bool,
+    /// Series (and pie slice) colors, repeating; the default palette if
+    /// empty.
+    pub palette: Vec<Color>,
+    /// Chart background, unless the chart data sets `background`.
+    pub background: Color,
+    /// Font size of the chart title.
+    pub title_size: f32,
+    /// Font size of tick, category, legend and axis title labels.
+    pub label_size: f32,
+    /// Color of the chart title.
+    pub title_color: Color,
+    /// Color of every other label.
+    pub text_color: Color,
+    /// Color of the axis lines.
+    pub axis_color: Color,
+    /// Draw gridlines at the value-axis ticks.
+    pub grid: bool,
+    /// Color of the gridlines.
+    pub grid_color: Color,
+    /// Value-axis ticks, including the axis itself.
+    pub ticks: usize,
+    /// Labels of value-axis ticks and the heatmap scale.
+    pub value_axis: AxisFormat,
+    /// Labels of numeric x values, such as histogram bin edges.
+    pub x_axis: AxisFormat,
+    /// Corner of the plot the series legend sits in.
+    pub legend: LegendPosition,
+}
+
+impl Default for ChartStyle {
+    fn default() -> Self {
+        Self {
+            dark: false,
+            palette: Vec::new(),
+            background: Color::WHITE,
+            title_size: 16.0,
+            label_size: 11.0,
+            title_color: Color::BLACK,
+            text_color: Color::from_rgb(0x33_3333),
+            axis_color: Color::from_rgb(0x66_6666),
+            grid: true,
+            grid_color: Color::from_rgb(0xe0_e0e0),
+            ticks: 5,
+            value_axis: AxisFormat::default(),
+            x_axis: AxisFormat::default(),
+            legend: LegendPosition::default(),
+        }
+    }
+}
+
+impl ChartStyle {
+    /// The default style in dark mode.
+    #[must_use]
+    pub fn dark() -> Self {
+        Self::default().with_dark(true)
+    }
+
+    /// This style with the dark or light background, text, axis and grid
+    /// colors.
+    #[must_use]
+    pub fn with_dark(mut self, dark: bool) -> Self {
+        let light = Self::default();
+        self.dark = dark;
+        if dark {
+            self.background = Color::from_rgb(0x1e_1e1e);
+            self.title_color = Color::from_rgb(0xf5_f5f5);
+            self.text_color = Color::from_rgb(0xbd_bdbd);
+            self.axis_color = Color::from_rgb(0x9e_9e9e);
+            self.grid_color = Color::from_rgb(0x3a_3a3a);
+        } else {
+            self.background = light.background;
+            self.title_color = light.title_color;
+            self.text_color = light.text_color;
+            self.axis_color = light.axis_color;
+            self.grid_color = light.grid_color;
+        }
+        self
+    }
+
+    /// This style with the fields of a chart's `style` JSON object laid
+    /// over it. Objects (the axis formats) are merged field by field.
+    #[must_use]
+    pub fn merged(&self, overrides: &serde_json::Value) -> Self {
+        let Some(overrides) = overrides.as_object() else {
+            return self.clone();
+        };
+        let mut style = match overrides.get("dark").and_then(serde_json::Value::as_bool) {
+            Some(dark) => self.clone().with_dark(dark),
+            None => self.clone(),
+        };
+        for (key, value) in overrides {
+            let Ok(serde_json::Value::Object(mut fields)) = serde_json::to_value(&style) else {
+                break;
+            };
+            let Some(field) = fields.get_mut(key) else {
+                continue;
+            };
+            match (field, value) {
+                (serde_json::Value::Object(field), serde_json::Value::Object(value)) => {
+                    field.extend(value.clone());
+                }
+                (field, value) => *field = value.clone(),
+            }
+            if let Ok(next) = serde_json::from_value(serde_json::Value::Object(fields)) {
+                style = next;
+            }
+        }
+        style.clamped()
+    }
+
+    /// Font sizes and tick count brought within their limits.
+    fn clamped(mut self) -> Self {
+        let font = |size: f32| {
+            if size.is_finite() {
+                size.clamp(MIN_FONT_SIZE, MAX_FONT_SIZE)
+            } else {
+                MIN_FONT_SIZE
+            }
+        };
+        self.title_size = font(self.title_size);
+        self.label_size = font(self.label_size);
+        self.ticks = self.ticks.clamp(2, MAX_TICKS);
+        self
+    }
+
+    /// Color of the series (or pie slice) at `index`: its own color if it
+    /// has one, else the palette's.
+    #[must_use]
+    pub fn series_color(&self, index: usize, custom: Option<Color>) -> Color {
+        let palette = if self.palette.is_empty() {
+            DEFAULT_PALETTE
+        } else {
+            &self.palette
+        };
+        custom.unwrap_or(palette[index % palette.len()])
+    }
+}
+
+/// Where the series legend sits in the plot.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LegendPosition {
+    /// Top right corner.
+    #[default]
+    TopRight,
+    /// Top left corner.
+    TopLeft,
+    /// Bottom right corner.
+    BottomRight,
+    /// Bottom left corner.
+    BottomLeft,
+}
+
+impl LegendPosition {
+    /// Whether the legend is on the left side.
+    #[must_use]
+    pub const fn is_left(self) -> bool {
+        matches!(self, Self::TopLeft | Self::BottomLeft)
+    }
+
+    /// Whether the legend is at the bottom.
+    #[must_use]
+    pub const fn is_bottom(self) -> bool {
+        matches!(self, Self::BottomRight | Self::BottomLeft)
+    }
+}
+
+/// How numbers are written along an axis.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AxisFormat {
+    /// Notation of the number.
+    pub format: NumberFormat,
+    /// Digits after the decimal point; up to two, without trailing zeros,
+    /// if unset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub decimals: Option<u8>,
+    /// Text before the number, e.g. `"$"`.
+    pub prefix: String,
+    /// Text after the number, e.g. `" ms"`.
+    pub suffix: String,
+}
+
+/// Notation of axis numbers.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NumberFormat {
+    /// `1250.5`
+    #[default]
+    Plain,
+    /// Fractions as percentages: `0.25` is `25%`.
+    Percent,
+    /// Thousands, millions and billions: `1500` is `1.5k`.
+    Compact,
+}
+
+/// Most decimal places an axis format writes.
+const MAX_DECIMALS: u8 = 6;
+
+impl AxisFormat {
+    /// Write `value` in this format.
+    #[must_use]
+    pub fn format(&self, value: f64) -> String {
+        let (scaled, unit) = match self.format {
+            NumberFormat::Plain => (value, ""),
+            NumberFormat::Percent => (value * 100.0, "%"),
+            NumberFormat::Compact => [(1e9, "B"), (1e6, "M"), (1e3, "k")]
+                .into_iter()
+                .find(|(size, _)| value.abs() >= *size)
+                .map_or((value, ""), |(size, unit)| (value / size, unit)),
+        };
+        let text = if let Some(decimals) = self.decimals {
+            let decimals = usize::from(decimals.min(MAX_DECIMALS));
+            format!("{scaled:.decimals$}")
+        } else {
+            let places = if unit.is_empty() || unit == "%" { 2 } else { 1 };
+            let text = format!("{scaled:.places$}");
+            text.trim_end_matches('0').trim_end_matches('.').to_string()
+        };
+        // Rounding can leave a negative zero
+        let text = match text.strip_prefix('-') {
+            Some(digits) if digits.chars().all(|c| c == '0' || c == '.') => digits.to_string(),
+            _ => text,
+        };
+        match text.strip_prefix('-') {
+            Some(digits) => format!("-{}{digits}{unit}{}", self.prefix, self.suffix),
+            None => format!("{}{text}{unit}{}", self.prefix, self.suffix),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_axis_formats() {
+        let plain = AxisFormat::default();
+        assert_eq!(plain.format(12.5), "12.5");
+        assert_eq!(plain.format(-0.001), "0");
+        let dollars = AxisFormat {
+            format: NumberFormat::Compact,
+            prefix: "$".to_string(),
+            ..AxisFormat::default()
+        };
+        assert_eq!(dollars.format(1_500.0), "$1.5k");
+        assert_eq!(dollars.format(-2_000_000.0), "-$2M");
+        let percent = AxisFormat {
+            format: NumberFormat::Percent,
+            decimals: Some(1),
+            ..AxisFormat::default()
+        };
+        assert_eq!(percent.format(0.25), "25.0%");
+    }
+
+    #[test]
+    fn test_style_overrides_merge_over_the_default() {
+        let base = ChartStyle {
+            palette: vec![Color::BLACK],
+            ..ChartStyle::default()
+        };
+        let style = base.merged(&serde_json::json!({
+            "dark": true,
+            "label_size": 200,
+            "grid": "sometimes",
+            "value_axis": { "suffix": " ms" },
+            "legend": "bottom_left"
+        }));
+        // The renderer's palette survives; dark colors apply
+        assert_eq!(style.series_color(3, None), Color::BLACK);
+        assert_eq!(style.background, ChartStyle::dark().background);
+        assert!((style.label_size - MAX_FONT_SIZE).abs() < f32::EPSILON);
+        // Unreadable fields keep the default
+        assert!(style.grid);
+        assert_eq!(style.value_axis.format(3.0), "3 ms");
+        assert_eq!(style.legend, LegendPosition::BottomLeft);
+        assert_eq!(
+            ChartStyle::default().series_color(1, None),
+            DEFAULT_PALETTE[1]
+        );
+    }
+}
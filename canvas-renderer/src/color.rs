@@ -0,0 +1,176 @@
+//! # Color Management
+//!
+//! Scene colors, theme colors and images are sRGB, as on the web: `#808080`
+//! is a middle gray whichever backend draws it. The `Canvas2D`, `WebGL2` and
+//! SVG backends hand them to the browser or file as they are. The wgpu
+//! backend decodes them to linear light in its shaders, so blending, image
+//! filtering and post effects work on light rather than on encoded values,
+//! and the target encodes them again for the display:
+//!
+//! ```text
+//! scene (sRGB) ─▶ decode ─▶ draw and blend (linear) ─▶ encode ─▶ display
+//!                                                        │
+//!               ColorSpace::Srgb ──── 8-bit sRGB surface ┤
+//!       ColorSpace::ExtendedSrgb ── half-float surface ──┤ (scRGB)
+//!              ColorSpace::Hdr10 ─ 10-bit surface, PQ ───┘ (Rec. 2020)
+//! ```
+//!
+//! Gradients are mixed between their stops in sRGB before decoding, as CSS
+//! and SVG mix them, so they match across backends too.
+//!
+//! The extended and HDR10 spaces need a display and platform whose surface
+//! offers their formats; elsewhere the wgpu backend falls back to the next
+//! space down and reports it through
+//! [`RenderBackend::color_space`](crate::RenderBackend::color_space). Both
+//! keep sRGB white at 1.0, [`SDR_WHITE_NITS`] on an HDR10 display, so scene
+//! colors look the same as on an sRGB one; only post effects such as bloom
+//! reach past it. Offscreen renders, snapshots and exports are always 8-bit
+//! sRGB.
+
+use serde::{Deserialize, Serialize};
+
+/// Brightness of sRGB white on an HDR10 display, in nits: the reference
+/// white of ITU-R BT.2408.
+pub const SDR_WHITE_NITS: f32 = 203.0;
+
+/// Brightest level the PQ curve encodes, in nits.
+pub const PQ_MAX_NITS: f32 = 10_000.0;
+
+/// The color space the renderer presents frames in.
+///
+/// Only the wgpu backend acts on this; other backends always present sRGB.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ColorSpace {
+    /// 8-bit sRGB, what every display shows.
+    #[default]
+    Srgb,
+    /// Linear sRGB primaries on a half-float surface (scRGB), with values
+    /// past 1.0 for highlights on extended-range displays.
+    ExtendedSrgb,
+    /// HDR10: Rec. 2020 primaries and the PQ curve on a 10-bit surface.
+    Hdr10,
+}
+
+impl ColorSpace {
+    /// Short name used in stats and logs.
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Srgb => "srgb",
+            Self::ExtendedSrgb => "extended_srgb",
+            Self::Hdr10 => "hdr10",
+        }
+    }
+
+    /// Whether frames can be brighter than sRGB white.
+    #[must_use]
+    pub const fn is_hdr(self) -> bool {
+        !matches!(self, Self::Srgb)
+    }
+
+    /// The space to try when a display lacks this one, if any.
+    #[must_use]
+    pub const fn fallback(self) -> Option<Self> {
+        match self {
+            Self::Hdr10 => Some(Self::ExtendedSrgb),
+            Self::ExtendedSrgb => Some(Self::Srgb),
+            Self::Srgb => None,
+        }
+    }
+}
+
+/// A space by name, e.g. for a command-line flag: `srgb`, `extended_srgb`
+/// (or `extended`, `scrgb`) or `hdr10` (or `hdr`).
+impl std::str::FromStr for ColorSpace {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().replace('-', "_").as_str() {
+            "srgb" | "sdr" => Ok(Self::Srgb),
+            "extended_srgb" | "extended" | "scrgb" => Ok(Self::ExtendedSrgb),
+            "hdr10" | "hdr" => Ok(Self::Hdr10),
+            _ => Err(format!("Unknown color space: {s}")),
+        }
+    }
+}
+
+/// Decode an sRGB channel (0.0 to 1.0) to linear light; the shaders do the
+/// same.
+#[must_use]
+pub fn srgb_to_linear(channel: f32) -> f32 {
+    if channel <= 0.040_45 {
+        channel / 12.92
+    } else {
+        ((channel + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Encode a linear channel (0.0 to 1.0) as sRGB.
+#[must_use]
+pub fn linear_to_srgb(channel: f32) -> f32 {
+    if channel <= 0.003_130_8 {
+        channel * 12.92
+    } else {
+        1.055 * channel.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// Decode an sRGB color with straight alpha; alpha stays as it is.
+#[must_use]
+pub fn srgba_to_linear([r, g, b, a]: [f32; 4]) -> [f32; 4] {
+    [srgb_to_linear(r), srgb_to_linear(g), srgb_to_linear(b), a]
+}
+
+/// Encode a brightness in nits with the PQ curve (SMPTE ST 2084), as the
+/// HDR10 output pass does.
+#[must_use]
+pub fn pq_encode(nits: f32) -> f32 {
+    const M1: f32 = 0.159_301_76;
+    const M2: f32 = 78.843_75;
+    const C1: f32 = 0.835_937_5;
+    const C2: f32 = 18.851_563;
+    const C3: f32 = 18.6875;
+    let y = (nits / PQ_MAX_NITS).clamp(0.0, 1.0).powf(M1);
+    ((C1 + C2 * y) / (1.0 + C3 * y)).powf(M2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_srgb_round_trips_through_linear() {
+        for step in 0..=20u8 {
+            let channel = f32::from(step) / 20.0;
+            assert!((linear_to_srgb(srgb_to_linear(channel)) - channel).abs() < 1e-5);
+        }
+        // Middle gray is about a fifth of white's light
+        assert!((srgb_to_linear(0.5) - 0.214).abs() < 1e-3);
+        assert!((srgb_to_linear(1.0) - 1.0).abs() < f32::EPSILON);
+        let [r, _, _, a] = srgba_to_linear([0.5, 0.0, 0.0, 0.5]);
+        assert!(r < 0.25 && (a - 0.5).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn test_pq_encodes_reference_levels() {
+        assert!(pq_encode(0.0) < 1e-6);
+        assert!((pq_encode(PQ_MAX_NITS) - 1.0).abs() < 1e-5);
+        // BT.2408: reference white sits at 58% of the signal
+        assert!((pq_encode(SDR_WHITE_NITS) - 0.58).abs() < 0.01);
+        assert!((pq_encode(100.0) - 0.508).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_color_spaces_parse_and_fall_back() {
+        assert_eq!("hdr10".parse(), Ok(ColorSpace::Hdr10));
+        assert_eq!("Extended-sRGB".parse(), Ok(ColorSpace::ExtendedSrgb));
+        assert_eq!("srgb".parse(), Ok(ColorSpace::Srgb));
+        assert!("p3".parse::<ColorSpace>().is_err());
+        assert_eq!(ColorSpace::Hdr10.fallback(), Some(ColorSpace::ExtendedSrgb));
+        assert_eq!(ColorSpace::Srgb.fallback(), None);
+        assert!(!ColorSpace::Srgb.is_hdr());
+        let parsed: ColorSpace = serde_json::from_str("\"extended_srgb\"").expect("json");
+        assert_eq!(parsed.name(), "extended_srgb");
+    }
+}
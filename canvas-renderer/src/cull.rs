@@ -0,0 +1,166 @@
+//! # Viewport Culling
+//!
+//! Finds the elements a frame can show before any backend work starts, so
+//! large scrollable scenes cost what is on screen rather than what is on
+//! the canvas.
+//!
+//! [`Renderer::render`](crate::Renderer::render) runs the pass when
+//! [`RendererConfig::culling`](crate::RendererConfig::culling) is on and
+//! hands the result to the backend with
+//! [`RenderBackend::set_visible_elements`](crate::RenderBackend::set_visible_elements).
+//! The scene's spatial index answers which elements overlap the viewport at
+//! the current zoom and pan:
+//!
+//! ```text
+//! ┌────────── canvas ──────────┐
+//! │  ▢        ┌─ viewport ─┐   │   ▢  culled: never cloned, textured
+//! │      ┌────┼──┐     ▣   │   │         or measured
+//! │      │ ▣  │▣ │ group   │   │   ▣  visible, and the containers
+//! │      └────┼──┘         │   │      holding them
+//! │  ▢        └────────────┘ ▢ │
+//! └────────────────────────────┘
+//! ```
+//!
+//! Containers of visible elements are kept even when they are offscreen,
+//! since backends draw children through them (clips, group opacity).
+//! Screen-anchored elements are always visible.
+
+use std::collections::HashSet;
+
+use canvas_core::{Element, ElementId, Scene};
+
+/// Elements of a scene that the next frame can show, in draw order.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct VisibleSet {
+    /// Visible elements and their containers, by z-index.
+    ids: Vec<ElementId>,
+    /// Elements in the scene when the set was built.
+    total: usize,
+}
+
+impl VisibleSet {
+    /// Shown elements overlapping the viewport of `scene`, found through
+    /// its spatial index, plus their ancestors.
+    #[must_use]
+    pub fn from_scene(scene: &Scene) -> Self {
+        let mut seen = HashSet::new();
+        let mut elements = Vec::new();
+        for element in scene.visible_elements() {
+            let mut current = Some(element);
+            // Walk up until an ancestor is already in; its own chain is too
+            while let Some(element) = current {
+                if !seen.insert(element.id) {
+                    break;
+                }
+                elements.push(element);
+                current = element.parent.and_then(|id| scene.get_element(id));
+            }
+        }
+        elements.sort_by_key(|e| e.transform.z_index);
+        Self {
+            ids: elements.iter().map(|e| e.id).collect(),
+            total: scene.element_count(),
+        }
+    }
+
+    /// The visible elements of `scene`, by z-index. Elements removed since
+    /// the set was built are skipped.
+    pub fn elements<'a>(&'a self, scene: &'a Scene) -> impl Iterator<Item = &'a Element> + 'a {
+        self.ids.iter().filter_map(|&id| scene.get_element(id))
+    }
+
+    /// Whether `id` is in the set.
+    #[must_use]
+    pub fn contains(&self, id: ElementId) -> bool {
+        self.ids.contains(&id)
+    }
+
+    /// Number of elements in the set.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    /// Whether nothing is visible.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+
+    /// Elements in the scene, visible or not.
+    #[must_use]
+    pub fn total(&self) -> usize {
+        self.total
+    }
+
+    /// Elements left out: offscreen or hidden.
+    #[must_use]
+    pub fn culled(&self) -> usize {
+        self.total.saturating_sub(self.ids.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use canvas_core::{ElementKind, Transform};
+
+    fn rect(x: f32, y: f32) -> Element {
+        Element::new(ElementKind::Group {
+            children: Vec::new(),
+            clip: false,
+            opacity: 1.0,
+        })
+        .with_transform(Transform {
+            x,
+            y,
+            width: 50.0,
+            height: 50.0,
+            ..Transform::default()
+        })
+    }
+
+    #[test]
+    fn test_offscreen_elements_are_culled() {
+        let mut scene = Scene::new(800.0, 600.0);
+        let near = scene.add_element(rect(10.0, 10.0));
+        let far = scene.add_element(rect(5000.0, 10.0));
+        let mut pinned = rect(700.0, 500.0);
+        pinned.screen_anchored = true;
+        let pinned = scene.add_element(pinned);
+        let mut hidden = rect(20.0, 20.0);
+        hidden.visible = false;
+        scene.add_element(hidden);
+
+        let visible = VisibleSet::from_scene(&scene);
+        assert!(visible.contains(near));
+        assert!(!visible.contains(far));
+        assert!(visible.contains(pinned));
+        assert_eq!(visible.total(), 4);
+        assert_eq!(visible.culled(), 2);
+
+        // Panning brings the far element into view; pinned ones stay put
+        scene.set_camera(1.0, -4900.0, 0.0);
+        let visible = VisibleSet::from_scene(&scene);
+        assert!(visible.contains(far));
+        assert!(visible.contains(pinned));
+        assert!(!visible.contains(near));
+    }
+
+    #[test]
+    fn test_containers_of_visible_elements_are_kept_in_z_order() {
+        let mut scene = Scene::new(800.0, 600.0);
+        let mut group = rect(-1000.0, -1000.0);
+        group.transform.z_index = 2;
+        let group = scene.add_element(group);
+        let mut child = rect(100.0, 100.0);
+        child.parent = Some(group);
+        child.transform.z_index = 1;
+        let child = scene.add_element(child);
+
+        let visible = VisibleSet::from_scene(&scene);
+        let ids: Vec<_> = visible.elements(&scene).map(|e| e.id).collect();
+        assert_eq!(ids, vec![child, group]);
+        assert_eq!(visible.culled(), 0);
+    }
+}
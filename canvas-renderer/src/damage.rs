@@ -0,0 +1,493 @@
+//! # Damage Tracking
+//!
+//! Works out which parts of the viewport changed since the last frame, so
+//! mostly-static scenes repaint a few small rectangles instead of the whole
+//! surface, or nothing at all.
+//!
+//! ```text
+//!  frame N                    frame N+1: B moved
+//!  ┌────────────────────┐     ┌────────────────────┐
+//!  │ [A]        [B]     │     │ [A]       ┏━━━━━━┓ │   damage = old B ∪ new B
+//!  │                    │  →  │           ┃  [B] ┃ │   A is left untouched
+//!  │      [C]           │     │      [C]  ┗━━━━━━┛ │
+//!  └────────────────────┘     └────────────────────┘
+//! ```
+//!
+//! A [`DamageTracker`] remembers the scene revision it last saw and where
+//! each element was drawn. Each frame it asks the scene what changed (see
+//! [`Scene::changes_since`]) and damages both the old and the new screen
+//! rectangle of every changed or removed element. Anything it cannot place
+//! precisely — a camera, viewport or theme change, exiting transitions, or
+//! a history gap — repaints in full, as does damage covering most of the
+//! viewport, where one full repaint is cheaper than many partial ones.
+//!
+//! Video and shader elements are damaged every frame, since their frames
+//! arrive outside the scene or change with time. State the scene does not see (images finishing a
+//! download, a host-side overlay) calls for
+//! [`invalidate`](DamageTracker::invalidate).
+
+use std::collections::{HashMap, HashSet};
+
+use canvas_core::{Element, ElementId, ElementKind, Scene, SceneBounds};
+
+/// Most separate regions handed to a backend before they are merged into
+/// their bounding box.
+pub const MAX_DAMAGE_REGIONS: usize = 16;
+
+/// Screen pixels added around each damaged element for antialiasing.
+const DAMAGE_MARGIN: f32 = 2.0;
+
+/// Share of the viewport above which a partial repaint is not worth it.
+const FULL_REPAINT_RATIO: f32 = 0.5;
+
+/// A rectangle of the viewport, in screen pixels.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DamageRect {
+    /// Left edge.
+    pub x: f32,
+    /// Top edge.
+    pub y: f32,
+    /// Width.
+    pub width: f32,
+    /// Height.
+    pub height: f32,
+}
+
+impl DamageRect {
+    /// A rectangle from its top-left corner and size.
+    #[must_use]
+    pub const fn new(x: f32, y: f32, width: f32, height: f32) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// Where `element` is drawn in `scene`'s viewport, including its stroke,
+    /// drop shadow and callout tail.
+    #[must_use]
+    pub fn of_element(scene: &Scene, element: &Element) -> Self {
+        let mut bounds = SceneBounds::from_transform(&element.transform);
+        if let Some(tail) = scene.callout_tail(element.id) {
+            for (x, y) in tail {
+                bounds = bounds.union(&SceneBounds {
+                    min_x: x,
+                    min_y: y,
+                    max_x: x,
+                    max_y: y,
+                });
+            }
+        }
+
+        let style = &element.style;
+        let stroke = style.stroke.as_ref().map_or(0.0, |s| s.width.max(0.0));
+        let pad = stroke / 2.0;
+        let (mut left, mut top, mut right, mut bottom) = (pad, pad, pad, pad);
+        if let Some(shadow) = &style.shadow {
+            let blur = shadow.blur.max(0.0);
+            left = left.max(blur - shadow.offset_x);
+            right = right.max(blur + shadow.offset_x);
+            top = top.max(blur - shadow.offset_y);
+            bottom = bottom.max(blur + shadow.offset_y);
+        }
+        let (min_x, min_y) = (bounds.min_x - left, bounds.min_y - top);
+        let (max_x, max_y) = (bounds.max_x + right, bounds.max_y + bottom);
+
+        let (zoom, pan_x, pan_y) = if element.screen_anchored {
+            (1.0, 0.0, 0.0)
+        } else {
+            (scene.zoom, scene.pan_x, scene.pan_y)
+        };
+        Self::new(
+            min_x * zoom + pan_x - DAMAGE_MARGIN,
+            min_y * zoom + pan_y - DAMAGE_MARGIN,
+            (max_x - min_x) * zoom + 2.0 * DAMAGE_MARGIN,
+            (max_y - min_y) * zoom + 2.0 * DAMAGE_MARGIN,
+        )
+    }
+
+    /// Right edge.
+    #[must_use]
+    pub fn right(&self) -> f32 {
+        self.x + self.width
+    }
+
+    /// Bottom edge.
+    #[must_use]
+    pub fn bottom(&self) -> f32 {
+        self.y + self.height
+    }
+
+    /// Area in square pixels.
+    #[must_use]
+    pub fn area(&self) -> f32 {
+        self.width * self.height
+    }
+
+    /// Check whether the rectangle covers no pixels.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.width <= 0.0 || self.height <= 0.0
+    }
+
+    /// Check whether `other` overlaps this rectangle (touching edges count).
+    #[must_use]
+    pub fn intersects(&self, other: &Self) -> bool {
+        self.x <= other.right()
+            && other.x <= self.right()
+            && self.y <= other.bottom()
+            && other.y <= self.bottom()
+    }
+
+    /// Smallest rectangle containing both.
+    #[must_use]
+    pub fn union(&self, other: &Self) -> Self {
+        let (x, y) = (self.x.min(other.x), self.y.min(other.y));
+        Self::new(
+            x,
+            y,
+            self.right().max(other.right()) - x,
+            self.bottom().max(other.bottom()) - y,
+        )
+    }
+
+    /// The part of this rectangle inside `other`, possibly empty.
+    #[must_use]
+    pub fn intersection(&self, other: &Self) -> Self {
+        let (x, y) = (self.x.max(other.x), self.y.max(other.y));
+        Self::new(
+            x,
+            y,
+            (self.right().min(other.right()) - x).max(0.0),
+            (self.bottom().min(other.bottom()) - y).max(0.0),
+        )
+    }
+
+    /// Grow outwards to whole pixels.
+    #[must_use]
+    pub fn round_out(&self) -> Self {
+        let (x, y) = (self.x.floor(), self.y.floor());
+        Self::new(x, y, self.right().ceil() - x, self.bottom().ceil() - y)
+    }
+}
+
+/// What a frame has to repaint.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum Damage {
+    /// Nothing changed; the previous frame is still correct.
+    None,
+    /// Repaint everything.
+    #[default]
+    Full,
+    /// Repaint only these disjoint, whole-pixel regions.
+    Regions(Vec<DamageRect>),
+}
+
+impl Damage {
+    /// Damage for `regions` in a `width` x `height` viewport: clipped to it,
+    /// merged where they overlap and widened to a full repaint when they
+    /// cover most of it.
+    #[must_use]
+    pub fn from_regions(regions: Vec<DamageRect>, width: f32, height: f32) -> Self {
+        let viewport = DamageRect::new(0.0, 0.0, width, height);
+        let mut merged: Vec<DamageRect> = Vec::new();
+        for rect in regions {
+            let mut rect = rect.intersection(&viewport).round_out();
+            if rect.is_empty() {
+                continue;
+            }
+            // Absorb everything the new rect touches, growing it as it goes
+            while let Some(i) = merged.iter().position(|other| other.intersects(&rect)) {
+                rect = rect.union(&merged.swap_remove(i));
+            }
+            merged.push(rect);
+        }
+
+        if merged.len() > MAX_DAMAGE_REGIONS {
+            merged = merged
+                .into_iter()
+                .reduce(|a, b| a.union(&b))
+                .into_iter()
+                .collect();
+        }
+        let area: f32 = merged.iter().map(DamageRect::area).sum();
+        if merged.is_empty() {
+            Self::None
+        } else if area > viewport.area() * FULL_REPAINT_RATIO {
+            Self::Full
+        } else {
+            Self::Regions(merged)
+        }
+    }
+
+    /// Check whether nothing needs repainting.
+    #[must_use]
+    pub fn is_none(&self) -> bool {
+        matches!(self, Self::None)
+    }
+}
+
+/// Tracks what a scene looked like when it was last drawn.
+#[derive(Debug, Clone, Default)]
+pub struct DamageTracker {
+    /// Scene revision at the last frame; `None` forces a full repaint.
+    revision: Option<u64>,
+    /// Where each shown element was last drawn.
+    drawn: HashMap<ElementId, DamageRect>,
+    /// Videos and shaders, whose frames change without a scene revision.
+    live: HashSet<ElementId>,
+    /// Callouts, whose tails follow their targets without a revision.
+    callouts: HashSet<ElementId>,
+    /// Elements in an entering transition.
+    animating: HashSet<ElementId>,
+}
+
+impl DamageTracker {
+    /// A tracker whose first frame repaints in full.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Repaint in full next frame, e.g. after a resize or a change the
+    /// scene does not know about.
+    pub fn invalidate(&mut self) {
+        self.revision = None;
+    }
+
+    /// Work out what changed in `scene` since the last call and remember
+    /// the scene as drawn.
+    pub fn track(&mut self, scene: &Scene) -> Damage {
+        let Some(revision) = self.revision else {
+            return self.reset(scene);
+        };
+        let changes = scene.changes_since(revision);
+        if changes.full || changes.settings || scene.exiting_elements().next().is_some() {
+            return self.reset(scene);
+        }
+
+        let mut regions: Vec<DamageRect> = changes
+            .removed
+            .iter()
+            .filter_map(|id| self.drawn.remove(id))
+            .collect();
+        for id in &changes.removed {
+            self.live.remove(id);
+            self.callouts.remove(id);
+            self.animating.remove(id);
+        }
+
+        // Changed containers take their descendants along, since hiding or
+        // clipping a group changes how its children draw
+        let mut dirty: HashSet<ElementId> = HashSet::new();
+        let mut pending: Vec<ElementId> = changes.changed;
+        pending.extend(self.live.iter().copied());
+        pending.extend(self.animating.iter().copied());
+        while let Some(id) = pending.pop() {
+            if dirty.insert(id) {
+                if let Some(element) = scene.get_element(id) {
+                    pending.extend(element.kind.children());
+                }
+            }
+        }
+
+        let moved_callouts: Vec<ElementId> = self
+            .callouts
+            .iter()
+            .filter(|id| !dirty.contains(id))
+            .filter(|&&id| {
+                scene
+                    .get_element(id)
+                    .map(|e| DamageRect::of_element(scene, e))
+                    != self.drawn.get(&id).copied()
+            })
+            .copied()
+            .collect();
+        for id in dirty.into_iter().chain(moved_callouts) {
+            let old = self.drawn.remove(&id);
+            let new = scene
+                .get_element(id)
+                .filter(|e| scene.is_shown(e))
+                .map(|e| self.place(scene, e));
+            // Elements redrawn in place only damage their own spot
+            regions.extend(old.filter(|old| Some(*old) != new));
+            regions.extend(new);
+        }
+        self.revision = Some(changes.revision);
+
+        Damage::from_regions(regions, scene.viewport_width, scene.viewport_height)
+    }
+
+    /// Remember every element as drawn and ask for a full repaint.
+    fn reset(&mut self, scene: &Scene) -> Damage {
+        self.drawn.clear();
+        self.live.clear();
+        self.callouts.clear();
+        self.animating.clear();
+        for element in scene.elements().filter(|e| scene.is_shown(e)) {
+            self.place(scene, element);
+        }
+        self.revision = Some(scene.revision());
+        Damage::Full
+    }
+
+    /// Record where `element` is drawn now, returning the rect.
+    fn place(&mut self, scene: &Scene, element: &Element) -> DamageRect {
+        let rect = DamageRect::of_element(scene, element);
+        self.drawn.insert(element.id, rect);
+        match element.kind {
+            ElementKind::Video { .. } | ElementKind::Shader { .. } => self.live.insert(element.id),
+            ElementKind::Callout { .. } => self.callouts.insert(element.id),
+            _ => false,
+        };
+        if scene.transition_frame(element.id).is_some() {
+            self.animating.insert(element.id);
+        } else {
+            self.animating.remove(&element.id);
+        }
+        rect
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use canvas_core::{Color, Transform};
+
+    fn text_at(x: f32, y: f32) -> Element {
+        Element::new(ElementKind::Text {
+            content: "hi".to_string(),
+            font_size: 16.0,
+            color: Color::BLACK.into(),
+        })
+        .with_transform(Transform {
+            x,
+            y,
+            width: 40.0,
+            height: 20.0,
+            ..Transform::default()
+        })
+    }
+
+    fn regions(damage: Damage) -> Vec<DamageRect> {
+        match damage {
+            Damage::Regions(regions) => regions,
+            other => panic!("expected regions, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_static_scene_needs_no_repaint() {
+        let mut scene = Scene::new(800.0, 600.0);
+        scene.add_element(text_at(10.0, 10.0));
+        let mut tracker = DamageTracker::new();
+        assert_eq!(tracker.track(&scene), Damage::Full);
+        assert_eq!(tracker.track(&scene), Damage::None);
+
+        tracker.invalidate();
+        assert_eq!(tracker.track(&scene), Damage::Full);
+    }
+
+    #[test]
+    fn test_moved_element_damages_old_and_new_spots() {
+        let mut scene = Scene::new(800.0, 600.0);
+        let id = scene.add_element(text_at(10.0, 10.0));
+        scene.add_element(text_at(600.0, 400.0));
+        let mut tracker = DamageTracker::new();
+        tracker.track(&scene);
+
+        scene.get_element_mut(id).unwrap().transform.x = 300.0;
+        let damage = regions(tracker.track(&scene));
+        assert_eq!(damage.len(), 2);
+        assert!(damage
+            .iter()
+            .any(|r| r.x <= 10.0 - DAMAGE_MARGIN && r.right() >= 50.0 + DAMAGE_MARGIN));
+        assert!(damage.iter().any(|r| r.x <= 300.0 && r.right() >= 340.0));
+        // The untouched element is not repainted
+        assert!(!damage.iter().any(|r| r.right() > 600.0));
+
+        scene.remove_element(&id).unwrap();
+        let damage = regions(tracker.track(&scene));
+        assert_eq!(damage.len(), 1);
+        assert!(damage[0].x <= 300.0 && damage[0].right() >= 340.0);
+    }
+
+    #[test]
+    fn test_camera_and_large_changes_repaint_in_full() {
+        let mut scene = Scene::new(800.0, 600.0);
+        let id = scene.add_element(text_at(10.0, 10.0));
+        let mut tracker = DamageTracker::new();
+        tracker.track(&scene);
+
+        scene.set_camera(2.0, 0.0, 0.0);
+        assert_eq!(tracker.track(&scene), Damage::Full);
+
+        scene.get_element_mut(id).unwrap().transform.width = 700.0;
+        scene.get_element_mut(id).unwrap().transform.height = 500.0;
+        assert_eq!(tracker.track(&scene), Damage::Full);
+    }
+
+    #[test]
+    fn test_regions_merge_and_clip() {
+        let damage = Damage::from_regions(
+            vec![
+                DamageRect::new(10.0, 10.0, 20.0, 20.0),
+                DamageRect::new(25.0, 25.0, 20.0, 20.0),
+                DamageRect::new(-50.0, 500.0, 60.5, 20.0),
+                DamageRect::new(900.0, 0.0, 10.0, 10.0),
+            ],
+            800.0,
+            600.0,
+        );
+        let damage = regions(damage);
+        assert_eq!(damage.len(), 2);
+        assert!(damage.contains(&DamageRect::new(10.0, 10.0, 35.0, 35.0)));
+        assert!(damage.contains(&DamageRect::new(0.0, 500.0, 11.0, 20.0)));
+
+        let many = (0u16..)
+            .take(MAX_DAMAGE_REGIONS + 1)
+            .map(|i| DamageRect::new(f32::from(i) * 10.0, 0.0, 5.0, 5.0))
+            .collect();
+        assert_eq!(
+            regions(Damage::from_regions(many, 800.0, 600.0)),
+            vec![DamageRect::new(0.0, 0.0, 165.0, 5.0)]
+        );
+        assert!(Damage::from_regions(Vec::new(), 800.0, 600.0).is_none());
+    }
+
+    #[test]
+    fn test_renderer_repaints_only_damage() {
+        use crate::backend::canvas2d::Canvas2DBackend;
+        use crate::{Renderer, RendererConfig};
+
+        let mut scene = Scene::new(800.0, 600.0);
+        let id = scene.add_element(text_at(10.0, 10.0));
+        scene.add_element(text_at(600.0, 400.0));
+        let mut renderer = Renderer::with_backend(
+            Box::new(Canvas2DBackend::new()),
+            RendererConfig {
+                damage_tracking: true,
+                ..RendererConfig::default()
+            },
+        );
+
+        renderer.render(&scene).unwrap();
+        assert_eq!(renderer.last_damage(), &Damage::Full);
+        renderer.render(&scene).unwrap();
+        assert!(renderer.last_damage().is_none());
+        assert_eq!(renderer.stats().frames_rendered, 1);
+        assert_eq!(renderer.stats().frames_skipped, 1);
+
+        scene.get_element_mut(id).unwrap().transform.y = 40.0;
+        renderer.render(&scene).unwrap();
+        assert!(matches!(renderer.last_damage(), Damage::Regions(_)));
+        assert_eq!(renderer.stats().last_frame.elements_drawn, 1);
+
+        renderer.resize(1024, 768).unwrap();
+        renderer.render(&scene).unwrap();
+        assert_eq!(renderer.last_damage(), &Damage::Full);
+        assert_eq!(renderer.stats().frames_rendered, 3);
+    }
+}
@@ -40,6 +40,14 @@ pub enum RenderError {
     #[error("Export failed: {0}")]
     Export(String),
 
+    /// Math formula could not be typeset.
+    #[error("Invalid math formula: {0}")]
+    Math(String),
+
+    /// Post-processing effect with unknown name or out-of-range settings.
+    #[error("Invalid post effect: {0}")]
+    PostEffect(String),
+
     /// I/O error during export.
     #[error("I/O error: {0}")]
     Io(#[from] std::io::Error),
@@ -2,14 +2,62 @@
 //!
 //! Renders a [`Scene`] to PNG, JPEG, SVG, PDF, or WebP using an SVG intermediate
 //! representation and the resvg/tiny-skia rasterization pipeline.
+//!
+//! A single element (and its children) can be exported on its own with
+//! [`SceneExporter::export_element`], cropped to its bounds, and a whole
+//! scene can be paginated onto paper with [`SceneExporter::export_print`].
 
 use std::fmt::Write;
 
-use canvas_core::element::ElementKind;
-use canvas_core::Scene;
+use canvas_core::agenda::{AgendaItem, AgendaLayout, AgendaStatus};
+use canvas_core::annotation::{caption_tab, MeasurementLayout, ANNOTATION_FONT_SIZE, REGION_TINT};
+use canvas_core::barcode::BarcodeLayout;
+use canvas_core::calendar::{events_on, CalendarCell, CalendarLayout, CalendarView, WEEKDAYS};
+use canvas_core::dashboard::{
+    gauge_fraction, threshold_color, Dial, GaugeLayout, GaugeShape, KpiDelta, KpiLayout,
+    GAUGE_TRACK_COLOR,
+};
+use canvas_core::element::{ElementKind, PlaybackState};
+use canvas_core::floor_plan::{
+    FloorMarker, FloorPlanLayout, MarkerShape, OccupancySummary, PlanPath,
+};
+use canvas_core::highlight::{
+    self, HighlightSpan, CODE_BACKGROUND, CODE_CHAR_WIDTH, CODE_FONT_SIZE, CODE_LINE_HEIGHT,
+    CODE_PADDING,
+};
+use canvas_core::ink;
+use canvas_core::log_view::{self, LOG_BACKGROUND, LOG_FOREGROUND};
+use canvas_core::poll::{PollLayout, PollResults};
+use canvas_core::randomizer::{RandomOutcome, RandomSource, RandomizerLayout, WHEEL_COLORS};
+use canvas_core::redact::{self, Redaction};
+use canvas_core::shape::{ShapeKind, ShapeOutline};
+use canvas_core::sparkline::{
+    sparkline_area, sparkline_bars, sparkline_points, SparklineKind, SPARKLINE_DOT_RADIUS,
+};
+use canvas_core::tree::{TreeDirection, TreeLayout, TreeNode, DEFAULT_NODE_COLOR};
+use canvas_core::{Color, Element, ElementId, Fill, Scene, SceneBounds, Theme};
 use image::ImageEncoder;
 
+use crate::chart::parse_chart_config;
+use crate::chart_mesh::{ChartGeometry, ChartPath};
 use crate::error::{RenderError, RenderResult};
+use crate::math::MathLayout;
+use crate::print::{PrintConfig, PrintLayout};
+use crate::snapshot::RgbaImage;
+
+/// Per-element override for SVG output: writes the element into the
+/// document and returns `true`, or returns `false` to use the default.
+pub(crate) type CustomElementSvg<'a> = dyn Fn(&mut String, &Element) -> bool + 'a;
+
+/// Margin kept around an exported element, in canvas units.
+const ELEMENT_PADDING: f32 = 8.0;
+
+/// Largest width or height of an element export, in pixels.
+pub const MAX_ELEMENT_EXPORT_SIZE: u32 = 8192;
+
+/// Canvas units per pixel of a blurred redaction, before the blur pass:
+/// coarse enough that no text or detail survives.
+const REDACTION_CELL: f32 = 6.0;
 
 /// Export output format.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -39,6 +87,9 @@ pub struct ExportConfig {
     pub jpeg_quality: u8,
     /// Scale factor (e.g. 2.0 for retina).
     pub scale: f32,
+    /// How to hide [`sensitive`](Element::sensitive) elements, or `None` to
+    /// draw them as they are.
+    pub redaction: Option<Redaction>,
 }
 
 impl Default for ExportConfig {
@@ -50,6 +101,7 @@ impl Default for ExportConfig {
             background: [255, 255, 255, 255],
             jpeg_quality: 85,
             scale: 1.0,
+            redaction: None,
         }
     }
 }
@@ -78,14 +130,168 @@ impl SceneExporter {
     ///
     /// Returns an error if the scene cannot be rendered or encoded.
     pub fn export(&self, scene: &Scene, format: ExportFormat) -> RenderResult<Vec<u8>> {
+        let svg = self.render_to_svg(scene)?;
+        self.encode(svg, self.output_dimensions(scene), format)
+    }
+
+    /// Export one element and its children, cropped to their bounds.
+    ///
+    /// The output is sized from the element's bounds unless the config sets
+    /// a width or height; setting only one keeps the aspect ratio.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the element is not in the scene, if the output
+    /// would exceed [`MAX_ELEMENT_EXPORT_SIZE`] on either side, or if it
+    /// cannot be rendered or encoded.
+    pub fn export_element(
+        &self,
+        scene: &Scene,
+        id: ElementId,
+        format: ExportFormat,
+    ) -> RenderResult<Vec<u8>> {
+        let view = element_view(scene, id)?;
+        let (out_w, out_h) = self.element_dimensions(&view);
+        if out_w > MAX_ELEMENT_EXPORT_SIZE || out_h > MAX_ELEMENT_EXPORT_SIZE {
+            return Err(RenderError::Export(format!(
+                "Output {out_w}x{out_h} exceeds {MAX_ELEMENT_EXPORT_SIZE} pixels"
+            )));
+        }
+        let svg = self.render_element_to_svg(scene, id)?;
+        self.encode(svg, (out_w, out_h), format)
+    }
+
+    /// Export one element and its children to an SVG string.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the element is not in the scene.
+    pub fn render_element_to_svg(&self, scene: &Scene, id: ElementId) -> RenderResult<String> {
+        let view = element_view(scene, id)?;
+        let mut elements = Vec::new();
+        collect_subtree(scene, id, &mut elements);
+        Ok(self.svg_document(scene, elements, &view, self.element_dimensions(&view)))
+    }
+
+    /// Export an image with the markup pinned to it, cropped to the image.
+    ///
+    /// Unlike [`export_element`](Self::export_element) there is no padding:
+    /// markup hanging over the image's edges is cut off, so the output can
+    /// stand in for the original image.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the element is not an image in the scene, if the
+    /// output would exceed [`MAX_ELEMENT_EXPORT_SIZE`] on either side, or if
+    /// it cannot be rendered or encoded.
+    pub fn flatten_image(
+        &self,
+        scene: &Scene,
+        id: ElementId,
+        format: ExportFormat,
+    ) -> RenderResult<Vec<u8>> {
+        let image = scene
+            .get_element(id)
+            .ok_or_else(|| RenderError::Export(format!("Element not found: {id}")))?;
+        if !matches!(image.kind, ElementKind::Image { .. }) {
+            return Err(RenderError::Export(format!(
+                "Only images can be flattened, not a {}",
+                image.kind.type_name()
+            )));
+        }
+        let view = SceneBounds::from_transform(&image.transform);
+        let dims = self.element_dimensions(&view);
+        if dims.0 > MAX_ELEMENT_EXPORT_SIZE || dims.1 > MAX_ELEMENT_EXPORT_SIZE {
+            return Err(RenderError::Export(format!(
+                "Output {}x{} exceeds {MAX_ELEMENT_EXPORT_SIZE} pixels",
+                dims.0, dims.1
+            )));
+        }
+        let mut elements = Vec::new();
+        collect_subtree(scene, id, &mut elements);
+        let svg = self.svg_document(scene, elements, &view, dims);
+        self.encode(svg, dims, format)
+    }
+
+    /// Export the scene as a multi-page PDF laid out for printing.
+    ///
+    /// See [`PrintLayout::compute`] for how the scene is split into pages.
+    /// The exporter's background color fills each page's content area.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the print config is invalid, the layout needs too
+    /// many pages, or a page cannot be rendered or encoded.
+    pub fn export_print(&self, scene: &Scene, print: &PrintConfig) -> RenderResult<Vec<u8>> {
+        let layout = PrintLayout::compute(scene, print)?;
+        let pages: Vec<String> = layout
+            .pages
+            .iter()
+            .enumerate()
+            .map(|(i, page)| {
+                self.print_page_svg(
+                    scene,
+                    print,
+                    layout.scale,
+                    &page.view,
+                    i + 1,
+                    layout.pages.len(),
+                )
+            })
+            .collect();
+        let (page_w, page_h) = print.page_mm();
+        Self::pdf_from_pages(&pages, (page_w, page_h), print.dpi)
+    }
+
+    /// One print page: the scene inside `view` placed inside the margins,
+    /// plus the header and footer, in page pixels.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn print_page_svg(
+        &self,
+        scene: &Scene,
+        print: &PrintConfig,
+        scale: f32,
+        view: &SceneBounds,
+        number: usize,
+        count: usize,
+    ) -> String {
+        let (page_w, page_h) = print.page_mm();
+        let content = self.svg_document(
+            scene,
+            scene.elements_in(view).collect(),
+            view,
+            (
+                print.px(view.width() * scale).round().max(1.0) as u32,
+                print.px(view.height() * scale).round().max(1.0) as u32,
+            ),
+        );
+        let mut svg = String::with_capacity(content.len() + 1024);
+        let _ = write!(
+            svg,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{1}\" viewBox=\"0 0 {0} {1}\">\
+             <rect width=\"100%\" height=\"100%\" fill=\"#ffffff\"/>\
+             <g transform=\"translate({2} {3})\">{content}</g>{4}</svg>",
+            print.px(page_w),
+            print.px(page_h),
+            print.px(print.margin_mm),
+            print.px(print.margin_mm + crate::print::BAND_MM),
+            print.bands_svg(number, count),
+        );
+        svg
+    }
+
+    /// Encode an SVG document of the given pixel size to `format`.
+    fn encode(
+        &self,
+        svg: String,
+        (out_w, out_h): (u32, u32),
+        format: ExportFormat,
+    ) -> RenderResult<Vec<u8>> {
         match format {
-            ExportFormat::Png => self.render_to_png(scene),
-            ExportFormat::Jpeg => self.render_to_jpeg(scene),
-            ExportFormat::Svg => {
-                let svg = self.render_to_svg(scene)?;
-                Ok(svg.into_bytes())
-            }
-            ExportFormat::Pdf => self.render_to_pdf(scene),
+            ExportFormat::Png => Self::png_from_svg(&svg),
+            ExportFormat::Jpeg => self.jpeg_from_svg(&svg),
+            ExportFormat::Svg => Ok(svg.into_bytes()),
+            ExportFormat::Pdf => self.pdf_from_svg(&svg, out_w, out_h),
         }
     }
 
@@ -95,8 +301,34 @@ impl SceneExporter {
     ///
     /// Returns an error if rendering or encoding fails.
     pub fn render_to_png(&self, scene: &Scene) -> RenderResult<Vec<u8>> {
-        let svg_string = self.render_to_svg(scene)?;
-        let pixmap = Self::rasterize_svg(&svg_string)?;
+        Self::png_from_svg(&self.render_to_svg(scene)?)
+    }
+
+    /// Rasterize the scene into an RGBA image with straight alpha.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if rendering fails.
+    pub fn render_to_image(&self, scene: &Scene) -> RenderResult<RgbaImage> {
+        Self::image_from_svg(&self.render_to_svg(scene)?)
+    }
+
+    /// Rasterize an SVG document into an RGBA image with straight alpha.
+    pub(crate) fn image_from_svg(svg_string: &str) -> RenderResult<RgbaImage> {
+        let pixmap = Self::rasterize_svg(svg_string)?;
+        let pixels = pixmap
+            .pixels()
+            .iter()
+            .flat_map(|p| {
+                let c = p.demultiply();
+                [c.red(), c.green(), c.blue(), c.alpha()]
+            })
+            .collect();
+        RgbaImage::new(pixmap.width(), pixmap.height(), pixels)
+    }
+
+    fn png_from_svg(svg_string: &str) -> RenderResult<Vec<u8>> {
+        let pixmap = Self::rasterize_svg(svg_string)?;
 
         pixmap
             .encode_png()
@@ -108,10 +340,13 @@ impl SceneExporter {
     /// # Errors
     ///
     /// Returns an error if rendering or encoding fails.
-    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
     pub fn render_to_jpeg(&self, scene: &Scene) -> RenderResult<Vec<u8>> {
-        let svg_string = self.render_to_svg(scene)?;
-        let pixmap = Self::rasterize_svg(&svg_string)?;
+        self.jpeg_from_svg(&self.render_to_svg(scene)?)
+    }
+
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn jpeg_from_svg(&self, svg_string: &str) -> RenderResult<Vec<u8>> {
+        let pixmap = Self::rasterize_svg(svg_string)?;
 
         let (width, height) = (pixmap.width(), pixmap.height());
         let bg = &self.config.background;
@@ -139,17 +374,72 @@ impl SceneExporter {
     /// # Errors
     ///
     /// Returns an error if scene elements cannot be represented as SVG.
-    #[allow(clippy::cast_precision_loss)]
     pub fn render_to_svg(&self, scene: &Scene) -> RenderResult<String> {
+        Ok(self.render_to_svg_with(scene, &|_, _| false))
+    }
+
+    /// Export the scene to an SVG string, letting `custom` draw elements
+    /// itself: it returns `true` when it has written the element.
+    #[allow(clippy::cast_precision_loss)]
+    pub(crate) fn render_to_svg_with(
+        &self,
+        scene: &Scene,
+        custom: &CustomElementSvg<'_>,
+    ) -> String {
         let (out_w, out_h) = self.output_dimensions(scene);
         let scale = self.config.scale;
-        let view_w = out_w as f32 / scale;
-        let view_h = out_h as f32 / scale;
+        let view = SceneBounds {
+            min_x: 0.0,
+            min_y: 0.0,
+            max_x: out_w as f32 / scale,
+            max_y: out_h as f32 / scale,
+        };
+        self.render_view_to_svg_with(scene, &view, (out_w, out_h), custom)
+    }
+
+    /// [`render_to_svg_with`](Self::render_to_svg_with), showing `view` (in
+    /// canvas coordinates) at `dims` pixels instead of the viewport.
+    pub(crate) fn render_view_to_svg_with(
+        &self,
+        scene: &Scene,
+        view: &SceneBounds,
+        dims: (u32, u32),
+        custom: &CustomElementSvg<'_>,
+    ) -> String {
+        self.svg_document_with(scene, scene.elements().collect(), view, dims, custom)
+    }
+
+    /// Build an SVG document showing `view` (in canvas coordinates) at the
+    /// given pixel size, with the scene's branding stamped on top.
+    fn svg_document(
+        &self,
+        scene: &Scene,
+        elements: Vec<&Element>,
+        view: &SceneBounds,
+        dims: (u32, u32),
+    ) -> String {
+        self.svg_document_with(scene, elements, view, dims, &|_, _| false)
+    }
 
+    /// [`svg_document`](Self::svg_document), with `custom` given the first
+    /// chance to draw each element.
+    #[allow(clippy::cast_precision_loss)] // Output dimensions fit in f32 mantissa
+    fn svg_document_with(
+        &self,
+        scene: &Scene,
+        mut elements: Vec<&Element>,
+        view: &SceneBounds,
+        (out_w, out_h): (u32, u32),
+        custom: &CustomElementSvg<'_>,
+    ) -> String {
         let mut svg = String::with_capacity(4096);
         let _ = write!(
             svg,
-            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{out_w}\" height=\"{out_h}\" viewBox=\"0 0 {view_w} {view_h}\">",
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{out_w}\" height=\"{out_h}\" viewBox=\"{} {} {} {}\">",
+            view.min_x,
+            view.min_y,
+            view.width(),
+            view.height(),
         );
 
         // Background
@@ -157,20 +447,79 @@ impl SceneExporter {
         let bg_alpha = f32::from(bg[3]) / 255.0;
         let _ = write!(
             svg,
-            "<rect width=\"100%\" height=\"100%\" fill=\"rgba({},{},{},{})\"/>",
-            bg[0], bg[1], bg[2], bg_alpha,
+            "<rect x=\"{}\" y=\"{}\" width=\"100%\" height=\"100%\" fill=\"rgba({},{},{},{})\"/>",
+            view.min_x, view.min_y, bg[0], bg[1], bg[2], bg_alpha,
         );
 
-        // Collect and sort elements by z-index
-        let mut elements: Vec<_> = scene.elements().collect();
-        elements.sort_by_key(|e| e.transform.z_index);
+        // Drop hidden elements and sort the rest by z-index. Exports have no
+        // camera, so screen-anchored elements keep their coordinates and
+        // only move on top.
+        elements.retain(|e| scene.is_shown(e));
+        elements.sort_by_key(|e| (e.screen_anchored, e.transform.z_index));
 
         for element in &elements {
-            render_element_svg(&mut svg, element);
+            let redacted = match self.config.redaction {
+                Some(redaction) if scene.is_sensitive(element) => Some(redaction),
+                _ => None,
+            };
+            if redacted.is_some() && redaction_covers(scene, element, &elements) {
+                continue;
+            }
+            let opacity = element.effective_opacity();
+            if opacity < 1.0 {
+                let _ = write!(svg, "<g opacity=\"{opacity}\">");
+            }
+            if let Some(redaction) = redacted {
+                match redaction {
+                    Redaction::Placeholder => render_element_svg(
+                        &mut svg,
+                        &redact::placeholder(element),
+                        scene.theme(),
+                        None,
+                    ),
+                    Redaction::Blur => {
+                        blurred_element_svg(
+                            &mut svg,
+                            scene,
+                            element,
+                            view.width() / out_w.max(1) as f32,
+                        );
+                    }
+                }
+            } else if !custom(&mut svg, element) {
+                render_element_svg(
+                    &mut svg,
+                    element,
+                    scene.theme(),
+                    scene.callout_tail(element.id),
+                );
+            }
+            if opacity < 1.0 {
+                svg.push_str("</g>");
+            }
+        }
+
+        if let Some(branding) = scene.branding() {
+            // Laid out in output pixels, so the stamp is the same size
+            // whatever part of the canvas is exported
+            let _ = write!(
+                svg,
+                "<g transform=\"translate({} {}) scale({} {})\">",
+                view.min_x,
+                view.min_y,
+                view.width() / out_w.max(1) as f32,
+                view.height() / out_h.max(1) as f32,
+            );
+            for element in branding.overlay(out_w as f32, out_h as f32) {
+                let _ = write!(svg, "<g opacity=\"{}\">", element.effective_opacity());
+                render_element_svg(&mut svg, &element, scene.theme(), None);
+                svg.push_str("</g>");
+            }
+            svg.push_str("</g>");
         }
 
         svg.push_str("</svg>");
-        Ok(svg)
+        svg
     }
 
     /// Export the scene to PDF bytes.
@@ -182,40 +531,56 @@ impl SceneExporter {
     /// Returns an error if rendering or PDF generation fails.
     #[allow(clippy::cast_precision_loss)]
     pub fn render_to_pdf(&self, scene: &Scene) -> RenderResult<Vec<u8>> {
-        let png_data = self.render_to_png(scene)?;
         let (out_w, out_h) = self.output_dimensions(scene);
+        self.pdf_from_svg(&self.render_to_svg(scene)?, out_w, out_h)
+    }
 
+    #[allow(clippy::cast_precision_loss)]
+    fn pdf_from_svg(&self, svg_string: &str, out_w: u32, out_h: u32) -> RenderResult<Vec<u8>> {
         // Convert pixel dimensions to mm: pixels / dpi * 25.4
-        let page_width_mm = out_w as f32 / self.config.dpi * 25.4;
-        let page_height_mm = out_h as f32 / self.config.dpi * 25.4;
+        let page_mm = (
+            out_w as f32 / self.config.dpi * 25.4,
+            out_h as f32 / self.config.dpi * 25.4,
+        );
+        Self::pdf_from_pages(&[svg_string.to_string()], page_mm, self.config.dpi)
+    }
 
-        let (doc, page1, layer1) = printpdf::PdfDocument::new(
+    /// Build a PDF with one page of `page_mm` per SVG, each rasterized and
+    /// placed at `dpi` so it fills its page.
+    fn pdf_from_pages(
+        pages: &[String],
+        (page_w, page_h): (f32, f32),
+        dpi: f32,
+    ) -> RenderResult<Vec<u8>> {
+        let (doc, first_page, first_layer) = printpdf::PdfDocument::new(
             "Canvas Export",
-            printpdf::Mm(page_width_mm),
-            printpdf::Mm(page_height_mm),
+            printpdf::Mm(page_w),
+            printpdf::Mm(page_h),
             "Layer 1",
         );
 
-        let current_layer = doc.get_page(page1).get_layer(layer1);
-
-        // Decode PNG using printpdf's bundled image crate for compatibility
-        let dynamic_image = printpdf::image_crate::load_from_memory(&png_data)
-            .map_err(|e| RenderError::Export(format!("Failed to decode PNG for PDF: {e}")))?;
-
-        let pdf_image = printpdf::Image::from_dynamic_image(&dynamic_image);
-
-        let scale_x = page_width_mm / out_w as f32;
-        let scale_y = page_height_mm / out_h as f32;
-
-        let transform = printpdf::ImageTransform {
-            translate_x: Some(printpdf::Mm(0.0)),
-            translate_y: Some(printpdf::Mm(0.0)),
-            scale_x: Some(scale_x),
-            scale_y: Some(scale_y),
-            ..Default::default()
-        };
-
-        pdf_image.add_to_layer(current_layer, transform);
+        for (i, svg_string) in pages.iter().enumerate() {
+            let (page, layer) = if i == 0 {
+                (first_page, first_layer)
+            } else {
+                doc.add_page(printpdf::Mm(page_w), printpdf::Mm(page_h), "Layer 1")
+            };
+            let current_layer = doc.get_page(page).get_layer(layer);
+
+            // Decode PNG using printpdf's bundled image crate for compatibility
+            let png_data = Self::png_from_svg(svg_string)?;
+            let dynamic_image = printpdf::image_crate::load_from_memory(&png_data)
+                .map_err(|e| RenderError::Export(format!("Failed to decode PNG for PDF: {e}")))?;
+
+            let pdf_image = printpdf::Image::from_dynamic_image(&dynamic_image);
+            let transform = printpdf::ImageTransform {
+                translate_x: Some(printpdf::Mm(0.0)),
+                translate_y: Some(printpdf::Mm(0.0)),
+                dpi: Some(dpi),
+                ..Default::default()
+            };
+            pdf_image.add_to_layer(current_layer, transform);
+        }
 
         doc.save_to_bytes()
             .map_err(|e| RenderError::Export(format!("PDF save failed: {e}")))
@@ -240,6 +605,27 @@ impl SceneExporter {
         (out_w.max(1), out_h.max(1))
     }
 
+    /// Output dimensions (width, height) in pixels for an element export
+    /// covering `view`.
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_precision_loss,
+        clippy::cast_sign_loss
+    )]
+    fn element_dimensions(&self, view: &SceneBounds) -> (u32, u32) {
+        let (view_w, view_h) = (view.width().max(1.0), view.height().max(1.0));
+        let (base_w, base_h) = match (self.config.width, self.config.height) {
+            (Some(w), Some(h)) => (w as f32, h as f32),
+            (Some(w), None) => (w as f32, w as f32 * view_h / view_w),
+            (None, Some(h)) => (h as f32 * view_w / view_h, h as f32),
+            (None, None) => (view_w, view_h),
+        };
+
+        let out_w = (base_w * self.config.scale).round() as u32;
+        let out_h = (base_h * self.config.scale).round() as u32;
+        (out_w.max(1), out_h.max(1))
+    }
+
     /// Rasterize an SVG string to a tiny-skia Pixmap.
     #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
     fn rasterize_svg(svg_string: &str) -> RenderResult<tiny_skia::Pixmap> {
@@ -259,26 +645,219 @@ impl SceneExporter {
     }
 }
 
+/// Padded bounds of an element and its children.
+fn element_view(scene: &Scene, id: ElementId) -> RenderResult<SceneBounds> {
+    let mut elements = Vec::new();
+    collect_subtree(scene, id, &mut elements);
+    let bounds = elements
+        .iter()
+        .map(|e| SceneBounds::from_transform(&e.transform))
+        .reduce(|a, b| a.union(&b))
+        .ok_or_else(|| RenderError::Export(format!("Element not found: {id}")))?;
+    Ok(SceneBounds {
+        min_x: bounds.min_x - ELEMENT_PADDING,
+        min_y: bounds.min_y - ELEMENT_PADDING,
+        max_x: bounds.max_x + ELEMENT_PADDING,
+        max_y: bounds.max_y + ELEMENT_PADDING,
+    })
+}
+
+/// Whether a sensitive `element` is already hidden by the redaction of a
+/// sensitive ancestor in the same document.
+fn redaction_covers(scene: &Scene, element: &Element, elements: &[&Element]) -> bool {
+    element
+        .parent
+        .and_then(|id| elements.iter().find(|e| e.id == id))
+        .is_some_and(|parent| scene.is_sensitive(parent))
+}
+
+/// Draw `element` and its subtree as an `<image>` of blurred pixels, so
+/// the document carries nothing that can be read back.
+///
+/// The subtree is rasterized at one pixel per [`REDACTION_CELL`] canvas
+/// units, box-blurred and scaled back up over its bounds. `units_per_px`
+/// is the document's scale, used to keep the blur soft at high DPI.
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_precision_loss,
+    clippy::cast_sign_loss
+)]
+fn blurred_element_svg(svg: &mut String, scene: &Scene, element: &Element, units_per_px: f32) {
+    use base64::Engine;
+
+    let mut subtree = Vec::new();
+    collect_subtree(scene, element.id, &mut subtree);
+    subtree.retain(|e| scene.is_shown(e));
+    subtree.sort_by_key(|e| e.transform.z_index);
+    let Some(bounds) = subtree
+        .iter()
+        .map(|e| SceneBounds::from_transform(&e.transform))
+        .reduce(|a, b| a.union(&b))
+    else {
+        return;
+    };
+    let cell = REDACTION_CELL.max(units_per_px);
+    let view = SceneBounds {
+        min_x: bounds.min_x - cell,
+        min_y: bounds.min_y - cell,
+        max_x: bounds.max_x + cell,
+        max_y: bounds.max_y + cell,
+    };
+    let px_w = (view.width() / cell).ceil().clamp(1.0, 1024.0) as u32;
+    let px_h = (view.height() / cell).ceil().clamp(1.0, 1024.0) as u32;
+
+    let mut inner = String::with_capacity(1024);
+    let _ = write!(
+        inner,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{px_w}\" height=\"{px_h}\" viewBox=\"{} {} {} {}\">",
+        view.min_x,
+        view.min_y,
+        view.width(),
+        view.height(),
+    );
+    for e in &subtree {
+        render_element_svg(&mut inner, e, scene.theme(), scene.callout_tail(e.id));
+    }
+    inner.push_str("</svg>");
+
+    let Ok(mut pixmap) = SceneExporter::rasterize_svg(&inner) else {
+        return;
+    };
+    box_blur(pixmap.data_mut(), px_w as usize, px_h as usize);
+    let Ok(png) = pixmap.encode_png() else {
+        return;
+    };
+    let _ = write!(
+        svg,
+        "<image x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" preserveAspectRatio=\"none\" href=\"data:image/png;base64,{}\"/>",
+        view.min_x,
+        view.min_y,
+        view.width(),
+        view.height(),
+        base64::engine::general_purpose::STANDARD.encode(png),
+    );
+}
+
+/// Blur premultiplied RGBA pixels in place with a 3x3 box, twice.
+fn box_blur(pixels: &mut [u8], width: usize, height: usize) {
+    let mut scratch = pixels.to_vec();
+    for _ in 0..2 {
+        scratch.copy_from_slice(pixels);
+        for y in 0..height {
+            for x in 0..width {
+                for channel in 0..4 {
+                    let (mut sum, mut count) = (0_u32, 0_u32);
+                    for ny in y.saturating_sub(1)..=(y + 1).min(height - 1) {
+                        for nx in x.saturating_sub(1)..=(x + 1).min(width - 1) {
+                            sum += u32::from(scratch[(ny * width + nx) * 4 + channel]);
+                            count += 1;
+                        }
+                    }
+                    // The mean of bytes is a byte
+                    #[allow(clippy::cast_possible_truncation)]
+                    {
+                        pixels[(y * width + x) * 4 + channel] = (sum / count) as u8;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Collect an element and, recursively, its children.
+pub(crate) fn collect_subtree<'a>(scene: &'a Scene, id: ElementId, out: &mut Vec<&'a Element>) {
+    let Some(element) = scene.get_element(id) else {
+        return;
+    };
+    // Guard against malformed scenes where a group contains itself
+    if out.iter().any(|e| e.id == id) {
+        return;
+    }
+    out.push(element);
+    for &child in element.kind.children() {
+        collect_subtree(scene, child, out);
+    }
+}
+
 /// Render a single element to SVG.
-fn render_element_svg(svg: &mut String, element: &canvas_core::Element) {
+///
+/// Colors are resolved against `theme`. `tail` is the resolved tail
+/// triangle of a callout, if it has one.
+#[allow(clippy::too_many_lines)]
+fn render_element_svg(
+    svg: &mut String,
+    element: &canvas_core::Element,
+    theme: &Theme,
+    tail: Option<[(f32, f32); 3]>,
+) {
     let tf = &element.transform;
+    // Hex colors need no XML escaping
+    let color = element
+        .kind
+        .resolved_color(theme)
+        .unwrap_or(ElementKind::TEXT_COLOR)
+        .to_hex();
 
     match &element.kind {
+        ElementKind::StickyNote {
+            content, author, ..
+        }
+        | ElementKind::Callout {
+            content, author, ..
+        } => {
+            if let Some([a, b, tip]) = tail {
+                let _ = write!(
+                    svg,
+                    "<polygon points=\"{},{} {},{} {},{}\" fill=\"{color}\" stroke=\"#999\" stroke-width=\"1\"/>",
+                    a.0, a.1, b.0, b.1, tip.0, tip.1,
+                );
+            }
+            let _ = write!(
+                svg,
+                "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" rx=\"4\" fill=\"{color}\" stroke=\"#999\" stroke-width=\"1\"/>",
+                tf.x, tf.y, tf.width, tf.height,
+            );
+            let escaped = escape_xml(content);
+            let (tx, ty) = (tf.x + 8.0, tf.y + 20.0);
+            let _ = write!(
+                svg,
+                "<text x=\"{tx}\" y=\"{ty}\" font-size=\"14\" fill=\"#333\" font-family=\"sans-serif\">{escaped}</text>",
+            );
+            if let Some(author) = author {
+                let escaped = escape_xml(author);
+                let ay = tf.y + tf.height - 8.0;
+                let _ = write!(
+                    svg,
+                    "<text x=\"{tx}\" y=\"{ay}\" font-size=\"11\" fill=\"#666\" font-style=\"italic\" font-family=\"sans-serif\">{escaped}</text>",
+                );
+            }
+        }
+
         ElementKind::Text {
-            content,
-            font_size,
-            color,
+            content, font_size, ..
         } => {
             let escaped = escape_xml(content);
-            let escaped_color = escape_xml(color);
             let text_y = tf.y + font_size;
             let _ = write!(
                 svg,
-                "<text x=\"{}\" y=\"{text_y}\" font-size=\"{font_size}\" fill=\"{escaped_color}\" font-family=\"sans-serif\">{escaped}</text>",
+                "<text x=\"{}\" y=\"{text_y}\" font-size=\"{font_size}\" fill=\"{color}\" font-family=\"sans-serif\">{escaped}</text>",
                 tf.x,
             );
         }
 
+        ElementKind::Math { tex, font_size, .. } => {
+            render_math_svg(svg, tf, tex, *font_size, &color);
+        }
+
+        ElementKind::Code { source, spans, .. } => render_code_svg(svg, tf, source, spans),
+
+        ElementKind::LogView {
+            title,
+            lines,
+            follow,
+            ..
+        } => render_log_svg(svg, tf, title, lines, *follow),
+
         ElementKind::Image { src, .. } => {
             let escaped_src = escape_xml(src);
             let _ = write!(
@@ -288,7 +867,9 @@ fn render_element_svg(svg: &mut String, element: &canvas_core::Element) {
             );
         }
 
-        ElementKind::Chart { chart_type, data } => {
+        ElementKind::Chart {
+            chart_type, data, ..
+        } => {
             render_chart_svg(svg, tf.x, tf.y, tf.width, tf.height, chart_type, data);
 
             let label = format!("{chart_type} chart");
@@ -304,25 +885,1270 @@ fn render_element_svg(svg: &mut String, element: &canvas_core::Element) {
         ElementKind::Group { .. } | ElementKind::OverlayLayer { .. } => {
             let _ = write!(svg, "<g transform=\"translate({},{})\"></g>", tf.x, tf.y);
         }
-
-        ElementKind::Model3D { .. } | ElementKind::Video { .. } => {
-            let label = match &element.kind {
-                ElementKind::Model3D { .. } => "3D Model",
-                ElementKind::Video { .. } => "Video",
-                _ => "Unknown",
-            };
-            let _ = write!(
-                svg,
-                "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"#e0e0e0\" stroke=\"#999\" stroke-width=\"1\"/>",
-                tf.x, tf.y, tf.width, tf.height,
-            );
-            let center_x = tf.x + tf.width / 2.0;
-            let center_y = tf.y + tf.height / 2.0;
-            let _ = write!(
-                svg,
-                "<text x=\"{center_x}\" y=\"{center_y}\" font-size=\"14\" fill=\"#666\" text-anchor=\"middle\" font-family=\"sans-serif\">{label}</text>",
-            );
+
+        ElementKind::Embed { url, .. } => {
+            // Exports cannot load pages; show where the embed points
+            let _ = write!(
+                svg,
+                "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"#eef0f5\" stroke=\"#999\" stroke-width=\"1\"/>",
+                tf.x, tf.y, tf.width, tf.height,
+            );
+            let center_x = tf.x + tf.width / 2.0;
+            let center_y = tf.y + tf.height / 2.0;
+            let escaped = escape_xml(url);
+            let _ = write!(
+                svg,
+                "<text x=\"{center_x}\" y=\"{center_y}\" font-size=\"14\" fill=\"#666\" text-anchor=\"middle\" font-family=\"sans-serif\">Web page</text>",
+            );
+            let url_y = center_y + 18.0;
+            let _ = write!(
+                svg,
+                "<text x=\"{center_x}\" y=\"{url_y}\" font-size=\"11\" fill=\"#888\" text-anchor=\"middle\" font-family=\"sans-serif\">{escaped}</text>",
+            );
+        }
+
+        ElementKind::Audio {
+            waveform, state, ..
+        } => render_audio_svg(svg, tf, waveform, *state),
+
+        ElementKind::Calendar {
+            view,
+            date,
+            events,
+            selected,
+        } => render_calendar_svg(svg, tf, *view, date, (events, theme), selected.as_deref()),
+
+        ElementKind::Poll { .. } => {
+            if let Some(results) = PollResults::from_kind(&element.kind) {
+                render_poll_svg(svg, tf, &results);
+            }
+        }
+
+        ElementKind::Agenda { title, items, .. } => {
+            if let Some(status) = AgendaStatus::from_kind(&element.kind, now_ms()) {
+                render_agenda_svg(svg, tf, title, items, &status);
+            }
+        }
+
+        ElementKind::Randomizer { source, outcome } => {
+            render_randomizer_svg(svg, tf, source, outcome.as_ref());
+        }
+
+        ElementKind::FloorPlan {
+            background,
+            plan_width,
+            plan_height,
+            paths,
+            markers,
+            ..
+        } => render_floor_plan_svg(
+            svg,
+            tf,
+            background.as_deref(),
+            (*plan_width, *plan_height),
+            paths,
+            markers,
+        ),
+
+        ElementKind::Tree { root, direction } => render_tree_svg(svg, tf, root, *direction, theme),
+
+        ElementKind::Gauge { .. } => render_gauge_svg(svg, tf, &element.kind, theme),
+
+        ElementKind::Kpi { .. } => render_kpi_svg(svg, tf, &element.kind, theme),
+
+        ElementKind::Sparkline { values, kind, .. } => {
+            let [x, y, width, height] = sparkline_area(tf.width, tf.height);
+            let color = element
+                .kind
+                .resolved_color(theme)
+                .unwrap_or(ElementKind::SPARKLINE_COLOR)
+                .to_hex();
+            render_sparkline_svg(
+                svg,
+                values,
+                *kind,
+                [tf.x + x, tf.y + y, width, height],
+                &color,
+            );
+        }
+
+        ElementKind::Ink { .. } => {
+            let color = element
+                .kind
+                .resolved_color(theme)
+                .unwrap_or(ElementKind::INK_COLOR)
+                .to_hex();
+            if let Some((points, width)) = ink::canvas_points(element) {
+                render_ink_svg(svg, &points, width, &color);
+            }
+        }
+
+        ElementKind::Measurement { .. } => {
+            if let Some(layout) = MeasurementLayout::from_element(element) {
+                let color = annotation_color(element, theme);
+                render_measurement_svg(svg, &layout, &color);
+            }
+        }
+
+        ElementKind::Region { caption, .. } => {
+            let color = annotation_color(element, theme);
+            render_region_svg(svg, tf, caption.as_deref(), &color);
+        }
+
+        ElementKind::Shape { shape } => render_shape_svg(svg, element, shape, theme),
+
+        ElementKind::Barcode {
+            symbology, modules, ..
+        } => render_barcode_svg(svg, &BarcodeLayout::new(*symbology, modules, tf), &color),
+
+        ElementKind::Model3D { .. } | ElementKind::Video { .. } | ElementKind::Shader { .. } => {
+            let label = match &element.kind {
+                ElementKind::Model3D { .. } => "3D Model",
+                ElementKind::Video { .. } => "Video",
+                ElementKind::Shader { .. } => "Shader",
+                _ => "Unknown",
+            };
+            let _ = write!(
+                svg,
+                "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"#e0e0e0\" stroke=\"#999\" stroke-width=\"1\"/>",
+                tf.x, tf.y, tf.width, tf.height,
+            );
+            let center_x = tf.x + tf.width / 2.0;
+            let center_y = tf.y + tf.height / 2.0;
+            let _ = write!(
+                svg,
+                "<text x=\"{center_x}\" y=\"{center_y}\" font-size=\"14\" fill=\"#666\" text-anchor=\"middle\" font-family=\"sans-serif\">{label}</text>",
+            );
+        }
+    }
+}
+
+/// Render an audio element as a play/pause button beside its waveform.
+#[allow(clippy::cast_precision_loss)]
+fn render_audio_svg(
+    svg: &mut String,
+    tf: &canvas_core::Transform,
+    waveform: &[f32],
+    state: PlaybackState,
+) {
+    let pad = (tf.height * 0.15).min(12.0);
+    let _ = write!(
+        svg,
+        "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" rx=\"{pad}\" fill=\"#263238\"/>",
+        tf.x, tf.y, tf.width, tf.height,
+    );
+
+    // Play/pause button
+    let diameter = (tf.height - 2.0 * pad).min(tf.width / 3.0).max(0.0);
+    let (cx, cy) = (tf.x + pad + diameter / 2.0, tf.y + tf.height / 2.0);
+    let r = diameter / 2.0;
+    let _ = write!(
+        svg,
+        "<circle cx=\"{cx}\" cy=\"{cy}\" r=\"{r}\" fill=\"#eceff1\"/>",
+    );
+    if state == PlaybackState::Playing {
+        for dx in [-0.35, 0.1] {
+            let _ = write!(
+                svg,
+                "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"#263238\"/>",
+                cx + dx * r,
+                cy - 0.4 * r,
+                0.25 * r,
+                0.8 * r,
+            );
+        }
+    } else {
+        let _ = write!(
+            svg,
+            "<polygon points=\"{},{} {},{} {},{}\" fill=\"#263238\"/>",
+            cx - 0.3 * r,
+            cy - 0.45 * r,
+            cx - 0.3 * r,
+            cy + 0.45 * r,
+            cx + 0.5 * r,
+            cy,
+        );
+    }
+
+    // Waveform bars, centered vertically
+    let left = tf.x + 2.0 * pad + diameter;
+    let width = tf.x + tf.width - pad - left;
+    if waveform.is_empty() || width <= 0.0 {
+        return;
+    }
+    let step = width / waveform.len() as f32;
+    let max_height = tf.height - 2.0 * pad;
+    let _ = write!(svg, "<g fill=\"#80cbc4\">");
+    for (i, amplitude) in waveform.iter().enumerate() {
+        let height = (amplitude.clamp(0.0, 1.0) * max_height).max(1.0);
+        let _ = write!(
+            svg,
+            "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{height}\"/>",
+            left + i as f32 * step,
+            cy - height / 2.0,
+            step * 0.6,
+        );
+    }
+    svg.push_str("</g>");
+}
+
+/// Render a calendar grid with its events into SVG.
+///
+/// Calendars with an invalid date are drawn as an empty frame.
+#[allow(clippy::cast_precision_loss)]
+fn render_calendar_svg(
+    svg: &mut String,
+    tf: &canvas_core::Transform,
+    view: CalendarView,
+    date: &str,
+    (events, theme): (&[canvas_core::CalendarEvent], &Theme),
+    selected: Option<&str>,
+) {
+    let _ = write!(
+        svg,
+        "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"#ffffff\" stroke=\"#b0bec5\" stroke-width=\"1\"/>",
+        tf.x, tf.y, tf.width, tf.height,
+    );
+    let Some(layout) = CalendarLayout::new(view, date, tf.width, tf.height) else {
+        return;
+    };
+
+    // Title band and weekday header
+    let _ = write!(
+        svg,
+        "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"#37474f\"/>",
+        tf.x, tf.y, tf.width, layout.title_height,
+    );
+    let _ = write!(
+        svg,
+        "<text x=\"{}\" y=\"{}\" font-size=\"{}\" fill=\"#ffffff\" font-family=\"sans-serif\" text-anchor=\"middle\" dominant-baseline=\"central\">{}</text>",
+        tf.x + tf.width / 2.0,
+        tf.y + layout.title_height / 2.0,
+        layout.title_height * 0.55,
+        escape_xml(&layout.title),
+    );
+    let _ = write!(
+        svg,
+        "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"#eceff1\"/>",
+        tf.x,
+        tf.y + layout.weekday_y,
+        tf.width,
+        layout.weekday_height,
+    );
+    for (i, name) in WEEKDAYS.iter().enumerate() {
+        let _ = write!(
+            svg,
+            "<text x=\"{}\" y=\"{}\" font-size=\"{}\" fill=\"#546e7a\" font-family=\"sans-serif\" text-anchor=\"middle\" dominant-baseline=\"central\">{name}</text>",
+            tf.x + (i as f32 + 0.5) * layout.column_width,
+            tf.y + layout.weekday_y + layout.weekday_height / 2.0,
+            layout.weekday_height * 0.6,
+        );
+    }
+
+    let selected = selected.and_then(canvas_core::CalendarDate::parse);
+    for cell in &layout.cells {
+        let (x, y) = (tf.x + cell.x, tf.y + cell.y);
+        let fill = if Some(cell.date) == selected {
+            "#bbdefb"
+        } else {
+            "none"
+        };
+        let _ = write!(
+            svg,
+            "<rect x=\"{x}\" y=\"{y}\" width=\"{}\" height=\"{}\" fill=\"{fill}\" stroke=\"#cfd8dc\" stroke-width=\"1\"/>",
+            cell.width, cell.height,
+        );
+        let number_size = cell.number_size();
+        let _ = write!(
+            svg,
+            "<text x=\"{}\" y=\"{}\" font-size=\"{number_size}\" fill=\"{}\" font-family=\"sans-serif\">{}</text>",
+            x + 4.0,
+            y + number_size + 2.0,
+            if cell.in_range { "#263238" } else { "#b0bec5" },
+            cell.date.day,
+        );
+        let day_events: Vec<_> = events_on(events, cell.date).collect();
+        render_calendar_events_svg(svg, cell, (x, y), &day_events, theme);
+    }
+}
+
+/// Render one calendar day's event chips into SVG, with a count of any
+/// that do not fit.
+#[allow(clippy::cast_precision_loss)]
+fn render_calendar_events_svg(
+    svg: &mut String,
+    cell: &CalendarCell,
+    (x, y): (f32, f32),
+    events: &[&canvas_core::CalendarEvent],
+    theme: &Theme,
+) {
+    let line = cell.event_line();
+    let top = y + cell.events_top();
+    let font_size = cell.number_size() * 0.85;
+    let shown = cell.visible_events(events.len());
+    for (i, event) in events.iter().take(shown).enumerate() {
+        let chip_y = top + i as f32 * line;
+        let color = event.resolved_color(theme);
+        let label = match &event.time {
+            Some(time) => format!("{time} {}", event.title),
+            None => event.title.clone(),
+        };
+        let _ = write!(
+            svg,
+            "<rect x=\"{}\" y=\"{chip_y}\" width=\"{}\" height=\"{}\" rx=\"2\" fill=\"{color}\"/>",
+            x + 2.0,
+            (cell.width - 4.0).max(0.0),
+            line - 2.0,
+        );
+        let _ = write!(
+            svg,
+            "<text x=\"{}\" y=\"{}\" font-size=\"{font_size}\" fill=\"#ffffff\" font-family=\"sans-serif\">{}</text>",
+            x + 4.0,
+            chip_y + line - 4.0,
+            escape_xml(&label),
+        );
+    }
+    if shown < events.len() && top + line <= y + cell.height {
+        let _ = write!(
+            svg,
+            "<text x=\"{}\" y=\"{}\" font-size=\"{font_size}\" fill=\"#546e7a\" font-family=\"sans-serif\">+{} more</text>",
+            x + 4.0,
+            top + (shown + 1) as f32 * line - 4.0,
+            events.len() - shown,
+        );
+    }
+}
+
+/// Render a poll's question and a result bar per option into SVG.
+fn render_poll_svg(svg: &mut String, tf: &canvas_core::Transform, results: &PollResults) {
+    let _ = write!(
+        svg,
+        "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"#ffffff\" stroke=\"#b0bec5\" stroke-width=\"1\"/>",
+        tf.x, tf.y, tf.width, tf.height,
+    );
+    let layout = PollLayout::new(results.options.len(), tf.width, tf.height);
+    let _ = write!(
+        svg,
+        "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"#37474f\"/>",
+        tf.x, tf.y, tf.width, layout.title_height,
+    );
+    let _ = write!(
+        svg,
+        "<text x=\"{}\" y=\"{}\" font-size=\"{}\" fill=\"#ffffff\" font-family=\"sans-serif\" dominant-baseline=\"central\">{}</text>",
+        tf.x + PollLayout::PADDING,
+        tf.y + layout.title_height / 2.0,
+        layout.title_height * 0.5,
+        escape_xml(&results.question),
+    );
+
+    let bar = if results.closed { "#90a4ae" } else { "#4db6ac" };
+    for (row, option) in layout.rows.iter().zip(&results.options) {
+        let (x, y) = (tf.x + row.x, tf.y + row.y);
+        let _ = write!(
+            svg,
+            "<rect x=\"{x}\" y=\"{y}\" width=\"{}\" height=\"{}\" rx=\"3\" fill=\"#eceff1\"/>",
+            row.width, row.height,
+        );
+        let _ = write!(
+            svg,
+            "<rect x=\"{x}\" y=\"{y}\" width=\"{}\" height=\"{}\" rx=\"3\" fill=\"{bar}\"/>",
+            row.bar_width(option.share),
+            row.height,
+        );
+        let font_size = row.font_size();
+        let text_y = y + row.height / 2.0;
+        let _ = write!(
+            svg,
+            "<text x=\"{}\" y=\"{text_y}\" font-size=\"{font_size}\" fill=\"#263238\" font-family=\"sans-serif\" dominant-baseline=\"central\">{}</text>",
+            x + 6.0,
+            escape_xml(&option.label),
+        );
+        let _ = write!(
+            svg,
+            "<text x=\"{}\" y=\"{text_y}\" font-size=\"{font_size}\" fill=\"#546e7a\" font-family=\"sans-serif\" text-anchor=\"end\" dominant-baseline=\"central\">{}</text>",
+            x + row.width - 6.0,
+            option.summary(),
+        );
+    }
+}
+
+/// Render an agenda: the title and clock in the header, then a row per
+/// item with the running one highlighted and finished ones dimmed.
+fn render_agenda_svg(
+    svg: &mut String,
+    tf: &canvas_core::Transform,
+    title: &str,
+    items: &[AgendaItem],
+    status: &AgendaStatus,
+) {
+    let _ = write!(
+        svg,
+        "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"#ffffff\" stroke=\"#b0bec5\" stroke-width=\"1\"/>",
+        tf.x, tf.y, tf.width, tf.height,
+    );
+    let layout = AgendaLayout::new(items.len(), tf.width, tf.height);
+    let _ = write!(
+        svg,
+        "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"#37474f\"/>",
+        tf.x, tf.y, tf.width, layout.header_height,
+    );
+    let header_y = tf.y + layout.header_height / 2.0;
+    let header_font = layout.header_font_size();
+    let _ = write!(
+        svg,
+        "<text x=\"{}\" y=\"{header_y}\" font-size=\"{header_font}\" fill=\"#ffffff\" font-family=\"sans-serif\" dominant-baseline=\"central\">{}</text>",
+        tf.x + AgendaLayout::PADDING * 2.0,
+        escape_xml(title),
+    );
+    let _ = write!(
+        svg,
+        "<text x=\"{}\" y=\"{header_y}\" font-size=\"{header_font}\" fill=\"#ffffff\" font-family=\"sans-serif\" text-anchor=\"end\" dominant-baseline=\"central\">{}</text>",
+        tf.x + tf.width - AgendaLayout::PADDING * 2.0,
+        status.clock_text(items),
+    );
+
+    for (row, item) in layout.rows.iter().zip(items) {
+        let (x, y) = (tf.x + row.x, tf.y + row.y);
+        let running = status.current() == Some(row.index);
+        if running {
+            let _ = write!(
+                svg,
+                "<rect x=\"{x}\" y=\"{y}\" width=\"{}\" height=\"{}\" rx=\"3\" fill=\"#e0f2f1\"/>",
+                row.width, row.height,
+            );
+            if let Some(progress) = status.progress() {
+                let [bx, by, width, height] = row.progress_bar(progress);
+                let _ = write!(
+                    svg,
+                    "<rect x=\"{}\" y=\"{}\" width=\"{width}\" height=\"{height}\" fill=\"#26a69a\"/>",
+                    tf.x + bx,
+                    tf.y + by,
+                );
+            }
+        }
+        let fill = if status.is_done(row.index) {
+            "#90a4ae"
+        } else {
+            "#263238"
+        };
+        let weight = if running { " font-weight=\"bold\"" } else { "" };
+        let font_size = row.font_size();
+        let text_y = y + row.height / 2.0;
+        let _ = write!(
+            svg,
+            "<text x=\"{}\" y=\"{text_y}\" font-size=\"{font_size}\" fill=\"{fill}\"{weight} font-family=\"sans-serif\" dominant-baseline=\"central\">{}</text>",
+            x + 6.0,
+            escape_xml(&item.label()),
+        );
+        let _ = write!(
+            svg,
+            "<text x=\"{}\" y=\"{text_y}\" font-size=\"{font_size}\" fill=\"{fill}\" font-family=\"sans-serif\" text-anchor=\"end\" dominant-baseline=\"central\">{}</text>",
+            x + row.width - 6.0,
+            item.duration_text(),
+        );
+    }
+}
+
+/// Current Unix time in milliseconds, for agenda clocks.
+fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| u64::try_from(d.as_millis()).unwrap_or(u64::MAX))
+}
+
+/// Render a gauge: its dial filled up to the value, threshold ticks, the
+/// value and the label.
+fn render_gauge_svg(
+    svg: &mut String,
+    tf: &canvas_core::Transform,
+    kind: &ElementKind,
+    theme: &Theme,
+) {
+    let ElementKind::Gauge {
+        value,
+        min,
+        max,
+        shape,
+        label,
+        thresholds,
+        format,
+    } = kind
+    else {
+        return;
+    };
+    let _ = write!(
+        svg,
+        "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" rx=\"6\" fill=\"#ffffff\" stroke=\"#b0bec5\" stroke-width=\"1\"/>",
+        tf.x, tf.y, tf.width, tf.height,
+    );
+    let layout = GaugeLayout::new(*shape, tf.width, tf.height);
+    let fraction = gauge_fraction(*value, *min, *max);
+    let color = threshold_color(*value, thresholds, theme).to_hex();
+    let track = GAUGE_TRACK_COLOR.to_hex();
+
+    match layout.dial {
+        Dial::Radial {
+            cx,
+            cy,
+            radius,
+            thickness,
+        } => {
+            let (cx, cy) = (tf.x + cx, tf.y + cy);
+            let arc = |to: f32, stroke: &str| {
+                let angle = Dial::angle(to);
+                format!(
+                    "<path d=\"M{},{cy} A{radius},{radius} 0 0 1 {},{}\" fill=\"none\" stroke=\"{stroke}\" stroke-width=\"{thickness}\"/>",
+                    cx - radius,
+                    cx + radius * angle.cos(),
+                    cy + radius * angle.sin(),
+                )
+            };
+            svg.push_str(&arc(1.0, &track));
+            if fraction > 0.0 {
+                svg.push_str(&arc(fraction, &color));
+            }
+        }
+        Dial::Linear {
+            x,
+            y,
+            width,
+            height,
+        } => {
+            let (x, y) = (tf.x + x, tf.y + y);
+            let _ = write!(
+                svg,
+                "<rect x=\"{x}\" y=\"{y}\" width=\"{width}\" height=\"{height}\" rx=\"{}\" fill=\"{track}\"/>",
+                height / 2.0,
+            );
+            let _ = write!(
+                svg,
+                "<rect x=\"{x}\" y=\"{y}\" width=\"{}\" height=\"{height}\" rx=\"{}\" fill=\"{color}\"/>",
+                width * fraction,
+                height / 2.0,
+            );
+        }
+    }
+
+    for threshold in thresholds {
+        let [(x1, y1), (x2, y2)] = layout
+            .dial
+            .tick(gauge_fraction(threshold.value, *min, *max));
+        let _ = write!(
+            svg,
+            "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"{}\" stroke-width=\"2\"/>",
+            tf.x + x1,
+            tf.y + y1,
+            tf.x + x2,
+            tf.y + y2,
+            threshold_color(threshold.value, thresholds, theme).to_hex(),
+        );
+    }
+
+    let _ = write!(
+        svg,
+        "<text x=\"{}\" y=\"{}\" font-size=\"{}\" fill=\"{color}\" font-family=\"sans-serif\" font-weight=\"bold\" text-anchor=\"middle\" dominant-baseline=\"central\">{}</text>",
+        tf.x + layout.value_x,
+        tf.y + layout.value_y,
+        layout.value_font_size,
+        escape_xml(&format.format(*value)),
+    );
+    if !label.is_empty() {
+        let (label_x, anchor) = match shape {
+            GaugeShape::Radial => (tf.x + tf.width / 2.0, "middle"),
+            GaugeShape::Linear => (tf.x + GaugeLayout::PADDING, "start"),
+        };
+        let _ = write!(
+            svg,
+            "<text x=\"{label_x}\" y=\"{}\" font-size=\"{}\" fill=\"#546e7a\" font-family=\"sans-serif\" text-anchor=\"{anchor}\" dominant-baseline=\"central\">{}</text>",
+            tf.y + layout.label_y,
+            layout.label_font_size,
+            escape_xml(label),
+        );
+    }
+}
+
+/// Render a KPI card: label, value, change since the previous value and
+/// sparkline.
+fn render_kpi_svg(
+    svg: &mut String,
+    tf: &canvas_core::Transform,
+    kind: &ElementKind,
+    theme: &Theme,
+) {
+    let ElementKind::Kpi {
+        label,
+        value,
+        previous,
+        sparkline,
+        sparkline_kind,
+        thresholds,
+        format,
+        higher_is_better,
+    } = kind
+    else {
+        return;
+    };
+    let _ = write!(
+        svg,
+        "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" rx=\"6\" fill=\"#ffffff\" stroke=\"#b0bec5\" stroke-width=\"1\"/>",
+        tf.x, tf.y, tf.width, tf.height,
+    );
+    let layout = KpiLayout::new(tf.width, tf.height, !sparkline.is_empty());
+    let color = threshold_color(*value, thresholds, theme).to_hex();
+    let text_x = tf.x + KpiLayout::PADDING;
+
+    let _ = write!(
+        svg,
+        "<text x=\"{text_x}\" y=\"{}\" font-size=\"{}\" fill=\"#546e7a\" font-family=\"sans-serif\" dominant-baseline=\"central\">{}</text>",
+        tf.y + layout.label_y,
+        layout.label_font_size,
+        escape_xml(label),
+    );
+    let _ = write!(
+        svg,
+        "<text x=\"{text_x}\" y=\"{}\" font-size=\"{}\" fill=\"{color}\" font-family=\"sans-serif\" font-weight=\"bold\" dominant-baseline=\"central\">{}</text>",
+        tf.y + layout.value_y,
+        layout.value_font_size,
+        escape_xml(&format.format(*value)),
+    );
+    if let Some(previous) = previous {
+        let delta = KpiDelta::new(*value, *previous, format, *higher_is_better);
+        let _ = write!(
+            svg,
+            "<text x=\"{text_x}\" y=\"{}\" font-size=\"{}\" fill=\"{}\" font-family=\"sans-serif\" dominant-baseline=\"central\">{}</text>",
+            tf.y + layout.delta_y,
+            layout.delta_font_size,
+            delta.color.to_hex(),
+            escape_xml(&delta.text),
+        );
+    }
+    if let Some([x, y, width, height]) = layout.sparkline {
+        render_sparkline_svg(
+            svg,
+            sparkline,
+            *sparkline_kind,
+            [tf.x + x, tf.y + y, width, height],
+            &color,
+        );
+    }
+}
+
+/// Line, label and caption color of a `Measurement` or `Region`.
+fn annotation_color(element: &Element, theme: &Theme) -> String {
+    element
+        .kind
+        .resolved_color(theme)
+        .unwrap_or(ElementKind::ANNOTATION_COLOR)
+        .to_hex()
+}
+
+/// Render a measurement line with its caps or ticks and a haloed label.
+fn render_measurement_svg(svg: &mut String, layout: &MeasurementLayout, color: &str) {
+    for [from, to] in
+        std::iter::once([layout.start, layout.end]).chain(layout.marks.iter().copied())
+    {
+        let _ = write!(
+            svg,
+            "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"{color}\" stroke-width=\"1.5\"/>",
+            from[0], from[1], to[0], to[1],
+        );
+    }
+    if layout.label.is_empty() {
+        return;
+    }
+    let [x, y] = layout.label_at;
+    let _ = write!(
+        svg,
+        "<text x=\"{x}\" y=\"{y}\" font-size=\"{ANNOTATION_FONT_SIZE}\" fill=\"{color}\" text-anchor=\"middle\" dominant-baseline=\"middle\" stroke=\"#ffffff\" stroke-width=\"3\" paint-order=\"stroke\" font-family=\"sans-serif\">{}</text>",
+        escape_xml(&layout.label),
+    );
+}
+
+/// Render a tinted, bordered region with its caption in a tab at the
+/// top-left corner.
+fn render_region_svg(
+    svg: &mut String,
+    tf: &canvas_core::Transform,
+    caption: Option<&str>,
+    color: &str,
+) {
+    let _ = write!(
+        svg,
+        "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{color}\" fill-opacity=\"{REGION_TINT}\" stroke=\"{color}\" stroke-width=\"2\"/>",
+        tf.x, tf.y, tf.width, tf.height,
+    );
+    let Some(caption) = caption.filter(|c| !c.trim().is_empty()) else {
+        return;
+    };
+    let [x, y, width, height] = caption_tab(tf, caption);
+    let _ = write!(
+        svg,
+        "<rect x=\"{x}\" y=\"{y}\" width=\"{width}\" height=\"{height}\" fill=\"{color}\"/>\
+         <text x=\"{}\" y=\"{}\" font-size=\"{ANNOTATION_FONT_SIZE}\" fill=\"#ffffff\" font-family=\"sans-serif\">{}</text>",
+        x + 6.0,
+        y + 14.0,
+        escape_xml(caption),
+    );
+}
+
+/// Render a barcode: a white background and its dark modules as one
+/// crisp-edged path.
+fn render_barcode_svg(svg: &mut String, layout: &BarcodeLayout, color: &str) {
+    let [x, y, width, height] = layout.background;
+    let _ = write!(
+        svg,
+        "<rect x=\"{x}\" y=\"{y}\" width=\"{width}\" height=\"{height}\" fill=\"#ffffff\"/>",
+    );
+    if layout.bars.is_empty() {
+        return;
+    }
+    let mut path = String::new();
+    for [bx, by, bw, bh] in &layout.bars {
+        let _ = write!(path, "M{bx} {by}h{bw}v{bh}h-{bw}z");
+    }
+    let _ = write!(
+        svg,
+        "<path d=\"{path}\" fill=\"{color}\" shape-rendering=\"crispEdges\"/>",
+    );
+}
+
+/// Render a shape's outline, filled and stroked by the element's style.
+///
+/// Gradient fills are drawn with their first stop.
+fn render_shape_svg(svg: &mut String, element: &Element, shape: &ShapeKind, theme: &Theme) {
+    let (tf, style) = (&element.transform, &element.style);
+    let fill = style
+        .fill
+        .as_ref()
+        .and_then(Fill::primary_color)
+        .map_or(ElementKind::SHAPE_COLOR, |color| {
+            theme.resolve_or(color, ElementKind::SHAPE_COLOR)
+        })
+        .to_hex();
+    let mut paint = format!("fill=\"{fill}\"");
+    if let Some(stroke) = style.stroke.as_ref().filter(|s| s.width > 0.0) {
+        let _ = write!(
+            paint,
+            " stroke=\"{}\" stroke-width=\"{}\" stroke-linejoin=\"round\"",
+            theme.resolve_or(&stroke.color, Color::BLACK).to_hex(),
+            stroke.width,
+        );
+    }
+    let opacity = style.effective_opacity();
+    if opacity < 1.0 {
+        let _ = write!(paint, " opacity=\"{opacity}\"");
+    }
+
+    let radius = style.effective_corner_radius(tf.width, tf.height);
+    let _ = match ShapeOutline::new(shape, tf, radius) {
+        ShapeOutline::Rect {
+            bounds: [x, y, width, height],
+            radius,
+        } => write!(
+            svg,
+            "<rect x=\"{x}\" y=\"{y}\" width=\"{width}\" height=\"{height}\" rx=\"{radius}\" {paint}/>",
+        ),
+        ShapeOutline::Ellipse {
+            center: [cx, cy],
+            radii: [rx, ry],
+        } => write!(
+            svg,
+            "<ellipse cx=\"{cx}\" cy=\"{cy}\" rx=\"{rx}\" ry=\"{ry}\" {paint}/>",
+        ),
+        ShapeOutline::Polygon(points) => {
+            let points: Vec<String> = points.iter().map(|[x, y]| format!("{x},{y}")).collect();
+            write!(svg, "<polygon points=\"{}\" {paint}/>", points.join(" "))
+        }
+    };
+}
+
+/// Render an ink stroke through canvas `points`: a round-capped polyline,
+/// or a dot for a single point.
+fn render_ink_svg(svg: &mut String, points: &[[f32; 2]], width: f32, color: &str) {
+    if let [[x, y]] = points {
+        let _ = write!(
+            svg,
+            "<circle cx=\"{x}\" cy=\"{y}\" r=\"{}\" fill=\"{color}\"/>",
+            width / 2.0
+        );
+        return;
+    }
+    let points: Vec<String> = points.iter().map(|[x, y]| format!("{x},{y}")).collect();
+    let _ = write!(
+        svg,
+        "<polyline points=\"{}\" fill=\"none\" stroke=\"{color}\" stroke-width=\"{width}\" \
+         stroke-linecap=\"round\" stroke-linejoin=\"round\"/>",
+        points.join(" ")
+    );
+}
+
+/// Render a sparkline into `area` (`[x, y, width, height]` in canvas
+/// coordinates): a polyline with a dot on the latest value, or bars.
+fn render_sparkline_svg(
+    svg: &mut String,
+    values: &[f32],
+    kind: SparklineKind,
+    area: [f32; 4],
+    color: &str,
+) {
+    match kind {
+        SparklineKind::Line => {
+            let points = sparkline_points(values, area);
+            let coords: Vec<String> = points.iter().map(|(px, py)| format!("{px},{py}")).collect();
+            let _ = write!(
+                svg,
+                "<polyline points=\"{}\" fill=\"none\" stroke=\"{color}\" stroke-width=\"1.5\" stroke-linejoin=\"round\"/>",
+                coords.join(" "),
+            );
+            if let Some((px, py)) = points.last() {
+                let _ = write!(
+                    svg,
+                    "<circle cx=\"{px}\" cy=\"{py}\" r=\"{SPARKLINE_DOT_RADIUS}\" fill=\"{color}\"/>",
+                );
+            }
+        }
+        SparklineKind::Bar => {
+            for [bx, by, width, height] in sparkline_bars(values, area) {
+                let _ = write!(
+                    svg,
+                    "<rect x=\"{bx}\" y=\"{by}\" width=\"{width}\" height=\"{height}\" fill=\"{color}\"/>",
+                );
+            }
+        }
+    }
+}
+
+/// Render a floor plan into SVG: background, outlines, a marker per seat or
+/// zone colored by occupancy, and the occupancy caption.
+fn render_floor_plan_svg(
+    svg: &mut String,
+    tf: &canvas_core::Transform,
+    background: Option<&str>,
+    (plan_width, plan_height): (f32, f32),
+    paths: &[PlanPath],
+    markers: &[FloorMarker],
+) {
+    let _ = write!(
+        svg,
+        "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"#f5f7f7\" stroke=\"#b0bec5\" stroke-width=\"1\"/>",
+        tf.x, tf.y, tf.width, tf.height,
+    );
+    let layout = FloorPlanLayout::new(plan_width, plan_height, tf.width, tf.height);
+    let (area_x, area_y) = (tf.x + layout.offset_x, tf.y + layout.offset_y);
+    let _ = write!(
+        svg,
+        "<rect x=\"{area_x}\" y=\"{area_y}\" width=\"{}\" height=\"{}\" fill=\"#ffffff\"/>",
+        layout.plan_width, layout.plan_height,
+    );
+    if let Some(src) = background {
+        let _ = write!(
+            svg,
+            "<image x=\"{area_x}\" y=\"{area_y}\" width=\"{}\" height=\"{}\" preserveAspectRatio=\"none\" href=\"{}\"/>",
+            layout.plan_width,
+            layout.plan_height,
+            escape_xml(src),
+        );
+    }
+
+    for path in paths {
+        let points: Vec<String> = path
+            .points
+            .iter()
+            .map(|[x, y]| {
+                let (x, y) = layout.to_local(*x, *y);
+                format!("{},{}", tf.x + x, tf.y + y)
+            })
+            .collect();
+        let tag = if path.closed { "polygon" } else { "polyline" };
+        let _ = write!(
+            svg,
+            "<{tag} points=\"{}\" fill=\"none\" stroke=\"#455a64\" stroke-width=\"2\"/>",
+            points.join(" "),
+        );
+    }
+
+    for marker in markers {
+        let rect = layout.marker_rect(marker);
+        let (x, y) = (tf.x + rect.x, tf.y + rect.y);
+        let color = marker.occupancy.color();
+        match marker.shape {
+            MarkerShape::Seat => {
+                let _ = write!(
+                    svg,
+                    "<ellipse cx=\"{}\" cy=\"{}\" rx=\"{}\" ry=\"{}\" fill=\"{color}\" stroke=\"#37474f\" stroke-width=\"1\"/>",
+                    x + rect.width / 2.0,
+                    y + rect.height / 2.0,
+                    rect.width / 2.0,
+                    rect.height / 2.0,
+                );
+            }
+            MarkerShape::Zone => {
+                let _ = write!(
+                    svg,
+                    "<rect x=\"{x}\" y=\"{y}\" width=\"{}\" height=\"{}\" rx=\"4\" fill=\"{color}\" fill-opacity=\"0.35\" stroke=\"{color}\" stroke-width=\"2\"/>",
+                    rect.width, rect.height,
+                );
+            }
+        }
+        let _ = write!(
+            svg,
+            "<text x=\"{}\" y=\"{}\" font-size=\"{}\" fill=\"#263238\" font-family=\"sans-serif\" text-anchor=\"middle\" dominant-baseline=\"central\">{}</text>",
+            x + rect.width / 2.0,
+            y + rect.height / 2.0,
+            rect.font_size(),
+            escape_xml(marker.display_label()),
+        );
+    }
+
+    let _ = write!(
+        svg,
+        "<text x=\"{}\" y=\"{}\" font-size=\"{}\" fill=\"#546e7a\" font-family=\"sans-serif\" text-anchor=\"middle\" dominant-baseline=\"central\">{}</text>",
+        tf.x + tf.width / 2.0,
+        tf.y + layout.caption_y + layout.caption_height / 2.0,
+        layout.caption_height * 0.6,
+        OccupancySummary::from_markers(markers).caption(),
+    );
+}
+
+/// Render a tree into SVG: elbow connectors, then a box per visible node
+/// with its label and detail, and a badge counting the nodes a collapsed
+/// node hides.
+fn render_tree_svg(
+    svg: &mut String,
+    tf: &canvas_core::Transform,
+    root: &TreeNode,
+    direction: TreeDirection,
+    theme: &Theme,
+) {
+    let layout = TreeLayout::new(root, direction, tf.width, tf.height);
+    for index in 0..layout.boxes.len() {
+        if let Some(points) = layout.connector(index) {
+            let coords: Vec<String> = points
+                .iter()
+                .map(|(x, y)| format!("{},{}", tf.x + x, tf.y + y))
+                .collect();
+            let _ = write!(
+                svg,
+                "<polyline points=\"{}\" fill=\"none\" stroke=\"#90a4ae\" stroke-width=\"{}\"/>",
+                coords.join(" "),
+                1.5 * layout.scale,
+            );
+        }
+    }
+
+    for (index, node_box) in layout.boxes.iter().enumerate() {
+        let node = node_box.node;
+        let fill = node
+            .color
+            .as_ref()
+            .map_or(DEFAULT_NODE_COLOR, |c| {
+                theme.resolve_or(c, DEFAULT_NODE_COLOR)
+            })
+            .to_hex();
+        let (x, y) = (tf.x + node_box.x, tf.y + node_box.y);
+        let _ = write!(
+            svg,
+            "<rect x=\"{x}\" y=\"{y}\" width=\"{}\" height=\"{}\" rx=\"{}\" fill=\"{fill}\" stroke=\"#607d8b\" stroke-width=\"1\"/>",
+            node_box.width,
+            node_box.height,
+            6.0 * layout.scale,
+        );
+        let center_x = x + node_box.width / 2.0;
+        let (label_y, detail_y) = node_box.text_y();
+        let _ = write!(
+            svg,
+            "<text x=\"{center_x}\" y=\"{}\" font-size=\"{}\" fill=\"#263238\" font-family=\"sans-serif\" font-weight=\"bold\" text-anchor=\"middle\" dominant-baseline=\"central\">{}</text>",
+            tf.y + label_y,
+            layout.label_font_size,
+            escape_xml(&node.label),
+        );
+        if let (Some(detail), Some(detail_y)) = (&node.detail, detail_y) {
+            let _ = write!(
+                svg,
+                "<text x=\"{center_x}\" y=\"{}\" font-size=\"{}\" fill=\"#546e7a\" font-family=\"sans-serif\" text-anchor=\"middle\" dominant-baseline=\"central\">{}</text>",
+                tf.y + detail_y,
+                layout.detail_font_size,
+                escape_xml(detail),
+            );
+        }
+        if let Some((badge_x, badge_y, radius)) = layout.badge(index) {
+            let (badge_x, badge_y) = (tf.x + badge_x, tf.y + badge_y);
+            let _ = write!(
+                svg,
+                "<circle cx=\"{badge_x}\" cy=\"{badge_y}\" r=\"{radius}\" fill=\"#546e7a\"/><text x=\"{badge_x}\" y=\"{badge_y}\" font-size=\"{}\" fill=\"#ffffff\" font-family=\"sans-serif\" text-anchor=\"middle\" dominant-baseline=\"central\">+{}</text>",
+                radius,
+                node_box.hidden,
+            );
+        }
+    }
+}
+
+/// Render dice, a wheel or a shuffled list above a caption with the latest
+/// result into SVG.
+#[allow(clippy::cast_precision_loss)]
+fn render_randomizer_svg(
+    svg: &mut String,
+    tf: &canvas_core::Transform,
+    source: &RandomSource,
+    outcome: Option<&RandomOutcome>,
+) {
+    let _ = write!(
+        svg,
+        "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" rx=\"6\" fill=\"#fafafa\" stroke=\"#b0bec5\" stroke-width=\"1\"/>",
+        tf.x, tf.y, tf.width, tf.height,
+    );
+    let layout = RandomizerLayout::new(tf.width, tf.height);
+    let values = outcome.map(|o| o.values.as_slice());
+
+    match source {
+        RandomSource::Dice { count, .. } => {
+            for (i, (x, y, size)) in layout.dice(*count).into_iter().enumerate() {
+                let (x, y) = (tf.x + x, tf.y + y);
+                let face = values
+                    .and_then(|v| v.get(i))
+                    .map_or_else(|| "?".to_string(), ToString::to_string);
+                let _ = write!(
+                    svg,
+                    "<rect x=\"{x}\" y=\"{y}\" width=\"{size}\" height=\"{size}\" rx=\"{}\" fill=\"#ffffff\" stroke=\"#37474f\" stroke-width=\"2\"/>",
+                    size * 0.15,
+                );
+                let _ = write!(
+                    svg,
+                    "<text x=\"{}\" y=\"{}\" font-size=\"{}\" fill=\"#263238\" font-family=\"sans-serif\" text-anchor=\"middle\" dominant-baseline=\"central\">{face}</text>",
+                    x + size / 2.0,
+                    y + size / 2.0,
+                    size * 0.5,
+                );
+            }
+        }
+        RandomSource::Wheel { segments } => {
+            let (cx, cy, r) = layout.wheel();
+            let (cx, cy) = (tf.x + cx, tf.y + cy);
+            let selected = values.and_then(|v| v.first().copied());
+            for (i, label) in segments.iter().enumerate() {
+                let (start, end) = RandomizerLayout::segment_arc(i, segments.len(), selected);
+                let large_arc = u8::from(end - start > std::f32::consts::PI);
+                let _ = write!(
+                    svg,
+                    "<path d=\"M{cx},{cy} L{},{} A{r},{r} 0 {large_arc} 1 {},{} Z\" fill=\"{}\" stroke=\"#ffffff\" stroke-width=\"1\"/>",
+                    cx + r * start.cos(),
+                    cy + r * start.sin(),
+                    cx + r * end.cos(),
+                    cy + r * end.sin(),
+                    WHEEL_COLORS[i % WHEEL_COLORS.len()],
+                );
+                let middle = f32::midpoint(start, end);
+                let _ = write!(
+                    svg,
+                    "<text x=\"{}\" y=\"{}\" font-size=\"{}\" fill=\"#263238\" font-family=\"sans-serif\" text-anchor=\"middle\" dominant-baseline=\"central\">{}</text>",
+                    cx + 0.62 * r * middle.cos(),
+                    cy + 0.62 * r * middle.sin(),
+                    (r * 0.16).clamp(8.0, 16.0),
+                    escape_xml(label),
+                );
+            }
+            // Pointer at the top
+            let tip = r * 0.12;
+            let _ = write!(
+                svg,
+                "<polygon points=\"{},{} {},{} {cx},{}\" fill=\"#37474f\"/>",
+                cx - tip,
+                cy - r - tip,
+                cx + tip,
+                cy - r - tip,
+                cy - r + tip,
+            );
+        }
+        RandomSource::Shuffle { items } => {
+            let order = source.list_order(outcome);
+            for (n, ((y, height), i)) in layout.rows(order.len()).into_iter().zip(order).enumerate()
+            {
+                let _ = write!(
+                    svg,
+                    "<text x=\"{}\" y=\"{}\" font-size=\"{}\" fill=\"#263238\" font-family=\"sans-serif\" dominant-baseline=\"central\">{}. {}</text>",
+                    tf.x + layout.body_x,
+                    tf.y + y + height / 2.0,
+                    (height * 0.6).clamp(8.0, 16.0),
+                    n + 1,
+                    escape_xml(&items[i]),
+                );
+            }
+        }
+    }
+
+    let _ = write!(
+        svg,
+        "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"#37474f\"/>",
+        tf.x,
+        tf.y + layout.caption_y,
+        tf.width,
+        layout.caption_height,
+    );
+    let _ = write!(
+        svg,
+        "<text x=\"{}\" y=\"{}\" font-size=\"{}\" fill=\"#ffffff\" font-family=\"sans-serif\" text-anchor=\"middle\" dominant-baseline=\"central\">{}</text>",
+        tf.x + tf.width / 2.0,
+        tf.y + layout.caption_y + layout.caption_height / 2.0,
+        layout.caption_height * 0.55,
+        escape_xml(&source.caption(outcome)),
+    );
+}
+
+/// Render a math formula into SVG, scaled down to fit its transform.
+///
+/// Formulas that cannot be typeset are shown as their TeX source in a
+/// monospace font.
+fn render_math_svg(
+    svg: &mut String,
+    tf: &canvas_core::Transform,
+    tex: &str,
+    font_size: f32,
+    color: &str,
+) {
+    let escaped_color = escape_xml(color);
+    let Ok(layout) = MathLayout::new(tex, font_size) else {
+        let escaped = escape_xml(tex);
+        let text_y = tf.y + font_size;
+        let _ = write!(
+            svg,
+            "<text x=\"{}\" y=\"{text_y}\" font-size=\"{font_size}\" fill=\"{escaped_color}\" font-family=\"monospace\">{escaped}</text>",
+            tf.x,
+        );
+        return;
+    };
+
+    // Left-aligned and vertically centered in the element
+    let scale = layout.fit_scale(tf.width, tf.height);
+    let oy = tf.y + (tf.height - layout.height * scale) / 2.0;
+    let _ = write!(
+        svg,
+        "<g transform=\"translate({},{oy}) scale({scale})\" fill=\"{escaped_color}\">",
+        tf.x,
+    );
+    for run in &layout.runs {
+        let escaped = escape_xml(&run.text);
+        let style = if run.italic {
+            " font-style=\"italic\""
+        } else {
+            ""
+        };
+        let _ = write!(
+            svg,
+            "<text x=\"{}\" y=\"{}\" font-size=\"{}\"{style} font-family=\"serif\">{escaped}</text>",
+            run.x, run.y, run.font_size,
+        );
+    }
+    for rule in &layout.rules {
+        let _ = write!(
+            svg,
+            "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\"/>",
+            rule.x, rule.y, rule.width, rule.thickness,
+        );
+    }
+    svg.push_str("</g>");
+}
+
+/// Render a code block into SVG: a dark panel with one `<text>` per line and
+/// a colored `<tspan>` per token. Lines that do not fit are dropped.
+#[allow(clippy::cast_precision_loss)] // Line counts are small
+fn render_code_svg(
+    svg: &mut String,
+    tf: &canvas_core::Transform,
+    source: &str,
+    spans: &[HighlightSpan],
+) {
+    let _ = write!(
+        svg,
+        "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" rx=\"4\" fill=\"{CODE_BACKGROUND}\"/>",
+        tf.x, tf.y, tf.width, tf.height,
+    );
+    let x = tf.x + CODE_PADDING;
+    let bottom = tf.y + tf.height - CODE_PADDING;
+    for (row, line) in highlight::lines(source, spans).iter().enumerate() {
+        let top = tf.y + CODE_PADDING + row as f32 * CODE_LINE_HEIGHT;
+        if top + CODE_LINE_HEIGHT > bottom {
+            break;
+        }
+        // Roughly centers the glyphs in the line
+        let baseline = top + f32::midpoint(CODE_LINE_HEIGHT, CODE_FONT_SIZE) - 2.0;
+        let _ = write!(
+            svg,
+            "<text x=\"{x}\" y=\"{baseline}\" font-size=\"{CODE_FONT_SIZE}\" font-family=\"monospace\" xml:space=\"preserve\">",
+        );
+        for (kind, text) in line {
+            let escaped = escape_xml(text);
+            let _ = write!(svg, "<tspan fill=\"{}\">{escaped}</tspan>", kind.color());
+        }
+        svg.push_str("</text>");
+    }
+}
+
+/// Render a log view: a title bar and the rows that fit, colored by their
+/// ANSI escape codes.
+#[allow(clippy::cast_precision_loss)]
+fn render_log_svg(
+    svg: &mut String,
+    tf: &canvas_core::Transform,
+    title: &str,
+    lines: &[String],
+    follow: bool,
+) {
+    let _ = write!(
+        svg,
+        "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" rx=\"4\" fill=\"{LOG_BACKGROUND}\"/>",
+        tf.x, tf.y, tf.width, tf.height,
+    );
+    let x = tf.x + CODE_PADDING;
+    // Roughly centers the glyphs in the line
+    let baseline = |top: f32| top + f32::midpoint(CODE_LINE_HEIGHT, CODE_FONT_SIZE) - 2.0;
+    if !title.is_empty() {
+        let escaped = escape_xml(title);
+        let _ = write!(
+            svg,
+            "<text x=\"{x}\" y=\"{}\" font-size=\"{CODE_FONT_SIZE}\" font-family=\"monospace\" font-weight=\"bold\" fill=\"{LOG_FOREGROUND}\">{escaped}</text>",
+            baseline(tf.y + CODE_PADDING),
+        );
+    }
+    let char_width = CODE_FONT_SIZE * CODE_CHAR_WIDTH;
+    let body_top = tf.y + log_view::body_top(title);
+    let range = log_view::visible_range(title, lines.len(), tf.height, follow);
+    for (row, line) in lines[range].iter().enumerate() {
+        let top = body_top + row as f32 * CODE_LINE_HEIGHT;
+        let spans = log_view::spans(line);
+        let mut column = 0;
+        for span in &spans {
+            let width = span.text.chars().count();
+            if let Some(background) = span.style.background() {
+                let _ = write!(
+                    svg,
+                    "<rect x=\"{}\" y=\"{top}\" width=\"{}\" height=\"{CODE_LINE_HEIGHT}\" fill=\"{}\"/>",
+                    x + column as f32 * char_width,
+                    width as f32 * char_width,
+                    background.to_hex(),
+                );
+            }
+            column += width;
+        }
+        let _ = write!(
+            svg,
+            "<text x=\"{x}\" y=\"{}\" font-size=\"{CODE_FONT_SIZE}\" font-family=\"monospace\" xml:space=\"preserve\">",
+            baseline(top),
+        );
+        for span in &spans {
+            let escaped = escape_xml(&span.text);
+            let _ = write!(svg, "<tspan fill=\"{}\"", span.style.foreground().to_hex());
+            if span.style.bold {
+                svg.push_str(" font-weight=\"bold\"");
+            }
+            if span.style.italic {
+                svg.push_str(" font-style=\"italic\"");
+            }
+            if span.style.underline {
+                svg.push_str(" text-decoration=\"underline\"");
+            }
+            let _ = write!(svg, ">{escaped}</tspan>");
         }
+        svg.push_str("</text>");
     }
 }
 
@@ -341,10 +2167,74 @@ fn render_chart_svg(
         "<rect x=\"{px}\" y=\"{py}\" width=\"{width}\" height=\"{height}\" fill=\"#fafafa\" stroke=\"#ddd\" stroke-width=\"1\"/>",
     );
 
+    // The simple drawings only know the default look
+    let styled = data.get("style").is_some();
     match chart_type {
-        "bar" => render_bar_chart_svg(svg, px, py, width, height, data),
-        "pie" => render_pie_chart_svg(svg, px, py, width, height, data),
-        _ => {} // For line, scatter, etc. show background box only
+        "bar" if !styled => render_bar_chart_svg(svg, px, py, width, height, data),
+        "pie" if !styled => render_pie_chart_svg(svg, px, py, width, height, data),
+        _ => render_chart_geometry_svg(svg, [px, py, width, height], chart_type, data),
+    }
+}
+
+/// Render a chart from its tessellated shapes and labels; unknown chart
+/// types keep the background box only.
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn render_chart_geometry_svg(
+    svg: &mut String,
+    rect: [f32; 4],
+    chart_type: &str,
+    data: &serde_json::Value,
+) {
+    let size = |extent: f32| extent.max(0.0) as u32;
+    let Ok(config) = parse_chart_config(chart_type, data, size(rect[2]), size(rect[3])) else {
+        return;
+    };
+    let geometry = ChartGeometry::new(&config, rect);
+    let points = |points: &[[f32; 2]]| {
+        points
+            .iter()
+            .map(|[x, y]| format!("{x},{y}"))
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+    for path in &geometry.paths {
+        match path {
+            ChartPath::Fill {
+                points: corners,
+                color,
+            } => {
+                let _ = write!(
+                    svg,
+                    "<polygon points=\"{}\" fill=\"{}\" fill-opacity=\"{}\"/>",
+                    points(corners),
+                    Color::rgb(color.r, color.g, color.b).to_hex(),
+                    color.alpha(),
+                );
+            }
+            ChartPath::Stroke {
+                points: line,
+                width,
+                color,
+            } => {
+                let _ = write!(
+                    svg,
+                    "<polyline points=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"{width}\" stroke-linecap=\"square\"/>",
+                    points(line),
+                    color.to_hex(),
+                );
+            }
+        }
+    }
+    for label in &geometry.labels {
+        let _ = write!(
+            svg,
+            "<text x=\"{}\" y=\"{}\" font-size=\"{}\" fill=\"{}\" font-family=\"sans-serif\" dominant-baseline=\"hanging\">{}</text>",
+            label.rect[0],
+            label.rect[1],
+            label.font_size,
+            label.color.to_hex(),
+            escape_xml(&label.text),
+        );
     }
 }
 
@@ -455,7 +2345,7 @@ fn render_pie_chart_svg(
 }
 
 /// Escape special XML characters.
-fn escape_xml(input: &str) -> String {
+pub(crate) fn escape_xml(input: &str) -> String {
     input
         .replace('&', "&amp;")
         .replace('<', "&lt;")
@@ -468,12 +2358,13 @@ fn escape_xml(input: &str) -> String {
 mod tests {
     use super::*;
     use canvas_core::element::{Element, ElementKind, Transform};
+    use canvas_core::Color;
 
     fn text_element(content: &str, ex: f32, ey: f32) -> Element {
         Element::new(ElementKind::Text {
             content: content.to_string(),
             font_size: 16.0,
-            color: "#000000".to_string(),
+            color: Color::BLACK.into(),
         })
         .with_transform(Transform {
             x: ex,
@@ -482,6 +2373,7 @@ mod tests {
             height: 30.0,
             rotation: 0.0,
             z_index: 0,
+            auto_size: false,
         })
     }
 
@@ -496,6 +2388,167 @@ mod tests {
         assert!(svg.contains("height=\"600\""));
     }
 
+    #[test]
+    fn test_export_element_crops_to_bounds() {
+        let mut scene = Scene::new(800.0, 600.0);
+        scene.add_element(text_element("Other", 10.0, 10.0));
+        let id = scene.add_element(text_element("Target", 300.0, 200.0));
+        let exporter = SceneExporter::with_defaults();
+
+        let svg = exporter.render_element_to_svg(&scene, id).expect("svg");
+        assert!(svg.contains("viewBox=\"292 192 216 46\""));
+        assert!(svg.contains("Target"));
+        assert!(!svg.contains("Other"));
+
+        // Width alone keeps the aspect ratio
+        let exporter = SceneExporter::new(ExportConfig {
+            width: Some(432),
+            ..ExportConfig::default()
+        });
+        let png = exporter
+            .export_element(&scene, id, ExportFormat::Png)
+            .expect("png");
+        let width = u32::from_be_bytes([png[16], png[17], png[18], png[19]]);
+        let height = u32::from_be_bytes([png[20], png[21], png[22], png[23]]);
+        assert_eq!((width, height), (432, 92));
+
+        assert!(exporter
+            .export_element(&scene, ElementId::new(), ExportFormat::Png)
+            .is_err());
+        let huge = SceneExporter::new(ExportConfig {
+            scale: 100.0,
+            ..ExportConfig::default()
+        });
+        assert!(huge.export_element(&scene, id, ExportFormat::Png).is_err());
+    }
+
+    #[test]
+    fn test_redacted_exports_hide_sensitive_subtrees() {
+        let mut scene = Scene::new(800.0, 600.0);
+        scene.add_element(text_element("Roadmap", 10.0, 10.0));
+        let secret = scene.add_element(text_element("Acquiring Initech", 10.0, 100.0));
+        let group = scene.add_element(
+            Element::new(ElementKind::Group {
+                children: vec![secret],
+                clip: false,
+                opacity: 1.0,
+            })
+            .with_sensitive(true),
+        );
+        scene.get_element_mut(secret).expect("secret").parent = Some(group);
+
+        let plain = SceneExporter::with_defaults()
+            .render_to_svg(&scene)
+            .expect("svg");
+        assert!(plain.contains("Initech"));
+
+        let placeholder = SceneExporter::new(ExportConfig {
+            redaction: Some(Redaction::Placeholder),
+            ..ExportConfig::default()
+        });
+        let svg = placeholder.render_to_svg(&scene).expect("svg");
+        assert!(svg.contains("Roadmap"));
+        assert!(!svg.contains("Initech"));
+        assert!(svg.contains("#9e9e9e"));
+
+        let blur = SceneExporter::new(ExportConfig {
+            redaction: Some(Redaction::Blur),
+            ..ExportConfig::default()
+        });
+        let svg = blur.render_to_svg(&scene).expect("svg");
+        assert!(!svg.contains("Initech"));
+        assert_eq!(svg.matches("data:image/png;base64,").count(), 1);
+        // The element export goes through the same redaction
+        let svg = blur.render_element_to_svg(&scene, group).expect("svg");
+        assert!(!svg.contains("Initech"));
+        assert!(blur.export(&scene, ExportFormat::Png).is_ok());
+    }
+
+    #[test]
+    fn test_flatten_image_crops_to_image_with_markup() {
+        let mut scene = Scene::new(800.0, 600.0);
+        let image = scene.add_element(
+            Element::new(ElementKind::Image {
+                src: "shot.png".to_string(),
+                format: canvas_core::ImageFormat::Png,
+                annotations: Vec::new(),
+                extracted_text: None,
+            })
+            .with_transform(Transform {
+                x: 100.0,
+                y: 50.0,
+                width: 400.0,
+                height: 300.0,
+                ..Transform::default()
+            }),
+        );
+        scene
+            .pin_markup(image, text_element("Look here", 120.0, 60.0))
+            .expect("pin");
+        scene.add_element(text_element("Elsewhere", 600.0, 500.0));
+        let exporter = SceneExporter::with_defaults();
+
+        let svg = exporter
+            .flatten_image(&scene, image, ExportFormat::Svg)
+            .expect("svg");
+        let svg = String::from_utf8(svg).expect("utf8");
+        assert!(svg.contains("viewBox=\"100 50 400 300\""));
+        assert!(svg.contains("href=\"shot.png\""));
+        assert!(svg.contains("Look here"));
+        assert!(!svg.contains("Elsewhere"));
+
+        let text = scene
+            .elements()
+            .find(|e| e.parent.is_none() && e.id != image);
+        assert!(exporter
+            .flatten_image(&scene, text.expect("text").id, ExportFormat::Svg)
+            .is_err());
+    }
+
+    #[test]
+    fn test_svg_export_draws_screen_anchored_on_top() {
+        let mut scene = Scene::new(800.0, 600.0);
+        let mut card = text_element("Card", 10.0, 20.0);
+        card.transform.z_index = 5;
+        scene.add_element(card);
+        scene.add_element(text_element("Badge", 10.0, 20.0).with_screen_anchored(true));
+
+        let svg = SceneExporter::with_defaults()
+            .render_to_svg(&scene)
+            .expect("svg export");
+        let card_at = svg.find("Card").expect("card");
+        let badge_at = svg.find("Badge").expect("badge");
+        assert!(badge_at > card_at);
+    }
+
+    #[test]
+    fn test_exports_stamp_branding() {
+        use canvas_core::Branding;
+
+        let mut scene = Scene::new(800.0, 600.0);
+        let id = scene.add_element(text_element("Card", 1000.0, 1000.0).with_screen_anchored(true));
+        scene
+            .set_branding(Branding::default().with_watermark("CONFIDENTIAL"))
+            .expect("branding");
+
+        let exporter = SceneExporter::with_defaults();
+        let svg = exporter.render_to_svg(&scene).expect("svg export");
+        let card_at = svg.find("Card").expect("card");
+        let stamp_at = svg.find("CONFIDENTIAL").expect("watermark");
+        assert!(stamp_at > card_at);
+        assert!(svg.contains("<g opacity=\"0.5\">"));
+
+        // Element exports are stamped too, scaled to their output size
+        let svg = exporter
+            .render_element_to_svg(&scene, id)
+            .expect("element export");
+        assert!(svg.contains("CONFIDENTIAL"));
+        assert!(svg.contains("translate(992 992)"));
+
+        let png = exporter.render_to_png(&scene).expect("png export");
+        assert_eq!(&png[0..4], &[0x89, 0x50, 0x4E, 0x47]);
+    }
+
     #[test]
     fn test_svg_export_with_text() {
         let mut scene = Scene::new(800.0, 600.0);
@@ -507,6 +2560,131 @@ mod tests {
         assert!(svg.contains("font-size=\"16\""));
     }
 
+    #[test]
+    fn test_svg_export_skips_hidden_and_fades() {
+        let mut scene = Scene::new(800.0, 600.0);
+        scene.add_element(text_element("Hidden", 10.0, 20.0).with_visible(false));
+        scene.add_element(text_element("Faded", 10.0, 60.0).with_opacity(0.5));
+
+        let exporter = SceneExporter::with_defaults();
+        let svg = exporter.render_to_svg(&scene).expect("svg export");
+        assert!(!svg.contains("Hidden"));
+        assert!(svg.contains("<g opacity=\"0.5\"><text"));
+    }
+
+    #[test]
+    fn test_svg_export_callout_with_tail() {
+        let mut scene = Scene::new(800.0, 600.0);
+        let callout = Element::new(ElementKind::Callout {
+            content: "Spike <here>".to_string(),
+            color: Color::WHITE.into(),
+            author: Some("agent".to_string()),
+            target: Some(canvas_core::CalloutTarget::Point { x: 400.0, y: 35.0 }),
+        })
+        .with_transform(text_element("", 10.0, 20.0).transform);
+        scene.add_element(callout);
+
+        let exporter = SceneExporter::with_defaults();
+        let svg = exporter.render_to_svg(&scene).expect("svg export");
+        assert!(svg.contains("<polygon"));
+        assert!(svg.contains("400,35"));
+        assert!(svg.contains("Spike &lt;here&gt;"));
+        assert!(svg.contains("agent"));
+    }
+
+    #[test]
+    fn test_svg_export_math() {
+        let mut scene = Scene::new(800.0, 600.0);
+        for tex in [r"\frac{\pi}{2}", r"\unsupported{x}"] {
+            scene.add_element(
+                Element::new(ElementKind::Math {
+                    tex: tex.to_string(),
+                    font_size: 24.0,
+                    color: Color::from_rgb(0x11_2233).into(),
+                })
+                .with_transform(text_element("", 10.0, 20.0).transform),
+            );
+        }
+
+        let exporter = SceneExporter::with_defaults();
+        let svg = exporter.render_to_svg(&scene).expect("svg export");
+        assert!(svg.contains("font-style=\"italic\" font-family=\"serif\">π</text>"));
+        assert!(svg.contains("fill=\"#112233\""));
+        // Fraction bar
+        assert!(svg.contains("<rect x=\"0\""));
+        // Unsupported formulas fall back to their source
+        assert!(svg.contains("font-family=\"monospace\">\\unsupported{x}</text>"));
+    }
+
+    #[test]
+    fn test_svg_export_code() {
+        let mut scene = Scene::new(800.0, 600.0);
+        let source = "let a = 1 < 2;\nlet b = a;\nhidden";
+        scene.add_element(
+            Element::new(ElementKind::Code {
+                language: "rust".to_string(),
+                source: source.to_string(),
+                spans: vec![canvas_core::HighlightSpan {
+                    start: 0,
+                    end: 3,
+                    kind: canvas_core::TokenKind::Keyword,
+                }],
+            })
+            .with_transform(Transform {
+                x: 0.0,
+                y: 0.0,
+                width: 300.0,
+                height: 56.0,
+                rotation: 0.0,
+                z_index: 0,
+                auto_size: false,
+            }),
+        );
+
+        let exporter = SceneExporter::with_defaults();
+        let svg = exporter.render_to_svg(&scene).expect("svg export");
+        let keyword = canvas_core::TokenKind::Keyword.color();
+        assert!(svg.contains(&format!("<tspan fill=\"{keyword}\">let</tspan>")));
+        assert!(svg.contains(" a = 1 &lt; 2;</tspan>"));
+        assert!(svg.contains("let b = a;"));
+        // The third line does not fit in the element
+        assert!(!svg.contains("hidden"));
+    }
+
+    #[test]
+    fn test_svg_export_log_view_shows_colored_tail() {
+        let mut scene = Scene::new(800.0, 600.0);
+        scene.add_element(
+            Element::new(ElementKind::LogView {
+                title: String::new(),
+                lines: vec![
+                    "scrolled away".to_string(),
+                    "Compiling canvas".to_string(),
+                    "\u{1b}[31merror\u{1b}[0m: a < b".to_string(),
+                ],
+                max_lines: 100,
+                follow: true,
+            })
+            .with_transform(Transform {
+                x: 0.0,
+                y: 0.0,
+                width: 300.0,
+                height: 56.0,
+                rotation: 0.0,
+                z_index: 0,
+                auto_size: false,
+            }),
+        );
+
+        let exporter = SceneExporter::with_defaults();
+        let svg = exporter.render_to_svg(&scene).expect("svg export");
+        assert!(svg.contains("Compiling canvas"));
+        assert!(svg.contains("<tspan fill=\"#cd3131\">error</tspan>"));
+        assert!(svg.contains(": a &lt; b</tspan>"));
+        // Following keeps the newest rows in view
+        assert!(!svg.contains("scrolled away"));
+    }
+
     #[test]
     fn test_png_export_produces_valid_bytes() {
         let mut scene = Scene::new(100.0, 100.0);
@@ -547,6 +2725,34 @@ mod tests {
         assert_eq!(&pdf[0..5], b"%PDF-");
     }
 
+    #[test]
+    fn test_print_export_paginates() {
+        let mut scene = Scene::new(800.0, 600.0);
+        scene.add_element(text_element("Agenda", 0.0, 0.0));
+        scene.add_element(text_element("Actions", 0.0, 2000.0));
+
+        let exporter = SceneExporter::with_defaults();
+        let print = PrintConfig {
+            title: Some("Standup & retro".to_string()),
+            timestamp: Some(0),
+            dpi: 72.0,
+            ..PrintConfig::default()
+        };
+        let layout = PrintLayout::compute(&scene, &print).expect("layout");
+        assert_eq!(layout.pages.len(), 2);
+
+        let page =
+            exporter.print_page_svg(&scene, &print, layout.scale, &layout.pages[0].view, 1, 2);
+        assert!(page.contains("Standup &amp; retro"));
+        assert!(page.contains("1970-01-01 00:00 UTC"));
+        assert!(page.contains("Page 1 of 2"));
+        assert!(page.contains("Agenda"));
+        assert!(!page.contains("Actions"));
+
+        let pdf = exporter.export_print(&scene, &print).expect("print export");
+        assert_eq!(&pdf[0..5], b"%PDF-");
+    }
+
     #[test]
     fn test_export_dispatch() {
         let mut scene = Scene::new(100.0, 100.0);
@@ -591,6 +2797,7 @@ mod tests {
                 "labels": ["A", "B", "C"],
                 "values": [10, 25, 15]
             }),
+            binding: None,
         })
         .with_transform(Transform {
             x: 10.0,
@@ -599,6 +2806,7 @@ mod tests {
             height: 280.0,
             rotation: 0.0,
             z_index: 0,
+            auto_size: false,
         });
         scene.add_element(chart);
 
@@ -617,6 +2825,7 @@ mod tests {
                 "labels": ["A", "B"],
                 "values": [60, 40]
             }),
+            binding: None,
         })
         .with_transform(Transform {
             x: 10.0,
@@ -625,6 +2834,7 @@ mod tests {
             height: 280.0,
             rotation: 0.0,
             z_index: 0,
+            auto_size: false,
         });
         scene.add_element(chart);
 
@@ -633,6 +2843,30 @@ mod tests {
         assert!(svg.contains("<path"));
     }
 
+    #[test]
+    fn test_other_chart_types_export_their_geometry() {
+        let mut scene = Scene::new(400.0, 300.0);
+        scene.add_element(
+            Element::new(ElementKind::Chart {
+                chart_type: "heatmap".to_string(),
+                data: serde_json::json!({ "values": [[1, 2], [3, 4]], "y_labels": ["A&B"] }),
+                binding: None,
+            })
+            .with_transform(Transform {
+                width: 380.0,
+                height: 280.0,
+                ..Transform::default()
+            }),
+        );
+
+        let svg = SceneExporter::with_defaults()
+            .render_to_svg(&scene)
+            .expect("svg");
+        // Background, four cells and the color scale
+        assert!(svg.matches("<polygon").count() >= 5);
+        assert!(svg.contains(">A&amp;B</text>"));
+    }
+
     #[test]
     fn test_xml_escaping() {
         let mut scene = Scene::new(200.0, 100.0);
@@ -660,6 +2894,7 @@ mod tests {
             height: 180.0,
             rotation: 0.0,
             z_index: 0,
+            auto_size: false,
         });
         scene.add_element(video);
 
@@ -669,6 +2904,576 @@ mod tests {
         assert!(svg.contains("#e0e0e0"));
     }
 
+    #[test]
+    fn test_audio_waveform() {
+        let mut scene = Scene::new(400.0, 100.0);
+        scene.add_element(
+            Element::new(ElementKind::Audio {
+                stream_id: "voice-note-1".to_string(),
+                waveform: vec![0.2, 1.0, 0.5],
+                state: PlaybackState::Playing,
+            })
+            .with_transform(Transform {
+                x: 0.0,
+                y: 0.0,
+                width: 300.0,
+                height: 60.0,
+                rotation: 0.0,
+                z_index: 0,
+                auto_size: false,
+            }),
+        );
+
+        let svg = SceneExporter::with_defaults()
+            .render_to_svg(&scene)
+            .expect("svg export");
+        assert!(svg.contains("<g fill=\"#80cbc4\">"));
+        // The loudest bar spans the full inner height
+        assert!(svg.contains("height=\"42\""));
+        // Playing shows pause bars rather than a play triangle
+        assert!(!svg.contains("<polygon"));
+    }
+
+    #[test]
+    fn test_calendar_grid() {
+        let mut scene = Scene::new(800.0, 600.0);
+        scene.add_element(
+            Element::new(ElementKind::Calendar {
+                view: CalendarView::Month,
+                date: "2026-10-01".to_string(),
+                events: vec![canvas_core::CalendarEvent {
+                    date: "2026-10-14".to_string(),
+                    title: "Launch <beta>".to_string(),
+                    time: Some("09:30".to_string()),
+                    color: Some(Color::from_rgb(0xe5_3935).into()),
+                }],
+                selected: Some("2026-10-14".to_string()),
+            })
+            .with_transform(Transform {
+                x: 0.0,
+                y: 0.0,
+                width: 700.0,
+                height: 500.0,
+                rotation: 0.0,
+                z_index: 0,
+                auto_size: false,
+            }),
+        );
+
+        let svg = SceneExporter::with_defaults()
+            .render_to_svg(&scene)
+            .expect("svg export");
+        assert!(svg.contains("October 2026"));
+        assert!(svg.contains(">Mon<"));
+        assert!(svg.contains("09:30 Launch &lt;beta&gt;"));
+        assert!(svg.contains("fill=\"#e53935\""));
+        assert_eq!(svg.matches("fill=\"#bbdefb\"").count(), 1);
+        // Leading September days are dimmed
+        assert!(svg.contains("fill=\"#b0bec5\" font-family=\"sans-serif\">28<"));
+    }
+
+    #[test]
+    fn test_floor_plan_markers_and_caption() {
+        use canvas_core::floor_plan::Occupancy;
+
+        let mut seat = FloorMarker::new("A1", MarkerShape::Seat, 100.0, 50.0);
+        seat.label = "<A1>".to_string();
+        seat.occupancy = Occupancy::Occupied;
+        let stage = FloorMarker {
+            width: Some(200.0),
+            height: Some(100.0),
+            occupancy: Occupancy::Reserved,
+            ..FloorMarker::new("stage", MarkerShape::Zone, 300.0, 100.0)
+        };
+        let mut scene = Scene::new(800.0, 600.0);
+        scene.add_element(
+            Element::new(ElementKind::FloorPlan {
+                background: Some("plan.png".to_string()),
+                plan_width: 400.0,
+                plan_height: 200.0,
+                paths: vec![PlanPath {
+                    points: vec![[0.0, 0.0], [400.0, 0.0], [400.0, 200.0]],
+                    closed: true,
+                }],
+                markers: vec![seat, stage],
+                locked: false,
+            })
+            .with_transform(Transform {
+                x: 0.0,
+                y: 0.0,
+                width: 412.0,
+                height: 260.0,
+                rotation: 0.0,
+                z_index: 0,
+                auto_size: false,
+            }),
+        );
+
+        let svg = SceneExporter::with_defaults()
+            .render_to_svg(&scene)
+            .expect("svg export");
+        // A 400 × 200 plan at scale 1, centered above the 24px caption
+        assert!(svg.contains("<image x=\"6\" y=\"18\" width=\"400\" height=\"200\""));
+        assert!(svg.contains("<polygon points=\"6,18 406,18 406,218\""));
+        assert!(svg.contains("<ellipse cx=\"106\" cy=\"68\" rx=\"12\" ry=\"12\" fill=\"#e57373\""));
+        assert!(svg.contains(
+            "<rect x=\"206\" y=\"68\" width=\"200\" height=\"100\" rx=\"4\" fill=\"#ffb74d\""
+        ));
+        assert!(svg.contains("&lt;A1&gt;"));
+        assert!(svg.contains(">1 occupied · 1 reserved<"));
+    }
+
+    #[test]
+    fn test_poll_result_bars() {
+        let mut scene = Scene::new(800.0, 600.0);
+        scene.add_element(
+            Element::new(ElementKind::Poll {
+                question: "Ship <today>?".to_string(),
+                options: vec!["Yes".to_string(), "No".to_string()],
+                votes: [("a", 0), ("b", 0), ("c", 0), ("d", 1)]
+                    .into_iter()
+                    .map(|(voter, option)| (voter.to_string(), option))
+                    .collect(),
+                closed: false,
+            })
+            .with_transform(Transform {
+                x: 0.0,
+                y: 0.0,
+                width: 312.0,
+                height: 200.0,
+                rotation: 0.0,
+                z_index: 0,
+                auto_size: false,
+            }),
+        );
+
+        let svg = SceneExporter::with_defaults()
+            .render_to_svg(&scene)
+            .expect("svg export");
+        assert!(svg.contains("Ship &lt;today&gt;?"));
+        assert!(svg.contains(">3 · 75%<"));
+        assert!(svg.contains(">1 · 25%<"));
+        // 300px tracks, filled to each option's share
+        assert!(svg.contains("width=\"225\" height=\"73\" rx=\"3\" fill=\"#4db6ac\""));
+        assert!(svg.contains("width=\"75\" height=\"73\" rx=\"3\" fill=\"#4db6ac\""));
+    }
+
+    #[test]
+    fn test_agenda_highlights_running_item() {
+        let mut scene = Scene::new(800.0, 600.0);
+        scene.add_element(
+            Element::new(ElementKind::Agenda {
+                title: "Weekly <sync>".to_string(),
+                items: vec![
+                    AgendaItem::new("Welcome", 300),
+                    AgendaItem::new("Roadmap review", 900).with_owner("Ada"),
+                    AgendaItem::new("Hiring", 600),
+                ],
+                current: 1,
+                started_at_ms: Some(now_ms() - 60_000),
+            })
+            .with_transform(Transform {
+                x: 0.0,
+                y: 0.0,
+                width: 360.0,
+                height: 156.0,
+                rotation: 0.0,
+                z_index: 0,
+                auto_size: false,
+            }),
+        );
+
+        let svg = SceneExporter::with_defaults()
+            .render_to_svg(&scene)
+            .expect("svg export");
+        assert!(svg.contains(">Weekly &lt;sync&gt;<"));
+        assert!(svg.contains(" left</text>"));
+        assert!(svg.contains(
+            "fill=\"#90a4ae\" font-family=\"sans-serif\" dominant-baseline=\"central\">Welcome<"
+        ));
+        assert!(svg.contains("font-weight=\"bold\" font-family=\"sans-serif\" dominant-baseline=\"central\">Roadmap review · Ada<"));
+        assert!(svg.contains(">15 min<"));
+        assert!(svg.contains("fill=\"#26a69a\""));
+    }
+
+    #[test]
+    fn test_randomizer_shows_outcome() {
+        let mut scene = Scene::new(800.0, 600.0);
+        let place = |x: f32| Transform {
+            x,
+            y: 0.0,
+            width: 200.0,
+            height: 160.0,
+            rotation: 0.0,
+            z_index: 0,
+            auto_size: false,
+        };
+        scene.add_element(
+            Element::new(ElementKind::Randomizer {
+                source: RandomSource::Dice { count: 2, sides: 6 },
+                outcome: Some(RandomOutcome {
+                    values: vec![4, 2],
+                    draw: 1,
+                    by: None,
+                }),
+            })
+            .with_transform(place(0.0)),
+        );
+        scene.add_element(
+            Element::new(ElementKind::Randomizer {
+                source: RandomSource::Wheel {
+                    segments: vec!["Pizza".to_string(), "Tacos & co".to_string()],
+                },
+                outcome: None,
+            })
+            .with_transform(place(300.0)),
+        );
+
+        let svg = SceneExporter::with_defaults()
+            .render_to_svg(&scene)
+            .expect("svg export");
+        assert!(svg.contains(">4 + 2 = 6<"));
+        assert!(svg.contains("dominant-baseline=\"central\">4<"));
+        assert!(svg.contains(">Tap to spin<"));
+        assert!(svg.contains(">Tacos &amp; co<"));
+        assert_eq!(svg.matches("<path d=\"M").count(), 2);
+    }
+
+    #[test]
+    fn test_gauge_and_kpi_cards() {
+        use canvas_core::{NumberFormat, Threshold};
+
+        let mut scene = Scene::new(800.0, 600.0);
+        let place = |x: f32, height: f32| Transform {
+            x,
+            y: 0.0,
+            width: 240.0,
+            height,
+            rotation: 0.0,
+            z_index: 0,
+            auto_size: false,
+        };
+        let thresholds = vec![Threshold {
+            value: 90.0,
+            color: canvas_core::Color::from_rgb(0xe5_3935).into(),
+        }];
+        for (x, shape) in [(0.0, GaugeShape::Radial), (260.0, GaugeShape::Linear)] {
+            scene.add_element(
+                Element::new(ElementKind::Gauge {
+                    value: 95.0,
+                    min: 0.0,
+                    max: 100.0,
+                    shape,
+                    label: "CPU & GPU".to_string(),
+                    thresholds: thresholds.clone(),
+                    format: NumberFormat {
+                        suffix: "%".to_string(),
+                        ..NumberFormat::default()
+                    },
+                })
+                .with_transform(place(x, 160.0)),
+            );
+        }
+        scene.add_element(
+            Element::new(ElementKind::Kpi {
+                label: "Revenue".to_string(),
+                value: 1_200_000.0,
+                previous: Some(1_080_000.0),
+                sparkline: vec![3.0, 5.0, 4.0, 6.0],
+                sparkline_kind: SparklineKind::Line,
+                thresholds: Vec::new(),
+                format: NumberFormat {
+                    prefix: "$".to_string(),
+                    compact: true,
+                    ..NumberFormat::default()
+                },
+                higher_is_better: true,
+            })
+            .with_transform(place(520.0, 170.0)),
+        );
+
+        let svg = SceneExporter::with_defaults()
+            .render_to_svg(&scene)
+            .expect("svg export");
+        assert_eq!(svg.matches(">95%<").count(), 2);
+        assert_eq!(svg.matches(">CPU &amp; GPU<").count(), 2);
+        // Over the threshold: red fill and value
+        assert!(svg.contains("stroke=\"#e53935\" stroke-width="));
+        assert!(svg.contains("fill=\"#e53935\" font-family=\"sans-serif\" font-weight=\"bold\""));
+        assert!(svg.contains(">$1.2M<"));
+        assert!(svg.contains(">▲ +$120k (+11.1%)<"));
+        assert!(svg.contains("fill=\"#2e7d32\""));
+        assert_eq!(svg.matches("<polyline").count(), 1);
+    }
+
+    #[test]
+    fn test_sparkline_line_and_bars() {
+        let mut scene = Scene::new(400.0, 100.0);
+        scene
+            .set_theme(Theme::default().with_variable("trend", Color::from_rgb(0x43_a047)))
+            .expect("valid theme");
+        let place = |x: f32| Transform {
+            x,
+            y: 0.0,
+            width: 104.0,
+            height: 24.0,
+            rotation: 0.0,
+            z_index: 0,
+            auto_size: false,
+        };
+        scene.add_element(
+            Element::new(ElementKind::Sparkline {
+                values: vec![1.0, 3.0, 2.0],
+                kind: SparklineKind::Line,
+                color: canvas_core::ThemeColor::variable("trend"),
+            })
+            .with_transform(place(0.0)),
+        );
+        scene.add_element(
+            Element::new(ElementKind::Sparkline {
+                values: vec![2.0, -1.0, 4.0, 3.0],
+                kind: SparklineKind::Bar,
+                color: ElementKind::default_sparkline_color(),
+            })
+            .with_transform(place(200.0)),
+        );
+
+        let svg = SceneExporter::with_defaults()
+            .render_to_svg(&scene)
+            .expect("svg export");
+        // Line: points inside the padding, dot on the latest value
+        assert!(
+            svg.contains("<polyline points=\"2,22 52,2 102,12\" fill=\"none\" stroke=\"#43a047\"")
+        );
+        assert!(svg.contains("<circle cx=\"102\" cy=\"12\" r=\"2\" fill=\"#43a047\"/>"));
+        assert_eq!(svg.matches("fill=\"#1e88e5\"/>").count(), 4);
+    }
+
+    #[test]
+    fn test_ink_strokes_and_dots() {
+        let mut scene = Scene::new(400.0, 100.0);
+        scene.add_element(ink::stroke(
+            &[[10.0, 10.0], [50.0, 30.0], [90.0, 10.0]],
+            ElementKind::default_ink_color(),
+            4.0,
+        ));
+        scene.add_element(ink::stroke(
+            &[[200.0, 50.0]],
+            Color::from_rgb(0xe5_3935).into(),
+            6.0,
+        ));
+
+        let svg = SceneExporter::with_defaults()
+            .render_to_svg(&scene)
+            .expect("svg export");
+        assert!(svg.contains(
+            "<polyline points=\"10,10 50,30 90,10\" fill=\"none\" stroke=\"#212121\" \
+             stroke-width=\"4\" stroke-linecap=\"round\" stroke-linejoin=\"round\"/>"
+        ));
+        assert!(svg.contains("<circle cx=\"200\" cy=\"50\" r=\"3\" fill=\"#e53935\"/>"));
+    }
+
+    #[test]
+    fn test_measurement_and_region_annotations() {
+        let mut scene = Scene::new(400.0, 300.0);
+        let mut dimension = canvas_core::annotation::measurement(
+            [100.0, 200.0],
+            [220.0, 200.0],
+            canvas_core::MeasurementKind::Dimension,
+        );
+        dimension.transform.z_index = 1;
+        scene.add_element(dimension);
+        scene.add_element(
+            Element::new(ElementKind::Region {
+                caption: Some("Peak & dip".to_string()),
+                color: ElementKind::default_annotation_color(),
+            })
+            .with_transform(Transform {
+                x: 20.0,
+                y: 30.0,
+                width: 150.0,
+                height: 80.0,
+                rotation: 0.0,
+                z_index: 0,
+                auto_size: false,
+            }),
+        );
+
+        let svg = SceneExporter::with_defaults()
+            .render_to_svg(&scene)
+            .expect("svg export");
+        assert!(
+            svg.contains("<line x1=\"100\" y1=\"200\" x2=\"220\" y2=\"200\" stroke=\"#e53935\"")
+        );
+        assert!(svg.contains("<line x1=\"100\" y1=\"206\" x2=\"100\" y2=\"194\""));
+        assert!(svg.contains("<text x=\"160\" y=\"190\""));
+        assert!(svg.contains(">120 px</text>"));
+        assert!(svg.contains("fill-opacity=\"0.15\" stroke=\"#e53935\" stroke-width=\"2\"/>"));
+        assert!(svg.contains(">Peak &amp; dip</text>"));
+    }
+
+    #[test]
+    fn test_barcode_draws_modules_on_white() {
+        let mut scene = Scene::new(400.0, 300.0);
+        let mut modules = vec!["0".repeat(21); 21];
+        modules[0] = format!("11{}", "0".repeat(19));
+        scene.add_element(
+            Element::new(ElementKind::Barcode {
+                symbology: canvas_core::Symbology::Qr,
+                payload: "https://example.com".to_string(),
+                modules,
+                color: Color::from_rgb(0x1a_237e).into(),
+            })
+            .with_transform(Transform {
+                x: 0.0,
+                y: 0.0,
+                width: 290.0,
+                height: 290.0,
+                rotation: 0.0,
+                z_index: 0,
+                auto_size: false,
+            }),
+        );
+
+        let svg = SceneExporter::with_defaults()
+            .render_to_svg(&scene)
+            .expect("svg export");
+        assert!(
+            svg.contains("<rect x=\"0\" y=\"0\" width=\"290\" height=\"290\" fill=\"#ffffff\"/>")
+        );
+        assert!(svg.contains(
+            "<path d=\"M40 40h20v10h-20z\" fill=\"#1a237e\" shape-rendering=\"crispEdges\"/>"
+        ));
+    }
+
+    #[test]
+    fn test_shapes_use_element_style() {
+        let mut scene = Scene::new(400.0, 300.0);
+        let at = |x: f32, z_index: i32| Transform {
+            x,
+            y: 10.0,
+            width: 100.0,
+            height: 40.0,
+            rotation: 0.0,
+            z_index,
+            auto_size: false,
+        };
+        scene.add_element(
+            Element::new(ElementKind::Shape {
+                shape: ShapeKind::Rectangle,
+            })
+            .with_transform(at(0.0, 0))
+            .with_style(
+                canvas_core::Style::solid(Color::from_rgb(0x43_a047))
+                    .with_stroke(Color::BLACK, 2.0)
+                    .with_corner_radius(6.0),
+            ),
+        );
+        scene.add_element(
+            Element::new(ElementKind::Shape {
+                shape: ShapeKind::Ellipse,
+            })
+            .with_transform(at(120.0, 1)),
+        );
+        scene.add_element(
+            Element::new(ElementKind::Shape {
+                shape: ShapeKind::Arrow {
+                    direction: canvas_core::ArrowDirection::Right,
+                    head_length: 0.25,
+                    shaft_width: 0.5,
+                },
+            })
+            .with_transform(at(240.0, 2))
+            .with_style(canvas_core::Style::default().with_opacity(0.5)),
+        );
+
+        let svg = SceneExporter::with_defaults()
+            .render_to_svg(&scene)
+            .expect("svg export");
+        assert!(svg.contains(
+            "<rect x=\"0\" y=\"10\" width=\"100\" height=\"40\" rx=\"6\" fill=\"#43a047\" \
+             stroke=\"#000000\" stroke-width=\"2\" stroke-linejoin=\"round\"/>"
+        ));
+        assert!(
+            svg.contains("<ellipse cx=\"170\" cy=\"30\" rx=\"50\" ry=\"20\" fill=\"#90caf9\"/>")
+        );
+        assert!(svg.contains(
+            "<polygon points=\"240,20 315,20 315,10 340,30 315,50 315,40 240,40\" \
+             fill=\"#90caf9\" opacity=\"0.5\"/>"
+        ));
+    }
+
+    #[test]
+    fn test_tree_connectors_and_collapsed_badge() {
+        let mut scene = Scene::new(800.0, 600.0);
+        let mut cfo = TreeNode::new("cfo", "CFO")
+            .with_children(vec![TreeNode::new("ap", "AP"), TreeNode::new("ar", "AR")]);
+        cfo.collapsed = true;
+        cfo.color = Some(canvas_core::ThemeColor::variable("finance"));
+        let root = TreeNode::new("ceo", "Ada & co")
+            .with_detail("CEO")
+            .with_children(vec![
+                TreeNode::new("cto", "CTO").with_children(vec![TreeNode::new("qa", "QA")]),
+                cfo,
+            ]);
+        scene
+            .set_theme(Theme::default().with_variable("finance", Color::from_rgb(0xff_e0b2)))
+            .expect("valid theme");
+        scene.add_element(
+            Element::new(ElementKind::Tree {
+                root,
+                direction: TreeDirection::Down,
+            })
+            .with_transform(Transform {
+                x: 0.0,
+                y: 0.0,
+                width: 400.0,
+                height: 300.0,
+                rotation: 0.0,
+                z_index: 0,
+                auto_size: false,
+            }),
+        );
+
+        let svg = SceneExporter::with_defaults()
+            .render_to_svg(&scene)
+            .expect("svg export");
+        // Four visible nodes, three connectors; AP and AR are hidden
+        assert_eq!(svg.matches("stroke=\"#607d8b\"").count(), 4);
+        assert_eq!(svg.matches("stroke=\"#90a4ae\"").count(), 3);
+        assert!(svg.contains(">Ada &amp; co<"));
+        assert!(svg.contains(">CEO<"));
+        assert!(!svg.contains(">AP<"));
+        assert!(svg.contains(">+2<"));
+        assert!(svg.contains("fill=\"#ffe0b2\""));
+    }
+
+    #[test]
+    fn test_embed_placeholder() {
+        let mut scene = Scene::new(400.0, 300.0);
+        scene.add_element(
+            Element::new(ElementKind::Embed {
+                url: "https://example.com/dash?a=1&b=2".to_string(),
+                sandbox: String::new(),
+            })
+            .with_transform(Transform {
+                x: 10.0,
+                y: 10.0,
+                width: 380.0,
+                height: 280.0,
+                rotation: 0.0,
+                z_index: 0,
+                auto_size: false,
+            }),
+        );
+
+        let svg = SceneExporter::with_defaults()
+            .render_to_svg(&scene)
+            .expect("svg");
+        assert!(svg.contains("Web page"));
+        assert!(svg.contains("https://example.com/dash?a=1&amp;b=2"));
+    }
+
     #[test]
     fn test_empty_scene_png() {
         let scene = Scene::new(50.0, 50.0);
@@ -10,16 +10,35 @@
 //! 2. Call render_quilt() with a scene and camera
 //! 3. The result is a QuiltRenderTarget with all views rendered
 //! ```
+//!
+//! Views are independent, so `render_quilt()` renders them in parallel on
+//! native targets and the GPU path records every view into one submission;
+//! [`HolographicStats`] keeps per-view timings next to the frame times.
+//!
+//! Canvas elements stand in front of the focal plane by their depth (see
+//! [`CanvasDepth`](crate::spatial::CanvasDepth)), so 2D content shows
+//! parallax; with `depth_map` set, `render_quilt()` also renders a depth
+//! map in the quilt's layout.
+//!
+//! On a Looking Glass display, `render_lightfield()` renders the quilt on
+//! the GPU and presents it through the display's lens calibration; see
+//! [`crate::lightfield`].
+//!
+//! To capture a session for playback on a device, start a
+//! [`QuiltRecorder`](crate::QuiltRecorder) with `record()` and render each
+//! frame into it with `record_quilt()`; see [`crate::quilt_video`].
 
 #[cfg(feature = "gpu")]
 use crate::backend::wgpu::{Viewport, WgpuBackend};
-#[cfg(feature = "gpu")]
+#[cfg(any(feature = "gpu", not(target_arch = "wasm32")))]
 use crate::error::RenderResult;
 #[cfg(feature = "gpu")]
-use crate::quilt::QuiltView;
-use crate::quilt::{Quilt, QuiltRenderSettings, QuiltRenderTarget};
+use crate::lightfield::LensCalibration;
+use crate::quilt::{Quilt, QuiltRenderSettings, QuiltRenderTarget, QuiltView};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::quilt_video::{QuiltRecorder, QuiltVideoConfig};
 use crate::spatial::{Camera, HolographicConfig};
-use canvas_core::Scene;
+use canvas_core::{chart3d, ElementKind, Scene};
 use serde::{Deserialize, Serialize};
 
 /// Result of a holographic render operation.
@@ -31,6 +50,14 @@ pub struct HolographicRenderResult {
     pub view_count: u32,
     /// Total render time in milliseconds.
     pub render_time_ms: f64,
+    /// Time each view took to render in milliseconds, in view order. Views
+    /// rendered in parallel overlap, so these can add up to more than
+    /// `render_time_ms`. Always empty for quilts rendered on the GPU, whose
+    /// views go out in one submission and finish together.
+    pub view_times_ms: Vec<f64>,
+    /// Depth of each view in the quilt's layout, brighter for nearer
+    /// content, when the settings ask for one.
+    pub depth_map: Option<QuiltRenderTarget>,
 }
 
 /// Holographic rendering statistics.
@@ -44,6 +71,12 @@ pub struct HolographicStats {
     pub peak_render_time_ms: f64,
     /// Total views rendered across all frames.
     pub total_views_rendered: u64,
+    /// Average time to render one view in milliseconds. Frames rendered on
+    /// the GPU do not time their views and leave this unchanged.
+    pub avg_view_time_ms: f64,
+    /// Longest time any one view took in milliseconds, like
+    /// `avg_view_time_ms` only counting views timed one by one.
+    pub peak_view_time_ms: f64,
 }
 
 impl HolographicStats {
@@ -61,6 +94,17 @@ impl HolographicStats {
         if result.render_time_ms > self.peak_render_time_ms {
             self.peak_render_time_ms = result.render_time_ms;
         }
+
+        if !result.view_times_ms.is_empty() {
+            #[allow(clippy::cast_precision_loss)] // View counts are small
+            let mean = result.view_times_ms.iter().sum::<f64>() / result.view_times_ms.len() as f64;
+            self.avg_view_time_ms = alpha * mean + (1.0 - alpha) * self.avg_view_time_ms;
+            self.peak_view_time_ms = result
+                .view_times_ms
+                .iter()
+                .copied()
+                .fold(self.peak_view_time_ms, f64::max);
+        }
     }
 
     /// Reset all statistics.
@@ -135,12 +179,16 @@ impl HolographicRenderer {
     ///
     /// This is a software-based reference implementation. For GPU-accelerated
     /// rendering, use the wgpu backend's holographic extension.
+    ///
+    /// Each view is rendered into a tile of its own, in parallel on native
+    /// targets, and the tiles are copied into the quilt; the result has the
+    /// time each view took.
     #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
     pub fn render_quilt(&mut self, scene: &Scene, camera: &Camera) -> HolographicRenderResult {
         let start = std::time::Instant::now();
 
         // Create the quilt with all camera positions
-        let quilt = Quilt::new(self.config.clone(), camera);
+        let quilt = Quilt::new(self.config.clone(), camera).with_depth(self.settings.depth);
 
         // Create the render target
         let mut target = QuiltRenderTarget::from_quilt(&quilt);
@@ -153,9 +201,29 @@ impl HolographicRenderer {
         // In a real implementation, this would use the GPU to render
         // each view with proper 3D projection. For now, we just fill
         // each view with a gradient to demonstrate the quilt layout.
-        for view in &quilt.views {
-            self.render_view_placeholder(&mut target, view, scene);
+        let renderer = &*self;
+        let render_view = |view: &QuiltView| {
+            let view_start = std::time::Instant::now();
+            let tile = renderer.render_view_placeholder(view, scene);
+            (tile, view_start.elapsed().as_secs_f64() * 1000.0)
+        };
+        #[cfg(not(target_arch = "wasm32"))]
+        let tiles: Vec<_> = {
+            use rayon::prelude::*;
+            quilt.views.par_iter().map(render_view).collect()
+        };
+        #[cfg(target_arch = "wasm32")]
+        let tiles: Vec<_> = quilt.views.iter().map(render_view).collect();
+
+        let mut view_times_ms = Vec::with_capacity(tiles.len());
+        for (view, (tile, time_ms)) in quilt.views.iter().zip(tiles) {
+            target.copy_from(&tile, view.x_offset, view.y_offset);
+            view_times_ms.push(time_ms);
         }
+        let depth_map = self
+            .settings
+            .depth_map
+            .then(|| quilt.render_depth_map(scene));
 
         let elapsed = start.elapsed();
         let render_time_ms = elapsed.as_secs_f64() * 1000.0;
@@ -167,6 +235,8 @@ impl HolographicRenderer {
             target,
             view_count,
             render_time_ms,
+            view_times_ms,
+            depth_map,
         };
 
         self.stats.update(&result);
@@ -174,6 +244,42 @@ impl HolographicRenderer {
         result
     }
 
+    /// Start recording quilts in this renderer's layout into a video next
+    /// to `output`, e.g. `recordings/session` for
+    /// `recordings/session_qs5x9a0.75.mp4`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the recording settings are invalid or `ffmpeg`
+    /// cannot be started.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn record(
+        &self,
+        output: impl AsRef<std::path::Path>,
+        config: &QuiltVideoConfig,
+    ) -> RenderResult<QuiltRecorder> {
+        QuiltRecorder::start(output, &self.config, config)
+    }
+
+    /// Render a scene to a quilt and append it to `recorder` as the next
+    /// frame.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the quilt does not match the recording's layout
+    /// or the encoder has stopped.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn record_quilt(
+        &mut self,
+        scene: &Scene,
+        camera: &Camera,
+        recorder: &mut QuiltRecorder,
+    ) -> RenderResult<HolographicRenderResult> {
+        let result = self.render_quilt(scene, camera);
+        recorder.push(&result.target)?;
+        Ok(result)
+    }
+
     /// Convert a float RGBA color (0.0-1.0) to byte RGBA (0-255).
     #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
     fn float_color_to_bytes(color: &[f32; 4]) -> [u8; 4] {
@@ -215,7 +321,41 @@ impl HolographicRenderer {
         backend.render_with_camera(scene, Some(&view.camera), Some(viewport))
     }
 
-    /// Placeholder view rendering (demonstrates quilt layout).
+    /// Render a scene to a quilt on the GPU and present it on a Looking
+    /// Glass display as the lightfield image its lens expects.
+    ///
+    /// `backend`'s surface should be on the display, and `calibration` the
+    /// display's own, e.g. from its `visual.json`. The views are submitted
+    /// together, so only the whole frame is timed in [`stats`](Self::stats).
+    ///
+    /// # Errors
+    ///
+    /// Returns a `RenderError` if rendering the quilt, composing it or
+    /// presenting fails.
+    #[cfg(feature = "gpu")]
+    pub fn render_lightfield(
+        &mut self,
+        backend: &mut WgpuBackend,
+        scene: &Scene,
+        camera: &Camera,
+        calibration: &LensCalibration,
+    ) -> RenderResult<()> {
+        let start = std::time::Instant::now();
+        let quilt = Quilt::new(self.config.clone(), camera).with_depth(self.settings.depth);
+        let target = quilt.render(backend, scene)?;
+        backend.present_lightfield(&target, &self.config, calibration)?;
+        self.stats.update(&HolographicRenderResult {
+            target,
+            view_count: self.config.num_views,
+            render_time_ms: start.elapsed().as_secs_f64() * 1000.0,
+            view_times_ms: Vec::new(),
+            depth_map: None,
+        });
+        Ok(())
+    }
+
+    /// Placeholder view rendering (demonstrates quilt layout), into a tile
+    /// the size of the view.
     ///
     /// In a real implementation, this would render the scene from
     /// the view's camera perspective.
@@ -224,12 +364,7 @@ impl HolographicRenderer {
         clippy::cast_possible_truncation,
         clippy::cast_sign_loss
     )]
-    fn render_view_placeholder(
-        &self,
-        target: &mut QuiltRenderTarget,
-        view: &crate::quilt::QuiltView,
-        _scene: &Scene,
-    ) {
+    fn render_view_placeholder(&self, view: &QuiltView, _scene: &Scene) -> QuiltRenderTarget {
         // Calculate a gradient based on view index to visualize the quilt
         let progress = view.index as f32 / (self.config.num_views - 1).max(1) as f32;
 
@@ -239,22 +374,12 @@ impl HolographicRenderer {
         let blue = ((1.0 - progress) * 255.0) as u8;
 
         // Fill the view area
-        target.fill_rect(
-            view.x_offset,
-            view.y_offset,
-            view.width,
-            view.height,
-            [red, green, blue, 255],
-        );
+        let mut tile = QuiltRenderTarget::new(view.width, view.height);
+        tile.clear([red, green, blue, 255]);
 
         // Draw border rectangle around the view
-        Self::draw_border(
-            target,
-            view.x_offset,
-            view.y_offset,
-            view.width,
-            view.height,
-        );
+        Self::draw_border(&mut tile, 0, 0, view.width, view.height);
+        tile
     }
 
     /// Draw a border rectangle at the specified position.
@@ -275,6 +400,22 @@ impl HolographicRenderer {
         }
     }
 
+    /// The camera framing the frontmost 3D model that has a camera hint,
+    /// or 3D chart, to use as the base camera for [`Self::render_quilt`].
+    #[must_use]
+    pub fn model_camera(scene: &Scene) -> Option<Camera> {
+        scene
+            .elements()
+            .filter_map(|element| match &element.kind {
+                ElementKind::Model3D {
+                    camera: Some(hint), ..
+                } => Some((element.transform.z_index, *hint)),
+                kind => chart3d::chart_camera(kind).map(|hint| (element.transform.z_index, hint)),
+            })
+            .max_by_key(|(z_index, _)| *z_index)
+            .map(|(_, hint)| Camera::from_model(&hint))
+    }
+
     /// Get the expected quilt dimensions for the current configuration.
     #[must_use]
     pub fn quilt_dimensions(&self) -> (u32, u32) {
@@ -317,6 +458,40 @@ mod tests {
         assert_eq!(renderer.config().num_views, 45);
     }
 
+    #[test]
+    fn test_model_camera_frames_model_with_hint() {
+        let mut scene = Scene::new(800.0, 600.0);
+        assert!(HolographicRenderer::model_camera(&scene).is_none());
+
+        let hint = canvas_core::ModelCamera {
+            distance: 4.0,
+            ..canvas_core::ModelCamera::default()
+        };
+        scene.add_element(canvas_core::Element::new(ElementKind::Model3D {
+            src: "asset:robot".to_string(),
+            rotation: [0.0; 3],
+            scale: 1.0,
+            pose: None,
+            camera: Some(hint),
+            lighting: None,
+        }));
+        let camera = HolographicRenderer::model_camera(&scene).expect("camera");
+        assert_eq!(camera.position, crate::spatial::Vec3::new(0.0, 0.0, 4.0));
+        assert!((camera.fov - hint.fov).abs() < f32::EPSILON);
+
+        // A 3D chart in front is framed by its own camera
+        let mut chart = canvas_core::Element::new(ElementKind::Chart {
+            chart_type: "surface".to_string(),
+            data: serde_json::json!({ "values": [[0, 1], [1, 0]] }),
+            binding: None,
+        });
+        chart.transform.z_index = 1;
+        scene.add_element(chart);
+        let camera = HolographicRenderer::model_camera(&scene).expect("camera");
+        let expected = chart3d::default_camera().position();
+        assert_eq!(camera.position, crate::spatial::Vec3::from(expected));
+    }
+
     #[test]
     fn test_holographic_renderer_with_settings() {
         let config = HolographicConfig::looking_glass_portrait();
@@ -325,6 +500,7 @@ mod tests {
             wireframe: true,
             depth_test: false,
             backface_cull: false,
+            ..QuiltRenderSettings::default()
         };
 
         let renderer = HolographicRenderer::with_settings(config, settings);
@@ -354,6 +530,75 @@ mod tests {
         assert_eq!(result.view_count, 4);
         assert_eq!(result.target.width, 200); // 2 columns * 100
         assert_eq!(result.target.height, 200); // 2 rows * 100
+
+        // Each view is timed and lands in its own cell, blue to red
+        assert_eq!(result.view_times_ms.len(), 4);
+        let center = |x: u32, y: u32| result.target.get_pixel(x, y).unwrap();
+        assert_eq!(center(50, 50), [0, 100, 255, 255]);
+        assert_eq!(center(150, 150), [255, 100, 0, 255]);
+        assert_eq!(center(100, 50), [255, 255, 255, 128]);
+        assert_eq!(renderer.stats().frames_rendered, 1);
+        assert!(result.depth_map.is_none());
+    }
+
+    #[test]
+    fn test_depth_map_shows_nearer_elements_brighter_with_parallax() {
+        use canvas_core::{Element, ShapeKind, Transform};
+
+        let config = HolographicConfig {
+            num_views: 2,
+            quilt_columns: 2,
+            quilt_rows: 1,
+            view_width: 100,
+            view_height: 100,
+            view_cone: 40.0_f32.to_radians(),
+            focal_distance: 2.0,
+        };
+        let mut renderer = HolographicRenderer::with_settings(
+            config,
+            QuiltRenderSettings {
+                depth_map: true,
+                ..QuiltRenderSettings::default()
+            },
+        );
+        let rect = |x: f32, z_index: i32| {
+            Element::new(ElementKind::Shape {
+                shape: ShapeKind::Rectangle,
+            })
+            .with_transform(Transform {
+                x,
+                y: 160.0,
+                width: 80.0,
+                height: 80.0,
+                z_index,
+                ..Transform::default()
+            })
+        };
+        // One rectangle a layer behind the focal plane, one as far in
+        // front of it as the depth of field allows
+        let mut scene = Scene::new(400.0, 400.0);
+        scene.add_element(rect(40.0, -1));
+        scene.add_element(rect(280.0, 0).with_depth(150.0));
+
+        let result = renderer.render_quilt(&scene, &Camera::default());
+        let depth = result.depth_map.expect("depth map");
+        assert_eq!((depth.width, depth.height), (200, 100));
+        let value = |x: u32, y: u32| depth.get_pixel(x, y).unwrap()[0];
+        // The plane is mid gray, nearer content brighter; content in front
+        // of the plane and behind it shifts opposite ways from the left
+        // view to the right one
+        assert_eq!(value(50, 10), 128);
+        let left_edge = |offset: u32, shade: u8| {
+            (offset..offset + 100)
+                .find(|&x| value(x, 50) == shade)
+                .map(|x| i64::from(x - offset))
+                .unwrap()
+        };
+        let near_shift = left_edge(100, 255) - left_edge(0, 255);
+        let far_shift = left_edge(100, 111) - left_edge(0, 111);
+        assert!(near_shift != 0 && far_shift != 0);
+        assert!(near_shift.signum() != far_shift.signum());
+        assert!(near_shift.abs() > far_shift.abs());
     }
 
     #[test]
@@ -364,6 +609,8 @@ mod tests {
             target: QuiltRenderTarget::new(100, 100),
             view_count: 45,
             render_time_ms: 16.6,
+            view_times_ms: vec![1.0, 3.0],
+            depth_map: None,
         };
 
         stats.update(&result);
@@ -371,6 +618,9 @@ mod tests {
         assert_eq!(stats.frames_rendered, 1);
         assert_eq!(stats.total_views_rendered, 45);
         assert!(stats.avg_render_time_ms > 0.0);
+        // A tenth of the 2 ms mean, as the first sample of the average
+        assert!((stats.avg_view_time_ms - 0.2).abs() < 1e-9);
+        assert!((stats.peak_view_time_ms - 3.0).abs() < f64::EPSILON);
     }
 
     #[test]
@@ -382,6 +632,8 @@ mod tests {
             target: QuiltRenderTarget::new(10, 10),
             view_count: 4,
             render_time_ms: 10.0,
+            view_times_ms: Vec::new(),
+            depth_map: None,
         });
 
         // Second render with higher time
@@ -389,6 +641,8 @@ mod tests {
             target: QuiltRenderTarget::new(10, 10),
             view_count: 4,
             render_time_ms: 20.0,
+            view_times_ms: Vec::new(),
+            depth_map: None,
         });
 
         assert!((stats.peak_render_time_ms - 20.0).abs() < f64::EPSILON);
@@ -433,6 +687,8 @@ mod tests {
             avg_render_time_ms: 16.6,
             peak_render_time_ms: 33.3,
             total_views_rendered: 4500,
+            avg_view_time_ms: 0.3,
+            peak_view_time_ms: 1.2,
         };
 
         stats.reset();
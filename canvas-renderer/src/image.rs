@@ -2,6 +2,10 @@
 //!
 //! Supports loading images from files, URLs, and base64-encoded data URIs.
 
+use std::path::Path;
+
+use canvas_core::asset::ASSET_URI_SCHEME;
+
 use crate::error::{RenderError, RenderResult};
 
 /// Loaded texture data ready for GPU upload.
@@ -53,6 +57,17 @@ impl ImageFormat {
         }
     }
 
+    /// MIME type of the format, `application/octet-stream` when unknown.
+    #[must_use]
+    pub const fn mime_type(self) -> &'static str {
+        match self {
+            Self::Png => "image/png",
+            Self::Jpeg => "image/jpeg",
+            Self::WebP => "image/webp",
+            Self::Unknown => "application/octet-stream",
+        }
+    }
+
     /// Detect format from magic bytes.
     #[must_use]
     pub fn from_magic_bytes(data: &[u8]) -> Self {
@@ -101,6 +116,48 @@ pub fn load_image_from_bytes(data: &[u8]) -> RenderResult<TextureData> {
     })
 }
 
+/// Where an image element's `src` points.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageSource<'a> {
+    /// Inline `data:` URI.
+    DataUri(&'a str),
+    /// Local file, given as a plain path or a `file://` URL.
+    File(&'a Path),
+    /// Network URL or `asset:` reference, which the host has to fetch.
+    Remote(&'a str),
+}
+
+impl<'a> ImageSource<'a> {
+    /// Classify an image `src`.
+    #[must_use]
+    pub fn parse(src: &'a str) -> Self {
+        if src.starts_with("data:") {
+            Self::DataUri(src)
+        } else if let Some(path) = src.strip_prefix("file://") {
+            Self::File(Path::new(path))
+        } else if src.contains("://") || src.starts_with(ASSET_URI_SCHEME) {
+            Self::Remote(src)
+        } else {
+            Self::File(Path::new(src))
+        }
+    }
+
+    /// Read the encoded image bytes, or `None` for a remote source.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the data URI is malformed or the file cannot be read.
+    pub fn read(self) -> RenderResult<Option<Vec<u8>>> {
+        match self {
+            Self::DataUri(uri) => decode_data_uri(uri).map(Some),
+            Self::File(path) => std::fs::read(path).map(Some).map_err(|e| {
+                RenderError::Resource(format!("Failed to read {}: {e}", path.display()))
+            }),
+            Self::Remote(_) => Ok(None),
+        }
+    }
+}
+
 /// Load an image from a data URI (base64 encoded).
 ///
 /// Supports formats like: `data:image/png;base64,iVBORw0KGgo...`
@@ -109,6 +166,15 @@ pub fn load_image_from_bytes(data: &[u8]) -> RenderResult<TextureData> {
 ///
 /// Returns an error if the data URI is malformed or the image cannot be decoded.
 pub fn load_image_from_data_uri(uri: &str) -> RenderResult<TextureData> {
+    load_image_from_bytes(&decode_data_uri(uri)?)
+}
+
+/// Decode the payload of a data URI (base64 or percent-encoded).
+///
+/// # Errors
+///
+/// Returns an error if the data URI is malformed.
+pub fn decode_data_uri(uri: &str) -> RenderResult<Vec<u8>> {
     // Parse data URI
     if !uri.starts_with("data:") {
         return Err(RenderError::Resource("Not a data URI".to_string()));
@@ -127,17 +193,15 @@ pub fn load_image_from_data_uri(uri: &str) -> RenderResult<TextureData> {
     // Check for base64 encoding
     let is_base64 = metadata.contains(";base64");
 
-    let bytes = if is_base64 {
+    if is_base64 {
         use base64::Engine;
         base64::engine::general_purpose::STANDARD
             .decode(encoded_data)
-            .map_err(|e| RenderError::Resource(format!("Failed to decode base64: {e}")))?
+            .map_err(|e| RenderError::Resource(format!("Failed to decode base64: {e}")))
     } else {
         // URL-encoded
-        urlencoding_decode(encoded_data)?
-    };
-
-    load_image_from_bytes(&bytes)
+        urlencoding_decode(encoded_data)
+    }
 }
 
 /// Simple URL decoding (percent-encoding).
@@ -290,6 +354,9 @@ mod tests {
         assert_eq!(ImageFormat::from_mime("image/png"), ImageFormat::Png);
         assert_eq!(ImageFormat::from_mime("image/jpeg"), ImageFormat::Jpeg);
         assert_eq!(ImageFormat::from_mime("image/webp"), ImageFormat::WebP);
+        for format in [ImageFormat::Png, ImageFormat::Jpeg, ImageFormat::WebP] {
+            assert_eq!(ImageFormat::from_mime(format.mime_type()), format);
+        }
     }
 
     #[test]
@@ -346,6 +413,41 @@ mod tests {
         assert_eq!(texture.format, ImageFormat::Png);
     }
 
+    #[test]
+    fn test_image_source_parse() {
+        assert_eq!(
+            ImageSource::parse("data:image/png;base64,AA=="),
+            ImageSource::DataUri("data:image/png;base64,AA==")
+        );
+        assert_eq!(
+            ImageSource::parse("file:///tmp/a.png"),
+            ImageSource::File(Path::new("/tmp/a.png"))
+        );
+        assert_eq!(
+            ImageSource::parse("images/a.webp"),
+            ImageSource::File(Path::new("images/a.webp"))
+        );
+        assert_eq!(
+            ImageSource::parse("https://example.com/a.jpg"),
+            ImageSource::Remote("https://example.com/a.jpg")
+        );
+        assert_eq!(
+            ImageSource::parse("asset:sha256-00"),
+            ImageSource::Remote("asset:sha256-00")
+        );
+    }
+
+    #[test]
+    fn test_image_source_read() {
+        let bytes = ImageSource::parse("data:text/plain,a%20b").read().unwrap();
+        assert_eq!(bytes.as_deref(), Some(&b"a b"[..]));
+        assert!(ImageSource::parse("https://example.com/a.png")
+            .read()
+            .unwrap()
+            .is_none());
+        assert!(ImageSource::parse("/nonexistent/image.png").read().is_err());
+    }
+
     #[test]
     fn test_invalid_data_uri() {
         let result = load_image_from_data_uri("not a data uri");
@@ -20,36 +20,73 @@
 #![allow(clippy::module_name_repetitions)]
 
 pub mod backend;
-#[cfg(feature = "charts")]
 pub mod chart;
+pub mod chart3d;
+pub mod chart_mesh;
+pub mod chart_style;
+pub mod color;
+pub mod cull;
+pub mod damage;
 pub mod error;
 #[cfg(feature = "export")]
 pub mod export;
 pub mod holographic;
 #[cfg(feature = "images")]
 pub mod image;
+pub mod lightfield;
+pub mod math;
+pub mod model3d;
+pub mod orbit;
+pub mod post;
+#[cfg(feature = "export")]
+pub mod print;
 pub mod quilt;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod quilt_video;
+pub mod shader;
+pub mod snapshot;
 pub mod spatial;
+pub mod stats;
 #[cfg(feature = "images")]
 pub mod texture_cache;
 #[cfg(feature = "gpu")]
 pub mod video;
 
+#[cfg(feature = "export")]
+pub use backend::software::SoftwareBackend;
+#[cfg(feature = "export")]
+pub use backend::svg::SvgBackend;
 pub use backend::RenderBackend;
+pub use chart_style::{AxisFormat, ChartStyle, LegendPosition, NumberFormat};
+pub use color::ColorSpace;
+pub use cull::VisibleSet;
+pub use damage::{Damage, DamageRect, DamageTracker};
 pub use error::{RenderError, RenderResult};
 #[cfg(feature = "export")]
 pub use export::{ExportConfig, ExportFormat, SceneExporter};
 pub use holographic::{
     HoloPlayInfo, HolographicRenderResult, HolographicRenderer, HolographicStats,
 };
+pub use lightfield::LensCalibration;
+pub use math::{GlyphRun, MathLayout, MathRule};
+pub use model3d::{Model, ModelLoader, ModelState};
+pub use orbit::{OrbitCamera, OrbitLimits};
+pub use post::{PostEffect, PostEffects};
+#[cfg(feature = "export")]
+pub use print::{Orientation, PaperSize, PrintConfig, PrintLayout, PrintPage};
 pub use quilt::{LookingGlassPreset, Quilt, QuiltRenderSettings, QuiltRenderTarget, QuiltView};
-pub use spatial::{Camera, HolographicConfig, Mat4, QuiltRenderInfo, Vec3};
+#[cfg(not(target_arch = "wasm32"))]
+pub use quilt_video::{quilt_file_name, QuiltRecorder, QuiltVideoCodec, QuiltVideoConfig};
+pub use snapshot::{RgbaImage, MAX_SNAPSHOT_SIZE};
+pub use spatial::{Camera, CanvasDepth, HolographicConfig, Mat4, QuiltRenderInfo, Vec3};
+pub use stats::{FrameStats, RenderStats};
 #[cfg(feature = "gpu")]
 pub use video::{
     VideoFrameData, VideoTextureEntry, VideoTextureError, VideoTextureManager, VideoTextureResult,
 };
 
-use canvas_core::Scene;
+use canvas_core::{Redaction, Scene};
+use serde::{Deserialize, Serialize};
 
 /// Configuration for the renderer.
 #[derive(Debug, Clone)]
@@ -58,10 +95,27 @@ pub struct RendererConfig {
     pub preferred_backend: BackendType,
     /// Target frames per second.
     pub target_fps: u32,
-    /// Enable anti-aliasing.
-    pub anti_aliasing: bool,
+    /// How edges are smoothed. See [`AntiAliasing`].
+    pub anti_aliasing: AntiAliasing,
+    /// Color space frames are presented in. See [`color`].
+    pub color_space: ColorSpace,
     /// Background color (RGBA).
     pub background_color: [f32; 4],
+    /// Repaint only what changed since the last frame, and skip frames
+    /// where nothing did. See [`damage`].
+    pub damage_tracking: bool,
+    /// Find the elements in view through the scene's spatial index before
+    /// rendering, so backends skip offscreen ones entirely. See [`cull`].
+    pub culling: bool,
+    /// Default look of charts; each chart's `data.style` is laid over it.
+    /// See [`chart_style`].
+    pub chart_style: ChartStyle,
+    /// Draw the last frame's [`RenderStats`] over every frame, in the
+    /// top-left corner. See [`RenderStats::overlay_element`].
+    pub debug_overlay: bool,
+    /// Full-screen effects over the frame and chosen overlay layers. See
+    /// [`post`].
+    pub post_effects: PostEffects,
 }
 
 impl Default for RendererConfig {
@@ -69,28 +123,138 @@ impl Default for RendererConfig {
         Self {
             preferred_backend: BackendType::WebGpu,
             target_fps: 60,
-            anti_aliasing: true,
+            anti_aliasing: AntiAliasing::Msaa4,
+            color_space: ColorSpace::Srgb,
             background_color: [1.0, 1.0, 1.0, 1.0], // White
+            damage_tracking: false,
+            culling: true,
+            chart_style: ChartStyle::default(),
+            debug_overlay: false,
+            post_effects: PostEffects::default(),
         }
     }
 }
 
 /// Available rendering backends.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum BackendType {
     /// WebGPU via wgpu (best quality, requires modern GPU).
+    #[serde(rename = "webgpu")]
     WebGpu,
     /// WebGL2 fallback (older GPU support).
+    #[serde(rename = "webgl2")]
     WebGl2,
     /// Pure 2D canvas fallback (no GPU required).
+    #[serde(rename = "canvas2d")]
     Canvas2D,
+    /// SVG document output for vector export (no drawing surface).
+    #[serde(rename = "svg")]
+    Svg,
+    /// CPU rasterizer into an RGBA buffer (no GPU or window system).
+    #[serde(rename = "software")]
+    Software,
+}
+
+impl BackendType {
+    /// Short name used in stats and logs.
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::WebGpu => "webgpu",
+            Self::WebGl2 => "webgl2",
+            Self::Canvas2D => "canvas2d",
+            Self::Svg => "svg",
+            Self::Software => "software",
+        }
+    }
+}
+
+/// How the renderer smooths the edges of what it draws.
+///
+/// Only the wgpu backend acts on this; other backends draw as they always
+/// do. Sample counts the GPU lacks fall back to 4x, which every WebGPU
+/// device supports.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AntiAliasing {
+    /// No smoothing: edges are aliased.
+    Off,
+    /// Multisampling with 2 samples per pixel.
+    Msaa2,
+    /// Multisampling with 4 samples per pixel.
+    #[default]
+    Msaa4,
+    /// Multisampling with 8 samples per pixel.
+    Msaa8,
+    /// Single-sampled, with 2D shapes fading out over one pixel at their
+    /// edges. Cheaper than multisampling, but leaves images, text and chart
+    /// fills as they are.
+    Analytic,
+}
+
+impl AntiAliasing {
+    /// Samples per pixel of the render targets: 1 unless multisampling.
+    #[must_use]
+    pub const fn sample_count(self) -> u32 {
+        match self {
+            Self::Off | Self::Analytic => 1,
+            Self::Msaa2 => 2,
+            Self::Msaa4 => 4,
+            Self::Msaa8 => 8,
+        }
+    }
+
+    /// Whether 2D shapes compute their own edge coverage.
+    #[must_use]
+    pub const fn is_analytic(self) -> bool {
+        matches!(self, Self::Analytic)
+    }
+
+    /// Short name used in stats and logs.
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Off => "off",
+            Self::Msaa2 => "msaa2",
+            Self::Msaa4 => "msaa4",
+            Self::Msaa8 => "msaa8",
+            Self::Analytic => "analytic",
+        }
+    }
+}
+
+/// Monotonic clock in milliseconds, used to time frames.
+pub type FrameClock = fn() -> f64;
+
+/// Default frame clock: `std::time::Instant` on native targets. There is no
+/// portable clock on `wasm32`, so hosts there pass one via
+/// [`Renderer::with_clock`].
+#[cfg(not(target_arch = "wasm32"))]
+fn monotonic_ms() -> f64 {
+    use std::sync::OnceLock;
+    use std::time::Instant;
+
+    static EPOCH: OnceLock<Instant> = OnceLock::new();
+    EPOCH.get_or_init(Instant::now).elapsed().as_secs_f64() * 1000.0
 }
 
+#[cfg(not(target_arch = "wasm32"))]
+const DEFAULT_CLOCK: Option<FrameClock> = Some(monotonic_ms);
+#[cfg(target_arch = "wasm32")]
+const DEFAULT_CLOCK: Option<FrameClock> = None;
+
 /// The main renderer interface.
 pub struct Renderer {
     config: RendererConfig,
     backend: Box<dyn RenderBackend>,
     frame_count: u64,
+    clock: Option<FrameClock>,
+    /// Clock reading at the first frame; `Shader` element time counts from it.
+    epoch: Option<f64>,
+    stats: RenderStats,
+    /// Present when [`RendererConfig::damage_tracking`] is on.
+    damage: Option<DamageTracker>,
+    last_damage: Damage,
 }
 
 impl Renderer {
@@ -98,22 +262,42 @@ impl Renderer {
     ///
     /// # Errors
     ///
-    /// Returns an error if no suitable backend is available.
+    /// Returns an error if no suitable backend is available, or
+    /// [`RenderError::PostEffect`] if the post effects are invalid.
     pub fn new(config: RendererConfig) -> RenderResult<Self> {
+        config.post_effects.validate()?;
         let backend = Self::create_backend(&config)?;
         Ok(Self::with_backend(backend, config))
     }
 
     /// Create a renderer from an explicit backend implementation.
     #[must_use]
-    pub fn with_backend(backend: Box<dyn RenderBackend>, config: RendererConfig) -> Self {
+    pub fn with_backend(mut backend: Box<dyn RenderBackend>, config: RendererConfig) -> Self {
+        backend.set_chart_style(config.chart_style.clone());
+        backend.set_antialiasing(config.anti_aliasing);
+        backend.set_color_space(config.color_space);
+        backend.set_post_effects(&config.post_effects);
+        let stats = RenderStats::new(backend.backend_type());
+        let damage = config.damage_tracking.then(DamageTracker::new);
         Self {
             config,
             backend,
             frame_count: 0,
+            clock: DEFAULT_CLOCK,
+            epoch: None,
+            stats,
+            damage,
+            last_damage: Damage::Full,
         }
     }
 
+    /// Time frames with `clock` instead of the platform default.
+    #[must_use]
+    pub fn with_clock(mut self, clock: FrameClock) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+
     /// Create the appropriate backend based on config and availability.
     fn create_backend(config: &RendererConfig) -> RenderResult<Box<dyn RenderBackend>> {
         match config.preferred_backend {
@@ -134,28 +318,198 @@ impl Renderer {
                 })
             }
             BackendType::WebGl2 => {
-                // TODO: Implement WebGL2 backend
-                tracing::warn!("WebGL2 not yet implemented, falling back to 2D");
+                #[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+                {
+                    match backend::webgl2::WebGl2Backend::new() {
+                        Ok(b) => return Ok(Box::new(b)),
+                        Err(e) => {
+                            tracing::warn!("WebGL2 unavailable, falling back: {}", e);
+                        }
+                    }
+                }
+                // Fall through to next backend
                 Self::create_backend(&RendererConfig {
                     preferred_backend: BackendType::Canvas2D,
                     ..config.clone()
                 })
             }
             BackendType::Canvas2D => Ok(Box::new(backend::canvas2d::Canvas2DBackend::new())),
+            BackendType::Svg => {
+                #[cfg(feature = "export")]
+                {
+                    Ok(Box::new(backend::svg::SvgBackend::new(800, 600)))
+                }
+                #[cfg(not(feature = "export"))]
+                {
+                    tracing::warn!("SVG backend requires the export feature, using Canvas2D");
+                    Self::create_backend(&RendererConfig {
+                        preferred_backend: BackendType::Canvas2D,
+                        ..config.clone()
+                    })
+                }
+            }
+            BackendType::Software => {
+                #[cfg(feature = "export")]
+                {
+                    Ok(Box::new(
+                        backend::software::SoftwareBackend::new(800, 600)
+                            .with_background(Self::background_bytes(config)),
+                    ))
+                }
+                #[cfg(not(feature = "export"))]
+                {
+                    tracing::warn!("Software backend requires the export feature, using Canvas2D");
+                    Self::create_backend(&RendererConfig {
+                        preferred_backend: BackendType::Canvas2D,
+                        ..config.clone()
+                    })
+                }
+            }
         }
     }
 
     /// Render a frame.
     ///
+    /// With [`culling`](RendererConfig::culling) on, elements outside the
+    /// viewport are left out before the backend sees the scene and counted
+    /// in [`FrameStats::elements_culled`].
+    ///
+    /// With [`damage_tracking`](RendererConfig::damage_tracking) on, only
+    /// the regions that changed since the last frame are repainted, and a
+    /// frame with no changes is skipped and counted in
+    /// [`RenderStats::frames_skipped`].
+    ///
+    /// With [`debug_overlay`](RendererConfig::debug_overlay) on, the
+    /// previous frame's statistics are drawn over the scene. The overlay
+    /// changes every frame, so frames are repainted in full and never
+    /// skipped while it shows, and it counts as one more element.
+    ///
     /// # Errors
     ///
     /// Returns an error if rendering fails.
     pub fn render(&mut self, scene: &Scene) -> RenderResult<()> {
-        self.backend.render(scene)?;
+        let damage = self
+            .damage
+            .as_mut()
+            .map_or(Damage::Full, |tracker| tracker.track(scene));
+        let overlaid = self.config.debug_overlay.then(|| {
+            let mut overlaid = scene.clone();
+            overlaid.add_element(self.stats.overlay_element());
+            overlaid
+        });
+        let (scene, damage) = match &overlaid {
+            Some(overlaid) => (overlaid, Damage::Full),
+            None => (scene, damage),
+        };
+        let start = self.clock.map(|clock| clock());
+        if let Some(now) = start {
+            let epoch = *self.epoch.get_or_insert(now);
+            #[allow(clippy::cast_possible_truncation)] // Seconds need no f64 precision
+            self.backend
+                .set_shader_time(((now - epoch) / 1000.0) as f32);
+        }
+        if damage != Damage::None && self.config.culling {
+            self.backend
+                .set_visible_elements(VisibleSet::from_scene(scene));
+        }
+        let result = match &damage {
+            Damage::None => {
+                self.stats.record_skipped();
+                self.last_damage = damage;
+                return Ok(());
+            }
+            Damage::Full => self.backend.render(scene),
+            Damage::Regions(regions) => self.backend.render_damage(scene, regions),
+        };
+        self.last_damage = damage;
+        if let Err(e) = result {
+            // The frame may be half drawn; start over next time
+            self.invalidate();
+            return Err(e);
+        }
+        let frame_time_ms = self
+            .clock
+            .zip(start)
+            .map_or(0.0, |(clock, start)| clock() - start);
+        self.stats.record(self.backend.frame_stats(), frame_time_ms);
+        self.stats.record_gpu_time(self.backend.gpu_time_ms());
         self.frame_count += 1;
         Ok(())
     }
 
+    /// What the most recent [`render`](Self::render) repainted.
+    #[must_use]
+    pub fn last_damage(&self) -> &Damage {
+        &self.last_damage
+    }
+
+    /// Repaint everything next frame, for changes the scene does not track
+    /// (e.g. an image finishing its download). A no-op without damage
+    /// tracking.
+    pub fn invalidate(&mut self) {
+        if let Some(tracker) = &mut self.damage {
+            tracker.invalidate();
+        }
+    }
+
+    /// Switch anti-aliasing mode, taking effect from the next frame, which
+    /// is repainted in full.
+    pub fn set_antialiasing(&mut self, mode: AntiAliasing) {
+        self.config.anti_aliasing = mode;
+        self.backend.set_antialiasing(mode);
+        self.invalidate();
+    }
+
+    /// Present frames in `space` from the next frame on, which is repainted
+    /// in full. Displays without it get the nearest space they have; see
+    /// [`color_space`](Self::color_space).
+    pub fn set_color_space(&mut self, space: ColorSpace) {
+        self.config.color_space = space;
+        self.backend.set_color_space(space);
+        self.invalidate();
+    }
+
+    /// The color space frames are presented in, which may fall short of
+    /// the configured one.
+    #[must_use]
+    pub fn color_space(&self) -> ColorSpace {
+        self.backend.color_space()
+    }
+
+    /// Replace the post-processing effects, taking effect from the next
+    /// frame, which is repainted in full.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RenderError::PostEffect`] if an effect is invalid, keeping
+    /// the current ones.
+    pub fn set_post_effects(&mut self, effects: PostEffects) -> RenderResult<()> {
+        effects.validate()?;
+        self.backend.set_post_effects(&effects);
+        self.config.post_effects = effects;
+        self.invalidate();
+        Ok(())
+    }
+
+    /// Show or hide the statistics overlay, repainting the next frame in
+    /// full. See [`RendererConfig::debug_overlay`].
+    pub fn set_debug_overlay(&mut self, enabled: bool) {
+        self.config.debug_overlay = enabled;
+        self.invalidate();
+    }
+
+    /// Get rendering statistics: CPU and GPU frame times, and the last
+    /// frame's element, draw call, triangle and texture memory counts.
+    #[must_use]
+    pub fn stats(&self) -> &RenderStats {
+        &self.stats
+    }
+
+    /// Reset rendering statistics.
+    pub fn reset_stats(&mut self) {
+        self.stats.reset();
+    }
+
     /// Get the current frame count.
     #[must_use]
     pub fn frame_count(&self) -> u64 {
@@ -180,6 +534,107 @@ impl Renderer {
     ///
     /// Returns an error if resize fails.
     pub fn resize(&mut self, width: u32, height: u32) -> RenderResult<()> {
+        self.invalidate();
         self.backend.resize(width, height)
     }
+
+    /// Render `scene` into a `width` x `height` image without touching the
+    /// window.
+    ///
+    /// GPU backends draw into an offscreen texture and read it back; other
+    /// backends fall back to the CPU rasterizer when the `export` feature is
+    /// enabled. Frame stats and the frame count are left untouched.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if either side is zero or larger than
+    /// [`MAX_SNAPSHOT_SIZE`], if rendering or readback fails, or if the
+    /// backend cannot render offscreen and the `export` feature is disabled.
+    pub fn render_to_image(
+        &mut self,
+        scene: &Scene,
+        width: u32,
+        height: u32,
+    ) -> RenderResult<RgbaImage> {
+        snapshot::check_size(width, height)?;
+        if let Some(image) = self.backend.render_to_image(scene, width, height)? {
+            return Ok(image);
+        }
+        self.rasterize(scene, width, height)
+    }
+
+    /// Render a small preview of `scene` whose longer side is `max_dim`
+    /// pixels, e.g. for a session list.
+    ///
+    /// The preview shows the viewport, grown to take in any content outside
+    /// it, at its own aspect ratio. It is always rasterized on the CPU with
+    /// [`SoftwareBackend`], so previews look the same whichever backend is
+    /// active and the window is left alone. With a `redaction`, sensitive
+    /// elements are hidden that way.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `max_dim` is zero or larger than
+    /// [`MAX_SNAPSHOT_SIZE`], if the preview cannot be rasterized, or if the
+    /// `export` feature is disabled.
+    #[cfg(feature = "export")]
+    pub fn render_thumbnail(
+        &self,
+        scene: &Scene,
+        max_dim: u32,
+        redaction: Option<Redaction>,
+    ) -> RenderResult<RgbaImage> {
+        SoftwareBackend::new(1, 1)
+            .with_background(Self::background_bytes(&self.config))
+            .with_chart_style(self.config.chart_style.clone())
+            .with_redaction(redaction)
+            .render_thumbnail(scene, max_dim)
+    }
+
+    /// Render a small preview of `scene`; needs the `export` feature.
+    ///
+    /// # Errors
+    ///
+    /// Always returns an error: there is no CPU rasterizer in this build.
+    #[cfg(not(feature = "export"))]
+    pub fn render_thumbnail(
+        &self,
+        _scene: &Scene,
+        _max_dim: u32,
+        _redaction: Option<Redaction>,
+    ) -> RenderResult<RgbaImage> {
+        Err(RenderError::Export(
+            "Thumbnails need the `export` feature".to_string(),
+        ))
+    }
+
+    /// The configured background color as RGBA bytes.
+    #[cfg(feature = "export")]
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)] // Clamped to 0..=255
+    fn background_bytes(config: &RendererConfig) -> [u8; 4] {
+        config
+            .background_color
+            .map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8)
+    }
+
+    /// CPU fallback for [`render_to_image`](Self::render_to_image).
+    #[cfg(feature = "export")]
+    fn rasterize(&self, scene: &Scene, width: u32, height: u32) -> RenderResult<RgbaImage> {
+        SceneExporter::new(ExportConfig {
+            width: Some(width),
+            height: Some(height),
+            background: Self::background_bytes(&self.config),
+            ..ExportConfig::default()
+        })
+        .render_to_image(scene)
+    }
+
+    /// CPU fallback for [`render_to_image`](Self::render_to_image).
+    #[cfg(not(feature = "export"))]
+    fn rasterize(&self, _scene: &Scene, _width: u32, _height: u32) -> RenderResult<RgbaImage> {
+        Err(RenderError::Export(format!(
+            "The {} backend cannot render offscreen; enable the `export` feature",
+            self.backend.backend_type().name()
+        )))
+    }
 }
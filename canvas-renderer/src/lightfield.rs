@@ -0,0 +1,344 @@
+//! # Lightfield Compositing
+//!
+//! A Looking Glass display is an ordinary panel under a slanted lenticular
+//! lens: each subpixel is seen from one direction only. Showing a quilt on
+//! it means giving every subpixel the color of the view its lens sends it
+//! towards, which scrambles the quilt into a lightfield image:
+//!
+//! ```text
+//! quilt (views in a grid) ─▶ lens model ─▶ per subpixel: view z ─▶ sample
+//!                                                                    │
+//!            lightfield image (screen size, RGB interleaved) ◀───────┘
+//! ```
+//!
+//! Every device is calibrated at the factory; [`LensCalibration`] holds the
+//! values its `visual.json` records. The wgpu backend composes on the GPU
+//! with `shaders/lightfield.wgsl`; [`compose`] is the same model on the CPU,
+//! for tests and builds without a GPU.
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{RenderError, RenderResult};
+use crate::quilt::QuiltRenderTarget;
+use crate::snapshot::RgbaImage;
+use crate::spatial::HolographicConfig;
+
+/// The lens of one Looking Glass display, in the form the compositor uses.
+///
+/// The view a subpixel shows is
+/// `fract((u + i * subpixel + v * tilt) * pitch - center)`, from 0 for the
+/// first view to 1 past the last, where (`u`, `v`) is the pixel's position
+/// on screen from the bottom left (0 to 1) and `i` the subpixel (0 to 2).
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LensCalibration {
+    /// Lens cycles across the screen width, along the screen's rows.
+    pub pitch: f32,
+    /// Horizontal shift of the lens per screen height, in screen widths.
+    pub tilt: f32,
+    /// Phase of the lens at the left edge, in cycles.
+    pub center: f32,
+    /// Width of one subpixel, in screen widths.
+    pub subpixel: f32,
+    /// Screen width in pixels.
+    pub screen_width: u32,
+    /// Screen height in pixels.
+    pub screen_height: u32,
+    /// Whether the first view is seen from the right rather than the left.
+    pub invert_views: bool,
+    /// Whether the panel's subpixels run blue, green, red.
+    pub bgr: bool,
+    /// Whether to mirror the views horizontally.
+    pub flip_x: bool,
+}
+
+/// One `{ "value": ... }` entry of `visual.json`.
+#[derive(Deserialize)]
+struct Entry {
+    value: f32,
+}
+
+/// The fields of `visual.json` the compositor needs.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct VisualJson {
+    pitch: Entry,
+    slope: Entry,
+    center: Entry,
+    #[serde(rename = "DPI")]
+    dpi: Entry,
+    screen_w: Entry,
+    screen_h: Entry,
+    inv_view: Option<Entry>,
+    flip_image_x: Option<Entry>,
+    flip_subp: Option<Entry>,
+}
+
+impl LensCalibration {
+    /// Derive the calibration from a device's measurements: `pitch` in lens
+    /// cycles per inch across the lens, `slope` in screen pixels down per
+    /// pixel across, `center` in cycles, and the screen's size and DPI.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the screen size or DPI is not positive, or the
+    /// slope is zero.
+    #[allow(
+        clippy::cast_precision_loss,
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss
+    )]
+    pub fn from_measurements(
+        pitch: f32,
+        slope: f32,
+        center: f32,
+        dpi: f32,
+        screen_width: f32,
+        screen_height: f32,
+    ) -> RenderResult<Self> {
+        if !(screen_width >= 1.0 && screen_height >= 1.0 && dpi > 0.0) {
+            return Err(RenderError::Resource(format!(
+                "Invalid lens calibration: {screen_width}x{screen_height} screen at {dpi} DPI"
+            )));
+        }
+        if slope == 0.0 || !slope.is_finite() {
+            return Err(RenderError::Resource(
+                "Invalid lens calibration: the lens slope is zero".to_string(),
+            ));
+        }
+        Ok(Self {
+            // Cycles across the lens become cycles along a row
+            pitch: pitch * screen_width / dpi * (1.0 / slope).atan().cos(),
+            tilt: screen_height / (screen_width * slope),
+            center,
+            subpixel: 1.0 / (3.0 * screen_width),
+            screen_width: screen_width as u32,
+            screen_height: screen_height as u32,
+            invert_views: false,
+            bgr: false,
+            flip_x: false,
+        })
+    }
+
+    /// Read the calibration a Looking Glass device stores in `visual.json`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the document is not a calibration or its values
+    /// are out of range.
+    pub fn from_visual_json(json: &str) -> RenderResult<Self> {
+        let visual: VisualJson = serde_json::from_str(json)
+            .map_err(|e| RenderError::Resource(format!("Invalid lens calibration: {e}")))?;
+        let flag = |entry: Option<Entry>| entry.is_some_and(|entry| entry.value != 0.0);
+        Ok(Self {
+            invert_views: flag(visual.inv_view),
+            flip_x: flag(visual.flip_image_x),
+            bgr: flag(visual.flip_subp),
+            ..Self::from_measurements(
+                visual.pitch.value,
+                visual.slope.value,
+                visual.center.value,
+                visual.dpi.value,
+                visual.screen_w.value,
+                visual.screen_h.value,
+            )?
+        })
+    }
+
+    /// The view, from 0 for the first to 1 past the last, that the lens
+    /// shows subpixel `subpixel` (0 to 2, left to right) of the point
+    /// (`u`, `v`) through, measured from the screen's bottom left.
+    #[must_use]
+    pub fn view_at(&self, u: f32, v: f32, subpixel: u32) -> f32 {
+        #[allow(clippy::cast_precision_loss)] // 0 to 2
+        let offset = subpixel as f32 * self.subpixel;
+        let z = (u + offset + v * self.tilt) * self.pitch - self.center;
+        let z = z - z.floor();
+        if self.invert_views {
+            1.0 - z
+        } else {
+            z
+        }
+    }
+
+    /// The subpixel (0 to 2, left to right) that shows color `channel`
+    /// (0 red, 1 green, 2 blue).
+    #[must_use]
+    pub const fn subpixel_of(&self, channel: u32) -> u32 {
+        if self.bgr {
+            2 - channel
+        } else {
+            channel
+        }
+    }
+}
+
+/// Compose `quilt`, laid out as `layout`, into the lightfield image for a
+/// display with `calibration`, on the CPU.
+///
+/// Each subpixel takes its view's nearest texel; the wgpu backend filters
+/// between texels.
+///
+/// # Errors
+///
+/// Returns an error if the quilt is not `layout`'s size or the screen size
+/// is invalid.
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_precision_loss,
+    clippy::cast_sign_loss
+)]
+pub fn compose(
+    quilt: &QuiltRenderTarget,
+    layout: &HolographicConfig,
+    calibration: &LensCalibration,
+) -> RenderResult<RgbaImage> {
+    let (quilt_width, quilt_height) = (layout.quilt_width(), layout.quilt_height());
+    if quilt.width != quilt_width
+        || quilt.height != quilt_height
+        || quilt.pixels.len() != quilt_width as usize * quilt_height as usize * 4
+        || layout.num_views == 0
+    {
+        return Err(RenderError::Frame(format!(
+            "Quilt is {}x{}, its layout {quilt_width}x{quilt_height}",
+            quilt.width, quilt.height
+        )));
+    }
+
+    let (width, height) = (calibration.screen_width, calibration.screen_height);
+    let mut pixels = Vec::with_capacity(width as usize * height as usize * 4);
+    for y in 0..height {
+        let v = (y as f32 + 0.5) / height as f32;
+        for x in 0..width {
+            let u = (x as f32 + 0.5) / width as f32;
+            let sample_u = if calibration.flip_x { 1.0 - u } else { u };
+            for channel in 0..3 {
+                let z = calibration.view_at(u, 1.0 - v, calibration.subpixel_of(channel));
+                let view = ((z * layout.num_views as f32) as u32).min(layout.num_views - 1);
+                let (x_offset, y_offset) = layout.view_offset(view);
+                let texel_x = x_offset
+                    + ((sample_u * layout.view_width as f32) as u32).min(layout.view_width - 1);
+                let texel_y =
+                    y_offset + ((v * layout.view_height as f32) as u32).min(layout.view_height - 1);
+                let index = (texel_y as usize * quilt_width as usize + texel_x as usize) * 4;
+                pixels.push(quilt.pixels[index + channel as usize]);
+            }
+            pixels.push(255);
+        }
+    }
+    RgbaImage::new(width, height, pixels)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A quilt of `views` views in one row, each filled with its own color.
+    fn striped(views: u32, size: u32) -> (HolographicConfig, QuiltRenderTarget) {
+        let layout = HolographicConfig {
+            num_views: views,
+            quilt_columns: views,
+            quilt_rows: 1,
+            view_width: size,
+            view_height: size,
+            ..HolographicConfig::looking_glass_portrait()
+        };
+        let mut quilt = QuiltRenderTarget::new(layout.quilt_width(), layout.quilt_height());
+        for view in 0..views {
+            let shade = u8::try_from(view * 60).unwrap_or(255);
+            quilt.fill_rect(view * size, 0, size, size, [shade, shade, shade, 255]);
+        }
+        (layout, quilt)
+    }
+
+    fn straight_lens(width: u32) -> LensCalibration {
+        LensCalibration {
+            pitch: 1.0,
+            tilt: 0.0,
+            center: 0.0,
+            subpixel: 0.0,
+            screen_width: width,
+            screen_height: 2,
+            invert_views: false,
+            bgr: false,
+            flip_x: false,
+        }
+    }
+
+    #[test]
+    fn test_visual_json_calibrations_are_derived_for_the_screen() {
+        let json = r#"{
+            "configVersion": "3.0",
+            "serial": "LKG-P00000",
+            "pitch": { "value": 52.58 },
+            "slope": { "value": -7.22 },
+            "center": { "value": 0.08 },
+            "viewCone": { "value": 40.0 },
+            "invView": { "value": 1.0 },
+            "DPI": { "value": 324.0 },
+            "screenW": { "value": 1536.0 },
+            "screenH": { "value": 2048.0 },
+            "flipImageX": { "value": 0.0 },
+            "flipSubp": { "value": 0.0 }
+        }"#;
+        let lens = LensCalibration::from_visual_json(json).expect("calibration");
+        assert_eq!((lens.screen_width, lens.screen_height), (1536, 2048));
+        assert!(lens.invert_views && !lens.bgr && !lens.flip_x);
+        // 4.74 inches of 52.58 cycles, shortened by the slant
+        let expected = 52.58 * 1536.0 / 324.0 * (1.0_f32 / -7.22).atan().cos();
+        assert!((lens.pitch - expected).abs() < 1e-3);
+        assert!((lens.tilt - 2048.0 / (1536.0 * -7.22)).abs() < 1e-6);
+        assert!((lens.subpixel - 1.0 / 4608.0).abs() < 1e-9);
+        assert!((lens.center - 0.08).abs() < f32::EPSILON);
+
+        assert!(LensCalibration::from_visual_json("{}").is_err());
+        assert!(LensCalibration::from_measurements(50.0, 0.0, 0.0, 324.0, 10.0, 10.0).is_err());
+        assert!(LensCalibration::from_measurements(50.0, 7.0, 0.0, 0.0, 10.0, 10.0).is_err());
+    }
+
+    #[test]
+    fn test_views_repeat_along_the_lens() {
+        let lens = LensCalibration {
+            pitch: 2.0,
+            center: 0.25,
+            ..straight_lens(4)
+        };
+        assert!((lens.view_at(0.0, 0.0, 0) - 0.75).abs() < 1e-6);
+        assert!((lens.view_at(0.5, 0.0, 0) - 0.75).abs() < 1e-6);
+        let inverted = LensCalibration {
+            invert_views: true,
+            ..lens
+        };
+        assert!((inverted.view_at(0.0, 0.0, 0) - 0.25).abs() < 1e-6);
+        // A slanted lens moves with height
+        let slanted = LensCalibration { tilt: 0.5, ..lens };
+        assert!((slanted.view_at(0.0, 0.5, 0) - 0.25).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_compose_gives_each_subpixel_its_view() {
+        let (layout, quilt) = striped(4, 2);
+        // One lens cycle across four pixels: each column sees one view
+        let image = compose(&quilt, &layout, &straight_lens(4)).expect("compose");
+        assert_eq!((image.width(), image.height()), (4, 2));
+        for x in 0..4u8 {
+            let shade = x * 60;
+            assert_eq!(
+                image.pixel(u32::from(x), 1),
+                Some([shade, shade, shade, 255])
+            );
+        }
+
+        // Subpixels a third of a pixel apart see neighboring views
+        let lens = LensCalibration {
+            subpixel: 1.0 / 12.0,
+            ..straight_lens(4)
+        };
+        let image = compose(&quilt, &layout, &lens).expect("compose");
+        assert_eq!(image.pixel(1, 0), Some([60, 60, 120, 255]));
+        let bgr = compose(&quilt, &layout, &LensCalibration { bgr: true, ..lens }).expect("bgr");
+        assert_eq!(bgr.pixel(1, 0), Some([120, 60, 60, 255]));
+
+        let (other, _) = striped(2, 2);
+        assert!(compose(&quilt, &other, &lens).is_err());
+    }
+}
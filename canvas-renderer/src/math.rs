@@ -0,0 +1,824 @@
+//! # Math Layout
+//!
+//! Typesets a subset of TeX math into positioned glyph runs and rules that
+//! any backend able to draw text can paint.
+//!
+//! ```text
+//! "\frac{a}{b} + x^2"  →  parse  →  Node tree  →  layout  →  MathLayout
+//!                                                             ├─ GlyphRun "a", "b", "+", "x", "2"
+//!                                                             └─ MathRule (fraction bar)
+//! ```
+//!
+//! Supported: letters, digits and punctuation, `^`/`_` scripts, `{}`
+//! groups, `\frac`, `\sqrt`, `\left`/`\right`, Greek letters, common
+//! operators, relations and arrows, big operators (`\sum`, `\int`, ...),
+//! function names (`\sin`, `\log`, ...), `\text{}`/`\mathrm{}` and spacing
+//! commands. Anything else is an error; callers then show the TeX source in
+//! a monospace font.
+//!
+//! Glyph advances are estimated from the font size rather than measured, so
+//! a formula has the same layout on every backend.
+
+use crate::{RenderError, RenderResult};
+
+/// Ascent of a glyph, as a fraction of the font size.
+const ASCENT: f32 = 0.75;
+/// Descent of a glyph, as a fraction of the font size.
+const DESCENT: f32 = 0.25;
+/// Scripts are drawn at this fraction of their base's size.
+const SCRIPT_SCALE: f32 = 0.7;
+/// Minimum superscript raise, as a fraction of the font size.
+const SUP_SHIFT: f32 = 0.4;
+/// Minimum subscript drop, as a fraction of the font size.
+const SUB_SHIFT: f32 = 0.2;
+/// Height of the math axis (fraction bars), as a fraction of the font size.
+const AXIS: f32 = 0.25;
+/// Rule thickness, as a fraction of the font size.
+const RULE: f32 = 0.05;
+/// Gap between a rule and the content above or below it.
+const RULE_GAP: f32 = 0.12;
+/// Space on each side of binary operators and relations, in ems.
+const OPERATOR_SPACE: f32 = 0.22;
+/// Big operators (`\sum`, `\int`) are drawn this much larger.
+const BIG_OPERATOR_SCALE: f32 = 1.4;
+/// Maximum nesting of groups and command arguments, to bound recursion on
+/// hostile input.
+const MAX_DEPTH: usize = 32;
+/// Longest formula source, in bytes.
+pub const MAX_TEX_LEN: usize = 8 * 1024;
+
+/// A run of glyphs sharing a font size and style.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GlyphRun {
+    /// Text to draw.
+    pub text: String,
+    /// Left edge, relative to the layout's top-left corner.
+    pub x: f32,
+    /// Baseline, relative to the layout's top-left corner (y down).
+    pub y: f32,
+    /// Font size in pixels.
+    pub font_size: f32,
+    /// Whether to use an italic face (math variables).
+    pub italic: bool,
+}
+
+/// A filled rectangle: fraction bars and radical overlines.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MathRule {
+    /// Left edge, relative to the layout's top-left corner.
+    pub x: f32,
+    /// Top edge, relative to the layout's top-left corner (y down).
+    pub y: f32,
+    /// Width in pixels.
+    pub width: f32,
+    /// Thickness in pixels.
+    pub thickness: f32,
+}
+
+/// A typeset formula, positioned from its top-left corner.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MathLayout {
+    /// Glyph runs to draw.
+    pub runs: Vec<GlyphRun>,
+    /// Rules to fill.
+    pub rules: Vec<MathRule>,
+    /// Total width in pixels.
+    pub width: f32,
+    /// Total height in pixels.
+    pub height: f32,
+    /// Distance from the top to the main baseline.
+    pub baseline: f32,
+}
+
+impl MathLayout {
+    /// Typeset `tex` at `font_size` pixels.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RenderError::Math`] if the formula uses unsupported
+    /// commands, has unbalanced braces, is nested too deeply or is longer
+    /// than [`MAX_TEX_LEN`].
+    pub fn new(tex: &str, font_size: f32) -> RenderResult<Self> {
+        if tex.len() > MAX_TEX_LEN {
+            return Err(math_error(format!(
+                "formula is longer than {MAX_TEX_LEN} bytes"
+            )));
+        }
+        let nodes = Parser::new(tex).parse()?;
+        let hbox = layout_list(&nodes, font_size.max(1.0));
+        let baseline = hbox.ascent;
+        Ok(Self {
+            runs: hbox
+                .runs
+                .into_iter()
+                .map(|run| GlyphRun {
+                    y: run.y + baseline,
+                    ..run
+                })
+                .collect(),
+            rules: hbox
+                .rules
+                .into_iter()
+                .map(|rule| MathRule {
+                    y: rule.y + baseline,
+                    ..rule
+                })
+                .collect(),
+            width: hbox.width,
+            height: hbox.ascent + hbox.descent,
+            baseline,
+        })
+    }
+
+    /// Scale that fits the formula inside `width` x `height` without
+    /// enlarging it.
+    #[must_use]
+    pub fn fit_scale(&self, width: f32, height: f32) -> f32 {
+        let mut scale: f32 = 1.0;
+        if self.width > 0.0 {
+            scale = scale.min(width / self.width);
+        }
+        if self.height > 0.0 {
+            scale = scale.min(height / self.height);
+        }
+        scale.max(0.0)
+    }
+}
+
+/// Parsed formula.
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    /// Glyphs drawn as-is.
+    Glyphs { text: String, italic: bool },
+    /// A binary operator or relation, spaced on both sides.
+    Operator(String),
+    /// A big operator such as `\sum`, drawn larger.
+    BigOperator(String),
+    /// Horizontal space in ems (may be negative).
+    Space(f32),
+    /// A braced group.
+    Group(Vec<Node>),
+    /// A base with a superscript and/or subscript.
+    Scripts {
+        base: Box<Node>,
+        sup: Option<Box<Node>>,
+        sub: Option<Box<Node>>,
+    },
+    /// `\frac{numerator}{denominator}`.
+    Fraction(Box<Node>, Box<Node>),
+    /// `\sqrt{radicand}`.
+    Sqrt(Box<Node>),
+}
+
+/// Recursive-descent parser over the TeX source.
+struct Parser<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+    depth: usize,
+}
+
+fn math_error(message: impl Into<String>) -> RenderError {
+    RenderError::Math(message.into())
+}
+
+impl<'a> Parser<'a> {
+    fn new(tex: &'a str) -> Self {
+        Self {
+            chars: tex.chars().peekable(),
+            depth: 0,
+        }
+    }
+
+    fn parse(mut self) -> RenderResult<Vec<Node>> {
+        self.parse_list(false)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.chars.next_if(|c| c.is_whitespace()).is_some() {}
+    }
+
+    /// Run `parse` one level deeper, failing past [`MAX_DEPTH`].
+    fn nested<T>(&mut self, parse: impl FnOnce(&mut Self) -> RenderResult<T>) -> RenderResult<T> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            return Err(math_error("formula is nested too deeply"));
+        }
+        let result = parse(self);
+        self.depth -= 1;
+        result
+    }
+
+    /// Parse nodes until the end of input, or until `}` if `closing`.
+    fn parse_list(&mut self, closing: bool) -> RenderResult<Vec<Node>> {
+        self.nested(|parser| parser.parse_nodes(closing))
+    }
+
+    fn parse_nodes(&mut self, closing: bool) -> RenderResult<Vec<Node>> {
+        let mut nodes = Vec::new();
+        loop {
+            self.skip_whitespace();
+            match self.chars.peek() {
+                None if closing => return Err(math_error("missing closing brace")),
+                None => break,
+                Some('}') if closing => {
+                    self.chars.next();
+                    break;
+                }
+                Some('}') => return Err(math_error("unexpected closing brace")),
+                Some(&marker @ ('^' | '_')) => {
+                    self.chars.next();
+                    let script = Box::new(self.parse_argument()?);
+                    let base = nodes.pop().unwrap_or(Node::Group(Vec::new()));
+                    nodes.push(Self::attach_script(base, marker == '^', script)?);
+                }
+                Some(_) => nodes.push(self.parse_atom()?),
+            }
+        }
+        Ok(nodes)
+    }
+
+    /// Attach a superscript or subscript to `base`.
+    fn attach_script(base: Node, is_sup: bool, script: Box<Node>) -> RenderResult<Node> {
+        let (base, mut sup, mut sub) = match base {
+            Node::Scripts { base, sup, sub } => (base, sup, sub),
+            other => (Box::new(other), None, None),
+        };
+        let slot = if is_sup { &mut sup } else { &mut sub };
+        if slot.is_some() {
+            return Err(math_error(if is_sup {
+                "double superscript"
+            } else {
+                "double subscript"
+            }));
+        }
+        *slot = Some(script);
+        Ok(Node::Scripts { base, sup, sub })
+    }
+
+    /// Parse a command or script argument: a group or a single atom.
+    fn parse_argument(&mut self) -> RenderResult<Node> {
+        // Arguments nest without braces too, as in \sqrt\sqrt\sqrt x
+        self.nested(Self::parse_bare_argument)
+    }
+
+    fn parse_bare_argument(&mut self) -> RenderResult<Node> {
+        self.skip_whitespace();
+        match self.chars.peek() {
+            None | Some('}') => Err(math_error("missing argument")),
+            Some('{') => {
+                self.chars.next();
+                Ok(Node::Group(self.parse_list(true)?))
+            }
+            Some(&c) if c.is_ascii_digit() => {
+                // A bare digit argument is one digit: x^23 is x² followed by 3
+                self.chars.next();
+                Ok(Node::Glyphs {
+                    text: c.to_string(),
+                    italic: false,
+                })
+            }
+            Some(_) => self.parse_atom(),
+        }
+    }
+
+    fn parse_atom(&mut self) -> RenderResult<Node> {
+        let Some(c) = self.chars.next() else {
+            return Err(math_error("unexpected end of formula"));
+        };
+        Ok(match c {
+            '{' => Node::Group(self.parse_list(true)?),
+            '\\' => self.parse_command()?,
+            c if c.is_ascii_alphabetic() => Node::Glyphs {
+                text: c.to_string(),
+                italic: true,
+            },
+            c if c.is_ascii_digit() || c == '.' => {
+                let mut text = c.to_string();
+                while let Some(next) = self.chars.next_if(|n| n.is_ascii_digit() || *n == '.') {
+                    text.push(next);
+                }
+                Node::Glyphs {
+                    text,
+                    italic: false,
+                }
+            }
+            '+' | '=' | '<' | '>' => Node::Operator(c.to_string()),
+            '-' => Node::Operator("\u{2212}".to_string()),
+            '*' => Node::Operator("\u{2217}".to_string()),
+            '&' | '#' | '$' | '%' | '~' => {
+                return Err(math_error(format!("unsupported character '{c}'")));
+            }
+            c => Node::Glyphs {
+                text: c.to_string(),
+                italic: false,
+            },
+        })
+    }
+
+    /// Parse the command after a backslash.
+    fn parse_command(&mut self) -> RenderResult<Node> {
+        let mut name = String::new();
+        while let Some(c) = self.chars.next_if(char::is_ascii_alphabetic) {
+            name.push(c);
+        }
+        if name.is_empty() {
+            // Control symbol: a single non-letter character
+            let Some(c) = self.chars.next() else {
+                return Err(math_error("trailing backslash"));
+            };
+            return match c {
+                ',' => Ok(Node::Space(0.17)),
+                ':' | '>' => Ok(Node::Space(0.22)),
+                ';' => Ok(Node::Space(0.28)),
+                '!' => Ok(Node::Space(-0.17)),
+                ' ' => Ok(Node::Space(0.25)),
+                '{' | '}' | '|' | '%' | '$' | '#' | '&' | '_' => Ok(Node::Glyphs {
+                    text: c.to_string(),
+                    italic: false,
+                }),
+                c => Err(math_error(format!("unsupported command \\{c}"))),
+            };
+        }
+
+        match name.as_str() {
+            "frac" | "dfrac" | "tfrac" => {
+                let numerator = self.parse_argument()?;
+                let denominator = self.parse_argument()?;
+                Ok(Node::Fraction(Box::new(numerator), Box::new(denominator)))
+            }
+            "sqrt" => Ok(Node::Sqrt(Box::new(self.parse_argument()?))),
+            "text" | "mathrm" | "operatorname" => Ok(Node::Glyphs {
+                text: self.parse_raw_group()?,
+                italic: false,
+            }),
+            "mathit" => Ok(Node::Glyphs {
+                text: self.parse_raw_group()?,
+                italic: true,
+            }),
+            "left" | "right" => self.parse_delimiter(),
+            "quad" => Ok(Node::Space(1.0)),
+            "qquad" => Ok(Node::Space(2.0)),
+            name if FUNCTIONS.contains(&name) => Ok(Node::Group(vec![
+                Node::Glyphs {
+                    text: name.to_string(),
+                    italic: false,
+                },
+                Node::Space(0.17),
+            ])),
+            name => symbol(name).ok_or_else(|| math_error(format!("unsupported command \\{name}"))),
+        }
+    }
+
+    /// Read a braced group verbatim (for `\text{...}`).
+    fn parse_raw_group(&mut self) -> RenderResult<String> {
+        self.skip_whitespace();
+        if self.chars.next() != Some('{') {
+            return Err(math_error("expected '{'"));
+        }
+        let mut text = String::new();
+        let mut depth = 0usize;
+        loop {
+            match self.chars.next() {
+                None => return Err(math_error("missing closing brace")),
+                Some('}') if depth == 0 => return Ok(text),
+                Some(c) => {
+                    match c {
+                        '{' => depth += 1,
+                        '}' => depth -= 1,
+                        _ => {}
+                    }
+                    text.push(c);
+                }
+            }
+        }
+    }
+
+    /// Parse the delimiter after `\left` or `\right`.
+    fn parse_delimiter(&mut self) -> RenderResult<Node> {
+        self.skip_whitespace();
+        let text = match self.chars.next() {
+            Some('.') => return Ok(Node::Group(Vec::new())),
+            Some('\\') => match self.chars.next() {
+                Some(c @ ('{' | '}' | '|')) => c.to_string(),
+                _ => return Err(math_error("unsupported delimiter")),
+            },
+            Some(c @ ('(' | ')' | '[' | ']' | '|' | '/')) => c.to_string(),
+            _ => return Err(math_error("missing delimiter")),
+        };
+        Ok(Node::Glyphs {
+            text,
+            italic: false,
+        })
+    }
+}
+
+/// Function names set upright, as `\sin` or `\log`.
+const FUNCTIONS: &[&str] = &[
+    "sin", "cos", "tan", "cot", "sec", "csc", "arcsin", "arccos", "arctan", "sinh", "cosh", "tanh",
+    "log", "ln", "lg", "exp", "lim", "max", "min", "sup", "inf", "det", "gcd", "deg", "dim", "ker",
+    "arg",
+];
+
+/// Greek letters and symbols by command name.
+#[allow(clippy::too_many_lines)]
+fn symbol(name: &str) -> Option<Node> {
+    let letter = |c: char, italic: bool| Node::Glyphs {
+        text: c.to_string(),
+        italic,
+    };
+    let operator = |c: char| Node::Operator(c.to_string());
+    let big = |c: char| Node::BigOperator(c.to_string());
+    Some(match name {
+        // Lowercase Greek is italic, like Latin variables
+        "alpha" => letter('α', true),
+        "beta" => letter('β', true),
+        "gamma" => letter('γ', true),
+        "delta" => letter('δ', true),
+        "epsilon" | "varepsilon" => letter('ε', true),
+        "zeta" => letter('ζ', true),
+        "eta" => letter('η', true),
+        "theta" | "vartheta" => letter('θ', true),
+        "iota" => letter('ι', true),
+        "kappa" => letter('κ', true),
+        "lambda" => letter('λ', true),
+        "mu" => letter('μ', true),
+        "nu" => letter('ν', true),
+        "xi" => letter('ξ', true),
+        "pi" => letter('π', true),
+        "rho" => letter('ρ', true),
+        "sigma" => letter('σ', true),
+        "tau" => letter('τ', true),
+        "upsilon" => letter('υ', true),
+        "phi" | "varphi" => letter('φ', true),
+        "chi" => letter('χ', true),
+        "psi" => letter('ψ', true),
+        "omega" => letter('ω', true),
+        // Uppercase Greek is upright
+        "Gamma" => letter('Γ', false),
+        "Delta" => letter('Δ', false),
+        "Theta" => letter('Θ', false),
+        "Lambda" => letter('Λ', false),
+        "Xi" => letter('Ξ', false),
+        "Pi" => letter('Π', false),
+        "Sigma" => letter('Σ', false),
+        "Upsilon" => letter('Υ', false),
+        "Phi" => letter('Φ', false),
+        "Psi" => letter('Ψ', false),
+        "Omega" => letter('Ω', false),
+        // Ordinary symbols
+        "infty" => letter('∞', false),
+        "partial" => letter('∂', false),
+        "nabla" => letter('∇', false),
+        "hbar" => letter('ℏ', false),
+        "ell" => letter('ℓ', false),
+        "emptyset" => letter('∅', false),
+        "forall" => letter('∀', false),
+        "exists" => letter('∃', false),
+        "neg" => letter('¬', false),
+        "prime" => letter('′', false),
+        "angle" => letter('∠', false),
+        "degree" => letter('°', false),
+        "ldots" | "dots" => letter('…', false),
+        "cdots" => letter('⋯', false),
+        "langle" => letter('⟨', false),
+        "rangle" => letter('⟩', false),
+        "lfloor" => letter('⌊', false),
+        "rfloor" => letter('⌋', false),
+        "lceil" => letter('⌈', false),
+        "rceil" => letter('⌉', false),
+        // Binary operators and relations
+        "pm" => operator('±'),
+        "mp" => operator('∓'),
+        "times" => operator('×'),
+        "div" => operator('÷'),
+        "cdot" => operator('·'),
+        "circ" => operator('∘'),
+        "cup" => operator('∪'),
+        "cap" => operator('∩'),
+        "wedge" | "land" => operator('∧'),
+        "vee" | "lor" => operator('∨'),
+        "oplus" => operator('⊕'),
+        "otimes" => operator('⊗'),
+        "leq" | "le" => operator('≤'),
+        "geq" | "ge" => operator('≥'),
+        "neq" | "ne" => operator('≠'),
+        "approx" => operator('≈'),
+        "equiv" => operator('≡'),
+        "sim" => operator('∼'),
+        "simeq" => operator('≃'),
+        "cong" => operator('≅'),
+        "propto" => operator('∝'),
+        "ll" => operator('≪'),
+        "gg" => operator('≫'),
+        "in" => operator('∈'),
+        "notin" => operator('∉'),
+        "ni" => operator('∋'),
+        "subset" => operator('⊂'),
+        "subseteq" => operator('⊆'),
+        "supset" => operator('⊃'),
+        "supseteq" => operator('⊇'),
+        "perp" => operator('⊥'),
+        "parallel" => operator('∥'),
+        "mid" => operator('∣'),
+        "to" | "rightarrow" => operator('→'),
+        "leftarrow" | "gets" => operator('←'),
+        "leftrightarrow" => operator('↔'),
+        "Rightarrow" | "implies" => operator('⇒'),
+        "Leftarrow" => operator('⇐'),
+        "Leftrightarrow" | "iff" => operator('⇔'),
+        "mapsto" => operator('↦'),
+        // Big operators
+        "sum" => big('∑'),
+        "prod" => big('∏'),
+        "coprod" => big('∐'),
+        "int" => big('∫'),
+        "iint" => big('∬'),
+        "iiint" => big('∭'),
+        "oint" => big('∮'),
+        "bigcup" => big('⋃'),
+        "bigcap" => big('⋂'),
+        _ => return None,
+    })
+}
+
+/// Estimated advance width of a glyph, as a fraction of the font size.
+fn advance(c: char) -> f32 {
+    match c {
+        'i' | 'j' | 'l' | '.' | ',' | ';' | ':' | '!' | '|' | '\'' | '′' => 0.28,
+        'f' | 't' | 'r' | '(' | ')' | '[' | ']' | '{' | '}' | '/' => 0.36,
+        'm' | 'w' => 0.8,
+        'M' | 'W' => 0.95,
+        ' ' => 0.25,
+        c if c.is_ascii_uppercase() => 0.68,
+        c if c.is_ascii_alphanumeric() => 0.52,
+        _ => 0.65,
+    }
+}
+
+/// A laid-out piece of a formula. Positions are relative to the box's
+/// left edge and baseline (y down, so content above the baseline has
+/// negative y).
+#[derive(Debug, Default)]
+struct MathBox {
+    width: f32,
+    ascent: f32,
+    descent: f32,
+    runs: Vec<GlyphRun>,
+    rules: Vec<MathRule>,
+}
+
+impl MathBox {
+    fn glyphs(text: &str, size: f32, italic: bool) -> Self {
+        Self {
+            width: text.chars().map(advance).sum::<f32>() * size,
+            ascent: ASCENT * size,
+            descent: DESCENT * size,
+            runs: vec![GlyphRun {
+                text: text.to_string(),
+                x: 0.0,
+                y: 0.0,
+                font_size: size,
+                italic,
+            }],
+            rules: Vec::new(),
+        }
+    }
+
+    fn space(width: f32) -> Self {
+        Self {
+            width,
+            ..Self::default()
+        }
+    }
+
+    /// Place `other` with its origin at (`dx`, `dy`) from this box's origin.
+    fn place(&mut self, other: Self, dx: f32, dy: f32) {
+        self.ascent = self.ascent.max(other.ascent - dy);
+        self.descent = self.descent.max(other.descent + dy);
+        self.width = self.width.max(dx + other.width);
+        self.runs.extend(other.runs.into_iter().map(|run| GlyphRun {
+            x: run.x + dx,
+            y: run.y + dy,
+            ..run
+        }));
+        self.rules
+            .extend(other.rules.into_iter().map(|rule| MathRule {
+                x: rule.x + dx,
+                y: rule.y + dy,
+                ..rule
+            }));
+    }
+
+    /// Append `other` on the baseline after this box's content.
+    fn append(&mut self, other: Self) {
+        let dx = self.width;
+        self.place(other, dx, 0.0);
+    }
+
+    fn rule(&mut self, x: f32, y: f32, width: f32, thickness: f32) {
+        self.rules.push(MathRule {
+            x,
+            y,
+            width,
+            thickness,
+        });
+    }
+}
+
+fn layout_list(nodes: &[Node], size: f32) -> MathBox {
+    let mut hbox = MathBox::default();
+    for node in nodes {
+        hbox.append(layout_node(node, size));
+    }
+    hbox
+}
+
+fn layout_node(node: &Node, size: f32) -> MathBox {
+    match node {
+        Node::Glyphs { text, italic } => MathBox::glyphs(text, size, *italic),
+        Node::Operator(text) => {
+            let mut hbox = MathBox::space(OPERATOR_SPACE * size);
+            hbox.append(MathBox::glyphs(text, size, false));
+            hbox.append(MathBox::space(OPERATOR_SPACE * size));
+            hbox
+        }
+        Node::BigOperator(text) => {
+            // Larger glyph, lowered so it is centered on the math axis
+            let big = size * BIG_OPERATOR_SCALE;
+            let mut hbox = MathBox::default();
+            hbox.place(
+                MathBox::glyphs(text, big, false),
+                0.0,
+                (ASCENT - DESCENT) / 2.0 * big - AXIS * size,
+            );
+            hbox.append(MathBox::space(0.1 * size));
+            hbox
+        }
+        Node::Space(ems) => MathBox::space(ems * size),
+        Node::Group(nodes) => layout_list(nodes, size),
+        Node::Scripts { base, sup, sub } => {
+            let mut hbox = layout_node(base, size);
+            let x = hbox.width;
+            let script_size = size * SCRIPT_SCALE;
+            if let Some(sup) = sup {
+                let sup = layout_node(sup, script_size);
+                let raise = (hbox.ascent - 0.3 * size).max(SUP_SHIFT * size);
+                hbox.place(sup, x, -raise);
+            }
+            if let Some(sub) = sub {
+                let sub = layout_node(sub, script_size);
+                let drop = hbox.descent.max(SUB_SHIFT * size);
+                hbox.place(sub, x, drop);
+            }
+            hbox
+        }
+        Node::Fraction(numerator, denominator) => {
+            let numerator = layout_node(numerator, size);
+            let denominator = layout_node(denominator, size);
+            let thickness = (RULE * size).max(1.0);
+            let gap = RULE_GAP * size;
+            let padding = 0.1 * size;
+            let width = numerator.width.max(denominator.width) + 2.0 * padding;
+
+            // Bar centered on the axis; numerator above, denominator below
+            let bar_top = -AXIS * size - thickness / 2.0;
+            let above = (
+                (width - numerator.width) / 2.0,
+                bar_top - gap - numerator.descent,
+            );
+            let below = (
+                (width - denominator.width) / 2.0,
+                bar_top + thickness + gap + denominator.ascent,
+            );
+
+            let mut hbox = MathBox::default();
+            hbox.place(numerator, above.0, above.1);
+            hbox.place(denominator, below.0, below.1);
+            hbox.rule(0.0, bar_top, width, thickness);
+            hbox.width = width + 0.1 * size;
+            hbox
+        }
+        Node::Sqrt(radicand) => {
+            let radicand = layout_node(radicand, size);
+            let thickness = (RULE * size).max(1.0);
+            let gap = RULE_GAP * size;
+
+            // Scale the radical sign to span the radicand plus the overline
+            let inner = radicand.ascent + radicand.descent + gap + thickness;
+            let sign_size = (inner / (ASCENT + DESCENT)).max(size);
+            let sign = MathBox::glyphs("\u{221a}", sign_size, false);
+            let sign_width = sign.width;
+            let sign_dy = radicand.descent - DESCENT * sign_size;
+
+            let overline_top = -(radicand.ascent + gap + thickness);
+            let radicand_width = radicand.width;
+            let mut hbox = MathBox::default();
+            hbox.place(sign, 0.0, sign_dy);
+            hbox.place(radicand, sign_width, 0.0);
+            hbox.rule(
+                sign_width,
+                overline_top,
+                radicand_width + 0.1 * size,
+                thickness,
+            );
+            hbox.width += 0.1 * size;
+            hbox
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn layout(tex: &str) -> MathLayout {
+        MathLayout::new(tex, 20.0).expect("formula should lay out")
+    }
+
+    fn run<'a>(layout: &'a MathLayout, text: &str) -> &'a GlyphRun {
+        layout
+            .runs
+            .iter()
+            .find(|run| run.text == text)
+            .unwrap_or_else(|| panic!("no run {text:?}"))
+    }
+
+    #[test]
+    fn test_superscript_is_smaller_and_raised() {
+        let layout = layout("x^2");
+        let base = run(&layout, "x");
+        let sup = run(&layout, "2");
+
+        assert!(base.italic);
+        assert!(sup.font_size < base.font_size);
+        assert!(sup.y < base.y);
+        assert!(sup.x >= base.x + 0.5 * base.font_size);
+        assert!((layout.baseline - base.y).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn test_fraction_stacks_around_bar() {
+        let layout = layout(r"\frac{a}{b}");
+        assert_eq!(layout.rules.len(), 1);
+        let bar = layout.rules[0];
+        assert!(run(&layout, "a").y < bar.y);
+        assert!(run(&layout, "b").y > bar.y + bar.thickness);
+        assert!(layout.height > 40.0);
+    }
+
+    #[test]
+    fn test_symbols_and_functions() {
+        let layout = layout(r"\sin\theta \leq \sqrt{\alpha + 1}");
+        assert!(!run(&layout, "sin").italic);
+        assert!(run(&layout, "θ").italic);
+        run(&layout, "≤");
+        run(&layout, "\u{221a}");
+        assert_eq!(layout.rules.len(), 1);
+    }
+
+    #[test]
+    fn test_runs_fit_inside_layout() {
+        let layout = layout(r"\sum_{i=1}^{n} \frac{x_i^2}{\sqrt{n}} - \left( \int_0^1 f \right)");
+        for run in &layout.runs {
+            assert!(run.x >= 0.0 && run.x <= layout.width, "{run:?}");
+            assert!(run.y >= 0.0 && run.y <= layout.height, "{run:?}");
+        }
+        for rule in &layout.rules {
+            assert!(rule.y >= 0.0 && rule.y + rule.thickness <= layout.height + 1e-3);
+        }
+    }
+
+    #[test]
+    fn test_invalid_formulas_are_errors() {
+        for tex in [r"\frac{a}", "x^", "{a", "a}", r"\unknown", "x^2^3", "a & b"] {
+            assert!(
+                matches!(MathLayout::new(tex, 20.0), Err(RenderError::Math(_))),
+                "{tex} should fail"
+            );
+        }
+        let deep = "{".repeat(100) + &"}".repeat(100);
+        assert!(MathLayout::new(&deep, 20.0).is_err());
+        let sqrts = r"\sqrt".repeat(3000) + " x";
+        assert!(matches!(
+            MathLayout::new(&sqrts, 20.0),
+            Err(RenderError::Math(_))
+        ));
+        let fracs = r"\frac".repeat(1500) + " ab";
+        assert!(matches!(
+            MathLayout::new(&fracs, 20.0),
+            Err(RenderError::Math(_))
+        ));
+        let scripts = "x^{".repeat(1000) + "y" + &"}".repeat(1000);
+        assert!(MathLayout::new(&scripts, 20.0).is_err());
+        // Shallow nesting still works
+        assert!(MathLayout::new(&(r"\sqrt".repeat(10) + " x"), 20.0).is_ok());
+        assert!(MathLayout::new(&"x".repeat(MAX_TEX_LEN + 1), 20.0).is_err());
+    }
+
+    #[test]
+    fn test_fit_scale_only_shrinks() {
+        let layout = layout("x + y");
+        assert!((layout.fit_scale(1000.0, 1000.0) - 1.0).abs() < f32::EPSILON);
+        let half = layout.fit_scale(layout.width / 2.0, 1000.0);
+        assert!((half - 0.5).abs() < 1e-4);
+    }
+}
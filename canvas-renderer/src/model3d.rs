@@ -0,0 +1,1042 @@
+//! # glTF Models
+//!
+//! Loads the glTF 2.0 files `Model3D` elements point at, as `.gltf` JSON
+//! with embedded or neighbouring buffers or as binary `.glb`, into flat
+//! triangle meshes the GPU backend can upload as they are.
+//!
+//! ```text
+//!   src ──▶ ModelLoader::request ──▶ Loading ──▶ Ready(Model)
+//!            │ data: / file            (parsed off the     └▶ Failed(reason)
+//!            │                          render thread)
+//!            └ http(s): / asset: ──▶ pending ──▶ ModelLoader::provide(bytes)
+//! ```
+//!
+//! Parsing covers what a model viewer needs: triangle meshes (lists, strips
+//! and fans), the node hierarchy, whose transforms are baked into the
+//! vertices, and the factors of the metallic-roughness material. Textures,
+//! skins, morph targets and animations are ignored.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use base64::Engine;
+use canvas_core::model3d::Transform3D;
+use serde::Deserialize;
+
+use crate::spatial::Mat4;
+use crate::{RenderError, RenderResult};
+
+/// First four bytes of a binary glTF file.
+const GLB_MAGIC: &[u8; 4] = b"glTF";
+
+/// GLB chunk holding the JSON document.
+const GLB_CHUNK_JSON: u32 = 0x4E4F_534A;
+
+/// GLB chunk holding the binary buffer.
+const GLB_CHUNK_BIN: u32 = 0x004E_4942;
+
+/// Most vertices a model may have, to keep a hostile file from exhausting
+/// memory.
+pub const MAX_MODEL_VERTICES: usize = 4_000_000;
+
+/// Surface properties of a primitive, from glTF's metallic-roughness model.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Material {
+    /// Linear RGBA base color.
+    pub base_color: [f32; 4],
+    /// How metallic the surface is, from 0.0 to 1.0.
+    pub metallic: f32,
+    /// How rough the surface is, from 0.0 (mirror) to 1.0.
+    pub roughness: f32,
+    /// Linear RGB light the surface gives off.
+    pub emissive: [f32; 3],
+}
+
+impl Default for Material {
+    /// glTF's default material: white, fully metallic and fully rough.
+    fn default() -> Self {
+        Self {
+            base_color: [1.0; 4],
+            metallic: 1.0,
+            roughness: 1.0,
+            emissive: [0.0; 3],
+        }
+    }
+}
+
+/// A triangle mesh in model space, with its node transforms applied.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Primitive {
+    /// Vertex positions.
+    pub positions: Vec<[f32; 3]>,
+    /// Unit vertex normals, one per position.
+    pub normals: Vec<[f32; 3]>,
+    /// Triangle list indices into `positions`.
+    pub indices: Vec<u32>,
+    /// How the surface is shaded.
+    pub material: Material,
+}
+
+/// A parsed glTF model.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Model {
+    /// Meshes of every node in the default scene.
+    pub primitives: Vec<Primitive>,
+    /// Smallest corner of the axis-aligned bounding box.
+    pub min: [f32; 3],
+    /// Largest corner of the axis-aligned bounding box.
+    pub max: [f32; 3],
+}
+
+impl Model {
+    /// Parse a `.glb` file or `.gltf` document. Buffers given as relative
+    /// URIs are read from `base`, the directory the document came from.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RenderError::Resource`] if the file is malformed, refers to
+    /// data it does not contain, or has no triangles.
+    pub fn parse(bytes: &[u8], base: Option<&Path>) -> RenderResult<Self> {
+        let (json, bin) = if bytes.starts_with(GLB_MAGIC) {
+            split_glb(bytes)?
+        } else {
+            (bytes, None)
+        };
+        let document: Document = serde_json::from_slice(json)
+            .map_err(|e| RenderError::Resource(format!("Invalid glTF JSON: {e}")))?;
+        let buffers = document
+            .buffers
+            .iter()
+            .enumerate()
+            .map(|(i, buffer)| load_buffer(buffer, i, bin, base))
+            .collect::<RenderResult<Vec<_>>>()?;
+        let reader = Reader {
+            document: &document,
+            buffers: &buffers,
+        };
+        reader.model()
+    }
+
+    /// Center and radius of the sphere around the bounding box.
+    #[must_use]
+    pub fn bounding_sphere(&self) -> ([f32; 3], f32) {
+        let center = std::array::from_fn(|axis| f32::midpoint(self.min[axis], self.max[axis]));
+        let radius = length(sub(self.max, center)).max(f32::EPSILON);
+        (center, radius)
+    }
+
+    /// Number of triangles across all primitives.
+    #[must_use]
+    pub fn triangle_count(&self) -> usize {
+        self.primitives.iter().map(|p| p.indices.len() / 3).sum()
+    }
+}
+
+/// Loading progress of a model source.
+#[derive(Debug, Clone)]
+pub enum ModelState {
+    /// Being read or parsed, or waiting for the host to supply its bytes.
+    Loading,
+    /// Parsed and ready to draw.
+    Ready(Arc<Model>),
+    /// Could not be loaded; the reason is shown in logs and to callers.
+    Failed(String),
+}
+
+impl ModelState {
+    /// The model, once it is ready.
+    #[must_use]
+    pub fn model(&self) -> Option<&Arc<Model>> {
+        match self {
+            Self::Ready(model) => Some(model),
+            _ => None,
+        }
+    }
+}
+
+/// Loads models by source, parsing them off the calling thread.
+///
+/// Inline `data:` URIs and local paths are read straight away. Network URLs
+/// and `asset:` references are not fetched: they wait in [`Self::pending`]
+/// until the host hands their bytes to [`Self::provide`].
+#[derive(Debug)]
+pub struct ModelLoader {
+    states: HashMap<String, ModelState>,
+    pending: HashSet<String>,
+    #[cfg(not(target_arch = "wasm32"))]
+    sender: std::sync::mpsc::Sender<(String, ModelState)>,
+    #[cfg(not(target_arch = "wasm32"))]
+    receiver: std::sync::mpsc::Receiver<(String, ModelState)>,
+}
+
+impl Default for ModelLoader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ModelLoader {
+    /// Create an empty loader.
+    #[must_use]
+    pub fn new() -> Self {
+        #[cfg(not(target_arch = "wasm32"))]
+        let (sender, receiver) = std::sync::mpsc::channel();
+        Self {
+            states: HashMap::new(),
+            pending: HashSet::new(),
+            #[cfg(not(target_arch = "wasm32"))]
+            sender,
+            #[cfg(not(target_arch = "wasm32"))]
+            receiver,
+        }
+    }
+
+    /// Start loading `src` unless it is already known, and return its state.
+    pub fn request(&mut self, src: &str) -> &ModelState {
+        if !self.states.contains_key(src) {
+            self.states.insert(src.to_string(), ModelState::Loading);
+            if let Some(path) = local_path(src) {
+                let base = path.parent().map(Path::to_path_buf);
+                self.spawn(src, move || {
+                    let bytes = std::fs::read(&path).map_err(|e| {
+                        RenderError::Resource(format!("Failed to read {}: {e}", path.display()))
+                    })?;
+                    Model::parse(&bytes, base.as_deref())
+                });
+            } else if src.starts_with("data:") {
+                let uri = src.to_string();
+                self.spawn(src, move || Model::parse(&decode_data_uri(&uri)?, None));
+            } else {
+                self.pending.insert(src.to_string());
+            }
+        }
+        self.states.get(src).unwrap_or(&ModelState::Loading)
+    }
+
+    /// Supply the bytes of a remote source and start parsing them.
+    pub fn provide(&mut self, src: &str, bytes: Vec<u8>) {
+        self.pending.remove(src);
+        self.states.insert(src.to_string(), ModelState::Loading);
+        self.spawn(src, move || Model::parse(&bytes, None));
+    }
+
+    /// Collect finished loads. Returns whether any model changed state.
+    pub fn poll(&mut self) -> bool {
+        let mut changed = false;
+        #[cfg(not(target_arch = "wasm32"))]
+        while let Ok((src, state)) = self.receiver.try_recv() {
+            // A source forgotten while it loaded stays forgotten
+            if let Some(slot) = self.states.get_mut(&src) {
+                *slot = state;
+                changed = true;
+            }
+        }
+        changed
+    }
+
+    /// The state of `src`, if it has been requested.
+    #[must_use]
+    pub fn state(&self, src: &str) -> Option<&ModelState> {
+        self.states.get(src)
+    }
+
+    /// Remote sources waiting for [`Self::provide`].
+    pub fn pending(&self) -> impl Iterator<Item = &str> {
+        self.pending.iter().map(String::as_str)
+    }
+
+    /// Drop `src`, so the next request loads it again.
+    pub fn forget(&mut self, src: &str) {
+        self.states.remove(src);
+        self.pending.remove(src);
+    }
+
+    /// Run `load` for `src` on a worker thread (inline on wasm, which has
+    /// no threads) and record its outcome.
+    fn spawn(&mut self, src: &str, load: impl FnOnce() -> RenderResult<Model> + Send + 'static) {
+        let finish = |result: RenderResult<Model>| match result {
+            Ok(model) => ModelState::Ready(Arc::new(model)),
+            Err(e) => ModelState::Failed(e.to_string()),
+        };
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let sender = self.sender.clone();
+            let key = src.to_string();
+            let spawned = std::thread::Builder::new()
+                .name("model-loader".to_string())
+                .spawn(move || {
+                    // The loader may be gone by the time parsing finishes
+                    let _ = sender.send((key, finish(load())));
+                });
+            if let Err(e) = spawned {
+                self.states
+                    .insert(src.to_string(), ModelState::Failed(e.to_string()));
+            }
+        }
+        #[cfg(target_arch = "wasm32")]
+        self.states.insert(src.to_string(), finish(load()));
+    }
+}
+
+/// Local file path of a source: a plain path or a `file://` URL.
+fn local_path(src: &str) -> Option<PathBuf> {
+    if let Some(path) = src.strip_prefix("file://") {
+        Some(PathBuf::from(path))
+    } else if src.starts_with("data:") || src.starts_with("asset:") || src.contains("://") {
+        None
+    } else {
+        Some(PathBuf::from(src))
+    }
+}
+
+/// Decode a base64 `data:` URI, the only form glTF embeds buffers in.
+fn decode_data_uri(uri: &str) -> RenderResult<Vec<u8>> {
+    let (meta, data) = uri
+        .strip_prefix("data:")
+        .and_then(|rest| rest.split_once(','))
+        .ok_or_else(|| RenderError::Resource("Invalid data URI".to_string()))?;
+    if !meta.ends_with(";base64") {
+        return Err(RenderError::Resource(
+            "glTF data URIs must be base64 encoded".to_string(),
+        ));
+    }
+    base64::engine::general_purpose::STANDARD
+        .decode(data)
+        .map_err(|e| RenderError::Resource(format!("Failed to decode base64: {e}")))
+}
+
+/// Split a GLB file into its JSON chunk and optional binary chunk.
+fn split_glb(bytes: &[u8]) -> RenderResult<(&[u8], Option<&[u8]>)> {
+    let word = |at: usize| {
+        bytes
+            .get(at..at + 4)
+            .and_then(|b| b.try_into().ok())
+            .map(u32::from_le_bytes)
+    };
+    let malformed = || RenderError::Resource("Truncated GLB file".to_string());
+    if word(4) != Some(2) {
+        return Err(RenderError::Resource(
+            "Only glTF 2.0 binaries are supported".to_string(),
+        ));
+    }
+    let mut json = None;
+    let mut bin = None;
+    let mut at = 12;
+    while at + 8 <= bytes.len() {
+        let length = word(at).ok_or_else(malformed)? as usize;
+        let kind = word(at + 4).ok_or_else(malformed)?;
+        let chunk = bytes.get(at + 8..at + 8 + length).ok_or_else(malformed)?;
+        match kind {
+            GLB_CHUNK_JSON if json.is_none() => json = Some(chunk),
+            GLB_CHUNK_BIN if bin.is_none() => bin = Some(chunk),
+            _ => {}
+        }
+        at += 8 + length;
+    }
+    let json = json.ok_or_else(|| RenderError::Resource("GLB has no JSON chunk".to_string()))?;
+    Ok((json, bin))
+}
+
+/// Bytes of buffer `index`: the GLB binary chunk, a data URI or a file
+/// next to the document.
+fn load_buffer(
+    buffer: &BufferDef,
+    index: usize,
+    bin: Option<&[u8]>,
+    base: Option<&Path>,
+) -> RenderResult<Vec<u8>> {
+    let bytes = match (&buffer.uri, bin) {
+        (None, Some(bin)) if index == 0 => bin.to_vec(),
+        (None, _) => {
+            return Err(RenderError::Resource(format!(
+                "glTF buffer {index} has no data"
+            )))
+        }
+        (Some(uri), _) if uri.starts_with("data:") => decode_data_uri(uri)?,
+        (Some(uri), _) => {
+            let base = base.ok_or_else(|| {
+                RenderError::Resource(format!(
+                    "glTF buffer {uri} can only be read next to a local file"
+                ))
+            })?;
+            let path = base.join(uri);
+            std::fs::read(&path).map_err(|e| {
+                RenderError::Resource(format!("Failed to read {}: {e}", path.display()))
+            })?
+        }
+    };
+    if bytes.len() < buffer.byte_length {
+        return Err(RenderError::Resource(format!(
+            "glTF buffer {index} is shorter than its byteLength"
+        )));
+    }
+    Ok(bytes)
+}
+
+/// Reads meshes out of a document and its loaded buffers.
+struct Reader<'a> {
+    document: &'a Document,
+    buffers: &'a [Vec<u8>],
+}
+
+impl Reader<'_> {
+    /// Walk the default scene's node tree and collect its meshes.
+    fn model(&self) -> RenderResult<Model> {
+        let doc = self.document;
+        let scene = doc.scene.or_else(|| (!doc.scenes.is_empty()).then_some(0));
+        let roots: Vec<usize> = if let Some(scene) = scene {
+            doc.scenes
+                .get(scene)
+                .ok_or_else(|| invalid("scene", scene))?
+                .nodes
+                .clone()
+        } else {
+            // Without scenes every node that is nobody's child is a root
+            let children: HashSet<usize> =
+                doc.nodes.iter().flat_map(|n| n.children.clone()).collect();
+            (0..doc.nodes.len())
+                .filter(|i| !children.contains(i))
+                .collect()
+        };
+
+        let mut primitives = Vec::new();
+        let mut vertices = 0;
+        let mut stack: Vec<(usize, Mat4, usize)> = roots
+            .into_iter()
+            .map(|n| (n, Mat4::identity(), 0))
+            .collect();
+        while let Some((index, parent, depth)) = stack.pop() {
+            // A node tree is never deeper than it has nodes; deeper means a cycle
+            if depth > doc.nodes.len() {
+                return Err(RenderError::Resource(
+                    "glTF node hierarchy has a cycle".to_string(),
+                ));
+            }
+            let node = doc.nodes.get(index).ok_or_else(|| invalid("node", index))?;
+            let world = parent.mul(&node.local_matrix());
+            if let Some(mesh) = node.mesh {
+                let mesh = doc.meshes.get(mesh).ok_or_else(|| invalid("mesh", mesh))?;
+                for def in &mesh.primitives {
+                    if let Some(primitive) = self.primitive(def, &world)? {
+                        vertices += primitive.positions.len();
+                        if vertices > MAX_MODEL_VERTICES {
+                            return Err(RenderError::Resource(format!(
+                                "Model has more than {MAX_MODEL_VERTICES} vertices"
+                            )));
+                        }
+                        primitives.push(primitive);
+                    }
+                }
+            }
+            stack.extend(node.children.iter().map(|&c| (c, world, depth + 1)));
+        }
+
+        let mut min = [f32::INFINITY; 3];
+        let mut max = [f32::NEG_INFINITY; 3];
+        for p in primitives.iter().flat_map(|p| &p.positions) {
+            for axis in 0..3 {
+                min[axis] = min[axis].min(p[axis]);
+                max[axis] = max[axis].max(p[axis]);
+            }
+        }
+        if primitives.is_empty() {
+            return Err(RenderError::Resource(
+                "Model has no triangle meshes".to_string(),
+            ));
+        }
+        Ok(Model {
+            primitives,
+            min,
+            max,
+        })
+    }
+
+    /// One mesh primitive, transformed by `world`. `None` for points and
+    /// lines, which are not drawn.
+    fn primitive(&self, def: &PrimitiveDef, world: &Mat4) -> RenderResult<Option<Primitive>> {
+        if !matches!(def.mode, 4..=6) {
+            return Ok(None);
+        }
+        let position = *def
+            .attributes
+            .get("POSITION")
+            .ok_or_else(|| RenderError::Resource("glTF primitive has no POSITION".to_string()))?;
+        let positions = self.vec3(position)?;
+        let indices = match def.indices {
+            Some(accessor) => self.indices(accessor)?,
+            None => (0..u32::try_from(positions.len()).unwrap_or(u32::MAX)).collect(),
+        };
+        if let Some(&bad) = indices.iter().find(|&&i| i as usize >= positions.len()) {
+            return Err(RenderError::Resource(format!(
+                "glTF index {bad} is out of range"
+            )));
+        }
+        let indices = triangulate(&indices, def.mode);
+
+        let positions: Vec<_> = positions
+            .iter()
+            .map(|&p| transform_point(world, p))
+            .collect();
+        let normals = match def.attributes.get("NORMAL") {
+            Some(&accessor) => {
+                let normals = self.vec3(accessor)?;
+                if normals.len() != positions.len() {
+                    return Err(RenderError::Resource(
+                        "glTF NORMAL and POSITION counts differ".to_string(),
+                    ));
+                }
+                let normal_matrix = normal_matrix(world);
+                normals
+                    .iter()
+                    .map(|&n| normalize(mul3(&normal_matrix, n)))
+                    .collect()
+            }
+            None => smooth_normals(&positions, &indices),
+        };
+        let material = match def.material {
+            Some(index) => self
+                .document
+                .materials
+                .get(index)
+                .ok_or_else(|| invalid("material", index))?
+                .material(),
+            None => Material::default(),
+        };
+        Ok(Some(Primitive {
+            positions,
+            normals,
+            indices,
+            material,
+        }))
+    }
+
+    /// Read a float `VEC3` accessor.
+    fn vec3(&self, index: usize) -> RenderResult<Vec<[f32; 3]>> {
+        let accessor = self.accessor(index)?;
+        if accessor.kind != "VEC3" || accessor.component_type != COMPONENT_F32 {
+            return Err(RenderError::Resource(format!(
+                "glTF accessor {index} must hold float VEC3 values"
+            )));
+        }
+        Ok(self
+            .elements(accessor, 12)?
+            .map(|b| {
+                let f = |at: usize| f32::from_le_bytes([b[at], b[at + 1], b[at + 2], b[at + 3]]);
+                [f(0), f(4), f(8)]
+            })
+            .collect())
+    }
+
+    /// Read an unsigned integer `SCALAR` index accessor.
+    fn indices(&self, index: usize) -> RenderResult<Vec<u32>> {
+        let accessor = self.accessor(index)?;
+        let size = match accessor.component_type {
+            COMPONENT_U8 => 1,
+            COMPONENT_U16 => 2,
+            COMPONENT_U32 => 4,
+            _ => 0,
+        };
+        if accessor.kind != "SCALAR" || size == 0 {
+            return Err(RenderError::Resource(format!(
+                "glTF accessor {index} must hold unsigned integer indices"
+            )));
+        }
+        Ok(self
+            .elements(accessor, size)?
+            .map(|b| match size {
+                1 => u32::from(b[0]),
+                2 => u32::from(u16::from_le_bytes([b[0], b[1]])),
+                _ => u32::from_le_bytes([b[0], b[1], b[2], b[3]]),
+            })
+            .collect())
+    }
+
+    fn accessor(&self, index: usize) -> RenderResult<&AccessorDef> {
+        self.document
+            .accessors
+            .get(index)
+            .ok_or_else(|| invalid("accessor", index))
+    }
+
+    /// The raw bytes of each of an accessor's elements, `size` bytes long.
+    fn elements<'b>(
+        &'b self,
+        accessor: &AccessorDef,
+        size: usize,
+    ) -> RenderResult<Box<dyn Iterator<Item = &'b [u8]> + 'b>> {
+        let Some(view_index) = accessor.buffer_view else {
+            // An accessor without a buffer view is all zeros
+            return Ok(Box::new(std::iter::repeat_n(
+                &ZEROS[..size],
+                accessor.count,
+            )));
+        };
+        let view = self
+            .document
+            .buffer_views
+            .get(view_index)
+            .ok_or_else(|| invalid("bufferView", view_index))?;
+        let buffer = self
+            .buffers
+            .get(view.buffer)
+            .ok_or_else(|| invalid("buffer", view.buffer))?;
+        let stride = view.byte_stride.unwrap_or(size).max(size);
+        let start = view.byte_offset + accessor.byte_offset;
+        let span = match accessor.count {
+            0 => 0,
+            n => (n - 1) * stride + size,
+        };
+        let fits = accessor.byte_offset + span <= view.byte_length
+            && view.byte_offset + view.byte_length <= buffer.len();
+        if !fits {
+            return Err(RenderError::Resource(
+                "glTF accessor reaches past its buffer view".to_string(),
+            ));
+        }
+        Ok(Box::new(
+            (0..accessor.count).map(move |i| &buffer[start + i * stride..][..size]),
+        ))
+    }
+}
+
+fn invalid(what: &str, index: usize) -> RenderError {
+    RenderError::Resource(format!("glTF refers to missing {what} {index}"))
+}
+
+const ZEROS: [u8; 12] = [0; 12];
+
+const COMPONENT_U8: u32 = 5121;
+const COMPONENT_U16: u32 = 5123;
+const COMPONENT_U32: u32 = 5125;
+const COMPONENT_F32: u32 = 5126;
+
+/// Turn strip (mode 5) and fan (mode 6) indices into a triangle list.
+fn triangulate(indices: &[u32], mode: u32) -> Vec<u32> {
+    match mode {
+        5 => (2..indices.len())
+            .flat_map(|i| {
+                // Every other triangle of a strip is wound the other way
+                if i % 2 == 0 {
+                    [indices[i - 2], indices[i - 1], indices[i]]
+                } else {
+                    [indices[i - 1], indices[i - 2], indices[i]]
+                }
+            })
+            .collect(),
+        6 => (2..indices.len())
+            .flat_map(|i| [indices[0], indices[i - 1], indices[i]])
+            .collect(),
+        _ => indices[..indices.len() - indices.len() % 3].to_vec(),
+    }
+}
+
+/// Area-weighted vertex normals, for meshes that do not carry their own.
+fn smooth_normals(positions: &[[f32; 3]], indices: &[u32]) -> Vec<[f32; 3]> {
+    let mut normals = vec![[0.0; 3]; positions.len()];
+    for triangle in indices.chunks_exact(3) {
+        let [a, b, c] = [0, 1, 2].map(|i| triangle[i] as usize);
+        let face = cross(
+            sub(positions[b], positions[a]),
+            sub(positions[c], positions[a]),
+        );
+        for vertex in [a, b, c] {
+            for axis in 0..3 {
+                normals[vertex][axis] += face[axis];
+            }
+        }
+    }
+    normals.into_iter().map(normalize).collect()
+}
+
+fn transform_point(m: &Mat4, p: [f32; 3]) -> [f32; 3] {
+    let d = &m.data;
+    [
+        d[0] * p[0] + d[4] * p[1] + d[8] * p[2] + d[12],
+        d[1] * p[0] + d[5] * p[1] + d[9] * p[2] + d[13],
+        d[2] * p[0] + d[6] * p[1] + d[10] * p[2] + d[14],
+    ]
+}
+
+/// Matrix taking normals along with `m`: the cofactors of its upper 3x3,
+/// which is its inverse transpose scaled by the determinant, with the sign
+/// kept so mirrored nodes still face outwards.
+fn normal_matrix(matrix: &Mat4) -> [[f32; 3]; 3] {
+    let column = |c: usize| {
+        [
+            matrix.data[c * 4],
+            matrix.data[c * 4 + 1],
+            matrix.data[c * 4 + 2],
+        ]
+    };
+    let axes = [column(0), column(1), column(2)];
+    // Rows of the cofactor matrix are the cross products of the columns
+    let rows = [
+        cross(axes[1], axes[2]),
+        cross(axes[2], axes[0]),
+        cross(axes[0], axes[1]),
+    ];
+    let sign = if dot(axes[0], rows[0]) < 0.0 {
+        -1.0
+    } else {
+        1.0
+    };
+    // mul3 takes columns, so transpose the rows
+    std::array::from_fn(|c| std::array::from_fn(|r| rows[r][c] * sign))
+}
+
+/// Multiply a column-major 3x3 matrix with a vector.
+fn mul3(m: &[[f32; 3]; 3], v: [f32; 3]) -> [f32; 3] {
+    [
+        m[0][0] * v[0] + m[1][0] * v[1] + m[2][0] * v[2],
+        m[0][1] * v[0] + m[1][1] * v[1] + m[2][1] * v[2],
+        m[0][2] * v[0] + m[1][2] * v[1] + m[2][2] * v[2],
+    ]
+}
+
+fn sub(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn cross(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+fn dot(a: [f32; 3], b: [f32; 3]) -> f32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn length(v: [f32; 3]) -> f32 {
+    dot(v, v).sqrt()
+}
+
+/// Unit vector along `v`, or +Z for degenerate input.
+fn normalize(v: [f32; 3]) -> [f32; 3] {
+    let len = length(v);
+    if len > f32::EPSILON {
+        v.map(|c| c / len)
+    } else {
+        [0.0, 0.0, 1.0]
+    }
+}
+
+/// The parts of a glTF document this module reads.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Document {
+    scene: Option<usize>,
+    #[serde(default)]
+    scenes: Vec<SceneDef>,
+    #[serde(default)]
+    nodes: Vec<NodeDef>,
+    #[serde(default)]
+    meshes: Vec<MeshDef>,
+    #[serde(default)]
+    materials: Vec<MaterialDef>,
+    #[serde(default)]
+    accessors: Vec<AccessorDef>,
+    #[serde(default)]
+    buffer_views: Vec<BufferViewDef>,
+    #[serde(default)]
+    buffers: Vec<BufferDef>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SceneDef {
+    #[serde(default)]
+    nodes: Vec<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+struct NodeDef {
+    #[serde(default)]
+    children: Vec<usize>,
+    mesh: Option<usize>,
+    matrix: Option<[f32; 16]>,
+    translation: Option<[f32; 3]>,
+    rotation: Option<[f32; 4]>,
+    scale: Option<[f32; 3]>,
+}
+
+impl NodeDef {
+    /// The node's transform relative to its parent.
+    fn local_matrix(&self) -> Mat4 {
+        match self.matrix {
+            Some(data) => Mat4 { data },
+            None => Mat4::from_pose(&Transform3D {
+                position: self.translation.unwrap_or([0.0; 3]),
+                rotation: self.rotation.unwrap_or([0.0, 0.0, 0.0, 1.0]),
+                scale: self.scale.unwrap_or([1.0; 3]),
+            }),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct MeshDef {
+    primitives: Vec<PrimitiveDef>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PrimitiveDef {
+    attributes: HashMap<String, usize>,
+    indices: Option<usize>,
+    material: Option<usize>,
+    #[serde(default = "triangles")]
+    mode: u32,
+}
+
+const fn triangles() -> u32 {
+    4
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MaterialDef {
+    pbr_metallic_roughness: Option<PbrDef>,
+    emissive_factor: Option<[f32; 3]>,
+}
+
+impl MaterialDef {
+    fn material(&self) -> Material {
+        let default = Material::default();
+        let pbr = self.pbr_metallic_roughness.as_ref();
+        Material {
+            base_color: pbr
+                .and_then(|p| p.base_color_factor)
+                .unwrap_or(default.base_color),
+            metallic: pbr
+                .and_then(|p| p.metallic_factor)
+                .unwrap_or(default.metallic)
+                .clamp(0.0, 1.0),
+            roughness: pbr
+                .and_then(|p| p.roughness_factor)
+                .unwrap_or(default.roughness)
+                .clamp(0.0, 1.0),
+            emissive: self.emissive_factor.unwrap_or(default.emissive),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[allow(clippy::struct_field_names)] // Named after glTF's properties
+struct PbrDef {
+    base_color_factor: Option<[f32; 4]>,
+    metallic_factor: Option<f32>,
+    roughness_factor: Option<f32>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AccessorDef {
+    buffer_view: Option<usize>,
+    #[serde(default)]
+    byte_offset: usize,
+    component_type: u32,
+    count: usize,
+    #[serde(rename = "type")]
+    kind: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BufferViewDef {
+    buffer: usize,
+    #[serde(default)]
+    byte_offset: usize,
+    byte_length: usize,
+    byte_stride: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BufferDef {
+    uri: Option<String>,
+    byte_length: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A unit right triangle in the XY plane: three float positions and
+    /// three u16 indices padded to four bytes.
+    fn triangle_buffer() -> Vec<u8> {
+        let mut bytes = Vec::new();
+        for v in [0.0f32, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0] {
+            bytes.extend_from_slice(&v.to_le_bytes());
+        }
+        for i in [0u16, 1, 2, 0] {
+            bytes.extend_from_slice(&i.to_le_bytes());
+        }
+        bytes
+    }
+
+    fn triangle_json(buffer_uri: Option<&str>, nodes: &str) -> String {
+        let uri = buffer_uri.map_or(String::new(), |u| format!(r#""uri": "{u}", "#));
+        format!(
+            r#"{{
+                "asset": {{ "version": "2.0" }},
+                "scene": 0,
+                "scenes": [{{ "nodes": [0] }}],
+                "nodes": {nodes},
+                "meshes": [{{ "primitives": [{{
+                    "attributes": {{ "POSITION": 0 }}, "indices": 1, "material": 0
+                }}] }}],
+                "materials": [{{
+                    "pbrMetallicRoughness": {{
+                        "baseColorFactor": [1.0, 0.0, 0.0, 1.0],
+                        "metallicFactor": 0.0,
+                        "roughnessFactor": 0.5
+                    }},
+                    "emissiveFactor": [0.1, 0.1, 0.1]
+                }}],
+                "accessors": [
+                    {{ "bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3" }},
+                    {{ "bufferView": 1, "componentType": 5123, "count": 3, "type": "SCALAR" }}
+                ],
+                "bufferViews": [
+                    {{ "buffer": 0, "byteOffset": 0, "byteLength": 36 }},
+                    {{ "buffer": 0, "byteOffset": 36, "byteLength": 6 }}
+                ],
+                "buffers": [{{ {uri}"byteLength": 44 }}]
+            }}"#
+        )
+    }
+
+    fn data_uri(bytes: &[u8]) -> String {
+        format!(
+            "data:application/octet-stream;base64,{}",
+            base64::engine::general_purpose::STANDARD.encode(bytes)
+        )
+    }
+
+    fn glb(json: &str, bin: &[u8]) -> Vec<u8> {
+        let mut json = json.as_bytes().to_vec();
+        json.resize(json.len().next_multiple_of(4), b' ');
+        let chunk = |bytes: &mut Vec<u8>, kind: u32, data: &[u8]| {
+            bytes.extend_from_slice(&u32::try_from(data.len()).unwrap().to_le_bytes());
+            bytes.extend_from_slice(&kind.to_le_bytes());
+            bytes.extend_from_slice(data);
+        };
+        let mut body = Vec::new();
+        chunk(&mut body, GLB_CHUNK_JSON, &json);
+        chunk(&mut body, GLB_CHUNK_BIN, bin);
+        let mut bytes = GLB_MAGIC.to_vec();
+        bytes.extend_from_slice(&2u32.to_le_bytes());
+        bytes.extend_from_slice(&u32::try_from(body.len() + 12).unwrap().to_le_bytes());
+        bytes.extend(body);
+        bytes
+    }
+
+    #[test]
+    fn test_parse_embedded_triangle() {
+        let json = triangle_json(Some(&data_uri(&triangle_buffer())), r#"[{ "mesh": 0 }]"#);
+        let model = Model::parse(json.as_bytes(), None).unwrap();
+
+        assert_eq!(model.triangle_count(), 1);
+        let primitive = &model.primitives[0];
+        assert_eq!(primitive.indices, vec![0, 1, 2]);
+        // Missing normals are computed from the winding: +Z
+        assert_eq!(primitive.normals, vec![[0.0, 0.0, 1.0]; 3]);
+        assert_eq!(
+            primitive.material,
+            Material {
+                base_color: [1.0, 0.0, 0.0, 1.0],
+                metallic: 0.0,
+                roughness: 0.5,
+                emissive: [0.1, 0.1, 0.1],
+            }
+        );
+        assert_eq!((model.min, model.max), ([0.0; 3], [1.0, 1.0, 0.0]));
+    }
+
+    #[test]
+    fn test_parse_glb() {
+        let json = triangle_json(None, r#"[{ "mesh": 0 }]"#);
+        let model = Model::parse(&glb(&json, &triangle_buffer()), None).unwrap();
+        assert_eq!(
+            model.primitives[0].positions,
+            vec![[0.0; 3], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]]
+        );
+    }
+
+    #[test]
+    fn test_node_hierarchy_is_baked_in() {
+        // Parent moves +10 X and doubles the size; child turns a quarter
+        // turn around Z and holds the mesh
+        let nodes = r#"[
+            { "children": [1], "translation": [10.0, 0.0, 0.0], "scale": [2.0, 2.0, 2.0] },
+            { "mesh": 0, "rotation": [0.0, 0.0, 0.7071068, 0.7071068] }
+        ]"#;
+        let json = triangle_json(Some(&data_uri(&triangle_buffer())), nodes);
+        let model = Model::parse(json.as_bytes(), None).unwrap();
+
+        let p = model.primitives[0].positions[1];
+        assert!((p[0] - 10.0).abs() < 1e-5 && (p[1] - 2.0).abs() < 1e-5);
+        let n = model.primitives[0].normals[0];
+        assert!((n[2] - 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_parse_rejects_bad_input() {
+        assert!(Model::parse(b"not json", None).is_err());
+        // Index buffer view too short for its accessor
+        let json = triangle_json(Some(&data_uri(&triangle_buffer())), r#"[{ "mesh": 0 }]"#)
+            .replace(
+                r#""byteOffset": 36, "byteLength": 6"#,
+                r#""byteOffset": 36, "byteLength": 4"#,
+            );
+        assert!(Model::parse(json.as_bytes(), None).is_err());
+        // A node that is its own child
+        let json = triangle_json(
+            Some(&data_uri(&triangle_buffer())),
+            r#"[{ "mesh": 0, "children": [0] }]"#,
+        );
+        assert!(Model::parse(json.as_bytes(), None).is_err());
+    }
+
+    #[test]
+    fn test_triangulate_strip_and_fan() {
+        assert_eq!(triangulate(&[0, 1, 2, 3], 5), vec![0, 1, 2, 2, 1, 3]);
+        assert_eq!(triangulate(&[0, 1, 2, 3], 6), vec![0, 1, 2, 0, 2, 3]);
+    }
+
+    #[test]
+    fn test_loader_states() {
+        let mut loader = ModelLoader::new();
+        let remote = "https://example.com/duck.glb";
+        assert!(matches!(loader.request(remote), ModelState::Loading));
+        assert_eq!(loader.pending().collect::<Vec<_>>(), vec![remote]);
+
+        let json = triangle_json(None, r#"[{ "mesh": 0 }]"#);
+        loader.provide(remote, glb(&json, &triangle_buffer()));
+        assert_eq!(loader.pending().count(), 0);
+
+        let missing = "/no/such/model.gltf";
+        loader.request(missing);
+        let loading = |loader: &ModelLoader| {
+            [remote, missing]
+                .iter()
+                .any(|src| matches!(loader.state(src), Some(ModelState::Loading)))
+        };
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+        while loading(&loader) && std::time::Instant::now() < deadline {
+            loader.poll();
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+        assert_eq!(
+            loader
+                .state(remote)
+                .and_then(ModelState::model)
+                .map(|m| m.triangle_count()),
+            Some(1)
+        );
+        assert!(matches!(loader.state(missing), Some(ModelState::Failed(_))));
+    }
+}
@@ -0,0 +1,464 @@
+//! # Orbit Camera
+//!
+//! Interactive control of a [`Camera`] for inspecting 3D and holographic
+//! scenes: dragging orbits around the target, panning slides the target
+//! across the view and zooming moves in and out.
+//!
+//! ```text
+//!             pitch
+//!               ▲   ● camera
+//!               │  ╱
+//!               │ ╱ distance
+//!               │╱
+//!     target ───●─────▶ yaw (around Y)
+//! ```
+//!
+//! Input moves a goal; [`OrbitCamera::update`] eases the camera towards it
+//! each frame, so motion keeps going smoothly after the pointer stops.
+//! [`OrbitLimits`] keep the camera from flipping over the poles, passing
+//! through the target or wandering off the scene.
+
+use crate::spatial::{Camera, Vec3};
+
+/// Pose of the orbit: where it looks and from where.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct OrbitPose {
+    target: Vec3,
+    yaw: f32,
+    pitch: f32,
+    distance: f32,
+}
+
+impl OrbitPose {
+    /// Offset of the camera from the target.
+    fn offset(&self) -> Vec3 {
+        let (sin_yaw, cos_yaw) = self.yaw.sin_cos();
+        let (sin_pitch, cos_pitch) = self.pitch.sin_cos();
+        Vec3::new(
+            self.distance * cos_pitch * sin_yaw,
+            self.distance * sin_pitch,
+            self.distance * cos_pitch * cos_yaw,
+        )
+    }
+
+    /// `self` moved `amount` (0 to 1) of the way to `goal`.
+    fn lerp(&self, goal: &Self, amount: f32) -> Self {
+        let mix = |from: f32, to: f32| from + (to - from) * amount;
+        Self {
+            target: self
+                .target
+                .add(&goal.target.sub(&self.target).scale(amount)),
+            yaw: mix(self.yaw, goal.yaw),
+            pitch: mix(self.pitch, goal.pitch),
+            distance: mix(self.distance, goal.distance),
+        }
+    }
+
+    /// Whether `self` is close enough to `goal` to stop easing.
+    fn settled(&self, goal: &Self) -> bool {
+        const ANGLE: f32 = 1e-4;
+        let scale = goal.distance.max(f32::EPSILON);
+        (self.yaw - goal.yaw).abs() < ANGLE
+            && (self.pitch - goal.pitch).abs() < ANGLE
+            && (self.distance - goal.distance).abs() / scale < 1e-4
+            && self.target.sub(&goal.target).length() / scale < 1e-4
+    }
+}
+
+/// Bounds on where an [`OrbitCamera`] may go.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OrbitLimits {
+    /// Closest the camera may come to the target.
+    pub min_distance: f32,
+    /// Furthest the camera may go from the target.
+    pub max_distance: f32,
+    /// Lowest pitch in radians (negative looks up from below).
+    pub min_pitch: f32,
+    /// Highest pitch in radians.
+    pub max_pitch: f32,
+    /// Furthest the target may be panned from where it started.
+    pub max_pan: f32,
+}
+
+impl OrbitLimits {
+    /// Limits suiting a camera `distance` from its target: a tenth to ten
+    /// times as far, pitch short of straight up or down, and panning up to
+    /// twice the distance.
+    #[must_use]
+    pub fn around(distance: f32) -> Self {
+        let distance = distance.abs().max(f32::EPSILON);
+        let pitch = 85.0_f32.to_radians();
+        Self {
+            min_distance: distance * 0.1,
+            max_distance: distance * 10.0,
+            min_pitch: -pitch,
+            max_pitch: pitch,
+            max_pan: distance * 2.0,
+        }
+    }
+}
+
+impl Default for OrbitLimits {
+    fn default() -> Self {
+        Self::around(Camera::default().position.length())
+    }
+}
+
+/// Orbits, pans and zooms a [`Camera`] around its target.
+///
+/// ```
+/// use canvas_renderer::{Camera, OrbitCamera};
+///
+/// let mut orbit = OrbitCamera::new(&Camera::default());
+/// orbit.drag(120.0, 0.0); // pointer moved 120 pixels right
+/// orbit.zoom(1.5);
+/// while orbit.update(1.0 / 60.0) {}
+/// let camera = orbit.camera();
+/// assert!(camera.position.x < 0.0);
+/// ```
+#[derive(Debug, Clone)]
+pub struct OrbitCamera {
+    /// Pose the camera shows.
+    current: OrbitPose,
+    /// Pose input has asked for.
+    goal: OrbitPose,
+    /// Pose to return to on [`reset`](Self::reset).
+    home: OrbitPose,
+    /// Lens and clipping of the camera.
+    lens: Camera,
+    limits: OrbitLimits,
+    damping: f32,
+    orbit_speed: f32,
+}
+
+impl OrbitCamera {
+    /// Time constant of the easing in seconds.
+    pub const DEFAULT_DAMPING: f32 = 0.08;
+
+    /// Radians of orbit per pixel dragged.
+    pub const DEFAULT_ORBIT_SPEED: f32 = 0.005;
+
+    /// Start orbiting from `camera`, keeping its field of view and
+    /// clipping planes, with [`OrbitLimits::around`] its distance.
+    #[must_use]
+    pub fn new(camera: &Camera) -> Self {
+        let offset = camera.position.sub(&camera.target);
+        let distance = offset.length();
+        let (yaw, pitch) = if distance > f32::EPSILON {
+            (
+                offset.x.atan2(offset.z),
+                (offset.y / distance).clamp(-1.0, 1.0).asin(),
+            )
+        } else {
+            (0.0, 0.0)
+        };
+        let limits = OrbitLimits::around(distance);
+        let pose = OrbitPose {
+            target: camera.target,
+            yaw,
+            pitch: pitch.clamp(limits.min_pitch, limits.max_pitch),
+            distance: distance.clamp(limits.min_distance, limits.max_distance),
+        };
+        Self {
+            current: pose,
+            goal: pose,
+            home: pose,
+            lens: camera.clone(),
+            limits,
+            damping: Self::DEFAULT_DAMPING,
+            orbit_speed: Self::DEFAULT_ORBIT_SPEED,
+        }
+    }
+
+    /// Keep the camera within `limits`.
+    #[must_use]
+    pub fn with_limits(mut self, limits: OrbitLimits) -> Self {
+        self.set_limits(limits);
+        self
+    }
+
+    /// Ease towards input with a time constant of `seconds`; zero moves
+    /// straight there.
+    #[must_use]
+    pub fn with_damping(mut self, seconds: f32) -> Self {
+        self.set_damping(seconds);
+        self
+    }
+
+    /// Orbit `radians` per pixel in [`drag`](Self::drag).
+    #[must_use]
+    pub fn with_orbit_speed(mut self, radians: f32) -> Self {
+        self.orbit_speed = radians;
+        self
+    }
+
+    /// Change the easing time constant in seconds.
+    pub fn set_damping(&mut self, seconds: f32) {
+        self.damping = if seconds.is_finite() {
+            seconds.max(0.0)
+        } else {
+            0.0
+        };
+    }
+
+    /// Change the limits, pulling the camera back within them.
+    pub fn set_limits(&mut self, limits: OrbitLimits) {
+        self.limits = limits;
+        self.goal = self.clamped(self.goal);
+        self.current = self.clamped(self.current);
+    }
+
+    /// The current limits.
+    #[must_use]
+    pub const fn limits(&self) -> &OrbitLimits {
+        &self.limits
+    }
+
+    /// The easing time constant in seconds.
+    #[must_use]
+    pub const fn damping(&self) -> f32 {
+        self.damping
+    }
+
+    /// Turn around the target by `yaw` radians (positive moves the camera
+    /// right, turning the scene left) and tilt by `pitch` radians
+    /// (positive looks down from above).
+    pub fn orbit(&mut self, yaw: f32, pitch: f32) {
+        self.goal.yaw += yaw;
+        self.goal.pitch += pitch;
+        self.goal = self.clamped(self.goal);
+    }
+
+    /// Orbit for a pointer drag of (`dx`, `dy`) pixels, as if dragging the
+    /// scene around: right turns it right, down tilts its top towards the
+    /// viewer.
+    pub fn drag(&mut self, dx: f32, dy: f32) {
+        self.orbit(-dx * self.orbit_speed, dy * self.orbit_speed);
+    }
+
+    /// Slide the target for a pointer drag of (`dx`, `dy`) pixels in a view
+    /// `viewport_height` pixels tall, so the scene follows the pointer on
+    /// the plane through the target.
+    pub fn pan(&mut self, dx: f32, dy: f32, viewport_height: f32) {
+        let pose = self.goal;
+        let forward = pose.offset().scale(-1.0).normalize();
+        let right = forward.cross(&Vec3::up()).normalize();
+        let up = right.cross(&forward);
+        let per_pixel =
+            2.0 * pose.distance * (self.lens.fov / 2.0).tan() / viewport_height.max(1.0);
+        self.goal.target = pose
+            .target
+            .add(&right.scale(-dx * per_pixel))
+            .add(&up.scale(dy * per_pixel));
+        self.goal = self.clamped(self.goal);
+    }
+
+    /// Move towards the target by `factor`: 2.0 halves the distance, 0.5
+    /// doubles it.
+    pub fn zoom(&mut self, factor: f32) {
+        if factor.is_finite() && factor > 0.0 {
+            self.goal.distance /= factor;
+            self.goal = self.clamped(self.goal);
+        }
+    }
+
+    /// Zoom for `lines` of scrolling, in by a tenth per line scrolled up
+    /// (positive).
+    pub fn scroll(&mut self, lines: f32) {
+        self.zoom(1.1_f32.powf(lines));
+    }
+
+    /// Return to the starting pose, as near as the limits allow.
+    pub fn reset(&mut self) {
+        self.goal = self.clamped(self.home);
+    }
+
+    /// Advance the easing by `dt` seconds. Returns `true` while the camera
+    /// is still moving, so the caller keeps drawing frames.
+    pub fn update(&mut self, dt: f32) -> bool {
+        let amount = if self.damping <= 0.0 {
+            1.0
+        } else {
+            1.0 - (-dt.max(0.0) / self.damping).exp()
+        };
+        self.current = self.current.lerp(&self.goal, amount);
+        if self.current.settled(&self.goal) {
+            self.current = self.goal;
+            return false;
+        }
+        true
+    }
+
+    /// Whether the camera has reached the pose input asked for.
+    #[must_use]
+    pub fn is_settled(&self) -> bool {
+        self.current == self.goal
+    }
+
+    /// Point the camera is looking at.
+    #[must_use]
+    pub const fn target(&self) -> Vec3 {
+        self.current.target
+    }
+
+    /// Angle around the target in radians, zero looking down -Z.
+    #[must_use]
+    pub const fn yaw(&self) -> f32 {
+        self.current.yaw
+    }
+
+    /// Angle above the target in radians.
+    #[must_use]
+    pub const fn pitch(&self) -> f32 {
+        self.current.pitch
+    }
+
+    /// Distance from the target.
+    #[must_use]
+    pub const fn distance(&self) -> f32 {
+        self.current.distance
+    }
+
+    /// The camera at the current pose.
+    #[must_use]
+    pub fn camera(&self) -> Camera {
+        let mut camera = self.lens.clone();
+        self.apply(&mut camera);
+        camera
+    }
+
+    /// Move `camera` to the current pose, keeping its lens.
+    pub fn apply(&self, camera: &mut Camera) {
+        camera.target = self.current.target;
+        camera.position = self.current.target.add(&self.current.offset());
+        camera.up = Vec3::up();
+    }
+
+    /// `pose` within the limits.
+    fn clamped(&self, mut pose: OrbitPose) -> OrbitPose {
+        let limits = &self.limits;
+        pose.pitch = pose
+            .pitch
+            .clamp(limits.min_pitch, limits.max_pitch.max(limits.min_pitch));
+        pose.distance = pose.distance.clamp(
+            limits.min_distance,
+            limits.max_distance.max(limits.min_distance),
+        );
+        let pan = pose.target.sub(&self.home.target);
+        let max_pan = limits.max_pan.max(0.0);
+        if pan.length() > max_pan {
+            pose.target = self.home.target.add(&pan.normalize().scale(max_pan));
+        }
+        pose
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EPSILON: f32 = 1e-4;
+
+    fn approx_eq(a: f32, b: f32) -> bool {
+        (a - b).abs() < EPSILON
+    }
+
+    fn settle(orbit: &mut OrbitCamera) -> usize {
+        let mut frames = 0;
+        while orbit.update(1.0 / 60.0) {
+            frames += 1;
+            assert!(frames < 1000, "never settles");
+        }
+        frames
+    }
+
+    #[test]
+    fn test_orbit_camera_starts_at_the_camera() {
+        let camera = Camera {
+            position: Vec3::new(3.0, 4.0, 0.0),
+            target: Vec3::zero(),
+            ..Camera::default()
+        };
+        let orbit = OrbitCamera::new(&camera);
+        assert!(approx_eq(orbit.distance(), 5.0));
+        assert!(approx_eq(orbit.yaw(), std::f32::consts::FRAC_PI_2));
+        assert!(approx_eq(orbit.pitch(), (4.0_f32 / 5.0).asin()));
+
+        let back = orbit.camera();
+        assert!(back.position.sub(&camera.position).length() < EPSILON);
+        assert!(approx_eq(back.fov, camera.fov));
+        assert!(orbit.is_settled());
+    }
+
+    #[test]
+    fn test_orbit_camera_eases_towards_input_and_settles() {
+        let mut orbit = OrbitCamera::new(&Camera::default());
+        orbit.drag(100.0, 0.0);
+        assert!(orbit.update(1.0 / 60.0));
+        let partway = orbit.yaw();
+        assert!(partway < 0.0 && partway > -0.5, "{partway}");
+        assert!(!orbit.is_settled());
+
+        assert!(settle(&mut orbit) > 10);
+        assert!(approx_eq(orbit.yaw(), -0.5));
+        assert!(orbit.is_settled());
+        // Dragging right turns the scene right, moving the camera left
+        assert!(orbit.camera().position.x < 0.0);
+
+        // Without damping input applies at once
+        let mut instant = OrbitCamera::new(&Camera::default()).with_damping(0.0);
+        instant.zoom(2.0);
+        assert!(!instant.update(0.0));
+        assert!(approx_eq(instant.distance(), 2.5));
+    }
+
+    #[test]
+    fn test_orbit_camera_stays_within_limits() {
+        let mut orbit = OrbitCamera::new(&Camera::default())
+            .with_damping(0.0)
+            .with_limits(OrbitLimits {
+                min_distance: 2.0,
+                max_distance: 10.0,
+                min_pitch: -0.5,
+                max_pitch: 0.5,
+                max_pan: 1.0,
+            });
+
+        orbit.orbit(0.0, 3.0);
+        orbit.zoom(100.0);
+        orbit.update(0.0);
+        assert!(approx_eq(orbit.pitch(), 0.5));
+        assert!(approx_eq(orbit.distance(), 2.0));
+
+        orbit.scroll(-100.0);
+        orbit.pan(1.0e6, 0.0, 600.0);
+        orbit.update(0.0);
+        assert!(approx_eq(orbit.distance(), 10.0));
+        assert!(approx_eq(orbit.target().length(), 1.0));
+
+        orbit.reset();
+        orbit.update(0.0);
+        assert!(approx_eq(orbit.distance(), 5.0));
+        assert!(approx_eq(orbit.pitch(), 0.0));
+        assert!(orbit.target().length() < EPSILON);
+    }
+
+    #[test]
+    fn test_orbit_camera_pans_with_the_pointer() {
+        // Looking down -Z from (0, 0, 5) with a 45° field of view, the
+        // focal plane is 2 * 5 * tan(22.5°) units tall
+        let mut orbit = OrbitCamera::new(&Camera::default()).with_damping(0.0);
+        let height = 2.0 * 5.0 * std::f32::consts::FRAC_PI_8.tan();
+        orbit.pan(300.0, 150.0, 600.0);
+        orbit.update(0.0);
+        let target = orbit.target();
+        assert!(approx_eq(target.x, -height / 2.0));
+        assert!(approx_eq(target.y, height / 4.0));
+        assert!(approx_eq(target.z, 0.0));
+
+        let mut camera = Camera::default();
+        orbit.apply(&mut camera);
+        assert!(approx_eq(camera.position.z, 5.0));
+        assert!(approx_eq(camera.position.x, target.x));
+    }
+}
@@ -0,0 +1,300 @@
+//! # Post-Processing
+//!
+//! Full-screen effects over a finished frame, or over one `OverlayLayer`
+//! before it is composited, for presentation polish on demo and kiosk
+//! displays. Each effect works on the output of the one before it:
+//!
+//! ```text
+//! scene ─▶ frame texture ─▶ bloom ─▶ color grade ─▶ vignette ─▶ surface
+//! ```
+//!
+//! Effects are set once for the renderer ([`RendererConfig::post_effects`]
+//! or [`Renderer::set_post_effects`]), not per element, so agents cannot
+//! turn them on:
+//!
+//! ```json
+//! { "frame": [{ "effect": "bloom", "threshold": 0.7, "intensity": 0.8, "radius": 8 },
+//!             { "effect": "vignette", "strength": 0.4, "radius": 0.6, "softness": 0.5 }],
+//!   "layers": { "<overlay id>": [{ "effect": "blur", "radius": 6 }] } }
+//! ```
+//!
+//! Only the wgpu backend applies them; the others draw as they always do.
+//!
+//! [`RendererConfig::post_effects`]: crate::RendererConfig::post_effects
+//! [`Renderer::set_post_effects`]: crate::Renderer::set_post_effects
+
+use std::collections::HashMap;
+
+use canvas_core::ElementId;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{RenderError, RenderResult};
+
+/// Widest blur or bloom, in pixels.
+pub const MAX_BLUR_RADIUS: f32 = 64.0;
+
+/// Most effects in one chain.
+pub const MAX_EFFECTS: usize = 8;
+
+/// Most texels a blur pass samples on each side of a pixel; wider blurs
+/// space their samples out.
+pub const MAX_BLUR_TAPS: f32 = 16.0;
+
+/// One full-screen effect.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "effect", rename_all = "snake_case")]
+pub enum PostEffect {
+    /// Gaussian blur.
+    Blur {
+        /// How far the blur spreads, in pixels, up to [`MAX_BLUR_RADIUS`].
+        radius: f32,
+    },
+    /// Darkening towards the corners.
+    Vignette {
+        /// How dark the corners get, from 0.0 (not at all) to 1.0 (black).
+        strength: f32,
+        /// Where darkening starts, as a fraction of the way from the center
+        /// to a corner.
+        radius: f32,
+        /// How far past `radius` darkening takes to reach full strength, in
+        /// the same fraction.
+        softness: f32,
+    },
+    /// Glow spreading out from bright areas.
+    Bloom {
+        /// Brightness, from 0.0 to 1.0, above which pixels glow.
+        threshold: f32,
+        /// How strongly the glow is added back, from 0.0 up to 4.0.
+        intensity: f32,
+        /// How far the glow spreads, in pixels, up to [`MAX_BLUR_RADIUS`].
+        radius: f32,
+    },
+    /// Brightness, contrast, saturation and white balance.
+    ColorGrade {
+        /// Added to every channel, from -1.0 to 1.0.
+        brightness: f32,
+        /// Spread around mid grey, from 0.0 (flat grey) to 4.0; 1.0 keeps
+        /// it.
+        contrast: f32,
+        /// From 0.0 (greyscale) to 4.0; 1.0 keeps the colors.
+        saturation: f32,
+        /// Shift towards warm (positive) or cool (negative) light, from
+        /// -1.0 to 1.0.
+        temperature: f32,
+    },
+}
+
+impl PostEffect {
+    /// The `effect` tag this effect is written with.
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Blur { .. } => "blur",
+            Self::Vignette { .. } => "vignette",
+            Self::Bloom { .. } => "bloom",
+            Self::ColorGrade { .. } => "color_grade",
+        }
+    }
+
+    /// Check every parameter is finite and in range.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RenderError::PostEffect`] naming the first parameter out of
+    /// range.
+    pub fn validate(&self) -> RenderResult<()> {
+        let check = |field: &str, value: f32, min: f32, max: f32| {
+            if value.is_finite() && (min..=max).contains(&value) {
+                Ok(())
+            } else {
+                Err(RenderError::PostEffect(format!(
+                    "{} {field} must be from {min} to {max}, not {value}",
+                    self.name()
+                )))
+            }
+        };
+        match *self {
+            Self::Blur { radius } => check("radius", radius, 0.0, MAX_BLUR_RADIUS),
+            Self::Vignette {
+                strength,
+                radius,
+                softness,
+            } => {
+                check("strength", strength, 0.0, 1.0)?;
+                check("radius", radius, 0.0, 2.0)?;
+                check("softness", softness, 0.0, 2.0)
+            }
+            Self::Bloom {
+                threshold,
+                intensity,
+                radius,
+            } => {
+                check("threshold", threshold, 0.0, 1.0)?;
+                check("intensity", intensity, 0.0, 4.0)?;
+                check("radius", radius, 0.0, MAX_BLUR_RADIUS)
+            }
+            Self::ColorGrade {
+                brightness,
+                contrast,
+                saturation,
+                temperature,
+            } => {
+                check("brightness", brightness, -1.0, 1.0)?;
+                check("contrast", contrast, 0.0, 4.0)?;
+                check("saturation", saturation, 0.0, 4.0)?;
+                check("temperature", temperature, -1.0, 1.0)
+            }
+        }
+    }
+}
+
+/// An effect by name with presentation-friendly settings, e.g. for a
+/// command-line flag: `blur`, `vignette`, `bloom` or `color_grade` (or
+/// `grade`).
+impl std::str::FromStr for PostEffect {
+    type Err = RenderError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "blur" => Ok(Self::Blur { radius: 4.0 }),
+            "vignette" => Ok(Self::Vignette {
+                strength: 0.45,
+                radius: 0.55,
+                softness: 0.6,
+            }),
+            "bloom" => Ok(Self::Bloom {
+                threshold: 0.75,
+                intensity: 0.8,
+                radius: 12.0,
+            }),
+            "color_grade" | "grade" => Ok(Self::ColorGrade {
+                brightness: 0.0,
+                contrast: 1.1,
+                saturation: 1.15,
+                temperature: 0.05,
+            }),
+            _ => Err(RenderError::PostEffect(format!("Unknown effect: {s}"))),
+        }
+    }
+}
+
+/// The effects a renderer applies to its frames and overlay layers.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PostEffects {
+    /// Effects over the whole frame, in order.
+    pub frame: Vec<PostEffect>,
+    /// Effects over the contents of `OverlayLayer`s, by element ID, applied
+    /// before the layer is drawn over what is beneath it.
+    pub layers: HashMap<ElementId, Vec<PostEffect>>,
+}
+
+impl PostEffects {
+    /// Effects over the whole frame only.
+    #[must_use]
+    pub fn frame(effects: Vec<PostEffect>) -> Self {
+        Self {
+            frame: effects,
+            layers: HashMap::new(),
+        }
+    }
+
+    /// Whether no effect is applied anywhere.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.frame.is_empty() && self.layers.values().all(Vec::is_empty)
+    }
+
+    /// The effects over the layer `id`, in order.
+    #[must_use]
+    pub fn layer(&self, id: ElementId) -> &[PostEffect] {
+        self.layers.get(&id).map_or(&[], Vec::as_slice)
+    }
+
+    /// Check every chain is at most [`MAX_EFFECTS`] long and every effect
+    /// [valid](PostEffect::validate).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RenderError::PostEffect`] for the first chain or effect
+    /// that is not.
+    pub fn validate(&self) -> RenderResult<()> {
+        for chain in std::iter::once(&self.frame).chain(self.layers.values()) {
+            if chain.len() > MAX_EFFECTS {
+                return Err(RenderError::PostEffect(format!(
+                    "At most {MAX_EFFECTS} effects per chain, not {}",
+                    chain.len()
+                )));
+            }
+            chain.iter().try_for_each(PostEffect::validate)?;
+        }
+        Ok(())
+    }
+}
+
+/// Samples a blur of `radius` pixels takes on each side of a pixel, and the
+/// distance between them in pixels: one per pixel up to
+/// [`MAX_BLUR_TAPS`], spread further apart beyond it.
+#[must_use]
+pub fn blur_taps(radius: f32) -> (f32, f32) {
+    let radius = radius.clamp(0.0, MAX_BLUR_RADIUS);
+    if radius < 1.0 {
+        return (0.0, 1.0);
+    }
+    let taps = radius.ceil().min(MAX_BLUR_TAPS);
+    (taps, radius / taps)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_effects_read_from_json_and_validate() {
+        let id = ElementId::new();
+        let effects: PostEffects = serde_json::from_value(serde_json::json!({
+            "frame": [
+                { "effect": "bloom", "threshold": 0.7, "intensity": 0.8, "radius": 8 },
+                { "effect": "vignette", "strength": 0.4, "radius": 0.6, "softness": 0.5 }
+            ],
+            "layers": { id.to_string(): [{ "effect": "blur", "radius": 6 }] }
+        }))
+        .expect("effects");
+        assert!(effects.validate().is_ok());
+        assert_eq!(effects.frame[0].name(), "bloom");
+        assert_eq!(effects.layer(id), [PostEffect::Blur { radius: 6.0 }]);
+        assert!(effects.layer(ElementId::new()).is_empty());
+        assert!(!effects.is_empty());
+        assert!(PostEffects::default().is_empty());
+
+        let wide = PostEffects::frame(vec![PostEffect::Blur { radius: 100.0 }]);
+        assert!(matches!(wide.validate(), Err(RenderError::PostEffect(_))));
+        let long = PostEffects::frame(vec![PostEffect::Blur { radius: 1.0 }; MAX_EFFECTS + 1]);
+        assert!(long.validate().is_err());
+        let nan = PostEffect::ColorGrade {
+            brightness: f32::NAN,
+            contrast: 1.0,
+            saturation: 1.0,
+            temperature: 0.0,
+        };
+        assert!(nan.validate().is_err());
+    }
+
+    #[test]
+    fn test_effects_by_name_are_valid() {
+        for name in ["blur", "Vignette", "bloom", "grade", "color_grade"] {
+            let effect: PostEffect = name.parse().expect("known effect");
+            assert!(effect.validate().is_ok(), "{name}");
+        }
+        assert!("sepia".parse::<PostEffect>().is_err());
+    }
+
+    #[test]
+    fn test_wide_blurs_space_out_their_taps() {
+        assert_eq!(blur_taps(0.5), (0.0, 1.0));
+        assert_eq!(blur_taps(6.0), (6.0, 1.0));
+        let (taps, stride) = blur_taps(MAX_BLUR_RADIUS);
+        assert!((taps - MAX_BLUR_TAPS).abs() < f32::EPSILON);
+        assert!((taps * stride - MAX_BLUR_RADIUS).abs() < 1e-4);
+    }
+}
@@ -0,0 +1,533 @@
+//! Print layout: paginating a canvas onto paper for PDF handouts.
+//!
+//! [`PrintLayout::compute`] scales the scene to the printable width of a
+//! page ([`PaperSize`]) and splits it into pages, moving each page break back to
+//! the top (or left) edge of any element it would cut through, as long as
+//! that element fits on a page. [`SceneExporter::export_print`] renders the
+//! pages with a header (title and timestamp) and a page-number footer.
+//!
+//! [`SceneExporter::export_print`]: crate::export::SceneExporter::export_print
+
+use std::collections::HashSet;
+use std::fmt::Write;
+
+use canvas_core::{ElementId, Scene, SceneBounds};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{RenderError, RenderResult};
+
+/// Millimetres per inch.
+const MM_PER_INCH: f32 = 25.4;
+
+/// Millimetres per canvas unit at the canvas's natural 96 DPI; small scenes
+/// are never enlarged past this.
+const NATURAL_SCALE: f32 = MM_PER_INCH / 96.0;
+
+/// Height of the header and footer bands inside the margins, in millimetres.
+pub(crate) const BAND_MM: f32 = 8.0;
+
+/// Font size of the header and footer, in points.
+const BAND_FONT_PT: f32 = 9.0;
+
+/// Largest number of pages a print export may produce.
+pub const MAX_PRINT_PAGES: usize = 200;
+
+/// Paper size of a print export.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PaperSize {
+    /// ISO A4, 210 × 297 mm.
+    #[default]
+    A4,
+    /// US Letter, 8.5 × 11 in.
+    Letter,
+    /// ISO A3, 297 × 420 mm, for wall charts and large boards.
+    A3,
+    /// ISO A5, 148 × 210 mm.
+    A5,
+    /// US Legal, 8.5 × 14 in.
+    Legal,
+    /// US Tabloid, 11 × 17 in.
+    Tabloid,
+}
+
+impl PaperSize {
+    /// Portrait (width, height) in millimetres.
+    #[must_use]
+    pub fn size_mm(self) -> (f32, f32) {
+        match self {
+            Self::A4 => (210.0, 297.0),
+            Self::Letter => (215.9, 279.4),
+            Self::A3 => (297.0, 420.0),
+            Self::A5 => (148.0, 210.0),
+            Self::Legal => (215.9, 355.6),
+            Self::Tabloid => (279.4, 431.8),
+        }
+    }
+}
+
+/// Page orientation of a print export.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Orientation {
+    /// Taller than wide.
+    #[default]
+    Portrait,
+    /// Wider than tall.
+    Landscape,
+}
+
+/// Configuration for a paginated print export.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PrintConfig {
+    /// Paper size (default A4).
+    pub paper: PaperSize,
+    /// Page orientation (default portrait).
+    pub orientation: Orientation,
+    /// Margin on every side, in millimetres (default 12).
+    pub margin_mm: f32,
+    /// Millimetres per canvas unit. Defaults to fitting the content to the
+    /// printable width, but never larger than the canvas's natural 96 DPI.
+    pub scale: Option<f32>,
+    /// Header text, usually the session name.
+    pub title: Option<String>,
+    /// Unix time (seconds) shown in the header; defaults to now.
+    pub timestamp: Option<u64>,
+    /// Raster resolution of each page (default 150).
+    pub dpi: f32,
+}
+
+impl Default for PrintConfig {
+    fn default() -> Self {
+        Self {
+            paper: PaperSize::A4,
+            orientation: Orientation::Portrait,
+            margin_mm: 12.0,
+            scale: None,
+            title: None,
+            timestamp: None,
+            dpi: 150.0,
+        }
+    }
+}
+
+impl PrintConfig {
+    /// Page (width, height) in millimetres, after orientation.
+    #[must_use]
+    pub fn page_mm(&self) -> (f32, f32) {
+        let (w, h) = self.paper.size_mm();
+        match self.orientation {
+            Orientation::Portrait => (w, h),
+            Orientation::Landscape => (h, w),
+        }
+    }
+
+    /// (width, height) in millimetres left for content inside the margins
+    /// and the header and footer bands.
+    #[must_use]
+    pub fn content_mm(&self) -> (f32, f32) {
+        let (w, h) = self.page_mm();
+        (
+            w - 2.0 * self.margin_mm,
+            h - 2.0 * self.margin_mm - 2.0 * BAND_MM,
+        )
+    }
+
+    /// Check that the margins leave room for content and the resolution
+    /// and scale are usable.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error describing the first invalid setting.
+    pub fn validate(&self) -> RenderResult<()> {
+        let (content_w, content_h) = self.content_mm();
+        if !self.margin_mm.is_finite()
+            || self.margin_mm < 0.0
+            || content_w < 10.0
+            || content_h < 10.0
+        {
+            return Err(RenderError::Export(format!(
+                "Margin {} mm leaves no room for content",
+                self.margin_mm
+            )));
+        }
+        if !(self.dpi.is_finite() && (36.0..=600.0).contains(&self.dpi)) {
+            return Err(RenderError::Export(format!(
+                "Print DPI must be between 36 and 600, got {}",
+                self.dpi
+            )));
+        }
+        if let Some(scale) = self.scale {
+            if !(scale.is_finite() && scale > 0.0) {
+                return Err(RenderError::Export(format!(
+                    "Print scale must be positive, got {scale}"
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Convert millimetres to pixels at the configured DPI.
+    #[must_use]
+    pub fn px(&self, mm: f32) -> f32 {
+        mm / MM_PER_INCH * self.dpi
+    }
+
+    /// Header and footer bands for page `number` (1-based) of `count`, as SVG
+    /// in page pixel coordinates.
+    pub(crate) fn bands_svg(&self, number: usize, count: usize) -> String {
+        let (page_w, page_h) = self.page_mm();
+        let font = BAND_FONT_PT / 72.0 * self.dpi;
+        let left = self.px(self.margin_mm);
+        let right = self.px(page_w - self.margin_mm);
+        let header_y = self.px(self.margin_mm + BAND_MM / 2.0);
+        let footer_y = self.px(page_h - self.margin_mm - BAND_MM / 2.0);
+        let timestamp = format_utc(self.timestamp.unwrap_or_else(now_secs));
+
+        let mut svg = String::new();
+        let _ = write!(
+            svg,
+            "<g font-family=\"sans-serif\" font-size=\"{font}\" fill=\"#555555\" dominant-baseline=\"central\">"
+        );
+        if let Some(title) = &self.title {
+            let _ = write!(
+                svg,
+                "<text x=\"{left}\" y=\"{header_y}\">{}</text>",
+                crate::export::escape_xml(title)
+            );
+        }
+        let _ = write!(
+            svg,
+            "<text x=\"{right}\" y=\"{header_y}\" text-anchor=\"end\">{timestamp}</text>\
+             <text x=\"{}\" y=\"{footer_y}\" text-anchor=\"middle\">Page {number} of {count}</text></g>",
+            self.px(page_w / 2.0),
+        );
+        svg
+    }
+}
+
+/// One page of a print layout.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PrintPage {
+    /// Area of the canvas shown on this page, in canvas units.
+    pub view: SceneBounds,
+    /// Zero-based row of the page in the layout grid.
+    pub row: usize,
+    /// Zero-based column of the page in the layout grid.
+    pub column: usize,
+}
+
+/// A scene split into pages.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PrintLayout {
+    /// Millimetres per canvas unit.
+    pub scale: f32,
+    /// Pages in reading order (row by row), skipping empty grid cells.
+    pub pages: Vec<PrintPage>,
+}
+
+impl PrintLayout {
+    /// Split the scene's content into pages.
+    ///
+    /// Top-level elements (with their children) are kept whole where they
+    /// fit on one page; larger ones are split across pages. An empty scene
+    /// produces one blank page.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the config is invalid or the layout would need
+    /// more than [`MAX_PRINT_PAGES`] pages.
+    pub fn compute(scene: &Scene, config: &PrintConfig) -> RenderResult<Self> {
+        config.validate()?;
+        let (content_w, content_h) = config.content_mm();
+        let blocks = top_level_bounds(scene);
+        let Some(content) = blocks.iter().copied().reduce(|a, b| a.union(&b)) else {
+            let scale = config.scale.unwrap_or(NATURAL_SCALE);
+            return Ok(Self {
+                scale,
+                pages: vec![PrintPage {
+                    view: SceneBounds {
+                        min_x: 0.0,
+                        min_y: 0.0,
+                        max_x: content_w / scale,
+                        max_y: content_h / scale,
+                    },
+                    row: 0,
+                    column: 0,
+                }],
+            });
+        };
+
+        let scale = config
+            .scale
+            .unwrap_or_else(|| (content_w / content.width().max(1.0)).min(NATURAL_SCALE));
+        let (page_w, page_h) = (content_w / scale, content_h / scale);
+
+        let columns = page_breaks(
+            content.min_x,
+            content.max_x,
+            page_w,
+            blocks.iter().map(|b| (b.min_x, b.max_x)),
+        );
+        let rows = page_breaks(
+            content.min_y,
+            content.max_y,
+            page_h,
+            blocks.iter().map(|b| (b.min_y, b.max_y)),
+        );
+        if rows.len().saturating_mul(columns.len()) > MAX_PRINT_PAGES * 4 {
+            return Err(too_many_pages(rows.len() * columns.len()));
+        }
+
+        let mut pages = Vec::new();
+        for (row, &(min_y, max_y)) in rows.iter().enumerate() {
+            for (column, &(min_x, max_x)) in columns.iter().enumerate() {
+                let view = SceneBounds {
+                    min_x,
+                    min_y,
+                    max_x,
+                    max_y,
+                };
+                if blocks.iter().any(|b| overlaps(b, &view)) {
+                    pages.push(PrintPage { view, row, column });
+                }
+            }
+        }
+        if pages.len() > MAX_PRINT_PAGES {
+            return Err(too_many_pages(pages.len()));
+        }
+        Ok(Self { scale, pages })
+    }
+}
+
+fn too_many_pages(count: usize) -> RenderError {
+    RenderError::Export(format!(
+        "Print layout needs {count} pages, more than {MAX_PRINT_PAGES}; use a smaller scale"
+    ))
+}
+
+/// Whether `block` has area inside `view` (touching edges do not count).
+fn overlaps(block: &SceneBounds, view: &SceneBounds) -> bool {
+    block.min_x < view.max_x
+        && block.max_x > view.min_x
+        && block.min_y < view.max_y
+        && block.max_y > view.min_y
+}
+
+/// Bounds of each element that is not a child of a container, including
+/// its children. Screen-anchored elements are not on the page.
+fn top_level_bounds(scene: &Scene) -> Vec<SceneBounds> {
+    let nested: HashSet<ElementId> = scene
+        .elements()
+        .flat_map(|e| e.kind.children().iter().copied())
+        .collect();
+    scene
+        .elements()
+        .filter(|e| !nested.contains(&e.id) && !e.screen_anchored)
+        .filter_map(|e| {
+            let mut subtree = Vec::new();
+            crate::export::collect_subtree(scene, e.id, &mut subtree);
+            subtree
+                .iter()
+                .map(|e| SceneBounds::from_transform(&e.transform))
+                .filter(|b| b.min_x.is_finite() && b.max_x.is_finite())
+                .filter(|b| b.min_y.is_finite() && b.max_y.is_finite())
+                .reduce(|a, b| a.union(&b))
+        })
+        .collect()
+}
+
+/// Split `[start, end]` into spans of at most `page` units.
+///
+/// A break that would cut through one of `blocks` (given as `(min, max)`)
+/// moves back to the block's start, unless the block is longer than a page
+/// or already starts at the top of the current page.
+fn page_breaks(
+    start: f32,
+    end: f32,
+    page: f32,
+    blocks: impl Iterator<Item = (f32, f32)>,
+) -> Vec<(f32, f32)> {
+    let blocks: Vec<(f32, f32)> = blocks.filter(|(a, b)| b - a <= page).collect();
+    let mut spans = Vec::new();
+    let mut top = start;
+    loop {
+        let mut bottom = top + page;
+        if bottom >= end {
+            spans.push((top, bottom));
+            return spans;
+        }
+        // Moving the break can make it cut a different block; repeat until
+        // it settles. It only ever moves up and stays below `top`.
+        while let Some(block_top) = blocks
+            .iter()
+            .filter(|(a, b)| *a > top && *a < bottom && *b > bottom)
+            .map(|(a, _)| *a)
+            .reduce(f32::min)
+        {
+            bottom = block_top;
+        }
+        spans.push((top, bottom));
+        top = bottom;
+        if spans.len() > MAX_PRINT_PAGES * 4 {
+            return spans;
+        }
+    }
+}
+
+/// Current Unix time in seconds.
+fn now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+/// Format Unix seconds as `YYYY-MM-DD HH:MM UTC`.
+fn format_utc(secs: u64) -> String {
+    let days = i64::try_from(secs / 86_400).unwrap_or(i64::MAX);
+    let minutes = secs % 86_400 / 60;
+
+    // Civil-from-days (Howard Hinnant), valid for any date after 1970
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02} UTC",
+        minutes / 60,
+        minutes % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use canvas_core::element::{Element, ElementKind, Transform};
+    use canvas_core::Color;
+
+    fn note_at(y: f32, height: f32) -> Element {
+        Element::new(ElementKind::StickyNote {
+            content: "Note".to_string(),
+            color: Color::from_rgb(0xff_f59d).into(),
+            author: None,
+        })
+        .with_transform(Transform {
+            x: 0.0,
+            y,
+            width: 400.0,
+            height,
+            rotation: 0.0,
+            z_index: 0,
+            auto_size: false,
+        })
+    }
+
+    #[test]
+    fn test_page_breaks_avoid_splitting_blocks() {
+        let breaks = page_breaks(
+            0.0,
+            250.0,
+            100.0,
+            [(80.0, 120.0), (130.0, 160.0)].into_iter(),
+        );
+        assert_eq!(breaks, vec![(0.0, 80.0), (80.0, 180.0), (180.0, 280.0)]);
+
+        // A block taller than a page is split where it falls
+        let breaks = page_breaks(0.0, 150.0, 100.0, [(50.0, 200.0)].into_iter());
+        assert_eq!(breaks, vec![(0.0, 100.0), (100.0, 200.0)]);
+    }
+
+    #[test]
+    fn test_layout_paginates_tall_scene() {
+        let mut scene = Scene::new(800.0, 600.0);
+        // Fit-to-width: 400 units across the 186 mm printable width
+        for i in 0..6u8 {
+            scene.add_element(note_at(f32::from(i) * 300.0, 250.0));
+        }
+        let config = PrintConfig::default();
+        let layout = PrintLayout::compute(&scene, &config).expect("layout");
+
+        let (content_w, content_h) = config.content_mm();
+        assert!((layout.scale - (content_w / 400.0).min(NATURAL_SCALE)).abs() < 1e-6);
+        assert!(layout.pages.len() > 1);
+        let page_h = content_h / layout.scale;
+        for page in &layout.pages {
+            assert!(page.view.height() <= page_h + 1e-3);
+            // No note is cut by a page break
+            for i in 0..6u8 {
+                let top = f32::from(i) * 300.0;
+                let bottom = top + 250.0;
+                let cut = top < page.view.max_y && bottom > page.view.max_y;
+                assert!(!cut, "note {i} split at {}", page.view.max_y);
+            }
+        }
+        assert!(layout.pages.iter().all(|p| p.column == 0));
+    }
+
+    #[test]
+    fn test_layout_empty_scene_and_limits() {
+        let scene = Scene::new(800.0, 600.0);
+        let layout = PrintLayout::compute(&scene, &PrintConfig::default()).expect("layout");
+        assert_eq!(layout.pages.len(), 1);
+
+        let mut scene = Scene::new(800.0, 600.0);
+        scene.add_element(note_at(0.0, 1_000_000.0));
+        let config = PrintConfig {
+            scale: Some(1.0),
+            ..PrintConfig::default()
+        };
+        assert!(PrintLayout::compute(&scene, &config).is_err());
+
+        let config = PrintConfig {
+            margin_mm: 150.0,
+            ..PrintConfig::default()
+        };
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_paper_sizes() {
+        let config = PrintConfig {
+            paper: PaperSize::A3,
+            orientation: Orientation::Landscape,
+            ..PrintConfig::default()
+        };
+        assert_eq!(config.page_mm(), (420.0, 297.0));
+        assert_eq!(PaperSize::A5.size_mm(), (148.0, 210.0));
+        assert_eq!(
+            serde_json::from_str::<PaperSize>("\"tabloid\"").unwrap(),
+            PaperSize::Tabloid
+        );
+
+        // Larger paper fits more of the scene on each page
+        let mut scene = Scene::new(800.0, 600.0);
+        for i in 0..6u8 {
+            scene.add_element(note_at(f32::from(i) * 300.0, 250.0));
+        }
+        let pages = |paper| {
+            let config = PrintConfig {
+                paper,
+                ..PrintConfig::default()
+            };
+            PrintLayout::compute(&scene, &config)
+                .expect("layout")
+                .pages
+                .len()
+        };
+        assert!(pages(PaperSize::Legal) <= pages(PaperSize::Letter));
+        assert!(pages(PaperSize::A5) >= pages(PaperSize::A4));
+    }
+
+    #[test]
+    fn test_format_utc() {
+        assert_eq!(format_utc(0), "1970-01-01 00:00 UTC");
+        assert_eq!(format_utc(1_709_210_096), "2024-02-29 12:34 UTC");
+    }
+}
@@ -29,9 +29,8 @@
 use crate::backend::wgpu::WgpuBackend;
 #[cfg(feature = "gpu")]
 use crate::error::RenderResult;
-use crate::spatial::{Camera, HolographicConfig};
-#[cfg(feature = "gpu")]
-use canvas_core::Scene;
+use crate::spatial::{Camera, CanvasDepth, HolographicConfig, Mat4};
+use canvas_core::{Element, ElementKind, Scene};
 use serde::{Deserialize, Serialize};
 
 /// A single view in the quilt.
@@ -62,6 +61,10 @@ pub struct Quilt {
     pub total_width: u32,
     /// Total height of the quilt texture.
     pub total_height: u32,
+    /// Camera the views are arranged around.
+    pub base_camera: Camera,
+    /// How far canvas elements stand from the focal plane.
+    pub depth: CanvasDepth,
 }
 
 impl Quilt {
@@ -91,9 +94,18 @@ impl Quilt {
             views,
             total_width,
             total_height,
+            base_camera: base_camera.clone(),
+            depth: CanvasDepth::default(),
         }
     }
 
+    /// Place canvas elements at depths from `depth`.
+    #[must_use]
+    pub fn with_depth(mut self, depth: CanvasDepth) -> Self {
+        self.depth = depth;
+        self
+    }
+
     /// Get the center view (the one that appears at the "front" of the hologram).
     #[must_use]
     pub fn center_view(&self) -> Option<&QuiltView> {
@@ -160,6 +172,8 @@ impl Quilt {
             self.total_height,
             &self.views,
             scene,
+            &self.base_camera,
+            &self.depth,
         )?;
 
         Ok(QuiltRenderTarget {
@@ -168,10 +182,97 @@ impl Quilt {
             pixels,
         })
     }
+
+    /// Render the depth of each view into a quilt of the same layout.
+    ///
+    /// Pixels are gray, brighter for nearer content: each element is drawn
+    /// as its rectangle at its depth, normalized by
+    /// [`CanvasDepth::normalized`], over the focal plane's mid gray. 3D
+    /// models and overlay layers are left out.
+    #[must_use]
+    pub fn render_depth_map(&self, scene: &Scene) -> QuiltRenderTarget {
+        let to_world = CanvasDepth::canvas_to_world(
+            &self.base_camera,
+            scene.viewport_width,
+            scene.viewport_height,
+        );
+        let mut elements: Vec<(&Element, f32)> = scene
+            .elements()
+            .filter(|e| scene.is_shown(e))
+            .filter(|e| {
+                !matches!(
+                    e.kind,
+                    ElementKind::OverlayLayer { .. } | ElementKind::Model3D { .. }
+                )
+            })
+            .map(|e| (e, self.depth.element_depth(e)))
+            .collect();
+        // Far to near, so nearer elements cover further ones
+        elements.sort_by(|a, b| a.1.total_cmp(&b.1));
+
+        let mut target = QuiltRenderTarget::from_quilt(self);
+        target.clear(depth_color(self.depth.normalized(0.0)));
+        for view in &self.views {
+            let to_clip = view_projection(view).mul(&to_world);
+            for &(element, depth) in &elements {
+                let corners = element_corners(element, depth).map(|c| to_clip.transform(c));
+                if corners.iter().any(|c| c[3] <= f32::EPSILON) {
+                    continue;
+                }
+                let points = corners.map(|c| view_point(view, c));
+                target.fill_quad(view, &points, depth_color(self.depth.normalized(depth)));
+            }
+        }
+        target
+    }
+}
+
+/// Clip space of `view`'s camera.
+#[allow(clippy::cast_precision_loss)] // View dimensions fit in f32 mantissa
+fn view_projection(view: &QuiltView) -> Mat4 {
+    let aspect = view.width as f32 / view.height.max(1) as f32;
+    view.camera
+        .projection_matrix(aspect)
+        .mul(&view.camera.view_matrix())
+}
+
+/// Corners of `element`'s rectangle at `depth`, in canvas units, in order
+/// around it.
+fn element_corners(element: &Element, depth: f32) -> [[f32; 4]; 4] {
+    let t = &element.transform;
+    let (cx, cy) = (t.x + t.width / 2.0, t.y + t.height / 2.0);
+    let (sin, cos) = t.rotation.sin_cos();
+    [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)].map(|(sx, sy)| {
+        let (dx, dy) = (sx * t.width / 2.0, sy * t.height / 2.0);
+        [
+            cx + dx * cos - dy * sin,
+            cy + dx * sin + dy * cos,
+            depth,
+            1.0,
+        ]
+    })
+}
+
+/// Pixel position of the clip space point `clip` within `view`.
+#[allow(clippy::cast_precision_loss)] // View dimensions fit in f32 mantissa
+fn view_point(view: &QuiltView, clip: [f32; 4]) -> (f32, f32) {
+    let (x, y) = (clip[0] / clip[3], clip[1] / clip[3]);
+    (
+        f32::midpoint(x, 1.0) * view.width as f32,
+        (1.0 - y) / 2.0 * view.height as f32,
+    )
+}
+
+/// Gray depth map pixel for a normalized depth.
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn depth_color(normalized: f32) -> [u8; 4] {
+    let value = (normalized.clamp(0.0, 1.0) * 255.0).round() as u8;
+    [value, value, value, 255]
 }
 
 /// Settings for quilt rendering.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[allow(clippy::struct_excessive_bools)]
 pub struct QuiltRenderSettings {
     /// Clear color for each view (RGBA).
     pub clear_color: [f32; 4],
@@ -181,6 +282,12 @@ pub struct QuiltRenderSettings {
     pub depth_test: bool,
     /// Whether to enable backface culling.
     pub backface_cull: bool,
+    /// How far canvas elements stand from the focal plane.
+    #[serde(default)]
+    pub depth: CanvasDepth,
+    /// Whether to render a depth map alongside each quilt.
+    #[serde(default)]
+    pub depth_map: bool,
 }
 
 impl Default for QuiltRenderSettings {
@@ -190,6 +297,8 @@ impl Default for QuiltRenderSettings {
             wireframe: false,
             depth_test: true,
             backface_cull: true,
+            depth: CanvasDepth::default(),
+            depth_map: false,
         }
     }
 }
@@ -228,14 +337,68 @@ impl QuiltRenderTarget {
 
     /// Fill a rectangular region with a color.
     pub fn fill_rect(&mut self, x: u32, y: u32, width: u32, height: u32, color: [u8; 4]) {
+        // One row of the color, copied into each row of the rectangle
+        let span = color.repeat(width.min(self.width.saturating_sub(x)) as usize);
         for row in y..(y + height).min(self.height) {
-            for col in x..(x + width).min(self.width) {
-                let idx = ((row * self.width + col) * 4) as usize;
-                if idx + 3 < self.pixels.len() {
-                    self.pixels[idx] = color[0];
-                    self.pixels[idx + 1] = color[1];
-                    self.pixels[idx + 2] = color[2];
-                    self.pixels[idx + 3] = color[3];
+            let start = ((row * self.width + x) * 4) as usize;
+            if let Some(pixels) = self.pixels.get_mut(start..start + span.len()) {
+                pixels.copy_from_slice(&span);
+            }
+        }
+    }
+
+    /// Copy `tile` in with its top left corner at (`x`, `y`), clipped to
+    /// this target.
+    pub fn copy_from(&mut self, tile: &Self, x: u32, y: u32) {
+        let width = tile.width.min(self.width.saturating_sub(x)) as usize * 4;
+        for row in 0..tile.height.min(self.height.saturating_sub(y)) {
+            let from = (row * tile.width) as usize * 4;
+            let to = ((y + row) * self.width + x) as usize * 4;
+            self.pixels[to..to + width].copy_from_slice(&tile.pixels[from..from + width]);
+        }
+    }
+
+    /// Fill the convex quadrilateral `points`, in pixels within `view`,
+    /// clipped to the view.
+    #[allow(
+        clippy::cast_precision_loss,
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss
+    )]
+    fn fill_quad(&mut self, view: &QuiltView, points: &[(f32, f32); 4], color: [u8; 4]) {
+        let (mut min_x, mut min_y) = (f32::MAX, f32::MAX);
+        let (mut max_x, mut max_y) = (f32::MIN, f32::MIN);
+        for &(x, y) in points {
+            (min_x, min_y) = (min_x.min(x), min_y.min(y));
+            (max_x, max_y) = (max_x.max(x), max_y.max(y));
+        }
+        let clip = |value: f32, size: u32| value.clamp(0.0, size as f32) as u32;
+        let (x0, x1) = (
+            clip(min_x.floor(), view.width),
+            clip(max_x.ceil(), view.width),
+        );
+        let (y0, y1) = (
+            clip(min_y.floor(), view.height),
+            clip(max_y.ceil(), view.height),
+        );
+
+        // Inside when on the same side of every edge, whichever way round
+        // the corners go
+        let edge = |i: usize, x: f32, y: f32| {
+            let (ax, ay) = points[i];
+            let (bx, by) = points[(i + 1) % 4];
+            (bx - ax) * (y - ay) - (by - ay) * (x - ax)
+        };
+        for y in y0..y1 {
+            for x in x0..x1 {
+                let (px, py) = (x as f32 + 0.5, y as f32 + 0.5);
+                let sides = [0, 1, 2, 3].map(|i| edge(i, px, py));
+                if sides.iter().all(|&side| side >= 0.0) || sides.iter().all(|&side| side <= 0.0) {
+                    let index =
+                        (((view.y_offset + y) * self.width + view.x_offset + x) * 4) as usize;
+                    if let Some(pixel) = self.pixels.get_mut(index..index + 4) {
+                        pixel.copy_from_slice(&color);
+                    }
                 }
             }
         }
@@ -243,12 +406,7 @@ impl QuiltRenderTarget {
 
     /// Clear the entire render target with a color.
     pub fn clear(&mut self, color: [u8; 4]) {
-        for chunk in self.pixels.chunks_exact_mut(4) {
-            chunk[0] = color[0];
-            chunk[1] = color[1];
-            chunk[2] = color[2];
-            chunk[3] = color[3];
-        }
+        self.pixels = color.repeat(self.pixels.len() / 4);
     }
 
     /// Get the pixel at a specific coordinate.
@@ -0,0 +1,421 @@
+//! # Quilt Video
+//!
+//! Records a sequence of quilts into a video file for playback on Looking
+//! Glass devices. Each quilt is piped as a raw RGBA frame into an `ffmpeg`
+//! process, which encodes HEVC into MP4 or VP9 into `WebM`:
+//!
+//! ```text
+//! render_quilt ─▶ QuiltRenderTarget ─▶ QuiltRecorder ─▶ ffmpeg ─▶ session_qs5x9a0.75.mp4
+//! ```
+//!
+//! The file name carries the quilt metadata Looking Glass software reads to
+//! slice each frame back into views: `_qs{columns}x{rows}a{aspect}`, where
+//! the aspect is a view's width over its height. Frames are laid out as
+//! Looking Glass expects, view 0 at the bottom left.
+//!
+//! Recording needs an `ffmpeg` build with `libx265` (HEVC) or `libvpx-vp9`
+//! (`WebM`) on the `PATH`, or at [`QuiltVideoConfig::ffmpeg`].
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdin, Command, Stdio};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{RenderError, RenderResult};
+use crate::quilt::QuiltRenderTarget;
+use crate::spatial::HolographicConfig;
+
+/// Video codec and container for a quilt recording.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QuiltVideoCodec {
+    /// HEVC (H.265) in MP4, what Looking Glass Studio and Bridge play best.
+    #[default]
+    Hevc,
+    /// VP9 in `WebM`, for browser players.
+    Vp9,
+}
+
+impl QuiltVideoCodec {
+    /// File extension of the container.
+    #[must_use]
+    pub const fn extension(self) -> &'static str {
+        match self {
+            Self::Hevc => "mp4",
+            Self::Vp9 => "webm",
+        }
+    }
+
+    /// Short name used in logs.
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Hevc => "hevc",
+            Self::Vp9 => "vp9",
+        }
+    }
+
+    /// The `ffmpeg` encoder options for this codec at constant quality `crf`.
+    fn encoder_args(self, crf: u8) -> Vec<String> {
+        let crf = crf.to_string();
+        let args: &[&str] = match self {
+            // hvc1 tags the stream so Apple players accept it
+            Self::Hevc => &["-c:v", "libx265", "-tag:v", "hvc1", "-crf", &crf],
+            // VP9 only honours the CRF with the bitrate cap lifted
+            Self::Vp9 => &[
+                "-c:v",
+                "libvpx-vp9",
+                "-b:v",
+                "0",
+                "-row-mt",
+                "1",
+                "-crf",
+                &crf,
+            ],
+        };
+        args.iter().map(ToString::to_string).collect()
+    }
+}
+
+/// Settings for a quilt recording.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuiltVideoConfig {
+    /// Codec and container.
+    pub codec: QuiltVideoCodec,
+    /// Frames per second of the video.
+    pub fps: u32,
+    /// Constant-quality factor, lower is better: 0-51 for HEVC, 0-63 for
+    /// VP9. Quilts need more detail than ordinary video, as each view is
+    /// small.
+    pub crf: u8,
+    /// The `ffmpeg` executable.
+    pub ffmpeg: PathBuf,
+}
+
+impl Default for QuiltVideoConfig {
+    fn default() -> Self {
+        Self {
+            codec: QuiltVideoCodec::default(),
+            fps: 30,
+            crf: 18,
+            ffmpeg: PathBuf::from("ffmpeg"),
+        }
+    }
+}
+
+impl QuiltVideoConfig {
+    /// The `ffmpeg` arguments to encode `width` x `height` RGBA frames from
+    /// stdin into `path`.
+    fn ffmpeg_args(&self, width: u32, height: u32, path: &Path) -> Vec<String> {
+        let mut args: Vec<String> = [
+            "-hide_banner",
+            "-loglevel",
+            "error",
+            "-y",
+            "-f",
+            "rawvideo",
+            "-pix_fmt",
+            "rgba",
+            "-s",
+        ]
+        .iter()
+        .map(ToString::to_string)
+        .collect();
+        args.push(format!("{width}x{height}"));
+        args.extend(["-r".to_string(), self.fps.to_string()]);
+        args.extend(["-i".to_string(), "-".to_string()]);
+        // 4:2:0 video needs even sides; pad at the top so the views keep
+        // their places from the bottom left
+        args.extend([
+            "-vf".to_string(),
+            "pad=ceil(iw/2)*2:ceil(ih/2)*2:0:oh-ih".to_string(),
+            "-pix_fmt".to_string(),
+            "yuv420p".to_string(),
+        ]);
+        args.extend(self.codec.encoder_args(self.crf));
+        args.push(path.display().to_string());
+        args
+    }
+}
+
+/// The file name for a recording of `quilt`: `stem` followed by the Looking
+/// Glass quilt suffix, e.g. `session_qs5x9a0.75.mp4`.
+#[must_use]
+#[allow(clippy::cast_precision_loss)] // View sizes are small
+pub fn quilt_file_name(stem: &str, quilt: &HolographicConfig, codec: QuiltVideoCodec) -> String {
+    let aspect = quilt.view_width as f32 / quilt.view_height.max(1) as f32;
+    let aspect = format!("{aspect:.4}");
+    let aspect = aspect.trim_end_matches('0').trim_end_matches('.');
+    format!(
+        "{stem}_qs{}x{}a{aspect}.{}",
+        quilt.quilt_columns,
+        quilt.quilt_rows,
+        codec.extension()
+    )
+}
+
+/// Records quilts into a video file through `ffmpeg`.
+///
+/// Start one with [`HolographicRenderer::record`](crate::HolographicRenderer::record),
+/// [`push`](Self::push) each rendered quilt, then [`finish`](Self::finish)
+/// to close the file. Dropping a recorder without finishing it stops the
+/// encoder and leaves the file incomplete.
+#[derive(Debug)]
+pub struct QuiltRecorder {
+    encoder: Option<Child>,
+    stdin: Option<ChildStdin>,
+    path: PathBuf,
+    quilt: HolographicConfig,
+    frames: u64,
+    frame: Vec<u8>,
+}
+
+impl QuiltRecorder {
+    /// Start recording quilts laid out as `quilt` into a file next to
+    /// `output`, named as [`quilt_file_name`] names it: `output` is
+    /// `recordings/session` for `recordings/session_qs5x9a0.75.mp4`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the frame rate is zero, the quilt is empty, or
+    /// `ffmpeg` cannot be started.
+    pub fn start(
+        output: impl AsRef<Path>,
+        quilt: &HolographicConfig,
+        config: &QuiltVideoConfig,
+    ) -> RenderResult<Self> {
+        if config.fps == 0 {
+            return Err(RenderError::Export(
+                "Quilt video needs a frame rate above zero".to_string(),
+            ));
+        }
+        let (width, height) = (quilt.quilt_width(), quilt.quilt_height());
+        if width == 0 || height == 0 {
+            return Err(RenderError::Export(format!(
+                "Quilt {width}x{height} has no pixels to record"
+            )));
+        }
+
+        let output = output.as_ref();
+        let stem = output
+            .file_name()
+            .map_or_else(|| "quilt".into(), |name| name.to_string_lossy());
+        let path = output.with_file_name(quilt_file_name(&stem, quilt, config.codec));
+
+        let mut encoder = Command::new(&config.ffmpeg)
+            .args(config.ffmpeg_args(width, height, &path))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| {
+                RenderError::Export(format!(
+                    "Could not start {}: {e}; quilt video needs ffmpeg",
+                    config.ffmpeg.display()
+                ))
+            })?;
+        let stdin = encoder.stdin.take();
+        tracing::info!(
+            "Recording {width}x{height} quilts as {} to {}",
+            config.codec.name(),
+            path.display()
+        );
+
+        Ok(Self {
+            encoder: Some(encoder),
+            stdin,
+            path,
+            quilt: quilt.clone(),
+            frames: 0,
+            frame: Vec::new(),
+        })
+    }
+
+    /// The file being written.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Number of frames recorded so far.
+    #[must_use]
+    pub const fn frames(&self) -> u64 {
+        self.frames
+    }
+
+    /// Append a quilt as the next frame.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the quilt is not the recording's size or the
+    /// encoder has stopped.
+    pub fn push(&mut self, target: &QuiltRenderTarget) -> RenderResult<()> {
+        frame_from_quilt(target, &self.quilt, &mut self.frame)?;
+        let stdin = self
+            .stdin
+            .as_mut()
+            .ok_or_else(|| RenderError::Export("The recording is finished".to_string()))?;
+        stdin
+            .write_all(&self.frame)
+            .map_err(|e| RenderError::Export(format!("The encoder stopped: {e}")))?;
+        self.frames += 1;
+        Ok(())
+    }
+
+    /// Close the video and wait for the encoder, returning the file's path.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no frames were recorded or the encoder fails,
+    /// with its last message.
+    pub fn finish(mut self) -> RenderResult<PathBuf> {
+        // Closing stdin ends the input
+        drop(self.stdin.take());
+        let Some(encoder) = self.encoder.take() else {
+            return Err(RenderError::Export("The recording is finished".to_string()));
+        };
+        let output = encoder
+            .wait_with_output()
+            .map_err(|e| RenderError::Export(format!("The encoder stopped: {e}")))?;
+        if self.frames == 0 {
+            return Err(RenderError::Export("No quilts were recorded".to_string()));
+        }
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let message = stderr.lines().last().unwrap_or("no output");
+            return Err(RenderError::Export(format!(
+                "ffmpeg failed ({}): {message}",
+                output.status
+            )));
+        }
+        tracing::info!("Recorded {} quilts to {}", self.frames, self.path.display());
+        Ok(std::mem::take(&mut self.path))
+    }
+}
+
+impl Drop for QuiltRecorder {
+    fn drop(&mut self) {
+        if let Some(mut encoder) = self.encoder.take() {
+            drop(self.stdin.take());
+            let _ = encoder.kill();
+            let _ = encoder.wait();
+        }
+    }
+}
+
+/// Lay `target` out as a video frame in `frame`: quilts are rendered with
+/// view 0 in the first rows, and Looking Glass reads it from the bottom.
+fn frame_from_quilt(
+    target: &QuiltRenderTarget,
+    quilt: &HolographicConfig,
+    frame: &mut Vec<u8>,
+) -> RenderResult<()> {
+    let (width, height) = (quilt.quilt_width(), quilt.quilt_height());
+    let row_bytes = width as usize * 4;
+    if target.width != width
+        || target.height != height
+        || target.pixels.len() != row_bytes * height as usize
+    {
+        return Err(RenderError::Export(format!(
+            "Quilt is {}x{}, the recording is {width}x{height}",
+            target.width, target.height
+        )));
+    }
+
+    frame.clear();
+    let band = row_bytes * quilt.view_height as usize;
+    for rows in target.pixels.chunks(band).rev() {
+        frame.extend_from_slice(rows);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid(columns: u32, rows: u32, view: u32) -> HolographicConfig {
+        HolographicConfig {
+            num_views: columns * rows,
+            quilt_columns: columns,
+            quilt_rows: rows,
+            view_width: view,
+            view_height: view,
+            ..HolographicConfig::looking_glass_portrait()
+        }
+    }
+
+    #[test]
+    fn test_file_names_carry_the_quilt_layout() {
+        let portrait = HolographicConfig::looking_glass_portrait();
+        assert_eq!(
+            quilt_file_name("demo", &portrait, QuiltVideoCodec::Hevc),
+            "demo_qs5x9a0.75.mp4"
+        );
+        assert_eq!(
+            quilt_file_name(
+                "demo",
+                &HolographicConfig::looking_glass_4k(),
+                QuiltVideoCodec::Vp9
+            ),
+            "demo_qs5x9a1.8.webm"
+        );
+        assert_eq!(
+            quilt_file_name("square", &grid(2, 2, 10), QuiltVideoCodec::Hevc),
+            "square_qs2x2a1.mp4"
+        );
+    }
+
+    #[test]
+    fn test_frames_put_view_zero_at_the_bottom_left() {
+        let quilt = grid(2, 2, 1);
+        let target = QuiltRenderTarget {
+            width: 2,
+            height: 2,
+            // Views 0 and 1 in the first row, 2 and 3 in the second
+            pixels: (0..4u8).flat_map(|view| [view; 4]).collect(),
+        };
+        let mut frame = Vec::new();
+        frame_from_quilt(&target, &quilt, &mut frame).unwrap();
+        assert_eq!(frame[..4], [2; 4]);
+        assert_eq!(frame[8..12], [0; 4]);
+
+        let small = QuiltRenderTarget::new(1, 1);
+        assert!(frame_from_quilt(&small, &quilt, &mut frame).is_err());
+    }
+
+    #[test]
+    fn test_ffmpeg_reads_raw_quilts_and_pads_odd_sizes() {
+        let config = QuiltVideoConfig {
+            codec: QuiltVideoCodec::Vp9,
+            ..QuiltVideoConfig::default()
+        };
+        let args = config.ffmpeg_args(4095, 4095, Path::new("out_qs5x9a1.8.webm"));
+        let joined = args.join(" ");
+        assert!(joined.contains("-f rawvideo -pix_fmt rgba -s 4095x4095 -r 30 -i -"));
+        assert!(joined.contains("pad=ceil(iw/2)*2:ceil(ih/2)*2:0:oh-ih"));
+        assert!(joined.contains("-c:v libvpx-vp9 -b:v 0"));
+        assert_eq!(args.last().map(String::as_str), Some("out_qs5x9a1.8.webm"));
+    }
+
+    #[test]
+    fn test_recording_reports_a_missing_encoder() {
+        let config = QuiltVideoConfig {
+            ffmpeg: PathBuf::from("/nonexistent/ffmpeg"),
+            ..QuiltVideoConfig::default()
+        };
+        let Err(RenderError::Export(message)) =
+            QuiltRecorder::start("session", &grid(2, 2, 8), &config)
+        else {
+            panic!("a missing ffmpeg should not start");
+        };
+        assert!(message.contains("/nonexistent/ffmpeg"), "{message}");
+
+        let still = QuiltVideoConfig {
+            fps: 0,
+            ..QuiltVideoConfig::default()
+        };
+        assert!(QuiltRecorder::start("session", &grid(2, 2, 8), &still).is_err());
+    }
+}
@@ -0,0 +1,120 @@
+//! # Shader Elements
+//!
+//! Compiling `Shader` elements for the wgpu backend. An element's source
+//! defines `shade`; the renderer appends `shaders/element_shader.wgsl`,
+//! which declares the `canvas` inputs and `param` and adds the entry points
+//! that run `shade` over the element's box:
+//!
+//! ```text
+//! element source ──┐
+//!                  ├─▶ sandbox check ─▶ naga parse + validate ─▶ pipeline
+//! entry points  ───┘        (core)          (this module)        (wgpu)
+//! ```
+//!
+//! Sources that fail either check are never handed to the GPU; the element
+//! draws as a placeholder instead, as it does in every other backend. See
+//! [`canvas_core::shader`] for what a source may use.
+
+#[cfg(feature = "gpu")]
+use canvas_core::ElementKind;
+
+#[cfg(feature = "gpu")]
+use crate::error::{RenderError, RenderResult};
+
+/// Entry points and declarations appended to every element source.
+const ENTRY_POINTS: &str = include_str!("shaders/element_shader.wgsl");
+
+/// The complete module for an element source: the source first, so compile
+/// errors point at its own lines, then the entry points.
+#[must_use]
+pub fn wrap(source: &str) -> String {
+    format!("{source}\n{ENTRY_POINTS}")
+}
+
+/// Check a `Shader` element's source against the sandbox rules and compile
+/// it, without a GPU; other kinds pass.
+///
+/// # Errors
+///
+/// Returns [`RenderError::Shader`] with the sandbox violation, or with the
+/// compiler's report, line numbers counted from the start of the source.
+#[cfg(feature = "gpu")]
+pub fn check(kind: &ElementKind) -> RenderResult<()> {
+    let ElementKind::Shader { wgsl, .. } = kind else {
+        return Ok(());
+    };
+    canvas_core::shader::validate(kind).map_err(|e| RenderError::Shader(e.to_string()))?;
+    let source = wrap(wgsl);
+    let module = naga::front::wgsl::parse_str(&source)
+        .map_err(|e| RenderError::Shader(e.emit_to_string(&source)))?;
+    naga::valid::Validator::new(
+        naga::valid::ValidationFlags::all(),
+        naga::valid::Capabilities::empty(),
+    )
+    .validate(&module)
+    .map_err(|e| RenderError::Shader(e.emit_to_string(&source)))?;
+    Ok(())
+}
+
+/// Pack an element's uniforms into the four vectors `param` reads,
+/// zero-filled past the last one.
+#[must_use]
+pub fn pack_uniforms(uniforms: &[f32]) -> [[f32; 4]; 4] {
+    let mut packed = [[0.0; 4]; 4];
+    for (index, value) in uniforms
+        .iter()
+        .take(canvas_core::MAX_SHADER_UNIFORMS)
+        .enumerate()
+    {
+        packed[index / 4][index % 4] = *value;
+    }
+    packed
+}
+
+#[cfg(all(test, feature = "gpu"))]
+mod tests {
+    use super::*;
+
+    fn shader(wgsl: &str) -> ElementKind {
+        ElementKind::Shader {
+            wgsl: wgsl.to_string(),
+            uniforms: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_check_compiles_sources_and_reports_their_errors() {
+        let plasma = "fn shade(uv: vec2<f32>) -> vec4<f32> {\n\
+                          let t = canvas.time * param(0u);\n\
+                          let v = sin(uv.x * 10.0 + t) + sin(uv.y * 10.0 - t);\n\
+                          return vec4<f32>(0.5 + 0.5 * sin(v), uv, 1.0);\n\
+                      }";
+        assert!(check(&shader(plasma)).is_ok());
+
+        // A syntax error on the source's own second line
+        let broken = "fn shade(uv: vec2<f32>) -> vec4<f32> {\n\
+                          return vec4<f32>(uv, 0.0 1.0);\n\
+                      }";
+        let Err(RenderError::Shader(report)) = check(&shader(broken)) else {
+            panic!("a missing comma should not compile");
+        };
+        assert!(report.contains("wgsl:2:"), "{report}");
+        // Returning the wrong type
+        let mistyped = "fn shade(uv: vec2<f32>) -> vec4<f32> { return uv; }";
+        assert!(check(&shader(mistyped)).is_err());
+
+        // Caught by the sandbox before the compiler
+        assert!(check(&shader("fn shade(uv: vec2<f32>) -> vec4<f32> { loop {} }")).is_err());
+        // Redeclaring the inputs
+        let clash = "var<private> canvas: f32;\n\
+                     fn shade(uv: vec2<f32>) -> vec4<f32> { return vec4<f32>(1.0); }";
+        assert!(check(&shader(clash)).is_err());
+    }
+
+    #[test]
+    fn test_uniforms_pack_four_to_a_vector() {
+        let packed = pack_uniforms(&[1.0, 2.0, 3.0, 4.0, 5.0]);
+        assert!(packed[1][0] > 4.9 && packed[1][1].abs() < f32::EPSILON);
+        assert!(packed[0][3] > 3.9);
+    }
+}
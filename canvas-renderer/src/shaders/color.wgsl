@@ -0,0 +1,10 @@
+// Color helpers prepended to the 2D shaders
+// Scene colors are sRGB; the shaders decode them to linear light, which the
+// target encodes again for the display (see color.rs)
+
+fn srgb_to_linear(color: vec3<f32>) -> vec3<f32> {
+    let low = color / 12.92;
+    let high = pow((max(color, vec3<f32>(0.0)) + vec3<f32>(0.055)) / 1.055, vec3<f32>(2.4));
+    return select(high, low, color <= vec3<f32>(0.04045));
+}
+
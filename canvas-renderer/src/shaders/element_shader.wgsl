@@ -0,0 +1,65 @@
+// Declarations and entry points appended to a Shader element's source
+// The source defines `fn shade(uv: vec2<f32>) -> vec4<f32>`; it comes first
+// so compile errors point at its own line numbers
+
+struct CanvasInputs {
+    // The element's box in clip space: left, bottom, right, top
+    rect: vec4<f32>,
+    // The element's size on screen, in pixels
+    resolution: vec2<f32>,
+    // Seconds since the renderer started
+    time: f32,
+    // Element opacity, applied to what `shade` returns
+    opacity: f32,
+    // Custom uniforms, four to a vector; read them with `param`
+    params: array<vec4<f32>, 4>,
+}
+
+@group(0) @binding(0)
+var<uniform> canvas: CanvasInputs;
+
+// The element's `uniforms[i]`, or 0.0 past the last one
+fn param(i: u32) -> f32 {
+    if i >= 16u {
+        return 0.0;
+    }
+    return canvas.params[i / 4u][i % 4u];
+}
+
+struct CanvasVertex {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+}
+
+@vertex
+fn canvas_vs(@builtin(vertex_index) index: u32) -> CanvasVertex {
+    // Two triangles over the unit square: (0, 0), (1, 0), (0, 1), (1, 1)
+    var corners = array<vec2<f32>, 6>(
+        vec2<f32>(0.0, 0.0),
+        vec2<f32>(1.0, 0.0),
+        vec2<f32>(0.0, 1.0),
+        vec2<f32>(0.0, 1.0),
+        vec2<f32>(1.0, 0.0),
+        vec2<f32>(1.0, 1.0),
+    );
+    let uv = corners[index];
+    var out: CanvasVertex;
+    out.clip_position = vec4<f32>(
+        mix(canvas.rect.x, canvas.rect.z, uv.x),
+        mix(canvas.rect.w, canvas.rect.y, uv.y),
+        0.0,
+        1.0,
+    );
+    out.uv = uv;
+    return out;
+}
+
+@fragment
+fn canvas_fs(in: CanvasVertex) -> @location(0) vec4<f32> {
+    let color = clamp(shade(in.uv), vec4<f32>(0.0), vec4<f32>(1.0));
+    // Like every scene color, what `shade` returns is sRGB
+    let low = color.rgb / 12.92;
+    let high = pow((color.rgb + vec3<f32>(0.055)) / 1.055, vec3<f32>(2.4));
+    let rgb = select(high, low, color.rgb <= vec3<f32>(0.04045));
+    return vec4<f32>(rgb, color.a * canvas.opacity);
+}
@@ -0,0 +1,43 @@
+// Fill shader for tessellated shapes: solid triangles colored per vertex
+// Supports both 2D (screen space) and 3D (camera space) rendering
+
+struct VertexInput {
+    @location(0) position: vec2<f32>,
+    @location(1) color: vec4<f32>,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) color: vec4<f32>,
+}
+
+struct Uniforms {
+    // Canvas dimensions: width, height, use_camera (1.0 = yes), reserved
+    canvas_size: vec4<f32>,
+    // View-projection matrix (used when use_camera = 1.0)
+    view_projection: mat4x4<f32>,
+}
+
+@group(0) @binding(0)
+var<uniform> uniforms: Uniforms;
+
+@vertex
+fn vs_main(in: VertexInput) -> VertexOutput {
+    var out: VertexOutput;
+
+    if (uniforms.canvas_size.z > 0.5) {
+        out.clip_position = uniforms.view_projection * vec4<f32>(in.position, 0.0, 1.0);
+    } else {
+        let ndc_x = (in.position.x / uniforms.canvas_size.x) * 2.0 - 1.0;
+        let ndc_y = 1.0 - (in.position.y / uniforms.canvas_size.y) * 2.0;
+        out.clip_position = vec4<f32>(ndc_x, ndc_y, 0.0, 1.0);
+    }
+
+    out.color = vec4<f32>(srgb_to_linear(in.color.rgb), in.color.a);
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return in.color;
+}
@@ -0,0 +1,70 @@
+// Lenticular compositing: turns a quilt into the lightfield image a Looking
+// Glass display shows, giving each subpixel the view its lens sends it to
+// One full-screen triangle, sampling the quilt three times per pixel
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+}
+
+struct Lens {
+    // pitch, tilt, center, subpixel width, as in LensCalibration
+    lens: vec4<f32>,
+    // Quilt columns, rows and views, reserved
+    quilt: vec4<f32>,
+    // 1.0 to invert the view order, swap red and blue (BGR panels) or
+    // mirror the views; reserved
+    flags: vec4<f32>,
+}
+
+@group(0) @binding(0)
+var<uniform> lens: Lens;
+
+@group(0) @binding(1)
+var t_quilt: texture_2d<f32>;
+
+@group(0) @binding(2)
+var s_quilt: sampler;
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOutput {
+    // A triangle covering the target: (0, 0), (2, 0), (0, 2) in UV space
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    var out: VertexOutput;
+    out.clip_position = vec4<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, 0.0, 1.0);
+    out.uv = uv;
+    return out;
+}
+
+// The view (0 to 1) the lens shows subpixel `i` of the point `uv` through;
+// the calibration measures height from the bottom of the screen
+fn view_at(uv: vec2<f32>, i: f32) -> f32 {
+    let z = fract((uv.x + i * lens.lens.w + (1.0 - uv.y) * lens.lens.y) * lens.lens.x - lens.lens.z);
+    return select(z, 1.0 - z, lens.flags.x > 0.5);
+}
+
+// Where view `z` shows the point `uv`, in the quilt
+fn quilt_uv(uv: vec2<f32>, z: f32) -> vec2<f32> {
+    let views = lens.quilt.z;
+    let view = min(floor(z * views), views - 1.0);
+    let column = view - floor(view / lens.quilt.x) * lens.quilt.x;
+    let row = floor(view / lens.quilt.x);
+    // Stay half a texel inside the view so filtering never reaches the next
+    let size = vec2<f32>(textureDimensions(t_quilt)) / lens.quilt.xy;
+    let local = clamp(uv * size, vec2<f32>(0.5), size - vec2<f32>(0.5));
+    return (vec2<f32>(column, row) * size + local) / vec2<f32>(textureDimensions(t_quilt));
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let sample_uv = vec2<f32>(select(in.uv.x, 1.0 - in.uv.x, lens.flags.z > 0.5), in.uv.y);
+    var rgb: array<vec3<f32>, 3>;
+    for (var i = 0; i < 3; i++) {
+        let z = view_at(in.uv, f32(i));
+        rgb[i] = textureSampleLevel(t_quilt, s_quilt, quilt_uv(sample_uv, z), 0.0).rgb;
+    }
+    let bgr = lens.flags.y > 0.5;
+    let red = select(rgb[0].r, rgb[2].r, bgr);
+    let blue = select(rgb[2].b, rgb[0].b, bgr);
+    return vec4<f32>(red, rgb[1].g, blue, 1.0);
+}
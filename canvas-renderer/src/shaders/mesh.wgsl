@@ -0,0 +1,90 @@
+// Mesh shader for glTF models
+// Metallic-roughness materials lit by up to 8 directional or point lights
+
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) normal: vec3<f32>,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) world_position: vec3<f32>,
+    @location(1) normal: vec3<f32>,
+}
+
+struct Light {
+    // Direction the light travels in (w = 0) or its position (w = 1)
+    position: vec4<f32>,
+    // Color multiplied by intensity
+    color: vec4<f32>,
+}
+
+struct Uniforms {
+    // Projection * view * pose
+    clip_from_model: mat4x4<f32>,
+    // Pose, placing vertices in the space lights and the eye live in
+    world_from_model: mat4x4<f32>,
+    // Pose rotation with inverted scale, for normals
+    normal_from_model: mat4x4<f32>,
+    // Linear RGBA base color
+    base_color: vec4<f32>,
+    // Linear RGB emissive color, reserved
+    emissive: vec4<f32>,
+    // Metallic, roughness, ambient light, light count
+    params: vec4<f32>,
+    // Camera position
+    eye: vec4<f32>,
+    lights: array<Light, 8>,
+}
+
+@group(0) @binding(0)
+var<uniform> uniforms: Uniforms;
+
+@vertex
+fn vs_main(in: VertexInput) -> VertexOutput {
+    var out: VertexOutput;
+    var clip = uniforms.clip_from_model * vec4<f32>(in.position, 1.0);
+    // Projection matrices use OpenGL's -1..1 depth range; wgpu's is 0..1
+    clip.z = (clip.z + clip.w) * 0.5;
+    out.clip_position = clip;
+    out.world_position = (uniforms.world_from_model * vec4<f32>(in.position, 1.0)).xyz;
+    out.normal = (uniforms.normal_from_model * vec4<f32>(in.normal, 0.0)).xyz;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput, @builtin(front_facing) front: bool) -> @location(0) vec4<f32> {
+    let base = uniforms.base_color;
+    let metallic = uniforms.params.x;
+    let roughness = uniforms.params.y;
+
+    // Models are drawn two-sided, so back faces are lit from behind
+    var n = normalize(in.normal);
+    if (!front) {
+        n = -n;
+    }
+    let v = normalize(uniforms.eye.xyz - in.world_position);
+
+    let diffuse_color = base.rgb * (1.0 - metallic);
+    let specular_color = mix(vec3<f32>(0.04), base.rgb, metallic);
+    let shininess = exp2(10.0 * (1.0 - roughness) + 1.0);
+
+    var color = base.rgb * uniforms.params.z;
+    let count = u32(uniforms.params.w);
+    for (var i = 0u; i < count; i = i + 1u) {
+        let light = uniforms.lights[i];
+        var l: vec3<f32>;
+        if (light.position.w > 0.5) {
+            l = normalize(light.position.xyz - in.world_position);
+        } else {
+            l = normalize(-light.position.xyz);
+        }
+        let n_dot_l = max(dot(n, l), 0.0);
+        let h = normalize(l + v);
+        let specular = pow(max(dot(n, h), 0.0), shininess) * (1.0 - 0.5 * roughness);
+        color = color + light.color.rgb * n_dot_l * (diffuse_color + specular_color * specular);
+    }
+    color = color + uniforms.emissive.rgb;
+
+    return vec4<f32>(clamp(color, vec3<f32>(0.0), vec3<f32>(1.0)), base.a);
+}
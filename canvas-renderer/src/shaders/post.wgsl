@@ -0,0 +1,121 @@
+// Post-processing passes over a finished frame or overlay layer
+// One full-screen triangle per pass, sampling the previous pass's output
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+}
+
+struct Uniforms {
+    // Pass parameters, as each entry point below describes
+    params: vec4<f32>,
+    // Size of one source texel in UV units, reserved
+    texel: vec4<f32>,
+}
+
+@group(0) @binding(0)
+var<uniform> uniforms: Uniforms;
+
+// What the pass works on
+@group(0) @binding(1)
+var t_source: texture_2d<f32>;
+
+// The unblurred frame, for the bloom composite
+@group(0) @binding(2)
+var t_base: texture_2d<f32>;
+
+@group(0) @binding(3)
+var s_linear: sampler;
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOutput {
+    // A triangle covering the target: (0, 0), (2, 0), (0, 2) in UV space
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    var out: VertexOutput;
+    out.clip_position = vec4<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, 0.0, 1.0);
+    out.uv = uv;
+    return out;
+}
+
+fn luma(color: vec3<f32>) -> f32 {
+    return dot(color, vec3<f32>(0.2126, 0.7152, 0.0722));
+}
+
+// One direction of a separable Gaussian blur
+// params: step in texels (x, y), taps on each side, reserved
+@fragment
+fn fs_blur(in: VertexOutput) -> @location(0) vec4<f32> {
+    let step = uniforms.params.xy * uniforms.texel.xy;
+    let taps = i32(uniforms.params.z);
+    let sigma = max(uniforms.params.z / 2.0, 0.5);
+    var sum = vec4<f32>(0.0);
+    var total = 0.0;
+    for (var i = -taps; i <= taps; i++) {
+        let offset = f32(i);
+        let weight = exp(-offset * offset / (2.0 * sigma * sigma));
+        sum += textureSampleLevel(t_source, s_linear, in.uv + step * offset, 0.0) * weight;
+        total += weight;
+    }
+    return sum / total;
+}
+
+// Keep only what is brighter than the bloom threshold
+// params: threshold, reserved
+@fragment
+fn fs_bright(in: VertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSampleLevel(t_source, s_linear, in.uv, 0.0);
+    let brightness = luma(color.rgb);
+    let keep = max(brightness - uniforms.params.x, 0.0) / max(brightness, 0.0001);
+    return vec4<f32>(color.rgb * keep, color.a);
+}
+
+// Add the blurred bright areas back over the frame
+// params: intensity, reserved
+@fragment
+fn fs_bloom(in: VertexOutput) -> @location(0) vec4<f32> {
+    let base = textureSampleLevel(t_base, s_linear, in.uv, 0.0);
+    let glow = textureSampleLevel(t_source, s_linear, in.uv, 0.0);
+    return vec4<f32>(base.rgb + glow.rgb * uniforms.params.x, max(base.a, glow.a));
+}
+
+// Darken towards the corners
+// params: strength, radius, softness, reserved
+@fragment
+fn fs_vignette(in: VertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSampleLevel(t_source, s_linear, in.uv, 0.0);
+    // 0 at the center, 1 at the corners
+    let distance = length(in.uv - vec2<f32>(0.5)) * 1.41421356;
+    let radius = uniforms.params.y;
+    let fade = smoothstep(radius, radius + max(uniforms.params.z, 0.0001), distance);
+    return vec4<f32>(color.rgb * (1.0 - uniforms.params.x * fade), color.a);
+}
+
+// Brightness, contrast, saturation and white balance
+// params: brightness, contrast, saturation, temperature
+@fragment
+fn fs_grade(in: VertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSampleLevel(t_source, s_linear, in.uv, 0.0);
+    let temperature = uniforms.params.w;
+    var rgb = color.rgb * vec3<f32>(1.0 + 0.1 * temperature, 1.0, 1.0 - 0.1 * temperature);
+    rgb = (rgb - vec3<f32>(0.5)) * uniforms.params.y + vec3<f32>(0.5) + uniforms.params.x;
+    rgb = mix(vec3<f32>(luma(rgb)), rgb, uniforms.params.z);
+    return vec4<f32>(clamp(rgb, vec3<f32>(0.0), vec3<f32>(1.0)), color.a);
+}
+
+// Encode linear Rec. 709 light for an HDR10 display: Rec. 2020 primaries
+// and the PQ curve (SMPTE ST 2084), as color.rs `pq_encode` does
+// params: nits of linear 1.0 (sRGB white), reserved
+@fragment
+fn fs_encode_pq(in: VertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSampleLevel(t_source, s_linear, in.uv, 0.0);
+    // Rec. 709 to Rec. 2020, by columns
+    let to_rec2020 = mat3x3<f32>(
+        vec3<f32>(0.6274, 0.0691, 0.0164),
+        vec3<f32>(0.3293, 0.9195, 0.0880),
+        vec3<f32>(0.0433, 0.0114, 0.8956),
+    );
+    let nits = to_rec2020 * max(color.rgb, vec3<f32>(0.0)) * uniforms.params.x;
+    let y = pow(clamp(nits / 10000.0, vec3<f32>(0.0), vec3<f32>(1.0)), vec3<f32>(0.1593017578125));
+    let pq = pow((0.8359375 + 18.8515625 * y) / (1.0 + 18.6875 * y), vec3<f32>(78.84375));
+    return vec4<f32>(pq, color.a);
+}
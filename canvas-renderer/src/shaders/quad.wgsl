@@ -15,12 +15,22 @@ struct VertexOutput {
 struct Uniforms {
     // Transform: x, y, width, height
     transform: vec4<f32>,
-    // Canvas dimensions: width, height, use_camera (1.0 = yes), reserved
+    // Canvas dimensions: width, height, use_camera (1.0 = yes), depth in
+    // front of the canvas plane (with use_camera)
     canvas_size: vec4<f32>,
     // Element color
     color: vec4<f32>,
     // View-projection matrix (used when use_camera = 1.0)
     view_projection: mat4x4<f32>,
+    // Gradient end color
+    fill_end: vec4<f32>,
+    // Stroke color
+    stroke_color: vec4<f32>,
+    // Fill mode (0 = solid, 1 = linear, 2 = radial), gradient angle or radius,
+    // corner radius, stroke width
+    style_params: vec4<f32>,
+    // Edge softness, radial center x, radial center y, reserved
+    style_extra: vec4<f32>,
 }
 
 @group(0) @binding(0)
@@ -39,8 +49,8 @@ fn vs_main(in: VertexInput) -> VertexOutput {
     // Check if camera mode is enabled
     if (uniforms.canvas_size.z > 0.5) {
         // 3D mode: Apply view-projection matrix
-        // World position is at z=0 for 2D elements rendered in 3D space
-        let world_pos_4d = vec4<f32>(world_pos.x, world_pos.y, 0.0, 1.0);
+        // 2D elements stand in front of the canvas plane by their depth
+        let world_pos_4d = vec4<f32>(world_pos.x, world_pos.y, uniforms.canvas_size.w, 1.0);
         out.clip_position = uniforms.view_projection * world_pos_4d;
     } else {
         // 2D mode: Convert to normalized device coordinates (-1 to 1)
@@ -56,7 +66,46 @@ fn vs_main(in: VertexInput) -> VertexOutput {
     return out;
 }
 
+// Signed distance from p to a rounded rectangle centered at the origin
+fn rounded_rect_sdf(p: vec2<f32>, half_size: vec2<f32>, radius: f32) -> f32 {
+    let q = abs(p) - half_size + vec2<f32>(radius);
+    return length(max(q, vec2<f32>(0.0))) + min(max(q.x, q.y), 0.0) - radius;
+}
+
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
-    return in.color;
+    let size = uniforms.transform.zw;
+    let mode = uniforms.style_params.x;
+
+    // Fill color: solid or two-color gradient
+    var fill = in.color;
+    if (mode > 1.5) {
+        let center = uniforms.style_extra.yz;
+        let radius = max(uniforms.style_params.y * max(size.x, size.y), 1.0);
+        let t = length((in.uv - center) * size) / radius;
+        fill = mix(in.color, uniforms.fill_end, clamp(t, 0.0, 1.0));
+    } else if (mode > 0.5) {
+        let angle = uniforms.style_params.y;
+        let dir = vec2<f32>(cos(angle), sin(angle));
+        let extent = max(abs(dir.x) + abs(dir.y), 0.0001);
+        let t = dot(in.uv - vec2<f32>(0.5), dir) / extent + 0.5;
+        fill = mix(in.color, uniforms.fill_end, clamp(t, 0.0, 1.0));
+    }
+
+    // Rounded-rect coverage; softness > 1 blurs the edge (used for shadows)
+    let p = (in.uv - vec2<f32>(0.5)) * size;
+    let dist = rounded_rect_sdf(p, size * 0.5, uniforms.style_params.z);
+    let softness = max(uniforms.style_extra.x, 1.0);
+    let coverage = clamp(0.5 - dist / softness, 0.0, 1.0);
+
+    // Inner stroke band
+    var color = fill;
+    let stroke_width = uniforms.style_params.w;
+    if (stroke_width > 0.0) {
+        let band = clamp(dist + stroke_width + 0.5, 0.0, 1.0);
+        color = mix(fill, uniforms.stroke_color, band);
+    }
+
+    // Gradients and strokes mix in sRGB, as in CSS; blending is linear
+    return vec4<f32>(srgb_to_linear(color.rgb), color.a * coverage);
 }
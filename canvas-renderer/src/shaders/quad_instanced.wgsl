@@ -0,0 +1,117 @@
+// Instanced quad shader: one instance per colored rectangle
+// Same styling as quad.wgsl, with the per-element data in instance attributes
+
+struct VertexInput {
+    @location(0) position: vec2<f32>,
+    @location(1) uv: vec2<f32>,
+}
+
+struct InstanceInput {
+    // x, y, width, height
+    @location(2) rect: vec4<f32>,
+    @location(3) color: vec4<f32>,
+    @location(4) fill_end: vec4<f32>,
+    @location(5) stroke_color: vec4<f32>,
+    // Fill mode (0 = solid, 1 = linear, 2 = radial), gradient angle or radius,
+    // corner radius, stroke width
+    @location(6) style_params: vec4<f32>,
+    // Edge softness, radial center x, radial center y, reserved
+    @location(7) style_extra: vec4<f32>,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+    @location(1) @interpolate(flat) color: vec4<f32>,
+    @location(2) @interpolate(flat) size: vec2<f32>,
+    @location(3) @interpolate(flat) fill_end: vec4<f32>,
+    @location(4) @interpolate(flat) stroke_color: vec4<f32>,
+    @location(5) @interpolate(flat) style_params: vec4<f32>,
+    @location(6) @interpolate(flat) style_extra: vec4<f32>,
+}
+
+struct Uniforms {
+    // Canvas dimensions: width, height, use_camera (1.0 = yes),
+    // analytic AA padding in canvas units (0.0 = off)
+    canvas_size: vec4<f32>,
+    // View-projection matrix (used when use_camera = 1.0)
+    view_projection: mat4x4<f32>,
+}
+
+@group(0) @binding(0)
+var<uniform> uniforms: Uniforms;
+
+@vertex
+fn vs_main(in: VertexInput, instance: InstanceInput) -> VertexOutput {
+    var out: VertexOutput;
+
+    // Analytic AA grows the quad so pixels just past its edges get coverage
+    let pad = uniforms.canvas_size.w;
+    let world_pos = in.position * (instance.rect.zw + 2.0 * pad) + instance.rect.xy - pad;
+
+    if (uniforms.canvas_size.z > 0.5) {
+        out.clip_position = uniforms.view_projection * vec4<f32>(world_pos, 0.0, 1.0);
+    } else {
+        let ndc_x = (world_pos.x / uniforms.canvas_size.x) * 2.0 - 1.0;
+        let ndc_y = 1.0 - (world_pos.y / uniforms.canvas_size.y) * 2.0;
+        out.clip_position = vec4<f32>(ndc_x, ndc_y, 0.0, 1.0);
+    }
+
+    out.uv = (world_pos - instance.rect.xy) / max(instance.rect.zw, vec2<f32>(0.0001));
+    out.color = instance.color;
+    out.size = instance.rect.zw;
+    out.fill_end = instance.fill_end;
+    out.stroke_color = instance.stroke_color;
+    out.style_params = instance.style_params;
+    out.style_extra = instance.style_extra;
+    return out;
+}
+
+// Signed distance from p to a rounded rectangle centered at the origin
+fn rounded_rect_sdf(p: vec2<f32>, half_size: vec2<f32>, radius: f32) -> f32 {
+    let q = abs(p) - half_size + vec2<f32>(radius);
+    return length(max(q, vec2<f32>(0.0))) + min(max(q.x, q.y), 0.0) - radius;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let size = in.size;
+    let mode = in.style_params.x;
+
+    // Fill color: solid or two-color gradient
+    var fill = in.color;
+    if (mode > 1.5) {
+        let center = in.style_extra.yz;
+        let radius = max(in.style_params.y * max(size.x, size.y), 1.0);
+        let t = length((in.uv - center) * size) / radius;
+        fill = mix(in.color, in.fill_end, clamp(t, 0.0, 1.0));
+    } else if (mode > 0.5) {
+        let angle = in.style_params.y;
+        let dir = vec2<f32>(cos(angle), sin(angle));
+        let extent = max(abs(dir.x) + abs(dir.y), 0.0001);
+        let t = dot(in.uv - vec2<f32>(0.5), dir) / extent + 0.5;
+        fill = mix(in.color, in.fill_end, clamp(t, 0.0, 1.0));
+    }
+
+    // Rounded-rect coverage; softness > 1 blurs the edge (used for shadows)
+    let p = (in.uv - vec2<f32>(0.5)) * size;
+    let dist = rounded_rect_sdf(p, size * 0.5, in.style_params.z);
+    // Analytic AA fades the edge over exactly one pixel, whatever the zoom
+    let pixel = fwidth(dist);
+    var softness = max(in.style_extra.x, 1.0);
+    if (uniforms.canvas_size.w > 0.0 && in.style_extra.x <= 1.0) {
+        softness = max(pixel, 0.0001);
+    }
+    let coverage = clamp(0.5 - dist / softness, 0.0, 1.0);
+
+    // Inner stroke band
+    var color = fill;
+    let stroke_width = in.style_params.w;
+    if (stroke_width > 0.0) {
+        let band = clamp(dist + stroke_width + 0.5, 0.0, 1.0);
+        color = mix(fill, in.stroke_color, band);
+    }
+
+    // Gradients and strokes mix in sRGB, as in CSS; blending is linear
+    return vec4<f32>(srgb_to_linear(color.rgb), color.a * coverage);
+}
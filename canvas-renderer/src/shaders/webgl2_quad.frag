@@ -0,0 +1,62 @@
+#version 300 es
+// Styled quad fragment shader for the WebGL2 backend (GLSL port of quad.wgsl)
+
+precision highp float;
+
+uniform vec4 u_transform;
+// Element color
+uniform vec4 u_color;
+// Gradient end color
+uniform vec4 u_fill_end;
+// Stroke color
+uniform vec4 u_stroke_color;
+// Fill mode (0 = solid, 1 = linear, 2 = radial), gradient angle or radius,
+// corner radius, stroke width
+uniform vec4 u_style_params;
+// Edge softness, radial center x, radial center y, reserved
+uniform vec4 u_style_extra;
+
+in vec2 v_uv;
+out vec4 frag_color;
+
+// Signed distance from p to a rounded rectangle centered at the origin
+float rounded_rect_sdf(vec2 p, vec2 half_size, float radius) {
+    vec2 q = abs(p) - half_size + vec2(radius);
+    return length(max(q, vec2(0.0))) + min(max(q.x, q.y), 0.0) - radius;
+}
+
+void main() {
+    vec2 size = u_transform.zw;
+    float mode = u_style_params.x;
+
+    // Fill color: solid or two-color gradient
+    vec4 fill = u_color;
+    if (mode > 1.5) {
+        vec2 center = u_style_extra.yz;
+        float radius = max(u_style_params.y * max(size.x, size.y), 1.0);
+        float t = length((v_uv - center) * size) / radius;
+        fill = mix(u_color, u_fill_end, clamp(t, 0.0, 1.0));
+    } else if (mode > 0.5) {
+        float angle = u_style_params.y;
+        vec2 dir = vec2(cos(angle), sin(angle));
+        float extent = max(abs(dir.x) + abs(dir.y), 0.0001);
+        float t = dot(v_uv - vec2(0.5), dir) / extent + 0.5;
+        fill = mix(u_color, u_fill_end, clamp(t, 0.0, 1.0));
+    }
+
+    // Rounded-rect coverage; softness > 1 blurs the edge (used for shadows)
+    vec2 p = (v_uv - vec2(0.5)) * size;
+    float dist = rounded_rect_sdf(p, size * 0.5, u_style_params.z);
+    float softness = max(u_style_extra.x, 1.0);
+    float coverage = clamp(0.5 - dist / softness, 0.0, 1.0);
+
+    // Inner stroke band
+    vec4 color = fill;
+    float stroke_width = u_style_params.w;
+    if (stroke_width > 0.0) {
+        float band = clamp(dist + stroke_width + 0.5, 0.0, 1.0);
+        color = mix(fill, u_stroke_color, band);
+    }
+
+    frag_color = vec4(color.rgb, color.a * coverage);
+}
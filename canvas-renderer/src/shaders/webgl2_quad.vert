@@ -0,0 +1,26 @@
+#version 300 es
+// Unit quad vertex shader for the WebGL2 backend (GLSL port of quad.wgsl)
+
+layout(location = 0) in vec2 a_position;
+
+// Transform: x, y, width, height
+uniform vec4 u_transform;
+// Canvas dimensions: width, height
+uniform vec2 u_canvas_size;
+// Scene camera: zoom, pan x, pan y
+uniform vec3 u_camera;
+
+out vec2 v_uv;
+
+void main() {
+    // Scale to the element, then apply the scene's zoom/pan
+    vec2 world_pos = a_position * u_transform.zw + u_transform.xy;
+    vec2 screen_pos = world_pos * u_camera.x + u_camera.yz;
+
+    // Top-left origin: x goes right (+), y goes down (+)
+    float ndc_x = (screen_pos.x / u_canvas_size.x) * 2.0 - 1.0;
+    float ndc_y = 1.0 - (screen_pos.y / u_canvas_size.y) * 2.0;
+    gl_Position = vec4(ndc_x, ndc_y, 0.0, 1.0);
+
+    v_uv = a_position;
+}
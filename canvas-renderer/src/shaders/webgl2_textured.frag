@@ -0,0 +1,16 @@
+#version 300 es
+// Textured quad fragment shader for the WebGL2 backend (GLSL port of
+// textured.wgsl): images, video frames and rasterized text
+
+precision mediump float;
+
+// Tint color (multiplied with texture)
+uniform vec4 u_tint;
+uniform sampler2D u_texture;
+
+in vec2 v_uv;
+out vec4 frag_color;
+
+void main() {
+    frag_color = texture(u_texture, v_uv) * u_tint;
+}
@@ -0,0 +1,164 @@
+//! Offscreen snapshots of a scene.
+//!
+//! [`Renderer::render_to_image`](crate::Renderer::render_to_image) renders a
+//! scene into an [`RgbaImage`] without a window: GPU backends draw into an
+//! offscreen texture and read it back, and everything else falls back to the
+//! CPU rasterizer behind the `export` feature.
+
+use crate::{RenderError, RenderResult};
+
+/// Largest width or height of a snapshot, in pixels.
+///
+/// Matches the minimum `max_texture_dimension_2d` wgpu guarantees.
+pub const MAX_SNAPSHOT_SIZE: u32 = 8192;
+
+/// An 8-bit RGBA image with straight (non-premultiplied) alpha, stored row
+/// by row from the top left.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RgbaImage {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+impl RgbaImage {
+    /// Wrap `pixels`, four bytes per pixel.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if either side is zero or `pixels` is not exactly
+    /// `width * height * 4` bytes long.
+    pub fn new(width: u32, height: u32, pixels: Vec<u8>) -> RenderResult<Self> {
+        check_size(width, height)?;
+        let expected = u64::from(width) * u64::from(height) * 4;
+        if pixels.len() as u64 != expected {
+            return Err(RenderError::Export(format!(
+                "{width}x{height} image needs {expected} bytes, got {}",
+                pixels.len()
+            )));
+        }
+        Ok(Self {
+            width,
+            height,
+            pixels,
+        })
+    }
+
+    /// Width in pixels.
+    #[must_use]
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Height in pixels.
+    #[must_use]
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// The raw RGBA bytes.
+    #[must_use]
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    /// Consume the image, returning its RGBA bytes.
+    #[must_use]
+    pub fn into_pixels(self) -> Vec<u8> {
+        self.pixels
+    }
+
+    /// The pixel at (`x`, `y`), or `None` outside the image.
+    #[must_use]
+    pub fn pixel(&self, x: u32, y: u32) -> Option<[u8; 4]> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        let start = (y as usize * self.width as usize + x as usize) * 4;
+        self.pixels
+            .get(start..start + 4)
+            .and_then(|p| p.try_into().ok())
+    }
+
+    /// Encode the image as PNG.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if encoding fails.
+    #[cfg(feature = "images")]
+    pub fn encode_png(&self) -> RenderResult<Vec<u8>> {
+        use image::ImageEncoder;
+
+        let mut buf = Vec::new();
+        image::codecs::png::PngEncoder::new(&mut buf)
+            .write_image(
+                &self.pixels,
+                self.width,
+                self.height,
+                image::ExtendedColorType::Rgba8,
+            )
+            .map_err(|e| RenderError::Export(format!("PNG encoding failed: {e}")))?;
+        Ok(buf)
+    }
+}
+
+/// Check a snapshot size is between 1 and [`MAX_SNAPSHOT_SIZE`] on each side.
+///
+/// # Errors
+///
+/// Returns an error describing the offending size.
+pub(crate) fn check_size(width: u32, height: u32) -> RenderResult<()> {
+    if width == 0 || height == 0 {
+        return Err(RenderError::Export(format!(
+            "Snapshot size {width}x{height} must be non-zero"
+        )));
+    }
+    if width > MAX_SNAPSHOT_SIZE || height > MAX_SNAPSHOT_SIZE {
+        return Err(RenderError::Export(format!(
+            "Snapshot size {width}x{height} exceeds {MAX_SNAPSHOT_SIZE} pixels"
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new_checks_length() {
+        assert!(RgbaImage::new(2, 2, vec![0; 16]).is_ok());
+        assert!(RgbaImage::new(2, 2, vec![0; 15]).is_err());
+        assert!(RgbaImage::new(0, 2, Vec::new()).is_err());
+    }
+
+    #[test]
+    fn test_size_limit() {
+        assert!(check_size(MAX_SNAPSHOT_SIZE, 1).is_ok());
+        assert!(check_size(MAX_SNAPSHOT_SIZE + 1, 1).is_err());
+        assert!(check_size(1, MAX_SNAPSHOT_SIZE + 1).is_err());
+    }
+
+    #[test]
+    fn test_pixel_lookup() {
+        let mut pixels = vec![0; 2 * 3 * 4];
+        pixels[(2 + 1) * 4..(2 + 1) * 4 + 4].copy_from_slice(&[1, 2, 3, 4]);
+        let image = RgbaImage::new(2, 3, pixels).unwrap();
+        assert_eq!(image.pixel(1, 1), Some([1, 2, 3, 4]));
+        assert_eq!(image.pixel(0, 0), Some([0, 0, 0, 0]));
+        assert_eq!(image.pixel(2, 0), None);
+        assert_eq!(image.pixel(0, 3), None);
+    }
+
+    #[cfg(feature = "images")]
+    #[test]
+    fn test_encode_png() {
+        let image = RgbaImage::new(3, 2, vec![255; 24]).unwrap();
+        let png = image.encode_png().unwrap();
+        assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+
+        let decoded = image::load_from_memory(&png).unwrap().to_rgba8();
+        assert_eq!(decoded.dimensions(), (3, 2));
+        assert_eq!(decoded.into_raw(), image.into_pixels());
+    }
+}
@@ -41,8 +41,13 @@
 //!        Quilt (5x9 grid = 45 views)
 //! ```
 
+use canvas_core::{Element, ModelCamera, Transform3D};
 use serde::{Deserialize, Serialize};
 
+use crate::error::{RenderError, RenderResult};
+use crate::lightfield::LensCalibration;
+use crate::snapshot::MAX_SNAPSHOT_SIZE;
+
 /// A 3D vector for positions and directions.
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct Vec3 {
@@ -131,6 +136,12 @@ impl Vec3 {
     }
 }
 
+impl From<[f32; 3]> for Vec3 {
+    fn from([x, y, z]: [f32; 3]) -> Self {
+        Self::new(x, y, z)
+    }
+}
+
 impl Default for Vec3 {
     fn default() -> Self {
         Self::zero()
@@ -191,6 +202,14 @@ impl Mat4 {
         Self { data }
     }
 
+    /// Create a model matrix from a 3D model's pose.
+    #[must_use]
+    pub fn from_pose(pose: &Transform3D) -> Self {
+        Self {
+            data: pose.matrix(),
+        }
+    }
+
     /// Multiply two matrices.
     #[must_use]
     pub fn mul(&self, other: &Self) -> Self {
@@ -206,6 +225,16 @@ impl Mat4 {
 
         Self { data: result }
     }
+
+    /// Transform a homogeneous point.
+    #[must_use]
+    pub fn transform(&self, point: [f32; 4]) -> [f32; 4] {
+        let mut result = [0.0f32; 4];
+        for (row, value) in result.iter_mut().enumerate() {
+            *value = (0..4).map(|k| self.data[k * 4 + row] * point[k]).sum();
+        }
+        result
+    }
 }
 
 impl Default for Mat4 {
@@ -245,6 +274,20 @@ impl Camera {
         }
     }
 
+    /// Create a camera from a model's framing hint, with clipping planes
+    /// scaled to its distance.
+    #[must_use]
+    pub fn from_model(hint: &ModelCamera) -> Self {
+        Self {
+            position: hint.position().into(),
+            target: hint.target.into(),
+            up: Vec3::up(),
+            fov: hint.fov,
+            near: (hint.distance * 0.01).max(0.001),
+            far: hint.distance * 100.0,
+        }
+    }
+
     /// Create view matrix for this camera.
     #[must_use]
     pub fn view_matrix(&self) -> Mat4 {
@@ -264,6 +307,119 @@ impl Default for Camera {
     }
 }
 
+/// Where 2D canvas content sits in the holographic camera rig.
+///
+/// The viewport lies on the focal plane, the world XY plane through the
+/// camera target, scaled so its height fills a view. Each element stands
+/// in front of the plane by its depth in canvas units, so nearer elements
+/// shift further between views; elements without an explicit
+/// [`depth`](Element::depth) are layered by `z_index`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CanvasDepth {
+    /// Depth per `z_index` step in canvas units.
+    pub layer_spacing: f32,
+    /// Furthest an element may stand from the focal plane in canvas units.
+    /// Lightfield displays blur content away from the plane, so depths
+    /// outside this depth of field are clamped to it.
+    pub max_depth: f32,
+}
+
+impl CanvasDepth {
+    /// Depth of `element` in canvas units, clamped to the depth of field.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)] // Z-indices are small
+    pub fn element_depth(&self, element: &Element) -> f32 {
+        let depth = element
+            .depth
+            .filter(|depth| depth.is_finite())
+            .unwrap_or(element.transform.z_index as f32 * self.layer_spacing);
+        let limit = self.max_depth.abs();
+        depth.clamp(-limit, limit)
+    }
+
+    /// `depth` as a depth map value: 0.0 at the back of the depth of
+    /// field, 0.5 on the focal plane and 1.0 at the front.
+    #[must_use]
+    pub fn normalized(&self, depth: f32) -> f32 {
+        if self.max_depth.abs() <= f32::EPSILON {
+            return 0.5;
+        }
+        (0.5 + depth / (2.0 * self.max_depth.abs())).clamp(0.0, 1.0)
+    }
+
+    /// Matrix taking canvas points `(x, y, depth, 1)` into the world of
+    /// `camera`'s rig, for a viewport of the given size in canvas units.
+    #[must_use]
+    pub fn canvas_to_world(camera: &Camera, viewport_width: f32, viewport_height: f32) -> Mat4 {
+        let distance = camera.position.sub(&camera.target).length();
+        let scale = 2.0 * distance * (camera.fov / 2.0).tan() / viewport_height.max(1.0);
+        let origin = camera.target;
+
+        // Canvas y grows down, world y up
+        #[rustfmt::skip]
+        let data = [
+            scale, 0.0, 0.0, 0.0,
+            0.0, -scale, 0.0, 0.0,
+            0.0, 0.0, scale, 0.0,
+            origin.x - scale * viewport_width / 2.0,
+            origin.y + scale * viewport_height / 2.0,
+            origin.z,
+            1.0,
+        ];
+        Mat4 { data }
+    }
+}
+
+impl Default for CanvasDepth {
+    fn default() -> Self {
+        Self {
+            layer_spacing: 20.0,
+            max_depth: 150.0,
+        }
+    }
+}
+
+/// Viewing angle of the presets in degrees, which a custom configuration's
+/// depthiness scales.
+const STANDARD_VIEW_CONE_DEGREES: f32 = 40.0;
+
+/// A custom configuration as JSON, e.g.
+/// `{"views": 48, "columns": 8, "rows": 6, "viewWidth": 420, "viewHeight": 560}`.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct CustomConfigJson {
+    views: u32,
+    columns: u32,
+    rows: u32,
+    view_width: u32,
+    view_height: u32,
+    #[serde(default = "default_depthiness")]
+    depthiness: f32,
+    #[serde(default = "default_focus")]
+    focus: f32,
+}
+
+const fn default_depthiness() -> f32 {
+    1.0
+}
+
+const fn default_focus() -> f32 {
+    2.0
+}
+
+/// The part of a Looking Glass `visual.json` the layout needs beyond the
+/// lens; the view cone is in degrees.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct VisualViewCone {
+    view_cone: Option<VisualValue>,
+}
+
+#[derive(Deserialize)]
+struct VisualValue {
+    value: f32,
+}
+
 /// Configuration for holographic multi-view rendering.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HolographicConfig {
@@ -312,6 +468,147 @@ impl HolographicConfig {
         }
     }
 
+    /// The preset named `name`, `portrait` or `4k`, in any case.
+    #[must_use]
+    pub fn preset(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "portrait" => Some(Self::looking_glass_portrait()),
+            "4k" => Some(Self::looking_glass_4k()),
+            _ => None,
+        }
+    }
+
+    /// A configuration for any display: `num_views` views of
+    /// `view_width` x `view_height` pixels in a `quilt_columns` x
+    /// `quilt_rows` grid. `depthiness` scales the presets' viewing angle
+    /// (1.0 for the same depth, 0.0 for none) and `focus` is the distance
+    /// to the focal plane, 2.0 in the presets.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the configuration is invalid; see
+    /// [`validate`](Self::validate).
+    pub fn custom(
+        num_views: u32,
+        quilt_columns: u32,
+        quilt_rows: u32,
+        view_width: u32,
+        view_height: u32,
+        depthiness: f32,
+        focus: f32,
+    ) -> RenderResult<Self> {
+        let config = Self {
+            num_views,
+            quilt_columns,
+            quilt_rows,
+            view_width,
+            view_height,
+            view_cone: (STANDARD_VIEW_CONE_DEGREES * depthiness).to_radians(),
+            focal_distance: focus,
+        };
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Load a configuration from JSON: either a custom one, with `views`,
+    /// `columns`, `rows`, `viewWidth`, `viewHeight` and optionally
+    /// `depthiness` and `focus` as in [`custom`](Self::custom), or a
+    /// device's calibration (`visual.json`), for which the layout of the
+    /// nearest preset is fitted to the screen's aspect ratio and the
+    /// device's view cone.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the document is neither or the configuration is
+    /// invalid.
+    pub fn from_calibration_json(json: &str) -> RenderResult<Self> {
+        let value: serde_json::Value = serde_json::from_str(json)
+            .map_err(|e| RenderError::Resource(format!("Invalid holographic config: {e}")))?;
+        if value.get("pitch").is_none() {
+            let custom: CustomConfigJson = serde_json::from_value(value)
+                .map_err(|e| RenderError::Resource(format!("Invalid holographic config: {e}")))?;
+            return Self::custom(
+                custom.views,
+                custom.columns,
+                custom.rows,
+                custom.view_width,
+                custom.view_height,
+                custom.depthiness,
+                custom.focus,
+            );
+        }
+
+        let lens = LensCalibration::from_visual_json(json)?;
+        let cone: VisualViewCone = serde_json::from_value(value)
+            .map_err(|e| RenderError::Resource(format!("Invalid lens calibration: {e}")))?;
+        let config = Self::for_screen(lens.screen_width, lens.screen_height, cone.view_cone);
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// The preset nearest a `width` x `height` screen, with views of the
+    /// screen's aspect ratio.
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_precision_loss,
+        clippy::cast_sign_loss
+    )]
+    fn for_screen(width: u32, height: u32, view_cone: Option<VisualValue>) -> Self {
+        let preset = if width < height {
+            Self::looking_glass_portrait()
+        } else {
+            Self::looking_glass_4k()
+        };
+        let view_width =
+            (preset.view_height as f32 * width as f32 / height.max(1) as f32).round() as u32;
+        Self {
+            view_width: view_width.max(1),
+            view_cone: view_cone.map_or(preset.view_cone, |cone| cone.value.to_radians()),
+            ..preset
+        }
+    }
+
+    /// Check the configuration can be rendered: at least one view, no more
+    /// than the grid holds, a quilt of at most
+    /// [`MAX_SNAPSHOT_SIZE`](crate::MAX_SNAPSHOT_SIZE) pixels a side, a view
+    /// cone under 180 degrees and a positive focal distance.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error naming the first problem found.
+    pub fn validate(&self) -> RenderResult<()> {
+        let invalid = |reason: String| {
+            Err(RenderError::Resource(format!(
+                "Invalid holographic config: {reason}"
+            )))
+        };
+        let cells = u64::from(self.quilt_columns) * u64::from(self.quilt_rows);
+        if self.num_views == 0 || u64::from(self.num_views) > cells {
+            return invalid(format!(
+                "{} views do not fit a {}x{} quilt",
+                self.num_views, self.quilt_columns, self.quilt_rows
+            ));
+        }
+        let width = u64::from(self.quilt_columns) * u64::from(self.view_width);
+        let height = u64::from(self.quilt_rows) * u64::from(self.view_height);
+        let limit = u64::from(MAX_SNAPSHOT_SIZE);
+        if self.view_width == 0 || self.view_height == 0 || width > limit || height > limit {
+            return invalid(format!(
+                "a {width}x{height} quilt, at most {MAX_SNAPSHOT_SIZE} pixels a side"
+            ));
+        }
+        if !(self.view_cone >= 0.0 && self.view_cone < std::f32::consts::PI) {
+            return invalid(format!(
+                "a view cone of {} degrees",
+                self.view_cone.to_degrees()
+            ));
+        }
+        if !(self.focal_distance > 0.0 && self.focal_distance.is_finite()) {
+            return invalid(format!("a focal distance of {}", self.focal_distance));
+        }
+        Ok(())
+    }
+
     /// Calculate the total quilt texture width.
     #[must_use]
     pub fn quilt_width(&self) -> u32 {
@@ -610,6 +907,50 @@ mod tests {
         assert_eq!(config.quilt_rows, 9);
     }
 
+    #[test]
+    fn test_custom_configs_are_validated() {
+        let config = HolographicConfig::custom(48, 8, 6, 420, 560, 0.5, 3.0).unwrap();
+        assert_eq!((config.quilt_width(), config.quilt_height()), (3360, 3360));
+        assert!(approx_eq(config.view_cone, 20.0_f32.to_radians()));
+        assert!(approx_eq(config.focal_distance, 3.0));
+        assert!(HolographicConfig::preset("4K").is_some());
+        assert!(HolographicConfig::preset("cube").is_none());
+
+        // Too many views, an empty view, a quilt past the size limit
+        assert!(HolographicConfig::custom(50, 8, 6, 420, 560, 1.0, 2.0).is_err());
+        assert!(HolographicConfig::custom(48, 8, 6, 0, 560, 1.0, 2.0).is_err());
+        assert!(HolographicConfig::custom(48, 8, 6, 1100, 560, 1.0, 2.0).is_err());
+        assert!(HolographicConfig::custom(48, 8, 6, 420, 560, 5.0, 2.0).is_err());
+        assert!(HolographicConfig::custom(48, 8, 6, 420, 560, 1.0, 0.0).is_err());
+    }
+
+    #[test]
+    fn test_configs_load_from_custom_json_and_device_calibrations() {
+        let custom = r#"{"views": 48, "columns": 8, "rows": 6,
+            "viewWidth": 420, "viewHeight": 560, "focus": 2.5}"#;
+        let config = HolographicConfig::from_calibration_json(custom).unwrap();
+        assert_eq!((config.num_views, config.quilt_columns), (48, 8));
+        assert!(approx_eq(config.view_cone, 40.0_f32.to_radians()));
+        assert!(approx_eq(config.focal_distance, 2.5));
+
+        // A Portrait's visual.json fits the portrait layout to its screen
+        let visual = r#"{
+            "pitch": { "value": 52.58 },
+            "slope": { "value": -7.22 },
+            "center": { "value": 0.08 },
+            "viewCone": { "value": 35.0 },
+            "DPI": { "value": 324.0 },
+            "screenW": { "value": 1536.0 },
+            "screenH": { "value": 2048.0 }
+        }"#;
+        let config = HolographicConfig::from_calibration_json(visual).unwrap();
+        assert_eq!((config.view_width, config.view_height), (420, 560));
+        assert!(approx_eq(config.view_cone, 35.0_f32.to_radians()));
+
+        assert!(HolographicConfig::from_calibration_json("{}").is_err());
+        assert!(HolographicConfig::from_calibration_json(r#"{"views": 0}"#).is_err());
+    }
+
     #[test]
     fn test_quilt_dimensions() {
         let config = HolographicConfig::looking_glass_portrait();
@@ -910,6 +1251,84 @@ mod tests {
         assert!(approx_eq(view_cam.position.z, base.position.z));
     }
 
+    // ===========================================
+    // TDD: CanvasDepth Tests
+    // ===========================================
+
+    #[test]
+    fn test_canvas_depth_follows_z_index_unless_set() {
+        use canvas_core::{ElementKind, ShapeKind, Transform};
+
+        let depth = CanvasDepth::default();
+        let element = |z_index| {
+            Element::new(ElementKind::Shape {
+                shape: ShapeKind::Rectangle,
+            })
+            .with_transform(Transform {
+                z_index,
+                ..Transform::default()
+            })
+        };
+        assert!(approx_eq(depth.element_depth(&element(0)), 0.0));
+        assert!(approx_eq(depth.element_depth(&element(2)), 40.0));
+        assert!(approx_eq(depth.element_depth(&element(-1)), -20.0));
+        assert!(approx_eq(
+            depth.element_depth(&element(2).with_depth(-35.0)),
+            -35.0
+        ));
+
+        // Clamped to the depth of field
+        assert!(approx_eq(depth.element_depth(&element(100)), 150.0));
+        assert!(approx_eq(
+            depth.element_depth(&element(0).with_depth(-1000.0)),
+            -150.0
+        ));
+
+        assert!(approx_eq(depth.normalized(0.0), 0.5));
+        assert!(approx_eq(depth.normalized(150.0), 1.0));
+        assert!(approx_eq(depth.normalized(-75.0), 0.25));
+    }
+
+    #[test]
+    fn test_canvas_to_world_fills_the_view_and_gives_parallax() {
+        let config = HolographicConfig {
+            num_views: 3,
+            ..HolographicConfig::looking_glass_portrait()
+        };
+        let base = Camera::new();
+        let to_world = CanvasDepth::canvas_to_world(&base, 400.0, 300.0);
+        let clip = |camera: &Camera, point: [f32; 4]| {
+            // Portrait views are 420x560
+            let aspect = 0.75;
+            let view_projection = camera.projection_matrix(aspect).mul(&camera.view_matrix());
+            let clip = view_projection.mul(&to_world).transform(point);
+            [clip[0] / clip[3], clip[1] / clip[3]]
+        };
+
+        // The viewport center is the target; its top and bottom edges are
+        // the center view's
+        let center = config.camera_for_view(&base, 1);
+        let middle = clip(&center, [200.0, 150.0, 0.0, 1.0]);
+        assert!(approx_eq(middle[0], 0.0) && approx_eq(middle[1], 0.0));
+        assert!((clip(&center, [200.0, 0.0, 0.0, 1.0])[1] - 1.0).abs() < 1e-4);
+        assert!((clip(&center, [200.0, 300.0, 0.0, 1.0])[1] + 1.0).abs() < 1e-4);
+
+        // Points on the focal plane stay put from view to view; nearer
+        // points shift further
+        let (left, right) = (
+            config.camera_for_view(&base, 0),
+            config.camera_for_view(&base, 2),
+        );
+        let shift = |depth: f32| {
+            let point = [200.0, 150.0, depth, 1.0];
+            clip(&right, point)[0] - clip(&left, point)[0]
+        };
+        assert!(shift(0.0).abs() < 1e-4);
+        assert!(shift(100.0).abs() > shift(20.0).abs());
+        assert!(shift(20.0).abs() > 1e-3);
+        assert!(shift(-20.0) * shift(20.0) < 0.0);
+    }
+
     // ===========================================
     // TDD: QuiltRenderInfo Tests
     // ===========================================
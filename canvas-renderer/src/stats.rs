@@ -0,0 +1,332 @@
+//! # Render Statistics
+//!
+//! Per-frame counters reported by backends and rolling statistics kept by
+//! hosts, so performance regressions show up in telemetry.
+//!
+//! Backends report a [`FrameStats`] for the last frame through
+//! [`RenderBackend::frame_stats`](crate::RenderBackend::frame_stats); hosts
+//! time the frame and feed both into [`RenderStats::record`]. Backends that
+//! can time the GPU report it through
+//! [`RenderBackend::gpu_time_ms`](crate::RenderBackend::gpu_time_ms).
+//!
+//! [`RenderStats::overlay_element`] lays the numbers out as a screen-anchored
+//! panel; [`RendererConfig::debug_overlay`](crate::RendererConfig::debug_overlay)
+//! draws it over every frame.
+
+use canvas_core::{Element, ElementKind, Transform};
+use serde::{Deserialize, Serialize};
+
+use crate::BackendType;
+
+/// Top-left corner of the debug overlay, in viewport pixels.
+const OVERLAY_ORIGIN: (f32, f32) = (8.0, 8.0);
+
+/// Width of the debug overlay panel, in viewport pixels.
+const OVERLAY_WIDTH: f32 = 340.0;
+
+/// Element counts for a single rendered frame.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FrameStats {
+    /// Elements in the scene.
+    pub elements_total: usize,
+    /// Elements drawn.
+    pub elements_drawn: usize,
+    /// Elements skipped because they were outside the viewport.
+    pub elements_culled: usize,
+    /// Draw calls issued, for backends that batch elements into them
+    /// (0 otherwise).
+    #[serde(default)]
+    pub draw_calls: usize,
+    /// Triangles submitted, for GPU backends (0 otherwise).
+    #[serde(default)]
+    pub triangles: usize,
+    /// Bytes of textures the backend keeps on the GPU (images, glyphs,
+    /// video frames and render targets), or 0 where it does not track
+    /// them.
+    #[serde(default)]
+    pub texture_bytes: u64,
+}
+
+impl FrameStats {
+    /// Counts for a frame that drew everything except `culled` elements.
+    #[must_use]
+    pub fn new(elements_total: usize, elements_culled: usize) -> Self {
+        Self {
+            elements_total,
+            elements_drawn: elements_total.saturating_sub(elements_culled),
+            elements_culled,
+            draw_calls: 0,
+            triangles: 0,
+            texture_bytes: 0,
+        }
+    }
+}
+
+/// Rolling renderer statistics.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RenderStats {
+    /// Backend that rendered the frames.
+    pub backend: BackendType,
+    /// Total frames rendered.
+    pub frames_rendered: u64,
+    /// Frames skipped because nothing had changed.
+    #[serde(default)]
+    pub frames_skipped: u64,
+    /// Element counts from the most recent frame.
+    pub last_frame: FrameStats,
+    /// CPU time of the most recent frame in milliseconds: recording and
+    /// submitting it, not waiting for the GPU.
+    pub frame_time_ms: f64,
+    /// Average frame time in milliseconds.
+    pub avg_frame_time_ms: f64,
+    /// Peak frame time in milliseconds.
+    pub peak_frame_time_ms: f64,
+    /// GPU time of the most recently timed frame in milliseconds, or
+    /// `None` if the backend cannot time the GPU. Usually a frame or two
+    /// behind, since the GPU finishes after the CPU.
+    #[serde(default)]
+    pub gpu_time_ms: Option<f64>,
+}
+
+impl RenderStats {
+    /// Empty statistics for `backend`.
+    #[must_use]
+    pub fn new(backend: BackendType) -> Self {
+        Self {
+            backend,
+            frames_rendered: 0,
+            frames_skipped: 0,
+            last_frame: FrameStats::default(),
+            frame_time_ms: 0.0,
+            avg_frame_time_ms: 0.0,
+            peak_frame_time_ms: 0.0,
+            gpu_time_ms: None,
+        }
+    }
+
+    /// Update statistics with a rendered frame.
+    pub fn record(&mut self, frame: FrameStats, frame_time_ms: f64) {
+        self.frames_rendered += 1;
+        self.last_frame = frame;
+        self.frame_time_ms = frame_time_ms;
+
+        // Exponential moving average, seeded with the first frame
+        let alpha = 0.1;
+        self.avg_frame_time_ms = if self.frames_rendered == 1 {
+            frame_time_ms
+        } else {
+            alpha * frame_time_ms + (1.0 - alpha) * self.avg_frame_time_ms
+        };
+
+        if frame_time_ms > self.peak_frame_time_ms {
+            self.peak_frame_time_ms = frame_time_ms;
+        }
+    }
+
+    /// Update the GPU time with the backend's latest measurement; `None`
+    /// keeps the previous one.
+    pub fn record_gpu_time(&mut self, gpu_time_ms: Option<f64>) {
+        if gpu_time_ms.is_some() {
+            self.gpu_time_ms = gpu_time_ms;
+        }
+    }
+
+    /// The statistics as the lines of the debug overlay.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)] // Byte counts are shown rounded
+    pub fn overlay_lines(&self) -> Vec<String> {
+        let frame = &self.last_frame;
+        let gpu = self
+            .gpu_time_ms
+            .map_or_else(|| "n/a".to_string(), |ms| format!("{ms:.2} ms"));
+        vec![
+            format!(
+                "{} cpu {:.2} ms (avg {:.2}, peak {:.2})",
+                self.backend.name(),
+                self.frame_time_ms,
+                self.avg_frame_time_ms,
+                self.peak_frame_time_ms
+            ),
+            format!("gpu {gpu}"),
+            format!(
+                "draws {} tris {} tex {:.1} MB",
+                frame.draw_calls,
+                frame.triangles,
+                frame.texture_bytes as f64 / (1024.0 * 1024.0)
+            ),
+            format!(
+                "elements {} drawn {} culled {}",
+                frame.elements_total, frame.elements_drawn, frame.elements_culled
+            ),
+            format!(
+                "frames {} skipped {}",
+                self.frames_rendered, self.frames_skipped
+            ),
+        ]
+    }
+
+    /// The statistics as a screen-anchored code panel in the top-left
+    /// corner, for hosts that draw their own overlay. Not interactive, so
+    /// it never takes a tap meant for the scene.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)] // A handful of lines
+    pub fn overlay_element(&self) -> Element {
+        let lines = self.overlay_lines();
+        let height = canvas_core::highlight::CODE_LINE_HEIGHT * lines.len() as f32
+            + 2.0 * canvas_core::highlight::CODE_PADDING;
+        Element::new(ElementKind::code("", lines.join("\n")))
+            .with_transform(Transform {
+                x: OVERLAY_ORIGIN.0,
+                y: OVERLAY_ORIGIN.1,
+                width: OVERLAY_WIDTH,
+                height,
+                rotation: 0.0,
+                z_index: i32::MAX,
+                auto_size: false,
+            })
+            .with_screen_anchored(true)
+            .with_interactive(false)
+    }
+
+    /// Count a frame skipped because nothing had changed.
+    pub fn record_skipped(&mut self) {
+        self.frames_skipped += 1;
+    }
+
+    /// Reset all statistics, keeping the backend.
+    pub fn reset(&mut self) {
+        *self = Self::new(self.backend);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::canvas2d::Canvas2DBackend;
+    use crate::{Renderer, RendererConfig};
+    use canvas_core::{Color, Element, ElementKind, Scene, Transform};
+
+    #[test]
+    fn test_frame_stats_counts_drawn() {
+        let frame = FrameStats::new(10, 3);
+        assert_eq!(frame.elements_drawn, 7);
+        assert_eq!(FrameStats::new(2, 5).elements_drawn, 0);
+    }
+
+    #[test]
+    fn test_record_tracks_average_and_peak() {
+        let mut stats = RenderStats::new(BackendType::Canvas2D);
+        stats.record(FrameStats::new(4, 1), 10.0);
+        assert!((stats.avg_frame_time_ms - 10.0).abs() < f64::EPSILON);
+
+        stats.record(FrameStats::new(4, 2), 20.0);
+        assert_eq!(stats.frames_rendered, 2);
+        assert_eq!(stats.last_frame.elements_culled, 2);
+        assert!((stats.frame_time_ms - 20.0).abs() < f64::EPSILON);
+        assert!((stats.avg_frame_time_ms - 11.0).abs() < 1e-9);
+        assert!((stats.peak_frame_time_ms - 20.0).abs() < f64::EPSILON);
+
+        stats.reset();
+        assert_eq!(stats.frames_rendered, 0);
+        assert_eq!(stats.backend, BackendType::Canvas2D);
+    }
+
+    #[test]
+    fn test_renderer_records_culled_elements() {
+        let mut scene = Scene::new(800.0, 600.0);
+        for x in [10.0, 5000.0] {
+            scene.add_element(
+                Element::new(ElementKind::Text {
+                    content: String::new(),
+                    font_size: 16.0,
+                    color: Color::BLACK.into(),
+                })
+                .with_transform(Transform {
+                    x,
+                    y: 10.0,
+                    width: 50.0,
+                    height: 50.0,
+                    rotation: 0.0,
+                    z_index: 0,
+                    auto_size: false,
+                }),
+            );
+        }
+
+        let mut renderer = Renderer::with_backend(
+            Box::new(Canvas2DBackend::new()),
+            RendererConfig {
+                preferred_backend: BackendType::Canvas2D,
+                ..RendererConfig::default()
+            },
+        );
+        renderer.render(&scene).expect("should render");
+
+        let stats = renderer.stats();
+        assert_eq!(stats.backend, BackendType::Canvas2D);
+        assert_eq!(stats.frames_rendered, 1);
+        assert_eq!(stats.last_frame, FrameStats::new(2, 1));
+        assert!(stats.frame_time_ms >= 0.0);
+    }
+
+    #[test]
+    fn test_gpu_time_keeps_last_measurement() {
+        let mut stats = RenderStats::new(BackendType::WebGpu);
+        assert!(stats.overlay_lines()[1].ends_with("n/a"));
+
+        stats.record_gpu_time(Some(1.5));
+        stats.record_gpu_time(None);
+        assert_eq!(stats.gpu_time_ms, Some(1.5));
+        assert_eq!(stats.overlay_lines()[1], "gpu 1.50 ms");
+    }
+
+    #[test]
+    fn test_overlay_element_shows_counts() {
+        let mut stats = RenderStats::new(BackendType::WebGpu);
+        stats.record(
+            FrameStats {
+                draw_calls: 3,
+                triangles: 12,
+                texture_bytes: 2 * 1024 * 1024,
+                ..FrameStats::new(8, 2)
+            },
+            4.0,
+        );
+
+        let element = stats.overlay_element();
+        let ElementKind::Code { source, .. } = &element.kind else {
+            panic!("overlay should be a code panel");
+        };
+        assert!(source.contains("draws 3 tris 12 tex 2.0 MB"));
+        assert!(source.contains("elements 8 drawn 6 culled 2"));
+        assert!(element.screen_anchored);
+        assert!(!element.interactive);
+        assert_eq!(element.transform.z_index, i32::MAX);
+    }
+
+    #[test]
+    fn test_debug_overlay_draws_every_frame() {
+        let mut renderer = Renderer::with_backend(
+            Box::new(Canvas2DBackend::new()),
+            RendererConfig {
+                preferred_backend: BackendType::Canvas2D,
+                damage_tracking: true,
+                ..RendererConfig::default()
+            },
+        );
+        let scene = Scene::new(800.0, 600.0);
+        renderer.render(&scene).expect("should render");
+        renderer.render(&scene).expect("should render");
+        assert_eq!(renderer.stats().frames_skipped, 1);
+
+        renderer.set_debug_overlay(true);
+        renderer.render(&scene).expect("should render");
+        renderer.render(&scene).expect("should render");
+        let stats = renderer.stats();
+        assert_eq!(stats.frames_skipped, 1);
+        assert_eq!(stats.frames_rendered, 3);
+        assert_eq!(stats.last_frame.elements_total, 1);
+        // The scene itself is untouched
+        assert_eq!(scene.element_count(), 0);
+    }
+}
@@ -35,7 +35,7 @@ impl Default for TextureCacheConfig {
     fn default() -> Self {
         Self {
             max_size_bytes: 256 * 1024 * 1024, // 256 MB
-            max_age: Duration::from_secs(300), // 5 minutes
+            max_age: Duration::from_mins(5),   // 5 minutes
             max_entries: 1000,
         }
     }
@@ -322,10 +322,7 @@ pub mod sync {
         /// Check if a texture is cached.
         #[must_use]
         pub fn contains(&self, key: &str) -> bool {
-            self.inner
-                .read()
-                .map(|cache| cache.contains(key))
-                .unwrap_or(false)
+            self.inner.read().is_ok_and(|cache| cache.contains(key))
         }
 
         /// Get cache statistics.
@@ -375,7 +372,7 @@ mod tests {
     fn test_cache_eviction_by_size() {
         let config = TextureCacheConfig {
             max_size_bytes: 1000, // Very small
-            max_age: Duration::from_secs(3600),
+            max_age: Duration::from_hours(1),
             max_entries: 100,
         };
 
@@ -393,7 +390,7 @@ mod tests {
     fn test_cache_eviction_by_count() {
         let config = TextureCacheConfig {
             max_size_bytes: 1024 * 1024,
-            max_age: Duration::from_secs(3600),
+            max_age: Duration::from_hours(1),
             max_entries: 2,
         };
 
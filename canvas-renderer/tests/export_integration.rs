@@ -4,7 +4,7 @@
 //! and edge cases.
 
 use canvas_core::element::{Element, ElementKind, Transform};
-use canvas_core::Scene;
+use canvas_core::{Color, Scene};
 use canvas_renderer::export::{ExportConfig, ExportFormat, SceneExporter};
 
 /// Create a text element at a given position.
@@ -12,7 +12,7 @@ fn text_element(content: &str, x: f32, y: f32) -> Element {
     Element::new(ElementKind::Text {
         content: content.to_string(),
         font_size: 16.0,
-        color: "#000000".to_string(),
+        color: Color::BLACK.into(),
     })
     .with_transform(Transform {
         x,
@@ -21,6 +21,7 @@ fn text_element(content: &str, x: f32, y: f32) -> Element {
         height: 30.0,
         rotation: 0.0,
         z_index: 0,
+        auto_size: false,
     })
 }
 
@@ -32,6 +33,7 @@ fn bar_chart_element(x: f32, y: f32) -> Element {
             "labels": ["Q1", "Q2", "Q3", "Q4"],
             "values": [120, 250, 180, 300]
         }),
+        binding: None,
     })
     .with_transform(Transform {
         x,
@@ -40,6 +42,7 @@ fn bar_chart_element(x: f32, y: f32) -> Element {
         height: 300.0,
         rotation: 0.0,
         z_index: 1,
+        auto_size: false,
     })
 }
 
@@ -259,3 +262,51 @@ fn test_special_characters_in_text() {
     let png = exporter.export(&scene, ExportFormat::Png).expect("png");
     assert_eq!(&png[0..4], &[137, 80, 78, 71]);
 }
+
+// ==========================================================================
+// Offscreen snapshots
+// ==========================================================================
+
+#[test]
+fn test_render_to_image_falls_back_to_cpu() {
+    use canvas_core::ShapeKind;
+    use canvas_renderer::backend::canvas2d::Canvas2DBackend;
+    use canvas_renderer::{Renderer, RendererConfig};
+
+    let mut scene = Scene::new(100.0, 80.0);
+    scene.add_element(
+        Element::new(ElementKind::Shape {
+            shape: ShapeKind::Rectangle,
+        })
+        .with_transform(Transform {
+            x: 10.0,
+            y: 10.0,
+            width: 40.0,
+            height: 30.0,
+            rotation: 0.0,
+            z_index: 0,
+            auto_size: false,
+        })
+        .with_style(canvas_core::Style::solid(Color::from_rgb(0xff_0000))),
+    );
+
+    let mut renderer = Renderer::with_backend(
+        Box::new(Canvas2DBackend::new()),
+        RendererConfig {
+            background_color: [0.0, 0.0, 1.0, 1.0],
+            ..RendererConfig::default()
+        },
+    );
+    let image = renderer.render_to_image(&scene, 100, 80).expect("snapshot");
+
+    assert_eq!((image.width(), image.height()), (100, 80));
+    assert_eq!(image.pixel(30, 25), Some([255, 0, 0, 255]));
+    assert_eq!(image.pixel(90, 70), Some([0, 0, 255, 255]));
+    assert_eq!(renderer.frame_count(), 0);
+
+    let png = image.encode_png().expect("png");
+    assert_eq!(&png[0..4], &[137, 80, 78, 71]);
+
+    assert!(renderer.render_to_image(&scene, 0, 80).is_err());
+    assert!(renderer.render_to_image(&scene, 100, 10_000).is_err());
+}
@@ -3,7 +3,7 @@
 //! These tests verify the complete pipeline from scene to quilt rendering,
 //! ensuring proper integration between spatial, quilt, and holographic modules.
 
-use canvas_core::{Element, ElementKind, Scene};
+use canvas_core::{Color, Element, ElementKind, Scene};
 use canvas_renderer::holographic::HolographicRenderer;
 use canvas_renderer::quilt::QuiltRenderSettings;
 use canvas_renderer::spatial::{Camera, HolographicConfig, Vec3};
@@ -56,7 +56,7 @@ fn integration_scene_to_quilt_with_elements() {
     let text_element = Element::new(ElementKind::Text {
         content: "Test".to_string(),
         font_size: 24.0,
-        color: "#FFFFFF".to_string(),
+        color: Color::WHITE.into(),
     });
     scene.add_element(text_element);
 
@@ -64,6 +64,8 @@ fn integration_scene_to_quilt_with_elements() {
     let image_element = Element::new(ElementKind::Image {
         src: "data:image/png;base64,".to_string(),
         format: canvas_core::ImageFormat::Png,
+        annotations: Vec::new(),
+        extracted_text: None,
     });
     scene.add_element(image_element);
 
@@ -356,6 +358,7 @@ fn custom_settings_applied() {
         wireframe: true,
         depth_test: false,
         backface_cull: false,
+        ..QuiltRenderSettings::default()
     };
 
     let renderer = HolographicRenderer::with_settings(config, settings);
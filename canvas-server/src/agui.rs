@@ -32,7 +32,7 @@ use axum::{
     },
     Json,
 };
-use canvas_core::A2UITree;
+use canvas_core::{A2UITree, ContextMenuItem};
 use futures::stream::Stream;
 use serde::{Deserialize, Serialize};
 use std::{convert::Infallible, time::Duration};
@@ -151,6 +151,42 @@ pub enum InteractionEvent {
         /// Center Y coordinate.
         center_y: f32,
     },
+
+    /// Tap on an element with a declared `on_tap` action.
+    Action {
+        /// ID of the tapped element.
+        element_id: String,
+        /// Action identifier declared on the element.
+        action: String,
+        /// Extra data declared with the action.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        payload: Option<serde_json::Value>,
+    },
+
+    /// Drag of an element with a declared `on_drag` behavior.
+    Drag {
+        /// ID of the dragged element.
+        element_id: String,
+        /// Action identifier declared on the element (if any).
+        #[serde(skip_serializing_if = "Option::is_none")]
+        action: Option<String>,
+        /// New X position.
+        x: f32,
+        /// New Y position.
+        y: f32,
+    },
+
+    /// Long press on an element with a declared `on_long_press` menu.
+    ContextMenu {
+        /// ID of the pressed element.
+        element_id: String,
+        /// Press X coordinate.
+        x: f32,
+        /// Press Y coordinate.
+        y: f32,
+        /// Menu entries shown to the user.
+        items: Vec<ContextMenuItem>,
+    },
 }
 
 /// Request to render an A2UI tree.
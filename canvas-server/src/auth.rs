@@ -0,0 +1,1199 @@
+//! # OIDC Authentication
+//!
+//! Optional sign-in through an organization's OpenID Connect provider, so
+//! the canvas is only open to people the provider vouches for.
+//!
+//! ```text
+//! browser ─▶ /auth/login ─▶ provider sign-in ─▶ /auth/callback?code&state
+//!   → code exchanged for an ID token (with PKCE), checked, and kept in an
+//!     HttpOnly session cookie
+//!
+//! API / MCP / WebSocket request
+//!   → token from `Authorization: Bearer`, the session cookie or
+//!     `?access_token=` (browsers cannot set WebSocket headers)
+//!   → signature checked against the provider's JWKS (RS256 or ES256),
+//!     then issuer, audience and expiry
+//!   → claims mapped to a role: viewers read, editors also write
+//! ```
+//!
+//! Health and metrics endpoints stay open for probes. Requests without a
+//! valid token get `401`, or a redirect to `/auth/login` when a browser
+//! navigates to a page. Viewers get `403` for REST writes, MCP tool calls
+//! that write are refused, and their sync WebSocket is read-only.
+//!
+//! Configured from `CANVAS_OIDC_*` environment variables; see
+//! [`OidcConfig::from_env`].
+
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use axum::{
+    extract::{Query, Request, State},
+    http::{header, HeaderMap, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Redirect, Response},
+    routing::get,
+    Extension, Json, Router,
+};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use ring::signature::{
+    RsaPublicKeyComponents, UnparsedPublicKey, ECDSA_P256_SHA256_FIXED, RSA_PKCS1_2048_8192_SHA256,
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio::sync::{Mutex, RwLock};
+
+/// Cookie holding the signed-in browser's ID token.
+pub const SESSION_COOKIE: &str = "canvas_session";
+
+/// JSON-RPC error code for MCP writes refused to viewers.
+pub const FORBIDDEN_CODE: i32 = -32004;
+
+/// Clock difference tolerated when checking token expiry.
+const LEEWAY_SECS: u64 = 60;
+/// How long a started sign-in may take.
+const LOGIN_TIMEOUT: Duration = Duration::from_secs(600);
+/// Most sign-ins in progress at once; the oldest are dropped first.
+const MAX_PENDING_LOGINS: usize = 1000;
+/// Shortest time between JWKS fetches for unknown key IDs.
+const KEY_REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Paths served without a token.
+const PUBLIC_PATHS: &[&str] = &[
+    "/health",
+    "/health/live",
+    "/health/ready",
+    "/metrics",
+    "/auth/login",
+    "/auth/callback",
+    "/auth/logout",
+    // Protected by CANVAS_STANDBY_TOKEN instead
+    "/ws/standby",
+    "/api/standby/promote",
+];
+
+/// What a signed-in user may do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    /// Read scenes and follow them live.
+    Viewer,
+    /// Everything a viewer can, plus changing scenes.
+    Editor,
+}
+
+impl Role {
+    /// Lowercase name, as in configuration.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Viewer => "viewer",
+            Self::Editor => "editor",
+        }
+    }
+
+    /// Whether this role may change scenes.
+    pub const fn can_edit(self) -> bool {
+        matches!(self, Self::Editor)
+    }
+}
+
+impl FromStr for Role {
+    type Err = AuthError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "viewer" => Ok(Self::Viewer),
+            "editor" => Ok(Self::Editor),
+            other => Err(AuthError::Config(format!("unknown role: {other}"))),
+        }
+    }
+}
+
+/// A claim value that grants a role.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoleMapping {
+    /// Value of the role claim, e.g. a group name.
+    pub value: String,
+    /// Role it grants.
+    pub role: Role,
+}
+
+/// Settings for signing in through an OIDC provider.
+#[derive(Debug, Clone)]
+pub struct OidcConfig {
+    /// Issuer URL; discovery is read from
+    /// `{issuer}/.well-known/openid-configuration`.
+    pub issuer: String,
+    /// Client ID registered with the provider.
+    pub client_id: String,
+    /// Client secret, for confidential clients.
+    pub client_secret: Option<String>,
+    /// Where the provider sends the browser back to: this server's
+    /// `/auth/callback`.
+    pub redirect_url: String,
+    /// Scopes requested at sign-in.
+    pub scopes: Vec<String>,
+    /// Another accepted token audience besides `client_id`, for access
+    /// tokens issued to API clients.
+    pub audience: Option<String>,
+    /// Claim holding the user's groups or roles; a dotted path reaches
+    /// into objects, e.g. `realm_access.roles`.
+    pub role_claim: String,
+    /// Claim values and the roles they grant; the highest matching role
+    /// wins.
+    pub role_map: Vec<RoleMapping>,
+    /// Role of users no mapping matches, or `None` to refuse them.
+    pub default_role: Option<Role>,
+}
+
+impl OidcConfig {
+    /// Settings for `client_id` at `issuer`, with the standard scopes and
+    /// every signed-in user an editor.
+    pub fn new(
+        issuer: impl Into<String>,
+        client_id: impl Into<String>,
+        redirect_url: impl Into<String>,
+    ) -> Self {
+        Self {
+            issuer: issuer.into().trim_end_matches('/').to_string(),
+            client_id: client_id.into(),
+            client_secret: None,
+            redirect_url: redirect_url.into(),
+            scopes: ["openid", "profile", "email"].map(String::from).to_vec(),
+            audience: None,
+            role_claim: "roles".to_string(),
+            role_map: Vec::new(),
+            default_role: Some(Role::Editor),
+        }
+    }
+
+    /// Read settings from the environment, or `None` when
+    /// `CANVAS_OIDC_ISSUER` is unset.
+    ///
+    /// - `CANVAS_OIDC_ISSUER`, `CANVAS_OIDC_CLIENT_ID` (required),
+    ///   `CANVAS_OIDC_CLIENT_SECRET`
+    /// - `CANVAS_OIDC_REDIRECT_URL`, by default
+    ///   `http://localhost:{port}/auth/callback`
+    /// - `CANVAS_OIDC_SCOPES`, space separated
+    /// - `CANVAS_OIDC_AUDIENCE`
+    /// - `CANVAS_OIDC_ROLE_CLAIM`, by default `roles`
+    /// - `CANVAS_OIDC_ROLES`, e.g. `canvas-editors=editor,staff=viewer`
+    /// - `CANVAS_OIDC_DEFAULT_ROLE`: `viewer`, `editor` or `none`; `editor`
+    ///   without `CANVAS_OIDC_ROLES`, otherwise `none`
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AuthError::Config`] if the client ID is missing or a role
+    /// is unknown.
+    pub fn from_env(port: u16) -> Result<Option<Self>, AuthError> {
+        let var = |name: &str| {
+            std::env::var(name)
+                .ok()
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+        };
+        let Some(issuer) = var("CANVAS_OIDC_ISSUER") else {
+            return Ok(None);
+        };
+        let client_id = var("CANVAS_OIDC_CLIENT_ID")
+            .ok_or_else(|| AuthError::Config("CANVAS_OIDC_CLIENT_ID is not set".to_string()))?;
+        let redirect_url = var("CANVAS_OIDC_REDIRECT_URL")
+            .unwrap_or_else(|| format!("http://localhost:{port}/auth/callback"));
+
+        let mut config = Self::new(issuer, client_id, redirect_url);
+        config.client_secret = var("CANVAS_OIDC_CLIENT_SECRET");
+        config.audience = var("CANVAS_OIDC_AUDIENCE");
+        if let Some(scopes) = var("CANVAS_OIDC_SCOPES") {
+            config.scopes = scopes.split_whitespace().map(String::from).collect();
+        }
+        if let Some(claim) = var("CANVAS_OIDC_ROLE_CLAIM") {
+            config.role_claim = claim;
+        }
+        if let Some(roles) = var("CANVAS_OIDC_ROLES") {
+            config.role_map = parse_role_map(&roles)?;
+            config.default_role = None;
+        }
+        if let Some(role) = var("CANVAS_OIDC_DEFAULT_ROLE") {
+            config.default_role = match role.as_str() {
+                "none" => None,
+                role => Some(role.parse()?),
+            };
+        }
+        Ok(Some(config))
+    }
+
+    /// Role granted by the claims, or `None` if they grant none.
+    pub fn role_for(&self, claims: &serde_json::Value) -> Option<Role> {
+        let claim = self
+            .role_claim
+            .split('.')
+            .try_fold(claims, |value, key| value.get(key));
+        let values: Vec<&str> = match claim {
+            Some(serde_json::Value::String(value)) => vec![value.as_str()],
+            Some(serde_json::Value::Array(values)) => values
+                .iter()
+                .filter_map(serde_json::Value::as_str)
+                .collect(),
+            _ => Vec::new(),
+        };
+        self.role_map
+            .iter()
+            .filter(|mapping| values.contains(&mapping.value.as_str()))
+            .map(|mapping| mapping.role)
+            .max()
+            .or(self.default_role)
+    }
+}
+
+/// Parse `value=role` pairs separated by commas.
+fn parse_role_map(text: &str) -> Result<Vec<RoleMapping>, AuthError> {
+    text.split(',')
+        .filter(|pair| !pair.trim().is_empty())
+        .map(|pair| {
+            let (value, role) = pair
+                .rsplit_once('=')
+                .ok_or_else(|| AuthError::Config(format!("expected value=role, got {pair}")))?;
+            Ok(RoleMapping {
+                value: value.trim().to_string(),
+                role: role.parse()?,
+            })
+        })
+        .collect()
+}
+
+/// Errors from signing in and checking tokens.
+#[derive(Debug, Error)]
+pub enum AuthError {
+    /// The settings are incomplete or invalid.
+    #[error("invalid OIDC configuration: {0}")]
+    Config(String),
+
+    /// The provider's discovery document or keys could not be fetched.
+    #[error("OIDC provider unavailable: {0}")]
+    Provider(String),
+
+    /// The request carries no token.
+    #[error("no credentials")]
+    MissingToken,
+
+    /// The token is malformed, badly signed or not meant for this server.
+    #[error("invalid token: {0}")]
+    InvalidToken(String),
+
+    /// The token has expired.
+    #[error("token expired")]
+    Expired,
+
+    /// The token is valid but grants no role.
+    #[error("no canvas role for {0}")]
+    NoRole(String),
+
+    /// The sign-in callback does not match a sign-in in progress.
+    #[error("unknown or expired sign-in")]
+    UnknownLogin,
+
+    /// The provider refused the authorization code.
+    #[error("token exchange failed: {0}")]
+    Exchange(String),
+}
+
+impl AuthError {
+    /// HTTP status for a request failing with this error.
+    pub fn status(&self) -> StatusCode {
+        match self {
+            Self::Config(_) | Self::Provider(_) => StatusCode::BAD_GATEWAY,
+            Self::NoRole(_) => StatusCode::FORBIDDEN,
+            _ => StatusCode::UNAUTHORIZED,
+        }
+    }
+}
+
+/// A signed-in user, as found in request extensions behind
+/// [`authenticate`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Principal {
+    /// Subject identifier (`sub` claim).
+    pub subject: String,
+    /// Display name, if the token has one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Email address, if the token has one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    /// What the user may do.
+    pub role: Role,
+    /// When the token expires, in seconds since the Unix epoch.
+    pub expires_at: u64,
+}
+
+/// Endpoints from the provider's discovery document.
+#[derive(Debug, Clone, Deserialize)]
+struct ProviderMetadata {
+    issuer: String,
+    authorization_endpoint: String,
+    token_endpoint: String,
+    jwks_uri: String,
+}
+
+/// A public key from the provider's JWKS.
+#[derive(Debug, Clone, Deserialize)]
+struct Jwk {
+    kty: String,
+    #[serde(default)]
+    kid: Option<String>,
+    #[serde(default, rename = "use")]
+    usage: Option<String>,
+    #[serde(default)]
+    n: Option<String>,
+    #[serde(default)]
+    e: Option<String>,
+    #[serde(default)]
+    crv: Option<String>,
+    #[serde(default)]
+    x: Option<String>,
+    #[serde(default)]
+    y: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct JwkSet {
+    keys: Vec<Jwk>,
+}
+
+#[derive(Debug, Deserialize)]
+struct JwtHeader {
+    alg: String,
+    #[serde(default)]
+    kid: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    id_token: String,
+}
+
+/// A sign-in waiting for the provider to send the browser back.
+#[derive(Debug)]
+struct PendingLogin {
+    nonce: String,
+    verifier: String,
+    next: String,
+    started: Instant,
+}
+
+/// Cached provider keys.
+#[derive(Debug, Default)]
+struct KeyCache {
+    keys: Vec<Jwk>,
+    fetched: Option<Instant>,
+}
+
+/// Signs users in through an OIDC provider and checks their tokens.
+pub struct OidcProvider {
+    config: OidcConfig,
+    http: reqwest::Client,
+    metadata: RwLock<Option<ProviderMetadata>>,
+    keys: RwLock<KeyCache>,
+    logins: Mutex<HashMap<String, PendingLogin>>,
+}
+
+impl std::fmt::Debug for OidcProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OidcProvider")
+            .field("issuer", &self.config.issuer)
+            .field("client_id", &self.config.client_id)
+            .finish_non_exhaustive()
+    }
+}
+
+impl OidcProvider {
+    /// Create a provider; discovery happens on first use.
+    pub fn new(config: OidcConfig) -> Self {
+        Self {
+            config,
+            http: reqwest::Client::new(),
+            metadata: RwLock::new(None),
+            keys: RwLock::new(KeyCache::default()),
+            logins: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// The settings.
+    pub fn config(&self) -> &OidcConfig {
+        &self.config
+    }
+
+    /// The provider's endpoints, fetched once.
+    async fn metadata(&self) -> Result<ProviderMetadata, AuthError> {
+        if let Some(metadata) = self.metadata.read().await.as_ref() {
+            return Ok(metadata.clone());
+        }
+        let url = format!("{}/.well-known/openid-configuration", self.config.issuer);
+        let metadata: ProviderMetadata = self.fetch_json(&url).await?;
+        if metadata.issuer.trim_end_matches('/') != self.config.issuer {
+            return Err(AuthError::Provider(format!(
+                "discovery names issuer {}",
+                metadata.issuer
+            )));
+        }
+        *self.metadata.write().await = Some(metadata.clone());
+        Ok(metadata)
+    }
+
+    async fn fetch_json<T: serde::de::DeserializeOwned>(&self, url: &str) -> Result<T, AuthError> {
+        self.http
+            .get(url)
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .map_err(|e| AuthError::Provider(e.to_string()))?
+            .json()
+            .await
+            .map_err(|e| AuthError::Provider(e.to_string()))
+    }
+
+    /// The key `kid` names (or the only signing key, without one),
+    /// refetching the JWKS when it is unknown so rotated keys are found.
+    async fn key(&self, kid: Option<&str>) -> Result<Jwk, AuthError> {
+        let find = |cache: &KeyCache| {
+            let mut keys = cache
+                .keys
+                .iter()
+                .filter(|key| key.usage.as_deref().is_none_or(|usage| usage == "sig"));
+            match kid {
+                Some(kid) => keys.find(|key| key.kid.as_deref() == Some(kid)).cloned(),
+                None => keys.next().cloned(),
+            }
+        };
+        {
+            let cache = self.keys.read().await;
+            if let Some(key) = find(&cache) {
+                return Ok(key);
+            }
+            if cache
+                .fetched
+                .is_some_and(|fetched| fetched.elapsed() < KEY_REFRESH_INTERVAL)
+            {
+                return Err(AuthError::InvalidToken("unknown signing key".to_string()));
+            }
+        }
+        let jwks_uri = self.metadata().await?.jwks_uri;
+        let set: JwkSet = self.fetch_json(&jwks_uri).await?;
+        let mut cache = self.keys.write().await;
+        *cache = KeyCache {
+            keys: set.keys,
+            fetched: Some(Instant::now()),
+        };
+        find(&cache).ok_or_else(|| AuthError::InvalidToken("unknown signing key".to_string()))
+    }
+
+    /// Start a sign-in: the provider URL to send the browser to, returning
+    /// to `next` (a path on this server) afterwards.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AuthError::Provider`] if discovery fails.
+    pub async fn login_url(&self, next: &str) -> Result<String, AuthError> {
+        let metadata = self.metadata().await?;
+        let (state, nonce, verifier) = (random_token(), random_token(), random_token());
+        let challenge = URL_SAFE_NO_PAD.encode(ring::digest::digest(
+            &ring::digest::SHA256,
+            verifier.as_bytes(),
+        ));
+        let mut url = url::Url::parse(&metadata.authorization_endpoint)
+            .map_err(|e| AuthError::Provider(format!("authorization endpoint: {e}")))?;
+        url.query_pairs_mut()
+            .append_pair("response_type", "code")
+            .append_pair("client_id", &self.config.client_id)
+            .append_pair("redirect_uri", &self.config.redirect_url)
+            .append_pair("scope", &self.config.scopes.join(" "))
+            .append_pair("state", &state)
+            .append_pair("nonce", &nonce)
+            .append_pair("code_challenge", &challenge)
+            .append_pair("code_challenge_method", "S256");
+
+        let mut logins = self.logins.lock().await;
+        logins.retain(|_, login| login.started.elapsed() < LOGIN_TIMEOUT);
+        if logins.len() >= MAX_PENDING_LOGINS {
+            if let Some(oldest) = logins
+                .iter()
+                .min_by_key(|(_, login)| login.started)
+                .map(|(state, _)| state.clone())
+            {
+                logins.remove(&oldest);
+            }
+        }
+        logins.insert(
+            state,
+            PendingLogin {
+                nonce,
+                verifier,
+                next: safe_next(next).to_string(),
+                started: Instant::now(),
+            },
+        );
+        Ok(url.into())
+    }
+
+    /// Finish a sign-in: exchange `code` for an ID token and check it.
+    /// Returns the user, the ID token and the path to return to.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AuthError::UnknownLogin`] for an unknown `state`,
+    /// [`AuthError::Exchange`] if the provider refuses the code, or a token
+    /// error if the ID token does not check out.
+    pub async fn complete_login(
+        &self,
+        code: &str,
+        state: &str,
+    ) -> Result<(Principal, String, String), AuthError> {
+        let login = self
+            .logins
+            .lock()
+            .await
+            .remove(state)
+            .filter(|login| login.started.elapsed() < LOGIN_TIMEOUT)
+            .ok_or(AuthError::UnknownLogin)?;
+        let metadata = self.metadata().await?;
+        let mut form = vec![
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", self.config.redirect_url.as_str()),
+            ("client_id", self.config.client_id.as_str()),
+            ("code_verifier", login.verifier.as_str()),
+        ];
+        if let Some(secret) = &self.config.client_secret {
+            form.push(("client_secret", secret));
+        }
+        let response = self
+            .http
+            .post(&metadata.token_endpoint)
+            .form(&form)
+            .send()
+            .await
+            .map_err(|e| AuthError::Exchange(e.to_string()))?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(AuthError::Exchange(format!("{status}: {body}")));
+        }
+        let tokens: TokenResponse = response
+            .json()
+            .await
+            .map_err(|e| AuthError::Exchange(e.to_string()))?;
+        let principal = self.check(&tokens.id_token, Some(&login.nonce)).await?;
+        Ok((principal, tokens.id_token, login.next))
+    }
+
+    /// Check a bearer token (an ID token or JWT access token) and map its
+    /// claims to a role.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AuthError::InvalidToken`] or [`AuthError::Expired`] for
+    /// tokens that do not check out, and [`AuthError::NoRole`] when the
+    /// claims grant no role.
+    pub async fn validate(&self, token: &str) -> Result<Principal, AuthError> {
+        self.check(token, None).await
+    }
+
+    async fn check(&self, token: &str, nonce: Option<&str>) -> Result<Principal, AuthError> {
+        let invalid = |reason: &str| AuthError::InvalidToken(reason.to_string());
+        let mut parts = token.split('.');
+        let (Some(header), Some(payload), Some(signature), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return Err(invalid("not a JWT"));
+        };
+        let decode = |part: &str| {
+            URL_SAFE_NO_PAD
+                .decode(part)
+                .map_err(|_| invalid("bad base64"))
+        };
+        let header: JwtHeader =
+            serde_json::from_slice(&decode(header)?).map_err(|_| invalid("bad header"))?;
+        let signature = decode(signature)?;
+        let key = self.key(header.kid.as_deref()).await?;
+        let message = &token.as_bytes()[..token.len() - signature_len(token)];
+        verify_signature(&header.alg, &key, message, &signature)?;
+
+        let claims: serde_json::Value =
+            serde_json::from_slice(&decode(payload)?).map_err(|_| invalid("bad claims"))?;
+        self.check_claims(&claims, nonce, now())?;
+        let text = |name: &str| claims.get(name).and_then(|v| v.as_str()).map(String::from);
+        let subject = text("sub").ok_or_else(|| invalid("no subject"))?;
+        let role = self
+            .config
+            .role_for(&claims)
+            .ok_or_else(|| AuthError::NoRole(subject.clone()))?;
+        Ok(Principal {
+            name: text("name").or_else(|| text("preferred_username")),
+            email: text("email"),
+            subject,
+            role,
+            expires_at: claims.get("exp").and_then(|v| v.as_u64()).unwrap_or(0),
+        })
+    }
+
+    /// Check issuer, audience, lifetime and (for sign-ins) nonce.
+    fn check_claims(
+        &self,
+        claims: &serde_json::Value,
+        nonce: Option<&str>,
+        now: u64,
+    ) -> Result<(), AuthError> {
+        let invalid = |reason: &str| AuthError::InvalidToken(reason.to_string());
+        let issuer = claims.get("iss").and_then(|v| v.as_str()).unwrap_or("");
+        if issuer.trim_end_matches('/') != self.config.issuer {
+            return Err(invalid("wrong issuer"));
+        }
+        let audiences: Vec<&str> = match claims.get("aud") {
+            Some(serde_json::Value::String(aud)) => vec![aud.as_str()],
+            Some(serde_json::Value::Array(auds)) => {
+                auds.iter().filter_map(serde_json::Value::as_str).collect()
+            }
+            _ => Vec::new(),
+        };
+        let accepted = [
+            Some(self.config.client_id.as_str()),
+            self.config.audience.as_deref(),
+        ];
+        if !audiences.iter().any(|aud| accepted.contains(&Some(*aud))) {
+            return Err(invalid("wrong audience"));
+        }
+        let exp = claims
+            .get("exp")
+            .and_then(|v| v.as_u64())
+            .ok_or_else(|| invalid("no expiry"))?;
+        if exp + LEEWAY_SECS <= now {
+            return Err(AuthError::Expired);
+        }
+        if claims
+            .get("nbf")
+            .and_then(|v| v.as_u64())
+            .is_some_and(|nbf| nbf > now + LEEWAY_SECS)
+        {
+            return Err(invalid("not yet valid"));
+        }
+        if let Some(nonce) = nonce {
+            if claims.get("nonce").and_then(|v| v.as_str()) != Some(nonce) {
+                return Err(invalid("wrong nonce"));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Length of the signature part of a JWT, with its leading dot.
+fn signature_len(token: &str) -> usize {
+    token.rsplit('.').next().map_or(0, |s| s.len() + 1)
+}
+
+/// Check `signature` over `message` with `key`, for the RS256 and ES256
+/// algorithms only.
+fn verify_signature(
+    alg: &str,
+    key: &Jwk,
+    message: &[u8],
+    signature: &[u8],
+) -> Result<(), AuthError> {
+    let invalid = |reason: &str| AuthError::InvalidToken(reason.to_string());
+    let field = |value: &Option<String>| {
+        value
+            .as_deref()
+            .and_then(|v| URL_SAFE_NO_PAD.decode(v).ok())
+            .ok_or_else(|| invalid("incomplete key"))
+    };
+    let verified = match (alg, key.kty.as_str()) {
+        ("RS256", "RSA") => RsaPublicKeyComponents {
+            n: field(&key.n)?,
+            e: field(&key.e)?,
+        }
+        .verify(&RSA_PKCS1_2048_8192_SHA256, message, signature),
+        ("ES256", "EC") if key.crv.as_deref() == Some("P-256") => {
+            let mut point = vec![0x04];
+            point.extend(field(&key.x)?);
+            point.extend(field(&key.y)?);
+            UnparsedPublicKey::new(&ECDSA_P256_SHA256_FIXED, point).verify(message, signature)
+        }
+        _ => return Err(invalid("unsupported algorithm")),
+    };
+    verified.map_err(|_| invalid("bad signature"))
+}
+
+/// A 256-bit random value, base64url encoded.
+fn random_token() -> String {
+    URL_SAFE_NO_PAD.encode(rand::random::<[u8; 32]>())
+}
+
+/// Seconds since the Unix epoch.
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+/// `next` if it is a path on this server, else `/`, so sign-in cannot be
+/// used to redirect elsewhere.
+fn safe_next(next: &str) -> &str {
+    if next.starts_with('/') && !next.starts_with("//") && !next.contains('\\') {
+        next
+    } else {
+        "/"
+    }
+}
+
+/// The request's token: a bearer token, the session cookie or an
+/// `access_token` query parameter, in that order.
+fn request_token(request: &Request) -> Option<String> {
+    let headers = request.headers();
+    if let Some(token) = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+    {
+        return Some(token.trim().to_string());
+    }
+    if let Some(token) = session_cookie(headers) {
+        return Some(token);
+    }
+    url::form_urlencoded::parse(request.uri().query()?.as_bytes())
+        .find(|(key, _)| key == "access_token")
+        .map(|(_, token)| token.into_owned())
+}
+
+fn session_cookie(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(';'))
+        .filter_map(|pair| pair.trim().split_once('='))
+        .find(|(name, _)| *name == SESSION_COOKIE)
+        .map(|(_, value)| value.to_string())
+}
+
+/// Whether `method` on `path` changes scenes through REST, which viewers
+/// may not do. MCP and WebSocket writes are checked by their handlers.
+fn writes(method: &Method, path: &str) -> bool {
+    *method == Method::POST && (path == "/api/scene" || path.starts_with("/ag-ui/"))
+}
+
+/// Whether the request is a browser navigating to a page.
+fn navigates(request: &Request) -> bool {
+    request.method() == Method::GET
+        && request
+            .headers()
+            .get(header::ACCEPT)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|accept| accept.contains("text/html"))
+}
+
+/// Middleware admitting requests with a valid token and adding their
+/// [`Principal`] to the request extensions.
+pub async fn authenticate(
+    State(provider): State<Arc<OidcProvider>>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let path = request.uri().path().to_string();
+    if PUBLIC_PATHS.contains(&path.as_str()) {
+        return next.run(request).await;
+    }
+    let result = match request_token(&request) {
+        Some(token) => provider.validate(&token).await,
+        None => Err(AuthError::MissingToken),
+    };
+    match result {
+        Ok(principal) => {
+            if !principal.role.can_edit() && writes(request.method(), &path) {
+                return (StatusCode::FORBIDDEN, "Viewers cannot edit the canvas").into_response();
+            }
+            request.extensions_mut().insert(principal);
+            next.run(request).await
+        }
+        Err(error) if error.status() == StatusCode::UNAUTHORIZED && navigates(&request) => {
+            let target = request
+                .uri()
+                .path_and_query()
+                .map_or("/", axum::http::uri::PathAndQuery::as_str);
+            let next: String = url::form_urlencoded::byte_serialize(target.as_bytes()).collect();
+            Redirect::to(&format!("/auth/login?next={next}")).into_response()
+        }
+        Err(error) => {
+            tracing::debug!("Rejected {path}: {error}");
+            let mut response = (error.status(), error.to_string()).into_response();
+            if error.status() == StatusCode::UNAUTHORIZED {
+                response.headers_mut().insert(
+                    header::WWW_AUTHENTICATE,
+                    header::HeaderValue::from_static("Bearer realm=\"saorsa-canvas\""),
+                );
+            }
+            response
+        }
+    }
+}
+
+/// Query parameters of `/auth/login`.
+#[derive(Debug, Deserialize)]
+pub struct LoginQuery {
+    /// Path to return to after signing in.
+    #[serde(default)]
+    pub next: Option<String>,
+}
+
+/// Query parameters the provider sends to `/auth/callback`.
+#[derive(Debug, Deserialize)]
+pub struct CallbackQuery {
+    /// Authorization code.
+    #[serde(default)]
+    pub code: Option<String>,
+    /// The state sent with the sign-in.
+    #[serde(default)]
+    pub state: Option<String>,
+    /// Error code, if sign-in failed.
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+/// Routes for signing in and out, to merge into the app before the
+/// [`authenticate`] layer.
+///
+/// - `GET /auth/login?next=/path` - redirect to the provider
+/// - `GET /auth/callback` - finish sign-in and set the session cookie
+/// - `GET /auth/logout` - clear the session cookie
+/// - `GET /auth/me` - the signed-in [`Principal`]
+pub fn routes<S>(provider: Arc<OidcProvider>) -> Router<S> {
+    Router::new()
+        .route("/auth/login", get(login_handler))
+        .route("/auth/callback", get(callback_handler))
+        .route("/auth/logout", get(logout_handler))
+        .route("/auth/me", get(me_handler))
+        .with_state(provider)
+}
+
+async fn login_handler(
+    State(provider): State<Arc<OidcProvider>>,
+    Query(query): Query<LoginQuery>,
+) -> Response {
+    match provider
+        .login_url(query.next.as_deref().unwrap_or("/"))
+        .await
+    {
+        Ok(url) => Redirect::to(&url).into_response(),
+        Err(error) => {
+            tracing::error!("Sign-in unavailable: {error}");
+            (error.status(), error.to_string()).into_response()
+        }
+    }
+}
+
+async fn callback_handler(
+    State(provider): State<Arc<OidcProvider>>,
+    Query(query): Query<CallbackQuery>,
+) -> Response {
+    if let Some(error) = query.error {
+        return (StatusCode::UNAUTHORIZED, format!("Sign-in failed: {error}")).into_response();
+    }
+    let (Some(code), Some(state)) = (query.code, query.state) else {
+        return (StatusCode::BAD_REQUEST, "Missing code or state").into_response();
+    };
+    match provider.complete_login(&code, &state).await {
+        Ok((principal, token, next)) => {
+            tracing::info!(
+                "Signed in {} as {}",
+                principal.subject,
+                principal.role.name()
+            );
+            let max_age = principal.expires_at.saturating_sub(now());
+            let secure = if provider.config.redirect_url.starts_with("https://") {
+                "; Secure"
+            } else {
+                ""
+            };
+            let cookie = format!(
+                "{SESSION_COOKIE}={token}; Path=/; HttpOnly; SameSite=Lax; Max-Age={max_age}{secure}"
+            );
+            ([(header::SET_COOKIE, cookie)], Redirect::to(&next)).into_response()
+        }
+        Err(error) => {
+            tracing::warn!("Sign-in failed: {error}");
+            (error.status(), error.to_string()).into_response()
+        }
+    }
+}
+
+async fn logout_handler() -> Response {
+    let cookie = format!("{SESSION_COOKIE}=; Path=/; HttpOnly; SameSite=Lax; Max-Age=0");
+    ([(header::SET_COOKIE, cookie)], "Signed out").into_response()
+}
+
+async fn me_handler(principal: Option<Extension<Principal>>) -> Response {
+    match principal {
+        Some(Extension(principal)) => Json(principal).into_response(),
+        None => (StatusCode::UNAUTHORIZED, "Not signed in").into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use ring::rand::SystemRandom;
+    use ring::signature::{EcdsaKeyPair, KeyPair, ECDSA_P256_SHA256_FIXED_SIGNING};
+    use tower::ServiceExt;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    struct Issuer {
+        server: MockServer,
+        key: EcdsaKeyPair,
+    }
+
+    impl Issuer {
+        async fn start() -> Self {
+            let server = MockServer::start().await;
+            let rng = SystemRandom::new();
+            let pkcs8 =
+                EcdsaKeyPair::generate_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, &rng).expect("key");
+            let key =
+                EcdsaKeyPair::from_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, pkcs8.as_ref(), &rng)
+                    .expect("key");
+            let point = key.public_key().as_ref();
+            Mock::given(method("GET"))
+                .and(path("/.well-known/openid-configuration"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                    "issuer": server.uri(),
+                    "authorization_endpoint": format!("{}/authorize", server.uri()),
+                    "token_endpoint": format!("{}/token", server.uri()),
+                    "jwks_uri": format!("{}/jwks", server.uri()),
+                })))
+                .mount(&server)
+                .await;
+            Mock::given(method("GET"))
+                .and(path("/jwks"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                    "keys": [{
+                        "kty": "EC", "crv": "P-256", "kid": "k1", "use": "sig",
+                        "x": URL_SAFE_NO_PAD.encode(&point[1..33]),
+                        "y": URL_SAFE_NO_PAD.encode(&point[33..]),
+                    }]
+                })))
+                .mount(&server)
+                .await;
+            Self { server, key }
+        }
+
+        fn config(&self) -> OidcConfig {
+            OidcConfig::new(
+                self.server.uri(),
+                "canvas",
+                "http://localhost/auth/callback",
+            )
+        }
+
+        fn token(&self, claims: serde_json::Value) -> String {
+            let header = URL_SAFE_NO_PAD.encode(br#"{"alg":"ES256","kid":"k1"}"#);
+            let payload = URL_SAFE_NO_PAD.encode(claims.to_string());
+            let message = format!("{header}.{payload}");
+            let signature = self
+                .key
+                .sign(&SystemRandom::new(), message.as_bytes())
+                .expect("sign");
+            format!("{message}.{}", URL_SAFE_NO_PAD.encode(signature.as_ref()))
+        }
+
+        fn claims(&self, extra: serde_json::Value) -> serde_json::Value {
+            let mut claims = serde_json::json!({
+                "iss": self.server.uri(),
+                "aud": "canvas",
+                "sub": "user-1",
+                "exp": now() + 300,
+            });
+            if let (Some(claims), Some(extra)) = (claims.as_object_mut(), extra.as_object()) {
+                claims.extend(extra.clone());
+            }
+            claims
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tokens_are_checked_and_claims_mapped_to_roles() {
+        let issuer = Issuer::start().await;
+        let mut config = issuer.config();
+        config.role_claim = "realm_access.roles".to_string();
+        config.role_map = parse_role_map("canvas-editors=editor, staff=viewer").expect("roles");
+        config.default_role = None;
+        let provider = OidcProvider::new(config);
+
+        let editor = issuer.token(issuer.claims(serde_json::json!({
+            "name": "Ada",
+            "realm_access": { "roles": ["staff", "canvas-editors"] }
+        })));
+        let principal = provider.validate(&editor).await.expect("valid");
+        assert_eq!(principal.role, Role::Editor);
+        assert_eq!(principal.name.as_deref(), Some("Ada"));
+
+        let outsider = issuer.token(issuer.claims(serde_json::json!({})));
+        assert!(matches!(
+            provider.validate(&outsider).await,
+            Err(AuthError::NoRole(_))
+        ));
+        let expired = issuer.token(issuer.claims(serde_json::json!({ "exp": now() - 600 })));
+        assert!(matches!(
+            provider.validate(&expired).await,
+            Err(AuthError::Expired)
+        ));
+        let elsewhere = issuer.token(issuer.claims(serde_json::json!({ "aud": "other" })));
+        assert!(provider.validate(&elsewhere).await.is_err());
+        // A changed payload no longer matches the signature
+        let mut parts: Vec<_> = editor.split('.').map(String::from).collect();
+        parts[1] = URL_SAFE_NO_PAD.encode(issuer.claims(serde_json::json!({})).to_string());
+        assert!(matches!(
+            provider.validate(&parts.join(".")).await,
+            Err(AuthError::InvalidToken(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_sign_in_sets_a_cookie_the_middleware_accepts() {
+        let issuer = Issuer::start().await;
+        let provider = Arc::new(OidcProvider::new(issuer.config()));
+        let app: Router = Router::new()
+            .route(
+                "/api/scene",
+                get(|| async { "scene" }).post(|| async { "saved" }),
+            )
+            .merge(routes(provider.clone()))
+            .layer(axum::middleware::from_fn_with_state(
+                provider.clone(),
+                authenticate,
+            ));
+        let send = |request: axum::http::Request<Body>| app.clone().oneshot(request);
+
+        // Browsers are sent to sign in; API clients are refused
+        let page = axum::http::Request::get("/api/scene")
+            .header(header::ACCEPT, "text/html")
+            .body(Body::empty())
+            .expect("request");
+        let response = send(page).await.expect("response");
+        assert_eq!(response.status(), StatusCode::SEE_OTHER);
+        let api = axum::http::Request::get("/api/scene")
+            .body(Body::empty())
+            .expect("request");
+        assert_eq!(
+            send(api).await.expect("response").status(),
+            StatusCode::UNAUTHORIZED
+        );
+
+        let login = send(
+            axum::http::Request::get("/auth/login?next=/api/scene")
+                .body(Body::empty())
+                .expect("request"),
+        )
+        .await
+        .expect("response");
+        let location = url::Url::parse(
+            login.headers()[header::LOCATION]
+                .to_str()
+                .expect("location"),
+        )
+        .expect("url");
+        let param = |name: &str| {
+            location
+                .query_pairs()
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| value.into_owned())
+                .expect("param")
+        };
+        let id_token = issuer.token(issuer.claims(serde_json::json!({
+            "nonce": param("nonce")
+        })));
+        Mock::given(method("POST"))
+            .and(path("/token"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({ "id_token": id_token })),
+            )
+            .mount(&issuer.server)
+            .await;
+
+        let callback = send(
+            axum::http::Request::get(format!("/auth/callback?code=abc&state={}", param("state")))
+                .body(Body::empty())
+                .expect("request"),
+        )
+        .await
+        .expect("response");
+        assert_eq!(callback.headers()[header::LOCATION], "/api/scene");
+        let cookie = callback.headers()[header::SET_COOKIE]
+            .to_str()
+            .expect("cookie")
+            .split(';')
+            .next()
+            .expect("cookie")
+            .to_string();
+        let signed_in = axum::http::Request::get("/api/scene")
+            .header(header::COOKIE, cookie)
+            .body(Body::empty())
+            .expect("request");
+        assert_eq!(
+            send(signed_in).await.expect("response").status(),
+            StatusCode::OK
+        );
+
+        // The state is used up
+        let replay = send(
+            axum::http::Request::get(format!("/auth/callback?code=abc&state={}", param("state")))
+                .body(Body::empty())
+                .expect("request"),
+        )
+        .await
+        .expect("response");
+        assert_eq!(replay.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_viewers_cannot_post_scene_changes() {
+        let issuer = Issuer::start().await;
+        let mut config = issuer.config();
+        config.default_role = Some(Role::Viewer);
+        let provider = Arc::new(OidcProvider::new(config));
+        let app: Router = Router::new()
+            .route(
+                "/api/scene",
+                get(|| async { "scene" }).post(|| async { "saved" }),
+            )
+            .route("/health", get(|| async { "ok" }))
+            .layer(axum::middleware::from_fn_with_state(provider, authenticate));
+        let token = issuer.token(issuer.claims(serde_json::json!({})));
+        let request = |method: Method, uri: &str| {
+            axum::http::Request::builder()
+                .method(method)
+                .uri(uri)
+                .header(header::AUTHORIZATION, format!("Bearer {token}"))
+                .body(Body::empty())
+                .expect("request")
+        };
+
+        let read = app.clone().oneshot(request(Method::GET, "/api/scene"));
+        assert_eq!(read.await.expect("response").status(), StatusCode::OK);
+        let write = app.clone().oneshot(request(Method::POST, "/api/scene"));
+        assert_eq!(
+            write.await.expect("response").status(),
+            StatusCode::FORBIDDEN
+        );
+        let probe = axum::http::Request::get("/health")
+            .body(Body::empty())
+            .expect("request");
+        assert_eq!(
+            app.oneshot(probe).await.expect("response").status(),
+            StatusCode::OK
+        );
+        assert_eq!(safe_next("//evil.example"), "/");
+    }
+}
@@ -11,7 +11,9 @@ use std::sync::{
 use std::time::Instant;
 
 use crate::sync::{ServerMessage, SyncOrigin, SyncState};
-use canvas_core::SceneDocument;
+use async_trait::async_trait;
+use canvas_core::{ArchiveError, SceneDocument, SessionArchive};
+use canvas_mcp::ArchiveStore;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
@@ -323,6 +325,60 @@ impl CommunitasMcpClient {
         Self::require_success_flag(&response, "push_scene")
     }
 
+    /// Store a session archive in the user's Communitas space.
+    ///
+    /// Returns the reference Communitas keeps the archive under.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CommunitasError::Http`] if the network request fails.
+    /// Returns [`CommunitasError::Rpc`] if the tool call fails.
+    /// Returns [`CommunitasError::UnexpectedResponse`] if no reference is returned.
+    pub async fn put_archive(&self, archive: &SessionArchive) -> Result<String, CommunitasError> {
+        let response = self
+            .call_tool(
+                "canvas_archive_put",
+                Some(json!({
+                    "session_id": archive.session_id,
+                    "archive": archive
+                })),
+            )
+            .await?;
+
+        response
+            .get("reference")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .ok_or_else(|| {
+                CommunitasError::UnexpectedResponse(format!(
+                    "put_archive missing reference: {response}"
+                ))
+            })
+    }
+
+    /// Fetch a session archive from the user's Communitas space.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CommunitasError::Http`] if the network request fails.
+    /// Returns [`CommunitasError::Rpc`] if the tool call fails.
+    /// Returns [`CommunitasError::UnexpectedResponse`] if no archive is returned.
+    pub async fn get_archive(&self, reference: &str) -> Result<SessionArchive, CommunitasError> {
+        let response = self
+            .call_tool(
+                "canvas_archive_get",
+                Some(json!({ "reference": reference })),
+            )
+            .await?;
+
+        match response.get("archive") {
+            Some(archive) => Ok(serde_json::from_value(archive.clone())?),
+            None => Err(CommunitasError::UnexpectedResponse(
+                "response did not contain an archive".to_string(),
+            )),
+        }
+    }
+
     /// Check that a response contains `"success": true`, returning an error otherwise.
     fn require_success_flag(response: &Value, operation: &str) -> Result<(), CommunitasError> {
         if response.get("success").and_then(Value::as_bool) == Some(true) {
@@ -623,6 +679,30 @@ impl CommunitasMcpClient {
     }
 }
 
+#[async_trait]
+impl ArchiveStore for CommunitasMcpClient {
+    fn name(&self) -> &'static str {
+        "communitas"
+    }
+
+    async fn put(&self, archive: &SessionArchive) -> Result<String, ArchiveError> {
+        self.put_archive(archive)
+            .await
+            .map_err(|e| ArchiveError::Storage(e.to_string()))
+    }
+
+    async fn get(&self, reference: &str) -> Result<SessionArchive, ArchiveError> {
+        match self.get_archive(reference).await {
+            Ok(archive) => Ok(archive),
+            // Communitas reports unknown references as tool errors
+            Err(CommunitasError::Rpc { .. } | CommunitasError::UnexpectedResponse(_)) => {
+                Err(ArchiveError::NotFound(reference.to_string()))
+            }
+            Err(e) => Err(ArchiveError::Storage(e.to_string())),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize)]
 struct JsonRpcRequest<'a> {
     jsonrpc: &'a str,
@@ -916,7 +996,14 @@ pub fn spawn_scene_bridge(sync: SyncState, client: CommunitasMcpClient) -> Bridg
                         continue;
                     }
 
-                    if let ServerMessage::SceneUpdate { scene } = event.message {
+                    let scene = match event.message {
+                        ServerMessage::SceneUpdate { scene } => Some(scene),
+                        // Communitas stores whole documents, so push the current scene
+                        ServerMessage::ScenePatch { .. } => Some(sync.scene_document(&event.session_id)),
+                        _ => None,
+                    };
+
+                    if let Some(scene) = scene {
                         // Update state to reconnecting if we had failures
                         if consecutive_failures > 0 {
                             if let Ok(mut guard) = state_clone.write() {
@@ -1274,7 +1361,10 @@ pub fn spawn_full_bridge(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use canvas_core::{ElementDocument, ElementKind, Scene, Transform, ViewportDocument};
+    use canvas_core::{
+        Color, CoordinateSystem, Element, ElementDocument, ElementKind, Interactions, Scene, Style,
+        Theme, Transform, ViewportDocument,
+    };
     use tokio::time::{sleep, Duration};
     use wiremock::matchers::{body_json, body_string_contains, method, path};
     use wiremock::{Mock, MockServer, ResponseTemplate};
@@ -1424,12 +1514,28 @@ mod tests {
                 kind: ElementKind::Text {
                     content: "Hello".into(),
                     font_size: 18.0,
-                    color: "#000000".into(),
+                    color: Color::BLACK.into(),
                 },
                 transform: Transform::default(),
                 interactive: true,
                 selected: false,
+                visible: true,
+                opacity: 1.0,
+                screen_anchored: false,
+                sensitive: false,
+                style: Style::default(),
+                interactions: Interactions::default(),
+                created_by: None,
+                last_modified_by: None,
+                attachment: None,
+                depth: None,
             }],
+            spotlight: None,
+            anchors: Vec::new(),
+            theme: Theme::default(),
+            branding: None,
+            datasets: Vec::new(),
+            coordinates: CoordinateSystem::default(),
             timestamp: 42,
         }
     }
@@ -1531,6 +1637,60 @@ mod tests {
         assert_eq!(fetched.elements.len(), 1);
     }
 
+    #[tokio::test]
+    async fn archive_store_round_trips_through_communitas() {
+        let server = MockServer::start().await;
+        let mut scene = Scene::new(800.0, 600.0);
+        scene.add_element(Element::new(ElementKind::Text {
+            content: "Roadmap".into(),
+            font_size: 16.0,
+            color: Color::BLACK.into(),
+        }));
+        let archive = SessionArchive::pack("default", &scene, Vec::new(), 42);
+
+        Mock::given(method("POST"))
+            .and(path("/mcp"))
+            .and(body_string_contains("canvas_archive_put"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "result": { "reference": "communitas:archive/1" }
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/mcp"))
+            .and(body_string_contains("communitas:archive/1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "jsonrpc": "2.0",
+                "id": 2,
+                "result": { "archive": archive }
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/mcp"))
+            .and(body_string_contains("canvas_archive_get"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "jsonrpc": "2.0",
+                "id": 3,
+                "error": { "code": -32000, "message": "no such archive" }
+            })))
+            .mount(&server)
+            .await;
+
+        let client = client_with_mock(&server).await;
+        let store: &dyn ArchiveStore = &client;
+        let reference = store.put(&archive).await.expect("put");
+        assert_eq!(reference, "communitas:archive/1");
+        let fetched = store.get(&reference).await.expect("get");
+        assert_eq!(fetched, archive);
+        assert!(matches!(
+            store.get("communitas:archive/2").await,
+            Err(ArchiveError::NotFound(_))
+        ));
+    }
+
     #[tokio::test]
     async fn authenticate_with_token_sends_request() {
         let server = MockServer::start().await;
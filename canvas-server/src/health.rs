@@ -4,21 +4,39 @@
 //! - `/health/live` - Liveness probe (restart if fails)
 //! - `/health/ready` - Readiness probe (remove from LB if fails)
 //! - `/health` - Combined check for backward compatibility
+//!
+//! An unpromoted warm standby reports `503` with status `"standby"`, so it
+//! only receives traffic once it has taken over from the primary.
 
 use axum::{extract::State, http::StatusCode, Json};
+use canvas_core::Consistency;
 use serde::Serialize;
 
+use crate::sync::StandbyStatus;
 use crate::AppState;
 
 /// Health status response.
 #[derive(Debug, Serialize)]
 pub struct HealthStatus {
-    /// Overall status: "healthy" or "unhealthy"
+    /// Overall status: "healthy", "unhealthy" or "standby"
     pub status: &'static str,
     /// Server version
     pub version: &'static str,
     /// Individual component checks
     pub checks: HealthChecks,
+    /// "eventual" for a read replica, otherwise "strong"
+    pub consistency: Consistency,
+    /// Sync WebSocket URL of the primary, for a read replica
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub primary: Option<String>,
+    /// "primary", "replica" or "standby"
+    pub role: &'static str,
+    /// Stream progress, for a warm standby (kept after promotion)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub standby: Option<StandbyStatus>,
+    /// Where clients should reconnect if this primary fails
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub failover: Option<String>,
 }
 
 /// Individual health checks.
@@ -53,17 +71,36 @@ pub async fn readiness(State(state): State<AppState>) -> (StatusCode, Json<Healt
     let ws_ok = true;
 
     let all_ok = scene_ok && ws_ok;
+    let standing_by = state.sync.is_standby();
+    let role = if state.sync.is_replica() {
+        "replica"
+    } else if standing_by {
+        "standby"
+    } else {
+        "primary"
+    };
 
     let status = HealthStatus {
-        status: if all_ok { "healthy" } else { "unhealthy" },
+        status: if !all_ok {
+            "unhealthy"
+        } else if standing_by {
+            "standby"
+        } else {
+            "healthy"
+        },
         version: env!("CARGO_PKG_VERSION"),
         checks: HealthChecks {
             scene_store: scene_ok,
             websocket: ws_ok,
         },
+        consistency: state.sync.consistency(),
+        primary: state.sync.primary_url(),
+        role,
+        standby: state.sync.standby_status(),
+        failover: state.sync.failover_url(),
     };
 
-    let code = if all_ok {
+    let code = if all_ok && !standing_by {
         StatusCode::OK
     } else {
         StatusCode::SERVICE_UNAVAILABLE
@@ -85,6 +122,11 @@ mod tests {
                 scene_store: true,
                 websocket: true,
             },
+            consistency: Consistency::Strong,
+            primary: None,
+            role: "primary",
+            standby: None,
+            failover: None,
         };
 
         let json = serde_json::to_string(&status).expect("should serialize");
@@ -92,6 +134,11 @@ mod tests {
         assert!(json.contains("0.1.0"));
         assert!(json.contains("scene_store"));
         assert!(json.contains("websocket"));
+        assert!(json.contains(r#""consistency":"strong""#));
+        assert!(json.contains(r#""role":"primary""#));
+        assert!(!json.contains(r#""primary":"#));
+        assert!(!json.contains("standby"));
+        assert!(!json.contains("failover"));
     }
 
     #[test]
@@ -103,10 +150,41 @@ mod tests {
                 scene_store: false,
                 websocket: true,
             },
+            consistency: Consistency::Eventual,
+            primary: Some("ws://primary:9473/ws/sync".to_string()),
+            role: "replica",
+            standby: None,
+            failover: None,
         };
 
         let json = serde_json::to_string(&status).expect("should serialize");
         assert!(json.contains("unhealthy"));
         assert!(json.contains("false")); // scene_store: false
+        assert!(json.contains(r#""consistency":"eventual""#));
+    }
+
+    #[test]
+    fn test_health_status_standby() {
+        let sync = crate::SyncState::new();
+        sync.set_standby("ws://primary:9473/ws/standby".to_string());
+        let status = HealthStatus {
+            status: "standby",
+            version: "0.1.0",
+            checks: HealthChecks {
+                scene_store: true,
+                websocket: true,
+            },
+            consistency: sync.consistency(),
+            primary: None,
+            role: "standby",
+            standby: sync.standby_status(),
+            failover: None,
+        };
+
+        let json = serde_json::to_string(&status).expect("should serialize");
+        assert!(json.contains(r#""role":"standby""#));
+        assert!(json.contains(r#""primary_url":"ws://primary:9473/ws/standby""#));
+        assert!(json.contains(r#""synced":false"#));
+        assert!(!json.contains("lag_secs"));
     }
 }
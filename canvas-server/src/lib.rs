@@ -8,17 +8,27 @@ use std::sync::Arc;
 use canvas_mcp::CanvasMcpServer;
 
 pub mod agui;
+pub mod auth;
 pub mod communitas;
 pub mod health;
 pub mod metrics;
+pub mod ocr;
+pub mod quota;
+pub mod replica;
 pub mod routes;
+pub mod standby;
 pub mod sync;
 pub mod validation;
 
+pub use auth::{OidcConfig, OidcProvider, Principal, Role};
 pub use communitas::{
     spawn_network_retry_task, CommunitasMcpClient, NetworkRetryConfig, NetworkRetryHandle,
     RetryConfig,
 };
+pub use ocr::CommandOcrEngine;
+pub use quota::{QuotaConfig, Quotas};
+pub use replica::{spawn_replica, ReplicaConfig, ReplicaHandle};
+pub use standby::{spawn_standby, StandbyConfig, StandbyHandle, StreamConfig};
 pub use sync::SyncState;
 
 /// Shared application state.
@@ -3,18 +3,19 @@
 //! Local embedded server for the Saorsa Canvas PWA.
 //! Binds to localhost only for security.
 
+use std::collections::HashMap;
 use std::net::SocketAddr;
 use std::path::PathBuf;
 use std::sync::Arc;
 
 use axum::{
-    extract::{ws::WebSocketUpgrade, State},
+    extract::{ws::WebSocketUpgrade, ConnectInfo, Query, State},
     http::{header, HeaderValue, Method, StatusCode},
-    response::IntoResponse,
+    response::{IntoResponse, Response},
     routing::{get, post},
-    Json, Router,
+    Extension, Json, Router,
 };
-use canvas_core::SceneDocument;
+use canvas_core::{Encoding, OcrEngine};
 use canvas_mcp::{CanvasMcpServer, JsonRpcRequest, JsonRpcResponse};
 use tower_http::{
     cors::CorsLayer,
@@ -26,14 +27,21 @@ use tracing::Level;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
 
 use canvas_server::agui;
+use canvas_server::auth::{self, OidcConfig, OidcProvider, Principal};
 use canvas_server::communitas::{
     self, spawn_network_retry_task, ClientDescriptor, CommunitasMcpClient, NetworkRetryConfig,
     NetworkRetryHandle, RetryConfig,
 };
 use canvas_server::health;
 use canvas_server::metrics;
+use canvas_server::ocr::CommandOcrEngine;
+use canvas_server::quota::{self, Budget, QuotaConfig};
+use canvas_server::replica::{spawn_replica, ReplicaConfig};
 use canvas_server::routes;
-use canvas_server::sync::{self, current_timestamp, handle_sync_socket, SyncOrigin, SyncState};
+use canvas_server::standby::{self, spawn_standby, StandbyConfig, StreamConfig};
+use canvas_server::sync::{
+    handle_sync_socket_with_options, SyncError, SyncOrigin, SyncSocketOptions, SyncState,
+};
 use canvas_server::AppState;
 use metrics_exporter_prometheus::PrometheusHandle;
 
@@ -183,20 +191,57 @@ async fn main() -> anyhow::Result<()> {
         );
     }
 
+    // Per-identity message, element and export budgets
+    sync_state.quotas().set_config(QuotaConfig::from_env()?);
+
+    // Run as a read replica of CANVAS_REPLICA_OF (the primary's /ws/sync URL)
+    let _replica_handle = std::env::var("CANVAS_REPLICA_OF")
+        .ok()
+        .filter(|url| !url.is_empty())
+        .map(|url| spawn_replica(sync_state.clone(), ReplicaConfig::new(url)));
+
+    // Stream to a warm standby, or follow a primary as one (CANVAS_STANDBY_OF)
+    let standby_stream = StreamConfig::from_env()?;
+    let _standby_handle =
+        StandbyConfig::from_env()?.map(|config| spawn_standby(sync_state.clone(), config));
+    if let Ok(url) = std::env::var("CANVAS_FAILOVER_URL") {
+        if !url.is_empty() {
+            sync_state.set_failover_url(url);
+        }
+    }
+
     let (communitas_client, _network_retry_handle) = init_communitas_client(&sync_state).await;
 
     // Create MCP server with change notification callback
-    let scene_tx = sync_state.sender();
+    let change_sync = sync_state.clone();
     let mut mcp = CanvasMcpServer::new(sync_state.store());
-    mcp.set_on_change(move |session_id, scene| {
-        let document = SceneDocument::from_scene(session_id, scene, current_timestamp());
-        let event = sync::SyncEvent {
-            session_id: session_id.to_string(),
-            message: sync::ServerMessage::SceneUpdate { scene: document },
-            origin: sync::SyncOrigin::Local,
-        };
-        // Ignore send errors (no receivers is okay)
-        let _ = scene_tx.send(event);
+    mcp.set_on_change(move |session_id, _scene| {
+        change_sync.broadcast_scene_change(session_id, SyncOrigin::Local);
+    });
+    mcp.set_read_only(sync_state.is_replica());
+
+    // Keep canvas_archive archives in the user's Communitas space
+    if let Some(client) = &communitas_client {
+        mcp.set_archive_store(Arc::new(client.clone()));
+    }
+
+    // Read image text for canvas_ocr with CANVAS_OCR_COMMAND, e.g. tesseract
+    if let Some(engine) = std::env::var("CANVAS_OCR_COMMAND")
+        .ok()
+        .and_then(|command| CommandOcrEngine::from_command_line(&command))
+    {
+        tracing::info!("OCR engine: {}", engine.name());
+        mcp.set_ocr_engine(Arc::new(engine));
+    }
+
+    // Sign users in through an OIDC provider when CANVAS_OIDC_ISSUER is set
+    let oidc = OidcConfig::from_env(port)?.map(|config| {
+        tracing::info!(
+            "OIDC sign-in enabled (issuer: {}, client: {})",
+            config.issuer,
+            config.client_id
+        );
+        Arc::new(OidcProvider::new(config))
     });
 
     // Create AG-UI state
@@ -221,7 +266,7 @@ async fn main() -> anyhow::Result<()> {
         .with_state(metrics_handle);
 
     // Build the router
-    let app = Router::new()
+    let mut app = Router::new()
         // Metrics endpoint (separate state)
         .merge(metrics_router)
         // Health check endpoints (Kubernetes probes)
@@ -236,7 +281,24 @@ async fn main() -> anyhow::Result<()> {
             get(routes::get_scene_handler).post(routes::update_scene_handler),
         )
         .route("/api/scene/{session_id}", get(routes::get_session_scene))
+        .route(
+            "/api/scene/{session_id}/validate",
+            get(routes::validate_scene_handler),
+        )
+        .route(
+            "/api/scene/{session_id}/element/{file}",
+            get(routes::export_element_handler),
+        )
+        .route(
+            "/api/scene/{session_id}/export.svg",
+            get(routes::export_svg_handler),
+        )
+        .route(
+            "/api/scene/{session_id}/thumbnail.png",
+            get(routes::thumbnail_handler),
+        )
         .route("/api/export", post(routes::export_scene_handler))
+        .route("/api/types.d.ts", get(routes::types_handler))
         // AG-UI endpoints
         .nest("/ag-ui", agui_router)
         // Serve WASM package at /pkg
@@ -245,7 +307,19 @@ async fn main() -> anyhow::Result<()> {
         .route("/manifest.json", get(manifest_handler))
         .route("/sw.js", get(sw_handler))
         // Fallback to index.html for SPA
-        .fallback_service(web_service)
+        .fallback_service(web_service);
+    if let Some(config) = standby_stream {
+        app = app.merge(standby::routes(sync_state.clone(), config));
+    }
+    if let Some(provider) = oidc {
+        app =
+            app.merge(auth::routes(provider.clone()))
+                .layer(axum::middleware::from_fn_with_state(
+                    provider,
+                    auth::authenticate,
+                ));
+    }
+    let app = app
         // Request ID for distributed tracing correlation
         .layer(PropagateRequestIdLayer::x_request_id())
         .layer(SetRequestIdLayer::x_request_id(MakeRequestUuid))
@@ -267,7 +341,12 @@ async fn main() -> anyhow::Result<()> {
     tracing::info!("Saorsa Canvas server starting on http://{}", addr);
     tracing::info!("Open http://localhost:{} in your browser", port);
 
-    axum::serve(listener, app).await?;
+    // Peer addresses key the quotas of callers who are not signed in
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .await?;
 
     Ok(())
 }
@@ -297,34 +376,157 @@ async fn metrics_handler(State(handle): State<PrometheusHandle>) -> impl IntoRes
 }
 
 /// MCP JSON-RPC endpoint.
-#[tracing::instrument(name = "mcp_handler", skip(state, request), fields(method = %request.method))]
+#[tracing::instrument(name = "mcp_handler", skip(state, principal, connection, request), fields(method = %request.method))]
 async fn mcp_handler(
     State(state): State<AppState>,
+    principal: Option<Extension<Principal>>,
+    connection: Option<Extension<ConnectInfo<SocketAddr>>>,
     Json(request): Json<JsonRpcRequest>,
 ) -> Json<JsonRpcResponse> {
     tracing::debug!("Processing MCP request");
+    let principal = principal.map(|Extension(p)| p);
+    let writes = CanvasMcpServer::is_write(&request);
+    if writes && state.sync.is_standby() {
+        return Json(JsonRpcResponse::error(
+            request.id,
+            auth::FORBIDDEN_CODE,
+            SyncError::ReadOnly.to_string(),
+        ));
+    }
+    if writes && principal.as_ref().is_some_and(|p| !p.role.can_edit()) {
+        return Json(JsonRpcResponse::error(
+            request.id,
+            auth::FORBIDDEN_CODE,
+            "Viewers cannot edit the canvas",
+        ));
+    }
+
+    // Charge the caller's quota: the signed-in user, else the caller's IP.
+    // A client-named agent_id is not proof of who is calling.
+    let arguments = &request.params["arguments"];
+    let identity = principal.map_or_else(
+        || quota::anonymous_identity(connection.map(|Extension(ConnectInfo(addr))| addr)),
+        |p| p.subject,
+    );
+    let quotas = state.sync.quotas();
+    let exports = request.params["name"] == "canvas_export";
+    let admitted = quotas
+        .try_use(&identity, Budget::Messages, 1)
+        .and_then(|()| {
+            if exports {
+                quotas.try_use(&identity, Budget::Exports, 1)
+            } else {
+                Ok(())
+            }
+        })
+        .and_then(|()| {
+            if writes {
+                quotas.check(&identity, Budget::Elements)
+            } else {
+                Ok(())
+            }
+        });
+    if let Err(e) = admitted {
+        metrics::record_rate_limited("mcp");
+        return Json(JsonRpcResponse::error(
+            request.id,
+            quota::QUOTA_EXCEEDED_CODE,
+            e.to_string(),
+        ));
+    }
+    // Elements added are only known afterwards
+    let session_id = arguments["session_id"]
+        .as_str()
+        .unwrap_or("default")
+        .to_string();
+    let before = writes.then(|| state.sync.store().element_count(&session_id));
+
     let response = state.mcp.handle_request(request).await;
+    if let Some(before) = before {
+        let added = state
+            .sync
+            .store()
+            .element_count(&session_id)
+            .saturating_sub(before);
+        quotas.charge(
+            &identity,
+            Budget::Elements,
+            u32::try_from(added).unwrap_or(u32::MAX),
+        );
+    }
     Json(response)
 }
 
+/// Parse WebSocket connection options from the query string.
+///
+/// Supports `encoding` (`json` or `msgpack`) and `page_size` (elements per
+/// scene message for progressive loading). Connections of users signed in
+/// as viewers are read-only. Usage counts against the signed-in user's
+/// quota, else the connection's own.
+fn sync_socket_options(
+    params: &HashMap<String, String>,
+    principal: Option<&Principal>,
+) -> Result<SyncSocketOptions, String> {
+    let encoding = match params.get("encoding") {
+        Some(name) => {
+            Encoding::from_name(name).ok_or_else(|| format!("Unsupported encoding: {name}"))?
+        }
+        None => Encoding::Json,
+    };
+    let page_size = match params.get("page_size") {
+        Some(value) => match value.parse::<usize>() {
+            Ok(size) if size > 0 => Some(size),
+            _ => return Err(format!("Invalid page_size: {value}")),
+        },
+        None => None,
+    };
+    Ok(SyncSocketOptions {
+        encoding,
+        page_size,
+        read_only: principal.is_some_and(|p| !p.role.can_edit()),
+        identity: principal.map(|p| p.subject.clone()),
+    })
+}
+
 /// Legacy WebSocket handler (backwards compatible).
-#[tracing::instrument(name = "websocket_connect", skip(ws, state))]
+///
+/// Accepts the same query options as [`sync_websocket_handler`].
+#[tracing::instrument(name = "websocket_connect", skip(ws, state, principal))]
 async fn websocket_handler(
     ws: WebSocketUpgrade,
+    Query(params): Query<HashMap<String, String>>,
     State(state): State<AppState>,
-) -> impl IntoResponse {
+    principal: Option<Extension<Principal>>,
+) -> Response {
+    let options = match sync_socket_options(&params, principal.as_deref()) {
+        Ok(options) => options,
+        Err(message) => return (StatusCode::BAD_REQUEST, message).into_response(),
+    };
     tracing::info!("WebSocket connection upgrade requested");
-    ws.on_upgrade(move |socket| handle_sync_socket(socket, state.sync))
+    ws.on_upgrade(move |socket| handle_sync_socket_with_options(socket, state.sync, options))
 }
 
 /// Sync WebSocket handler for real-time scene synchronization.
-#[tracing::instrument(name = "sync_websocket_connect", skip(ws, state))]
+///
+/// Pass `?encoding=msgpack` to receive `MessagePack` binary frames instead of
+/// JSON text frames, and `?page_size=N` to receive large scenes progressively
+/// in pages of at most `N` elements.
+#[tracing::instrument(name = "sync_websocket_connect", skip(ws, state, principal))]
 async fn sync_websocket_handler(
     ws: WebSocketUpgrade,
+    Query(params): Query<HashMap<String, String>>,
     State(state): State<AppState>,
-) -> impl IntoResponse {
-    tracing::info!("Sync WebSocket connection upgrade requested");
-    ws.on_upgrade(move |socket| handle_sync_socket(socket, state.sync))
+    principal: Option<Extension<Principal>>,
+) -> Response {
+    let options = match sync_socket_options(&params, principal.as_deref()) {
+        Ok(options) => options,
+        Err(message) => return (StatusCode::BAD_REQUEST, message).into_response(),
+    };
+    tracing::info!(
+        "Sync WebSocket connection upgrade requested ({})",
+        options.encoding.name()
+    );
+    ws.on_upgrade(move |socket| handle_sync_socket_with_options(socket, state.sync, options))
 }
 
 /// Initialize Communitas MCP client for upstream scene synchronization.
@@ -0,0 +1,108 @@
+//! # OCR Engines
+//!
+//! Server-side engines for the `canvas_ocr` tool. [`CommandOcrEngine`] runs
+//! an external recognizer such as Tesseract, so the server needs no OCR
+//! libraries of its own:
+//!
+//! ```text
+//! CANVAS_OCR_COMMAND="tesseract stdin stdout"
+//!   image bytes ──stdin──► tesseract ──stdout──► OcrText
+//! ```
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use canvas_core::{OcrEngine, OcrError, OcrText, MAX_OCR_TEXT_LEN};
+
+/// Reads image text with an external command.
+///
+/// The encoded image is written to the command's standard input and its
+/// standard output is taken as the text.
+#[derive(Debug, Clone)]
+pub struct CommandOcrEngine {
+    program: String,
+    args: Vec<String>,
+}
+
+impl CommandOcrEngine {
+    /// Parse a whitespace-separated command line, e.g. `tesseract stdin stdout`.
+    ///
+    /// Returns `None` if the command line is empty.
+    #[must_use]
+    pub fn from_command_line(command_line: &str) -> Option<Self> {
+        let mut words = command_line.split_whitespace().map(str::to_string);
+        let program = words.next()?;
+        Some(Self {
+            program,
+            args: words.collect(),
+        })
+    }
+}
+
+impl OcrEngine for CommandOcrEngine {
+    fn name(&self) -> &str {
+        std::path::Path::new(&self.program)
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or(&self.program)
+    }
+
+    fn recognize(&self, image: &[u8], _mime_type: &str) -> Result<OcrText, OcrError> {
+        let mut child = Command::new(&self.program)
+            .args(&self.args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| OcrError::Engine(format!("cannot run {}: {e}", self.program)))?;
+
+        // Write from another thread so a command that streams its output
+        // before reading all input cannot deadlock on a full pipe
+        let mut stdin = child
+            .stdin
+            .take()
+            .ok_or_else(|| OcrError::Engine("no stdin".to_string()))?;
+        let image = image.to_vec();
+        let writer = std::thread::spawn(move || stdin.write_all(&image));
+
+        let output = child
+            .wait_with_output()
+            .map_err(|e| OcrError::Engine(e.to_string()))?;
+        // A command may exit without reading everything; its status decides
+        let _ = writer.join();
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(OcrError::Engine(format!(
+                "{} exited with {}: {}",
+                self.program,
+                output.status,
+                stderr.trim()
+            )));
+        }
+
+        let text: String = String::from_utf8_lossy(&output.stdout)
+            .trim()
+            .chars()
+            .take(MAX_OCR_TEXT_LEN)
+            .collect();
+        Ok(OcrText::new(text, self.name()))
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_command_engine_reads_stdout() {
+        let engine = CommandOcrEngine::from_command_line("cat").expect("command");
+        let text = engine
+            .recognize(b"  Error 404\n", "image/png")
+            .expect("recognize");
+        assert_eq!(text, OcrText::new("Error 404", "cat"));
+
+        let failing = CommandOcrEngine::from_command_line("false").expect("command");
+        assert!(failing.recognize(b"", "image/png").is_err());
+        assert!(CommandOcrEngine::from_command_line("  ").is_none());
+    }
+}
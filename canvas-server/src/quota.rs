@@ -0,0 +1,518 @@
+//! # Quotas
+//!
+//! Per-identity budgets that keep one chatty agent from starving everyone
+//! else on a shared server:
+//!
+//! | Budget | Counts | Default |
+//! |--------|--------|---------|
+//! | messages | WebSocket messages and MCP requests | 10/s, bursts of 100 |
+//! | elements | elements added, through sync or MCP | unlimited |
+//! | exports | export jobs (REST and `canvas_export`) | unlimited |
+//!
+//! An identity is the signed-in user's subject, else the caller's IP
+//! address; an `agent_id` a client names is not trusted. Budgets refill
+//! continuously (token buckets), and are shared by all of an identity's
+//! connections. Clients see what is left in `quota_status` messages.
+//!
+//! Configured from the environment; see [`QuotaConfig::from_env`].
+
+use std::collections::HashMap;
+use std::fmt;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use canvas_core::{QuotaBudget, QuotaStatus};
+use thiserror::Error;
+
+use crate::sync::RateLimiter;
+
+/// JSON-RPC error code for MCP requests over quota.
+pub const QUOTA_EXCEEDED_CODE: i32 = -32005;
+
+/// Identity of callers that are not signed in, suffixed with their IP
+/// address when it is known.
+pub const ANONYMOUS: &str = "anonymous";
+
+/// Default sustained message rate (messages per second).
+const DEFAULT_MESSAGES_PER_SEC: u32 = 10;
+/// Default message burst.
+const DEFAULT_MESSAGE_BURST: u32 = 100;
+/// Identities tracked before idle, then least recently seen, ones are
+/// forgotten.
+const MAX_TRACKED_IDENTITIES: usize = 10_000;
+/// Idle time after which an identity's budgets are full again anyway.
+const IDLE_TIMEOUT: Duration = Duration::from_secs(3600);
+
+const MINUTE: Duration = Duration::from_secs(60);
+const HOUR: Duration = Duration::from_secs(3600);
+
+/// Identity of a caller who is not signed in, from their peer address.
+///
+/// Keyed on the IP alone, so every connection from one host shares a
+/// budget instead of each new port starting afresh.
+pub fn anonymous_identity(peer: Option<SocketAddr>) -> String {
+    peer.map_or_else(
+        || ANONYMOUS.to_string(),
+        |addr| format!("{ANONYMOUS}:{}", addr.ip()),
+    )
+}
+
+/// What a quota limits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Budget {
+    /// WebSocket messages and MCP requests.
+    Messages,
+    /// Elements added.
+    Elements,
+    /// Export jobs.
+    Exports,
+}
+
+impl fmt::Display for Budget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Messages => "message",
+            Self::Elements => "element",
+            Self::Exports => "export",
+        })
+    }
+}
+
+/// Errors from configuring or checking quotas.
+#[derive(Debug, Error)]
+pub enum QuotaError {
+    /// The quota settings cannot be read.
+    #[error("invalid quota configuration: {0}")]
+    Config(String),
+
+    /// A budget is used up.
+    #[error("{budget} quota exceeded, retry after {}ms", retry_after.as_millis())]
+    Exceeded {
+        /// The budget that is used up.
+        budget: Budget,
+        /// When enough of it is available again.
+        retry_after: Duration,
+    },
+}
+
+impl QuotaError {
+    /// Seconds to wait before retrying, rounded up, for `Retry-After`.
+    pub fn retry_after_secs(&self) -> u64 {
+        match self {
+            Self::Exceeded { retry_after, .. } => retry_after.as_secs_f64().ceil() as u64,
+            Self::Config(_) => 0,
+        }
+    }
+}
+
+/// Budgets of one identity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuotaLimits {
+    /// Sustained messages per second.
+    pub messages_per_sec: u32,
+    /// Messages that may be sent at once.
+    pub message_burst: u32,
+    /// Elements added per minute, or `None` for no limit.
+    pub elements_per_min: Option<u32>,
+    /// Export jobs per hour, or `None` for no limit.
+    pub exports_per_hour: Option<u32>,
+}
+
+impl Default for QuotaLimits {
+    fn default() -> Self {
+        Self {
+            messages_per_sec: DEFAULT_MESSAGES_PER_SEC,
+            message_burst: DEFAULT_MESSAGE_BURST,
+            elements_per_min: None,
+            exports_per_hour: None,
+        }
+    }
+}
+
+impl QuotaLimits {
+    /// These limits with `key:value` pairs separated by commas laid over
+    /// them, e.g. `messages:5,elements:200,exports:none`.
+    fn with_overrides(mut self, text: &str) -> Result<Self, QuotaError> {
+        for pair in text.split(',').filter(|pair| !pair.trim().is_empty()) {
+            let (key, value) = pair
+                .split_once(':')
+                .ok_or_else(|| QuotaError::Config(format!("expected key:value, got {pair}")))?;
+            let value = value.trim();
+            let limit = || {
+                if value == "none" {
+                    Ok(None)
+                } else {
+                    value
+                        .parse()
+                        .map(Some)
+                        .map_err(|_| QuotaError::Config(format!("invalid {key}: {value}")))
+                }
+            };
+            let rate = || match limit()? {
+                Some(rate) if rate > 0 => Ok(rate),
+                _ => Err(QuotaError::Config(format!("{key} must be positive"))),
+            };
+            match key.trim() {
+                "messages" => self.messages_per_sec = rate()?,
+                "burst" => self.message_burst = rate()?,
+                "elements" => self.elements_per_min = limit()?,
+                "exports" => self.exports_per_hour = limit()?,
+                other => return Err(QuotaError::Config(format!("unknown quota: {other}"))),
+            }
+        }
+        Ok(self)
+    }
+}
+
+/// Budgets of every identity.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct QuotaConfig {
+    /// Budgets of identities without their own.
+    pub default: QuotaLimits,
+    /// Budgets of particular identities.
+    pub identities: HashMap<String, QuotaLimits>,
+}
+
+impl QuotaConfig {
+    /// Read budgets from the environment.
+    ///
+    /// - `WS_RATE_LIMIT_SUSTAINED`, `WS_RATE_LIMIT_BURST`: messages per
+    ///   second and burst (default 10 and 100)
+    /// - `CANVAS_QUOTA_ELEMENTS_PER_MIN`: elements added per minute
+    /// - `CANVAS_QUOTA_EXPORTS_PER_HOUR`: export jobs per hour
+    /// - `CANVAS_QUOTAS`: budgets of particular identities, e.g.
+    ///   `report-bot=messages:2,exports:5;alice@example.com=elements:none`;
+    ///   unnamed budgets are the defaults
+    ///
+    /// # Errors
+    ///
+    /// Returns [`QuotaError::Config`] if a value cannot be read.
+    pub fn from_env() -> Result<Self, QuotaError> {
+        let var = |name: &str| {
+            std::env::var(name)
+                .ok()
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+        };
+        let mut defaults = String::new();
+        for (name, key) in [
+            ("WS_RATE_LIMIT_SUSTAINED", "messages"),
+            ("WS_RATE_LIMIT_BURST", "burst"),
+            ("CANVAS_QUOTA_ELEMENTS_PER_MIN", "elements"),
+            ("CANVAS_QUOTA_EXPORTS_PER_HOUR", "exports"),
+        ] {
+            if let Some(value) = var(name) {
+                defaults.push_str(&format!("{key}:{value},"));
+            }
+        }
+        let mut config = Self {
+            default: QuotaLimits::default().with_overrides(&defaults)?,
+            identities: HashMap::new(),
+        };
+        if let Some(quotas) = var("CANVAS_QUOTAS") {
+            config.parse_identities(&quotas)?;
+        }
+        Ok(config)
+    }
+
+    /// Add `identity=key:value,...` entries separated by semicolons.
+    fn parse_identities(&mut self, text: &str) -> Result<(), QuotaError> {
+        for entry in text.split(';').filter(|entry| !entry.trim().is_empty()) {
+            let (identity, limits) = entry
+                .split_once('=')
+                .ok_or_else(|| QuotaError::Config(format!("expected identity=..., got {entry}")))?;
+            let limits = self.default.with_overrides(limits)?;
+            self.identities.insert(identity.trim().to_string(), limits);
+        }
+        Ok(())
+    }
+
+    /// Budgets of `identity`.
+    pub fn limits(&self, identity: &str) -> QuotaLimits {
+        self.identities
+            .get(identity)
+            .copied()
+            .unwrap_or(self.default)
+    }
+}
+
+/// Usage of one identity.
+struct Usage {
+    limits: QuotaLimits,
+    messages: RateLimiter,
+    elements: Option<RateLimiter>,
+    exports: Option<RateLimiter>,
+    last_seen: Instant,
+}
+
+impl Usage {
+    fn new(limits: QuotaLimits) -> Self {
+        Self {
+            limits,
+            messages: RateLimiter::new(limits.message_burst, limits.messages_per_sec),
+            elements: limits
+                .elements_per_min
+                .map(|limit| RateLimiter::per_window(limit, MINUTE)),
+            exports: limits
+                .exports_per_hour
+                .map(|limit| RateLimiter::per_window(limit, HOUR)),
+            last_seen: Instant::now(),
+        }
+    }
+
+    fn limiter(&mut self, budget: Budget) -> Option<&mut RateLimiter> {
+        match budget {
+            Budget::Messages => Some(&mut self.messages),
+            Budget::Elements => self.elements.as_mut(),
+            Budget::Exports => self.exports.as_mut(),
+        }
+    }
+}
+
+#[derive(Default)]
+struct QuotaState {
+    config: QuotaConfig,
+    usage: HashMap<String, Usage>,
+}
+
+/// Budgets and usage of every identity, shared by the sync, MCP and
+/// export handlers.
+#[derive(Clone, Default)]
+pub struct Quotas {
+    inner: Arc<Mutex<QuotaState>>,
+}
+
+impl fmt::Debug for Quotas {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Quotas").finish_non_exhaustive()
+    }
+}
+
+impl Quotas {
+    /// Create quotas with the given budgets.
+    pub fn new(config: QuotaConfig) -> Self {
+        let quotas = Self::default();
+        quotas.set_config(config);
+        quotas
+    }
+
+    /// Replace the budgets, starting every identity afresh.
+    pub fn set_config(&self, config: QuotaConfig) {
+        let mut state = self.lock();
+        state.config = config;
+        state.usage.clear();
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, QuotaState> {
+        self.inner
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    /// Run `f` on the usage of `identity`.
+    fn with_usage<T>(&self, identity: &str, f: impl FnOnce(&mut Usage) -> T) -> T {
+        let mut state = self.lock();
+        if state.usage.len() >= MAX_TRACKED_IDENTITIES && !state.usage.contains_key(identity) {
+            state
+                .usage
+                .retain(|_, usage| usage.last_seen.elapsed() < IDLE_TIMEOUT);
+            if state.usage.len() >= MAX_TRACKED_IDENTITIES {
+                let stalest = state
+                    .usage
+                    .iter()
+                    .min_by_key(|(_, usage)| usage.last_seen)
+                    .map(|(identity, _)| identity.clone());
+                if let Some(stalest) = stalest {
+                    state.usage.remove(&stalest);
+                }
+            }
+        }
+        let limits = state.config.limits(identity);
+        let usage = state
+            .usage
+            .entry(identity.to_string())
+            .or_insert_with(|| Usage::new(limits));
+        usage.last_seen = Instant::now();
+        f(usage)
+    }
+
+    /// Use `count` units of `budget`, or none if fewer are left.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`QuotaError::Exceeded`] if the budget cannot cover `count`.
+    pub fn try_use(&self, identity: &str, budget: Budget, count: u32) -> Result<(), QuotaError> {
+        self.with_usage(identity, |usage| {
+            let Some(limiter) = usage.limiter(budget) else {
+                return Ok(());
+            };
+            if limiter.try_consume_n(count) {
+                Ok(())
+            } else {
+                let retry_after = limiter.time_until(count).unwrap_or_default();
+                Err(QuotaError::Exceeded {
+                    budget,
+                    retry_after,
+                })
+            }
+        })
+    }
+
+    /// Check that at least one unit of `budget` is left, without using it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`QuotaError::Exceeded`] if the budget is used up.
+    pub fn check(&self, identity: &str, budget: Budget) -> Result<(), QuotaError> {
+        self.with_usage(identity, |usage| {
+            let Some(limiter) = usage.limiter(budget) else {
+                return Ok(());
+            };
+            if limiter.available() > 0 {
+                return Ok(());
+            }
+            Err(QuotaError::Exceeded {
+                budget,
+                retry_after: limiter.time_until(1).unwrap_or_default(),
+            })
+        })
+    }
+
+    /// Charge `count` units of `budget` used already, even past the limit.
+    pub fn charge(&self, identity: &str, budget: Budget, count: u32) {
+        if count == 0 {
+            return;
+        }
+        self.with_usage(identity, |usage| {
+            if let Some(limiter) = usage.limiter(budget) {
+                limiter.charge(count);
+            }
+        });
+    }
+
+    /// What is left of `identity`'s budgets.
+    pub fn status(&self, identity: &str) -> QuotaStatus {
+        self.with_usage(identity, |usage| {
+            let limits = usage.limits;
+            let budget = |limiter: &mut RateLimiter, limit: u32, window: Duration| QuotaBudget {
+                limit,
+                window_secs: u32::try_from(window.as_secs()).unwrap_or(u32::MAX),
+                remaining: limiter.available(),
+                reset_ms: u64::try_from(limiter.time_until_full().as_millis()).unwrap_or(u64::MAX),
+            };
+            QuotaStatus {
+                identity: identity.to_string(),
+                messages: budget(
+                    &mut usage.messages,
+                    limits.messages_per_sec,
+                    Duration::from_secs(1),
+                ),
+                elements: usage
+                    .elements
+                    .as_mut()
+                    .zip(limits.elements_per_min)
+                    .map(|(limiter, limit)| budget(limiter, limit, MINUTE)),
+                exports: usage
+                    .exports
+                    .as_mut()
+                    .zip(limits.exports_per_hour)
+                    .map(|(limiter, limit)| budget(limiter, limit, HOUR)),
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_identities_override_the_default_budgets() {
+        let mut config = QuotaConfig {
+            default: QuotaLimits::default()
+                .with_overrides("elements:10")
+                .expect("defaults"),
+            identities: HashMap::new(),
+        };
+        config
+            .parse_identities("report-bot=messages:2,burst:2,exports:1; alice=elements:none")
+            .expect("identities");
+        let bot = config.limits("report-bot");
+        assert_eq!(bot.messages_per_sec, 2);
+        assert_eq!(bot.elements_per_min, Some(10));
+        assert_eq!(bot.exports_per_hour, Some(1));
+        assert_eq!(config.limits("alice").elements_per_min, None);
+        assert_eq!(
+            config.limits("someone").message_burst,
+            DEFAULT_MESSAGE_BURST
+        );
+        assert!(config.parse_identities("bot=messages:0").is_err());
+        assert!(config.parse_identities("bot=speed:3").is_err());
+    }
+
+    #[test]
+    fn test_budgets_are_shared_per_identity() {
+        let mut config = QuotaConfig::default();
+        config.default.elements_per_min = Some(5);
+        config.default.exports_per_hour = Some(1);
+        let quotas = Quotas::new(config);
+
+        assert!(quotas.try_use("agent-a", Budget::Elements, 4).is_ok());
+        let Err(QuotaError::Exceeded {
+            budget,
+            retry_after,
+        }) = quotas.try_use("agent-a", Budget::Elements, 2)
+        else {
+            panic!("over the element budget");
+        };
+        assert_eq!(budget, Budget::Elements);
+        assert!(retry_after > Duration::ZERO);
+        // Other identities have their own budgets
+        assert!(quotas.try_use("agent-b", Budget::Elements, 5).is_ok());
+
+        // Usage only known afterwards can overdraw the budget
+        quotas.charge("agent-a", Budget::Elements, 3);
+        assert!(quotas.check("agent-a", Budget::Elements).is_err());
+
+        assert!(quotas.try_use("agent-a", Budget::Exports, 1).is_ok());
+        assert!(quotas.try_use("agent-a", Budget::Exports, 1).is_err());
+
+        let status = quotas.status("agent-a");
+        assert_eq!(status.messages.remaining, DEFAULT_MESSAGE_BURST);
+        assert_eq!(status.elements.map(|b| b.remaining), Some(0));
+        let exports = status.exports.expect("export budget");
+        assert_eq!((exports.limit, exports.window_secs), (1, 3600));
+        assert!(exports.reset_ms > 0);
+    }
+
+    #[test]
+    fn test_connections_from_one_ip_share_a_budget() {
+        let mut config = QuotaConfig::default();
+        config.default.exports_per_hour = Some(1);
+        let quotas = Quotas::new(config);
+        let first = anonymous_identity(Some(SocketAddr::from(([10, 0, 0, 7], 50001))));
+        let second = anonymous_identity(Some(SocketAddr::from(([10, 0, 0, 7], 50002))));
+        assert_eq!(first, second);
+
+        assert!(quotas.try_use(&first, Budget::Exports, 1).is_ok());
+        assert!(quotas.try_use(&second, Budget::Exports, 1).is_err());
+        let other = anonymous_identity(Some(SocketAddr::from(([10, 0, 0, 8], 50001))));
+        assert!(quotas.try_use(&other, Budget::Exports, 1).is_ok());
+    }
+
+    #[test]
+    fn test_tracked_identities_are_capped() {
+        let quotas = Quotas::default();
+        for i in 0..=MAX_TRACKED_IDENTITIES {
+            quotas.status(&format!("agent-{i}"));
+        }
+        let state = quotas.lock();
+        assert_eq!(state.usage.len(), MAX_TRACKED_IDENTITIES);
+        // The least recently seen identity makes room for the newest
+        assert!(!state.usage.contains_key("agent-0"));
+        assert!(state
+            .usage
+            .contains_key(&format!("agent-{MAX_TRACKED_IDENTITIES}")));
+    }
+}
@@ -0,0 +1,204 @@
+//! # Read Replicas
+//!
+//! A read replica mirrors sessions from a primary server and serves them to
+//! read-only WebSocket and REST clients, so broadcast-style sessions (town
+//! halls) can fan out beyond one box.
+//!
+//! ```text
+//!  writers ──► primary ──/ws/sync──► replica ──► viewers
+//!                 │
+//!                 └──────/ws/sync──► replica ──► viewers
+//! ```
+//!
+//! Each session is mirrored over its own [`CanvasClient`] connection, opened
+//! the first time a viewer touches the session on the replica. Scene changes
+//! and presenter views are applied locally as [`SyncOrigin::Remote`] events;
+//! writes are rejected and must go to the primary.
+//!
+//! Replicas advertise `"consistency": "eventual"`: a viewer may briefly see
+//! a scene the primary has already moved past.
+//!
+//! [`SyncOrigin::Remote`]: crate::sync::SyncOrigin::Remote
+
+use std::collections::HashSet;
+
+use canvas_client::{
+    CanvasClient, ClientConfig, ClientEvent, ClientMessage, ReconnectPolicy, ServerMessage,
+};
+use tokio::sync::{broadcast, mpsc};
+use tokio::task::{JoinHandle, JoinSet};
+use tracing::warn;
+
+use crate::sync::SyncState;
+
+/// Configuration for a read replica.
+#[derive(Debug, Clone)]
+pub struct ReplicaConfig {
+    /// Sync WebSocket URL of the primary, e.g. `ws://primary:9473/ws/sync`.
+    pub primary_url: String,
+    /// Reconnect schedule for connections to the primary.
+    pub reconnect: ReconnectPolicy,
+}
+
+impl ReplicaConfig {
+    /// Create a configuration mirroring `primary_url`.
+    #[must_use]
+    pub fn new(primary_url: impl Into<String>) -> Self {
+        Self {
+            primary_url: primary_url.into(),
+            reconnect: ReconnectPolicy::default(),
+        }
+    }
+
+    /// Set the reconnect schedule.
+    #[must_use]
+    pub fn with_reconnect(mut self, reconnect: ReconnectPolicy) -> Self {
+        self.reconnect = reconnect;
+        self
+    }
+}
+
+/// Handle to a running replica.
+pub struct ReplicaHandle {
+    task: JoinHandle<()>,
+}
+
+impl ReplicaHandle {
+    /// Stop mirroring every session.
+    ///
+    /// The server stays read-only.
+    pub fn abort(self) {
+        self.task.abort();
+    }
+}
+
+/// Turn `sync` into a read replica of the configured primary.
+///
+/// From now on local writes are rejected, and each session accessed through
+/// [`SyncState::record_access`] is mirrored from the primary.
+pub fn spawn_replica(sync: SyncState, config: ReplicaConfig) -> ReplicaHandle {
+    let (follow_tx, mut follow_rx) = mpsc::unbounded_channel();
+    sync.set_replica(config.primary_url.clone(), follow_tx);
+    tracing::info!("Read replica of {}", config.primary_url);
+
+    let task = tokio::spawn(async move {
+        // Dropping the set (when the task is aborted) stops every mirror
+        let mut mirrors = JoinSet::new();
+        let mut followed = HashSet::new();
+        loop {
+            tokio::select! {
+                Some(session_id) = follow_rx.recv() => {
+                    if followed.insert(session_id.clone()) {
+                        mirrors.spawn(mirror_session(sync.clone(), config.clone(), session_id));
+                    }
+                }
+                // A mirror that gave up is restarted on the next access
+                Some(Ok(session_id)) = mirrors.join_next() => {
+                    followed.remove(&session_id);
+                }
+                else => break,
+            }
+        }
+    });
+
+    ReplicaHandle { task }
+}
+
+/// Mirror one session until the connection to the primary gives up.
+///
+/// Returns the session ID so the caller can forget it.
+async fn mirror_session(sync: SyncState, config: ReplicaConfig, session_id: String) -> String {
+    let client_config = ClientConfig::new(config.primary_url.as_str())
+        .with_session(session_id.as_str())
+        .with_reconnect(config.reconnect);
+
+    let mut attempt = 0;
+    let client = loop {
+        match CanvasClient::connect(client_config.clone()).await {
+            Ok(client) => break client,
+            Err(e) if config.reconnect.allows(attempt) => {
+                warn!(
+                    "Replica of session {} cannot reach primary: {}",
+                    session_id, e
+                );
+                tokio::time::sleep(config.reconnect.delay(attempt)).await;
+                attempt += 1;
+            }
+            Err(e) => {
+                warn!("Replica of session {} gave up: {}", session_id, e);
+                return session_id;
+            }
+        }
+    };
+    tracing::info!("Mirroring session {} from primary", session_id);
+
+    let mut events = client.events();
+    // The scene may have arrived before `events` subscribed; ask again
+    if let Err(e) = client.send(ClientMessage::GetScene) {
+        warn!(
+            "Replica of session {} cannot request the scene: {}",
+            session_id, e
+        );
+    }
+    // Every connection starts on the primary's default session, so ignore
+    // traffic until this session's scene arrives
+    let mut loaded = false;
+
+    loop {
+        let message = match events.recv().await {
+            Ok(ClientEvent::Message(message)) => message,
+            Ok(ClientEvent::Disconnected) => {
+                loaded = false;
+                continue;
+            }
+            Ok(ClientEvent::Connected) => continue,
+            Ok(ClientEvent::Closed) | Err(broadcast::error::RecvError::Closed) => break,
+            Err(broadcast::error::RecvError::Lagged(n)) => {
+                // The client's scene mirror is complete; catch up from it
+                warn!("Replica of session {} lagged by {} messages", session_id, n);
+                if let (true, Some(scene)) = (loaded, client.scene()) {
+                    mirror(&sync, &session_id, scene);
+                }
+                continue;
+            }
+        };
+
+        match message {
+            ServerMessage::SceneUpdate { scene } => {
+                loaded = scene.session_id == session_id;
+                if !loaded {
+                    continue;
+                }
+            }
+            ServerMessage::ScenePatch { .. }
+            | ServerMessage::ScenePage { .. }
+            | ServerMessage::ElementAdded { .. }
+            | ServerMessage::ElementUpdated { .. }
+            | ServerMessage::ElementRemoved { .. } => {
+                if !loaded {
+                    continue;
+                }
+            }
+            ServerMessage::ViewState { .. } | ServerMessage::PresenterStopped { .. } => {
+                if loaded {
+                    sync.mirror_presenter(&session_id, message);
+                }
+                continue;
+            }
+            _ => continue,
+        }
+        if let Some(scene) = client.scene() {
+            mirror(&sync, &session_id, scene);
+        }
+    }
+
+    warn!("Replica of session {} lost the primary", session_id);
+    session_id
+}
+
+/// Apply a mirrored scene, logging failures.
+fn mirror(sync: &SyncState, session_id: &str, scene: canvas_core::Scene) {
+    if let Err(e) = sync.mirror_scene(session_id, scene) {
+        warn!("Failed to mirror session {}: {}", session_id, e);
+    }
+}
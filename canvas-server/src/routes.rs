@@ -1,18 +1,27 @@
 //! API route handlers for scene management.
 
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::{header, StatusCode},
-    response::IntoResponse,
-    Json,
+    response::{IntoResponse, Response},
+    Extension, Json,
 };
-use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
 
-use canvas_core::{ElementDocument, SceneDocument};
-use canvas_renderer::export::{ExportConfig, ExportFormat, SceneExporter};
+use serde::{Deserialize, Serialize};
 
-use crate::metrics::record_validation_failure;
-use crate::sync::{current_timestamp, SyncOrigin};
+use canvas_core::{
+    Consistency, ElementDocument, ElementId, IssueSeverity, Redaction, SceneDocument, SceneIssue,
+    SceneStats,
+};
+use canvas_renderer::export::{ExportConfig, ExportFormat, SceneExporter, MAX_ELEMENT_EXPORT_SIZE};
+use canvas_renderer::print::{PrintConfig, PrintLayout};
+use canvas_renderer::{BackendType, Renderer, RendererConfig, SvgBackend};
+
+use crate::auth::Principal;
+use crate::metrics::{record_rate_limited, record_validation_failure};
+use crate::quota::{self, Budget};
+use crate::sync::{current_timestamp, SyncError, SyncOrigin};
 use crate::validation::validate_session_id;
 use crate::AppState;
 
@@ -27,6 +36,8 @@ pub struct SceneResponse {
     /// Error message if failed.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error: Option<String>,
+    /// `eventual` when served by a read replica, otherwise `strong`.
+    pub consistency: Consistency,
 }
 
 /// Request to update the scene.
@@ -51,14 +62,60 @@ fn default_session() -> String {
 }
 
 /// Get the current scene for the default session.
-pub async fn get_scene_handler(State(state): State<AppState>) -> impl IntoResponse {
-    get_scene_for_session(&state, "default").await
+///
+/// Viewers get the [`redacted`](canvas_core::Scene::redacted) document.
+pub async fn get_scene_handler(
+    State(state): State<AppState>,
+    principal: Option<Extension<Principal>>,
+) -> impl IntoResponse {
+    state.sync().record_access("default");
+    if is_viewer(principal.as_ref()) {
+        return redacted_scene(&state, "default").into_response();
+    }
+    get_scene_for_session(&state, "default")
+        .await
+        .into_response()
+}
+
+/// Whether the caller is signed in as a viewer, who only sees redacted
+/// scenes.
+fn is_viewer(principal: Option<&Extension<Principal>>) -> bool {
+    principal.is_some_and(|Extension(p)| !p.role.can_edit())
+}
+
+/// The session's scene with sensitive elements redacted.
+fn redacted_scene(state: &AppState, session_id: &str) -> Json<SceneResponse> {
+    let scene = state.sync().get_or_create_scene(session_id).redacted();
+    Json(SceneResponse {
+        success: true,
+        scene: Some(SceneDocument::from_scene(
+            session_id,
+            &scene,
+            current_timestamp(),
+        )),
+        error: None,
+        consistency: state.sync().consistency(),
+    })
+}
+
+/// Query parameters for the session scene endpoint.
+#[derive(Debug, Default, Deserialize)]
+pub struct SceneQuery {
+    /// Replace sensitive elements with placeholders, for sharing the
+    /// document outside the team.
+    #[serde(default)]
+    pub redact: bool,
 }
 
 /// Get the scene for a specific session.
+///
+/// With `?redact=true`, and always for viewers, the document is
+/// [`redacted`](canvas_core::Scene::redacted).
 pub async fn get_session_scene(
     State(state): State<AppState>,
+    principal: Option<Extension<Principal>>,
     Path(session_id): Path<String>,
+    Query(query): Query<SceneQuery>,
 ) -> impl IntoResponse {
     if let Err(e) = validate_session_id(&session_id) {
         record_validation_failure("session_id");
@@ -68,16 +125,86 @@ pub async fn get_session_scene(
                 success: false,
                 scene: None,
                 error: Some(e.to_string()),
+                consistency: state.sync().consistency(),
             }),
         )
             .into_response();
     }
     state.sync().record_access(&session_id);
+    if query.redact || is_viewer(principal.as_ref()) {
+        return redacted_scene(&state, &session_id).into_response();
+    }
     get_scene_for_session(&state, &session_id)
         .await
         .into_response()
 }
 
+/// Response for the scene validation endpoint.
+#[derive(Debug, Serialize)]
+pub struct ValidateResponse {
+    /// Whether the request succeeded (not whether the scene is valid).
+    pub success: bool,
+    /// Whether the scene has no error-level issues.
+    pub valid: bool,
+    /// Integrity issues, ordered by element ID.
+    pub issues: Vec<SceneIssue>,
+    /// Scene statistics.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stats: Option<SceneStats>,
+    /// Error message if the request failed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl ValidateResponse {
+    fn error(status: StatusCode, error: String) -> Response {
+        (
+            status,
+            Json(Self {
+                success: false,
+                valid: false,
+                issues: Vec::new(),
+                stats: None,
+                error: Some(error),
+            }),
+        )
+            .into_response()
+    }
+}
+
+/// Check a session's scene for integrity issues and report its statistics.
+///
+/// Meant for debugging agent-generated scenes; see [`canvas_core::Scene::validate`].
+pub async fn validate_scene_handler(
+    State(state): State<AppState>,
+    Path(session_id): Path<String>,
+) -> Response {
+    if let Err(e) = validate_session_id(&session_id) {
+        record_validation_failure("session_id");
+        return ValidateResponse::error(StatusCode::BAD_REQUEST, e.to_string());
+    }
+    let sync = state.sync();
+    sync.record_access(&session_id);
+    let Some(scene) = sync.store().get(&session_id) else {
+        return ValidateResponse::error(
+            StatusCode::NOT_FOUND,
+            format!("Session not found: {session_id}"),
+        );
+    };
+
+    let issues = scene.validate();
+    Json(ValidateResponse {
+        success: true,
+        valid: issues
+            .iter()
+            .all(|issue| issue.severity != IssueSeverity::Error),
+        issues,
+        stats: Some(scene.stats()),
+        error: None,
+    })
+    .into_response()
+}
+
 /// Internal function to get scene for a session.
 async fn get_scene_for_session(state: &AppState, session_id: &str) -> Json<SceneResponse> {
     if let Some(communitas) = state.communitas() {
@@ -96,6 +223,7 @@ async fn get_scene_for_session(state: &AppState, session_id: &str) -> Json<Scene
                     success: true,
                     scene: Some(document),
                     error: None,
+                    consistency: state.sync().consistency(),
                 });
             }
             Err(err) => tracing::warn!("Communitas scene fetch failed: {}", err),
@@ -109,16 +237,21 @@ async fn get_scene_for_session(state: &AppState, session_id: &str) -> Json<Scene
         success: true,
         scene: Some(document),
         error: None,
+        consistency: state.sync().consistency(),
     })
 }
 
 /// Update the scene.
+///
+/// Read replicas and warm standbys reject updates with `403 Forbidden`.
 pub async fn update_scene_handler(
     State(state): State<AppState>,
     Json(request): Json<UpdateSceneRequest>,
 ) -> impl IntoResponse {
     tracing::debug!("Scene update request: {:?}", request);
 
+    let sync = state.sync();
+
     // Validate session_id
     if let Err(e) = validate_session_id(&request.session_id) {
         record_validation_failure("session_id");
@@ -126,10 +259,24 @@ pub async fn update_scene_handler(
             success: false,
             scene: None,
             error: Some(e.to_string()),
-        });
+            consistency: sync.consistency(),
+        })
+        .into_response();
+    }
+
+    if sync.is_read_only() {
+        return (
+            StatusCode::FORBIDDEN,
+            Json(SceneResponse {
+                success: false,
+                scene: None,
+                error: Some(SyncError::ReadOnly.to_string()),
+                consistency: sync.consistency(),
+            }),
+        )
+            .into_response();
     }
 
-    let sync = state.sync();
     let session_id = &request.session_id;
 
     // Clear if requested
@@ -139,7 +286,9 @@ pub async fn update_scene_handler(
                 success: false,
                 scene: None,
                 error: Some(e.to_string()),
-            });
+                consistency: sync.consistency(),
+            })
+            .into_response();
         }
     }
 
@@ -152,7 +301,7 @@ pub async fn update_scene_handler(
 
     // Add elements
     for element in &request.add {
-        if let Err(e) = sync.add_element(session_id, element) {
+        if let Err(e) = sync.add_element(session_id, element, None) {
             tracing::warn!("Failed to add element: {}", e);
         }
     }
@@ -165,7 +314,9 @@ pub async fn update_scene_handler(
     }
 
     // Return updated scene
-    get_scene_for_session(&state, session_id).await
+    get_scene_for_session(&state, session_id)
+        .await
+        .into_response()
 }
 
 /// Request body for the export endpoint.
@@ -185,59 +336,59 @@ pub struct ExportRequest {
     pub quality: Option<u8>,
     /// Scale factor (default 1.0).
     pub scale: Option<f32>,
+    /// Paginate onto paper pages with a header and footer (PDF only).
+    /// The header title defaults to the session ID.
+    pub print: Option<PrintConfig>,
+    /// Hide sensitive elements: `"blur"` or `"placeholder"`.
+    pub redact: Option<Redaction>,
+}
+
+/// Serve TypeScript definitions for the sync protocol and scene schema.
+///
+/// Generated from the `canvas-core` types on first request, so JS clients
+/// always see the definitions this server speaks.
+pub async fn types_handler() -> impl IntoResponse {
+    static DEFINITIONS: OnceLock<String> = OnceLock::new();
+    (
+        StatusCode::OK,
+        [(
+            header::CONTENT_TYPE,
+            "application/typescript; charset=utf-8",
+        )],
+        DEFINITIONS
+            .get_or_init(canvas_core::typescript::definitions)
+            .as_str(),
+    )
 }
 
 /// Export a session's scene to an image/document format.
 pub async fn export_scene_handler(
     State(state): State<AppState>,
+    principal: Option<Extension<Principal>>,
     Json(request): Json<ExportRequest>,
 ) -> impl IntoResponse {
     // Validate session ID
     if let Err(e) = validate_session_id(&request.session_id) {
         record_validation_failure("session_id");
-        return (
-            StatusCode::BAD_REQUEST,
-            [(header::CONTENT_TYPE, "application/json")],
-            serde_json::json!({"success": false, "error": e.to_string()})
-                .to_string()
-                .into_bytes(),
-        )
-            .into_response();
+        return export_error(StatusCode::BAD_REQUEST, &e.to_string());
     }
 
     // Parse format
-    let format = match request.format.as_str() {
-        "png" => ExportFormat::Png,
-        "jpeg" | "jpg" => ExportFormat::Jpeg,
-        "svg" => ExportFormat::Svg,
-        "pdf" => ExportFormat::Pdf,
-        other => {
-            return (
-                StatusCode::BAD_REQUEST,
-                [(header::CONTENT_TYPE, "application/json")],
-                serde_json::json!({"success": false, "error": format!("Unsupported format: {other}")})
-                    .to_string()
-                    .into_bytes(),
-            )
-                .into_response();
-        }
+    let Some(format) = parse_export_format(&request.format) else {
+        return export_error(
+            StatusCode::BAD_REQUEST,
+            &format!("Unsupported format: {}", request.format),
+        );
     };
 
     // Get the scene
     let sync = state.sync();
-    let scene = match sync.store().get(&request.session_id) {
-        Some(scene) => scene,
-        None => {
-            return (
-                StatusCode::NOT_FOUND,
-                [(header::CONTENT_TYPE, "application/json")],
-                serde_json::json!({"success": false, "error": "Session not found"})
-                    .to_string()
-                    .into_bytes(),
-            )
-                .into_response();
-        }
+    let Some(scene) = sync.store().get(&request.session_id) else {
+        return export_error(StatusCode::NOT_FOUND, "Session not found");
     };
+    if let Some(response) = export_quota_error(&state, principal.as_deref()) {
+        return response;
+    }
 
     // Configure exporter
     let config = ExportConfig {
@@ -246,40 +397,351 @@ pub async fn export_scene_handler(
         dpi: request.dpi.unwrap_or(96.0),
         jpeg_quality: request.quality.unwrap_or(85),
         scale: request.scale.unwrap_or(1.0),
+        redaction: request.redact,
         ..Default::default()
     };
 
     let exporter = SceneExporter::new(config);
 
+    if let Some(mut print) = request.print {
+        if format != ExportFormat::Pdf {
+            return export_error(
+                StatusCode::BAD_REQUEST,
+                "Print layout requires the pdf format",
+            );
+        }
+        print
+            .title
+            .get_or_insert_with(|| request.session_id.clone());
+        if let Err(e) = PrintLayout::compute(&scene, &print) {
+            return export_error(StatusCode::BAD_REQUEST, &e.to_string());
+        }
+        return match exporter.export_print(&scene, &print) {
+            Ok(data) => (
+                StatusCode::OK,
+                [(header::CONTENT_TYPE, export_content_type(format))],
+                data,
+            )
+                .into_response(),
+            Err(e) => export_error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                &format!("Export failed: {e}"),
+            ),
+        };
+    }
+
     // Export
     match exporter.export(&scene, format) {
-        Ok(data) => {
-            let content_type = match format {
-                ExportFormat::Png => "image/png",
-                ExportFormat::Jpeg => "image/jpeg",
-                ExportFormat::Svg => "image/svg+xml",
-                ExportFormat::Pdf => "application/pdf",
-            };
-
-            (StatusCode::OK, [(header::CONTENT_TYPE, content_type)], data).into_response()
-        }
-        Err(e) => (
+        Ok(data) => (
+            StatusCode::OK,
+            [(header::CONTENT_TYPE, export_content_type(format))],
+            data,
+        )
+            .into_response(),
+        Err(e) => export_error(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            &format!("Export failed: {e}"),
+        ),
+    }
+}
+
+/// Largest scale factor accepted for an element export.
+const MAX_ELEMENT_EXPORT_SCALE: f32 = 8.0;
+
+/// Query parameters for the element export endpoint.
+#[derive(Debug, Default, Deserialize)]
+pub struct ElementExportQuery {
+    /// Output width in pixels (default: the element's width).
+    pub width: Option<u32>,
+    /// Output height in pixels (default: the element's height).
+    pub height: Option<u32>,
+    /// Scale factor (default 1.0).
+    pub scale: Option<f32>,
+    /// JPEG quality 1-100 (default 85).
+    pub quality: Option<u8>,
+    /// Hide sensitive elements: `blur` or `placeholder`.
+    pub redact: Option<Redaction>,
+}
+
+/// Export a single element, cropped to its bounds.
+///
+/// `file` is `{element_id}.{format}`, e.g. `GET /api/scene/default/element/<uuid>.png`.
+/// Setting only one of `width` or `height` keeps the element's aspect ratio.
+pub async fn export_element_handler(
+    State(state): State<AppState>,
+    principal: Option<Extension<Principal>>,
+    Path((session_id, file)): Path<(String, String)>,
+    Query(query): Query<ElementExportQuery>,
+) -> Response {
+    if let Err(e) = validate_session_id(&session_id) {
+        record_validation_failure("session_id");
+        return export_error(StatusCode::BAD_REQUEST, &e.to_string());
+    }
+
+    let Some((element_id, extension)) = file.rsplit_once('.') else {
+        return export_error(
+            StatusCode::BAD_REQUEST,
+            "Expected {element_id}.{format}, e.g. <uuid>.png",
+        );
+    };
+    let Some(format) = parse_export_format(extension) else {
+        return export_error(
+            StatusCode::BAD_REQUEST,
+            &format!("Unsupported format: {extension}"),
+        );
+    };
+    let Ok(element_id) = ElementId::parse(element_id) else {
+        record_validation_failure("element_id");
+        return export_error(
+            StatusCode::BAD_REQUEST,
+            &format!("Invalid element ID: {element_id}"),
+        );
+    };
+
+    let too_large = |size: Option<u32>| size.is_some_and(|s| s == 0 || s > MAX_ELEMENT_EXPORT_SIZE);
+    let scale = query.scale.unwrap_or(1.0);
+    if too_large(query.width)
+        || too_large(query.height)
+        || !(scale > 0.0 && scale <= MAX_ELEMENT_EXPORT_SCALE)
+    {
+        return export_error(
+            StatusCode::BAD_REQUEST,
+            &format!(
+                "Width and height must be 1-{MAX_ELEMENT_EXPORT_SIZE} and scale at most {MAX_ELEMENT_EXPORT_SCALE}"
+            ),
+        );
+    }
+
+    let sync = state.sync();
+    sync.record_access(&session_id);
+    let Some(scene) = sync.store().get(&session_id) else {
+        return export_error(StatusCode::NOT_FOUND, "Session not found");
+    };
+    if scene.get_element(element_id).is_none() {
+        return export_error(
+            StatusCode::NOT_FOUND,
+            &format!("Element not found: {element_id}"),
+        );
+    }
+    if let Some(response) = export_quota_error(&state, principal.as_deref()) {
+        return response;
+    }
+
+    let exporter = SceneExporter::new(ExportConfig {
+        width: query.width,
+        height: query.height,
+        jpeg_quality: query.quality.unwrap_or(85),
+        scale,
+        redaction: query.redact,
+        ..Default::default()
+    });
+    match exporter.export_element(&scene, element_id, format) {
+        Ok(data) => (
+            StatusCode::OK,
+            [(header::CONTENT_TYPE, export_content_type(format))],
+            data,
+        )
+            .into_response(),
+        Err(e) => export_error(
             StatusCode::INTERNAL_SERVER_ERROR,
-            [(header::CONTENT_TYPE, "application/json")],
-            serde_json::json!({"success": false, "error": format!("Export failed: {e}")})
-                .to_string()
-                .into_bytes(),
+            &format!("Export failed: {e}"),
+        ),
+    }
+}
+
+/// Query parameters for the SVG export endpoint.
+#[derive(Debug, Default, Deserialize)]
+pub struct SvgExportQuery {
+    /// Document width in canvas units (default: the session viewport).
+    pub width: Option<u32>,
+    /// Document height in canvas units (default: the session viewport).
+    pub height: Option<u32>,
+    /// Pixels per canvas unit (default 1.0).
+    pub scale: Option<f32>,
+    /// Hide sensitive elements: `blur` or `placeholder`.
+    pub redact: Option<Redaction>,
+}
+
+/// Export a session's scene as a self-contained SVG document.
+///
+/// `GET /api/scene/default/export.svg`. Unlike `POST /api/export` with the
+/// svg format, charts are drawn as vector paths and local images are
+/// embedded as data URIs.
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+pub async fn export_svg_handler(
+    State(state): State<AppState>,
+    principal: Option<Extension<Principal>>,
+    Path(session_id): Path<String>,
+    Query(query): Query<SvgExportQuery>,
+) -> Response {
+    if let Err(e) = validate_session_id(&session_id) {
+        record_validation_failure("session_id");
+        return export_error(StatusCode::BAD_REQUEST, &e.to_string());
+    }
+    let too_large = |size: Option<u32>| size.is_some_and(|s| s == 0 || s > MAX_ELEMENT_EXPORT_SIZE);
+    let scale = query.scale.unwrap_or(1.0);
+    if too_large(query.width)
+        || too_large(query.height)
+        || !(scale > 0.0 && scale <= MAX_ELEMENT_EXPORT_SCALE)
+    {
+        return export_error(
+            StatusCode::BAD_REQUEST,
+            &format!(
+                "Width and height must be 1-{MAX_ELEMENT_EXPORT_SIZE} and scale at most {MAX_ELEMENT_EXPORT_SCALE}"
+            ),
+        );
+    }
+
+    let sync = state.sync();
+    sync.record_access(&session_id);
+    let Some(scene) = sync.store().get(&session_id) else {
+        return export_error(StatusCode::NOT_FOUND, "Session not found");
+    };
+    if let Some(response) = export_quota_error(&state, principal.as_deref()) {
+        return response;
+    }
+
+    let width = query
+        .width
+        .unwrap_or_else(|| scene.viewport_width.max(1.0) as u32);
+    let height = query
+        .height
+        .unwrap_or_else(|| scene.viewport_height.max(1.0) as u32);
+    let svg = SvgBackend::new(width, height)
+        .with_scale(scale)
+        .with_redaction(query.redact)
+        .render_to_string(&scene);
+    (
+        StatusCode::OK,
+        [(header::CONTENT_TYPE, export_content_type(ExportFormat::Svg))],
+        svg,
+    )
+        .into_response()
+}
+
+/// Default longest side of a thumbnail, in pixels.
+const DEFAULT_THUMBNAIL_SIZE: u32 = 256;
+
+/// Largest longest side of a thumbnail, in pixels.
+const MAX_THUMBNAIL_SIZE: u32 = 1024;
+
+/// Query parameters for the thumbnail endpoint.
+#[derive(Debug, Default, Deserialize)]
+pub struct ThumbnailQuery {
+    /// Longest side in pixels (default 256).
+    pub size: Option<u32>,
+    /// Hide sensitive elements: `blur` or `placeholder`.
+    pub redact: Option<Redaction>,
+}
+
+/// Render a PNG thumbnail of a session on the CPU.
+///
+/// `GET /api/scene/default/thumbnail.png?size=256`. The viewport, grown to
+/// take in any content outside it, is scaled so its longest side is `size`
+/// pixels; no GPU or window system is needed.
+pub async fn thumbnail_handler(
+    State(state): State<AppState>,
+    principal: Option<Extension<Principal>>,
+    Path(session_id): Path<String>,
+    Query(query): Query<ThumbnailQuery>,
+) -> Response {
+    if let Err(e) = validate_session_id(&session_id) {
+        record_validation_failure("session_id");
+        return export_error(StatusCode::BAD_REQUEST, &e.to_string());
+    }
+    let size = query.size.unwrap_or(DEFAULT_THUMBNAIL_SIZE);
+    if size == 0 || size > MAX_THUMBNAIL_SIZE {
+        return export_error(
+            StatusCode::BAD_REQUEST,
+            &format!("Size must be 1-{MAX_THUMBNAIL_SIZE}"),
+        );
+    }
+
+    let sync = state.sync();
+    sync.record_access(&session_id);
+    let Some(scene) = sync.store().get(&session_id) else {
+        return export_error(StatusCode::NOT_FOUND, "Session not found");
+    };
+    if let Some(response) = export_quota_error(&state, principal.as_deref()) {
+        return response;
+    }
+
+    let png = Renderer::new(RendererConfig {
+        preferred_backend: BackendType::Software,
+        ..RendererConfig::default()
+    })
+    .and_then(|renderer| renderer.render_thumbnail(&scene, size, query.redact))
+    .and_then(|image| image.encode_png());
+    match png {
+        Ok(png) => (
+            StatusCode::OK,
+            [(header::CONTENT_TYPE, export_content_type(ExportFormat::Png))],
+            png,
         )
             .into_response(),
+        Err(e) => export_error(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            &format!("Thumbnail failed: {e}"),
+        ),
+    }
+}
+
+/// Parse an export format name or file extension.
+fn parse_export_format(name: &str) -> Option<ExportFormat> {
+    match name {
+        "png" => Some(ExportFormat::Png),
+        "jpeg" | "jpg" => Some(ExportFormat::Jpeg),
+        "svg" => Some(ExportFormat::Svg),
+        "pdf" => Some(ExportFormat::Pdf),
+        _ => None,
+    }
+}
+
+/// MIME type of an export format.
+fn export_content_type(format: ExportFormat) -> &'static str {
+    match format {
+        ExportFormat::Png => "image/png",
+        ExportFormat::Jpeg => "image/jpeg",
+        ExportFormat::Svg => "image/svg+xml",
+        ExportFormat::Pdf => "application/pdf",
     }
 }
 
+/// Use one export job of the caller's quota, returning the `429`
+/// response refusing it if none is left.
+fn export_quota_error(state: &AppState, principal: Option<&Principal>) -> Option<Response> {
+    let identity = principal.map_or(quota::ANONYMOUS, |p| p.subject.as_str());
+    let error = state
+        .sync()
+        .quotas()
+        .try_use(identity, Budget::Exports, 1)
+        .err()?;
+    record_rate_limited("export");
+    let mut response = export_error(StatusCode::TOO_MANY_REQUESTS, &error.to_string());
+    response
+        .headers_mut()
+        .insert(header::RETRY_AFTER, error.retry_after_secs().into());
+    Some(response)
+}
+
+/// JSON error body for the export endpoints.
+fn export_error(status: StatusCode, message: &str) -> Response {
+    (
+        status,
+        [(header::CONTENT_TYPE, "application/json")],
+        serde_json::json!({"success": false, "error": message})
+            .to_string()
+            .into_bytes(),
+    )
+        .into_response()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::communitas::{ClientDescriptor, CommunitasMcpClient};
     use crate::sync::SyncState;
-    use canvas_core::ViewportDocument;
+    use canvas_core::{CoordinateSystem, Theme, ViewportDocument};
     use canvas_mcp::CanvasMcpServer;
     use std::sync::Arc;
     use wiremock::matchers::{method, path};
@@ -325,15 +787,23 @@ mod tests {
                     pan_y: 0.0,
                 },
                 elements: vec![],
+                spotlight: None,
+                anchors: Vec::new(),
+                theme: Theme::default(),
+                branding: None,
+                datasets: Vec::new(),
+                coordinates: CoordinateSystem::default(),
                 timestamp: 0,
             }),
             error: None,
+            consistency: Consistency::Strong,
         };
 
         let json = serde_json::to_string(&response).expect("should serialize");
         assert!(json.contains("success"));
         assert!(json.contains("800"));
         assert!(!json.contains("error")); // Skip serializing None
+        assert!(json.contains(r#""consistency":"strong""#));
     }
 
     #[test]
@@ -342,6 +812,7 @@ mod tests {
             success: false,
             scene: None,
             error: Some("Something went wrong".to_string()),
+            consistency: Consistency::Strong,
         };
 
         let json = serde_json::to_string(&response).expect("should serialize");
@@ -384,6 +855,12 @@ mod tests {
                 pan_y: 0.0,
             },
             elements: vec![],
+            spotlight: None,
+            anchors: Vec::new(),
+            theme: Theme::default(),
+            branding: None,
+            datasets: Vec::new(),
+            coordinates: CoordinateSystem::default(),
             timestamp: 123,
         };
 
@@ -410,4 +887,61 @@ mod tests {
         let Json(response) = get_scene_for_session(&state, "default").await;
         assert_eq!(response.scene.unwrap().timestamp, 123);
     }
+
+    #[tokio::test]
+    async fn test_viewers_get_redacted_scenes() {
+        let sync = SyncState::new();
+        let state = AppState {
+            mcp: Arc::new(CanvasMcpServer::new(sync.store())),
+            sync,
+            communitas: None,
+        };
+        state
+            .sync()
+            .update_scene("board", |scene| {
+                scene.add_element(
+                    canvas_core::Element::new(canvas_core::ElementKind::Text {
+                        content: "Salary: 120k".to_string(),
+                        font_size: 16.0,
+                        color: canvas_core::Color::BLACK.into(),
+                    })
+                    .with_sensitive(true),
+                );
+            })
+            .expect("update");
+        let principal = |role| {
+            Some(Extension(Principal {
+                subject: "alice".to_string(),
+                name: None,
+                email: None,
+                role,
+                expires_at: u64::MAX,
+            }))
+        };
+        let body = |response: Response| async {
+            let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .expect("body");
+            String::from_utf8(bytes.to_vec()).expect("utf-8")
+        };
+
+        let viewer = get_session_scene(
+            State(state.clone()),
+            principal(crate::auth::Role::Viewer),
+            Path("board".to_string()),
+            Query(SceneQuery::default()),
+        )
+        .await
+        .into_response();
+        assert!(!body(viewer).await.contains("Salary"));
+        let editor = get_session_scene(
+            State(state),
+            principal(crate::auth::Role::Editor),
+            Path("board".to_string()),
+            Query(SceneQuery::default()),
+        )
+        .await
+        .into_response();
+        assert!(body(editor).await.contains("Salary"));
+    }
 }
@@ -0,0 +1,732 @@
+//! # Warm Standby
+//!
+//! A warm standby keeps a full copy of every session on a primary so it can
+//! take over when the primary fails.
+//!
+//! ```text
+//!  clients ──► primary ──/ws/standby──► standby (read-only)
+//!                 ▲                        │
+//!                 └──── /health/ready ─────┘  POST /api/standby/promote
+//! ```
+//!
+//! The primary serves [`routes`] when `CANVAS_STANDBY_TOKEN` is set. A
+//! standby connects to its `/ws/standby` stream and receives:
+//!
+//! - a [`StandbyFrame::Snapshot`] of every session on connect and then every
+//!   `CANVAS_STANDBY_SNAPSHOT_SECS`;
+//! - a [`StandbyFrame::Event`] for each scene or presenter change;
+//! - a [`StandbyFrame::Heartbeat`] when nothing else was sent.
+//!
+//! Events that cannot be applied make the standby ask for a fresh snapshot
+//! with `{"type": "resync"}`, so a gap never outlives one round trip.
+//!
+//! Until it is promoted a standby rejects writes and reports `503` with
+//! status `"standby"` on `/health/ready`, so load balancers keep clients on
+//! the primary. Promotion is manual (`POST /api/standby/promote`) or
+//! automatic after the primary has been unreachable for
+//! `CANVAS_STANDBY_PROMOTE_AFTER_SECS`.
+
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        State,
+    },
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use canvas_client::{ReconnectPolicy, ServerMessage};
+use canvas_core::{ElementId, SceneDocument};
+use futures::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+use tokio_tungstenite::tungstenite::{self, client::IntoClientRequest};
+use tracing::warn;
+
+use crate::sync::{SyncError, SyncState};
+
+/// Default interval between full snapshots.
+pub const DEFAULT_SNAPSHOT_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Interval between heartbeats on an otherwise idle stream.
+pub const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(5);
+
+/// A stream silent for this long is treated as lost.
+const STALL_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Errors from configuring a warm standby.
+#[derive(Debug, Error)]
+pub enum StandbyError {
+    /// The standby settings cannot be read.
+    #[error("invalid standby configuration: {0}")]
+    Config(String),
+}
+
+/// Frames a primary streams to its standby.
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum StandbyFrame {
+    /// Every session on the primary.
+    Snapshot {
+        /// Position of this frame in the stream.
+        sequence: u64,
+        /// Full scene of each session.
+        sessions: Vec<SceneDocument>,
+    },
+    /// A scene or presenter change in one session.
+    Event {
+        /// Position of this frame in the stream.
+        sequence: u64,
+        /// Session the change applies to.
+        session_id: String,
+        /// The change, as broadcast to the primary's clients.
+        message: ServerMessage,
+    },
+    /// The primary is alive but has nothing new.
+    Heartbeat {
+        /// Sequence of the last snapshot or event sent.
+        sequence: u64,
+    },
+}
+
+/// Requests a standby sends back to its primary.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum StandbyRequest {
+    /// Send a full snapshot now.
+    Resync,
+}
+
+/// Settings for serving [`routes`] on a primary (and for promoting a standby).
+#[derive(Debug, Clone)]
+pub struct StreamConfig {
+    /// Shared secret presented as `Authorization: Bearer <token>`.
+    pub token: String,
+    /// Interval between full snapshots.
+    pub snapshot_interval: Duration,
+}
+
+impl StreamConfig {
+    /// Create a configuration protected by `token`.
+    #[must_use]
+    pub fn new(token: impl Into<String>) -> Self {
+        Self {
+            token: token.into(),
+            snapshot_interval: DEFAULT_SNAPSHOT_INTERVAL,
+        }
+    }
+
+    /// Set the interval between full snapshots.
+    #[must_use]
+    pub fn with_snapshot_interval(mut self, interval: Duration) -> Self {
+        self.snapshot_interval = interval;
+        self
+    }
+
+    /// Read `CANVAS_STANDBY_TOKEN` and `CANVAS_STANDBY_SNAPSHOT_SECS`.
+    ///
+    /// Returns `None` when no token is set.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`StandbyError::Config`] for an invalid interval.
+    pub fn from_env() -> Result<Option<Self>, StandbyError> {
+        let Some(token) = env_var("CANVAS_STANDBY_TOKEN") else {
+            return Ok(None);
+        };
+        let mut config = Self::new(token);
+        if let Some(secs) = env_secs("CANVAS_STANDBY_SNAPSHOT_SECS")? {
+            config.snapshot_interval = secs;
+        }
+        Ok(Some(config))
+    }
+}
+
+/// Configuration for a warm standby.
+#[derive(Debug, Clone)]
+pub struct StandbyConfig {
+    /// Standby stream URL of the primary, e.g. `ws://primary:9473/ws/standby`.
+    pub primary_url: String,
+    /// Shared secret the primary expects.
+    pub token: String,
+    /// Promote automatically after the primary is unreachable this long
+    /// (`None` waits for a manual promotion).
+    pub promote_after: Option<Duration>,
+    /// Reconnect schedule for the stream; retries never give up.
+    pub reconnect: ReconnectPolicy,
+}
+
+impl StandbyConfig {
+    /// Create a configuration following `primary_url`.
+    #[must_use]
+    pub fn new(primary_url: impl Into<String>, token: impl Into<String>) -> Self {
+        Self {
+            primary_url: primary_url.into(),
+            token: token.into(),
+            promote_after: None,
+            reconnect: ReconnectPolicy::default(),
+        }
+    }
+
+    /// Promote automatically after the primary is unreachable for `after`.
+    #[must_use]
+    pub fn with_promote_after(mut self, after: Duration) -> Self {
+        self.promote_after = Some(after);
+        self
+    }
+
+    /// Set the reconnect schedule.
+    #[must_use]
+    pub fn with_reconnect(mut self, reconnect: ReconnectPolicy) -> Self {
+        self.reconnect = reconnect;
+        self
+    }
+
+    /// Read `CANVAS_STANDBY_OF`, `CANVAS_STANDBY_TOKEN` and
+    /// `CANVAS_STANDBY_PROMOTE_AFTER_SECS`.
+    ///
+    /// Returns `None` when `CANVAS_STANDBY_OF` is not set.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`StandbyError::Config`] when the token is missing or the
+    /// promotion delay is invalid.
+    pub fn from_env() -> Result<Option<Self>, StandbyError> {
+        let Some(primary_url) = env_var("CANVAS_STANDBY_OF") else {
+            return Ok(None);
+        };
+        let token = env_var("CANVAS_STANDBY_TOKEN").ok_or_else(|| {
+            StandbyError::Config("CANVAS_STANDBY_OF requires CANVAS_STANDBY_TOKEN".to_string())
+        })?;
+        let mut config = Self::new(primary_url, token);
+        config.promote_after = env_secs("CANVAS_STANDBY_PROMOTE_AFTER_SECS")?;
+        Ok(Some(config))
+    }
+}
+
+fn env_var(name: &str) -> Option<String> {
+    std::env::var(name)
+        .ok()
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
+
+fn env_secs(name: &str) -> Result<Option<Duration>, StandbyError> {
+    env_var(name)
+        .map(|value| match value.parse::<u64>() {
+            Ok(secs) if secs > 0 => Ok(Duration::from_secs(secs)),
+            _ => Err(StandbyError::Config(format!(
+                "{name} must be a positive number of seconds, got {value:?}"
+            ))),
+        })
+        .transpose()
+}
+
+/// Compare secrets without leaking the mismatch position through timing.
+fn tokens_match(expected: &str, presented: &str) -> bool {
+    expected.len() == presented.len()
+        && expected
+            .bytes()
+            .zip(presented.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+#[derive(Clone)]
+struct RouteState {
+    sync: SyncState,
+    config: StreamConfig,
+}
+
+impl RouteState {
+    fn authorized(&self, headers: &HeaderMap) -> bool {
+        headers
+            .get(header::AUTHORIZATION)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("Bearer "))
+            .is_some_and(|token| tokens_match(&self.config.token, token.trim()))
+    }
+}
+
+/// Routes for streaming to a standby and promoting one.
+///
+/// - `GET /ws/standby` streams [`StandbyFrame`]s to a standby.
+/// - `POST /api/standby/promote` promotes this server if it is a standby.
+///
+/// Both require `Authorization: Bearer <token>`.
+pub fn routes<S>(sync: SyncState, config: StreamConfig) -> Router<S> {
+    Router::new()
+        .route("/ws/standby", get(stream_handler))
+        .route("/api/standby/promote", post(promote_handler))
+        .with_state(RouteState { sync, config })
+}
+
+async fn stream_handler(
+    State(state): State<RouteState>,
+    headers: HeaderMap,
+    ws: WebSocketUpgrade,
+) -> Response {
+    if !state.authorized(&headers) {
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+    tracing::info!("Standby connected");
+    ws.on_upgrade(move |socket| stream(socket, state.sync, state.config.snapshot_interval))
+}
+
+/// Response body of `POST /api/standby/promote`.
+#[derive(Debug, Serialize)]
+struct PromoteResponse {
+    promoted: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<&'static str>,
+}
+
+async fn promote_handler(State(state): State<RouteState>, headers: HeaderMap) -> Response {
+    if !state.authorized(&headers) {
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+    if state.sync.promote() {
+        Json(PromoteResponse {
+            promoted: true,
+            error: None,
+        })
+        .into_response()
+    } else {
+        (
+            StatusCode::CONFLICT,
+            Json(PromoteResponse {
+                promoted: false,
+                error: Some("This server is not a standby"),
+            }),
+        )
+            .into_response()
+    }
+}
+
+/// Build a snapshot of every session in `sync`.
+fn snapshot(sync: &SyncState, sequence: u64) -> StandbyFrame {
+    let store = sync.store();
+    let sessions = store
+        .session_ids()
+        .iter()
+        .map(|id| store.scene_document(id))
+        .collect();
+    StandbyFrame::Snapshot { sequence, sessions }
+}
+
+/// Returns true for broadcasts that change what a standby must hold.
+fn is_replicated(message: &ServerMessage) -> bool {
+    matches!(
+        message,
+        ServerMessage::SceneUpdate { .. }
+            | ServerMessage::ScenePatch { .. }
+            | ServerMessage::ElementAdded { .. }
+            | ServerMessage::ElementUpdated { .. }
+            | ServerMessage::ElementRemoved { .. }
+            | ServerMessage::ViewState { .. }
+            | ServerMessage::PresenterStopped { .. }
+    )
+}
+
+/// Stream snapshots, events and heartbeats to one standby until it leaves.
+async fn stream(socket: WebSocket, sync: SyncState, snapshot_interval: Duration) {
+    let (mut sink, mut incoming) = socket.split();
+    // Subscribe before the first snapshot so no event falls between them
+    let mut events = sync.subscribe();
+    let mut snapshots = tokio::time::interval(snapshot_interval);
+    let mut heartbeats = tokio::time::interval(HEARTBEAT_INTERVAL);
+    heartbeats.reset();
+    let mut sequence = 0;
+
+    loop {
+        let frame = tokio::select! {
+            _ = snapshots.tick() => {
+                sequence += 1;
+                snapshot(&sync, sequence)
+            }
+            _ = heartbeats.tick() => StandbyFrame::Heartbeat { sequence },
+            event = events.recv() => match event {
+                Ok(event) if is_replicated(&event.message) => {
+                    sequence += 1;
+                    StandbyFrame::Event {
+                        sequence,
+                        session_id: event.session_id,
+                        message: event.message,
+                    }
+                }
+                Ok(_) => continue,
+                Err(broadcast::error::RecvError::Lagged(n)) => {
+                    // Events were dropped; a snapshot covers them
+                    warn!("Standby stream lagged by {} events, resyncing", n);
+                    snapshots.reset();
+                    sequence += 1;
+                    snapshot(&sync, sequence)
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+            request = incoming.next() => match request {
+                Some(Ok(Message::Text(text))) => {
+                    if serde_json::from_str::<StandbyRequest>(text.as_str()).is_err() {
+                        continue;
+                    }
+                    snapshots.reset();
+                    sequence += 1;
+                    snapshot(&sync, sequence)
+                }
+                Some(Ok(Message::Close(_)) | Err(_)) | None => break,
+                Some(Ok(_)) => continue,
+            },
+        };
+        heartbeats.reset();
+
+        let text = match serde_json::to_string(&frame) {
+            Ok(text) => text,
+            Err(e) => {
+                warn!("Failed to encode standby frame: {}", e);
+                continue;
+            }
+        };
+        if sink.send(Message::Text(text.into())).await.is_err() {
+            break;
+        }
+    }
+    tracing::info!("Standby disconnected");
+}
+
+/// Handle to a running standby.
+pub struct StandbyHandle {
+    task: JoinHandle<()>,
+}
+
+impl StandbyHandle {
+    /// Stop following the primary.
+    ///
+    /// The server stays read-only until it is promoted.
+    pub fn abort(self) {
+        self.task.abort();
+    }
+}
+
+/// Turn `sync` into a warm standby of the configured primary.
+///
+/// From now on local writes are rejected and every session on the primary
+/// is copied here, until the standby is promoted with
+/// [`SyncState::promote`] or `POST /api/standby/promote`.
+pub fn spawn_standby(sync: SyncState, config: StandbyConfig) -> StandbyHandle {
+    sync.set_standby(config.primary_url.clone());
+    tracing::info!("Warm standby of {}", config.primary_url);
+    let task = tokio::spawn(follow(sync, config));
+    StandbyHandle { task }
+}
+
+/// Follow the primary until this server is promoted.
+async fn follow(sync: SyncState, config: StandbyConfig) {
+    let mut attempt = 0;
+    let mut lost_since = Instant::now();
+
+    while sync.is_standby() {
+        match connect(&config).await {
+            Ok(socket) => {
+                attempt = 0;
+                sync.update_standby(|status| status.connected = true);
+                follow_stream(&sync, socket).await;
+                sync.update_standby(|status| status.connected = false);
+                lost_since = Instant::now();
+                if sync.is_standby() {
+                    warn!("Standby lost the primary at {}", config.primary_url);
+                }
+            }
+            Err(e) => warn!("Standby cannot reach {}: {}", config.primary_url, e),
+        }
+
+        // Never promote a standby that holds no copy of the primary
+        let synced = sync.standby_status().is_some_and(|status| status.synced);
+        if let Some(after) = config.promote_after {
+            if synced && lost_since.elapsed() >= after && sync.promote() {
+                break;
+            }
+        }
+        tokio::time::sleep(config.reconnect.delay(attempt)).await;
+        attempt = attempt.saturating_add(1);
+    }
+}
+
+type StandbySocket =
+    tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>>;
+
+async fn connect(config: &StandbyConfig) -> Result<StandbySocket, tungstenite::Error> {
+    let mut request = config.primary_url.as_str().into_client_request()?;
+    let bearer = header::HeaderValue::from_str(&format!("Bearer {}", config.token))
+        .map_err(|e| tungstenite::Error::HttpFormat(e.into()))?;
+    request.headers_mut().insert(header::AUTHORIZATION, bearer);
+    let (socket, _) = tokio_tungstenite::connect_async(request).await?;
+    Ok(socket)
+}
+
+/// Apply frames from one connection until it closes, stalls or the standby
+/// is promoted.
+async fn follow_stream(sync: &SyncState, socket: StandbySocket) {
+    use tungstenite::Message;
+
+    let (mut sink, mut stream) = socket.split();
+    while sync.is_standby() {
+        let text = match tokio::time::timeout(STALL_TIMEOUT, stream.next()).await {
+            Ok(Some(Ok(Message::Text(text)))) => text,
+            Ok(Some(Ok(Message::Close(_)) | Err(_)) | None) => return,
+            Ok(Some(Ok(_))) => continue,
+            Err(_) => {
+                warn!("Standby stream silent for {:?}", STALL_TIMEOUT);
+                return;
+            }
+        };
+        let frame: StandbyFrame = match serde_json::from_str(text.as_str()) {
+            Ok(frame) => frame,
+            Err(e) => {
+                warn!("Ignoring invalid standby frame: {}", e);
+                continue;
+            }
+        };
+        if !sync.is_standby() {
+            return;
+        }
+        if let Err(e) = apply_frame(sync, frame) {
+            warn!("Standby out of step with the primary ({}), resyncing", e);
+            let resync = serde_json::to_string(&StandbyRequest::Resync).unwrap_or_default();
+            if sink.send(Message::Text(resync)).await.is_err() {
+                return;
+            }
+        }
+    }
+}
+
+/// Apply one frame from the primary to `sync`.
+///
+/// # Errors
+///
+/// Returns [`SyncError`] when an event does not apply to the local copy;
+/// the caller should request a snapshot.
+pub fn apply_frame(sync: &SyncState, frame: StandbyFrame) -> Result<(), SyncError> {
+    let sequence = match &frame {
+        StandbyFrame::Snapshot { sequence, .. }
+        | StandbyFrame::Event { sequence, .. }
+        | StandbyFrame::Heartbeat { sequence } => *sequence,
+    };
+    let result = match frame {
+        StandbyFrame::Snapshot { sessions, .. } => apply_snapshot(sync, sessions),
+        StandbyFrame::Event {
+            session_id,
+            message,
+            ..
+        } => apply_event(sync, &session_id, message),
+        StandbyFrame::Heartbeat { .. } => Ok(false),
+    };
+    let snapshot_applied = result.as_ref().is_ok_and(|&snapshot| snapshot);
+    sync.update_standby(|status| {
+        status.sequence = sequence;
+        status.last_frame = Some(Instant::now());
+        status.synced |= snapshot_applied;
+    });
+    result.map(|_| ())
+}
+
+/// Replace every local session with the primary's copy, clearing sessions
+/// the primary does not have.
+///
+/// Returns `Ok(true)` so [`apply_frame`] can mark the standby synced.
+fn apply_snapshot(sync: &SyncState, sessions: Vec<SceneDocument>) -> Result<bool, SyncError> {
+    let mut kept = HashSet::new();
+    for document in sessions {
+        let session_id = document.session_id.clone();
+        let scene = document
+            .into_scene()
+            .map_err(|e| SyncError::InvalidMessage(e.to_string()))?;
+        sync.mirror_scene(&session_id, scene)?;
+        kept.insert(session_id);
+    }
+    for session_id in sync.store().session_ids() {
+        if !kept.contains(&session_id) {
+            sync.remove_session(&session_id);
+        }
+    }
+    Ok(true)
+}
+
+/// Apply one scene or presenter change to the local copy of a session.
+fn apply_event(
+    sync: &SyncState,
+    session_id: &str,
+    message: ServerMessage,
+) -> Result<bool, SyncError> {
+    let mut scene = match &message {
+        ServerMessage::ViewState { .. } | ServerMessage::PresenterStopped { .. } => {
+            sync.mirror_presenter(session_id, message);
+            return Ok(false);
+        }
+        ServerMessage::SceneUpdate { scene } => {
+            let scene = scene
+                .clone()
+                .into_scene()
+                .map_err(|e| SyncError::InvalidMessage(e.to_string()))?;
+            sync.mirror_scene(session_id, scene)?;
+            return Ok(false);
+        }
+        _ => sync
+            .get_scene(session_id)
+            .ok_or_else(|| SyncError::SessionNotFound(session_id.to_string()))?,
+    };
+    match message {
+        ServerMessage::ScenePatch { patch } => scene.apply_patch(&patch)?,
+        ServerMessage::ElementAdded { element, .. }
+        | ServerMessage::ElementUpdated { element, .. } => scene.upsert_element(
+            element
+                .into_element()
+                .map_err(|e| SyncError::InvalidMessage(e.to_string()))?,
+        ),
+        ServerMessage::ElementRemoved { id, .. } => {
+            let id = ElementId::parse(&id).map_err(|_| SyncError::InvalidElementId(id.clone()))?;
+            scene.remove_element(&id)?;
+        }
+        _ => return Ok(false),
+    }
+    sync.mirror_scene(session_id, scene)?;
+    Ok(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use canvas_core::{Color, Element, ElementDocument, ElementKind, Scene};
+
+    fn text(content: &str) -> Element {
+        Element::new(ElementKind::Text {
+            content: content.to_string(),
+            font_size: 16.0,
+            color: Color::BLACK.into(),
+        })
+    }
+
+    fn standby() -> SyncState {
+        let sync = SyncState::new();
+        sync.set_standby("ws://primary/ws/standby".to_string());
+        sync
+    }
+
+    #[test]
+    fn test_frames_round_trip() {
+        let frame = StandbyFrame::Event {
+            sequence: 7,
+            session_id: "s".to_string(),
+            message: ServerMessage::PresenterStopped {
+                presenter_id: "p".to_string(),
+            },
+        };
+        let json = serde_json::to_string(&frame).expect("encode");
+        assert!(json.starts_with(r#"{"type":"event","sequence":7"#));
+        let decoded: StandbyFrame = serde_json::from_str(&json).expect("decode");
+        assert!(matches!(decoded, StandbyFrame::Event { sequence: 7, .. }));
+
+        let resync: StandbyRequest = serde_json::from_str(r#"{"type":"resync"}"#).expect("decode");
+        assert_eq!(resync, StandbyRequest::Resync);
+    }
+
+    #[test]
+    fn test_tokens_match() {
+        assert!(tokens_match("secret", "secret"));
+        assert!(!tokens_match("secret", "secreT"));
+        assert!(!tokens_match("secret", "secret2"));
+        assert!(!tokens_match("secret", ""));
+    }
+
+    #[test]
+    fn test_snapshot_replaces_sessions_and_marks_synced() {
+        let primary = SyncState::new();
+        let element = text("Agenda");
+        primary
+            .add_element("town-hall", &ElementDocument::from(&element), None)
+            .expect("add");
+
+        let sync = standby();
+        let mut stale = Scene::new(800.0, 600.0);
+        stale.add_element(text("Gone from the primary"));
+        sync.mirror_scene("stale", stale).expect("mirror");
+        assert!(!sync.standby_status().expect("status").synced);
+
+        apply_frame(&sync, snapshot(&primary, 1)).expect("apply snapshot");
+
+        let scene = sync.get_scene("town-hall").expect("session copied");
+        assert!(scene.get_element(element.id).is_some());
+        assert!(sync.get_scene("stale").is_none_or(|scene| scene.is_empty()));
+        let status = sync.standby_status().expect("status");
+        assert!(status.synced);
+        assert_eq!(status.sequence, 1);
+    }
+
+    #[test]
+    fn test_events_apply_and_unknown_sessions_need_resync() {
+        let sync = standby();
+        let element = text("Hello");
+        let added = ServerMessage::ElementAdded {
+            element: ElementDocument::from(&element),
+            timestamp: 0,
+        };
+
+        // No copy of the session yet: the standby must ask for a snapshot
+        let frame = StandbyFrame::Event {
+            sequence: 1,
+            session_id: "s".to_string(),
+            message: added.clone(),
+        };
+        assert!(matches!(
+            apply_frame(&sync, frame),
+            Err(SyncError::SessionNotFound(_))
+        ));
+
+        sync.mirror_scene("s", Scene::new(800.0, 600.0))
+            .expect("mirror");
+        let frame = StandbyFrame::Event {
+            sequence: 2,
+            session_id: "s".to_string(),
+            message: added,
+        };
+        apply_frame(&sync, frame).expect("apply add");
+        assert!(sync
+            .get_scene("s")
+            .is_some_and(|scene| scene.get_element(element.id).is_some()));
+
+        let frame = StandbyFrame::Event {
+            sequence: 3,
+            session_id: "s".to_string(),
+            message: ServerMessage::ElementRemoved {
+                id: element.id.to_string(),
+                timestamp: 0,
+            },
+        };
+        apply_frame(&sync, frame).expect("apply remove");
+        assert!(sync
+            .get_scene("s")
+            .is_some_and(|scene| scene.get_element(element.id).is_none()));
+    }
+
+    #[test]
+    fn test_promote_makes_standby_writable() {
+        let sync = standby();
+        assert!(sync.is_read_only());
+        assert!(sync
+            .add_element("s", &ElementDocument::from(&text("x")), None)
+            .is_err());
+
+        assert!(sync.promote());
+        assert!(!sync.promote(), "a promoted server is no longer a standby");
+        assert!(!sync.is_read_only());
+        assert!(sync.standby_status().expect("status").promoted);
+        sync.add_element("s", &ElementDocument::from(&text("x")), None)
+            .expect("writable after promotion");
+    }
+}
@@ -25,6 +25,7 @@
 //!
 //! - `{"type": "welcome", "version": "...", "session_id": "..."}`
 //! - `{"type": "scene_update", "elements": [...]}`
+//! - `{"type": "scene_page", "page": {"index": 1, "total": 3, "elements": [...]}}`
 //! - `{"type": "element_added", "element": {...}}`
 //! - `{"type": "element_removed", "id": "..."}`
 //! - `{"type": "ack", "message_id": "..."}`
@@ -38,26 +39,49 @@
 //! - `{"type": "relay_answer", "from_peer_id": "...", "sdp": "..."}`
 //! - `{"type": "relay_ice_candidate", "from_peer_id": "...", "candidate": "..."}`
 //! - `{"type": "call_ended", "from_peer_id": "...", "reason": "..."}`
+//!
+//! ## Progressive Loading
+//!
+//! Connections opened with a page size (see [`SyncSocketOptions`]) receive
+//! large scenes in pages: the `scene_update` carries the elements in the
+//! viewport, and `scene_page` messages follow with the rest, nearest first.
+//! All pages are sent before any later broadcast, so patches always apply to
+//! a fully loaded scene.
+//!
+//! ## Read Replicas
+//!
+//! A server started as a read replica (see [`crate::replica`]) mirrors
+//! sessions from a primary and advertises `"consistency": "eventual"` in its
+//! welcome message. Writes are rejected with a `read_only` error.
 
-use std::collections::{HashMap, HashSet};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, RwLock};
 use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 use axum::extract::ws::{Message, WebSocket};
 use canvas_core::{
-    ConflictResolution, ConflictStrategy, Element, ElementDocument, ElementId, OfflineQueue,
-    Operation, Scene, SceneDocument, SceneStore, StoreError,
+    Anchor, Branding, CameraCommand, CameraFrame, CanvasError, ConflictResolution,
+    ConflictStrategy, DatasetDelta, Element, ElementDocument, ElementId, Encoding, EncodingError,
+    EraseMode, FitOptions, InkEdit, OfflineQueue, Operation, PollResults, RandomOutcome, Scene,
+    SceneDocument, SceneStore, StoreError, Theme, ThemeColor, ViewState,
 };
+use futures::stream::SplitSink;
 use futures::{SinkExt, StreamExt};
 use serde::{Deserialize, Serialize};
 use tokio::sync::broadcast;
 use tokio::sync::mpsc;
 use uuid::Uuid;
 
+pub use canvas_core::protocol::{
+    BatchOperation, ClientMessage, Consistency, FailedOperationInfo, OperationType,
+    QueuedOperation, ServerMessage,
+};
+
 use crate::agui::InteractionEvent;
 use crate::communitas::CommunitasMcpClient;
 use crate::metrics::{record_rate_limited, record_validation_failure};
+use crate::quota::{Budget, QuotaError, Quotas};
 use crate::validation::{
     validate_element_id, validate_ice_candidate, validate_message_size, validate_peer_id,
     validate_sdp, validate_session_id, ValidationError,
@@ -67,6 +91,8 @@ use crate::validation::{
 const DEFAULT_RATE_LIMIT_BURST: u32 = 100;
 /// Default sustained rate for rate limiting (messages per second).
 const DEFAULT_RATE_LIMIT_SUSTAINED: u32 = 10;
+/// Ink edits each connection can undo.
+const MAX_INK_HISTORY: usize = 100;
 
 /// Token bucket rate limiter for WebSocket connections.
 ///
@@ -117,17 +143,57 @@ impl RateLimiter {
         Self::new(burst, sustained)
     }
 
+    /// Create a rate limiter allowing `limit` tokens per `window`, all of
+    /// which may be used at once.
+    #[must_use]
+    pub fn per_window(limit: u32, window: Duration) -> Self {
+        Self {
+            tokens: f64::from(limit),
+            capacity: f64::from(limit),
+            refill_rate: f64::from(limit) / window.as_secs_f64().max(f64::EPSILON),
+            last_refill: Instant::now(),
+        }
+    }
+
     /// Try to consume one token. Returns true if allowed, false if rate limited.
     pub fn try_consume(&mut self) -> bool {
+        self.try_consume_n(1)
+    }
+
+    /// Try to consume `n` tokens at once; none are consumed if fewer are
+    /// available.
+    pub fn try_consume_n(&mut self, n: u32) -> bool {
         self.refill();
-        if self.tokens >= 1.0 {
-            self.tokens -= 1.0;
+        if self.tokens >= f64::from(n) {
+            self.tokens -= f64::from(n);
             true
         } else {
             false
         }
     }
 
+    /// Consume `n` tokens even if fewer are available, for usage only known
+    /// afterwards. The debt is paid off before tokens are available again.
+    pub fn charge(&mut self, n: u32) {
+        self.refill();
+        self.tokens -= f64::from(n);
+    }
+
+    /// Whole tokens available now.
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    pub fn available(&mut self) -> u32 {
+        self.refill();
+        self.tokens.max(0.0).floor() as u32
+    }
+
+    /// Time until every token is available again.
+    #[must_use]
+    pub fn time_until_full(&self) -> Duration {
+        let needed = (self.capacity - self.tokens).max(0.0);
+        Duration::from_secs_f64(needed / self.refill_rate.max(f64::EPSILON))
+    }
+
     /// Refill tokens based on elapsed time.
     fn refill(&mut self) {
         let now = Instant::now();
@@ -142,399 +208,20 @@ impl RateLimiter {
     /// Returns `None` if tokens are already available.
     #[must_use]
     pub fn time_until_available(&self) -> Option<Duration> {
-        if self.tokens >= 1.0 {
-            None
-        } else {
-            let needed = 1.0 - self.tokens;
-            let seconds = needed / self.refill_rate;
-            Some(Duration::from_secs_f64(seconds))
-        }
-    }
-}
-
-/// Client-to-server WebSocket message types.
-#[derive(Debug, Clone, Deserialize)]
-#[serde(tag = "type", rename_all = "snake_case")]
-pub enum ClientMessage {
-    /// Subscribe to scene updates for a session.
-    Subscribe {
-        /// Session ID to subscribe to.
-        #[serde(default = "default_session")]
-        session_id: String,
-    },
-    /// Add a new element to the scene.
-    AddElement {
-        /// The element to add.
-        element: ElementDocument,
-        /// Optional message ID for acknowledgment.
-        #[serde(default)]
-        message_id: Option<String>,
-    },
-    /// Update an existing element.
-    UpdateElement {
-        /// Element ID to update.
-        id: String,
-        /// Changes to apply (partial element data).
-        changes: serde_json::Value,
-        /// Optional message ID for acknowledgment.
-        #[serde(default)]
-        message_id: Option<String>,
-    },
-    /// Remove an element from the scene.
-    RemoveElement {
-        /// Element ID to remove.
-        id: String,
-        /// Optional message ID for acknowledgment.
-        #[serde(default)]
-        message_id: Option<String>,
-    },
-    /// Ping to keep connection alive.
-    Ping,
-    /// Sync queued offline operations.
-    SyncQueue {
-        /// Queued operations to sync.
-        operations: Vec<QueuedOperation>,
-    },
-    /// Request current scene state.
-    GetScene,
-
-    // === WebRTC Signaling Messages ===
-    /// Start a call to a peer.
-    StartCall {
-        /// Target peer ID to call.
-        target_peer_id: String,
-        /// Session ID for the call.
-        session_id: String,
-    },
-    /// SDP offer from caller.
-    Offer {
-        /// Target peer ID.
-        target_peer_id: String,
-        /// SDP offer string.
-        sdp: String,
-    },
-    /// SDP answer from callee.
-    Answer {
-        /// Target peer ID.
-        target_peer_id: String,
-        /// SDP answer string.
-        sdp: String,
-    },
-    /// ICE candidate exchange.
-    IceCandidate {
-        /// Target peer ID.
-        target_peer_id: String,
-        /// ICE candidate string.
-        candidate: String,
-        /// SDP media ID.
-        #[serde(default)]
-        sdp_mid: Option<String>,
-        /// SDP media line index.
-        #[serde(default)]
-        sdp_m_line_index: Option<u16>,
-    },
-    /// End a call with a peer.
-    EndCall {
-        /// Target peer ID.
-        target_peer_id: String,
-    },
-
-    // === Communitas Call Control Messages ===
-    /// Start a new Communitas-backed call for the current session.
-    StartCommunitasCall {
-        /// Whether video should be enabled for this call.
-        #[serde(default)]
-        video_enabled: bool,
-        /// Optional message ID for acknowledgment.
-        #[serde(default)]
-        message_id: Option<String>,
-    },
-    /// Join an existing Communitas call by call ID.
-    JoinCommunitasCall {
-        /// The call ID to join.
-        call_id: String,
-        /// Optional message ID for acknowledgment.
-        #[serde(default)]
-        message_id: Option<String>,
-    },
-    /// Leave the current Communitas call.
-    LeaveCommunitasCall {
-        /// Optional message ID for acknowledgment.
-        #[serde(default)]
-        message_id: Option<String>,
-    },
-
-    // === Interaction Events (AG-UI) ===
-    /// Report a user interaction on the canvas.
-    Interaction {
-        /// Interaction type: "touch", "button_click", "form_input", "selection", "gesture".
-        interaction_type: String,
-        /// Element ID involved in the interaction (if any).
-        #[serde(default)]
-        element_id: Option<String>,
-        /// Interaction-specific data.
-        data: serde_json::Value,
-        /// Optional message ID for acknowledgment.
-        #[serde(default)]
-        message_id: Option<String>,
-    },
-}
-
-/// Server-to-client WebSocket message types.
-#[derive(Debug, Clone, Serialize)]
-#[serde(tag = "type", rename_all = "snake_case")]
-pub enum ServerMessage {
-    /// Welcome message on connection.
-    Welcome {
-        /// Server version.
-        version: String,
-        /// Assigned session ID.
-        session_id: String,
-        /// Connection timestamp.
-        timestamp: u64,
-        /// Whether legacy (browser-native) signaling is enabled.
-        #[serde(skip_serializing_if = "Option::is_none")]
-        legacy_signaling: Option<bool>,
-    },
-    /// Full scene state update.
-    SceneUpdate {
-        /// Canonical scene document.
-        scene: SceneDocument,
-    },
-    /// Single element added to scene.
-    ElementAdded {
-        /// The added element.
-        element: ElementDocument,
-        /// Event timestamp.
-        timestamp: u64,
-    },
-    /// Single element updated.
-    ElementUpdated {
-        /// The updated element data.
-        element: ElementDocument,
-        /// Event timestamp.
-        timestamp: u64,
-    },
-    /// Single element removed from scene.
-    ElementRemoved {
-        /// ID of removed element.
-        id: String,
-        /// Event timestamp.
-        timestamp: u64,
-    },
-    /// Acknowledgment of a client message.
-    Ack {
-        /// The message ID being acknowledged.
-        message_id: String,
-        /// Whether the operation succeeded.
-        success: bool,
-        /// Optional result data.
-        #[serde(skip_serializing_if = "Option::is_none")]
-        result: Option<serde_json::Value>,
-    },
-    /// Error response.
-    Error {
-        /// Error code.
-        code: String,
-        /// Human-readable error message.
-        message: String,
-        /// Related message ID if applicable.
-        #[serde(skip_serializing_if = "Option::is_none")]
-        message_id: Option<String>,
-    },
-    /// Pong response to ping.
-    Pong {
-        /// Response timestamp.
-        timestamp: u64,
-    },
-    /// Sync result after processing queued operations.
-    SyncResult {
-        /// Number of operations synced.
-        synced_count: usize,
-        /// Number of conflicts encountered.
-        conflict_count: usize,
-        /// Event timestamp.
-        timestamp: u64,
-        /// Details of failed operations (up to 10).
-        #[serde(skip_serializing_if = "Vec::is_empty")]
-        failed_operations: Vec<FailedOperationInfo>,
-    },
-    /// Communitas call state update for this session.
-    CallState {
-        /// Session identifier for this call state.
-        session_id: String,
-        /// Active Communitas call ID (if established).
-        #[serde(skip_serializing_if = "Option::is_none")]
-        call_id: Option<String>,
-        /// Active peer IDs participating in the call.
-        participants: Vec<String>,
-    },
-    /// Result of a Communitas call operation.
-    CommunitasCallResult {
-        /// The operation that was performed.
-        operation: String,
-        /// Whether the operation succeeded.
-        success: bool,
-        /// Call ID if available.
-        #[serde(skip_serializing_if = "Option::is_none")]
-        call_id: Option<String>,
-        /// Error message if operation failed.
-        #[serde(skip_serializing_if = "Option::is_none")]
-        error: Option<String>,
-        /// Related message ID for acknowledgment.
-        #[serde(skip_serializing_if = "Option::is_none")]
-        message_id: Option<String>,
-    },
-
-    // === WebRTC Signaling Messages ===
-    /// Incoming call notification.
-    IncomingCall {
-        /// Peer ID initiating the call.
-        from_peer_id: String,
-        /// Session ID for the call.
-        session_id: String,
-    },
-    /// Relay SDP offer to target peer.
-    RelayOffer {
-        /// Peer ID sending the offer.
-        from_peer_id: String,
-        /// SDP offer string.
-        sdp: String,
-    },
-    /// Relay SDP answer to target peer.
-    RelayAnswer {
-        /// Peer ID sending the answer.
-        from_peer_id: String,
-        /// SDP answer string.
-        sdp: String,
-    },
-    /// Relay ICE candidate to target peer.
-    RelayIceCandidate {
-        /// Peer ID sending the candidate.
-        from_peer_id: String,
-        /// ICE candidate string.
-        candidate: String,
-        /// SDP media ID.
-        #[serde(skip_serializing_if = "Option::is_none")]
-        sdp_mid: Option<String>,
-        /// SDP media line index.
-        #[serde(skip_serializing_if = "Option::is_none")]
-        sdp_m_line_index: Option<u16>,
-    },
-    /// Call ended notification.
-    CallEnded {
-        /// Peer ID that ended the call.
-        from_peer_id: String,
-        /// Reason for call ending.
-        reason: String,
-    },
-    /// Your assigned peer ID (sent on connection).
-    PeerAssigned {
-        /// Assigned unique peer ID.
-        peer_id: String,
-    },
-}
-
-/// A queued operation from offline queue.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(tag = "op", rename_all = "snake_case")]
-pub enum QueuedOperation {
-    /// Add element operation.
-    Add {
-        /// Element to add.
-        element: ElementDocument,
-        /// Operation timestamp.
-        timestamp: u64,
-    },
-    /// Update element operation.
-    Update {
-        /// Element ID.
-        id: String,
-        /// Changes to apply.
-        changes: serde_json::Value,
-        /// Operation timestamp.
-        timestamp: u64,
-    },
-    /// Remove element operation.
-    Remove {
-        /// Element ID.
-        id: String,
-        /// Operation timestamp.
-        timestamp: u64,
-    },
-}
-
-impl QueuedOperation {
-    /// Get the type of this operation.
-    #[must_use]
-    pub fn operation_type(&self) -> OperationType {
-        match self {
-            Self::Add { .. } => OperationType::Add,
-            Self::Update { .. } => OperationType::Update,
-            Self::Remove { .. } => OperationType::Remove,
-        }
-    }
-
-    /// Get the element ID affected by this operation (if any).
-    #[must_use]
-    pub fn element_id(&self) -> Option<&str> {
-        match self {
-            Self::Add { element, .. } => Some(&element.id),
-            Self::Update { id, .. } | Self::Remove { id, .. } => Some(id),
-        }
-    }
-}
-
-/// Type of sync operation.
-///
-/// Provides type-safe representation of operation types rather than using strings.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
-#[serde(rename_all = "snake_case")]
-pub enum OperationType {
-    /// Add a new element.
-    Add,
-    /// Update an existing element.
-    Update,
-    /// Remove an element.
-    Remove,
-}
-
-impl std::fmt::Display for OperationType {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Self::Add => write!(f, "add"),
-            Self::Update => write!(f, "update"),
-            Self::Remove => write!(f, "remove"),
-        }
+        self.time_until(1)
     }
-}
-
-/// Information about a failed sync operation sent to clients.
-///
-/// This struct provides minimal but useful details about operations
-/// that failed during queue processing, enabling client-side reconciliation.
-#[derive(Debug, Clone, Serialize)]
-pub struct FailedOperationInfo {
-    /// Type of operation that failed.
-    pub operation: OperationType,
-    /// Element ID involved in the failed operation, if available.
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub element_id: Option<String>,
-    /// Human-readable error message.
-    pub error: String,
-}
-
-impl FailedOperationInfo {
-    /// Maximum number of failed operations to include in a sync result.
-    pub const MAX_FAILURES_IN_RESPONSE: usize = 10;
 
-    /// Create from a failed operation and its error message.
+    /// Get the time until `n` tokens are available.
+    ///
+    /// Returns `None` if they are already available.
     #[must_use]
-    pub fn from_failed_op(op: &QueuedOperation, error: &str) -> Self {
-        Self {
-            operation: op.operation_type(),
-            element_id: op.element_id().map(String::from),
-            error: error.to_string(),
+    pub fn time_until(&self, n: u32) -> Option<Duration> {
+        let needed = f64::from(n) - self.tokens;
+        if needed <= 0.0 {
+            None
+        } else {
+            let seconds = needed / self.refill_rate.max(f64::EPSILON);
+            Some(Duration::from_secs_f64(seconds))
         }
     }
 }
@@ -628,6 +315,50 @@ impl ActiveCall {
     }
 }
 
+/// Upstream primary of a read replica.
+#[derive(Debug, Clone)]
+struct ReplicaLink {
+    /// Sync WebSocket URL of the primary.
+    primary_url: String,
+    /// Sessions to start mirroring from the primary.
+    follow_tx: mpsc::UnboundedSender<String>,
+}
+
+/// Progress of a warm standby following its primary.
+#[derive(Debug, Clone, Serialize)]
+pub struct StandbyStatus {
+    /// Standby stream URL of the primary.
+    pub primary_url: String,
+    /// Whether the stream to the primary is currently open.
+    pub connected: bool,
+    /// Sequence number of the last frame applied.
+    pub sequence: u64,
+    /// Whether at least one full snapshot has been applied.
+    pub synced: bool,
+    /// Seconds since the last frame from the primary, if any arrived.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lag_secs: Option<u64>,
+    /// Set once the standby has been promoted to primary.
+    pub promoted: bool,
+    /// When the last frame arrived.
+    #[serde(skip)]
+    pub last_frame: Option<Instant>,
+}
+
+impl StandbyStatus {
+    fn new(primary_url: String) -> Self {
+        Self {
+            primary_url,
+            connected: false,
+            sequence: 0,
+            synced: false,
+            lag_secs: None,
+            promoted: false,
+            last_frame: None,
+        }
+    }
+}
+
 /// Lightweight snapshot for broadcasting to clients.
 #[derive(Debug, Clone, Default)]
 struct CallSnapshot {
@@ -659,6 +390,19 @@ pub struct SyncState {
     conflict_count: Arc<AtomicU64>,
     /// Last access time per session (for expiry).
     last_access: Arc<RwLock<HashMap<String, Instant>>>,
+    /// Last scene document broadcast per session and the scene revision it
+    /// was taken at, used as the base for patches.
+    last_broadcast: Arc<RwLock<HashMap<String, (u64, SceneDocument)>>>,
+    /// Current presenter (peer ID and view) per session.
+    presenters: Arc<RwLock<HashMap<String, (String, ViewState)>>>,
+    /// Primary this server mirrors, when running as a read replica.
+    replica: Arc<RwLock<Option<ReplicaLink>>>,
+    /// Stream progress, when running as a warm standby.
+    standby: Arc<RwLock<Option<StandbyStatus>>>,
+    /// Where clients should go if this primary fails.
+    failover_url: Arc<RwLock<Option<String>>>,
+    /// Per-identity message, element and export budgets.
+    quotas: Quotas,
 }
 
 impl SyncState {
@@ -678,6 +422,12 @@ impl SyncState {
             communitas: Arc::new(RwLock::new(None)),
             conflict_count: Arc::new(AtomicU64::new(0)),
             last_access: Arc::new(RwLock::new(HashMap::new())),
+            last_broadcast: Arc::new(RwLock::new(HashMap::new())),
+            presenters: Arc::new(RwLock::new(HashMap::new())),
+            replica: Arc::new(RwLock::new(None)),
+            standby: Arc::new(RwLock::new(None)),
+            failover_url: Arc::new(RwLock::new(None)),
+            quotas: Quotas::default(),
         }
     }
 
@@ -704,6 +454,12 @@ impl SyncState {
             communitas: Arc::new(RwLock::new(None)),
             conflict_count: Arc::new(AtomicU64::new(0)),
             last_access: Arc::new(RwLock::new(HashMap::new())),
+            last_broadcast: Arc::new(RwLock::new(HashMap::new())),
+            presenters: Arc::new(RwLock::new(HashMap::new())),
+            replica: Arc::new(RwLock::new(None)),
+            standby: Arc::new(RwLock::new(None)),
+            failover_url: Arc::new(RwLock::new(None)),
+            quotas: Quotas::default(),
         })
     }
 
@@ -744,6 +500,131 @@ impl SyncState {
         !self.communitas_enabled()
     }
 
+    /// Make this server a read replica of `primary_url`.
+    ///
+    /// Local writes are rejected from now on, and every session passed to
+    /// [`record_access`](Self::record_access) is sent on `follow_tx` so it
+    /// can be mirrored from the primary.
+    pub(crate) fn set_replica(
+        &self,
+        primary_url: String,
+        follow_tx: mpsc::UnboundedSender<String>,
+    ) {
+        if let Ok(mut guard) = self.replica.write() {
+            *guard = Some(ReplicaLink {
+                primary_url,
+                follow_tx,
+            });
+        }
+    }
+
+    /// Per-identity budgets, shared with the MCP and export handlers.
+    pub fn quotas(&self) -> &Quotas {
+        &self.quotas
+    }
+
+    /// Returns true when this server is a read replica.
+    pub fn is_replica(&self) -> bool {
+        self.replica.read().is_ok_and(|guard| guard.is_some())
+    }
+
+    /// Sync WebSocket URL of the primary, when this server is a read replica.
+    pub fn primary_url(&self) -> Option<String> {
+        self.replica
+            .read()
+            .ok()
+            .and_then(|guard| guard.as_ref().map(|link| link.primary_url.clone()))
+    }
+
+    /// Make this server a warm standby of `primary_url`.
+    ///
+    /// Local writes are rejected until [`promote`](Self::promote) is called.
+    pub(crate) fn set_standby(&self, primary_url: String) {
+        if let Ok(mut guard) = self.standby.write() {
+            *guard = Some(StandbyStatus::new(primary_url));
+        }
+    }
+
+    /// Record stream progress on a warm standby.
+    pub(crate) fn update_standby(&self, f: impl FnOnce(&mut StandbyStatus)) {
+        if let Ok(mut guard) = self.standby.write() {
+            if let Some(status) = guard.as_mut() {
+                f(status);
+            }
+        }
+    }
+
+    /// Returns true while this server is an unpromoted warm standby.
+    pub fn is_standby(&self) -> bool {
+        self.standby
+            .read()
+            .is_ok_and(|guard| guard.as_ref().is_some_and(|status| !status.promoted))
+    }
+
+    /// Stream progress, when this server is (or was) a warm standby.
+    pub fn standby_status(&self) -> Option<StandbyStatus> {
+        let mut status = self.standby.read().ok()?.clone()?;
+        status.lag_secs = status.last_frame.map(|at| at.elapsed().as_secs());
+        Some(status)
+    }
+
+    /// Promote a warm standby to primary, making it writable.
+    ///
+    /// Returns false when this server is not an unpromoted standby.
+    pub fn promote(&self) -> bool {
+        let Ok(mut guard) = self.standby.write() else {
+            return false;
+        };
+        match guard.as_mut() {
+            Some(status) if !status.promoted => {
+                status.promoted = true;
+                status.connected = false;
+                tracing::warn!("Promoted from standby of {} to primary", status.primary_url);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Advertise where clients should go if this server fails.
+    pub fn set_failover_url(&self, url: String) {
+        if let Ok(mut guard) = self.failover_url.write() {
+            *guard = Some(url);
+        }
+    }
+
+    /// Failover URL advertised by this server, if any.
+    pub fn failover_url(&self) -> Option<String> {
+        self.failover_url
+            .read()
+            .ok()
+            .and_then(|guard| guard.clone())
+    }
+
+    /// Returns true when local writes are rejected: on a read replica or an
+    /// unpromoted warm standby.
+    pub fn is_read_only(&self) -> bool {
+        self.is_replica() || self.is_standby()
+    }
+
+    /// Consistency of the scenes this server serves.
+    pub fn consistency(&self) -> Consistency {
+        if self.is_read_only() {
+            Consistency::Eventual
+        } else {
+            Consistency::Strong
+        }
+    }
+
+    /// Reject local writes on a read replica or warm standby.
+    fn ensure_writable(&self) -> Result<(), SyncError> {
+        if self.is_read_only() {
+            Err(SyncError::ReadOnly)
+        } else {
+            Ok(())
+        }
+    }
+
     fn communitas_client(&self) -> Option<CommunitasMcpClient> {
         match self.communitas.read() {
             Ok(guard) => guard.clone(),
@@ -1270,6 +1151,7 @@ impl SyncState {
         }
         if let Some(old_session) = previous {
             self.remove_call_participant(&old_session, peer_id);
+            self.stop_presenting(&old_session, peer_id);
         }
     }
 
@@ -1289,6 +1171,7 @@ impl SyncState {
         if let Some(session_id) = session {
             tracing::info!("Unregistered peer {} from session {}", peer_id, session_id);
             self.remove_call_participant(&session_id, peer_id);
+            self.stop_presenting(&session_id, peer_id);
         } else {
             tracing::info!("Unregistered peer {}", peer_id);
         }
@@ -1359,10 +1242,17 @@ impl SyncState {
     }
 
     /// Record access to a session for expiry tracking.
+    ///
+    /// On a read replica this also starts mirroring the session.
     pub fn record_access(&self, session_id: &str) {
         if let Ok(mut map) = self.last_access.write() {
             map.insert(session_id.to_string(), Instant::now());
         }
+        if let Ok(guard) = self.replica.read() {
+            if let Some(link) = guard.as_ref() {
+                let _ = link.follow_tx.send(session_id.to_string());
+            }
+        }
     }
 
     /// Remove sessions that have not been accessed within `ttl`.
@@ -1384,17 +1274,29 @@ impl SyncState {
 
         let mut removed = 0;
         for session_id in &expired {
-            // Remove from in-memory store
-            if self.store.clear(session_id).is_ok() {
+            if self.remove_session(session_id) {
                 tracing::info!("Expired session removed: {}", session_id);
                 removed += 1;
             }
-            // Remove persisted file
-            self.store.delete_session_file(session_id);
-            // Remove from access map
-            if let Ok(mut map) = self.last_access.write() {
-                map.remove(session_id);
-            }
+        }
+        removed
+    }
+
+    /// Clear a session's scene and sync bookkeeping and delete its persisted
+    /// JSON file.
+    ///
+    /// Returns true when the session was in memory.
+    pub fn remove_session(&self, session_id: &str) -> bool {
+        let removed = self.store.clear(session_id).is_ok();
+        self.store.delete_session_file(session_id);
+        if let Ok(mut map) = self.last_access.write() {
+            map.remove(session_id);
+        }
+        if let Ok(mut map) = self.last_broadcast.write() {
+            map.remove(session_id);
+        }
+        if let Ok(mut map) = self.presenters.write() {
+            map.remove(session_id);
         }
         removed
     }
@@ -1451,7 +1353,9 @@ impl SyncState {
     ) -> Result<(), SyncError> {
         self.store.replace(session_id, scene.clone())?;
 
+        let revision = self.store.revision(session_id).unwrap_or_default();
         let document = SceneDocument::from_scene(session_id, &scene, current_timestamp());
+        self.set_last_broadcast(session_id, revision, document.clone());
         self.broadcast(
             session_id,
             ServerMessage::SceneUpdate { scene: document },
@@ -1460,6 +1364,41 @@ impl SyncState {
         Ok(())
     }
 
+    /// Replace a session's scene with a copy mirrored from the primary.
+    ///
+    /// Unlike [`replace_scene`](Self::replace_scene), connected clients
+    /// receive a patch against the last broadcast rather than the whole
+    /// scene.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SyncError`] if the store operation fails.
+    pub fn mirror_scene(&self, session_id: &str, scene: Scene) -> Result<(), SyncError> {
+        self.store.replace(session_id, scene)?;
+        self.broadcast_scene_change(session_id, SyncOrigin::Remote);
+        Ok(())
+    }
+
+    /// Apply a presenter change mirrored from the primary.
+    ///
+    /// Messages other than `view_state` and `presenter_stopped` are ignored.
+    pub fn mirror_presenter(&self, session_id: &str, message: ServerMessage) {
+        let Ok(mut map) = self.presenters.write() else {
+            return;
+        };
+        match &message {
+            ServerMessage::ViewState { presenter_id, view } => {
+                map.insert(session_id.to_string(), (presenter_id.clone(), *view));
+            }
+            ServerMessage::PresenterStopped { .. } => {
+                map.remove(session_id);
+            }
+            _ => return,
+        }
+        drop(map);
+        self.broadcast(session_id, message, SyncOrigin::Remote);
+    }
+
     /// Get a scene by session ID.
     #[allow(dead_code)]
     #[must_use]
@@ -1482,22 +1421,68 @@ impl SyncState {
     where
         F: FnOnce(&mut Scene),
     {
+        self.ensure_writable()?;
         // Ensure the scene exists first
         let _ = self.store.get_or_create(session_id);
 
         self.store.update(session_id, f)?;
 
+        self.broadcast_scene_change(session_id, SyncOrigin::Local);
+        Ok(())
+    }
+
+    /// Broadcast the current scene of a session as a patch.
+    ///
+    /// The patch is computed against the last scene broadcast for the session.
+    /// A full [`ServerMessage::SceneUpdate`] is sent instead when there is no
+    /// previous broadcast to diff against. Nothing is sent if the scene is
+    /// unchanged; when its revision has not moved since the last broadcast
+    /// the scene is not even serialized.
+    pub fn broadcast_scene_change(&self, session_id: &str, origin: SyncOrigin) {
+        // One lock from reading the base to sending the patch, so
+        // concurrent changes are diffed, stored and sent in order
+        let mut map = self
+            .last_broadcast
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        let revision = self.store.revision(session_id);
+        let previous = match map.get(session_id) {
+            Some((seen, _)) if Some(*seen) == revision => return,
+            entry => entry.map(|(_, document)| document),
+        };
         let document = self.store.scene_document(session_id);
-        self.broadcast(
-            session_id,
-            ServerMessage::SceneUpdate { scene: document },
-            SyncOrigin::Local,
+
+        let message = match previous {
+            Some(previous) => {
+                let patch = previous.diff(&document);
+                (!patch.is_empty()).then_some(ServerMessage::ScenePatch { patch })
+            }
+            None => Some(ServerMessage::SceneUpdate {
+                scene: document.clone(),
+            }),
+        };
+        map.insert(
+            session_id.to_string(),
+            (revision.unwrap_or_default(), document),
         );
-        Ok(())
+        if let Some(message) = message {
+            self.broadcast(session_id, message, origin);
+        }
+    }
+
+    /// Record the scene document most recently broadcast for a session and
+    /// the scene revision it was taken at.
+    fn set_last_broadcast(&self, session_id: &str, revision: u64, document: SceneDocument) {
+        if let Ok(mut map) = self.last_broadcast.write() {
+            map.insert(session_id.to_string(), (revision, document));
+        }
     }
 
     /// Add an element to a session's scene.
     ///
+    /// When `author` is given the element is attributed to that peer,
+    /// overriding any attribution in `element_data`.
+    ///
     /// # Errors
     ///
     /// Returns [`SyncError`] if the element data is invalid.
@@ -1505,26 +1490,27 @@ impl SyncState {
         &self,
         session_id: &str,
         element_data: &ElementDocument,
+        author: Option<&str>,
     ) -> Result<ElementId, SyncError> {
-        let element = element_from_data(element_data)?;
+        self.ensure_writable()?;
+        let mut element = element_from_data(element_data)?;
+        if let Some(author) = author {
+            element = element.with_author(author);
+        }
         let id = element.id;
+        let added = element_to_data(&element);
 
         self.store.add_element(session_id, element)?;
 
         // Broadcast the addition
         let message = ServerMessage::ElementAdded {
-            element: element_data.clone(),
+            element: added,
             timestamp: current_timestamp(),
         };
         self.broadcast(session_id, message, SyncOrigin::Local);
 
-        // Also broadcast full scene update
-        let document = self.store.scene_document(session_id);
-        self.broadcast(
-            session_id,
-            ServerMessage::SceneUpdate { scene: document },
-            SyncOrigin::Local,
-        );
+        // Also broadcast the scene change for clients tracking the whole scene
+        self.broadcast_scene_change(session_id, SyncOrigin::Local);
 
         Ok(id)
     }
@@ -1535,6 +1521,7 @@ impl SyncState {
     ///
     /// Returns [`SyncError`] if the element is not found or the ID is invalid.
     pub fn remove_element(&self, session_id: &str, id: &str) -> Result<(), SyncError> {
+        self.ensure_writable()?;
         let element_id = parse_element_id(id)?;
 
         self.store.remove_element(session_id, element_id)?;
@@ -1551,6 +1538,8 @@ impl SyncState {
 
     /// Update an element in a session's scene.
     ///
+    /// When `author` is given it is recorded as the element's last modifier.
+    ///
     /// # Errors
     ///
     /// Returns [`SyncError`] if the element is not found or the ID is invalid.
@@ -1559,7 +1548,9 @@ impl SyncState {
         session_id: &str,
         id: &str,
         changes: &serde_json::Value,
+        author: Option<&str>,
     ) -> Result<ElementDocument, SyncError> {
+        self.ensure_writable()?;
         let element_id = parse_element_id(id)?;
 
         // Clone changes for the closure
@@ -1568,6 +1559,9 @@ impl SyncState {
         self.store
             .update_element(session_id, element_id, |element| {
                 apply_changes_to_element(element, &changes_clone);
+                if let Some(author) = author {
+                    element.touch(author);
+                }
             })?;
 
         // Get the updated element for the response
@@ -1580,6 +1574,14 @@ impl SyncState {
             .ok_or_else(|| SyncError::ElementNotFound(id.to_string()))?;
         let updated_element = element_to_data(element);
 
+        // Markup pinned to a moved image moved too, so send a patch
+        if changes.get("transform").is_some()
+            && matches!(&element.kind, canvas_core::ElementKind::Image { annotations, .. } if !annotations.is_empty())
+        {
+            self.broadcast_scene_change(session_id, SyncOrigin::Local);
+            return Ok(updated_element);
+        }
+
         // Broadcast the update
         let message = ServerMessage::ElementUpdated {
             element: updated_element.clone(),
@@ -1590,41 +1592,534 @@ impl SyncState {
         Ok(updated_element)
     }
 
-    /// Get full scene state as a server message.
-    #[must_use]
-    pub fn get_scene_update(&self, session_id: &str) -> ServerMessage {
-        let document = self.store.scene_document(session_id);
-        ServerMessage::SceneUpdate { scene: document }
-    }
-
-    /// Process queued offline operations with full error tracking.
+    /// Apply a camera command to a session's scene and broadcast the new view.
     ///
-    /// Returns a detailed result with processed/failed counts and error details
-    /// for operations that could not be applied.
-    #[must_use]
-    pub fn process_queue(
+    /// # Errors
+    ///
+    /// Returns [`SyncError`] if the session or elements are not found.
+    pub fn apply_camera_command(
         &self,
         session_id: &str,
-        operations: Vec<QueuedOperation>,
-    ) -> ProcessQueueResult {
-        let mut processed_count = 0;
-        let mut failed_ops: Vec<(QueuedOperation, String)> = Vec::new();
+        command: &CameraCommand,
+        options: &FitOptions,
+    ) -> Result<CameraFrame, SyncError> {
+        self.ensure_writable()?;
+        let mut result = Err(SyncError::SessionNotFound(session_id.to_string()));
+        self.store.update(session_id, |scene| {
+            result = command.apply(scene, options).map_err(SyncError::from);
+        })?;
+        let frame = result?;
 
-        for op in operations {
-            let result = match &op {
-                QueuedOperation::Add { element, .. } => {
-                    self.add_element(session_id, element).map(|_| ())
-                }
-                QueuedOperation::Update { id, changes, .. } => {
-                    self.update_element(session_id, id, changes).map(|_| ())
-                }
-                QueuedOperation::Remove { id, .. } => self.remove_element(session_id, id),
-            };
+        self.broadcast_scene_change(session_id, SyncOrigin::Local);
+        Ok(frame)
+    }
 
-            match result {
-                Ok(()) => processed_count += 1,
-                Err(err) => {
-                    let error_msg = err.to_string();
+    /// Save a named anchor in a session's scene and broadcast the change.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SyncError`] if the session is not found or the anchor is
+    /// invalid.
+    pub fn set_anchor(&self, session_id: &str, anchor: Anchor) -> Result<(), SyncError> {
+        self.ensure_writable()?;
+        let mut result = Err(SyncError::SessionNotFound(session_id.to_string()));
+        self.store.update(session_id, |scene| {
+            result = scene.set_anchor(anchor.clone()).map_err(SyncError::from);
+        })?;
+        result?;
+
+        self.broadcast_scene_change(session_id, SyncOrigin::Local);
+        Ok(())
+    }
+
+    /// Delete a named anchor from a session's scene and broadcast the change.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SyncError`] if the session or anchor is not found.
+    pub fn remove_anchor(&self, session_id: &str, name: &str) -> Result<Anchor, SyncError> {
+        self.ensure_writable()?;
+        let mut removed = None;
+        self.store.update(session_id, |scene| {
+            removed = scene.remove_anchor(name);
+        })?;
+        let anchor =
+            removed.ok_or_else(|| SyncError::InvalidMessage(format!("No anchor named {name}")))?;
+
+        self.broadcast_scene_change(session_id, SyncOrigin::Local);
+        Ok(anchor)
+    }
+
+    /// Replace a session's theme and broadcast the change.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SyncError`] if the session is not found or the theme is
+    /// invalid.
+    pub fn set_theme(&self, session_id: &str, theme: Theme) -> Result<(), SyncError> {
+        self.ensure_writable()?;
+        let mut result = Err(SyncError::SessionNotFound(session_id.to_string()));
+        self.store.update(session_id, |scene| {
+            result = scene.set_theme(theme.clone()).map_err(SyncError::from);
+        })?;
+        result?;
+
+        self.broadcast_scene_change(session_id, SyncOrigin::Local);
+        Ok(())
+    }
+
+    /// Set or clear a session's branding and broadcast the change.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SyncError`] if the session is not found or the branding is
+    /// invalid.
+    pub fn set_branding(
+        &self,
+        session_id: &str,
+        branding: Option<Branding>,
+    ) -> Result<(), SyncError> {
+        self.ensure_writable()?;
+        let mut result = Err(SyncError::SessionNotFound(session_id.to_string()));
+        self.store.update(session_id, |scene| {
+            result = match branding.clone() {
+                Some(branding) => scene.set_branding(branding).map_err(SyncError::from),
+                None => {
+                    scene.clear_branding();
+                    Ok(())
+                }
+            };
+        })?;
+        result?;
+
+        self.broadcast_scene_change(session_id, SyncOrigin::Local);
+        Ok(())
+    }
+
+    /// Change a dataset in a session's scene and broadcast the change.
+    ///
+    /// Returns the IDs of the charts refilled from the dataset.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SyncError`] if the session is not found or the change is
+    /// invalid.
+    pub fn update_dataset(
+        &self,
+        session_id: &str,
+        delta: &DatasetDelta,
+    ) -> Result<Vec<ElementId>, SyncError> {
+        self.ensure_writable()?;
+        let mut result = Err(SyncError::SessionNotFound(session_id.to_string()));
+        self.store.update(session_id, |scene| {
+            result = scene.apply_data_delta(delta).map_err(SyncError::from);
+        })?;
+        let refreshed = result?;
+
+        self.broadcast_scene_change(session_id, SyncOrigin::Local);
+        Ok(refreshed)
+    }
+
+    /// Move a session's camera to a named anchor and broadcast the new view.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SyncError`] if the session or anchor is not found.
+    pub fn go_to_anchor(&self, session_id: &str, name: &str) -> Result<CameraFrame, SyncError> {
+        self.apply_camera_command(
+            session_id,
+            &CameraCommand::GoToAnchor {
+                name: name.to_string(),
+            },
+            &FitOptions::default(),
+        )
+    }
+
+    /// Group sibling elements in a session's scene and broadcast the change.
+    ///
+    /// Returns the new group's ID.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SyncError`] if an ID is invalid, an element is not found,
+    /// or the elements do not share a parent.
+    pub fn group_elements(&self, session_id: &str, ids: &[String]) -> Result<ElementId, SyncError> {
+        self.ensure_writable()?;
+        let ids = ids
+            .iter()
+            .map(|id| parse_element_id(id))
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut result = Err(SyncError::SessionNotFound(session_id.to_string()));
+        self.store.update(session_id, |scene| {
+            result = scene.group(&ids).map_err(SyncError::from);
+        })?;
+        let group = result?;
+
+        self.broadcast_scene_change(session_id, SyncOrigin::Local);
+        Ok(group)
+    }
+
+    /// Dissolve a group in a session's scene and broadcast the change.
+    ///
+    /// Returns the former children.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SyncError`] if the ID is invalid, the element is not found
+    /// or it is not a group.
+    pub fn ungroup_element(&self, session_id: &str, id: &str) -> Result<Vec<ElementId>, SyncError> {
+        self.ensure_writable()?;
+        let element_id = parse_element_id(id)?;
+        let mut result = Err(SyncError::SessionNotFound(session_id.to_string()));
+        self.store.update(session_id, |scene| {
+            result = scene.ungroup(element_id).map_err(SyncError::from);
+        })?;
+        let children = result?;
+
+        self.broadcast_scene_change(session_id, SyncOrigin::Local);
+        Ok(children)
+    }
+
+    /// Erase ink under a round eraser in a session's scene and broadcast
+    /// the change if any stroke was touched.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SyncError`] if the session is not found.
+    pub fn erase_ink(
+        &self,
+        session_id: &str,
+        center: (f32, f32),
+        radius: f32,
+        mode: EraseMode,
+    ) -> Result<InkEdit, SyncError> {
+        self.ensure_writable()?;
+        let mut edit = InkEdit::default();
+        self.store.update(session_id, |scene| {
+            edit = scene.erase_ink(center.0, center.1, radius, mode);
+        })?;
+
+        if !edit.is_empty() {
+            self.broadcast_scene_change(session_id, SyncOrigin::Local);
+        }
+        Ok(edit)
+    }
+
+    /// Change the color and/or width of ink strokes in a session's scene
+    /// and broadcast the change.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SyncError`] if an ID is invalid, a stroke is not found or
+    /// not ink, or the width is invalid.
+    pub fn restyle_ink(
+        &self,
+        session_id: &str,
+        ids: &[String],
+        color: Option<ThemeColor>,
+        width: Option<f32>,
+    ) -> Result<InkEdit, SyncError> {
+        self.ensure_writable()?;
+        let ids = ids
+            .iter()
+            .map(|id| parse_element_id(id))
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut result = Err(SyncError::SessionNotFound(session_id.to_string()));
+        self.store.update(session_id, |scene| {
+            result = scene
+                .restyle_ink(&ids, color.as_ref(), width)
+                .map_err(SyncError::from);
+        })?;
+        let edit = result?;
+
+        self.broadcast_scene_change(session_id, SyncOrigin::Local);
+        Ok(edit)
+    }
+
+    /// Apply an ink edit (e.g. to undo or redo one) to a session's scene
+    /// and broadcast the change.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SyncError`] if the strokes the edit expects are missing or
+    /// the ones it adds already exist.
+    pub fn apply_ink_edit(&self, session_id: &str, edit: &InkEdit) -> Result<(), SyncError> {
+        self.ensure_writable()?;
+        let mut result = Err(SyncError::SessionNotFound(session_id.to_string()));
+        self.store.update(session_id, |scene| {
+            result = scene.apply_ink_edit(edit).map_err(SyncError::from);
+        })?;
+        result?;
+
+        self.broadcast_scene_change(session_id, SyncOrigin::Local);
+        Ok(())
+    }
+
+    /// Record `voter`'s vote on a poll and broadcast the updated results.
+    ///
+    /// Returns `None`, without changing the scene, if the element is not a
+    /// poll, so `vote` actions on other elements pass through untouched.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SyncError`] if the ID is invalid, the element is not found,
+    /// the poll is closed or `option` is out of range.
+    pub fn record_vote(
+        &self,
+        session_id: &str,
+        id: &str,
+        voter: &str,
+        option: usize,
+    ) -> Result<Option<PollResults>, SyncError> {
+        self.ensure_writable()?;
+        let element_id = parse_element_id(id)?;
+        let mut result = Err(SyncError::SessionNotFound(session_id.to_string()));
+        self.store.update(session_id, |scene| {
+            result = match scene.get_element_mut(element_id) {
+                None => Err(SyncError::ElementNotFound(id.to_string())),
+                Some(element) if PollResults::from_kind(&element.kind).is_none() => Ok(None),
+                Some(element) => canvas_core::poll::cast_vote(&mut element.kind, voter, option)
+                    .map(|()| PollResults::from_kind(&element.kind))
+                    .map_err(SyncError::from),
+            };
+        })?;
+        let results = result?;
+
+        if results.is_some() {
+            self.broadcast_scene_change(session_id, SyncOrigin::Local);
+        }
+        Ok(results)
+    }
+
+    /// Draw a new outcome for a randomizer and broadcast it, so every
+    /// participant sees the same roll.
+    ///
+    /// Returns the outcome with its description (e.g. `"4 + 2 = 6"`), or
+    /// `None`, without changing the scene, if the element is not a
+    /// randomizer.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SyncError`] if the ID is invalid, the element is not found
+    /// or the randomizer's source is invalid.
+    pub fn randomize(
+        &self,
+        session_id: &str,
+        id: &str,
+        by: &str,
+    ) -> Result<Option<(RandomOutcome, String)>, SyncError> {
+        use rand::Rng;
+
+        self.ensure_writable()?;
+        let element_id = parse_element_id(id)?;
+        let mut rng = rand::rng();
+        let mut result = Err(SyncError::SessionNotFound(session_id.to_string()));
+        self.store.update(session_id, |scene| {
+            result = match scene.get_element_mut(element_id) {
+                None => Err(SyncError::ElementNotFound(id.to_string())),
+                Some(element) => match &element.kind {
+                    canvas_core::ElementKind::Randomizer { source, .. } => {
+                        let source = source.clone();
+                        canvas_core::randomizer::randomize(&mut element.kind, by, &mut |n| {
+                            rng.random_range(0..n)
+                        })
+                        .map(|outcome| {
+                            let description = source.describe(&outcome.values);
+                            Some((outcome, description))
+                        })
+                        .map_err(SyncError::from)
+                    }
+                    _ => Ok(None),
+                },
+            };
+        })?;
+        let drawn = result?;
+
+        if drawn.is_some() {
+            self.broadcast_scene_change(session_id, SyncOrigin::Local);
+        }
+        Ok(drawn)
+    }
+
+    /// Move a floor plan marker and broadcast the plan, so every peer sees
+    /// the new layout.
+    ///
+    /// Returns the marker's position after clamping to the plan, or `None`,
+    /// without changing the scene, if the element is not a floor plan.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SyncError`] if the ID is invalid, the element is not found,
+    /// the plan is locked or has no such marker.
+    pub fn move_marker(
+        &self,
+        session_id: &str,
+        id: &str,
+        marker: &str,
+        x: f32,
+        y: f32,
+    ) -> Result<Option<(f32, f32)>, SyncError> {
+        self.ensure_writable()?;
+        let element_id = parse_element_id(id)?;
+        let mut result = Err(SyncError::SessionNotFound(session_id.to_string()));
+        self.store.update(session_id, |scene| {
+            result = match scene.get_element_mut(element_id) {
+                None => Err(SyncError::ElementNotFound(id.to_string())),
+                Some(element) => {
+                    if matches!(element.kind, canvas_core::ElementKind::FloorPlan { .. }) {
+                        canvas_core::floor_plan::move_marker(&mut element.kind, marker, x, y)
+                            .map_err(SyncError::from)
+                            .map(|()| match &element.kind {
+                                canvas_core::ElementKind::FloorPlan { markers, .. } => {
+                                    markers.iter().find(|m| m.id == marker).map(|m| (m.x, m.y))
+                                }
+                                _ => None,
+                            })
+                    } else {
+                        Ok(None)
+                    }
+                }
+            };
+        })?;
+        let moved = result?;
+
+        if moved.is_some() {
+            self.broadcast_scene_change(session_id, SyncOrigin::Local);
+        }
+        Ok(moved)
+    }
+
+    /// Collapse or expand a tree node and broadcast the tree, so every peer
+    /// sees the same branches. Auto-sized trees are resized to fit.
+    ///
+    /// Returns `false`, without changing the scene, if the element is not a
+    /// tree.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SyncError`] if the ID is invalid, the element is not found,
+    /// or the tree has no such node or it has no children.
+    pub fn set_node_collapsed(
+        &self,
+        session_id: &str,
+        id: &str,
+        node: &str,
+        collapsed: bool,
+    ) -> Result<bool, SyncError> {
+        self.ensure_writable()?;
+        let element_id = parse_element_id(id)?;
+        let mut result = Err(SyncError::SessionNotFound(session_id.to_string()));
+        self.store.update(session_id, |scene| {
+            result = match scene.get_element_mut(element_id) {
+                None => Err(SyncError::ElementNotFound(id.to_string())),
+                Some(element) => {
+                    if matches!(element.kind, canvas_core::ElementKind::Tree { .. }) {
+                        canvas_core::tree::set_collapsed(&mut element.kind, node, collapsed)
+                            .map_err(SyncError::from)
+                            .map(|()| {
+                                element.apply_auto_size(&canvas_core::MeasureContext::default());
+                                true
+                            })
+                    } else {
+                        Ok(false)
+                    }
+                }
+            };
+        })?;
+        let changed = result?;
+
+        if changed {
+            self.broadcast_scene_change(session_id, SyncOrigin::Local);
+        }
+        Ok(changed)
+    }
+
+    /// Record a peer's view and broadcast it, making the peer the presenter.
+    ///
+    /// A new presenter replaces any previous one in the session.
+    pub fn update_view_state(&self, session_id: &str, peer_id: &str, view: ViewState) {
+        if let Ok(mut map) = self.presenters.write() {
+            map.insert(session_id.to_string(), (peer_id.to_string(), view));
+        }
+        let message = ServerMessage::ViewState {
+            presenter_id: peer_id.to_string(),
+            view,
+        };
+        self.broadcast(session_id, message, SyncOrigin::Local);
+    }
+
+    /// Stop `peer_id` presenting in a session.
+    ///
+    /// Returns `true` (and notifies followers) if the peer was the presenter.
+    pub fn stop_presenting(&self, session_id: &str, peer_id: &str) -> bool {
+        let stopped = match self.presenters.write() {
+            Ok(mut map) => {
+                if map.get(session_id).is_some_and(|(id, _)| id == peer_id) {
+                    map.remove(session_id);
+                    true
+                } else {
+                    false
+                }
+            }
+            Err(_) => false,
+        };
+        if stopped {
+            let message = ServerMessage::PresenterStopped {
+                presenter_id: peer_id.to_string(),
+            };
+            self.broadcast(session_id, message, SyncOrigin::Local);
+        }
+        stopped
+    }
+
+    /// The current presenter's view for a session, if anyone is presenting.
+    #[must_use]
+    pub fn presenter_view(&self, session_id: &str) -> Option<ServerMessage> {
+        let map = self.presenters.read().ok()?;
+        let (presenter_id, view) = map.get(session_id)?;
+        Some(ServerMessage::ViewState {
+            presenter_id: presenter_id.clone(),
+            view: *view,
+        })
+    }
+
+    /// Get full scene state as a server message.
+    #[must_use]
+    pub fn get_scene_update(&self, session_id: &str) -> ServerMessage {
+        let document = self.store.scene_document(session_id);
+        ServerMessage::SceneUpdate { scene: document }
+    }
+
+    /// Process queued offline operations with full error tracking.
+    ///
+    /// Returns a detailed result with processed/failed counts and error details
+    /// for operations that could not be applied. Changes are attributed to
+    /// `author` when given.
+    #[must_use]
+    pub fn process_queue(
+        &self,
+        session_id: &str,
+        operations: Vec<QueuedOperation>,
+        author: Option<&str>,
+    ) -> ProcessQueueResult {
+        let mut processed_count = 0;
+        let mut failed_ops: Vec<(QueuedOperation, String)> = Vec::new();
+
+        for op in operations {
+            let result = match &op {
+                QueuedOperation::Add { element, .. } => {
+                    self.add_element(session_id, element, author).map(|_| ())
+                }
+                QueuedOperation::Update { id, changes, .. } => self
+                    .update_element(session_id, id, changes, author)
+                    .map(|_| ()),
+                QueuedOperation::Remove { id, .. } => self.remove_element(session_id, id),
+            };
+
+            match result {
+                Ok(()) => processed_count += 1,
+                Err(err) => {
+                    let error_msg = err.to_string();
                     tracing::warn!(
                         "process_queue failed for op {:?}: {}",
                         op.operation_type(),
@@ -1651,6 +2146,40 @@ impl SyncState {
         }
     }
 
+    /// Apply a batch of element operations atomically.
+    ///
+    /// The operations run against a copy of the scene, which replaces the
+    /// live scene only if all of them succeed. A single scene change is
+    /// broadcast. Returns the IDs of added elements. Changes are attributed
+    /// to `author` when given.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SyncError::BatchFailed`] naming the first operation that
+    /// failed; the scene is left unchanged.
+    pub fn apply_batch(
+        &self,
+        session_id: &str,
+        operations: &[BatchOperation],
+        author: Option<&str>,
+    ) -> Result<Vec<ElementId>, SyncError> {
+        self.ensure_writable()?;
+        let _ = self.store.get_or_create(session_id);
+
+        let mut outcome = Ok(Vec::new());
+        self.store.update(session_id, |scene| {
+            let mut draft = scene.clone();
+            outcome = apply_batch_to_scene(&mut draft, operations, author);
+            if outcome.is_ok() {
+                *scene = draft;
+            }
+        })?;
+        let added = outcome?;
+
+        self.broadcast_scene_change(session_id, SyncOrigin::Local);
+        Ok(added)
+    }
+
     /// Get total conflict count since server start.
     #[must_use]
     pub fn total_conflict_count(&self) -> u64 {
@@ -1704,6 +2233,20 @@ pub enum SyncError {
     /// Serialization error.
     #[error("Serialization error: {0}")]
     Serialization(#[from] serde_json::Error),
+    /// This server is a read replica or an unpromoted warm standby.
+    #[error("Read-only server: send writes to the primary")]
+    ReadOnly,
+    /// The client is signed in as a viewer.
+    #[error("Viewers cannot edit the canvas")]
+    ViewOnly,
+    /// An operation in a batch failed, so none were applied.
+    #[error("Batch operation {index} failed: {source}")]
+    BatchFailed {
+        /// Position of the failed operation in the batch.
+        index: usize,
+        /// Why it failed.
+        source: Box<SyncError>,
+    },
 }
 
 impl From<StoreError> for SyncError {
@@ -1719,6 +2262,15 @@ impl From<StoreError> for SyncError {
     }
 }
 
+impl From<CanvasError> for SyncError {
+    fn from(e: CanvasError) -> Self {
+        match e {
+            CanvasError::ElementNotFound(id) => SyncError::ElementNotFound(id),
+            other => SyncError::InvalidMessage(other.to_string()),
+        }
+    }
+}
+
 /// A failed operation with its error message.
 ///
 /// This struct captures detailed information about why an operation failed,
@@ -2459,6 +3011,21 @@ pub struct ClientConnection {
     /// Event receiver for broadcasts.
     #[allow(dead_code)]
     event_rx: broadcast::Receiver<SyncEvent>,
+    /// Maximum elements per scene message, or `None` to send scenes whole.
+    page_size: Option<usize>,
+    /// Whether this client may only read scenes.
+    read_only: bool,
+    /// Redacted scene last sent to a read-only client, which its patches
+    /// are computed against.
+    redacted_base: Option<SceneDocument>,
+    /// Identity whose quota this client uses; its peer ID unless set.
+    identity: String,
+    /// Scene pages still to be sent for the current load.
+    pending_pages: VecDeque<ServerMessage>,
+    /// This client's ink edits, oldest first, for `undo_ink`.
+    ink_undo: VecDeque<InkEdit>,
+    /// Undone ink edits, most recent last, for `redo_ink`.
+    ink_redo: Vec<InkEdit>,
 }
 
 impl ClientConnection {
@@ -2466,13 +3033,7 @@ impl ClientConnection {
     #[allow(dead_code)]
     #[must_use]
     pub fn new(state: SyncState) -> Self {
-        let event_rx = state.subscribe();
-        Self {
-            peer_id: Uuid::new_v4().to_string(),
-            session_id: "default".to_string(),
-            state,
-            event_rx,
-        }
+        Self::with_peer_id(state, Uuid::new_v4().to_string())
     }
 
     /// Create a new client connection with a specific peer ID.
@@ -2480,10 +3041,17 @@ impl ClientConnection {
     pub fn with_peer_id(state: SyncState, peer_id: String) -> Self {
         let event_rx = state.subscribe();
         Self {
+            identity: peer_id.clone(),
             peer_id,
             session_id: "default".to_string(),
             state,
             event_rx,
+            page_size: None,
+            read_only: false,
+            redacted_base: None,
+            pending_pages: VecDeque::new(),
+            ink_undo: VecDeque::new(),
+            ink_redo: Vec::new(),
         }
     }
 
@@ -2493,45 +3061,278 @@ impl ClientConnection {
         &self.peer_id
     }
 
-    /// Create a validation error response.
-    fn validation_error(err: &ValidationError, message_id: Option<String>) -> ServerMessage {
-        ServerMessage::Error {
-            code: "validation_error".to_string(),
-            message: err.to_string(),
-            message_id,
-        }
+    /// Load scenes in pages of at most `page_size` elements.
+    pub fn set_page_size(&mut self, page_size: Option<usize>) {
+        self.page_size = page_size;
     }
 
-    /// Error returned when legacy signaling is disabled.
-    fn legacy_disabled_error(message_id: Option<String>) -> ServerMessage {
-        ServerMessage::Error {
-            code: "legacy_signaling_disabled".to_string(),
-            message: "Legacy WebRTC signaling is disabled; Communitas handles calls upstream."
-                .to_string(),
-            message_id,
-        }
+    /// Reject this client's writes, e.g. for users signed in as viewers.
+    ///
+    /// Read-only clients are only ever sent the
+    /// [`redacted`](canvas_core::Scene::redacted) scene.
+    pub fn set_read_only(&mut self, read_only: bool) {
+        self.read_only = read_only;
     }
 
-    /// Handle an incoming client message.
-    pub fn handle_message(&mut self, msg: ClientMessage) -> Option<ServerMessage> {
-        match msg {
-            ClientMessage::Subscribe { session_id } => {
-                // Validate session_id
-                if let Err(e) = validate_session_id(&session_id) {
-                    tracing::warn!("Invalid session_id from peer {}: {}", self.peer_id, e);
-                    record_validation_failure("session_id");
-                    return Some(Self::validation_error(&e, None));
-                }
-                self.session_id = session_id.clone();
-                self.state.record_access(&self.session_id);
-                // Send current scene state
-                Some(self.state.get_scene_update(&self.session_id))
-            }
-            ClientMessage::AddElement {
-                element,
+    /// Count this client's usage against the quota of `identity`, e.g. a
+    /// signed-in user or agent, rather than its own.
+    pub fn set_identity(&mut self, identity: impl Into<String>) {
+        self.identity = identity.into();
+    }
+
+    /// Identity whose quota this client uses.
+    #[must_use]
+    pub fn identity(&self) -> &str {
+        &self.identity
+    }
+
+    /// Error for a message adding more elements than the client's quota
+    /// has left, or `None` if the quota covers it.
+    fn element_quota_error(&self, msg: &ClientMessage) -> Option<ServerMessage> {
+        let (count, message_id) = match msg {
+            ClientMessage::AddElement { message_id, .. } => (1, message_id),
+            ClientMessage::Batch {
+                operations,
                 message_id,
-            } => {
-                let result = self.state.add_element(&self.session_id, &element);
+            } => (
+                operations
+                    .iter()
+                    .filter(|op| matches!(op, BatchOperation::Add { .. }))
+                    .count(),
+                message_id,
+            ),
+            _ => return None,
+        };
+        let count = u32::try_from(count).unwrap_or(u32::MAX);
+        let error = self
+            .state
+            .quotas()
+            .try_use(&self.identity, Budget::Elements, count)
+            .err()?;
+        record_rate_limited("elements");
+        Some(ServerMessage::Error {
+            code: "quota_exceeded".to_string(),
+            message: error.to_string(),
+            message_id: message_id.clone(),
+        })
+    }
+
+    /// Current scene state, with any further pages queued for
+    /// [`next_pending_page`](Self::next_pending_page).
+    pub fn load_scene(&mut self) -> ServerMessage {
+        let document = if self.read_only {
+            let document = self.redacted_document();
+            self.redacted_base = Some(document.clone());
+            document
+        } else {
+            self.state.scene_document(&self.session_id)
+        };
+        let Some(page_size) = self.page_size else {
+            return ServerMessage::SceneUpdate { scene: document };
+        };
+        let (first, pages) = document.paginate(page_size);
+        self.pending_pages = pages
+            .into_iter()
+            .map(|page| ServerMessage::ScenePage { page })
+            .collect();
+        ServerMessage::SceneUpdate { scene: first }
+    }
+
+    /// The session's scene with sensitive elements redacted.
+    fn redacted_document(&self) -> SceneDocument {
+        let scene = self.state.get_or_create_scene(&self.session_id).redacted();
+        SceneDocument::from_scene(&self.session_id, &scene, current_timestamp())
+    }
+
+    /// The form of a broadcast `message` this client may see.
+    ///
+    /// Read-only clients get scene and element changes as a patch between
+    /// redacted scenes instead, or nothing if the redacted scene has not
+    /// changed.
+    pub fn visible_event(&mut self, message: ServerMessage) -> Option<ServerMessage> {
+        if !self.read_only {
+            return Some(message);
+        }
+        match message {
+            ServerMessage::SceneUpdate { .. }
+            | ServerMessage::ScenePatch { .. }
+            | ServerMessage::ScenePage { .. }
+            | ServerMessage::ElementAdded { .. }
+            | ServerMessage::ElementUpdated { .. }
+            | ServerMessage::ElementRemoved { .. } => {
+                let document = self.redacted_document();
+                let previous = self.redacted_base.replace(document.clone());
+                match previous {
+                    Some(previous) => {
+                        let patch = previous.diff(&document);
+                        (!patch.is_empty()).then_some(ServerMessage::ScenePatch { patch })
+                    }
+                    None => Some(ServerMessage::SceneUpdate { scene: document }),
+                }
+            }
+            message => Some(message),
+        }
+    }
+
+    /// Take the next queued scene page, if a paged load is in progress.
+    pub fn next_pending_page(&mut self) -> Option<ServerMessage> {
+        self.pending_pages.pop_front()
+    }
+
+    /// Record a new ink edit for undo, forgetting anything undone.
+    fn push_ink_edit(&mut self, edit: InkEdit) {
+        if edit.is_empty() {
+            return;
+        }
+        if self.ink_undo.len() == MAX_INK_HISTORY {
+            self.ink_undo.pop_front();
+        }
+        self.ink_undo.push_back(edit);
+        self.ink_redo.clear();
+    }
+
+    /// Undo the most recent ink edit, returning the edit applied.
+    ///
+    /// An edit that no longer applies is dropped from the history.
+    fn undo_ink(&mut self) -> Result<InkEdit, SyncError> {
+        let edit = self
+            .ink_undo
+            .pop_back()
+            .ok_or_else(|| SyncError::InvalidMessage("Nothing to undo".to_string()))?;
+        let inverse = edit.inverse();
+        self.state.apply_ink_edit(&self.session_id, &inverse)?;
+        self.ink_redo.push(edit);
+        Ok(inverse)
+    }
+
+    /// Redo the most recently undone ink edit, returning it.
+    fn redo_ink(&mut self) -> Result<InkEdit, SyncError> {
+        let edit = self
+            .ink_redo
+            .pop()
+            .ok_or_else(|| SyncError::InvalidMessage("Nothing to redo".to_string()))?;
+        self.state.apply_ink_edit(&self.session_id, &edit)?;
+        self.ink_undo.push_back(edit.clone());
+        Ok(edit)
+    }
+
+    /// Ack for an ink edit: the strokes it removed and added or changed.
+    fn ink_ack(message_id: String, edit: &InkEdit) -> ServerMessage {
+        ServerMessage::Ack {
+            message_id,
+            success: true,
+            result: Some(serde_json::json!({
+                "removed": edit.removed().iter().map(ToString::to_string).collect::<Vec<_>>(),
+                "changed": edit.changed().iter().map(ToString::to_string).collect::<Vec<_>>(),
+            })),
+        }
+    }
+
+    /// Create a validation error response.
+    fn validation_error(err: &ValidationError, message_id: Option<String>) -> ServerMessage {
+        ServerMessage::Error {
+            code: "validation_error".to_string(),
+            message: err.to_string(),
+            message_id,
+        }
+    }
+
+    /// Error returned when legacy signaling is disabled.
+    fn legacy_disabled_error(message_id: Option<String>) -> ServerMessage {
+        ServerMessage::Error {
+            code: "legacy_signaling_disabled".to_string(),
+            message: "Legacy WebRTC signaling is disabled; Communitas handles calls upstream."
+                .to_string(),
+            message_id,
+        }
+    }
+
+    /// Error returned for a write refused with `reason`, or `None` if
+    /// `msg` does not write.
+    fn read_only_error(msg: &ClientMessage, reason: &SyncError) -> Option<ServerMessage> {
+        let message_id = match msg {
+            ClientMessage::AddElement { message_id, .. }
+            | ClientMessage::UpdateElement { message_id, .. }
+            | ClientMessage::RemoveElement { message_id, .. }
+            | ClientMessage::Batch { message_id, .. }
+            | ClientMessage::Group { message_id, .. }
+            | ClientMessage::Ungroup { message_id, .. }
+            | ClientMessage::EraseInk { message_id, .. }
+            | ClientMessage::RestyleInk { message_id, .. }
+            | ClientMessage::UndoInk { message_id }
+            | ClientMessage::RedoInk { message_id }
+            | ClientMessage::Camera { message_id, .. }
+            | ClientMessage::SetAnchor { message_id, .. }
+            | ClientMessage::RemoveAnchor { message_id, .. }
+            | ClientMessage::SetTheme { message_id, .. }
+            | ClientMessage::SetBranding { message_id, .. }
+            | ClientMessage::UpdateDataset { message_id, .. } => message_id.clone(),
+            // Element actions that rewrite the shared scene
+            ClientMessage::Interaction {
+                interaction_type,
+                data,
+                message_id,
+                ..
+            } if interaction_type == "action"
+                && matches!(
+                    data.get("action").and_then(serde_json::Value::as_str),
+                    Some("vote" | "randomize" | "move_marker" | "toggle_node")
+                ) =>
+            {
+                message_id.clone()
+            }
+            ClientMessage::SyncQueue { .. }
+            | ClientMessage::ViewState { .. }
+            | ClientMessage::StopPresenting => None,
+            _ => return None,
+        };
+        Some(ServerMessage::Error {
+            code: "read_only".to_string(),
+            message: reason.to_string(),
+            message_id,
+        })
+    }
+
+    /// Handle an incoming client message.
+    pub fn handle_message(&mut self, msg: ClientMessage) -> Option<ServerMessage> {
+        let refusal = if self.state.is_read_only() {
+            Some(SyncError::ReadOnly)
+        } else if self.read_only {
+            Some(SyncError::ViewOnly)
+        } else {
+            None
+        };
+        if let Some(error) = refusal.and_then(|reason| Self::read_only_error(&msg, &reason)) {
+            return Some(error);
+        }
+        if let Some(error) = self.element_quota_error(&msg) {
+            return Some(error);
+        }
+        match msg {
+            ClientMessage::Subscribe { session_id } => {
+                // Validate session_id
+                if let Err(e) = validate_session_id(&session_id) {
+                    tracing::warn!("Invalid session_id from peer {}: {}", self.peer_id, e);
+                    record_validation_failure("session_id");
+                    return Some(Self::validation_error(&e, None));
+                }
+                if self.session_id != session_id {
+                    // Ink history belongs to the scene it was made in
+                    self.ink_undo.clear();
+                    self.ink_redo.clear();
+                }
+                self.session_id = session_id.clone();
+                self.state.record_access(&self.session_id);
+                // Send current scene state
+                Some(self.load_scene())
+            }
+            ClientMessage::AddElement {
+                element,
+                message_id,
+            } => {
+                let result =
+                    self.state
+                        .add_element(&self.session_id, &element, Some(&self.peer_id));
                 message_id.map(|mid| match result {
                     Ok(id) => ServerMessage::Ack {
                         message_id: mid,
@@ -2556,7 +3357,9 @@ impl ClientConnection {
                     record_validation_failure("element_id");
                     return Some(Self::validation_error(&e, message_id));
                 }
-                let result = self.state.update_element(&self.session_id, &id, &changes);
+                let result =
+                    self.state
+                        .update_element(&self.session_id, &id, &changes, Some(&self.peer_id));
                 message_id.map(|mid| match result {
                     Ok(_) => ServerMessage::Ack {
                         message_id: mid,
@@ -2595,7 +3398,9 @@ impl ClientConnection {
                 timestamp: current_timestamp(),
             }),
             ClientMessage::SyncQueue { operations } => {
-                let result = self.state.process_queue(&self.session_id, operations);
+                let result =
+                    self.state
+                        .process_queue(&self.session_id, operations, Some(&self.peer_id));
                 // Log any failed operations for debugging
                 for (op, err) in &result.failed_ops {
                     tracing::debug!(
@@ -2607,7 +3412,244 @@ impl ClientConnection {
                 }
                 Some(result.into_server_message())
             }
-            ClientMessage::GetScene => Some(self.state.get_scene_update(&self.session_id)),
+            ClientMessage::GetScene => Some(self.load_scene()),
+            ClientMessage::Batch {
+                operations,
+                message_id,
+            } => {
+                let result =
+                    self.state
+                        .apply_batch(&self.session_id, &operations, Some(&self.peer_id));
+                message_id.map(|mid| match result {
+                    Ok(ids) => ServerMessage::Ack {
+                        message_id: mid,
+                        success: true,
+                        result: Some(serde_json::json!({
+                            "applied": operations.len(),
+                            "ids": ids.iter().map(ToString::to_string).collect::<Vec<_>>()
+                        })),
+                    },
+                    Err(e) => ServerMessage::Error {
+                        code: "batch_failed".to_string(),
+                        message: e.to_string(),
+                        message_id: Some(mid),
+                    },
+                })
+            }
+            ClientMessage::Group {
+                element_ids,
+                message_id,
+            } => {
+                let result = self.state.group_elements(&self.session_id, &element_ids);
+                message_id.map(|mid| match result {
+                    Ok(id) => ServerMessage::Ack {
+                        message_id: mid,
+                        success: true,
+                        result: Some(serde_json::json!({ "id": id.to_string() })),
+                    },
+                    Err(e) => ServerMessage::Error {
+                        code: "group_failed".to_string(),
+                        message: e.to_string(),
+                        message_id: Some(mid),
+                    },
+                })
+            }
+            ClientMessage::Ungroup { id, message_id } => {
+                let result = self.state.ungroup_element(&self.session_id, &id);
+                message_id.map(|mid| match result {
+                    Ok(children) => ServerMessage::Ack {
+                        message_id: mid,
+                        success: true,
+                        result: Some(serde_json::json!({
+                            "children": children.iter().map(ToString::to_string).collect::<Vec<_>>()
+                        })),
+                    },
+                    Err(e) => ServerMessage::Error {
+                        code: "ungroup_failed".to_string(),
+                        message: e.to_string(),
+                        message_id: Some(mid),
+                    },
+                })
+            }
+            ClientMessage::EraseInk {
+                x,
+                y,
+                radius,
+                mode,
+                message_id,
+            } => {
+                let result = self.state.erase_ink(&self.session_id, (x, y), radius, mode);
+                if let Ok(edit) = &result {
+                    self.push_ink_edit(edit.clone());
+                }
+                message_id.map(|mid| match result {
+                    Ok(edit) => Self::ink_ack(mid, &edit),
+                    Err(e) => ServerMessage::Error {
+                        code: "erase_failed".to_string(),
+                        message: e.to_string(),
+                        message_id: Some(mid),
+                    },
+                })
+            }
+            ClientMessage::RestyleInk {
+                element_ids,
+                color,
+                width,
+                message_id,
+            } => {
+                let result = self
+                    .state
+                    .restyle_ink(&self.session_id, &element_ids, color, width);
+                if let Ok(edit) = &result {
+                    self.push_ink_edit(edit.clone());
+                }
+                message_id.map(|mid| match result {
+                    Ok(edit) => Self::ink_ack(mid, &edit),
+                    Err(e) => ServerMessage::Error {
+                        code: "restyle_failed".to_string(),
+                        message: e.to_string(),
+                        message_id: Some(mid),
+                    },
+                })
+            }
+            ClientMessage::UndoInk { message_id } => {
+                let result = self.undo_ink();
+                message_id.map(|mid| match result {
+                    Ok(edit) => Self::ink_ack(mid, &edit),
+                    Err(e) => ServerMessage::Error {
+                        code: "undo_failed".to_string(),
+                        message: e.to_string(),
+                        message_id: Some(mid),
+                    },
+                })
+            }
+            ClientMessage::RedoInk { message_id } => {
+                let result = self.redo_ink();
+                message_id.map(|mid| match result {
+                    Ok(edit) => Self::ink_ack(mid, &edit),
+                    Err(e) => ServerMessage::Error {
+                        code: "redo_failed".to_string(),
+                        message: e.to_string(),
+                        message_id: Some(mid),
+                    },
+                })
+            }
+            ClientMessage::ViewState { view } => {
+                self.state
+                    .update_view_state(&self.session_id, &self.peer_id, view);
+                None
+            }
+            ClientMessage::StopPresenting => {
+                self.state.stop_presenting(&self.session_id, &self.peer_id);
+                None
+            }
+            ClientMessage::GetQuota => Some(ServerMessage::QuotaStatus {
+                quota: self.state.quotas().status(&self.identity),
+            }),
+            ClientMessage::Camera {
+                command,
+                options,
+                message_id,
+            } => {
+                let result = self
+                    .state
+                    .apply_camera_command(&self.session_id, &command, &options);
+                message_id.map(|mid| match result {
+                    Ok(frame) => ServerMessage::Ack {
+                        message_id: mid,
+                        success: true,
+                        result: serde_json::to_value(frame).ok(),
+                    },
+                    Err(e) => ServerMessage::Error {
+                        code: "camera_failed".to_string(),
+                        message: e.to_string(),
+                        message_id: Some(mid),
+                    },
+                })
+            }
+            ClientMessage::SetAnchor { anchor, message_id } => {
+                let result = self.state.set_anchor(&self.session_id, anchor);
+                message_id.map(|mid| match result {
+                    Ok(()) => ServerMessage::Ack {
+                        message_id: mid,
+                        success: true,
+                        result: None,
+                    },
+                    Err(e) => ServerMessage::Error {
+                        code: "anchor_failed".to_string(),
+                        message: e.to_string(),
+                        message_id: Some(mid),
+                    },
+                })
+            }
+            ClientMessage::RemoveAnchor { name, message_id } => {
+                let result = self.state.remove_anchor(&self.session_id, &name);
+                message_id.map(|mid| match result {
+                    Ok(anchor) => ServerMessage::Ack {
+                        message_id: mid,
+                        success: true,
+                        result: serde_json::to_value(anchor).ok(),
+                    },
+                    Err(e) => ServerMessage::Error {
+                        code: "anchor_failed".to_string(),
+                        message: e.to_string(),
+                        message_id: Some(mid),
+                    },
+                })
+            }
+            ClientMessage::SetTheme { theme, message_id } => {
+                let result = self.state.set_theme(&self.session_id, theme);
+                message_id.map(|mid| match result {
+                    Ok(()) => ServerMessage::Ack {
+                        message_id: mid,
+                        success: true,
+                        result: None,
+                    },
+                    Err(e) => ServerMessage::Error {
+                        code: "theme_failed".to_string(),
+                        message: e.to_string(),
+                        message_id: Some(mid),
+                    },
+                })
+            }
+            ClientMessage::SetBranding {
+                branding,
+                message_id,
+            } => {
+                let result = self.state.set_branding(&self.session_id, branding);
+                message_id.map(|mid| match result {
+                    Ok(()) => ServerMessage::Ack {
+                        message_id: mid,
+                        success: true,
+                        result: None,
+                    },
+                    Err(e) => ServerMessage::Error {
+                        code: "branding_failed".to_string(),
+                        message: e.to_string(),
+                        message_id: Some(mid),
+                    },
+                })
+            }
+            ClientMessage::UpdateDataset { delta, message_id } => {
+                let result = self.state.update_dataset(&self.session_id, &delta);
+                message_id.map(|mid| match result {
+                    Ok(refreshed) => ServerMessage::Ack {
+                        message_id: mid,
+                        success: true,
+                        result: Some(serde_json::json!({
+                            "refreshed": refreshed
+                                .iter()
+                                .map(ToString::to_string)
+                                .collect::<Vec<_>>(),
+                        })),
+                    },
+                    Err(e) => ServerMessage::Error {
+                        code: "dataset_failed".to_string(),
+                        message: e.to_string(),
+                        message_id: Some(mid),
+                    },
+                })
+            }
 
             // WebRTC signaling messages - relay to target peer
             ClientMessage::StartCall {
@@ -3047,21 +4089,202 @@ impl ClientConnection {
                             center_y,
                         }
                     }
-                    _ => {
-                        return Some(ServerMessage::Error {
-                            code: "INVALID_INTERACTION".to_string(),
-                            message: format!("Unknown interaction type: {interaction_type}"),
-                            message_id,
-                        });
-                    }
-                };
-
-                // Broadcast to AG-UI clients via the interaction channel
-                self.state
-                    .broadcast_interaction(&self.session_id, interaction);
-
-                // Acknowledge if message_id was provided
-                message_id.map(|id| ServerMessage::Ack {
+                    // Declarative element interactions (see canvas_core::interaction)
+                    "action" => {
+                        let action = data
+                            .get("action")
+                            .and_then(|v| v.as_str())
+                            .unwrap_or_default()
+                            .to_string();
+                        let mut payload = data.get("payload").cloned();
+
+                        // Poll votes are tallied here so every peer sees
+                        // the results; agents get them with the event
+                        if let (Some(id), "vote") = (&element_id, action.as_str()) {
+                            let option = payload
+                                .as_ref()
+                                .and_then(|p| p.get("option"))
+                                .and_then(serde_json::Value::as_u64)
+                                .and_then(|o| usize::try_from(o).ok())
+                                .unwrap_or(usize::MAX);
+                            match self.state.record_vote(
+                                &self.session_id,
+                                id,
+                                &self.peer_id,
+                                option,
+                            ) {
+                                Ok(Some(results)) => {
+                                    if let Some(fields) =
+                                        payload.as_mut().and_then(|p| p.as_object_mut())
+                                    {
+                                        fields.insert(
+                                            "voter".to_string(),
+                                            serde_json::json!(self.peer_id),
+                                        );
+                                        fields.insert(
+                                            "results".to_string(),
+                                            serde_json::json!(results),
+                                        );
+                                    }
+                                }
+                                Ok(None) => {}
+                                Err(e) => {
+                                    return Some(ServerMessage::Error {
+                                        code: "vote_failed".to_string(),
+                                        message: e.to_string(),
+                                        message_id,
+                                    });
+                                }
+                            }
+                        }
+
+                        // Randomizer outcomes are drawn here, never by clients
+                        if let (Some(id), "randomize") = (&element_id, action.as_str()) {
+                            match self.state.randomize(&self.session_id, id, &self.peer_id) {
+                                Ok(Some((outcome, description))) => {
+                                    let fields = payload
+                                        .get_or_insert_with(|| serde_json::json!({}))
+                                        .as_object_mut();
+                                    if let Some(fields) = fields {
+                                        fields.insert(
+                                            "outcome".to_string(),
+                                            serde_json::json!(outcome),
+                                        );
+                                        fields.insert(
+                                            "result".to_string(),
+                                            serde_json::json!(description),
+                                        );
+                                    }
+                                }
+                                Ok(None) => {}
+                                Err(e) => {
+                                    return Some(ServerMessage::Error {
+                                        code: "randomize_failed".to_string(),
+                                        message: e.to_string(),
+                                        message_id,
+                                    });
+                                }
+                            }
+                        }
+
+                        // Dragged floor plan markers are moved for everyone
+                        if let (Some(id), "move_marker") = (&element_id, action.as_str()) {
+                            let field = |key: &str| {
+                                payload
+                                    .as_ref()
+                                    .and_then(|p| p.get(key))
+                                    .and_then(serde_json::Value::as_f64)
+                            };
+                            let marker = payload
+                                .as_ref()
+                                .and_then(|p| p.get("marker"))
+                                .and_then(serde_json::Value::as_str)
+                                .unwrap_or_default()
+                                .to_string();
+                            #[allow(clippy::cast_possible_truncation)]
+                            let (x, y) = (
+                                field("x").unwrap_or(f64::NAN) as f32,
+                                field("y").unwrap_or(f64::NAN) as f32,
+                            );
+                            match self.state.move_marker(&self.session_id, id, &marker, x, y) {
+                                Ok(Some((x, y))) => {
+                                    if let Some(fields) =
+                                        payload.as_mut().and_then(|p| p.as_object_mut())
+                                    {
+                                        fields.insert("x".to_string(), serde_json::json!(x));
+                                        fields.insert("y".to_string(), serde_json::json!(y));
+                                    }
+                                }
+                                Ok(None) => {}
+                                Err(e) => {
+                                    return Some(ServerMessage::Error {
+                                        code: "move_marker_failed".to_string(),
+                                        message: e.to_string(),
+                                        message_id,
+                                    });
+                                }
+                            }
+                        }
+
+                        // Collapsed and expanded tree nodes are shared
+                        if let (Some(id), "toggle_node") = (&element_id, action.as_str()) {
+                            let field = |key: &str| payload.as_ref().and_then(|p| p.get(key));
+                            let node = field("node")
+                                .and_then(serde_json::Value::as_str)
+                                .unwrap_or_default()
+                                .to_string();
+                            let collapsed = field("collapsed")
+                                .and_then(serde_json::Value::as_bool)
+                                .unwrap_or(false);
+                            if let Err(e) = self.state.set_node_collapsed(
+                                &self.session_id,
+                                id,
+                                &node,
+                                collapsed,
+                            ) {
+                                return Some(ServerMessage::Error {
+                                    code: "toggle_node_failed".to_string(),
+                                    message: e.to_string(),
+                                    message_id,
+                                });
+                            }
+                        }
+
+                        InteractionEvent::Action {
+                            element_id: element_id.unwrap_or_default(),
+                            action,
+                            payload,
+                        }
+                    }
+                    "drag" => {
+                        let action = data
+                            .get("action")
+                            .and_then(|v| v.as_str())
+                            .map(str::to_string);
+                        #[allow(clippy::cast_possible_truncation)]
+                        let x = data.get("x").and_then(|v| v.as_f64()).unwrap_or(0.0) as f32;
+                        #[allow(clippy::cast_possible_truncation)]
+                        let y = data.get("y").and_then(|v| v.as_f64()).unwrap_or(0.0) as f32;
+
+                        InteractionEvent::Drag {
+                            element_id: element_id.unwrap_or_default(),
+                            action,
+                            x,
+                            y,
+                        }
+                    }
+                    "context_menu" => {
+                        #[allow(clippy::cast_possible_truncation)]
+                        let x = data.get("x").and_then(|v| v.as_f64()).unwrap_or(0.0) as f32;
+                        #[allow(clippy::cast_possible_truncation)]
+                        let y = data.get("y").and_then(|v| v.as_f64()).unwrap_or(0.0) as f32;
+                        let items = data
+                            .get("items")
+                            .and_then(|v| serde_json::from_value(v.clone()).ok())
+                            .unwrap_or_default();
+
+                        InteractionEvent::ContextMenu {
+                            element_id: element_id.unwrap_or_default(),
+                            x,
+                            y,
+                            items,
+                        }
+                    }
+                    _ => {
+                        return Some(ServerMessage::Error {
+                            code: "INVALID_INTERACTION".to_string(),
+                            message: format!("Unknown interaction type: {interaction_type}"),
+                            message_id,
+                        });
+                    }
+                };
+
+                // Broadcast to AG-UI clients via the interaction channel
+                self.state
+                    .broadcast_interaction(&self.session_id, interaction);
+
+                // Acknowledge if message_id was provided
+                message_id.map(|id| ServerMessage::Ack {
                     message_id: id,
                     success: true,
                     result: None,
@@ -3081,25 +4304,71 @@ impl ClientConnection {
     #[allow(dead_code)]
     pub fn try_recv_event(&mut self) -> Option<ServerMessage> {
         match self.event_rx.try_recv() {
-            Ok(event) if event.session_id == self.session_id => Some(event.message),
+            Ok(event) if event.session_id == self.session_id => self.visible_event(event.message),
             _ => None,
         }
     }
 }
 
+/// Per-connection options for [`handle_sync_socket_with_options`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SyncSocketOptions {
+    /// Wire encoding for server messages.
+    pub encoding: Encoding,
+    /// Maximum elements per scene message; larger scenes are sent in pages.
+    pub page_size: Option<usize>,
+    /// Reject the client's writes.
+    pub read_only: bool,
+    /// Identity whose quota the client uses, or `None` for a quota of its
+    /// own.
+    pub identity: Option<String>,
+}
+
 /// Handle a WebSocket connection with full sync support.
+///
+/// Server messages are sent as JSON text frames.
 pub async fn handle_sync_socket(socket: WebSocket, state: SyncState) {
+    handle_sync_socket_with_options(socket, state, SyncSocketOptions::default()).await;
+}
+
+/// Handle a WebSocket connection, sending server messages in `encoding`.
+///
+/// JSON is sent as text frames and `MessagePack` as binary frames. Incoming
+/// frames are decoded by frame type, so clients may send either.
+pub async fn handle_sync_socket_with_encoding(
+    socket: WebSocket,
+    state: SyncState,
+    encoding: Encoding,
+) {
+    let options = SyncSocketOptions {
+        encoding,
+        ..SyncSocketOptions::default()
+    };
+    handle_sync_socket_with_options(socket, state, options).await;
+}
+
+/// Handle a WebSocket connection with the given encoding and paging.
+pub async fn handle_sync_socket_with_options(
+    socket: WebSocket,
+    state: SyncState,
+    options: SyncSocketOptions,
+) {
+    let encoding = options.encoding;
     let (mut sender, mut receiver) = socket.split();
 
     // Generate peer ID and create client connection
     let peer_id = Uuid::new_v4().to_string();
     let mut client = ClientConnection::with_peer_id(state.clone(), peer_id.clone());
-
-    // Create per-connection rate limiter
-    let mut rate_limiter = RateLimiter::from_env();
+    client.set_page_size(options.page_size);
+    client.set_read_only(options.read_only);
+    if let Some(identity) = options.identity {
+        client.set_identity(identity);
+    }
+    let quotas = state.quotas().clone();
 
     // Register peer with the default session initially
     let mut peer_rx = state.register_peer(&peer_id, client.session_id());
+    state.record_access(client.session_id());
 
     // Send welcome message
     let welcome = ServerMessage::Welcome {
@@ -3107,11 +4376,12 @@ pub async fn handle_sync_socket(socket: WebSocket, state: SyncState) {
         session_id: client.session_id().to_string(),
         timestamp: current_timestamp(),
         legacy_signaling: Some(state.legacy_signaling_enabled()),
+        consistency: state.consistency(),
     };
 
-    match serde_json::to_string(&welcome) {
-        Ok(json) => {
-            if sender.send(Message::Text(json.into())).await.is_err() {
+    match encode_frame(&welcome, encoding) {
+        Ok(frame) => {
+            if sender.send(frame).await.is_err() {
                 state.unregister_peer(&peer_id);
                 return;
             }
@@ -3128,9 +4398,9 @@ pub async fn handle_sync_socket(socket: WebSocket, state: SyncState) {
         peer_id: peer_id.clone(),
     };
 
-    match serde_json::to_string(&peer_assigned) {
-        Ok(json) => {
-            if sender.send(Message::Text(json.into())).await.is_err() {
+    match encode_frame(&peer_assigned, encoding) {
+        Ok(frame) => {
+            if sender.send(frame).await.is_err() {
                 state.unregister_peer(&peer_id);
                 return;
             }
@@ -3143,10 +4413,10 @@ pub async fn handle_sync_socket(socket: WebSocket, state: SyncState) {
     }
 
     // Send initial scene state
-    let scene_update = client.state.get_scene_update(client.session_id());
-    match serde_json::to_string(&scene_update) {
-        Ok(json) => {
-            if sender.send(Message::Text(json.into())).await.is_err() {
+    let scene_update = client.load_scene();
+    match encode_frame(&scene_update, encoding) {
+        Ok(frame) => {
+            if sender.send(frame).await.is_err() {
                 state.unregister_peer(&peer_id);
                 return;
             }
@@ -3157,6 +4427,10 @@ pub async fn handle_sync_socket(socket: WebSocket, state: SyncState) {
             return;
         }
     }
+    if !send_pending_pages(&mut sender, &mut client, encoding).await {
+        state.unregister_peer(&peer_id);
+        return;
+    }
 
     // Send initial call state snapshot
     let call_snapshot = state.call_snapshot(client.session_id());
@@ -3165,9 +4439,9 @@ pub async fn handle_sync_socket(socket: WebSocket, state: SyncState) {
         call_id: call_snapshot.call_id,
         participants: call_snapshot.participants,
     };
-    match serde_json::to_string(&call_message) {
-        Ok(json) => {
-            if sender.send(Message::Text(json.into())).await.is_err() {
+    match encode_frame(&call_message, encoding) {
+        Ok(frame) => {
+            if sender.send(frame).await.is_err() {
                 state.unregister_peer(&peer_id);
                 return;
             }
@@ -3179,6 +4453,23 @@ pub async fn handle_sync_socket(socket: WebSocket, state: SyncState) {
         }
     }
 
+    // Send the current presenter's view, if anyone is presenting
+    if let Some(view_message) = state.presenter_view(client.session_id()) {
+        match encode_frame(&view_message, encoding) {
+            Ok(frame) => {
+                if sender.send(frame).await.is_err() {
+                    state.unregister_peer(&peer_id);
+                    return;
+                }
+            }
+            Err(e) => {
+                tracing::error!(peer_id = %peer_id, "Failed to serialize view_state message: {}", e);
+                state.unregister_peer(&peer_id);
+                return;
+            }
+        }
+    }
+
     // Subscribe to broadcast events
     let mut event_rx = state.subscribe();
 
@@ -3186,87 +4477,106 @@ pub async fn handle_sync_socket(socket: WebSocket, state: SyncState) {
         tokio::select! {
             // Handle incoming WebSocket messages
             msg = receiver.next() => {
-                match msg {
-                    Some(Ok(Message::Text(text))) => {
-                        // Check rate limit first
-                        if !rate_limiter.try_consume() {
-                            tracing::warn!("Rate limit exceeded for peer {}", peer_id);
-                            record_rate_limited("websocket");
-                            let retry_after = rate_limiter
-                                .time_until_available()
-                                .map_or(100, |d| d.as_millis().min(10000) as u64);
-                            let error = ServerMessage::Error {
-                                code: "rate_limited".to_string(),
-                                message: format!("Rate limit exceeded. Retry after {}ms", retry_after),
-                                message_id: None,
-                            };
-                            if let Ok(json) = serde_json::to_string(&error) {
-                                if sender.send(Message::Text(json.into())).await.is_err() {
-                                    break;
-                                }
-                            }
-                            continue;
-                        }
+                // Text frames carry JSON; binary frames carry MessagePack
+                let (payload, frame_encoding) = match &msg {
+                    Some(Ok(Message::Text(text))) => (text.as_str().as_bytes(), Encoding::Json),
+                    Some(Ok(Message::Binary(data))) => (data.as_ref(), Encoding::MessagePack),
+                    Some(Ok(Message::Close(_))) => {
+                        tracing::info!("Peer {} disconnected", peer_id);
+                        break;
+                    }
+                    Some(Err(e)) => {
+                        tracing::error!("WebSocket error for peer {}: {}", peer_id, e);
+                        break;
+                    }
+                    None => break,
+                    Some(Ok(_)) => continue,
+                };
 
-                        // Validate message size before processing
-                        if let Err(e) = validate_message_size(text.len()) {
-                            tracing::warn!("Message from peer {} rejected: {}", peer_id, e);
-                            record_validation_failure("message_size");
-                            let error = ServerMessage::Error {
-                                code: "message_too_large".to_string(),
-                                message: e.to_string(),
-                                message_id: None,
-                            };
-                            if let Ok(json) = serde_json::to_string(&error) {
-                                if sender.send(Message::Text(json.into())).await.is_err() {
-                                    break;
-                                }
-                            }
-                            continue;
-                        }
+                // Check rate limit first
+                if let Err(QuotaError::Exceeded { retry_after, .. }) =
+                    quotas.try_use(client.identity(), Budget::Messages, 1)
+                {
+                    tracing::warn!("Rate limit exceeded for peer {}", peer_id);
+                    record_rate_limited("websocket");
+                    let retry_after = retry_after.as_millis().clamp(1, 10000) as u64;
+                    let error = ServerMessage::Error {
+                        code: "rate_limited".to_string(),
+                        message: format!("Rate limit exceeded. Retry after {}ms", retry_after),
+                        message_id: None,
+                    };
+                    let status = ServerMessage::QuotaStatus {
+                        quota: quotas.status(client.identity()),
+                    };
+                    let frames = [error, status].map(|message| encode_frame(&message, encoding));
+                    let mut closed = false;
+                    for frame in frames.into_iter().flatten() {
+                        closed |= sender.send(frame).await.is_err();
+                    }
+                    if closed {
+                        break;
+                    }
+                    continue;
+                }
 
-                        tracing::debug!("Received from {}: {}", peer_id, text);
+                // Validate message size before processing
+                if let Err(e) = validate_message_size(payload.len()) {
+                    tracing::warn!("Message from peer {} rejected: {}", peer_id, e);
+                    record_validation_failure("message_size");
+                    let error = ServerMessage::Error {
+                        code: "message_too_large".to_string(),
+                        message: e.to_string(),
+                        message_id: None,
+                    };
+                    if let Ok(frame) = encode_frame(&error, encoding) {
+                        if sender.send(frame).await.is_err() {
+                            break;
+                        }
+                    }
+                    continue;
+                }
 
-                        match serde_json::from_str::<ClientMessage>(&text) {
-                            Ok(client_msg) => {
-                                // Handle subscribe specially to update session and peer registry
-                                if let ClientMessage::Subscribe { ref session_id } = client_msg {
-                                    tracing::info!("Peer {} subscribed to session: {}", peer_id, session_id);
-                                    state.update_peer_session(&peer_id, session_id);
-                                }
+                tracing::debug!(
+                    "Received {} bytes of {} from {}",
+                    payload.len(),
+                    frame_encoding.name(),
+                    peer_id
+                );
 
-                                if let Some(response) = client.handle_message(client_msg) {
-                                    if let Ok(json) = serde_json::to_string(&response) {
-                                        if sender.send(Message::Text(json.into())).await.is_err() {
-                                            break;
-                                        }
-                                    }
-                                }
+                match frame_encoding.decode::<ClientMessage>(payload) {
+                    Ok(client_msg) => {
+                        // Handle subscribe specially to update session and peer registry
+                        if let ClientMessage::Subscribe { ref session_id } = client_msg {
+                            tracing::info!("Peer {} subscribed to session: {}", peer_id, session_id);
+                            state.update_peer_session(&peer_id, session_id);
+                            if let Some(view_message) = state.presenter_view(session_id) {
+                                state.send_to_peer(&peer_id, view_message);
                             }
-                            Err(e) => {
-                                let error = ServerMessage::Error {
-                                    code: "parse_error".to_string(),
-                                    message: e.to_string(),
-                                    message_id: None,
-                                };
-                                if let Ok(json) = serde_json::to_string(&error) {
-                                    if sender.send(Message::Text(json.into())).await.is_err() {
-                                        break;
-                                    }
+                        }
+
+                        if let Some(response) = client.handle_message(client_msg) {
+                            if let Ok(frame) = encode_frame(&response, encoding) {
+                                if sender.send(frame).await.is_err() {
+                                    break;
                                 }
                             }
                         }
+                        if !send_pending_pages(&mut sender, &mut client, encoding).await {
+                            break;
+                        }
                     }
-                    Some(Ok(Message::Close(_))) => {
-                        tracing::info!("Peer {} disconnected", peer_id);
-                        break;
-                    }
-                    Some(Err(e)) => {
-                        tracing::error!("WebSocket error for peer {}: {}", peer_id, e);
-                        break;
+                    Err(e) => {
+                        let error = ServerMessage::Error {
+                            code: "parse_error".to_string(),
+                            message: e.to_string(),
+                            message_id: None,
+                        };
+                        if let Ok(frame) = encode_frame(&error, encoding) {
+                            if sender.send(frame).await.is_err() {
+                                break;
+                            }
+                        }
                     }
-                    None => break,
-                    _ => {}
                 }
             }
 
@@ -3274,8 +4584,8 @@ pub async fn handle_sync_socket(socket: WebSocket, state: SyncState) {
             peer_msg = peer_rx.recv() => {
                 match peer_msg {
                     Some(message) => {
-                        if let Ok(json) = serde_json::to_string(&message) {
-                            if sender.send(Message::Text(json.into())).await.is_err() {
+                        if let Ok(frame) = encode_frame(&message, encoding) {
+                            if sender.send(frame).await.is_err() {
                                 break;
                             }
                         }
@@ -3292,8 +4602,11 @@ pub async fn handle_sync_socket(socket: WebSocket, state: SyncState) {
             event = event_rx.recv() => {
                 match event {
                     Ok(sync_event) if sync_event.session_id == client.session_id() => {
-                        if let Ok(json) = serde_json::to_string(&sync_event.message) {
-                            if sender.send(Message::Text(json.into())).await.is_err() {
+                        let Some(message) = client.visible_event(sync_event.message) else {
+                            continue;
+                        };
+                        if let Ok(frame) = encode_frame(&message, encoding) {
+                            if sender.send(frame).await.is_err() {
                                 break;
                             }
                         }
@@ -3318,8 +4631,33 @@ pub async fn handle_sync_socket(socket: WebSocket, state: SyncState) {
 
 // Helper functions
 
-fn default_session() -> String {
-    "default".to_string()
+/// Send any queued scene pages, returning `false` if the socket closed.
+async fn send_pending_pages(
+    sender: &mut SplitSink<WebSocket, Message>,
+    client: &mut ClientConnection,
+    encoding: Encoding,
+) -> bool {
+    while let Some(page) = client.next_pending_page() {
+        match encode_frame(&page, encoding) {
+            Ok(frame) => {
+                if sender.send(frame).await.is_err() {
+                    return false;
+                }
+            }
+            Err(e) => {
+                tracing::error!(peer_id = %client.peer_id(), "Failed to serialize scene_page message: {}", e);
+            }
+        }
+    }
+    true
+}
+
+/// Encode a server message as a WebSocket frame.
+fn encode_frame(message: &ServerMessage, encoding: Encoding) -> Result<Message, EncodingError> {
+    match encoding {
+        Encoding::Json => Ok(Message::Text(serde_json::to_string(message)?.into())),
+        Encoding::MessagePack => Ok(Message::Binary(encoding.encode(message)?.into())),
+    }
 }
 
 /// Get the current Unix timestamp in milliseconds.
@@ -3338,6 +4676,47 @@ fn parse_element_id(id: &str) -> Result<ElementId, SyncError> {
         .map_err(|_| SyncError::InvalidElementId(id.to_string()))
 }
 
+/// Apply batch operations to a scene in order, stopping at the first failure.
+fn apply_batch_to_scene(
+    scene: &mut Scene,
+    operations: &[BatchOperation],
+    author: Option<&str>,
+) -> Result<Vec<ElementId>, SyncError> {
+    let mut added = Vec::new();
+    for (index, op) in operations.iter().enumerate() {
+        let result = match op {
+            BatchOperation::Add { element } => element_from_data(element).map(|mut element| {
+                if let Some(author) = author {
+                    element = element.with_author(author);
+                }
+                added.push(scene.add_element(element));
+            }),
+            BatchOperation::Update { id, changes } => parse_element_id(id).and_then(|element_id| {
+                scene
+                    .update_element(element_id, |element| {
+                        apply_changes_to_element(element, changes);
+                        if let Some(author) = author {
+                            element.touch(author);
+                        }
+                    })
+                    .map(|_| ())
+                    .map_err(|_| SyncError::ElementNotFound(id.clone()))
+            }),
+            BatchOperation::Remove { id } => parse_element_id(id).and_then(|element_id| {
+                scene
+                    .remove_element(&element_id)
+                    .map(|_| ())
+                    .map_err(|_| SyncError::ElementNotFound(id.clone()))
+            }),
+        };
+        result.map_err(|source| SyncError::BatchFailed {
+            index,
+            source: Box::new(source),
+        })?;
+    }
+    Ok(added)
+}
+
 fn element_from_data(data: &ElementDocument) -> Result<Element, SyncError> {
     let mut doc = data.clone();
     if doc.id.is_empty() {
@@ -3385,13 +4764,29 @@ fn validate_f64_for_f32(value: f64, field_name: &str) -> Option<f32> {
 /// - `transform.rotation`: Rotation in radians (f32)
 /// - `transform.z_index`: Layer ordering (i32)
 /// - `interactive`: Whether element responds to input (bool)
+/// - `visible`: Whether element is drawn and hit-testable (bool)
+/// - `opacity`: Element opacity, clamped to 0.0..=1.0 (f32)
+/// - `screen_anchored`: Whether element is pinned to the viewport (bool)
+/// - `sensitive`: Whether element is redacted in redacted exports (bool)
+/// - `style`: Replacement visual style (fill, stroke, corner radius, shadow, opacity)
+/// - `attachment`: Element to follow and where (object), or `null` to detach
 ///
 /// Unknown fields are logged at debug level and silently ignored for forward
 /// compatibility (newer clients may send fields older servers don't understand).
 /// Invalid values (NaN, Infinity, out-of-range) are logged and ignored.
 fn apply_changes_to_element(element: &mut Element, changes: &serde_json::Value) {
     // Known top-level fields
-    const KNOWN_TOP_LEVEL: &[&str] = &["transform", "interactive"];
+    const KNOWN_TOP_LEVEL: &[&str] = &[
+        "transform",
+        "interactive",
+        "visible",
+        "opacity",
+        "screen_anchored",
+        "sensitive",
+        "style",
+        "interactions",
+        "attachment",
+    ];
     // Known transform fields
     const KNOWN_TRANSFORM: &[&str] = &["x", "y", "width", "height", "rotation", "z_index"];
 
@@ -3463,6 +4858,54 @@ fn apply_changes_to_element(element: &mut Element, changes: &serde_json::Value)
     if let Some(interactive) = changes.get("interactive").and_then(|v| v.as_bool()) {
         element.interactive = interactive;
     }
+
+    if let Some(visible) = changes.get("visible").and_then(|v| v.as_bool()) {
+        element.visible = visible;
+    }
+
+    if let Some(opacity) = changes.get("opacity").and_then(|v| v.as_f64()) {
+        if let Some(validated) = validate_f64_for_f32(opacity, "opacity") {
+            element.opacity = validated.clamp(0.0, 1.0);
+        }
+    }
+
+    if let Some(anchored) = changes.get("screen_anchored").and_then(|v| v.as_bool()) {
+        element.screen_anchored = anchored;
+    }
+
+    if let Some(sensitive) = changes.get("sensitive").and_then(|v| v.as_bool()) {
+        element.sensitive = sensitive;
+    }
+
+    if let Some(style) = changes.get("style") {
+        match serde_json::from_value(style.clone()) {
+            Ok(style) => element.style = style,
+            Err(e) => tracing::warn!(
+                error = %e,
+                "apply_changes_to_element: ignoring invalid style"
+            ),
+        }
+    }
+
+    if let Some(interactions) = changes.get("interactions") {
+        match serde_json::from_value(interactions.clone()) {
+            Ok(interactions) => element.interactions = interactions,
+            Err(e) => tracing::warn!(
+                error = %e,
+                "apply_changes_to_element: ignoring invalid interactions"
+            ),
+        }
+    }
+
+    if let Some(attachment) = changes.get("attachment") {
+        match serde_json::from_value(attachment.clone()) {
+            Ok(attachment) => element.attachment = attachment,
+            Err(e) => tracing::warn!(
+                error = %e,
+                "apply_changes_to_element: ignoring invalid attachment"
+            ),
+        }
+    }
 }
 
 /// Convert an Element to serializable ElementDocument.
@@ -3474,7 +4917,10 @@ pub fn element_to_data(element: &Element) -> ElementDocument {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use canvas_core::{ElementKind, Transform, ViewportDocument};
+    use canvas_core::{
+        Color, CoordinateSystem, ElementKind, Interactions, PatchOp, Style, ThemeColor, Transform,
+        ViewportDocument,
+    };
 
     #[test]
     fn test_client_message_parse_subscribe() {
@@ -3539,6 +4985,7 @@ mod tests {
             session_id: "default".to_string(),
             timestamp: 12345,
             legacy_signaling: Some(true),
+            consistency: Consistency::Strong,
         };
         let json = serde_json::to_string(&msg).expect("should serialize");
         assert!(json.contains("welcome"));
@@ -3563,12 +5010,28 @@ mod tests {
                     kind: ElementKind::Text {
                         content: "Test".to_string(),
                         font_size: 16.0,
-                        color: "#000000".to_string(),
+                        color: Color::BLACK.into(),
                     },
                     transform: Transform::default(),
                     interactive: true,
                     selected: false,
+                    visible: true,
+                    opacity: 1.0,
+                    screen_anchored: false,
+                    sensitive: false,
+                    style: Style::default(),
+                    interactions: Interactions::default(),
+                    created_by: None,
+                    last_modified_by: None,
+                    attachment: None,
+                    depth: None,
                 }],
+                spotlight: None,
+                anchors: Vec::new(),
+                theme: Theme::default(),
+                branding: None,
+                datasets: Vec::new(),
+                coordinates: CoordinateSystem::default(),
                 timestamp: 12345,
             },
         };
@@ -3586,11 +5049,21 @@ mod tests {
                 kind: ElementKind::Text {
                     content: "New".to_string(),
                     font_size: 16.0,
-                    color: "#000000".to_string(),
+                    color: Color::BLACK.into(),
                 },
                 transform: Transform::default(),
                 interactive: true,
                 selected: false,
+                visible: true,
+                opacity: 1.0,
+                screen_anchored: false,
+                sensitive: false,
+                style: Style::default(),
+                interactions: Interactions::default(),
+                created_by: None,
+                last_modified_by: None,
+                attachment: None,
+                depth: None,
             },
             timestamp: 12345,
         };
@@ -3645,14 +5118,24 @@ mod tests {
             kind: ElementKind::Text {
                 content: "Hello".to_string(),
                 font_size: 16.0,
-                color: "#000000".to_string(),
+                color: Color::BLACK.into(),
             },
             transform: Transform::default(),
             interactive: true,
             selected: false,
+            visible: true,
+            opacity: 1.0,
+            screen_anchored: false,
+            sensitive: false,
+            style: Style::default(),
+            interactions: Interactions::default(),
+            created_by: None,
+            last_modified_by: None,
+            attachment: None,
+            depth: None,
         };
 
-        let result = state.add_element("default", &element);
+        let result = state.add_element("default", &element, None);
         assert!(result.is_ok());
 
         let scene = state.get_scene("default").expect("should have scene");
@@ -3667,14 +5150,26 @@ mod tests {
             kind: ElementKind::Text {
                 content: "Hello".to_string(),
                 font_size: 16.0,
-                color: "#000000".to_string(),
+                color: Color::BLACK.into(),
             },
             transform: Transform::default(),
             interactive: true,
             selected: false,
+            visible: true,
+            opacity: 1.0,
+            screen_anchored: false,
+            sensitive: false,
+            style: Style::default(),
+            interactions: Interactions::default(),
+            created_by: None,
+            last_modified_by: None,
+            attachment: None,
+            depth: None,
         };
 
-        let id = state.add_element("default", &element).expect("should add");
+        let id = state
+            .add_element("default", &element, None)
+            .expect("should add");
         let result = state.remove_element("default", &id.to_string());
         assert!(result.is_ok());
 
@@ -3690,7 +5185,7 @@ mod tests {
             kind: ElementKind::Text {
                 content: "Hello".to_string(),
                 font_size: 16.0,
-                color: "#000000".to_string(),
+                color: Color::BLACK.into(),
             },
             transform: Transform {
                 x: 0.0,
@@ -3699,14 +5194,27 @@ mod tests {
                 height: 100.0,
                 rotation: 0.0,
                 z_index: 0,
+                auto_size: false,
             },
             interactive: true,
             selected: false,
+            visible: true,
+            opacity: 1.0,
+            screen_anchored: false,
+            sensitive: false,
+            style: Style::default(),
+            interactions: Interactions::default(),
+            created_by: None,
+            last_modified_by: None,
+            attachment: None,
+            depth: None,
         };
 
-        let id = state.add_element("default", &element).expect("should add");
+        let id = state
+            .add_element("default", &element, None)
+            .expect("should add");
         let changes = serde_json::json!({"transform": {"x": 50.0, "y": 75.0}});
-        let result = state.update_element("default", &id.to_string(), &changes);
+        let result = state.update_element("default", &id.to_string(), &changes, None);
         assert!(result.is_ok());
 
         let scene = state.get_scene("default").expect("should have scene");
@@ -3723,14 +5231,24 @@ mod tests {
             kind: ElementKind::Text {
                 content: "Hello".to_string(),
                 font_size: 16.0,
-                color: "#000000".to_string(),
+                color: Color::BLACK.into(),
             },
             transform: Transform::default(),
             interactive: true,
             selected: false,
+            visible: true,
+            opacity: 1.0,
+            screen_anchored: false,
+            sensitive: false,
+            style: Style::default(),
+            interactions: Interactions::default(),
+            created_by: None,
+            last_modified_by: None,
+            attachment: None,
+            depth: None,
         };
 
-        let _ = state.add_element("default", &element);
+        let _ = state.add_element("default", &element, None);
         let update = state.get_scene_update("default");
 
         match update {
@@ -3751,11 +5269,21 @@ mod tests {
                     kind: ElementKind::Text {
                         content: "Queued 1".to_string(),
                         font_size: 16.0,
-                        color: "#000000".to_string(),
+                        color: Color::BLACK.into(),
                     },
                     transform: Transform::default(),
                     interactive: true,
                     selected: false,
+                    visible: true,
+                    opacity: 1.0,
+                    screen_anchored: false,
+                    sensitive: false,
+                    style: Style::default(),
+                    interactions: Interactions::default(),
+                    created_by: None,
+                    last_modified_by: None,
+                    attachment: None,
+                    depth: None,
                 },
                 timestamp: 100,
             },
@@ -3765,186 +5293,1298 @@ mod tests {
                     kind: ElementKind::Text {
                         content: "Queued 2".to_string(),
                         font_size: 16.0,
-                        color: "#000000".to_string(),
+                        color: Color::BLACK.into(),
                     },
                     transform: Transform::default(),
                     interactive: true,
                     selected: false,
+                    visible: true,
+                    opacity: 1.0,
+                    screen_anchored: false,
+                    sensitive: false,
+                    style: Style::default(),
+                    interactions: Interactions::default(),
+                    created_by: None,
+                    last_modified_by: None,
+                    attachment: None,
+                    depth: None,
                 },
                 timestamp: 200,
             },
         ];
 
-        let result = state.process_queue("default", operations);
-        assert_eq!(result.processed_count, 2);
-        assert_eq!(result.failed_count, 0);
+        let result = state.process_queue("default", operations, None);
+        assert_eq!(result.processed_count, 2);
+        assert_eq!(result.failed_count, 0);
+
+        let scene = state.get_scene("default").expect("should have scene");
+        assert_eq!(scene.element_count(), 2);
+    }
+
+    #[test]
+    fn test_sync_state_multiple_sessions() {
+        let state = SyncState::new();
+
+        let element1 = ElementDocument {
+            id: String::new(),
+            kind: ElementKind::Text {
+                content: "Session 1".to_string(),
+                font_size: 16.0,
+                color: Color::BLACK.into(),
+            },
+            transform: Transform::default(),
+            interactive: true,
+            selected: false,
+            visible: true,
+            opacity: 1.0,
+            screen_anchored: false,
+            sensitive: false,
+            style: Style::default(),
+            interactions: Interactions::default(),
+            created_by: None,
+            last_modified_by: None,
+            attachment: None,
+            depth: None,
+        };
+
+        let element2 = ElementDocument {
+            id: String::new(),
+            kind: ElementKind::Text {
+                content: "Session 2".to_string(),
+                font_size: 16.0,
+                color: Color::BLACK.into(),
+            },
+            transform: Transform::default(),
+            interactive: true,
+            selected: false,
+            visible: true,
+            opacity: 1.0,
+            screen_anchored: false,
+            sensitive: false,
+            style: Style::default(),
+            interactions: Interactions::default(),
+            created_by: None,
+            last_modified_by: None,
+            attachment: None,
+            depth: None,
+        };
+
+        let _ = state.add_element("session-1", &element1, None);
+        let _ = state.add_element("session-2", &element2, None);
+
+        let scene1 = state.get_scene("session-1").expect("should have scene 1");
+        let scene2 = state.get_scene("session-2").expect("should have scene 2");
+
+        assert_eq!(scene1.element_count(), 1);
+        assert_eq!(scene2.element_count(), 1);
+    }
+
+    #[test]
+    fn test_client_connection_handle_ping() {
+        let state = SyncState::new();
+        let mut client = ClientConnection::new(state);
+
+        let response = client.handle_message(ClientMessage::Ping);
+        assert!(response.is_some());
+        assert!(matches!(response.unwrap(), ServerMessage::Pong { .. }));
+    }
+
+    #[test]
+    fn test_client_connection_handle_subscribe() {
+        let state = SyncState::new();
+        let mut client = ClientConnection::new(state);
+
+        let response = client.handle_message(ClientMessage::Subscribe {
+            session_id: "test-session".to_string(),
+        });
+
+        assert!(response.is_some());
+        assert!(matches!(
+            response.unwrap(),
+            ServerMessage::SceneUpdate { .. }
+        ));
+        assert_eq!(client.session_id(), "test-session");
+    }
+
+    #[test]
+    fn test_client_connection_relays_element_action() {
+        use crate::agui::InteractionEvent;
+        use canvas_core::{ElementId, ElementInteraction};
+
+        let state = SyncState::new();
+        let mut interactions = state.subscribe_interactions();
+        let mut client = ClientConnection::new(state);
+
+        let element_id = ElementId::new();
+        let tap = ElementInteraction::Action {
+            element_id,
+            action: "approve".to_string(),
+            payload: Some(serde_json::json!({ "row": 3 })),
+        };
+        let response = client.handle_message(tap.to_client_message(Some("tap-1".to_string())));
+        assert!(matches!(
+            response,
+            Some(ServerMessage::Ack { success: true, .. })
+        ));
+
+        let (_, event) = interactions.try_recv().expect("interaction relayed");
+        match event {
+            InteractionEvent::Action {
+                element_id: relayed,
+                action,
+                payload,
+            } => {
+                assert_eq!(relayed, element_id.to_string());
+                assert_eq!(action, "approve");
+                assert_eq!(payload, Some(serde_json::json!({ "row": 3 })));
+            }
+            other => panic!("Expected Action interaction, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_poll_votes_are_tallied_per_peer() {
+        use crate::agui::InteractionEvent;
+        use canvas_core::ElementInteraction;
+
+        let state = SyncState::new();
+        let mut poll_id = None;
+        state
+            .update_scene("default", |scene| {
+                poll_id = Some(scene.add_element(Element::new(ElementKind::Poll {
+                    question: "Lunch?".to_string(),
+                    options: vec!["Pizza".to_string(), "Salad".to_string()],
+                    votes: std::collections::BTreeMap::new(),
+                    closed: false,
+                })));
+            })
+            .expect("update");
+        let element_id = poll_id.expect("poll");
+        let mut interactions = state.subscribe_interactions();
+        let mut alice = ClientConnection::with_peer_id(state.clone(), "alice".to_string());
+        let mut bob = ClientConnection::with_peer_id(state.clone(), "bob".to_string());
+        let vote = |option: usize| {
+            ElementInteraction::Action {
+                element_id,
+                action: "vote".to_string(),
+                payload: Some(serde_json::json!({ "option": option })),
+            }
+            .to_client_message(None)
+        };
+
+        assert!(alice.handle_message(vote(0)).is_none());
+        assert!(bob.handle_message(vote(0)).is_none());
+        // Alice changes her mind
+        assert!(alice.handle_message(vote(1)).is_none());
+
+        let mut last = None;
+        while let Ok((_, event)) = interactions.try_recv() {
+            last = Some(event);
+        }
+        let Some(InteractionEvent::Action { payload, .. }) = last else {
+            panic!("expected vote action");
+        };
+        let payload = payload.expect("payload");
+        assert_eq!(payload["voter"], "alice");
+        assert_eq!(payload["results"]["total"], 2);
+        assert_eq!(payload["results"]["options"][1]["votes"], 1);
+
+        let scene = state.get_scene("default").expect("scene");
+        let kind = &scene.get_element(element_id).expect("poll").kind;
+        assert_eq!(
+            PollResults::from_kind(kind).map(|r| r.options[0].votes),
+            Some(1)
+        );
+
+        // Out-of-range options are rejected
+        assert!(matches!(
+            bob.handle_message(vote(5)),
+            Some(ServerMessage::Error { ref code, .. }) if code == "vote_failed"
+        ));
+    }
+
+    #[test]
+    fn test_randomizer_outcomes_are_drawn_by_server() {
+        use crate::agui::InteractionEvent;
+        use canvas_core::{ElementInteraction, RandomSource};
+
+        let state = SyncState::new();
+        let mut ids = Vec::new();
+        state
+            .update_scene("default", |scene| {
+                for source in [
+                    RandomSource::Dice { count: 2, sides: 6 },
+                    RandomSource::Wheel {
+                        segments: vec!["Only".to_string()],
+                    },
+                ] {
+                    ids.push(scene.add_element(Element::new(ElementKind::Randomizer {
+                        source,
+                        outcome: None,
+                    })));
+                }
+            })
+            .expect("update");
+        let mut interactions = state.subscribe_interactions();
+        let mut rx = state.subscribe();
+        let mut client = ClientConnection::with_peer_id(state.clone(), "alice".to_string());
+        let roll = |element_id| {
+            ElementInteraction::Action {
+                element_id,
+                action: "randomize".to_string(),
+                payload: Some(serde_json::json!({ "mode": "dice" })),
+            }
+            .to_client_message(None)
+        };
+
+        assert!(client.handle_message(roll(ids[0])).is_none());
+        assert!(rx.try_recv().is_ok(), "outcome broadcast");
+        let Ok((_, InteractionEvent::Action { payload, .. })) = interactions.try_recv() else {
+            panic!("expected randomize action");
+        };
+        let payload = payload.expect("payload");
+        let faces: Vec<u64> = payload["outcome"]["values"]
+            .as_array()
+            .expect("values")
+            .iter()
+            .filter_map(serde_json::Value::as_u64)
+            .collect();
+        assert_eq!(faces.len(), 2);
+        assert!(faces.iter().all(|face| (1..=6).contains(face)));
+        assert_eq!(
+            payload["result"],
+            format!("{} + {} = {}", faces[0], faces[1], faces[0] + faces[1])
+        );
+        assert_eq!(payload["outcome"]["by"], "alice");
+
+        let scene = state.get_scene("default").expect("scene");
+        assert!(matches!(
+            scene.get_element(ids[0]).map(|e| &e.kind),
+            Some(ElementKind::Randomizer { outcome: Some(outcome), .. }) if outcome.draw == 1
+        ));
+
+        // A one-segment wheel cannot be spun
+        assert!(matches!(
+            client.handle_message(roll(ids[1])),
+            Some(ServerMessage::Error { ref code, .. }) if code == "randomize_failed"
+        ));
+    }
+
+    #[test]
+    fn test_floor_plan_marker_moves_are_shared() {
+        use crate::agui::InteractionEvent;
+        use canvas_core::{ElementInteraction, FloorMarker, MarkerShape};
+
+        let state = SyncState::new();
+        let mut id = None;
+        state
+            .update_scene("default", |scene| {
+                id = Some(scene.add_element(Element::new(ElementKind::FloorPlan {
+                    background: None,
+                    plan_width: 400.0,
+                    plan_height: 200.0,
+                    paths: Vec::new(),
+                    markers: vec![FloorMarker::new("A1", MarkerShape::Seat, 20.0, 20.0)],
+                    locked: false,
+                })));
+            })
+            .expect("update");
+        let id = id.expect("floor plan");
+        let mut interactions = state.subscribe_interactions();
+        let mut rx = state.subscribe();
+        let mut client = ClientConnection::with_peer_id(state.clone(), "alice".to_string());
+        let drag = |marker: &str, x: f32| {
+            ElementInteraction::Action {
+                element_id: id,
+                action: "move_marker".to_string(),
+                payload: Some(serde_json::json!({ "marker": marker, "x": x, "y": 80.0 })),
+            }
+            .to_client_message(None)
+        };
+
+        // Positions past the edge are clamped to the plan
+        assert!(client.handle_message(drag("A1", 450.0)).is_none());
+        assert!(rx.try_recv().is_ok(), "plan broadcast");
+        let Ok((_, InteractionEvent::Action { payload, .. })) = interactions.try_recv() else {
+            panic!("expected move_marker action");
+        };
+        assert_eq!(payload.expect("payload")["x"], 400.0);
+        let scene = state.get_scene("default").expect("scene");
+        assert!(matches!(
+            scene.get_element(id).map(|e| &e.kind),
+            Some(ElementKind::FloorPlan { markers, .. })
+                if (markers[0].x - 400.0).abs() < f32::EPSILON && (markers[0].y - 80.0).abs() < f32::EPSILON
+        ));
+
+        assert!(matches!(
+            client.handle_message(drag("Z9", 10.0)),
+            Some(ServerMessage::Error { ref code, .. }) if code == "move_marker_failed"
+        ));
+    }
+
+    #[test]
+    fn test_tree_toggles_are_shared() {
+        use crate::agui::InteractionEvent;
+        use canvas_core::{ElementInteraction, TreeDirection, TreeNode};
+
+        let state = SyncState::new();
+        let mut id = None;
+        state
+            .update_scene("default", |scene| {
+                id = Some(
+                    scene.add_element(Element::new(ElementKind::Tree {
+                        root: TreeNode::new("ceo", "CEO")
+                            .with_children(vec![TreeNode::new("cto", "CTO")]),
+                        direction: TreeDirection::Down,
+                    })),
+                );
+            })
+            .expect("update");
+        let id = id.expect("tree");
+        let mut interactions = state.subscribe_interactions();
+        let mut rx = state.subscribe();
+        let mut client = ClientConnection::with_peer_id(state.clone(), "alice".to_string());
+        let toggle = |node: &str, collapsed: bool| {
+            ElementInteraction::Action {
+                element_id: id,
+                action: "toggle_node".to_string(),
+                payload: Some(serde_json::json!({ "node": node, "collapsed": collapsed })),
+            }
+            .to_client_message(None)
+        };
+
+        assert!(client.handle_message(toggle("ceo", true)).is_none());
+        assert!(rx.try_recv().is_ok(), "tree broadcast");
+        assert!(matches!(
+            interactions.try_recv(),
+            Ok((_, InteractionEvent::Action { ref action, .. })) if action == "toggle_node"
+        ));
+        let scene = state.get_scene("default").expect("scene");
+        assert!(matches!(
+            scene.get_element(id).map(|e| &e.kind),
+            Some(ElementKind::Tree { root, .. }) if root.collapsed
+        ));
+
+        // Leaves have nothing to collapse
+        assert!(matches!(
+            client.handle_message(toggle("cto", true)),
+            Some(ServerMessage::Error { ref code, .. }) if code == "toggle_node_failed"
+        ));
+    }
+
+    #[test]
+    fn test_client_connection_handle_get_scene() {
+        let state = SyncState::new();
+        let mut client = ClientConnection::new(state);
+
+        let response = client.handle_message(ClientMessage::GetScene);
+        assert!(response.is_some());
+        assert!(matches!(
+            response.unwrap(),
+            ServerMessage::SceneUpdate { .. }
+        ));
+    }
+
+    #[test]
+    fn test_client_connection_pages_scene_load() {
+        let state = SyncState::new();
+        for i in 0..5 {
+            let json = format!(
+                r##"{{"id":"","kind":{{"type":"Text","data":{{"content":"{i}","font_size":16.0,"color":"#000000"}}}}}}"##
+            );
+            let doc: ElementDocument = serde_json::from_str(&json).expect("should parse");
+            state
+                .add_element("default", &doc, None)
+                .expect("should add");
+        }
+        let mut client = ClientConnection::new(state);
+        client.set_page_size(Some(2));
+
+        let Some(ServerMessage::SceneUpdate { scene }) =
+            client.handle_message(ClientMessage::GetScene)
+        else {
+            panic!("Expected SceneUpdate");
+        };
+        assert_eq!(scene.elements.len(), 2);
+
+        let mut pages = Vec::new();
+        while let Some(ServerMessage::ScenePage { page }) = client.next_pending_page() {
+            pages.push(page);
+        }
+        assert_eq!(pages.len(), 2);
+        assert_eq!(pages.iter().map(|p| p.elements.len()).sum::<usize>(), 3);
+        assert!(pages[1].is_last());
+    }
+
+    #[test]
+    fn test_element_data_default_id() {
+        let json = r##"{"id":"","kind":{"type":"Text","data":{"content":"Test","font_size":16.0,"color":"#000000"}}}"##;
+        let element_doc: ElementDocument = serde_json::from_str(json).expect("should parse");
+        let element = element_from_data(&element_doc).expect("should convert");
+        assert!(!element.id.to_string().is_empty());
+    }
+
+    #[test]
+    fn test_element_kind_data_text_defaults() {
+        let json =
+            r##"{"type":"Text","data":{"content":"Test","font_size":16.0,"color":"#000000"}}"##;
+        let kind: ElementKind = serde_json::from_str(json).expect("should parse");
+        match kind {
+            ElementKind::Text {
+                font_size, color, ..
+            } => {
+                assert!((font_size - 16.0).abs() < f32::EPSILON);
+                assert_eq!(color, ThemeColor::from(Color::BLACK));
+            }
+            _ => panic!("Expected Text"),
+        }
+    }
+
+    #[test]
+    fn test_transform_data_defaults() {
+        let json = r##"{"id":"","kind":{"type":"Text","data":{"content":"Example","font_size":16.0,"color":"#000000"}}}"##;
+        let doc: ElementDocument = serde_json::from_str(json).expect("should parse");
+        assert!((doc.transform.width - 100.0).abs() < f32::EPSILON);
+        assert!((doc.transform.height - 100.0).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn test_queued_operation_add() {
+        let json = r##"{"op":"add","element":{"id":"","kind":{"type":"Text","data":{"content":"Queued","font_size":16.0,"color":"#000000"}}},"timestamp":12345}"##;
+        let op: QueuedOperation = serde_json::from_str(json).expect("should parse");
+        match op {
+            QueuedOperation::Add { timestamp, .. } => assert_eq!(timestamp, 12345),
+            _ => panic!("Expected Add"),
+        }
+    }
+
+    #[test]
+    fn test_queued_operation_remove() {
+        let json = r#"{"op":"remove","id":"some-id","timestamp":54321}"#;
+        let op: QueuedOperation = serde_json::from_str(json).expect("should parse");
+        match op {
+            QueuedOperation::Remove { id, timestamp } => {
+                assert_eq!(id, "some-id");
+                assert_eq!(timestamp, 54321);
+            }
+            _ => panic!("Expected Remove"),
+        }
+    }
+
+    #[test]
+    fn test_broadcast_event_subscription() {
+        let state = SyncState::new();
+        let mut rx = state.subscribe();
+
+        let element = ElementDocument {
+            id: String::new(),
+            kind: ElementKind::Text {
+                content: "Broadcast Test".to_string(),
+                font_size: 16.0,
+                color: Color::BLACK.into(),
+            },
+            transform: Transform::default(),
+            interactive: true,
+            selected: false,
+            visible: true,
+            opacity: 1.0,
+            screen_anchored: false,
+            sensitive: false,
+            style: Style::default(),
+            interactions: Interactions::default(),
+            created_by: None,
+            last_modified_by: None,
+            attachment: None,
+            depth: None,
+        };
+
+        // This should trigger a broadcast
+        let _ = state.add_element("default", &element, None);
+
+        // Should receive the broadcast
+        let event = rx.try_recv();
+        assert!(event.is_ok());
+        let sync_event = event.unwrap();
+        assert_eq!(sync_event.session_id, "default");
+        assert!(
+            matches!(sync_event.message, ServerMessage::ElementAdded { .. })
+                || matches!(sync_event.message, ServerMessage::SceneUpdate { .. })
+        );
+    }
+
+    #[test]
+    fn test_scene_changes_broadcast_as_patches() {
+        let state = SyncState::new();
+        let mut rx = state.subscribe();
+
+        let element = Element::new(ElementKind::Text {
+            content: "Patch Test".to_string(),
+            font_size: 16.0,
+            color: Color::BLACK.into(),
+        });
+        let id = element.id;
+
+        // First change has no baseline, so a full scene is sent
+        state
+            .update_scene("default", |scene| {
+                scene.add_element(element.clone());
+            })
+            .expect("update");
+        let event = rx.try_recv().expect("first event");
+        assert!(matches!(event.message, ServerMessage::SceneUpdate { .. }));
+
+        // Subsequent changes only carry the difference
+        state
+            .update_scene("default", |scene| {
+                if let Some(el) = scene.get_element_mut(id) {
+                    el.transform.x = 42.0;
+                }
+            })
+            .expect("update");
+        let event = rx.try_recv().expect("second event");
+        match event.message {
+            ServerMessage::ScenePatch { patch } => {
+                assert_eq!(patch.ops.len(), 1);
+                assert!(patch.viewport.is_none());
+            }
+            other => panic!("expected scene patch, got {other:?}"),
+        }
+
+        // No-op changes are not broadcast
+        state.update_scene("default", |_| {}).expect("update");
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_batch_is_atomic_with_single_broadcast() {
+        let state = SyncState::new();
+        let existing = state
+            .add_element(
+                "default",
+                &ElementDocument::from(&Element::new(ElementKind::Text {
+                    content: "Old".to_string(),
+                    font_size: 16.0,
+                    color: Color::BLACK.into(),
+                })),
+                None,
+            )
+            .expect("add");
+        state.broadcast_scene_change("default", SyncOrigin::Local);
+        let mut rx = state.subscribe();
+
+        let text = serde_json::json!({
+            "id": "",
+            "kind": { "type": "Text", "data": { "content": "New", "font_size": 16.0, "color": "#000000" } },
+            "transform": { "x": 0.0, "y": 0.0, "width": 10.0, "height": 10.0, "rotation": 0.0, "z_index": 0 }
+        });
+        let json = serde_json::json!({
+            "type": "batch",
+            "operations": [
+                { "op": "add", "element": text },
+                { "op": "add", "element": text },
+                { "op": "remove", "id": existing.to_string() }
+            ]
+        });
+        let Ok(ClientMessage::Batch { operations, .. }) = serde_json::from_value(json) else {
+            panic!("Expected Batch");
+        };
+        let added = state
+            .apply_batch("default", &operations, None)
+            .expect("batch");
+        assert_eq!(added.len(), 2);
+        assert_eq!(state.store().get("default").unwrap().element_count(), 2);
+        assert!(matches!(
+            rx.try_recv().expect("broadcast").message,
+            ServerMessage::ScenePatch { .. }
+        ));
+        assert!(rx.try_recv().is_err());
+
+        // A failing operation leaves the scene untouched
+        let failing = vec![
+            BatchOperation::Remove {
+                id: added[0].to_string(),
+            },
+            BatchOperation::Remove {
+                id: existing.to_string(),
+            },
+        ];
+        assert!(matches!(
+            state.apply_batch("default", &failing, None),
+            Err(SyncError::BatchFailed { index: 1, .. })
+        ));
+        assert_eq!(state.store().get("default").unwrap().element_count(), 2);
+    }
+
+    #[test]
+    fn test_camera_message_fits_content() {
+        let state = SyncState::new();
+        state
+            .update_scene("default", |scene| {
+                scene.add_element(
+                    Element::new(ElementKind::Text {
+                        content: "Far away".to_string(),
+                        font_size: 16.0,
+                        color: Color::BLACK.into(),
+                    })
+                    .with_transform(Transform {
+                        x: 2000.0,
+                        y: 2000.0,
+                        width: 100.0,
+                        height: 100.0,
+                        rotation: 0.0,
+                        z_index: 0,
+                        auto_size: false,
+                    }),
+                );
+            })
+            .expect("update");
+        let mut rx = state.subscribe();
+
+        let json = r#"{"type":"camera","command":"fit_to_content","padding":0,"max_zoom":1.0}"#;
+        let msg: ClientMessage = serde_json::from_str(json).expect("should parse");
+        let ClientMessage::Camera {
+            command, options, ..
+        } = msg
+        else {
+            panic!("Expected Camera");
+        };
+        assert_eq!(command, CameraCommand::FitToContent);
+        assert!(options.padding.abs() < f32::EPSILON);
+
+        let frame = state
+            .apply_camera_command("default", &command, &options)
+            .expect("camera");
+        assert!((frame.zoom - 1.0).abs() < f32::EPSILON);
+        assert!((frame.pan_x - (400.0 - 2050.0)).abs() < 0.01);
+
+        // Viewers receive the new camera as a viewport patch
+        let event = rx.try_recv().expect("camera broadcast");
+        match event.message {
+            ServerMessage::ScenePatch { patch } => {
+                let viewport = patch.viewport.expect("viewport");
+                assert!((viewport.pan_x - frame.pan_x).abs() < f32::EPSILON);
+            }
+            other => panic!("expected scene patch, got {other:?}"),
+        }
+
+        let missing = CameraCommand::FitToElements {
+            element_ids: vec![ElementId::new()],
+        };
+        assert!(matches!(
+            state.apply_camera_command("default", &missing, &FitOptions::default()),
+            Err(SyncError::ElementNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_anchor_messages_broadcast_views() {
+        let state = SyncState::new();
+        let mut client = ClientConnection::with_peer_id(state.clone(), "peer-a".to_string());
+        let mut rx = state.subscribe();
+
+        let msg: ClientMessage = serde_json::from_value(serde_json::json!({
+            "type": "set_anchor",
+            "anchor": { "name": "details", "x": 1000.0, "y": 500.0, "zoom": 2.0 },
+            "message_id": "a1"
+        }))
+        .expect("should parse");
+        assert!(matches!(
+            client.handle_message(msg),
+            Some(ServerMessage::Ack { success: true, .. })
+        ));
+        match rx.try_recv().expect("anchor broadcast").message {
+            ServerMessage::SceneUpdate { scene } => assert_eq!(scene.anchors[0].name, "details"),
+            other => panic!("expected scene update, got {other:?}"),
+        }
+        state
+            .set_anchor("default", Anchor::new("summary", 400.0, 300.0, 1.0))
+            .expect("anchor");
+        match rx.try_recv().expect("anchor broadcast").message {
+            ServerMessage::ScenePatch { patch } => {
+                assert!(matches!(
+                    patch.ops.as_slice(),
+                    [PatchOp::Anchors { anchors }] if anchors.len() == 2
+                ));
+            }
+            other => panic!("expected scene patch, got {other:?}"),
+        }
+
+        let msg: ClientMessage = serde_json::from_str(
+            r#"{"type":"camera","command":"go_to_anchor","name":"details","message_id":"c1"}"#,
+        )
+        .expect("should parse");
+        assert!(matches!(
+            client.handle_message(msg),
+            Some(ServerMessage::Ack { success: true, .. })
+        ));
+        // Viewers are moved with a viewport patch
+        match rx.try_recv().expect("camera broadcast").message {
+            ServerMessage::ScenePatch { patch } => {
+                let viewport = patch.viewport.expect("viewport");
+                assert!((viewport.zoom - 2.0).abs() < f32::EPSILON);
+                assert!((viewport.pan_x - (400.0 - 2000.0)).abs() < 0.01);
+            }
+            other => panic!("expected scene patch, got {other:?}"),
+        }
+
+        assert!(state.go_to_anchor("default", "overview").is_err());
+        assert_eq!(
+            state
+                .remove_anchor("default", "details")
+                .expect("remove")
+                .name,
+            "details"
+        );
+        let msg: ClientMessage =
+            serde_json::from_str(r#"{"type":"remove_anchor","name":"details","message_id":"r1"}"#)
+                .expect("should parse");
+        assert!(matches!(
+            client.handle_message(msg),
+            Some(ServerMessage::Error { code, .. }) if code == "anchor_failed"
+        ));
+    }
+
+    #[test]
+    fn test_moving_image_syncs_pinned_markup() {
+        let state = SyncState::new();
+        let image = state
+            .store
+            .add_element(
+                "default",
+                Element::new(ElementKind::Image {
+                    src: "shot.png".to_string(),
+                    format: canvas_core::ImageFormat::Png,
+                    annotations: Vec::new(),
+                    extracted_text: None,
+                })
+                .with_transform(Transform {
+                    x: 0.0,
+                    y: 0.0,
+                    width: 200.0,
+                    height: 100.0,
+                    ..Transform::default()
+                }),
+            )
+            .expect("add image");
+        let mut note = None;
+        state
+            .store
+            .update("default", |scene| {
+                let mark = Element::new(ElementKind::Region {
+                    caption: None,
+                    color: Color::from_rgb(0xe5_3935).into(),
+                })
+                .with_transform(Transform {
+                    x: 50.0,
+                    y: 50.0,
+                    width: 20.0,
+                    height: 10.0,
+                    ..Transform::default()
+                });
+                note = scene.pin_markup(image, mark).ok();
+            })
+            .expect("pin");
+        let note = note.expect("pinned");
+        state.broadcast_scene_change("default", SyncOrigin::Local);
+        let mut rx = state.subscribe();
+
+        let changes = serde_json::json!({ "transform": { "x": 100.0, "width": 400.0 } });
+        state
+            .update_element("default", &image.to_string(), &changes, None)
+            .expect("move image");
+        match rx.try_recv().expect("move broadcast").message {
+            ServerMessage::ScenePatch { patch } => assert_eq!(patch.ops.len(), 2),
+            other => panic!("expected scene patch, got {other:?}"),
+        }
+        let scene = state.store.get("default").expect("scene");
+        let moved = scene.get_element(note).expect("note");
+        assert!((moved.transform.x - 200.0).abs() < 1e-3);
+        assert!((moved.transform.width - 40.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_update_dataset_message_syncs_delta() {
+        let state = SyncState::new();
+        let mut client = ClientConnection::with_peer_id(state.clone(), "peer-a".to_string());
+        state
+            .update_dataset(
+                "default",
+                &DatasetDelta::Put {
+                    dataset: canvas_core::Dataset::new(
+                        "sales",
+                        vec!["month".into(), "revenue".into()],
+                        vec![vec!["Jan".into(), 100.into()]],
+                    ),
+                },
+            )
+            .expect("put");
+        let chart = Element::new(ElementKind::Chart {
+            chart_type: "bar".to_string(),
+            data: serde_json::json!({}),
+            binding: serde_json::from_value(
+                serde_json::json!({ "dataset": "sales", "query": { "x": "month" } }),
+            )
+            .ok(),
+        });
+        let chart_id = chart.id;
+        state
+            .add_element("default", &ElementDocument::from(&chart), None)
+            .expect("add chart");
+        let mut rx = state.subscribe();
 
-        let scene = state.get_scene("default").expect("should have scene");
-        assert_eq!(scene.element_count(), 2);
+        let msg: ClientMessage = serde_json::from_value(serde_json::json!({
+            "type": "update_dataset",
+            "delta": { "op": "append", "id": "sales", "rows": [["Feb", 120]] },
+            "message_id": "d1"
+        }))
+        .expect("should parse");
+        match client.handle_message(msg) {
+            Some(ServerMessage::Ack {
+                success: true,
+                result: Some(result),
+                ..
+            }) => assert_eq!(result["refreshed"][0], chart_id.to_string()),
+            other => panic!("expected ack, got {other:?}"),
+        }
+        // Viewers get the appended row, not the refilled chart
+        match rx.try_recv().expect("dataset broadcast").message {
+            ServerMessage::ScenePatch { patch } => assert!(matches!(
+                patch.ops.as_slice(),
+                [PatchOp::Data { delta: DatasetDelta::Append { rows, .. } }] if rows.len() == 1
+            )),
+            other => panic!("expected scene patch, got {other:?}"),
+        }
+        let document = state.scene_document("default");
+        let ElementKind::Chart { data, .. } = &document.elements[0].kind else {
+            panic!("expected chart");
+        };
+        assert_eq!(data["x_labels"], serde_json::json!(["Jan", "Feb"]));
+
+        let msg: ClientMessage = serde_json::from_value(serde_json::json!({
+            "type": "update_dataset",
+            "delta": { "op": "append", "id": "missing", "rows": [] },
+            "message_id": "d2"
+        }))
+        .expect("should parse");
+        assert!(matches!(
+            client.handle_message(msg),
+            Some(ServerMessage::Error { code, .. }) if code == "dataset_failed"
+        ));
     }
 
     #[test]
-    fn test_sync_state_multiple_sessions() {
+    fn test_erase_restyle_and_undo_ink_messages() {
         let state = SyncState::new();
-
-        let element1 = ElementDocument {
-            id: String::new(),
-            kind: ElementKind::Text {
-                content: "Session 1".to_string(),
-                font_size: 16.0,
-                color: "#000000".to_string(),
-            },
-            transform: Transform::default(),
-            interactive: true,
-            selected: false,
+        let mut client = ClientConnection::with_peer_id(state.clone(), "peer-a".to_string());
+        let mut stroke = ElementId::new();
+        state
+            .update_scene("default", |scene| {
+                stroke = scene.add_element(canvas_core::ink::stroke(
+                    &[[0.0, 50.0], [200.0, 50.0]],
+                    Color::BLACK.into(),
+                    4.0,
+                ));
+            })
+            .expect("update");
+        let mut send = |json: serde_json::Value| {
+            let msg: ClientMessage = serde_json::from_value(json).expect("should parse");
+            client.handle_message(msg)
         };
 
-        let element2 = ElementDocument {
-            id: String::new(),
-            kind: ElementKind::Text {
-                content: "Session 2".to_string(),
-                font_size: 16.0,
-                color: "#000000".to_string(),
-            },
-            transform: Transform::default(),
-            interactive: true,
-            selected: false,
+        let Some(ServerMessage::Ack {
+            result: Some(result),
+            ..
+        }) = send(serde_json::json!({
+            "type": "erase_ink", "x": 100, "y": 50, "radius": 10, "message_id": "e1"
+        }))
+        else {
+            panic!("expected ack");
         };
+        assert_eq!(result["removed"], serde_json::json!([stroke.to_string()]));
+        let pieces = result["changed"].as_array().expect("pieces").clone();
+        assert_eq!(pieces.len(), 2);
+        assert_eq!(
+            state.get_scene("default").expect("scene").element_count(),
+            2
+        );
 
-        let _ = state.add_element("session-1", &element1);
-        let _ = state.add_element("session-2", &element2);
+        let Some(ServerMessage::Ack { .. }) = send(serde_json::json!({
+            "type": "restyle_ink", "element_ids": [pieces[0]], "color": "#e53935",
+            "width": 8, "message_id": "r1"
+        })) else {
+            panic!("expected ack");
+        };
 
-        let scene1 = state.get_scene("session-1").expect("should have scene 1");
-        let scene2 = state.get_scene("session-2").expect("should have scene 2");
+        // Undo the restyle, then the erase; redo the erase
+        for id in ["u1", "u2"] {
+            assert!(matches!(
+                send(serde_json::json!({ "type": "undo_ink", "message_id": id })),
+                Some(ServerMessage::Ack { success: true, .. })
+            ));
+        }
+        let scene = state.get_scene("default").expect("scene");
+        assert_eq!(scene.element_count(), 1);
+        assert!(scene.get_element(stroke).is_some());
+        assert!(matches!(
+            send(serde_json::json!({ "type": "undo_ink", "message_id": "u3" })),
+            Some(ServerMessage::Error { code, .. }) if code == "undo_failed"
+        ));
+        assert!(matches!(
+            send(serde_json::json!({ "type": "redo_ink", "message_id": "d1" })),
+            Some(ServerMessage::Ack { success: true, .. })
+        ));
+        assert!(state
+            .get_scene("default")
+            .expect("scene")
+            .get_element(stroke)
+            .is_none());
 
-        assert_eq!(scene1.element_count(), 1);
-        assert_eq!(scene2.element_count(), 1);
+        // Restyling something that is not ink fails
+        assert!(matches!(
+            send(serde_json::json!({
+                "type": "restyle_ink", "element_ids": [ElementId::new().to_string()],
+                "width": 2, "message_id": "r2"
+            })),
+            Some(ServerMessage::Error { code, .. }) if code == "restyle_failed"
+        ));
     }
 
     #[test]
-    fn test_client_connection_handle_ping() {
+    fn test_group_and_ungroup_messages() {
         let state = SyncState::new();
-        let mut client = ClientConnection::new(state);
+        let mut client = ClientConnection::with_peer_id(state.clone(), "peer-a".to_string());
+        let mut ids = Vec::new();
+        state
+            .update_scene("default", |scene| {
+                for x in [0.0, 300.0] {
+                    ids.push(
+                        scene.add_element(
+                            Element::new(ElementKind::Text {
+                                content: "Card".to_string(),
+                                font_size: 16.0,
+                                color: Color::BLACK.into(),
+                            })
+                            .with_transform(Transform {
+                                x,
+                                y: 50.0,
+                                width: 100.0,
+                                height: 40.0,
+                                rotation: 0.0,
+                                z_index: 0,
+                                auto_size: false,
+                            }),
+                        ),
+                    );
+                }
+            })
+            .expect("update");
+        let mut rx = state.subscribe();
 
-        let response = client.handle_message(ClientMessage::Ping);
-        assert!(response.is_some());
-        assert!(matches!(response.unwrap(), ServerMessage::Pong { .. }));
+        let json = serde_json::json!({
+            "type": "group",
+            "element_ids": ids.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            "message_id": "g1"
+        });
+        let msg: ClientMessage = serde_json::from_value(json).expect("should parse");
+        let Some(ServerMessage::Ack {
+            result: Some(result),
+            ..
+        }) = client.handle_message(msg)
+        else {
+            panic!("expected ack");
+        };
+        let group = result["id"].as_str().expect("group id").to_string();
+        assert!(rx.try_recv().is_ok(), "group broadcast");
+
+        let scene = state.get_scene("default").expect("scene");
+        let group_element = scene
+            .get_element(parse_element_id(&group).expect("id"))
+            .expect("group");
+        assert_eq!(group_element.kind.children(), ids.as_slice());
+        assert!((group_element.transform.width - 400.0).abs() < f32::EPSILON);
+
+        let msg: ClientMessage = serde_json::from_value(
+            serde_json::json!({ "type": "ungroup", "id": group, "message_id": "u1" }),
+        )
+        .expect("should parse");
+        let Some(ServerMessage::Ack {
+            result: Some(result),
+            ..
+        }) = client.handle_message(msg)
+        else {
+            panic!("expected ack");
+        };
+        assert_eq!(result["children"].as_array().map(Vec::len), Some(2));
+        let scene = state.get_scene("default").expect("scene");
+        assert_eq!(scene.element_count(), 2);
+        assert_eq!(scene.root_elements().count(), 2);
+
+        // Ungrouping a non-group fails without changes
+        let msg = ClientMessage::Ungroup {
+            id: ids[0].to_string(),
+            message_id: Some("u2".to_string()),
+        };
+        assert!(matches!(
+            client.handle_message(msg),
+            Some(ServerMessage::Error { ref code, .. }) if code == "ungroup_failed"
+        ));
     }
 
     #[test]
-    fn test_client_connection_handle_subscribe() {
+    fn test_peer_attribution_on_add_and_update() {
         let state = SyncState::new();
-        let mut client = ClientConnection::new(state);
+        let mut client_a = ClientConnection::with_peer_id(state.clone(), "peer-a".to_string());
+        let mut client_b = ClientConnection::with_peer_id(state.clone(), "peer-b".to_string());
 
-        let response = client.handle_message(ClientMessage::Subscribe {
-            session_id: "test-session".to_string(),
+        // Client-supplied attribution is overridden by the connection's peer
+        let json = r##"{"type":"add_element","message_id":"m1","element":{"id":"","created_by":"spoofed","kind":{"type":"Text","data":{"content":"Hi","font_size":16.0,"color":"#000"}}}}"##;
+        let msg: ClientMessage = serde_json::from_str(json).expect("should parse");
+        let Some(ServerMessage::Ack {
+            result: Some(result),
+            ..
+        }) = client_a.handle_message(msg)
+        else {
+            panic!("expected ack");
+        };
+        let id = result["id"].as_str().expect("id").to_string();
+        let element_id = parse_element_id(&id).expect("uuid");
+
+        client_b.handle_message(ClientMessage::UpdateElement {
+            id,
+            changes: serde_json::json!({ "transform": { "x": 10.0 } }),
+            message_id: None,
         });
 
-        assert!(response.is_some());
-        assert!(matches!(
-            response.unwrap(),
-            ServerMessage::SceneUpdate { .. }
-        ));
-        assert_eq!(client.session_id(), "test-session");
+        let scene = state.store().get("default").expect("scene");
+        let element = scene.get_element(element_id).expect("element");
+        assert_eq!(element.created_by.as_deref(), Some("peer-a"));
+        assert_eq!(element.last_modified_by.as_deref(), Some("peer-b"));
+        let document = element_to_data(element);
+        assert_eq!(document.created_by.as_deref(), Some("peer-a"));
     }
 
     #[test]
-    fn test_client_connection_handle_get_scene() {
+    fn test_view_state_presenter_lifecycle() {
         let state = SyncState::new();
-        let mut client = ClientConnection::new(state);
+        let _rx_a = state.register_peer("peer-a", "default");
+        let _rx_b = state.register_peer("peer-b", "default");
+        let mut client_a = ClientConnection::with_peer_id(state.clone(), "peer-a".to_string());
+        let mut client_b = ClientConnection::with_peer_id(state.clone(), "peer-b".to_string());
+        let mut rx = state.subscribe();
 
-        let response = client.handle_message(ClientMessage::GetScene);
-        assert!(response.is_some());
-        assert!(matches!(
-            response.unwrap(),
-            ServerMessage::SceneUpdate { .. }
-        ));
+        let json = r#"{"type":"view_state","view":{"camera":{"zoom":2.0,"pan_x":-100.0,"pan_y":0.0},"viewport_width":1024.0,"viewport_height":768.0}}"#;
+        let msg: ClientMessage = serde_json::from_str(json).expect("should parse");
+        assert!(client_a.handle_message(msg).is_none());
+
+        let event = rx.try_recv().expect("view broadcast");
+        match event.message {
+            ServerMessage::ViewState { presenter_id, view } => {
+                assert_eq!(presenter_id, "peer-a");
+                assert!((view.camera.zoom - 2.0).abs() < f32::EPSILON);
+            }
+            other => panic!("expected view_state, got {other:?}"),
+        }
+        assert!(state.presenter_view("default").is_some());
+
+        // Only the presenter can stop the presentation
+        assert!(client_b
+            .handle_message(ClientMessage::StopPresenting)
+            .is_none());
+        assert!(state.presenter_view("default").is_some());
+
+        // Disconnecting the presenter ends it
+        state.unregister_peer("peer-a");
+        assert!(state.presenter_view("default").is_none());
+        let stopped = std::iter::from_fn(|| rx.try_recv().ok())
+            .any(|event| matches!(event.message, ServerMessage::PresenterStopped { ref presenter_id } if presenter_id == "peer-a"));
+        assert!(stopped);
     }
 
     #[test]
-    fn test_element_data_default_id() {
-        let json = r##"{"id":"","kind":{"type":"Text","data":{"content":"Test","font_size":16.0,"color":"#000000"}}}"##;
-        let element_doc: ElementDocument = serde_json::from_str(json).expect("should parse");
-        let element = element_from_data(&element_doc).expect("should convert");
-        assert!(!element.id.to_string().is_empty());
+    fn test_replica_mirrors_and_rejects_writes() {
+        let state = SyncState::new();
+        let (follow_tx, mut follow_rx) = mpsc::unbounded_channel();
+        state.set_replica("ws://primary/ws/sync".to_string(), follow_tx);
+        assert_eq!(state.consistency(), Consistency::Eventual);
+
+        // Accessing a session asks for it to be mirrored
+        state.record_access("town-hall");
+        assert_eq!(follow_rx.try_recv().ok().as_deref(), Some("town-hall"));
+
+        let element = element_to_data(&Element::new(ElementKind::Text {
+            content: "Mirrored".to_string(),
+            font_size: 16.0,
+            color: Color::BLACK.into(),
+        }));
+        assert!(matches!(
+            state.add_element("town-hall", &element, None),
+            Err(SyncError::ReadOnly)
+        ));
+
+        let mut client = ClientConnection::new(state.clone());
+        let response = client.handle_message(ClientMessage::AddElement {
+            element: element.clone(),
+            message_id: Some("m1".to_string()),
+        });
+        assert!(matches!(
+            response,
+            Some(ServerMessage::Error { ref code, message_id: Some(ref id), .. })
+                if code == "read_only" && id == "m1"
+        ));
+
+        // Mirrored scenes still reach clients, as remote events
+        let mut rx = state.subscribe();
+        let mut scene = Scene::new(800.0, 600.0);
+        scene.add_element(element_from_data(&element).expect("element"));
+        state.mirror_scene("town-hall", scene).expect("mirror");
+        let event = rx.try_recv().expect("broadcast");
+        assert_eq!(event.origin, SyncOrigin::Remote);
+        assert_eq!(state.scene_document("town-hall").elements.len(), 1);
     }
 
     #[test]
-    fn test_element_kind_data_text_defaults() {
-        let json =
-            r##"{"type":"Text","data":{"content":"Test","font_size":16.0,"color":"#000000"}}"##;
-        let kind: ElementKind = serde_json::from_str(json).expect("should parse");
-        match kind {
-            ElementKind::Text {
-                font_size, color, ..
-            } => {
-                assert!((font_size - 16.0).abs() < f32::EPSILON);
-                assert_eq!(color, "#000000");
-            }
-            _ => panic!("Expected Text"),
+    fn test_read_only_client_cannot_change_the_scene_through_actions() {
+        let state = SyncState::new();
+        let mut client = ClientConnection::new(state.clone());
+        client.set_read_only(true);
+        for action in ["vote", "randomize", "move_marker", "toggle_node"] {
+            let response = client.handle_message(ClientMessage::Interaction {
+                interaction_type: "action".to_string(),
+                element_id: Some("poll".to_string()),
+                data: serde_json::json!({
+                    "action": action,
+                    "payload": { "option": 0, "marker": "desk", "x": 1.0, "y": 2.0, "node": "root" }
+                }),
+                message_id: Some(action.to_string()),
+            });
+            assert!(
+                matches!(
+                    response,
+                    Some(ServerMessage::Error { ref code, message_id: Some(ref id), .. })
+                        if code == "read_only" && id == action
+                ),
+                "{action} was not refused: {response:?}"
+            );
         }
     }
 
     #[test]
-    fn test_transform_data_defaults() {
-        let json = r##"{"id":"","kind":{"type":"Text","data":{"content":"Example","font_size":16.0,"color":"#000000"}}}"##;
-        let doc: ElementDocument = serde_json::from_str(json).expect("should parse");
-        assert!((doc.transform.width - 100.0).abs() < f32::EPSILON);
-        assert!((doc.transform.height - 100.0).abs() < f32::EPSILON);
+    fn test_read_only_client_cannot_write() {
+        let state = SyncState::new();
+        let mut client = ClientConnection::new(state.clone());
+        client.set_read_only(true);
+        let element = element_to_data(&Element::new(ElementKind::Text {
+            content: "Viewer".to_string(),
+            font_size: 16.0,
+            color: Color::BLACK.into(),
+        }));
+        let response = client.handle_message(ClientMessage::AddElement {
+            element,
+            message_id: Some("m1".to_string()),
+        });
+        assert!(matches!(
+            response,
+            Some(ServerMessage::Error { ref code, ref message, .. })
+                if code == "read_only" && message.contains("Viewers")
+        ));
+        assert!(state.scene_document("default").elements.is_empty());
     }
 
     #[test]
-    fn test_queued_operation_add() {
-        let json = r##"{"op":"add","element":{"id":"","kind":{"type":"Text","data":{"content":"Queued","font_size":16.0,"color":"#000000"}}},"timestamp":12345}"##;
-        let op: QueuedOperation = serde_json::from_str(json).expect("should parse");
-        match op {
-            QueuedOperation::Add { timestamp, .. } => assert_eq!(timestamp, 12345),
-            _ => panic!("Expected Add"),
-        }
-    }
+    fn test_read_only_client_never_sees_sensitive_content() {
+        let state = SyncState::new();
+        let secret = |content: &str| {
+            element_to_data(
+                &Element::new(ElementKind::Text {
+                    content: content.to_string(),
+                    font_size: 16.0,
+                    color: Color::BLACK.into(),
+                })
+                .with_sensitive(true),
+            )
+        };
+        let id = state
+            .add_element("default", &secret("Salary: 120k"), None)
+            .expect("add");
+        let mut viewer = ClientConnection::new(state.clone());
+        viewer.set_read_only(true);
+        let mut editor = ClientConnection::new(state.clone());
+        let seen = |message: &ServerMessage| serde_json::to_string(message).expect("json");
 
-    #[test]
-    fn test_queued_operation_remove() {
-        let json = r#"{"op":"remove","id":"some-id","timestamp":54321}"#;
-        let op: QueuedOperation = serde_json::from_str(json).expect("should parse");
-        match op {
-            QueuedOperation::Remove { id, timestamp } => {
-                assert_eq!(id, "some-id");
-                assert_eq!(timestamp, 54321);
-            }
-            _ => panic!("Expected Remove"),
+        let loaded = seen(&viewer.load_scene());
+        assert!(!loaded.contains("Salary"), "{loaded}");
+        assert!(seen(&editor.load_scene()).contains("Salary"));
+
+        state
+            .add_element("default", &secret("Bonus: 30k"), None)
+            .expect("add");
+        state
+            .update_element(
+                "default",
+                &id.to_string(),
+                &serde_json::json!({ "transform": { "x": 40.0 } }),
+                None,
+            )
+            .expect("update");
+        let mut patches = 0;
+        while let Some(message) = viewer.try_recv_event() {
+            let json = seen(&message);
+            assert!(
+                !json.contains("Salary") && !json.contains("Bonus"),
+                "{json}"
+            );
+            patches += usize::from(matches!(message, ServerMessage::ScenePatch { .. }));
         }
+        // The viewer still learns that the scene changed
+        assert!(patches > 0);
+        let mut edits = Vec::new();
+        while let Some(message) = editor.try_recv_event() {
+            edits.push(seen(&message));
+        }
+        assert!(edits.iter().any(|json| json.contains("Bonus")));
+        assert!(edits.iter().any(|json| json.contains("Salary")));
     }
 
     #[test]
-    fn test_broadcast_event_subscription() {
+    fn test_element_quota_is_shared_by_an_identity() {
         let state = SyncState::new();
-        let mut rx = state.subscribe();
-
-        let element = ElementDocument {
-            id: String::new(),
-            kind: ElementKind::Text {
-                content: "Broadcast Test".to_string(),
+        let mut config = crate::quota::QuotaConfig::default();
+        config.default.elements_per_min = Some(2);
+        state.quotas().set_config(config);
+        let add = |id: &str| ClientMessage::AddElement {
+            element: element_to_data(&Element::new(ElementKind::Text {
+                content: "Chatty".to_string(),
                 font_size: 16.0,
-                color: "#000000".to_string(),
-            },
-            transform: Transform::default(),
-            interactive: true,
-            selected: false,
+                color: Color::BLACK.into(),
+            })),
+            message_id: Some(id.to_string()),
         };
 
-        // This should trigger a broadcast
-        let _ = state.add_element("default", &element);
+        let mut first = ClientConnection::new(state.clone());
+        let mut second = ClientConnection::new(state.clone());
+        first.set_identity("agent-a");
+        second.set_identity("agent-a");
+        assert!(matches!(
+            first.handle_message(add("m1")),
+            Some(ServerMessage::Ack { .. })
+        ));
+        assert!(matches!(
+            second.handle_message(add("m2")),
+            Some(ServerMessage::Ack { .. })
+        ));
+        assert!(matches!(
+            first.handle_message(add("m3")),
+            Some(ServerMessage::Error { ref code, .. }) if code == "quota_exceeded"
+        ));
+        assert_eq!(state.scene_document("default").elements.len(), 2);
 
-        // Should receive the broadcast
-        let event = rx.try_recv();
-        assert!(event.is_ok());
-        let sync_event = event.unwrap();
-        assert_eq!(sync_event.session_id, "default");
-        assert!(
-            matches!(sync_event.message, ServerMessage::ElementAdded { .. })
-                || matches!(sync_event.message, ServerMessage::SceneUpdate { .. })
-        );
+        let Some(ServerMessage::QuotaStatus { quota }) =
+            second.handle_message(ClientMessage::GetQuota)
+        else {
+            panic!("expected quota_status");
+        };
+        assert_eq!(quota.identity, "agent-a");
+        assert_eq!(quota.elements.map(|b| b.remaining), Some(0));
     }
 
     #[test]
@@ -4314,11 +6954,21 @@ mod tests {
                 kind: ElementKind::Text {
                     content: "test".to_string(),
                     font_size: 16.0,
-                    color: "#000".to_string(),
+                    color: Color::BLACK.into(),
                 },
                 transform: Transform::default(),
                 interactive: true,
                 selected: false,
+                visible: true,
+                opacity: 1.0,
+                screen_anchored: false,
+                sensitive: false,
+                style: Style::default(),
+                interactions: Interactions::default(),
+                created_by: None,
+                last_modified_by: None,
+                attachment: None,
+                depth: None,
             },
             timestamp: 100,
         };
@@ -4346,11 +6996,21 @@ mod tests {
                 kind: ElementKind::Text {
                     content: "test".to_string(),
                     font_size: 16.0,
-                    color: "#000".to_string(),
+                    color: Color::BLACK.into(),
                 },
                 transform: Transform::default(),
                 interactive: true,
                 selected: false,
+                visible: true,
+                opacity: 1.0,
+                screen_anchored: false,
+                sensitive: false,
+                style: Style::default(),
+                interactions: Interactions::default(),
+                created_by: None,
+                last_modified_by: None,
+                attachment: None,
+                depth: None,
             },
             timestamp: 100,
         };
@@ -4487,7 +7147,7 @@ mod tests {
             timestamp: 100,
         }];
 
-        let result = state.process_queue("default", operations);
+        let result = state.process_queue("default", operations, None);
         // The remove should fail since element doesn't exist
         assert_eq!(result.processed_count, 0);
         assert_eq!(result.failed_count, 1);
@@ -4534,7 +7194,7 @@ mod tests {
         let element = Element::new(ElementKind::Text {
             content: "Test".to_string(),
             font_size: 16.0,
-            color: "#000000".to_string(),
+            color: Color::BLACK.into(),
         });
         let operations = vec![Operation::AddElement {
             element,
@@ -4582,7 +7242,7 @@ mod tests {
         let element = Element::new(ElementKind::Text {
             content: "Hello".to_string(),
             font_size: 24.0,
-            color: "#000000".to_string(),
+            color: Color::BLACK.into(),
         });
         let element_id = element.id;
         let add_op = Operation::AddElement {
@@ -4625,7 +7285,7 @@ mod tests {
         let element = Element::new(ElementKind::Text {
             content: "ToRemove".to_string(),
             font_size: 16.0,
-            color: "#ff0000".to_string(),
+            color: Color::from_rgb(0xff_0000).into(),
         });
         let element_id = element.id;
         let add_op = Operation::AddElement {
@@ -4716,7 +7376,7 @@ mod tests {
         let mut element = Element::new(ElementKind::Text {
             content: "Test".to_string(),
             font_size: 16.0,
-            color: "#000000".to_string(),
+            color: Color::BLACK.into(),
         });
         let original_x = element.transform.x;
         let original_y = element.transform.y;
@@ -4740,7 +7400,7 @@ mod tests {
         let mut element = Element::new(ElementKind::Text {
             content: "Test".to_string(),
             font_size: 16.0,
-            color: "#000000".to_string(),
+            color: Color::BLACK.into(),
         });
 
         // Try to set a value larger than f32::MAX
@@ -4761,7 +7421,7 @@ mod tests {
         let mut element = Element::new(ElementKind::Text {
             content: "Test".to_string(),
             font_size: 16.0,
-            color: "#000000".to_string(),
+            color: Color::BLACK.into(),
         });
 
         // Include unknown fields alongside known ones
@@ -4786,7 +7446,7 @@ mod tests {
         let mut element = Element::new(ElementKind::Text {
             content: "Test".to_string(),
             font_size: 16.0,
-            color: "#000000".to_string(),
+            color: Color::BLACK.into(),
         });
         // Set initial transform values
         element.transform.x = 10.0;
@@ -4815,7 +7475,7 @@ mod tests {
         let mut element = Element::new(ElementKind::Text {
             content: "Test".to_string(),
             font_size: 16.0,
-            color: "#000000".to_string(),
+            color: Color::BLACK.into(),
         });
 
         let changes = serde_json::json!({
@@ -4840,12 +7500,108 @@ mod tests {
         assert!(element.interactive);
     }
 
+    #[test]
+    fn test_apply_changes_visibility_and_opacity() {
+        let mut element = Element::new(ElementKind::Text {
+            content: "Test".to_string(),
+            font_size: 16.0,
+            color: Color::BLACK.into(),
+        });
+
+        let changes = serde_json::json!({ "visible": false, "opacity": 1.5 });
+        apply_changes_to_element(&mut element, &changes);
+        assert!(!element.visible);
+        assert!((element.opacity - 1.0).abs() < f32::EPSILON);
+
+        let changes = serde_json::json!({ "opacity": 0.25 });
+        apply_changes_to_element(&mut element, &changes);
+        assert!(!element.visible);
+        assert!((element.opacity - 0.25).abs() < f32::EPSILON);
+        assert!(element.effective_opacity().abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn test_apply_changes_screen_anchored() {
+        let mut element = Element::new(ElementKind::Text {
+            content: "Timer".to_string(),
+            font_size: 16.0,
+            color: Color::BLACK.into(),
+        });
+
+        apply_changes_to_element(
+            &mut element,
+            &serde_json::json!({ "screen_anchored": true }),
+        );
+        assert!(element.screen_anchored);
+
+        apply_changes_to_element(
+            &mut element,
+            &serde_json::json!({ "screen_anchored": "yes" }),
+        );
+        assert!(element.screen_anchored);
+    }
+
+    #[test]
+    fn test_apply_changes_attachment() {
+        let mut element = Element::new(ElementKind::Text {
+            content: "Label".to_string(),
+            font_size: 16.0,
+            color: Color::BLACK.into(),
+        });
+        let target = ElementId::new();
+
+        apply_changes_to_element(
+            &mut element,
+            &serde_json::json!({ "attachment": { "target": target, "at": "bottom" } }),
+        );
+        assert_eq!(element.attachment.map(|a| a.target), Some(target));
+
+        apply_changes_to_element(&mut element, &serde_json::json!({ "attachment": 42 }));
+        assert!(element.attachment.is_some());
+
+        apply_changes_to_element(&mut element, &serde_json::json!({ "attachment": null }));
+        assert!(element.attachment.is_none());
+    }
+
+    #[test]
+    fn test_apply_changes_style() {
+        let mut element = Element::new(ElementKind::Text {
+            content: "Test".to_string(),
+            font_size: 16.0,
+            color: Color::BLACK.into(),
+        });
+
+        let changes = serde_json::json!({
+            "style": {
+                "fill": { "type": "solid", "color": "#ff0000" },
+                "stroke": { "color": "#000000", "width": 2.0 },
+                "corner_radius": 6.0,
+                "opacity": 0.5
+            }
+        });
+        apply_changes_to_element(&mut element, &changes);
+
+        assert_eq!(
+            element.style.fill,
+            Some(canvas_core::Fill::Solid {
+                color: Color::from_rgb(0xff_0000).into()
+            })
+        );
+        assert!((element.style.corner_radius - 6.0).abs() < f32::EPSILON);
+        assert!((element.style.opacity - 0.5).abs() < f32::EPSILON);
+
+        // Invalid style payloads leave the existing style untouched
+        let changes = serde_json::json!({ "style": { "fill": "not-a-fill" } });
+        apply_changes_to_element(&mut element, &changes);
+        assert!((element.style.corner_radius - 6.0).abs() < f32::EPSILON);
+    }
+
     #[test]
     fn test_apply_changes_z_index_clamping() {
         let mut element = Element::new(ElementKind::Text {
             content: "Test".to_string(),
             font_size: 16.0,
-            color: "#000000".to_string(),
+            color: Color::BLACK.into(),
         });
 
         // Try to set z_index larger than i32::MAX
@@ -4870,7 +7626,7 @@ mod tests {
         let element = Element::new(ElementKind::Text {
             content: "Valid".to_string(),
             font_size: 16.0,
-            color: "#000000".to_string(),
+            color: Color::BLACK.into(),
         });
         let valid_id = element.id;
 
@@ -4981,7 +7737,7 @@ mod tests {
         let element = Element::new(ElementKind::Text {
             content: "Test".to_string(),
             font_size: 16.0,
-            color: "#000000".to_string(),
+            color: Color::BLACK.into(),
         });
         let _element_id = element.id;
         let _ = store.add_element("default", element.clone());
@@ -5005,7 +7761,7 @@ mod tests {
         let element = Element::new(ElementKind::Text {
             content: "Test".to_string(),
             font_size: 16.0,
-            color: "#000000".to_string(),
+            color: Color::BLACK.into(),
         });
         let element_id = element.id;
 
@@ -5074,7 +7830,7 @@ mod tests {
         let element = Element::new(ElementKind::Text {
             content: "Test".to_string(),
             font_size: 16.0,
-            color: "#000000".to_string(),
+            color: Color::BLACK.into(),
         });
         let op = Operation::AddElement {
             element,
@@ -5094,7 +7850,7 @@ mod tests {
         let element = Element::new(ElementKind::Text {
             content: "Test".to_string(),
             font_size: 16.0,
-            color: "#000000".to_string(),
+            color: Color::BLACK.into(),
         });
         let op = Operation::AddElement {
             element,
@@ -5174,7 +7930,7 @@ mod tests {
         let element = Element::new(ElementKind::Text {
             content: "Test".to_string(),
             font_size: 16.0,
-            color: "#000000".to_string(),
+            color: Color::BLACK.into(),
         });
         let _element_id = element.id;
         let _ = store.add_element("default", element.clone());
@@ -5266,7 +8022,7 @@ mod tests {
             element: Element::new(ElementKind::Text {
                 content: "Test".to_string(),
                 font_size: 16.0,
-                color: "#000000".to_string(),
+                color: Color::BLACK.into(),
             }),
             timestamp: 100,
         };
@@ -5391,7 +8147,7 @@ mod tests {
         let element = Element::new(ElementKind::Text {
             content: "test text".to_string(),
             font_size: 16.0,
-            color: "#ffffff".to_string(),
+            color: Color::WHITE.into(),
         });
         let operations = vec![Operation::AddElement {
             element: element.clone(),
@@ -5440,7 +8196,7 @@ mod tests {
         let element = Element::new(ElementKind::Text {
             content: "test".to_string(),
             font_size: 16.0,
-            color: "#ffffff".to_string(),
+            color: Color::WHITE.into(),
         });
         let operations = vec![Operation::AddElement {
             element,
@@ -5469,12 +8225,12 @@ mod tests {
         let element1 = Element::new(ElementKind::Text {
             content: "First".to_string(),
             font_size: 16.0,
-            color: "#000000".to_string(),
+            color: Color::BLACK.into(),
         });
         let element2 = Element::new(ElementKind::Text {
             content: "Second".to_string(),
             font_size: 18.0,
-            color: "#ffffff".to_string(),
+            color: Color::WHITE.into(),
         });
         let element1_id = element1.id;
         let element2_id = element2.id;
@@ -5522,7 +8278,7 @@ mod tests {
         let element = Element::new(ElementKind::Text {
             content: "Test".to_string(),
             font_size: 16.0,
-            color: "#000000".to_string(),
+            color: Color::BLACK.into(),
         });
         let element_id = element.id;
         let add_op = Operation::AddElement {
@@ -5562,7 +8318,7 @@ mod tests {
                 element: Element::new(ElementKind::Text {
                     content: "Test".to_string(),
                     font_size: 16.0,
-                    color: "#000000".to_string(),
+                    color: Color::BLACK.into(),
                 }),
                 timestamp: 1000,
             },
@@ -5609,7 +8365,7 @@ mod tests {
                 element: Element::new(ElementKind::Text {
                     content: "Test".to_string(),
                     font_size: 16.0,
-                    color: "#000000".to_string(),
+                    color: Color::BLACK.into(),
                 }),
                 timestamp: 1000,
             },
@@ -5657,7 +8413,7 @@ mod tests {
                 element: Element::new(ElementKind::Text {
                     content: "Test".to_string(),
                     font_size: 16.0,
-                    color: "#000000".to_string(),
+                    color: Color::BLACK.into(),
                 }),
                 timestamp: 1000,
             },
@@ -5722,7 +8478,7 @@ mod tests {
         let element = Element::new(ElementKind::Text {
             content: "Success".to_string(),
             font_size: 16.0,
-            color: "#000000".to_string(),
+            color: Color::BLACK.into(),
         });
         let operations = vec![Operation::AddElement {
             element,
@@ -5747,7 +8503,7 @@ mod tests {
         let element = Element::new(ElementKind::Text {
             content: "Exists".to_string(),
             font_size: 16.0,
-            color: "#000000".to_string(),
+            color: Color::BLACK.into(),
         });
         let element_id = element.id;
         let add_op = Operation::AddElement {
@@ -5812,7 +8568,7 @@ mod tests {
                 element: Element::new(ElementKind::Text {
                     content: "T".to_string(),
                     font_size: 16.0,
-                    color: "#000".to_string(),
+                    color: Color::BLACK.into(),
                 }),
                 timestamp: 1000,
             },
@@ -5874,7 +8630,7 @@ mod tests {
                 element: Element::new(ElementKind::Text {
                     content: "T".to_string(),
                     font_size: 16.0,
-                    color: "#000".to_string(),
+                    color: Color::BLACK.into(),
                 }),
                 timestamp: 1000,
             },
@@ -5907,11 +8663,11 @@ mod tests {
         use proptest::prelude::*;
 
         fn arb_element_kind() -> impl Strategy<Value = ElementKind> {
-            (any::<String>(), 1.0f32..100.0f32, any::<String>()).prop_map(
+            (any::<String>(), 1.0f32..100.0f32, any::<u32>()).prop_map(
                 |(content, font_size, color)| ElementKind::Text {
                     content: content.chars().take(100).collect(),
                     font_size,
-                    color: format!("#{:06x}", color.len() % 0xFFFFFF),
+                    color: Color::from_rgb(color & 0x00ff_ffff).into(),
                 },
             )
         }
@@ -6029,7 +8785,7 @@ mod tests {
                 Element::new(canvas_core::ElementKind::Text {
                     content: "stale".into(),
                     font_size: 12.0,
-                    color: "#000".into(),
+                    color: Color::BLACK.into(),
                 }),
             )
             .expect("add");
@@ -6041,7 +8797,7 @@ mod tests {
                 Element::new(canvas_core::ElementKind::Text {
                     content: "active".into(),
                     font_size: 12.0,
-                    color: "#000".into(),
+                    color: Color::BLACK.into(),
                 }),
             )
             .expect("add");
@@ -0,0 +1,95 @@
+//! Integration tests for the `canvas-client` crate against a real server.
+
+mod common;
+
+use std::time::Duration;
+
+use canvas_client::{CanvasClient, ClientConfig, ClientEvent, ServerMessage};
+use canvas_core::{Color, Element, ElementDocument, ElementKind};
+use tokio::sync::broadcast;
+use tokio::time::timeout;
+
+use common::TestServer;
+
+/// Wait for the first server message matching `matches`.
+async fn recv_message(
+    events: &mut broadcast::Receiver<ClientEvent>,
+    matches: impl Fn(&ServerMessage) -> bool,
+) -> Option<ServerMessage> {
+    timeout(Duration::from_secs(5), async {
+        loop {
+            match events.recv().await {
+                Ok(ClientEvent::Message(message)) if matches(&message) => return Some(message),
+                Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => {}
+                Err(broadcast::error::RecvError::Closed) => return None,
+            }
+        }
+    })
+    .await
+    .ok()
+    .flatten()
+}
+
+#[tokio::test]
+async fn client_adds_element_and_peer_mirror_follows() {
+    let server = TestServer::start().await;
+
+    let writer = CanvasClient::connect(ClientConfig::new(server.ws_url()))
+        .await
+        .expect("writer connects");
+    let reader = CanvasClient::connect(ClientConfig::new(server.ws_url()))
+        .await
+        .expect("reader connects");
+    let mut writer_events = writer.events();
+
+    let element = ElementDocument::from(&Element::new(ElementKind::Text {
+        content: "From the client crate".to_string(),
+        font_size: 16.0,
+        color: Color::BLACK.into(),
+    }));
+    let element_id = element.id.clone();
+    writer
+        .add_element(element, Some("add-1".to_string()))
+        .expect("send");
+
+    let ack = recv_message(
+        &mut writer_events,
+        |m| matches!(m, ServerMessage::Ack { message_id, .. } if message_id == "add-1"),
+    )
+    .await;
+    assert!(matches!(
+        ack,
+        Some(ServerMessage::Ack { success: true, .. })
+    ));
+
+    // The reader's mirror follows the broadcast patch
+    let mirrored = timeout(Duration::from_secs(5), async {
+        loop {
+            if reader
+                .scene()
+                .is_some_and(|scene| scene.elements().any(|e| e.id.to_string() == element_id))
+            {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await;
+    assert!(mirrored.is_ok(), "reader never saw the element");
+    assert!(reader.peer_id().is_some());
+    assert_eq!(reader.queued_len(), 0);
+
+    writer.close();
+    let closed = timeout(Duration::from_secs(5), async {
+        loop {
+            if let Ok(ClientEvent::Closed) = writer_events.recv().await {
+                break;
+            }
+        }
+    })
+    .await;
+    assert!(closed.is_ok());
+    assert!(writer.send(canvas_client::ClientMessage::Ping).is_err());
+
+    server.shutdown().await;
+}
@@ -3,16 +3,17 @@
 //! Provides a way to spin up a real Axum server on a random port
 //! for integration testing with WebSocket, HTTP, and export clients.
 
+use std::collections::HashMap;
 use std::net::SocketAddr;
 use std::sync::Arc;
 
 use axum::{
-    extract::{ws::WebSocketUpgrade, State},
+    extract::{ws::WebSocketUpgrade, Query, State},
     response::IntoResponse,
     routing::{get, post},
     Json, Router,
 };
-use canvas_core::SceneDocument;
+use canvas_core::{Encoding, SceneDocument};
 use canvas_mcp::{server::CanvasMcpServer, JsonRpcRequest, JsonRpcResponse};
 use tokio::net::TcpListener;
 use tokio::sync::oneshot;
@@ -21,7 +22,9 @@ use tower_http::cors::{Any, CorsLayer};
 
 // Re-use types from canvas-server
 use canvas_server::routes;
-use canvas_server::sync::{current_timestamp, handle_sync_socket, SyncOrigin, SyncState};
+use canvas_server::sync::{
+    current_timestamp, handle_sync_socket_with_options, SyncOrigin, SyncSocketOptions, SyncState,
+};
 use canvas_server::AppState;
 
 /// A test server instance with control handles.
@@ -70,7 +73,24 @@ impl TestServer {
             .route("/ws", get(ws_handler))
             .route("/ws/sync", get(ws_handler))
             .route("/mcp", post(mcp_handler))
+            .route(
+                "/api/scene/{session_id}/validate",
+                get(routes::validate_scene_handler),
+            )
+            .route(
+                "/api/scene/{session_id}/element/{file}",
+                get(routes::export_element_handler),
+            )
+            .route(
+                "/api/scene/{session_id}/export.svg",
+                get(routes::export_svg_handler),
+            )
+            .route(
+                "/api/scene/{session_id}/thumbnail.png",
+                get(routes::thumbnail_handler),
+            )
             .route("/api/export", post(routes::export_scene_handler))
+            .route("/api/types.d.ts", get(routes::types_handler))
             .layer(CorsLayer::new().allow_origin(Any).allow_methods(Any))
             .with_state(state);
 
@@ -152,8 +172,20 @@ async fn health_handler() -> &'static str {
     "ok"
 }
 
-async fn ws_handler(ws: WebSocketUpgrade, State(state): State<AppState>) -> impl IntoResponse {
-    ws.on_upgrade(move |socket| handle_sync_socket(socket, state.sync))
+async fn ws_handler(
+    ws: WebSocketUpgrade,
+    Query(params): Query<HashMap<String, String>>,
+    State(state): State<AppState>,
+) -> impl IntoResponse {
+    let options = SyncSocketOptions {
+        encoding: params
+            .get("encoding")
+            .and_then(|name| Encoding::from_name(name))
+            .unwrap_or_default(),
+        page_size: params.get("page_size").and_then(|size| size.parse().ok()),
+        ..SyncSocketOptions::default()
+    };
+    ws.on_upgrade(move |socket| handle_sync_socket_with_options(socket, state.sync, options))
 }
 
 async fn mcp_handler(
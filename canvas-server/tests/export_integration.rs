@@ -6,6 +6,7 @@
 mod common;
 
 use canvas_core::element::{Element, ElementKind, Transform};
+use canvas_core::{Color, Fill, ShapeKind, Style, ThemeColor};
 use common::TestServer;
 
 /// Helper to create a text element at a given position.
@@ -13,7 +14,7 @@ fn text_element(content: &str, x: f32, y: f32) -> Element {
     Element::new(ElementKind::Text {
         content: content.to_string(),
         font_size: 16.0,
-        color: "#000000".to_string(),
+        color: Color::BLACK.into(),
     })
     .with_transform(Transform {
         x,
@@ -22,6 +23,7 @@ fn text_element(content: &str, x: f32, y: f32) -> Element {
         height: 30.0,
         rotation: 0.0,
         z_index: 0,
+        auto_size: false,
     })
 }
 
@@ -171,6 +173,47 @@ async fn test_export_pdf_returns_valid_document() {
     server.shutdown().await;
 }
 
+#[tokio::test]
+async fn test_export_pdf_print_layout() {
+    let server = TestServer::start().await;
+    seed_session(&server, "test-print");
+
+    let client = reqwest::Client::new();
+    let resp = client
+        .post(server.export_url())
+        .json(&serde_json::json!({
+            "session_id": "test-print",
+            "format": "pdf",
+            "print": { "paper": "letter", "orientation": "landscape", "dpi": 72 }
+        }))
+        .send()
+        .await
+        .expect("request");
+
+    assert_eq!(resp.status(), 200);
+    let bytes = resp.bytes().await.expect("body");
+    assert_eq!(&bytes[0..5], b"%PDF-");
+
+    for (format, print) in [
+        ("png", serde_json::json!({})),
+        ("pdf", serde_json::json!({ "margin_mm": 500 })),
+    ] {
+        let resp = client
+            .post(server.export_url())
+            .json(&serde_json::json!({
+                "session_id": "test-print",
+                "format": format,
+                "print": print
+            }))
+            .send()
+            .await
+            .expect("request");
+        assert_eq!(resp.status(), 400, "{format} {print}");
+    }
+
+    server.shutdown().await;
+}
+
 #[tokio::test]
 async fn test_export_jpg_alias_works() {
     let server = TestServer::start().await;
@@ -377,3 +420,254 @@ async fn test_export_large_scene() {
 
     server.shutdown().await;
 }
+
+// ==========================================================================
+// Single element export
+// ==========================================================================
+
+#[tokio::test]
+async fn test_export_element_png_is_cropped() {
+    let server = TestServer::start().await;
+    let store = server.sync_state().store();
+    let _ = store.get_or_create("element-export");
+    let target = text_element("Chart title", 300.0, 200.0);
+    let id = target.id;
+    store
+        .update("element-export", |scene| {
+            scene.add_element(text_element("Background", 0.0, 0.0));
+            scene.add_element(target);
+        })
+        .expect("seed session");
+
+    let client = reqwest::Client::new();
+    let resp = client
+        .get(format!(
+            "{}/api/scene/element-export/element/{id}.png?width=432",
+            server.base_url()
+        ))
+        .send()
+        .await
+        .expect("request");
+
+    assert_eq!(resp.status(), 200);
+    assert_eq!(
+        resp.headers()
+            .get("content-type")
+            .and_then(|v| v.to_str().ok()),
+        Some("image/png")
+    );
+    let bytes = resp.bytes().await.expect("body");
+    assert_eq!(&bytes[0..4], &[137, 80, 78, 71]);
+    // IHDR: 200x30 element plus padding, scaled to the requested width
+    let width = u32::from_be_bytes([bytes[16], bytes[17], bytes[18], bytes[19]]);
+    let height = u32::from_be_bytes([bytes[20], bytes[21], bytes[22], bytes[23]]);
+    assert_eq!((width, height), (432, 92));
+
+    let svg = client
+        .get(format!(
+            "{}/api/scene/element-export/element/{id}.svg",
+            server.base_url()
+        ))
+        .send()
+        .await
+        .expect("request")
+        .text()
+        .await
+        .expect("body");
+    assert!(svg.contains("Chart title"));
+    assert!(!svg.contains("Background"));
+
+    server.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_export_element_errors() {
+    let server = TestServer::start().await;
+    seed_session(&server, "element-errors");
+    let base = format!("{}/api/scene/element-errors/element", server.base_url());
+    let missing = canvas_core::ElementId::new();
+
+    let client = reqwest::Client::new();
+    for (path, status) in [
+        (format!("{base}/{missing}.png"), 404),
+        (format!("{base}/{missing}.gif"), 400),
+        (format!("{base}/not-a-uuid.png"), 400),
+        (format!("{base}/{missing}"), 400),
+        (format!("{base}/{missing}.png?width=100000"), 400),
+        (
+            format!(
+                "{}/api/scene/no-such-session/element/{missing}.png",
+                server.base_url()
+            ),
+            404,
+        ),
+    ] {
+        let resp = client.get(&path).send().await.expect("request");
+        assert_eq!(resp.status(), status, "{path}");
+    }
+
+    server.shutdown().await;
+}
+
+// ==========================================================================
+// SVG export
+// ==========================================================================
+
+#[tokio::test]
+async fn test_export_svg_route() {
+    let server = TestServer::start().await;
+    seed_session(&server, "svg-export");
+
+    let client = reqwest::Client::new();
+    let resp = client
+        .get(format!(
+            "{}/api/scene/svg-export/export.svg?width=640&height=480",
+            server.base_url()
+        ))
+        .send()
+        .await
+        .expect("request");
+
+    assert_eq!(resp.status(), 200);
+    assert_eq!(
+        resp.headers()
+            .get("content-type")
+            .and_then(|v| v.to_str().ok()),
+        Some("image/svg+xml")
+    );
+    let svg = resp.text().await.expect("body");
+    assert!(svg.starts_with("<svg"));
+    assert!(svg.contains("width=\"640\" height=\"480\""));
+    assert!(svg.contains("Hello export"));
+    assert!(svg.contains("Second line"));
+
+    for (path, status) in [
+        ("/api/scene/no-such-session/export.svg", 404),
+        ("/api/scene/svg-export/export.svg?scale=0", 400),
+    ] {
+        let resp = client
+            .get(format!("{}{path}", server.base_url()))
+            .send()
+            .await
+            .expect("request");
+        assert_eq!(resp.status(), status, "{path}");
+    }
+
+    server.shutdown().await;
+}
+
+// ==========================================================================
+// Thumbnails
+// ==========================================================================
+
+#[tokio::test]
+async fn test_thumbnail_route_renders_a_png_on_the_cpu() {
+    let server = TestServer::start().await;
+    seed_session(&server, "thumbnail");
+
+    let client = reqwest::Client::new();
+    let resp = client
+        .get(format!(
+            "{}/api/scene/thumbnail/thumbnail.png?size=200",
+            server.base_url()
+        ))
+        .send()
+        .await
+        .expect("request");
+
+    assert_eq!(resp.status(), 200);
+    assert_eq!(
+        resp.headers()
+            .get("content-type")
+            .and_then(|v| v.to_str().ok()),
+        Some("image/png")
+    );
+    let png = resp.bytes().await.expect("body");
+    assert!(png.starts_with(&[0x89, b'P', b'N', b'G']));
+    // The IHDR chunk holds the size: the viewport's longest side is 200
+    let width = u32::from_be_bytes([png[16], png[17], png[18], png[19]]);
+    let height = u32::from_be_bytes([png[20], png[21], png[22], png[23]]);
+    assert_eq!(width.max(height), 200);
+
+    for (path, status) in [
+        ("/api/scene/no-such-session/thumbnail.png", 404),
+        ("/api/scene/thumbnail/thumbnail.png?size=0", 400),
+        ("/api/scene/thumbnail/thumbnail.png?size=5000", 400),
+    ] {
+        let resp = client
+            .get(format!("{}{path}", server.base_url()))
+            .send()
+            .await
+            .expect("request");
+        assert_eq!(resp.status(), status, "{path}");
+    }
+
+    server.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_thumbnail_route_redacts_sensitive_elements() {
+    let server = TestServer::start().await;
+    let store = server.sync_state().store();
+    for (session_id, secret) in [("secret-a", "#ff0000"), ("secret-b", "#0000ff")] {
+        let _ = store.get_or_create(session_id);
+        let sensitive = Element::new(ElementKind::Shape {
+            shape: ShapeKind::Rectangle,
+        })
+        .with_transform(Transform {
+            x: 10.0,
+            y: 50.0,
+            width: 200.0,
+            height: 100.0,
+            ..Transform::default()
+        })
+        .with_style(Style {
+            fill: Some(Fill::Solid {
+                color: ThemeColor::parse(secret).expect("color"),
+            }),
+            ..Style::default()
+        })
+        .with_sensitive(true);
+        store
+            .update(session_id, |scene| {
+                scene.add_element(text_element("Public", 10.0, 20.0));
+                scene.add_element(sensitive);
+            })
+            .expect("seed session");
+    }
+
+    let client = reqwest::Client::new();
+    let thumbnail = |session_id: &str, query: &str| {
+        let request = client.get(format!(
+            "{}/api/scene/{session_id}/thumbnail.png?size=200{query}",
+            server.base_url()
+        ));
+        async move {
+            let resp = request.send().await.expect("request");
+            assert_eq!(resp.status(), 200);
+            resp.bytes().await.expect("body")
+        }
+    };
+
+    // The secrets differ, but not once they are hidden
+    assert_ne!(
+        thumbnail("secret-a", "").await,
+        thumbnail("secret-b", "").await
+    );
+    assert_eq!(
+        thumbnail("secret-a", "&redact=placeholder").await,
+        thumbnail("secret-b", "&redact=placeholder").await
+    );
+
+    let resp = client
+        .get(format!(
+            "{}/api/scene/secret-a/thumbnail.png?redact=smudge",
+            server.base_url()
+        ))
+        .send()
+        .await
+        .expect("request");
+    assert_eq!(resp.status(), 400);
+
+    server.shutdown().await;
+}
@@ -5,7 +5,7 @@
 
 use canvas_core::element::{Element, ElementKind};
 use canvas_core::offline::{ConflictStrategy, Operation};
-use canvas_core::SceneStore;
+use canvas_core::{Color, SceneStore};
 use canvas_server::sync::SyncProcessor;
 use std::sync::Arc;
 use std::time::Duration;
@@ -15,7 +15,7 @@ fn create_text_element(content: &str) -> Element {
     Element::new(ElementKind::Text {
         content: content.to_string(),
         font_size: 16.0,
-        color: "#000000".to_string(),
+        color: Color::BLACK.into(),
     })
 }
 
@@ -0,0 +1,165 @@
+//! Integration tests for read replicas mirroring a primary server.
+
+mod common;
+
+use std::time::Duration;
+
+use canvas_client::{CanvasClient, ClientConfig, ClientEvent, ServerMessage};
+use canvas_core::{
+    CameraFrame, Color, Consistency, Element, ElementDocument, ElementKind, ViewState,
+};
+use canvas_server::replica::{spawn_replica, ReplicaConfig};
+use futures_util::StreamExt;
+use tokio::sync::broadcast;
+use tokio::time::timeout;
+use tokio_tungstenite::{connect_async, tungstenite::Message};
+
+use common::TestServer;
+
+/// Start a primary and a replica mirroring it.
+async fn start_pair() -> (TestServer, TestServer) {
+    let primary = TestServer::start().await;
+    let replica = TestServer::start().await;
+    spawn_replica(
+        replica.sync_state().clone(),
+        ReplicaConfig::new(primary.ws_url()),
+    );
+    (primary, replica)
+}
+
+/// Wait for the first server message matching `matches`.
+async fn recv_message(
+    events: &mut broadcast::Receiver<ClientEvent>,
+    matches: impl Fn(&ServerMessage) -> bool,
+) -> Option<ServerMessage> {
+    timeout(Duration::from_secs(5), async {
+        loop {
+            match events.recv().await {
+                Ok(ClientEvent::Message(message)) if matches(&message) => return Some(message),
+                Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => {}
+                Err(broadcast::error::RecvError::Closed) => return None,
+            }
+        }
+    })
+    .await
+    .ok()
+    .flatten()
+}
+
+/// Wait until the client's scene mirror holds the element.
+async fn wait_for_element(client: &CanvasClient, element_id: &str) -> bool {
+    timeout(Duration::from_secs(5), async {
+        while !client
+            .scene()
+            .is_some_and(|scene| scene.elements().any(|e| e.id.to_string() == element_id))
+        {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .is_ok()
+}
+
+fn text_element(content: &str) -> ElementDocument {
+    ElementDocument::from(&Element::new(ElementKind::Text {
+        content: content.to_string(),
+        font_size: 16.0,
+        color: Color::BLACK.into(),
+    }))
+}
+
+#[tokio::test]
+async fn replica_welcome_advertises_eventual_consistency() {
+    let (primary, replica) = start_pair().await;
+
+    let (mut socket, _) = connect_async(replica.ws_url()).await.expect("connect");
+    let welcome = timeout(Duration::from_secs(5), socket.next())
+        .await
+        .expect("welcome in time");
+    let Some(Ok(Message::Text(text))) = welcome else {
+        panic!("expected a text welcome frame");
+    };
+    let welcome: ServerMessage = serde_json::from_str(&text).expect("welcome");
+    assert!(matches!(
+        welcome,
+        ServerMessage::Welcome {
+            consistency: Consistency::Eventual,
+            ..
+        }
+    ));
+    assert_eq!(primary.sync_state().consistency(), Consistency::Strong);
+
+    replica.shutdown().await;
+    primary.shutdown().await;
+}
+
+#[tokio::test]
+async fn replica_mirrors_session_and_rejects_writes() {
+    let (primary, replica) = start_pair().await;
+
+    // Sessions already on the primary are loaded when a viewer subscribes
+    let opening = text_element("Welcome to the town hall");
+    primary
+        .sync_state()
+        .add_element("town-hall", &opening, Some("host"))
+        .expect("add on primary");
+
+    let viewer =
+        CanvasClient::connect(ClientConfig::new(replica.ws_url()).with_session("town-hall"))
+            .await
+            .expect("viewer connects");
+    let mut events = viewer.events();
+    assert!(
+        wait_for_element(&viewer, &opening.id).await,
+        "viewer never saw the opening element"
+    );
+
+    // Later writes on the primary are mirrored live
+    let agenda = text_element("Agenda");
+    primary
+        .sync_state()
+        .add_element("town-hall", &agenda, Some("host"))
+        .expect("add on primary");
+    assert!(
+        wait_for_element(&viewer, &agenda.id).await,
+        "viewer never saw the live element"
+    );
+
+    // Presenter views are relayed too
+    let view = ViewState {
+        camera: CameraFrame {
+            zoom: 2.0,
+            pan_x: 10.0,
+            pan_y: 20.0,
+        },
+        viewport_width: 800.0,
+        viewport_height: 600.0,
+    };
+    primary
+        .sync_state()
+        .update_view_state("town-hall", "host", view);
+    let relayed = recv_message(&mut events, |m| {
+        matches!(m, ServerMessage::ViewState { .. })
+    })
+    .await;
+    assert!(matches!(
+        relayed,
+        Some(ServerMessage::ViewState { presenter_id, .. }) if presenter_id == "host"
+    ));
+
+    // Writes to the replica are rejected and never reach the primary
+    viewer
+        .add_element(text_element("Heckle"), Some("add-1".to_string()))
+        .expect("send");
+    let rejected = recv_message(&mut events, |m| matches!(m, ServerMessage::Error { .. })).await;
+    assert!(matches!(
+        rejected,
+        Some(ServerMessage::Error { code, message_id: Some(id), .. })
+            if code == "read_only" && id == "add-1"
+    ));
+    let primary_scene = primary.sync_state().get_scene("town-hall").expect("scene");
+    assert_eq!(primary_scene.element_count(), 2);
+
+    replica.shutdown().await;
+    primary.shutdown().await;
+}
@@ -0,0 +1,245 @@
+//! Integration tests for warm standbys following a primary server.
+
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use canvas_client::ReconnectPolicy;
+use canvas_core::{Color, Element, ElementDocument, ElementKind};
+use canvas_server::standby::{self, spawn_standby, StandbyConfig, StreamConfig};
+use canvas_server::SyncState;
+use tokio::net::TcpListener;
+use tokio::sync::oneshot;
+use tokio::time::timeout;
+
+const TOKEN: &str = "standby-secret";
+
+/// Serves the standby routes for one `SyncState` on its own runtime.
+///
+/// Stopping drops the runtime, which closes open streams the way a crashed
+/// server would.
+struct StandbyRoutes {
+    addr: SocketAddr,
+    shutdown_tx: Option<oneshot::Sender<()>>,
+}
+
+impl StandbyRoutes {
+    fn start(sync: SyncState) -> Self {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").expect("bind");
+        listener.set_nonblocking(true).expect("nonblocking");
+        let addr = listener.local_addr().expect("local addr");
+        let app = standby::routes(
+            sync,
+            StreamConfig::new(TOKEN).with_snapshot_interval(Duration::from_secs(1)),
+        );
+        let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+        std::thread::spawn(move || {
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .expect("runtime");
+            runtime.block_on(async move {
+                let listener = TcpListener::from_std(listener).expect("listener");
+                tokio::select! {
+                    result = axum::serve(listener, app) => result.expect("server error"),
+                    _ = shutdown_rx => {}
+                }
+            });
+        });
+        Self {
+            addr,
+            shutdown_tx: Some(shutdown_tx),
+        }
+    }
+
+    fn stream_url(&self) -> String {
+        format!("ws://{}/ws/standby", self.addr)
+    }
+
+    fn promote_url(&self) -> String {
+        format!("http://{}/api/standby/promote", self.addr)
+    }
+
+    fn stop(&mut self) {
+        if let Some(tx) = self.shutdown_tx.take() {
+            let _ = tx.send(());
+        }
+    }
+}
+
+fn fast_reconnect() -> ReconnectPolicy {
+    ReconnectPolicy {
+        initial_delay: Duration::from_millis(50),
+        max_delay: Duration::from_millis(200),
+        max_attempts: None,
+    }
+}
+
+fn text_element(content: &str) -> ElementDocument {
+    ElementDocument::from(&Element::new(ElementKind::Text {
+        content: content.to_string(),
+        font_size: 16.0,
+        color: Color::BLACK.into(),
+    }))
+}
+
+fn has_element(sync: &SyncState, session_id: &str, element_id: &str) -> bool {
+    sync.get_scene(session_id)
+        .is_some_and(|scene| scene.elements().any(|e| e.id.to_string() == element_id))
+}
+
+/// Poll `condition` for up to five seconds.
+async fn eventually(condition: impl Fn() -> bool) -> bool {
+    timeout(Duration::from_secs(5), async {
+        while !condition() {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    })
+    .await
+    .is_ok()
+}
+
+#[tokio::test]
+async fn standby_copies_sessions_and_follows_events() {
+    let primary = SyncState::new();
+    let opening = text_element("Opening");
+    primary
+        .add_element("town-hall", &opening, None)
+        .expect("add on primary");
+    let mut routes = StandbyRoutes::start(primary.clone());
+
+    let standby_sync = SyncState::new();
+    let handle = spawn_standby(
+        standby_sync.clone(),
+        StandbyConfig::new(routes.stream_url(), TOKEN).with_reconnect(fast_reconnect()),
+    );
+
+    // The first snapshot carries sessions created before the standby joined
+    assert!(
+        eventually(|| has_element(&standby_sync, "town-hall", &opening.id)).await,
+        "standby never received the snapshot"
+    );
+    let status = standby_sync.standby_status().expect("standby status");
+    assert!(status.connected && status.synced);
+
+    // Later changes arrive as events
+    let agenda = text_element("Agenda");
+    primary
+        .add_element("town-hall", &agenda, None)
+        .expect("add on primary");
+    assert!(
+        eventually(|| has_element(&standby_sync, "town-hall", &agenda.id)).await,
+        "standby never received the event"
+    );
+    primary
+        .remove_element("town-hall", &opening.id)
+        .expect("remove on primary");
+    assert!(
+        eventually(|| !has_element(&standby_sync, "town-hall", &opening.id)).await,
+        "standby kept a removed element"
+    );
+
+    // Writes are refused until promotion
+    assert!(standby_sync.is_read_only());
+    assert!(standby_sync
+        .add_element("town-hall", &text_element("Nope"), None)
+        .is_err());
+
+    handle.abort();
+    routes.stop();
+}
+
+#[tokio::test]
+async fn standby_with_wrong_token_is_refused() {
+    let primary = SyncState::new();
+    let mut routes = StandbyRoutes::start(primary);
+
+    let standby_sync = SyncState::new();
+    let handle = spawn_standby(
+        standby_sync.clone(),
+        StandbyConfig::new(routes.stream_url(), "wrong").with_reconnect(fast_reconnect()),
+    );
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    let status = standby_sync.standby_status().expect("standby status");
+    assert!(!status.connected);
+    assert!(!status.synced);
+
+    handle.abort();
+    routes.stop();
+}
+
+#[tokio::test]
+async fn standby_promotes_itself_after_losing_the_primary() {
+    let primary = SyncState::new();
+    let opening = text_element("Opening");
+    primary
+        .add_element("town-hall", &opening, None)
+        .expect("add on primary");
+    let mut routes = StandbyRoutes::start(primary);
+
+    let standby_sync = SyncState::new();
+    let _handle = spawn_standby(
+        standby_sync.clone(),
+        StandbyConfig::new(routes.stream_url(), TOKEN)
+            .with_reconnect(fast_reconnect())
+            .with_promote_after(Duration::from_millis(300)),
+    );
+    assert!(eventually(|| has_element(&standby_sync, "town-hall", &opening.id)).await);
+    assert!(standby_sync.is_standby());
+
+    routes.stop();
+    assert!(
+        eventually(|| !standby_sync.is_standby()).await,
+        "standby was never promoted"
+    );
+
+    // The promoted server keeps the copy and accepts writes
+    assert!(has_element(&standby_sync, "town-hall", &opening.id));
+    standby_sync
+        .add_element("town-hall", &text_element("Back online"), None)
+        .expect("promoted server is writable");
+}
+
+#[tokio::test]
+async fn promote_endpoint_requires_token_and_promotes_once() {
+    let primary = SyncState::new();
+    let mut primary_routes = StandbyRoutes::start(primary);
+
+    let standby_sync = SyncState::new();
+    let handle = spawn_standby(
+        standby_sync.clone(),
+        StandbyConfig::new(primary_routes.stream_url(), TOKEN).with_reconnect(fast_reconnect()),
+    );
+    let mut standby_routes = StandbyRoutes::start(standby_sync.clone());
+    let client = reqwest::Client::new();
+
+    let response = client
+        .post(standby_routes.promote_url())
+        .send()
+        .await
+        .expect("request");
+    assert_eq!(response.status(), reqwest::StatusCode::UNAUTHORIZED);
+    assert!(standby_sync.is_standby());
+
+    let response = client
+        .post(standby_routes.promote_url())
+        .bearer_auth(TOKEN)
+        .send()
+        .await
+        .expect("request");
+    assert_eq!(response.status(), reqwest::StatusCode::OK);
+    let body: serde_json::Value = response.json().await.expect("json");
+    assert_eq!(body["promoted"], true);
+    assert!(!standby_sync.is_read_only());
+
+    let response = client
+        .post(standby_routes.promote_url())
+        .bearer_auth(TOKEN)
+        .send()
+        .await
+        .expect("request");
+    assert_eq!(response.status(), reqwest::StatusCode::CONFLICT);
+
+    handle.abort();
+    standby_routes.stop();
+    primary_routes.stop();
+}
@@ -5,7 +5,7 @@
 
 use canvas_core::element::{Element, ElementId, ElementKind};
 use canvas_core::offline::{ConflictStrategy, Operation};
-use canvas_core::SceneStore;
+use canvas_core::{Color, SceneStore};
 use canvas_server::sync::{RetryConfig, SyncProcessor};
 use std::sync::Arc;
 
@@ -17,7 +17,7 @@ fn create_text_element(content: &str) -> Element {
     Element::new(ElementKind::Text {
         content: content.to_string(),
         font_size: 16.0,
-        color: "#000000".to_string(),
+        color: Color::BLACK.into(),
     })
 }
 
@@ -26,6 +26,7 @@ fn create_rect_element(width: f32, height: f32) -> Element {
     Element::new(ElementKind::Chart {
         chart_type: "bar".to_string(),
         data: serde_json::json!({"values": [width as i32, height as i32]}),
+        binding: None,
     })
 }
 
@@ -361,7 +362,7 @@ fn test_conflict_strategies_differ() {
         let mut element = Element::new(ElementKind::Text {
             content: content.to_string(),
             font_size: 20.0,
-            color: "#FF0000".to_string(),
+            color: Color::from_rgb(0xff_0000).into(),
         });
         element.id = id;
         element
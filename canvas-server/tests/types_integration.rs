@@ -0,0 +1,38 @@
+//! Integration tests for the GET /api/types.d.ts endpoint.
+
+mod common;
+
+use common::TestServer;
+
+#[tokio::test]
+async fn test_types_endpoint_serves_protocol_definitions() {
+    let server = TestServer::start().await;
+
+    let resp = reqwest::get(format!("{}/api/types.d.ts", server.base_url()))
+        .await
+        .expect("request");
+
+    assert_eq!(resp.status(), 200);
+    let content_type = resp
+        .headers()
+        .get("content-type")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default()
+        .to_string();
+    assert!(content_type.starts_with("application/typescript"));
+
+    let body = resp.text().await.expect("body");
+    for name in [
+        "ClientMessage",
+        "ServerMessage",
+        "SceneDocument",
+        "ElementDocument",
+    ] {
+        assert!(
+            body.contains(&format!("export type {name} = ")),
+            "missing {name}"
+        );
+    }
+
+    server.shutdown().await;
+}
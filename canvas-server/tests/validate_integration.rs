@@ -0,0 +1,88 @@
+//! Integration tests for the GET /api/scene/{session_id}/validate endpoint.
+
+mod common;
+
+use canvas_core::element::{Element, ElementKind, Transform};
+use canvas_core::{Color, ElementId};
+use common::TestServer;
+
+#[tokio::test]
+async fn test_validate_reports_issues_and_stats() {
+    let server = TestServer::start().await;
+    let store = server.sync_state().store();
+    let _ = store.get_or_create("debug");
+    let missing = ElementId::new();
+    let group = Element::new(ElementKind::Group {
+        children: vec![missing],
+        clip: false,
+        opacity: 1.0,
+    });
+    let group_id = group.id;
+    store
+        .update("debug", |scene| {
+            scene.add_element(group);
+            scene.add_element(
+                Element::new(ElementKind::Text {
+                    content: "Flat".to_string(),
+                    font_size: 16.0,
+                    color: Color::BLACK.into(),
+                })
+                .with_transform(Transform {
+                    x: 0.0,
+                    y: 0.0,
+                    width: 100.0,
+                    height: 0.0,
+                    rotation: 0.0,
+                    z_index: 0,
+                    auto_size: false,
+                }),
+            );
+        })
+        .expect("seed session");
+
+    let resp = reqwest::get(format!("{}/api/scene/debug/validate", server.base_url()))
+        .await
+        .expect("request");
+    assert_eq!(resp.status(), 200);
+    let body: serde_json::Value = resp.json().await.expect("json");
+
+    assert_eq!(body["success"], true);
+    assert_eq!(body["valid"], false);
+    let issues = body["issues"].as_array().expect("issues");
+    assert_eq!(issues.len(), 2);
+    let orphan = issues
+        .iter()
+        .find(|issue| issue["issue"] == "orphan_child")
+        .expect("orphan child reported");
+    assert_eq!(orphan["element_id"], group_id.to_string());
+    assert_eq!(orphan["child"], missing.to_string());
+    assert_eq!(orphan["severity"], "error");
+    assert!(issues
+        .iter()
+        .any(|issue| issue["issue"] == "zero_size" && issue["severity"] == "warning"));
+
+    assert_eq!(body["stats"]["element_count"], 2);
+    assert_eq!(body["stats"]["by_kind"]["Group"], 1);
+    assert_eq!(body["stats"]["by_kind"]["Text"], 1);
+
+    server.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_validate_unknown_session() {
+    let server = TestServer::start().await;
+
+    let resp = reqwest::get(format!("{}/api/scene/nobody/validate", server.base_url()))
+        .await
+        .expect("request");
+    assert_eq!(resp.status(), 404);
+    let body: serde_json::Value = resp.json().await.expect("json");
+    assert_eq!(body["success"], false);
+
+    let resp = reqwest::get(format!("{}/api/scene/bad$id/validate", server.base_url()))
+        .await
+        .expect("request");
+    assert_eq!(resp.status(), 400);
+
+    server.shutdown().await;
+}
@@ -10,6 +10,8 @@ use std::time::Duration;
 use tokio::time::timeout;
 use tokio_tungstenite::{connect_async, tungstenite::Message};
 
+use canvas_core::{Color, Element, ElementKind, Encoding, SceneDocument, Transform};
+use canvas_server::sync::element_to_data;
 use common::TestServer;
 
 /// Helper to receive and parse a JSON message with timeout.
@@ -27,6 +29,21 @@ async fn recv_json(
     }
 }
 
+/// Helper to receive and decode a `MessagePack` binary message with timeout.
+async fn recv_msgpack(
+    stream: &mut (impl StreamExt<Item = Result<Message, tokio_tungstenite::tungstenite::Error>> + Unpin),
+) -> Option<Value> {
+    let msg = timeout(Duration::from_secs(5), stream.next())
+        .await
+        .ok()??
+        .ok()?;
+
+    match msg {
+        Message::Binary(bytes) => Encoding::MessagePack.decode(&bytes).ok(),
+        _ => None,
+    }
+}
+
 /// Helper to receive multiple messages and find one by type.
 async fn recv_until_type(
     stream: &mut (impl StreamExt<Item = Result<Message, tokio_tungstenite::tungstenite::Error>> + Unpin),
@@ -74,6 +91,7 @@ async fn connect_and_receive_welcome() {
     assert_eq!(msg["type"], "welcome");
     assert!(msg["version"].is_string());
     assert!(msg["session_id"].is_string());
+    assert_eq!(msg["consistency"], "strong");
 
     server.shutdown().await;
 }
@@ -415,3 +433,89 @@ async fn get_scene_returns_current_state() {
 
     server.shutdown().await;
 }
+
+#[tokio::test]
+async fn msgpack_encoding_round_trip() {
+    let server = TestServer::start().await;
+    let url = format!("ws://{}/ws/sync?encoding=msgpack", server.addr());
+
+    let (ws_stream, _) = connect_async(&url).await.expect("Failed to connect");
+    let (mut write, mut read) = ws_stream.split();
+
+    let welcome = recv_msgpack(&mut read)
+        .await
+        .expect("No binary welcome message");
+    assert_eq!(welcome["type"], "welcome");
+
+    // Binary frames from the client are decoded as MessagePack
+    let request = Encoding::MessagePack
+        .encode(&json!({ "type": "get_scene", "session_id": "default" }))
+        .unwrap();
+    write.send(Message::Binary(request)).await.unwrap();
+
+    let mut scene = None;
+    for _ in 0..5 {
+        let Some(msg) = recv_msgpack(&mut read).await else {
+            break;
+        };
+        if msg["type"] == "scene_update" {
+            scene = Some(msg);
+            break;
+        }
+    }
+    let scene = scene.expect("No binary scene_update received");
+    let document: SceneDocument =
+        serde_json::from_value(scene["scene"].clone()).expect("Scene document should decode");
+    assert_eq!(document.session_id, "default");
+
+    server.shutdown().await;
+}
+
+#[tokio::test]
+async fn paged_scene_load_streams_remaining_elements() {
+    let server = TestServer::start().await;
+    for i in 0..5 {
+        let element = Element::new(ElementKind::Text {
+            content: format!("item {i}"),
+            font_size: 16.0,
+            color: Color::BLACK.into(),
+        })
+        .with_transform(Transform {
+            x: 2000.0 * i as f32,
+            y: 0.0,
+            width: 50.0,
+            height: 50.0,
+            rotation: 0.0,
+            z_index: 0,
+            auto_size: false,
+        });
+        server
+            .sync_state()
+            .add_element("default", &element_to_data(&element), None)
+            .expect("should add element");
+    }
+
+    let url = format!("ws://{}/ws/sync?page_size=2", server.addr());
+    let (ws_stream, _) = connect_async(&url).await.expect("Failed to connect");
+    let (_, mut read) = ws_stream.split();
+
+    let (scene, _) = recv_until_type(&mut read, "scene_update", 5).await;
+    let scene = scene.expect("No scene_update received");
+    let elements = scene["scene"]["elements"].as_array().unwrap();
+    assert_eq!(elements.len(), 2);
+    // The element inside the viewport is sent first
+    assert_eq!(elements[0]["kind"]["data"]["content"], "item 0");
+
+    let mut received = elements.len();
+    loop {
+        let (page, _) = recv_until_type(&mut read, "scene_page", 5).await;
+        let page = page.expect("No scene_page received");
+        received += page["page"]["elements"].as_array().unwrap().len();
+        if page["page"]["index"].as_u64().unwrap() + 1 == page["page"]["total"].as_u64().unwrap() {
+            break;
+        }
+    }
+    assert_eq!(received, 5);
+
+    server.shutdown().await;
+}
@@ -45,6 +45,9 @@ export class CanvasRenderer {
         /** @type {Map<string, Object>|null} Media stats by peer ID */
         this.mediaStats = null;
 
+        /** @type {number} Server clock minus local clock, for agenda timers */
+        this.clockOffsetMs = 0;
+
         console.log('[CanvasRenderer] Initialized');
     }
 
@@ -56,6 +59,15 @@ export class CanvasRenderer {
         this.mediaStats = stats;
     }
 
+    /**
+     * Set the server's current time, e.g. the `timestamp` of the `welcome`
+     * message, so agenda timers agree with other participants.
+     * @param {number} serverMs - Server time in ms since the Unix epoch
+     */
+    setServerTime(serverMs) {
+        this.clockOffsetMs = serverMs - Date.now();
+    }
+
     /**
      * Set the scene to render.
      * @param {Object} scene - Scene data with elements array
@@ -131,9 +143,12 @@ export class CanvasRenderer {
 
         // Render scene elements if available
         if (this.scene && this.scene.elements) {
-            // Sort by z-index
+            // Sort by z-index, with screen-anchored (HUD) elements on top.
+            // There is no camera here, so their viewport pixels are used as-is.
             const elements = [...this.scene.elements].sort(
-                (a, b) => (a.transform?.z_index || 0) - (b.transform?.z_index || 0)
+                (a, b) =>
+                    Number(Boolean(a.screen_anchored)) - Number(Boolean(b.screen_anchored)) ||
+                    (a.transform?.z_index || 0) - (b.transform?.z_index || 0)
             );
 
             for (const element of elements) {
@@ -141,12 +156,75 @@ export class CanvasRenderer {
             }
         }
 
+        if (this.scene?.branding) {
+            this.renderBranding(this.scene.branding);
+        }
+
         // Render debug overlay if enabled
         if (this.debugMode) {
             this.renderDebugOverlay();
         }
     }
 
+    /**
+     * Render the session branding over everything, pinned to the viewport.
+     * Mirrors the layout of `Branding::overlay` in canvas-core.
+     * @param {Object} branding - Logo, watermark, position and opacity
+     * @private
+     */
+    renderBranding(branding) {
+        const margin = 16;
+        const gap = 8;
+        const logoSize = 48;
+        const fontSize = 16;
+        const { logo, watermark, position = 'bottom_right' } = branding;
+        const opacity = Number.isFinite(branding.opacity)
+            ? Math.min(Math.max(branding.opacity, 0), 1)
+            : 0.5;
+
+        this.ctx.save();
+        this.ctx.globalAlpha *= opacity;
+        this.ctx.font = `${fontSize}px sans-serif`;
+        const logoWidth = logo ? logoSize : 0;
+        const textWidth = watermark ? this.ctx.measureText(watermark).width : 0;
+        const spacing = logo && watermark ? gap : 0;
+        const contentWidth = logoWidth + spacing + textWidth;
+        const contentHeight = logo ? logoSize : watermark ? fontSize * 1.2 : 0;
+        const { width, height } = this.canvas;
+
+        let x = margin;
+        if (position === 'top_right' || position === 'bottom_right') {
+            x = width - margin - contentWidth;
+        } else if (position === 'center') {
+            x = (width - contentWidth) / 2;
+        }
+        let y = margin;
+        if (position === 'bottom_left' || position === 'bottom_right') {
+            y = height - margin - contentHeight;
+        } else if (position === 'center') {
+            y = (height - contentHeight) / 2;
+        }
+
+        // Logos load once and appear on a later frame
+        if (logo) {
+            let image = this.imageCache.get(logo);
+            if (!image) {
+                image = new Image();
+                image.src = logo;
+                this.imageCache.set(logo, image);
+            }
+            if (image.complete && image.naturalWidth > 0) {
+                this.ctx.drawImage(image, x, y, logoSize, logoSize);
+            }
+        }
+        if (watermark) {
+            this.ctx.fillStyle = '#808080';
+            this.ctx.textBaseline = 'middle';
+            this.ctx.fillText(watermark, x + logoWidth + spacing, y + contentHeight / 2);
+        }
+        this.ctx.restore();
+    }
+
     /**
      * Render a single element.
      * @param {Object} element - Element to render
@@ -156,10 +234,17 @@ export class CanvasRenderer {
         const kind = element.kind;
         const transform = element.transform || {};
 
-        if (!kind || !kind.type) {
+        if (!kind || !kind.type || element.visible === false) {
             return;
         }
 
+        // Element opacity times style opacity, each clamped to 0..1
+        const alpha = [element.opacity, element.style?.opacity]
+            .map((value) => (Number.isFinite(value) ? Math.min(Math.max(value, 0), 1) : 1))
+            .reduce((a, b) => a * b, 1);
+        this.ctx.save();
+        this.ctx.globalAlpha *= alpha;
+
         switch (kind.type) {
             case 'Video':
                 this.renderVideoElement(element);
@@ -167,15 +252,80 @@ export class CanvasRenderer {
             case 'Text':
                 this.renderTextElement(element);
                 break;
+            case 'Math':
+                this.renderMathSource(element);
+                break;
+            case 'Code':
+                this.renderCodeElement(element);
+                break;
+            case 'LogView':
+                this.renderLogElement(element);
+                break;
+            case 'StickyNote':
+            case 'Callout':
+                this.renderNoteElement(element);
+                break;
             case 'Image':
                 this.renderImageElement(element);
                 break;
             case 'Chart':
                 this.renderChartPlaceholder(element);
                 break;
+            case 'Embed':
+                this.renderEmbedPlaceholder(element);
+                break;
+            case 'Shader':
+                this.renderShaderPlaceholder(element);
+                break;
+            case 'Audio':
+                this.renderAudioElement(element);
+                break;
+            case 'Calendar':
+                this.renderCalendarElement(element);
+                break;
+            case 'Poll':
+                this.renderPollElement(element);
+                break;
+            case 'Agenda':
+                this.renderAgendaElement(element);
+                break;
+            case 'Randomizer':
+                this.renderRandomizerElement(element);
+                break;
+            case 'FloorPlan':
+                this.renderFloorPlanElement(element);
+                break;
+            case 'Gauge':
+                this.renderGaugeElement(element);
+                break;
+            case 'Kpi':
+                this.renderKpiElement(element);
+                break;
+            case 'Sparkline':
+                this.renderSparklineElement(element);
+                break;
+            case 'Tree':
+                this.renderTreeElement(element);
+                break;
+            case 'Ink':
+                this.renderInkElement(element);
+                break;
+            case 'Measurement':
+                this.renderMeasurementElement(element);
+                break;
+            case 'Region':
+                this.renderRegionElement(element);
+                break;
+            case 'Shape':
+                this.renderShapeElement(element);
+                break;
+            case 'Barcode':
+                this.renderBarcodeElement(element);
+                break;
             default:
                 this.renderPlaceholder(element);
         }
+        this.ctx.restore();
     }
 
     /**
@@ -291,12 +441,259 @@ export class CanvasRenderer {
         const y = transform.y || 0;
 
         this.ctx.save();
-        this.ctx.fillStyle = color || '#ffffff';
+        this.ctx.fillStyle = this._resolveColor(color, '#ffffff');
         this.ctx.font = `${font_size || 16}px sans-serif`;
         this.ctx.fillText(content || '', x, y + (font_size || 16));
         this.ctx.restore();
     }
 
+    /**
+     * Render a math element as its TeX source in a monospace font.
+     * Typeset rendering is done by the WASM renderer.
+     * @param {Object} element - Math element
+     * @private
+     */
+    renderMathSource(element) {
+        const { tex, font_size, color } = element.kind;
+        const transform = element.transform || {};
+        const x = transform.x || 0;
+        const y = transform.y || 0;
+
+        this.ctx.save();
+        this.ctx.fillStyle = this._resolveColor(color, '#000000');
+        this.ctx.font = `${font_size || 24}px monospace`;
+        this.ctx.fillText(tex || '', x, y + (font_size || 24));
+        this.ctx.restore();
+    }
+
+    /**
+     * Render a code element, coloring the highlight spans computed by the
+     * server. Span offsets are UTF-8 byte offsets into the source.
+     * @param {Object} element - Code element
+     * @private
+     */
+    renderCodeElement(element) {
+        const { source, spans } = element.kind;
+        const transform = element.transform || {};
+        const x = transform.x || 0;
+        const y = transform.y || 0;
+        const width = transform.width || 0;
+        const height = transform.height || 0;
+        const padding = 8;
+        const lineHeight = 20;
+        const colors = {
+            plain: '#d4d4d4',
+            keyword: '#569cd6',
+            type: '#4ec9b0',
+            function: '#dcdcaa',
+            string: '#ce9178',
+            number: '#b5cea8',
+            comment: '#6a9955',
+        };
+
+        // Cover the source with [kind, text] segments
+        const bytes = new TextEncoder().encode(source || '');
+        const decoder = new TextDecoder();
+        const segments = [];
+        let cursor = 0;
+        const sorted = [...(spans || [])].sort((a, b) => a.start - b.start);
+        for (const span of sorted) {
+            if (span.start < cursor || span.end <= span.start || span.end > bytes.length) {
+                continue;
+            }
+            if (span.start > cursor) {
+                segments.push(['plain', decoder.decode(bytes.subarray(cursor, span.start))]);
+            }
+            segments.push([span.kind, decoder.decode(bytes.subarray(span.start, span.end))]);
+            cursor = span.end;
+        }
+        if (cursor < bytes.length) {
+            segments.push(['plain', decoder.decode(bytes.subarray(cursor))]);
+        }
+
+        this.ctx.save();
+        this.ctx.fillStyle = '#1e1e1e';
+        this.ctx.fillRect(x, y, width, height);
+        this.ctx.beginPath();
+        this.ctx.rect(x, y, width, height);
+        this.ctx.clip();
+        this.ctx.font = '14px monospace';
+        this.ctx.textBaseline = 'middle';
+
+        let row = 0;
+        let lineX = x + padding;
+        for (const [kind, text] of segments) {
+            text.split('\n').forEach((part, i) => {
+                if (i > 0) {
+                    row += 1;
+                    lineX = x + padding;
+                }
+                this.ctx.fillStyle = colors[kind] || colors.plain;
+                this.ctx.fillText(part, lineX, y + padding + (row + 0.5) * lineHeight);
+                lineX += this.ctx.measureText(part).width;
+            });
+        }
+        this.ctx.restore();
+    }
+
+    /**
+     * Render a log view: its title and the rows that fit, newest last when
+     * following, colored by their ANSI SGR escape codes.
+     * @param {Object} element - LogView element
+     * @private
+     */
+    renderLogElement(element) {
+        const { title, lines, follow } = element.kind;
+        const transform = element.transform || {};
+        const x = transform.x || 0;
+        const y = transform.y || 0;
+        const width = transform.width || 0;
+        const height = transform.height || 0;
+        const padding = 8;
+        const lineHeight = 20;
+        const foreground = '#cccccc';
+        const background = '#0c0c0c';
+        const palette = [
+            '#000000', '#cd3131', '#0dbc79', '#e5e510', '#2472c8', '#bc3fbc', '#11a8cd', '#e5e5e5',
+            '#666666', '#f14c4c', '#23d18b', '#f5f543', '#3b8eea', '#d670d6', '#29b8db', '#ffffff',
+        ];
+        const indexed = (n) => {
+            if (n < 16) return palette[n];
+            if (n < 232) {
+                const level = (v) => (v === 0 ? 0 : 55 + v * 40);
+                const c = n - 16;
+                return `rgb(${level(Math.floor(c / 36))}, ${level(Math.floor(c / 6) % 6)}, ${level(c % 6)})`;
+            }
+            const gray = 8 + (n - 232) * 10;
+            return `rgb(${gray}, ${gray}, ${gray})`;
+        };
+        // Read a 38/48 extended color starting at params[i]; returns [color, next index]
+        const extended = (params, i) => {
+            if (params[i] === 5 && i + 1 < params.length) {
+                return [indexed(Math.min(params[i + 1], 255)), i + 2];
+            }
+            if (params[i] === 2 && i + 3 < params.length) {
+                return [`rgb(${params[i + 1]}, ${params[i + 2]}, ${params[i + 3]})`, i + 4];
+            }
+            return [null, params.length];
+        };
+        const applySgr = (style, params) => {
+            if (params.length === 0) params = [0];
+            for (let i = 0; i < params.length; i++) {
+                const p = params[i];
+                if (p === 0) Object.assign(style, { fg: null, bg: null, bold: false, dim: false, italic: false, underline: false, inverse: false });
+                else if (p === 1) style.bold = true;
+                else if (p === 2) style.dim = true;
+                else if (p === 3) style.italic = true;
+                else if (p === 4) style.underline = true;
+                else if (p === 7) style.inverse = true;
+                else if (p === 22) { style.bold = false; style.dim = false; }
+                else if (p === 23) style.italic = false;
+                else if (p === 24) style.underline = false;
+                else if (p === 27) style.inverse = false;
+                else if (p >= 30 && p <= 37) style.fg = palette[p - 30];
+                else if (p >= 90 && p <= 97) style.fg = palette[p - 82];
+                else if (p === 39) style.fg = null;
+                else if (p >= 40 && p <= 47) style.bg = palette[p - 40];
+                else if (p >= 100 && p <= 107) style.bg = palette[p - 92];
+                else if (p === 49) style.bg = null;
+                else if (p === 38 || p === 48) {
+                    const [color, next] = extended(params, i + 1);
+                    if (color) style[p === 38 ? 'fg' : 'bg'] = color;
+                    i = next - 1;
+                }
+            }
+        };
+        // Split a line into [text, style] runs; other escape sequences are dropped
+        const spans = (line) => {
+            const runs = [];
+            const style = {};
+            applySgr(style, [0]);
+            const pattern = /\x1b\[([0-?]*)[ -/]*([@-~])|\x1b\][^\x07\x1b]*(?:\x07|\x1b\\)?|[\x00-\x08\x0a-\x1f\x7f]/g;
+            let cursor = 0;
+            let match;
+            while ((match = pattern.exec(line)) !== null) {
+                if (match.index > cursor) runs.push([line.slice(cursor, match.index), { ...style }]);
+                if (match[2] === 'm') {
+                    applySgr(style, match[1] === '' ? [] : match[1].split(';').map((p) => Number(p) || 0));
+                }
+                cursor = pattern.lastIndex;
+            }
+            if (cursor < line.length) runs.push([line.slice(cursor), { ...style }]);
+            return runs;
+        };
+
+        this.ctx.save();
+        this.ctx.fillStyle = background;
+        this.ctx.fillRect(x, y, width, height);
+        this.ctx.beginPath();
+        this.ctx.rect(x, y, width, height);
+        this.ctx.clip();
+        this.ctx.textBaseline = 'middle';
+
+        let top = y + padding;
+        if (title) {
+            this.ctx.font = 'bold 14px monospace';
+            this.ctx.fillStyle = foreground;
+            this.ctx.fillText(title, x + padding, top + lineHeight / 2);
+            top += lineHeight + 4;
+        }
+        const all = lines || [];
+        const rows = Math.min(Math.max(Math.floor((y + height - padding - top) / lineHeight), 0), all.length);
+        const visible = follow === false ? all.slice(0, rows) : all.slice(all.length - rows);
+        visible.forEach((line, row) => {
+            const lineTop = top + row * lineHeight;
+            let lineX = x + padding;
+            for (const [text, style] of spans(line)) {
+                this.ctx.font = `${style.italic ? 'italic ' : ''}${style.bold ? 'bold ' : ''}14px monospace`;
+                const textWidth = this.ctx.measureText(text).width;
+                const fill = style.inverse ? style.bg || background : style.fg || foreground;
+                const back = style.inverse ? style.fg || foreground : style.bg;
+                if (back) {
+                    this.ctx.fillStyle = back;
+                    this.ctx.fillRect(lineX, lineTop, textWidth, lineHeight);
+                }
+                this.ctx.save();
+                if (style.dim) this.ctx.globalAlpha *= 0.6;
+                this.ctx.fillStyle = fill;
+                this.ctx.fillText(text, lineX, lineTop + lineHeight / 2);
+                if (style.underline) this.ctx.fillRect(lineX, lineTop + lineHeight - 4, textWidth, 1);
+                this.ctx.restore();
+                lineX += textWidth;
+            }
+        });
+        this.ctx.restore();
+    }
+
+    /**
+     * Render a sticky note or callout body with its author line.
+     * @param {Object} element - StickyNote or Callout element
+     * @private
+     */
+    renderNoteElement(element) {
+        const { content, color, author } = element.kind;
+        const transform = element.transform || {};
+        const x = transform.x || 0;
+        const y = transform.y || 0;
+        const width = transform.width || 160;
+        const height = transform.height || 120;
+
+        this.ctx.save();
+        this.ctx.fillStyle = this._resolveColor(color, '#fff59d');
+        this.ctx.fillRect(x, y, width, height);
+        this.ctx.strokeStyle = '#5a5a7a';
+        this.ctx.strokeRect(x, y, width, height);
+
+        this.ctx.fillStyle = '#1a1a2e';
+        this.ctx.font = '14px sans-serif';
+        this.ctx.fillText(content || '', x + 8, y + 22, width - 16);
+        if (author) {
+            this.ctx.font = 'italic 11px sans-serif';
+            this.ctx.fillText(`— ${author}`, x + 8, y + height - 8, width - 16);
+        }
+        this.ctx.restore();
+    }
+
     /**
      * Render an image element.
      * @param {Object} element - Image element
@@ -351,6 +748,608 @@ export class CanvasRenderer {
         this.ctx.restore();
     }
 
+    /**
+     * Render an audio element as a play/pause button beside its waveform.
+     * @param {Object} element - Audio element
+     * @private
+     */
+    renderAudioElement(element) {
+        const { waveform = [], state } = element.kind;
+        const transform = element.transform || {};
+        const x = transform.x || 0;
+        const y = transform.y || 0;
+        const width = transform.width || 300;
+        const height = transform.height || 60;
+        const pad = Math.min(height * 0.15, 12);
+        const diameter = Math.max(Math.min(height - 2 * pad, width / 3), 0);
+        const r = diameter / 2;
+        const cx = x + pad + r;
+        const cy = y + height / 2;
+
+        this.ctx.save();
+        this.ctx.fillStyle = '#263238';
+        this.ctx.fillRect(x, y, width, height);
+
+        this.ctx.fillStyle = '#eceff1';
+        this.ctx.beginPath();
+        this.ctx.arc(cx, cy, r, 0, Math.PI * 2);
+        this.ctx.fill();
+
+        this.ctx.fillStyle = '#263238';
+        if (state === 'playing') {
+            this.ctx.fillRect(cx - 0.35 * r, cy - 0.4 * r, 0.25 * r, 0.8 * r);
+            this.ctx.fillRect(cx + 0.1 * r, cy - 0.4 * r, 0.25 * r, 0.8 * r);
+        } else {
+            this.ctx.beginPath();
+            this.ctx.moveTo(cx - 0.3 * r, cy - 0.45 * r);
+            this.ctx.lineTo(cx - 0.3 * r, cy + 0.45 * r);
+            this.ctx.lineTo(cx + 0.5 * r, cy);
+            this.ctx.closePath();
+            this.ctx.fill();
+        }
+
+        const left = x + 2 * pad + diameter;
+        const area = x + width - pad - left;
+        if (waveform.length > 0 && area > 0) {
+            const step = area / waveform.length;
+            const maxHeight = height - 2 * pad;
+            this.ctx.fillStyle = '#80cbc4';
+            waveform.forEach((amplitude, i) => {
+                const bar = Math.max(Math.min(Math.max(amplitude, 0), 1) * maxHeight, 1);
+                this.ctx.fillRect(left + i * step, cy - bar / 2, step * 0.6, bar);
+            });
+        }
+        this.ctx.restore();
+    }
+
+    /**
+     * Render a calendar month or week grid with its events, using the same
+     * layout as canvas-core's `CalendarLayout`.
+     * @param {Object} element - Calendar element
+     * @private
+     */
+    renderCalendarElement(element) {
+        const { view = 'month', date, events = [], selected } = element.kind;
+        const transform = element.transform || {};
+        const x = transform.x || 0;
+        const y = transform.y || 0;
+        const width = transform.width || 700;
+        const height = transform.height || 500;
+        const months = ['January', 'February', 'March', 'April', 'May', 'June', 'July',
+            'August', 'September', 'October', 'November', 'December'];
+        const iso = (d) => d.toISOString().slice(0, 10);
+        const addDays = (d, days) => new Date(d.getTime() + days * 86400000);
+        const weekStart = (d) => addDays(d, -((d.getUTCDay() + 6) % 7));
+
+        this.ctx.save();
+        this.ctx.fillStyle = '#ffffff';
+        this.ctx.fillRect(x, y, width, height);
+        this.ctx.strokeStyle = '#b0bec5';
+        this.ctx.strokeRect(x, y, width, height);
+
+        const day = /^\d{4}-\d{2}-\d{2}$/.test(date || '') ? new Date(`${date}T00:00:00Z`) : null;
+        if (!day || Number.isNaN(day.getTime()) || iso(day) !== date) {
+            this.ctx.restore();
+            return;
+        }
+
+        let title, start, rows, month = null;
+        if (view === 'week') {
+            start = weekStart(day);
+            title = `Week of ${start.getUTCDate()} ${months[start.getUTCMonth()].slice(0, 3)} ${start.getUTCFullYear()}`;
+            rows = 1;
+        } else {
+            const first = new Date(Date.UTC(day.getUTCFullYear(), day.getUTCMonth(), 1));
+            const days = new Date(Date.UTC(day.getUTCFullYear(), day.getUTCMonth() + 1, 0)).getUTCDate();
+            start = weekStart(first);
+            title = `${months[day.getUTCMonth()]} ${day.getUTCFullYear()}`;
+            rows = Math.ceil(((first.getUTCDay() + 6) % 7 + days) / 7);
+            month = day.getUTCMonth();
+        }
+
+        const titleHeight = Math.min(Math.min(Math.max(height * 0.12, 16), 32), height / 3);
+        const weekdayHeight = Math.min(Math.min(Math.max(height * 0.07, 12), 20), height / 4);
+        const top = titleHeight + weekdayHeight;
+        const column = width / 7;
+        const rowHeight = Math.max(height - top, 0) / rows;
+
+        this.ctx.fillStyle = '#37474f';
+        this.ctx.fillRect(x, y, width, titleHeight);
+        this.ctx.fillStyle = '#eceff1';
+        this.ctx.fillRect(x, y + titleHeight, width, weekdayHeight);
+        this.ctx.textAlign = 'center';
+        this.ctx.textBaseline = 'middle';
+        this.ctx.fillStyle = '#ffffff';
+        this.ctx.font = `${titleHeight * 0.55}px sans-serif`;
+        this.ctx.fillText(title, x + width / 2, y + titleHeight / 2);
+        this.ctx.fillStyle = '#546e7a';
+        this.ctx.font = `${weekdayHeight * 0.6}px sans-serif`;
+        ['Mon', 'Tue', 'Wed', 'Thu', 'Fri', 'Sat', 'Sun'].forEach((name, i) => {
+            this.ctx.fillText(name, x + (i + 0.5) * column, y + titleHeight + weekdayHeight / 2);
+        });
+        this.ctx.textAlign = 'left';
+        this.ctx.textBaseline = 'alphabetic';
+
+        const numberSize = Math.min(Math.max(rowHeight * 0.2, 8), 14);
+        const line = numberSize + 2;
+        const room = Math.max(Math.floor((rowHeight - numberSize - 6) / line), 0);
+        this.ctx.strokeStyle = '#cfd8dc';
+        for (let i = 0; i < rows * 7; i++) {
+            const cellDate = addDays(start, i);
+            const key = iso(cellDate);
+            const cx = x + (i % 7) * column;
+            const cy = y + top + Math.floor(i / 7) * rowHeight;
+            if (key === selected) {
+                this.ctx.fillStyle = '#bbdefb';
+                this.ctx.fillRect(cx, cy, column, rowHeight);
+            }
+            this.ctx.strokeRect(cx, cy, column, rowHeight);
+
+            const inRange = month === null || cellDate.getUTCMonth() === month;
+            this.ctx.fillStyle = inRange ? '#263238' : '#b0bec5';
+            this.ctx.font = `${numberSize}px sans-serif`;
+            this.ctx.fillText(String(cellDate.getUTCDate()), cx + 4, cy + numberSize + 2);
+
+            // One chip per event, with a count of any that do not fit
+            const dayEvents = events.filter((event) => event.date === key);
+            const shown = dayEvents.length > room ? Math.max(room - 1, 0) : dayEvents.length;
+            const eventsTop = cy + numberSize + 6;
+            this.ctx.font = `${numberSize * 0.85}px sans-serif`;
+            dayEvents.slice(0, shown).forEach((event, n) => {
+                const chipY = eventsTop + n * line;
+                this.ctx.fillStyle = this._resolveColor(event.color, '#1e88e5');
+                this.ctx.fillRect(cx + 2, chipY, Math.max(column - 4, 0), line - 2);
+                this.ctx.fillStyle = '#ffffff';
+                const label = event.time ? `${event.time} ${event.title}` : event.title;
+                this.ctx.fillText(label, cx + 4, chipY + line - 4, Math.max(column - 8, 0));
+            });
+            if (shown < dayEvents.length && room > 0) {
+                this.ctx.fillStyle = '#546e7a';
+                this.ctx.fillText(`+${dayEvents.length - shown} more`, cx + 4, eventsTop + (shown + 1) * line - 4);
+            }
+        }
+        this.ctx.restore();
+    }
+
+    /**
+     * Render a poll's question and a result bar per option, using the same
+     * layout as canvas-core's `PollLayout`.
+     * @param {Object} element - Poll element
+     * @private
+     */
+    renderPollElement(element) {
+        const { question = '', options = [], votes = {}, closed = false } = element.kind;
+        const transform = element.transform || {};
+        const x = transform.x || 0;
+        const y = transform.y || 0;
+        const width = transform.width || 320;
+        const height = transform.height || 200;
+        const pad = 6;
+
+        // One vote per voter; votes for removed options are ignored
+        const counts = options.map(() => 0);
+        Object.values(votes).forEach((option) => {
+            if (option < counts.length) {
+                counts[option] += 1;
+            }
+        });
+        const total = counts.reduce((sum, count) => sum + count, 0);
+
+        this.ctx.save();
+        this.ctx.fillStyle = '#ffffff';
+        this.ctx.fillRect(x, y, width, height);
+        this.ctx.strokeStyle = '#b0bec5';
+        this.ctx.strokeRect(x, y, width, height);
+
+        const titleHeight = Math.min(Math.min(Math.max(height * 0.2, 18), 36), height / 3);
+        this.ctx.fillStyle = '#37474f';
+        this.ctx.fillRect(x, y, width, titleHeight);
+        this.ctx.textBaseline = 'middle';
+        this.ctx.fillStyle = '#ffffff';
+        this.ctx.font = `${titleHeight * 0.5}px sans-serif`;
+        this.ctx.fillText(question, x + pad, y + titleHeight / 2, Math.max(width - 2 * pad, 0));
+
+        const slot = Math.max(height - titleHeight - pad, 0) / Math.max(options.length, 1);
+        const trackWidth = Math.max(width - 2 * pad, 0);
+        const rowHeight = Math.max(slot - pad, 0);
+        const fontSize = Math.min(Math.max(rowHeight * 0.55, 8), 16);
+        options.forEach((label, i) => {
+            const share = total === 0 ? 0 : counts[i] / total;
+            const rowY = y + titleHeight + pad + i * slot;
+            this.ctx.fillStyle = '#eceff1';
+            this.ctx.fillRect(x + pad, rowY, trackWidth, rowHeight);
+            this.ctx.fillStyle = closed ? '#90a4ae' : '#4db6ac';
+            this.ctx.fillRect(x + pad, rowY, trackWidth * share, rowHeight);
+
+            this.ctx.font = `${fontSize}px sans-serif`;
+            this.ctx.fillStyle = '#263238';
+            this.ctx.fillText(label, x + pad + 6, rowY + rowHeight / 2);
+            this.ctx.textAlign = 'right';
+            this.ctx.fillStyle = '#546e7a';
+            this.ctx.fillText(`${counts[i]} · ${Math.round(share * 100)}%`, x + pad + trackWidth - 6, rowY + rowHeight / 2);
+            this.ctx.textAlign = 'left';
+        });
+        this.ctx.textBaseline = 'alphabetic';
+        this.ctx.restore();
+    }
+
+    /**
+     * Render an agenda's header clock and item rows, using the same schedule
+     * and layout as canvas-core's `AgendaStatus` and `AgendaLayout`.
+     * @param {Object} element - Agenda element
+     * @private
+     */
+    renderAgendaElement(element) {
+        const { title = '', items = [], current = 0, started_at_ms: startedAt } = element.kind;
+        const transform = element.transform || {};
+        const x = transform.x || 0;
+        const y = transform.y || 0;
+        const width = transform.width || 360;
+        const height = transform.height || 156;
+        const pad = 4;
+
+        // mm:ss, or h:mm:ss from an hour up; partial seconds round up
+        const clock = (ms) => {
+            const secs = Math.ceil(ms / 1000);
+            const two = (n) => String(n).padStart(2, '0');
+            const [h, m, sec] = [Math.floor(secs / 3600), Math.floor(secs / 60) % 60, secs % 60];
+            return h > 0 ? `${h}:${two(m)}:${two(sec)}` : `${two(m)}:${two(sec)}`;
+        };
+        const durationText = (secs) => {
+            if (secs < 60) {
+                return `${secs} s`;
+            }
+            return secs % 60 === 0 ? `${secs / 60} min` : clock(secs * 1000);
+        };
+
+        // Items after `current` follow on by their durations
+        const now = Date.now() + this.clockOffsetMs;
+        let status;
+        if (startedAt === undefined || startedAt === null) {
+            const total = items.reduce((sum, item) => sum + item.duration_secs * 1000, 0);
+            status = { running: -1, done: -1, clock: `${clock(total)} total` };
+        } else if (now < startedAt) {
+            status = { running: -1, done: -1, clock: `Starts in ${clock(startedAt - now)}` };
+        } else {
+            let elapsed = now - startedAt;
+            status = { running: -1, done: items.length, clock: 'Done' };
+            for (let i = current; i < items.length; i++) {
+                const duration = items[i].duration_secs * 1000;
+                if (elapsed < duration) {
+                    status = {
+                        running: i,
+                        done: i,
+                        progress: elapsed / duration,
+                        clock: `${clock(duration - elapsed)} left`,
+                    };
+                    break;
+                }
+                elapsed -= duration;
+            }
+        }
+
+        this.ctx.save();
+        this.ctx.fillStyle = '#ffffff';
+        this.ctx.fillRect(x, y, width, height);
+        this.ctx.strokeStyle = '#b0bec5';
+        this.ctx.strokeRect(x, y, width, height);
+
+        const headerHeight = Math.min(Math.min(Math.max(height * 0.2, 20), 40), height / 3);
+        this.ctx.fillStyle = '#37474f';
+        this.ctx.fillRect(x, y, width, headerHeight);
+        this.ctx.textBaseline = 'middle';
+        this.ctx.fillStyle = '#ffffff';
+        this.ctx.font = `${headerHeight * 0.45}px sans-serif`;
+        this.ctx.fillText(title, x + 2 * pad, y + headerHeight / 2);
+        this.ctx.textAlign = 'right';
+        this.ctx.fillText(status.clock, x + width - 2 * pad, y + headerHeight / 2);
+        this.ctx.textAlign = 'left';
+
+        const slot = Math.max(height - headerHeight - pad, 0) / Math.max(items.length, 1);
+        const rowWidth = Math.max(width - 2 * pad, 0);
+        const rowHeight = Math.max(slot - pad, 0);
+        const fontSize = Math.min(Math.max(rowHeight * 0.45, 8), 16);
+        items.forEach((item, i) => {
+            const rowY = y + headerHeight + pad + i * slot;
+            const running = i === status.running;
+            if (running) {
+                this.ctx.fillStyle = '#e0f2f1';
+                this.ctx.fillRect(x + pad, rowY, rowWidth, rowHeight);
+                const barHeight = Math.min(Math.max(rowHeight * 0.1, 2), 4);
+                this.ctx.fillStyle = '#26a69a';
+                this.ctx.fillRect(x + pad, rowY + rowHeight - barHeight, rowWidth * status.progress, barHeight);
+            }
+
+            const label = item.owner ? `${item.title} · ${item.owner}` : item.title;
+            this.ctx.font = `${running ? 'bold ' : ''}${fontSize}px sans-serif`;
+            this.ctx.fillStyle = i < status.done ? '#90a4ae' : '#263238';
+            this.ctx.fillText(label, x + pad + 6, rowY + rowHeight / 2);
+            this.ctx.textAlign = 'right';
+            this.ctx.fillText(durationText(item.duration_secs), x + pad + rowWidth - 6, rowY + rowHeight / 2);
+            this.ctx.textAlign = 'left';
+        });
+        this.ctx.textBaseline = 'alphabetic';
+        this.ctx.restore();
+    }
+
+    /**
+     * Render dice, a wheel or a shuffled list above a caption with the
+     * latest result, using the same layout as canvas-core's
+     * `RandomizerLayout`. Outcomes are drawn by the server.
+     * @param {Object} element - Randomizer element
+     * @private
+     */
+    renderRandomizerElement(element) {
+        const { source = {}, outcome } = element.kind;
+        const transform = element.transform || {};
+        const x = transform.x || 0;
+        const y = transform.y || 0;
+        const width = transform.width || 200;
+        const height = transform.height || 160;
+        const pad = 8;
+        const colors = ['#4db6ac', '#7986cb', '#ffb74d', '#e57373', '#81c784', '#ba68c8'];
+        const values = outcome ? outcome.values : null;
+        const entries = source.segments || source.items || [];
+
+        const captionHeight = Math.min(Math.min(Math.max(height * 0.18, 16), 32), height / 3);
+        const bodyWidth = Math.max(width - 2 * pad, 0);
+        const bodyHeight = Math.max(height - captionHeight - 2 * pad, 0);
+
+        this.ctx.save();
+        this.ctx.fillStyle = '#fafafa';
+        this.ctx.fillRect(x, y, width, height);
+        this.ctx.strokeStyle = '#b0bec5';
+        this.ctx.strokeRect(x, y, width, height);
+        this.ctx.textBaseline = 'middle';
+
+        let caption;
+        if (source.mode === 'wheel') {
+            const cx = x + pad + bodyWidth / 2;
+            const cy = y + pad + bodyHeight / 2;
+            const r = Math.min(bodyWidth, bodyHeight) / 2;
+            const step = (Math.PI * 2) / Math.max(entries.length, 1);
+            const offset = values ? (values[0] + 0.5) * step : 0;
+            this.ctx.textAlign = 'center';
+            this.ctx.strokeStyle = '#ffffff';
+            this.ctx.font = `${Math.min(Math.max(r * 0.16, 8), 16)}px sans-serif`;
+            entries.forEach((label, i) => {
+                const start = -Math.PI / 2 - offset + i * step;
+                this.ctx.beginPath();
+                this.ctx.moveTo(cx, cy);
+                this.ctx.arc(cx, cy, r, start, start + step);
+                this.ctx.closePath();
+                this.ctx.fillStyle = colors[i % colors.length];
+                this.ctx.fill();
+                this.ctx.stroke();
+                this.ctx.fillStyle = '#263238';
+                const middle = start + step / 2;
+                this.ctx.fillText(label, cx + 0.62 * r * Math.cos(middle), cy + 0.62 * r * Math.sin(middle));
+            });
+            const tip = r * 0.12;
+            this.ctx.beginPath();
+            this.ctx.moveTo(cx - tip, cy - r - tip);
+            this.ctx.lineTo(cx + tip, cy - r - tip);
+            this.ctx.lineTo(cx, cy - r + tip);
+            this.ctx.closePath();
+            this.ctx.fillStyle = '#37474f';
+            this.ctx.fill();
+            caption = values ? entries[values[0]] || '' : 'Tap to spin';
+        } else if (source.mode === 'shuffle') {
+            const valid = values && values.every((i) => i < entries.length);
+            const order = valid ? values : entries.map((_, i) => i);
+            const rowHeight = bodyHeight / Math.max(order.length, 1);
+            this.ctx.fillStyle = '#263238';
+            this.ctx.font = `${Math.min(Math.max(rowHeight * 0.6, 8), 16)}px sans-serif`;
+            order.forEach((item, n) => {
+                this.ctx.fillText(`${n + 1}. ${entries[item]}`, x + pad, y + pad + (n + 0.5) * rowHeight);
+            });
+            caption = values ? values.map((i) => entries[i] || '').join(', ') : 'Tap to shuffle';
+        } else {
+            const count = Math.max(source.count || 1, 1);
+            const size = Math.min(bodyHeight, bodyWidth / (count + 0.2 * (count - 1)));
+            const gap = size * 0.2;
+            const left = x + pad + (bodyWidth - (count * size + (count - 1) * gap)) / 2;
+            const top = y + pad + (bodyHeight - size) / 2;
+            this.ctx.textAlign = 'center';
+            this.ctx.lineWidth = 2;
+            this.ctx.strokeStyle = '#37474f';
+            this.ctx.font = `${size * 0.5}px sans-serif`;
+            for (let i = 0; i < count; i++) {
+                const dx = left + i * (size + gap);
+                this.ctx.fillStyle = '#ffffff';
+                this.ctx.fillRect(dx, top, size, size);
+                this.ctx.strokeRect(dx, top, size, size);
+                this.ctx.fillStyle = '#263238';
+                this.ctx.fillText(values ? String(values[i]) : '?', dx + size / 2, top + size / 2);
+            }
+            if (!values) {
+                caption = 'Tap to roll';
+            } else if (values.length > 1) {
+                caption = `${values.join(' + ')} = ${values.reduce((sum, v) => sum + v, 0)}`;
+            } else {
+                caption = String(values[0]);
+            }
+        }
+
+        this.ctx.fillStyle = '#37474f';
+        this.ctx.fillRect(x, y + height - captionHeight, width, captionHeight);
+        this.ctx.textAlign = 'center';
+        this.ctx.fillStyle = '#ffffff';
+        this.ctx.font = `${captionHeight * 0.55}px sans-serif`;
+        this.ctx.fillText(caption, x + width / 2, y + height - captionHeight / 2);
+        this.ctx.textAlign = 'left';
+        this.ctx.textBaseline = 'alphabetic';
+        this.ctx.restore();
+    }
+
+    /**
+     * Render a floor plan: background image, outlines, a marker per seat or
+     * zone colored by occupancy and the occupancy caption, using the same
+     * layout as canvas-core's `FloorPlanLayout`.
+     * @param {Object} element - FloorPlan element
+     * @private
+     */
+    renderFloorPlanElement(element) {
+        const {
+            background = null,
+            plan_width: planWidth = 1000,
+            plan_height: planHeight = 600,
+            paths = [],
+            markers = [],
+        } = element.kind;
+        const transform = element.transform || {};
+        const x = transform.x || 0;
+        const y = transform.y || 0;
+        const width = transform.width || 480;
+        const height = transform.height || 317;
+        const pad = 6;
+        const colors = { free: '#81c784', occupied: '#e57373', reserved: '#ffb74d', blocked: '#90a4ae' };
+
+        const captionHeight = Math.min(Math.min(Math.max(height * 0.1, 14), 24), height / 4);
+        const areaWidth = Math.max(width - 2 * pad, 0);
+        const areaHeight = Math.max(height - captionHeight - 2 * pad, 0);
+        const scale = planWidth > 0 && planHeight > 0
+            ? Math.min(areaWidth / planWidth, areaHeight / planHeight)
+            : 0;
+        const originX = x + pad + (areaWidth - planWidth * scale) / 2;
+        const originY = y + pad + (areaHeight - planHeight * scale) / 2;
+
+        this.ctx.save();
+        this.ctx.fillStyle = '#f5f7f7';
+        this.ctx.fillRect(x, y, width, height);
+        this.ctx.strokeStyle = '#b0bec5';
+        this.ctx.strokeRect(x, y, width, height);
+        this.ctx.fillStyle = '#ffffff';
+        this.ctx.fillRect(originX, originY, planWidth * scale, planHeight * scale);
+
+        // Backgrounds load once and appear on a later frame
+        if (background) {
+            let image = this.imageCache.get(background);
+            if (!image) {
+                image = new Image();
+                image.src = background;
+                this.imageCache.set(background, image);
+            }
+            if (image.complete && image.naturalWidth > 0) {
+                this.ctx.drawImage(image, originX, originY, planWidth * scale, planHeight * scale);
+            }
+        }
+
+        this.ctx.strokeStyle = '#455a64';
+        this.ctx.lineWidth = 2;
+        paths.forEach(({ points = [], closed = false }) => {
+            this.ctx.beginPath();
+            points.forEach(([px, py], i) => {
+                const method = i === 0 ? 'moveTo' : 'lineTo';
+                this.ctx[method](originX + px * scale, originY + py * scale);
+            });
+            if (closed) {
+                this.ctx.closePath();
+            }
+            this.ctx.stroke();
+        });
+
+        this.ctx.textAlign = 'center';
+        this.ctx.textBaseline = 'middle';
+        const counts = { free: 0, occupied: 0, reserved: 0, blocked: 0 };
+        markers.forEach((marker) => {
+            const shape = marker.shape || 'seat';
+            const occupancy = marker.occupancy || 'free';
+            counts[occupancy] += 1;
+            const markerWidth = (marker.width ?? (shape === 'zone' ? 120 : 24)) * scale;
+            const markerHeight = (marker.height ?? (shape === 'zone' ? 80 : 24)) * scale;
+            const cx = originX + marker.x * scale;
+            const cy = originY + marker.y * scale;
+            const color = colors[occupancy];
+            if (shape === 'zone') {
+                const alpha = this.ctx.globalAlpha;
+                this.ctx.globalAlpha = alpha * 0.35;
+                this.ctx.fillStyle = color;
+                this.ctx.fillRect(cx - markerWidth / 2, cy - markerHeight / 2, markerWidth, markerHeight);
+                this.ctx.globalAlpha = alpha;
+                this.ctx.strokeStyle = color;
+                this.ctx.lineWidth = 2;
+                this.ctx.strokeRect(cx - markerWidth / 2, cy - markerHeight / 2, markerWidth, markerHeight);
+            } else {
+                this.ctx.beginPath();
+                this.ctx.ellipse(cx, cy, markerWidth / 2, markerHeight / 2, 0, 0, Math.PI * 2);
+                this.ctx.fillStyle = color;
+                this.ctx.fill();
+                this.ctx.strokeStyle = '#37474f';
+                this.ctx.lineWidth = 1;
+                this.ctx.stroke();
+            }
+            const fontSize = Math.min(Math.max(Math.min(markerWidth, markerHeight) * 0.45, 6), 14);
+            this.ctx.font = `${fontSize}px sans-serif`;
+            this.ctx.fillStyle = '#263238';
+            this.ctx.fillText(marker.label || marker.id, cx, cy);
+        });
+
+        const caption = Object.entries(counts)
+            .filter(([, count]) => count > 0)
+            .map(([state, count]) => `${count} ${state}`)
+            .join(' · ') || 'No seats';
+        this.ctx.font = `${captionHeight * 0.6}px sans-serif`;
+        this.ctx.fillStyle = '#546e7a';
+        this.ctx.fillText(caption, x + width / 2, y + height - captionHeight / 2);
+        this.ctx.textAlign = 'left';
+        this.ctx.textBaseline = 'alphabetic';
+        this.ctx.restore();
+    }
+
+    /**
+     * Render an embedded web page as a labelled box; only the WASM host
+     * overlays live iframes.
+     * @param {Object} element - Embed element
+     * @private
+     */
+    renderEmbedPlaceholder(element) {
+        const transform = element.transform || {};
+        const x = transform.x || 0;
+        const y = transform.y || 0;
+        const width = transform.width || 320;
+        const height = transform.height || 240;
+
+        this.ctx.save();
+        this.ctx.fillStyle = '#eef0f5';
+        this.ctx.fillRect(x, y, width, height);
+        this.ctx.strokeStyle = '#9aa3b5';
+        this.ctx.strokeRect(x, y, width, height);
+
+        this.ctx.fillStyle = '#4a5468';
+        this.ctx.font = '14px sans-serif';
+        this.ctx.textAlign = 'center';
+        this.ctx.fillText('Web page', x + width / 2, y + height / 2 - 8);
+        this.ctx.font = '10px monospace';
+        this.ctx.fillText(element.kind.url || '', x + width / 2, y + height / 2 + 10, width - 10);
+        this.ctx.textAlign = 'left';
+        this.ctx.restore();
+    }
+
+    /**
+     * Render a shader element's placeholder; shaders run in the wgpu renderer.
+     * @param {Object} element - Shader element
+     * @private
+     */
+    renderShaderPlaceholder(element) {
+        const transform = element.transform || {};
+        const x = transform.x || 0;
+        const y = transform.y || 0;
+        const width = transform.width || 320;
+        const height = transform.height || 240;
+
+        this.ctx.save();
+        this.ctx.fillStyle = '#2b2d42';
+        this.ctx.fillRect(x, y, width, height);
+
+        this.ctx.fillStyle = '#edf2f4';
+        this.ctx.font = '14px sans-serif';
+        this.ctx.textAlign = 'center';
+        this.ctx.fillText('Shader (needs WebGPU)', x + width / 2, y + height / 2);
+        this.ctx.textAlign = 'left';
+        this.ctx.restore();
+    }
+
     /**
      * Render a generic placeholder for unknown elements.
      * @param {Object} element - Element to render
@@ -446,6 +1445,816 @@ export class CanvasRenderer {
         }
     }
 
+    /**
+     * Render a radial or linear gauge filled up to its value, with threshold
+     * ticks, using the same layout as canvas-core's `GaugeLayout`.
+     * @param {Object} element - Gauge element
+     * @private
+     */
+    renderGaugeElement(element) {
+        const {
+            value,
+            min = 0,
+            max = 100,
+            shape = 'radial',
+            label = '',
+            thresholds = [],
+            format = {},
+        } = element.kind;
+        const transform = element.transform || {};
+        const x = transform.x || 0;
+        const y = transform.y || 0;
+        const width = transform.width || 240;
+        const height = transform.height || 160;
+        const pad = 8;
+        const clamp = (v, lo, hi) => Math.min(Math.max(v, lo), hi);
+        const fractionOf = (v) => {
+            const fraction = (v - min) / (max - min);
+            return Number.isFinite(fraction) ? clamp(fraction, 0, 1) : 0;
+        };
+        const fraction = fractionOf(value);
+        const color = this._thresholdColor(value, thresholds);
+        const labelFontSize = clamp(height * 0.12, 9, 16);
+
+        this.ctx.save();
+        this.ctx.fillStyle = '#ffffff';
+        this.ctx.fillRect(x, y, width, height);
+        this.ctx.strokeStyle = '#cfd8dc';
+        this.ctx.strokeRect(x, y, width, height);
+
+        let tick;
+        let valueX;
+        let valueY;
+        let valueFontSize;
+        let labelY;
+        if (shape === 'linear') {
+            valueFontSize = clamp(height * 0.3, 10, 28);
+            const valueWidth = valueFontSize * 3.5;
+            const barHeight = clamp(height * 0.2, 6, 24);
+            labelY = pad + labelFontSize / 2;
+            const barY = Math.min(
+                Math.max(labelY + labelFontSize / 2 + pad, (height - barHeight) / 2),
+                height - pad - barHeight,
+            );
+            const barWidth = Math.max(width - 3 * pad - valueWidth, 0);
+            this.ctx.fillStyle = '#eceff1';
+            this.ctx.fillRect(x + pad, y + barY, barWidth, barHeight);
+            this.ctx.fillStyle = color;
+            this.ctx.fillRect(x + pad, y + barY, barWidth * fraction, barHeight);
+            tick = (f) => {
+                const tx = x + pad + barWidth * f;
+                return [[tx, y + barY - barHeight * 0.3], [tx, y + barY + barHeight * 1.3]];
+            };
+            valueX = x + width - pad - valueWidth / 2;
+            valueY = y + barY + barHeight / 2;
+        } else {
+            labelY = height - pad - labelFontSize / 2;
+            const bottom = height - 2 * pad - labelFontSize;
+            const outer = Math.max(Math.min((width - 2 * pad) / 2, bottom - pad), 0);
+            const thickness = outer * 0.22;
+            const radius = outer - thickness / 2;
+            const cx = x + width / 2;
+            const cy = y + bottom;
+            const angle = (f) => Math.PI + f * Math.PI;
+            this.ctx.lineWidth = thickness;
+            [[1, '#eceff1'], [fraction, color]].forEach(([to, stroke]) => {
+                if (to > 0) {
+                    this.ctx.beginPath();
+                    this.ctx.arc(cx, cy, radius, angle(0), angle(to));
+                    this.ctx.strokeStyle = stroke;
+                    this.ctx.stroke();
+                }
+            });
+            tick = (f) => {
+                const a = angle(f);
+                const inner = radius - thickness * 0.7;
+                const outerEdge = radius + thickness * 0.7;
+                return [
+                    [cx + inner * Math.cos(a), cy + inner * Math.sin(a)],
+                    [cx + outerEdge * Math.cos(a), cy + outerEdge * Math.sin(a)],
+                ];
+            };
+            valueFontSize = clamp(radius * 0.4, 10, 40);
+            valueX = cx;
+            valueY = cy - valueFontSize / 2;
+        }
+
+        this.ctx.lineWidth = 2;
+        thresholds.forEach((threshold) => {
+            const [[x1, y1], [x2, y2]] = tick(fractionOf(threshold.value));
+            this.ctx.beginPath();
+            this.ctx.moveTo(x1, y1);
+            this.ctx.lineTo(x2, y2);
+            this.ctx.strokeStyle = this._thresholdColor(threshold.value, thresholds);
+            this.ctx.stroke();
+        });
+
+        this.ctx.textBaseline = 'middle';
+        this.ctx.textAlign = 'center';
+        this.ctx.fillStyle = color;
+        this.ctx.font = `bold ${valueFontSize}px sans-serif`;
+        this.ctx.fillText(this._formatNumber(value, format), valueX, valueY);
+        if (label) {
+            this.ctx.textAlign = shape === 'linear' ? 'left' : 'center';
+            this.ctx.fillStyle = '#546e7a';
+            this.ctx.font = `${labelFontSize}px sans-serif`;
+            this.ctx.fillText(label, shape === 'linear' ? x + pad : x + width / 2, y + labelY);
+        }
+        this.ctx.restore();
+    }
+
+    /**
+     * Render a KPI card: label, value colored by threshold, change since the
+     * previous value and sparkline, using the same layout as canvas-core's
+     * `KpiLayout`.
+     * @param {Object} element - Kpi element
+     * @private
+     */
+    renderKpiElement(element) {
+        const {
+            label = '',
+            value,
+            previous = null,
+            sparkline = [],
+            sparkline_kind: sparklineKind = 'line',
+            thresholds = [],
+            format = {},
+            higher_is_better: higherIsBetter = true,
+        } = element.kind;
+        const transform = element.transform || {};
+        const x = transform.x || 0;
+        const y = transform.y || 0;
+        const width = transform.width || 240;
+        const height = transform.height || 130;
+        const pad = 12;
+        const clamp = (v, lo, hi) => Math.min(Math.max(v, lo), hi);
+        const sparkHeight = sparkline.length > 0 ? clamp(height * 0.25, 12, 64) : 0;
+        const textHeight = Math.max(height - 2 * pad - sparkHeight, 0);
+        const labelFontSize = clamp(textHeight * 0.16, 10, 16);
+        const valueFontSize = clamp(textHeight * 0.4, 14, 48);
+        const deltaFontSize = clamp(textHeight * 0.14, 9, 14);
+        const labelY = pad + labelFontSize / 2;
+        const valueY = labelY + labelFontSize / 2 + valueFontSize * 0.7;
+        const deltaY = valueY + valueFontSize * 0.55 + deltaFontSize * 0.7;
+        const color = this._thresholdColor(value, thresholds);
+
+        this.ctx.save();
+        this.ctx.fillStyle = '#ffffff';
+        this.ctx.fillRect(x, y, width, height);
+        this.ctx.strokeStyle = '#cfd8dc';
+        this.ctx.strokeRect(x, y, width, height);
+
+        this.ctx.textBaseline = 'middle';
+        this.ctx.textAlign = 'left';
+        this.ctx.fillStyle = '#546e7a';
+        this.ctx.font = `${labelFontSize}px sans-serif`;
+        this.ctx.fillText(label, x + pad, y + labelY);
+        this.ctx.fillStyle = color;
+        this.ctx.font = `bold ${valueFontSize}px sans-serif`;
+        this.ctx.fillText(this._formatNumber(value, format), x + pad, y + valueY);
+
+        if (previous !== null && previous !== undefined) {
+            const delta = value - previous;
+            const trend = Math.sign(delta);
+            const arrow = trend > 0 ? '▲' : trend < 0 ? '▼' : '▶';
+            let text = this._formatNumber(delta, format);
+            if (delta > 0 && /[1-9]/.test(text)) text = `+${text}`;
+            text = `${arrow} ${text}`;
+            if (previous !== 0) {
+                const percent = (delta / Math.abs(previous)) * 100;
+                text += ` (${percent > 0 ? '+' : ''}${percent.toFixed(1)}%)`;
+            }
+            const better = higherIsBetter ? trend > 0 : trend < 0;
+            this.ctx.fillStyle = trend === 0 ? '#757575' : better ? '#2e7d32' : '#c62828';
+            this.ctx.font = `${deltaFontSize}px sans-serif`;
+            this.ctx.fillText(text, x + pad, y + deltaY);
+        }
+
+        if (sparkline.length > 0) {
+            const sparkWidth = Math.max(width - 2 * pad, 0);
+            const sparkY = y + height - pad - sparkHeight;
+            this._drawSparkline(sparkline, sparklineKind, [x + pad, sparkY, sparkWidth, sparkHeight], color);
+        }
+        this.ctx.restore();
+    }
+
+    /**
+     * Render a tree element: a tidy-tree layout of the visible nodes with
+     * elbow connectors, matching canvas-core's `TreeLayout`. Collapsed nodes
+     * show a badge counting the nodes they hide.
+     * @param {Object} element - Tree element
+     * @private
+     */
+    renderTreeElement(element) {
+        const { root, direction = 'down' } = element.kind;
+        if (!root) return;
+        const transform = element.transform || {};
+        const x = transform.x || 0;
+        const y = transform.y || 0;
+        const nodeWidth = 140;
+        const nodeHeight = 48;
+        const siblingGap = 16;
+        const levelGap = 36;
+        const pad = 8;
+        const down = direction !== 'right';
+        const half = (down ? nodeWidth : nodeHeight) / 2;
+
+        // Flatten the visible nodes, parents before children
+        const slots = [];
+        const stack = [{ node: root, parent: null, depth: 0 }];
+        while (stack.length > 0) {
+            const { node, parent, depth } = stack.pop();
+            const index = slots.length;
+            if (parent !== null) slots[parent].children.push(index);
+            slots.push({ node, parent, depth, children: [] });
+            if (!node.collapsed && depth < 32) {
+                const children = node.children || [];
+                for (let i = children.length - 1; i >= 0; i--) {
+                    stack.push({ node: children[i], parent: index, depth: depth + 1 });
+                }
+            }
+        }
+
+        // Pack subtrees against each other's contours, bottom-up
+        const offsets = new Array(slots.length).fill(0);
+        const contours = new Array(slots.length);
+        for (let index = slots.length - 1; index >= 0; index--) {
+            const merged = [];
+            const positions = [];
+            for (const child of slots[index].children) {
+                const contour = contours[child];
+                let position = 0;
+                if (merged.length > 0) {
+                    position = -Infinity;
+                    const shared = Math.min(merged.length, contour.length);
+                    for (let level = 0; level < shared; level++) {
+                        position = Math.max(position, merged[level][1] - contour[level][0] + siblingGap);
+                    }
+                }
+                contour.forEach(([left, right], level) => {
+                    if (level < merged.length) {
+                        merged[level] = [Math.min(merged[level][0], left + position), Math.max(merged[level][1], right + position)];
+                    } else {
+                        merged.push([left + position, right + position]);
+                    }
+                });
+                positions.push(position);
+            }
+            const center = positions.length > 0 ? (positions[0] + positions[positions.length - 1]) / 2 : 0;
+            slots[index].children.forEach((child, i) => {
+                offsets[child] = positions[i] - center;
+            });
+            contours[index] = [[-half, half], ...merged.map(([l, r]) => [l - center, r - center])];
+        }
+        const along = new Array(slots.length).fill(0);
+        for (let index = 1; index < slots.length; index++) {
+            along[index] = along[slots[index].parent] + offsets[index];
+        }
+        const min = Math.min(...along) - half;
+        const breadth = Math.max(...along) + half - min;
+        const levels = Math.max(...slots.map((slot) => slot.depth));
+        const treeWidth = down ? breadth : (levels + 1) * nodeWidth + levels * levelGap;
+        const treeHeight = down ? (levels + 1) * nodeHeight + levels * levelGap : breadth;
+
+        const width = transform.width || treeWidth + 2 * pad;
+        const height = transform.height || treeHeight + 2 * pad;
+        const areaWidth = Math.max(width - 2 * pad, 0);
+        const areaHeight = Math.max(height - 2 * pad, 0);
+        const scale = Math.min(areaWidth / treeWidth, areaHeight / treeHeight);
+        // Centered across the levels; the root keeps to the top or left edge
+        const originX = x + pad + (down ? (areaWidth - treeWidth * scale) / 2 : 0);
+        const originY = y + pad + (down ? 0 : (areaHeight - treeHeight * scale) / 2);
+        const boxes = slots.map((slot, index) => {
+            const level = slot.depth;
+            const b = along[index] - min;
+            const [bx, by] = down
+                ? [b - nodeWidth / 2, level * (nodeHeight + levelGap)]
+                : [level * (nodeWidth + levelGap), b - nodeHeight / 2];
+            return {
+                ...slot,
+                x: originX + bx * scale,
+                y: originY + by * scale,
+                width: nodeWidth * scale,
+                height: nodeHeight * scale,
+            };
+        });
+        const countNodes = (node) => 1 + (node.children || []).reduce((sum, child) => sum + countNodes(child), 0);
+
+        this.ctx.save();
+        this.ctx.strokeStyle = '#90a4ae';
+        this.ctx.lineWidth = 1.5 * scale;
+        for (const box of boxes) {
+            if (box.parent === null) continue;
+            const parent = boxes[box.parent];
+            this.ctx.beginPath();
+            if (down) {
+                const fromX = parent.x + parent.width / 2;
+                const fromY = parent.y + parent.height;
+                const toX = box.x + box.width / 2;
+                const mid = (fromY + box.y) / 2;
+                this.ctx.moveTo(fromX, fromY);
+                this.ctx.lineTo(fromX, mid);
+                this.ctx.lineTo(toX, mid);
+                this.ctx.lineTo(toX, box.y);
+            } else {
+                const fromX = parent.x + parent.width;
+                const fromY = parent.y + parent.height / 2;
+                const toY = box.y + box.height / 2;
+                const mid = (fromX + box.x) / 2;
+                this.ctx.moveTo(fromX, fromY);
+                this.ctx.lineTo(mid, fromY);
+                this.ctx.lineTo(mid, toY);
+                this.ctx.lineTo(box.x, toY);
+            }
+            this.ctx.stroke();
+        }
+
+        this.ctx.textAlign = 'center';
+        this.ctx.textBaseline = 'middle';
+        for (const box of boxes) {
+            const { node } = box;
+            this.ctx.beginPath();
+            this.ctx.roundRect(box.x, box.y, box.width, box.height, 6 * scale);
+            this.ctx.fillStyle = this._resolveColor(node.color, '#e3f2fd');
+            this.ctx.fill();
+            this.ctx.lineWidth = 1;
+            this.ctx.strokeStyle = '#607d8b';
+            this.ctx.stroke();
+
+            const centerX = box.x + box.width / 2;
+            const hasDetail = node.detail !== undefined && node.detail !== null;
+            this.ctx.fillStyle = '#263238';
+            this.ctx.font = `bold ${13 * scale}px sans-serif`;
+            this.ctx.fillText(node.label || '', centerX, box.y + box.height * (hasDetail ? 0.36 : 0.5));
+            if (hasDetail) {
+                this.ctx.fillStyle = '#546e7a';
+                this.ctx.font = `${11 * scale}px sans-serif`;
+                this.ctx.fillText(node.detail, centerX, box.y + box.height * 0.7);
+            }
+
+            const hidden = node.collapsed ? countNodes(node) - 1 : 0;
+            if (hidden > 0) {
+                const [badgeX, badgeY] = down
+                    ? [centerX, box.y + box.height]
+                    : [box.x + box.width, box.y + box.height / 2];
+                const radius = 9 * scale;
+                this.ctx.beginPath();
+                this.ctx.arc(badgeX, badgeY, radius, 0, Math.PI * 2);
+                this.ctx.fillStyle = '#546e7a';
+                this.ctx.fill();
+                this.ctx.fillStyle = '#ffffff';
+                this.ctx.font = `${radius}px sans-serif`;
+                this.ctx.fillText(`+${hidden}`, badgeX, badgeY);
+            }
+        }
+        this.ctx.restore();
+    }
+
+    /**
+     * Render a sparkline element: a bare trend line or bar series inset by
+     * canvas-core's `SPARKLINE_PADDING`.
+     * @param {Object} element - Sparkline element
+     * @private
+     */
+    renderSparklineElement(element) {
+        const { values = [], kind = 'line', color } = element.kind;
+        const transform = element.transform || {};
+        const x = transform.x || 0;
+        const y = transform.y || 0;
+        const width = transform.width || 120;
+        const height = transform.height || 28;
+        const pad = 2;
+
+        this.ctx.save();
+        this._drawSparkline(
+            values,
+            kind,
+            [x + pad, y + pad, Math.max(width - 2 * pad, 0), Math.max(height - 2 * pad, 0)],
+            this._resolveColor(color, '#1e88e5'),
+        );
+        this.ctx.restore();
+    }
+
+    /**
+     * Render an ink stroke. Points are local at natural size, so the stroke
+     * scales with the element, matching canvas-core's `ink::canvas_points`.
+     * @param {Object} element - Ink element
+     * @private
+     */
+    renderInkElement(element) {
+        const { points = [], color, width = 3 } = element.kind;
+        if (points.length === 0) return;
+        const transform = element.transform || {};
+        const half = width / 2;
+        let naturalWidth = width;
+        let naturalHeight = width;
+        for (const [px, py] of points) {
+            naturalWidth = Math.max(naturalWidth, px + half);
+            naturalHeight = Math.max(naturalHeight, py + half);
+        }
+        const x = transform.x || 0;
+        const y = transform.y || 0;
+        const sx = (transform.width || naturalWidth) / naturalWidth;
+        const sy = (transform.height || naturalHeight) / naturalHeight;
+        const cx = x + (naturalWidth * sx) / 2;
+        const cy = y + (naturalHeight * sy) / 2;
+
+        this.ctx.save();
+        this.ctx.translate(cx, cy);
+        this.ctx.rotate(transform.rotation || 0);
+        this.ctx.translate(-cx, -cy);
+        const stroke = this._resolveColor(color, '#212121');
+        const lineWidth = width * Math.sqrt(Math.abs(sx * sy));
+        if (points.length === 1) {
+            this.ctx.fillStyle = stroke;
+            this.ctx.beginPath();
+            this.ctx.arc(x + points[0][0] * sx, y + points[0][1] * sy, lineWidth / 2, 0, Math.PI * 2);
+            this.ctx.fill();
+        } else {
+            this.ctx.strokeStyle = stroke;
+            this.ctx.lineWidth = lineWidth;
+            this.ctx.lineCap = 'round';
+            this.ctx.lineJoin = 'round';
+            this.ctx.beginPath();
+            points.forEach(([px, py], i) => {
+                if (i === 0) this.ctx.moveTo(x + px * sx, y + py * sy);
+                else this.ctx.lineTo(x + px * sx, y + py * sy);
+            });
+            this.ctx.stroke();
+        }
+        this.ctx.restore();
+    }
+
+    /**
+     * Render a measurement line with end caps (dimension), ticks (ruler) or
+     * an arrowhead (arrow) and a haloed label, matching canvas-core's
+     * `MeasurementLayout`.
+     * @param {Object} element - Measurement element
+     * @private
+     */
+    renderMeasurementElement(element) {
+        const { start = [0, 0.5], end = [1, 0.5], kind = 'dimension', label, unit = 'px', scale = 1, color } = element.kind;
+        const transform = element.transform || {};
+        const x = transform.x || 0;
+        const y = transform.y || 0;
+        const width = transform.width || 160;
+        const height = transform.height || 40;
+        const [x1, y1] = [x + start[0] * width, y + start[1] * height];
+        const [x2, y2] = [x + end[0] * width, y + end[1] * height];
+        const [dx, dy] = [x2 - x1, y2 - y1];
+        const length = Math.hypot(dx, dy);
+        // Unit normal pointing up, or right for vertical lines
+        let normal = [0, -1];
+        if (length > 1e-6) {
+            const [nx, ny] = [-dy / length, dx / length];
+            normal = ny > 0 || (Math.abs(ny) <= 1e-6 && nx < 0) ? [-nx, -ny] : [nx, ny];
+        }
+        const offset = ([px, py], by) => [px + normal[0] * by, py + normal[1] * by];
+
+        const segments = [[[x1, y1], [x2, y2]]];
+        if (length > 1e-6 && kind === 'ruler') {
+            const units = length * scale;
+            const rough = units / 20;
+            const magnitude = Math.pow(10, Math.floor(Math.log10(rough)));
+            const step = [1, 2, 5, 10].map((m) => m * magnitude).find((s) => s >= rough);
+            const count = Math.min(Math.floor(units / step) + 1, 200);
+            for (let i = 0; i < count; i++) {
+                const along = (i * step) / units;
+                const at = [x1 + dx * along, y1 + dy * along];
+                segments.push([at, offset(at, i % 5 === 0 ? -8 : -4)]);
+            }
+        } else if (length > 1e-6 && kind === 'arrow') {
+            // Arrowhead at the end, 12px long (canvas-core's ARROW_HEAD)
+            const back = [x2 - (dx / length) * 12, y2 - (dy / length) * 12];
+            segments.push([[x2, y2], offset(back, 6)], [[x2, y2], offset(back, -6)]);
+        } else if (length > 1e-6) {
+            for (const p of [[x1, y1], [x2, y2]]) {
+                segments.push([offset(p, -6), offset(p, 6)]);
+            }
+        }
+
+        let text = typeof label === 'string' ? label.trim() : '';
+        if (!text && kind !== 'arrow') {
+            const value = length * scale;
+            const number = Math.abs(value) >= 100 ? value.toFixed(0) : value.toFixed(1).replace(/\.0$/, '');
+            text = unit ? `${number} ${unit}` : number;
+        }
+        const [lx, ly] = offset([(x1 + x2) / 2, (y1 + y2) / 2], 10);
+        const stroke = this._resolveColor(color, '#e53935');
+
+        this.ctx.save();
+        this.ctx.strokeStyle = stroke;
+        this.ctx.lineWidth = 1.5;
+        this.ctx.beginPath();
+        for (const [[ax, ay], [bx, by]] of segments) {
+            this.ctx.moveTo(ax, ay);
+            this.ctx.lineTo(bx, by);
+        }
+        this.ctx.stroke();
+        if (!text) {
+            this.ctx.restore();
+            return;
+        }
+        this.ctx.font = '12px sans-serif';
+        this.ctx.textAlign = 'center';
+        this.ctx.textBaseline = 'middle';
+        this.ctx.strokeStyle = '#ffffff';
+        this.ctx.lineWidth = 3;
+        this.ctx.strokeText(text, lx, ly);
+        this.ctx.fillStyle = stroke;
+        this.ctx.fillText(text, lx, ly);
+        this.ctx.restore();
+    }
+
+    /**
+     * Render a region highlight: a tinted, bordered rectangle with its
+     * caption in a tab at the top-left corner.
+     * @param {Object} element - Region element
+     * @private
+     */
+    renderRegionElement(element) {
+        const { caption, color } = element.kind;
+        const transform = element.transform || {};
+        const x = transform.x || 0;
+        const y = transform.y || 0;
+        const width = transform.width || 200;
+        const height = transform.height || 120;
+        const tint = this._resolveColor(color, '#e53935');
+
+        this.ctx.save();
+        this.ctx.fillStyle = tint;
+        const alpha = this.ctx.globalAlpha;
+        this.ctx.globalAlpha = alpha * 0.15;
+        this.ctx.fillRect(x, y, width, height);
+        this.ctx.globalAlpha = alpha;
+        this.ctx.strokeStyle = tint;
+        this.ctx.lineWidth = 2;
+        this.ctx.strokeRect(x, y, width, height);
+        if (typeof caption === 'string' && caption.trim()) {
+            const tabWidth = Math.min([...caption].length * 12 * 0.6 + 12, width);
+            this.ctx.fillRect(x, y, tabWidth, Math.min(20, height));
+            this.ctx.fillStyle = '#ffffff';
+            this.ctx.font = '12px sans-serif';
+            this.ctx.fillText(caption, x + 6, y + 14);
+        }
+        this.ctx.restore();
+    }
+
+    /**
+     * Render a QR code or Code 128 barcode from its server-generated modules,
+     * matching canvas-core's `BarcodeLayout`: square QR modules centered with
+     * a 4-module quiet zone, Code 128 bars full height with a 10-module one.
+     * @param {Object} element - Barcode element
+     * @private
+     */
+    renderBarcodeElement(element) {
+        const { symbology, modules = [], color } = element.kind;
+        const transform = element.transform || {};
+        const x = transform.x || 0;
+        const y = transform.y || 0;
+        const width = transform.width || 0;
+        const height = transform.height || 0;
+        this.ctx.fillStyle = '#ffffff';
+        this.ctx.fillRect(x, y, width, height);
+
+        const columns = modules[0]?.length || 0;
+        if (columns === 0) return;
+        const qr = symbology !== 'code128';
+        const quiet = qr ? 8 : 20;
+        let module, rowHeight, left, top;
+        if (qr) {
+            module = Math.min(width, height) / (columns + quiet);
+            rowHeight = module;
+            left = x + (width - module * columns) / 2;
+            top = y + (height - module * columns) / 2;
+        } else {
+            module = width / (columns + quiet);
+            rowHeight = height / modules.length;
+            left = x + module * (quiet / 2);
+            top = y;
+        }
+
+        this.ctx.fillStyle = this._resolveColor(color, '#000000');
+        modules.forEach((row, r) => {
+            let start = -1;
+            for (let c = 0; c <= row.length; c++) {
+                const dark = row[c] === '1';
+                if (dark && start < 0) {
+                    start = c;
+                } else if (!dark && start >= 0) {
+                    this.ctx.fillRect(left + start * module, top + r * rowHeight, (c - start) * module, rowHeight);
+                    start = -1;
+                }
+            }
+        });
+    }
+
+    /**
+     * Render a rectangle, ellipse, polygon, star or block arrow filling the
+     * element's box, matching canvas-core's `ShapeOutline`. Gradient fills
+     * are drawn with their first stop.
+     * @param {Object} element - Shape element
+     * @private
+     */
+    renderShapeElement(element) {
+        const shape = element.kind.shape || { kind: 'rectangle' };
+        const style = element.style || {};
+        const transform = element.transform || {};
+        const x = transform.x || 0;
+        const y = transform.y || 0;
+        const width = transform.width || 120;
+        const height = transform.height || 120;
+        const [cx, cy, rx, ry] = [x + width / 2, y + height / 2, width / 2, height / 2];
+        const count = (value, fallback) =>
+            Math.min(Math.max(Number.isInteger(value) ? value : fallback, 3), 64);
+
+        this.ctx.beginPath();
+        switch (shape.kind) {
+            case 'ellipse':
+                this.ctx.ellipse(cx, cy, rx, ry, 0, 0, Math.PI * 2);
+                break;
+            case 'polygon':
+            case 'star': {
+                const star = shape.kind === 'star';
+                const n = star ? count(shape.points, 5) * 2 : count(shape.sides, 6);
+                const inner = star ? shape.inner_ratio ?? 0.5 : 1;
+                for (let i = 0; i < n; i++) {
+                    const angle = (i * Math.PI * 2) / n - Math.PI / 2;
+                    const scale = i % 2 === 1 ? inner : 1;
+                    this.ctx.lineTo(cx + rx * scale * Math.cos(angle), cy + ry * scale * Math.sin(angle));
+                }
+                break;
+            }
+            case 'arrow': {
+                const direction = shape.direction || 'right';
+                const horizontal = direction === 'right' || direction === 'left';
+                const [length, thickness] = horizontal ? [width, height] : [height, width];
+                const neck = length * (1 - Math.min(Math.max(shape.head_length ?? 0.35, 0), 1));
+                const half = thickness / 2;
+                const shaft = half * Math.min(Math.max(shape.shaft_width ?? 0.5, 0), 1);
+                const local = [[0, -shaft], [neck, -shaft], [neck, -half], [length, 0], [neck, half], [neck, shaft], [0, shaft]];
+                for (const [along, across] of local) {
+                    const point = {
+                        right: [x + along, y + half + across],
+                        left: [x + width - along, y + half + across],
+                        down: [x + half + across, y + along],
+                        up: [x + half + across, y + height - along],
+                    }[direction] || [x + along, y + half + across];
+                    this.ctx.lineTo(point[0], point[1]);
+                }
+                break;
+            }
+            default: {
+                const radius = Math.min(Math.max(style.corner_radius || 0, 0), width / 2, height / 2);
+                if (radius > 0 && this.ctx.roundRect) {
+                    this.ctx.roundRect(x, y, width, height, radius);
+                } else {
+                    this.ctx.rect(x, y, width, height);
+                }
+            }
+        }
+        this.ctx.closePath();
+
+        const fill = style.fill;
+        const fillColor = fill && (fill.type === 'solid' ? fill.color : fill.stops?.[0]?.color);
+        this.ctx.fillStyle = this._resolveColor(fillColor, '#90caf9');
+        this.ctx.fill();
+        if (style.stroke && style.stroke.width > 0) {
+            this.ctx.strokeStyle = this._resolveColor(style.stroke.color, '#000000');
+            this.ctx.lineWidth = style.stroke.width;
+            this.ctx.lineJoin = 'round';
+            this.ctx.stroke();
+        }
+    }
+
+    /**
+     * Draw a sparkline across an area, matching canvas-core's
+     * `sparkline_points` and `sparkline_bars`.
+     * @param {number[]} values - Values, oldest first
+     * @param {string} kind - `line` or `bar`
+     * @param {number[]} area - `[x, y, width, height]`
+     * @param {string} color - CSS color
+     * @private
+     */
+    _drawSparkline(values, kind, [x, y, width, height], color) {
+        const finite = values.filter(Number.isFinite);
+        if (finite.length === 0) return;
+        const lo = Math.min(...finite);
+        const hi = Math.max(...finite);
+
+        if (kind === 'bar') {
+            const low = Math.min(lo, 0);
+            const high = Math.max(hi, 0);
+            const span = high > low ? high - low : 1;
+            const levelY = (v) => y + (height * (high - v)) / span;
+            const zeroY = levelY(0);
+            const slot = width / values.length;
+            this.ctx.fillStyle = color;
+            values.forEach((v, i) => {
+                if (!Number.isFinite(v)) return;
+                const top = levelY(v);
+                this.ctx.fillRect(x + i * slot + slot * 0.15, Math.min(top, zeroY), slot * 0.7, Math.abs(top - zeroY));
+            });
+            return;
+        }
+
+        const step = width / (Math.max(values.length, 2) - 1);
+        this.ctx.beginPath();
+        let last = null;
+        values.forEach((v, i) => {
+            if (!Number.isFinite(v)) return;
+            const level = hi > lo ? (v - lo) / (hi - lo) : 0.5;
+            const point = [x + i * step, y + height * (1 - level)];
+            this.ctx[last ? 'lineTo' : 'moveTo'](...point);
+            last = point;
+        });
+        this.ctx.lineWidth = 1.5;
+        this.ctx.lineJoin = 'round';
+        this.ctx.strokeStyle = color;
+        this.ctx.stroke();
+        this.ctx.beginPath();
+        this.ctx.arc(last[0], last[1], 2, 0, Math.PI * 2);
+        this.ctx.fillStyle = color;
+        this.ctx.fill();
+    }
+
+    /**
+     * Color of a gauge or KPI value: that of the highest threshold it
+     * reaches, or the default blue below every threshold.
+     * @param {number} value - Value to color
+     * @param {Array<{value: number, color: string}>} thresholds - Thresholds
+     * @returns {string} CSS color
+     * @private
+     */
+    _thresholdColor(value, thresholds) {
+        const reached = thresholds
+            .filter((threshold) => value >= threshold.value)
+            .sort((a, b) => b.value - a.value)[0];
+        return reached ? this._resolveColor(reached.color, '#1e88e5') : '#1e88e5';
+    }
+
+    /**
+     * Format a number like canvas-core's `NumberFormat`: thousands
+     * separators, fixed decimals, optional k/M/B abbreviation, prefix and
+     * suffix.
+     * @param {number} value - Number to format
+     * @param {Object} format - `{ decimals, prefix, suffix, compact }`
+     * @returns {string} Formatted number
+     * @private
+     */
+    _formatNumber(value, format = {}) {
+        if (!Number.isFinite(value)) return '–';
+        const { prefix = '', suffix = '', compact = false } = format;
+        const decimals = Math.min(format.decimals || 0, 6);
+        const group = (digits) => {
+            const [integer, fraction] = digits.split('.');
+            const grouped = integer.replace(/\B(?=(\d{3})+(?!\d))/g, ',');
+            return fraction === undefined ? grouped : `${grouped}.${fraction}`;
+        };
+        let magnitude = group(Math.abs(value).toFixed(decimals));
+        if (compact) {
+            // Move up a unit when rounding reaches 1000 (999.96k → 1M)
+            let scaled = Math.abs(value);
+            for (const unit of ['', 'k', 'M', 'B']) {
+                let digits = scaled.toFixed(unit ? Math.max(decimals, 1) : decimals);
+                if (unit && digits.includes('.')) digits = digits.replace(/\.?0+$/, '');
+                if (Number(digits) < 1000 || unit === 'B') {
+                    magnitude = `${group(digits)}${unit}`;
+                    break;
+                }
+                scaled /= 1000;
+            }
+        }
+        const sign = value < 0 && /[1-9]/.test(magnitude) ? '-' : '';
+        return `${sign}${prefix}${magnitude}${suffix}`;
+    }
+
+    /**
+     * Resolve an element color against the scene theme. Colors are CSS
+     * literals, `var(--name)` theme variables or `palette(name, index)`
+     * palette entries; undefined references use the fallback.
+     * @param {string|undefined} color - Color as stored on the element
+     * @param {string} fallback - CSS color for missing or unresolved colors
+     * @returns {string} CSS color
+     * @private
+     */
+    _resolveColor(color, fallback) {
+        if (!color) return fallback;
+        const theme = this.scene?.theme || {};
+        const variable = /^var\(\s*--([\w-]+)\s*\)$/.exec(color);
+        if (variable) {
+            color = theme.variables?.[variable[1]];
+            if (!color) return fallback;
+        }
+        const palette = /^palette\(\s*([\w-]+)\s*,\s*(\d+)\s*\)$/.exec(color);
+        if (palette) {
+            const colors = theme.palettes?.[palette[1]];
+            if (!colors || colors.length === 0) return fallback;
+            return colors[Number(palette[2]) % colors.length];
+        }
+        return color.startsWith('var(') ? fallback : color;
+    }
+
     /**
      * Get color based on connection quality.
      * @param {Object} stats - Stats object
@@ -20,7 +20,8 @@
  * @property {string} language - Recognition language (default: 'en-US')
  * @property {boolean} continuous - Whether to continuously recognize (default: true)
  * @property {boolean} interimResults - Whether to return interim results (default: true)
- * @property {number} fusionWindowMs - Time window to fuse with touch events (default: 2000)
+ * @property {number} fusionWindowMs - How long before an utterance starts a touch still fuses with it (default: 2000)
+ * @property {number} maxRecognitionLatencyMs - Longest recognition delay allowed for (default: 5000)
  */
 
 /**
@@ -37,6 +38,7 @@ class VoiceInput {
             continuous: config.continuous !== false,
             interimResults: config.interimResults !== false,
             fusionWindowMs: config.fusionWindowMs || 2000,
+            maxRecognitionLatencyMs: config.maxRecognitionLatencyMs || 5000,
             ...config
         };
 
@@ -55,6 +57,11 @@ class VoiceInput {
         this.lastTouch = null;
         this.lastTouchTimestamp = 0;
 
+        // When the utterance in progress produced its first interim result,
+        // and the smoothed time from there to its final result
+        this.utteranceStart = null;
+        this.recognitionLatencyMs = null;
+
         this._initRecognition();
     }
 
@@ -106,10 +113,13 @@ class VoiceInput {
             timestamp: Date.now()
         };
 
-        // Check for touch fusion
-        if (result.isFinal && this.lastTouch) {
-            const elapsed = voiceResult.timestamp - this.lastTouchTimestamp;
-            if (elapsed < this.config.fusionWindowMs) {
+        // Check for touch fusion against when the utterance started, not
+        // when a slow recognizer finished with it
+        if (!result.isFinal) {
+            this.utteranceStart ??= voiceResult.timestamp;
+        } else {
+            const spokenAt = this._utteranceStarted(voiceResult.timestamp);
+            if (this.lastTouch && this.lastTouchTimestamp >= spokenAt - this.config.fusionWindowMs) {
                 voiceResult.fusedTouch = this.lastTouch;
                 this.lastTouch = null;
             }
@@ -126,6 +136,36 @@ class VoiceInput {
         }
     }
 
+    /**
+     * When the utterance ending in a final result at `at` started: its first
+     * interim result, or without one `at` less the latency observed so far.
+     * @private
+     * @param {number} at - Timestamp of the final result
+     * @returns {number} Timestamp the utterance started
+     */
+    _utteranceStarted(at) {
+        const start = this.utteranceStart;
+        this.utteranceStart = null;
+        // An older interim belongs to an utterance the recognizer gave up on
+        if (start !== null && at - start <= this.config.maxRecognitionLatencyMs) {
+            const latency = Math.max(0, at - start);
+            this.recognitionLatencyMs = this.recognitionLatencyMs === null
+                ? latency
+                : this.recognitionLatencyMs + 0.25 * (latency - this.recognitionLatencyMs);
+            return start;
+        }
+        return at - this._latencyAllowance();
+    }
+
+    /**
+     * Observed recognition latency, capped at `maxRecognitionLatencyMs`.
+     * @private
+     * @returns {number} Milliseconds
+     */
+    _latencyAllowance() {
+        return Math.min(this.recognitionLatencyMs ?? 0, this.config.maxRecognitionLatencyMs);
+    }
+
     /**
      * Handle speech recognition errors.
      * @private
@@ -290,7 +330,7 @@ class VoiceInput {
             return false;
         }
         const elapsed = Date.now() - this.lastTouchTimestamp;
-        return elapsed < this.config.fusionWindowMs;
+        return elapsed < this.config.fusionWindowMs + this._latencyAllowance();
     }
 
     /**
@@ -370,9 +410,20 @@ class VoiceCommands {
         // Zoom commands
         this.register(/zoom (in|out)/i,
             (match) => ({ action: 'zoom', direction: match[1].toLowerCase() }));
+        this.register(/zoom to fit|fit (?:to )?(?:the )?(?:screen|content|everything|all)/i,
+            () => ({ action: 'fitToContent' }));
+        this.register(/reset (?:the )?(?:view|zoom|camera)/i, () => ({ action: 'resetView' }));
+        this.register(/(?:go|jump) to (?:the )?(.+?)(?: view)?$/i,
+            (match) => ({ action: 'goToAnchor', name: match[1].trim() }));
 
         // Save command
         this.register(/save/i, () => ({ action: 'save' }));
+
+        // Spotlight commands (clear first so "stop focusing" is not a target)
+        this.register(/(?:clear|stop|exit|end) (?:the )?(?:focus(?:ing)?|spotlight(?:ing)?)|show everything|unfocus/i,
+            () => ({ action: 'clearSpotlight' }));
+        this.register(/(?:focus on|spotlight|highlight) (.+)/i,
+            (match) => ({ action: 'spotlight', target: match[1].trim() }));
     }
 
     /**
@@ -404,6 +455,7 @@ class VoiceCommands {
  * Fused input combining touch and voice.
  * @typedef {Object} FusedIntent
  * @property {string} transcript - Voice transcript
+ * @property {number} confidence - Recognition confidence (0.0 to 1.0)
  * @property {Object} touch - Touch location info
  * @property {number} touch.x - X coordinate
  * @property {number} touch.y - Y coordinate
@@ -427,6 +479,7 @@ class VoiceManager {
         this.onCommand = null;
         this.onFusedIntent = null;
         this.onTranscript = null;
+        this.onInterim = null;
         this.onStatusChange = null;
 
         this._setupHandlers();
@@ -445,6 +498,7 @@ class VoiceManager {
                 // Fused intent (touch + voice)
                 const intent = {
                     transcript: result.transcript,
+                    confidence: result.confidence,
                     touch: result.fusedTouch,
                     command,
                     timestamp: result.timestamp
@@ -467,7 +521,9 @@ class VoiceManager {
         };
 
         this.voice.onInterim = (result) => {
-            // Could show interim results in UI
+            if (this.onInterim) {
+                this.onInterim(result);
+            }
         };
 
         this.voice.onStart = () => {